@@ -18,7 +18,7 @@
 //!
 use std::sync::Arc;
 use vm::{Vm, Schedule};
-use ethereum_types::U256;
+use ethereum_types::{U256, H256};
 use super::vm::ActionParams;
 use super::interpreter::SharedCache;
 use super::vmtype::VMType;
@@ -52,6 +52,16 @@ impl Factory {
 		}
 	}
 
+	/// Keep the analysis of a contract with given code hash once it's first executed.
+	pub fn pin(&self, code_hash: H256) {
+		self.evm_cache.pin(code_hash);
+	}
+
+	/// Analyze the code of a contract ahead of its execution and keep the analysis.
+	pub fn prewarm(&self, code_hash: &H256, code: &[u8]) {
+		self.evm_cache.prewarm(code_hash, code);
+	}
+
 	fn can_fit_in_usize(gas: &U256) -> bool {
 		gas == &U256::from(gas.low_u64() as usize)
 	}
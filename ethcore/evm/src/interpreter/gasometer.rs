@@ -121,6 +121,9 @@ impl<Gas: evm::CostType> Gasometer<Gas> {
 				Request::Gas(Gas::from(1))
 			},
 			instructions::SSTORE => {
+				if schedule.eip1706 && self.current_gas <= Gas::from(schedule.call_stipend) {
+					return Err(vm::Error::OutOfGas);
+				}
 				let address = H256::from(stack.peek(0));
 				let newval = stack.peek(1);
 				let val = U256::from(&*ext.storage_at(&address)?);
@@ -137,19 +140,30 @@ impl<Gas: evm::CostType> Gasometer<Gas> {
 						schedule.sstore_reset_gas
 					}
 				};
+				let gas = if schedule.eip2929 && !ext.access_list_contains_storage_key(&address) {
+					gas + schedule.sload_gas
+				} else {
+					gas
+				};
 				Request::Gas(Gas::from(gas))
 			},
 			instructions::SLOAD => {
-				Request::Gas(Gas::from(schedule.sload_gas))
+				let key = H256::from(stack.peek(0));
+				let gas = if schedule.eip2929 && ext.access_list_contains_storage_key(&key) {
+					schedule.warm_storage_read_cost
+				} else {
+					schedule.sload_gas
+				};
+				Request::Gas(Gas::from(gas))
 			},
 			instructions::BALANCE => {
-				Request::Gas(Gas::from(schedule.balance_gas))
+				Request::Gas(Gas::from(account_access_gas(ext, stack.peek(0), schedule.balance_gas)))
 			},
 			instructions::EXTCODESIZE => {
-				Request::Gas(Gas::from(schedule.extcodesize_gas))
+				Request::Gas(Gas::from(account_access_gas(ext, stack.peek(0), schedule.extcodesize_gas)))
 			},
 			instructions::EXTCODEHASH => {
-				Request::Gas(Gas::from(schedule.extcodehash_gas))
+				Request::Gas(Gas::from(account_access_gas(ext, stack.peek(0), schedule.extcodehash_gas)))
 			},
 			instructions::SUICIDE => {
 				let mut gas = Gas::from(schedule.suicide_gas);
@@ -164,6 +178,10 @@ impl<Gas: evm::CostType> Gasometer<Gas> {
 					gas = overflowing!(gas.overflow_add(schedule.suicide_to_new_account_cost.into()));
 				}
 
+				if schedule.eip2929 && !ext.access_list_contains_address(&address) {
+					gas = overflowing!(gas.overflow_add(schedule.cold_account_access_cost.into()));
+				}
+
 				Request::Gas(gas)
 			},
 			instructions::MSTORE | instructions::MLOAD => {
@@ -185,7 +203,8 @@ impl<Gas: evm::CostType> Gasometer<Gas> {
 				Request::GasMemCopy(default_gas, mem_needed(stack.peek(0), stack.peek(2))?, Gas::from_u256(*stack.peek(2))?)
 			},
 			instructions::EXTCODECOPY => {
-				Request::GasMemCopy(schedule.extcodecopy_base_gas.into(), mem_needed(stack.peek(1), stack.peek(3))?, Gas::from_u256(*stack.peek(3))?)
+				let base = account_access_gas(ext, stack.peek(0), schedule.extcodecopy_base_gas);
+				Request::GasMemCopy(base.into(), mem_needed(stack.peek(1), stack.peek(3))?, Gas::from_u256(*stack.peek(3))?)
 			},
 			instructions::LOG0 | instructions::LOG1 | instructions::LOG2 | instructions::LOG3 | instructions::LOG4 => {
 				let no_of_topics = instruction.log_topics().expect("log_topics always return some for LOG* instructions; qed");
@@ -196,7 +215,7 @@ impl<Gas: evm::CostType> Gasometer<Gas> {
 				Request::GasMem(gas, mem_needed(stack.peek(0), stack.peek(1))?)
 			},
 			instructions::CALL | instructions::CALLCODE => {
-				let mut gas = Gas::from(schedule.call_gas);
+				let mut gas = Gas::from(account_access_gas(ext, stack.peek(1), schedule.call_gas));
 				let mem = cmp::max(
 					mem_needed(stack.peek(5), stack.peek(6))?,
 					mem_needed(stack.peek(3), stack.peek(4))?
@@ -222,7 +241,7 @@ impl<Gas: evm::CostType> Gasometer<Gas> {
 				Request::GasMemProvide(gas, mem, Some(requested))
 			},
 			instructions::DELEGATECALL | instructions::STATICCALL => {
-				let gas = Gas::from(schedule.call_gas);
+				let gas = Gas::from(account_access_gas(ext, stack.peek(1), schedule.call_gas));
 				let mem = cmp::max(
 					mem_needed(stack.peek(4), stack.peek(5))?,
 					mem_needed(stack.peek(2), stack.peek(3))?
@@ -304,9 +323,12 @@ impl<Gas: evm::CostType> Gasometer<Gas> {
 			// s * memory_gas + s * s / quad_coeff_div
 			let a = overflowing!(s.overflow_mul(Gas::from(schedule.memory_gas)));
 
-			// Calculate s*s/quad_coeff_div
-			assert_eq!(schedule.quad_coeff_div, 512);
-			let b = overflowing!(s.overflow_mul_shr(s, 9));
+			// Calculate s*s/quad_coeff_div, as a shift for powers of two (512 on the main net)
+			let b = if schedule.quad_coeff_div.is_power_of_two() {
+				overflowing!(s.overflow_mul_shr(s, schedule.quad_coeff_div.trailing_zeros() as usize))
+			} else {
+				overflowing!(s.overflow_mul(s)) / Gas::from(schedule.quad_coeff_div)
+			};
 			Ok(overflowing!(a.overflow_add(b)))
 		};
 
@@ -343,12 +365,24 @@ fn add_gas_usize<Gas: evm::CostType>(value: Gas, num: usize) -> (Gas, bool) {
 	value.overflow_add(Gas::from(num))
 }
 
+/// Cost of accessing an account: `cold_gas`, or the warm storage read cost if the
+/// account was already accessed by the transaction (EIP-2929).
+#[inline]
+fn account_access_gas(ext: &vm::Ext, address: &U256, cold_gas: usize) -> usize {
+	if ext.schedule().eip2929 && ext.access_list_contains_address(&u256_to_address(address)) {
+		ext.schedule().warm_storage_read_cost
+	} else {
+		cold_gas
+	}
+}
+
 #[inline]
 fn calculate_eip1283_sstore_gas<Gas: evm::CostType>(schedule: &Schedule, original: &U256, current: &U256, new: &U256) -> Gas {
+	let sstore_dirty_gas = schedule.sstore_dirty_gas.unwrap_or(schedule.sload_gas);
 	Gas::from(
 		if current == new {
 			// 1. If current value equals new value (this is a no-op), 200 gas is deducted.
-			schedule.sload_gas
+			sstore_dirty_gas
 		} else {
 			// 2. If current value does not equal new value
 			if original == current {
@@ -364,7 +398,7 @@ fn calculate_eip1283_sstore_gas<Gas: evm::CostType>(schedule: &Schedule, origina
 				}
 			} else {
 				// 2.2. If original value does not equal current value (this storage slot is dirty), 200 gas is deducted. Apply both of the following clauses.
-				schedule.sload_gas
+				sstore_dirty_gas
 
 				// 2.2.1. If original value is not 0
 				// 2.2.1.1. If current value is 0 (also means that new value is not 0), remove 15000 gas from refund counter. We can prove that refund counter will never go below 0.
@@ -380,6 +414,7 @@ fn calculate_eip1283_sstore_gas<Gas: evm::CostType>(schedule: &Schedule, origina
 
 pub fn handle_eip1283_sstore_clears_refund(ext: &mut vm::Ext, original: &U256, current: &U256, new: &U256) {
 	let sstore_clears_schedule = U256::from(ext.schedule().sstore_refund_gas);
+	let sstore_dirty_gas = ext.schedule().sstore_dirty_gas.unwrap_or(ext.schedule().sload_gas);
 
 	if current == new {
 		// 1. If current value equals new value (this is a no-op), 200 gas is deducted.
@@ -414,11 +449,11 @@ pub fn handle_eip1283_sstore_clears_refund(ext: &mut vm::Ext, original: &U256, c
 				// 2.2.2. If original value equals new value (this storage slot is reset)
 				if original.is_zero() {
 					// 2.2.2.1. If original value is 0, add 19800 gas to refund counter.
-					let refund = U256::from(ext.schedule().sstore_set_gas - ext.schedule().sload_gas);
+					let refund = U256::from(ext.schedule().sstore_set_gas - sstore_dirty_gas);
 					ext.add_sstore_refund(refund);
 				} else {
 					// 2.2.2.2. Otherwise, add 4800 gas to refund counter.
-					let refund = U256::from(ext.schedule().sstore_reset_gas - ext.schedule().sload_gas);
+					let refund = U256::from(ext.schedule().sstore_reset_gas - sstore_dirty_gas);
 					ext.add_sstore_refund(refund);
 				}
 			}
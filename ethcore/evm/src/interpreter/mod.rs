@@ -274,6 +274,10 @@ impl<Cost: CostType> Interpreter<Cost> {
 		self.gasometer.as_mut().expect(GASOMETER_PROOF).current_mem_gas = requirements.memory_total_gas;
 		self.gasometer.as_mut().expect(GASOMETER_PROOF).current_gas = self.gasometer.as_mut().expect(GASOMETER_PROOF).current_gas - requirements.gas_cost;
 
+		if ext.schedule().eip2929 {
+			Self::mark_accessed(ext, instruction, &self.stack);
+		}
+
 		evm_debug!({ self.informant.before_instruction(self.reader.position, instruction, info, &self.gasometer.as_mut().expect(GASOMETER_PROOF).current_gas, &self.stack) });
 
 		let (mem_written, store_written) = match self.do_trace {
@@ -343,7 +347,9 @@ impl<Cost: CostType> Interpreter<Cost> {
 			((instruction == instructions::RETURNDATACOPY || instruction == instructions::RETURNDATASIZE) && !schedule.have_return_data) ||
 			(instruction == instructions::REVERT && !schedule.have_revert) ||
 			((instruction == instructions::SHL || instruction == instructions::SHR || instruction == instructions::SAR) && !schedule.have_bitwise_shifting) ||
-			(instruction == instructions::EXTCODEHASH && !schedule.have_extcodehash)
+			(instruction == instructions::EXTCODEHASH && !schedule.have_extcodehash) ||
+			(instruction == instructions::CHAINID && !schedule.have_chain_id) ||
+			(instruction == instructions::SELFBALANCE && !schedule.have_selfbalance)
 		{
 			return Err(vm::Error::BadInstruction {
 				instruction: instruction as u8
@@ -367,6 +373,23 @@ impl<Cost: CostType> Interpreter<Cost> {
 		}
 	}
 
+	/// Adds the storage key or account touched by the instruction to the access list (EIP-2929).
+	fn mark_accessed(ext: &mut vm::Ext, instruction: Instruction, stack: &Stack<U256>) {
+		match instruction {
+			instructions::SLOAD | instructions::SSTORE => {
+				ext.access_list_insert_storage_key(H256::from(stack.peek(0)))
+			},
+			instructions::BALANCE | instructions::EXTCODESIZE | instructions::EXTCODEHASH |
+			instructions::EXTCODECOPY | instructions::SUICIDE => {
+				ext.access_list_insert_address(u256_to_address(stack.peek(0)))
+			},
+			instructions::CALL | instructions::CALLCODE | instructions::DELEGATECALL | instructions::STATICCALL => {
+				ext.access_list_insert_address(u256_to_address(stack.peek(1)))
+			},
+			_ => {},
+		}
+	}
+
 	fn mem_written(
 		instruction: Instruction,
 		stack: &Stack<U256>
@@ -750,6 +773,12 @@ impl<Cost: CostType> Interpreter<Cost> {
 			instructions::GASLIMIT => {
 				self.stack.push(ext.env_info().gas_limit.clone());
 			},
+			instructions::CHAINID => {
+				self.stack.push(ext.chain_id().into())
+			},
+			instructions::SELFBALANCE => {
+				self.stack.push(ext.balance(&self.params.address)?);
+			},
 
 			// Stack instructions
 
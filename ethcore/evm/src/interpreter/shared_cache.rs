@@ -14,11 +14,12 @@
 // You should have received a copy of the GNU General Public License
 // along with Parity.  If not, see <http://www.gnu.org/licenses/>.
 
+use std::collections::HashMap;
 use std::sync::Arc;
 use hash::KECCAK_EMPTY;
 use heapsize::HeapSizeOf;
 use ethereum_types::H256;
-use parking_lot::Mutex;
+use parking_lot::{Mutex, RwLock};
 use memory_cache::MemoryLruCache;
 use bit_set::BitSet;
 use super::super::instructions::{self, Instruction};
@@ -38,6 +39,8 @@ impl HeapSizeOf for Bits {
 /// Global cache for EVM interpreter
 pub struct SharedCache {
 	jump_destinations: Mutex<MemoryLruCache<H256, Bits>>,
+	/// Analysis of contracts which are never evicted, `None` if the contract wasn't analyzed yet.
+	pinned: RwLock<HashMap<H256, Option<Arc<BitSet>>>>,
 }
 
 impl SharedCache {
@@ -46,15 +49,39 @@ impl SharedCache {
 	pub fn new(max_size: usize) -> Self {
 		SharedCache {
 			jump_destinations: Mutex::new(MemoryLruCache::new(max_size)),
+			pinned: RwLock::new(HashMap::new()),
 		}
 	}
 
+	/// Keep the analysis of a contract with given code hash once it's first executed.
+	pub fn pin(&self, code_hash: H256) {
+		self.pinned.write().entry(code_hash).or_insert(None);
+	}
+
+	/// Analyze the code of a contract ahead of its execution and keep the analysis.
+	pub fn prewarm(&self, code_hash: &H256, code: &[u8]) {
+		let d = Self::find_jump_destinations(code);
+		self.pinned.write().insert(code_hash.clone(), Some(d));
+	}
+
 	/// Get jump destinations bitmap for a contract.
 	pub fn jump_destinations(&self, code_hash: &H256, code: &[u8]) -> Arc<BitSet> {
 		if code_hash == &KECCAK_EMPTY {
 			return Self::find_jump_destinations(code);
 		}
 
+		let pinned = match self.pinned.read().get(code_hash) {
+			Some(&Some(ref d)) => return d.clone(),
+			Some(&None) => true,
+			None => false,
+		};
+
+		if pinned {
+			let d = Self::find_jump_destinations(code);
+			self.pinned.write().insert(code_hash.clone(), Some(d.clone()));
+			return d;
+		}
+
 		if let Some(d) = self.jump_destinations.lock().get_mut(code_hash) {
 			return d.0.clone();
 		}
@@ -105,3 +132,24 @@ fn test_find_jump_destinations() {
 	// then
 	assert!(valid_jump_destinations.contains(66));
 }
+
+#[test]
+fn test_prewarmed_contracts_are_not_evicted() {
+	use rustc_hex::FromHex;
+	// given
+	let cache = SharedCache::new(0);
+	let code = "600456005b00".from_hex().unwrap();
+	let prewarmed = H256::from(1);
+	let pinned = H256::from(2);
+	cache.prewarm(&prewarmed, &code);
+	cache.pin(pinned);
+
+	// when
+	let first = cache.jump_destinations(&pinned, &code);
+	let second = cache.jump_destinations(&pinned, &code);
+
+	// then
+	assert!(cache.jump_destinations(&prewarmed, &[]).contains(4));
+	assert!(first.contains(4));
+	assert!(Arc::ptr_eq(&first, &second));
+}
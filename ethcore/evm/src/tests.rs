@@ -285,6 +285,117 @@ fn test_author(factory: super::Factory) {
 	assert_store(&ext, 0, "0000000000000000000000000f572e5295c57f15886f9b263e2f6d2d6c7b5ec6");
 }
 
+evm_test!{test_eip2929_access_costs: test_eip2929_access_costs_int}
+fn test_eip2929_access_costs(factory: super::Factory) {
+	// SLOAD(0) twice, then BALANCE(1) twice
+	let code = "60005450600054506001315060013150".from_hex().unwrap();
+
+	let mut params = ActionParams::default();
+	params.gas = U256::from(100_000);
+	params.code = Some(Arc::new(code));
+
+	let mut ext = FakeExt::new_berlin();
+	let gas_left = {
+		let mut vm = factory.create(params.clone(), ext.schedule(), ext.depth());
+		test_finalize(vm.exec(&mut ext)).unwrap()
+	};
+
+	assert_eq!(gas_left, U256::from(95_080));
+	assert!(ext.access_list.contains_storage_key(&Address::default(), &H256::zero()));
+	assert!(ext.access_list.contains_address(&Address::from(1)));
+
+	// keys from the transaction access list are warm from the start
+	let mut ext = FakeExt::new_berlin();
+	ext.access_list.insert_storage_key(Address::default(), H256::zero());
+	let gas_left = {
+		let mut vm = factory.create(params, ext.schedule(), ext.depth());
+		test_finalize(vm.exec(&mut ext)).unwrap()
+	};
+
+	assert_eq!(gas_left, U256::from(97_080));
+}
+
+evm_test!{test_extcodehash: test_extcodehash_int}
+fn test_extcodehash(factory: super::Factory) {
+	use hash::keccak;
+
+	// hashes of an existing and a missing account stored at 0 and 1
+	let code = "73015ff760ad0a2ee7d0ab83ae09d1b7fd0f0d7ae03f600055730000000000000000000000000000000000000dead3f600155".from_hex().unwrap();
+	let other = Address::from_str("015ff760ad0a2ee7d0ab83ae09d1b7fd0f0d7ae0").unwrap();
+	let other_code = "6000".from_hex().unwrap();
+
+	let mut params = ActionParams::default();
+	params.gas = U256::from(100_000);
+	params.code = Some(Arc::new(code));
+	let mut ext = FakeExt::new_constantinople();
+	ext.codes.insert(other, Arc::new(other_code.clone()));
+
+	let gas_left = {
+		let mut vm = factory.create(params.clone(), ext.schedule(), ext.depth());
+		test_finalize(vm.exec(&mut ext)).unwrap()
+	};
+
+	assert_eq!(gas_left, U256::from(74_188));
+	assert_eq!(ext.store.get(&H256::from(0)).unwrap(), &keccak(&other_code));
+	assert_eq!(ext.store.get(&H256::from(1)).cloned().unwrap_or_default(), H256::zero());
+
+	// not available before activation
+	let mut ext = FakeExt::new_byzantium();
+	let mut vm = factory.create(params, ext.schedule(), ext.depth());
+	match test_finalize(vm.exec(&mut ext)) {
+		Err(vm::Error::BadInstruction { instruction: 0x3f }) => {},
+		other => panic!("Expected BadInstruction, got {:?}", other),
+	}
+}
+
+evm_test!{test_chain_id: test_chain_id_int}
+fn test_chain_id(factory: super::Factory) {
+	let code = "46600055".from_hex().unwrap();
+
+	let mut params = ActionParams::default();
+	params.gas = U256::from(100_000);
+	params.code = Some(Arc::new(code));
+	let mut ext = FakeExt::new_istanbul();
+	ext.chain_id = 9;
+
+	let gas_left = {
+		let mut vm = factory.create(params.clone(), ext.schedule(), ext.depth());
+		test_finalize(vm.exec(&mut ext)).unwrap()
+	};
+
+	assert_eq!(gas_left, U256::from(79_995));
+	assert_store(&ext, 0, "0000000000000000000000000000000000000000000000000000000000000009");
+
+	// not available before activation
+	let mut ext = FakeExt::new_constantinople();
+	let mut vm = factory.create(params, ext.schedule(), ext.depth());
+	match test_finalize(vm.exec(&mut ext)) {
+		Err(vm::Error::BadInstruction { instruction: 0x46 }) => {},
+		other => panic!("Expected BadInstruction, got {:?}", other),
+	}
+}
+
+evm_test!{test_selfbalance: test_selfbalance_int}
+fn test_selfbalance(factory: super::Factory) {
+	let own_addr = Address::from_str("1337000000000000000000000000000000000000").unwrap();
+	let code = "47600055".from_hex().unwrap();
+
+	let mut params = ActionParams::default();
+	params.gas = U256::from(100_000);
+	params.code = Some(Arc::new(code));
+	params.address = own_addr.clone();
+	let mut ext = FakeExt::new_istanbul();
+	ext.balances.insert(own_addr, U256::from(1_025));
+
+	let gas_left = {
+		let mut vm = factory.create(params, ext.schedule(), ext.depth());
+		test_finalize(vm.exec(&mut ext)).unwrap()
+	};
+
+	assert_eq!(gas_left, U256::from(79_992));
+	assert_store(&ext, 0, "0000000000000000000000000000000000000000000000000000000000000401");
+}
+
 evm_test!{test_timestamp: test_timestamp_int}
 fn test_timestamp(factory: super::Factory) {
 	let timestamp = 0x1234;
@@ -29,8 +29,8 @@ use hashdb::HashDB;
 use keccak_hasher::KeccakHasher;
 use memorydb::MemoryDB;
 use bytes::Bytes;
-use trie::{TrieMut, Trie, Recorder};
-use ethtrie::{self, TrieDB, TrieDBMut};
+use trie::{TrieMut, Trie};
+use ethtrie::{self, TrieDB, TrieDBMut, TrieProof};
 use rlp::{RlpStream, Rlp};
 
 // encode a key.
@@ -71,11 +71,8 @@ impl<DB: HashDB<KeccakHasher>> CHT<DB> {
 	pub fn prove(&self, num: u64, from_level: u32) -> ethtrie::Result<Option<Vec<Bytes>>> {
 		if block_to_cht_number(num) != Some(self.number) { return Ok(None) }
 
-		let mut recorder = Recorder::with_depth(from_level);
 		let t = TrieDB::new(&self.db, &self.root)?;
-		t.get_with(&key!(num), &mut recorder)?;
-
-		Ok(Some(recorder.drain().into_iter().map(|x| x.data).collect()))
+		Ok(Some(t.prove_from_level(&key!(num), from_level)?))
 	}
 }
 
@@ -149,9 +146,7 @@ pub fn compute_root<I>(cht_num: u64, iterable: I) -> Option<H256>
 /// verify the given trie branch and extract the canonical hash and total difficulty.
 // TODO: better support for partially-checked queries.
 pub fn check_proof(proof: &[Bytes], num: u64, root: H256) -> Option<(H256, U256)> {
-	let mut db = MemoryDB::<KeccakHasher>::new();
-
-	for node in proof { db.insert(&node[..]); }
+	let db = ethtrie::proof_db(proof);
 	let res = match TrieDB::new(&db, &root) {
 		Err(_) => return None,
 		Ok(trie) => trie.get_with(&key!(num), |val: &[u8]| {
@@ -34,7 +34,7 @@ use ethcore::block_status::BlockStatus;
 use ethcore::encoded;
 use ethcore::engines::epoch::{Transition as EpochTransition, PendingTransition as PendingEpochTransition};
 use ethcore::error::{Error, EthcoreResult, ErrorKind as EthcoreErrorKind, BlockError};
-use ethcore::header::Header;
+use ethcore::header::{BlockNumber, Header};
 use ethcore::ids::BlockId;
 use ethcore::spec::{Spec, SpecHardcodedSync};
 use ethereum_types::{H256, H264, U256};
@@ -179,10 +179,10 @@ fn encode_canonical_transition(header: &Header, proof: &[u8]) -> Vec<u8> {
 }
 
 // decode last canonical transition entry.
-fn decode_canonical_transition(t: &[u8]) -> Result<(Header, &[u8]), DecoderError> {
+fn decode_canonical_transition(t: &[u8], eip1559_transition: BlockNumber) -> Result<(Header, &[u8]), DecoderError> {
 	let rlp = Rlp::new(t);
 
-	Ok((rlp.val_at(0)?, rlp.at(1)?.data()?))
+	Ok((Header::decode_rlp(&rlp.at(0)?, eip1559_transition)?, rlp.at(1)?.data()?))
 }
 
 /// Pending changes from `insert` to be applied after the database write has finished.
@@ -205,6 +205,7 @@ pub struct HeaderChain {
 	db: Arc<KeyValueDB>,
 	col: Option<u32>,
 	cache: Arc<Mutex<Cache>>,
+	eip1559_transition: BlockNumber,
 }
 
 impl HeaderChain {
@@ -220,6 +221,7 @@ impl HeaderChain {
 
 		let genesis = ::rlp::encode(&spec.genesis_header()).into_vec();
 		let decoded_header = spec.genesis_header();
+		let eip1559_transition = spec.engine.params().eip1559_transition;
 
 		let chain = if let Some(current) = db.get(col, CURRENT_KEY)? {
 			let curr : BestAndLatest = ::rlp::decode(&current).expect("decoding db value failed");
@@ -273,6 +275,7 @@ impl HeaderChain {
 				db,
 				col,
 				cache,
+				eip1559_transition,
 			}
 
 		} else {
@@ -288,6 +291,7 @@ impl HeaderChain {
 				db: db.clone(),
 				col,
 				cache,
+				eip1559_transition,
 			};
 
 			// insert the hardcoded sync into the database.
@@ -299,7 +303,7 @@ impl HeaderChain {
 					batch.put(col, cht_key(cht_num as u64).as_bytes(), &::rlp::encode(cht_root));
 				}
 
-				let decoded_header = hardcoded_sync.header.decode()?;
+				let decoded_header = hardcoded_sync.header.decode(eip1559_transition)?;
 				let decoded_header_num = decoded_header.number();
 
 				// write the block in the DB.
@@ -520,7 +524,8 @@ impl HeaderChain {
 										Ok(None) => panic!("stored candidates always have corresponding headers; qed"),
 										Ok(Some(header)) => Some((
 											epoch_transition,
-											::rlp::decode(&header).expect("decoding value from db failed")
+											Header::decode_rlp(&Rlp::new(&header), self.eip1559_transition)
+												.expect("decoding value from db failed")
 										)),
 									};
 								}
@@ -579,7 +584,7 @@ impl HeaderChain {
 						bail!(msg);
 					};
 
-					let decoded = header.decode().expect("decoding db value failed");
+					let decoded = header.decode(self.eip1559_transition).expect("decoding db value failed");
 
 					let entry: Entry = {
 						let bytes = self.db.get(self.col, era_key(h_num).as_bytes())?
@@ -808,7 +813,7 @@ impl HeaderChain {
 
 		for hdr in self.ancestry_iter(BlockId::Hash(parent_hash)) {
 			if let Some(transition) = live_proofs.get(&hdr.hash()).cloned() {
-				return hdr.decode().map(|decoded_hdr| {
+				return hdr.decode(self.eip1559_transition).map(|decoded_hdr| {
 					(decoded_hdr, transition.proof)
 				}).ok();
 			}
@@ -819,7 +824,7 @@ impl HeaderChain {
 			Ok(x) => {
 				let x = x.expect("last canonical transition always instantiated; qed");
 
-				let (hdr, proof) = decode_canonical_transition(&x)
+				let (hdr, proof) = decode_canonical_transition(&x, self.eip1559_transition)
 					.expect("last canonical transition always encoded correctly; qed");
 
 				Some((hdr, proof.to_vec()))
@@ -864,7 +869,7 @@ mod tests {
 
 	use ethereum_types::U256;
 	use ethcore::ids::BlockId;
-	use ethcore::header::Header;
+	use ethcore::header::{BlockNumber, Header};
 	use ethcore::spec::Spec;
 	use cache::Cache;
 	use kvdb::KeyValueDB;
@@ -1219,7 +1224,7 @@ mod tests {
 		let hardcoded_sync = chain.read_hardcoded_sync().expect("failed reading hardcoded sync").expect("failed unwrapping hardcoded sync");
 		assert_eq!(hardcoded_sync.chts.len(), 3);
 		assert_eq!(hardcoded_sync.total_difficulty, total_difficulty);
-		let decoded: Header = hardcoded_sync.header.decode().expect("decoding failed");
+		let decoded: Header = hardcoded_sync.header.decode(BlockNumber::max_value()).expect("decoding failed");
 		assert_eq!(decoded.number(), h_num);
 	}
 }
@@ -315,7 +315,7 @@ impl<T: ChainDataFetcher> Client<T> {
 
 			let epoch_proof = self.engine.is_epoch_end(
 				&verified_header,
-				&|h| self.chain.block_header(BlockId::Hash(h)).and_then(|hdr| hdr.decode().ok()),
+				&|h| self.chain.block_header(BlockId::Hash(h)).and_then(|hdr| hdr.decode(self.engine.params().eip1559_transition).ok()),
 				&|h| self.chain.pending_transition(h),
 			);
 
@@ -384,6 +384,7 @@ impl<T: ChainDataFetcher> Client<T> {
 			difficulty: header.difficulty(),
 			last_hashes: self.build_last_hashes(header.parent_hash()),
 			gas_used: Default::default(),
+			base_fee: None,
 			gas_limit: header.gas_limit(),
 		})
 	}
@@ -425,7 +426,7 @@ impl<T: ChainDataFetcher> Client<T> {
 		// Verify Block Family
 
 		let verify_family_result = {
-			parent_header.decode()
+			parent_header.decode(self.engine.params().eip1559_transition)
 				.map_err(|dec_err| dec_err.into())
 				.and_then(|decoded| {
 					self.engine.verify_block_family(&verified_header, &decoded)
@@ -27,16 +27,13 @@ use ethcore::machine::EthereumMachine;
 use ethcore::receipt::Receipt;
 use ethcore::state::{self, ProvedExecution};
 use ethereum_types::{H256, U256, Address};
-use ethtrie::{TrieError, TrieDB};
+use ethtrie::{self, TrieError};
 use hash::{KECCAK_NULL_RLP, KECCAK_EMPTY, KECCAK_EMPTY_LIST_RLP, keccak};
-use hashdb::HashDB;
 use kvdb::DBValue;
-use memorydb::MemoryDB;
 use parking_lot::Mutex;
 use request::{self as net_request, IncompleteRequest, CompleteRequest, Output, OutputKind, Field};
 use rlp::{RlpStream, Rlp};
 use transaction::SignedTransaction;
-use trie::Trie;
 use vm::EnvInfo;
 
 const SUPPLIED_MATCHES: &str = "supplied responses always match produced requests; enforced by `check_response`; qed";
@@ -926,7 +923,7 @@ impl Body {
 	pub fn check_response(&self, cache: &Mutex<::cache::Cache>, body: &encoded::Body) -> Result<encoded::Block, Error> {
 		// check the integrity of the the body against the header
 		let header = self.0.as_ref()?;
-		let tx_root = ::triehash::ordered_trie_root(body.transactions_rlp().iter().map(|r| r.as_raw()));
+		let tx_root = ::triehash::ordered_trie_root(body.transactions_rlp().iter().map(|r| r.data().unwrap_or_else(|_| r.as_raw())));
 		if tx_root != header.transactions_root() {
 			trace!(target: "on_demand", "Body Response: \"WrongTrieRoot\" tx_root: {:?} header_root: {:?}", tx_root, header.transactions_root());
 			return Err(Error::WrongTrieRoot(header.transactions_root(), tx_root));
@@ -954,7 +951,7 @@ impl BlockReceipts {
 	/// Check a response with receipts against the stored header.
 	pub fn check_response(&self, cache: &Mutex<::cache::Cache>, receipts: &[Receipt]) -> Result<Vec<Receipt>, Error> {
 		let receipts_root = self.0.as_ref()?.receipts_root();
-		let found_root = ::triehash::ordered_trie_root(receipts.iter().map(|r| ::rlp::encode(r)));
+		let found_root = ::triehash::ordered_trie_root(receipts.iter().map(|r| r.encode_raw()));
 
 		if receipts_root == found_root {
 			cache.lock().insert_block_receipts(receipts_root, receipts.to_vec());
@@ -981,10 +978,7 @@ impl Account {
 		let header = self.header.as_ref()?;
 		let state_root = header.state_root();
 
-		let mut db = MemoryDB::new();
-		for node in proof { db.insert(&node[..]); }
-
-		match TrieDB::new(&db, &state_root).and_then(|t| t.get(&keccak(&self.address)))? {
+		match ethtrie::verify_proof(&state_root, &keccak(&self.address), proof)? {
 			Some(val) => {
 				let rlp = Rlp::new(&val);
 				Ok(Some(BasicAccount {
@@ -1103,9 +1097,8 @@ mod tests {
 	use ethereum_types::{H256, Address};
 	use memorydb::MemoryDB;
 	use parking_lot::Mutex;
-	use trie::{Trie, TrieMut};
-	use ethtrie::{SecTrieDB, SecTrieDBMut};
-	use trie::Recorder;
+	use trie::TrieMut;
+	use ethtrie::{SecTrieDB, SecTrieDBMut, TrieProof};
 	use hash::keccak;
 
 	use ::ethcore::client::{BlockChainClient, BlockInfo, TestBlockChainClient, EachBlockWith};
@@ -1261,6 +1254,7 @@ mod tests {
 			gas_used: 21_000u64.into(),
 			log_bloom: Default::default(),
 			logs: Vec::new(),
+			transaction_type: 0,
 		}).collect::<Vec<_>>();
 
 		let mut header = Header::new();
@@ -1308,11 +1302,7 @@ mod tests {
 
 		let proof = {
 			let trie = SecTrieDB::new(&db, &root).unwrap();
-			let mut recorder = Recorder::new();
-
-			trie.get_with(&*addr, &mut recorder).unwrap().unwrap();
-
-			recorder.drain().into_iter().map(|r| r.data).collect::<Vec<_>>()
+			trie.prove(&*addr).unwrap()
 		};
 
 		header.set_state_root(root.clone());
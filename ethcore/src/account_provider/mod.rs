@@ -20,7 +20,7 @@ mod stores;
 
 use self::stores::AddressBook;
 
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 use std::fmt;
 use std::time::{Instant, Duration};
 
@@ -29,7 +29,7 @@ use ethstore::ethkey::{Address, Message, Public, Secret, Password, Random, Gener
 use ethjson::misc::AccountMeta;
 use ethstore::{
 	SimpleSecretStore, SecretStore, Error as SSError, EthStore, EthMultiStore,
-	random_string, SecretVaultRef, StoreAccountRef, OpaqueSecret,
+	random_string, SecretVaultRef, StoreAccountRef, OpaqueSecret, AccountLabels,
 };
 use parking_lot::RwLock;
 
@@ -384,6 +384,29 @@ impl AccountProvider {
 		Ok(())
 	}
 
+	/// Sets tags of an account, keeping the rest of its metadata.
+	pub fn set_account_tags(&self, address: Address, tags: Vec<String>) -> Result<(), Error> {
+		self.update_account_labels(address, |labels| labels.tags = tags)
+	}
+
+	/// Sets structured fields of an account, keeping the rest of its metadata.
+	pub fn set_account_fields(&self, address: Address, fields: BTreeMap<String, String>) -> Result<(), Error> {
+		self.update_account_labels(address, |labels| labels.fields = fields)
+	}
+
+	fn update_account_labels<F>(&self, address: Address, f: F) -> Result<(), Error> where
+		F: FnOnce(&mut AccountLabels),
+	{
+		let account = self.sstore.account_ref(&address)?;
+		let meta = self.sstore.meta(&account)?;
+		let mut labels = AccountLabels::from_json_meta(&meta);
+		f(&mut labels);
+		let meta = labels.insert_to_json_meta(&meta)
+			.map_err(|e| SSError::Custom(format!("Cannot update account meta: {}", e)))?;
+		self.sstore.set_meta(&account, meta)?;
+		Ok(())
+	}
+
 	/// Returns `true` if the password for `account` is `password`. `false` if not.
 	pub fn test_password(&self, address: &Address, password: &Password) -> Result<bool, Error> {
 		self.sstore.test_password(&self.sstore.account_ref(&address)?, password)
@@ -41,14 +41,15 @@ use error::{Error, BlockError};
 use ethereum_types::{H256, U256, Address, Bloom};
 use factory::Factories;
 use hash::keccak;
-use header::{Header, ExtendedHeader};
+use header::{BlockNumber, Header, ExtendedHeader};
+use rayon::prelude::*;
 use receipt::{Receipt, TransactionOutcome};
 use rlp::{Rlp, RlpStream, Encodable, Decodable, DecoderError, encode_list};
 use state_db::StateDB;
-use state::State;
+use state::{State, CleanupMode};
 use trace::Tracing;
 use transaction::{UnverifiedTransaction, SignedTransaction, Error as TransactionError};
-use triehash::ordered_trie_root;
+use triehash::par_ordered_trie_root;
 use unexpected::{Mismatch, OutOfBounds};
 use verification::PreverifiedBlock;
 use vm::{EnvInfo, LastHashes};
@@ -75,8 +76,9 @@ impl Block {
 	}
 }
 
-impl Decodable for Block {
-	fn decode(rlp: &Rlp) -> Result<Self, DecoderError> {
+impl Block {
+	/// Decode a block from RLP, splitting the base fee out of headers from the EIP-1559 transition on.
+	pub fn decode_rlp(rlp: &Rlp, eip1559_transition: BlockNumber) -> Result<Self, DecoderError> {
 		if rlp.as_raw().len() != rlp.payload_info()?.total() {
 			return Err(DecoderError::RlpIsTooBig);
 		}
@@ -84,13 +86,19 @@ impl Decodable for Block {
 			return Err(DecoderError::RlpIncorrectListLen);
 		}
 		Ok(Block {
-			header: rlp.val_at(0)?,
+			header: Header::decode_rlp(&rlp.at(0)?, eip1559_transition)?,
 			transactions: rlp.list_at(1)?,
-			uncles: rlp.list_at(2)?,
+			uncles: rlp.at(2)?.iter().map(|r| Header::decode_rlp(&r, eip1559_transition)).collect::<Result<_, _>>()?,
 		})
 	}
 }
 
+impl Decodable for Block {
+	fn decode(rlp: &Rlp) -> Result<Self, DecoderError> {
+		Block::decode_rlp(rlp, BlockNumber::max_value())
+	}
+}
+
 /// An internal type for a block's common elements.
 #[derive(Clone)]
 pub struct ExecutedBlock {
@@ -141,6 +149,7 @@ impl ExecutedBlock {
 			difficulty: self.header.difficulty().clone(),
 			last_hashes: self.last_hashes.clone(),
 			gas_used: self.receipts.last().map_or(U256::zero(), |r| r.gas_used),
+			base_fee: self.header.base_fee(),
 			gas_limit: self.header.gas_limit().clone(),
 		}
 	}
@@ -374,6 +383,87 @@ impl<'x> OpenBlock<'x> {
 		Ok(())
 	}
 
+	/// Push transactions onto the block, executing them optimistically in parallel.
+	///
+	/// Each transaction is first executed on its own copy of the state the block starts with.
+	/// The results are then applied in order. A result is kept only if the transaction did not
+	/// access any account modified by the transactions before it, otherwise the transaction is
+	/// replayed on top of the block state. The outcome is the same as of serial execution.
+	fn push_transactions_parallel(&mut self, transactions: Vec<SignedTransaction>) -> Result<(), Error> {
+		let env_info = self.env_info();
+		let machine = self.engine.machine();
+		let params = machine.params();
+
+		// receipts holding intermediate state roots need every transaction committed in turn.
+		let no_intermediate_commits = env_info.number >= params.eip658_transition ||
+			(env_info.number >= params.eip98_transition && env_info.number >= params.validate_receipts_transition);
+		if transactions.len() < 2 || !no_intermediate_commits || !self.block.receipts.is_empty() {
+			return self.push_transactions(transactions);
+		}
+
+		let tracing = self.block.traces.is_enabled();
+		let author = env_info.author;
+		let author_balance = self.block.state.balance(&author)?;
+
+		let states: Vec<_> = transactions.iter().map(|_| self.block.state.clone()).collect();
+		let speculative: Vec<_> = states.into_par_iter()
+			.zip(transactions.par_iter())
+			.map(|(mut state, t)| {
+				state.set_access_log(Some(HashSet::new()));
+				let outcome = state.apply(&env_info, machine, t, tracing);
+				let accessed = state.take_access_log().expect("access log enabled above; qed");
+				(state, accessed, outcome)
+			})
+			.collect();
+
+		let gas_limit = *self.block.header.gas_limit();
+		let mut modified = HashSet::new();
+		let mut replayed = 0;
+		for (t, (state, accessed, outcome)) in transactions.into_iter().zip(speculative) {
+			let gas_used = self.block.receipts.last().map_or(U256::zero(), |r| r.gas_used);
+			let must_replay = !accessed.is_disjoint(&modified)
+				|| gas_used + t.gas > gas_limit
+				|| self.block.transactions_set.contains(&t.hash());
+
+			match outcome {
+				Ok(mut outcome) if !must_replay => {
+					// fees are paid without accessing the author account, so they're added on top
+					// of whatever the previous transactions paid.
+					let fees = match accessed.contains(&author) {
+						true => U256::zero(),
+						false => state.balance(&author)? - author_balance,
+					};
+					let changed: HashSet<_> = state.dirty_accounts().intersection(&accessed).cloned().collect();
+					self.block.state.merge_accounts(state, &changed);
+					if !fees.is_zero() {
+						self.block.state.add_balance(&author, &fees, CleanupMode::NoEmpty)?;
+					}
+					modified.extend(changed);
+
+					outcome.receipt.gas_used = gas_used + outcome.receipt.gas_used;
+					self.block.transactions_set.insert(t.hash());
+					self.block.transactions.push(t);
+					if let Tracing::Enabled(ref mut traces) = self.block.traces {
+						traces.push(outcome.trace.into());
+					}
+					self.block.receipts.push(outcome.receipt);
+				},
+				_ => {
+					replayed += 1;
+					self.block.state.set_access_log(Some(HashSet::new()));
+					let result = self.push_transaction(t, None).map(|_| ());
+					let accessed = self.block.state.take_access_log().expect("access log enabled above; qed");
+					result?;
+					modified.extend(self.block.state.dirty_accounts().intersection(&accessed).cloned());
+				},
+			}
+			modified.insert(author);
+		}
+
+		debug!(target: "enact", "Block #{}: {} transactions executed in parallel, {} replayed", env_info.number, self.block.transactions.len() - replayed, replayed);
+		Ok(())
+	}
+
 	/// Populate self from a header.
 	fn populate_from(&mut self, header: &Header) {
 		self.block.header.set_difficulty(*header.difficulty());
@@ -382,6 +472,7 @@ impl<'x> OpenBlock<'x> {
 		self.block.header.set_author(*header.author());
 		self.block.header.set_uncles_hash(*header.uncles_hash());
 		self.block.header.set_transactions_root(*header.transactions_root());
+		self.block.header.set_base_fee(header.base_fee());
 		// TODO: that's horrible. set only for backwards compatibility
 		if header.extra_data().len() > self.engine.maximum_extra_data_size() {
 			warn!("Couldn't set extradata. Ignoring.");
@@ -408,11 +499,11 @@ impl<'x> OpenBlock<'x> {
 		s.engine.on_close_block(&mut s.block)?;
 		s.block.state.commit()?;
 
-		s.block.header.set_transactions_root(ordered_trie_root(s.block.transactions.iter().map(|e| e.rlp_bytes())));
+		s.block.header.set_transactions_root(par_ordered_trie_root(s.block.transactions.iter().map(|e| e.encode_raw())));
 		let uncle_bytes = encode_list(&s.block.uncles);
 		s.block.header.set_uncles_hash(keccak(&uncle_bytes));
 		s.block.header.set_state_root(s.block.state.root().clone());
-		s.block.header.set_receipts_root(ordered_trie_root(s.block.receipts.iter().map(|r| r.rlp_bytes())));
+		s.block.header.set_receipts_root(par_ordered_trie_root(s.block.receipts.iter().map(|r| r.encode_raw())));
 		s.block.header.set_log_bloom(s.block.receipts.iter().fold(Bloom::zero(), |mut b, r| {
 			b.accrue_bloom(&r.log_bloom);
 			b
@@ -476,7 +567,7 @@ impl LockedBlock {
 			receipt.outcome = TransactionOutcome::Unknown;
 		}
 		self.block.header.set_receipts_root(
-			ordered_trie_root(self.block.receipts.iter().map(|r| r.rlp_bytes()))
+			par_ordered_trie_root(self.block.receipts.iter().map(|r| r.encode_raw()))
 		);
 		// compute hash and cache it.
 		self.block.header.compute_hash();
@@ -556,6 +647,7 @@ fn enact(
 	uncles: Vec<Header>,
 	engine: &EthEngine,
 	tracing: bool,
+	parallel: bool,
 	db: StateDB,
 	parent: &Header,
 	last_hashes: Arc<LastHashes>,
@@ -586,7 +678,11 @@ fn enact(
 	)?;
 
 	b.populate_from(&header);
-	b.push_transactions(transactions)?;
+	if parallel {
+		b.push_transactions_parallel(transactions)?;
+	} else {
+		b.push_transactions(transactions)?;
+	}
 
 	for u in uncles {
 		b.push_uncle(u)?;
@@ -600,6 +696,7 @@ pub fn enact_verified(
 	block: PreverifiedBlock,
 	engine: &EthEngine,
 	tracing: bool,
+	parallel: bool,
 	db: StateDB,
 	parent: &Header,
 	last_hashes: Arc<LastHashes>,
@@ -614,6 +711,7 @@ pub fn enact_verified(
 		block.uncles,
 		engine,
 		tracing,
+		parallel,
 		db,
 		parent,
 		last_hashes,
@@ -649,7 +747,7 @@ mod tests {
 		last_hashes: Arc<LastHashes>,
 		factories: Factories,
 	) -> Result<LockedBlock, Error> {
-		let block = Unverified::from_rlp(block_bytes)?;
+		let block = Unverified::from_rlp(block_bytes, engine.params().eip1559_transition)?;
 		let header = block.header;
 		let transactions: Result<Vec<_>, Error> = block
 			.transactions
@@ -701,7 +799,7 @@ mod tests {
 		last_hashes: Arc<LastHashes>,
 		factories: Factories,
 	) -> Result<SealedBlock, Error> {
-		let header = Unverified::from_rlp(block_bytes.clone())?.header;
+		let header = Unverified::from_rlp(block_bytes.clone(), engine.params().eip1559_transition)?.header;
 		Ok(enact_bytes(block_bytes, engine, tracing, db, parent, last_hashes, factories)?
 		   .seal(engine, header.seal().to_vec())?)
 	}
@@ -768,11 +866,64 @@ mod tests {
 
 		let bytes = e.rlp_bytes();
 		assert_eq!(bytes, orig_bytes);
-		let uncles = view!(BlockView, &bytes).uncles();
+		let uncles = view!(BlockView, &bytes).uncles(engine.params().eip1559_transition);
 		assert_eq!(uncles[1].extra_data(), b"uncle2");
 
 		let db = e.drain().state.drop().1;
 		assert_eq!(orig_db.journal_db().keys(), db.journal_db().keys());
 		assert!(orig_db.journal_db().keys().iter().filter(|k| orig_db.journal_db().get(k.0) != db.journal_db().get(k.0)).next() == None);
 	}
+
+	#[test]
+	fn parallel_execution_matches_serial() {
+		use ethereum::new_byzantium_test;
+		use ethkey::{Generator, Random};
+		use state::CleanupMode;
+		use transaction::{Action, Transaction};
+
+		let spec = new_byzantium_test();
+		let engine = &*spec.engine;
+		let genesis_header = spec.genesis_header();
+		let last_hashes = Arc::new(vec![genesis_header.hash()]);
+
+		let senders: Vec<_> = (0..4).map(|_| Random.generate().unwrap()).collect();
+		let transfer = |from: usize, nonce: u64, to: Address| Transaction {
+			action: Action::Call(to),
+			value: 1000.into(),
+			data: vec![],
+			gas: 21_000.into(),
+			gas_price: 1.into(),
+			nonce: nonce.into(),
+		}.sign(senders[from].secret(), None);
+		// the third transaction is sent by the recipient of the first one
+		// and the fourth is the second one of the same sender, both have to be replayed.
+		let transactions = vec![
+			transfer(0, 0, senders[1].address()),
+			transfer(2, 0, 0x100.into()),
+			transfer(1, 0, 0x101.into()),
+			transfer(0, 1, 0x102.into()),
+			transfer(3, 0, 0x103.into()),
+		];
+
+		let enact = |parallel: bool| {
+			let db = spec.ensure_db_good(get_temp_state_db(), &Default::default()).unwrap();
+			let mut b = OpenBlock::new(engine, Default::default(), false, db, &genesis_header, last_hashes.clone(), 0x200.into(), (3141562.into(), 31415620.into()), vec![], false, &mut Vec::new().into_iter()).unwrap();
+			b.remove_gas_limit();
+			for sender in &senders {
+				b.block_mut().state_mut().add_balance(&sender.address(), &1_000_000.into(), CleanupMode::NoEmpty).unwrap();
+			}
+			if parallel {
+				b.push_transactions_parallel(transactions.clone()).unwrap();
+			} else {
+				b.push_transactions(transactions.clone()).unwrap();
+			}
+			b.close_and_lock().unwrap()
+		};
+
+		let serial = enact(false);
+		let parallel = enact(true);
+		assert_eq!(parallel.block().receipts, serial.block().receipts);
+		assert_eq!(parallel.header().receipts_root(), serial.header().receipts_root());
+		assert_eq!(parallel.header().state_root(), serial.header().state_root());
+	}
 }
@@ -138,8 +138,8 @@ pub trait BlockProvider {
 
 	/// Get a list of uncles for a given block.
 	/// Returns None if block does not exist.
-	fn uncles(&self, hash: &H256) -> Option<Vec<Header>> {
-		self.block_body(hash).map(|body| body.uncles())
+	fn uncles(&self, hash: &H256, eip1559_transition: BlockNumber) -> Option<Vec<Header>> {
+		self.block_body(hash).map(|body| body.uncles(eip1559_transition))
 	}
 
 	/// Get a list of uncle hashes for a given block.
@@ -212,8 +212,8 @@ pub struct BlockChain {
 	// Only updated with `insert_unordered_block`.
 	best_ancient_block: RwLock<Option<BestAncientBlock>>,
 	// Stores the last block of the last sequence of blocks. `None` if there are no gaps.
-	// This is calculated on start and does not get updated.
-	first_block: Option<H256>,
+	// This is calculated on start and only updated with `insert_first_block_parent`.
+	first_block: RwLock<Option<H256>>,
 
 	// block cache
 	block_headers: RwLock<HashMap<H256, encoded::Header>>,
@@ -233,6 +233,9 @@ pub struct BlockChain {
 	pending_block_hashes: RwLock<HashMap<BlockNumber, H256>>,
 	pending_block_details: RwLock<HashMap<H256, BlockDetails>>,
 	pending_transaction_addresses: RwLock<HashMap<H256, Option<TransactionAddress>>>,
+	pending_first_block: RwLock<Option<H256>>,
+
+	eip1559_transition: BlockNumber,
 }
 
 impl BlockProvider for BlockChain {
@@ -243,7 +246,7 @@ impl BlockProvider for BlockChain {
 	}
 
 	fn first_block(&self) -> Option<H256> {
-		self.first_block.clone()
+		self.first_block.read().clone()
 	}
 
 	fn best_ancient_block(&self) -> Option<H256> {
@@ -453,7 +456,7 @@ impl<'a> Iterator for AncestryWithMetadataIter<'a> {
 		} else {
 			let details = self.chain.block_details(&self.current);
 			let header = self.chain.block_header_data(&self.current)
-				.map(|h| h.decode().expect("Stored block header data is valid RLP; qed"));
+				.map(|h| h.decode(self.chain.eip1559_transition).expect("Stored block header data is valid RLP; qed"));
 
 			match (details, header) {
 				(Some(details), Some(header)) => {
@@ -516,13 +519,18 @@ impl<'a> Iterator for EpochTransitionIter<'a> {
 }
 
 impl BlockChain {
+	/// Block from which stored headers carry the EIP-1559 base fee.
+	pub fn eip1559_transition(&self) -> BlockNumber {
+		self.eip1559_transition
+	}
+
 	/// Create new instance of blockchain from given Genesis.
 	pub fn new(config: Config, genesis: &[u8], db: Arc<BlockChainDB>) -> BlockChain {
 		// 400 is the average size of the key
 		let cache_man = CacheManager::new(config.pref_cache_size, config.max_cache_size, 400);
 
 		let mut bc = BlockChain {
-			first_block: None,
+			first_block: RwLock::new(None),
 			best_block: RwLock::new(BestBlock {
 				// BestBlock will be overwritten anyway.
 				header: Default::default(),
@@ -542,6 +550,8 @@ impl BlockChain {
 			pending_block_hashes: RwLock::new(HashMap::new()),
 			pending_block_details: RwLock::new(HashMap::new()),
 			pending_transaction_addresses: RwLock::new(HashMap::new()),
+			pending_first_block: RwLock::new(None),
+			eip1559_transition: config.eip1559_transition,
 		};
 
 		// load best block
@@ -591,7 +601,7 @@ impl BlockChain {
 			let mut best_block = bc.best_block.write();
 			*best_block = BestBlock {
 				total_difficulty: best_block_total_difficulty,
-				header: best_block_rlp.decode_header(),
+				header: best_block_rlp.decode_header(bc.eip1559_transition),
 				block: best_block_rlp,
 			};
 		}
@@ -636,11 +646,11 @@ impl BlockChain {
 						let mut batch = db.key_value().transaction();
 						batch.put(db::COL_EXTRA, b"first", &hash);
 						db.key_value().write(batch).expect("Low level database error when writing 'first' block. Some issue with disk?");
-						bc.first_block = Some(hash);
+						*bc.first_block.write() = Some(hash);
 					}
 				},
 				Some(raw_first) => {
-					bc.first_block = Some(H256::from_slice(&raw_first));
+					*bc.first_block.write() = Some(H256::from_slice(&raw_first));
 				},
 			}
 
@@ -860,6 +870,85 @@ impl BlockChain {
 		}
 	}
 
+	/// Returns true if the given block is the parent of the first block after the gap in the chain.
+	pub fn is_first_block_parent(&self, hash: &H256) -> bool {
+		self.first_block()
+			.and_then(|first| self.block_details(&first))
+			.map_or(false, |details| details.parent == *hash)
+	}
+
+	/// Inserts the parent of the first block after the gap in the chain, so that the gap can be filled
+	/// backwards, from a restored snapshot towards genesis. The block becomes the new first block once
+	/// the changes are committed. Returns `false` if the block is not the parent of the first block.
+	pub fn insert_first_block_parent(&self, batch: &mut DBTransaction, block: encoded::Block, receipts: Vec<Receipt>) -> bool {
+		let hash = block.header_view().hash();
+		let first = match self.first_block() {
+			Some(first) => first,
+			None => return false,
+		};
+		let first_details = match self.block_details(&first) {
+			Some(details) => details,
+			None => return false,
+		};
+		if first_details.parent != hash || self.is_known(&hash) {
+			return false;
+		}
+
+		assert!(self.pending_best_block.read().is_none());
+
+		let first_difficulty = self.block_header_data(&first)
+			.expect("first block is always an existing block; qed")
+			.difficulty();
+		let block_number = block.header_view().number();
+		let block_parent_hash = block.header_view().parent_hash();
+		let info = BlockInfo {
+			hash: hash,
+			number: block_number,
+			total_difficulty: first_details.total_difficulty - first_difficulty,
+			location: BlockLocation::CanonChain,
+		};
+
+		let compressed_header = compress(block.header_view().rlp().as_raw(), blocks_swapper());
+		let compressed_body = compress(&Self::block_to_body(block.raw()), blocks_swapper());
+		batch.put(db::COL_HEADERS, &hash, &compressed_header);
+		batch.put(db::COL_BODIES, &hash, &compressed_body);
+
+		// the gap is filled once the parent is known
+		let fills_gap = self.is_known(&block_parent_hash);
+		let mut block_details = if fills_gap {
+			self.prepare_block_details_update(block_parent_hash, &info, false)
+		} else {
+			let mut update = HashMap::new();
+			update.insert(hash, BlockDetails {
+				number: block_number,
+				total_difficulty: info.total_difficulty,
+				parent: block_parent_hash,
+				children: Vec::new(),
+				is_finalized: false,
+			});
+			update
+		};
+		block_details.get_mut(&hash).expect("details of the inserted block are always prepared; qed").children.push(first);
+
+		self.prepare_update(batch, ExtrasUpdate {
+			block_hashes: self.prepare_block_hashes_update(&info),
+			block_details: block_details,
+			block_receipts: self.prepare_block_receipts_update(receipts, &info),
+			blocks_blooms: self.prepare_block_blooms_update(block.header_view().log_bloom(), &info),
+			transactions_addresses: self.prepare_transaction_addresses_update(block.view().transaction_hashes(), &info),
+			info: info,
+			block,
+		}, false);
+
+		if fills_gap {
+			batch.delete(db::COL_EXTRA, b"ancient");
+			*self.best_ancient_block.write() = None;
+		}
+		batch.put(db::COL_EXTRA, b"first", &hash);
+		*self.pending_first_block.write() = Some(hash);
+		true
+	}
+
 	/// Insert an epoch transition. Provide an epoch number being transitioned to
 	/// and epoch transition object.
 	///
@@ -1095,7 +1184,7 @@ impl BlockChain {
 				batch.put(db::COL_EXTRA, b"best", &update.info.hash);
 				*best_block = Some(BestBlock {
 					total_difficulty: update.info.total_difficulty,
-					header: update.block.decode_header(),
+					header: update.block.decode_header(self.eip1559_transition),
 					block: update.block,
 				});
 			}
@@ -1116,6 +1205,7 @@ impl BlockChain {
 		let mut pending_write_hashes = self.pending_block_hashes.write();
 		let mut pending_block_details = self.pending_block_details.write();
 		let mut pending_write_txs = self.pending_transaction_addresses.write();
+		let mut pending_first_block = self.pending_first_block.write();
 
 		let mut best_block = self.best_block.write();
 		let mut write_block_details = self.block_details.write();
@@ -1141,6 +1231,10 @@ impl BlockChain {
 			write_txs.remove(hash);
 		}
 
+		if let Some(first) = pending_first_block.take() {
+			*self.first_block.write() = Some(first);
+		}
+
 		let mut cache_man = self.cache_man.lock();
 		for n in pending_hashes_keys {
 			cache_man.note_used(CacheId::BlockHashes(n));
@@ -1700,6 +1794,34 @@ mod tests {
 		assert_eq!(bc.transaction_address(&t1_hash), None);
 	}
 
+	#[test]
+	fn test_typed_transaction_addresses() {
+		let t1 = Transaction {
+			nonce: 0.into(),
+			gas_price: 0.into(),
+			gas: 100_000.into(),
+			action: Action::Create,
+			value: 100.into(),
+			data: "601080600c6000396000f3006000355415600957005b60203560003555".from_hex().unwrap(),
+		}.sign_with_access_list(&secret(), 1, vec![]);
+
+		let t1_hash = t1.hash();
+
+		let genesis = BlockBuilder::genesis();
+		let b1 = genesis.add_block_with_transactions(iter::once(t1));
+		let b1_hash = b1.last().hash();
+
+		let db = new_db();
+		let bc = new_chain(genesis.last().encoded(), db.clone());
+		insert_block(&db, &bc, b1.last().encoded(), vec![]);
+
+		assert_eq!(bc.block(&b1_hash).unwrap().transaction_hashes(), vec![t1_hash]);
+		assert_eq!(bc.transaction_address(&t1_hash), Some(TransactionAddress {
+			block_hash: b1_hash,
+			index: 0,
+		}));
+	}
+
 	#[test]
 	fn test_overwriting_transaction_addresses() {
 		let t1 = Transaction {
@@ -2058,6 +2180,7 @@ mod tests {
 				LogEntry { address: Default::default(), topics: vec![], data: vec![1], },
 				LogEntry { address: Default::default(), topics: vec![], data: vec![2], },
 			],
+			transaction_type: 0,
 		},
 		Receipt {
 			outcome: TransactionOutcome::StateRoot(H256::default()),
@@ -2066,6 +2189,7 @@ mod tests {
 			logs: vec![
 				LogEntry { address: Default::default(), topics: vec![], data: vec![3], },
 			],
+			transaction_type: 0,
 		}]);
 		insert_block(&db, &bc, b2.last().encoded(), vec![
 			Receipt {
@@ -2075,6 +2199,7 @@ mod tests {
 				logs: vec![
 					LogEntry { address: Default::default(), topics: vec![], data: vec![4], },
 				],
+				transaction_type: 0,
 			}
 		]);
 		insert_block(&db, &bc, b3.last().encoded(), vec![
@@ -2085,6 +2210,7 @@ mod tests {
 				logs: vec![
 					LogEntry { address: Default::default(), topics: vec![], data: vec![5], },
 				],
+				transaction_type: 0,
 			}
 		]);
 
@@ -2263,6 +2389,48 @@ mod tests {
 		assert_eq!(blocks_b3, vec![3]);
 	}
 
+	#[test]
+	fn test_insert_first_block_parent() {
+		let genesis = BlockBuilder::genesis();
+		let b1 = genesis.add_block();
+		let b2 = b1.add_block();
+		let b3 = b2.add_block();
+		let b4 = b3.add_block();
+		let b2_total_difficulty = genesis.last().difficulty() + b1.last().difficulty() + b2.last().difficulty();
+
+		let db = new_db();
+		{
+			let bc = new_chain(genesis.last().encoded(), db.clone());
+			let mut batch = db.key_value().transaction();
+			bc.insert_unordered_block(&mut batch, b3.last().encoded(), vec![], Some(b2_total_difficulty), true, false);
+			bc.commit();
+			db.key_value().write(batch).unwrap();
+			insert_block(&db, &bc, b4.last().encoded(), vec![]);
+		}
+
+		let bc = new_chain(genesis.last().encoded(), db.clone());
+		assert_eq!(bc.first_block(), Some(b3.last().hash()));
+		assert_eq!(bc.best_ancient_block(), Some(genesis.last().hash()));
+
+		let mut batch = db.key_value().transaction();
+		assert!(!bc.insert_first_block_parent(&mut batch, b1.last().encoded(), vec![]));
+		assert!(bc.insert_first_block_parent(&mut batch, b2.last().encoded(), vec![]));
+		bc.commit();
+		assert_eq!(bc.first_block(), Some(b2.last().hash()));
+		assert_eq!(bc.best_ancient_block(), Some(genesis.last().hash()));
+		assert!(bc.insert_first_block_parent(&mut batch, b1.last().encoded(), vec![]));
+		bc.commit();
+		db.key_value().write(batch).unwrap();
+
+		assert_eq!(bc.first_block(), Some(b1.last().hash()));
+		assert_eq!(bc.best_ancient_block(), None);
+		assert_eq!(bc.block_hash(1), Some(b1.last().hash()));
+		assert_eq!(bc.block_hash(2), Some(b2.last().hash()));
+		assert_eq!(bc.block_details(&b1.last().hash()).unwrap().children, vec![b2.last().hash()]);
+		assert_eq!(bc.block_details(&b2.last().hash()).unwrap().total_difficulty, b2_total_difficulty);
+		assert_eq!(bc.block_details(&genesis.last().hash()).unwrap().children, vec![b1.last().hash()]);
+	}
+
 	#[test]
 	fn test_best_block_update() {
 		let genesis = BlockBuilder::genesis();
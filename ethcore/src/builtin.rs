@@ -51,6 +51,15 @@ pub trait Impl: Send + Sync {
 	fn execute(&self, input: &[u8], output: &mut BytesRef) -> Result<(), Error>;
 }
 
+/// Provider of native built-in implementations which are not part of `ethcore`.
+///
+/// Chain specs may reference any name known to the registry in their `builtin` section,
+/// which lets custom chains ship additional precompiles without patching this module.
+pub trait BuiltinRegistry: Send + Sync {
+	/// Returns the native implementation registered under `name`, if any.
+	fn native(&self, name: &str) -> Option<Box<Impl>>;
+}
+
 /// A gas pricing scheme for built-in contracts.
 pub trait Pricer: Send + Sync {
 	/// The gas cost of running this built-in for the given input data.
@@ -175,8 +184,15 @@ impl Builtin {
 	pub fn is_active(&self, at: u64) -> bool { at >= self.activate_at }
 }
 
-impl From<ethjson::spec::Builtin> for Builtin {
-	fn from(b: ethjson::spec::Builtin) -> Self {
+impl Builtin {
+	/// Create a builtin from its spec, resolving names unknown to `ethereum_builtin` through `registry`.
+	///
+	/// Panics if the name is neither a standard builtin nor provided by the registry.
+	pub fn from_spec(b: ethjson::spec::Builtin, registry: Option<&BuiltinRegistry>) -> Self {
+		let native = ethereum_native(&b.name)
+			.or_else(|| registry.and_then(|r| r.native(&b.name)))
+			.unwrap_or_else(|| panic!("invalid builtin name: {}", b.name));
+
 		let pricer: Box<Pricer> = match b.pricing {
 			ethjson::spec::Pricing::Linear(linear) => {
 				Box::new(Linear {
@@ -204,15 +220,25 @@ impl From<ethjson::spec::Builtin> for Builtin {
 
 		Builtin {
 			pricer: pricer,
-			native: ethereum_builtin(&b.name),
+			native: native,
 			activate_at: b.activate_at.map(Into::into).unwrap_or(0),
 		}
 	}
 }
 
+impl From<ethjson::spec::Builtin> for Builtin {
+	fn from(b: ethjson::spec::Builtin) -> Self {
+		Builtin::from_spec(b, None)
+	}
+}
+
 /// Ethereum built-in factory.
 pub fn ethereum_builtin(name: &str) -> Box<Impl> {
-	match name {
+	ethereum_native(name).unwrap_or_else(|| panic!("invalid builtin name: {}", name))
+}
+
+fn ethereum_native(name: &str) -> Option<Box<Impl>> {
+	let native = match name {
 		"identity" => Box::new(Identity) as Box<Impl>,
 		"ecrecover" => Box::new(EcRecover) as Box<Impl>,
 		"sha256" => Box::new(Sha256) as Box<Impl>,
@@ -221,8 +247,10 @@ pub fn ethereum_builtin(name: &str) -> Box<Impl> {
 		"alt_bn128_add" => Box::new(Bn128AddImpl) as Box<Impl>,
 		"alt_bn128_mul" => Box::new(Bn128MulImpl) as Box<Impl>,
 		"alt_bn128_pairing" => Box::new(Bn128PairingImpl) as Box<Impl>,
-		_ => panic!("invalid builtin name: {}", name),
-	}
+		_ => return None,
+	};
+
+	Some(native)
 }
 
 // Ethereum builtins:
@@ -538,7 +566,7 @@ impl Bn128PairingImpl {
 
 #[cfg(test)]
 mod tests {
-	use super::{Builtin, Linear, ethereum_builtin, Pricer, ModexpPricer, modexp as me};
+	use super::{Builtin, BuiltinRegistry, Error, Impl, Linear, ethereum_builtin, Pricer, ModexpPricer, modexp as me};
 	use ethjson;
 	use ethereum_types::U256;
 	use bytes::BytesRef;
@@ -1039,4 +1067,64 @@ mod tests {
 		b.execute(&i[..], &mut BytesRef::Fixed(&mut o[..])).expect("Builtin should not fail");
 		assert_eq!(i, o);
 	}
+
+	struct Reverse;
+
+	impl Impl for Reverse {
+		fn execute(&self, input: &[u8], output: &mut BytesRef) -> Result<(), Error> {
+			let reversed: Vec<u8> = input.iter().rev().cloned().collect();
+			output.write(0, &reversed);
+			Ok(())
+		}
+	}
+
+	struct TestRegistry;
+
+	impl BuiltinRegistry for TestRegistry {
+		fn native(&self, name: &str) -> Option<Box<Impl>> {
+			match name {
+				"reverse" => Some(Box::new(Reverse)),
+				_ => None,
+			}
+		}
+	}
+
+	fn linear_spec(name: &str) -> ethjson::spec::Builtin {
+		ethjson::spec::Builtin {
+			name: name.to_owned(),
+			pricing: ethjson::spec::Pricing::Linear(ethjson::spec::Linear {
+				base: 10,
+				word: 20,
+			}),
+			activate_at: None,
+		}
+	}
+
+	#[test]
+	fn from_registry() {
+		let b = Builtin::from_spec(linear_spec("reverse"), Some(&TestRegistry));
+
+		assert_eq!(b.cost(&[0; 1]), U256::from(30));
+
+		let i = [0u8, 1, 2, 3];
+		let mut o = [255u8; 4];
+		b.execute(&i[..], &mut BytesRef::Fixed(&mut o[..])).expect("Builtin should not fail");
+		assert_eq!(o, [3u8, 2, 1, 0]);
+	}
+
+	#[test]
+	fn standard_builtins_take_precedence_over_registry() {
+		let b = Builtin::from_spec(linear_spec("identity"), Some(&TestRegistry));
+
+		let i = [0u8, 1, 2, 3];
+		let mut o = [255u8; 4];
+		b.execute(&i[..], &mut BytesRef::Fixed(&mut o[..])).expect("Builtin should not fail");
+		assert_eq!(i, o);
+	}
+
+	#[test]
+	#[should_panic]
+	fn from_registry_unknown() {
+		let _ = Builtin::from_spec(linear_spec("foo"), Some(&TestRegistry));
+	}
 }
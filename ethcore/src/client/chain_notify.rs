@@ -18,6 +18,7 @@ use bytes::Bytes;
 use ethereum_types::H256;
 use transaction::UnverifiedTransaction;
 use blockchain::ImportRoute;
+use header::BlockNumber;
 use std::time::Duration;
 use std::collections::HashMap;
 
@@ -114,6 +115,19 @@ impl ChainRoute {
 	}
 }
 
+/// Change of the canonical chain which retracted some previously canonical blocks.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ChainReorg {
+	/// Hash of the last block shared by the old and the new canonical chain.
+	pub common_ancestor: H256,
+	/// Number of the common ancestor.
+	pub common_ancestor_number: BlockNumber,
+	/// Blocks removed from the canonical chain, ordered by number starting after the common ancestor.
+	pub retracted: Vec<H256>,
+	/// Blocks added to the canonical chain, ordered by number starting after the common ancestor.
+	pub enacted: Vec<H256>,
+}
+
 /// Represents what has to be handled by actor listening to chain events
 pub trait ChainNotify : Send + Sync {
 	/// fires when chain has new blocks.
@@ -130,6 +144,12 @@ pub trait ChainNotify : Send + Sync {
 		// does nothing by default
 	}
 
+	/// fires when the canonical chain is reorganized.
+	/// Called after `new_blocks` for the same import.
+	fn reorg(&self, _reorg: ChainReorg) {
+		// does nothing by default
+	}
+
 	/// fires when chain achieves active mode
 	fn start(&self) {
 		// does nothing by default
@@ -14,19 +14,24 @@
 // You should have received a copy of the GNU General Public License
 // along with Parity.  If not, see <http://www.gnu.org/licenses/>.
 
-use std::collections::{HashSet, BTreeMap, VecDeque};
+use std::collections::{HashSet, HashMap, BTreeMap, VecDeque};
 use std::cmp;
+use std::net::IpAddr;
 use std::str::FromStr;
 use std::sync::atomic::{AtomicUsize, AtomicBool, Ordering as AtomicOrdering};
 use std::sync::{Arc, Weak};
+use std::thread;
 use std::time::{Instant, Duration};
 
 // util
-use hash::keccak;
+use hash::{keccak, KECCAK_EMPTY, KECCAK_NULL_RLP};
 use bytes::Bytes;
 use itertools::Itertools;
 use journaldb;
 use trie::{TrieSpec, TrieFactory, Trie};
+use ethtrie;
+use hashdb::HashDB;
+use keccak_hasher::KeccakHasher;
 use kvdb::{DBValue, KeyValueDB, DBTransaction};
 
 // other
@@ -34,6 +39,7 @@ use ethereum_types::{H256, Address, U256};
 use block::{IsBlock, LockedBlock, Drain, ClosedBlock, OpenBlock, enact_verified, SealedBlock};
 use blockchain::{BlockChain, BlockChainDB, BlockProvider, TreeRoute, ImportRoute, TransactionAddress, ExtrasInsert};
 use client::ancient_import::AncientVerifier;
+use client::cold_state::ColdState;
 use client::{
 	Nonce, Balance, ChainInfo, BlockInfo, CallContract, TransactionInfo,
 	RegistryInfo, ReopenBlock, PrepareOpenBlock, ScheduleInfo, ImportSealedBlock,
@@ -43,9 +49,9 @@ use client::{
 };
 use client::{
 	BlockId, TransactionId, UncleId, TraceId, ClientConfig, BlockChainClient,
-	TraceFilter, CallAnalytics, Mode,
-	ChainNotify, ChainRoute, PruningInfo, ProvingBlockChainClient, EngineInfo, ChainMessageType,
-	IoClient, BadBlocks,
+	TraceFilter, CallAnalytics, Mode, PrewarmContract,
+	ChainNotify, ChainReorg, ChainRoute, PruningInfo, ProvingBlockChainClient, EngineInfo, ChainMessageType,
+	IoClient, BadBlocks, TransactionLifecycle, TransactionLifecycleLog,
 };
 use client::bad_blocks;
 use encoded;
@@ -57,6 +63,9 @@ use error::{
 use vm::{EnvInfo, LastHashes};
 use evm::Schedule;
 use executive::{Executive, Executed, TransactOptions, contract_address};
+use account_db;
+use pod_account::{self, PodAccount};
+use pod_state::PodState;
 use factory::{Factories, VmFactory};
 use header::{BlockNumber, Header, ExtendedHeader};
 use io::IoChannel;
@@ -69,12 +78,17 @@ use receipt::{Receipt, LocalizedReceipt};
 use snapshot::{self, io as snapshot_io};
 use spec::Spec;
 use state_db::StateDB;
+use stats::{LatencyHistogram, PrometheusMetrics, PrometheusRegistry};
 use state::{self, State};
 use trace;
 use trace::{TraceDB, ImportRequest as TraceImportRequest, LocalizedTrace, Database as TraceDatabase};
 use transaction::{self, LocalizedTransaction, UnverifiedTransaction, SignedTransaction, Transaction, Action};
 use types::filter::Filter;
 use types::ancestry_action::AncestryAction;
+use types::basic_account::BasicAccount;
+use types::state_diff::StateDiff;
+use witness::BlockWitness;
+use invariant_checker::InvariantChecker;
 use verification;
 use verification::{PreverifiedBlock, Verifier, BlockQueue};
 use verification::queue::kind::blocks::Unverified;
@@ -83,6 +97,7 @@ use verification::queue::kind::BlockLike;
 // re-export
 pub use types::blockchain_info::BlockChainInfo;
 pub use types::block_status::BlockStatus;
+pub use types::verification_queue_info::ImportQueueStatus;
 pub use blockchain::CacheSize as BlockChainCacheSize;
 pub use verification::QueueInfo as BlockQueueInfo;
 
@@ -167,6 +182,9 @@ struct Importer {
 
 	/// A lru cache of recently detected bad blocks
 	pub bad_blocks: bad_blocks::BadBlocks,
+
+	/// Invariant contract called after every block, if configured
+	pub invariant_checker: Option<InvariantChecker>,
 }
 
 /// Blockchain database client backed by a persistent database. Owns and manages a blockchain and a block queue.
@@ -180,6 +198,18 @@ pub struct Client {
 	/// knows it can't proceed further.
 	enabled: AtomicBool,
 
+	/// Flag used to temporarily suspend block import and sealing for maintenance.
+	import_paused: AtomicBool,
+
+	/// Hash of the block currently being executed by the importer.
+	executing_block: RwLock<Option<H256>>,
+
+	/// Record of what happened to transactions seen by this node, if enabled.
+	tx_lifecycle: Option<Arc<TransactionLifecycleLog>>,
+
+	/// Reconstruction of pruned state from the local snapshot, if enabled.
+	cold_state: Option<Arc<ColdState>>,
+
 	/// Operating mode for the client
 	mode: Mutex<Mode>,
 
@@ -201,6 +231,9 @@ pub struct Client {
 	/// Report on the status of client
 	report: RwLock<ClientReport>,
 
+	/// Time taken to execute and commit single blocks.
+	import_times: RwLock<LatencyHistogram>,
+
 	sleep_state: Mutex<SleepState>,
 
 	/// Flag changed by `sleep` and `wake_up` methods. Not to be confused with `enabled`.
@@ -214,9 +247,9 @@ pub struct Client {
 	queue_transactions: IoChannelQueue,
 	/// Ancient blocks import queue
 	queue_ancient_blocks: IoChannelQueue,
-	/// Queued ancient blocks, make sure they are imported in order.
+	/// Queued ancient blocks with their parents, make sure they are imported in order.
 	queued_ancient_blocks: Arc<RwLock<(
-		HashSet<H256>,
+		HashMap<H256, H256>,
 		VecDeque<(Unverified, Bytes)>
 	)>>,
 	ancient_blocks_import_lock: Arc<Mutex<()>>,
@@ -255,8 +288,9 @@ impl Importer {
 			block_queue,
 			miner,
 			ancient_verifier: AncientVerifier::new(engine.clone()),
+			invariant_checker: InvariantChecker::from_params(engine.params()),
+			bad_blocks: bad_blocks::BadBlocks::new(engine.params().eip1559_transition),
 			engine,
-			bad_blocks: Default::default(),
 		})
 	}
 
@@ -268,6 +302,11 @@ impl Importer {
 			return 0;
 		}
 
+		// Leave verified blocks in the queue until import is resumed.
+		if client.import_paused.load(AtomicOrdering::SeqCst) {
+			return 0;
+		}
+
 		let max_blocks_to_import = 4;
 		let (imported_blocks, import_results, invalid_blocks, imported, proposed_blocks, duration, is_empty) = {
 			let mut imported_blocks = Vec::with_capacity(max_blocks_to_import);
@@ -294,6 +333,8 @@ impl Importer {
 					continue;
 				}
 
+				*client.executing_block.write() = Some(hash);
+				let block_start = Instant::now();
 				match self.check_and_lock_block(block, client) {
 					Ok(closed_block) => {
 						if self.engine.is_proposal(&header) {
@@ -308,6 +349,7 @@ impl Importer {
 							import_results.push(route);
 
 							client.report.write().accrue_block(&header, transactions_len);
+							client.import_times.write().observe(block_start.elapsed());
 						}
 					},
 					Err(err) => {
@@ -316,6 +358,7 @@ impl Importer {
 					},
 				}
 			}
+			*client.executing_block.write() = None;
 
 			let imported = imported_blocks.len();
 			let invalid_blocks = invalid_blocks.into_iter().collect::<Vec<H256>>();
@@ -346,6 +389,13 @@ impl Importer {
 					);
 				});
 			}
+
+			// reorgs are reported right away, even if more blocks are waiting in the queue.
+			if !imported_blocks.is_empty() {
+				if let Some(reorg) = client.chain_reorg(&ChainRoute::from(import_results.as_ref())) {
+					client.notify(|notify| notify.reorg(reorg.clone()));
+				}
+			}
 		}
 
 		let db = client.db.read();
@@ -406,6 +456,7 @@ impl Importer {
 			block,
 			engine,
 			client.tracedb.read().tracing_enabled(),
+			client.config.parallel_transactions,
 			db,
 			&parent,
 			last_hashes,
@@ -437,6 +488,16 @@ impl Importer {
 			bail!(e);
 		}
 
+		if let Some(ref checker) = self.invariant_checker {
+			if let Err(e) = checker.check(engine.machine(), locked_block.block()) {
+				if checker.halts() {
+					error!(target: "client", "Block import failed for #{} ({})\nInvariant violation: {}", header.number(), header.hash(), e);
+					bail!(e);
+				}
+				error!(target: "client", "Block #{} ({}) violates invariants: {}", header.number(), header.hash(), e);
+			}
+		}
+
 		Ok(locked_block)
 	}
 
@@ -448,6 +509,17 @@ impl Importer {
 		let receipts = ::rlp::decode_list(receipts_bytes);
 		let _import_lock = self.import_lock.lock();
 
+		// blocks downloaded backwards are verified by their hash being the parent hash of the first block
+		if chain.is_first_block_parent(&unverified.hash()) {
+			trace_time!("import_first_block_parent");
+			let mut batch = DBTransaction::new();
+			chain.insert_first_block_parent(&mut batch, encoded::Block::new(unverified.bytes), receipts);
+			db.write_buffered(batch);
+			chain.commit();
+			db.flush().expect("DB flush failed.");
+			return Ok(());
+		}
+
 		{
 			trace_time!("import_old_block");
 			// verify the block, passing the chain for updating the epoch verifier.
@@ -498,7 +570,7 @@ impl Importer {
 			let hash = best_hash;
 			let header = chain.block_header_data(&hash)
 				.expect("Best block is in the database; qed")
-				.decode()
+				.decode(self.engine.params().eip1559_transition)
 				.expect("Stored block header is valid RLP; qed");
 			let details = chain.block_details(&hash)
 				.expect("Best block is in the database; qed");
@@ -541,6 +613,11 @@ impl Importer {
 			chain.mark_finalized(&mut batch, ancestry).expect("Engine's ancestry action must be known blocks; qed");
 		}
 
+		let transaction_hashes = match client.tx_lifecycle {
+			Some(_) => block_data.transaction_hashes(),
+			None => Vec::new(),
+		};
+
 		let route = chain.insert_block(&mut batch, block_data, receipts.clone(), ExtrasInsert {
 			fork_choice: fork_choice,
 			is_finalized,
@@ -560,6 +637,10 @@ impl Importer {
 		client.db.read().key_value().write_buffered(batch);
 		chain.commit();
 
+		if let Some(ref log) = client.tx_lifecycle {
+			log.included(&transaction_hashes, number, hash);
+		}
+
 		self.check_epoch_end(&header, &chain, client);
 
 		client.update_last_hashes(&parent, hash);
@@ -606,6 +687,7 @@ impl Importer {
 							difficulty: header.difficulty().clone(),
 							last_hashes: client.build_last_hashes(header.parent_hash()),
 							gas_used: U256::default(),
+							base_fee: header.base_fee(),
 							gas_limit: u64::max_value().into(),
 						};
 
@@ -696,12 +778,14 @@ impl Client {
 	/// Create a new client with given parameters.
 	/// The database is assumed to have been initialized with the correct columns.
 	pub fn new(
-		config: ClientConfig,
+		mut config: ClientConfig,
 		spec: &Spec,
 		db: Arc<BlockChainDB>,
 		miner: Arc<Miner>,
 		message_channel: IoChannel<ClientIoMessage>,
 	) -> Result<Arc<Client>, ::error::Error> {
+		config.blockchain.eip1559_transition = spec.engine.params().eip1559_transition;
+
 		let trie_spec = match config.fat_db {
 			true => TrieSpec::Fat,
 			false => TrieSpec::Secure,
@@ -747,6 +831,13 @@ impl Client {
 
 		let awake = match config.mode { Mode::Dark(..) | Mode::Off => false, _ => true };
 
+		let tx_lifecycle = config.transaction_lifecycle_log.map(|capacity| {
+			let log = Arc::new(TransactionLifecycleLog::new(db.key_value().clone(), capacity));
+			let listener = log.clone();
+			miner.add_transactions_lifecycle_listener(Box::new(move |hash: &H256, event| listener.pool_event(hash, event)));
+			log
+		});
+
 		let importer = Importer::new(&config, engine.clone(), message_channel.clone(), miner)?;
 
 		let registrar_address = engine.additional_params().get("registrar").and_then(|s| Address::from_str(s).ok());
@@ -756,6 +847,10 @@ impl Client {
 
 		let client = Arc::new(Client {
 			enabled: AtomicBool::new(true),
+			import_paused: AtomicBool::new(false),
+			executing_block: RwLock::new(None),
+			tx_lifecycle,
+			cold_state: config.cold_state.clone().map(|config| Arc::new(ColdState::new(config))),
 			sleep_state: Mutex::new(SleepState::new(awake)),
 			liveness: AtomicBool::new(awake),
 			mode: Mutex::new(config.mode.clone()),
@@ -766,6 +861,7 @@ impl Client {
 			db: RwLock::new(db.clone()),
 			state_db: RwLock::new(state_db),
 			report: RwLock::new(Default::default()),
+			import_times: RwLock::new(Default::default()),
 			io_channel: RwLock::new(message_channel),
 			notify: RwLock::new(Vec::new()),
 			queue_transactions: IoChannelQueue::new(config.transaction_verification_queue_size),
@@ -822,11 +918,49 @@ impl Client {
 			}
 		}
 
+		client.prewarm_contracts(spec);
+
 		// ensure buffered changes are flushed.
 		client.db.read().key_value().flush()?;
 		Ok(client)
 	}
 
+	/// Analyze the code of configured contracts, so that they never hit a cold EVM cache.
+	/// Contracts given by code hash are looked up in the genesis state
+	/// and otherwise kept in the cache once they are first executed.
+	fn prewarm_contracts(&self, spec: &Spec) {
+		if self.config.prewarm_contracts.is_empty() {
+			return;
+		}
+
+		let state = self.latest_state();
+		for contract in &self.config.prewarm_contracts {
+			match *contract {
+				PrewarmContract::Address(ref address) => match (state.code_hash(address), state.code(address)) {
+					(Ok(Some(code_hash)), Ok(Some(code))) => {
+						trace!(target: "client", "Prewarming contract at {:?}", address);
+						self.factories.vm.prewarm(&code_hash, &code);
+					},
+					(Err(e), _) | (_, Err(e)) => warn!(target: "client", "Unable to prewarm contract at {:?}: {}", address, e),
+					_ => warn!(target: "client", "Unable to prewarm contract at {:?}: no code found.", address),
+				},
+				PrewarmContract::CodeHash(ref code_hash) => {
+					let code = spec.genesis_state().get().values()
+						.filter_map(|account| account.code.as_ref())
+						.find(|code| keccak(code) == *code_hash);
+
+					match code {
+						Some(code) => {
+							trace!(target: "client", "Prewarming genesis contract with code hash {:?}", code_hash);
+							self.factories.vm.prewarm(code_hash, code);
+						},
+						None => self.factories.vm.pin(*code_hash),
+					}
+				},
+			}
+		}
+	}
+
 	/// Wakes up client if it's a sleep.
 	pub fn keep_alive(&self) {
 		let should_wake = match *self.mode.lock() {
@@ -865,6 +999,32 @@ impl Client {
 		}
 	}
 
+	/// Describes the change of the canonical chain made by `route`, if it retracted any blocks.
+	fn chain_reorg(&self, route: &ChainRoute) -> Option<ChainReorg> {
+		if route.retracted().is_empty() {
+			return None;
+		}
+
+		let chain = self.chain.read();
+		let by_number = |hashes: &[H256]| {
+			let mut numbered: Vec<_> = hashes.iter()
+				.filter_map(|hash| chain.block_number(hash).map(|number| (number, *hash)))
+				.collect();
+			numbered.sort();
+			numbered.into_iter().map(|(_, hash)| hash).collect::<Vec<_>>()
+		};
+		let retracted = by_number(route.retracted());
+		let enacted = by_number(route.enacted());
+
+		let lowest = chain.block_details(retracted.first()?)?;
+		Some(ChainReorg {
+			common_ancestor: lowest.parent,
+			common_ancestor_number: lowest.number - 1,
+			retracted,
+			enacted,
+		})
+	}
+
 	/// Register an action to be done if a mode/spec_name change happens.
 	pub fn on_user_defaults_change<F>(&self, f: F) where F: 'static + FnMut(Option<Mode>) + Send {
 		*self.on_user_defaults_change.lock() = Some(Box::new(f));
@@ -873,7 +1033,7 @@ impl Client {
 	/// Flush the block import queue.
 	pub fn flush_queue(&self) {
 		self.importer.block_queue.flush();
-		while !self.importer.block_queue.queue_info().is_empty() {
+		while !self.importer.block_queue.queue_info().is_empty() && !self.import_paused.load(AtomicOrdering::SeqCst) {
 			self.import_verified_blocks();
 		}
 	}
@@ -886,19 +1046,36 @@ impl Client {
 	/// The env info as of a given block.
 	/// returns `None` if the block unknown.
 	pub fn env_info(&self, id: BlockId) -> Option<EnvInfo> {
-		self.block_header(id).map(|header| {
+		self.block_header_decoded(id).map(|header| {
 			EnvInfo {
 				number: header.number(),
-				author: header.author(),
+				author: *header.author(),
 				timestamp: header.timestamp(),
-				difficulty: header.difficulty(),
-				last_hashes: self.build_last_hashes(&header.parent_hash()),
+				difficulty: *header.difficulty(),
+				last_hashes: self.build_last_hashes(header.parent_hash()),
 				gas_used: U256::default(),
-				gas_limit: header.gas_limit(),
+				base_fee: header.base_fee(),
+				gas_limit: *header.gas_limit(),
 			}
 		})
 	}
 
+	/// The env info for a virtual call of `t` on top of `header`.
+	/// Calls priced below the base fee, like the zero gas price `eth_call` defaults to,
+	/// are run as if the block had no base fee.
+	fn virtual_call_env_info(&self, header: &Header, t: &SignedTransaction, gas_limit: U256) -> EnvInfo {
+		EnvInfo {
+			number: header.number(),
+			author: header.author().clone(),
+			timestamp: header.timestamp(),
+			difficulty: header.difficulty().clone(),
+			last_hashes: self.build_last_hashes(header.parent_hash()),
+			gas_used: U256::default(),
+			base_fee: header.base_fee().filter(|base_fee| t.gas_price >= *base_fee),
+			gas_limit,
+		}
+	}
+
 	fn build_last_hashes(&self, parent_hash: &H256) -> Arc<LastHashes> {
 		{
 			let hashes = self.last_hashes.read();
@@ -1044,6 +1221,108 @@ impl Client {
 		})
 	}
 
+	/// Restore the state of the latest local snapshot for reconstructing pruned state, if enabled.
+	/// Blocks until the state is restored; called right after a snapshot is taken.
+	pub fn restore_cold_state(&self) {
+		if let Some(ref cold_state) = self.cold_state {
+			if let Err(e) = cold_state.restore_latest() {
+				warn!(target: "client", "Failed to restore state of the local snapshot: {}", e);
+			}
+		}
+	}
+
+	/// Reconstruct the final state of a pruned block by replaying blocks on top of the local snapshot.
+	fn reconstruct_state(&self, block_number: BlockNumber) -> Result<State<StateDB>, CallError> {
+		let cold_state = self.cold_state.as_ref().ok_or(CallError::StatePruned)?;
+		if cold_state.needs_restore() {
+			let cold_state = cold_state.clone();
+			let res = thread::Builder::new().name("Cold State Restoration".into()).spawn(move || {
+				if let Err(e) = cold_state.restore_latest() {
+					warn!(target: "client", "Failed to restore state of the local snapshot: {}", e);
+				}
+			});
+			if let Err(e) = res {
+				debug!(target: "client", "Failed to spawn snapshot state restoration thread: {:?}", e);
+			}
+		}
+		let snapshot = cold_state.snapshot_state().ok_or(CallError::StateNotReconstructible(None))?;
+
+		// don't hold the lock while replaying, the importer might need it.
+		let chain = self.chain.read().clone();
+		let last_block = snapshot.block_number + cold_state.max_blocks();
+		let range = Some((snapshot.block_number, cmp::min(last_block, chain.best_block_number())));
+		if block_number < snapshot.block_number || block_number > last_block {
+			return Err(CallError::StateNotReconstructible(range));
+		}
+		if chain.block_hash(snapshot.block_number) != Some(snapshot.block_hash) {
+			// the snapshot was taken at a block which is no longer canonical.
+			return Err(CallError::StateNotReconstructible(None));
+		}
+
+		let eip1559_transition = self.engine.params().eip1559_transition;
+		let header = chain.block_hash(block_number)
+			.and_then(|hash| chain.block_header_data(&hash))
+			.and_then(|h| h.decode(eip1559_transition).ok())
+			.ok_or(CallError::StatePruned)?;
+
+		{
+			// states of replayed blocks are kept, only replay the blocks after the last one.
+			let mut replayed = snapshot.replayed();
+			if chain.block_hash(replayed.0) != Some(replayed.1) {
+				*replayed = (snapshot.block_number, snapshot.block_hash);
+			}
+
+			for number in (replayed.0 + 1)..(block_number + 1) {
+				let block = chain.block_hash(number).and_then(|hash| chain.block(&hash)).ok_or(CallError::StatePruned)?;
+				let header = block.decode_header(eip1559_transition);
+				let parent = chain.block_header_data(header.parent_hash())
+					.and_then(|h| h.decode(eip1559_transition).ok())
+					.ok_or(CallError::StatePruned)?;
+				let transactions = block.transactions().into_iter()
+					.map(SignedTransaction::new)
+					.collect::<Result<Vec<_>, _>>()
+					.map_err(|_| CallError::StateCorrupt)?;
+				let is_epoch_begin = chain.epoch_transition(parent.number(), *header.parent_hash()).is_some();
+
+				let locked_block = enact_verified(
+					PreverifiedBlock {
+						header: header.clone(),
+						transactions,
+						uncles: block.uncles(eip1559_transition),
+						bytes: block.into_inner(),
+					},
+					&*self.engine,
+					false,
+					false,
+					snapshot.state_db(),
+					&parent,
+					self.build_last_hashes(header.parent_hash()),
+					self.factories.clone(),
+					is_epoch_begin,
+					&mut chain.ancestry_with_metadata_iter(*header.parent_hash()),
+				).map_err(|e| {
+					warn!(target: "client", "Failed to replay block #{} on top of the snapshot: {}", number, e);
+					CallError::StateCorrupt
+				})?;
+
+				if locked_block.block().header().state_root() != header.state_root() {
+					warn!(target: "client", "Replayed block #{} has wrong state root", number);
+					return Err(CallError::StateCorrupt);
+				}
+
+				let mut db = locked_block.drain().state.drop().1;
+				snapshot.commit(&mut db, number, &header.hash()).map_err(|e| {
+					warn!(target: "client", "Failed to write replayed state of block #{}: {}", number, e);
+					CallError::StateCorrupt
+				})?;
+				*replayed = (number, header.hash());
+			}
+		}
+
+		State::from_existing(snapshot.state_db(), *header.state_root(), self.engine.account_start_nonce(block_number), self.factories.clone())
+			.map_err(|_| CallError::StateCorrupt)
+	}
+
 	/// Attempt to get a copy of a specific block's beginning state.
 	///
 	/// This will not fail if given BlockId::Latest.
@@ -1056,6 +1335,43 @@ impl Client {
 		}
 	}
 
+	/// Iterate over all accounts of a specific block's final state, optionally with their storage.
+	///
+	/// Returns `None` if the state is unavailable or the fat database is not enabled.
+	pub fn state_producer(&self, id: BlockId, storage: bool) -> Option<StateProducer> {
+		self.state_at(id).and_then(|state| StateProducer::new(state, storage))
+	}
+
+	/// Get the environment, the transaction itself and the state it was executed on top of.
+	///
+	/// The returned state contains only accounts and storage items touched by the transaction,
+	/// which is enough to execute it in isolation.
+	pub fn transaction_pre_state(&self, id: TransactionId) -> Result<(EnvInfo, SignedTransaction, PodState), CallError> {
+		let address = self.transaction_address(id).ok_or(CallError::TransactionNotFound)?;
+		let block = BlockId::Hash(address.block_hash);
+		let mut env_info = self.env_info(block).ok_or(CallError::StatePruned)?;
+		let body = self.block_body(block).ok_or(CallError::StatePruned)?;
+		let mut state = self.state_at_beginning(block).ok_or(CallError::StatePruned)?;
+		let machine = self.engine.machine();
+
+		const PROOF: &'static str = "Transactions fetched from blockchain; blockchain transactions are valid; qed";
+		const INDEX_PROOF: &'static str = "The transaction address contains a valid index within block; qed";
+
+		let mut transactions = body.transactions().into_iter()
+			.map(|t| SignedTransaction::new(t).expect(PROOF));
+		for t in transactions.by_ref().take(address.index) {
+			let executed = Self::do_virtual_call(machine, &env_info, &mut state, &t, Default::default())?;
+			env_info.gas_used = env_info.gas_used + executed.gas_used;
+		}
+
+		let transaction = transactions.next().expect(INDEX_PROOF);
+		let pre = state.clone();
+		Self::do_virtual_call(machine, &env_info, &mut state, &transaction, Default::default())?;
+		let pre_state = pre.to_pod_touched(&state).map_err(ExecutionError::from)?;
+
+		Ok((env_info, transaction, pre_state))
+	}
+
 	/// Get a copy of the best block's state.
 	pub fn state(&self) -> Box<StateInfo> {
 		Box::new(self.latest_state()) as Box<_>
@@ -1227,39 +1543,39 @@ impl Client {
 		t: &SignedTransaction,
 		analytics: CallAnalytics,
 	) -> Result<Executed, CallError> {
-		fn call<V, T>(
-			state: &mut State<StateDB>,
-			env_info: &EnvInfo,
-			machine: &::machine::EthereumMachine,
-			state_diff: bool,
-			transaction: &SignedTransaction,
-			options: TransactOptions<T, V>,
-		) -> Result<Executed<T::Output, V::Output>, CallError> where
-			T: trace::Tracer,
-			V: trace::VMTracer,
-		{
-			let options = options
-				.dont_check_nonce()
-				.save_output_from_contract();
-			let original_state = if state_diff { Some(state.clone()) } else { None };
-			let schedule = machine.schedule(env_info.number);
-
-			let mut ret = Executive::new(state, env_info, &machine, &schedule).transact_virtual(transaction, options)?;
+		let state_diff = analytics.state_diffing;
 
-			if let Some(original) = original_state {
-				ret.state_diff = Some(state.diff_from(original).map_err(ExecutionError::from)?);
-			}
-			Ok(ret)
+		match (analytics.transaction_tracing, analytics.vm_tracing) {
+			(true, true) => Self::do_virtual_call_with_options(machine, env_info, state, state_diff, t, TransactOptions::with_tracing_and_vm_tracing()),
+			(true, false) => Self::do_virtual_call_with_options(machine, env_info, state, state_diff, t, TransactOptions::with_tracing()),
+			(false, true) => Self::do_virtual_call_with_options(machine, env_info, state, state_diff, t, TransactOptions::with_vm_tracing()),
+			(false, false) => Self::do_virtual_call_with_options(machine, env_info, state, state_diff, t, TransactOptions::with_no_tracing()),
 		}
+	}
 
-		let state_diff = analytics.state_diffing;
+	fn do_virtual_call_with_options<T, V>(
+		machine: &::machine::EthereumMachine,
+		env_info: &EnvInfo,
+		state: &mut State<StateDB>,
+		state_diff: bool,
+		transaction: &SignedTransaction,
+		options: TransactOptions<T, V>,
+	) -> Result<Executed<T::Output, V::Output>, CallError> where
+		T: trace::Tracer,
+		V: trace::VMTracer,
+	{
+		let options = options
+			.dont_check_nonce()
+			.save_output_from_contract();
+		let original_state = if state_diff { Some(state.clone()) } else { None };
+		let schedule = machine.schedule(env_info.number);
 
-		match (analytics.transaction_tracing, analytics.vm_tracing) {
-			(true, true) => call(state, env_info, machine, state_diff, t, TransactOptions::with_tracing_and_vm_tracing()),
-			(true, false) => call(state, env_info, machine, state_diff, t, TransactOptions::with_tracing()),
-			(false, true) => call(state, env_info, machine, state_diff, t, TransactOptions::with_vm_tracing()),
-			(false, false) => call(state, env_info, machine, state_diff, t, TransactOptions::with_no_tracing()),
+		let mut ret = Executive::new(state, env_info, &machine, &schedule).transact_virtual(transaction, options)?;
+
+		if let Some(original) = original_state {
+			ret.state_diff = Some(state.diff_from(original).map_err(ExecutionError::from)?);
 		}
+		Ok(ret)
 	}
 
 	fn block_number_ref(&self, id: &BlockId) -> Option<BlockNumber> {
@@ -1283,7 +1599,7 @@ impl Client {
 				=> Some(self.chain.read().best_block_header()),
 			BlockId::Number(number) if number == self.chain.read().best_block_number()
 				=> Some(self.chain.read().best_block_header()),
-			_   => self.block_header(id).and_then(|h| h.decode().ok())
+			_   => self.block_header(id).and_then(|h| h.decode(self.engine.params().eip1559_transition).ok())
 		}
 	}
 }
@@ -1305,6 +1621,9 @@ impl snapshot::DatabaseRestore for Client {
 		*state_db = StateDB::new(journaldb::new(db.key_value().clone(), self.pruning, ::db::COL_STATE), cache_size);
 		*chain = Arc::new(BlockChain::new(self.config.blockchain.clone(), &[], db.clone()));
 		*tracedb = TraceDB::new(self.config.tracing.clone(), db.clone(), chain.clone());
+		if let Some(ref log) = self.tx_lifecycle {
+			log.reload();
+		}
 		Ok(())
 	}
 }
@@ -1416,6 +1735,12 @@ impl ImportBlock for Client {
 			Err(e) => Err(e),
 		}
 	}
+
+	fn import_block_sync(&self, unverified: Unverified) -> EthcoreResult<H256> {
+		let hash = self.import_block(unverified)?;
+		self.flush_queue();
+		Ok(hash)
+	}
 }
 
 impl StateClient for Client {
@@ -1428,63 +1753,65 @@ impl StateClient for Client {
 	fn state_at(&self, id: BlockId) -> Option<Self::State> {
 		Client::state_at(self, id)
 	}
+
+	fn reconstructed_state_at(&self, id: BlockId) -> Result<Self::State, CallError> {
+		if let Some(state) = Client::state_at(self, id) {
+			return Ok(state);
+		}
+
+		match self.block_number(id) {
+			Some(block_number) => self.reconstruct_state(block_number),
+			None => Err(CallError::StatePruned),
+		}
+	}
 }
 
 impl Call for Client {
 	type State = State<::state_db::StateDB>;
 
 	fn call(&self, transaction: &SignedTransaction, analytics: CallAnalytics, state: &mut Self::State, header: &Header) -> Result<Executed, CallError> {
-		let env_info = EnvInfo {
-			number: header.number(),
-			author: header.author().clone(),
-			timestamp: header.timestamp(),
-			difficulty: header.difficulty().clone(),
-			last_hashes: self.build_last_hashes(header.parent_hash()),
-			gas_used: U256::default(),
-			gas_limit: U256::max_value(),
-		};
+		let env_info = self.virtual_call_env_info(header, transaction, U256::max_value());
 		let machine = self.engine.machine();
 
 		Self::do_virtual_call(&machine, &env_info, state, transaction, analytics)
 	}
 
 	fn call_many(&self, transactions: &[(SignedTransaction, CallAnalytics)], state: &mut Self::State, header: &Header) -> Result<Vec<Executed>, CallError> {
-		let mut env_info = EnvInfo {
-			number: header.number(),
-			author: header.author().clone(),
-			timestamp: header.timestamp(),
-			difficulty: header.difficulty().clone(),
-			last_hashes: self.build_last_hashes(header.parent_hash()),
-			gas_used: U256::default(),
-			gas_limit: U256::max_value(),
-		};
-
+		let mut gas_used = U256::default();
 		let mut results = Vec::with_capacity(transactions.len());
 		let machine = self.engine.machine();
 
 		for &(ref t, analytics) in transactions {
+			let mut env_info = self.virtual_call_env_info(header, t, U256::max_value());
+			env_info.gas_used = gas_used;
 			let ret = Self::do_virtual_call(machine, &env_info, state, t, analytics)?;
-			env_info.gas_used = ret.cumulative_gas_used;
+			gas_used = ret.cumulative_gas_used;
 			results.push(ret);
 		}
 
 		Ok(results)
 	}
 
+	fn call_struct_logs(&self, transaction: &SignedTransaction, options: trace::StructLogOptions, state: &mut Self::State, header: &Header) -> Result<Executed<trace::FlatTrace, Vec<trace::StructLog>>, CallError> {
+		let env_info = self.virtual_call_env_info(header, transaction, U256::max_value());
+		let machine = self.engine.machine();
+
+		Self::do_virtual_call_with_options(machine, &env_info, state, false, transaction, TransactOptions::with_struct_logging(options))
+	}
+
+	fn call_stepped(&self, transaction: &SignedTransaction, tracer: trace::StepTracer, state: &mut Self::State, header: &Header) -> Result<Executed<trace::FlatTrace, Vec<trace::StructLog>>, CallError> {
+		let env_info = self.virtual_call_env_info(header, transaction, U256::max_value());
+		let machine = self.engine.machine();
+
+		Self::do_virtual_call_with_options(machine, &env_info, state, false, transaction, TransactOptions::with_stepping(tracer))
+	}
+
 	fn estimate_gas(&self, t: &SignedTransaction, state: &Self::State, header: &Header) -> Result<U256, CallError> {
 		let (mut upper, max_upper, env_info) = {
 			let init = *header.gas_limit();
 			let max = init * U256::from(10);
 
-			let env_info = EnvInfo {
-				number: header.number(),
-				author: header.author().clone(),
-				timestamp: header.timestamp(),
-				difficulty: header.difficulty().clone(),
-				last_hashes: self.build_last_hashes(header.parent_hash()),
-				gas_used: U256::default(),
-				gas_limit: max,
-			};
+			let env_info = self.virtual_call_env_info(header, t, max);
 
 			(init, max, env_info)
 		};
@@ -1503,15 +1830,16 @@ impl Call for Client {
 			Executive::new(&mut clone, &env_info, &machine, &schedule)
 				.transact_virtual(&tx, options())
 				.ok()
-				.map(|r| r.exception.is_none())
 		};
 
-		let cond = |gas| exec(gas).unwrap_or(false);
+		let cond = |gas| exec(gas).map_or(false, |r| r.exception.is_none());
 
 		if !cond(upper) {
 			upper = max_upper;
 			match exec(upper) {
-				Some(false) => return Err(CallError::Exceptional),
+				// the call fails regardless of gas, report why if it reverted
+				Some(ref r) if r.exception == Some(vm::Error::Reverted) => return Err(CallError::Reverted(r.output.clone())),
+				Some(ref r) if r.exception.is_some() => return Err(CallError::Exceptional),
 				None => {
 					trace!(target: "estimate_gas", "estimate_gas failed with {}", upper);
 					let err = ExecutionError::Internal(format!("Requires higher than upper limit of {}", upper));
@@ -1549,6 +1877,13 @@ impl Call for Client {
 		trace!(target: "estimate_gas", "estimate_gas chopping {} .. {}", lower, upper);
 		binary_chop(lower, upper, cond)
 	}
+
+	fn apply_state_override(&self, state: &mut Self::State, overrides: &state::StateOverride) -> Result<(), CallError> {
+		state.apply_overrides(overrides).map_err(|e| {
+			warn!(target: "client", "Failed to apply state override: {}", e);
+			CallError::StateCorrupt
+		})
+	}
 }
 
 impl EngineInfo for Client {
@@ -1593,6 +1928,26 @@ impl BlockChainClient for Client {
 			})))
 	}
 
+	fn replay_struct_logs(&self, id: TransactionId, options: trace::StructLogOptions) -> Result<Executed<trace::FlatTrace, Vec<trace::StructLog>>, CallError> {
+		let address = self.transaction_address(id).ok_or(CallError::TransactionNotFound)?;
+		let block = BlockId::Hash(address.block_hash);
+		let mut env_info = self.env_info(block).ok_or(CallError::StatePruned)?;
+		let body = self.block_body(block).ok_or(CallError::StatePruned)?;
+		let mut state = self.state_at_beginning(block).ok_or(CallError::StatePruned)?;
+		let machine = self.engine.machine();
+
+		const PROOF: &'static str = "Transactions fetched from blockchain; blockchain transactions are valid; qed";
+
+		let mut txs = body.transactions().into_iter().map(|t| SignedTransaction::new(t).expect(PROOF));
+		for t in txs.by_ref().take(address.index) {
+			let x = Self::do_virtual_call(machine, &env_info, &mut state, &t, CallAnalytics::default())?;
+			env_info.gas_used = env_info.gas_used + x.gas_used;
+		}
+
+		let t = txs.next().ok_or(CallError::TransactionNotFound)?;
+		Self::do_virtual_call_with_options(machine, &env_info, &mut state, false, &t, TransactOptions::with_struct_logging(options))
+	}
+
 	fn mode(&self) -> Mode {
 		let r = self.mode.lock().clone().into();
 		trace!(target: "mode", "Asked for mode = {:?}. returning {:?}", &*self.mode.lock(), r);
@@ -1605,6 +1960,45 @@ impl BlockChainClient for Client {
 		self.clear_queue();
 	}
 
+	fn set_import_paused(&self, paused: bool) {
+		trace!(target: "client", "Client::set_import_paused({})", paused);
+		self.import_paused.store(paused, AtomicOrdering::SeqCst);
+		self.importer.miner.set_sealing_paused(paused);
+
+		if paused {
+			info!("Block import and sealing paused");
+		} else {
+			info!("Block import and sealing resumed");
+			// the queue won't signal again for blocks verified while paused
+			if let Err(e) = self.io_channel.read().send(ClientIoMessage::BlockVerified) {
+				warn!(target: "client", "Unable to resume block import: {:?}", e);
+			}
+			self.importer.miner.update_sealing(self);
+		}
+	}
+
+	fn is_import_paused(&self) -> bool {
+		self.import_paused.load(AtomicOrdering::SeqCst)
+	}
+
+	fn import_queue_status(&self) -> ImportQueueStatus {
+		ImportQueueStatus {
+			info: self.importer.block_queue.queue_info(),
+			items: self.importer.block_queue.queued_items(),
+			executing: *self.executing_block.read(),
+		}
+	}
+
+	fn transaction_lifecycle(&self, hash: &H256) -> Option<TransactionLifecycle> {
+		self.tx_lifecycle.as_ref().and_then(|log| log.get(hash))
+	}
+
+	fn transactions_propagated(&self, propagated: &[(H256, usize)]) {
+		if let Some(ref log) = self.tx_lifecycle {
+			log.propagated(propagated);
+		}
+	}
+
 	fn set_mode(&self, new_mode: Mode) {
 		trace!(target: "mode", "Client::set_mode({:?})", new_mode);
 		if !self.enabled.load(AtomicOrdering::Relaxed) {
@@ -1781,6 +2175,81 @@ impl BlockChainClient for Client {
 		Some(keys)
 	}
 
+	fn state_diff_between(&self, from: BlockId, to: BlockId) -> Option<StateDiff> {
+		if !self.factories.trie.is_fat() {
+			trace!(target: "fatdb", "state_diff_between: Not a fat DB");
+			return None;
+		}
+
+		let to_root = match self.state_at(to) {
+			Some(state) => *state.root(),
+			None => return None,
+		};
+		let (from_root, db) = match self.state_at(from) {
+			Some(state) => state.drop(),
+			None => return None,
+		};
+
+		match diff_states(db.as_hashdb(), &self.factories.accountdb, &from_root, &to_root) {
+			Ok(diff) => Some(diff),
+			Err(e) => {
+				trace!(target: "fatdb", "state_diff_between: Couldn't diff the states: {}", e);
+				None
+			}
+		}
+	}
+
+	fn block_witness(&self, id: BlockId) -> Option<BlockWitness> {
+		let chain = self.chain.read();
+		let hash = Self::block_hash(&chain, id)?;
+		let block = chain.block(&hash)?;
+		let eip1559_transition = self.engine.params().eip1559_transition;
+		let header = block.decode_header(eip1559_transition);
+		let parent = chain.block_header_data(header.parent_hash())?.decode(eip1559_transition).ok()?;
+		if !self.state_db.read().journal_db().contains(parent.state_root()) {
+			trace!(target: "client", "block_witness: State of {} is not available", header.parent_hash());
+			return None;
+		}
+
+		let transactions = block.transactions().into_iter()
+			.map(SignedTransaction::new)
+			.collect::<Result<Vec<_>, _>>()
+			.ok()?;
+		let last_hashes = self.build_last_hashes(header.parent_hash());
+		let is_epoch_begin = chain.epoch_transition(parent.number(), *header.parent_hash()).is_some();
+		let (db, recorder) = self.state_db.read().boxed_clone_recording();
+
+		let result = enact_verified(
+			PreverifiedBlock {
+				header: header.clone(),
+				transactions: transactions,
+				uncles: block.uncles(eip1559_transition),
+				bytes: block.into_inner(),
+			},
+			&*self.engine,
+			false,
+			false,
+			db,
+			&parent,
+			last_hashes.clone(),
+			self.factories.clone(),
+			is_epoch_begin,
+			&mut chain.ancestry_with_metadata_iter(*header.parent_hash()),
+		);
+
+		if let Err(e) = result {
+			warn!(target: "client", "block_witness: Failed to execute block {}: {:?}", hash, e);
+			return None;
+		}
+
+		Some(BlockWitness {
+			parent: parent,
+			last_hashes: (*last_hashes).clone(),
+			is_epoch_begin: is_epoch_begin,
+			state: recorder.values().into_iter().map(|value| value.into_vec()).collect(),
+		})
+	}
+
 	fn transaction(&self, id: TransactionId) -> Option<LocalizedTransaction> {
 		self.transaction_address(id).and_then(|address| self.chain.read().transaction(&address))
 	}
@@ -1979,12 +2448,11 @@ impl BlockChainClient for Client {
 			to_address: filter.to_address.into(),
 		};
 
-		let traces = self.tracedb.read()
-			.filter(&db_filter)
-			.into_iter()
-			.skip(filter.after.unwrap_or(0))
-			.take(filter.count.unwrap_or(usize::max_value()))
-			.collect();
+		let traces = self.tracedb.read().filter_page(
+			&db_filter,
+			filter.after.unwrap_or(0),
+			filter.count.unwrap_or(usize::max_value()),
+		);
 		Some(traces)
 	}
 
@@ -2018,6 +2486,16 @@ impl BlockChainClient for Client {
 			return None;
 		}
 
+		if let BlockId::Hash(ref hash) = block {
+			// transactions of non-canonical blocks are not indexed by number.
+			let chain = self.chain.read();
+			if !chain.is_canon(hash) {
+				let number = chain.block_details(hash)?.number;
+				let body = chain.block_body(hash)?;
+				return self.tracedb.read().block_traces_by_hash(hash, number, &body.transaction_hashes());
+			}
+		}
+
 		self.block_number(block)
 			.and_then(|number| self.tracedb.read().block_traces(number))
 	}
@@ -2048,6 +2526,10 @@ impl BlockChainClient for Client {
 		self.importer.miner.ready_transactions(self, max_len, ::miner::PendingOrdering::Priority)
 	}
 
+	fn transactions_to_rebroadcast(&self) -> Vec<H256> {
+		self.importer.miner.transactions_to_rebroadcast()
+	}
+
 	fn signing_chain_id(&self) -> Option<u64> {
 		self.engine.signing_chain_id(&self.latest_env_info())
 	}
@@ -2060,7 +2542,7 @@ impl BlockChainClient for Client {
 	fn uncle_extra_info(&self, id: UncleId) -> Option<BTreeMap<String, String>> {
 		self.uncle(id)
 			.and_then(|h| {
-				h.decode().map(|dh| {
+				h.decode(self.engine.params().eip1559_transition).map(|dh| {
 					self.engine.extra_info(&dh)
 				}).ok()
 			})
@@ -2096,7 +2578,7 @@ impl BlockChainClient for Client {
 }
 
 impl IoClient for Client {
-	fn queue_transactions(&self, transactions: Vec<Bytes>, peer_id: usize) {
+	fn queue_transactions(&self, transactions: Vec<Bytes>, peer_id: usize, origin: Option<IpAddr>) {
 		trace_time!("queue_transactions");
 		let len = transactions.len();
 		self.queue_transactions.queue(&self.io_channel.read(), len, move |client| {
@@ -2111,7 +2593,7 @@ impl IoClient for Client {
 				notify.transactions_received(&txs, peer_id);
 			});
 
-			client.importer.miner.import_external_transactions(client, txs);
+			client.importer.miner.import_external_transactions_from(client, txs, origin);
 		}).unwrap_or_else(|e| {
 			debug!(target: "client", "Ignoring {} transactions: {}", len, e);
 		});
@@ -2129,8 +2611,15 @@ impl IoClient for Client {
 			let parent_hash = unverified.parent_hash();
 			// NOTE To prevent race condition with import, make sure to check queued blocks first
 			// (and attempt to acquire lock)
-			let is_parent_pending = self.queued_ancient_blocks.read().0.contains(&parent_hash);
-			if !is_parent_pending {
+			let (is_parent_pending, is_child_pending) = {
+				let queued = self.queued_ancient_blocks.read();
+				if queued.0.contains_key(&hash) {
+					bail!(EthcoreErrorKind::Import(ImportErrorKind::AlreadyQueued));
+				}
+				// blocks downloaded backwards are queued after their children
+				(queued.0.contains_key(&parent_hash), queued.0.values().any(|parent| *parent == hash))
+			};
+			if !is_parent_pending && !is_child_pending && !self.chain.read().is_first_block_parent(&hash) {
 				let status = self.block_status(BlockId::Hash(parent_hash));
 				if  status == BlockStatus::Unknown {
 					bail!(EthcoreErrorKind::Block(BlockError::UnknownParent(parent_hash)));
@@ -2141,7 +2630,7 @@ impl IoClient for Client {
 		// we queue blocks here and trigger an IO message.
 		{
 			let mut queued = self.queued_ancient_blocks.write();
-			queued.0.insert(hash);
+			queued.0.insert(hash, parent_hash);
 			queued.1.push_back((unverified, receipts_bytes));
 		}
 
@@ -2206,7 +2695,7 @@ impl ReopenBlock for Client {
 			for h in uncles {
 				if !block.uncles().iter().any(|header| header.hash() == h) {
 					let uncle = chain.block_header_data(&h).expect("find_uncle_hashes only returns hashes for existing headers; qed");
-					let uncle = uncle.decode().expect("decoding failure");
+					let uncle = uncle.decode(engine.params().eip1559_transition).expect("decoding failure");
 					block.push_uncle(uncle).expect("pushing up to maximum_uncle_count;
 												push_uncle is not ok only if more than maximum_uncle_count is pushed;
 												so all push_uncle are Ok;
@@ -2249,7 +2738,7 @@ impl PrepareOpenBlock for Client {
 			.into_iter()
 			.take(engine.maximum_uncle_count(open_block.header().number()))
 			.foreach(|h| {
-				open_block.push_uncle(h.decode().expect("decoding failure")).expect("pushing maximum_uncle_count;
+				open_block.push_uncle(h.decode(engine.params().eip1559_transition).expect("decoding failure")).expect("pushing maximum_uncle_count;
 												open_block was just created;
 												push_uncle is not ok only if more than maximum_uncle_count is pushed;
 												so all push_uncle are Ok;
@@ -2270,6 +2759,10 @@ impl ScheduleInfo for Client {
 
 impl ImportSealedBlock for Client {
 	fn import_sealed_block(&self, block: SealedBlock) -> EthcoreResult<H256> {
+		if self.import_paused.load(AtomicOrdering::SeqCst) {
+			bail!(EthcoreErrorKind::Import(ImportErrorKind::Paused));
+		}
+
 		let h = block.header().hash();
 		let start = Instant::now();
 		let route = {
@@ -2305,6 +2798,9 @@ impl ImportSealedBlock for Client {
 				start.elapsed(),
 			);
 		});
+		if let Some(reorg) = self.chain_reorg(&route) {
+			self.notify(|notify| notify.reorg(reorg.clone()));
+		}
 		self.db.read().key_value().flush().expect("DB flush failed.");
 		Ok(h)
 	}
@@ -2406,6 +2902,25 @@ impl Drop for Client {
 	}
 }
 
+impl PrometheusMetrics for Client {
+	fn prometheus_metrics(&self, registry: &mut PrometheusRegistry) {
+		let chain = self.chain_info();
+		let queue = self.queue_info();
+		let report = self.report();
+		let cache = self.blockchain_cache_info();
+
+		registry.register_gauge("chain_block", "Best block number", chain.best_block_number as i64);
+		registry.register_gauge("chain_block_timestamp", "Timestamp of the best block", chain.best_block_timestamp as i64);
+		registry.register_gauge("queue_size_total", "Number of blocks waiting in the import queue", queue.total_queue_size() as i64);
+		registry.register_counter("import_blocks", "Number of blocks imported since start", report.blocks_imported as u64);
+		registry.register_counter("import_transactions", "Number of transactions applied since start", report.transactions_applied as u64);
+		registry.register_counter("import_gas", "Gas processed since start", report.gas_processed.low_u64());
+		registry.register_histogram("import_duration_seconds", "Time taken to execute and commit a block", &self.import_times.read());
+		registry.register_gauge("db_state_cache_bytes", "Memory used by the state database", report.state_db_mem as i64);
+		registry.register_gauge("db_chain_cache_bytes", "Memory used by the blockchain caches", cache.total() as i64);
+	}
+}
+
 /// Returns `LocalizedReceipt` given `LocalizedTransaction`
 /// and a vector of receipts from given block up to transaction index.
 fn transaction_receipt(
@@ -2567,6 +3082,7 @@ mod tests {
 			gas_used: gas_used,
 			log_bloom: Default::default(),
 			logs: logs.clone(),
+			transaction_type: 0,
 		};
 
 		// when
@@ -2615,6 +3131,88 @@ struct IoChannelQueue {
 	limit: usize,
 }
 
+/// Difference between two states, computed from the changed leaves of their tries.
+/// Addresses and storage keys are recovered from the preimages stored by fat DB.
+fn diff_states(
+	db: &HashDB<KeccakHasher>,
+	accountdb: &account_db::Factory,
+	from_root: &H256,
+	to_root: &H256,
+) -> Result<StateDiff, String> {
+	fn preimage(db: &HashDB<KeccakHasher>, hash: &[u8]) -> Result<DBValue, String> {
+		db.get(&keccak(hash)).ok_or_else(|| format!("Missing preimage of {}", H256::from_slice(hash)))
+	}
+
+	fn values(change: ethtrie::TrieChange) -> (Option<DBValue>, Option<DBValue>) {
+		match change {
+			ethtrie::TrieChange::Created { value, .. } => (None, Some(value)),
+			ethtrie::TrieChange::Deleted { value, .. } => (Some(value), None),
+			ethtrie::TrieChange::Modified { old, new, .. } => (Some(old), Some(new)),
+		}
+	}
+
+	let mut raw = BTreeMap::new();
+	for change in ethtrie::trie_diff(db, from_root, to_root).map_err(|e| e.to_string())? {
+		let address_hash = H256::from_slice(change.key());
+		let address = Address::from_slice(&preimage(db, change.key())?);
+		let account_db = accountdb.readonly(db, address_hash);
+
+		let (pre, post) = values(change);
+		let decode = |value: Option<DBValue>| -> Result<Option<BasicAccount>, String> {
+			match value {
+				Some(value) => ::rlp::decode::<BasicAccount>(&value).map(Some).map_err(|e| e.to_string()),
+				None => Ok(None),
+			}
+		};
+		let (pre, post) = (decode(pre)?, decode(post)?);
+
+		let storage_root = |account: &Option<BasicAccount>| account.as_ref().map_or(KECCAK_NULL_RLP, |a| a.storage_root);
+		let mut pre_storage: BTreeMap<H256, H256> = BTreeMap::new();
+		let mut post_storage: BTreeMap<H256, H256> = BTreeMap::new();
+		for change in ethtrie::trie_diff(account_db.as_hashdb(), &storage_root(&pre), &storage_root(&post)).map_err(|e| e.to_string())? {
+			let key = H256::from_slice(&preimage(account_db.as_hashdb(), change.key())?);
+			let decode = |value: DBValue| ::rlp::decode::<U256>(&value).map(H256::from).map_err(|e| e.to_string());
+			match values(change) {
+				(Some(pre_value), Some(post_value)) => {
+					pre_storage.insert(key, decode(pre_value)?);
+					post_storage.insert(key, decode(post_value)?);
+				},
+				(Some(pre_value), None) => { pre_storage.insert(key, decode(pre_value)?); },
+				(None, Some(post_value)) => { post_storage.insert(key, decode(post_value)?); },
+				(None, None) => {},
+			}
+		}
+
+		let pod = |account: Option<BasicAccount>, storage: BTreeMap<H256, H256>| -> Result<Option<PodAccount>, String> {
+			let account = match account {
+				Some(account) => account,
+				None => return Ok(None),
+			};
+			let code = if account.code_hash == KECCAK_EMPTY {
+				Vec::new()
+			} else {
+				account_db.get(&account.code_hash)
+					.ok_or_else(|| format!("Missing code of {}", address))?
+					.into_vec()
+			};
+			Ok(Some(PodAccount {
+				balance: account.balance,
+				nonce: account.nonce,
+				code: Some(code),
+				storage,
+			}))
+		};
+		let pre = pod(pre, pre_storage)?;
+		let post = pod(post, post_storage)?;
+
+		if let Some(diff) = pod_account::diff_pod(pre.as_ref(), post.as_ref()) {
+			raw.insert(address, diff);
+		}
+	}
+
+	Ok(StateDiff { raw })
+}
+
 impl IoChannelQueue {
 	pub fn new(limit: usize) -> Self {
 		IoChannelQueue {
@@ -0,0 +1,268 @@
+// Copyright 2015-2018 Parity Technologies (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Reconstruction of pruned state from the local snapshot.
+//!
+//! The state of the latest local snapshot is restored into a separate archive database,
+//! either right after the snapshot is taken or in the background once it's first needed.
+//! The state of a later block is obtained by replaying the blocks on top of it; replayed
+//! states are written to the same database, so each block is replayed at most once.
+
+use std::fs;
+use std::io::ErrorKind;
+use std::mem;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use ethereum_types::H256;
+use journaldb::{self, Algorithm};
+use kvdb::{DBTransaction, KeyValueDB};
+use parking_lot::{Mutex, MutexGuard, RwLock};
+use snappy;
+
+use error::Error;
+use header::BlockNumber;
+use snapshot::{self, StateRebuilder};
+use snapshot::io::{LooseReader, SnapshotReader};
+use snapshot::service::current_snapshot_dir;
+use state_db::StateDB;
+
+/// Size of the account and node cache of reconstructed states.
+const STATE_CACHE_SIZE: usize = 1024 * 1024;
+
+/// Configuration of pruned state reconstruction.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ColdStateConfig {
+	/// Root directory of local snapshots.
+	pub snapshot_root: PathBuf,
+	/// Maximal number of blocks replayed on top of the snapshot.
+	pub max_blocks: u64,
+}
+
+/// State of the block a local snapshot was taken at.
+pub struct SnapshotState {
+	/// Number of the snapshot block.
+	pub block_number: BlockNumber,
+	/// Hash of the snapshot block.
+	pub block_hash: H256,
+	/// State root of the snapshot block.
+	pub state_root: H256,
+	replayed: Mutex<(BlockNumber, H256)>,
+	db: Arc<KeyValueDB>,
+	dir: PathBuf,
+}
+
+impl SnapshotState {
+	/// Get a new state database on top of the snapshot state and all replayed states.
+	pub fn state_db(&self) -> StateDB {
+		let journal_db = journaldb::new(self.db.clone(), Algorithm::Archive, ::db::COL_STATE);
+		StateDB::new(journal_db, STATE_CACHE_SIZE)
+	}
+
+	/// Number and hash of the last block replayed on top of the snapshot.
+	/// Holding the guard keeps other threads from replaying at the same time.
+	pub fn replayed(&self) -> MutexGuard<(BlockNumber, H256)> {
+		self.replayed.lock()
+	}
+
+	/// Write the state of a replayed block to the database, so the block is never replayed again.
+	pub fn commit(&self, state_db: &mut StateDB, number: BlockNumber, hash: &H256) -> Result<(), Error> {
+		let mut batch = DBTransaction::new();
+		state_db.journal_under(&mut batch, number, hash)?;
+		self.db.write(batch)?;
+		Ok(())
+	}
+
+	// whether the database is used by anyone but the `ColdState`.
+	fn in_use(state: &Arc<SnapshotState>) -> bool {
+		Arc::strong_count(state) > 1 || Arc::strong_count(&state.db) > 1
+	}
+}
+
+/// Restores and caches the state of the latest local snapshot.
+pub struct ColdState {
+	config: ColdStateConfig,
+	restored: RwLock<Option<Arc<SnapshotState>>>,
+	// states of older snapshots, kept until nobody uses their database anymore.
+	retired: Mutex<Vec<Arc<SnapshotState>>>,
+	restoring: AtomicBool,
+}
+
+impl ColdState {
+	/// Create a new `ColdState`. Nothing is restored until `restore_latest` is called.
+	pub fn new(config: ColdStateConfig) -> Self {
+		ColdState {
+			config,
+			restored: RwLock::new(None),
+			retired: Mutex::new(Vec::new()),
+			restoring: AtomicBool::new(false),
+		}
+	}
+
+	/// Maximal number of blocks replayed on top of the snapshot.
+	pub fn max_blocks(&self) -> u64 {
+		self.config.max_blocks
+	}
+
+	fn root_dir(&self) -> PathBuf {
+		self.config.snapshot_root.join("cold_state")
+	}
+
+	/// Get the restored state of a local snapshot, if any. This never restores anything,
+	/// so the state may belong to an older snapshot than the latest one.
+	pub fn snapshot_state(&self) -> Option<Arc<SnapshotState>> {
+		self.restored.read().clone()
+	}
+
+	/// Whether the latest local snapshot differs from the restored one and isn't being restored yet.
+	pub fn needs_restore(&self) -> bool {
+		if self.restoring.load(Ordering::SeqCst) {
+			return false;
+		}
+
+		match LooseReader::new(current_snapshot_dir(&self.config.snapshot_root)) {
+			Ok(reader) => self.restored.read().as_ref().map_or(true, |state| state.block_hash != reader.manifest().block_hash),
+			Err(_) => false,
+		}
+	}
+
+	/// Restore the state of the latest local snapshot unless it's restored already.
+	/// This takes a while, so it should be called from a background thread.
+	/// Returns without doing anything if another restoration is in progress.
+	pub fn restore_latest(&self) -> Result<(), Error> {
+		if self.restoring.compare_and_swap(false, true, Ordering::SeqCst) {
+			debug!(target: "client", "Skipping restoration of the snapshot state as another one is in progress");
+			return Ok(());
+		}
+
+		let res = self.restore_latest_impl();
+		self.restoring.store(false, Ordering::SeqCst);
+		res
+	}
+
+	fn restore_latest_impl(&self) -> Result<(), Error> {
+		let reader = match LooseReader::new(current_snapshot_dir(&self.config.snapshot_root)) {
+			Ok(reader) => reader,
+			Err(_) => return Ok(()),
+		};
+
+		if self.restored.read().as_ref().map_or(false, |state| state.block_hash == reader.manifest().block_hash) {
+			return Ok(());
+		}
+
+		self.remove_unused()?;
+		let state = Arc::new(self.restore(&reader)?);
+
+		// calls may still use the previous state, so its database is only removed once they're done.
+		let previous = mem::replace(&mut *self.restored.write(), Some(state));
+		if let Some(previous) = previous {
+			self.retired.lock().push(previous);
+		}
+		Ok(())
+	}
+
+	// remove databases of retired states which are no longer used, along with any leftovers
+	// of earlier runs.
+	fn remove_unused(&self) -> Result<(), Error> {
+		let mut retired = self.retired.lock();
+		retired.retain(SnapshotState::in_use);
+
+		let root_dir = self.root_dir();
+		let entries = match fs::read_dir(&root_dir) {
+			Ok(entries) => entries,
+			Err(ref e) if e.kind() == ErrorKind::NotFound => return Ok(()),
+			Err(e) => return Err(e.into()),
+		};
+
+		let restored = self.restored.read();
+		for entry in entries {
+			let path = entry?.path();
+			let used = restored.iter().chain(retired.iter()).any(|state| state.dir == path);
+			if !used {
+				fs::remove_dir_all(&path)?;
+			}
+		}
+		Ok(())
+	}
+
+	fn restore(&self, reader: &LooseReader) -> Result<SnapshotState, Error> {
+		let manifest = reader.manifest();
+		info!(target: "client", "Restoring state of snapshot at block #{} to reconstruct pruned state", manifest.block_number);
+
+		let dir = self.root_dir().join(format!("{:x}", manifest.block_hash));
+		let db = open_db(&dir)?;
+		let mut rebuilder = StateRebuilder::new(db.clone(), Algorithm::Archive);
+		let flag = AtomicBool::new(true);
+		let mut buffer = Vec::new();
+		for hash in &manifest.state_hashes {
+			let chunk = reader.chunk(*hash)?;
+			let len = snappy::decompressed_len(&chunk)?;
+			buffer.resize(len, 0);
+			let len = snappy::decompress_into(&chunk, &mut buffer)?;
+			rebuilder.feed(&buffer[..len], &flag)?;
+		}
+
+		let root = rebuilder.state_root()?;
+		if root != manifest.state_root {
+			return Err(snapshot::Error::WrongStateRoot(manifest.state_root, root).into());
+		}
+		rebuilder.finalize(manifest.block_number, manifest.block_hash)?;
+		db.flush()?;
+
+		info!(target: "client", "Restored state of snapshot at block #{}", manifest.block_number);
+		Ok(SnapshotState {
+			block_number: manifest.block_number,
+			block_hash: manifest.block_hash,
+			state_root: manifest.state_root,
+			replayed: Mutex::new((manifest.block_number, manifest.block_hash)),
+			db,
+			dir,
+		})
+	}
+}
+
+#[cfg(feature = "rocksdb")]
+fn open_db(path: &Path) -> Result<Arc<KeyValueDB>, Error> {
+	use kvdb_rocksdb::{Database, DatabaseConfig};
+
+	let config = DatabaseConfig::with_columns(::db::NUM_COLUMNS);
+	Ok(Arc::new(Database::open(&config, &path.to_string_lossy())?))
+}
+
+#[cfg(not(feature = "rocksdb"))]
+fn open_db(_path: &Path) -> Result<Arc<KeyValueDB>, Error> {
+	Ok(Arc::new(::kvdb_memorydb::create(::db::NUM_COLUMNS.unwrap_or(0))))
+}
+
+#[cfg(test)]
+mod tests {
+	use tempdir::TempDir;
+	use super::{ColdState, ColdStateConfig};
+
+	#[test]
+	fn should_not_restore_without_local_snapshot() {
+		let tempdir = TempDir::new("").unwrap();
+		let cold_state = ColdState::new(ColdStateConfig {
+			snapshot_root: tempdir.path().to_owned(),
+			max_blocks: 5000,
+		});
+
+		assert!(!cold_state.needs_restore());
+		cold_state.restore_latest().unwrap();
+		assert!(cold_state.snapshot_state().is_none());
+	}
+}
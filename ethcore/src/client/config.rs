@@ -17,9 +17,11 @@
 use std::str::FromStr;
 use std::fmt::{Display, Formatter, Error as FmtError};
 
+use ethereum_types::{Address, H256};
 use verification::{VerifierType, QueueConfig};
 use journaldb;
 use snapshot::SnapshotConfiguration;
+use client::cold_state::ColdStateConfig;
 
 pub use std::time::Duration;
 pub use blockchain::Config as BlockChainConfig;
@@ -56,6 +58,29 @@ impl FromStr for DatabaseCompactionProfile {
 	}
 }
 
+/// Contract analyzed at startup, ahead of its execution.
+#[derive(Debug, PartialEq, Clone)]
+pub enum PrewarmContract {
+	/// Contract deployed at given address.
+	Address(Address),
+	/// Contract with given code hash.
+	CodeHash(H256),
+}
+
+impl FromStr for PrewarmContract {
+	type Err = String;
+
+	fn from_str(s: &str) -> Result<Self, Self::Err> {
+		let hex = if s.starts_with("0x") { &s[2..] } else { s };
+		let invalid = |_| format!("Invalid contract {}. Expected an address or a code hash.", s);
+		match hex.len() {
+			40 => hex.parse().map(PrewarmContract::Address).map_err(invalid),
+			64 => hex.parse().map(PrewarmContract::CodeHash).map_err(invalid),
+			_ => Err(format!("Invalid contract {}. Expected an address or a code hash.", s)),
+		}
+	}
+}
+
 /// Operating mode for the client.
 #[derive(Debug, Eq, PartialEq, Clone)]
 pub enum Mode {
@@ -123,6 +148,14 @@ pub struct ClientConfig {
 	pub transaction_verification_queue_size: usize,
 	/// Snapshot configuration
 	pub snapshot: SnapshotConfiguration,
+	/// Number of transactions kept in the transaction lifecycle log, disabled if `None`.
+	pub transaction_lifecycle_log: Option<usize>,
+	/// Execute non-conflicting transactions of imported blocks in parallel.
+	pub parallel_transactions: bool,
+	/// Reconstruction of pruned state from the local snapshot for calls, disabled if `None`.
+	pub cold_state: Option<ColdStateConfig>,
+	/// Contracts analyzed at startup and kept in the EVM cache.
+	pub prewarm_contracts: Vec<PrewarmContract>,
 }
 
 impl Default for ClientConfig {
@@ -148,12 +181,16 @@ impl Default for ClientConfig {
 			check_seal: true,
 			transaction_verification_queue_size: 8192,
 			snapshot: Default::default(),
+			transaction_lifecycle_log: None,
+			parallel_transactions: false,
+			cold_state: None,
+			prewarm_contracts: Vec::new(),
 		}
 	}
 }
 #[cfg(test)]
 mod test {
-	use super::DatabaseCompactionProfile;
+	use super::{DatabaseCompactionProfile, PrewarmContract};
 
 	#[test]
 	fn test_default_compaction_profile() {
@@ -166,4 +203,17 @@ mod test {
 		assert_eq!(DatabaseCompactionProfile::SSD, "ssd".parse().unwrap());
 		assert_eq!(DatabaseCompactionProfile::HDD, "hdd".parse().unwrap());
 	}
+
+	#[test]
+	fn test_parsing_prewarm_contract() {
+		assert_eq!(
+			"0x0000000000000000000000000000000000000005".parse(),
+			Ok(PrewarmContract::Address(5.into()))
+		);
+		assert_eq!(
+			"0000000000000000000000000000000000000000000000000000000000000005".parse(),
+			Ok(PrewarmContract::CodeHash(5.into()))
+		);
+		assert!("0x05".parse::<PrewarmContract>().is_err());
+	}
 }
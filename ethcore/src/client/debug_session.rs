@@ -0,0 +1,122 @@
+// Copyright 2015-2018 Parity Technologies (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Interactive, instruction by instruction execution of a call.
+
+use std::io;
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::thread::{self, JoinHandle};
+
+use error::{CallError, ExecutionError};
+use executive::Executed;
+use trace::{FlatTrace, StepTracer, StructLog};
+
+type DebugResult = Result<Executed<FlatTrace, Vec<StructLog>>, CallError>;
+
+/// State of a debug session.
+#[derive(Debug, Clone)]
+pub enum DebugState {
+	/// Execution is paused right before executing an instruction.
+	Paused(StructLog),
+	/// Execution has finished.
+	Finished(DebugResult),
+}
+
+/// A call executed one instruction at a time.
+///
+/// The call runs on a dedicated thread, which is blocked between instructions.
+/// Dropping the session lets the execution run to completion in the background.
+pub struct DebugSession {
+	steps: Receiver<StructLog>,
+	commands: Sender<()>,
+	handle: Option<JoinHandle<DebugResult>>,
+	state: DebugState,
+}
+
+impl DebugSession {
+	/// Starts the execution and pauses it before its first instruction.
+	///
+	/// `execute` is given the tracer to run the call with.
+	pub fn start<F>(execute: F) -> io::Result<Self> where
+		F: FnOnce(StepTracer) -> DebugResult + Send + 'static,
+	{
+		let (steps_tx, steps) = mpsc::channel();
+		let (commands, commands_rx) = mpsc::channel();
+		let tracer = StepTracer::toplevel(steps_tx, commands_rx);
+		let handle = thread::Builder::new()
+			.name("debug-session".into())
+			.spawn(move || execute(tracer))?;
+
+		let mut handle = Some(handle);
+		let state = next_state(&steps, &mut handle);
+		Ok(DebugSession { steps, commands, handle, state })
+	}
+
+	/// Current state of the session.
+	pub fn state(&self) -> &DebugState {
+		&self.state
+	}
+
+	/// Executes the current instruction and pauses before the next one.
+	pub fn step(&mut self) -> &DebugState {
+		if let DebugState::Paused(_) = self.state {
+			// the execution thread only ever waits for this message, it can't be gone
+			let _ = self.commands.send(());
+			self.state = next_state(&self.steps, &mut self.handle);
+		}
+		&self.state
+	}
+}
+
+fn next_state(steps: &Receiver<StructLog>, handle: &mut Option<JoinHandle<DebugResult>>) -> DebugState {
+	if let Ok(log) = steps.recv() {
+		return DebugState::Paused(log);
+	}
+
+	// all tracers are gone, so the execution has finished
+	let handle = handle.take().expect("handle is only taken once the execution has finished; qed");
+	DebugState::Finished(handle.join().unwrap_or_else(|_| {
+		Err(CallError::Execution(ExecutionError::Internal("debug session execution panicked".into())))
+	}))
+}
+
+#[cfg(test)]
+mod tests {
+	use trace::VMTracer;
+	use super::{DebugSession, DebugState};
+	use error::CallError;
+
+	#[test]
+	fn should_step_until_finished() {
+		let mut session = DebugSession::start(|tracer| {
+			let mut tracer = tracer.prepare_subtrace(&[]);
+			tracer.trace_next_instruction(0, 0x00, 10.into());
+			tracer.trace_prepare_execute(0, 0x00, 0.into());
+			tracer.trace_executed(10.into(), &[], None, None);
+			Err(CallError::StatePruned)
+		}).unwrap();
+
+		match *session.state() {
+			DebugState::Paused(ref log) => assert_eq!(log.pc, 0),
+			ref state => panic!("unexpected state: {:?}", state),
+		}
+
+		match *session.step() {
+			DebugState::Finished(Err(CallError::StatePruned)) => {},
+			ref state => panic!("unexpected state: {:?}", state),
+		}
+	}
+}
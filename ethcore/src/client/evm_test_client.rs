@@ -180,6 +180,7 @@ impl<'a> EvmTestClient<'a> {
 			difficulty: *genesis.difficulty(),
 			last_hashes: Arc::new([H256::default(); 256].to_vec()),
 			gas_used: 0.into(),
+			base_fee: None,
 			gas_limit: *genesis.gas_limit(),
 		};
 		let mut substate = state::Substate::new();
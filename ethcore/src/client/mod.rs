@@ -19,22 +19,28 @@
 mod ancient_import;
 mod bad_blocks;
 mod client;
+mod cold_state;
 mod config;
-#[cfg(any(test, feature = "test-helpers"))]
+mod debug_session;
 mod evm_test_client;
 mod io_message;
+mod state_test;
 #[cfg(any(test, feature = "test-helpers"))]
 mod test_client;
 mod trace;
+mod tx_lifecycle;
 
 pub use self::client::*;
-pub use self::config::{Mode, ClientConfig, DatabaseCompactionProfile, BlockChainConfig, VMType};
-#[cfg(any(test, feature = "test-helpers"))]
+pub use self::cold_state::ColdStateConfig;
+pub use self::config::{Mode, ClientConfig, DatabaseCompactionProfile, BlockChainConfig, VMType, PrewarmContract};
+pub use self::debug_session::{DebugSession, DebugState};
 pub use self::evm_test_client::{EvmTestClient, EvmTestError, TransactResult};
 pub use self::io_message::ClientIoMessage;
+pub use self::state_test::{run_state_tests, StateTestResult, StateTestOutcome};
+pub use self::tx_lifecycle::TransactionLifecycleLog;
 #[cfg(any(test, feature = "test-helpers"))]
 pub use self::test_client::{TestBlockChainClient, EachBlockWith};
-pub use self::chain_notify::{ChainNotify, ChainRoute, ChainRouteType, ChainMessageType};
+pub use self::chain_notify::{ChainNotify, ChainReorg, ChainRoute, ChainRouteType, ChainMessageType};
 pub use self::traits::{
     Nonce, Balance, ChainInfo, BlockInfo, ReopenBlock, PrepareOpenBlock, CallContract, TransactionInfo, RegistryInfo, ScheduleInfo, ImportSealedBlock, BroadcastProposalBlock, ImportBlock,
     StateOrBlock, StateClient, Call, EngineInfo, AccountData, BlockChain, BlockProducer, SealedBlockImporter, BadBlocks,
@@ -46,6 +52,7 @@ pub use types::ids::*;
 pub use types::trace_filter::Filter as TraceFilter;
 pub use types::pruning_info::PruningInfo;
 pub use types::call_analytics::CallAnalytics;
+pub use types::transaction_lifecycle::{TransactionLifecycle, LifecycleEntry, LifecycleEvent};
 
 pub use executive::{Executed, Executive, TransactOptions};
 pub use vm::{LastHashes, EnvInfo};
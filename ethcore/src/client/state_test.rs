@@ -0,0 +1,212 @@
+// Copyright 2015-2018 Parity Technologies (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Runner of state tests in the standard ethereum/tests JSON format.
+
+use ethereum_types::H256;
+use ethjson;
+use ethjson::spec::ForkSpec;
+use pod_state::PodState;
+use trace;
+use transaction::SignedTransaction;
+use vm::EnvInfo;
+
+use super::{EvmTestClient, TransactResult};
+
+/// Outcome of a single state test case.
+#[derive(Debug, Clone, PartialEq)]
+pub enum StateTestOutcome {
+	/// The post state root matches the expected one.
+	Passed,
+	/// The post state root doesn't match the expected one.
+	Failed {
+		/// Expected state root.
+		expected: H256,
+		/// Actual state root.
+		actual: H256,
+		/// Error of the transaction execution, if any.
+		error: Option<String>,
+	},
+	/// The test could not be run.
+	Error(String),
+	/// The fork is not supported.
+	Skipped,
+}
+
+/// Result of a single state test case.
+#[derive(Debug, Clone, PartialEq)]
+pub struct StateTestResult {
+	/// Name of the test.
+	pub name: String,
+	/// Fork rules the case was run with.
+	pub fork: ForkSpec,
+	/// Index of the case among all cases of the fork.
+	pub index: usize,
+	/// Outcome of the case.
+	pub outcome: StateTestOutcome,
+}
+
+impl StateTestResult {
+	/// Whether the case failed.
+	pub fn is_failure(&self) -> bool {
+		match self.outcome {
+			StateTestOutcome::Failed { .. } | StateTestOutcome::Error(_) => true,
+			StateTestOutcome::Passed | StateTestOutcome::Skipped => false,
+		}
+	}
+}
+
+/// Run all state tests of the JSON file.
+///
+/// `filter` is called with the name and fork of every test and decides whether its cases are run.
+pub fn run_state_tests<F>(json_data: &[u8], mut filter: F) -> Result<Vec<StateTestResult>, String> where
+	F: FnMut(&str, &ForkSpec) -> bool,
+{
+	let tests = ethjson::state::Test::load(json_data).map_err(|e| format!("Invalid state test: {}", e))?;
+	let mut results = Vec::new();
+
+	for (name, test) in tests.into_iter() {
+		let multitransaction = test.transaction;
+		let env: EnvInfo = test.env.into();
+		let pre: PodState = test.pre_state.into();
+
+		for (fork, states) in test.post_states {
+			if !filter(&name, &fork) {
+				continue;
+			}
+
+			let spec = EvmTestClient::spec_from_json(&fork);
+			for (index, state) in states.into_iter().enumerate() {
+				let outcome = match spec {
+					None => StateTestOutcome::Skipped,
+					Some(ref spec) => {
+						let expected: H256 = state.hash.into();
+						let transaction: SignedTransaction = multitransaction.select(&state.indexes).into();
+						match EvmTestClient::from_pod_state(spec, pre.clone()) {
+							Err(e) => StateTestOutcome::Error(e.to_string()),
+							Ok(mut client) => match client.transact(&env, transaction, trace::NoopTracer, trace::NoopVMTracer) {
+								TransactResult::Ok { state_root, .. } if state_root == expected => StateTestOutcome::Passed,
+								TransactResult::Err { state_root, .. } if state_root == expected => StateTestOutcome::Passed,
+								TransactResult::Ok { state_root, .. } => StateTestOutcome::Failed {
+									expected,
+									actual: state_root,
+									error: None,
+								},
+								TransactResult::Err { state_root, error } => StateTestOutcome::Failed {
+									expected,
+									actual: state_root,
+									error: Some(error.to_string()),
+								},
+							},
+						}
+					},
+				};
+
+				results.push(StateTestResult {
+					name: name.clone(),
+					fork,
+					index,
+					outcome,
+				});
+			}
+		}
+	}
+
+	Ok(results)
+}
+
+#[cfg(test)]
+mod tests {
+	use ethjson::spec::ForkSpec;
+	use super::{run_state_tests, StateTestOutcome};
+
+	// expected state roots are deliberately wrong
+	const TEST: &'static str = r#"{
+		"add11" : {
+			"env" : {
+				"currentCoinbase" : "2adc25665018aa1fe0e6bc666dac8fc2697ff9ba",
+				"currentDifficulty" : "0x020000",
+				"currentGasLimit" : "0x7fffffffffffffff",
+				"currentNumber" : "0x01",
+				"currentTimestamp" : "0x03e8",
+				"previousHash" : "5e20a0453cecd065ea59c37ac63e079ee08998b6045136a8ce6635c7912ec0b6"
+			},
+			"post" : {
+				"Byzantium" : [
+					{
+						"hash" : "0000000000000000000000000000000000000000000000000000000000000000",
+						"indexes" : { "data" : 0, "gas" : 0, "value" : 0 }
+					}
+				],
+				"FrontierToHomesteadAt5" : [
+					{
+						"hash" : "0000000000000000000000000000000000000000000000000000000000000000",
+						"indexes" : { "data" : 0, "gas" : 0, "value" : 0 }
+					}
+				]
+			},
+			"pre" : {
+				"095e7baea6a6c7c4c2dfeb977efac326af552d87" : {
+					"balance" : "0x0de0b6b3a7640000",
+					"code" : "0x600160010160005500",
+					"nonce" : "0x00",
+					"storage" : {
+					}
+				},
+				"a94f5374fce5edbc8e2a8697c15331677e6ebf0b" : {
+					"balance" : "0x0de0b6b3a7640000",
+					"code" : "0x",
+					"nonce" : "0x00",
+					"storage" : {
+					}
+				}
+			},
+			"transaction" : {
+				"data" : [ "0x" ],
+				"gasLimit" : [ "0x061a80" ],
+				"gasPrice" : "0x01",
+				"nonce" : "0x00",
+				"secretKey" : "45a915e4d060149eb4365960e6a7a45f334393093061116b197e3240065ff2d8",
+				"to" : "095e7baea6a6c7c4c2dfeb977efac326af552d87",
+				"value" : [ "0x0186a0" ]
+			}
+		}
+	}"#;
+
+	#[test]
+	fn should_report_outcome_of_each_case() {
+		let results = run_state_tests(TEST.as_bytes(), |_, _| true).unwrap();
+		assert_eq!(results.len(), 2);
+
+		let byzantium = results.iter().find(|r| r.fork == ForkSpec::Byzantium).unwrap();
+		assert_eq!(byzantium.name, "add11");
+		assert!(byzantium.is_failure());
+		match byzantium.outcome {
+			StateTestOutcome::Failed { ref expected, ref actual, .. } => assert!(expected != actual),
+			ref other => panic!("Unexpected outcome {:?}", other),
+		}
+
+		let unsupported = results.iter().find(|r| r.fork == ForkSpec::FrontierToHomesteadAt5).unwrap();
+		assert_eq!(unsupported.outcome, StateTestOutcome::Skipped);
+	}
+
+	#[test]
+	fn should_filter_tests() {
+		let results = run_state_tests(TEST.as_bytes(), |_, fork| *fork == ForkSpec::Byzantium).unwrap();
+		assert_eq!(results.len(), 1);
+		assert!(run_state_tests(b"{", |_, _| true).is_err());
+	}
+}
@@ -20,6 +20,7 @@ use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering as AtomicOrder};
 use std::sync::Arc;
 use std::collections::{HashMap, BTreeMap};
 use std::mem;
+use std::net::IpAddr;
 use itertools::Itertools;
 use rustc_hex::FromHex;
 use hash::keccak;
@@ -52,19 +53,23 @@ use vm::Schedule;
 use miner::{self, Miner, MinerService};
 use spec::Spec;
 use types::basic_account::BasicAccount;
+use types::state_diff::StateDiff;
+use witness::BlockWitness;
 use types::pruning_info::PruningInfo;
+use types::verification_queue_info::ImportQueueStatus;
+use types::transaction_lifecycle::TransactionLifecycle;
 use verification::queue::QueueInfo;
 use verification::queue::kind::blocks::Unverified;
 use block::{OpenBlock, SealedBlock, ClosedBlock};
 use executive::Executed;
 use error::CallError;
-use trace::LocalizedTrace;
+use trace::{FlatTrace, LocalizedTrace, StepTracer, StructLog, StructLogOptions, VMTracer};
 use state_db::StateDB;
 use header::Header;
 use encoded;
 use engines::EthEngine;
 use ethtrie;
-use state::StateInfo;
+use state::{StateInfo, StateOverride};
 use views::BlockView;
 
 /// Test client.
@@ -91,6 +96,8 @@ pub struct TestBlockChainClient {
 	pub code: RwLock<HashMap<Address, Bytes>>,
 	/// Execution result.
 	pub execution_result: RwLock<Option<Result<Executed, CallError>>>,
+	/// Struct logging execution result.
+	pub struct_logs_result: RwLock<Option<Result<Executed<FlatTrace, Vec<StructLog>>, CallError>>>,
 	/// Transaction receipts.
 	pub receipts: RwLock<HashMap<TransactionId, LocalizedReceipt>>,
 	/// Logs
@@ -115,6 +122,10 @@ pub struct TestBlockChainClient {
 	pub history: RwLock<Option<u64>>,
 	/// Is disabled
 	pub disabled: AtomicBool,
+	/// Is block import paused
+	pub import_paused: AtomicBool,
+	/// Recorded transaction lifecycles.
+	pub lifecycles: RwLock<HashMap<H256, TransactionLifecycle>>,
 }
 
 /// Used for generating test client blocks.
@@ -170,6 +181,7 @@ impl TestBlockChainClient {
 			storage: RwLock::new(HashMap::new()),
 			code: RwLock::new(HashMap::new()),
 			execution_result: RwLock::new(None),
+			struct_logs_result: RwLock::new(None),
 			receipts: RwLock::new(HashMap::new()),
 			logs: RwLock::new(Vec::new()),
 			queue_size: AtomicUsize::new(0),
@@ -181,6 +193,8 @@ impl TestBlockChainClient {
 			traces: RwLock::new(None),
 			history: RwLock::new(None),
 			disabled: AtomicBool::new(false),
+			import_paused: AtomicBool::new(false),
+			lifecycles: RwLock::new(HashMap::new()),
 			error_on_logs: RwLock::new(None),
 		};
 
@@ -202,6 +216,11 @@ impl TestBlockChainClient {
 		*self.execution_result.write() = Some(result);
 	}
 
+	/// Set the struct logging execution result.
+	pub fn set_struct_logs_result(&self, result: Result<Executed<FlatTrace, Vec<StructLog>>, CallError>) {
+		*self.struct_logs_result.write() = Some(result);
+	}
+
 	/// Set the balance of account `address` to `balance`.
 	pub fn set_balance(&self, address: Address, balance: U256) {
 		self.balances.write().insert(address, balance);
@@ -290,7 +309,7 @@ impl TestBlockChainClient {
 			rlp.append(&header);
 			rlp.append_raw(&txs, 1);
 			rlp.append_raw(uncles.as_raw(), 1);
-			let unverified = Unverified::from_rlp(rlp.out()).unwrap();
+			let unverified = Unverified::from_rlp(rlp.out(), self.spec.engine.params().eip1559_transition).unwrap();
 			self.import_block(unverified).unwrap();
 		}
 	}
@@ -298,7 +317,7 @@ impl TestBlockChainClient {
 	/// Make a bad block by setting invalid extra data.
 	pub fn corrupt_block(&self, n: BlockNumber) {
 		let hash = self.block_hash(BlockId::Number(n)).unwrap();
-		let mut header: BlockHeader = self.block_header(BlockId::Number(n)).unwrap().decode().expect("decoding failed");
+		let mut header: BlockHeader = self.block_header(BlockId::Number(n)).unwrap().decode(self.spec.engine.params().eip1559_transition).expect("decoding failed");
 		header.set_extra_data(b"This extra data is way too long to be considered valid".to_vec());
 		let mut rlp = RlpStream::new_list(3);
 		rlp.append(&header);
@@ -310,7 +329,7 @@ impl TestBlockChainClient {
 	/// Make a bad block by setting invalid parent hash.
 	pub fn corrupt_block_parent(&self, n: BlockNumber) {
 		let hash = self.block_hash(BlockId::Number(n)).unwrap();
-		let mut header: BlockHeader = self.block_header(BlockId::Number(n)).unwrap().decode().expect("decoding failed");
+		let mut header: BlockHeader = self.block_header(BlockId::Number(n)).unwrap().decode(self.spec.engine.params().eip1559_transition).expect("decoding failed");
 		header.set_parent_hash(H256::from(42));
 		let mut rlp = RlpStream::new_list(3);
 		rlp.append(&header);
@@ -488,7 +507,7 @@ impl BlockInfo for TestBlockChainClient {
 	fn best_block_header(&self) -> Header {
 		self.block_header(BlockId::Hash(self.chain_info().best_block_hash))
 			.expect("Best block always has header.")
-			.decode()
+			.decode(self.spec.engine.params().eip1559_transition)
 			.expect("decoding failed")
 	}
 
@@ -533,7 +552,7 @@ impl ImportBlock for TestBlockChainClient {
 		if number > 0 {
 			match self.blocks.read().get(header.parent_hash()) {
 				Some(parent) => {
-					let parent = view!(BlockView, parent).header();
+					let parent = view!(BlockView, parent).header_view();
 					if parent.number() != (header.number() - 1) {
 						panic!("Unexpected block parent");
 					}
@@ -558,7 +577,7 @@ impl ImportBlock for TestBlockChainClient {
 				while n > 0 && self.numbers.read()[&n] != parent_hash {
 					*self.numbers.write().get_mut(&n).unwrap() = parent_hash.clone();
 					n -= 1;
-					parent_hash = view!(BlockView, &self.blocks.read()[&parent_hash]).header().parent_hash().clone();
+					parent_hash = view!(BlockView, &self.blocks.read()[&parent_hash]).header_view().parent_hash();
 				}
 			}
 		}
@@ -588,6 +607,26 @@ impl Call for TestBlockChainClient {
 	fn estimate_gas(&self, _t: &SignedTransaction, _state: &Self::State, _header: &Header) -> Result<U256, CallError> {
 		Ok(21000.into())
 	}
+
+	fn call_struct_logs(&self, _t: &SignedTransaction, _options: StructLogOptions, _state: &mut Self::State, _header: &Header) -> Result<Executed<FlatTrace, Vec<StructLog>>, CallError> {
+		self.struct_logs_result.read().clone().unwrap()
+	}
+
+	fn call_stepped(&self, _t: &SignedTransaction, tracer: StepTracer, _state: &mut Self::State, _header: &Header) -> Result<Executed<FlatTrace, Vec<StructLog>>, CallError> {
+		// PUSH1 0x2a, STOP
+		let mut tracer = tracer.prepare_subtrace(&[]);
+		tracer.trace_next_instruction(0, 0x60, 100.into());
+		tracer.trace_prepare_execute(0, 0x60, 3.into());
+		tracer.trace_executed(97.into(), &[0x2a.into()], None, None);
+		tracer.trace_next_instruction(2, 0x00, 97.into());
+		tracer.trace_prepare_execute(2, 0x00, 0.into());
+		tracer.trace_executed(97.into(), &[], None, None);
+		self.struct_logs_result.read().clone().unwrap()
+	}
+
+	fn apply_state_override(&self, _state: &mut Self::State, _overrides: &StateOverride) -> Result<(), CallError> {
+		Ok(())
+	}
 }
 
 impl StateInfo for () {
@@ -612,7 +651,7 @@ impl StateClient for TestBlockChainClient {
 
 impl EngineInfo for TestBlockChainClient {
 	fn engine(&self) -> &EthEngine {
-		unimplemented!()
+		&*self.spec.engine
 	}
 }
 
@@ -638,6 +677,22 @@ impl BlockChainClient for TestBlockChainClient {
 		Ok(Box::new(self.traces.read().clone().unwrap().into_iter().map(|t| t.transaction_hash.unwrap_or(H256::new())).zip(self.execution_result.read().clone().unwrap().into_iter())))
 	}
 
+	fn replay_struct_logs(&self, _id: TransactionId, _options: StructLogOptions) -> Result<Executed<FlatTrace, Vec<StructLog>>, CallError> {
+		self.struct_logs_result.read().clone().unwrap()
+	}
+
+	fn call_stepped(&self, _t: &SignedTransaction, tracer: StepTracer, _state: &mut Self::State, _header: &Header) -> Result<Executed<FlatTrace, Vec<StructLog>>, CallError> {
+		// PUSH1 0x2a, STOP
+		let mut tracer = tracer.prepare_subtrace(&[]);
+		tracer.trace_next_instruction(0, 0x60, 100.into());
+		tracer.trace_prepare_execute(0, 0x60, 3.into());
+		tracer.trace_executed(97.into(), &[0x2a.into()], None, None);
+		tracer.trace_next_instruction(2, 0x00, 97.into());
+		tracer.trace_prepare_execute(2, 0x00, 0.into());
+		tracer.trace_executed(97.into(), &[], None, None);
+		self.struct_logs_result.read().clone().unwrap()
+	}
+
 	fn block_total_difficulty(&self, _id: BlockId) -> Option<U256> {
 		Some(U256::zero())
 	}
@@ -671,6 +726,15 @@ impl BlockChainClient for TestBlockChainClient {
 	fn list_storage(&self, _id: BlockId, _account: &Address, _after: Option<&H256>, _count: u64) -> Option<Vec<H256>> {
 		None
 	}
+
+	fn state_diff_between(&self, _from: BlockId, _to: BlockId) -> Option<StateDiff> {
+		None
+	}
+
+	fn block_witness(&self, _id: BlockId) -> Option<BlockWitness> {
+		None
+	}
+
 	fn transaction(&self, _id: TransactionId) -> Option<LocalizedTransaction> {
 		None	// Simple default.
 	}
@@ -731,7 +795,7 @@ impl BlockChainClient for TestBlockChainClient {
 
 	fn block_extra_info(&self, id: BlockId) -> Option<BTreeMap<String, String>> {
 		self.block(id)
-			.map(|block| block.view().header())
+			.map(|block| block.view().header(self.spec.engine.params().eip1559_transition))
 			.map(|header| self.spec.engine.extra_info(&header))
 	}
 
@@ -842,6 +906,10 @@ impl BlockChainClient for TestBlockChainClient {
 		self.miner.ready_transactions(self, 4096, miner::PendingOrdering::Priority)
 	}
 
+	fn transactions_to_rebroadcast(&self) -> Vec<H256> {
+		self.miner.transactions_to_rebroadcast()
+	}
+
 	fn signing_chain_id(&self) -> Option<u64> { None }
 
 	fn mode(&self) -> Mode { Mode::Active }
@@ -854,6 +922,24 @@ impl BlockChainClient for TestBlockChainClient {
 
 	fn disable(&self) { self.disabled.store(true, AtomicOrder::Relaxed); }
 
+	fn set_import_paused(&self, paused: bool) { self.import_paused.store(paused, AtomicOrder::Relaxed); }
+
+	fn is_import_paused(&self) -> bool { self.import_paused.load(AtomicOrder::Relaxed) }
+
+	fn import_queue_status(&self) -> ImportQueueStatus {
+		ImportQueueStatus {
+			info: self.queue_info(),
+			items: Vec::new(),
+			executing: None,
+		}
+	}
+
+	fn transaction_lifecycle(&self, hash: &H256) -> Option<TransactionLifecycle> {
+		self.lifecycles.read().get(hash).cloned()
+	}
+
+	fn transactions_propagated(&self, _propagated: &[(H256, usize)]) {}
+
 	fn pruning_info(&self) -> PruningInfo {
 		let best_num = self.chain_info().best_block_number;
 		PruningInfo {
@@ -881,7 +967,7 @@ impl BlockChainClient for TestBlockChainClient {
 }
 
 impl IoClient for TestBlockChainClient {
-	fn queue_transactions(&self, transactions: Vec<Bytes>, _peer_id: usize) {
+	fn queue_transactions(&self, transactions: Vec<Bytes>, _peer_id: usize, _origin: Option<IpAddr>) {
 		// import right here
 		let txs = transactions.into_iter().filter_map(|bytes| Rlp::new(&bytes).as_val().ok()).collect();
 		self.miner.import_external_transactions(self, txs);
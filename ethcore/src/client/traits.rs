@@ -15,6 +15,7 @@
 // along with Parity.  If not, see <http://www.gnu.org/licenses/>.
 
 use std::collections::BTreeMap;
+use std::net::IpAddr;
 use std::sync::Arc;
 
 use itertools::Itertools;
@@ -31,11 +32,11 @@ use filter::Filter;
 use header::{BlockNumber};
 use log_entry::LocalizedLogEntry;
 use receipt::LocalizedReceipt;
-use trace::LocalizedTrace;
+use trace::{FlatTrace, LocalizedTrace, StepTracer, StructLog, StructLogOptions};
 use transaction::{self, LocalizedTransaction, SignedTransaction};
 use verification::queue::QueueInfo as BlockQueueInfo;
 use verification::queue::kind::blocks::Unverified;
-use state::StateInfo;
+use state::{StateInfo, StateOverride};
 use header::Header;
 use engines::EthEngine;
 
@@ -46,11 +47,15 @@ use hashdb::DBValue;
 
 use types::ids::*;
 use types::basic_account::BasicAccount;
+use types::state_diff::StateDiff;
+use witness::BlockWitness;
 use types::trace_filter::Filter as TraceFilter;
 use types::call_analytics::CallAnalytics;
 use types::blockchain_info::BlockChainInfo;
 use types::block_status::BlockStatus;
 use types::pruning_info::PruningInfo;
+use types::verification_queue_info::ImportQueueStatus;
+use types::transaction_lifecycle::TransactionLifecycle;
 
 /// State information to be used during client query
 pub enum StateOrBlock {
@@ -153,6 +158,12 @@ pub trait StateClient {
 	/// Otherwise, this can fail (but may not) if the DB prunes state or the block
 	/// is unknown.
 	fn state_at(&self, id: BlockId) -> Option<Self::State>;
+
+	/// Attempt to get a copy of a specific block's final state, reconstructing
+	/// it from the local snapshot if it was pruned and reconstruction is enabled.
+	fn reconstructed_state_at(&self, id: BlockId) -> Result<Self::State, CallError> {
+		self.state_at(id).ok_or(CallError::StatePruned)
+	}
 }
 
 /// Provides various blockchain information, like block header, chain state etc.
@@ -169,6 +180,12 @@ pub trait RegistryInfo {
 pub trait ImportBlock {
 	/// Import a block into the blockchain.
 	fn import_block(&self, block: Unverified) -> EthcoreResult<H256>;
+
+	/// Import a block and wait until the verification queue has been processed,
+	/// so that the outcome of the import is reflected in `block_status` on return.
+	fn import_block_sync(&self, block: Unverified) -> EthcoreResult<H256> {
+		self.import_block(block)
+	}
 }
 
 /// Provides `call_contract` method
@@ -191,6 +208,16 @@ pub trait Call {
 
 	/// Estimates how much gas will be necessary for a call.
 	fn estimate_gas(&self, t: &SignedTransaction, state: &Self::State, header: &Header) -> Result<U256, CallError>;
+
+	/// Makes a non-persistent transaction call, recording an opcode-level struct log.
+	fn call_struct_logs(&self, tx: &SignedTransaction, options: StructLogOptions, state: &mut Self::State, header: &Header) -> Result<Executed<FlatTrace, Vec<StructLog>>, CallError>;
+
+	/// Makes a non-persistent transaction call, pausing before every instruction.
+	/// See `DebugSession` for driving the execution.
+	fn call_stepped(&self, tx: &SignedTransaction, tracer: StepTracer, state: &mut Self::State, header: &Header) -> Result<Executed<FlatTrace, Vec<StructLog>>, CallError>;
+
+	/// Applies temporary account overrides to the given state, e.g. before a call.
+	fn apply_state_override(&self, state: &mut Self::State, overrides: &StateOverride) -> Result<(), CallError>;
 }
 
 /// Provides `engine` method
@@ -201,8 +228,9 @@ pub trait EngineInfo {
 
 /// IO operations that should off-load heavy work to another thread.
 pub trait IoClient: Sync + Send {
-	/// Queue transactions for importing.
-	fn queue_transactions(&self, transactions: Vec<Bytes>, peer_id: usize);
+	/// Queue transactions received from given peer for importing.
+	/// The peer's IP address, if known, is used to enforce per-origin queue quotas.
+	fn queue_transactions(&self, transactions: Vec<Bytes>, peer_id: usize, origin: Option<IpAddr>);
 
 	/// Queue block import with transaction receipts. Does no sealing and transaction validation.
 	fn queue_ancient_block(&self, block_bytes: Unverified, receipts_bytes: Bytes) -> EthcoreResult<H256>;
@@ -272,6 +300,14 @@ pub trait BlockChainClient : Sync + Send + AccountData + BlockChain + CallContra
 	/// If `after` is set the list starts with the following item.
 	fn list_storage(&self, id: BlockId, account: &Address, after: Option<&H256>, count: u64) -> Option<Vec<H256>>;
 
+	/// Get the difference between states after blocks `from` and `to`, if fat DB is in operation, otherwise `None`.
+	/// Only the parts of the state which differ are read from the database.
+	fn state_diff_between(&self, from: BlockId, to: BlockId) -> Option<StateDiff>;
+
+	/// Re-execute the block, producing a witness which allows to execute it without the state database.
+	/// Returns `None` if the block is unknown or the parent state is not available.
+	fn block_witness(&self, id: BlockId) -> Option<BlockWitness>;
+
 	/// Get transaction with given hash.
 	fn transaction(&self, id: TransactionId) -> Option<LocalizedTransaction>;
 
@@ -315,6 +351,9 @@ pub trait BlockChainClient : Sync + Send + AccountData + BlockChain + CallContra
 	/// Replays all the transactions in a given block for inspection.
 	fn replay_block_transactions(&self, block: BlockId, analytics: CallAnalytics) -> Result<Box<Iterator<Item = (H256, Executed)>>, CallError>;
 
+	/// Replays a given transaction, recording an opcode-level struct log.
+	fn replay_struct_logs(&self, t: TransactionId, options: StructLogOptions) -> Result<Executed<FlatTrace, Vec<StructLog>>, CallError>;
+
 	/// Returns traces matching given filter.
 	fn filter_traces(&self, filter: TraceFilter) -> Option<Vec<LocalizedTrace>>;
 
@@ -333,6 +372,9 @@ pub trait BlockChainClient : Sync + Send + AccountData + BlockChain + CallContra
 	/// List all ready transactions that should be propagated to other peers.
 	fn transactions_to_propagate(&self) -> Vec<Arc<VerifiedTransaction>>;
 
+	/// List hashes of stuck local transactions which should be sent again to peers that already got them.
+	fn transactions_to_rebroadcast(&self) -> Vec<H256>;
+
 	/// Sorted list of transaction gas prices from at least last sample_size blocks.
 	fn gas_price_corpus(&self, sample_size: usize) -> ::stats::Corpus<U256> {
 		let mut h = self.chain_info().best_block_hash;
@@ -373,6 +415,23 @@ pub trait BlockChainClient : Sync + Send + AccountData + BlockChain + CallContra
 	/// that a subsystem has reason to believe this executable incapable of syncing the chain.
 	fn disable(&self);
 
+	/// Pause or resume block import and sealing, e.g. for a maintenance window.
+	/// Networking is unaffected and incoming blocks keep being queued.
+	fn set_import_paused(&self, paused: bool);
+
+	/// Returns true if block import and sealing are paused.
+	fn is_import_paused(&self) -> bool;
+
+	/// Get detailed import queue status: queued blocks with their stage and wait time,
+	/// and the block currently being executed.
+	fn import_queue_status(&self) -> ImportQueueStatus;
+
+	/// Get everything recorded about a transaction, if the transaction lifecycle log is enabled.
+	fn transaction_lifecycle(&self, hash: &H256) -> Option<TransactionLifecycle>;
+
+	/// Record that transactions were sent to given number of peers.
+	fn transactions_propagated(&self, propagated: &[(H256, usize)]);
+
 	/// Returns engine-related extra info for `BlockId`.
 	fn block_extra_info(&self, id: BlockId) -> Option<BTreeMap<String, String>>;
 
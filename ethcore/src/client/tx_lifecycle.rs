@@ -0,0 +1,265 @@
+// Copyright 2015-2018 Parity Technologies (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Persistent log of what happened to transactions seen by this node.
+//!
+//! Records are kept in a ring buffer in `COL_TX_LIFECYCLE`: each newly seen transaction takes
+//! the next slot, overwriting the oldest record once `capacity` is reached.
+
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use ethcore_miner::pool::TransactionEvent;
+use ethereum_types::H256;
+use kvdb::{DBTransaction, KeyValueDB};
+use parking_lot::Mutex;
+use rlp::{Rlp, RlpStream};
+use types::BlockNumber;
+use types::transaction_lifecycle::{LifecycleEntry, LifecycleEvent, TransactionLifecycle};
+
+use db::COL_TX_LIFECYCLE;
+
+/// Maximal number of events kept for a single transaction.
+const MAX_EVENTS: usize = 64;
+
+#[derive(Default)]
+struct Index {
+	/// Sequence number of the next record.
+	next: u64,
+	/// Slots of transactions currently in the log.
+	slots: HashMap<H256, u64>,
+}
+
+/// Ring buffer of transaction lifecycles backed by the database.
+pub struct TransactionLifecycleLog {
+	db: Arc<KeyValueDB>,
+	capacity: u64,
+	index: Mutex<Index>,
+}
+
+impl TransactionLifecycleLog {
+	/// Open the log keeping at most `capacity` transactions.
+	pub fn new(db: Arc<KeyValueDB>, capacity: usize) -> Self {
+		let log = TransactionLifecycleLog {
+			db,
+			capacity: ::std::cmp::max(capacity, 1) as u64,
+			index: Mutex::new(Index::default()),
+		};
+		log.reload();
+		log
+	}
+
+	/// Rebuild the in-memory index from the database.
+	pub fn reload(&self) {
+		let mut index = Index::default();
+		let mut batch = DBTransaction::new();
+		for (key, value) in self.db.iter(COL_TX_LIFECYCLE) {
+			let record = Rlp::new(&value);
+			let slot = slot_from_key(&key);
+			match (slot, record.val_at::<u64>(0), record.val_at::<TransactionLifecycle>(1)) {
+				(Some(slot), Ok(seq), Ok(lifecycle)) if slot < self.capacity => {
+					index.next = ::std::cmp::max(index.next, seq + 1);
+					index.slots.insert(lifecycle.hash, slot);
+				},
+				_ => batch.delete(COL_TX_LIFECYCLE, &key),
+			}
+		}
+		self.db.write_buffered(batch);
+		*self.index.lock() = index;
+	}
+
+	/// Get everything recorded about given transaction.
+	pub fn get(&self, hash: &H256) -> Option<TransactionLifecycle> {
+		let index = self.index.lock();
+		index.slots.get(hash).and_then(|slot| self.read(*slot)).map(|(_, lifecycle)| lifecycle)
+	}
+
+	/// Record a transaction pool event.
+	pub fn pool_event(&self, hash: &H256, event: TransactionEvent) {
+		let event = match event {
+			TransactionEvent::Added => LifecycleEvent::Accepted,
+			TransactionEvent::Rejected(reason) => LifecycleEvent::Rejected(reason),
+			TransactionEvent::Replaced(by) => LifecycleEvent::Replaced(by),
+			TransactionEvent::Dropped(by) => LifecycleEvent::Dropped(by),
+			TransactionEvent::Invalid => LifecycleEvent::Invalid,
+			TransactionEvent::Canceled => LifecycleEvent::Canceled,
+			// inclusion is recorded separately, together with the block.
+			TransactionEvent::Culled | TransactionEvent::Mined => LifecycleEvent::Culled,
+			TransactionEvent::Demoted(gas_price) => LifecycleEvent::Demoted(gas_price),
+		};
+		self.record(hash, event);
+	}
+
+	/// Record that transactions were sent to given number of peers.
+	pub fn propagated(&self, propagated: &[(H256, usize)]) {
+		for &(ref hash, peers) in propagated {
+			self.record(hash, LifecycleEvent::Propagated(peers as u64));
+		}
+	}
+
+	/// Record that transactions were included in an imported block.
+	pub fn included(&self, hashes: &[H256], number: BlockNumber, block_hash: &H256) {
+		for hash in hashes {
+			self.record(hash, LifecycleEvent::Included(number, *block_hash));
+		}
+	}
+
+	/// Append an event to the transaction's record.
+	///
+	/// Only acceptance or rejection starts a new record; other events are ignored for
+	/// transactions which are not in the log.
+	fn record(&self, hash: &H256, event: LifecycleEvent) {
+		let entry = LifecycleEntry {
+			timestamp: SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or(0),
+			event,
+		};
+
+		let mut index = self.index.lock();
+		let existing = index.slots.get(hash).and_then(|slot| self.read(*slot).map(|record| (*slot, record)));
+		let (slot, seq, lifecycle) = match existing {
+			Some((slot, (seq, mut lifecycle))) => {
+				push_entry(&mut lifecycle.events, entry);
+				(slot, seq, lifecycle)
+			},
+			None => match entry.event {
+				LifecycleEvent::Accepted | LifecycleEvent::Rejected(_) => {
+					let seq = index.next;
+					let slot = seq % self.capacity;
+					index.next += 1;
+					if let Some((_, old)) = self.read(slot) {
+						index.slots.remove(&old.hash);
+					}
+					index.slots.insert(*hash, slot);
+					(slot, seq, TransactionLifecycle { hash: *hash, events: vec![entry] })
+				},
+				_ => return,
+			},
+		};
+
+		let mut record = RlpStream::new_list(2);
+		record.append(&seq);
+		record.append(&lifecycle);
+		let mut batch = DBTransaction::new();
+		batch.put(COL_TX_LIFECYCLE, &slot_key(slot), &record.out());
+		self.db.write_buffered(batch);
+	}
+
+	fn read(&self, slot: u64) -> Option<(u64, TransactionLifecycle)> {
+		let value = self.db.get(COL_TX_LIFECYCLE, &slot_key(slot)).expect("Low level database error. Some issue with disk?")?;
+		let record = Rlp::new(&value);
+		match (record.val_at(0), record.val_at(1)) {
+			(Ok(seq), Ok(lifecycle)) => Some((seq, lifecycle)),
+			_ => None,
+		}
+	}
+}
+
+/// Append an entry, merging consecutive propagations into a single total.
+fn push_entry(events: &mut Vec<LifecycleEntry>, entry: LifecycleEntry) {
+	if let LifecycleEvent::Propagated(peers) = entry.event {
+		if let Some(&mut LifecycleEntry { event: LifecycleEvent::Propagated(ref mut total), .. }) = events.last_mut() {
+			*total += peers;
+			return;
+		}
+	}
+
+	if events.len() < MAX_EVENTS {
+		events.push(entry);
+	}
+}
+
+fn slot_key(slot: u64) -> [u8; 8] {
+	let mut key = [0u8; 8];
+	for (i, byte) in key.iter_mut().enumerate() {
+		*byte = (slot >> (8 * (7 - i))) as u8;
+	}
+	key
+}
+
+fn slot_from_key(key: &[u8]) -> Option<u64> {
+	if key.len() != 8 {
+		return None;
+	}
+	Some(key.iter().fold(0u64, |acc, byte| (acc << 8) | *byte as u64))
+}
+
+#[cfg(test)]
+mod tests {
+	use std::sync::Arc;
+	use ethcore_miner::pool::TransactionEvent;
+	use kvdb_memorydb;
+	use types::transaction_lifecycle::LifecycleEvent;
+	use super::TransactionLifecycleLog;
+
+	fn new_log(capacity: usize) -> TransactionLifecycleLog {
+		let db = Arc::new(kvdb_memorydb::create(::db::NUM_COLUMNS.unwrap()));
+		TransactionLifecycleLog::new(db, capacity)
+	}
+
+	fn events(log: &TransactionLifecycleLog, hash: u64) -> Option<Vec<LifecycleEvent>> {
+		log.get(&hash.into()).map(|lifecycle| lifecycle.events.into_iter().map(|e| e.event).collect())
+	}
+
+	#[test]
+	fn records_transaction_lifecycle() {
+		let log = new_log(16);
+
+		log.pool_event(&1.into(), TransactionEvent::Added);
+		log.propagated(&[(1.into(), 3)]);
+		log.propagated(&[(1.into(), 2)]);
+		log.included(&[1.into(), 2.into()], 10, &5.into());
+		log.pool_event(&1.into(), TransactionEvent::Culled);
+
+		assert_eq!(events(&log, 1), Some(vec![
+			LifecycleEvent::Accepted,
+			LifecycleEvent::Propagated(5),
+			LifecycleEvent::Included(10, 5.into()),
+			LifecycleEvent::Culled,
+		]));
+		assert_eq!(events(&log, 2), None);
+	}
+
+	#[test]
+	fn overwrites_oldest_records() {
+		let log = new_log(2);
+
+		log.pool_event(&1.into(), TransactionEvent::Added);
+		log.pool_event(&2.into(), TransactionEvent::Rejected("too cheap".into()));
+		log.pool_event(&3.into(), TransactionEvent::Added);
+
+		assert_eq!(events(&log, 1), None);
+		assert_eq!(events(&log, 2), Some(vec![LifecycleEvent::Rejected("too cheap".into())]));
+		assert_eq!(events(&log, 3), Some(vec![LifecycleEvent::Accepted]));
+	}
+
+	#[test]
+	fn restores_index_from_database() {
+		let db = Arc::new(kvdb_memorydb::create(::db::NUM_COLUMNS.unwrap()));
+		{
+			let log = TransactionLifecycleLog::new(db.clone(), 2);
+			log.pool_event(&1.into(), TransactionEvent::Added);
+			log.pool_event(&2.into(), TransactionEvent::Added);
+		}
+
+		let log = TransactionLifecycleLog::new(db, 2);
+		log.pool_event(&3.into(), TransactionEvent::Added);
+
+		assert_eq!(events(&log, 1), None);
+		assert_eq!(events(&log, 2), Some(vec![LifecycleEvent::Accepted]));
+		assert_eq!(events(&log, 3), Some(vec![LifecycleEvent::Accepted]));
+	}
+}
@@ -47,8 +47,8 @@ impl Header {
 	pub fn new(encoded: Vec<u8>) -> Self { Header(encoded) }
 
 	/// Upgrade this encoded view to a fully owned `Header` object.
-	pub fn decode(&self) -> Result<FullHeader, rlp::DecoderError> {
-		rlp::decode(&self.0)
+	pub fn decode(&self, eip1559_transition: BlockNumber) -> Result<FullHeader, rlp::DecoderError> {
+		FullHeader::decode_rlp(&self.rlp(), eip1559_transition)
 	}
 
 	/// Get a borrowed header view onto the data.
@@ -129,8 +129,8 @@ impl Body {
 	pub fn view(&self) -> BodyView { view!(BodyView, &self.0) }
 
 	/// Fully decode this block body.
-	pub fn decode(&self) -> (Vec<UnverifiedTransaction>, Vec<FullHeader>) {
-		(self.view().transactions(), self.view().uncles())
+	pub fn decode(&self, eip1559_transition: BlockNumber) -> (Vec<UnverifiedTransaction>, Vec<FullHeader>) {
+		(self.view().transactions(), self.view().uncles(eip1559_transition))
 	}
 
 	/// Get the RLP of this block body.
@@ -164,7 +164,7 @@ impl Body {
 	pub fn uncles_rlp(&self) -> Rlp { self.view().uncles_rlp().rlp }
 
 	/// Decode uncle headers.
-	pub fn uncles(&self) -> Vec<FullHeader> { self.view().uncles() }
+	pub fn uncles(&self, eip1559_transition: BlockNumber) -> Vec<FullHeader> { self.view().uncles(eip1559_transition) }
 
 	/// Number of uncles.
 	pub fn uncles_count(&self) -> usize { self.view().uncles_count() }
@@ -206,10 +206,12 @@ impl Block {
 	pub fn header_view(&self) -> HeaderView { self.view().header_view() }
 
 	/// Decode to a full block.
-	pub fn decode(&self) -> Result<FullBlock, rlp::DecoderError> { rlp::decode(&self.0) }
+	pub fn decode(&self, eip1559_transition: BlockNumber) -> Result<FullBlock, rlp::DecoderError> {
+		FullBlock::decode_rlp(&self.rlp(), eip1559_transition)
+	}
 
 	/// Decode the header.
-	pub fn decode_header(&self) -> FullHeader { self.view().rlp().val_at(0) }
+	pub fn decode_header(&self, eip1559_transition: BlockNumber) -> FullHeader { self.view().header(eip1559_transition) }
 
 	/// Clone the encoded header.
 	pub fn header(&self) -> Header { Header(self.view().rlp().at(0).as_raw().to_vec()) }
@@ -292,7 +294,7 @@ impl Block {
 	pub fn transaction_hashes(&self) -> Vec<H256> { self.view().transaction_hashes() }
 
 	/// Decode uncle headers.
-	pub fn uncles(&self) -> Vec<FullHeader> { self.view().uncles() }
+	pub fn uncles(&self, eip1559_transition: BlockNumber) -> Vec<FullHeader> { self.view().uncles(eip1559_transition) }
 
 	/// Number of uncles.
 	pub fn uncles_count(&self) -> usize { self.view().uncles_count() }
@@ -431,7 +431,11 @@ impl super::EpochVerifier<EthereumMachine> for EpochVerifier {
 		let mut finality_checker = RollingFinality::blank(self.subchain_validators.clone().into_inner());
 		let mut finalized = Vec::new();
 
-		let headers: Vec<Header> = Rlp::new(proof).as_list().ok()?;
+		let eip1559_transition = self.machine.params().eip1559_transition;
+		let headers = Rlp::new(proof).iter()
+			.map(|r| Header::decode_rlp(&r, eip1559_transition))
+			.collect::<Result<Vec<_>, _>>()
+			.ok()?;
 
 		{
 			let mut push_header = |parent_header: &Header, header: Option<&Header>| {
@@ -1063,7 +1067,7 @@ impl Engine<EthereumMachine> for AuthorityRound {
 
 				let parent = client.block_header(::client::BlockId::Hash(*block.header().parent_hash()))
 					.expect("hash is from parent; parent header must exist; qed")
-					.decode()?;
+					.decode(self.machine.params().eip1559_transition)?;
 
 				let parent_step = header_step(&parent, self.empty_steps_transition)?;
 				let current_step = self.step.inner.load();
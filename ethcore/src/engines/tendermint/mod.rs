@@ -143,7 +143,7 @@ impl <F> super::EpochVerifier<EthereumMachine> for EpochVerifier<F>
 	}
 
 	fn check_finality_proof(&self, proof: &[u8]) -> Option<Vec<H256>> {
-		match ::rlp::decode(proof) {
+		match Header::decode_rlp(&Rlp::new(proof), self.machine.params().eip1559_transition) {
 			Ok(header) => self.verify_light(&header).ok().map(|_| vec![header.hash()]),
 			Err(_) => None
 		}
@@ -77,6 +77,8 @@ pub enum BlockError {
 	InvalidSeal,
 	/// Gas limit header field is invalid.
 	InvalidGasLimit(OutOfBounds<U256>),
+	/// Base fee header field is invalid.
+	InvalidBaseFee(Mismatch<Option<U256>>),
 	/// Receipts trie root header field is invalid.
 	InvalidReceiptsRoot(Mismatch<H256>),
 	/// Timestamp header field is invalid.
@@ -123,6 +125,7 @@ impl fmt::Display for BlockError {
 			InvalidProofOfWork(ref oob) => format!("Block has invalid PoW: {}", oob),
 			InvalidSeal => "Block has invalid seal.".into(),
 			InvalidGasLimit(ref oob) => format!("Invalid gas limit: {}", oob),
+			InvalidBaseFee(ref mis) => format!("Invalid base fee: expected {:?}, found {:?}", mis.expected, mis.found),
 			InvalidReceiptsRoot(ref mis) => format!("Invalid receipts trie root in header: {}", mis),
 			InvalidTimestamp(ref oob) => {
 				let oob = oob.map(|st| st.elapsed().unwrap_or_default().as_secs());
@@ -192,6 +195,12 @@ error_chain! {
 			description("block known to be bad")
 			display("block known to be bad")
 		}
+
+		#[doc = "Block import is paused for maintenance."]
+		Paused {
+			description("block import is paused")
+			display("block import is paused")
+		}
 	}
 }
 
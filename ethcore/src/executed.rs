@@ -26,6 +26,11 @@ use state_diff::StateDiff;
 
 use std::{fmt, error};
 
+use ethabi::{self, ParamType, Token};
+
+/// Selector of the `Error(string)` function, used by Solidity to encode revert reasons.
+const REVERT_REASON_SELECTOR: [u8; 4] = [0x08, 0xc3, 0x79, 0xa0];
+
 /// Transaction execution receipt.
 #[derive(Debug, PartialEq, Clone)]
 pub struct Executed<T = FlatTrace, V = VMTrace> {
@@ -107,6 +112,14 @@ pub enum ExecutionError {
 		/// Actual balance.
 		got: U512
 	},
+	/// Returned when the max fee per gas of the transaction is lower than
+	/// the base fee of the block.
+	GasPriceLowerThanBaseFee {
+		/// Max fee per gas of the transaction.
+		gas_price: U256,
+		/// Base fee of the block.
+		base_fee: U256,
+	},
 	/// When execution tries to modify the state in static context
 	MutableCallInStaticContext,
 	/// Returned when transacting from a non-existing account with dust protection enabled.
@@ -143,6 +156,8 @@ impl fmt::Display for ExecutionError {
 			NotEnoughCash { ref required, ref got } =>
 				format!("Cost of transaction exceeds sender balance. {} is required \
 					but the sender only has {}", required, got),
+			GasPriceLowerThanBaseFee { ref gas_price, ref base_fee } =>
+				format!("Max fee per gas {} is lower than the block base fee {}", gas_price, base_fee),
 			MutableCallInStaticContext => "Mutable Call in static context".to_owned(),
 			SenderMustExist => "Transacting from an empty account".to_owned(),
 			Internal(ref msg) => msg.clone(),
@@ -166,12 +181,17 @@ pub enum CallError {
 	TransactionNotFound,
 	/// Couldn't find requested block's state in the chain.
 	StatePruned,
+	/// Requested block's state was pruned and can't be reconstructed from the local snapshot.
+	/// Contains the range of blocks whose state can be reconstructed, if the state of a local snapshot is restored.
+	StateNotReconstructible(Option<(u64, u64)>),
 	/// Couldn't find an amount of gas that didn't result in an exception.
 	Exceptional,
 	/// Corrupt state.
 	StateCorrupt,
 	/// Error executing.
 	Execution(ExecutionError),
+	/// The call reverted, with given output.
+	Reverted(Bytes),
 }
 
 impl From<ExecutionError> for CallError {
@@ -187,9 +207,15 @@ impl fmt::Display for CallError {
 		let msg = match *self {
 			TransactionNotFound => "Transaction couldn't be found in the chain".into(),
 			StatePruned => "Couldn't find the transaction block's state in the chain".into(),
+			StateNotReconstructible(Some((from, to))) => format!("State is pruned and can only be reconstructed for blocks #{} to #{}", from, to),
+			StateNotReconstructible(None) => "State is pruned and there is no restored local snapshot to reconstruct it from (yet)".into(),
 			Exceptional => "An exception happened in the execution".into(),
 			StateCorrupt => "Stored state found to be corrupted.".into(),
 			Execution(ref e) => format!("{}", e),
+			Reverted(ref output) => match revert_reason(output) {
+				Some(reason) => format!("Execution reverted: {}", reason),
+				None => "Execution reverted".into(),
+			},
 		};
 
 		f.write_fmt(format_args!("Transaction execution error ({}).", msg))
@@ -198,3 +224,34 @@ impl fmt::Display for CallError {
 
 /// Transaction execution result.
 pub type ExecutionResult = Result<Executed, ExecutionError>;
+
+/// Decodes the reason string of a Solidity `revert("...")` or `require(..., "...")` from the return data.
+pub fn revert_reason(output: &[u8]) -> Option<String> {
+	if output.len() < 4 || output[..4] != REVERT_REASON_SELECTOR {
+		return None;
+	}
+
+	match ethabi::decode(&[ParamType::String], &output[4..]).ok()?.pop() {
+		Some(Token::String(reason)) => Some(reason),
+		_ => None,
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use rustc_hex::FromHex;
+	use super::revert_reason;
+
+	#[test]
+	fn should_decode_revert_reason() {
+		let output: Vec<u8> = "08c379a0\
+			0000000000000000000000000000000000000000000000000000000000000020\
+			000000000000000000000000000000000000000000000000000000000000000e\
+			4e6f7420617574686f72697a6564000000000000000000000000000000000000".from_hex().unwrap();
+
+		assert_eq!(revert_reason(&output), Some("Not authorized".into()));
+		assert_eq!(revert_reason(&output[..4]), None);
+		assert_eq!(revert_reason(&[]), None);
+		assert_eq!(revert_reason(&[0xde, 0xad, 0xbe, 0xef]), None);
+	}
+}
@@ -153,6 +153,30 @@ impl TransactOptions<trace::NoopTracer, trace::ExecutiveVMTracer> {
 	}
 }
 
+impl TransactOptions<trace::NoopTracer, trace::StructLogTracer> {
+	/// Creates new `TransactOptions` with no tracing and opcode-level struct logging.
+	pub fn with_struct_logging(options: trace::StructLogOptions) -> Self {
+		TransactOptions {
+			tracer: trace::NoopTracer,
+			vm_tracer: trace::StructLogTracer::toplevel(options),
+			check_nonce: true,
+			output_from_init_contract: false,
+		}
+	}
+}
+
+impl TransactOptions<trace::NoopTracer, trace::StepTracer> {
+	/// Creates new `TransactOptions` with no tracing, pausing before every instruction.
+	pub fn with_stepping(tracer: trace::StepTracer) -> Self {
+		TransactOptions {
+			tracer: trace::NoopTracer,
+			vm_tracer: tracer,
+			check_nonce: true,
+			output_from_init_contract: false,
+		}
+	}
+}
+
 impl TransactOptions<trace::NoopTracer, trace::NoopVMTracer> {
 	/// Creates new `TransactOptions` without any tracing.
 	pub fn with_no_tracing() -> Self {
@@ -277,10 +301,18 @@ impl<'a, B: 'a + StateBackend> Executive<'a, B> {
 			});
 		}
 
+		// validate if the max fee covers the base fee of the block
+		if let Some(base_fee) = self.info.base_fee {
+			if t.gas_price < base_fee {
+				return Err(ExecutionError::GasPriceLowerThanBaseFee { gas_price: t.gas_price, base_fee });
+			}
+		}
+
 		// TODO: we might need bigints here, or at least check overflows.
 		let balance = self.state.balance(&sender)?;
-		let gas_cost = t.gas.full_mul(t.gas_price);
-		let total_cost = U512::from(t.value) + gas_cost;
+		let gas_price = t.effective_gas_price(self.info.base_fee);
+		let gas_cost = t.gas.full_mul(gas_price);
+		let total_cost = U512::from(t.value) + t.gas.full_mul(t.gas_price);
 
 		// avoid unaffordable transactions
 		let balance512 = U512::from(balance);
@@ -290,6 +322,26 @@ impl<'a, B: 'a + StateBackend> Executive<'a, B> {
 
 		let mut substate = Substate::new();
 
+		// EIP-2929: sender, recipient, precompiles and the transaction access list start warm.
+		if schedule.eip2929 {
+			let access_list = &mut substate.access_list;
+			access_list.insert_address(sender);
+			if let Action::Call(ref address) = t.action {
+				access_list.insert_address(*address);
+			}
+			let active_builtins = self.machine.builtins().iter()
+				.filter(|&(_, builtin)| builtin.is_active(self.info.number));
+			for (address, _) in active_builtins {
+				access_list.insert_address(*address);
+			}
+			for item in t.access_list().into_iter().flat_map(|items| items.iter()) {
+				access_list.insert_address(item.address);
+				for key in &item.storage_keys {
+					access_list.insert_storage_key(item.address, *key);
+				}
+			}
+		}
+
 		// NOTE: there can be no invalid transactions from this point.
 		if !schedule.keep_unsigned_nonce || !t.is_unsigned() {
 			self.state.inc_nonce(&sender)?;
@@ -306,7 +358,7 @@ impl<'a, B: 'a + StateBackend> Executive<'a, B> {
 					sender: sender.clone(),
 					origin: sender.clone(),
 					gas: init_gas,
-					gas_price: t.gas_price,
+					gas_price: gas_price,
 					value: ActionValue::Transfer(t.value),
 					code: Some(Arc::new(t.data.clone())),
 					data: None,
@@ -327,7 +379,7 @@ impl<'a, B: 'a + StateBackend> Executive<'a, B> {
 					sender: sender.clone(),
 					origin: sender.clone(),
 					gas: init_gas,
-					gas_price: t.gas_price,
+					gas_price: gas_price,
 					value: ActionValue::Transfer(t.value),
 					code: self.state.code(address)?,
 					code_hash: self.state.code_hash(address)?,
@@ -489,7 +541,7 @@ impl<'a, B: 'a + StateBackend> Executive<'a, B> {
 
 			if params.code.is_some() {
 				// part of substate that may be reverted
-				let mut unconfirmed_substate = Substate::new();
+				let mut unconfirmed_substate = Substate::nested(substate);
 
 				// TODO: make ActionParams pass by ref then avoid copy altogether.
 				let mut subvmtracer = vm_tracer.prepare_subtrace(params.code.as_ref().expect("scope is conditional on params.code.is_some(); qed"));
@@ -546,6 +598,11 @@ impl<'a, B: 'a + StateBackend> Executive<'a, B> {
 		vm_tracer: &mut V,
 	) -> vm::Result<FinalizationResult> where T: Tracer, V: VMTracer {
 
+		// EIP-2929: the address being created is warm even if the creation fails.
+		if self.schedule.eip2929 {
+			substate.access_list.insert_address(params.address);
+		}
+
 		// EIP-684: If a contract creation is attempted, due to either a creation transaction or the
 		// CREATE (or future CREATE2) opcode, and the destination address already has either
 		// nonzero nonce, or nonempty code, then the creation throws immediately, with exactly
@@ -565,9 +622,6 @@ impl<'a, B: 'a + StateBackend> Executive<'a, B> {
 		// backup used in case of running out of gas
 		self.state.checkpoint();
 
-		// part of substate that may be reverted
-		let mut unconfirmed_substate = Substate::new();
-
 		// create contract and transfer value to it if necessary
 		let schedule = self.schedule;
 		let nonce_offset = if schedule.no_empty {1} else {0}.into();
@@ -584,6 +638,9 @@ impl<'a, B: 'a + StateBackend> Executive<'a, B> {
 		let gas = params.gas;
 		let created = params.address.clone();
 
+		// part of substate that may be reverted
+		let mut unconfirmed_substate = Substate::nested(substate);
+
 		let mut subvmtracer = vm_tracer.prepare_subtrace(params.code.as_ref().expect("two ways into create (Externalities::create and Executive::transact_with_tracer); both place `Some(...)` `code` in `params`; qed"));
 
 		let res = self.exec_vm(
@@ -638,8 +695,14 @@ impl<'a, B: 'a + StateBackend> Executive<'a, B> {
 		let gas_left = gas_left_prerefund + refunded;
 
 		let gas_used = t.gas - gas_left;
-		let refund_value = gas_left * t.gas_price;
-		let fees_value = gas_used * t.gas_price;
+		let gas_price = t.effective_gas_price(self.info.base_fee);
+		let refund_value = gas_left * gas_price;
+		// EIP-1559: the base fee is burnt, the author only receives the priority fee.
+		let fees_value = if schedule.eip1559 {
+			gas_used * t.effective_priority_fee(self.info.base_fee)
+		} else {
+			gas_used * gas_price
+		};
 
 		trace!("exec::finalize: t.gas={}, sstore_refunds={}, suicide_refunds={}, refunds_bound={}, gas_left_prerefund={}, refunded={}, gas_left={}, gas_used={}, refund_value={}, fees_value={}\n",
 			t.gas, sstore_refunds, suicide_refunds, refunds_bound, gas_left_prerefund, refunded, gas_left, gas_used, refund_value, fees_value);
@@ -649,7 +712,12 @@ impl<'a, B: 'a + StateBackend> Executive<'a, B> {
 		// Below: NoEmpty is safe since the sender must already be non-null to have sent this transaction
 		self.state.add_balance(&sender, &refund_value, CleanupMode::NoEmpty)?;
 		trace!("exec::finalize: Compensating author: fees_value={}, author={}\n", fees_value, &self.info.author);
-		self.state.add_balance(&self.info.author, &fees_value, substate.to_cleanup_mode(&schedule))?;
+		// Paying fees does not count as an access to the author account, so that transactions
+		// executed in parallel do not all conflict on it (see `OpenBlock::push_transactions_parallel`).
+		let access_log = self.state.take_access_log();
+		let paid = self.state.add_balance(&self.info.author, &fees_value, substate.to_cleanup_mode(&schedule));
+		self.state.set_access_log(access_log);
+		paid?;
 
 		// perform suicides
 		for address in &substate.suicides {
@@ -709,6 +777,7 @@ impl<'a, B: 'a + StateBackend> Executive<'a, B> {
 				| Err(vm::Error::Reverted)
 				| Ok(FinalizationResult { apply_state: false, .. }) => {
 					self.state.revert_to_checkpoint();
+					substate.revert(un_substate);
 			},
 			Ok(_) | Err(vm::Error::Internal(_)) => {
 				self.state.discard_checkpoint();
@@ -1546,6 +1615,49 @@ mod tests {
 		}
 	}
 
+	evm_test!{test_eip1559_fees: test_eip1559_fees_int}
+	fn test_eip1559_fees(factory: Factory) {
+		let keypair = Random.generate().unwrap();
+		let author = Address::from(0x1000);
+		let t = Transaction {
+			action: Action::Call(Address::from(0x2000)),
+			value: U256::zero(),
+			data: vec![],
+			gas: U256::from(21_000),
+			gas_price: U256::from(10),
+			nonce: U256::zero()
+		}.sign_with_dynamic_fee(keypair.secret(), 1, vec![], U256::from(2));
+		let sender = t.sender();
+
+		let mut state = get_temp_state_with_factory(factory);
+		state.add_balance(&sender, &U256::from(1_000_000), CleanupMode::NoEmpty).unwrap();
+		let mut info = EnvInfo::default();
+		info.gas_limit = U256::from(100_000);
+		info.author = author;
+		let machine = make_frontier_machine(0);
+		let mut schedule = machine.schedule(info.number);
+		schedule.eip1559 = true;
+
+		// the max fee must cover the base fee
+		info.base_fee = Some(U256::from(11));
+		let res = {
+			let mut ex = Executive::new(&mut state, &info, &machine, &schedule);
+			ex.transact(&t, TransactOptions::with_no_tracing())
+		};
+		assert_eq!(res.unwrap_err(), ExecutionError::GasPriceLowerThanBaseFee { gas_price: 10.into(), base_fee: 11.into() });
+
+		info.base_fee = Some(U256::from(5));
+		let executed = {
+			let mut ex = Executive::new(&mut state, &info, &machine, &schedule);
+			ex.transact(&t, TransactOptions::with_no_tracing()).unwrap()
+		};
+
+		// the sender pays the base fee and the priority fee, the base fee is burnt
+		assert_eq!(executed.gas_used, U256::from(21_000));
+		assert_eq!(state.balance(&sender).unwrap(), U256::from(1_000_000 - 21_000 * 7));
+		assert_eq!(state.balance(&author).unwrap(), U256::from(21_000 * 2));
+	}
+
 	evm_test!{test_keccak: test_keccak_int}
 	fn test_keccak(factory: Factory) {
 		let code = "6064640fffffffff20600055".from_hex().unwrap();
@@ -1674,6 +1786,91 @@ mod tests {
 		assert_eq!(refund, U256::from(19800));
 	}
 
+	evm_test!{test_eip2200: test_eip2200_int}
+	fn test_eip2200(factory: Factory) {
+		let x1 = Address::from(0x1000);
+		let y1 = Address::from(0x2001);
+		let operating_address = Address::from(0);
+		let k = H256::new();
+
+		let mut state = get_temp_state_with_factory(factory.clone());
+		state.new_contract(&x1, U256::zero(), U256::from(1)).unwrap();
+		state.init_code(&x1, "600160005560006000556001600055".from_hex().unwrap()).unwrap();
+		state.new_contract(&y1, U256::zero(), U256::from(1)).unwrap();
+		state.init_code(&y1, "600060006000600061100062fffffff4".from_hex().unwrap()).unwrap();
+
+		let info = EnvInfo::default();
+		let machine = ::ethereum::new_constantinople_test_machine();
+		let mut schedule = machine.schedule(info.number);
+		schedule.eip1706 = true;
+		schedule.sstore_dirty_gas = Some(800);
+
+		// Test a call via top-level -> y1 -> x1
+		let (FinalizationResult { gas_left, .. }, refund, gas) = {
+			let gas = U256::from(0xffffffffffu64);
+			let mut params = ActionParams::default();
+			params.code = Some(Arc::new("6001600055600060006000600061200163fffffffff4".from_hex().unwrap()));
+			params.gas = gas;
+			let mut substate = Substate::new();
+			let mut ex = Executive::new(&mut state, &info, &machine, &schedule);
+			let res = ex.call(params, &mut substate, &mut NoopTracer, &mut NoopVMTracer).unwrap();
+
+			(res, substate.sstore_clears_refund, gas)
+		};
+		let gas_used = gas - gas_left;
+		// sstore: 0 -> (1) -> () -> (1 -> 0 -> 1)
+		assert_eq!(gas_used, U256::from(43660));
+		assert_eq!(refund, U256::from(19200));
+		assert_eq!(state.storage_at(&operating_address, &k).unwrap(), H256::from(U256::from(1)));
+
+		// SSTORE with no more gas than the call stipend fails
+		let mut params = ActionParams::default();
+		params.code = Some(Arc::new("6000600055".from_hex().unwrap()));
+		params.gas = U256::from(2306);
+		let mut substate = Substate::new();
+		let mut ex = Executive::new(&mut state, &info, &machine, &schedule);
+		match ex.call(params, &mut substate, &mut NoopTracer, &mut NoopVMTracer) {
+			Err(vm::Error::OutOfGas) => {},
+			_ => panic!("Expected OutOfGas"),
+		}
+	}
+
+	evm_test!{test_eip2929: test_eip2929_int}
+	fn test_eip2929(factory: Factory) {
+		let x1 = Address::from(0x1000);
+
+		let mut state = get_temp_state_with_factory(factory.clone());
+		state.new_contract(&x1, U256::zero(), U256::from(1)).unwrap();
+		state.init_code(&x1, "60005450".from_hex().unwrap()).unwrap();
+
+		let info = EnvInfo::default();
+		let machine = ::ethereum::new_constantinople_test_machine();
+		let schedule = Schedule::new_berlin();
+
+		// SLOAD(0), then DELEGATECALL x1 which loads the same, now warm, slot
+		let code = Arc::new("60005450600060006000600061100062fffffff4".from_hex().unwrap());
+		let gas_used = |state: &mut State<_>, substate: &mut Substate| {
+			let gas = U256::from(0xffffffffffu64);
+			let mut params = ActionParams::default();
+			params.code = Some(code.clone());
+			params.gas = gas;
+			let mut ex = Executive::new(state, &info, &machine, &schedule);
+			let FinalizationResult { gas_left, .. } = ex.call(params, substate, &mut NoopTracer, &mut NoopVMTracer).unwrap();
+			gas - gas_left
+		};
+
+		// cold slot and cold call target
+		let mut substate = Substate::new();
+		assert_eq!(gas_used(&mut state, &mut substate), U256::from(4828));
+		assert!(substate.access_list.contains_address(&x1));
+		assert!(substate.access_list.contains_storage_key(&Address::default(), &H256::zero()));
+
+		// call target pre-warmed by the transaction access list
+		let mut substate = Substate::new();
+		substate.access_list.insert_address(x1);
+		assert_eq!(gas_used(&mut state, &mut substate), U256::from(2328));
+	}
+
 	fn wasm_sample_code() -> Arc<Vec<u8>> {
 		Arc::new(
 			"0061736d01000000010d0360027f7f0060017f0060000002270303656e7603726574000003656e760673656e646572000103656e76066d656d6f727902010110030201020404017000000501000708010463616c6c00020901000ac10101be0102057f017e4100410028020441c0006b22043602042004412c6a41106a220041003602002004412c6a41086a22014200370200200441186a41106a22024100360200200441186a41086a220342003703002004420037022c2004410036021c20044100360218200441186a1001200020022802002202360200200120032903002205370200200441106a2002360200200441086a200537030020042004290318220537022c200420053703002004411410004100200441c0006a3602040b0b0a010041040b0410c00000"
@@ -309,7 +309,12 @@ impl<'a, T: 'a, V: 'a, B: 'a> Ext for Externalities<'a, T, V, B>
 	}
 
 	fn extcodehash(&self, address: &Address) -> vm::Result<Option<H256>> {
-		Ok(self.state.code_hash(address)?)
+		// EIP-1052: empty accounts are treated as non-existent ones
+		if self.state.exists_and_not_null(address)? {
+			Ok(self.state.code_hash(address)?)
+		} else {
+			Ok(None)
+		}
 	}
 
 	fn extcodesize(&self, address: &Address) -> vm::Result<Option<usize>> {
@@ -390,6 +395,10 @@ impl<'a, T: 'a, V: 'a, B: 'a> Ext for Externalities<'a, T, V, B>
 		self.env_info
 	}
 
+	fn chain_id(&self) -> u64 {
+		self.machine.params().chain_id
+	}
+
 	fn depth(&self) -> usize {
 		self.depth
 	}
@@ -402,6 +411,22 @@ impl<'a, T: 'a, V: 'a, B: 'a> Ext for Externalities<'a, T, V, B>
 		self.substate.sstore_clears_refund = self.substate.sstore_clears_refund.saturating_sub(value);
 	}
 
+	fn access_list_contains_address(&self, address: &Address) -> bool {
+		self.substate.access_list.contains_address(address)
+	}
+
+	fn access_list_insert_address(&mut self, address: Address) {
+		self.substate.access_list.insert_address(address)
+	}
+
+	fn access_list_contains_storage_key(&self, key: &H256) -> bool {
+		self.substate.access_list.contains_storage_key(&self.origin_info.address, key)
+	}
+
+	fn access_list_insert_storage_key(&mut self, key: H256) {
+		self.substate.access_list.insert_storage_key(self.origin_info.address, key)
+	}
+
 	fn trace_next_instruction(&mut self, pc: usize, instruction: u8, current_gas: U256) -> bool {
 		self.vm_tracer.trace_next_instruction(pc, instruction, current_gas)
 	}
@@ -441,6 +466,7 @@ mod tests {
 			difficulty: 0.into(),
 			last_hashes: Arc::new(vec![]),
 			gas_used: 0.into(),
+			base_fee: None,
 			gas_limit: 0.into(),
 		}
 	}
@@ -621,4 +647,25 @@ mod tests {
 
 		assert_eq!(address, Address::from_str("e33c0c7f7df4809055c3eba6c09cfe4baf1bd9e0").unwrap());
 	}
+
+	#[test]
+	fn should_not_return_code_hash_of_empty_account() {
+		use hash::KECCAK_EMPTY;
+
+		let mut setup = TestSetup::new();
+		let empty = Address::from(1);
+		let funded = Address::from(2);
+		setup.state.add_balance(&empty, &U256::zero(), CleanupMode::ForceCreate).unwrap();
+		setup.state.add_balance(&funded, &U256::one(), CleanupMode::NoEmpty).unwrap();
+		assert!(setup.state.exists(&empty).unwrap());
+
+		let state = &mut setup.state;
+		let mut tracer = NoopTracer;
+		let mut vm_tracer = NoopVMTracer;
+		let ext = Externalities::new(state, &setup.env_info, &setup.machine, &setup.schedule, 0, get_test_origin(), &mut setup.sub_state, OutputPolicy::InitContract, &mut tracer, &mut vm_tracer, false);
+
+		assert_eq!(ext.extcodehash(&Address::from(3)).unwrap(), None);
+		assert_eq!(ext.extcodehash(&empty).unwrap(), None);
+		assert_eq!(ext.extcodehash(&funded).unwrap(), Some(KECCAK_EMPTY));
+	}
 }
@@ -21,6 +21,7 @@ use evm::{Factory as EvmFactory, VMType};
 use vm::{Vm, ActionParams, Schedule};
 use wasm::WasmInterpreter;
 use keccak_hasher::KeccakHasher;
+use ethereum_types::H256;
 
 const WASM_MAGIC_NUMBER: &'static [u8; 4] = b"\0asm";
 
@@ -42,6 +43,20 @@ impl VmFactory {
 	pub fn new(evm: VMType, cache_size: usize) -> Self {
 		VmFactory { evm: EvmFactory::new(evm, cache_size) }
 	}
+
+	/// Keep the analysis of a contract with given code hash once it's first executed.
+	pub fn pin(&self, code_hash: H256) {
+		self.evm.pin(code_hash)
+	}
+
+	/// Analyze the code of a contract ahead of its execution and keep the analysis.
+	/// WebAssembly contracts are not analyzed.
+	pub fn prewarm(&self, code_hash: &H256, code: &[u8]) {
+		if code.len() > 4 && &code[0..4] == WASM_MAGIC_NUMBER {
+			return;
+		}
+		self.evm.prewarm(code_hash, code)
+	}
 }
 
 impl From<EvmFactory> for VmFactory {
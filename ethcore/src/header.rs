@@ -84,6 +84,8 @@ pub struct Header {
 	difficulty: U256,
 	/// Vector of post-RLP-encoded fields.
 	seal: Vec<Bytes>,
+	/// Base fee per gas (EIP-1559), encoded after the seal.
+	base_fee: Option<U256>,
 
 	/// Memoized hash of that header and the seal.
 	hash: Option<H256>,
@@ -110,7 +112,8 @@ impl PartialEq for Header {
 		self.gas_used == c.gas_used &&
 		self.gas_limit == c.gas_limit &&
 		self.difficulty == c.difficulty &&
-		self.seal == c.seal
+		self.seal == c.seal &&
+		self.base_fee == c.base_fee
 	}
 }
 
@@ -134,6 +137,7 @@ impl Default for Header {
 
 			difficulty: U256::default(),
 			seal: vec![],
+			base_fee: None,
 			hash: None,
 		}
 	}
@@ -185,6 +189,9 @@ impl Header {
 	/// Get the seal field of the header.
 	pub fn seal(&self) -> &[Bytes] { &self.seal }
 
+	/// Get the base fee field of the header.
+	pub fn base_fee(&self) -> Option<U256> { self.base_fee }
+
 	/// Get the seal field with RLP-decoded values as bytes.
 	pub fn decode_seal<'a, T: ::std::iter::FromIterator<&'a [u8]>>(&'a self) -> Result<T, DecoderError> {
 		self.seal.iter().map(|rlp| {
@@ -269,6 +276,11 @@ impl Header {
 		change_field(&mut self.hash, &mut self.seal, a)
 	}
 
+	/// Set the base fee field of the header.
+	pub fn set_base_fee(&mut self, a: Option<U256>) {
+		change_field(&mut self.hash, &mut self.base_fee, a)
+	}
+
 	/// Get & memoize the hash of this header (keccak of the RLP with seal).
 	pub fn compute_hash(&mut self) -> H256 {
 		let hash = self.hash();
@@ -300,10 +312,11 @@ impl Header {
 
 	/// Place this header into an RLP stream `s`, optionally `with_seal`.
 	fn stream_rlp(&self, s: &mut RlpStream, with_seal: Seal) {
+		let base_fee_items = if self.base_fee.is_some() { 1 } else { 0 };
 		if let Seal::With = with_seal {
-			s.begin_list(13 + self.seal.len());
+			s.begin_list(13 + self.seal.len() + base_fee_items);
 		} else {
-			s.begin_list(13);
+			s.begin_list(13 + base_fee_items);
 		}
 
 		s.append(&self.parent_hash);
@@ -325,6 +338,10 @@ impl Header {
 				s.append_raw(b, 1);
 			}
 		}
+
+		if let Some(ref base_fee) = self.base_fee {
+			s.append(base_fee);
+		}
 	}
 }
 
@@ -336,8 +353,10 @@ fn change_field<T>(hash: &mut Option<H256>, field: &mut T, value: T) where T: Pa
 	}
 }
 
-impl Decodable for Header {
-	fn decode(r: &Rlp) -> Result<Self, DecoderError> {
+impl Header {
+	/// Decode a header from RLP. Headers from the EIP-1559 transition on carry the base fee
+	/// as their last field, which can't be told apart from the seal without the transition.
+	pub fn decode_rlp(r: &Rlp, eip1559_transition: BlockNumber) -> Result<Self, DecoderError> {
 		let mut blockheader = Header {
 			parent_hash: r.val_at(0)?,
 			uncles_hash: r.val_at(1)?,
@@ -353,10 +372,20 @@ impl Decodable for Header {
 			timestamp: cmp::min(r.val_at::<U256>(11)?, u64::max_value().into()).as_u64(),
 			extra_data: r.val_at(12)?,
 			seal: vec![],
+			base_fee: None,
 			hash: keccak(r.as_raw()).into(),
 		};
 
-		for i in 13..r.item_count()? {
+		let mut seal_end = r.item_count()?;
+		if blockheader.number >= eip1559_transition {
+			if seal_end <= 13 {
+				return Err(DecoderError::RlpIncorrectListLen);
+			}
+			seal_end -= 1;
+			blockheader.base_fee = Some(r.val_at(seal_end)?);
+		}
+
+		for i in 13..seal_end {
 			blockheader.seal.push(r.at(i)?.as_raw().to_vec())
 		}
 
@@ -364,6 +393,12 @@ impl Decodable for Header {
 	}
 }
 
+impl Decodable for Header {
+	fn decode(r: &Rlp) -> Result<Self, DecoderError> {
+		Header::decode_rlp(r, BlockNumber::max_value())
+	}
+}
+
 impl Encodable for Header {
 	fn rlp_append(&self, s: &mut RlpStream) {
 		self.stream_rlp(s, Seal::With);
@@ -419,7 +454,8 @@ impl ::parity_machine::FinalizableHeader for ExtendedHeader {
 #[cfg(test)]
 mod tests {
 	use rustc_hex::FromHex;
-	use rlp;
+	use rlp::{self, Rlp};
+	use ethereum_types::{H256, U256};
 	use super::Header;
 
 	#[test]
@@ -453,4 +489,34 @@ mod tests {
 
 		assert_eq!(header_rlp, encoded_header);
 	}
+
+	#[test]
+	fn decode_with_base_fee() {
+		let mut header = Header::new();
+		header.set_number(10);
+		header.set_seal(vec![rlp::encode(&H256::from(1)).into_vec(), rlp::encode(&8u64).into_vec()]);
+		header.set_base_fee(Some(U256::from(1_000_000_000)));
+		let hash = header.hash();
+		let encoded = rlp::encode(&header);
+
+		// before the transition the last field is part of the seal
+		let legacy = Header::decode_rlp(&Rlp::new(&encoded), 11).expect("error decoding header");
+		assert_eq!(legacy.seal().len(), 3);
+		assert_eq!(legacy.base_fee(), None);
+
+		let decoded = Header::decode_rlp(&Rlp::new(&encoded), 10).expect("error decoding header");
+		assert_eq!(decoded.seal(), header.seal());
+		assert_eq!(decoded.base_fee(), Some(U256::from(1_000_000_000)));
+		assert_eq!(decoded.hash(), hash);
+		assert_eq!(decoded, header);
+	}
+
+	#[test]
+	fn reject_missing_base_fee() {
+		let mut header = Header::new();
+		header.set_number(10);
+		let encoded = rlp::encode(&header);
+
+		assert!(Header::decode_rlp(&Rlp::new(&encoded), 10).is_err());
+	}
 }
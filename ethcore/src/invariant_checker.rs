@@ -0,0 +1,98 @@
+// Copyright 2015-2018 Parity Technologies (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Smart contract based invariant checks of imported blocks.
+//!
+//! The contract is called read-only on top of the post-state of every block.
+//! A block is considered to violate the invariants if the call fails or returns `false`.
+
+use ethabi::FunctionOutputDecoder;
+use ethereum_types::{U256, Address};
+
+use block::{ExecutedBlock, IsBlock};
+use executive::{Executive, TransactOptions};
+use machine::EthereumMachine;
+use spec::CommonParams;
+use transaction::{Action, Transaction};
+use types::BlockNumber;
+
+use_contract!(invariant_checker, "res/contracts/invariant_checker.json");
+
+/// Calls the invariant contract after every block.
+pub struct InvariantChecker {
+	contract_address: Address,
+	transition_block: BlockNumber,
+	halt: bool,
+}
+
+impl InvariantChecker {
+	/// Create a new instance if address is specified in params.
+	pub fn from_params(params: &CommonParams) -> Option<InvariantChecker> {
+		params.invariant_contract.map(|address|
+			InvariantChecker {
+				contract_address: address,
+				transition_block: params.invariant_contract_transition,
+				halt: params.invariant_contract_halt,
+			}
+		)
+	}
+
+	/// Whether blocks violating the invariants should be rejected.
+	pub fn halts(&self) -> bool {
+		self.halt
+	}
+
+	/// Check the invariants against the post-state of the block.
+	/// Returns the reason of the violation, if any.
+	pub fn check(&self, machine: &EthereumMachine, block: &ExecutedBlock) -> Result<(), String> {
+		let number = block.header().number();
+		if number < self.transition_block {
+			return Ok(());
+		}
+
+		// the state is cloned, so nothing done by the contract is committed
+		let mut state = block.state().clone();
+		let mut env_info = block.env_info();
+		env_info.gas_limit = U256::max_value();
+
+		let (data, decoder) = invariant_checker::functions::check_invariants::call();
+		let sender = Address::default();
+		let transaction = Transaction {
+			nonce: state.nonce(&sender).map_err(|e| format!("{}", e))?,
+			action: Action::Call(self.contract_address),
+			gas: *block.header().gas_limit(),
+			gas_price: U256::default(),
+			value: U256::default(),
+			data: data,
+		}.fake_sign(sender);
+
+		let schedule = machine.schedule(number);
+		let options = TransactOptions::with_no_tracing().dont_check_nonce().save_output_from_contract();
+		let executed = Executive::new(&mut state, &env_info, machine, &schedule)
+			.transact_virtual(&transaction, options)
+			.map_err(|e| format!("Invariant contract call failed: {}", e))?;
+
+		if let Some(exception) = executed.exception {
+			return Err(format!("Invariant contract call failed: {}", exception));
+		}
+
+		match decoder.decode(&executed.output) {
+			Ok(true) => Ok(()),
+			Ok(false) => Err("Invariant contract reported a violation".into()),
+			Err(e) => Err(format!("Invalid invariant contract output: {}", e)),
+		}
+	}
+}
@@ -201,6 +201,10 @@ impl<'a, T: 'a, V: 'a, B: 'a> Ext for TestExt<'a, T, V, B>
 		self.ext.env_info()
 	}
 
+	fn chain_id(&self) -> u64 {
+		self.ext.chain_id()
+	}
+
 	fn depth(&self) -> usize {
 		0
 	}
@@ -216,6 +220,22 @@ impl<'a, T: 'a, V: 'a, B: 'a> Ext for TestExt<'a, T, V, B>
 	fn sub_sstore_refund(&mut self, value: U256) {
 		self.ext.sub_sstore_refund(value)
 	}
+
+	fn access_list_contains_address(&self, address: &Address) -> bool {
+		self.ext.access_list_contains_address(address)
+	}
+
+	fn access_list_insert_address(&mut self, address: Address) {
+		self.ext.access_list_insert_address(address)
+	}
+
+	fn access_list_contains_storage_key(&self, key: &H256) -> bool {
+		self.ext.access_list_contains_storage_key(key)
+	}
+
+	fn access_list_insert_storage_key(&mut self, key: H256) {
+		self.ext.access_list_insert_storage_key(key)
+	}
 }
 
 fn do_json_test<H: FnMut(&str, HookType)>(json_data: &[u8], h: &mut H) -> Vec<String> {
@@ -81,6 +81,7 @@ extern crate hashdb;
 extern crate itertools;
 extern crate kvdb;
 extern crate kvdb_memorydb;
+#[cfg(any(test, feature = "rocksdb"))]
 extern crate kvdb_rocksdb;
 extern crate lru_cache;
 extern crate num_cpus;
@@ -113,10 +114,10 @@ extern crate journaldb;
 #[cfg(any(test, feature = "json-tests", feature = "test-helpers"))]
 extern crate tempdir;
 
-#[cfg(any(target_os = "linux", target_os = "macos", target_os = "windows", target_os = "android"))]
+#[cfg(all(feature = "hw", any(target_os = "linux", target_os = "macos", target_os = "windows", target_os = "android")))]
 extern crate hardware_wallet;
 
-#[cfg(not(any(target_os = "linux", target_os = "macos", target_os = "windows", target_os = "android")))]
+#[cfg(not(all(feature = "hw", any(target_os = "linux", target_os = "macos", target_os = "windows", target_os = "android"))))]
 extern crate fake_hardware_wallet as hardware_wallet;
 
 #[macro_use]
@@ -165,6 +166,7 @@ pub mod state;
 pub mod state_db;
 pub mod trace;
 pub mod verification;
+pub mod witness;
 
 mod cache_manager;
 mod pod_account;
@@ -173,6 +175,7 @@ mod externalities;
 mod blockchain;
 mod factory;
 mod tx_filter;
+mod invariant_checker;
 
 #[cfg(test)]
 mod tests;
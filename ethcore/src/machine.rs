@@ -231,7 +231,8 @@ impl EthereumMachine {
 	/// The gas floor target must not be lower than the engine's minimum gas limit.
 	pub fn populate_from_parent(&self, header: &mut Header, parent: &Header, gas_floor_target: U256, gas_ceil_target: U256) {
 		header.set_difficulty(parent.difficulty().clone());
-		let gas_limit = parent.gas_limit().clone();
+		header.set_base_fee(self.calc_base_fee(parent));
+		let gas_limit = self.parent_gas_limit(header.number(), parent);
 		assert!(!gas_limit.is_zero(), "Gas limit should be > 0");
 
 		if let Some(ref ethash_params) = self.ethash_extensions {
@@ -276,6 +277,46 @@ impl EthereumMachine {
 		});
 	}
 
+	/// The gas limit of the parent as seen by the block `number`.
+	/// At the EIP-1559 transition the parent gas limit becomes the gas target,
+	/// so it is scaled up by the elasticity multiplier.
+	pub fn parent_gas_limit(&self, number: BlockNumber, parent: &Header) -> U256 {
+		if number == self.params().eip1559_transition {
+			parent.gas_limit().saturating_mul(self.params().eip1559_elasticity_multiplier)
+		} else {
+			*parent.gas_limit()
+		}
+	}
+
+	/// Calculate the base fee of the block following `parent` (EIP-1559).
+	/// Returns `None` before the EIP-1559 transition.
+	pub fn calc_base_fee(&self, parent: &Header) -> Option<U256> {
+		let params = self.params();
+		let number = parent.number() + 1;
+		if number < params.eip1559_transition {
+			return None;
+		}
+		if number == params.eip1559_transition {
+			return Some(params.eip1559_base_fee_initial_value);
+		}
+
+		let parent_base_fee = parent.base_fee().unwrap_or(params.eip1559_base_fee_initial_value);
+		let gas_target = *parent.gas_limit() / params.eip1559_elasticity_multiplier;
+		let denominator = params.eip1559_base_fee_max_change_denominator;
+		let gas_used = *parent.gas_used();
+
+		let base_fee = if gas_target.is_zero() || gas_used == gas_target {
+			parent_base_fee
+		} else if gas_used > gas_target {
+			let delta = parent_base_fee.saturating_mul(gas_used - gas_target) / gas_target / denominator;
+			parent_base_fee.saturating_add(cmp::max(delta, U256::one()))
+		} else {
+			let delta = parent_base_fee.saturating_mul(gas_target - gas_used) / gas_target / denominator;
+			parent_base_fee - delta
+		};
+		Some(base_fee)
+	}
+
 	/// Get the general parameters of the chain.
 	pub fn params(&self) -> &CommonParams {
 		&self.params
@@ -287,7 +328,9 @@ impl EthereumMachine {
 			None => self.params.schedule(block_number),
 			Some(ref ext) => {
 				if block_number < ext.homestead_transition {
-					Schedule::new_frontier()
+					let mut schedule = Schedule::new_frontier();
+					self.params.update_evm_limits(block_number, &mut schedule);
+					schedule
 				} else {
 					self.params.schedule(block_number)
 				}
@@ -367,6 +410,14 @@ impl EthereumMachine {
 		};
 		t.verify_basic(check_low_s, chain_id, false)?;
 
+		if t.access_list().is_some() && header.number() < self.params().eip2930_transition {
+			return Err(transaction::Error::TransactionTypeNotEnabled);
+		}
+
+		if t.max_priority_fee_per_gas().is_some() && header.number() < self.params().eip1559_transition {
+			return Err(transaction::Error::TransactionTypeNotEnabled);
+		}
+
 		Ok(())
 	}
 
@@ -533,6 +584,38 @@ mod tests {
 		assert_eq!(res, Err(transaction::Error::InvalidSignature("Crypto error (Invalid EC signature)".into())));
 	}
 
+	#[test]
+	fn should_calculate_base_fee() {
+		let spec = ::ethereum::new_homestead_test();
+		let mut params = spec.params().clone();
+		params.eip1559_transition = 10;
+		let machine = EthereumMachine::regular(params, Default::default());
+
+		let mut parent = ::header::Header::new();
+		parent.set_gas_limit(20_000_000.into());
+		parent.set_number(8);
+		assert_eq!(machine.calc_base_fee(&parent), None);
+
+		parent.set_number(9);
+		assert_eq!(machine.calc_base_fee(&parent), Some(1_000_000_000.into()));
+		assert_eq!(machine.parent_gas_limit(10, &parent), 40_000_000.into());
+
+		parent.set_number(10);
+		parent.set_base_fee(Some(1_000_000_000.into()));
+		parent.set_gas_used(10_000_000.into());
+		assert_eq!(machine.calc_base_fee(&parent), Some(1_000_000_000.into()));
+
+		parent.set_gas_used(20_000_000.into());
+		assert_eq!(machine.calc_base_fee(&parent), Some(1_125_000_000.into()));
+
+		parent.set_gas_used(0.into());
+		assert_eq!(machine.calc_base_fee(&parent), Some(875_000_000.into()));
+
+		parent.set_number(9);
+		parent.set_gas_limit(U256::max_value());
+		assert_eq!(machine.parent_gas_limit(10, &parent), U256::max_value());
+	}
+
 	#[test]
 	fn ethash_gas_limit_is_multiple_of_determinant() {
 		use ethereum_types::U256;
@@ -15,8 +15,9 @@
 // along with Parity.  If not, see <http://www.gnu.org/licenses/>.
 
 use std::cmp;
+use std::net::IpAddr;
 use std::time::{Instant, Duration};
-use std::collections::{BTreeMap, BTreeSet, HashSet};
+use std::collections::{BTreeMap, BTreeSet, BinaryHeap, HashMap, HashSet, VecDeque};
 use std::sync::Arc;
 
 use ansi_term::Colour;
@@ -24,7 +25,7 @@ use bytes::Bytes;
 use engines::{EthEngine, Seal};
 use error::{Error, ErrorKind, ExecutionError};
 use ethcore_miner::gas_pricer::GasPricer;
-use ethcore_miner::pool::{self, TransactionQueue, VerifiedTransaction, QueueStatus, PrioritizationStrategy};
+use ethcore_miner::pool::{self, TransactionQueue, VerifiedTransaction, QueueStatus, Quotas, PrioritizationStrategy, TransactionEvent};
 #[cfg(feature = "work-notify")]
 use ethcore_miner::work_notify::NotifyWork;
 use ethereum_types::{H256, U256, Address};
@@ -50,9 +51,11 @@ use executive::contract_address;
 use header::{Header, BlockNumber};
 use miner;
 use miner::pool_client::{PoolClient, CachedNonceClient, NonceCache};
+use miner::rebroadcast::{Rebroadcast, MAX_REBROADCAST_BACKOFF};
 use receipt::RichReceipt;
 use spec::Spec;
 use state::State;
+use stats::{PrometheusMetrics, PrometheusRegistry};
 use ethkey::Password;
 
 /// Different possible definitions for pending transaction set.
@@ -135,6 +138,16 @@ pub struct MinerOptions {
 	/// NOTE: Such block will contain all pending transactions but
 	/// will be invalid if mined.
 	pub infinite_pending_block: bool,
+	/// Build pending blocks from a deterministically ordered transaction set.
+	///
+	/// Transactions with equal score are ordered by hash instead of insertion order,
+	/// so the same pool contents always produce the same block template.
+	pub deterministic_block_template: bool,
+	/// Keep the pending block up to date as transactions arrive, even when not sealing.
+	///
+	/// New transactions are pushed to the existing pending block instead of rebuilding it,
+	/// so pending state queries and work requests are served without preparing a block.
+	pub pending_block_cache: bool,
 
 	/// Strategy to use for prioritizing transactions in the queue.
 	pub tx_queue_strategy: PrioritizationStrategy,
@@ -142,6 +155,14 @@ pub struct MinerOptions {
 	pub tx_queue_penalization: Penalization,
 	/// Do we want to mark transactions recieved locally (e.g. RPC) as local if we don't have the sending account?
 	pub tx_queue_no_unfamiliar_locals: bool,
+	/// How long external transactions which became underpriced after raising minimal gas price are kept in the queue.
+	pub tx_queue_demotion_grace: Duration,
+	/// Gas price bump (in basis points) required to replace a queued transaction with the same sender and nonce.
+	pub tx_queue_replacement_bump: usize,
+	/// How long local transactions stay pending before being sent to peers again (`None` disables rebroadcasting).
+	pub tx_rebroadcast_after: Option<Duration>,
+	/// Senders whose ready transactions are always pushed to a new block first, e.g. system or oracle accounts.
+	pub tx_priority_senders: HashSet<Address>,
 	/// Do we refuse to accept service transactions even if sender is certified.
 	pub refuse_service_transactions: bool,
 	/// Transaction pool limits.
@@ -163,9 +184,15 @@ impl Default for MinerOptions {
 			work_queue_size: 20,
 			enable_resubmission: true,
 			infinite_pending_block: false,
+			deterministic_block_template: false,
+			pending_block_cache: false,
 			tx_queue_strategy: PrioritizationStrategy::GasPriceOnly,
 			tx_queue_penalization: Penalization::Disabled,
 			tx_queue_no_unfamiliar_locals: false,
+			tx_queue_demotion_grace: Duration::from_secs(60),
+			tx_queue_replacement_bump: pool::scoring::DEFAULT_REPLACEMENT_BUMP,
+			tx_rebroadcast_after: Some(Duration::from_secs(10 * 60)),
+			tx_priority_senders: HashSet::new(),
 			refuse_service_transactions: false,
 			pool_limits: pool::Options {
 				max_count: 8_192,
@@ -196,10 +223,14 @@ pub struct AuthoringParams {
 struct SealingWork {
 	queue: UsingQueue<ClosedBlock>,
 	enabled: bool,
+	// sealing suspended for maintenance, see `Miner::set_sealing_paused`
+	paused: bool,
 	next_allowed_reseal: Instant,
 	next_mandatory_reseal: Instant,
 	// block number when sealing work was last requested
 	last_request: Option<u64>,
+	// ordered candidate transactions used to prepare the last pending block (and its number)
+	last_candidates: Option<(BlockNumber, Vec<Arc<VerifiedTransaction>>)>,
 }
 
 impl SealingWork {
@@ -225,6 +256,7 @@ pub struct Miner {
 	engine: Arc<EthEngine>,
 	accounts: Option<Arc<AccountProvider>>,
 	io_channel: RwLock<Option<IoChannel<ClientIoMessage>>>,
+	rebroadcast: Option<Mutex<Rebroadcast>>,
 }
 
 impl Miner {
@@ -240,6 +272,58 @@ impl Miner {
 		self.transaction_queue.add_listener(f);
 	}
 
+	/// Set a callback to be notified about all imported transactions, including their details.
+	pub fn add_full_transactions_listener(&self, f: Box<Fn(&[Arc<VerifiedTransaction>]) + Send + Sync>) {
+		self.transaction_queue.add_full_listener(f);
+	}
+
+	/// Set a callback to be notified about newly accepted local transactions.
+	pub fn add_local_transactions_listener(&self, f: Box<Fn(&[Arc<VerifiedTransaction>]) + Send + Sync>) {
+		self.transaction_queue.add_local_listener(f);
+	}
+
+	/// Set a callback to be notified about every status change of transactions in the queue.
+	pub fn add_transactions_lifecycle_listener(&self, f: Box<Fn(&H256, TransactionEvent) + Send + Sync>) {
+		self.transaction_queue.add_lifecycle_listener(f);
+	}
+
+	/// Imports transactions received from given IP address to transaction queue.
+	///
+	/// The origin is used to enforce the per-origin quota of the queue.
+	pub fn import_external_transactions_from<C: miner::BlockChainClient>(
+		&self,
+		chain: &C,
+		transactions: Vec<UnverifiedTransaction>,
+		origin: Option<IpAddr>,
+	) -> Vec<Result<(), transaction::Error>> {
+		trace!(target: "external_tx", "Importing external transactions");
+		let client = self.pool_client(chain);
+		let results = self.transaction_queue.import_from(
+			client,
+			transactions.into_iter().map(pool::verifier::Transaction::Unverified).collect(),
+			origin,
+		);
+
+		// --------------------------------------------------------------------------
+		// | NOTE Code below requires sealing locks.                                |
+		// | Make sure to release the locks before calling that method.             |
+		// --------------------------------------------------------------------------
+		if !results.is_empty() && self.options.reseal_on_external_tx &&	self.sealing.lock().reseal_allowed() {
+			self.prepare_and_update_sealing(chain);
+		} else if results.iter().any(Result::is_ok) {
+			self.update_pending_block_cache(chain);
+		}
+
+		results
+	}
+
+	/// Suspends or resumes block sealing.
+	///
+	/// While paused no blocks are prepared, sealed or proposed; transactions are still accepted.
+	pub fn set_sealing_paused(&self, paused: bool) {
+		self.sealing.lock().paused = paused;
+	}
+
 	/// Creates new instance of miner Arc.
 	pub fn new(
 		options: MinerOptions,
@@ -251,15 +335,23 @@ impl Miner {
 		let verifier_options = options.pool_verification_options.clone();
 		let tx_queue_strategy = options.tx_queue_strategy;
 		let nonce_cache_size = cmp::max(4096, limits.max_count / 4);
+		let transaction_queue = TransactionQueue::new(limits, verifier_options, tx_queue_strategy);
+		transaction_queue.set_demotion_grace(options.tx_queue_demotion_grace);
+		transaction_queue.set_replacement_bump(options.tx_queue_replacement_bump);
+		let rebroadcast = options.tx_rebroadcast_after
+			.map(|after| Mutex::new(Rebroadcast::new(after, MAX_REBROADCAST_BACKOFF)));
 
 		Miner {
 			sealing: Mutex::new(SealingWork {
 				queue: UsingQueue::new(options.work_queue_size),
 				enabled: options.force_sealing
+					|| options.pending_block_cache
 					|| spec.engine.seals_internally().is_some(),
+				paused: false,
 				next_allowed_reseal: Instant::now(),
 				next_mandatory_reseal: Instant::now() + options.reseal_max_period,
 				last_request: None,
+				last_candidates: None,
 			}),
 			params: RwLock::new(AuthoringParams::default()),
 			#[cfg(feature = "work-notify")]
@@ -267,10 +359,11 @@ impl Miner {
 			gas_pricer: Mutex::new(gas_pricer),
 			nonce_cache: NonceCache::new(nonce_cache_size),
 			options,
-			transaction_queue: Arc::new(TransactionQueue::new(limits, verifier_options, tx_queue_strategy)),
+			transaction_queue: Arc::new(transaction_queue),
 			accounts,
 			engine: spec.engine.clone(),
 			io_channel: RwLock::new(None),
+			rebroadcast,
 		}
 	}
 
@@ -326,6 +419,7 @@ impl Miner {
 			options.minimal_gas_price = gas_price;
 			options.block_gas_limit = block_gas_limit;
 			txq.set_verifier_options(options);
+			txq.demote_underpriced();
 		});
 	}
 
@@ -434,16 +528,43 @@ impl Miner {
 			MAX_SKIPPED_TRANSACTIONS.saturating_add(cmp::min(*open_block.block().header().gas_limit() / min_tx_gas, u64::max_value().into()).as_u64() as usize)
 		};
 
-		let pending: Vec<Arc<_>> = self.transaction_queue.pending(
-			client.clone(),
-			pool::PendingSettings {
-				block_number: chain_info.best_block_number,
-				current_timestamp: chain_info.best_block_timestamp,
-				nonce_cap,
-				max_len: max_transactions,
-				ordering: miner::PendingOrdering::Priority,
-			}
-		);
+		let ordering = if self.options.deterministic_block_template {
+			miner::PendingOrdering::Deterministic
+		} else {
+			miner::PendingOrdering::Priority
+		};
+
+		let settings = pool::PendingSettings {
+			block_number: chain_info.best_block_number,
+			current_timestamp: chain_info.best_block_timestamp,
+			nonce_cap,
+			max_len: max_transactions,
+			ordering,
+		};
+		let pending: Vec<Arc<_>> = self.transaction_queue.pending(client.clone(), settings.clone());
+
+		let base_fee = open_block.block().header().base_fee();
+		let pending = match (base_fee, ordering) {
+			(Some(base_fee), miner::PendingOrdering::Priority) => order_by_effective_priority_fee(pending, base_fee),
+			_ => pending,
+		};
+		let pending = if self.options.tx_priority_senders.is_empty() {
+			pending
+		} else {
+			// fetched separately, so they can't be cut off by `max_len` in favour of better paying ones.
+			let priority = self.transaction_queue.pending_from_senders(client.clone(), settings, &self.options.tx_priority_senders);
+			priority_senders_first(priority, pending)
+		};
+
+		self.sealing.lock().last_candidates = Some((block_number, pending.clone()));
+
+		// Transactions already in a reopened block don't need to be executed again.
+		let pending = if open_block.transactions().is_empty() {
+			pending
+		} else {
+			let included = open_block.transactions().iter().map(|tx| tx.hash()).collect::<HashSet<_>>();
+			pending.into_iter().filter(|tx| !included.contains(&tx.signed().hash())).collect()
+		};
 
 		let took_ms = |elapsed: &Duration| {
 			elapsed.as_secs() * 1000 + elapsed.subsec_nanos() as u64 / 1_000_000
@@ -459,6 +580,12 @@ impl Miner {
 			let hash = transaction.hash();
 			let sender = transaction.sender();
 
+			// Transactions paying less than the base fee may become includable in a later block.
+			if base_fee.map_or(false, |base_fee| transaction.gas_price < base_fee) {
+				debug!(target: "miner", "Skipping transaction below the block base fee: {:?}", hash);
+				continue;
+			}
+
 			// Re-verify transaction again vs current state.
 			let result = client.verify_signed(&transaction)
 				.map_err(|e| e.into())
@@ -563,6 +690,11 @@ impl Miner {
 	/// Check is reseal is allowed and necessary.
 	fn requires_reseal(&self, best_block: BlockNumber) -> bool {
 		let mut sealing = self.sealing.lock();
+		if sealing.paused {
+			trace!(target: "miner", "requires_reseal: sealing is paused");
+			return false
+		}
+
 		if !sealing.enabled {
 			trace!(target: "miner", "requires_reseal: sealing is disabled");
 			return false
@@ -583,6 +715,7 @@ impl Miner {
 
 		// keep sealing enabled if any of the conditions is met
 		let sealing_enabled = self.forced_sealing()
+			|| self.options.pending_block_cache
 			|| self.transaction_queue.has_local_pending_transactions()
 			|| self.engine.seals_internally() == Some(true)
 			|| had_requests;
@@ -627,7 +760,7 @@ impl Miner {
 
 		let parent_header = match chain.block_header(BlockId::Hash(*block.header().parent_hash())) {
 			Some(h) => {
-				match h.decode() {
+				match h.decode(self.engine.params().eip1559_transition) {
 					Ok(decoded_hdr) => decoded_hdr,
 					Err(_) => return false
 				}
@@ -792,6 +925,24 @@ impl Miner {
 		preparation_status
 	}
 
+	/// Push newly imported transactions to the cached pending block, if enabled.
+	///
+	/// Unlike `update_sealing` this is not throttled by `reseal_min_period`, and never seals.
+	fn update_pending_block_cache<C: miner::BlockChainClient>(&self, chain: &C) {
+		if !self.options.pending_block_cache || self.sealing.lock().paused {
+			return;
+		}
+
+		// --------------------------------------------------------------------------
+		// | NOTE Code below requires sealing locks.                                |
+		// | Make sure to release the locks before calling that method.             |
+		// --------------------------------------------------------------------------
+		trace!(target: "miner", "update_pending_block_cache: pushing new transactions");
+		if let Some((block, original_work_hash)) = self.prepare_block(chain) {
+			self.prepare_work(block, original_work_hash);
+		}
+	}
+
 	/// Prepare pending block, check whether sealing is needed, and then update sealing.
 	fn prepare_and_update_sealing<C: miner::BlockChainClient>(&self, chain: &C) {
 		use miner::MinerService;
@@ -864,22 +1015,7 @@ impl miner::MinerService for Miner {
 		chain: &C,
 		transactions: Vec<UnverifiedTransaction>
 	) -> Vec<Result<(), transaction::Error>> {
-		trace!(target: "external_tx", "Importing external transactions");
-		let client = self.pool_client(chain);
-		let results = self.transaction_queue.import(
-			client,
-			transactions.into_iter().map(pool::verifier::Transaction::Unverified).collect(),
-		);
-
-		// --------------------------------------------------------------------------
-		// | NOTE Code below requires sealing locks.                                |
-		// | Make sure to release the locks before calling that method.             |
-		// --------------------------------------------------------------------------
-		if !results.is_empty() && self.options.reseal_on_external_tx &&	self.sealing.lock().reseal_allowed() {
-			self.prepare_and_update_sealing(chain);
-		}
-
-		results
+		self.import_external_transactions_from(chain, transactions, None)
 	}
 
 	fn import_own_transaction<C: miner::BlockChainClient>(
@@ -903,6 +1039,8 @@ impl miner::MinerService for Miner {
 		// --------------------------------------------------------------------------
 		if imported.is_ok() && self.options.reseal_on_own_tx && self.sealing.lock().reseal_allowed() {
 			self.prepare_and_update_sealing(chain);
+		} else if imported.is_ok() {
+			self.update_pending_block_cache(chain);
 		}
 
 		imported
@@ -934,6 +1072,23 @@ impl miner::MinerService for Miner {
 		self.transaction_queue.local_transactions()
 	}
 
+	fn transactions_to_rebroadcast(&self) -> Vec<H256> {
+		let mut rebroadcast = match self.rebroadcast {
+			Some(ref rebroadcast) => rebroadcast.lock(),
+			None => return Vec::new(),
+		};
+
+		let pending = self.transaction_queue.local_transactions()
+			.into_iter()
+			.filter(|&(_, ref status)| status.is_pending())
+			.map(|(hash, _)| hash);
+		let due = rebroadcast.due(pending, Instant::now());
+		if !due.is_empty() {
+			debug!(target: "miner", "Rebroadcasting {} stuck local transactions.", due.len());
+		}
+		due
+	}
+
 	fn queued_transactions(&self) -> Vec<Arc<VerifiedTransaction>> {
 		self.transaction_queue.all_transactions()
 	}
@@ -1039,6 +1194,18 @@ impl miner::MinerService for Miner {
 		self.transaction_queue.status()
 	}
 
+	fn set_replacement_bump(&self, bump: usize) {
+		self.transaction_queue.set_replacement_bump(bump);
+	}
+
+	fn queue_quotas(&self) -> Quotas {
+		self.transaction_queue.quotas()
+	}
+
+	fn set_queue_quotas(&self, quotas: Quotas) {
+		self.transaction_queue.set_quotas(quotas);
+	}
+
 	fn pending_receipts(&self, best_block: BlockNumber) -> Option<Vec<RichReceipt>> {
 		self.map_existing_pending_block(|pending| {
 			let receipts = pending.receipts();
@@ -1140,6 +1307,18 @@ impl miner::MinerService for Miner {
 		})
 	}
 
+	fn block_template<C>(&self, chain: &C) -> Option<ClosedBlock> where
+		C: BlockChain + CallContract + BlockProducer + SealedBlockImporter + Nonce + Sync,
+	{
+		if self.engine.seals_internally().is_some() {
+			return None;
+		}
+
+		self.prepare_pending_block(chain);
+
+		self.sealing.lock().queue.use_last_ref().cloned()
+	}
+
 	// Note used for external submission (PoW) and internally by sealing engines.
 	fn submit_seal(&self, block_hash: H256, seal: Vec<Bytes>) -> Result<SealedBlock, Error> {
 		let result =
@@ -1225,6 +1404,15 @@ impl miner::MinerService for Miner {
 		}
 
 		if has_new_best_block {
+			// Let lifecycle listeners tell mined transactions apart from stale ones when culled.
+			if self.transaction_queue.has_lifecycle_listeners() {
+				for hash in enacted {
+					if let Some(block) = chain.block(BlockId::Hash(*hash)) {
+						self.transaction_queue.mined(block.transaction_hashes());
+					}
+				}
+			}
+
 			// Make sure to cull transactions after we update sealing.
 			// Not culling won't lead to old transactions being added to the block
 			// (thanks to Ready), but culling can take significant amount of time,
@@ -1274,6 +1462,65 @@ impl miner::MinerService for Miner {
 	fn pending_transactions(&self, latest_block_number: BlockNumber) -> Option<Vec<SignedTransaction>> {
 		self.map_existing_pending_block(|b| b.transactions().into_iter().cloned().collect(), latest_block_number)
 	}
+
+	fn pending_block_candidates(&self, latest_block_number: BlockNumber) -> Option<Vec<Arc<VerifiedTransaction>>> {
+		match self.sealing.lock().last_candidates {
+			Some((number, ref candidates)) if number >= latest_block_number => Some(candidates.clone()),
+			_ => None,
+		}
+	}
+}
+
+impl PrometheusMetrics for Miner {
+	fn prometheus_metrics(&self, registry: &mut PrometheusRegistry) {
+		let status = self.transaction_queue.status();
+		registry.register_gauge("txpool_transactions", "Number of transactions in the pool", status.status.transaction_count as i64);
+		registry.register_gauge("txpool_senders", "Number of senders with transactions in the pool", status.status.senders as i64);
+		registry.register_gauge("txpool_mem_bytes", "Memory used by the pooled transactions", status.status.mem_usage as i64);
+		registry.register_gauge("txpool_max_transactions", "Maximal number of transactions in the pool", status.limits.max_count as i64);
+	}
+}
+
+/// Reorder pending transactions by the priority fee they pay to the block author given
+/// the base fee of the block (EIP-1559), keeping the nonce order of every sender.
+fn order_by_effective_priority_fee(pending: Vec<Arc<VerifiedTransaction>>, base_fee: U256) -> Vec<Arc<VerifiedTransaction>> {
+	let fee = |tx: &Arc<VerifiedTransaction>| tx.signed().effective_priority_fee(Some(base_fee));
+
+	let len = pending.len();
+	let mut senders = Vec::new();
+	let mut queues: HashMap<Address, VecDeque<_>> = HashMap::new();
+	for tx in pending {
+		let sender = tx.signed().sender();
+		queues.entry(sender).or_insert_with(|| {
+			senders.push(sender);
+			VecDeque::new()
+		}).push_back(tx);
+	}
+
+	// Ties are resolved by the original order of the senders.
+	let mut heads = BinaryHeap::new();
+	for (index, sender) in senders.iter().enumerate() {
+		heads.push((fee(&queues[sender][0]), cmp::Reverse(index)));
+	}
+
+	let mut ordered = Vec::with_capacity(len);
+	while let Some((_, cmp::Reverse(index))) = heads.pop() {
+		let queue = queues.get_mut(&senders[index]).expect("every sender in the heap has a queue; qed");
+		ordered.push(queue.pop_front().expect("senders are only in the heap if their queue is not empty; qed"));
+		if let Some(next) = queue.front() {
+			heads.push((fee(next), cmp::Reverse(index)));
+		}
+	}
+	ordered
+}
+
+/// Move transactions of priority senders to the front, keeping the relative order
+/// of all transactions (and thus the nonce order of every sender) intact.
+fn priority_senders_first(priority: Vec<Arc<VerifiedTransaction>>, pending: Vec<Arc<VerifiedTransaction>>) -> Vec<Arc<VerifiedTransaction>> {
+	let included = priority.iter().map(|tx| tx.signed().hash()).collect::<HashSet<_>>();
+	let mut ordered = priority;
+	ordered.extend(pending.into_iter().filter(|tx| !included.contains(&tx.signed().hash())));
+	ordered
 }
 
 #[cfg(test)]
@@ -1335,9 +1582,15 @@ mod tests {
 				work_queue_size: 5,
 				enable_resubmission: true,
 				infinite_pending_block: false,
+				deterministic_block_template: false,
+				pending_block_cache: false,
 				tx_queue_penalization: Penalization::Disabled,
 				tx_queue_strategy: PrioritizationStrategy::GasPriceOnly,
 				tx_queue_no_unfamiliar_locals: false,
+				tx_queue_demotion_grace: Duration::from_secs(0),
+				tx_queue_replacement_bump: pool::scoring::DEFAULT_REPLACEMENT_BUMP,
+				tx_rebroadcast_after: None,
+				tx_priority_senders: HashSet::new(),
 				refuse_service_transactions: false,
 				pool_limits: Default::default(),
 				pool_verification_options: pool::verifier::Options {
@@ -1371,6 +1624,62 @@ mod tests {
 		}.sign(keypair.secret(), Some(chain_id))
 	}
 
+	#[test]
+	fn should_order_pending_transactions_by_effective_priority_fee() {
+		let transaction = |keypair: &::ethkey::KeyPair, nonce: u64, gas_price: u64, max_priority_fee: Option<u64>| {
+			let t = Transaction {
+				action: Action::Create,
+				value: U256::zero(),
+				data: vec![],
+				gas: U256::from(100_000),
+				gas_price: gas_price.into(),
+				nonce: nonce.into(),
+			};
+			let t = match max_priority_fee {
+				Some(fee) => t.sign_with_dynamic_fee(keypair.secret(), TEST_CHAIN_ID, vec![], fee.into()),
+				None => t.sign(keypair.secret(), Some(TEST_CHAIN_ID)),
+			};
+			Arc::new(VerifiedTransaction::from_pending_block_transaction(t))
+		};
+		let a = Random.generate().unwrap();
+		let b = Random.generate().unwrap();
+
+		// priority fees with a base fee of 10: a0 -> 2, a1 -> 10, b0 -> 3
+		let a0 = transaction(&a, 0, 12, None);
+		let a1 = transaction(&a, 1, 20, None);
+		let b0 = transaction(&b, 0, 30, Some(3));
+
+		let ordered = order_by_effective_priority_fee(vec![a0.clone(), a1.clone(), b0.clone()], 10.into());
+		let hashes: Vec<_> = ordered.iter().map(|tx| tx.signed().hash()).collect();
+		assert_eq!(hashes, vec![b0.signed().hash(), a0.signed().hash(), a1.signed().hash()]);
+	}
+
+	#[test]
+	fn should_order_transactions_of_priority_senders_first() {
+		let transaction = |keypair: &::ethkey::KeyPair, nonce: u64, gas_price: u64| {
+			let t = Transaction {
+				action: Action::Create,
+				value: U256::zero(),
+				data: vec![],
+				gas: U256::from(100_000),
+				gas_price: gas_price.into(),
+				nonce: nonce.into(),
+			}.sign(keypair.secret(), Some(TEST_CHAIN_ID));
+			Arc::new(VerifiedTransaction::from_pending_block_transaction(t))
+		};
+		let a = Random.generate().unwrap();
+		let oracle = Random.generate().unwrap();
+
+		let a0 = transaction(&a, 0, 30);
+		let o0 = transaction(&oracle, 0, 1);
+		let a1 = transaction(&a, 1, 20);
+		let o1 = transaction(&oracle, 1, 1);
+
+		let ordered = priority_senders_first(vec![o0.clone(), o1.clone()], vec![a0.clone(), o0.clone(), a1.clone()]);
+		let hashes: Vec<_> = ordered.iter().map(|tx| tx.signed().hash()).collect();
+		assert_eq!(hashes, vec![o0.signed().hash(), o1.signed().hash(), a0.signed().hash(), a1.signed().hash()]);
+	}
+
 	#[test]
 	fn should_make_pending_block_when_importing_own_transaction() {
 		// given
@@ -1430,6 +1739,94 @@ mod tests {
 		assert_eq!(miner.ready_transactions(&client, 10, PendingOrdering::Priority).len(), 1);
 	}
 
+	#[test]
+	fn should_import_transactions_into_pool_limited_by_memory() {
+		// given
+		let client = TestBlockChainClient::default();
+		let max_mem_usage = 64 * 1024 * 1024;
+		let miner = Miner::new(MinerOptions {
+			pool_limits: pool::Options {
+				max_count: pool::max_count_for_memory(max_mem_usage),
+				max_per_sender: 16,
+				max_mem_usage,
+			},
+			pool_verification_options: pool::verifier::Options {
+				minimal_gas_price: 0.into(),
+				block_gas_limit: U256::max_value(),
+				tx_gas_limit: U256::max_value(),
+				no_early_reject: false,
+			},
+			..Default::default()
+		}, GasPricer::new_fixed(0u64.into()), &Spec::new_test(), None);
+
+		// when
+		let res = miner.import_external_transactions(&client, vec![transaction().into()]).pop().unwrap();
+
+		// then
+		assert_eq!(res.unwrap(), ());
+		assert_eq!(miner.queue_status().status.transaction_count, 1);
+		assert!(miner.queue_status().limits.max_count < usize::max_value() / 1024);
+	}
+
+	#[test]
+	fn should_keep_pending_block_up_to_date_when_cache_is_enabled() {
+		// given
+		let client = TestBlockChainClient::default();
+		let miner = Miner::new(
+			MinerOptions {
+				pending_block_cache: true,
+				..miner().options
+			},
+			GasPricer::new_fixed(0u64.into()),
+			&Spec::new_test(),
+			None,
+		);
+		let best_block = 0;
+
+		// when
+		let res = miner.import_external_transactions(&client, vec![transaction().into()]).pop().unwrap();
+		assert_eq!(res.unwrap(), ());
+		assert_eq!(miner.pending_transactions(best_block).unwrap().len(), 1);
+		let res = miner.import_external_transactions(&client, vec![transaction().into()]).pop().unwrap();
+		assert_eq!(res.unwrap(), ());
+
+		// then
+		assert_eq!(miner.pending_transactions(best_block).unwrap().len(), 2);
+		assert_eq!(miner.pending_receipts(best_block).unwrap().len(), 2);
+		// Pending block is already there.
+		assert_eq!(miner.prepare_pending_block(&client), BlockPreparationStatus::NotPrepared);
+	}
+
+	#[test]
+	fn should_expose_candidates_used_for_pending_block() {
+		// given
+		let client = TestBlockChainClient::default();
+		let miner = Miner::new(
+			MinerOptions {
+				deterministic_block_template: true,
+				..miner().options
+			},
+			GasPricer::new_fixed(0u64.into()),
+			&Spec::new_test(),
+			None,
+		);
+		let transaction = transaction();
+		let hash = transaction.hash();
+		let best_block = 0;
+
+		// when
+		let res = miner.import_external_transactions(&client, vec![transaction.into()]).pop().unwrap();
+		assert_eq!(res.unwrap(), ());
+		assert_eq!(miner.pending_block_candidates(best_block), None);
+		assert_eq!(miner.prepare_pending_block(&client), BlockPreparationStatus::Succeeded);
+
+		// then
+		let candidates = miner.pending_block_candidates(best_block).unwrap();
+		assert_eq!(candidates.len(), 1);
+		assert_eq!(candidates[0].signed().hash(), hash);
+		assert_eq!(miner.pending_block_candidates(best_block + 2), None);
+	}
+
 	#[test]
 	fn should_treat_unfamiliar_locals_selectively() {
 		// given
@@ -20,6 +20,7 @@
 //! Keeps track of transactions and currently sealed pending block.
 
 mod miner;
+mod rebroadcast;
 mod service_transaction_checker;
 
 pub mod pool_client;
@@ -34,9 +35,9 @@ use std::collections::{BTreeSet, BTreeMap};
 
 use bytes::Bytes;
 use ethereum_types::{H256, U256, Address};
-use ethcore_miner::pool::{VerifiedTransaction, QueueStatus, local_transactions};
+use ethcore_miner::pool::{VerifiedTransaction, QueueStatus, Quotas, local_transactions};
 
-use block::{Block, SealedBlock};
+use block::{Block, ClosedBlock, SealedBlock};
 use client::{
 	CallContract, RegistryInfo, ScheduleInfo,
 	BlockChain, BlockProducer, SealedBlockImporter, ChainInfo,
@@ -80,6 +81,13 @@ pub trait MinerService : Send + Sync {
 	fn work_package<C>(&self, chain: &C) -> Option<(H256, BlockNumber, u64, U256)>
 		where C: BlockChain + CallContract + BlockProducer + SealedBlockImporter + Nonce + Sync;
 
+	/// Get the block that is being sealed, preparing it if doesn't exist yet.
+	/// The block can be sealed externally and submitted with `submit_seal`.
+	///
+	/// Returns `None` if engine seals internally.
+	fn block_template<C>(&self, chain: &C) -> Option<ClosedBlock>
+		where C: BlockChain + CallContract + BlockProducer + SealedBlockImporter + Nonce + Sync;
+
 	/// Update current pending block
 	fn update_sealing<C>(&self, chain: &C)
 		where C: BlockChain + CallContract + BlockProducer + SealedBlockImporter + Nonce + Sync;
@@ -115,6 +123,13 @@ pub trait MinerService : Send + Sync {
 	/// Get `Some` `clone()` of the current pending block transactions or `None` if we're not sealing.
 	fn pending_transactions(&self, latest_block_number: BlockNumber) -> Option<Vec<SignedTransaction>>;
 
+	/// Get the ordered list of candidate transactions used to prepare the current pending block
+	/// or `None` if we're not sealing.
+	///
+	/// Candidates are listed in the order they were attempted to be pushed to the block,
+	/// some of them might have been skipped (e.g. because of block gas limit).
+	fn pending_block_candidates(&self, latest_block_number: BlockNumber) -> Option<Vec<Arc<VerifiedTransaction>>>;
+
 	// Block authoring
 
 	/// Get current authoring parameters.
@@ -188,11 +203,26 @@ pub trait MinerService : Send + Sync {
 	/// Get a list of local transactions with statuses.
 	fn local_transactions(&self) -> BTreeMap<H256, local_transactions::Status>;
 
+	/// Get hashes of local transactions pending for long enough to be sent to peers again.
+	///
+	/// Each call advances the rebroadcast schedule, so returned transactions should be propagated.
+	fn transactions_to_rebroadcast(&self) -> Vec<H256>;
+
 	/// Get current queue status.
 	///
 	/// Status includes verification thresholds and current pool utilization and limits.
 	fn queue_status(&self) -> QueueStatus;
 
+	/// Set the gas price bump (in basis points) a transaction needs to replace
+	/// a queued one with the same sender and nonce.
+	fn set_replacement_bump(&self, bump: usize);
+
+	/// Get runtime quotas of the transaction queue.
+	fn queue_quotas(&self) -> Quotas;
+
+	/// Set runtime quotas of the transaction queue, tightening the limits set at startup.
+	fn set_queue_quotas(&self, quotas: Quotas);
+
 	// Misc
 
 	/// Suggested gas price.
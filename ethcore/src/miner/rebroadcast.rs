@@ -0,0 +1,127 @@
+// Copyright 2015-2018 Parity Technologies (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Rebroadcasting of stuck local transactions.
+//!
+//! Peers prune their pools, so a local transaction which was propagated once may
+//! silently disappear from the network. Transactions still pending after a while
+//! are sent again, each time waiting twice as long, up to a cap.
+
+use std::cmp;
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+
+use ethereum_types::H256;
+
+/// Maximal delay between two rebroadcasts of the same transaction.
+pub const MAX_REBROADCAST_BACKOFF: Duration = Duration::from_secs(2 * 60 * 60);
+
+#[derive(Debug)]
+struct Schedule {
+	next: Instant,
+	attempts: u32,
+}
+
+/// Rebroadcast schedule of pending local transactions.
+#[derive(Debug)]
+pub struct Rebroadcast {
+	after: Duration,
+	max_backoff: Duration,
+	scheduled: HashMap<H256, Schedule>,
+}
+
+impl Rebroadcast {
+	/// Rebroadcast transactions pending for longer than `after`,
+	/// never waiting more than `max_backoff` between attempts.
+	pub fn new(after: Duration, max_backoff: Duration) -> Self {
+		Rebroadcast {
+			after,
+			max_backoff: cmp::max(after, max_backoff),
+			scheduled: HashMap::new(),
+		}
+	}
+
+	/// Returns transactions due for rebroadcast and schedules their next attempt.
+	///
+	/// `pending` are all local transactions still in the pool. Transactions no longer
+	/// pending (e.g. mined or dropped) are forgotten, newly seen ones are scheduled.
+	pub fn due<I: IntoIterator<Item = H256>>(&mut self, pending: I, now: Instant) -> Vec<H256> {
+		let mut due = Vec::new();
+		let mut scheduled = HashMap::new();
+
+		for hash in pending {
+			let mut schedule = self.scheduled.remove(&hash).unwrap_or_else(|| Schedule {
+				next: now + self.after,
+				attempts: 0,
+			});
+
+			if now >= schedule.next {
+				schedule.attempts += 1;
+				schedule.next = now + self.backoff(schedule.attempts);
+				due.push(hash);
+			}
+
+			scheduled.insert(hash, schedule);
+		}
+
+		self.scheduled = scheduled;
+		due
+	}
+
+	fn backoff(&self, attempts: u32) -> Duration {
+		self.after
+			.checked_mul(1 << cmp::min(attempts, 16))
+			.map_or(self.max_backoff, |backoff| cmp::min(backoff, self.max_backoff))
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn should_rebroadcast_with_exponential_backoff() {
+		let start = Instant::now();
+		let minute = Duration::from_secs(60);
+		let mut rebroadcast = Rebroadcast::new(minute, minute * 5);
+		let tx = H256::from(1);
+
+		assert_eq!(rebroadcast.due(vec![tx], start), vec![]);
+		assert_eq!(rebroadcast.due(vec![tx], start + minute), vec![tx]);
+		// next attempt after 2 minutes
+		assert_eq!(rebroadcast.due(vec![tx], start + minute * 2), vec![]);
+		assert_eq!(rebroadcast.due(vec![tx], start + minute * 3), vec![tx]);
+		// 4 minutes later
+		assert_eq!(rebroadcast.due(vec![tx], start + minute * 7), vec![tx]);
+		// capped at 5 minutes
+		assert_eq!(rebroadcast.due(vec![tx], start + minute * 11), vec![]);
+		assert_eq!(rebroadcast.due(vec![tx], start + minute * 12), vec![tx]);
+	}
+
+	#[test]
+	fn should_forget_transactions_no_longer_pending() {
+		let start = Instant::now();
+		let minute = Duration::from_secs(60);
+		let mut rebroadcast = Rebroadcast::new(minute, minute * 5);
+		let tx = H256::from(1);
+
+		assert_eq!(rebroadcast.due(vec![tx], start), vec![]);
+		assert_eq!(rebroadcast.due(vec![], start + minute), vec![]);
+		// seen again, e.g. after a reorg, so the delay starts over
+		assert_eq!(rebroadcast.due(vec![tx], start + minute), vec![]);
+		assert_eq!(rebroadcast.due(vec![tx], start + minute * 2), vec![tx]);
+	}
+}
@@ -20,7 +20,7 @@ use std::fmt;
 use std::collections::BTreeMap;
 use itertools::Itertools;
 use ethereum_types::{H256, Address};
-use triehash::sec_trie_root;
+use triehash::par_sec_trie_root;
 use pod_account::{self, PodAccount};
 use types::state_diff::StateDiff;
 use ethjson;
@@ -41,7 +41,7 @@ impl PodState {
 
 	/// Get the root hash of the trie of the RLP of this.
 	pub fn root(&self) -> H256 {
-		sec_trie_root(self.0.iter().map(|(k, v)| (k, v.rlp())))
+		par_sec_trie_root(self.0.iter().map(|(k, v)| (k, v.rlp())))
 	}
 
 	/// Drain object to get the underlying map.
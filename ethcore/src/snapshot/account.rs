@@ -20,7 +20,7 @@ use account_db::{AccountDB, AccountDBMut};
 use basic_account::BasicAccount;
 use bytes::Bytes;
 use ethereum_types::{H256, U256};
-use ethtrie::{TrieDB, TrieDBMut};
+use ethtrie::{TrieDB, TrieDBMut, TrieBuilder};
 use hash::{KECCAK_EMPTY, KECCAK_NULL_RLP};
 use hashdb::HashDB;
 use rlp::{RlpStream, Rlp};
@@ -179,17 +179,22 @@ pub fn from_fat_rlp(
 		}
 	};
 
-	{
+	let pairs = rlp.at(4)?.iter()
+		.map(|pair_rlp| Ok((pair_rlp.val_at::<Bytes>(0)?, pair_rlp.val_at::<Bytes>(1)?)))
+		.collect::<Result<Vec<_>, Error>>()?;
+	let sorted = pairs.windows(2).all(|w| w[0].0 < w[1].0);
+
+	if storage_root.is_zero() && sorted {
+		// storage is written in trie order, build it bottom-up without intermediate nodes
+		storage_root = TrieBuilder::from_sorted_iter(acct_db, pairs)
+			.expect("keys are checked to be strictly ascending; qed");
+	} else {
 		let mut storage_trie = if storage_root.is_zero() {
 			TrieDBMut::new(acct_db, &mut storage_root)
 		} else {
 			TrieDBMut::from_existing(acct_db, &mut storage_root)?
 		};
-		let pairs = rlp.at(4)?;
-		for pair_rlp in pairs.iter() {
-			let k: Bytes  = pair_rlp.val_at(0)?;
-			let v: Bytes = pair_rlp.val_at(1)?;
-
+		for (k, v) in pairs {
 			storage_trie.insert(&k, &v)?;
 		}
 	}
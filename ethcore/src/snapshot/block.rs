@@ -17,7 +17,7 @@
 //! Block RLP compression.
 
 use block::Block;
-use header::Header;
+use header::{BlockNumber, Header};
 use hash::keccak;
 use views::BlockView;
 use rlp::{DecoderError, RlpStream, Rlp};
@@ -72,7 +72,7 @@ impl AbridgedBlock {
 		// write block values.
 		stream
 			.append_list(&block_view.transactions())
-			.append_list(&block_view.uncles());
+			.append_raw(block_view.uncles_rlp().as_raw(), 1);
 
 		// write seal fields.
 		for field in seal_fields {
@@ -85,9 +85,10 @@ impl AbridgedBlock {
 	}
 
 	/// Flesh out an abridged block view with the provided parent hash and block number.
+	/// From the EIP-1559 transition on, the last of the trailing fields is the base fee.
 	///
 	/// Will fail if contains invalid rlp.
-	pub fn to_block(&self, parent_hash: H256, number: u64, receipts_root: H256) -> Result<Block, DecoderError> {
+	pub fn to_block(&self, parent_hash: H256, number: u64, receipts_root: H256, eip1559_transition: BlockNumber) -> Result<Block, DecoderError> {
 		let rlp = Rlp::new(&self.rlp);
 
 		let mut header: Header = Default::default();
@@ -103,7 +104,9 @@ impl AbridgedBlock {
 		header.set_extra_data(rlp.val_at(7)?);
 
 		let transactions = rlp.list_at(8)?;
-		let uncles: Vec<Header> = rlp.list_at(9)?;
+		let uncles = rlp.at(9)?.iter()
+			.map(|r| Header::decode_rlp(&r, eip1559_transition))
+			.collect::<Result<Vec<_>, _>>()?;
 
 		header.set_transactions_root(ordered_trie_root(
 			rlp.at(8)?.iter().map(|r| r.as_raw())
@@ -114,8 +117,17 @@ impl AbridgedBlock {
 		uncles_rlp.append_list(&uncles);
 		header.set_uncles_hash(keccak(uncles_rlp.as_raw()));
 
+		let mut seal_end = rlp.item_count()?;
+		if number >= eip1559_transition {
+			if seal_end <= HEADER_FIELDS + BLOCK_FIELDS {
+				return Err(DecoderError::RlpIncorrectListLen);
+			}
+			seal_end -= 1;
+			header.set_base_fee(Some(rlp.val_at(seal_end)?));
+		}
+
 		let mut seal_fields = Vec::new();
-		for i in (HEADER_FIELDS + BLOCK_FIELDS)..rlp.item_count()? {
+		for i in (HEADER_FIELDS + BLOCK_FIELDS)..seal_end {
 			let seal_rlp = rlp.at(i)?;
 			seal_fields.push(seal_rlp.as_raw().to_owned());
 		}
@@ -134,6 +146,7 @@ impl AbridgedBlock {
 mod tests {
 	use views::BlockView;
 	use block::Block;
+	use header::BlockNumber;
 	use super::AbridgedBlock;
 	use transaction::{Action, Transaction};
 
@@ -151,7 +164,7 @@ mod tests {
 		let encoded = encode_block(&b);
 
 		let abridged = AbridgedBlock::from_block_view(&view!(BlockView, &encoded));
-		assert_eq!(abridged.to_block(H256::new(), 0, receipts_root).unwrap(), b);
+		assert_eq!(abridged.to_block(H256::new(), 0, receipts_root, BlockNumber::max_value()).unwrap(), b);
 	}
 
 	#[test]
@@ -162,7 +175,7 @@ mod tests {
 		let encoded = encode_block(&b);
 
 		let abridged = AbridgedBlock::from_block_view(&view!(BlockView, &encoded));
-		assert_eq!(abridged.to_block(H256::new(), 2, receipts_root).unwrap(), b);
+		assert_eq!(abridged.to_block(H256::new(), 2, receipts_root, BlockNumber::max_value()).unwrap(), b);
 	}
 
 	#[test]
@@ -198,6 +211,19 @@ mod tests {
 		let encoded = encode_block(&b);
 
 		let abridged = AbridgedBlock::from_block_view(&view!(BlockView, &encoded[..]));
-		assert_eq!(abridged.to_block(H256::new(), 0, receipts_root).unwrap(), b);
+		assert_eq!(abridged.to_block(H256::new(), 0, receipts_root, BlockNumber::max_value()).unwrap(), b);
+	}
+
+	#[test]
+	fn with_base_fee() {
+		let mut b = Block::default();
+		b.header.set_number(5);
+		b.header.set_seal(vec![::rlp::encode(&H256::from(1)).into_vec(), ::rlp::encode(&8u64).into_vec()]);
+		b.header.set_base_fee(Some(U256::from(7)));
+		let receipts_root = b.header.receipts_root().clone();
+		let encoded = encode_block(&b);
+
+		let abridged = AbridgedBlock::from_block_view(&view!(BlockView, &encoded));
+		assert_eq!(abridged.to_block(H256::new(), 5, receipts_root, 5).unwrap(), b);
 	}
 }
@@ -253,7 +253,7 @@ impl Rebuilder for PowRebuilder {
 			let receipts: Vec<::receipt::Receipt> = pair.list_at(1)?;
 			let receipts_root = ordered_trie_root(pair.at(1)?.iter().map(|r| r.as_raw()));
 
-			let block = abridged_block.to_block(parent_hash, cur_number, receipts_root)?;
+			let block = abridged_block.to_block(parent_hash, cur_number, receipts_root, engine.params().eip1559_transition)?;
 			let block_bytes = encoded::Block::new(block.rlp_bytes());
 			let is_best = cur_number == self.best_number;
 
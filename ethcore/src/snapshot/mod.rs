@@ -40,8 +40,8 @@ use bytes::Bytes;
 use parking_lot::Mutex;
 use journaldb::{self, Algorithm, JournalDB};
 use kvdb::KeyValueDB;
-use trie::{Trie, TrieMut};
-use ethtrie::{TrieDB, TrieDBMut};
+use trie::TrieMut;
+use ethtrie::{TrieDB, TrieDBMut, TrieBuilder, BoundedIterator};
 use rlp::{RlpStream, Rlp};
 use bloom_journal::Bloom;
 use num_cpus;
@@ -336,33 +336,28 @@ pub fn chunk_state<'a>(db: &HashDB<KeccakHasher>, root: &H256, writer: &Mutex<Sn
 
 	let mut used_code = HashSet::new();
 
-	// account_key here is the address' hash.
-	let mut account_iter = account_trie.iter()?;
-
+	let mut seek_from = vec![0; 32];
 	let mut seek_to = None;
 
 	if let Some(part) = part {
 		assert!(part < 16, "Wrong chunk state part number (must be <16) in snapshot creation.");
 
 		let part_offset = MAX_SNAPSHOT_SUBPARTS / SNAPSHOT_SUBPARTS;
-		let mut seek_from = vec![0; 32];
 		seek_from[0] = (part * part_offset) as u8;
-		account_iter.seek(&seek_from)?;
 
 		// Set the upper-bound, except for the last part
 		if part < SNAPSHOT_SUBPARTS - 1 {
-			seek_to = Some(((part + 1) * part_offset) as u8)
+			seek_to = Some(vec![((part + 1) * part_offset) as u8]);
 		}
 	}
 
+	// account_key here is the address' hash.
+	let account_iter = BoundedIterator::range(&account_trie, &seek_from, seek_to.as_ref().map(|end| &end[..]))?;
+
 	for item in account_iter {
 		let (account_key, account_data) = item?;
 		let account_key_hash = H256::from_slice(&account_key);
 
-		if seek_to.map_or(false, |seek_to| account_key[0] >= seek_to) {
-			break;
-		}
-
 		let account = ::rlp::decode(&*account_data)?;
 		let account_db = AccountDB::from_hash(db, account_key_hash);
 
@@ -390,6 +385,7 @@ pub struct StateRebuilder {
 	missing_code: HashMap<H256, Vec<H256>>, // maps code hashes to lists of accounts missing that code.
 	bloom: Bloom,
 	known_storage_roots: HashMap<H256, H256>, // maps account hashes to last known storage root. Only filled for last account per chunk.
+	account_trie: Option<TrieBuilder>, // builds the account trie while chunks arrive in order.
 }
 
 impl StateRebuilder {
@@ -402,6 +398,7 @@ impl StateRebuilder {
 			missing_code: HashMap::new(),
 			bloom: StateDB::load_bloom(&*db),
 			known_storage_roots: HashMap::new(),
+			account_trie: Some(TrieBuilder::new()),
 		}
 	}
 
@@ -439,8 +436,27 @@ impl StateRebuilder {
 
 		let backing = self.db.backing().clone();
 
+		// chunks arriving in order are built bottom-up, anything else is inserted into the trie.
+		if let Some(mut builder) = self.account_trie.take() {
+			let sorted = pairs.windows(2).all(|w| w[0].0 < w[1].0);
+			if sorted && pairs.first().map_or(true, |&(ref hash, _)| builder.accepts(hash)) {
+				for (hash, thin_rlp) in pairs.drain(..) {
+					if !flag.load(Ordering::SeqCst) { return Err(Error::RestorationAborted.into()) }
+
+					if &thin_rlp[..] != &empty_rlp[..] {
+						self.bloom.set(&*hash);
+					}
+					builder.insert(self.db.as_hashdb_mut(), &hash, &thin_rlp)
+						.expect("keys are checked to be ascending; qed");
+				}
+				self.account_trie = Some(builder);
+			} else {
+				self.state_root = builder.finish(self.db.as_hashdb_mut());
+			}
+		}
+
 		// batch trie writes
-		{
+		if self.account_trie.is_none() {
 			let mut account_trie = if self.state_root != KECCAK_NULL_RLP {
 				TrieDBMut::from_existing(self.db.as_hashdb_mut(), &mut self.state_root)?
 			} else {
@@ -473,6 +489,8 @@ impl StateRebuilder {
 		let missing = self.missing_code.keys().cloned().collect::<Vec<_>>();
 		if !missing.is_empty() { return Err(Error::MissingCode(missing).into()) }
 
+		self.state_root()?;
+
 		let mut batch = self.db.backing().transaction();
 		self.db.journal_under(&mut batch, era, &id)?;
 		self.db.backing().write_buffered(batch);
@@ -480,8 +498,18 @@ impl StateRebuilder {
 		Ok(self.db)
 	}
 
-	/// Get the state root of the rebuilder.
-	pub fn state_root(&self) -> H256 { self.state_root }
+	/// Get the state root of the rebuilder, writing out the account trie if it's still being built.
+	pub fn state_root(&mut self) -> Result<H256, ::error::Error> {
+		if let Some(builder) = self.account_trie.take() {
+			self.state_root = builder.finish(self.db.as_hashdb_mut());
+
+			let backing = self.db.backing().clone();
+			let mut batch = backing.transaction();
+			self.db.inject(&mut batch)?;
+			backing.write_buffered(batch);
+		}
+		Ok(self.state_root)
+	}
 }
 
 #[derive(Default)]
@@ -567,7 +595,7 @@ pub fn verify_old_block(rng: &mut OsRng, header: &Header, engine: &EthEngine, ch
 	if always || rng.gen::<f32>() <= POW_VERIFY_RATE {
 		engine.verify_block_unordered(header)?;
 		match chain.block_header_data(header.parent_hash()) {
-			Some(parent) => engine.verify_block_family(header, &parent.decode()?),
+			Some(parent) => engine.verify_block_family(header, &parent.decode(engine.params().eip1559_transition)?),
 			None => Ok(()),
 		}
 	} else {
@@ -19,14 +19,14 @@
 use std::collections::HashSet;
 use std::io::{self, Read, ErrorKind};
 use std::fs::{self, File};
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::sync::Arc;
 use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
 
 use super::{ManifestData, StateRebuilder, Rebuilder, RestorationStatus, SnapshotService, MAX_CHUNK_SIZE};
 use super::io::{SnapshotReader, LooseReader, SnapshotWriter, LooseWriter};
 
-use blockchain::{BlockChain, BlockChainDB, BlockChainDBHandler};
+use blockchain::{BlockChain, BlockChainDB, BlockChainDBHandler, Config as BlockChainConfig};
 use client::{Client, ChainInfo, ClientIoMessage};
 use engines::EthEngine;
 use error::{Error, ErrorKind as SnapshotErrorKind};
@@ -102,7 +102,11 @@ impl Restoration {
 
 		let raw_db = params.db;
 
-		let chain = BlockChain::new(Default::default(), params.genesis, raw_db.clone());
+		let chain_config = BlockChainConfig {
+			eip1559_transition: params.engine.params().eip1559_transition,
+			..Default::default()
+		};
+		let chain = BlockChain::new(chain_config, params.genesis, raw_db.clone());
 		let components = params.engine.snapshot_components()
 			.ok_or_else(|| ::snapshot::Error::SnapshotsUnsupported)?;
 
@@ -174,7 +178,7 @@ impl Restoration {
 		if !self.is_done() { return Ok(()) }
 
 		// verify final state root.
-		let root = self.state.state_root();
+		let root = self.state.state_root()?;
 		if root != self.final_state_root {
 			warn!("Final restored state has wrong state root: expected {:?}, got {:?}", self.final_state_root, root);
 			return Err(TrieError::InvalidStateRoot(root).into());
@@ -222,6 +226,11 @@ pub struct ServiceParams {
 	pub db_restore: Arc<DatabaseRestore>,
 }
 
+/// Get the directory of the latest local snapshot within the snapshot root.
+pub fn current_snapshot_dir(snapshot_root: &Path) -> PathBuf {
+	snapshot_root.join("current")
+}
+
 /// `SnapshotService` implementation.
 /// This controls taking snapshots and restoring from them.
 pub struct Service {
@@ -240,6 +249,7 @@ pub struct Service {
 	progress: super::Progress,
 	taking_snapshot: AtomicBool,
 	restoring_snapshot: AtomicBool,
+	interrupted_restoration: Mutex<Option<ManifestData>>,
 }
 
 impl Service {
@@ -261,6 +271,7 @@ impl Service {
 			progress: Default::default(),
 			taking_snapshot: AtomicBool::new(false),
 			restoring_snapshot: AtomicBool::new(false),
+			interrupted_restoration: Mutex::new(None),
 		};
 
 		// create the root snapshot dir if it doesn't exist.
@@ -284,6 +295,16 @@ impl Service {
 			}
 		}
 
+		// chunks received before the restart are kept and reused if the restoration is resumed.
+		if service.temp_recovery_dir().exists() {
+			let manifest = fs::read(service.restoration_manifest()).ok()
+				.and_then(|manifest| ManifestData::from_rlp(&manifest).ok());
+			if let Some(ref manifest) = manifest {
+				info!(target: "snapshot", "Found interrupted restoration of snapshot at #{}", manifest.block_number);
+			}
+			*service.interrupted_restoration.get_mut() = manifest;
+		}
+
 		let reader = LooseReader::new(service.snapshot_dir()).ok();
 		*service.reader.get_mut() = reader;
 
@@ -292,9 +313,7 @@ impl Service {
 
 	// get the current snapshot dir.
 	fn snapshot_dir(&self) -> PathBuf {
-		let mut dir = self.snapshot_root.clone();
-		dir.push("current");
-		dir
+		current_snapshot_dir(&self.snapshot_root)
 	}
 
 	// get the temporary snapshot dir.
@@ -325,6 +344,13 @@ impl Service {
 		dir
 	}
 
+	// path of the manifest of the ongoing restoration.
+	fn restoration_manifest(&self) -> PathBuf {
+		let mut path = self.restoration_dir();
+		path.push("manifest");
+		path
+	}
+
 	// previous snapshot chunks path.
 	fn prev_chunks_dir(&self) -> PathBuf {
 		let mut dir = self.snapshot_root.clone();
@@ -407,6 +433,10 @@ impl Service {
 		*reader = Some(LooseReader::new(snapshot_dir)?);
 
 		guard.disarm();
+		drop(reader);
+
+		// restore the state of the new snapshot right away, so calls don't have to wait for it.
+		client.restore_cold_state();
 		Ok(())
 	}
 
@@ -439,6 +469,7 @@ impl Service {
 
 		// tear down existing restoration.
 		*res = None;
+		*self.interrupted_restoration.lock() = None;
 
 		// delete and restore the restoration dir.
 		if let Err(e) = fs::remove_dir_all(&rest_dir) {
@@ -454,6 +485,9 @@ impl Service {
 
 		fs::create_dir_all(&rest_dir)?;
 
+		// journal the manifest so that the restoration can be resumed after a restart.
+		fs::write(self.restoration_manifest(), manifest.clone().into_rlp())?;
+
 		// make new restoration.
 		let writer = match recover {
 			true => Some(LooseWriter::new(recovery_temp)?),
@@ -674,6 +708,10 @@ impl SnapshotService for Service {
 		self.reader.read().as_ref().and_then(|r| r.chunk(hash).ok())
 	}
 
+	fn interrupted_restoration(&self) -> Option<ManifestData> {
+		self.interrupted_restoration.lock().clone()
+	}
+
 	fn completed_chunks(&self) -> Option<Vec<H256>> {
 		let restoration = self.restoration.lock();
 
@@ -146,3 +146,38 @@ fn guards_delete_folders() {
 	assert!(!path.join("db").exists());
 	assert!(path.join("temp").exists());
 }
+
+#[test]
+fn keeps_interrupted_restoration() {
+	let spec = Spec::new_null();
+	let tempdir = TempDir::new("").unwrap();
+	let service_params = || ServiceParams {
+		engine: spec.engine.clone(),
+		genesis_block: spec.genesis_block(),
+		restoration_db_handler: restoration_db_handler(DatabaseConfig::with_columns(::db::NUM_COLUMNS)),
+		pruning: ::journaldb::Algorithm::Archive,
+		channel: IoChannel::disconnected(),
+		snapshot_root: tempdir.path().to_owned(),
+		db_restore: Arc::new(NoopDBRestore),
+	};
+
+	let manifest = ManifestData {
+		version: 2,
+		state_hashes: vec![1.into()],
+		block_hashes: vec![2.into()],
+		block_number: 10,
+		block_hash: Default::default(),
+		state_root: Default::default(),
+	};
+
+	let service = Service::new(service_params()).unwrap();
+	assert_eq!(service.interrupted_restoration(), None);
+	service.init_restore(manifest.clone(), true).unwrap();
+	drop(service);
+
+	// The restoration is remembered after a restart until it's resumed
+	let service = Service::new(service_params()).unwrap();
+	assert_eq!(service.interrupted_restoration(), Some(manifest.clone()));
+	service.init_restore(manifest, true).unwrap();
+	assert_eq!(service.interrupted_restoration(), None);
+}
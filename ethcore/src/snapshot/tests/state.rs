@@ -83,7 +83,7 @@ fn snap_and_restore() {
 			rebuilder.feed(&chunk, &flag).unwrap();
 		}
 
-		assert_eq!(rebuilder.state_root(), state_root);
+		assert_eq!(rebuilder.state_root().unwrap(), state_root);
 		rebuilder.finalize(1000, H256::default()).unwrap();
 
 		new_db
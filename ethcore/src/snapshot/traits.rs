@@ -30,6 +30,10 @@ pub trait SnapshotService : Sync + Send {
 	/// `None` indicates warp sync isn't supported by the consensus engine.
 	fn supported_versions(&self) -> Option<(u64, u64)>;
 
+	/// Returns the manifest of a restoration interrupted by a restart.
+	/// Chunks received before the restart are reused if the restoration is resumed with `begin_restore`.
+	fn interrupted_restoration(&self) -> Option<ManifestData>;
+
 	/// Returns a list of the completed chunks
 	fn completed_chunks(&self) -> Option<Vec<H256>>;
 
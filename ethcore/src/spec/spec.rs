@@ -17,6 +17,7 @@
 //! Parameters for a block chain.
 
 use std::collections::BTreeMap;
+use std::fmt;
 use std::io::Read;
 use std::path::Path;
 use std::sync::Arc;
@@ -31,7 +32,7 @@ use rlp::{Rlp, RlpStream};
 use rustc_hex::{FromHex, ToHex};
 use vm::{EnvInfo, CallType, ActionValue, ActionParams, ParamsType};
 
-use builtin::Builtin;
+use builtin::{Builtin, BuiltinRegistry};
 use encoded;
 use engines::{EthEngine, NullEngine, InstantSeal, BasicAuthority, AuthorityRound, Tendermint, DEFAULT_BLOCKHASH_CONTRACT};
 use error::Error;
@@ -49,6 +50,10 @@ use trace::{NoopTracer, NoopVMTracer};
 pub use ethash::OptimizeFor;
 
 const MAX_TRANSACTION_SIZE: usize = 300 * 1024;
+/// Default future drift (ms) after which blocks are held back.
+const DEFAULT_ACCEPTABLE_TIMESTAMP_DRIFT: u64 = 15_000;
+/// Default future drift (ms) after which blocks are rejected.
+const DEFAULT_INVALID_TIMESTAMP_DRIFT: u64 = 150_000;
 
 // helper for formatting errors.
 fn fmt_err<F: ::std::fmt::Display>(f: F) -> String {
@@ -117,6 +122,24 @@ pub struct CommonParams {
 	pub eip1052_transition: BlockNumber,
 	/// Number of first block where EIP-1283 rules begin.
 	pub eip1283_transition: BlockNumber,
+	/// Number of first block where EIP-2200 rules begin.
+	pub eip2200_transition: BlockNumber,
+	/// Number of first block where EIP-1344 rules begin.
+	pub eip1344_transition: BlockNumber,
+	/// Number of first block where EIP-1884 rules begin.
+	pub eip1884_transition: BlockNumber,
+	/// Number of first block where EIP-2929 rules begin.
+	pub eip2929_transition: BlockNumber,
+	/// Number of first block where EIP-2930 rules begin.
+	pub eip2930_transition: BlockNumber,
+	/// Number of first block where EIP-1559 rules begin.
+	pub eip1559_transition: BlockNumber,
+	/// Bound divisor of the base fee change between blocks (EIP-1559).
+	pub eip1559_base_fee_max_change_denominator: U256,
+	/// Ratio of the gas limit to the gas target of a block (EIP-1559).
+	pub eip1559_elasticity_multiplier: U256,
+	/// Base fee of the first EIP-1559 block.
+	pub eip1559_base_fee_initial_value: U256,
 	/// Number of first block where EIP-1014 rules begin.
 	pub eip1014_transition: BlockNumber,
 	/// Number of first block where dust cleanup rules (EIP-168 and EIP169) begin.
@@ -131,6 +154,16 @@ pub struct CommonParams {
 	pub kip4_transition: BlockNumber,
 	/// Number of first block where KIP-6 rules begin. Only has effect if Wasm is activated.
 	pub kip6_transition: BlockNumber,
+	/// Wasm instruction and memory costs applied once Wasm is activated.
+	pub wasm_costs: ::vm::WasmCosts,
+	/// Maximal number of items on the EVM stack.
+	pub stack_limit: usize,
+	/// Maximal depth of nested calls and creates.
+	pub max_call_depth: usize,
+	/// Linear memory cost, per word.
+	pub memory_gas: usize,
+	/// Divisor of the quadratic memory cost.
+	pub quad_coeff_div: usize,
 	/// Gas limit bound divisor (how much gas limit can change per block)
 	pub gas_limit_bound_divisor: U256,
 	/// Registrar contract address.
@@ -145,15 +178,29 @@ pub struct CommonParams {
 	pub transaction_permission_contract: Option<Address>,
 	/// Block at which the transaction permission contract should start being used.
 	pub transaction_permission_contract_transition: BlockNumber,
+	/// Invariant contract address, called after every imported block.
+	pub invariant_contract: Option<Address>,
+	/// Block at which the invariant contract should start being called.
+	pub invariant_contract_transition: BlockNumber,
+	/// Whether to reject blocks violating the invariants or only to report them.
+	pub invariant_contract_halt: bool,
 	/// Maximum size of transaction's RLP payload
 	pub max_transaction_size: usize,
+	/// How far into the future (in milliseconds) a block timestamp may be before the block
+	/// is held back as temporarily invalid.
+	pub acceptable_timestamp_drift: u64,
+	/// How far into the future (in milliseconds) a block timestamp may be before the block
+	/// is rejected outright. Never less than `acceptable_timestamp_drift`.
+	pub invalid_timestamp_drift: u64,
 }
 
 impl CommonParams {
 	/// Schedule for an EVM in the post-EIP-150-era of the Ethereum main net.
 	pub fn schedule(&self, block_number: u64) -> ::vm::Schedule {
 		if block_number < self.eip150_transition {
-			::vm::Schedule::new_homestead()
+			let mut schedule = ::vm::Schedule::new_homestead();
+			self.update_evm_limits(block_number, &mut schedule);
+			schedule
 		} else {
 			let max_code_size = self.max_code_size(block_number);
 			let mut schedule = ::vm::Schedule::new_post_eip150(
@@ -177,15 +224,51 @@ impl CommonParams {
 		}
 	}
 
+	/// Apply spec-configured EVM limits and memory costs to the schedule.
+	pub fn update_evm_limits(&self, block_number: u64, schedule: &mut ::vm::Schedule) {
+		schedule.stack_limit = self.stack_limit;
+		schedule.max_depth = self.max_call_depth;
+		schedule.memory_gas = self.memory_gas;
+		schedule.quad_coeff_div = self.quad_coeff_div;
+		schedule.create_data_limit = self.max_code_size(block_number) as _;
+	}
+
 	/// Apply common spec config parameters to the schedule.
 	pub fn update_schedule(&self, block_number: u64, schedule: &mut ::vm::Schedule) {
+		self.update_evm_limits(block_number, schedule);
 		schedule.have_create2 = block_number >= self.eip1014_transition;
 		schedule.have_revert = block_number >= self.eip140_transition;
 		schedule.have_static_call = block_number >= self.eip214_transition;
 		schedule.have_return_data = block_number >= self.eip211_transition;
 		schedule.have_bitwise_shifting = block_number >= self.eip145_transition;
 		schedule.have_extcodehash = block_number >= self.eip1052_transition;
-		schedule.eip1283 = block_number >= self.eip1283_transition;
+		schedule.eip1283 = block_number >= self.eip1283_transition || block_number >= self.eip2200_transition;
+		schedule.have_chain_id = block_number >= self.eip1344_transition;
+		if block_number >= self.eip1884_transition {
+			schedule.have_selfbalance = true;
+			schedule.sload_gas = 800;
+			schedule.balance_gas = 700;
+			schedule.extcodehash_gas = 700;
+		}
+		if block_number >= self.eip2200_transition {
+			schedule.eip1706 = true;
+			schedule.sstore_dirty_gas = Some(800);
+		}
+		if block_number >= self.eip2929_transition {
+			schedule.eip2929 = true;
+			schedule.eip1283 = true;
+			// prices of cold accesses, warm ones cost `warm_storage_read_cost`
+			schedule.sload_gas = 2100;
+			schedule.call_gas = 2600;
+			schedule.balance_gas = 2600;
+			schedule.extcodesize_gas = 2600;
+			schedule.extcodecopy_base_gas = 2600;
+			schedule.extcodehash_gas = 2600;
+			schedule.sstore_dirty_gas = Some(schedule.warm_storage_read_cost);
+			schedule.sstore_reset_gas = 2900;
+		}
+		schedule.eip2930 = block_number >= self.eip2930_transition;
+		schedule.eip1559 = block_number >= self.eip1559_transition;
 		if block_number >= self.eip210_transition {
 			schedule.blockhash_gas = 800;
 		}
@@ -196,7 +279,7 @@ impl CommonParams {
 			};
 		}
 		if block_number >= self.wasm_activation_transition {
-			let mut wasm = ::vm::WasmCosts::default();
+			let mut wasm = self.wasm_costs.clone();
 			if block_number >= self.kip4_transition {
 				wasm.have_create2 = true;
 			}
@@ -227,6 +310,8 @@ impl CommonParams {
 
 impl From<ethjson::spec::Params> for CommonParams {
 	fn from(p: ethjson::spec::Params) -> Self {
+		let acceptable_timestamp_drift = p.acceptable_timestamp_drift.map_or(DEFAULT_ACCEPTABLE_TIMESTAMP_DRIFT, Into::into);
+		let evm = p.evm.unwrap_or_default();
 		CommonParams {
 			account_start_nonce: p.account_start_nonce.map_or_else(U256::zero, Into::into),
 			maximum_extra_data_size: p.maximum_extra_data_size.into(),
@@ -293,6 +378,33 @@ impl From<ethjson::spec::Params> for CommonParams {
 				BlockNumber::max_value,
 				Into::into,
 			),
+			eip2200_transition: p.eip2200_transition.map_or_else(
+				BlockNumber::max_value,
+				Into::into,
+			),
+			eip1344_transition: p.eip1344_transition.map_or_else(
+				BlockNumber::max_value,
+				Into::into,
+			),
+			eip1884_transition: p.eip1884_transition.map_or_else(
+				BlockNumber::max_value,
+				Into::into,
+			),
+			eip2929_transition: p.eip2929_transition.map_or_else(
+				BlockNumber::max_value,
+				Into::into,
+			),
+			eip2930_transition: p.eip2930_transition.map_or_else(
+				BlockNumber::max_value,
+				Into::into,
+			),
+			eip1559_transition: p.eip1559_transition.map_or_else(
+				BlockNumber::max_value,
+				Into::into,
+			),
+			eip1559_base_fee_max_change_denominator: p.eip1559_base_fee_max_change_denominator.map_or(8.into(), Into::into),
+			eip1559_elasticity_multiplier: p.eip1559_elasticity_multiplier.map_or(2.into(), Into::into),
+			eip1559_base_fee_initial_value: p.eip1559_base_fee_initial_value.map_or(1_000_000_000.into(), Into::into),
 			eip1014_transition: p.eip1014_transition.map_or_else(
 				BlockNumber::max_value,
 				Into::into,
@@ -312,6 +424,9 @@ impl From<ethjson::spec::Params> for CommonParams {
 			transaction_permission_contract: p.transaction_permission_contract.map(Into::into),
 			transaction_permission_contract_transition:
 				p.transaction_permission_contract_transition.map_or(0, Into::into),
+			invariant_contract: p.invariant_contract.map(Into::into),
+			invariant_contract_transition: p.invariant_contract_transition.map_or(0, Into::into),
+			invariant_contract_halt: p.invariant_contract_halt.unwrap_or(true),
 			wasm_activation_transition: p.wasm_activation_transition.map_or_else(
 				BlockNumber::max_value,
 				Into::into
@@ -324,13 +439,43 @@ impl From<ethjson::spec::Params> for CommonParams {
 				BlockNumber::max_value,
 				Into::into
 			),
+			wasm_costs: p.wasm.map_or_else(::vm::WasmCosts::default, wasm_costs),
+			stack_limit: evm.stack_limit.map_or(1024, Into::into),
+			max_call_depth: evm.max_call_depth.map_or(1024, Into::into),
+			memory_gas: evm.memory_gas.map_or(3, Into::into),
+			quad_coeff_div: evm.quad_coeff_div.map_or(512, Into::into),
+			acceptable_timestamp_drift: acceptable_timestamp_drift,
+			invalid_timestamp_drift: ::std::cmp::max(
+				p.invalid_timestamp_drift.map_or(DEFAULT_INVALID_TIMESTAMP_DRIFT, Into::into),
+				acceptable_timestamp_drift,
+			),
 		}
 	}
 }
 
+fn wasm_costs(costs: ethjson::spec::WasmCosts) -> ::vm::WasmCosts {
+	let defaults = ::vm::WasmCosts::default();
+	::vm::WasmCosts {
+		regular: costs.regular.map_or(defaults.regular, Into::into),
+		div: costs.div.map_or(defaults.div, Into::into),
+		mul: costs.mul.map_or(defaults.mul, Into::into),
+		mem: costs.mem.map_or(defaults.mem, Into::into),
+		static_u256: costs.static_u256.map_or(defaults.static_u256, Into::into),
+		static_address: costs.static_address.map_or(defaults.static_address, Into::into),
+		initial_mem: costs.initial_mem.map_or(defaults.initial_mem, Into::into),
+		grow_mem: costs.grow_mem.map_or(defaults.grow_mem, Into::into),
+		memcpy: costs.memcpy.map_or(defaults.memcpy, Into::into),
+		max_stack_height: costs.max_stack_height.map_or(defaults.max_stack_height, Into::into),
+		opcodes_mul: costs.opcodes_mul.map_or(defaults.opcodes_mul, Into::into),
+		opcodes_div: costs.opcodes_div.map_or(defaults.opcodes_div, Into::into),
+		have_create2: defaults.have_create2,
+		have_gasleft: defaults.have_gasleft,
+	}
+}
+
 /// Runtime parameters for the spec that are related to how the software should run the chain,
 /// rather than integral properties of the chain itself.
-#[derive(Debug, Clone, Copy)]
+#[derive(Clone, Copy)]
 pub struct SpecParams<'a> {
 	/// The path to the folder used to cache nodes. This is typically /tmp/ on Unix-like systems
 	pub cache_dir: &'a Path,
@@ -339,6 +484,8 @@ pub struct SpecParams<'a> {
 	/// memory. This may get more fine-grained in the future but for now is simply a binary
 	/// option.
 	pub optimization_setting: Option<OptimizeFor>,
+	/// Source of additional native builtins referenced by name from the spec.
+	pub builtin_registry: Option<&'a BuiltinRegistry>,
 }
 
 impl<'a> SpecParams<'a> {
@@ -347,6 +494,7 @@ impl<'a> SpecParams<'a> {
 		SpecParams {
 			cache_dir: path,
 			optimization_setting: None,
+			builtin_registry: None,
 		}
 	}
 
@@ -355,8 +503,25 @@ impl<'a> SpecParams<'a> {
 		SpecParams {
 			cache_dir: path,
 			optimization_setting: Some(optimization),
+			builtin_registry: None,
 		}
 	}
+
+	/// Resolve builtins not shipped with ethcore through the given registry.
+	pub fn with_builtin_registry(mut self, registry: &'a BuiltinRegistry) -> Self {
+		self.builtin_registry = Some(registry);
+		self
+	}
+}
+
+impl<'a> fmt::Debug for SpecParams<'a> {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		f.debug_struct("SpecParams")
+			.field("cache_dir", &self.cache_dir)
+			.field("optimization_setting", &self.optimization_setting)
+			.field("builtin_registry", &self.builtin_registry.is_some())
+			.finish()
+	}
 }
 
 impl<'a, T: AsRef<Path>> From<&'a T> for SpecParams<'a> {
@@ -489,7 +654,7 @@ fn load_from(spec_params: SpecParams, s: ethjson::spec::Spec) -> Result<Spec, Er
 	let builtins = s.accounts
 		.builtins()
 		.into_iter()
-		.map(|p| (p.0.into(), From::from(p.1)))
+		.map(|p| (p.0.into(), Builtin::from_spec(p.1, spec_params.builtin_registry)))
 		.collect();
 	let g = Genesis::from(s.genesis);
 	let GenericSeal(seal_rlp) = g.seal.into();
@@ -643,6 +808,7 @@ impl Spec {
 				difficulty: self.difficulty,
 				last_hashes: Default::default(),
 				gas_used: U256::zero(),
+				base_fee: None,
 				gas_limit: U256::max_value(),
 			};
 
@@ -861,6 +1027,7 @@ impl Spec {
 				gas_limit: U256::max_value(),
 				last_hashes: Arc::new(Vec::new()),
 				gas_used: 0.into(),
+				base_fee: None,
 			};
 
 			let from = Address::default();
@@ -912,6 +1079,11 @@ impl Spec {
 	#[cfg(any(test, feature = "test-helpers"))]
 	pub fn new_test_with_reward() -> Spec { load_bundled!("null_morden_with_reward") }
 
+	/// Create a new Spec which is a NullEngine consensus with an invariant contract
+	/// which is violated by all blocks after the first one.
+	#[cfg(any(test, feature = "test-helpers"))]
+	pub fn new_test_with_invariant() -> Spec { load_bundled!("null_morden_with_invariant") }
+
 	/// Create a new Spec which is a NullEngine consensus with a premine of address whose
 	/// secret is keccak('').
 	#[cfg(any(test, feature = "test-helpers"))]
@@ -1018,6 +1190,51 @@ mod tests {
 		);
 	}
 
+	#[test]
+	fn default_timestamp_drift() {
+		let test_spec = Spec::new_test();
+		assert_eq!(test_spec.params().acceptable_timestamp_drift, 15_000);
+		assert_eq!(test_spec.params().invalid_timestamp_drift, 150_000);
+	}
+
+	#[test]
+	fn evm_limits_from_spec() {
+		let tempdir = TempDir::new("").unwrap();
+		let json = include_str!("../../res/null_morden.json").replace(
+			r#""networkID" : "0x2""#,
+			r#""networkID" : "0x2", "evm": { "maxCallDepth": "0x800", "quadCoeffDiv": "0x400" }"#,
+		);
+		let spec = Spec::load(&tempdir.path(), json.as_bytes()).unwrap();
+
+		let schedule = spec.engine.schedule(0);
+		assert_eq!(schedule.max_depth, 2048);
+		assert_eq!(schedule.quad_coeff_div, 1024);
+		assert_eq!(schedule.stack_limit, 1024);
+		assert_eq!(schedule.memory_gas, 3);
+
+		let invalid = json.replace(r#""0x800""#, r#""0x0""#);
+		assert!(Spec::load(&tempdir.path(), invalid.as_bytes()).is_err());
+	}
+
+	#[test]
+	fn eip1559_params_from_spec() {
+		let tempdir = TempDir::new("").unwrap();
+		let json = include_str!("../../res/null_morden.json").replace(
+			r#""networkID" : "0x2""#,
+			r#""networkID" : "0x2", "eip1559ElasticityMultiplier": "0x4", "eip1559BaseFeeMaxChangeDenominator": "0x10""#,
+		);
+		let spec = Spec::load(&tempdir.path(), json.as_bytes()).unwrap();
+		assert_eq!(spec.params().eip1559_elasticity_multiplier, 4.into());
+		assert_eq!(spec.params().eip1559_base_fee_max_change_denominator, 16.into());
+
+		let zero_multiplier = json.replace(r#""eip1559ElasticityMultiplier": "0x4""#, r#""eip1559ElasticityMultiplier": "0x0""#);
+		assert!(Spec::load(&tempdir.path(), zero_multiplier.as_bytes()).is_err());
+		let zero_denominator = json.replace(r#""0x10""#, r#""0x0""#);
+		assert!(Spec::load(&tempdir.path(), zero_denominator.as_bytes()).is_err());
+		let huge_multiplier = json.replace(r#""eip1559ElasticityMultiplier": "0x4""#, r#""eip1559ElasticityMultiplier": "0xffffffffffffffffffffffff""#);
+		assert!(Spec::load(&tempdir.path(), huge_multiplier.as_bytes()).is_err());
+	}
+
 	#[test]
 	fn genesis_constructor() {
 		::ethcore_logger::init_log();
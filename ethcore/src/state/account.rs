@@ -18,7 +18,7 @@
 
 use std::fmt;
 use std::sync::Arc;
-use std::collections::{HashMap, BTreeMap};
+use std::collections::{HashMap, BTreeMap, BTreeSet};
 use hash::{KECCAK_EMPTY, KECCAK_NULL_RLP, keccak};
 use ethereum_types::{H256, U256, Address};
 use error::Error;
@@ -26,8 +26,8 @@ use hashdb::HashDB;
 use keccak_hasher::KeccakHasher;
 use kvdb::DBValue;
 use bytes::{Bytes, ToPretty};
-use trie::{Trie, Recorder};
-use ethtrie::{TrieFactory, TrieDB, SecTrieDB, Result as TrieResult};
+use trie::Trie;
+use ethtrie::{TrieFactory, TrieDB, SecTrieDB, TrieProof, Result as TrieResult};
 use pod_account::*;
 use rlp::{RlpStream, encode};
 use lru_cache::LruCache;
@@ -455,6 +455,34 @@ impl Account {
 	/// Return the storage overlay.
 	pub fn storage_changes(&self) -> &HashMap<H256, H256> { &self.storage_changes }
 
+	/// Keys of all storage items read or written since the account was loaded.
+	pub fn touched_storage_keys(&self) -> BTreeSet<H256> {
+		let mut keys = self.storage_changes.keys().cloned().collect::<BTreeSet<_>>();
+		keys.extend(self.storage_cache.borrow().iter().map(|(k, _)| *k));
+		if let Some((_, ref original_storage_cache)) = self.original_storage_cache {
+			keys.extend(original_storage_cache.borrow().iter().map(|(k, _)| *k));
+		}
+		keys
+	}
+
+	/// Replace the whole storage of this account, discarding any values stored in the trie.
+	pub fn replace_storage(&mut self, storage: HashMap<H256, H256>) {
+		self.storage_root = KECCAK_NULL_RLP;
+		self.original_storage_cache = None;
+		self.storage_cache = Self::empty_storage_cache();
+		self.storage_changes = storage;
+	}
+
+	/// Set the nonce of the account.
+	pub fn set_nonce(&mut self, nonce: U256) {
+		self.nonce = nonce;
+	}
+
+	/// Set the balance of the account.
+	pub fn set_balance(&mut self, balance: U256) {
+		self.balance = balance;
+	}
+
 	/// Increment the nonce of the account by one.
 	pub fn inc_nonce(&mut self) {
 		self.nonce = self.nonce + U256::from(1u8);
@@ -580,16 +608,14 @@ impl Account {
 	/// `storage_key` is the hash of the desired storage key, meaning
 	/// this will only work correctly under a secure trie.
 	pub fn prove_storage(&self, db: &HashDB<KeccakHasher>, storage_key: H256) -> TrieResult<(Vec<Bytes>, H256)> {
-		let mut recorder = Recorder::new();
-
 		let trie = TrieDB::new(db, &self.storage_root)?;
+		let proof = trie.prove(&storage_key)?;
 		let item: U256 = {
 			let panicky_decoder = |bytes:&[u8]| ::rlp::decode(bytes).expect("decoding db value failed");
-			let query = (&mut recorder, panicky_decoder);
-			trie.get_with(&storage_key, query)?.unwrap_or_else(U256::zero)
+			trie.get_with(&storage_key, panicky_decoder)?.unwrap_or_else(U256::zero)
 		};
 
-		Ok((recorder.drain().into_iter().map(|r| r.data).collect(), item.into()))
+		Ok((proof, item.into()))
 	}
 }
 
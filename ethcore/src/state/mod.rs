@@ -48,16 +48,22 @@ use keccak_hasher::KeccakHasher;
 use kvdb::DBValue;
 use bytes::Bytes;
 
-use trie::{Trie, TrieError, Recorder};
-use ethtrie::{TrieDB, Result as TrieResult};
+use trie::{Trie, TrieError};
+use ethtrie::{TrieDB, TrieProof, Result as TrieResult};
+use rayon::prelude::*;
+
+use self::overlay::{Overlay, apply_changes};
 
 mod account;
+mod overlay;
+mod producer;
 mod substate;
 
 pub mod backend;
 
 pub use self::account::Account;
 pub use self::backend::Backend;
+pub use self::producer::{StateProducer, ExportedAccount};
 pub use self::substate::Substate;
 
 /// Used to return information about an `State::apply` operation.
@@ -313,6 +319,8 @@ pub struct State<B> {
 	cache: RefCell<HashMap<Address, AccountEntry>>,
 	// The original account is preserved in
 	checkpoints: RefCell<Vec<HashMap<Address, Option<AccountEntry>>>>,
+	// Addresses of all accounts accessed while recording is enabled.
+	access_log: RefCell<Option<HashSet<Address>>>,
 	account_start_nonce: U256,
 	factories: Factories,
 }
@@ -335,6 +343,24 @@ pub enum CleanupMode<'a> {
 	TrackTouched(&'a mut HashSet<Address>),
 }
 
+/// Temporary modifications of a single account, applied to the state before a call.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct AccountOverride {
+	/// Balance to use instead of the current one.
+	pub balance: Option<U256>,
+	/// Nonce to use instead of the current one.
+	pub nonce: Option<U256>,
+	/// Code to use instead of the current one.
+	pub code: Option<Bytes>,
+	/// Storage replacing the whole account storage.
+	pub state: Option<HashMap<H256, H256>>,
+	/// Storage slots to override, leaving all the other slots intact.
+	pub state_diff: Option<HashMap<H256, H256>>,
+}
+
+/// Account overrides keyed by address.
+pub type StateOverride = BTreeMap<Address, AccountOverride>;
+
 /// Provides subset of `State` methods to query state information
 pub trait StateInfo {
 	/// Get the nonce of account `a`.
@@ -360,6 +386,9 @@ impl<B: Backend> StateInfo for State<B> {
 const SEC_TRIE_DB_UNWRAP_STR: &'static str = "A state can only be created with valid root. Creating a SecTrieDB with a valid root will not fail. \
 			 Therefore creating a SecTrieDB with this state's root will not fail.";
 
+/// Minimal number of accounts with storage changes for their storage tries to be committed in parallel.
+const PARALLEL_COMMIT_THRESHOLD: usize = 4;
+
 impl<B: Backend> State<B> {
 	/// Creates new state with empty state root
 	/// Used for tests.
@@ -375,6 +404,7 @@ impl<B: Backend> State<B> {
 			root: root,
 			cache: RefCell::new(HashMap::new()),
 			checkpoints: RefCell::new(Vec::new()),
+			access_log: RefCell::new(None),
 			account_start_nonce: account_start_nonce,
 			factories: factories,
 		}
@@ -391,6 +421,7 @@ impl<B: Backend> State<B> {
 			root: root,
 			cache: RefCell::new(HashMap::new()),
 			checkpoints: RefCell::new(Vec::new()),
+			access_log: RefCell::new(None),
 			account_start_nonce: account_start_nonce,
 			factories: factories
 		};
@@ -457,7 +488,25 @@ impl<B: Backend> State<B> {
 		}
 	}
 
+	/// Replace the access log. Recording of accessed accounts is enabled with `Some`
+	/// and disabled with `None`.
+	pub fn set_access_log(&self, log: Option<HashSet<Address>>) {
+		*self.access_log.borrow_mut() = log;
+	}
+
+	/// Stop recording accessed accounts and return the ones recorded so far.
+	pub fn take_access_log(&self) -> Option<HashSet<Address>> {
+		self.access_log.borrow_mut().take()
+	}
+
+	fn note_access(&self, address: &Address) {
+		if let Some(ref mut log) = *self.access_log.borrow_mut() {
+			log.insert(*address);
+		}
+	}
+
 	fn insert_cache(&self, address: &Address, account: AccountEntry) {
+		self.note_access(address);
 		// Dirty account which is not in the cache means this is a new account.
 		// It goes directly into the checkpoint as there's nothing to rever to.
 		//
@@ -626,6 +675,7 @@ impl<B: Backend> State<B> {
 		// 2. If there's an entry for the account in the global cache check for the key or load it into that account.
 		// 3. If account is missing in the global cache load it into the local cache and cache the key there.
 
+		self.note_access(address);
 		{
 			// check local cache first without updating
 			let local_cache = self.cache.borrow_mut();
@@ -827,7 +877,7 @@ impl<B: Backend> State<B> {
 		};
 
 		let output = e.output;
-		let receipt = Receipt::new(outcome, e.cumulative_gas_used, e.logs);
+		let receipt = Receipt::new(outcome, e.cumulative_gas_used, e.logs).with_transaction_type(t.tx_type());
 		trace!(target: "state", "Transaction receipt: {:?}", receipt);
 
 		Ok(ApplyOutcome {
@@ -864,6 +914,34 @@ impl<B: Backend> State<B> {
 		assert!(self.checkpoints.borrow().is_empty());
 		// first, commit the sub trees.
 		let mut accounts = self.cache.borrow_mut();
+		{
+			let mut storage_dirty: Vec<_> = accounts.iter_mut()
+				.filter(|&(_, ref a)| a.is_dirty())
+				.filter_map(|(address, a)| a.account.as_mut().map(|account| (account.address_hash(address), account)))
+				.filter(|&(_, ref account)| !account.storage_is_clean())
+				.collect();
+
+			// storage tries of different accounts are independent, so with enough of them
+			// they're committed in parallel and the changes applied afterwards.
+			if storage_dirty.len() >= PARALLEL_COMMIT_THRESHOLD {
+				let changes = {
+					let base = self.db.as_hashdb();
+					let trie_factory = &self.factories.trie;
+					let accountdb = &self.factories.accountdb;
+					storage_dirty.par_iter_mut()
+						.map(|&mut (addr_hash, ref mut account)| {
+							let mut overlay = Overlay::new(base);
+							account.commit_storage(trie_factory, accountdb.create(&mut overlay, addr_hash).as_hashdb_mut())?;
+							Ok(overlay.into_changes())
+						})
+						.collect::<TrieResult<Vec<_>>>()?
+				};
+				for changes in changes {
+					apply_changes(changes, self.db.as_hashdb_mut());
+				}
+			}
+		}
+
 		for (address, ref mut a) in accounts.iter_mut().filter(|&(_, ref a)| a.is_dirty()) {
 			if let Some(ref mut account) = a.account {
 				let addr_hash = account.address_hash(address);
@@ -911,6 +989,28 @@ impl<B: Backend> State<B> {
 		self.cache.borrow_mut().clear();
 	}
 
+	/// Addresses of accounts modified since the last commit.
+	pub fn dirty_accounts(&self) -> HashSet<Address> {
+		self.cache.borrow().iter()
+			.filter(|&(_, entry)| entry.is_dirty())
+			.map(|(address, _)| *address)
+			.collect()
+	}
+
+	/// Move modified entries of given accounts from `other` into this state.
+	///
+	/// Both states must have been created from the same base and this state must not have
+	/// modified any of the `accounts` since then.
+	pub fn merge_accounts(&mut self, other: State<B>, accounts: &HashSet<Address>) {
+		assert!(self.checkpoints.borrow().is_empty());
+		let cache = self.cache.get_mut();
+		for (address, entry) in other.cache.into_inner() {
+			if entry.is_dirty() && accounts.contains(&address) {
+				cache.insert(address, entry);
+			}
+		}
+	}
+
 	/// Remove any touched empty or dust accounts.
 	pub fn kill_garbage(&mut self, touched: &HashSet<Address>, remove_empty_touched: bool, min_balance: &Option<U256>, kill_contracts: bool) -> TrieResult<()> {
 		let to_kill: HashSet<_> = {
@@ -1005,6 +1105,43 @@ impl<B: Backend> State<B> {
 		})?))
 	}
 
+	/// Populate a PodAccount map with values from this state of all accounts and storage
+	/// items touched in `touched`. Accounts which don't exist in this state are skipped.
+	pub fn to_pod_touched<X: Backend>(&self, touched: &State<X>) -> TrieResult<PodState> {
+		assert!(self.checkpoints.borrow().is_empty());
+
+		let touched = touched.cache.borrow().iter()
+			.map(|(address, entry)| {
+				let keys = entry.account.as_ref().map(|acc| acc.touched_storage_keys()).unwrap_or_default();
+				(*address, keys)
+			})
+			.collect::<BTreeMap<_, _>>();
+
+		let mut pod = BTreeMap::new();
+		for (address, keys) in touched {
+			if !self.exists(&address)? {
+				continue;
+			}
+
+			let mut storage = BTreeMap::new();
+			for key in keys {
+				let value = self.storage_at(&address, &key)?;
+				if !value.is_zero() {
+					storage.insert(key, value);
+				}
+			}
+
+			pod.insert(address, PodAccount {
+				balance: self.balance(&address)?,
+				nonce: self.nonce(&address)?,
+				code: self.code(&address)?.map(|code| code.to_vec()),
+				storage,
+			});
+		}
+
+		Ok(PodState::from(pod))
+	}
+
 	/// Returns a `StateDiff` describing the difference from `orig` to `self`.
 	/// Consumes self.
 	pub fn diff_from<X: Backend>(&self, mut orig: State<X>) -> TrieResult<StateDiff> {
@@ -1055,6 +1192,7 @@ impl<B: Backend> State<B> {
 	/// Populates local cache if nothing found.
 	fn ensure_cached<F, U>(&self, a: &Address, require: RequireCache, check_null: bool, f: F) -> TrieResult<U>
 		where F: Fn(Option<&Account>) -> U {
+		self.note_access(a);
 		// check local cache first
 		if let Some(ref mut maybe_acc) = self.cache.borrow_mut().get_mut(a) {
 			if let Some(ref mut account) = maybe_acc.account {
@@ -1110,6 +1248,7 @@ impl<B: Backend> State<B> {
 	fn require_or_from<'a, F, G>(&'a self, a: &Address, require_code: bool, default: F, not_default: G) -> TrieResult<RefMut<'a, Account>>
 		where F: FnOnce() -> Account, G: FnOnce(&mut Account),
 	{
+		self.note_access(a);
 		let contains_key = self.cache.borrow().contains_key(a);
 		if !contains_key {
 			match self.db.get_cached_account(a) {
@@ -1157,6 +1296,32 @@ impl<B: Backend> State<B> {
 	pub fn patch_account(&self, a: &Address, code: Arc<Bytes>, storage: HashMap<H256, H256>) -> TrieResult<()> {
 		Ok(self.require(a, false)?.reset_code_and_storage(code, storage))
 	}
+
+	/// Apply temporary account overrides. Creates accounts which do not exist.
+	/// The resulting state is only meant for calls and should never be committed.
+	pub fn apply_overrides(&mut self, overrides: &StateOverride) -> TrieResult<()> {
+		for (address, account_override) in overrides {
+			let mut account = self.require(address, false)?;
+			if let Some(balance) = account_override.balance {
+				account.set_balance(balance);
+			}
+			if let Some(nonce) = account_override.nonce {
+				account.set_nonce(nonce);
+			}
+			if let Some(ref code) = account_override.code {
+				account.reset_code(code.clone());
+			}
+			if let Some(ref storage) = account_override.state {
+				account.replace_storage(storage.clone());
+			}
+			if let Some(ref storage) = account_override.state_diff {
+				for (key, value) in storage {
+					account.set_storage(*key, *value);
+				}
+			}
+		}
+		Ok(())
+	}
 }
 
 // State proof implementations; useful for light client protocols.
@@ -1167,14 +1332,13 @@ impl<B: Backend> State<B> {
 	/// Requires a secure trie to be used for accurate results.
 	/// `account_key` == keccak(address)
 	pub fn prove_account(&self, account_key: H256) -> TrieResult<(Vec<Bytes>, BasicAccount)> {
-		let mut recorder = Recorder::new();
 		let trie = TrieDB::new(self.db.as_hashdb(), &self.root)?;
+		let proof = trie.prove(&account_key)?;
 		let maybe_account: Option<BasicAccount> = {
 			let panicky_decoder = |bytes: &[u8]| {
 				::rlp::decode(bytes).expect(&format!("prove_account, could not query trie for account key={}", &account_key))
 			};
-			let query = (&mut recorder, panicky_decoder);
-			trie.get_with(&account_key, query)?
+			trie.get_with(&account_key, panicky_decoder)?
 		};
 		let account = maybe_account.unwrap_or_else(|| BasicAccount {
 			balance: 0.into(),
@@ -1183,7 +1347,7 @@ impl<B: Backend> State<B> {
 			storage_root: KECCAK_NULL_RLP,
 		});
 
-		Ok((recorder.drain().into_iter().map(|r| r.data).collect(), account))
+		Ok((proof, account))
 	}
 
 	/// Prove an account's storage key's existence or nonexistence in the state.
@@ -1231,6 +1395,7 @@ impl Clone for State<StateDB> {
 			root: self.root.clone(),
 			cache: RefCell::new(cache),
 			checkpoints: RefCell::new(Vec::new()),
+			access_log: RefCell::new(None),
 			account_start_nonce: self.account_start_nonce.clone(),
 			factories: self.factories.clone(),
 		}
@@ -2120,6 +2285,85 @@ mod tests {
 		assert_eq!(s.storage_at(&a, &H256::from(&U256::from(1u64))).unwrap(), H256::from(&U256::from(69u64)));
 	}
 
+	#[test]
+	fn should_commit_storage_in_parallel() {
+		let key = |n: u64| H256::from(&U256::from(n));
+		let addresses: Vec<_> = (0..2 * PARALLEL_COMMIT_THRESHOLD as u64).map(Address::from).collect();
+
+		// one account per commit is always committed sequentially.
+		let mut sequential = get_temp_state();
+		for (i, a) in addresses.iter().enumerate() {
+			sequential.set_storage(a, key(1), key(i as u64 + 1)).unwrap();
+			sequential.set_storage(a, key(2), key(2)).unwrap();
+			sequential.commit().unwrap();
+		}
+
+		let (root, db) = {
+			let mut state = get_temp_state();
+			for (i, a) in addresses.iter().enumerate() {
+				state.set_storage(a, key(1), key(i as u64 + 1)).unwrap();
+				state.set_storage(a, key(2), key(2)).unwrap();
+			}
+			state.commit().unwrap();
+			assert_eq!(state.root(), sequential.root());
+
+			// change and clear storage of all accounts with the previous tries in place.
+			for a in &addresses {
+				state.set_storage(a, key(1), H256::zero()).unwrap();
+				state.set_storage(a, key(3), key(3)).unwrap();
+			}
+			state.commit().unwrap();
+			state.drop()
+		};
+
+		let state = State::from_existing(db, root, U256::from(0u8), Default::default()).unwrap();
+		for a in &addresses {
+			assert_eq!(state.storage_at(a, &key(1)).unwrap(), H256::zero());
+			assert_eq!(state.storage_at(a, &key(2)).unwrap(), key(2));
+			assert_eq!(state.storage_at(a, &key(3)).unwrap(), key(3));
+		}
+	}
+
+	#[test]
+	fn should_apply_overrides() {
+		let a = Address::zero();
+		let b = Address::from(1);
+		let key = |n: u64| H256::from(&U256::from(n));
+		let (root, db) = {
+			let mut state = get_temp_state();
+			state.add_balance(&a, &U256::from(69u64), CleanupMode::NoEmpty).unwrap();
+			state.set_storage(&a, key(1), key(1)).unwrap();
+			state.set_storage(&a, key(2), key(2)).unwrap();
+			state.set_storage(&b, key(1), key(1)).unwrap();
+			state.commit().unwrap();
+			state.drop()
+		};
+
+		let mut overrides = StateOverride::new();
+		overrides.insert(a, AccountOverride {
+			balance: Some(U256::from(1u64)),
+			nonce: Some(U256::from(5u64)),
+			code: Some(vec![1u8, 2, 3]),
+			state_diff: Some(vec![(key(2), key(42))].into_iter().collect()),
+			..Default::default()
+		});
+		overrides.insert(b, AccountOverride {
+			state: Some(vec![(key(3), key(3))].into_iter().collect()),
+			..Default::default()
+		});
+
+		let mut state = State::from_existing(db, root, U256::from(0u8), Default::default()).unwrap();
+		state.apply_overrides(&overrides).unwrap();
+
+		assert_eq!(state.balance(&a).unwrap(), U256::from(1u64));
+		assert_eq!(state.nonce(&a).unwrap(), U256::from(5u64));
+		assert_eq!(state.code(&a).unwrap(), Some(Arc::new(vec![1u8, 2, 3])));
+		assert_eq!(state.storage_at(&a, &key(1)).unwrap(), key(1));
+		assert_eq!(state.storage_at(&a, &key(2)).unwrap(), key(42));
+		assert_eq!(state.storage_at(&b, &key(1)).unwrap(), H256::zero());
+		assert_eq!(state.storage_at(&b, &key(3)).unwrap(), key(3));
+	}
+
 	#[test]
 	fn get_from_database() {
 		let a = Address::zero();
@@ -2629,4 +2873,64 @@ mod tests {
 						   .into_iter().collect(),
 				   })).as_ref());
 	}
+
+	#[test]
+	fn should_collect_pre_state_of_touched_items() {
+		let a: Address = 0xa.into();
+		let b: Address = 0xb.into();
+		let c: Address = 0xc.into();
+		let (root, db) = {
+			let mut state = get_temp_state();
+			state.init_code(&a, vec![0x60, 0x00]).unwrap();
+			state.set_storage(&a, 0x1.into(), 0x11.into()).unwrap();
+			state.set_storage(&a, 0x2.into(), 0x22.into()).unwrap();
+			state.add_balance(&b, &100.into(), CleanupMode::NoEmpty).unwrap();
+			state.add_balance(&c, &200.into(), CleanupMode::NoEmpty).unwrap();
+			state.commit().unwrap();
+			state.drop()
+		};
+
+		let pre = State::from_existing(db.boxed_clone(), root, U256::zero(), Default::default()).unwrap();
+		let mut post = State::from_existing(db, root, U256::zero(), Default::default()).unwrap();
+		// read one storage item, modify another account and create a new one
+		assert_eq!(post.storage_at(&a, &0x1.into()).unwrap(), 0x11.into());
+		post.add_balance(&b, &1.into(), CleanupMode::NoEmpty).unwrap();
+		post.add_balance(&0xd.into(), &1.into(), CleanupMode::NoEmpty).unwrap();
+
+		let pod = pre.to_pod_touched(&post).unwrap();
+		let mut storage = BTreeMap::new();
+		storage.insert(0x1.into(), 0x11.into());
+		assert_eq!(pod.get().len(), 2);
+		assert_eq!(pod.get()[&a], PodAccount {
+			balance: 0.into(),
+			nonce: 0.into(),
+			code: Some(vec![0x60, 0x00]),
+			storage,
+		});
+		assert_eq!(pod.get()[&b].balance, 100.into());
+	}
+
+	#[test]
+	fn proofs_verify_against_state_root() {
+		let a = Address::from(10);
+		let absent = Address::from(11);
+		let mut state = get_temp_state();
+		state.add_balance(&a, &69.into(), CleanupMode::NoEmpty).unwrap();
+		state.set_storage(&a, 0x1.into(), 0x11.into()).unwrap();
+		state.commit().unwrap();
+		let root = *state.root();
+
+		let (proof, account) = state.prove_account(keccak(&a)).unwrap();
+		let value = ::ethtrie::verify_proof(&root, &keccak(&a), &proof).unwrap().unwrap();
+		assert_eq!(::rlp::decode::<BasicAccount>(&value).unwrap(), account);
+		assert_eq!(account.balance, 69.into());
+
+		let (proof, value) = state.prove_storage(keccak(&a), keccak(&H256::from(1))).unwrap();
+		let stored = ::ethtrie::verify_proof(&account.storage_root, &keccak(&H256::from(1)), &proof).unwrap().unwrap();
+		assert_eq!(H256::from(::rlp::decode::<U256>(&stored).unwrap()), value);
+		assert_eq!(value, 0x11.into());
+
+		let (proof, _) = state.prove_account(keccak(&absent)).unwrap();
+		assert_eq!(::ethtrie::verify_proof(&root, &keccak(&absent), &proof).unwrap(), None);
+	}
 }
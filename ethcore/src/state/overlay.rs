@@ -0,0 +1,126 @@
+// Copyright 2015-2018 Parity Technologies (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Write overlay over a shared, read-only database.
+//!
+//! Lets the storage tries of different accounts be committed on separate threads;
+//! the changes are applied to the actual database afterwards.
+
+use std::collections::HashMap;
+
+use ethereum_types::H256;
+use hashdb::{AsHashDB, HashDB, DBValue};
+use keccak_hasher::KeccakHasher;
+use memorydb::MemoryDB;
+
+/// Database collecting all changes in memory, reading anything else from the base.
+pub struct Overlay<'db> {
+	base: &'db HashDB<KeccakHasher>,
+	changed: MemoryDB<KeccakHasher>,
+}
+
+impl<'db> Overlay<'db> {
+	/// Create a new overlay over `base`.
+	pub fn new(base: &'db HashDB<KeccakHasher>) -> Self {
+		Overlay {
+			base,
+			changed: MemoryDB::new(),
+		}
+	}
+
+	/// Consume the overlay, returning the changes made through it.
+	pub fn into_changes(self) -> MemoryDB<KeccakHasher> {
+		self.changed
+	}
+}
+
+/// Apply changes made through an overlay to `db`, with the same reference counts
+/// as if they had been made to `db` directly.
+pub fn apply_changes(mut changes: MemoryDB<KeccakHasher>, db: &mut HashDB<KeccakHasher>) {
+	for (key, (value, rc)) in changes.drain() {
+		for _ in rc..0 {
+			db.remove(&key);
+		}
+		for _ in 0..rc {
+			db.emplace(key, value.clone());
+		}
+	}
+}
+
+impl<'db> AsHashDB<KeccakHasher> for Overlay<'db> {
+	fn as_hashdb(&self) -> &HashDB<KeccakHasher> { self }
+	fn as_hashdb_mut(&mut self) -> &mut HashDB<KeccakHasher> { self }
+}
+
+impl<'db> HashDB<KeccakHasher> for Overlay<'db> {
+	fn keys(&self) -> HashMap<H256, i32> {
+		let mut keys = self.base.keys();
+		for (key, rc) in self.changed.keys() {
+			*keys.entry(key).or_insert(0) += rc;
+		}
+		keys
+	}
+
+	fn get(&self, key: &H256) -> Option<DBValue> {
+		self.changed.get(key).or_else(|| self.base.get(key))
+	}
+
+	fn contains(&self, key: &H256) -> bool {
+		self.get(key).is_some()
+	}
+
+	fn insert(&mut self, value: &[u8]) -> H256 {
+		self.changed.insert(value)
+	}
+
+	fn emplace(&mut self, key: H256, value: DBValue) {
+		self.changed.emplace(key, value)
+	}
+
+	fn remove(&mut self, key: &H256) {
+		self.changed.remove(key)
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use hashdb::{HashDB, DBValue};
+	use keccak_hasher::KeccakHasher;
+	use memorydb::MemoryDB;
+	use super::{Overlay, apply_changes};
+
+	#[test]
+	fn applies_changes_like_direct_writes() {
+		let mut base = MemoryDB::<KeccakHasher>::new();
+		let removed = base.insert(b"removed");
+		let kept = base.insert(b"kept");
+		let mut direct = base.clone();
+
+		let changes = {
+			let mut overlay = Overlay::new(&base);
+			assert_eq!(overlay.get(&kept), Some(DBValue::from_slice(b"kept")));
+			overlay.remove(&removed);
+			let inserted = overlay.insert(b"inserted");
+			assert_eq!(overlay.get(&inserted), Some(DBValue::from_slice(b"inserted")));
+			overlay.into_changes()
+		};
+		direct.remove(&removed);
+		direct.insert(b"inserted");
+
+		apply_changes(changes, &mut base);
+		assert_eq!(base.keys(), direct.keys());
+	}
+}
@@ -0,0 +1,210 @@
+// Copyright 2015-2018 Parity Technologies (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Iterator over all accounts of the state, with their code and storage.
+//!
+//! Requires the fat database, since the account trie is keyed by hashes of the addresses.
+//! Accounts are read in batches straight from the tries, without going through the
+//! account cache, so memory use doesn't grow with the size of the state.
+
+use std::collections::{BTreeMap, VecDeque};
+
+use ethereum_types::{H256, U256, Address};
+use hash::{keccak, KECCAK_EMPTY, KECCAK_NULL_RLP};
+use rlp::{self, RlpStream, Encodable};
+use bytes::Bytes;
+use types::basic_account::BasicAccount;
+use ethtrie::{TrieError, Result as TrieResult};
+use trie::Trie;
+use state_db::StateDB;
+
+use super::State;
+
+/// Number of accounts read from the trie at once.
+const ACCOUNTS_BATCH: usize = 1000;
+
+/// Account exported by the `StateProducer`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ExportedAccount {
+	/// Address of the account.
+	pub address: Address,
+	/// Account balance.
+	pub balance: U256,
+	/// Account nonce.
+	pub nonce: U256,
+	/// Hash of the account code.
+	pub code_hash: H256,
+	/// Account code, if any.
+	pub code: Option<Bytes>,
+	/// Root of the account storage trie.
+	pub storage_root: H256,
+	/// Account storage, if requested.
+	pub storage: BTreeMap<H256, H256>,
+}
+
+impl Encodable for ExportedAccount {
+	fn rlp_append(&self, s: &mut RlpStream) {
+		s.begin_list(5)
+			.append(&self.address)
+			.append(&self.nonce)
+			.append(&self.balance)
+			.append(self.code.as_ref().unwrap_or(&Vec::new()));
+		s.begin_list(self.storage.len());
+		for (key, value) in &self.storage {
+			s.begin_list(2).append(key).append(value);
+		}
+	}
+}
+
+/// Iterator over all accounts of the state.
+pub struct StateProducer {
+	state: State<StateDB>,
+	storage: bool,
+	last: Option<Address>,
+	pending: VecDeque<(Address, BasicAccount)>,
+	exhausted: bool,
+}
+
+impl StateProducer {
+	/// Create a producer of accounts of given state, optionally with their storage.
+	/// Returns `None` if the state doesn't use the fat database.
+	pub fn new(state: State<StateDB>, storage: bool) -> Option<Self> {
+		if !state.factories.trie.is_fat() {
+			return None;
+		}
+
+		Some(StateProducer {
+			state,
+			storage,
+			last: None,
+			pending: VecDeque::new(),
+			exhausted: false,
+		})
+	}
+
+	/// Read the next batch of accounts following the last one read.
+	fn fill(&mut self) -> TrieResult<()> {
+		let root = self.state.root;
+		let trie = self.state.factories.trie.readonly(self.state.db.as_hashdb(), &root)?;
+		let mut iter = trie.iter()?;
+		if let Some(ref last) = self.last {
+			iter.seek(last)?;
+			// position the iterator after the last account
+			iter.next();
+		}
+
+		for item in iter.take(ACCOUNTS_BATCH) {
+			let (key, value) = item?;
+			let account = rlp::decode::<BasicAccount>(&value).map_err(|e| Box::new(TrieError::DecoderError(root, e)))?;
+			self.pending.push_back((Address::from_slice(&key), account));
+		}
+
+		self.exhausted = self.pending.len() < ACCOUNTS_BATCH;
+		self.last = self.pending.back().map(|&(address, _)| address);
+		Ok(())
+	}
+
+	fn export(&self, address: Address, account: BasicAccount) -> TrieResult<ExportedAccount> {
+		let account_db = self.state.factories.accountdb.readonly(self.state.db.as_hashdb(), keccak(&address));
+
+		let code = if account.code_hash == KECCAK_EMPTY {
+			None
+		} else {
+			match account_db.get(&account.code_hash) {
+				Some(code) => Some(code.into_vec()),
+				None => return Err(Box::new(TrieError::IncompleteDatabase(account.code_hash))),
+			}
+		};
+
+		let mut storage = BTreeMap::new();
+		if self.storage && account.storage_root != KECCAK_NULL_RLP {
+			let trie = self.state.factories.trie.readonly(&*account_db, &account.storage_root)?;
+			for item in trie.iter()? {
+				let (key, value) = item?;
+				let value = rlp::decode::<U256>(&value).map_err(|e| Box::new(TrieError::DecoderError(account.storage_root, e)))?;
+				storage.insert(H256::from_slice(&key), value.into());
+			}
+		}
+
+		Ok(ExportedAccount {
+			address,
+			balance: account.balance,
+			nonce: account.nonce,
+			code_hash: account.code_hash,
+			code,
+			storage_root: account.storage_root,
+			storage,
+		})
+	}
+}
+
+impl Iterator for StateProducer {
+	type Item = TrieResult<ExportedAccount>;
+
+	fn next(&mut self) -> Option<Self::Item> {
+		if self.pending.is_empty() && !self.exhausted {
+			if let Err(e) = self.fill() {
+				self.exhausted = true;
+				return Some(Err(e));
+			}
+		}
+
+		self.pending.pop_front().map(|(address, account)| self.export(address, account))
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use std::collections::BTreeMap;
+	use ethereum_types::{H256, U256, Address};
+	use hash::keccak;
+	use factory::Factories;
+	use trie::{TrieFactory, TrieSpec};
+	use rlp;
+	use state::{State, CleanupMode};
+	use test_helpers::get_temp_state_db;
+	use super::StateProducer;
+
+	#[test]
+	fn should_produce_all_accounts() {
+		let mut factories = Factories::default();
+		factories.trie = TrieFactory::new(TrieSpec::Fat);
+
+		let a = Address::from(1);
+		let b = Address::from(2);
+		let mut state = State::new(get_temp_state_db(), U256::zero(), factories.clone());
+		state.add_balance(&a, &100.into(), CleanupMode::NoEmpty).unwrap();
+		state.init_code(&b, vec![0x60, 0x00]).unwrap();
+		state.set_storage(&b, H256::from(1), H256::from(2)).unwrap();
+		state.commit().unwrap();
+		let (root, db) = state.drop();
+
+		let state = State::from_existing(db, root, U256::zero(), factories).unwrap();
+		let accounts = StateProducer::new(state, true).unwrap().collect::<Result<Vec<_>, _>>().unwrap();
+
+		let mut storage = BTreeMap::new();
+		storage.insert(H256::from(1), H256::from(2));
+		assert_eq!(accounts.len(), 2);
+		assert!(accounts.iter().any(|account| account.address == a && account.balance == 100.into() && account.code.is_none()));
+		let contract = accounts.iter().find(|account| account.address == b).unwrap();
+		assert_eq!(contract.code, Some(vec![0x60, 0x00]));
+		assert_eq!(contract.code_hash, keccak(&[0x60, 0x00]));
+		assert_eq!(contract.storage, storage);
+
+		let encoded = rlp::encode(contract);
+		assert_eq!(rlp::Rlp::new(&encoded).item_count().unwrap(), 5);
+	}
+}
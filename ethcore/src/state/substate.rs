@@ -16,9 +16,11 @@
 
 //! Execution environment substate.
 use std::collections::HashSet;
+use std::mem;
 use ethereum_types::{U256, Address};
 use log_entry::LogEntry;
 use evm::{Schedule, CleanDustMode};
+use vm::AccessList;
 use super::CleanupMode;
 
 /// State changes which should be applied in finalize,
@@ -39,6 +41,12 @@ pub struct Substate {
 
 	/// Created contracts.
 	pub contracts_created: Vec<Address>,
+
+	/// Addresses and storage keys accessed so far (EIP-2929).
+	pub access_list: AccessList,
+
+	/// Checkpoint of the access list taken when this substate was nested.
+	access_list_checkpoint: usize,
 }
 
 impl Substate {
@@ -47,6 +55,17 @@ impl Substate {
 		Substate::default()
 	}
 
+	/// Creates new substate of a nested call frame, which takes over the accesses of the parent.
+	/// They are handed back to the parent by either `accrue` or `revert`.
+	pub fn nested(parent: &mut Substate) -> Self {
+		let access_list = mem::replace(&mut parent.access_list, AccessList::default());
+		Substate {
+			access_list_checkpoint: access_list.checkpoint(),
+			access_list: access_list,
+			..Substate::default()
+		}
+	}
+
 	/// Merge secondary substate `s` into self, accruing each element correspondingly.
 	pub fn accrue(&mut self, s: Substate) {
 		self.suicides.extend(s.suicides);
@@ -54,6 +73,13 @@ impl Substate {
 		self.logs.extend(s.logs);
 		self.sstore_clears_refund = self.sstore_clears_refund + s.sstore_clears_refund;
 		self.contracts_created.extend(s.contracts_created);
+		self.access_list = s.access_list;
+	}
+
+	/// Discard nested substate `s`, taking back the accesses made before it was nested.
+	pub fn revert(&mut self, s: Substate) {
+		self.access_list = s.access_list;
+		self.access_list.revert_to_checkpoint(s.access_list_checkpoint);
 	}
 
 	/// Get the cleanup mode object from this.
@@ -16,7 +16,7 @@
 
 //! State database abstraction. For more info, see the doc for `StateDB`
 
-use std::collections::{VecDeque, HashSet};
+use std::collections::{VecDeque, HashSet, HashMap};
 use std::io;
 use std::sync::Arc;
 
@@ -25,7 +25,7 @@ use byteorder::{LittleEndian, ByteOrder};
 use db::COL_ACCOUNT_BLOOM;
 use ethereum_types::{H256, Address};
 use hash::keccak;
-use hashdb::HashDB;
+use hashdb::{HashDB, AsHashDB, DBValue};
 use keccak_hasher::KeccakHasher;
 use header::BlockNumber;
 use journaldb::JournalDB;
@@ -53,6 +53,12 @@ const STATE_CACHE_BLOCKS: usize = 12;
 // The percentage of supplied cache size to go to accounts.
 const ACCOUNT_CACHE_RATIO: usize = 90;
 
+// The percentage of supplied cache size to go to trie nodes.
+const NODE_CACHE_RATIO: usize = 5;
+
+// Number of independently locked parts of the trie nodes cache.
+const NODE_CACHE_SHARDS: usize = 16;
+
 /// Shared canonical state cache.
 struct AccountCache {
 	/// DB Account cache. `None` indicates that account is known to be missing.
@@ -90,6 +96,139 @@ struct BlockChanges {
 	is_canon: bool,
 }
 
+/// Cache of trie nodes, split into shards by the first byte of the node hash,
+/// so that concurrent readers rarely wait for each other.
+struct NodeCache {
+	shards: Vec<Mutex<MemoryLruCache<H256, Vec<u8>>>>,
+}
+
+impl NodeCache {
+	fn new(size: usize) -> Self {
+		NodeCache {
+			shards: (0..NODE_CACHE_SHARDS).map(|_| Mutex::new(MemoryLruCache::new(size / NODE_CACHE_SHARDS))).collect(),
+		}
+	}
+
+	fn shard(&self, key: &H256) -> &Mutex<MemoryLruCache<H256, Vec<u8>>> {
+		&self.shards[key[0] as usize % NODE_CACHE_SHARDS]
+	}
+
+	fn get(&self, key: &H256) -> Option<DBValue> {
+		self.shard(key).lock().get_mut(key).map(|node| DBValue::from_slice(node))
+	}
+
+	fn insert(&self, key: H256, node: &[u8]) {
+		self.shard(&key).lock().insert(key, node.to_vec());
+	}
+
+	fn current_size(&self) -> usize {
+		self.shards.iter().map(|shard| shard.lock().current_size()).sum()
+	}
+}
+
+/// Backing database with shared cache of trie nodes.
+///
+/// Nodes read from the database are kept in the LRU cache keyed by node hash,
+/// so tries walked over and over (e.g. by repeated calls to the same contract)
+/// are served from memory. Nodes are addressed by their hash, so cached entries
+/// are never invalidated by writes.
+struct NodeCachingDB {
+	/// Backing database.
+	journal: Box<JournalDB>,
+	/// Shared node cache.
+	cache: Arc<NodeCache>,
+	/// Recorder of the values read, if recording.
+	recorder: Option<Arc<StateRecorder>>,
+}
+
+impl NodeCachingDB {
+	fn boxed_clone(&self) -> Self {
+		NodeCachingDB {
+			journal: self.journal.boxed_clone(),
+			cache: self.cache.clone(),
+			recorder: None,
+		}
+	}
+
+	fn get_node(&self, key: &H256) -> Option<DBValue> {
+		if let Some(node) = self.cache.get(key) {
+			return Some(node);
+		}
+
+		let node = self.journal.as_hashdb().get(key)?;
+		self.cache.insert(*key, &node);
+		Some(node)
+	}
+}
+
+impl AsHashDB<KeccakHasher> for NodeCachingDB {
+	fn as_hashdb(&self) -> &HashDB<KeccakHasher> { self }
+	fn as_hashdb_mut(&mut self) -> &mut HashDB<KeccakHasher> { self }
+}
+
+impl HashDB<KeccakHasher> for NodeCachingDB {
+	fn keys(&self) -> HashMap<H256, i32> {
+		self.journal.as_hashdb().keys()
+	}
+
+	fn get(&self, key: &H256) -> Option<DBValue> {
+		let node = self.get_node(key)?;
+		if let Some(ref recorder) = self.recorder {
+			recorder.note_read(key, &node);
+		}
+		Some(node)
+	}
+
+	// availability of the state is decided by the backing database only
+	fn contains(&self, key: &H256) -> bool {
+		self.journal.as_hashdb().contains(key)
+	}
+
+	fn insert(&mut self, value: &[u8]) -> H256 {
+		let key = self.journal.as_hashdb_mut().insert(value);
+		if let Some(ref recorder) = self.recorder {
+			recorder.note_written(key);
+		}
+		key
+	}
+
+	fn emplace(&mut self, key: H256, value: DBValue) {
+		if let Some(ref recorder) = self.recorder {
+			recorder.note_written(key);
+		}
+		self.journal.as_hashdb_mut().emplace(key, value)
+	}
+
+	fn remove(&mut self, key: &H256) {
+		self.journal.as_hashdb_mut().remove(key)
+	}
+}
+
+/// Trie nodes and code read from the database by a recording `StateDB`.
+/// Values written by the recording state itself are not recorded.
+#[derive(Default)]
+pub struct StateRecorder {
+	read: Mutex<HashSet<DBValue>>,
+	written: Mutex<HashSet<H256>>,
+}
+
+impl StateRecorder {
+	fn note_read(&self, key: &H256, value: &DBValue) {
+		if !self.written.lock().contains(key) {
+			self.read.lock().insert(value.clone());
+		}
+	}
+
+	fn note_written(&self, key: H256) {
+		self.written.lock().insert(key);
+	}
+
+	/// Values read so far.
+	pub fn values(&self) -> Vec<DBValue> {
+		self.read.lock().iter().cloned().collect()
+	}
+}
+
 /// State database abstraction.
 /// Manages shared global state cache which reflects the canonical
 /// state as it is on the disk. All the entries in the cache are clean.
@@ -105,8 +244,8 @@ struct BlockChanges {
 /// Then, after the block has been added to the chain the local cache in the
 /// `StateDB` is propagated into the global cache.
 pub struct StateDB {
-	/// Backing database.
-	db: Box<JournalDB>,
+	/// Backing database with shared trie nodes cache.
+	db: NodeCachingDB,
 	/// Shared canonical state cache.
 	account_cache: Arc<Mutex<AccountCache>>,
 	/// DB Code cache. Maps code hashes to shared bytes.
@@ -134,11 +273,16 @@ impl StateDB {
 	pub fn new(db: Box<JournalDB>, cache_size: usize) -> StateDB {
 		let bloom = Self::load_bloom(&**db.backing());
 		let acc_cache_size = cache_size * ACCOUNT_CACHE_RATIO / 100;
-		let code_cache_size = cache_size - acc_cache_size;
+		let node_cache_size = cache_size * NODE_CACHE_RATIO / 100;
+		let code_cache_size = cache_size - acc_cache_size - node_cache_size;
 		let cache_items = acc_cache_size / ::std::mem::size_of::<Option<Account>>();
 
 		StateDB {
-			db: db,
+			db: NodeCachingDB {
+				journal: db,
+				cache: Arc::new(NodeCache::new(node_cache_size)),
+				recorder: None,
+			},
 			account_cache: Arc::new(Mutex::new(AccountCache {
 				accounts: LruCache::new(cache_items),
 				modifications: VecDeque::new(),
@@ -202,7 +346,7 @@ impl StateDB {
  			let mut bloom_lock = self.account_bloom.lock();
  			Self::commit_bloom(batch, bloom_lock.drain_journal())?;
  		}
-		let records = self.db.journal_under(batch, now, id)?;
+		let records = self.db.journal.journal_under(batch, now, id)?;
 		self.commit_hash = Some(id.clone());
 		self.commit_number = Some(now);
 		Ok(records)
@@ -211,7 +355,7 @@ impl StateDB {
 	/// Mark a given candidate from an ancient era as canonical, enacting its removals from the
 	/// backing database and reverting any non-canonical historical commit's insertions.
 	pub fn mark_canonical(&mut self, batch: &mut DBTransaction, end_era: u64, canon_id: &H256) -> io::Result<u32> {
-		self.db.mark_canonical(batch, end_era, canon_id)
+		self.db.journal.mark_canonical(batch, end_era, canon_id)
 	}
 
 	/// Propagate local cache into the global cache and synchonize
@@ -313,12 +457,12 @@ impl StateDB {
 
 	/// Conversion method to interpret self as `HashDB` reference
 	pub fn as_hashdb(&self) -> &HashDB<KeccakHasher> {
-		self.db.as_hashdb()
+		&self.db
 	}
 
 	/// Conversion method to interpret self as mutable `HashDB` reference
 	pub fn as_hashdb_mut(&mut self) -> &mut HashDB<KeccakHasher> {
-		self.db.as_hashdb_mut()
+		&mut self.db
 	}
 
 	/// Clone the database.
@@ -336,6 +480,16 @@ impl StateDB {
 		}
 	}
 
+	/// Clone the database, recording all trie nodes and code read from it.
+	/// Account and code caches are bypassed, so every value needed to access
+	/// the state again is recorded.
+	pub fn boxed_clone_recording(&self) -> (StateDB, Arc<StateRecorder>) {
+		let recorder = Arc::new(StateRecorder::default());
+		let mut db = self.boxed_clone();
+		db.db.recorder = Some(recorder.clone());
+		(db, recorder)
+	}
+
 	/// Clone the database for a canonical state.
 	pub fn boxed_clone_canon(&self, parent: &H256) -> StateDB {
 		StateDB {
@@ -353,22 +507,23 @@ impl StateDB {
 
 	/// Check if pruning is enabled on the database.
 	pub fn is_pruned(&self) -> bool {
-		self.db.is_pruned()
+		self.db.journal.is_pruned()
 	}
 
 	/// Heap size used.
 	pub fn mem_used(&self) -> usize {
 		// TODO: account for LRU-cache overhead; this is a close approximation.
-		self.db.mem_used() + {
+		self.db.journal.mem_used() + {
 			let accounts = self.account_cache.lock().accounts.len();
 			let code_size = self.code_cache.lock().current_size();
-			code_size + accounts * ::std::mem::size_of::<Option<Account>>()
+			let nodes_size = self.db.cache.current_size();
+			code_size + nodes_size + accounts * ::std::mem::size_of::<Option<Account>>()
 		}
 	}
 
 	/// Returns underlying `JournalDB`.
 	pub fn journal_db(&self) -> &JournalDB {
-		&*self.db
+		&*self.db.journal
 	}
 
 	/// Query how much memory is set aside for the accounts cache (in bytes).
@@ -412,10 +567,10 @@ impl StateDB {
 }
 
 impl state::Backend for StateDB {
-	fn as_hashdb(&self) -> &HashDB<KeccakHasher> { self.db.as_hashdb() }
+	fn as_hashdb(&self) -> &HashDB<KeccakHasher> { &self.db }
 
 	fn as_hashdb_mut(&mut self) -> &mut HashDB<KeccakHasher> {
-		self.db.as_hashdb_mut()
+		&mut self.db
 	}
 
 	fn add_to_account_cache(&mut self, addr: Address, data: Option<Account>, modified: bool) {
@@ -450,6 +605,10 @@ impl state::Backend for StateDB {
 	}
 
 	fn get_cached_code(&self, hash: &H256) -> Option<Arc<Vec<u8>>> {
+		if self.db.recorder.is_some() {
+			return None;
+		}
+
 		let mut cache = self.code_cache.lock();
 
 		cache.get_mut(hash).map(|code| code.clone())
@@ -462,6 +621,10 @@ impl state::Backend for StateDB {
 	}
 
 	fn is_known_null(&self, address: &Address) -> bool {
+		if self.db.recorder.is_some() {
+			return false;
+		}
+
 		trace!(target: "account_bloom", "Check account bloom: {:?}", address);
 		let bloom = self.account_bloom.lock();
 		let is_null = !bloom.check(&*keccak(address));
@@ -483,6 +646,7 @@ mod tests {
 	use test_helpers::get_temp_state_db;
 	use state::{Account, Backend};
 	use ethcore_logger::init_log;
+	use hashdb::HashDB;
 
 	#[test]
 	fn state_db_smoke() {
@@ -550,4 +714,21 @@ mod tests {
 		let s = state_db.boxed_clone_canon(&h3a);
 		assert!(s.get_cached_account(&address).is_none());
 	}
+
+	#[test]
+	fn trie_nodes_are_cached_across_clones() {
+		let mut state_db = get_temp_state_db();
+		let node = b"trie node".to_vec();
+		let key = state_db.as_hashdb_mut().insert(&node);
+
+		let s = state_db.boxed_clone();
+		assert!(s.db.cache.get(&key).is_none());
+		assert_eq!(s.as_hashdb().get(&key).map(|v| v.to_vec()), Some(node.clone()));
+		assert!(s.db.cache.get(&key).is_some());
+
+		let s = state_db.boxed_clone_canon(&H256::random());
+		assert!(s.db.cache.get(&key).is_some());
+		assert_eq!(s.as_hashdb().get(&key).map(|v| v.to_vec()), Some(node));
+		assert!(s.as_hashdb().get(&H256::random()).is_none());
+	}
 }
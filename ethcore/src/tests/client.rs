@@ -18,15 +18,18 @@ use std::str::FromStr;
 use std::sync::Arc;
 use hash::keccak;
 use io::IoChannel;
-use client::{BlockChainClient, Client, ClientConfig, BlockId, ChainInfo, BlockInfo, PrepareOpenBlock, ImportSealedBlock, ImportBlock};
+use client::{BlockChainClient, Client, ClientConfig, BlockId, ChainInfo, BlockInfo, PrepareOpenBlock, ImportSealedBlock, ImportBlock, ChainNotify, ChainReorg};
 use state::{self, State, CleanupMode};
 use executive::{Executive, TransactOptions};
 use ethereum;
 use block::IsBlock;
 use test_helpers::{
 	generate_dummy_client, push_blocks_to_client, get_test_client_with_blocks, get_good_dummy_block_seq,
-	generate_dummy_client_with_data, get_good_dummy_block, get_bad_state_dummy_block
+	generate_dummy_client_with_data, get_good_dummy_block, get_bad_state_dummy_block,
+	generate_dummy_client_with_spec_and_data, push_block_with_transactions, create_test_block
 };
+use header::Header;
+use parking_lot::RwLock;
 use types::filter::Filter;
 use ethereum_types::{U256, Address};
 use miner::{Miner, PendingOrdering};
@@ -97,7 +100,7 @@ fn imports_good_block() {
 		IoChannel::disconnected(),
 	).unwrap();
 	let good_block = get_good_dummy_block();
-	if client.import_block(Unverified::from_rlp(good_block).unwrap()).is_err() {
+	if client.import_block(Unverified::from_rlp(good_block, spec.engine.params().eip1559_transition).unwrap()).is_err() {
 		panic!("error importing block being good by definition");
 	}
 	client.flush_queue();
@@ -107,6 +110,32 @@ fn imports_good_block() {
 	assert!(!block.into_inner().is_empty());
 }
 
+#[test]
+fn does_not_import_while_paused() {
+	let db = test_helpers::new_db();
+	let spec = Spec::new_test();
+
+	let client = Client::new(
+		ClientConfig::default(),
+		&spec,
+		db,
+		Arc::new(Miner::new_for_tests(&spec, None)),
+		IoChannel::disconnected(),
+	).unwrap();
+	client.set_import_paused(true);
+	assert!(client.is_import_paused());
+
+	let good_block = get_good_dummy_block();
+	client.import_block(Unverified::from_rlp(good_block, spec.engine.params().eip1559_transition).unwrap()).unwrap();
+	client.flush_queue();
+	client.import_verified_blocks();
+	assert!(client.block_header(BlockId::Number(1)).is_none());
+
+	client.set_import_paused(false);
+	client.flush_queue();
+	assert!(client.block_header(BlockId::Number(1)).is_some());
+}
+
 #[test]
 fn query_none_block() {
 	let db = test_helpers::new_db();
@@ -245,6 +274,52 @@ fn can_handle_long_fork() {
 	assert_eq!(2000, client.chain_info().best_block_number);
 }
 
+#[test]
+fn notifies_about_reorgs() {
+	#[derive(Default)]
+	struct ReorgNotify(RwLock<Vec<ChainReorg>>);
+
+	impl ChainNotify for ReorgNotify {
+		fn reorg(&self, reorg: ChainReorg) {
+			self.0.write().push(reorg);
+		}
+	}
+
+	let client = generate_dummy_client(3);
+	let notify = Arc::new(ReorgNotify::default());
+	client.add_notify(notify.clone());
+
+	let ancestor = client.block_hash(BlockId::Number(1)).unwrap();
+	let retracted = vec![client.block_hash(BlockId::Number(2)).unwrap(), client.block_hash(BlockId::Number(3)).unwrap()];
+
+	// a longer fork on top of block 1
+	let test_spec = Spec::new_test();
+	let mut parent = ancestor;
+	let mut enacted = vec![];
+	for number in 2..5 {
+		let mut header = Header::new();
+		header.set_gas_limit(test_spec.genesis_header().gas_limit().clone());
+		header.set_difficulty(U256::from(0x20000));
+		header.set_timestamp(45 + number * 10);
+		header.set_number(number);
+		header.set_parent_hash(parent);
+		header.set_state_root(test_spec.genesis_header().state_root().clone());
+		parent = header.hash();
+		enacted.push(parent);
+		client.import_block(Unverified::from_rlp(create_test_block(&header), test_spec.engine.params().eip1559_transition).unwrap()).unwrap();
+	}
+	client.flush_queue();
+	client.import_verified_blocks();
+
+	assert_eq!(client.chain_info().best_block_hash, parent);
+	assert_eq!(*notify.0.read(), vec![ChainReorg {
+		common_ancestor: ancestor,
+		common_ancestor_number: 1,
+		retracted,
+		enacted,
+	}]);
+}
+
 #[test]
 fn can_mine() {
 	let dummy_blocks = get_good_dummy_block_seq(2);
@@ -365,3 +440,82 @@ fn transaction_proof() {
 	assert_eq!(state.balance(&Address::default()).unwrap(), 5.into());
 	assert_eq!(state.balance(&address).unwrap(), 95.into());
 }
+
+#[test]
+fn block_witness_allows_stateless_execution() {
+	use witness::verify_block_witness;
+
+	let client = generate_dummy_client_with_data(3, 2, &[10.into(), 20.into()]);
+	let witness = client.block_witness(BlockId::Number(2)).unwrap();
+	let block = client.block(BlockId::Number(2)).unwrap().into_inner();
+	assert!(!witness.state.is_empty());
+	verify_block_witness(client.engine(), &witness, block.clone()).unwrap();
+
+	let mut incomplete = witness.clone();
+	incomplete.state.clear();
+	assert!(verify_block_witness(client.engine(), &incomplete, block).is_err());
+}
+
+#[test]
+fn rejects_blocks_violating_invariants() {
+	let client = generate_dummy_client_with_spec_and_data(Spec::new_test_with_invariant, 0, 0, &[]);
+
+	push_block_with_transactions(&client, &[]);
+	assert_eq!(client.chain_info().best_block_number, 1);
+
+	// the invariant contract reports a violation for every block after the first one
+	push_block_with_transactions(&client, &[]);
+	assert_eq!(client.chain_info().best_block_number, 1);
+}
+
+#[test]
+fn reconstructs_pruned_state_from_local_snapshot() {
+	use client::{ColdStateConfig, StateClient};
+	use executed::CallError;
+	use snapshot::Progress;
+	use snapshot::io::LooseWriter;
+	use snapshot::service::current_snapshot_dir;
+
+	let source = generate_dummy_client_with_spec_and_data(Spec::new_test, 20, 1, &[1.into()]);
+	let tempdir = TempDir::new("").unwrap();
+	let writer = LooseWriter::new(current_snapshot_dir(tempdir.path())).unwrap();
+	source.take_snapshot(writer, BlockId::Number(10), &Progress::default()).unwrap();
+
+	let spec = Spec::new_test();
+	let mut config = ClientConfig::default();
+	config.history = 1;
+	config.history_mem = 0;
+	config.cold_state = Some(ColdStateConfig {
+		snapshot_root: tempdir.path().to_owned(),
+		max_blocks: 8,
+	});
+	let client = Client::new(
+		config,
+		&spec,
+		test_helpers::new_db(),
+		Arc::new(Miner::new_for_tests(&spec, None)),
+		IoChannel::disconnected(),
+	).unwrap();
+	for number in 1..21 {
+		let block = source.block(BlockId::Number(number)).unwrap().into_inner();
+		client.import_block(Unverified::from_rlp(block, spec.engine.params().eip1559_transition).unwrap()).unwrap();
+	}
+	client.flush_queue();
+	client.import_verified_blocks();
+	assert_eq!(client.chain_info().best_block_number, 20);
+	assert!(client.state_at(BlockId::Number(15)).is_none());
+
+	client.restore_cold_state();
+
+	let author = KeyPair::from_secret_slice(&keccak("")).unwrap().address();
+	for &number in &[15, 12, 18] {
+		let state = client.reconstructed_state_at(BlockId::Number(number)).unwrap();
+		let expected = source.state_at(BlockId::Number(number)).unwrap();
+		assert_eq!(*state.root(), source.block_header(BlockId::Number(number)).unwrap().state_root());
+		assert_eq!(state.balance(&author).unwrap(), expected.balance(&author).unwrap());
+		assert_eq!(state.nonce(&author).unwrap(), expected.nonce(&author).unwrap());
+	}
+
+	assert_eq!(client.reconstructed_state_at(BlockId::Number(19)).err(), Some(CallError::StateNotReconstructible(Some((10, 18)))));
+	assert_eq!(client.reconstructed_state_at(BlockId::Number(9)).err(), Some(CallError::StateNotReconstructible(Some((10, 18)))));
+}
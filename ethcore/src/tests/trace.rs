@@ -92,11 +92,11 @@ fn can_trace_block_and_uncle_reward() {
 
 	let root_block = root_block.close_and_lock().unwrap().seal(engine, vec![]).unwrap();
 
-	if let Err(e) = client.import_block(Unverified::from_rlp(root_block.rlp_bytes()).unwrap()) {
+	if let Err(e) = client.import_block(Unverified::from_rlp(root_block.rlp_bytes(), engine.params().eip1559_transition).unwrap()) {
 		panic!("error importing block which is valid by definition: {:?}", e);
 	}
 
-	last_header = view!(BlockView, &root_block.rlp_bytes()).header();
+	last_header = view!(BlockView, &root_block.rlp_bytes()).header(engine.params().eip1559_transition);
 	let root_header = last_header.clone();
 	db = root_block.drain().state.drop().1;
 
@@ -121,11 +121,11 @@ fn can_trace_block_and_uncle_reward() {
 
 	let parent_block = parent_block.close_and_lock().unwrap().seal(engine, vec![]).unwrap();
 
-	if let Err(e) = client.import_block(Unverified::from_rlp(parent_block.rlp_bytes()).unwrap()) {
+	if let Err(e) = client.import_block(Unverified::from_rlp(parent_block.rlp_bytes(), engine.params().eip1559_transition).unwrap()) {
 		panic!("error importing block which is valid by definition: {:?}", e);
 	}
 
-	last_header = view!(BlockView,&parent_block.rlp_bytes()).header();
+	last_header = view!(BlockView,&parent_block.rlp_bytes()).header(engine.params().eip1559_transition);
 	db = parent_block.drain().state.drop().1;
 
 	last_hashes.push(last_header.hash());
@@ -171,7 +171,7 @@ fn can_trace_block_and_uncle_reward() {
 
 	let block = block.close_and_lock().unwrap().seal(engine, vec![]).unwrap();
 
-	let res = client.import_block(Unverified::from_rlp(block.rlp_bytes()).unwrap());
+	let res = client.import_block(Unverified::from_rlp(block.rlp_bytes(), engine.params().eip1559_transition).unwrap());
 	if res.is_err() {
 		panic!("error importing block: {:#?}", res.err().unwrap());
 	}
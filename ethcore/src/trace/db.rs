@@ -124,6 +124,38 @@ impl<T> TraceDB<T> where T: DatabaseExtras {
 		self.traces(block_hash).map(Into::into)
 	}
 
+	fn localized_block_traces<F>(&self, block_hash: H256, block_number: BlockNumber, transaction_hash: F) -> Option<Vec<LocalizedTrace>> where
+		F: Fn(usize) -> Option<H256>,
+	{
+		self.transactions_traces(&block_hash)
+			.map(|traces| {
+				traces.into_iter()
+					.map(Into::<Vec<FlatTrace>>::into)
+					.enumerate()
+					.flat_map(|(tx_position, traces)| {
+						let (trace_tx_number, trace_tx_hash) = match transaction_hash(tx_position) {
+							Some(hash) => (Some(tx_position), Some(hash)),
+							//None means trace without transaction (reward)
+							None => (None, None),
+						};
+
+						traces.into_iter()
+							.map(|trace| LocalizedTrace {
+								action: trace.action,
+								result: trace.result,
+								subtraces: trace.subtraces,
+								trace_address: trace.trace_address.into_iter().collect(),
+								transaction_number: trace_tx_number,
+								transaction_hash: trace_tx_hash,
+								block_number: block_number,
+								block_hash: block_hash,
+							})
+							.collect::<Vec<LocalizedTrace>>()
+					})
+					.collect::<Vec<LocalizedTrace>>()
+			})
+	}
+
 	fn matching_block_traces(
 		&self,
 		filter: &Filter,
@@ -279,37 +311,20 @@ impl<T> TraceDatabase for TraceDB<T> where T: DatabaseExtras {
 
 	fn block_traces(&self, block_number: BlockNumber) -> Option<Vec<LocalizedTrace>> {
 		self.extras.block_hash(block_number)
-			.and_then(|block_hash| self.transactions_traces(&block_hash)
-				.map(|traces| {
-					traces.into_iter()
-						.map(Into::<Vec<FlatTrace>>::into)
-						.enumerate()
-						.flat_map(|(tx_position, traces)| {
-							let (trace_tx_number, trace_tx_hash) = match self.extras.transaction_hash(block_number, tx_position) {
-								Some(hash) => (Some(tx_position), Some(hash.clone())),
-								//None means trace without transaction (reward)
-								None => (None, None),
-							};
-
-							traces.into_iter()
-								.map(|trace| LocalizedTrace {
-									action: trace.action,
-									result: trace.result,
-									subtraces: trace.subtraces,
-									trace_address: trace.trace_address.into_iter().collect(),
-									transaction_number: trace_tx_number,
-									transaction_hash: trace_tx_hash,
-									block_number: block_number,
-									block_hash: block_hash,
-								})
-								.collect::<Vec<LocalizedTrace>>()
-						})
-						.collect::<Vec<LocalizedTrace>>()
-				})
-			)
+			.and_then(|block_hash| self.localized_block_traces(block_hash, block_number, |tx_position| {
+				self.extras.transaction_hash(block_number, tx_position)
+			}))
+	}
+
+	fn block_traces_by_hash(&self, block_hash: &H256, block_number: BlockNumber, transaction_hashes: &[H256]) -> Option<Vec<LocalizedTrace>> {
+		self.localized_block_traces(*block_hash, block_number, |tx_position| transaction_hashes.get(tx_position).cloned())
 	}
 
 	fn filter(&self, filter: &Filter) -> Vec<LocalizedTrace> {
+		self.filter_page(filter, 0, usize::max_value())
+	}
+
+	fn filter_page(&self, filter: &Filter, after: usize, count: usize) -> Vec<LocalizedTrace> {
 		let possibilities = filter.bloom_possibilities();
 		let numbers = self.db.trace_blooms()
 			.filter(filter.range.start as u64, filter.range.end as u64, &possibilities)
@@ -324,6 +339,8 @@ impl<T> TraceDatabase for TraceDB<T> where T: DatabaseExtras {
 					.expect("Expected to find a trace. Db is probably corrupted.");
 				self.matching_block_traces(filter, traces, hash, number)
 			})
+			.skip(after)
+			.take(count)
 			.collect()
 	}
 }
@@ -503,6 +520,30 @@ mod tests {
 		assert!(tracedb.traces(&block_0).is_some(), "Traces should be available even if block is non-canon.");
 	}
 
+	#[test]
+	fn test_non_canon_block_traces_by_hash() {
+		let db = new_db();
+		let mut config = Config::default();
+		config.enabled = true;
+		let block_1 = H256::from(0xa2);
+		let fork_1 = H256::from(0xb2);
+		let fork_tx = H256::from(0xbf);
+
+		let mut extras = Extras::default();
+		extras.block_hashes.insert(1, block_1.clone());
+		extras.transaction_hashes.insert(1, vec![H256::from(0xaf)]);
+
+		let tracedb = TraceDB::new(config, db.clone(), Arc::new(extras));
+
+		let request = create_noncanon_import_request(1, fork_1.clone());
+		let mut batch = DBTransaction::new();
+		tracedb.import(&mut batch, request);
+		db.key_value().write(batch).unwrap();
+
+		assert_eq!(tracedb.block_traces(1), None);
+		assert_eq!(tracedb.block_traces_by_hash(&fork_1, 1, &[fork_tx]), Some(vec![create_simple_localized_trace(1, fork_1, fork_tx)]));
+	}
+
 	#[test]
 	fn test_import() {
 		let db = new_db();
@@ -556,6 +597,10 @@ mod tests {
 		assert_eq!(traces[0], create_simple_localized_trace(1, block_1.clone(), tx_1.clone()));
 		assert_eq!(traces[1], create_simple_localized_trace(2, block_2.clone(), tx_2.clone()));
 
+		let traces = tracedb.filter_page(&filter, 1, 1);
+		assert_eq!(traces, vec![create_simple_localized_trace(2, block_2.clone(), tx_2.clone())]);
+		assert!(tracedb.filter_page(&filter, 0, 0).is_empty());
+
 		assert!(tracedb.block_traces(0).is_some(), "Genesis trace should be always present.");
 
 		let traces = tracedb.block_traces(1).unwrap();
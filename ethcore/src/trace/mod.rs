@@ -21,12 +21,16 @@ mod db;
 mod executive_tracer;
 mod import;
 mod noop_tracer;
+mod step_tracer;
+mod struct_log_tracer;
 mod types;
 
 pub use self::config::Config;
 pub use self::db::TraceDB;
 pub use self::noop_tracer::{NoopTracer, NoopVMTracer};
 pub use self::executive_tracer::{ExecutiveTracer, ExecutiveVMTracer};
+pub use self::step_tracer::StepTracer;
+pub use self::struct_log_tracer::{StructLog, StructLogOptions, StructLogTracer, run_wasm_tracer};
 pub use self::import::ImportRequest;
 pub use self::localized::LocalizedTrace;
 
@@ -157,6 +161,14 @@ pub trait Database {
 	/// Returns localized traces created in given block.
 	fn block_traces(&self, block_number: BlockNumber) -> Option<Vec<LocalizedTrace>>;
 
+	/// Returns localized traces created in the block with given hash, which doesn't have to be canonical.
+	/// Transaction hashes of the block have to be provided, since they are only indexed for canonical blocks.
+	fn block_traces_by_hash(&self, block_hash: &H256, block_number: BlockNumber, transaction_hashes: &[H256]) -> Option<Vec<LocalizedTrace>>;
+
 	/// Filter traces matching given filter.
 	fn filter(&self, filter: &Filter) -> Vec<LocalizedTrace>;
+
+	/// Filter traces matching given filter, skipping the first `after` matches and returning at most `count` of them.
+	/// Blocks past the last returned trace are not read.
+	fn filter_page(&self, filter: &Filter, after: usize, count: usize) -> Vec<LocalizedTrace>;
 }
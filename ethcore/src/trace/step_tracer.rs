@@ -0,0 +1,134 @@
+// Copyright 2015-2018 Parity Technologies (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+//! VM tracer pausing execution before every instruction.
+
+use std::sync::Arc;
+use std::sync::mpsc::{Receiver, Sender};
+
+use ethereum_types::U256;
+use parking_lot::Mutex;
+use trace::{StructLog, StructLogTracer, VMTracer};
+
+/// VM tracer which blocks the executing thread before every instruction.
+///
+/// The state of the machine is sent to `steps` and execution resumes once a message
+/// is received from `commands`. If either side of the channel is gone, the tracer detaches
+/// and lets the execution run to completion.
+pub struct StepTracer {
+	inner: StructLogTracer,
+	steps: Sender<StructLog>,
+	commands: Arc<Mutex<Receiver<()>>>,
+	detached: bool,
+}
+
+impl StepTracer {
+	/// Create a new top-level instance.
+	pub fn toplevel(steps: Sender<StructLog>, commands: Receiver<()>) -> Self {
+		StepTracer {
+			inner: StructLogTracer::toplevel(Default::default()),
+			steps,
+			commands: Arc::new(Mutex::new(commands)),
+			detached: false,
+		}
+	}
+
+	fn pause(&mut self) {
+		let log = self.inner.last_log().cloned().expect("trace_prepare_execute is always called after a trace_next_instruction");
+		self.detached = self.steps.send(log).is_err() || self.commands.lock().recv().is_err();
+	}
+}
+
+impl VMTracer for StepTracer {
+	type Output = Vec<StructLog>;
+
+	fn trace_next_instruction(&mut self, pc: usize, instruction: u8, current_gas: U256) -> bool {
+		if self.detached {
+			return false;
+		}
+
+		// only the state of the current instruction is of interest
+		self.inner.clear_logs();
+		self.inner.trace_next_instruction(pc, instruction, current_gas)
+	}
+
+	fn trace_prepare_execute(&mut self, pc: usize, instruction: u8, gas_cost: U256) {
+		self.inner.trace_prepare_execute(pc, instruction, gas_cost);
+		if !self.detached {
+			self.pause();
+		}
+	}
+
+	fn trace_executed(&mut self, gas_used: U256, stack_push: &[U256], mem_diff: Option<(usize, &[u8])>, store_diff: Option<(U256, U256)>) {
+		self.inner.trace_executed(gas_used, stack_push, mem_diff, store_diff);
+	}
+
+	fn prepare_subtrace(&self, code: &[u8]) -> Self {
+		StepTracer {
+			inner: self.inner.prepare_subtrace(code),
+			steps: self.steps.clone(),
+			commands: self.commands.clone(),
+			detached: self.detached,
+		}
+	}
+
+	fn done_subtrace(&mut self, sub: Self) {
+		self.detached |= sub.detached;
+		self.inner.done_subtrace(sub.inner);
+	}
+
+	fn drain(self) -> Option<Vec<StructLog>> { Some(Vec::new()) }
+}
+
+#[cfg(test)]
+mod tests {
+	use std::sync::mpsc;
+	use std::thread;
+	use trace::VMTracer;
+	use super::StepTracer;
+
+	#[test]
+	fn should_pause_before_every_instruction() {
+		let (steps_tx, steps) = mpsc::channel();
+		let (commands, commands_rx) = mpsc::channel();
+
+		let handle = thread::spawn(move || {
+			let mut tracer = StepTracer::toplevel(steps_tx, commands_rx).prepare_subtrace(&[]);
+			// PUSH1 0x2a
+			tracer.trace_next_instruction(0, 0x60, 100.into());
+			tracer.trace_prepare_execute(0, 0x60, 3.into());
+			tracer.trace_executed(97.into(), &[0x2a.into()], None, None);
+			// STOP
+			tracer.trace_next_instruction(2, 0x00, 97.into());
+			tracer.trace_prepare_execute(2, 0x00, 0.into());
+			tracer.trace_executed(97.into(), &[], None, None);
+		});
+
+		let first = steps.recv().unwrap();
+		assert_eq!((first.pc, first.depth), (0, 1));
+		assert_eq!(first.stack, Some(vec![]));
+
+		commands.send(()).unwrap();
+		let second = steps.recv().unwrap();
+		assert_eq!(second.pc, 2);
+		assert_eq!(second.stack, Some(vec![0x2a.into()]));
+
+		// dropping the command channel lets the execution finish
+		drop(commands);
+		handle.join().unwrap();
+		assert!(steps.recv().is_err());
+	}
+}
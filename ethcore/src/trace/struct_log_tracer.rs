@@ -0,0 +1,251 @@
+// Copyright 2015-2018 Parity Technologies (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Opcode-level (struct log) VM tracer.
+
+use std::collections::BTreeMap;
+
+use ethereum_types::{H256, U256};
+use evm::Instruction;
+use trace::VMTracer;
+use wasm::tracer::{self, TracerStep};
+
+/// Gas available to a wasm tracer for processing a whole trace.
+const WASM_TRACER_GAS_LIMIT: u64 = 1_000_000_000;
+
+/// Options concerning which parts of the machine state are captured by struct logging.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct StructLogOptions {
+	/// Don't capture the stack.
+	pub disable_stack: bool,
+	/// Don't capture the memory.
+	pub disable_memory: bool,
+	/// Don't capture the storage.
+	pub disable_storage: bool,
+}
+
+/// Machine state right before executing a single instruction.
+#[derive(Debug, Clone, PartialEq)]
+pub struct StructLog {
+	/// Program counter.
+	pub pc: usize,
+	/// Instruction to execute.
+	pub instruction: u8,
+	/// Gas available before executing the instruction.
+	pub gas: U256,
+	/// Gas cost of the instruction.
+	pub gas_cost: U256,
+	/// Call depth, starting at 1 for the outermost call.
+	pub depth: usize,
+	/// Stack, bottom item first.
+	pub stack: Option<Vec<U256>>,
+	/// Memory contents.
+	pub memory: Option<Vec<u8>>,
+	/// Storage slots of the current frame accessed so far.
+	pub storage: Option<BTreeMap<H256, H256>>,
+}
+
+impl StructLog {
+	/// Name of the instruction, empty if the instruction is invalid.
+	pub fn op_name(&self) -> &'static str {
+		Instruction::from_u8(self.instruction).map(|i| i.info().name).unwrap_or("")
+	}
+}
+
+/// VM tracer recording a `StructLog` for every executed instruction.
+///
+/// Stack, memory and storage are rebuilt from the diffs reported by the interpreter.
+pub struct StructLogTracer {
+	options: StructLogOptions,
+	depth: usize,
+	instruction: u8,
+	stack: Vec<U256>,
+	memory: Vec<u8>,
+	storage: BTreeMap<H256, H256>,
+	logs: Vec<StructLog>,
+}
+
+impl StructLogTracer {
+	/// Create a new top-level instance.
+	pub fn toplevel(options: StructLogOptions) -> Self {
+		StructLogTracer {
+			options,
+			depth: 0,
+			instruction: 0,
+			stack: Vec::new(),
+			memory: Vec::new(),
+			storage: BTreeMap::new(),
+			logs: Vec::new(),
+		}
+	}
+
+	/// Log of the most recent instruction.
+	pub(super) fn last_log(&self) -> Option<&StructLog> {
+		self.logs.last()
+	}
+
+	/// Forget the logs recorded so far.
+	pub(super) fn clear_logs(&mut self) {
+		self.logs.clear();
+	}
+}
+
+impl VMTracer for StructLogTracer {
+	type Output = Vec<StructLog>;
+
+	fn trace_next_instruction(&mut self, pc: usize, instruction: u8, current_gas: U256) -> bool {
+		self.instruction = instruction;
+		self.logs.push(StructLog {
+			pc,
+			instruction,
+			gas: current_gas,
+			gas_cost: U256::zero(),
+			depth: self.depth,
+			stack: if self.options.disable_stack { None } else { Some(self.stack.clone()) },
+			memory: if self.options.disable_memory { None } else { Some(self.memory.clone()) },
+			storage: if self.options.disable_storage { None } else { Some(self.storage.clone()) },
+		});
+
+		true
+	}
+
+	fn trace_prepare_execute(&mut self, _pc: usize, _instruction: u8, gas_cost: U256) {
+		self.logs.last_mut().expect("trace_prepare_execute is always called after a trace_next_instruction").gas_cost = gas_cost;
+	}
+
+	fn trace_executed(&mut self, _gas_used: U256, stack_push: &[U256], mem_diff: Option<(usize, &[u8])>, store_diff: Option<(U256, U256)>) {
+		let instruction = Instruction::from_u8(self.instruction);
+		let info = instruction.map(|i| i.info());
+
+		// SLOAD doesn't report a storage diff, so capture the slot from the stack.
+		if instruction == Some(Instruction::SLOAD) {
+			if let (Some(key), Some(value)) = (self.stack.last(), stack_push.first()) {
+				self.storage.insert((*key).into(), (*value).into());
+			}
+		}
+
+		let len = self.stack.len();
+		let info_args = info.map(|i| i.args).unwrap_or(0);
+		self.stack.truncate(if len > info_args { len - info_args } else { 0 });
+		self.stack.extend_from_slice(stack_push);
+
+		if let Some((offset, data)) = mem_diff {
+			let end = offset + data.len();
+			if end > self.memory.len() {
+				// memory always grows in whole words
+				self.memory.resize((end + 31) / 32 * 32, 0);
+			}
+			self.memory[offset..end].copy_from_slice(data);
+		}
+
+		if let Some((key, value)) = store_diff {
+			self.storage.insert(key.into(), value.into());
+		}
+	}
+
+	fn prepare_subtrace(&self, _code: &[u8]) -> Self {
+		let mut sub = StructLogTracer::toplevel(self.options);
+		sub.depth = self.depth + 1;
+		sub
+	}
+
+	fn done_subtrace(&mut self, sub: Self) {
+		self.logs.extend(sub.logs);
+	}
+
+	fn drain(self) -> Option<Vec<StructLog>> { Some(self.logs) }
+}
+
+/// Runs the wasm tracer `code` over recorded struct logs and returns its output.
+///
+/// See `wasm::tracer` for the interface a tracer module has to implement.
+pub fn run_wasm_tracer(code: &[u8], logs: &[StructLog]) -> Result<Vec<u8>, String> {
+	let steps = logs.iter().map(|log| TracerStep {
+		pc: log.pc,
+		instruction: log.instruction,
+		gas: log.gas.low_u64(),
+		gas_cost: log.gas_cost.low_u64(),
+		depth: log.depth,
+		stack: log.stack.as_ref().map_or(&[][..], |stack| &stack[..]),
+		memory: log.memory.as_ref().map_or(&[][..], |memory| &memory[..]),
+	});
+
+	tracer::run(code, WASM_TRACER_GAS_LIMIT, steps).map_err(|e| e.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+	use ethereum_types::U256;
+	use trace::VMTracer;
+	use super::{StructLogOptions, StructLogTracer};
+
+	#[test]
+	fn should_rebuild_stack_memory_and_storage() {
+		let mut tracer = StructLogTracer::toplevel(Default::default()).prepare_subtrace(&[]);
+
+		// PUSH1 0x2a
+		tracer.trace_next_instruction(0, 0x60, 100.into());
+		tracer.trace_prepare_execute(0, 0x60, 3.into());
+		tracer.trace_executed(97.into(), &[0x2a.into()], None, None);
+		// PUSH1 0x00
+		tracer.trace_next_instruction(2, 0x60, 97.into());
+		tracer.trace_prepare_execute(2, 0x60, 3.into());
+		tracer.trace_executed(94.into(), &[0.into()], None, None);
+		// MSTORE
+		tracer.trace_next_instruction(4, 0x52, 94.into());
+		tracer.trace_prepare_execute(4, 0x52, 6.into());
+		tracer.trace_executed(88.into(), &[], Some((0, &[1u8; 32][..])), None);
+		// PUSH1 0x01, PUSH1 0x00, SSTORE
+		tracer.trace_next_instruction(5, 0x60, 88.into());
+		tracer.trace_executed(85.into(), &[1.into()], None, None);
+		tracer.trace_next_instruction(7, 0x60, 85.into());
+		tracer.trace_executed(82.into(), &[0.into()], None, None);
+		tracer.trace_next_instruction(9, 0x55, 82.into());
+		tracer.trace_executed(60.into(), &[], None, Some((0.into(), 1.into())));
+		// STOP
+		tracer.trace_next_instruction(10, 0x00, 60.into());
+
+		let mut top = StructLogTracer::toplevel(Default::default());
+		top.done_subtrace(tracer);
+		let logs = top.drain().unwrap();
+
+		assert_eq!(logs.len(), 7);
+		assert_eq!(logs[0].depth, 1);
+		assert_eq!(logs[0].gas_cost, U256::from(3));
+		assert_eq!(logs[2].stack, Some(vec![0x2a.into(), 0.into()]));
+		assert_eq!(logs[2].memory, Some(vec![]));
+		assert_eq!(logs[3].stack, Some(vec![]));
+		assert_eq!(logs[3].memory, Some(vec![1u8; 32]));
+		assert_eq!(logs[5].storage.as_ref().map(|s| s.len()), Some(0));
+		assert_eq!(logs[6].storage.as_ref().map(|s| s.len()), Some(1));
+	}
+
+	#[test]
+	fn should_respect_options() {
+		let options = StructLogOptions {
+			disable_stack: true,
+			disable_memory: true,
+			disable_storage: false,
+		};
+		let mut tracer = StructLogTracer::toplevel(options).prepare_subtrace(&[]);
+		tracer.trace_next_instruction(0, 0x00, 100.into());
+		let logs = tracer.drain().unwrap();
+
+		assert_eq!(logs[0].stack, None);
+		assert_eq!(logs[0].memory, None);
+		assert_eq!(logs[0].storage, Some(Default::default()));
+	}
+}
@@ -21,6 +21,7 @@ use std::thread::{self, JoinHandle};
 use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering as AtomicOrdering};
 use std::sync::Arc;
 use std::cmp;
+use std::time::Instant;
 use std::collections::{VecDeque, HashSet, HashMap};
 use heapsize::HeapSizeOf;
 use ethereum_types::{H256, U256};
@@ -33,6 +34,7 @@ use client::ClientIoMessage;
 use self::kind::{BlockLike, Kind};
 
 pub use types::verification_queue_info::VerificationQueueInfo as QueueInfo;
+pub use types::verification_queue_info::{QueueStage, QueuedItem};
 
 pub mod kind;
 
@@ -143,7 +145,7 @@ pub struct VerificationQueue<K: Kind> {
 	deleting: Arc<AtomicBool>,
 	ready_signal: Arc<QueueSignal>,
 	empty: Arc<Condvar>,
-	processing: RwLock<HashMap<H256, U256>>, // hash to difficulty
+	processing: RwLock<HashMap<H256, (U256, Instant)>>, // hash to difficulty and time of queueing
 	ticks_since_adjustment: AtomicUsize,
 	max_queue_size: usize,
 	max_mem_use: usize,
@@ -151,6 +153,7 @@ pub struct VerificationQueue<K: Kind> {
 	verifier_handles: Vec<JoinHandle<()>>,
 	state: Arc<(Mutex<State>, Condvar)>,
 	total_difficulty: RwLock<U256>,
+	usage: Arc<ThreadPoolUsage>,
 }
 
 struct QueueSignal {
@@ -240,6 +243,7 @@ impl<K: Kind> VerificationQueue<K> {
 		};
 
 		let state = Arc::new((Mutex::new(State::Work(default_amount)), Condvar::new()));
+		let usage = ThreadPoolUsage::register("verification", default_amount);
 		let mut verifier_handles = Vec::with_capacity(number_of_threads);
 
 		debug!(target: "verification", "Allocating {} verifiers, {} initially active", number_of_threads, default_amount);
@@ -254,10 +258,12 @@ impl<K: Kind> VerificationQueue<K> {
 			let ready = ready_signal.clone();
 			let empty = empty.clone();
 			let state = state.clone();
+			let usage = usage.clone();
 
 			let handle = thread::Builder::new()
 				.name(format!("Verifier #{}", i))
 				.spawn(move || {
+					::io::threads::pin_current_thread(i);
 					VerificationQueue::verify(
 						verification,
 						engine,
@@ -265,6 +271,7 @@ impl<K: Kind> VerificationQueue<K> {
 						ready,
 						empty,
 						state,
+						usage,
 						i,
 					)
 				})
@@ -287,6 +294,7 @@ impl<K: Kind> VerificationQueue<K> {
 			verifier_handles: verifier_handles,
 			state: state,
 			total_difficulty: RwLock::new(0.into()),
+			usage: usage,
 		}
 	}
 
@@ -297,6 +305,7 @@ impl<K: Kind> VerificationQueue<K> {
 		ready: Arc<QueueSignal>,
 		empty: Arc<Condvar>,
 		state: Arc<(Mutex<State>, Condvar)>,
+		usage: Arc<ThreadPoolUsage>,
 		id: usize,
 	) {
 		loop {
@@ -358,7 +367,7 @@ impl<K: Kind> VerificationQueue<K> {
 			};
 
 			let hash = item.hash();
-			let is_ready = match K::verify(item, &*engine, verification.check_seal) {
+			let is_ready = match usage.measure(|| K::verify(item, &*engine, verification.check_seal)) {
 				Ok(verified) => {
 					let mut verifying = verification.verifying.lock();
 					let mut idx = None;
@@ -491,7 +500,7 @@ impl<K: Kind> VerificationQueue<K> {
 			Ok(item) => {
 				self.verification.sizes.unverified.fetch_add(item.heap_size_of_children(), AtomicOrdering::SeqCst);
 
-				self.processing.write().insert(hash, item.difficulty());
+				self.processing.write().insert(hash, (item.difficulty(), Instant::now()));
 				{
 					let mut td = self.total_difficulty.write();
 					*td = *td + item.difficulty();
@@ -526,7 +535,7 @@ impl<K: Kind> VerificationQueue<K> {
 		bad.reserve(hashes.len());
 		for hash in hashes {
 			bad.insert(hash.clone());
-			if let Some(difficulty) = processing.remove(hash) {
+			if let Some((difficulty, _)) = processing.remove(hash) {
 				let mut td = self.total_difficulty.write();
 				*td = *td - difficulty;
 			}
@@ -538,7 +547,7 @@ impl<K: Kind> VerificationQueue<K> {
 			if bad.contains(&output.parent_hash()) {
 				removed_size += output.heap_size_of_children();
 				bad.insert(output.hash());
-				if let Some(difficulty) = processing.remove(&output.hash()) {
+				if let Some((difficulty, _)) = processing.remove(&output.hash()) {
 					let mut td = self.total_difficulty.write();
 					*td = *td - difficulty;
 				}
@@ -559,7 +568,7 @@ impl<K: Kind> VerificationQueue<K> {
 		}
 		let mut processing = self.processing.write();
 		for hash in hashes {
-			if let Some(difficulty) = processing.remove(hash) {
+			if let Some((difficulty, _)) = processing.remove(hash) {
 				let mut td = self.total_difficulty.write();
 				*td = *td - difficulty;
 			}
@@ -616,6 +625,32 @@ impl<K: Kind> VerificationQueue<K> {
 		}
 	}
 
+	/// Get all items in the queue, longest waiting first.
+	pub fn queued_items(&self) -> Vec<QueuedItem> {
+		let mut stages = Vec::new();
+		{
+			let unverified = self.verification.unverified.lock();
+			let verifying = self.verification.verifying.lock();
+			let verified = self.verification.verified.lock();
+			stages.extend(unverified.iter().map(|item| (item.hash(), QueueStage::Unverified)));
+			stages.extend(verifying.iter().map(|item| (item.hash, QueueStage::Verifying)));
+			stages.extend(verified.iter().map(|item| (item.hash(), QueueStage::Verified)));
+		}
+
+		let processing = self.processing.read();
+		let now = Instant::now();
+		let mut items: Vec<_> = stages.into_iter()
+			.filter_map(|(hash, stage)| processing.get(&hash).map(|&(_, queued_at)| QueuedItem {
+				hash,
+				stage,
+				wait_time: now.duration_since(queued_at),
+			}))
+			.collect();
+
+		items.sort_by(|a, b| b.wait_time.cmp(&a.wait_time));
+		items
+	}
+
 	/// Get the total difficulty of all the blocks in the queue.
 	pub fn total_difficulty(&self) -> U256 {
 		self.total_difficulty.read().clone()
@@ -697,6 +732,7 @@ impl<K: Kind> VerificationQueue<K> {
 
 		*self.state.0.lock() = State::Work(target);
 		self.state.1.notify_all();
+		self.usage.set_threads(target);
 	}
 }
 
@@ -730,12 +766,13 @@ impl<K: Kind> Drop for VerificationQueue<K> {
 mod tests {
 	use io::*;
 	use spec::Spec;
-	use super::{BlockQueue, Config, State};
+	use super::{BlockQueue, Config, QueueStage, State};
 	use super::kind::blocks::Unverified;
 	use test_helpers::{get_good_dummy_block_seq, get_good_dummy_block};
 	use error::*;
 	use views::BlockView;
 	use bytes::Bytes;
+	use header::BlockNumber;
 
 	// create a test block queue.
 	// auto_scaling enables verifier adjustment.
@@ -756,7 +793,7 @@ mod tests {
 	}
 
 	fn new_unverified(bytes: Bytes) -> Unverified {
-		Unverified::from_rlp(bytes).expect("Should be valid rlp")
+		Unverified::from_rlp(bytes, BlockNumber::max_value()).expect("Should be valid rlp")
 	}
 
 	#[test]
@@ -798,7 +835,7 @@ mod tests {
 	fn returns_total_difficulty() {
 		let queue = get_test_queue(false);
 		let block = get_good_dummy_block();
-		let hash = view!(BlockView, &block).header().hash().clone();
+		let hash = view!(BlockView, &block).hash();
 		if let Err(e) = queue.import(new_unverified(block)) {
 			panic!("error importing block that is valid by definition({:?})", e);
 		}
@@ -814,7 +851,7 @@ mod tests {
 	fn returns_ok_for_drained_duplicates() {
 		let queue = get_test_queue(false);
 		let block = get_good_dummy_block();
-		let hash = view!(BlockView, &block).header().hash().clone();
+		let hash = view!(BlockView, &block).hash();
 		if let Err(e) = queue.import(new_unverified(block)) {
 			panic!("error importing block that is valid by definition({:?})", e);
 		}
@@ -827,6 +864,24 @@ mod tests {
 		}
 	}
 
+	#[test]
+	fn returns_queued_items() {
+		let queue = get_test_queue(false);
+		let block = get_good_dummy_block();
+		let hash = view!(BlockView, &block).hash();
+		queue.import(new_unverified(block))
+			.expect("error importing block that is valid by definition");
+		queue.flush();
+
+		let items = queue.queued_items();
+		assert_eq!(items.len(), 1);
+		assert_eq!(items[0].hash, hash);
+		assert_eq!(items[0].stage, QueueStage::Verified);
+
+		queue.drain(10);
+		assert!(queue.queued_items().is_empty());
+	}
+
 	#[test]
 	fn returns_empty_once_finished() {
 		let queue = get_test_queue(false);
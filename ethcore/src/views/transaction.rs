@@ -56,7 +56,11 @@ impl<'a> TransactionView<'a> {
 
 	/// Returns transaction hash.
 	pub fn hash(&self) -> H256 {
-		keccak(self.rlp.as_raw())
+		// typed transactions are byte strings wrapping their EIP-2718 envelope
+		match self.rlp.is_list() {
+			true => keccak(self.rlp.as_raw()),
+			false => keccak(self.rlp.data()),
+		}
 	}
 
 	/// Get the nonce field of the transaction.
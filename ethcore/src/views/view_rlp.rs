@@ -77,6 +77,11 @@ impl<'a, 'view> ViewRlp<'a> where 'a : 'view {
 		self.expect_valid_rlp(self.rlp.as_val())
 	}
 
+	/// Returns value of this rlp decoded with the given function, panics if rlp not valid
+	pub fn as_val_with<T, F>(&self, decode: F) -> T where F: FnOnce(&Rlp<'a>) -> Result<T, DecoderError> {
+		self.expect_valid_rlp(decode(&self.rlp))
+	}
+
 	/// Returns decoded value at the given index, panics not present or valid at that index
 	pub fn val_at<T>(&self, index: usize) -> T where T : Decodable {
 		self.expect_valid_rlp(self.rlp.val_at(index))
@@ -96,6 +101,16 @@ impl<'a, 'view> ViewRlp<'a> where 'a : 'view {
 	pub fn as_raw(&'view self) -> &'a [u8] {
 		self.rlp.as_raw()
 	}
+
+	/// Returns the payload of a byte string rlp, panics if rlp is a list or not valid
+	pub fn data(&'view self) -> &'a [u8] {
+		self.expect_valid_rlp(self.rlp.data())
+	}
+
+	/// Returns true if the rlp is a list
+	pub fn is_list(&self) -> bool {
+		self.rlp.is_list()
+	}
 }
 
 /// Iterator over rlp-slice list elements.
@@ -0,0 +1,157 @@
+// Copyright 2015-2018 Parity Technologies (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Stateless block execution witnesses.
+//!
+//! A witness contains every trie node and contract code read while executing a block,
+//! along with the parent header and recent block hashes, so that the block could be
+//! executed and its state root checked without access to the state database.
+
+use std::iter;
+use std::sync::Arc;
+
+use block::enact_verified;
+use bytes::Bytes;
+use db;
+use engines::EthEngine;
+use error::{Error, BlockError};
+use ethereum_types::H256;
+use factory::Factories;
+use header::Header;
+use journaldb::{self, Algorithm};
+use kvdb::{DBTransaction, KeyValueDB};
+use kvdb_memorydb;
+use rlp::{Rlp, RlpStream, Encodable, Decodable, DecoderError};
+use state_db::StateDB;
+use transaction::SignedTransaction;
+use verification::{PreverifiedBlock, verify_block_final};
+use verification::queue::kind::blocks::Unverified;
+
+/// Everything needed to execute a block without the state database.
+#[derive(Debug, Clone, PartialEq)]
+pub struct BlockWitness {
+	/// Header of the parent block.
+	pub parent: Header,
+	/// Hashes of the recent blocks, most recent first.
+	pub last_hashes: Vec<H256>,
+	/// Whether the block begins a new validator set epoch.
+	pub is_epoch_begin: bool,
+	/// Trie nodes and contract code read during the execution.
+	pub state: Vec<Bytes>,
+}
+
+impl Encodable for BlockWitness {
+	fn rlp_append(&self, s: &mut RlpStream) {
+		s.begin_list(4)
+			.append(&self.parent)
+			.append_list(&self.last_hashes)
+			.append(&self.is_epoch_begin)
+			.append_list::<Vec<u8>, _>(&self.state);
+	}
+}
+
+impl Decodable for BlockWitness {
+	fn decode(rlp: &Rlp) -> Result<Self, DecoderError> {
+		if rlp.item_count()? != 4 {
+			return Err(DecoderError::RlpIncorrectListLen);
+		}
+
+		Ok(BlockWitness {
+			parent: rlp.val_at(0)?,
+			last_hashes: rlp.list_at(1)?,
+			is_epoch_begin: rlp.val_at(2)?,
+			state: rlp.list_at(3)?,
+		})
+	}
+}
+
+/// Execute the block given by `block_bytes` against the witness only and check the resulting
+/// state root, receipts root, log bloom and gas used against the block header.
+pub fn verify_block_witness(engine: &EthEngine, witness: &BlockWitness, block_bytes: Bytes) -> Result<(), Error> {
+	let block = Unverified::from_rlp(block_bytes, engine.params().eip1559_transition)?;
+	if *block.header.parent_hash() != witness.parent.hash() {
+		return Err(BlockError::UnknownParent(*block.header.parent_hash()).into());
+	}
+
+	let transactions = block.transactions.into_iter()
+		.map(|t| SignedTransaction::new(t).map_err(Into::into))
+		.collect::<Result<Vec<_>, Error>>()?;
+
+	// trie nodes are looked up by their hashes only, the same nodes may be shared between accounts
+	let mut factories = Factories::default();
+	factories.accountdb = ::account_db::Factory::Plain;
+
+	let expected = block.header.clone();
+	let locked = enact_verified(
+		PreverifiedBlock {
+			header: block.header,
+			transactions: transactions,
+			uncles: block.uncles,
+			bytes: block.bytes,
+		},
+		engine,
+		false,
+		false,
+		witness_state_db(&witness.state)?,
+		&witness.parent,
+		Arc::new(witness.last_hashes.clone()),
+		factories,
+		witness.is_epoch_begin,
+		&mut iter::empty(),
+	)?;
+
+	verify_block_final(&expected, locked.header())
+}
+
+/// State database containing only the witness values. Values are never removed from it,
+/// so nodes shared by different parts of the state stay available.
+fn witness_state_db(state: &[Bytes]) -> Result<StateDB, Error> {
+	let kvdb: Arc<KeyValueDB> = Arc::new(kvdb_memorydb::create(db::NUM_COLUMNS.unwrap_or(0)));
+	let mut journal = journaldb::new(kvdb.clone(), Algorithm::Archive, db::COL_STATE);
+	for value in state {
+		journal.as_hashdb_mut().insert(value);
+	}
+
+	let mut batch = DBTransaction::new();
+	journal.inject(&mut batch)?;
+	kvdb.write(batch)?;
+
+	// the account bloom of the fresh database is empty, use a clone which doesn't consult it.
+	let (state_db, _) = StateDB::new(journal, 0).boxed_clone_recording();
+	Ok(state_db)
+}
+
+#[cfg(test)]
+mod tests {
+	use rlp;
+	use header::Header;
+	use super::BlockWitness;
+
+	#[test]
+	fn witness_rlp_roundtrip() {
+		let mut parent = Header::new();
+		parent.set_number(10);
+		let witness = BlockWitness {
+			parent: parent,
+			last_hashes: vec![1.into(), 2.into()],
+			is_epoch_begin: false,
+			state: vec![b"node".to_vec(), b"code".to_vec()],
+		};
+
+		let encoded = rlp::encode(&witness);
+		assert_eq!(rlp::decode::<BlockWitness>(&encoded).unwrap(), witness);
+	}
+}
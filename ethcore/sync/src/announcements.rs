@@ -0,0 +1,219 @@
+// Copyright 2015-2018 Parity Technologies (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+use api::BlockAnnouncementStats;
+use std::collections::{HashSet, VecDeque};
+use ethereum_types::H256;
+use fastmap::H256FastMap;
+use network::PeerId;
+
+/// Deduplicates block announcements within a window of recently seen blocks.
+///
+/// For every block in the window we remember which peers are known to have it,
+/// either because they announced it to us or because we already sent it to them.
+/// A window of `0` disables deduplication, only counting announcements.
+#[derive(Debug)]
+pub struct Announcements {
+	window: usize,
+	known_by: H256FastMap<HashSet<PeerId>>,
+	order: VecDeque<H256>,
+	stats: BlockAnnouncementStats,
+}
+
+impl Announcements {
+	/// Create a new deduplication window of given size (in blocks).
+	pub fn new(window: usize) -> Self {
+		Announcements {
+			window,
+			known_by: Default::default(),
+			order: VecDeque::new(),
+			stats: Default::default(),
+		}
+	}
+
+	fn peers_knowing(&mut self, hash: &H256) -> Option<&mut HashSet<PeerId>> {
+		if self.window == 0 {
+			return None;
+		}
+
+		if !self.known_by.contains_key(hash) {
+			if self.order.len() >= self.window {
+				if let Some(oldest) = self.order.pop_front() {
+					self.known_by.remove(&oldest);
+				}
+			}
+			self.order.push_back(*hash);
+			self.known_by.insert(*hash, HashSet::new());
+		}
+
+		self.known_by.get_mut(hash)
+	}
+
+	/// Records an announcement of a block received from the peer.
+	/// Returns `true` if the block was already announced to us within the window.
+	pub fn received(&mut self, peer_id: PeerId, hash: &H256) -> bool {
+		self.stats.received += 1;
+		let duplicate = match self.peers_knowing(hash) {
+			Some(peers) => {
+				let duplicate = !peers.is_empty();
+				peers.insert(peer_id);
+				duplicate
+			},
+			None => false,
+		};
+		if duplicate {
+			self.stats.duplicates_received += 1;
+		}
+		duplicate
+	}
+
+	/// Checks whether the hash of the block should be announced to the peer.
+	/// Returns `false` for peers already known to have the block.
+	pub fn should_announce(&mut self, peer_id: PeerId, hash: &H256) -> bool {
+		self.should_send(peer_id, hash, false)
+	}
+
+	/// Checks whether the full block should be sent to the peer and records it as known if so.
+	/// Returns `false` for peers already known to have the block.
+	pub fn should_send_block(&mut self, peer_id: PeerId, hash: &H256) -> bool {
+		self.should_send(peer_id, hash, true)
+	}
+
+	fn should_send(&mut self, peer_id: PeerId, hash: &H256, mark_known: bool) -> bool {
+		let send = match self.peers_knowing(hash) {
+			Some(peers) => {
+				let known = peers.contains(&peer_id);
+				if !known && mark_known {
+					peers.insert(peer_id);
+				}
+				!known
+			},
+			None => true,
+		};
+		if send {
+			self.stats.sent += 1;
+		} else {
+			self.stats.duplicates_suppressed += 1;
+		}
+		send
+	}
+
+	/// Forget everything known about a disconnected peer.
+	pub fn peer_disconnected(&mut self, peer_id: PeerId) {
+		for peers in self.known_by.values_mut() {
+			peers.remove(&peer_id);
+		}
+	}
+
+	/// Returns announcement counters.
+	pub fn stats(&self) -> &BlockAnnouncementStats {
+		&self.stats
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::Announcements;
+
+	#[test]
+	fn should_count_duplicate_announcements() {
+		// given
+		let mut announcements = Announcements::new(16);
+		let hash = 5.into();
+
+		// when
+		assert!(!announcements.received(1, &hash));
+		assert!(announcements.received(2, &hash));
+		assert!(announcements.received(1, &hash));
+
+		// then
+		let stats = announcements.stats();
+		assert_eq!(stats.received, 3);
+		assert_eq!(stats.duplicates_received, 2);
+	}
+
+	#[test]
+	fn should_not_send_to_peers_knowing_the_block() {
+		// given
+		let mut announcements = Announcements::new(16);
+		let hash = 5.into();
+		announcements.received(1, &hash);
+
+		// when
+		let to_announcer = announcements.should_send_block(1, &hash);
+		let first = announcements.should_send_block(2, &hash);
+		let second = announcements.should_send_block(2, &hash);
+		let announce = announcements.should_announce(2, &hash);
+
+		// then
+		assert!(!to_announcer);
+		assert!(first);
+		assert!(!second);
+		assert!(!announce);
+		let stats = announcements.stats();
+		assert_eq!(stats.sent, 1);
+		assert_eq!(stats.duplicates_suppressed, 3);
+	}
+
+	#[test]
+	fn should_send_block_after_hash_announcement() {
+		let mut announcements = Announcements::new(16);
+		let hash = 5.into();
+
+		assert!(announcements.should_announce(1, &hash));
+		assert!(announcements.should_send_block(1, &hash));
+		assert_eq!(announcements.stats().sent, 2);
+	}
+
+	#[test]
+	fn should_forget_blocks_outside_of_window() {
+		// given
+		let mut announcements = Announcements::new(2);
+		announcements.received(1, &1.into());
+		announcements.received(1, &2.into());
+
+		// when
+		announcements.received(1, &3.into());
+
+		// then
+		assert!(announcements.should_send_block(1, &1.into()));
+		assert!(!announcements.should_send_block(1, &3.into()));
+	}
+
+	#[test]
+	fn should_not_deduplicate_with_empty_window() {
+		let mut announcements = Announcements::new(0);
+		let hash = 5.into();
+
+		assert!(!announcements.received(1, &hash));
+		assert!(!announcements.received(1, &hash));
+		assert!(announcements.should_send_block(1, &hash));
+		assert!(announcements.should_send_block(1, &hash));
+		assert_eq!(announcements.stats().received, 2);
+		assert_eq!(announcements.stats().duplicates_received, 0);
+	}
+
+	#[test]
+	fn should_forget_disconnected_peers() {
+		let mut announcements = Announcements::new(16);
+		let hash = 5.into();
+		announcements.received(1, &hash);
+
+		announcements.peer_disconnected(1);
+
+		assert!(announcements.should_send_block(1, &hash));
+	}
+}
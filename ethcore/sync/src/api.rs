@@ -23,7 +23,7 @@ use bytes::Bytes;
 use devp2p::NetworkService;
 use network::{NetworkProtocolHandler, NetworkContext, PeerId, ProtocolId,
 	NetworkConfiguration as BasicNetworkConfiguration, NonReservedPeerMode, Error, ErrorKind,
-	ConnectionFilter};
+	ConnectionFilter, ProtocolSlots, FleetConfiguration, HandshakeFailure, Traffic};
 
 use types::pruning_info::PruningInfo;
 use ethereum_types::{H256, H512, U256};
@@ -49,6 +49,7 @@ use light::net::{
 use network::IpFilter;
 use private_tx::PrivateTxHandler;
 use transaction::UnverifiedTransaction;
+use stats::{PrometheusMetrics, PrometheusRegistry};
 
 /// Parity sync protocol
 pub const WARP_SYNC_PROTOCOL_ID: ProtocolId = *b"par";
@@ -92,6 +93,30 @@ impl WarpSync {
 	}
 }
 
+/// Strategy used to propagate newly imported blocks to peers that don't have them yet.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BlockPropagation {
+	/// Send full blocks to sqrt(peers) and announce hashes to all of them.
+	Sqrt,
+	/// Send full blocks to all peers.
+	Push,
+	/// Only announce hashes, peers request the blocks they are missing.
+	Announce,
+}
+
+impl FromStr for BlockPropagation {
+	type Err = String;
+
+	fn from_str(s: &str) -> Result<Self, Self::Err> {
+		match s {
+			"sqrt" => Ok(BlockPropagation::Sqrt),
+			"push" => Ok(BlockPropagation::Push),
+			"announce" => Ok(BlockPropagation::Announce),
+			other => Err(format!("Invalid block propagation strategy: {}", other)),
+		}
+	}
+}
+
 /// Sync configuration
 #[derive(Debug, Clone, Copy)]
 pub struct SyncConfig {
@@ -99,6 +124,8 @@ pub struct SyncConfig {
 	pub max_download_ahead_blocks: usize,
 	/// Enable ancient block download.
 	pub download_old_blocks: bool,
+	/// Download receipts of ancient blocks.
+	pub download_old_receipts: bool,
 	/// Network ID
 	pub network_id: u64,
 	/// Main "eth" subprotocol name.
@@ -107,10 +134,24 @@ pub struct SyncConfig {
 	pub light_subprotocol_name: [u8; 3],
 	/// Fork block to check
 	pub fork_block: Option<(BlockNumber, H256)>,
+	/// Block from which headers carry the EIP-1559 base fee.
+	pub eip1559_transition: BlockNumber,
 	/// Enable snapshot sync
 	pub warp_sync: WarpSync,
 	/// Enable light client server.
 	pub serve_light: bool,
+	/// Block propagation strategy.
+	pub block_propagation: BlockPropagation,
+	/// Number of recent blocks for which duplicate announcements are suppressed.
+	pub announcement_window: usize,
+	/// Number of block headers requested at once from a peer with average latency.
+	pub headers_batch: usize,
+	/// Number of block bodies requested at once from a peer with average latency.
+	pub bodies_batch: usize,
+	/// Cap of bandwidth used for sending snapshot chunks to a single peer, in bytes per second.
+	pub snapshot_serving_peer_rate: Option<u64>,
+	/// Cap of bandwidth used for sending snapshot chunks to all peers, in bytes per second.
+	pub snapshot_serving_rate: Option<u64>,
 }
 
 impl Default for SyncConfig {
@@ -118,12 +159,20 @@ impl Default for SyncConfig {
 		SyncConfig {
 			max_download_ahead_blocks: 20000,
 			download_old_blocks: true,
+			download_old_receipts: true,
 			network_id: 1,
 			subprotocol_name: ETH_PROTOCOL,
 			light_subprotocol_name: LIGHT_PROTOCOL,
 			fork_block: None,
+			eip1559_transition: BlockNumber::max_value(),
 			warp_sync: WarpSync::Disabled,
 			serve_light: false,
+			block_propagation: BlockPropagation::Sqrt,
+			announcement_window: 256,
+			headers_batch: 128,
+			bodies_batch: 32,
+			snapshot_serving_peer_rate: None,
+			snapshot_serving_rate: None,
 		}
 	}
 }
@@ -141,6 +190,12 @@ pub trait SyncProvider: Send + Sync {
 
 	/// Returns propagation count for pending transactions.
 	fn transactions_stats(&self) -> BTreeMap<H256, TransactionStats>;
+
+	/// Returns announcement and import times of recently announced blocks.
+	fn blocks_stats(&self) -> BTreeMap<H256, BlockPropagationStats>;
+
+	/// Returns counters of block announcements received and sent.
+	fn announcement_stats(&self) -> BlockAnnouncementStats;
 }
 
 /// Transaction stats
@@ -152,6 +207,32 @@ pub struct TransactionStats {
 	pub propagated_to: BTreeMap<H512, usize>,
 }
 
+/// Block propagation stats
+#[derive(Debug)]
+pub struct BlockPropagationStats {
+	/// Block number.
+	pub number: u64,
+	/// Time of the first announcement, in milliseconds since the unix epoch.
+	pub first_seen: u64,
+	/// Milliseconds between the first announcement and import, if imported.
+	pub import_delay: Option<u64>,
+	/// Milliseconds between the first announcement and the announcement by each peer.
+	pub announced_by: BTreeMap<H512, u64>,
+}
+
+/// Block announcement counters
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct BlockAnnouncementStats {
+	/// Block announcements (new blocks and new hashes) received from peers.
+	pub received: u64,
+	/// Received announcements of blocks already announced by another peer.
+	pub duplicates_received: u64,
+	/// Block announcements sent to peers.
+	pub sent: u64,
+	/// Announcements not sent because the peer was already known to have the block.
+	pub duplicates_suppressed: u64,
+}
+
 /// Peer connection information
 #[derive(Debug)]
 pub struct PeerInfo {
@@ -169,6 +250,8 @@ pub struct PeerInfo {
 	pub eth_info: Option<EthProtocolInfo>,
 	/// Light protocol info.
 	pub pip_info: Option<PipProtocolInfo>,
+	/// Bytes exchanged with the peer per protocol.
+	pub traffic: HashMap<ProtocolId, Traffic>,
 }
 
 /// Ethereum protocol info.
@@ -288,6 +371,11 @@ fn light_params(
 	light_params
 }
 
+/// Discovery v5 topic of nodes serving light clients of the given network.
+fn light_topic(subprotocol_name: &[u8; 3], network_id: u64) -> String {
+	format!("{}@{}", String::from_utf8_lossy(subprotocol_name), network_id)
+}
+
 impl EthSync {
 	/// Creates and register protocol with the network service
 	pub fn new(params: Params, connection_filter: Option<Arc<ConnectionFilter>>) -> Result<Arc<EthSync>, Error> {
@@ -315,8 +403,13 @@ impl EthSync {
 			})
 		};
 
+		let mut network_config = params.network_config.clone().into_basic()?;
+		if light_proto.is_some() {
+			network_config.advertised_topics.push(light_topic(&params.config.light_subprotocol_name, params.config.network_id));
+		}
+
 		let chain_sync = ChainSync::new(params.config, &*params.chain, params.private_tx_handler.clone());
-		let service = NetworkService::new(params.network_config.clone().into_basic()?, connection_filter)?;
+		let service = NetworkService::new(network_config, connection_filter)?;
 
 		let sync = Arc::new(EthSync {
 			network: service,
@@ -336,6 +429,27 @@ impl EthSync {
 	}
 }
 
+impl PrometheusMetrics for EthSync {
+	fn prometheus_metrics(&self, registry: &mut PrometheusRegistry) {
+		let status = self.status();
+		registry.register_gauge("sync_peers", "Number of connected peers", status.num_peers as i64);
+		registry.register_gauge("sync_active_peers", "Number of peers actively used for syncing", status.num_active_peers as i64);
+		registry.register_gauge("sync_highest_block", "Highest block advertised by peers", status.highest_block_number.unwrap_or(0) as i64);
+		registry.register_gauge("sync_snapshot_chunks", "Number of chunks of the snapshot being restored", status.num_snapshot_chunks as i64);
+		registry.register_gauge("sync_snapshot_chunks_done", "Number of restored snapshot chunks", status.snapshot_chunks_done as i64);
+		registry.register_gauge("sync_mem_bytes", "Memory used by the sync", status.mem_used as i64);
+
+		let mut traffic: Vec<_> = self.network.traffic().into_iter()
+			.map(|(protocol, t)| (String::from_utf8_lossy(&protocol).into_owned(), t))
+			.collect();
+		traffic.sort_by(|a, b| a.0.cmp(&b.0));
+		registry.register_counters("network_bytes_in", "Bytes received from peers per protocol", "protocol",
+			traffic.iter().map(|&(ref protocol, ref t)| (protocol.as_str(), t.bytes_in)));
+		registry.register_counters("network_bytes_out", "Bytes sent to peers per protocol", "protocol",
+			traffic.iter().map(|&(ref protocol, ref t)| (protocol.as_str(), t.bytes_out)));
+	}
+}
+
 impl SyncProvider for EthSync {
 	/// Get sync status
 	fn status(&self) -> EthSyncStatus {
@@ -363,6 +477,7 @@ impl SyncProvider for EthSync {
 					local_address: session_info.local_address,
 					eth_info: eth_sync.peer_info(&peer_id),
 					pip_info: light_proto.as_ref().and_then(|lp| lp.peer_status(peer_id)).map(Into::into),
+					traffic: session_info.traffic,
 				})
 			}).collect()
 		}).unwrap_or_else(Vec::new)
@@ -379,6 +494,18 @@ impl SyncProvider for EthSync {
 			.map(|(hash, stats)| (*hash, stats.into()))
 			.collect()
 	}
+
+	fn blocks_stats(&self) -> BTreeMap<H256, BlockPropagationStats> {
+		let sync = self.eth_handler.sync.read();
+		sync.blocks_stats()
+			.iter()
+			.map(|(hash, stats)| (*hash, stats.into()))
+			.collect()
+	}
+
+	fn announcement_stats(&self) -> BlockAnnouncementStats {
+		self.eth_handler.sync.read().announcement_stats().clone()
+	}
 }
 
 const PEERS_TIMER: TimerToken = 0;
@@ -544,7 +671,7 @@ struct TxRelay(Arc<BlockChainClient>);
 impl LightHandler for TxRelay {
 	fn on_transactions(&self, ctx: &EventContext, relay: &[::transaction::UnverifiedTransaction]) {
 		trace!(target: "pip", "Relaying {} transactions from peer {}", relay.len(), ctx.peer());
-		self.0.queue_transactions(relay.iter().map(|tx| ::rlp::encode(tx).into_vec()).collect(), ctx.peer())
+		self.0.queue_transactions(relay.iter().map(|tx| ::rlp::encode(tx).into_vec()).collect(), ctx.peer(), None)
 	}
 }
 
@@ -558,6 +685,8 @@ pub trait ManageNetwork : Send + Sync {
 	fn remove_reserved_peer(&self, peer: String) -> Result<(), String>;
 	/// Add reserved peer
 	fn add_reserved_peer(&self, peer: String) -> Result<(), String>;
+	/// Replace all reserved peers, keeping connections to peers which stay reserved
+	fn set_reserved_peers(&self, peers: Vec<String>) -> Result<(), String>;
 	/// Start network
 	fn start_network(&self);
 	/// Stop network
@@ -566,6 +695,10 @@ pub trait ManageNetwork : Send + Sync {
 	/// Note that `range.end` is *exclusive*.
 	// TODO: Range should be changed to RangeInclusive once stable (https://github.com/rust-lang/rust/pull/50758)
 	fn num_peers_range(&self) -> Range<u32>;
+	/// Returns recent failed handshakes, oldest first.
+	fn handshake_failures(&self) -> Vec<HandshakeFailure>;
+	/// Returns bytes exchanged with all peers since the network was started, per protocol.
+	fn traffic(&self) -> HashMap<ProtocolId, Traffic>;
 	/// Get network context for protocol.
 	fn with_proto_context(&self, proto: ProtocolId, f: &mut FnMut(&NetworkContext));
 }
@@ -587,6 +720,10 @@ impl ManageNetwork for EthSync {
 		self.network.add_reserved_peer(&peer).map_err(|e| format!("{:?}", e))
 	}
 
+	fn set_reserved_peers(&self, peers: Vec<String>) -> Result<(), String> {
+		self.network.set_reserved_peers(&peers).map_err(|e| format!("{:?}", e))
+	}
+
 	fn start_network(&self) {
 		self.start();
 	}
@@ -608,6 +745,14 @@ impl ManageNetwork for EthSync {
 		self.network.num_peers_range()
 	}
 
+	fn handshake_failures(&self) -> Vec<HandshakeFailure> {
+		self.network.handshake_failures()
+	}
+
+	fn traffic(&self) -> HashMap<ProtocolId, Traffic> {
+		self.network.traffic()
+	}
+
 	fn with_proto_context(&self, proto: ProtocolId, f: &mut FnMut(&NetworkContext)) {
 		self.network.with_context_eval(proto, f);
 	}
@@ -628,8 +773,12 @@ pub struct NetworkConfiguration {
 	pub udp_port: Option<u16>,
 	/// Enable NAT configuration
 	pub nat_enabled: bool,
+	/// Enable NAT-PMP and PCP port mapping, used when UPnP is not available
+	pub nat_pmp_enabled: bool,
 	/// Enable discovery
 	pub discovery_enabled: bool,
+	/// Enable topic discovery of discovery v5, so that light clients find nodes serving them
+	pub discovery_v5: bool,
 	/// List of initial node addresses
 	pub boot_nodes: Vec<String>,
 	/// Use provided node key instead of default
@@ -642,14 +791,24 @@ pub struct NetworkConfiguration {
 	pub max_pending_peers: u32,
 	/// Reserved snapshot sync peers.
 	pub snapshot_peers: u32,
+	/// Peer slots of specific subprotocols, in order of priority.
+	pub peer_slots: Vec<ProtocolSlots>,
 	/// List of reserved node addresses.
 	pub reserved_nodes: Vec<String>,
 	/// The non-reserved peer mode.
 	pub allow_non_reserved: bool,
 	/// IP Filtering
 	pub ip_filter: IpFilter,
+	/// File with IP addresses and CIDR ranges to refuse connections with, reloaded when it changes
+	pub denied_ips_path: Option<String>,
 	/// Client version string
 	pub client_version: String,
+	/// Number of network IO worker threads
+	pub io_threads: usize,
+	/// File to append secrets of established RLPx sessions to. For debugging only.
+	pub key_log_path: Option<String>,
+	/// Sharing of peers and bans with other nodes of the same operator.
+	pub fleet: Option<FleetConfiguration>,
 }
 
 impl NetworkConfiguration {
@@ -672,17 +831,26 @@ impl NetworkConfiguration {
 			public_address: match self.public_address { None => None, Some(addr) => Some(SocketAddr::from_str(&addr)?) },
 			udp_port: self.udp_port,
 			nat_enabled: self.nat_enabled,
+			nat_pmp_enabled: self.nat_pmp_enabled,
 			discovery_enabled: self.discovery_enabled,
+			discovery_v5: self.discovery_v5,
+			advertised_topics: Vec::new(),
+			searched_topics: Vec::new(),
 			boot_nodes: self.boot_nodes,
 			use_secret: self.use_secret,
 			max_peers: self.max_peers,
 			min_peers: self.min_peers,
 			max_handshakes: self.max_pending_peers,
 			reserved_protocols: hash_map![WARP_SYNC_PROTOCOL_ID => self.snapshot_peers],
+			peer_slots: self.peer_slots,
 			reserved_nodes: self.reserved_nodes,
 			ip_filter: self.ip_filter,
+			denied_ips_path: self.denied_ips_path,
 			non_reserved_mode: if self.allow_non_reserved { NonReservedPeerMode::Accept } else { NonReservedPeerMode::Deny },
 			client_version: self.client_version,
+			io_threads: self.io_threads,
+			key_log_path: self.key_log_path,
+			fleet: self.fleet,
 		})
 	}
 }
@@ -696,17 +864,24 @@ impl From<BasicNetworkConfiguration> for NetworkConfiguration {
 			public_address: other.public_address.and_then(|addr| Some(format!("{}", addr))),
 			udp_port: other.udp_port,
 			nat_enabled: other.nat_enabled,
+			nat_pmp_enabled: other.nat_pmp_enabled,
 			discovery_enabled: other.discovery_enabled,
+			discovery_v5: other.discovery_v5,
 			boot_nodes: other.boot_nodes,
 			use_secret: other.use_secret,
 			max_peers: other.max_peers,
 			min_peers: other.min_peers,
 			max_pending_peers: other.max_handshakes,
 			snapshot_peers: *other.reserved_protocols.get(&WARP_SYNC_PROTOCOL_ID).unwrap_or(&0),
+			peer_slots: other.peer_slots,
 			reserved_nodes: other.reserved_nodes,
 			ip_filter: other.ip_filter,
+			denied_ips_path: other.denied_ips_path,
 			allow_non_reserved: match other.non_reserved_mode { NonReservedPeerMode::Accept => true, _ => false } ,
 			client_version: other.client_version,
+			io_threads: other.io_threads,
+			key_log_path: other.key_log_path,
+			fleet: other.fleet,
 		}
 	}
 }
@@ -811,7 +986,9 @@ impl LightSync {
 			(sync_handler, Arc::new(light_proto))
 		};
 
-		let service = NetworkService::new(params.network_config, None)?;
+		let mut network_config = params.network_config;
+		network_config.searched_topics.push(light_topic(&params.subprotocol_name, params.network_id));
+		let service = NetworkService::new(network_config, None)?;
 
 		Ok(LightSync {
 			proto: light_proto,
@@ -857,6 +1034,10 @@ impl ManageNetwork for LightSync {
 		self.network.add_reserved_peer(&peer).map_err(|e| format!("{:?}", e))
 	}
 
+	fn set_reserved_peers(&self, peers: Vec<String>) -> Result<(), String> {
+		self.network.set_reserved_peers(&peers).map_err(|e| format!("{:?}", e))
+	}
+
 	fn start_network(&self) {
 		match self.network.start() {
 			Err((err, listen_address)) => {
@@ -887,6 +1068,14 @@ impl ManageNetwork for LightSync {
 		self.network.num_peers_range()
 	}
 
+	fn handshake_failures(&self) -> Vec<HandshakeFailure> {
+		self.network.handshake_failures()
+	}
+
+	fn traffic(&self) -> HashMap<ProtocolId, Traffic> {
+		self.network.traffic()
+	}
+
 	fn with_proto_context(&self, proto: ProtocolId, f: &mut FnMut(&NetworkContext)) {
 		self.network.with_context_eval(proto, f);
 	}
@@ -923,6 +1112,7 @@ impl LightSyncProvider for LightSync {
 					local_address: session_info.local_address,
 					eth_info: None,
 					pip_info: self.proto.peer_status(peer_id).map(Into::into),
+					traffic: session_info.traffic,
 				})
 			}).collect()
 		}).unwrap_or_else(Vec::new)
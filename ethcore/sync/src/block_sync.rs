@@ -20,6 +20,7 @@
 
 use std::collections::{HashSet, VecDeque};
 use std::cmp;
+use std::time::Duration;
 use heapsize::HeapSizeOf;
 use ethereum_types::H256;
 use rlp::{self, Rlp};
@@ -28,6 +29,7 @@ use ethcore::client::{BlockStatus, BlockId};
 use ethcore::error::{ImportErrorKind, QueueErrorKind, BlockError, Error as EthcoreError, ErrorKind as EthcoreErrorKind};
 use sync_io::SyncIo;
 use blocks::{BlockCollection, SyncBody, SyncHeader};
+use chain::{MAX_HEADERS_TO_SEND, MAX_BODIES_TO_SEND};
 
 const MAX_HEADERS_TO_REQUEST: usize = 128;
 const MAX_BODIES_TO_REQUEST: usize = 32;
@@ -35,6 +37,49 @@ const MAX_RECEPITS_TO_REQUEST: usize = 128;
 const SUBCHAIN_SIZE: u64 = 256;
 const MAX_ROUND_PARENTS: usize = 16;
 const MAX_PARALLEL_SUBCHAIN_DOWNLOAD: usize = 5;
+/// Response time batch sizes are configured for. Peers responding faster are asked for more data at once.
+const TARGET_RESPONSE_TIME_MS: u64 = 1000;
+/// Maximal factor batch sizes are scaled up or down by.
+const MAX_BATCH_SCALE: u64 = 4;
+
+/// Number of headers and bodies requested from a peer at once.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct BatchSize {
+	/// Number of headers.
+	pub headers: usize,
+	/// Number of bodies.
+	pub bodies: usize,
+}
+
+impl Default for BatchSize {
+	fn default() -> Self {
+		BatchSize {
+			headers: MAX_HEADERS_TO_REQUEST,
+			bodies: MAX_BODIES_TO_REQUEST,
+		}
+	}
+}
+
+impl BatchSize {
+	/// Scales batch sizes to the average response time of a peer, so that low latency peers are utilized
+	/// and slow peers are not overwhelmed. Sizes are unchanged if the response time is unknown.
+	pub fn for_latency(&self, latency: Option<Duration>) -> BatchSize {
+		let latency_ms = match latency {
+			Some(latency) => cmp::max(latency.as_secs() * 1000 + latency.subsec_millis() as u64, 1),
+			None => return *self,
+		};
+		let scale = |size: usize, max: usize| {
+			let size = size as u64;
+			let scaled = cmp::min(size * TARGET_RESPONSE_TIME_MS / latency_ms, size * MAX_BATCH_SCALE);
+			let scaled = cmp::max(scaled, size / MAX_BATCH_SCALE);
+			cmp::max(cmp::min(scaled, max as u64), 1) as usize
+		};
+		BatchSize {
+			headers: scale(self.headers, MAX_HEADERS_TO_SEND),
+			bodies: scale(self.bodies, MAX_BODIES_TO_SEND),
+		}
+	}
+}
 
 #[derive(Copy, Clone, Eq, PartialEq, Debug)]
 /// Downloader state
@@ -55,6 +100,7 @@ pub enum BlockRequest {
 		start: H256,
 		count: u64,
 		skip: u64,
+		reverse: bool,
 	},
 	Bodies {
 		hashes: Vec<H256>,
@@ -86,6 +132,22 @@ impl From<rlp::DecoderError> for BlockDownloaderImportError {
 	}
 }
 
+/// State of a download going backwards, from the first block after the gap in the chain towards genesis.
+/// Blocks are downloaded in batches: headers of a batch are validated as a chain of parents first,
+/// then bodies and receipts are downloaded and the whole batch is queued from the highest block down.
+struct ReverseDownload {
+	/// Hash of the highest block not downloaded yet.
+	next_hash: H256,
+	/// Number of the highest block not downloaded yet.
+	next_number: BlockNumber,
+	/// Number of the highest block before the gap, download is complete once it is reached.
+	target_number: BlockNumber,
+	/// Headers of the next batch are being downloaded.
+	downloading_headers: bool,
+	/// Headers of the current batch are downloaded and waiting for bodies and receipts.
+	has_batch: bool,
+}
+
 /// Block downloader strategy.
 /// Manages state and block data for a block download process.
 pub struct BlockDownloader {
@@ -114,6 +176,10 @@ pub struct BlockDownloader {
 	retract_step: u64,
 	/// Whether reorg should be limited.
 	limit_reorg: bool,
+	/// Whether blocks are imported as ancient blocks.
+	ancient: bool,
+	/// Set if blocks are downloaded backwards.
+	reverse: Option<ReverseDownload>,
 }
 
 impl BlockDownloader {
@@ -134,10 +200,12 @@ impl BlockDownloader {
 			target_hash: None,
 			retract_step: 1,
 			limit_reorg: true,
+			ancient: false,
+			reverse: None,
 		}
 	}
 
-	/// Create a new instance of sync with unlimited reorg allowed.
+	/// Create a new instance of sync for ancient blocks with unlimited reorg allowed.
 	pub fn with_unlimited_reorg(sync_receipts: bool, start_hash: &H256, start_number: BlockNumber) -> Self {
 		BlockDownloader {
 			state: State::Idle,
@@ -153,9 +221,26 @@ impl BlockDownloader {
 			target_hash: None,
 			retract_step: 1,
 			limit_reorg: false,
+			ancient: true,
+			reverse: None,
 		}
 	}
 
+	/// Create a new instance of sync for ancient blocks downloading backwards from block `next_hash`
+	/// with number `next_number` down to the block after `target_number`.
+	pub fn reverse(sync_receipts: bool, next_hash: &H256, next_number: BlockNumber, target_number: BlockNumber) -> Self {
+		let mut downloader = BlockDownloader::with_unlimited_reorg(sync_receipts, next_hash, next_number + 1);
+		downloader.state = if next_number > target_number { State::Blocks } else { State::Complete };
+		downloader.reverse = Some(ReverseDownload {
+			next_hash: next_hash.clone(),
+			next_number,
+			target_number,
+			downloading_headers: false,
+			has_batch: false,
+		});
+		downloader
+	}
+
 	/// Reset sync. Clear all local downloaded data.
 	pub fn reset(&mut self) {
 		self.blocks.clear();
@@ -190,6 +275,11 @@ impl BlockDownloader {
 
 	/// Unmark header as being downloaded.
 	pub fn clear_header_download(&mut self, hash: &H256) {
+		if let Some(ref mut reverse) = self.reverse {
+			if reverse.next_hash == *hash {
+				reverse.downloading_headers = false;
+			}
+		}
 		self.blocks.clear_header_download(hash)
 	}
 
@@ -220,7 +310,10 @@ impl BlockDownloader {
 	}
 
 	/// Add new block headers.
-	pub fn import_headers(&mut self, io: &mut SyncIo, r: &Rlp, expected_hash: Option<H256>) -> Result<DownloadAction, BlockDownloaderImportError> {
+	pub fn import_headers(&mut self, io: &mut SyncIo, r: &Rlp, expected_hash: Option<H256>, eip1559_transition: BlockNumber) -> Result<DownloadAction, BlockDownloaderImportError> {
+		if self.reverse.is_some() {
+			return self.import_reverse_headers(r, expected_hash, eip1559_transition);
+		}
 		let item_count = r.item_count().unwrap_or(0);
 		if self.state == State::Idle {
 			trace!(target: "sync", "Ignored unexpected block headers");
@@ -235,7 +328,7 @@ impl BlockDownloader {
 		let mut valid_response = item_count == 0; //empty response is valid
 		let mut any_known = false;
 		for i in 0..item_count {
-			let info = SyncHeader::from_rlp(r.at(i)?.as_raw().to_vec())?;
+			let info = SyncHeader::from_rlp(r.at(i)?.as_raw().to_vec(), eip1559_transition)?;
 			let number = BlockNumber::from(info.header.number());
 			let hash = info.header.hash();
 			// Check if any of the headers matches the hash we requested
@@ -314,7 +407,7 @@ impl BlockDownloader {
 	}
 
 	/// Called by peer once it has new block bodies
-	pub fn import_bodies(&mut self, r: &Rlp) -> Result<(), BlockDownloaderImportError> {
+	pub fn import_bodies(&mut self, r: &Rlp, eip1559_transition: BlockNumber) -> Result<(), BlockDownloaderImportError> {
 		let item_count = r.item_count().unwrap_or(0);
 		if item_count == 0 {
 			return Err(BlockDownloaderImportError::Useless);
@@ -323,7 +416,7 @@ impl BlockDownloader {
 		} else {
 			let mut bodies = Vec::with_capacity(item_count);
 			for i in 0..item_count {
-				let body = SyncBody::from_rlp(r.at(i)?.as_raw())?;
+				let body = SyncBody::from_rlp(r.at(i)?.as_raw(), eip1559_transition)?;
 				bodies.push(body);
 			}
 
@@ -408,13 +501,56 @@ impl BlockDownloader {
 		self.imported_this_round = None;
 	}
 
+	/// Add headers of the next batch of a backwards download. Headers must form a chain of parents
+	/// starting with the highest block not downloaded yet.
+	fn import_reverse_headers(&mut self, r: &Rlp, expected_hash: Option<H256>, eip1559_transition: BlockNumber) -> Result<DownloadAction, BlockDownloaderImportError> {
+		let item_count = r.item_count().unwrap_or(0);
+		let mut headers = Vec::with_capacity(item_count);
+		{
+			let reverse = self.reverse.as_mut().expect("import_reverse_headers is called only for backwards downloads; qed");
+			if self.state != State::Blocks || reverse.has_batch || expected_hash != Some(reverse.next_hash) {
+				trace!(target: "sync", "Ignored unexpected block headers");
+				return Ok(DownloadAction::None);
+			}
+			if item_count == 0 {
+				return Err(BlockDownloaderImportError::Useless);
+			}
+
+			let count = cmp::min(item_count as u64, reverse.next_number - reverse.target_number) as usize;
+			let mut expected = (reverse.next_hash, reverse.next_number);
+			for i in 0..count {
+				let info = SyncHeader::from_rlp(r.at(i)?.as_raw().to_vec(), eip1559_transition)?;
+				let hash = info.header.hash();
+				if hash != expected.0 || info.header.number() != expected.1 {
+					trace!(target: "sync", "Invalid header {} ({:?}), expected {} ({:?})", info.header.number(), hash, expected.1, expected.0);
+					return Err(BlockDownloaderImportError::Invalid);
+				}
+				expected = (*info.header.parent_hash(), expected.1 - 1);
+				headers.push(info);
+			}
+			reverse.downloading_headers = false;
+			reverse.has_batch = true;
+		}
+
+		// blocks are collected from the lowest one
+		headers.reverse();
+		trace!(target: "sync", "New batch of {} old blocks starting at #{}", headers.len(), headers[0].header.number());
+		let lowest = headers[0].header.hash();
+		self.blocks.reset_to(vec![lowest]);
+		self.blocks.insert_headers(headers);
+		Ok(DownloadAction::None)
+	}
+
 	/// Find some headers or blocks to download for a peer.
-	pub fn request_blocks(&mut self, io: &mut SyncIo, num_active_peers: usize) -> Option<BlockRequest> {
+	pub fn request_blocks(&mut self, io: &mut SyncIo, num_active_peers: usize, batch_size: BatchSize) -> Option<BlockRequest> {
+		if self.reverse.is_some() {
+			return self.request_reverse_blocks(batch_size);
+		}
 		match self.state {
 			State::Idle => {
 				self.start_sync_round(io);
 				if self.state == State::ChainHead {
-					return self.request_blocks(io, num_active_peers);
+					return self.request_blocks(io, num_active_peers, batch_size);
 				}
 			},
 			State::ChainHead => {
@@ -427,12 +563,13 @@ impl BlockDownloader {
 						start: self.last_imported_hash.clone(),
 						count: SUBCHAIN_SIZE,
 						skip: (MAX_HEADERS_TO_REQUEST - 2) as u64,
+						reverse: false,
 					});
 				}
 			},
 			State::Blocks => {
 				// check to see if we need to download any block bodies first
-				let needed_bodies = self.blocks.needed_bodies(MAX_BODIES_TO_REQUEST, false);
+				let needed_bodies = self.blocks.needed_bodies(batch_size.bodies, false);
 				if !needed_bodies.is_empty() {
 					return Some(BlockRequest::Bodies {
 						hashes: needed_bodies,
@@ -449,11 +586,12 @@ impl BlockDownloader {
 				}
 
 				// find subchain to download
-				if let Some((h, count)) = self.blocks.needed_headers(MAX_HEADERS_TO_REQUEST, false) {
+				if let Some((h, count)) = self.blocks.needed_headers(batch_size.headers, false) {
 					return Some(BlockRequest::Headers {
 						start: h,
 						count: count as u64,
 						skip: 0,
+						reverse: false,
 					});
 				}
 			},
@@ -462,8 +600,55 @@ impl BlockDownloader {
 		None
 	}
 
+	/// Find bodies and receipts of the current batch or headers of the next batch of a backwards download.
+	fn request_reverse_blocks(&mut self, batch_size: BatchSize) -> Option<BlockRequest> {
+		if self.state == State::Idle {
+			// restart the current batch
+			self.blocks.clear();
+			self.state = State::Blocks;
+			if let Some(ref mut reverse) = self.reverse {
+				reverse.downloading_headers = false;
+				reverse.has_batch = false;
+			}
+		}
+		if self.state != State::Blocks {
+			return None;
+		}
+
+		let reverse = self.reverse.as_mut().expect("request_reverse_blocks is called only for backwards downloads; qed");
+		if reverse.has_batch {
+			let needed_bodies = self.blocks.needed_bodies(batch_size.bodies, false);
+			if !needed_bodies.is_empty() {
+				return Some(BlockRequest::Bodies {
+					hashes: needed_bodies,
+				});
+			}
+
+			if self.download_receipts {
+				let needed_receipts = self.blocks.needed_receipts(MAX_RECEPITS_TO_REQUEST, false);
+				if !needed_receipts.is_empty() {
+					return Some(BlockRequest::Receipts {
+						hashes: needed_receipts,
+					});
+				}
+			}
+		} else if !reverse.downloading_headers {
+			reverse.downloading_headers = true;
+			return Some(BlockRequest::Headers {
+				start: reverse.next_hash,
+				count: cmp::min(batch_size.headers as u64, reverse.next_number - reverse.target_number),
+				skip: 0,
+				reverse: true,
+			});
+		}
+		None
+	}
+
 	/// Checks if there are blocks fully downloaded that can be imported into the blockchain and does the import.
 	pub fn collect_blocks(&mut self, io: &mut SyncIo, allow_out_of_order: bool) -> Result<(), BlockDownloaderImportError> {
+		if self.reverse.is_some() {
+			return self.collect_reverse_blocks(io);
+		}
 		let mut bad = false;
 		let mut imported = HashSet::new();
 		let blocks = self.blocks.drain();
@@ -482,10 +667,11 @@ impl BlockDownloader {
 				return Ok(());
 			}
 
-			let result = if let Some(receipts) = receipts {
-				io.chain().queue_ancient_block(block, receipts)
-			} else {
-				io.chain().import_block(block)
+			let result = match receipts {
+				Some(receipts) => io.chain().queue_ancient_block(block, receipts),
+				// receipts of ancient blocks are not downloaded
+				None if self.ancient => io.chain().queue_ancient_block(block, rlp::EMPTY_LIST_RLP.to_vec()),
+				None => io.chain().import_block(block),
 			};
 
 			match result {
@@ -539,6 +725,79 @@ impl BlockDownloader {
 		Ok(())
 	}
 
+	/// Queues the current batch of a backwards download once all of its blocks are downloaded.
+	/// Blocks are queued from the highest one, so that each of them is the parent of an already known block.
+	fn collect_reverse_blocks(&mut self, io: &mut SyncIo) -> Result<(), BlockDownloaderImportError> {
+		let has_batch = self.reverse.as_ref().map_or(false, |reverse| reverse.has_batch);
+		if !has_batch || !self.blocks.is_downloaded() {
+			return Ok(());
+		}
+
+		let mut blocks = self.blocks.drain();
+		blocks.reverse();
+		let count = blocks.len();
+		let mut imported = 0;
+		let mut bad = false;
+		for block_and_receipts in blocks {
+			let block = block_and_receipts.block;
+			let h = block.header.hash();
+			let number = block.header.number();
+			let parent = *block.header.parent_hash();
+			// receipts of ancient blocks are not downloaded
+			let receipts = block_and_receipts.receipts.unwrap_or_else(|| rlp::EMPTY_LIST_RLP.to_vec());
+
+			match io.chain().queue_ancient_block(block, receipts) {
+				Ok(_) => {
+					trace!(target: "sync", "Block queued {:?}", h);
+					imported += 1;
+				},
+				Err(EthcoreError(EthcoreErrorKind::Import(ImportErrorKind::AlreadyInChain), _)) => {
+					trace!(target: "sync", "Block already in chain {:?}", h);
+				},
+				Err(EthcoreError(EthcoreErrorKind::Import(ImportErrorKind::AlreadyQueued), _)) => {
+					trace!(target: "sync", "Block already queued {:?}", h);
+				},
+				Err(EthcoreError(EthcoreErrorKind::Queue(QueueErrorKind::Full(limit)), _)) => {
+					debug!(target: "sync", "Block import queue full ({}), restarting batch", limit);
+					break;
+				},
+				Err(e) => {
+					debug!(target: "sync", "Bad block {:?} : {:?}", h, e);
+					bad = true;
+					break;
+				},
+			}
+
+			self.last_imported_block = number;
+			self.last_imported_hash = h;
+			if let Some(ref mut reverse) = self.reverse {
+				reverse.next_hash = parent;
+				reverse.next_number = number - 1;
+			}
+		}
+		trace!(target: "sync", "Imported {} of {}", imported, count);
+
+		// the rest of the batch is downloaded again
+		self.blocks.clear();
+		let complete = match self.reverse {
+			Some(ref mut reverse) => {
+				reverse.has_batch = false;
+				reverse.downloading_headers = false;
+				reverse.next_number <= reverse.target_number
+			},
+			None => false,
+		};
+		if complete {
+			trace!(target: "sync", "Old blocks download complete");
+			self.state = State::Complete;
+		}
+
+		if bad {
+			return Err(BlockDownloaderImportError::Invalid);
+		}
+		Ok(())
+	}
+
 	fn block_imported(&mut self, hash: &H256, number: BlockNumber, parent: &H256) {
 		self.last_imported_block = number;
 		self.last_imported_hash = hash.clone();
@@ -549,4 +808,20 @@ impl BlockDownloader {
 	}
 }
 
-//TODO: module tests
+#[cfg(test)]
+mod tests {
+	use std::time::Duration;
+	use super::BatchSize;
+
+	#[test]
+	fn should_scale_batch_size_with_latency() {
+		let batch_size = BatchSize { headers: 128, bodies: 32 };
+
+		assert_eq!(batch_size.for_latency(None), batch_size);
+		assert_eq!(batch_size.for_latency(Some(Duration::from_millis(1000))), batch_size);
+		assert_eq!(batch_size.for_latency(Some(Duration::from_millis(500))), BatchSize { headers: 256, bodies: 64 });
+		assert_eq!(batch_size.for_latency(Some(Duration::from_millis(1))), BatchSize { headers: 512, bodies: 128 });
+		assert_eq!(batch_size.for_latency(Some(Duration::from_secs(60))), BatchSize { headers: 32, bodies: 8 });
+		assert_eq!(BatchSize { headers: 1, bodies: 1 }.for_latency(Some(Duration::from_secs(60))), BatchSize { headers: 1, bodies: 1 });
+	}
+}
@@ -22,7 +22,7 @@ use triehash_ethereum::ordered_trie_root;
 use bytes::Bytes;
 use rlp::{Rlp, RlpStream, DecoderError};
 use network;
-use ethcore::header::Header as BlockHeader;
+use ethcore::header::{BlockNumber, Header as BlockHeader};
 use ethcore::verification::queue::kind::blocks::Unverified;
 use transaction::UnverifiedTransaction;
 
@@ -42,9 +42,9 @@ impl HeapSizeOf for SyncHeader {
 }
 
 impl SyncHeader {
-	pub fn from_rlp(bytes: Bytes) -> Result<Self, DecoderError> {
+	pub fn from_rlp(bytes: Bytes, eip1559_transition: BlockNumber) -> Result<Self, DecoderError> {
 		let result = SyncHeader {
-			header: ::rlp::decode(&bytes)?,
+			header: BlockHeader::decode_rlp(&Rlp::new(&bytes), eip1559_transition)?,
 			bytes,
 		};
 
@@ -60,7 +60,7 @@ pub struct SyncBody {
 }
 
 impl SyncBody {
-	pub fn from_rlp(bytes: &[u8]) -> Result<Self, DecoderError> {
+	pub fn from_rlp(bytes: &[u8], eip1559_transition: BlockNumber) -> Result<Self, DecoderError> {
 		let rlp = Rlp::new(bytes);
 		let transactions_rlp = rlp.at(0)?;
 		let uncles_rlp = rlp.at(1)?;
@@ -69,7 +69,9 @@ impl SyncBody {
 			transactions_bytes: transactions_rlp.as_raw().to_vec(),
 			transactions: transactions_rlp.as_list()?,
 			uncles_bytes: uncles_rlp.as_raw().to_vec(),
-			uncles: uncles_rlp.as_list()?,
+			uncles: uncles_rlp.iter()
+				.map(|r| BlockHeader::decode_rlp(&r, eip1559_transition))
+				.collect::<Result<_, _>>()?,
 		};
 
 		Ok(result)
@@ -393,6 +395,11 @@ impl BlockCollection {
 		self.heads.len() == 0 || (self.heads.len() == 1 && self.head.map_or(false, |h| h == self.heads[0]))
 	}
 
+	/// Check if bodies and receipts of all the collected blocks are downloaded.
+	pub fn is_downloaded(&self) -> bool {
+		!self.blocks.is_empty() && self.blocks.values().all(|b| b.body.is_some() && (!self.need_receipts || b.receipts.is_some()))
+	}
+
 	/// Check if collection contains a block header.
 	pub fn contains(&self, hash: &H256) -> bool {
 		self.blocks.contains_key(hash)
@@ -420,7 +427,7 @@ impl BlockCollection {
 
 	fn insert_body(&mut self, body: SyncBody) -> Result<(), network::Error> {
 		let header_id = {
-			let tx_root = ordered_trie_root(Rlp::new(&body.transactions_bytes).iter().map(|r| r.as_raw()));
+			let tx_root = ordered_trie_root(Rlp::new(&body.transactions_bytes).iter().map(|r| r.data().unwrap_or_else(|_| r.as_raw())));
 			let uncles = keccak(&body.uncles_bytes);
 			HeaderId {
 				transactions_root: tx_root,
@@ -453,7 +460,7 @@ impl BlockCollection {
 	fn insert_receipt(&mut self, r: Bytes) -> Result<(), network::Error> {
 		let receipt_root = {
 			let receipts = Rlp::new(&r);
-			ordered_trie_root(receipts.iter().map(|r| r.as_raw()))
+			ordered_trie_root(receipts.iter().map(|r| r.data().unwrap_or_else(|_| r.as_raw())))
 		};
 		self.downloading_receipts.remove(&receipt_root);
 		match self.receipt_ids.entry(receipt_root) {
@@ -572,7 +579,7 @@ impl BlockCollection {
 
 #[cfg(test)]
 mod test {
-	use super::{BlockCollection, SyncHeader};
+	use super::{BlockCollection, SyncBody, SyncHeader};
 	use ethcore::client::{TestBlockChainClient, EachBlockWith, BlockId, BlockChainClient};
 	use ethcore::header::BlockNumber;
 	use ethcore::verification::queue::kind::blocks::Unverified;
@@ -611,7 +618,7 @@ mod test {
 		let blocks: Vec<_> = (0..nblocks)
 			.map(|i| (&client as &BlockChainClient).block(BlockId::Number(i as BlockNumber)).unwrap().into_inner())
 			.collect();
-		let headers: Vec<_> = blocks.iter().map(|b| SyncHeader::from_rlp(Rlp::new(b).at(0).unwrap().as_raw().to_vec()).unwrap()).collect();
+		let headers: Vec<_> = blocks.iter().map(|b| SyncHeader::from_rlp(Rlp::new(b).at(0).unwrap().as_raw().to_vec(), BlockNumber::max_value()).unwrap()).collect();
 		let hashes: Vec<_> = headers.iter().map(|h| h.header.hash()).collect();
 		let heads: Vec<_> = hashes.iter().enumerate().filter_map(|(i, h)| if i % 20 == 0 { Some(*h) } else { None }).collect();
 		bc.reset_to(heads);
@@ -639,7 +646,7 @@ mod test {
 
 		assert_eq!(
 			bc.drain().into_iter().map(|b| b.block).collect::<Vec<_>>(),
-			blocks[0..6].iter().map(|b| Unverified::from_rlp(b.to_vec()).unwrap()).collect::<Vec<_>>()
+			blocks[0..6].iter().map(|b| Unverified::from_rlp(b.to_vec(), BlockNumber::max_value()).unwrap()).collect::<Vec<_>>()
 		);
 		assert!(!bc.contains(&hashes[0]));
 		assert_eq!(hashes[5], bc.head.unwrap());
@@ -653,7 +660,7 @@ mod test {
 		bc.insert_headers(headers[5..10].into_iter().map(Clone::clone).collect());
 		assert_eq!(
 			bc.drain().into_iter().map(|b| b.block).collect::<Vec<_>>(),
-			blocks[6..16].iter().map(|b| Unverified::from_rlp(b.to_vec()).unwrap()).collect::<Vec<_>>()
+			blocks[6..16].iter().map(|b| Unverified::from_rlp(b.to_vec(), BlockNumber::max_value()).unwrap()).collect::<Vec<_>>()
 		);
 
 		assert_eq!(hashes[15], bc.heads[0]);
@@ -673,7 +680,7 @@ mod test {
 		let blocks: Vec<_> = (0..nblocks)
 			.map(|i| (&client as &BlockChainClient).block(BlockId::Number(i as BlockNumber)).unwrap().into_inner())
 			.collect();
-		let headers: Vec<_> = blocks.iter().map(|b| SyncHeader::from_rlp(Rlp::new(b).at(0).unwrap().as_raw().to_vec()).unwrap()).collect();
+		let headers: Vec<_> = blocks.iter().map(|b| SyncHeader::from_rlp(Rlp::new(b).at(0).unwrap().as_raw().to_vec(), BlockNumber::max_value()).unwrap()).collect();
 		let hashes: Vec<_> = headers.iter().map(|h| h.header.hash()).collect();
 		let heads: Vec<_> = hashes.iter().enumerate().filter_map(|(i, h)| if i % 20 == 0 { Some(*h) } else { None }).collect();
 		bc.reset_to(heads);
@@ -697,7 +704,7 @@ mod test {
 		let blocks: Vec<_> = (0..nblocks)
 			.map(|i| (&client as &BlockChainClient).block(BlockId::Number(i as BlockNumber)).unwrap().into_inner())
 			.collect();
-		let headers: Vec<_> = blocks.iter().map(|b| SyncHeader::from_rlp(Rlp::new(b).at(0).unwrap().as_raw().to_vec()).unwrap()).collect();
+		let headers: Vec<_> = blocks.iter().map(|b| SyncHeader::from_rlp(Rlp::new(b).at(0).unwrap().as_raw().to_vec(), BlockNumber::max_value()).unwrap()).collect();
 		let hashes: Vec<_> = headers.iter().map(|h| h.header.hash()).collect();
 		let heads: Vec<_> = hashes.iter().enumerate().filter_map(|(i, h)| if i % 20 == 0 { Some(*h) } else { None }).collect();
 		bc.reset_to(heads);
@@ -707,4 +714,24 @@ mod test {
 		bc.insert_headers(headers[0..1].into_iter().map(Clone::clone).collect());
 		assert_eq!(bc.drain().len(), 2);
 	}
+
+	#[test]
+	fn is_downloaded_once_all_bodies_are_inserted() {
+		let mut bc = BlockCollection::new(false);
+		let client = TestBlockChainClient::new();
+		client.add_blocks(2, EachBlockWith::Transaction);
+		let block = (&client as &BlockChainClient).block(BlockId::Number(1)).unwrap().into_inner();
+		let header = SyncHeader::from_rlp(Rlp::new(&block).at(0).unwrap().as_raw().to_vec(), BlockNumber::max_value()).unwrap();
+		let mut body = RlpStream::new_list(2);
+		body.append_raw(Rlp::new(&block).at(1).unwrap().as_raw(), 1);
+		body.append_raw(Rlp::new(&block).at(2).unwrap().as_raw(), 1);
+		assert!(!bc.is_downloaded());
+
+		bc.reset_to(vec![header.header.hash()]);
+		bc.insert_headers(vec![header]);
+		assert!(!bc.is_downloaded());
+
+		bc.insert_bodies(vec![SyncBody::from_rlp(&body.out(), BlockNumber::max_value()).unwrap()]);
+		assert!(bc.is_downloaded());
+	}
 }
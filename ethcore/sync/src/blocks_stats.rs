@@ -0,0 +1,163 @@
+// Copyright 2015-2018 Parity Technologies (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+use api::BlockPropagationStats;
+use std::collections::{BTreeMap, HashMap, VecDeque};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+use ethereum_types::{H256, H512};
+use fastmap::H256FastMap;
+
+type NodeId = H512;
+type BlockNumber = u64;
+
+/// Number of most recently announced blocks to keep statistics for.
+const MAX_TRACKED_BLOCKS: usize = 256;
+
+#[derive(Debug, PartialEq, Clone)]
+pub struct Stats {
+	number: BlockNumber,
+	first_seen: SystemTime,
+	first_seen_at: Instant,
+	announced_by: HashMap<NodeId, Duration>,
+	imported_after: Option<Duration>,
+}
+
+impl Stats {
+	fn new(number: BlockNumber) -> Self {
+		Stats {
+			number,
+			first_seen: SystemTime::now(),
+			first_seen_at: Instant::now(),
+			announced_by: Default::default(),
+			imported_after: None,
+		}
+	}
+}
+
+fn as_millis(duration: Duration) -> u64 {
+	duration.as_secs() * 1000 + (duration.subsec_nanos() / 1_000_000) as u64
+}
+
+impl<'a> From<&'a Stats> for BlockPropagationStats {
+	fn from(other: &'a Stats) -> Self {
+		BlockPropagationStats {
+			number: other.number,
+			first_seen: other.first_seen.duration_since(UNIX_EPOCH).map(as_millis).unwrap_or(0),
+			import_delay: other.imported_after.map(as_millis),
+			announced_by: other.announced_by
+				.iter()
+				.map(|(id, delay)| (*id, as_millis(*delay)))
+				.collect::<BTreeMap<_, _>>(),
+		}
+	}
+}
+
+/// Keeps track of when recent blocks were announced by peers and imported.
+#[derive(Debug, Default)]
+pub struct BlocksStats {
+	blocks: H256FastMap<Stats>,
+	order: VecDeque<H256>,
+}
+
+impl BlocksStats {
+	/// Records an announcement of a block by the given peer.
+	pub fn announced(&mut self, hash: &H256, number: BlockNumber, enode_id: Option<NodeId>) {
+		if !self.blocks.contains_key(hash) {
+			if self.order.len() >= MAX_TRACKED_BLOCKS {
+				if let Some(oldest) = self.order.pop_front() {
+					self.blocks.remove(&oldest);
+				}
+			}
+			self.order.push_back(*hash);
+			self.blocks.insert(*hash, Stats::new(number));
+		}
+
+		let stats = self.blocks.get_mut(hash).expect("inserted above if missing; qed");
+		let delay = stats.first_seen_at.elapsed();
+		stats.announced_by.entry(enode_id.unwrap_or_default()).or_insert(delay);
+	}
+
+	/// Records import of a block, returns the time elapsed since its first announcement.
+	/// Blocks which were never announced are not tracked.
+	pub fn imported(&mut self, hash: &H256) -> Option<Duration> {
+		let stats = self.blocks.get_mut(hash)?;
+		if stats.imported_after.is_none() {
+			stats.imported_after = Some(stats.first_seen_at.elapsed());
+		}
+		stats.imported_after
+	}
+
+	/// Returns propagation stats for given hash or `None` if hash is not known.
+	#[cfg(test)]
+	pub fn get(&self, hash: &H256) -> Option<&Stats> {
+		self.blocks.get(hash)
+	}
+
+	pub fn stats(&self) -> &H256FastMap<Stats> {
+		&self.blocks
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::{BlocksStats, MAX_TRACKED_BLOCKS};
+
+	#[test]
+	fn should_keep_track_of_announcements_and_import() {
+		// given
+		let mut stats = BlocksStats::default();
+		let hash = 5.into();
+		let enodeid1 = 2.into();
+		let enodeid2 = 5.into();
+
+		// when
+		stats.announced(&hash, 10, Some(enodeid1));
+		stats.announced(&hash, 10, Some(enodeid2));
+		stats.announced(&hash, 10, Some(enodeid1));
+		let imported = stats.imported(&hash);
+
+		// then
+		let stats = stats.get(&hash).unwrap();
+		assert_eq!(stats.number, 10);
+		assert_eq!(stats.announced_by.len(), 2);
+		assert!(stats.announced_by[&enodeid1] <= stats.announced_by[&enodeid2]);
+		assert_eq!(stats.imported_after, imported);
+		assert!(imported.unwrap() >= stats.announced_by[&enodeid2]);
+	}
+
+	#[test]
+	fn should_ignore_blocks_which_were_not_announced() {
+		let mut stats = BlocksStats::default();
+		assert_eq!(stats.imported(&5.into()), None);
+		assert!(stats.stats().is_empty());
+	}
+
+	#[test]
+	fn should_forget_oldest_blocks() {
+		// given
+		let mut stats = BlocksStats::default();
+
+		// when
+		for i in 0..MAX_TRACKED_BLOCKS as u64 + 1 {
+			stats.announced(&i.into(), i, None);
+		}
+
+		// then
+		assert_eq!(stats.stats().len(), MAX_TRACKED_BLOCKS);
+		assert!(stats.get(&0.into()).is_none());
+		assert!(stats.get(&1.into()).is_some());
+	}
+}
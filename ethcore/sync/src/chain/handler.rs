@@ -24,11 +24,12 @@ use ethcore::snapshot::{ManifestData, RestorationStatus};
 use ethcore::verification::queue::kind::blocks::Unverified;
 use ethereum_types::{H256, U256};
 use hash::keccak;
-use network::PeerId;
+use network::{PeerId, HandshakeFailureReason, ReputationChange};
 use rlp::Rlp;
 use snapshot::ChunkType;
 use std::cmp;
 use std::collections::HashSet;
+use std::net::{IpAddr, SocketAddr};
 use std::time::Instant;
 use sync_io::SyncIo;
 
@@ -92,6 +93,7 @@ impl SyncHandler {
 		match result {
 			Err(DownloaderImportError::Invalid) => {
 				debug!(target:"sync", "{} -> Invalid packet {}", peer, packet_id);
+				io.note_reputation(peer, ReputationChange::InvalidResponse);
 				io.disable_peer(peer);
 				sync.deactivate_peer(io, peer);
 			},
@@ -118,11 +120,13 @@ impl SyncHandler {
 	pub fn on_peer_aborting(sync: &mut ChainSync, io: &mut SyncIo, peer_id: PeerId) {
 		trace!(target: "sync", "== Disconnecting {}: {}", peer_id, io.peer_info(peer_id));
 		sync.handshaking_peers.remove(&peer_id);
+		sync.snapshot_serving.get_mut().remove_peer(peer_id);
 		if sync.peers.contains_key(&peer_id) {
 			debug!(target: "sync", "Disconnected {}", peer_id);
 			sync.clear_peer_download(peer_id);
 			sync.peers.remove(&peer_id);
 			sync.active_peers.remove(&peer_id);
+			sync.announcements.peer_disconnected(peer_id);
 
 			if sync.state == SyncState::SnapshotManifest {
 				// Check if we are asking other peers for
@@ -163,10 +167,11 @@ impl SyncHandler {
 				peer.difficulty = Some(difficulty);
 			}
 		}
-		let block = Unverified::from_rlp(r.at(0)?.as_raw().to_vec())?;
+		let block = Unverified::from_rlp(r.at(0)?.as_raw().to_vec(), sync.eip1559_transition)?;
 		let hash = block.header.hash();
 		let number = block.header.number();
 		trace!(target: "sync", "{} -> NewBlock ({})", peer_id, hash);
+		sync.block_announced(io, peer_id, &hash, number);
 		if number > sync.highest_block.unwrap_or(0) {
 			sync.highest_block = Some(number);
 		}
@@ -222,6 +227,11 @@ impl SyncHandler {
 			return Ok(());
 		}
 		let hashes: Vec<_> = r.iter().take(MAX_NEW_HASHES).map(|item| (item.val_at::<H256>(0), item.val_at::<BlockNumber>(1))).collect();
+		for &(ref hash, ref number) in &hashes {
+			if let (&Ok(ref hash), &Ok(number)) = (hash, number) {
+				sync.block_announced(io, peer_id, hash, number);
+			}
+		}
 		if let Some(ref mut peer) = sync.peers.get_mut(&peer_id) {
 			// Peer has new blocks with unknown difficulty
 			peer.difficulty = None;
@@ -315,7 +325,7 @@ impl SyncHandler {
 						Some(ref mut blocks) => blocks,
 					}
 				};
-				downloader.import_bodies(r)?;
+				downloader.import_bodies(r, sync.eip1559_transition)?;
 			}
 			sync.collect_blocks(io, block_set);
 			Ok(())
@@ -396,7 +406,7 @@ impl SyncHandler {
 					}
 				}
 			};
-			downloader.import_headers(io, r, expected_hash)?
+			downloader.import_headers(io, r, expected_hash, sync.eip1559_transition)?
 		};
 
 		if let DownloadAction::Reset = result {
@@ -467,6 +477,7 @@ impl SyncHandler {
 			trace!(target: "sync", "{}: Snapshot manifest version not supported: {}", peer_id, manifest.version);
 			return Err(DownloaderImportError::Invalid);
 		}
+		io.note_reputation(peer_id, ReputationChange::UsefulData);
 		sync.snapshot.reset_to(&manifest, &keccak(manifest_rlp.as_raw()));
 		io.snapshot_service().begin_restore(manifest);
 		sync.state = SyncState::SnapshotData;
@@ -480,8 +491,10 @@ impl SyncHandler {
 			trace!(target: "sync", "Ignoring snapshot data from unconfirmed peer {}", peer_id);
 			return Ok(());
 		}
-		sync.clear_peer_download(peer_id);
-		if !sync.reset_peer_asking(peer_id, PeerAsking::SnapshotData) || (sync.state != SyncState::SnapshotData && sync.state != SyncState::SnapshotWaiting) {
+		let expected = sync.peers.get(&peer_id).map_or(false, |p| p.asking == PeerAsking::SnapshotData);
+		if !expected || (sync.state != SyncState::SnapshotData && sync.state != SyncState::SnapshotWaiting) {
+			sync.clear_peer_download(peer_id);
+			sync.reset_peer_asking(peer_id, PeerAsking::SnapshotData);
 			trace!(target: "sync", "{}: Ignored unexpected snapshot data", peer_id);
 			return Ok(());
 		}
@@ -490,6 +503,8 @@ impl SyncHandler {
 		let status = io.snapshot_service().status();
 		match status {
 			RestorationStatus::Inactive | RestorationStatus::Failed => {
+				sync.clear_peer_download(peer_id);
+				sync.reset_peer_asking(peer_id, PeerAsking::SnapshotData);
 				trace!(target: "sync", "{}: Snapshot restoration aborted", peer_id);
 				sync.state = SyncState::WaitingPeers;
 
@@ -503,6 +518,8 @@ impl SyncHandler {
 				return Ok(());
 			},
 			RestorationStatus::Initializing  { .. } => {
+				sync.clear_peer_download(peer_id);
+				sync.reset_peer_asking(peer_id, PeerAsking::SnapshotData);
 				trace!(target: "warp", "{}: Snapshot restoration is initializing", peer_id);
 				return Ok(());
 			}
@@ -523,10 +540,19 @@ impl SyncHandler {
 			}
 			Err(()) => {
 				trace!(target: "sync", "{}: Got bad snapshot chunk", peer_id);
+				io.note_reputation(peer_id, ReputationChange::InvalidResponse);
 				io.disconnect_peer(peer_id);
 				return Ok(());
 			}
 		}
+		io.note_reputation(peer_id, ReputationChange::UsefulData);
+
+		// the peer stays busy until all chunks requested from it are delivered
+		if sync.snapshot.chunk_requests(peer_id) == 0 {
+			sync.reset_peer_asking(peer_id, PeerAsking::SnapshotData);
+		} else if let Some(peer) = sync.peers.get_mut(&peer_id) {
+			peer.ask_time = Instant::now();
+		}
 
 		if sync.snapshot.is_complete() {
 			// wait for snapshot restoration process to complete
@@ -555,10 +581,10 @@ impl SyncHandler {
 			last_sent_private_transactions: HashSet::new(),
 			expired: false,
 			confirmation: if sync.fork_block.is_none() { ForkConfirmation::Confirmed } else { ForkConfirmation::Unconfirmed },
-			asking_snapshot_data: None,
 			snapshot_hash: if warp_protocol { Some(r.val_at(5)?) } else { None },
 			snapshot_number: if warp_protocol { Some(r.val_at(6)?) } else { None },
 			block_set: None,
+			latency: None,
 		};
 
 		trace!(target: "sync", "New peer {} (protocol: {}, network: {:?}, difficulty: {:?}, latest:{}, genesis:{}, snapshot:{:?})",
@@ -575,10 +601,12 @@ impl SyncHandler {
 		let chain_info = io.chain().chain_info();
 		if peer.genesis != chain_info.genesis_hash {
 			trace!(target: "sync", "Peer {} genesis hash mismatch (ours: {}, theirs: {})", peer_id, chain_info.genesis_hash, peer.genesis);
+			io.note_handshake_failure(peer_id, HandshakeFailureReason::GenesisMismatch);
 			return Err(DownloaderImportError::Invalid);
 		}
 		if peer.network_id != sync.network_id {
 			trace!(target: "sync", "Peer {} network id mismatch (ours: {}, theirs: {})", peer_id, sync.network_id, peer.network_id);
+			io.note_handshake_failure(peer_id, HandshakeFailureReason::NetworkIdMismatch);
 			return Err(DownloaderImportError::Invalid);
 		}
 
@@ -607,6 +635,13 @@ impl SyncHandler {
 		Ok(())
 	}
 
+	/// IP address of the peer, used to enforce per-origin transaction queue quotas.
+	fn peer_origin(io: &SyncIo, peer_id: PeerId) -> Option<IpAddr> {
+		io.peer_session_info(peer_id)
+			.and_then(|info| info.remote_address.parse::<SocketAddr>().ok())
+			.map(|address| address.ip())
+	}
+
 	/// Called when peer sends us new transactions
 	fn on_peer_transactions(sync: &mut ChainSync, io: &mut SyncIo, peer_id: PeerId, r: &Rlp) -> Result<(), DownloaderImportError> {
 		// Accept transactions only when fully synced
@@ -627,7 +662,7 @@ impl SyncHandler {
 			let tx = rlp.as_raw().to_vec();
 			transactions.push(tx);
 		}
-		io.chain().queue_transactions(transactions, peer_id);
+		io.chain().queue_transactions(transactions, peer_id, SyncHandler::peer_origin(io, peer_id));
 		Ok(())
 	}
 
@@ -681,7 +716,7 @@ impl SyncHandler {
 mod tests {
 	use ethcore::client::{ChainInfo, EachBlockWith, TestBlockChainClient};
 	use parking_lot::RwLock;
-	use rlp::{Rlp};
+	use rlp::{Rlp, RlpStream};
 	use std::collections::{VecDeque};
 	use tests::helpers::{TestIo};
 	use tests::snapshot::TestSnapshotService;
@@ -783,4 +818,29 @@ mod tests {
 
 		assert!(result.is_ok());
 	}
+
+	#[test]
+	fn notes_genesis_mismatch_on_status() {
+		let mut client = TestBlockChainClient::new();
+		client.add_blocks(10, EachBlockWith::Uncle);
+		let queue = RwLock::new(VecDeque::new());
+		let mut sync = dummy_sync_with_peer(client.block_hash_delta_minus(5), &client);
+		let ss = TestSnapshotService::new();
+		let mut io = TestIo::new(&mut client, &ss, &queue, None);
+
+		let mut status = RlpStream::new_list(7);
+		status.append(&63u8);
+		status.append(&sync.network_id);
+		status.append(&U256::from(1));
+		status.append(&H256::default());
+		status.append(&H256::from(1));
+		status.append(&H256::default());
+		status.append(&0u64);
+		let status = status.out();
+
+		let result = SyncHandler::on_peer_status(&mut sync, &mut io, 1, &Rlp::new(&status));
+
+		assert!(result.is_err());
+		assert_eq!(io.handshake_failures, vec![(1, HandshakeFailureReason::GenesisMismatch)]);
+	}
 }
@@ -100,21 +100,24 @@ use hash::keccak;
 use heapsize::HeapSizeOf;
 use ethereum_types::{H256, U256};
 use fastmap::H256FastMap;
-use parking_lot::RwLock;
+use parking_lot::{Mutex, RwLock};
 use bytes::Bytes;
 use rlp::{Rlp, RlpStream, DecoderError};
-use network::{self, PeerId, PacketId};
+use network::{self, PeerId, PacketId, ReputationChange};
 use ethcore::header::{BlockNumber};
 use ethcore::client::{BlockChainClient, BlockStatus, BlockId, BlockChainInfo, BlockQueueInfo};
 use ethcore::snapshot::{RestorationStatus};
 use sync_io::SyncIo;
-use super::{WarpSync, SyncConfig};
-use block_sync::{BlockDownloader, BlockDownloaderImportError as DownloaderImportError};
+use super::{WarpSync, SyncConfig, BlockPropagation};
+use block_sync::{BatchSize, BlockDownloader, BlockDownloaderImportError as DownloaderImportError};
 use rand::Rng;
 use snapshot::{Snapshot};
-use api::{EthProtocolInfo as PeerInfoDigest, WARP_SYNC_PROTOCOL_ID};
+use snapshot_serving::{SnapshotServing, SnapshotServingLimits};
+use api::{EthProtocolInfo as PeerInfoDigest, BlockAnnouncementStats, WARP_SYNC_PROTOCOL_ID};
 use private_tx::PrivateTxHandler;
 use transactions_stats::{TransactionsStats, Stats as TransactionStats};
+use blocks_stats::{BlocksStats, Stats as BlockStats};
+use announcements::Announcements;
 use transaction::UnverifiedTransaction;
 
 use self::handler::SyncHandler;
@@ -177,6 +180,10 @@ pub const PRIVATE_TRANSACTION_PACKET: u8 = 0x16;
 pub const SIGNED_PRIVATE_TRANSACTION_PACKET: u8 = 0x17;
 
 const MAX_SNAPSHOT_CHUNKS_DOWNLOAD_AHEAD: usize = 3;
+/// Maximal number of snapshot chunks requested from a single peer at the same time.
+const MAX_SNAPSHOT_CHUNK_REQUESTS_PER_PEER: usize = 4;
+/// Number of peers with the highest reputation a snapshot manifest is requested from.
+const SNAPSHOT_MANIFEST_PEERS: usize = 3;
 
 const WAIT_PEERS_TIMEOUT: Duration = Duration::from_secs(5);
 const STATUS_TIMEOUT: Duration = Duration::from_secs(5);
@@ -186,6 +193,8 @@ const RECEIPTS_TIMEOUT: Duration = Duration::from_secs(10);
 const FORK_HEADER_TIMEOUT: Duration = Duration::from_secs(3);
 const SNAPSHOT_MANIFEST_TIMEOUT: Duration = Duration::from_secs(5);
 const SNAPSHOT_DATA_TIMEOUT: Duration = Duration::from_secs(120);
+/// Time after which a requested snapshot chunk is requested from another peer.
+const SNAPSHOT_CHUNK_TIMEOUT: Duration = Duration::from_secs(60);
 
 #[derive(Copy, Clone, Eq, PartialEq, Debug)]
 /// Sync state
@@ -318,8 +327,6 @@ pub struct PeerInfo {
 	asking_blocks: Vec<H256>,
 	/// Holds requested header hash if currently requesting block header by hash
 	asking_hash: Option<H256>,
-	/// Holds requested snapshot chunk hash if any.
-	asking_snapshot_data: Option<H256>,
 	/// Request timestamp
 	ask_time: Instant,
 	/// Holds a set of transactions recently sent to this peer to avoid spamming.
@@ -336,6 +343,8 @@ pub struct PeerInfo {
 	snapshot_number: Option<BlockNumber>,
 	/// Block set requested
 	block_set: Option<BlockSet>,
+	/// Average response time of block data requests
+	latency: Option<Duration>,
 }
 
 impl PeerInfo {
@@ -359,6 +368,13 @@ impl PeerInfo {
 	fn reset_private_stats(&mut self) {
 		self.last_sent_private_transactions.clear();
 	}
+
+	fn note_response_time(&mut self, elapsed: Duration) {
+		self.latency = Some(match self.latency {
+			Some(latency) => (latency * 3 + elapsed) / 4,
+			None => elapsed,
+		});
+	}
 }
 
 #[cfg(not(test))]
@@ -398,6 +414,8 @@ pub struct ChainSync {
 	network_id: u64,
 	/// Optional fork block to check
 	fork_block: Option<(BlockNumber, H256)>,
+	/// Block from which headers carry the EIP-1559 base fee.
+	eip1559_transition: BlockNumber,
 	/// Snapshot downloader.
 	snapshot: Snapshot,
 	/// Connected peers pending Status message.
@@ -407,8 +425,20 @@ pub struct ChainSync {
 	sync_start_time: Option<Instant>,
 	/// Transactions propagation statistics
 	transactions_stats: TransactionsStats,
+	/// Blocks propagation statistics
+	blocks_stats: BlocksStats,
+	/// Recent block announcements, used to avoid sending duplicates
+	announcements: Announcements,
+	/// Block propagation strategy
+	block_propagation: BlockPropagation,
 	/// Enable ancient block downloading
 	download_old_blocks: bool,
+	/// Download receipts of ancient blocks
+	download_old_receipts: bool,
+	/// Block data request sizes for peers with average latency
+	batch_size: BatchSize,
+	/// Bandwidth caps of snapshot chunks sent to peers
+	snapshot_serving: Mutex<SnapshotServing>,
 	/// Shared private tx service.
 	private_tx_handler: Arc<PrivateTxHandler>,
 	/// Enable warp sync.
@@ -434,10 +464,23 @@ impl ChainSync {
 			last_sent_block_number: 0,
 			network_id: config.network_id,
 			fork_block: config.fork_block,
+			eip1559_transition: config.eip1559_transition,
 			download_old_blocks: config.download_old_blocks,
+			download_old_receipts: config.download_old_receipts,
+			batch_size: BatchSize {
+				headers: config.headers_batch,
+				bodies: config.bodies_batch,
+			},
+			snapshot_serving: Mutex::new(SnapshotServing::new(SnapshotServingLimits {
+				per_peer: config.snapshot_serving_peer_rate,
+				total: config.snapshot_serving_rate,
+			})),
 			snapshot: Snapshot::new(),
 			sync_start_time: None,
 			transactions_stats: TransactionsStats::default(),
+			blocks_stats: BlocksStats::default(),
+			announcements: Announcements::new(config.announcement_window),
+			block_propagation: config.block_propagation,
 			private_tx_handler,
 			warp_sync: config.warp_sync,
 		};
@@ -494,6 +537,25 @@ impl ChainSync {
 		self.transactions_stats.stats()
 	}
 
+	/// Returns blocks propagation statistics
+	pub fn blocks_stats(&self) -> &H256FastMap<BlockStats> {
+		self.blocks_stats.stats()
+	}
+
+	/// Returns block announcement counters
+	pub fn announcement_stats(&self) -> &BlockAnnouncementStats {
+		self.announcements.stats()
+	}
+
+	/// Records an announcement of a block by a peer
+	fn block_announced(&mut self, io: &SyncIo, peer_id: PeerId, hash: &H256, number: BlockNumber) {
+		let id = io.peer_session_info(peer_id).and_then(|info| info.id);
+		self.blocks_stats.announced(hash, number, id);
+		if self.announcements.received(peer_id, hash) {
+			trace!(target: "sync", "{} -> Duplicate announcement of {}", peer_id, hash);
+		}
+	}
+
 	/// Updates transactions were received by a peer
 	pub fn transactions_received(&mut self, txs: &[UnverifiedTransaction], peer_id: PeerId) {
 		if let Some(peer_info) = self.peers.get_mut(&peer_id) {
@@ -595,6 +657,18 @@ impl ChainSync {
 			(best_hash, max_peers, snapshot_peers)
 		};
 
+		// Resume the restoration interrupted by a restart, chunks received before are not downloaded again
+		if let Some(manifest) = io.snapshot_service().interrupted_restoration() {
+			let hash = keccak(manifest.clone().into_rlp());
+			if let Some(peers) = snapshot_peers.get(&hash) {
+				trace!(target: "sync", "Resuming interrupted snapshot sync {:?} with {:?}", hash, peers);
+				self.snapshot.reset_to(&manifest, &hash);
+				io.snapshot_service().begin_restore(manifest);
+				self.state = SyncState::SnapshotData;
+				return;
+			}
+		}
+
 		let timeout = (self.state == SyncState::WaitingPeers) && self.sync_start_time.map_or(false, |t| t.elapsed() > WAIT_PEERS_TIMEOUT);
 
 		if let (Some(hash), Some(peers)) = (best_hash, best_hash.map_or(None, |h| snapshot_peers.get(&h))) {
@@ -614,10 +688,14 @@ impl ChainSync {
 
 	fn start_snapshot_sync(&mut self, io: &mut SyncIo, peers: &[PeerId]) {
 		if !self.snapshot.have_manifest() {
-			for p in peers {
-				if self.peers.get(p).map_or(false, |p| p.asking == PeerAsking::Nothing) {
-					SyncRequester::request_snapshot_manifest(self, io, *p);
-				}
+			// ask the most reputable peers only, a manifest from a bad peer stalls the whole sync
+			let mut idle: Vec<PeerId> = peers.iter()
+				.filter(|p| self.peers.get(*p).map_or(false, |peer| peer.asking == PeerAsking::Nothing))
+				.cloned()
+				.collect();
+			idle.sort_by_key(|p| cmp::Reverse(io.peer_reputation(*p)));
+			for p in idle.into_iter().take(SNAPSHOT_MANIFEST_PEERS) {
+				SyncRequester::request_snapshot_manifest(self, io, p);
 			}
 			self.state = SyncState::SnapshotManifest;
 			trace!(target: "sync", "New snapshot sync with {:?}", peers);
@@ -634,16 +712,23 @@ impl ChainSync {
 	}
 
 	/// Update sync after the blockchain has been changed externally.
-	pub fn update_targets(&mut self, chain: &BlockChainClient) {
+	pub fn update_targets(&mut self, client: &BlockChainClient) {
 		// Do not assume that the block queue/chain still has our last_imported_block
-		let chain = chain.chain_info();
+		let chain = client.chain_info();
 		self.new_blocks = BlockDownloader::new(false, &chain.best_block_hash, chain.best_block_number);
 		self.old_blocks = None;
 		if self.download_old_blocks {
 			if let (Some(ancient_block_hash), Some(ancient_block_number)) = (chain.ancient_block_hash, chain.ancient_block_number) {
+				// download backwards from the first block after the gap, so that recent blocks are available first
+				let first_block = chain.first_block_hash.and_then(|hash| client.block_header(BlockId::Hash(hash)));
+				if let Some(first_block) = first_block {
+					trace!(target: "sync", "Downloading old blocks backwards from {:?} (#{}) till {:?} (#{})", first_block.parent_hash(), first_block.number() - 1, ancient_block_hash, ancient_block_number);
+					self.old_blocks = Some(BlockDownloader::reverse(self.download_old_receipts, &first_block.parent_hash(), first_block.number() - 1, ancient_block_number));
+					return;
+				}
 
 				trace!(target: "sync", "Downloading old blocks from {:?} (#{}) till {:?} (#{:?})", ancient_block_hash, ancient_block_number, chain.first_block_hash, chain.first_block_number);
-				let mut downloader = BlockDownloader::with_unlimited_reorg(true, &ancient_block_hash, ancient_block_number);
+				let mut downloader = BlockDownloader::with_unlimited_reorg(self.download_old_receipts, &ancient_block_hash, ancient_block_number);
 				if let Some(hash) = chain.first_block_hash {
 					trace!(target: "sync", "Downloader target set to {:?}", hash);
 					downloader.set_target(&hash);
@@ -716,7 +801,10 @@ impl ChainSync {
 		}
 		let (peer_latest, peer_difficulty, peer_snapshot_number, peer_snapshot_hash) = {
 			if let Some(peer) = self.peers.get_mut(&peer_id) {
-				if peer.asking != PeerAsking::Nothing || !peer.can_sync() {
+				// peers downloading snapshot chunks can take more chunk requests
+				let busy = peer.asking != PeerAsking::Nothing &&
+					!(peer.asking == PeerAsking::SnapshotData && self.state == SyncState::SnapshotData);
+				if busy || !peer.can_sync() {
 					trace!(target: "sync", "Skipping busy peer {}", peer_id);
 					return;
 				}
@@ -728,6 +816,7 @@ impl ChainSync {
 		let chain_info = io.chain().chain_info();
 		let syncing_difficulty = chain_info.pending_total_difficulty;
 		let num_active_peers = self.peers.values().filter(|p| p.asking != PeerAsking::Nothing).count();
+		let batch_size = self.batch_size.for_latency(self.peers.get(&peer_id).and_then(|p| p.latency));
 
 		let higher_difficulty = peer_difficulty.map_or(true, |pd| pd > syncing_difficulty);
 		if force || higher_difficulty || self.old_blocks.is_some() {
@@ -753,7 +842,7 @@ impl ChainSync {
 					if !have_latest && (higher_difficulty || force || self.state == SyncState::NewBlocks) {
 						// check if got new blocks to download
 						trace!(target: "sync", "Syncing with peer {}, force={}, td={:?}, our td={}, state={:?}", peer_id, force, peer_difficulty, syncing_difficulty, self.state);
-						if let Some(request) = self.new_blocks.request_blocks(io, num_active_peers) {
+						if let Some(request) = self.new_blocks.request_blocks(io, num_active_peers, batch_size) {
 							SyncRequester::request_blocks(self, io, peer_id, request, BlockSet::NewBlocks);
 							if self.state == SyncState::Idle {
 								self.state = SyncState::Blocks;
@@ -768,7 +857,7 @@ impl ChainSync {
 					});
 
 					if force || last_imported_old_block_difficulty.map_or(true, |ld| peer_difficulty.map_or(true, |pd| pd > ld)) {
-						if let Some(request) = self.old_blocks.as_mut().and_then(|d| d.request_blocks(io, num_active_peers)) {
+						if let Some(request) = self.old_blocks.as_mut().and_then(|d| d.request_blocks(io, num_active_peers, batch_size)) {
 							SyncRequester::request_blocks(self, io, peer_id, request, BlockSet::OldBlocks);
 							return;
 						}
@@ -804,7 +893,6 @@ impl ChainSync {
 					}
 
 					if peer_snapshot_hash.is_some() && peer_snapshot_hash == self.snapshot.snapshot_hash() {
-						self.clear_peer_download(peer_id);
 						SyncRequester::request_snapshot_data(self, io, peer_id);
 					}
 				},
@@ -842,9 +930,7 @@ impl ChainSync {
 					}
 				},
 				PeerAsking::SnapshotData => {
-					if let Some(hash) = peer.asking_snapshot_data {
-						self.snapshot.clear_chunk_download(&hash);
-					}
+					self.snapshot.clear_peer_downloads(peer_id);
 				},
 				_ => (),
 			}
@@ -880,6 +966,13 @@ impl ChainSync {
 				peer.asking = PeerAsking::Nothing;
 				return false;
 			} else {
+				match asking {
+					PeerAsking::BlockHeaders | PeerAsking::BlockBodies | PeerAsking::BlockReceipts => {
+						let elapsed = peer.ask_time.elapsed();
+						peer.note_response_time(elapsed);
+					},
+					_ => (),
+				}
 				peer.asking = PeerAsking::Nothing;
 				return true;
 			}
@@ -926,6 +1019,7 @@ impl ChainSync {
 			};
 			if timeout {
 				debug!(target:"sync", "Timeout {}", peer_id);
+				io.note_reputation(*peer_id, ReputationChange::Timeout);
 				io.disconnect_peer(*peer_id);
 				aborting.push(*peer_id);
 			}
@@ -934,6 +1028,16 @@ impl ChainSync {
 			SyncHandler::on_peer_aborting(self, io, p);
 		}
 
+		// Retry chunks with other peers, the peers which didn't deliver them are not given more work
+		for peer_id in self.snapshot.expire_chunk_requests(SNAPSHOT_CHUNK_TIMEOUT) {
+			debug!(target: "sync", "{}: Snapshot chunk request timed out", peer_id);
+			io.note_reputation(peer_id, ReputationChange::Timeout);
+			self.deactivate_peer(io, peer_id);
+		}
+
+		// Send snapshot chunks which were deferred to stay within bandwidth caps
+		SyncSupplier::send_deferred_snapshot_data(self.snapshot_serving.get_mut(), io);
+
 		// Check for handshake timeouts
 		for (peer, &ask_time) in &self.handshaking_peers {
 			let elapsed = (tick - ask_time) / 1_000_000_000;
@@ -1080,7 +1184,13 @@ impl ChainSync {
 	}
 
 	/// called when block is imported to chain - propagates the blocks and updates transactions sent to peers
-	pub fn chain_new_blocks(&mut self, io: &mut SyncIo, _imported: &[H256], invalid: &[H256], enacted: &[H256], _retracted: &[H256], sealed: &[H256], proposed: &[Bytes]) {
+	pub fn chain_new_blocks(&mut self, io: &mut SyncIo, imported: &[H256], invalid: &[H256], enacted: &[H256], _retracted: &[H256], sealed: &[H256], proposed: &[Bytes]) {
+		for hash in imported {
+			if let Some(delay) = self.blocks_stats.imported(hash) {
+				debug!(target: "sync", "Block {} imported {}ms after first announcement", hash, delay.as_secs() * 1000 + (delay.subsec_nanos() / 1_000_000) as u64);
+			}
+		}
+
 		let queue_info = io.chain().queue_info();
 		let is_syncing = self.status().is_syncing(queue_info);
 
@@ -1271,8 +1381,8 @@ pub mod tests {
 				confirmation: super::ForkConfirmation::Confirmed,
 				snapshot_number: None,
 				snapshot_hash: None,
-				asking_snapshot_data: None,
 				block_set: None,
+				latency: None,
 			});
 
 	}
@@ -1420,4 +1530,27 @@ pub mod tests {
 		let status = io.chain.miner.queue_status();
 		assert_eq!(status.status.transaction_count, 0);
 	}
+
+	#[test]
+	fn requests_snapshot_manifest_from_reputable_peers() {
+		let mut client = TestBlockChainClient::new();
+		let queue = RwLock::new(VecDeque::new());
+		let ss = TestSnapshotService::new();
+		let mut sync = ChainSync::new(SyncConfig::default(), &client, Arc::new(NoopPrivateTxHandler));
+		for peer in 0..5 {
+			insert_dummy_peer(&mut sync, peer, H256::zero());
+		}
+		let mut io = TestIo::new(&mut client, &ss, &queue, None);
+		io.reputations.insert(1, 30);
+		io.reputations.insert(3, -10);
+		io.reputations.insert(4, 20);
+		io.reputations.insert(2, 10);
+
+		sync.start_snapshot_sync(&mut io, &[0, 1, 2, 3, 4]);
+
+		let mut recipients: Vec<_> = io.packets.iter().map(|p| p.recipient).collect();
+		recipients.sort();
+		assert_eq!(recipients, vec![1, 2, 4]);
+		assert_eq!(sync.state, SyncState::SnapshotManifest);
+	}
 }
@@ -23,11 +23,12 @@ use rand::Rng;
 use rlp::{Encodable, RlpStream};
 use sync_io::SyncIo;
 use std::cmp;
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet};
 use transaction::SignedTransaction;
 
 use super::{
 	random,
+	BlockPropagation,
 	ChainSync,
 	MAX_TRANSACTION_PACKET_SIZE,
 	MAX_PEER_LAG_PROPAGATION,
@@ -72,12 +73,16 @@ impl SyncPropagator {
 		let mut sent = 0;
 		for peer_id in peers {
 			if blocks.is_empty() {
-				let rlp =  ChainSync::create_latest_block_rlp(io.chain());
-				SyncPropagator::send_packet(io, *peer_id, NEW_BLOCK_PACKET, rlp);
+				if sync.announcements.should_send_block(*peer_id, &chain_info.best_block_hash) {
+					let rlp =  ChainSync::create_latest_block_rlp(io.chain());
+					SyncPropagator::send_packet(io, *peer_id, NEW_BLOCK_PACKET, rlp);
+				}
 			} else {
 				for h in blocks {
-					let rlp =  ChainSync::create_new_block_rlp(io.chain(), h);
-					SyncPropagator::send_packet(io, *peer_id, NEW_BLOCK_PACKET, rlp);
+					if sync.announcements.should_send_block(*peer_id, h) {
+						let rlp =  ChainSync::create_new_block_rlp(io.chain(), h);
+						SyncPropagator::send_packet(io, *peer_id, NEW_BLOCK_PACKET, rlp);
+					}
 				}
 			}
 			if let Some(ref mut peer) = sync.peers.get_mut(peer_id) {
@@ -94,6 +99,9 @@ impl SyncPropagator {
 		let mut sent = 0;
 		let last_parent = *io.chain().best_block_header().parent_hash();
 		for peer_id in peers {
+			if !sync.announcements.should_announce(*peer_id, &chain_info.best_block_hash) {
+				continue;
+			}
 			sent += match ChainSync::create_new_hashes_rlp(io.chain(), &last_parent, &chain_info.best_block_hash) {
 				Some(rlp) => {
 					{
@@ -117,6 +125,17 @@ impl SyncPropagator {
 			return 0;
 		}
 
+		// Stuck local transactions might have been pruned by peers, so send them again.
+		let rebroadcast = io.chain().transactions_to_rebroadcast();
+		if !rebroadcast.is_empty() {
+			trace!(target: "sync", "Re-broadcasting {} stuck local transactions.", rebroadcast.len());
+			for peer_info in sync.peers.values_mut() {
+				for hash in &rebroadcast {
+					peer_info.last_sent_transactions.remove(hash);
+				}
+			}
+		}
+
 		let transactions = io.chain().transactions_to_propagate();
 		if transactions.is_empty() {
 			return 0;
@@ -160,6 +179,9 @@ impl SyncPropagator {
 		// sqrt(x)/x scaled to max u32
 		let block_number = io.chain().chain_info().best_block_number;
 
+		// Number of peers each transaction is sent to
+		let mut propagated = HashMap::new();
+
 		let lucky_peers = {
 			peers.into_iter()
 				.filter_map(|peer_id| {
@@ -173,6 +195,7 @@ impl SyncPropagator {
 						for hash in &all_transactions_hashes {
 							let id = io.peer_session_info(peer_id).and_then(|info| info.id);
 							stats.propagated(hash, id, block_number);
+							*propagated.entry(*hash).or_insert(0) += 1;
 						}
 						peer_info.last_sent_transactions = all_transactions_hashes.clone();
 						return Some((peer_id, all_transactions_hashes.len(), all_transactions_rlp.clone()));
@@ -216,6 +239,7 @@ impl SyncPropagator {
 					for hash in &to_send {
 						// update stats
 						stats.propagated(hash, id, block_number);
+						*propagated.entry(*hash).or_insert(0) += 1;
 					}
 
 					peer_info.last_sent_transactions = all_transactions_hashes
@@ -243,17 +267,27 @@ impl SyncPropagator {
 			debug!(target: "sync", "Sent up to {} transactions to {} peers.", max_sent, lucky_peers_len);
 		}
 
+		if !propagated.is_empty() {
+			io.chain().transactions_propagated(&propagated.into_iter().collect::<Vec<_>>());
+		}
+
 		peers
 	}
 
 	pub fn propagate_latest_blocks(sync: &mut ChainSync, io: &mut SyncIo, sealed: &[H256]) {
 		let chain_info = io.chain().chain_info();
 		if (((chain_info.best_block_number as i64) - (sync.last_sent_block_number as i64)).abs() as BlockNumber) < MAX_PEER_LAG_PROPAGATION {
-			let mut peers = sync.get_lagging_peers(&chain_info);
+			let peers = sync.get_lagging_peers(&chain_info);
 			if sealed.is_empty() {
-				let hashes = SyncPropagator::propagate_new_hashes(sync, &chain_info, io, &peers);
-				peers = ChainSync::select_random_peers(&peers);
-				let blocks = SyncPropagator::propagate_blocks(sync, &chain_info, io, sealed, &peers);
+				let (hashes, blocks) = match sync.block_propagation {
+					BlockPropagation::Sqrt => {
+						let hashes = SyncPropagator::propagate_new_hashes(sync, &chain_info, io, &peers);
+						let peers = ChainSync::select_random_peers(&peers);
+						(hashes, SyncPropagator::propagate_blocks(sync, &chain_info, io, sealed, &peers))
+					},
+					BlockPropagation::Push => (0, SyncPropagator::propagate_blocks(sync, &chain_info, io, sealed, &peers)),
+					BlockPropagation::Announce => (SyncPropagator::propagate_new_hashes(sync, &chain_info, io, &peers), 0),
+				};
 				if blocks != 0 || hashes != 0 {
 					trace!(target: "sync", "Sent latest {} blocks and {} hashes to peers.", blocks, hashes);
 				}
@@ -400,6 +434,63 @@ mod tests {
 		assert_eq!(0x07, io.packets[0].packet_id);
 	}
 
+	#[test]
+	fn does_not_send_block_back_to_announcing_peer() {
+		let mut client = TestBlockChainClient::new();
+		client.add_blocks(100, EachBlockWith::Uncle);
+		let queue = RwLock::new(VecDeque::new());
+		let mut sync = dummy_sync_with_peer(client.block_hash_delta_minus(5), &client);
+		let chain_info = client.chain_info();
+		sync.announcements.received(0, &chain_info.best_block_hash);
+		let ss = TestSnapshotService::new();
+		let mut io = TestIo::new(&mut client, &ss, &queue, None);
+		let peers = sync.get_lagging_peers(&chain_info);
+		SyncPropagator::propagate_blocks(&mut sync, &chain_info, &mut io, &[], &peers);
+		SyncPropagator::propagate_new_hashes(&mut sync, &chain_info, &mut io, &peers);
+
+		// no messages should be sent
+		assert_eq!(0, io.packets.len());
+		assert_eq!(2, sync.announcement_stats().duplicates_suppressed);
+	}
+
+	#[test]
+	fn announces_only_hashes_with_announce_strategy() {
+		let mut client = TestBlockChainClient::new();
+		client.add_blocks(100, EachBlockWith::Uncle);
+		let queue = RwLock::new(VecDeque::new());
+		let mut sync = dummy_sync_with_peer(client.block_hash_delta_minus(5), &client);
+		insert_dummy_peer(&mut sync, 1, client.block_hash_delta_minus(5));
+		sync.block_propagation = BlockPropagation::Announce;
+		sync.last_sent_block_number = client.chain_info().best_block_number;
+		let ss = TestSnapshotService::new();
+		let mut io = TestIo::new(&mut client, &ss, &queue, None);
+		SyncPropagator::propagate_latest_blocks(&mut sync, &mut io, &[]);
+
+		// NEW_BLOCK_HASHES_PACKET to both peers
+		assert_eq!(2, io.packets.len());
+		assert!(io.packets.iter().all(|p| p.packet_id == 0x01));
+	}
+
+	#[test]
+	fn pushes_blocks_to_all_peers_with_push_strategy() {
+		let mut client = TestBlockChainClient::new();
+		client.add_blocks(100, EachBlockWith::Uncle);
+		let queue = RwLock::new(VecDeque::new());
+		let mut sync = dummy_sync_with_peer(client.block_hash_delta_minus(5), &client);
+		for peer_id in 1..10 {
+			insert_dummy_peer(&mut sync, peer_id, client.block_hash_delta_minus(5));
+		}
+		sync.block_propagation = BlockPropagation::Push;
+		sync.last_sent_block_number = client.chain_info().best_block_number;
+		let ss = TestSnapshotService::new();
+		let mut io = TestIo::new(&mut client, &ss, &queue, None);
+		SyncPropagator::propagate_latest_blocks(&mut sync, &mut io, &[]);
+
+		// NEW_BLOCK_PACKET to all 10 peers
+		assert_eq!(10, io.packets.len());
+		assert!(io.packets.iter().all(|p| p.packet_id == 0x07));
+	}
+
 	#[test]
 	fn sends_proposed_block() {
 		let mut client = TestBlockChainClient::new();
@@ -425,8 +516,8 @@ mod tests {
 				confirmation: ForkConfirmation::Confirmed,
 				snapshot_number: None,
 				snapshot_hash: None,
-				asking_snapshot_data: None,
 				block_set: None,
+				latency: None,
 			});
 		let ss = TestSnapshotService::new();
 		let mut io = TestIo::new(&mut client, &ss, &queue, None);
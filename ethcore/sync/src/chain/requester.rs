@@ -29,6 +29,7 @@ use super::{
 	ChainSync,
 	PeerAsking,
 	ETH_PROTOCOL_VERSION_63,
+	MAX_SNAPSHOT_CHUNK_REQUESTS_PER_PEER,
 	GET_BLOCK_BODIES_PACKET,
 	GET_BLOCK_HEADERS_PACKET,
 	GET_RECEIPTS_PACKET,
@@ -43,8 +44,8 @@ impl SyncRequester {
 	/// Perform block download request`
 	pub fn request_blocks(sync: &mut ChainSync, io: &mut SyncIo, peer_id: PeerId, request: BlockRequest, block_set: BlockSet) {
 		match request {
-			BlockRequest::Headers { start, count, skip } => {
-				SyncRequester::request_headers_by_hash(sync, io, peer_id, &start, count, skip, false, block_set);
+			BlockRequest::Headers { start, count, skip, reverse } => {
+				SyncRequester::request_headers_by_hash(sync, io, peer_id, &start, count, skip, reverse, block_set);
 			},
 			BlockRequest::Bodies { hashes } => {
 				SyncRequester::request_bodies(sync, io, peer_id, hashes, block_set);
@@ -81,12 +82,13 @@ impl SyncRequester {
 
 	/// Find some headers or blocks to download for a peer.
 	pub fn request_snapshot_data(sync: &mut ChainSync, io: &mut SyncIo, peer_id: PeerId) {
-		// find chunk data to download
-		if let Some(hash) = sync.snapshot.needed_chunk() {
-			if let Some(ref mut peer) = sync.peers.get_mut(&peer_id) {
-				peer.asking_snapshot_data = Some(hash.clone());
+		// keep several chunk requests outstanding with each peer
+		while sync.snapshot.chunk_requests(peer_id) < MAX_SNAPSHOT_CHUNK_REQUESTS_PER_PEER {
+			// find chunk data to download
+			match sync.snapshot.needed_chunk(peer_id) {
+				Some(hash) => SyncRequester::request_snapshot_chunk(sync, io, peer_id, &hash),
+				None => break,
 			}
-			SyncRequester::request_snapshot_chunk(sync, io, peer_id, &hash);
 		}
 	}
 
@@ -135,7 +137,9 @@ impl SyncRequester {
 	/// Generic request sender
 	fn send_request(sync: &mut ChainSync, io: &mut SyncIo, peer_id: PeerId, asking: PeerAsking,  packet_id: PacketId, packet: Bytes) {
 		if let Some(ref mut peer) = sync.peers.get_mut(&peer_id) {
-			if peer.asking != PeerAsking::Nothing {
+			// snapshot chunks are requested from a peer in parallel
+			let parallel = peer.asking == PeerAsking::SnapshotData && asking == PeerAsking::SnapshotData;
+			if peer.asking != PeerAsking::Nothing && !parallel {
 				warn!(target:"sync", "Asking {:?} while requesting {:?}", peer.asking, asking);
 			}
 			peer.asking = asking;
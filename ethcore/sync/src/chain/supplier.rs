@@ -22,7 +22,10 @@ use network::{self, PeerId};
 use parking_lot::RwLock;
 use rlp::{Rlp, RlpStream};
 use std::cmp;
+use std::time::Instant;
 
+use api::WARP_SYNC_PROTOCOL_ID;
+use snapshot_serving::SnapshotServing;
 use sync_io::SyncIo;
 
 use super::{
@@ -77,9 +80,17 @@ impl SyncSupplier {
 				SyncSupplier::return_snapshot_manifest,
 				|e| format!("Error sending snapshot manifest: {:?}", e)),
 
-			GET_SNAPSHOT_DATA_PACKET => SyncSupplier::return_rlp(io, &rlp, peer,
-				SyncSupplier::return_snapshot_data,
-				|e| format!("Error sending snapshot data: {:?}", e)),
+			GET_SNAPSHOT_DATA_PACKET => {
+				let sync = sync.read();
+				let mut serving = sync.snapshot_serving.lock();
+				if serving.is_limited() {
+					SyncSupplier::defer_snapshot_data(&mut serving, io, &rlp, peer)
+				} else {
+					SyncSupplier::return_rlp(io, &rlp, peer,
+						SyncSupplier::return_snapshot_data,
+						|e| format!("Error sending snapshot data: {:?}", e))
+				}
+			},
 			CONSENSUS_DATA_PACKET => ChainSync::on_consensus_packet(io, peer, &rlp),
 			_ => {
 				sync.write().on_packet(io, peer, packet_id, data);
@@ -265,7 +276,11 @@ impl SyncSupplier {
 	fn return_snapshot_data(io: &SyncIo, r: &Rlp, peer_id: PeerId) -> RlpResponseResult {
 		let hash: H256 = r.val_at(0)?;
 		trace!(target: "warp", "{} -> GetSnapshotData {:?}", peer_id, hash);
-		let rlp = match io.snapshot_service().chunk(hash) {
+		Ok(Some((SNAPSHOT_DATA_PACKET, SyncSupplier::snapshot_data(io, hash, peer_id))))
+	}
+
+	fn snapshot_data(io: &SyncIo, hash: H256, peer_id: PeerId) -> RlpStream {
+		match io.snapshot_service().chunk(hash) {
 			Some(data) => {
 				let mut rlp = RlpStream::new_list(1);
 				trace!(target: "warp", "{} <- SnapshotData", peer_id);
@@ -276,8 +291,29 @@ impl SyncSupplier {
 				trace!(target: "warp", "{}: No snapshot data to return", peer_id);
 				RlpStream::new_list(0)
 			}
-		};
-		Ok(Some((SNAPSHOT_DATA_PACKET, rlp)))
+		}
+	}
+
+	/// Queue GetSnapshotData request until there is bandwidth left to respond to it
+	fn defer_snapshot_data(serving: &mut SnapshotServing, io: &mut SyncIo, r: &Rlp, peer_id: PeerId) -> Result<(), PacketDecodeError> {
+		let hash: H256 = r.val_at(0)?;
+		trace!(target: "warp", "{} -> GetSnapshotData {:?}", peer_id, hash);
+		if !serving.defer(peer_id, hash) {
+			debug!(target: "warp", "{}: Too many snapshot data requests, ignoring {:?}", peer_id, hash);
+		}
+		SyncSupplier::send_deferred_snapshot_data(serving, io);
+		Ok(())
+	}
+
+	/// Respond to deferred GetSnapshotData requests within bandwidth caps
+	pub fn send_deferred_snapshot_data(serving: &mut SnapshotServing, io: &mut SyncIo) {
+		let now = Instant::now();
+		while let Some((peer_id, hash)) = serving.next(now) {
+			let data = SyncSupplier::snapshot_data(io, hash, peer_id).out();
+			serving.sent(peer_id, data.len());
+			io.send_protocol(WARP_SYNC_PROTOCOL_ID, peer_id, SNAPSHOT_DATA_PACKET, data).unwrap_or_else(
+				|e| debug!(target: "sync", "Error sending snapshot data: {:?}", e));
+		}
 	}
 
 	fn return_rlp<FRlp, FError>(io: &mut SyncIo, rlp: &Rlp, peer: PeerId, rlp_func: FRlp, error_func: FError) -> Result<(), PacketDecodeError>
@@ -309,6 +345,8 @@ mod test {
 	use super::{*, super::tests::*};
 	use blocks::SyncHeader;
 	use ethcore::client::{BlockChainClient, EachBlockWith, TestBlockChainClient};
+	use ethcore::snapshot::SnapshotService;
+	use snapshot_serving::SnapshotServingLimits;
 
 	#[test]
 	fn return_block_headers() {
@@ -330,14 +368,14 @@ mod test {
 			rlp.out()
 		}
 		fn to_header_vec(rlp: ::chain::RlpResponseResult) -> Vec<SyncHeader> {
-			Rlp::new(&rlp.unwrap().unwrap().1.out()).iter().map(|r| SyncHeader::from_rlp(r.as_raw().to_vec()).unwrap()).collect()
+			Rlp::new(&rlp.unwrap().unwrap().1.out()).iter().map(|r| SyncHeader::from_rlp(r.as_raw().to_vec(), BlockNumber::max_value()).unwrap()).collect()
 		}
 
 		let mut client = TestBlockChainClient::new();
 		client.add_blocks(100, EachBlockWith::Nothing);
 		let blocks: Vec<_> = (0 .. 100)
 			.map(|i| (&client as &BlockChainClient).block(BlockId::Number(i as BlockNumber)).map(|b| b.into_inner()).unwrap()).collect();
-		let headers: Vec<_> = blocks.iter().map(|b| SyncHeader::from_rlp(Rlp::new(b).at(0).unwrap().as_raw().to_vec()).unwrap()).collect();
+		let headers: Vec<_> = blocks.iter().map(|b| SyncHeader::from_rlp(Rlp::new(b).at(0).unwrap().as_raw().to_vec(), BlockNumber::max_value()).unwrap()).collect();
 		let hashes: Vec<_> = headers.iter().map(|h| h.header.hash()).collect();
 
 		let queue = RwLock::new(VecDeque::new());
@@ -448,4 +486,26 @@ mod test {
 		ChainSync::dispatch_packet(&RwLock::new(sync), &mut io, 0usize, GET_RECEIPTS_PACKET, &receipts_request);
 		assert_eq!(1, io.packets.len());
 	}
+
+	#[test]
+	fn defers_snapshot_data_over_bandwidth_cap() {
+		let mut client = TestBlockChainClient::new();
+		let queue = RwLock::new(VecDeque::new());
+		let mut sync = dummy_sync_with_peer(H256::new(), &client);
+		*sync.snapshot_serving.get_mut() = SnapshotServing::new(SnapshotServingLimits { per_peer: Some(1), total: None });
+		let sync = RwLock::new(sync);
+		let ss = TestSnapshotService::new_with_snapshot(4, H256::new(), 1);
+		let hashes = ss.manifest().unwrap().state_hashes;
+		let mut io = TestIo::new(&mut client, &ss, &queue, None);
+
+		for hash in &hashes {
+			let mut request = RlpStream::new_list(1);
+			request.append(hash);
+			ChainSync::dispatch_packet(&sync, &mut io, 0usize, GET_SNAPSHOT_DATA_PACKET, &request.out());
+		}
+
+		assert_eq!(2, hashes.len());
+		assert_eq!(1, io.packets.len());
+		assert_eq!(SNAPSHOT_DATA_PACKET, io.packets[0].packet_id);
+	}
 }
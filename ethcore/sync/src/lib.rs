@@ -34,6 +34,7 @@ extern crate fastmap;
 extern crate rand;
 extern crate parking_lot;
 extern crate rlp;
+extern crate stats;
 extern crate keccak_hash as hash;
 extern crate triehash_ethereum;
 
@@ -53,12 +54,15 @@ extern crate heapsize;
 #[macro_use]
 extern crate trace_time;
 
+mod announcements;
 mod chain;
 mod blocks;
 mod block_sync;
+mod blocks_stats;
 mod sync_io;
 mod private_tx;
 mod snapshot;
+mod snapshot_serving;
 mod transactions_stats;
 
 pub mod light_sync;
@@ -71,5 +75,6 @@ mod api;
 pub use api::*;
 pub use chain::{SyncStatus, SyncState};
 pub use devp2p::validate_node_url;
-pub use network::{NonReservedPeerMode, Error, ErrorKind, ConnectionFilter, ConnectionDirection};
+pub use network::{NonReservedPeerMode, Error, ErrorKind, ConnectionFilter, ConnectionDirection, ProtocolSlots, FleetConfiguration};
+pub use network::{HandshakeFailure, HandshakeFailureReason, ReputationChange, Traffic};
 pub use private_tx::{PrivateTxHandler, NoopPrivateTxHandler, SimplePrivateTxHandler};
@@ -38,6 +38,7 @@ use std::sync::Arc;
 use std::time::{Instant, Duration};
 
 use ethcore::encoded;
+use ethcore::header::BlockNumber;
 use light::client::{AsLightClient, LightChainClient};
 use light::net::{
 	PeerStatus, Announcement, Handler, BasicContext,
@@ -124,7 +125,7 @@ impl AncestorSearch {
 		match self {
 			AncestorSearch::Awaiting(id, start, req) => {
 				if &id == ctx.req_id() {
-					match response::verify(ctx.data(), &req) {
+					match response::verify(ctx.data(), &req, ctx.eip1559_transition()) {
 						Ok(headers) => {
 							for header in &headers {
 								if client.is_known(&header.hash()) {
@@ -218,12 +219,14 @@ struct ResponseCtx<'a> {
 	req_id: ReqId,
 	ctx: &'a BasicContext,
 	data: &'a [encoded::Header],
+	eip1559_transition: BlockNumber,
 }
 
 impl<'a> ResponseContext for ResponseCtx<'a> {
 	fn responder(&self) -> PeerId { self.peer }
 	fn req_id(&self) -> &ReqId { &self.req_id }
 	fn data(&self) -> &[encoded::Header] { self.data }
+	fn eip1559_transition(&self) -> BlockNumber { self.eip1559_transition }
 	fn punish_responder(&self) { self.ctx.disable_peer(self.peer) }
 }
 
@@ -388,6 +391,7 @@ impl<L: AsLightClient + Send + Sync> Handler for LightSync<L> {
 				req_id: req_id,
 				ctx: ctx.as_basic(),
 				data: headers,
+				eip1559_transition: self.client.as_light_client().engine().params().eip1559_transition,
 			};
 
 			*state = match mem::replace(&mut *state, SyncState::Idle) {
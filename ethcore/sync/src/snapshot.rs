@@ -17,9 +17,11 @@
 use ethcore::snapshot::{ManifestData, SnapshotService};
 use ethereum_types::H256;
 use hash::keccak;
+use network::PeerId;
 
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet};
 use std::iter::FromIterator;
+use std::time::{Duration, Instant};
 
 #[derive(PartialEq, Eq, Debug)]
 pub enum ChunkType {
@@ -27,10 +29,19 @@ pub enum ChunkType {
 	Block(H256),
 }
 
+/// Outstanding request of a chunk.
+struct ChunkRequest {
+	peer_id: PeerId,
+	requested: Instant,
+}
+
+/// Downloaded snapshot and the scheduler of chunk requests.
+/// Chunks are requested from all peers serving the snapshot at the same time,
+/// a chunk which isn't delivered in time can be requested from another peer.
 pub struct Snapshot {
 	pending_state_chunks: Vec<H256>,
 	pending_block_chunks: Vec<H256>,
-	downloading_chunks: HashSet<H256>,
+	downloading_chunks: HashMap<H256, ChunkRequest>,
 	completed_chunks: HashSet<H256>,
 	snapshot_hash: Option<H256>,
 	bad_hashes: HashSet<H256>,
@@ -43,7 +54,7 @@ impl Snapshot {
 		Snapshot {
 			pending_state_chunks: Vec::new(),
 			pending_block_chunks: Vec::new(),
-			downloading_chunks: HashSet::new(),
+			downloading_chunks: HashMap::new(),
 			completed_chunks: HashSet::new(),
 			snapshot_hash: None,
 			bad_hashes: HashSet::new(),
@@ -113,11 +124,11 @@ impl Snapshot {
 		Err(())
 	}
 
-	/// Find a chunk to download
-	pub fn needed_chunk(&mut self) -> Option<H256> {
+	/// Find a chunk to download from given peer
+	pub fn needed_chunk(&mut self, peer_id: PeerId) -> Option<H256> {
 		// Find next needed chunk: first block, then state chunks
 		let chunk = {
-			let chunk_filter = |h| !self.downloading_chunks.contains(h) && !self.completed_chunks.contains(h);
+			let chunk_filter = |h| !self.downloading_chunks.contains_key(h) && !self.completed_chunks.contains(h);
 
 			let needed_block_chunk = self.pending_block_chunks.iter()
 				.filter(|&h| chunk_filter(h))
@@ -136,13 +147,38 @@ impl Snapshot {
 		};
 
 		if let Some(hash) = chunk {
-			self.downloading_chunks.insert(hash.clone());
+			self.downloading_chunks.insert(hash.clone(), ChunkRequest {
+				peer_id,
+				requested: Instant::now(),
+			});
 		}
 		chunk
 	}
 
-	pub fn clear_chunk_download(&mut self, hash: &H256) {
-		self.downloading_chunks.remove(hash);
+	/// Number of chunks requested from given peer and not delivered yet.
+	pub fn chunk_requests(&self, peer_id: PeerId) -> usize {
+		self.downloading_chunks.values().filter(|request| request.peer_id == peer_id).count()
+	}
+
+	/// Release all chunks requested from given peer, so that they can be requested from other peers.
+	pub fn clear_peer_downloads(&mut self, peer_id: PeerId) {
+		self.downloading_chunks.retain(|_, request| request.peer_id != peer_id);
+	}
+
+	/// Release chunks which were requested longer than `timeout` ago.
+	/// Returns peers which didn't deliver requested chunks in time.
+	pub fn expire_chunk_requests(&mut self, timeout: Duration) -> HashSet<PeerId> {
+		let now = Instant::now();
+		let mut expired = HashSet::new();
+		self.downloading_chunks.retain(|_, request| {
+			if now.duration_since(request.requested) > timeout {
+				expired.insert(request.peer_id);
+				false
+			} else {
+				true
+			}
+		});
+		expired
 	}
 
 	// note snapshot hash as bad.
@@ -221,8 +257,8 @@ mod test {
 		assert_eq!(snapshot.done_chunks(), 0);
 		assert!(snapshot.validate_chunk(&H256::random().to_vec()).is_err());
 
-		let requested: Vec<H256> = (0..40).map(|_| snapshot.needed_chunk().unwrap()).collect();
-		assert!(snapshot.needed_chunk().is_none());
+		let requested: Vec<H256> = (0..40).map(|_| snapshot.needed_chunk(0).unwrap()).collect();
+		assert!(snapshot.needed_chunk(0).is_none());
 
 		let requested_all_block_chunks = manifest.block_hashes.iter()
 			.all(|h| requested.iter().any(|rh| rh == h));
@@ -269,4 +305,28 @@ mod test {
 		snapshot.note_bad(hash);
 		assert_eq!(snapshot.is_known_bad(&hash), true);
 	}
+
+	#[test]
+	fn stripes_chunk_requests_across_peers() {
+		let mut snapshot = Snapshot::new();
+		let (manifest, mhash, _, block_chunks) = test_manifest();
+		snapshot.reset_to(&manifest, &mhash);
+
+		let first = snapshot.needed_chunk(1).unwrap();
+		let second = snapshot.needed_chunk(2).unwrap();
+		snapshot.needed_chunk(2).unwrap();
+		assert!(first != second);
+		assert_eq!(snapshot.chunk_requests(1), 1);
+		assert_eq!(snapshot.chunk_requests(2), 2);
+
+		// delivered chunks are no longer outstanding
+		assert!(snapshot.validate_chunk(&block_chunks[0]).is_ok());
+		assert_eq!(snapshot.chunk_requests(1), 0);
+
+		// expired requests are released and can be retried with other peers
+		assert!(snapshot.expire_chunk_requests(Duration::from_secs(60)).is_empty());
+		assert_eq!(snapshot.expire_chunk_requests(Duration::from_secs(0)), HashSet::from_iter(vec![2]));
+		assert_eq!(snapshot.chunk_requests(2), 0);
+		assert_eq!(snapshot.needed_chunk(3), Some(second));
+	}
 }
@@ -0,0 +1,220 @@
+// Copyright 2015-2018 Parity Technologies (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+use std::cmp;
+use std::collections::{HashMap, VecDeque};
+use std::time::Instant;
+use ethereum_types::H256;
+use network::PeerId;
+
+/// Maximal number of chunk requests of a single peer waiting for bandwidth. Further requests are dropped.
+const MAX_DEFERRED_REQUESTS: usize = 16;
+
+/// Bytes which may be sent, refilled at a constant rate up to one second worth of bytes.
+/// Available bytes may go negative, so that chunks larger than the rate can still be sent.
+#[derive(Debug)]
+struct Bucket {
+	rate: u64,
+	available: i64,
+	updated: Instant,
+}
+
+impl Bucket {
+	fn new(rate: u64, now: Instant) -> Self {
+		Bucket {
+			rate,
+			available: rate as i64,
+			updated: now,
+		}
+	}
+
+	fn refill(&mut self, now: Instant) {
+		if now <= self.updated {
+			return;
+		}
+		let elapsed = now - self.updated;
+		let elapsed_ms = elapsed.as_secs() * 1000 + elapsed.subsec_millis() as u64;
+		if elapsed_ms == 0 {
+			return;
+		}
+		self.available = cmp::min(self.available + (self.rate * elapsed_ms / 1000) as i64, self.rate as i64);
+		self.updated = now;
+	}
+
+	fn is_exhausted(&self) -> bool {
+		self.available <= 0
+	}
+}
+
+/// Bandwidth caps for snapshot chunks served to peers, in bytes per second.
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub struct SnapshotServingLimits {
+	/// Cap of a single peer.
+	pub per_peer: Option<u64>,
+	/// Cap of all peers together.
+	pub total: Option<u64>,
+}
+
+/// Schedules sending of snapshot chunks within bandwidth caps.
+///
+/// Requests which can't be served right away are deferred. Deferred requests are served
+/// one per peer in turn, so that peers requesting a lot of chunks don't starve the others.
+#[derive(Debug)]
+pub struct SnapshotServing {
+	limits: SnapshotServingLimits,
+	total: Option<Bucket>,
+	peers: HashMap<PeerId, Bucket>,
+	deferred: HashMap<PeerId, VecDeque<H256>>,
+	/// Peers with deferred requests in the order they are served.
+	queue: VecDeque<PeerId>,
+}
+
+impl SnapshotServing {
+	/// Create a new scheduler with given caps.
+	pub fn new(limits: SnapshotServingLimits) -> Self {
+		SnapshotServing {
+			limits,
+			total: limits.total.map(|rate| Bucket::new(rate, Instant::now())),
+			peers: HashMap::new(),
+			deferred: HashMap::new(),
+			queue: VecDeque::new(),
+		}
+	}
+
+	/// Returns `true` if any cap is set.
+	pub fn is_limited(&self) -> bool {
+		self.limits.per_peer.is_some() || self.limits.total.is_some()
+	}
+
+	/// Defers a request for chunk `hash` from `peer`. Returns `false` if the peer has too many deferred requests.
+	pub fn defer(&mut self, peer: PeerId, hash: H256) -> bool {
+		let queue = &mut self.queue;
+		let requests = self.deferred.entry(peer).or_insert_with(|| {
+			queue.push_back(peer);
+			VecDeque::new()
+		});
+		if requests.len() >= MAX_DEFERRED_REQUESTS {
+			return false;
+		}
+		requests.push_back(hash);
+		true
+	}
+
+	/// Returns the next deferred request which can be served at `now`.
+	pub fn next(&mut self, now: Instant) -> Option<(PeerId, H256)> {
+		if let Some(ref mut total) = self.total {
+			total.refill(now);
+			if total.is_exhausted() {
+				return None;
+			}
+		}
+
+		for _ in 0..self.queue.len() {
+			let peer = self.queue.pop_front()?;
+			if let Some(rate) = self.limits.per_peer {
+				let bucket = self.peers.entry(peer).or_insert_with(|| Bucket::new(rate, now));
+				bucket.refill(now);
+				if bucket.is_exhausted() {
+					self.queue.push_back(peer);
+					continue;
+				}
+			}
+
+			let (hash, is_empty) = match self.deferred.get_mut(&peer) {
+				Some(requests) => (requests.pop_front(), requests.is_empty()),
+				None => (None, true),
+			};
+			if is_empty {
+				self.deferred.remove(&peer);
+			} else {
+				self.queue.push_back(peer);
+			}
+			if let Some(hash) = hash {
+				return Some((peer, hash));
+			}
+		}
+		None
+	}
+
+	/// Accounts `bytes` sent to `peer`.
+	pub fn sent(&mut self, peer: PeerId, bytes: usize) {
+		if let Some(ref mut total) = self.total {
+			total.available -= bytes as i64;
+		}
+		if let Some(bucket) = self.peers.get_mut(&peer) {
+			bucket.available -= bytes as i64;
+		}
+	}
+
+	/// Forgets all state of a disconnected peer.
+	pub fn remove_peer(&mut self, peer: PeerId) {
+		self.peers.remove(&peer);
+		self.deferred.remove(&peer);
+		self.queue.retain(|p| *p != peer);
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use std::time::{Duration, Instant};
+	use super::{SnapshotServing, SnapshotServingLimits, MAX_DEFERRED_REQUESTS};
+
+	#[test]
+	fn should_serve_peers_in_turn_within_total_cap() {
+		let now = Instant::now();
+		let mut serving = SnapshotServing::new(SnapshotServingLimits { per_peer: None, total: Some(1000) });
+		for i in 0..3 {
+			assert!(serving.defer(1, i.into()));
+		}
+		assert!(serving.defer(2, 10.into()));
+
+		assert_eq!(serving.next(now), Some((1, 0.into())));
+		serving.sent(1, 600);
+		assert_eq!(serving.next(now), Some((2, 10.into())));
+		serving.sent(2, 600);
+		assert_eq!(serving.next(now), None);
+
+		assert_eq!(serving.next(now + Duration::from_millis(500)), Some((1, 1.into())));
+	}
+
+	#[test]
+	fn should_skip_peers_over_their_cap() {
+		let now = Instant::now();
+		let mut serving = SnapshotServing::new(SnapshotServingLimits { per_peer: Some(1000), total: None });
+		serving.defer(1, 0.into());
+		serving.defer(1, 1.into());
+		serving.defer(2, 2.into());
+
+		assert_eq!(serving.next(now), Some((1, 0.into())));
+		serving.sent(1, 1000);
+		assert_eq!(serving.next(now), Some((2, 2.into())));
+		serving.sent(2, 1000);
+		assert_eq!(serving.next(now), None);
+		assert_eq!(serving.next(now + Duration::from_secs(1)), Some((1, 1.into())));
+	}
+
+	#[test]
+	fn should_drop_requests_over_the_limit() {
+		let mut serving = SnapshotServing::new(SnapshotServingLimits { per_peer: Some(1000), total: None });
+		for i in 0..MAX_DEFERRED_REQUESTS {
+			assert!(serving.defer(1, (i as u64).into()));
+		}
+
+		assert!(!serving.defer(1, 100.into()));
+		serving.remove_peer(1);
+		assert!(serving.defer(1, 100.into()));
+	}
+}
@@ -15,7 +15,7 @@
 // along with Parity.  If not, see <http://www.gnu.org/licenses/>.
 
 use std::collections::HashMap;
-use network::{NetworkContext, PeerId, PacketId, Error, SessionInfo, ProtocolId};
+use network::{NetworkContext, PeerId, PacketId, Error, SessionInfo, ProtocolId, HandshakeFailureReason, ReputationChange};
 use bytes::Bytes;
 use ethcore::client::BlockChainClient;
 use ethcore::header::BlockNumber;
@@ -30,6 +30,10 @@ pub trait SyncIo {
 	fn disable_peer(&mut self, peer_id: PeerId);
 	/// Disconnect peer
 	fn disconnect_peer(&mut self, peer_id: PeerId);
+	/// Record a failed status handshake with a peer
+	fn note_handshake_failure(&mut self, peer_id: PeerId, reason: HandshakeFailureReason);
+	/// Adjust the reputation of a peer
+	fn note_reputation(&mut self, peer_id: PeerId, change: ReputationChange);
 	/// Respond to current request with a packet. Can be called from an IO handler for incoming packet.
 	fn respond(&mut self, packet_id: PacketId, data: Vec<u8>) -> Result<(), Error>;
 	/// Send a packet to a peer.
@@ -46,6 +50,10 @@ pub trait SyncIo {
 	}
 	/// Returns information on p2p session
 	fn peer_session_info(&self, peer_id: PeerId) -> Option<SessionInfo>;
+	/// Returns the reputation of a peer
+	fn peer_reputation(&self, peer_id: PeerId) -> i32 {
+		self.peer_session_info(peer_id).map_or(0, |info| info.reputation)
+	}
 	/// Maximum mutually supported ETH protocol version
 	fn eth_protocol_version(&self, peer_id: PeerId) -> u8;
 	/// Maximum mutually supported version of a gien protocol.
@@ -92,6 +100,14 @@ impl<'s> SyncIo for NetSyncIo<'s> {
 		self.network.disconnect_peer(peer_id);
 	}
 
+	fn note_handshake_failure(&mut self, peer_id: PeerId, reason: HandshakeFailureReason) {
+		self.network.note_handshake_failure(peer_id, reason);
+	}
+
+	fn note_reputation(&mut self, peer_id: PeerId, change: ReputationChange) {
+		self.network.note_reputation(peer_id, change);
+	}
+
 	fn respond(&mut self, packet_id: PacketId, data: Vec<u8>) -> Result<(), Error>{
 		self.network.respond(packet_id, data)
 	}
@@ -20,7 +20,7 @@ use std::time::Duration;
 use ethereum_types::H256;
 use parking_lot::{RwLock, Mutex};
 use bytes::Bytes;
-use network::{self, PeerId, ProtocolId, PacketId, SessionInfo};
+use network::{self, PeerId, ProtocolId, PacketId, SessionInfo, HandshakeFailureReason, ReputationChange};
 use tests::snapshot::*;
 use ethcore::client::{TestBlockChainClient, BlockChainClient, Client as EthcoreClient,
 	ClientConfig, ChainNotify, ChainRoute, ChainMessageType, ClientIoMessage};
@@ -55,8 +55,10 @@ pub struct TestIo<'p, C> where C: FlushingBlockChainClient, C: 'p {
 	pub queue: &'p RwLock<VecDeque<TestPacket>>,
 	pub sender: Option<PeerId>,
 	pub to_disconnect: HashSet<PeerId>,
+	pub handshake_failures: Vec<(PeerId, HandshakeFailureReason)>,
 	pub packets: Vec<TestPacket>,
 	pub peers_info: HashMap<PeerId, String>,
+	pub reputations: HashMap<PeerId, i32>,
 	overlay: RwLock<HashMap<BlockNumber, Bytes>>,
 }
 
@@ -68,9 +70,11 @@ impl<'p, C> TestIo<'p, C> where C: FlushingBlockChainClient, C: 'p {
 			queue: queue,
 			sender: sender,
 			to_disconnect: HashSet::new(),
+			handshake_failures: Vec::new(),
 			overlay: RwLock::new(HashMap::new()),
 			packets: Vec::new(),
 			peers_info: HashMap::new(),
+			reputations: HashMap::new(),
 		}
 	}
 }
@@ -90,6 +94,14 @@ impl<'p, C> SyncIo for TestIo<'p, C> where C: FlushingBlockChainClient, C: 'p {
 		self.to_disconnect.insert(peer_id);
 	}
 
+	fn note_handshake_failure(&mut self, peer_id: PeerId, reason: HandshakeFailureReason) {
+		self.handshake_failures.push((peer_id, reason));
+	}
+
+	fn note_reputation(&mut self, peer_id: PeerId, change: ReputationChange) {
+		*self.reputations.entry(peer_id).or_insert(0) += change.value();
+	}
+
 	fn is_expired(&self) -> bool {
 		false
 	}
@@ -134,6 +146,10 @@ impl<'p, C> SyncIo for TestIo<'p, C> where C: FlushingBlockChainClient, C: 'p {
 		None
 	}
 
+	fn peer_reputation(&self, peer_id: PeerId) -> i32 {
+		self.reputations.get(&peer_id).cloned().unwrap_or(0)
+	}
+
 	fn eth_protocol_version(&self, _peer: PeerId) -> u8 {
 		ETH_PROTOCOL_VERSION_63.0
 	}
@@ -80,6 +80,10 @@ impl SnapshotService for TestSnapshotService {
 		Some((1, 2))
 	}
 
+	fn interrupted_restoration(&self) -> Option<ManifestData> {
+		None
+	}
+
 	fn completed_chunks(&self) -> Option<Vec<H256>> {
 		Some(vec![])
 	}
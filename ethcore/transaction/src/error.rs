@@ -79,6 +79,10 @@ pub enum Error {
 	TooBig,
 	/// Invalid RLP encoding
 	InvalidRlp(String),
+	/// Transaction type is not enabled yet.
+	TransactionTypeNotEnabled,
+	/// Max priority fee per gas is higher than max fee per gas (EIP-1559).
+	PriorityFeeTooHigh,
 }
 
 impl From<ethkey::Error> for Error {
@@ -119,6 +123,8 @@ impl fmt::Display for Error {
 			NotAllowed => "Sender does not have permissions to execute this type of transction".into(),
 			TooBig => "Transaction too big".into(),
 			InvalidRlp(ref err) => format!("Transaction has invalid RLP structure: {}.", err),
+			TransactionTypeNotEnabled => "Transaction type is not enabled for current block".into(),
+			PriorityFeeTooHigh => "Max priority fee per gas is higher than max fee per gas".into(),
 		};
 
 		f.write_fmt(format_args!("Transaction error ({})", msg))
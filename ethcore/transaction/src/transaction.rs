@@ -16,6 +16,7 @@
 
 //! Transaction data structure.
 
+use std::{cmp, mem};
 use std::ops::Deref;
 use ethereum_types::{H256, H160, Address, U256};
 use error;
@@ -35,6 +36,12 @@ pub const UNSIGNED_SENDER: Address = H160([0xff; 20]);
 /// System sender address for internal state updates.
 pub const SYSTEM_ADDRESS: Address = H160([0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff,0xff, 0xff, 0xff, 0xff,0xff, 0xff, 0xff, 0xff,0xff, 0xff, 0xff, 0xfe]);
 
+/// EIP-2718 type of transactions with an access list (EIP-2930).
+pub const ACCESS_LIST_TX_TYPE: u8 = 0x01;
+
+/// EIP-2718 type of transactions with dynamic fees (EIP-1559).
+pub const DYNAMIC_FEE_TX_TYPE: u8 = 0x02;
+
 /// Transaction action type.
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub enum Action {
@@ -68,6 +75,63 @@ impl rlp::Encodable for Action {
 	}
 }
 
+/// Item of the access list of a transaction (EIP-2930).
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct AccessListItem {
+	/// Accessed address.
+	pub address: Address,
+	/// Accessed storage keys of the address.
+	pub storage_keys: Vec<H256>,
+}
+
+impl HeapSizeOf for AccessListItem {
+	fn heap_size_of_children(&self) -> usize {
+		self.storage_keys.capacity() * mem::size_of::<H256>()
+	}
+}
+
+impl rlp::Decodable for AccessListItem {
+	fn decode(rlp: &Rlp) -> Result<Self, DecoderError> {
+		if rlp.item_count()? != 2 {
+			return Err(DecoderError::RlpIncorrectListLen);
+		}
+		Ok(AccessListItem {
+			address: rlp.val_at(0)?,
+			storage_keys: rlp.list_at(1)?,
+		})
+	}
+}
+
+impl rlp::Encodable for AccessListItem {
+	fn rlp_append(&self, s: &mut RlpStream) {
+		s.begin_list(2);
+		s.append(&self.address);
+		s.append_list(&self.storage_keys);
+	}
+}
+
+/// Access list of a transaction (EIP-2930).
+pub type AccessList = Vec<AccessListItem>;
+
+/// Prefix the payload with the transaction type (EIP-2718).
+fn typed_envelope(tx_type: u8, payload: &[u8]) -> Bytes {
+	let mut envelope = Vec::with_capacity(payload.len() + 1);
+	envelope.push(tx_type);
+	envelope.extend_from_slice(payload);
+	envelope
+}
+
+/// Hash of a transaction as embedded in a block or a network packet.
+///
+/// Typed transactions are byte strings wrapping their EIP-2718 envelope,
+/// which is hashed without the string header.
+pub fn rlp_hash(rlp: &Rlp) -> Result<H256, DecoderError> {
+	match rlp.is_list() {
+		true => Ok(keccak(rlp.as_raw())),
+		false => rlp.data().map(|envelope| keccak(envelope)),
+	}
+}
+
 /// Transaction activation condition.
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub enum Condition {
@@ -130,6 +194,37 @@ impl Transaction {
 			s.append(&0u8);
 		}
 	}
+
+	/// Append the payload of a typed transaction into RLP stream. Transactions with dynamic fees
+	/// carry `max_priority_fee_per_gas`, `gas_price` is then the max fee per gas.
+	/// The signature is given as `(y_parity, r, s)`.
+	fn rlp_append_typed_payload(
+		&self,
+		s: &mut RlpStream,
+		chain_id: u64,
+		access_list: &AccessList,
+		max_priority_fee_per_gas: Option<&U256>,
+		signature: Option<(u8, &U256, &U256)>,
+	) {
+		let fee_items = if max_priority_fee_per_gas.is_some() { 1 } else { 0 };
+		s.begin_list(if signature.is_none() { 8 } else { 11 } + fee_items);
+		s.append(&chain_id);
+		s.append(&self.nonce);
+		if let Some(max_priority_fee_per_gas) = max_priority_fee_per_gas {
+			s.append(max_priority_fee_per_gas);
+		}
+		s.append(&self.gas_price);
+		s.append(&self.gas);
+		s.append(&self.action);
+		s.append(&self.value);
+		s.append(&self.data);
+		s.append_list(access_list);
+		if let Some((y_parity, r, sig_s)) = signature {
+			s.append(&y_parity);
+			s.append(r);
+			s.append(sig_s);
+		}
+	}
 }
 
 impl HeapSizeOf for Transaction {
@@ -175,6 +270,8 @@ impl From<ethjson::transaction::Transaction> for UnverifiedTransaction {
 				value: t.value.into(),
 				data: t.data.into(),
 			},
+			access_list: None,
+			max_priority_fee_per_gas: None,
 			r: t.r.into(),
 			s: t.s.into(),
 			v: t.v.into(),
@@ -203,6 +300,8 @@ impl Transaction {
 	pub fn with_signature(self, sig: Signature, chain_id: Option<u64>) -> UnverifiedTransaction {
 		UnverifiedTransaction {
 			unsigned: self,
+			access_list: None,
+			max_priority_fee_per_gas: None,
 			r: sig.r().into(),
 			s: sig.s().into(),
 			v: signature::add_chain_replay_protection(sig.v() as u64, chain_id),
@@ -210,11 +309,71 @@ impl Transaction {
 		}.compute_hash()
 	}
 
+	/// The message hash of the transaction with an access list (EIP-2930).
+	pub fn access_list_hash(&self, chain_id: u64, access_list: &AccessList) -> H256 {
+		let mut stream = RlpStream::new();
+		self.rlp_append_typed_payload(&mut stream, chain_id, access_list, None, None);
+		keccak(&typed_envelope(ACCESS_LIST_TX_TYPE, stream.as_raw()))
+	}
+
+	/// The message hash of the transaction with dynamic fees (EIP-1559).
+	pub fn dynamic_fee_hash(&self, chain_id: u64, access_list: &AccessList, max_priority_fee_per_gas: &U256) -> H256 {
+		let mut stream = RlpStream::new();
+		self.rlp_append_typed_payload(&mut stream, chain_id, access_list, Some(max_priority_fee_per_gas), None);
+		keccak(&typed_envelope(DYNAMIC_FEE_TX_TYPE, stream.as_raw()))
+	}
+
+	/// Signs the transaction with an access list (EIP-2930) as coming from `sender`.
+	pub fn sign_with_access_list(self, secret: &Secret, chain_id: u64, access_list: AccessList) -> SignedTransaction {
+		let sig = ::ethkey::sign(secret, &self.access_list_hash(chain_id, &access_list))
+			.expect("data is valid and context has signing capabilities; qed");
+		SignedTransaction::new(self.with_access_list_signature(sig, chain_id, access_list))
+			.expect("secret is valid so it's recoverable")
+	}
+
+	/// Signs the transaction with an access list (EIP-2930) with signature.
+	pub fn with_access_list_signature(self, sig: Signature, chain_id: u64, access_list: AccessList) -> UnverifiedTransaction {
+		UnverifiedTransaction {
+			unsigned: self,
+			access_list: Some(access_list),
+			max_priority_fee_per_gas: None,
+			r: sig.r().into(),
+			s: sig.s().into(),
+			// the chain id is kept in `v` the same way as for replay protected legacy transactions
+			v: signature::add_chain_replay_protection(sig.v() as u64, Some(chain_id)),
+			hash: 0.into(),
+		}.compute_hash()
+	}
+
+	/// Signs the transaction with dynamic fees (EIP-1559) as coming from `sender`.
+	/// `gas_price` is used as the max fee per gas.
+	pub fn sign_with_dynamic_fee(self, secret: &Secret, chain_id: u64, access_list: AccessList, max_priority_fee_per_gas: U256) -> SignedTransaction {
+		let sig = ::ethkey::sign(secret, &self.dynamic_fee_hash(chain_id, &access_list, &max_priority_fee_per_gas))
+			.expect("data is valid and context has signing capabilities; qed");
+		SignedTransaction::new(self.with_dynamic_fee_signature(sig, chain_id, access_list, max_priority_fee_per_gas))
+			.expect("secret is valid so it's recoverable")
+	}
+
+	/// Signs the transaction with dynamic fees (EIP-1559) with signature.
+	pub fn with_dynamic_fee_signature(self, sig: Signature, chain_id: u64, access_list: AccessList, max_priority_fee_per_gas: U256) -> UnverifiedTransaction {
+		UnverifiedTransaction {
+			unsigned: self,
+			access_list: Some(access_list),
+			max_priority_fee_per_gas: Some(max_priority_fee_per_gas),
+			r: sig.r().into(),
+			s: sig.s().into(),
+			v: signature::add_chain_replay_protection(sig.v() as u64, Some(chain_id)),
+			hash: 0.into(),
+		}.compute_hash()
+	}
+
 	/// Useful for test incorrectly signed transactions.
 	#[cfg(test)]
 	pub fn invalid_sign(self) -> UnverifiedTransaction {
 		UnverifiedTransaction {
 			unsigned: self,
+			access_list: None,
+			max_priority_fee_per_gas: None,
 			r: U256::one(),
 			s: U256::one(),
 			v: 0,
@@ -227,6 +386,8 @@ impl Transaction {
 		SignedTransaction {
 			transaction: UnverifiedTransaction {
 				unsigned: self,
+				access_list: None,
+				max_priority_fee_per_gas: None,
 				r: U256::one(),
 				s: U256::one(),
 				v: 0,
@@ -242,6 +403,8 @@ impl Transaction {
 		SignedTransaction {
 			transaction: UnverifiedTransaction {
 				unsigned: self,
+				access_list: None,
+				max_priority_fee_per_gas: None,
 				r: U256::zero(),
 				s: U256::zero(),
 				v: chain_id,
@@ -271,6 +434,11 @@ impl Transaction {
 pub struct UnverifiedTransaction {
 	/// Plain Transaction.
 	unsigned: Transaction,
+	/// Access list of a typed transaction (EIP-2930), `None` for legacy transactions.
+	access_list: Option<AccessList>,
+	/// Max priority fee per gas of a transaction with dynamic fees (EIP-1559).
+	/// `gas_price` of such transactions is the max fee per gas.
+	max_priority_fee_per_gas: Option<U256>,
 	/// The V field of the signature; the LS bit described which half of the curve our point falls
 	/// in. The MS bits describe which chain this transaction is for. If 27/28, its for all chains.
 	v: u64,
@@ -284,7 +452,7 @@ pub struct UnverifiedTransaction {
 
 impl HeapSizeOf for UnverifiedTransaction {
 	fn heap_size_of_children(&self) -> usize {
-		self.unsigned.heap_size_of_children()
+		self.unsigned.heap_size_of_children() + self.access_list.heap_size_of_children()
 	}
 }
 
@@ -298,6 +466,10 @@ impl Deref for UnverifiedTransaction {
 
 impl rlp::Decodable for UnverifiedTransaction {
 	fn decode(d: &Rlp) -> Result<Self, DecoderError> {
+		// typed transactions are embedded as byte strings (EIP-2718)
+		if !d.is_list() {
+			return UnverifiedTransaction::decode_typed(d.data()?);
+		}
 		if d.item_count()? != 9 {
 			return Err(DecoderError::RlpIncorrectListLen);
 		}
@@ -311,6 +483,8 @@ impl rlp::Decodable for UnverifiedTransaction {
 				value: d.val_at(4)?,
 				data: d.val_at(5)?,
 			},
+			access_list: None,
+			max_priority_fee_per_gas: None,
 			v: d.val_at(6)?,
 			r: d.val_at(7)?,
 			s: d.val_at(8)?,
@@ -326,11 +500,72 @@ impl rlp::Encodable for UnverifiedTransaction {
 impl UnverifiedTransaction {
 	/// Used to compute hash of created transactions
 	fn compute_hash(mut self) -> UnverifiedTransaction {
-		let hash = keccak(&*self.rlp_bytes());
+		let hash = keccak(&self.encode_raw());
 		self.hash = hash;
 		self
 	}
 
+	/// Decode a transaction from its raw form: RLP of a legacy transaction
+	/// or EIP-2718 envelope of a typed transaction.
+	pub fn decode_raw(bytes: &[u8]) -> Result<Self, DecoderError> {
+		match bytes.first() {
+			Some(&first) if first >= 0xc0 => rlp::decode(bytes),
+			_ => UnverifiedTransaction::decode_typed(bytes),
+		}
+	}
+
+	/// Raw form of the transaction, as accepted by `decode_raw`.
+	pub fn encode_raw(&self) -> Bytes {
+		match self.access_list {
+			Some(ref access_list) => self.encode_typed(access_list),
+			None => self.rlp_bytes().into_vec(),
+		}
+	}
+
+	fn decode_typed(bytes: &[u8]) -> Result<Self, DecoderError> {
+		// transactions with dynamic fees have `max_priority_fee_per_gas` after the nonce
+		let offset = match bytes.first() {
+			Some(&ACCESS_LIST_TX_TYPE) => 0,
+			Some(&DYNAMIC_FEE_TX_TYPE) => 1,
+			Some(_) => return Err(DecoderError::Custom("Unknown transaction type.")),
+			None => return Err(DecoderError::RlpIsTooShort),
+		};
+
+		let d = Rlp::new(&bytes[1..]);
+		if d.item_count()? != 11 + offset {
+			return Err(DecoderError::RlpIncorrectListLen);
+		}
+		let chain_id: u64 = d.val_at(0)?;
+		let y_parity: u64 = d.val_at(8 + offset)?;
+		if y_parity > 1 {
+			return Err(DecoderError::Custom("Invalid signature parity."));
+		}
+		Ok(UnverifiedTransaction {
+			unsigned: Transaction {
+				nonce: d.val_at(1)?,
+				gas_price: d.val_at(2 + offset)?,
+				gas: d.val_at(3 + offset)?,
+				action: d.val_at(4 + offset)?,
+				value: d.val_at(5 + offset)?,
+				data: d.val_at(6 + offset)?,
+			},
+			access_list: Some(d.list_at(7 + offset)?),
+			max_priority_fee_per_gas: if offset == 1 { Some(d.val_at(2)?) } else { None },
+			v: signature::add_chain_replay_protection(y_parity, Some(chain_id)),
+			r: d.val_at(9 + offset)?,
+			s: d.val_at(10 + offset)?,
+			hash: keccak(bytes),
+		})
+	}
+
+	fn encode_typed(&self, access_list: &AccessList) -> Bytes {
+		let mut s = RlpStream::new();
+		let chain_id = self.chain_id().unwrap_or_default();
+		let signature = Some((self.standard_v(), &self.r, &self.s));
+		self.unsigned.rlp_append_typed_payload(&mut s, chain_id, access_list, self.max_priority_fee_per_gas.as_ref(), signature);
+		typed_envelope(self.tx_type(), s.as_raw())
+	}
+
 	/// Checks is signature is empty.
 	pub fn is_unsigned(&self) -> bool {
 		self.r.is_zero() && self.s.is_zero()
@@ -338,6 +573,10 @@ impl UnverifiedTransaction {
 
 	/// Append object with a signature into RLP stream
 	fn rlp_append_sealed_transaction(&self, s: &mut RlpStream) {
+		if let Some(ref access_list) = self.access_list {
+			s.append(&self.encode_typed(access_list));
+			return;
+		}
 		s.begin_list(9);
 		s.append(&self.nonce);
 		s.append(&self.gas_price);
@@ -355,6 +594,48 @@ impl UnverifiedTransaction {
 		&self.unsigned
 	}
 
+	/// Access list of the transaction, `None` for legacy transactions.
+	pub fn access_list(&self) -> Option<&AccessList> {
+		self.access_list.as_ref()
+	}
+
+	/// EIP-2718 type of the transaction, `0` for legacy transactions.
+	pub fn tx_type(&self) -> u8 {
+		match (&self.access_list, &self.max_priority_fee_per_gas) {
+			(_, &Some(_)) => DYNAMIC_FEE_TX_TYPE,
+			(&Some(_), _) => ACCESS_LIST_TX_TYPE,
+			_ => 0,
+		}
+	}
+
+	/// Max priority fee per gas of a transaction with dynamic fees (EIP-1559).
+	pub fn max_priority_fee_per_gas(&self) -> Option<&U256> {
+		self.max_priority_fee_per_gas.as_ref()
+	}
+
+	/// Price per gas paid by the sender given the base fee of the block (EIP-1559).
+	/// Equals `gas_price` for transactions without dynamic fees.
+	pub fn effective_gas_price(&self, base_fee: Option<U256>) -> U256 {
+		match (self.max_priority_fee_per_gas, base_fee) {
+			(Some(max_priority_fee), Some(base_fee)) => cmp::min(self.gas_price, base_fee.saturating_add(max_priority_fee)),
+			_ => self.gas_price,
+		}
+	}
+
+	/// Price per gas received by the block author given the base fee of the block (EIP-1559).
+	pub fn effective_priority_fee(&self, base_fee: Option<U256>) -> U256 {
+		self.effective_gas_price(base_fee).saturating_sub(base_fee.unwrap_or_default())
+	}
+
+	/// Get the transaction cost in gas, including the cost of the access list.
+	pub fn gas_required(&self, schedule: &Schedule) -> u64 {
+		let access_list_gas = self.access_list.as_ref().map_or(0, |access_list| access_list.iter().fold(0, |gas, item| {
+			gas + schedule.tx_access_list_address_gas as u64
+				+ item.storage_keys.len() as u64 * schedule.tx_access_list_storage_key_gas as u64
+		}));
+		self.unsigned.gas_required(schedule) + access_list_gas
+	}
+
 	pub fn standard_v(&self) -> u8 { signature::check_replay_protection(self.v) }
 
 	/// The `v` value that appears in the RLP.
@@ -390,7 +671,14 @@ impl UnverifiedTransaction {
 
 	/// Recovers the public key of the sender.
 	pub fn recover_public(&self) -> Result<Public, ethkey::Error> {
-		Ok(recover(&self.signature(), &self.unsigned.hash(self.chain_id()))?)
+		let chain_id = self.chain_id();
+		let message = match (&self.access_list, &self.max_priority_fee_per_gas) {
+			(&Some(ref access_list), &Some(ref max_priority_fee)) =>
+				self.unsigned.dynamic_fee_hash(chain_id.unwrap_or_default(), access_list, max_priority_fee),
+			(&Some(ref access_list), &None) => self.unsigned.access_list_hash(chain_id.unwrap_or_default(), access_list),
+			_ => self.unsigned.hash(chain_id),
+		};
+		Ok(recover(&self.signature(), &message)?)
 	}
 
 	/// Verify basic signature params. Does not attempt sender recovery.
@@ -399,7 +687,7 @@ impl UnverifiedTransaction {
 			self.check_low_s()?;
 		}
 		// Disallow unsigned transactions in case EIP-86 is disabled.
-		if !allow_empty_signature && self.is_unsigned() {
+		if (!allow_empty_signature || self.access_list.is_some()) && self.is_unsigned() {
 			return Err(ethkey::Error::InvalidSignature.into());
 		}
 		// EIP-86: Transactions of this form MUST have gasprice = 0, nonce = 0, value = 0, and do NOT increment the nonce of account 0.
@@ -411,6 +699,9 @@ impl UnverifiedTransaction {
 			(Some(n), Some(m)) if n == m => {},
 			_ => return Err(error::Error::InvalidChainId),
 		};
+		if self.max_priority_fee_per_gas.map_or(false, |max_priority_fee| max_priority_fee > self.gas_price) {
+			return Err(error::Error::PriorityFeeTooHigh);
+		}
 		Ok(())
 	}
 }
@@ -656,4 +947,105 @@ mod tests {
 		test_vector("f867088504a817c8088302e2489435353535353535353535353535353535353535358202008025a064b1702d9298fee62dfeccc57d322a463ad55ca201256d01f62b45b2e1c21c12a064b1702d9298fee62dfeccc57d322a463ad55ca201256d01f62b45b2e1c21c10", "0x9bddad43f934d313c2b79ca28a432dd2b7281029");
 		test_vector("f867098504a817c809830334509435353535353535353535353535353535353535358202d98025a052f8f61201b2b11a78d6e866abc9c3db2ae8631fa656bfe5cb53668255367afba052f8f61201b2b11a78d6e866abc9c3db2ae8631fa656bfe5cb53668255367afb", "0x3c24d7329e92f84f08556ceb6df1cdb0104ca49f");
 	}
+
+	#[test]
+	fn should_encode_and_decode_access_list_transaction() {
+		use ethkey::{Random, Generator};
+
+		let key = Random.generate().unwrap();
+		let access_list = vec![AccessListItem {
+			address: Address::from(0x69),
+			storage_keys: vec![H256::from(1), H256::from(2)],
+		}];
+		let t = Transaction {
+			action: Action::Call(Address::from(0x42)),
+			nonce: U256::from(42),
+			gas_price: U256::from(3000),
+			gas: U256::from(50_000),
+			value: U256::from(1),
+			data: b"Hello!".to_vec()
+		}.sign_with_access_list(&key.secret(), 69, access_list.clone());
+		assert_eq!(Address::from(keccak(key.public())), t.sender());
+		assert_eq!(t.chain_id(), Some(69));
+		assert_eq!(t.tx_type(), ACCESS_LIST_TX_TYPE);
+		assert_eq!(t.access_list(), Some(&access_list));
+
+		// raw form is the typed envelope
+		let raw = t.encode_raw();
+		assert_eq!(raw[0], ACCESS_LIST_TX_TYPE);
+		assert_eq!(t.hash(), keccak(&raw));
+		let decoded = UnverifiedTransaction::decode_raw(&raw).unwrap();
+		assert_eq!(&decoded, &*t);
+		assert_eq!(public_to_address(&decoded.recover_public().unwrap()), t.sender());
+
+		// in blocks the envelope is embedded as a byte string
+		let decoded: UnverifiedTransaction = rlp::decode(&rlp::encode(&t)).unwrap();
+		assert_eq!(&decoded, &*t);
+
+		// legacy transactions keep their RLP form
+		let legacy = Transaction::default().sign(&key.secret(), Some(69));
+		assert_eq!(UnverifiedTransaction::decode_raw(&legacy.encode_raw()).unwrap(), *legacy);
+		assert_eq!(legacy.tx_type(), 0);
+
+		assert!(UnverifiedTransaction::decode_raw(&[0x03, 0xc0]).is_err());
+	}
+
+	#[test]
+	fn should_encode_and_decode_dynamic_fee_transaction() {
+		use ethkey::{Random, Generator};
+
+		let key = Random.generate().unwrap();
+		let t = Transaction {
+			action: Action::Call(Address::from(0x42)),
+			nonce: U256::from(42),
+			gas_price: U256::from(3000),
+			gas: U256::from(50_000),
+			value: U256::from(1),
+			data: b"Hello!".to_vec()
+		}.sign_with_dynamic_fee(&key.secret(), 69, vec![], U256::from(100));
+		assert_eq!(Address::from(keccak(key.public())), t.sender());
+		assert_eq!(t.tx_type(), DYNAMIC_FEE_TX_TYPE);
+		assert_eq!(t.max_priority_fee_per_gas(), Some(&U256::from(100)));
+
+		let raw = t.encode_raw();
+		assert_eq!(raw[0], DYNAMIC_FEE_TX_TYPE);
+		let decoded = UnverifiedTransaction::decode_raw(&raw).unwrap();
+		assert_eq!(&decoded, &*t);
+		assert_eq!(public_to_address(&decoded.recover_public().unwrap()), t.sender());
+		let decoded: UnverifiedTransaction = rlp::decode(&rlp::encode(&t)).unwrap();
+		assert_eq!(&decoded, &*t);
+
+		// the sender pays at most the max fee, the author gets the rest above the base fee
+		assert_eq!(t.effective_gas_price(Some(U256::from(1000))), U256::from(1100));
+		assert_eq!(t.effective_priority_fee(Some(U256::from(1000))), U256::from(100));
+		assert_eq!(t.effective_gas_price(Some(U256::from(2950))), U256::from(3000));
+		assert_eq!(t.effective_priority_fee(Some(U256::from(2950))), U256::from(50));
+	}
+
+	#[test]
+	fn should_reject_priority_fee_above_max_fee() {
+		use ethkey::{Random, Generator};
+
+		let key = Random.generate().unwrap();
+		let t = Transaction {
+			gas_price: U256::from(10),
+			..Default::default()
+		}.sign_with_dynamic_fee(&key.secret(), 69, vec![], U256::from(11));
+		assert_eq!(t.verify_basic(true, Some(69), false), Err(error::Error::PriorityFeeTooHigh));
+	}
+
+	#[test]
+	fn should_charge_for_access_list() {
+		use ethkey::{Random, Generator};
+
+		let key = Random.generate().unwrap();
+		let schedule = Schedule::new_berlin();
+		let t = Transaction::default();
+		let legacy_gas = t.gas_required(&schedule);
+		let t = t.sign_with_access_list(&key.secret(), 1, vec![
+			AccessListItem { address: Address::from(1), storage_keys: vec![H256::from(1), H256::from(2)] },
+			AccessListItem { address: Address::from(2), storage_keys: vec![] },
+		]);
+		assert_eq!(t.gas_required(&schedule), legacy_gas + 2 * 2400 + 2 * 1900);
+	}
 }
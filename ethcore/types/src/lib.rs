@@ -43,6 +43,7 @@ pub mod security_level;
 pub mod snapshot_manifest;
 pub mod state_diff;
 pub mod trace_filter;
+pub mod transaction_lifecycle;
 pub mod tree_route;
 pub mod verification_queue_info;
 pub mod ancestry_action;
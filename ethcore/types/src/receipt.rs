@@ -45,6 +45,8 @@ pub struct Receipt {
 	pub logs: Vec<LogEntry>,
 	/// Transaction outcome.
 	pub outcome: TransactionOutcome,
+	/// EIP-2718 type of the transaction, `0` for legacy transactions.
+	pub transaction_type: u8,
 }
 
 impl Receipt {
@@ -58,12 +60,31 @@ impl Receipt {
 			}),
 			logs,
 			outcome,
+			transaction_type: 0,
 		}
 	}
-}
 
-impl Encodable for Receipt {
-	fn rlp_append(&self, s: &mut RlpStream) {
+	/// Set the EIP-2718 type of the transaction the receipt belongs to.
+	pub fn with_transaction_type(mut self, transaction_type: u8) -> Self {
+		self.transaction_type = transaction_type;
+		self
+	}
+
+	/// Receipt as stored in the receipts trie: typed receipts are not wrapped in an RLP string.
+	pub fn encode_raw(&self) -> Vec<u8> {
+		let mut payload = RlpStream::new();
+		self.rlp_append_payload(&mut payload);
+		match self.transaction_type {
+			0 => payload.out(),
+			transaction_type => {
+				let mut envelope = vec![transaction_type];
+				envelope.extend_from_slice(payload.as_raw());
+				envelope
+			},
+		}
+	}
+
+	fn rlp_append_payload(&self, s: &mut RlpStream) {
 		match self.outcome {
 			TransactionOutcome::Unknown => {
 				s.begin_list(3);
@@ -83,20 +104,44 @@ impl Encodable for Receipt {
 	}
 }
 
+impl Encodable for Receipt {
+	fn rlp_append(&self, s: &mut RlpStream) {
+		if self.transaction_type == 0 {
+			self.rlp_append_payload(s);
+		} else {
+			// typed receipts are embedded as `type || rlp(receipt)` byte strings
+			s.append(&self.encode_raw());
+		}
+	}
+}
+
 impl Decodable for Receipt {
 	fn decode(rlp: &Rlp) -> Result<Self, DecoderError> {
+		if rlp.is_data() {
+			let envelope = rlp.data()?;
+			return match envelope.split_first() {
+				Some((&transaction_type, payload)) if transaction_type != 0 && transaction_type < 0x80 => {
+					let receipt: Receipt = ::rlp::decode(payload)?;
+					Ok(receipt.with_transaction_type(transaction_type))
+				},
+				_ => Err(DecoderError::Custom("Unknown receipt type.")),
+			};
+		}
+
 		if rlp.item_count()? == 3 {
 			Ok(Receipt {
 				outcome: TransactionOutcome::Unknown,
 				gas_used: rlp.val_at(0)?,
 				log_bloom: rlp.val_at(1)?,
 				logs: rlp.list_at(2)?,
+				transaction_type: 0,
 			})
 		} else {
 			Ok(Receipt {
 				gas_used: rlp.val_at(1)?,
 				log_bloom: rlp.val_at(2)?,
 				logs: rlp.list_at(3)?,
+				transaction_type: 0,
 				outcome: {
 					let first = rlp.at(0)?;
 					if first.is_data() && first.data()?.len() <= 1 {
@@ -221,4 +266,21 @@ mod tests {
 		let decoded: Receipt = ::rlp::decode(&encoded).expect("decoding receipt failed");
 		assert_eq!(decoded, r);
 	}
+
+	#[test]
+	fn test_typed_receipt() {
+		let r = Receipt::new(
+			TransactionOutcome::StatusCode(1),
+			0x5208.into(),
+			vec![]
+		).with_transaction_type(1);
+		let raw = r.encode_raw();
+		assert_eq!(raw[0], 1);
+		assert_eq!(&raw[1..], &Receipt::new(TransactionOutcome::StatusCode(1), 0x5208.into(), vec![]).encode_raw()[..]);
+
+		let encoded = ::rlp::encode(&r);
+		assert!(::rlp::Rlp::new(&encoded).is_data());
+		let decoded: Receipt = ::rlp::decode(&encoded).expect("decoding receipt failed");
+		assert_eq!(decoded, r);
+	}
 }
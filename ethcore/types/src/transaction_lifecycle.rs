@@ -0,0 +1,170 @@
+// Copyright 2015-2018 Parity Technologies (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Transaction lifecycle types
+
+use ethereum_types::{H256, U256};
+use rlp::{Rlp, RlpStream, Encodable, Decodable, DecoderError};
+
+use BlockNumber;
+
+/// Single step in the life of a transaction seen by this node.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum LifecycleEvent {
+	/// Transaction was accepted to the transaction pool.
+	Accepted,
+	/// Transaction was never accepted to the transaction pool.
+	Rejected(String),
+	/// Transaction was sent to given number of peers.
+	Propagated(u64),
+	/// Transaction was replaced by another one with the same sender and nonce.
+	Replaced(H256),
+	/// Transaction was dropped because of pool limits, optionally pushed out by another transaction.
+	Dropped(Option<H256>),
+	/// Transaction was marked as invalid when building a block.
+	Invalid,
+	/// Transaction was canceled by the user.
+	Canceled,
+	/// Transaction was removed from the pool because it got mined or its nonce became stale.
+	Culled,
+	/// Transaction was included in an imported block.
+	Included(BlockNumber, H256),
+	/// Transaction was removed from the pool after it fell below a raised minimal gas price.
+	Demoted(U256),
+}
+
+/// Lifecycle event together with the time it happened.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LifecycleEntry {
+	/// Seconds since the unix epoch.
+	pub timestamp: u64,
+	/// What happened to the transaction.
+	pub event: LifecycleEvent,
+}
+
+impl Encodable for LifecycleEntry {
+	fn rlp_append(&self, s: &mut RlpStream) {
+		match self.event {
+			LifecycleEvent::Accepted => {
+				s.begin_list(2).append(&self.timestamp).append(&0u8);
+			},
+			LifecycleEvent::Rejected(ref reason) => {
+				s.begin_list(3).append(&self.timestamp).append(&1u8).append(reason);
+			},
+			LifecycleEvent::Propagated(peers) => {
+				s.begin_list(3).append(&self.timestamp).append(&2u8).append(&peers);
+			},
+			LifecycleEvent::Replaced(ref by) => {
+				s.begin_list(3).append(&self.timestamp).append(&3u8).append(by);
+			},
+			LifecycleEvent::Dropped(None) => {
+				s.begin_list(2).append(&self.timestamp).append(&4u8);
+			},
+			LifecycleEvent::Dropped(Some(ref by)) => {
+				s.begin_list(3).append(&self.timestamp).append(&4u8).append(by);
+			},
+			LifecycleEvent::Invalid => {
+				s.begin_list(2).append(&self.timestamp).append(&5u8);
+			},
+			LifecycleEvent::Canceled => {
+				s.begin_list(2).append(&self.timestamp).append(&6u8);
+			},
+			LifecycleEvent::Culled => {
+				s.begin_list(2).append(&self.timestamp).append(&7u8);
+			},
+			LifecycleEvent::Included(number, ref hash) => {
+				s.begin_list(4).append(&self.timestamp).append(&8u8).append(&number).append(hash);
+			},
+			LifecycleEvent::Demoted(ref gas_price) => {
+				s.begin_list(3).append(&self.timestamp).append(&9u8).append(gas_price);
+			},
+		}
+	}
+}
+
+impl Decodable for LifecycleEntry {
+	fn decode(rlp: &Rlp) -> Result<Self, DecoderError> {
+		let event = match rlp.val_at::<u8>(1)? {
+			0 => LifecycleEvent::Accepted,
+			1 => LifecycleEvent::Rejected(rlp.val_at(2)?),
+			2 => LifecycleEvent::Propagated(rlp.val_at(2)?),
+			3 => LifecycleEvent::Replaced(rlp.val_at(2)?),
+			4 if rlp.item_count()? == 2 => LifecycleEvent::Dropped(None),
+			4 => LifecycleEvent::Dropped(Some(rlp.val_at(2)?)),
+			5 => LifecycleEvent::Invalid,
+			6 => LifecycleEvent::Canceled,
+			7 => LifecycleEvent::Culled,
+			8 => LifecycleEvent::Included(rlp.val_at(2)?, rlp.val_at(3)?),
+			9 => LifecycleEvent::Demoted(rlp.val_at(2)?),
+			_ => return Err(DecoderError::Custom("Unknown lifecycle event.")),
+		};
+
+		Ok(LifecycleEntry {
+			timestamp: rlp.val_at(0)?,
+			event,
+		})
+	}
+}
+
+/// Everything this node has recorded about a transaction, oldest event first.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TransactionLifecycle {
+	/// Transaction hash.
+	pub hash: H256,
+	/// Recorded events.
+	pub events: Vec<LifecycleEntry>,
+}
+
+impl Encodable for TransactionLifecycle {
+	fn rlp_append(&self, s: &mut RlpStream) {
+		s.begin_list(2);
+		s.append(&self.hash);
+		s.append_list(&self.events);
+	}
+}
+
+impl Decodable for TransactionLifecycle {
+	fn decode(rlp: &Rlp) -> Result<Self, DecoderError> {
+		Ok(TransactionLifecycle {
+			hash: rlp.val_at(0)?,
+			events: rlp.list_at(1)?,
+		})
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn lifecycle_roundtrip() {
+		let lifecycle = TransactionLifecycle {
+			hash: 1.into(),
+			events: vec![
+				LifecycleEntry { timestamp: 10, event: LifecycleEvent::Accepted },
+				LifecycleEntry { timestamp: 11, event: LifecycleEvent::Propagated(5) },
+				LifecycleEntry { timestamp: 12, event: LifecycleEvent::Dropped(None) },
+				LifecycleEntry { timestamp: 12, event: LifecycleEvent::Dropped(Some(2.into())) },
+				LifecycleEntry { timestamp: 13, event: LifecycleEvent::Rejected("too cheap".into()) },
+				LifecycleEntry { timestamp: 14, event: LifecycleEvent::Included(100, 3.into()) },
+			],
+		};
+
+		let encoded = ::rlp::encode(&lifecycle);
+		let decoded: TransactionLifecycle = ::rlp::decode(&encoded).unwrap();
+		assert_eq!(decoded, lifecycle);
+	}
+}
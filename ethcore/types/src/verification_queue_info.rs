@@ -16,6 +16,9 @@
 
 //! Verification queue info types
 
+use std::time::Duration;
+use ethereum_types::H256;
+
 /// Verification queue status
 #[derive(Debug, Clone)]
 pub struct VerificationQueueInfo {
@@ -51,3 +54,36 @@ impl VerificationQueueInfo {
 		self.unverified_queue_size + self.verified_queue_size + self.verifying_queue_size == 0
 	}
 }
+
+/// Stage of an item in the verification queue.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum QueueStage {
+	/// Pending verification.
+	Unverified,
+	/// Being verified.
+	Verifying,
+	/// Verified, pending import.
+	Verified,
+}
+
+/// An item in the verification queue.
+#[derive(Debug, Clone, PartialEq)]
+pub struct QueuedItem {
+	/// Hash of the item.
+	pub hash: H256,
+	/// Stage the item is currently in.
+	pub stage: QueueStage,
+	/// Time elapsed since the item was queued.
+	pub wait_time: Duration,
+}
+
+/// Detailed status of the import queue.
+#[derive(Debug, Clone)]
+pub struct ImportQueueStatus {
+	/// Queue sizes.
+	pub info: VerificationQueueInfo,
+	/// Queued items, longest waiting first.
+	pub items: Vec<QueuedItem>,
+	/// Hash of the block currently being executed, if any.
+	pub executing: Option<H256>,
+}
@@ -0,0 +1,99 @@
+// Copyright 2015-2018 Parity Technologies (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Access list of a transaction (EIP-2929).
+
+use std::collections::HashSet;
+use ethereum_types::{H256, Address};
+
+#[derive(Debug, Clone, PartialEq)]
+enum Access {
+	Address(Address),
+	StorageKey(Address, H256),
+}
+
+/// Addresses and storage keys already accessed by a transaction.
+///
+/// A single list is shared by all call frames of the transaction. Accesses of a
+/// failed frame are undone by reverting to the checkpoint taken when it started.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct AccessList {
+	addresses: HashSet<Address>,
+	storage_keys: HashSet<(Address, H256)>,
+	journal: Vec<Access>,
+}
+
+impl AccessList {
+	/// Checks if the address was accessed.
+	pub fn contains_address(&self, address: &Address) -> bool {
+		self.addresses.contains(address)
+	}
+
+	/// Marks the address as accessed.
+	pub fn insert_address(&mut self, address: Address) {
+		if self.addresses.insert(address) {
+			self.journal.push(Access::Address(address));
+		}
+	}
+
+	/// Checks if the storage key of the address was accessed.
+	pub fn contains_storage_key(&self, address: &Address, key: &H256) -> bool {
+		self.storage_keys.contains(&(*address, *key))
+	}
+
+	/// Marks the storage key of the address as accessed.
+	pub fn insert_storage_key(&mut self, address: Address, key: H256) {
+		if self.storage_keys.insert((address, key)) {
+			self.journal.push(Access::StorageKey(address, key));
+		}
+	}
+
+	/// Returns a checkpoint to revert to, undoing all accesses made after it.
+	pub fn checkpoint(&self) -> usize {
+		self.journal.len()
+	}
+
+	/// Forgets all accesses made since the checkpoint.
+	pub fn revert_to_checkpoint(&mut self, checkpoint: usize) {
+		for access in self.journal.drain(checkpoint..) {
+			match access {
+				Access::Address(address) => { self.addresses.remove(&address); },
+				Access::StorageKey(address, key) => { self.storage_keys.remove(&(address, key)); },
+			}
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn should_revert_to_checkpoint() {
+		let mut access_list = AccessList::default();
+		access_list.insert_address(1.into());
+
+		let checkpoint = access_list.checkpoint();
+		access_list.insert_address(1.into());
+		access_list.insert_address(2.into());
+		access_list.insert_storage_key(1.into(), 3.into());
+		access_list.revert_to_checkpoint(checkpoint);
+
+		assert!(access_list.contains_address(&1.into()));
+		assert!(!access_list.contains_address(&2.into()));
+		assert!(!access_list.contains_storage_key(&1.into(), &3.into()));
+	}
+}
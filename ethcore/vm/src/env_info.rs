@@ -44,6 +44,8 @@ pub struct EnvInfo {
 	pub last_hashes: Arc<LastHashes>,
 	/// The gas used.
 	pub gas_used: U256,
+	/// The block base fee (EIP-1559), if any.
+	pub base_fee: Option<U256>,
 }
 
 impl Default for EnvInfo {
@@ -56,6 +58,7 @@ impl Default for EnvInfo {
 			gas_limit: 0.into(),
 			last_hashes: Arc::new(vec![]),
 			gas_used: 0.into(),
+			base_fee: None,
 		}
 	}
 }
@@ -71,6 +74,7 @@ impl From<ethjson::vm::Env> for EnvInfo {
 			timestamp: e.timestamp.into(),
 			last_hashes: Arc::new((1..cmp::min(number + 1, 257)).map(|i| keccak(format!("{}", number - i).as_bytes())).collect()),
 			gas_used: U256::default(),
+			base_fee: None,
 		}
 	}
 }
@@ -133,6 +133,9 @@ pub trait Ext {
 	/// Returns environment info.
 	fn env_info(&self) -> &EnvInfo;
 
+	/// Returns the chain ID of the blockchain
+	fn chain_id(&self) -> u64;
+
 	/// Returns current depth of execution.
 	///
 	/// If contract A calls contract B, and contract B calls C,
@@ -156,4 +159,16 @@ pub trait Ext {
 
 	/// Check if running in static context.
 	fn is_static(&self) -> bool;
+
+	/// Check if the address is in the access list of the transaction (EIP-2929).
+	fn access_list_contains_address(&self, address: &Address) -> bool;
+
+	/// Add the address to the access list of the transaction (EIP-2929).
+	fn access_list_insert_address(&mut self, address: Address);
+
+	/// Check if the storage key of the current contract is in the access list of the transaction (EIP-2929).
+	fn access_list_contains_storage_key(&self, key: &H256) -> bool;
+
+	/// Add the storage key of the current contract to the access list of the transaction (EIP-2929).
+	fn access_list_insert_storage_key(&mut self, key: H256);
 }
@@ -25,6 +25,7 @@ extern crate keccak_hash as hash;
 extern crate patricia_trie_ethereum as ethtrie;
 extern crate patricia_trie as trie;
 
+mod access_list;
 mod action_params;
 mod call_type;
 mod env_info;
@@ -35,6 +36,7 @@ mod error;
 
 pub mod tests;
 
+pub use access_list::AccessList;
 pub use action_params::{ActionParams, ActionValue, ParamsType};
 pub use call_type::CallType;
 pub use env_info::{EnvInfo, LastHashes};
@@ -28,6 +28,10 @@ pub struct Schedule {
 	pub have_revert: bool,
 	/// Does it have a EXTCODEHASH instruction
 	pub have_extcodehash: bool,
+	/// Does it have a CHAINID instruction
+	pub have_chain_id: bool,
+	/// Does it have a SELFBALANCE instruction
+	pub have_selfbalance: bool,
 	/// VM stack limit
 	pub stack_limit: usize,
 	/// Max number of nested calls/creates
@@ -119,6 +123,24 @@ pub struct Schedule {
 	pub kill_dust: CleanDustMode,
 	/// Enable EIP-1283 rules
 	pub eip1283: bool,
+	/// Enable EIP-1706 rules (SSTORE fails if gas left is not greater than the call stipend)
+	pub eip1706: bool,
+	/// Gas price of SSTORE of a dirty slot or without changing the value. `sload_gas` if not set (EIP-2200).
+	pub sstore_dirty_gas: Option<usize>,
+	/// Enable EIP-2929 rules (state access gas prices depend on the access list of the transaction)
+	pub eip2929: bool,
+	/// Gas price of an access to an account or storage slot in the access list (EIP-2929)
+	pub warm_storage_read_cost: usize,
+	/// Gas price of a SUICIDE to an account not in the access list (EIP-2929)
+	pub cold_account_access_cost: usize,
+	/// Typed transactions with access lists are allowed (EIP-2930)
+	pub eip2930: bool,
+	/// Gas price of an address in the access list of a transaction (EIP-2930)
+	pub tx_access_list_address_gas: usize,
+	/// Gas price of a storage key in the access list of a transaction (EIP-2930)
+	pub tx_access_list_storage_key_gas: usize,
+	/// Transactions with dynamic fees are allowed and the base fee is burnt (EIP-1559)
+	pub eip1559: bool,
 	/// VM execution does not increase null signed address nonce if this field is true.
 	pub keep_unsigned_nonce: bool,
 	/// Wasm extra schedule settings, if wasm activated
@@ -126,6 +148,7 @@ pub struct Schedule {
 }
 
 /// Wasm cost table
+#[derive(Debug, Clone, PartialEq)]
 pub struct WasmCosts {
 	/// Default opcode cost
 	pub regular: u32,
@@ -210,6 +233,8 @@ impl Schedule {
 			have_return_data: false,
 			have_bitwise_shifting: false,
 			have_extcodehash: false,
+			have_chain_id: false,
+			have_selfbalance: false,
 			stack_limit: 1024,
 			max_depth: 1024,
 			tier_step_gas: [0, 2, 3, 5, 8, 10, 20, 0],
@@ -253,6 +278,15 @@ impl Schedule {
 			have_static_call: false,
 			kill_dust: CleanDustMode::Off,
 			eip1283: false,
+			eip1706: false,
+			sstore_dirty_gas: None,
+			eip2929: false,
+			warm_storage_read_cost: 100,
+			cold_account_access_cost: 2600,
+			eip2930: false,
+			tx_access_list_address_gas: 2400,
+			tx_access_list_storage_key_gas: 1900,
+			eip1559: false,
 			keep_unsigned_nonce: false,
 			wasm: None,
 		}
@@ -272,6 +306,38 @@ impl Schedule {
 	pub fn new_constantinople() -> Schedule {
 		let mut schedule = Self::new_byzantium();
 		schedule.have_bitwise_shifting = true;
+		schedule.have_extcodehash = true;
+		schedule
+	}
+
+	/// Schedule for the Istanbul fork of the Ethereum main net.
+	pub fn new_istanbul() -> Schedule {
+		let mut schedule = Self::new_constantinople();
+		schedule.eip1283 = true;
+		schedule.eip1706 = true;
+		schedule.sstore_dirty_gas = Some(800);
+		schedule.have_chain_id = true;
+		schedule.have_selfbalance = true;
+		schedule.sload_gas = 800;
+		schedule.balance_gas = 700;
+		schedule.extcodehash_gas = 700;
+		schedule
+	}
+
+	/// Schedule for the Berlin fork of the Ethereum main net.
+	pub fn new_berlin() -> Schedule {
+		let mut schedule = Self::new_istanbul();
+		schedule.eip2929 = true;
+		schedule.eip2930 = true;
+		// prices of cold accesses, warm ones cost `warm_storage_read_cost`
+		schedule.sload_gas = 2100;
+		schedule.call_gas = 2600;
+		schedule.balance_gas = 2600;
+		schedule.extcodesize_gas = 2600;
+		schedule.extcodecopy_base_gas = 2600;
+		schedule.extcodehash_gas = 2600;
+		schedule.sstore_dirty_gas = Some(100);
+		schedule.sstore_reset_gas = 2900;
 		schedule
 	}
 
@@ -284,6 +350,8 @@ impl Schedule {
 			have_return_data: false,
 			have_bitwise_shifting: false,
 			have_extcodehash: false,
+			have_chain_id: false,
+			have_selfbalance: false,
 			stack_limit: 1024,
 			max_depth: 1024,
 			tier_step_gas: [0, 2, 3, 5, 8, 10, 20, 0],
@@ -327,6 +395,15 @@ impl Schedule {
 			have_static_call: false,
 			kill_dust: CleanDustMode::Off,
 			eip1283: false,
+			eip1706: false,
+			sstore_dirty_gas: None,
+			eip2929: false,
+			warm_storage_read_cost: 100,
+			cold_account_access_cost: 2600,
+			eip2930: false,
+			tx_access_list_address_gas: 2400,
+			tx_access_list_storage_key_gas: 1900,
+			eip1559: false,
 			keep_unsigned_nonce: false,
 			wasm: None,
 		}
@@ -20,7 +20,7 @@ use std::collections::{HashMap, HashSet};
 use ethereum_types::{U256, H256, Address};
 use bytes::Bytes;
 use {
-	CallType, Schedule, EnvInfo,
+	AccessList, CallType, Schedule, EnvInfo,
 	ReturnData, Ext, ContractCreateResult, MessageCallResult,
 	CreateContractAddress, Result, GasLeft,
 };
@@ -66,6 +66,8 @@ pub struct FakeExt {
 	pub balances: HashMap<Address, U256>,
 	pub tracing: bool,
 	pub is_static: bool,
+	pub chain_id: u64,
+	pub access_list: AccessList,
 }
 
 // similar to the normal `finalize` function, but ignoring NeedsReturn.
@@ -97,6 +99,20 @@ impl FakeExt {
 		ext
 	}
 
+	/// New fake externalities with Istanbul opcodes enabled
+	pub fn new_istanbul() -> Self {
+		let mut ext = FakeExt::default();
+		ext.schedule = Schedule::new_istanbul();
+		ext
+	}
+
+	/// New fake externalities with Berlin schedule rules
+	pub fn new_berlin() -> Self {
+		let mut ext = FakeExt::default();
+		ext.schedule = Schedule::new_berlin();
+		ext
+	}
+
 	/// Alter fake externalities to allow wasm
 	pub fn with_wasm(mut self) -> Self {
 		self.schedule.wasm = Some(Default::default());
@@ -212,6 +228,10 @@ impl Ext for FakeExt {
 		&self.info
 	}
 
+	fn chain_id(&self) -> u64 {
+		self.chain_id
+	}
+
 	fn depth(&self) -> usize {
 		self.depth
 	}
@@ -231,4 +251,20 @@ impl Ext for FakeExt {
 	fn trace_next_instruction(&mut self, _pc: usize, _instruction: u8, _gas: U256) -> bool {
 		self.tracing
 	}
+
+	fn access_list_contains_address(&self, address: &Address) -> bool {
+		self.access_list.contains_address(address)
+	}
+
+	fn access_list_insert_address(&mut self, address: Address) {
+		self.access_list.insert_address(address)
+	}
+
+	fn access_list_contains_storage_key(&self, key: &H256) -> bool {
+		self.access_list.contains_storage_key(&Address::default(), key)
+	}
+
+	fn access_list_insert_storage_key(&mut self, key: H256) {
+		self.access_list.insert_storage_key(Address::default(), key)
+	}
 }
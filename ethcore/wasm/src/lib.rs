@@ -32,6 +32,7 @@ mod tests;
 mod env;
 mod panic_payload;
 mod parser;
+pub mod tracer;
 
 use vm::{GasLeft, ReturnData, ActionParams};
 use wasmi::{Error as InterpreterError, Trap};
@@ -0,0 +1,324 @@
+// Copyright 2015-2018 Parity Technologies (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Host for user-supplied wasm tracers.
+//!
+//! A tracer is a wasm module which is fed every executed EVM instruction and finally asked
+//! to produce a result. It must import its memory as `env.memory` and export:
+//!
+//! - `step(pc: i32, op: i32, gas: i64, gas_cost: i64, depth: i32)`, called for every instruction,
+//! - `result()`, called once at the end; it should pass its output to `ret`.
+//!
+//! The following functions may be imported from `env`:
+//!
+//! - `stack_length() -> i32`, number of items on the stack of the current step,
+//! - `stack_peek(index: i32, ptr: i32)`, writes the `index`-th item from the top of the stack
+//!   as 32 big-endian bytes to `ptr`,
+//! - `memory_length() -> i32`, size of the EVM memory of the current step,
+//! - `memory_read(offset: i32, len: i32, ptr: i32)`, copies EVM memory to `ptr`, zero-padded,
+//! - `ret(ptr: i32, len: i32)`, sets the output of the tracer.
+//!
+//! Execution is metered and aborted once the given gas limit is exhausted.
+
+use std::fmt;
+use std::cell::RefCell;
+
+use ethereum_types::U256;
+use parity_wasm::elements::{self, Deserialize};
+use wasm_utils::{self, rules};
+use wasmi::{
+	self, Externals, FuncInstance, FuncRef, ImportsBuilder, MemoryDescriptor, MemoryInstance, MemoryRef,
+	ModuleImportResolver, ModuleInstance, RuntimeArgs, RuntimeValue, Signature, Trap, TrapKind, ValueType,
+	memory_units,
+};
+
+/// Maximal memory of a tracer, in wasm pages (64kb).
+const MAX_MEMORY: u32 = 16;
+/// Maximal stack height of a tracer.
+const MAX_STACK_HEIGHT: u32 = 64 * 1024;
+
+const GAS_FUNC: usize = 0;
+const STACK_LENGTH_FUNC: usize = 10;
+const STACK_PEEK_FUNC: usize = 20;
+const MEMORY_LENGTH_FUNC: usize = 30;
+const MEMORY_READ_FUNC: usize = 40;
+const RET_FUNC: usize = 50;
+
+/// A single executed instruction, as seen by the tracer.
+pub struct TracerStep<'a> {
+	/// Program counter.
+	pub pc: usize,
+	/// Instruction.
+	pub instruction: u8,
+	/// Gas available before executing the instruction.
+	pub gas: u64,
+	/// Gas cost of the instruction.
+	pub gas_cost: u64,
+	/// Call depth.
+	pub depth: usize,
+	/// Stack, bottom item first.
+	pub stack: &'a [U256],
+	/// Memory contents.
+	pub memory: &'a [u8],
+}
+
+/// Tracer execution error.
+#[derive(Debug)]
+pub enum TracerError {
+	/// The module could not be decoded or prepared.
+	InvalidModule(String),
+	/// The module could not be instantiated.
+	Instantiation(String),
+	/// The module trapped.
+	Trap(String),
+	/// The gas limit was exhausted.
+	GasLimit,
+	/// The module accessed memory out of bounds.
+	MemoryAccessViolation,
+}
+
+impl fmt::Display for TracerError {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		match *self {
+			TracerError::InvalidModule(ref e) => write!(f, "Invalid tracer module: {}", e),
+			TracerError::Instantiation(ref e) => write!(f, "Tracer instantiation failed: {}", e),
+			TracerError::Trap(ref e) => write!(f, "Tracer trapped: {}", e),
+			TracerError::GasLimit => write!(f, "Tracer gas limit reached"),
+			TracerError::MemoryAccessViolation => write!(f, "Tracer memory access violation"),
+		}
+	}
+}
+
+impl wasmi::HostError for TracerError { }
+
+impl From<wasmi::Error> for TracerError {
+	fn from(err: wasmi::Error) -> Self {
+		match err {
+			wasmi::Error::Trap(trap) => trap.into(),
+			wasmi::Error::Memory(_) => TracerError::MemoryAccessViolation,
+			e => TracerError::Trap(format!("{:?}", e)),
+		}
+	}
+}
+
+impl From<Trap> for TracerError {
+	fn from(trap: Trap) -> Self {
+		match *trap.kind() {
+			TrapKind::Host(ref boxed) => match boxed.downcast_ref::<TracerError>() {
+				Some(&TracerError::GasLimit) => TracerError::GasLimit,
+				Some(&TracerError::MemoryAccessViolation) => TracerError::MemoryAccessViolation,
+				Some(e) => TracerError::Trap(e.to_string()),
+				None => TracerError::Trap("unknown host error".into()),
+			},
+			ref kind => TracerError::Trap(format!("{:?}", kind)),
+		}
+	}
+}
+
+fn host(params: &[ValueType], ret: Option<ValueType>, idx: usize) -> FuncRef {
+	FuncInstance::alloc_host(Signature::new(params, ret), idx)
+}
+
+#[derive(Default)]
+struct ImportResolver {
+	memory: RefCell<Option<MemoryRef>>,
+}
+
+impl ImportResolver {
+	fn memory_ref(&self) -> Result<MemoryRef, TracerError> {
+		self.memory.borrow().clone().ok_or_else(|| TracerError::Instantiation("memory is not imported".into()))
+	}
+}
+
+impl ModuleImportResolver for ImportResolver {
+	fn resolve_func(&self, field_name: &str, _signature: &Signature) -> Result<FuncRef, wasmi::Error> {
+		use wasmi::ValueType::*;
+
+		Ok(match field_name {
+			"gas" => host(&[I32], None, GAS_FUNC),
+			"stack_length" => host(&[], Some(I32), STACK_LENGTH_FUNC),
+			"stack_peek" => host(&[I32, I32], None, STACK_PEEK_FUNC),
+			"memory_length" => host(&[], Some(I32), MEMORY_LENGTH_FUNC),
+			"memory_read" => host(&[I32, I32, I32], None, MEMORY_READ_FUNC),
+			"ret" => host(&[I32, I32], None, RET_FUNC),
+			_ => return Err(wasmi::Error::Instantiation(format!("Export {} not found", field_name))),
+		})
+	}
+
+	fn resolve_memory(&self, field_name: &str, descriptor: &MemoryDescriptor) -> Result<MemoryRef, wasmi::Error> {
+		if field_name != "memory" {
+			return Err(wasmi::Error::Instantiation("Memory imported under unknown name".to_owned()));
+		}
+
+		let effective_max = descriptor.maximum().unwrap_or(MAX_MEMORY);
+		if descriptor.initial() > MAX_MEMORY || effective_max > MAX_MEMORY {
+			return Err(wasmi::Error::Instantiation("Module requested too much memory".to_owned()));
+		}
+
+		let mem = MemoryInstance::alloc(
+			memory_units::Pages(descriptor.initial() as usize),
+			Some(memory_units::Pages(effective_max as usize)),
+		)?;
+		*self.memory.borrow_mut() = Some(mem.clone());
+		Ok(mem)
+	}
+}
+
+struct Host<'a> {
+	memory: MemoryRef,
+	gas_left: u64,
+	step: Option<TracerStep<'a>>,
+	result: Vec<u8>,
+}
+
+impl<'a> Host<'a> {
+	fn stack(&self) -> &[U256] {
+		self.step.as_ref().map_or(&[][..], |step| step.stack)
+	}
+
+	fn evm_memory(&self) -> &[u8] {
+		self.step.as_ref().map_or(&[][..], |step| step.memory)
+	}
+
+	fn charge(&mut self, amount: u64) -> Result<(), Trap> {
+		match self.gas_left.checked_sub(amount) {
+			Some(left) => {
+				self.gas_left = left;
+				Ok(())
+			},
+			None => Err(TracerError::GasLimit.into()),
+		}
+	}
+
+	fn gas(&mut self, args: RuntimeArgs) -> Result<(), Trap> {
+		let amount: u32 = args.nth_checked(0)?;
+		self.charge(amount as u64)
+	}
+
+	fn stack_peek(&mut self, args: RuntimeArgs) -> Result<(), Trap> {
+		let index: u32 = args.nth_checked(0)?;
+		let ptr: u32 = args.nth_checked(1)?;
+
+		let mut value = [0u8; 32];
+		let stack = self.stack();
+		if (index as usize) < stack.len() {
+			stack[stack.len() - 1 - index as usize].to_big_endian(&mut value);
+		}
+		self.memory.set(ptr, &value).map_err(|_| TracerError::MemoryAccessViolation.into())
+	}
+
+	fn memory_read(&mut self, args: RuntimeArgs) -> Result<(), Trap> {
+		let offset: u32 = args.nth_checked(0)?;
+		let len: u32 = args.nth_checked(1)?;
+		let ptr: u32 = args.nth_checked(2)?;
+
+		// charge for the copy, so that the amount of work done is bounded
+		self.charge(len as u64)?;
+
+		let mut data = vec![0u8; len as usize];
+		let memory = self.evm_memory();
+		let start = ::std::cmp::min(offset as usize, memory.len());
+		let end = ::std::cmp::min(offset as usize + len as usize, memory.len());
+		data[..end - start].copy_from_slice(&memory[start..end]);
+		self.memory.set(ptr, &data).map_err(|_| TracerError::MemoryAccessViolation.into())
+	}
+
+	fn ret(&mut self, args: RuntimeArgs) -> Result<(), Trap> {
+		let ptr: u32 = args.nth_checked(0)?;
+		let len: u32 = args.nth_checked(1)?;
+
+		self.result = self.memory.get(ptr, len as usize).map_err(|_| Trap::from(TracerError::MemoryAccessViolation))?;
+		Ok(())
+	}
+}
+
+impl<'a> Externals for Host<'a> {
+	fn invoke_index(&mut self, index: usize, args: RuntimeArgs) -> Result<Option<RuntimeValue>, Trap> {
+		match index {
+			GAS_FUNC => self.gas(args).map(|_| None),
+			STACK_LENGTH_FUNC => Ok(Some(RuntimeValue::I32(self.stack().len() as i32))),
+			STACK_PEEK_FUNC => self.stack_peek(args).map(|_| None),
+			MEMORY_LENGTH_FUNC => Ok(Some(RuntimeValue::I32(self.evm_memory().len() as i32))),
+			MEMORY_READ_FUNC => self.memory_read(args).map(|_| None),
+			RET_FUNC => self.ret(args).map(|_| None),
+			_ => panic!("env module doesn't provide function at index {}", index),
+		}
+	}
+}
+
+fn prepare_module(code: &[u8]) -> Result<wasmi::Module, TracerError> {
+	let module = elements::Module::deserialize(&mut ::std::io::Cursor::new(code))
+		.map_err(|e| TracerError::InvalidModule(format!("{:?}", e)))?;
+
+	if module.memory_section().map_or(false, |ms| ms.entries().len() > 0) {
+		return Err(TracerError::InvalidModule("internal memory".into()));
+	}
+
+	let module = wasm_utils::inject_gas_counter(module, &rules::Set::new(1, Default::default()))
+		.map_err(|_| TracerError::InvalidModule("bytecode invalid".into()))?;
+	let module = wasm_utils::stack_height::inject_limiter(module, MAX_STACK_HEIGHT)
+		.map_err(|_| TracerError::InvalidModule("stack limiter failure".into()))?;
+
+	wasmi::Module::from_parity_wasm_module(module).map_err(|e| TracerError::InvalidModule(format!("{:?}", e)))
+}
+
+/// Runs the tracer module `code` over the given steps and returns its output.
+pub fn run<'a, I>(code: &[u8], gas_limit: u64, steps: I) -> Result<Vec<u8>, TracerError> where
+	I: IntoIterator<Item = TracerStep<'a>>,
+{
+	let module = prepare_module(code)?;
+	let resolver = ImportResolver::default();
+	let instance = ModuleInstance::new(&module, &ImportsBuilder::new().with_resolver("env", &resolver))
+		.map_err(|e| TracerError::Instantiation(format!("{:?}", e)))?;
+
+	let mut host = Host {
+		memory: resolver.memory_ref()?,
+		gas_left: gas_limit,
+		step: None,
+		result: Vec::new(),
+	};
+
+	let instance = instance.run_start(&mut host)?;
+
+	for step in steps {
+		let args = [
+			RuntimeValue::I32(step.pc as i32),
+			RuntimeValue::I32(step.instruction as i32),
+			RuntimeValue::I64(step.gas as i64),
+			RuntimeValue::I64(step.gas_cost as i64),
+			RuntimeValue::I32(step.depth as i32),
+		];
+		host.step = Some(step);
+		instance.invoke_export("step", &args, &mut host)?;
+	}
+
+	host.step = None;
+	instance.invoke_export("result", &[], &mut host)?;
+	Ok(host.result)
+}
+
+#[cfg(test)]
+mod tests {
+	use super::{run, TracerError};
+
+	#[test]
+	fn should_reject_invalid_module() {
+		match run(&[0x00, 0x61, 0x73], 1_000, Vec::new()) {
+			Err(TracerError::InvalidModule(_)) => {},
+			other => panic!("unexpected result: {:?}", other),
+		}
+	}
+}
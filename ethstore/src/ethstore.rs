@@ -693,6 +693,7 @@ mod tests {
 	use ethkey::{Random, Generator, KeyPair};
 	use secret_store::{SimpleSecretStore, SecretStore, SecretVaultRef, StoreAccountRef, Derivation};
 	use super::{EthStore, EthMultiStore};
+	use json::AccountLabels;
 	use self::tempdir::TempDir;
 	use ethereum_types::H256;
 
@@ -920,6 +921,31 @@ mod tests {
 		assert_eq!(store.meta(&StoreAccountRef::vault("vault2", account3.address)).unwrap(), r#"{"vault":"vault2"}"#);
 	}
 
+	#[test]
+	fn should_keep_account_labels_when_moving_between_vaults() {
+		// given
+		let mut dir = RootDiskDirectoryGuard::new();
+		let store = EthStore::open(dir.key_dir.take().unwrap()).unwrap();
+		let name = "vault"; let password = "password".into();
+		store.create_vault(name, &password).unwrap();
+		let account = store.insert_account(SecretVaultRef::Root, keypair().secret().clone(), &password).unwrap();
+		let labels = AccountLabels {
+			tags: vec!["ops".to_owned()],
+			fields: vec![("role".to_owned(), "signer".to_owned())].into_iter().collect(),
+		};
+		let meta = labels.insert_to_json_meta(&store.meta(&account).unwrap()).unwrap();
+		store.set_meta(&account, meta).unwrap();
+
+		// when
+		let account = store.change_account_vault(SecretVaultRef::Vault(name.to_owned()), account).unwrap();
+
+		// then
+		assert_eq!(AccountLabels::from_json_meta(&store.meta(&account).unwrap()), labels);
+		store.close_vault(name).unwrap();
+		store.open_vault(name, &password).unwrap();
+		assert_eq!(AccountLabels::from_json_meta(&store.meta(&account).unwrap()), labels);
+	}
+
 	#[test]
 	fn should_not_remove_account_when_moving_to_self() {
 		// given
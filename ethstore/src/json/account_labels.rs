@@ -0,0 +1,121 @@
+// Copyright 2015-2018 Parity Technologies (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+use std::collections::BTreeMap;
+use serde::de::Error;
+use serde_json;
+use serde_json::value::Value;
+use serde_json::error;
+
+/// Meta key name for tags field
+const TAGS_META_KEY: &'static str = "tags";
+/// Meta key name for structured fields
+const FIELDS_META_KEY: &'static str = "fields";
+
+/// User-defined labels of an account, stored in its JSON meta field
+/// next to other keys (like the vault name).
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct AccountLabels {
+	/// Free-form tags
+	pub tags: Vec<String>,
+	/// Structured fields, e.g. `role` or `owner`
+	pub fields: BTreeMap<String, String>,
+}
+
+impl AccountLabels {
+	/// Read labels from the JSON meta field. Meta which is not a JSON object has no labels.
+	pub fn from_json_meta(meta: &str) -> Self {
+		let meta: Value = match serde_json::from_str(meta) {
+			Ok(meta) => meta,
+			Err(_) => return AccountLabels::default(),
+		};
+
+		AccountLabels {
+			tags: meta.get(TAGS_META_KEY)
+				.and_then(|tags| serde_json::from_value(tags.clone()).ok())
+				.unwrap_or_default(),
+			fields: meta.get(FIELDS_META_KEY)
+				.and_then(|fields| serde_json::from_value(fields.clone()).ok())
+				.unwrap_or_default(),
+		}
+	}
+
+	/// Write labels to the JSON meta field, preserving the other keys.
+	pub fn insert_to_json_meta(&self, meta: &str) -> Result<String, error::Error> {
+		let mut meta = if meta.is_empty() {
+			Value::Object(serde_json::Map::new())
+		} else {
+			serde_json::from_str(meta)?
+		};
+
+		if let Some(meta_obj) = meta.as_object_mut() {
+			meta_obj.insert(TAGS_META_KEY.to_owned(), serde_json::to_value(&self.tags)?);
+			meta_obj.insert(FIELDS_META_KEY.to_owned(), serde_json::to_value(&self.fields)?);
+			serde_json::to_string(meta_obj)
+		} else {
+			Err(error::Error::custom("Meta is expected to be a serialized JSON object"))
+		}
+	}
+
+	/// Returns true if the labels contain all given tags and field values.
+	pub fn matches(&self, tags: &[String], fields: &BTreeMap<String, String>) -> bool {
+		tags.iter().all(|tag| self.tags.contains(tag))
+			&& fields.iter().all(|(key, value)| self.fields.get(key) == Some(value))
+	}
+}
+
+#[cfg(test)]
+mod test {
+	use std::collections::BTreeMap;
+	use json::AccountLabels;
+
+	fn labels() -> AccountLabels {
+		let mut fields = BTreeMap::new();
+		fields.insert("role".to_owned(), "hot".to_owned());
+		AccountLabels {
+			tags: vec!["ops".to_owned()],
+			fields: fields,
+		}
+	}
+
+	#[test]
+	fn should_insert_labels_to_json_meta() {
+		assert_eq!(labels().insert_to_json_meta("").unwrap(), r#"{"fields":{"role":"hot"},"tags":["ops"]}"#);
+		assert_eq!(labels().insert_to_json_meta(r#"{"tags":["old"],"vault":"MyVault"}"#).unwrap(), r#"{"fields":{"role":"hot"},"tags":["ops"],"vault":"MyVault"}"#);
+		assert!(labels().insert_to_json_meta(r#""string""#).is_err());
+		assert!(labels().insert_to_json_meta(r#"{foo: 69}"#).is_err());
+	}
+
+	#[test]
+	fn should_read_labels_from_json_meta() {
+		assert_eq!(AccountLabels::from_json_meta(r#"{"fields":{"role":"hot"},"tags":["ops"],"vault":"MyVault"}"#), labels());
+		assert_eq!(AccountLabels::from_json_meta(r#"{"tags":"ops"}"#), AccountLabels::default());
+		assert_eq!(AccountLabels::from_json_meta(r#"{foo: 69}"#), AccountLabels::default());
+	}
+
+	#[test]
+	fn should_match_labels() {
+		let labels = labels();
+		let mut fields = BTreeMap::new();
+		assert!(labels.matches(&[], &fields));
+		assert!(labels.matches(&["ops".to_owned()], &fields));
+		assert!(!labels.matches(&["ops".to_owned(), "cold".to_owned()], &fields));
+		fields.insert("role".to_owned(), "hot".to_owned());
+		assert!(labels.matches(&[], &fields));
+		fields.insert("role".to_owned(), "cold".to_owned());
+		assert!(!labels.matches(&[], &fields));
+	}
+}
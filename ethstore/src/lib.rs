@@ -63,6 +63,7 @@ pub use self::error::Error;
 pub use self::ethstore::{EthStore, EthMultiStore};
 pub use self::import::{import_account, import_accounts, read_geth_accounts};
 pub use self::json::OpaqueKeyFile as KeyFile;
+pub use self::json::AccountLabels;
 pub use self::presale::PresaleWallet;
 pub use self::secret_store::{
 	SecretVaultRef, StoreAccountRef, SimpleSecretStore, SecretStore,
@@ -0,0 +1,97 @@
+// Copyright 2015-2018 Parity Technologies (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Spec EVM limits deserialization.
+
+use serde::Deserializer;
+use serde::de::{Error, Unexpected};
+use ethereum_types::U256;
+use uint::{self, Uint};
+
+/// Maximal value of `stackLimit` and `maxCallDepth`.
+pub const MAX_LIMIT: u64 = 16 * 1024;
+
+/// EVM limits and memory costs. Missing entries fall back to the values of the Ethereum main net.
+#[derive(Debug, Default, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct EvmLimits {
+	/// Maximal number of items on the stack.
+	#[serde(rename="stackLimit", default, deserialize_with="validate_limit")]
+	pub stack_limit: Option<Uint>,
+	/// Maximal depth of nested calls and creates.
+	#[serde(rename="maxCallDepth", default, deserialize_with="validate_limit")]
+	pub max_call_depth: Option<Uint>,
+	/// Linear memory cost, per word.
+	#[serde(rename="memoryGas")]
+	pub memory_gas: Option<Uint>,
+	/// Divisor of the quadratic memory cost.
+	#[serde(rename="quadCoeffDiv", default, deserialize_with="uint::validate_optional_non_zero")]
+	pub quad_coeff_div: Option<Uint>,
+}
+
+fn validate_limit<'de, D>(d: D) -> Result<Option<Uint>, D::Error> where D: Deserializer<'de> {
+	let value = uint::validate_optional_non_zero(d)?;
+
+	if let Some(value) = value {
+		if value.0 > U256::from(MAX_LIMIT) {
+			return Err(Error::invalid_value(Unexpected::Unsigned(value.into()), &"a value not greater than 16384"))
+		}
+	}
+
+	Ok(value)
+}
+
+#[cfg(test)]
+mod tests {
+	use serde_json;
+	use uint::Uint;
+	use ethereum_types::U256;
+	use super::*;
+
+	#[test]
+	fn evm_limits_deserialization() {
+		let s = r#"{
+			"maxCallDepth": "0x800",
+			"memoryGas": "0x4"
+		}"#;
+
+		let deserialized: EvmLimits = serde_json::from_str(s).unwrap();
+		assert_eq!(deserialized.max_call_depth, Some(Uint(U256::from(0x800))));
+		assert_eq!(deserialized.memory_gas, Some(Uint(U256::from(4))));
+		assert_eq!(deserialized.stack_limit, None);
+		assert_eq!(deserialized.quad_coeff_div, None);
+	}
+
+	#[test]
+	#[should_panic(expected = "a non-zero value")]
+	fn evm_limits_zero_quad_coeff_div() {
+		let s = r#"{
+			"quadCoeffDiv": "0x0"
+		}"#;
+
+		let _deserialized: EvmLimits = serde_json::from_str(s).unwrap();
+	}
+
+	#[test]
+	#[should_panic(expected = "a value not greater than 16384")]
+	fn evm_limits_too_deep_calls() {
+		let s = r#"{
+			"maxCallDepth": "0x100000"
+		}"#;
+
+		let _deserialized: EvmLimits = serde_json::from_str(s).unwrap();
+	}
+}
@@ -16,9 +16,16 @@
 
 //! Spec params deserialization.
 
+use serde::Deserializer;
+use serde::de::{Error, Unexpected};
+use ethereum_types::U256;
 use uint::{self, Uint};
 use hash::{H256, Address};
 use bytes::Bytes;
+use spec::{EvmLimits, WasmCosts};
+
+/// Maximal value of `eip1559ElasticityMultiplier`.
+pub const MAX_ELASTICITY_MULTIPLIER: u64 = 1024;
 
 /// Spec params.
 #[derive(Debug, PartialEq, Deserialize)]
@@ -111,6 +118,33 @@ pub struct Params {
 	/// See `CommonParams` docs.
 	#[serde(rename="eip1283Transition")]
 	pub eip1283_transition: Option<Uint>,
+	/// See `CommonParams` docs.
+	#[serde(rename="eip2200Transition")]
+	pub eip2200_transition: Option<Uint>,
+	/// See `CommonParams` docs.
+	#[serde(rename="eip1344Transition")]
+	pub eip1344_transition: Option<Uint>,
+	/// See `CommonParams` docs.
+	#[serde(rename="eip1884Transition")]
+	pub eip1884_transition: Option<Uint>,
+	/// See `CommonParams` docs.
+	#[serde(rename="eip2929Transition")]
+	pub eip2929_transition: Option<Uint>,
+	/// See `CommonParams` docs.
+	#[serde(rename="eip2930Transition")]
+	pub eip2930_transition: Option<Uint>,
+	/// See `CommonParams` docs.
+	#[serde(rename="eip1559Transition")]
+	pub eip1559_transition: Option<Uint>,
+	/// See `CommonParams` docs.
+	#[serde(rename="eip1559BaseFeeMaxChangeDenominator", default, deserialize_with="uint::validate_optional_non_zero")]
+	pub eip1559_base_fee_max_change_denominator: Option<Uint>,
+	/// See `CommonParams` docs.
+	#[serde(rename="eip1559ElasticityMultiplier", default, deserialize_with="validate_elasticity_multiplier")]
+	pub eip1559_elasticity_multiplier: Option<Uint>,
+	/// See `CommonParams` docs.
+	#[serde(rename="eip1559BaseFeeInitialValue")]
+	pub eip1559_base_fee_initial_value: Option<Uint>,
 	#[serde(rename="eip1014Transition")]
 	pub eip1014_transition: Option<Uint>,
 	/// See `CommonParams` docs.
@@ -148,6 +182,15 @@ pub struct Params {
 	/// Block at which the transaction permission contract should start being used.
 	#[serde(rename="transactionPermissionContractTransition")]
 	pub transaction_permission_contract_transition: Option<Uint>,
+	/// Invariant contract address, called after every imported block.
+	#[serde(rename="invariantContract")]
+	pub invariant_contract: Option<Address>,
+	/// Block at which the invariant contract should start being called.
+	#[serde(rename="invariantContractTransition")]
+	pub invariant_contract_transition: Option<Uint>,
+	/// Whether to reject blocks violating the invariants (default) or only to report them.
+	#[serde(rename="invariantContractHalt")]
+	pub invariant_contract_halt: Option<bool>,
 	/// Wasm activation block height, if not activated from start
 	#[serde(rename="wasmActivationTransition")]
 	pub wasm_activation_transition: Option<Uint>,
@@ -157,6 +200,29 @@ pub struct Params {
 	/// KIP6 activiation block height.
 	#[serde(rename="kip6Transition")]
 	pub kip6_transition: Option<Uint>,
+	/// Wasm instruction and memory costs, if different from the defaults.
+	pub wasm: Option<WasmCosts>,
+	/// EVM limits and memory costs, if different from the defaults.
+	pub evm: Option<EvmLimits>,
+	/// See `CommonParams` docs.
+	#[serde(rename="acceptableTimestampDrift")]
+	pub acceptable_timestamp_drift: Option<Uint>,
+	/// See `CommonParams` docs.
+	#[serde(rename="invalidTimestampDrift")]
+	pub invalid_timestamp_drift: Option<Uint>,
+}
+
+fn validate_elasticity_multiplier<'de, D>(d: D) -> Result<Option<Uint>, D::Error> where D: Deserializer<'de> {
+	let value = uint::validate_optional_non_zero(d)?;
+
+	if let Some(value) = value {
+		if value.0 > U256::from(MAX_ELASTICITY_MULTIPLIER) {
+			// the value might not fit into `u64`
+			return Err(Error::invalid_value(Unexpected::Other(&value.0.to_string()), &"a value not greater than 1024"))
+		}
+	}
+
+	Ok(value)
 }
 
 #[cfg(test)]
@@ -177,7 +243,13 @@ mod tests {
 			"accountStartNonce": "0x01",
 			"gasLimitBoundDivisor": "0x20",
 			"maxCodeSize": "0x1000",
-			"wasmActivationTransition": "0x1010"
+			"wasmActivationTransition": "0x1010",
+			"wasm": {
+				"regular": "0x2",
+				"growMem": "0x8000"
+			},
+			"acceptableTimestampDrift": "0x1f4",
+			"invalidTimestampDrift": "0x7d0"
 		}"#;
 
 		let deserialized: Params = serde_json::from_str(s).unwrap();
@@ -190,6 +262,12 @@ mod tests {
 		assert_eq!(deserialized.gas_limit_bound_divisor, Uint(U256::from(0x20)));
 		assert_eq!(deserialized.max_code_size, Some(Uint(U256::from(0x1000))));
 		assert_eq!(deserialized.wasm_activation_transition, Some(Uint(U256::from(0x1010))));
+		let wasm = deserialized.wasm.unwrap();
+		assert_eq!(wasm.regular, Some(Uint(U256::from(2))));
+		assert_eq!(wasm.grow_mem, Some(Uint(U256::from(0x8000))));
+		assert_eq!(wasm.div, None);
+		assert_eq!(deserialized.acceptable_timestamp_drift, Some(Uint(U256::from(500))));
+		assert_eq!(deserialized.invalid_timestamp_drift, Some(Uint(U256::from(2000))));
 	}
 
 	#[test]
@@ -208,4 +286,32 @@ mod tests {
 
 		let _deserialized: Params = serde_json::from_str(s).unwrap();
 	}
+
+	#[test]
+	#[should_panic(expected = "a non-zero value")]
+	fn test_zero_base_fee_max_change_denominator() {
+		let s = r#"{
+			"maximumExtraDataSize": "0x20",
+			"networkID" : "0x1",
+			"minGasLimit": "0x1388",
+			"gasLimitBoundDivisor": "0x400",
+			"eip1559BaseFeeMaxChangeDenominator": "0x0"
+		}"#;
+
+		let _deserialized: Params = serde_json::from_str(s).unwrap();
+	}
+
+	#[test]
+	#[should_panic(expected = "a value not greater than 1024")]
+	fn test_too_large_elasticity_multiplier() {
+		let s = r#"{
+			"maximumExtraDataSize": "0x20",
+			"networkID" : "0x1",
+			"minGasLimit": "0x1388",
+			"gasLimitBoundDivisor": "0x400",
+			"eip1559ElasticityMultiplier": "0xffffffffffffffffffffffffffffffff"
+		}"#;
+
+		let _deserialized: Params = serde_json::from_str(s).unwrap();
+	}
 }
@@ -22,7 +22,7 @@ use serde_json::Error;
 use spec::{Params, Genesis, Engine, State, HardcodedSync};
 
 /// Fork spec definition
-#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Deserialize)]
 pub enum ForkSpec {
 	EIP150,
 	EIP158,
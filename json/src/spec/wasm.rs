@@ -0,0 +1,89 @@
+// Copyright 2015-2018 Parity Technologies (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Wasm cost table deserialization.
+
+use uint::{self, Uint};
+
+/// Wasm cost table. Missing entries fall back to the built-in defaults.
+#[derive(Debug, PartialEq, Deserialize)]
+pub struct WasmCosts {
+	/// Default opcode cost.
+	pub regular: Option<Uint>,
+	/// Div operations multiplier.
+	pub div: Option<Uint>,
+	/// Mul operations multiplier.
+	pub mul: Option<Uint>,
+	/// Memory (load/store) operations multiplier.
+	pub mem: Option<Uint>,
+	/// General static query of U256 value from env-info.
+	#[serde(rename="staticU256")]
+	pub static_u256: Option<Uint>,
+	/// General static query of Address value from env-info.
+	#[serde(rename="staticAddress")]
+	pub static_address: Option<Uint>,
+	/// Amount of free memory (in 64kb pages) each contract can use for stack.
+	#[serde(rename="initialMem")]
+	pub initial_mem: Option<Uint>,
+	/// Grow memory cost, per page (64kb).
+	#[serde(rename="growMem")]
+	pub grow_mem: Option<Uint>,
+	/// Memory copy cost, per byte.
+	pub memcpy: Option<Uint>,
+	/// Max stack height (native WebAssembly stack limiter).
+	#[serde(rename="maxStackHeight")]
+	pub max_stack_height: Option<Uint>,
+	/// Opcode cost multiplier.
+	#[serde(rename="opcodesMul")]
+	pub opcodes_mul: Option<Uint>,
+	/// Opcode cost divisor.
+	#[serde(rename="opcodesDiv", default, deserialize_with="uint::validate_optional_non_zero")]
+	pub opcodes_div: Option<Uint>,
+}
+
+#[cfg(test)]
+mod tests {
+	use serde_json;
+	use uint::Uint;
+	use ethereum_types::U256;
+	use super::*;
+
+	#[test]
+	fn wasm_costs_deserialization() {
+		let s = r#"{
+			"regular": "0x2",
+			"growMem": "0x8000",
+			"opcodesDiv": "0x3"
+		}"#;
+
+		let deserialized: WasmCosts = serde_json::from_str(s).unwrap();
+		assert_eq!(deserialized.regular, Some(Uint(U256::from(2))));
+		assert_eq!(deserialized.grow_mem, Some(Uint(U256::from(0x8000))));
+		assert_eq!(deserialized.opcodes_div, Some(Uint(U256::from(3))));
+		assert_eq!(deserialized.div, None);
+		assert_eq!(deserialized.initial_mem, None);
+	}
+
+	#[test]
+	#[should_panic(expected = "a non-zero value")]
+	fn wasm_costs_zero_opcodes_div() {
+		let s = r#"{
+			"opcodesDiv": "0x0"
+		}"#;
+
+		let _deserialized: WasmCosts = serde_json::from_str(s).unwrap();
+	}
+}
@@ -16,7 +16,9 @@
 
 //! Manages local node data: pending local transactions, sync security level
 
+use std::collections::HashSet;
 use std::sync::Arc;
+use std::sync::atomic::{AtomicUsize, Ordering};
 use std::fmt;
 use std::time::Duration;
 
@@ -49,6 +51,8 @@ extern crate ethkey;
 extern crate kvdb_memorydb;
 
 const LOCAL_TRANSACTIONS_KEY: &'static [u8] = &*b"LOCAL_TXS";
+/// Prefix of journal entries, followed by the big-endian index of the entry.
+const JOURNAL_PREFIX: &'static [u8] = &*b"TXS_JOURNAL";
 
 const UPDATE_TIMER: ::io::TimerToken = 0;
 const UPDATE_TIMEOUT: Duration = Duration::from_secs(15 * 60); // once every 15 minutes.
@@ -99,6 +103,9 @@ impl Into<TransactionCondition> for Condition {
 struct TransactionEntry {
 	rlp_bytes: Vec<u8>,
 	condition: Option<Condition>,
+	/// Whether the transaction was received from the network rather than submitted locally.
+	#[serde(default)]
+	external: bool,
 }
 
 impl TransactionEntry {
@@ -127,23 +134,54 @@ impl From<PendingTransaction> for TransactionEntry {
 		TransactionEntry {
 			rlp_bytes: ::rlp::encode(&pending.transaction).into_vec(),
 			condition: pending.condition.map(Into::into),
+			external: false,
 		}
 	}
 }
 
+fn external_entry(pending: PendingTransaction) -> TransactionEntry {
+	TransactionEntry { external: true, ..pending.into() }
+}
+
+fn journal_key(index: usize) -> Vec<u8> {
+	let mut key = JOURNAL_PREFIX.to_vec();
+	key.extend_from_slice(&[
+		(index >> 56) as u8, (index >> 48) as u8, (index >> 40) as u8, (index >> 32) as u8,
+		(index >> 24) as u8, (index >> 16) as u8, (index >> 8) as u8, index as u8,
+	]);
+	key
+}
+
+fn journal_index(key: &[u8]) -> Option<usize> {
+	if key.len() != JOURNAL_PREFIX.len() + 8 || !key.starts_with(JOURNAL_PREFIX) {
+		return None;
+	}
+	Some(key[JOURNAL_PREFIX.len()..].iter().fold(0, |index, byte| (index << 8) | *byte as usize))
+}
+
 /// Something which can provide information about the local node.
 pub trait NodeInfo: Send + Sync {
 	/// Get all pending transactions of local origin.
 	fn pending_transactions(&self) -> Vec<PendingTransaction>;
+
+	/// Get transactions received from the network which are still in the queue.
+	fn external_transactions(&self) -> Vec<PendingTransaction> {
+		Vec::new()
+	}
 }
 
 /// Create a new local data store, given a database, a column to write to, and a node.
 /// Attempts to read data out of the store, and move it into the node.
 pub fn create<T: NodeInfo>(db: Arc<KeyValueDB>, col: Option<u32>, node: T) -> LocalDataStore<T> {
+	let next_journal_index = db.iter_from_prefix(col, JOURNAL_PREFIX)
+		.filter_map(|(key, _)| journal_index(&key))
+		.max()
+		.map_or(0, |index| index + 1);
 	LocalDataStore {
 		db: db,
 		col: col,
 		node: node,
+		next_journal_index: AtomicUsize::new(next_journal_index),
 	}
 }
 
@@ -151,55 +189,103 @@ pub fn create<T: NodeInfo>(db: Arc<KeyValueDB>, col: Option<u32>, node: T) -> Lo
 ///
 /// In specific, this will be used to store things like unpropagated local transactions
 /// and the node security level.
+///
+/// Transactions are kept as a snapshot written periodically, and a journal of transactions
+/// imported since the last snapshot. Both are replayed on startup, so a crash loses nothing
+/// that made it into the journal.
 pub struct LocalDataStore<T: NodeInfo> {
 	db: Arc<KeyValueDB>,
 	col: Option<u32>,
 	node: T,
+	/// Index of the next journal entry. Never reset, so that a snapshot only replaces
+	/// the entries journaled before the transactions were read from the node.
+	next_journal_index: AtomicUsize,
 }
 
 impl<T: NodeInfo> LocalDataStore<T> {
 	/// Attempt to read pending transactions out of the local store.
 	pub fn pending_transactions(&self) -> Result<Vec<PendingTransaction>, Error> {
-		if let Some(val) = self.db.get(self.col, LOCAL_TRANSACTIONS_KEY).map_err(Error::Io)? {
-			let local_txs: Vec<_> = ::serde_json::from_slice::<Vec<TransactionEntry>>(&val)
-				.map_err(Error::Json)?
-				.into_iter()
-				.filter_map(TransactionEntry::into_pending)
-				.collect();
-
-			Ok(local_txs)
-		} else {
-			Ok(Vec::new())
+		self.transactions(false)
+	}
+
+	/// Attempt to read transactions received from the network out of the local store.
+	pub fn external_transactions(&self) -> Result<Vec<PendingTransaction>, Error> {
+		self.transactions(true)
+	}
+
+	// helper for reading transactions of either origin from the snapshot and the journal.
+	fn transactions(&self, external: bool) -> Result<Vec<PendingTransaction>, Error> {
+		let mut entries = match self.db.get(self.col, LOCAL_TRANSACTIONS_KEY).map_err(Error::Io)? {
+			Some(val) => ::serde_json::from_slice::<Vec<TransactionEntry>>(&val).map_err(Error::Json)?,
+			None => Vec::new(),
+		};
+		for (_, val) in self.db.iter_from_prefix(self.col, JOURNAL_PREFIX) {
+			match ::serde_json::from_slice::<Vec<TransactionEntry>>(&val) {
+				Ok(journaled) => entries.extend(journaled),
+				Err(e) => warn!(target: "local_store", "Invalid journal entry: {}", e),
+			}
 		}
+
+		let mut seen = HashSet::new();
+		Ok(entries.into_iter()
+			.filter(|entry| entry.external == external)
+			.filter_map(TransactionEntry::into_pending)
+			.filter(|pending| seen.insert(pending.hash()))
+			.collect())
+	}
+
+	/// Append newly imported transactions to the journal.
+	pub fn journal(&self, local: Vec<PendingTransaction>, external: Vec<PendingTransaction>) -> Result<(), Error> {
+		if local.is_empty() && external.is_empty() {
+			return Ok(());
+		}
+		let entries: Vec<TransactionEntry> = local.into_iter()
+			.map(Into::into)
+			.chain(external.into_iter().map(external_entry))
+			.collect();
+		let json = ::serde_json::to_vec(&entries).map_err(Error::Json)?;
+
+		let mut batch = self.db.transaction();
+		batch.put_vec(self.col, &journal_key(self.next_journal_index.fetch_add(1, Ordering::SeqCst)), json);
+		self.db.write(batch).map_err(Error::Io)
 	}
 
 	/// Update the entries in the database.
 	pub fn update(&self) -> Result<(), Error> {
 		trace!(target: "local_store", "Updating local store entries.");
 
-		let local_entries: Vec<TransactionEntry> = self.node.pending_transactions()
+		// transactions journaled from now on might be missing in the snapshot.
+		let journaled = self.next_journal_index.load(Ordering::SeqCst);
+		let entries: Vec<TransactionEntry> = self.node.pending_transactions()
 			.into_iter()
 			.map(Into::into)
+			.chain(self.node.external_transactions().into_iter().map(external_entry))
 			.collect();
 
-		self.write_txs(&local_entries)
+		self.write_txs(&entries, journaled)
 	}
 
 	/// Clear data in this column.
 	pub fn clear(&self) -> Result<(), Error> {
 		trace!(target: "local_store", "Clearing local store entries.");
 
-		self.write_txs(&[])
+		self.write_txs(&[], self.next_journal_index.load(Ordering::SeqCst))
 	}
 
-	// helper for writing a vector of transaction entries to disk.
-	fn write_txs(&self, txs: &[TransactionEntry]) -> Result<(), Error> {
+	// helper for writing a vector of transaction entries to disk, replacing journal entries
+	// with an index lower than `journaled`.
+	fn write_txs(&self, txs: &[TransactionEntry], journaled: usize) -> Result<(), Error> {
 		let mut batch = self.db.transaction();
 
 		let local_json = ::serde_json::to_value(txs).map_err(Error::Json)?;
 		let json_str = format!("{}", local_json);
 
 		batch.put_vec(self.col, LOCAL_TRANSACTIONS_KEY, json_str.into_bytes());
+		for (key, _) in self.db.iter_from_prefix(self.col, JOURNAL_PREFIX) {
+			if journal_index(&key).map_or(true, |index| index < journaled) {
+				batch.delete(self.col, &key);
+			}
+		}
 		self.db.write(batch).map_err(Error::Io)
 	}
 }
@@ -233,6 +319,7 @@ mod tests {
 	use super::NodeInfo;
 
 	use std::sync::Arc;
+	use kvdb::KeyValueDB;
 	use transaction::{Transaction, Condition, PendingTransaction};
 	use ethkey::{Brain, Generator};
 
@@ -328,4 +415,65 @@ mod tests {
 			assert_eq!(loaded, transactions);
 		}
 	}
+
+	#[test]
+	fn replays_journal() {
+		let keypair = Brain::new("abcd".into()).generate().unwrap();
+		let transactions: Vec<_> = (0..4u64).map(|nonce| {
+			let mut tx = Transaction::default();
+			tx.nonce = nonce.into();
+
+			PendingTransaction::new(tx.sign(keypair.secret(), None), None)
+		}).collect();
+
+		let db = Arc::new(::kvdb_memorydb::create(0));
+		{
+			// journal without a snapshot, as if the node crashed.
+			let store = super::create(db.clone(), None, Dummy(vec![]));
+			store.journal(transactions[..2].to_vec(), vec![transactions[2].clone()]).unwrap();
+			store.journal(vec![transactions[0].clone()], vec![transactions[3].clone()]).unwrap();
+			::std::mem::forget(store);
+		}
+		{
+			let store = super::create(db.clone(), None, Dummy(vec![]));
+			assert_eq!(store.pending_transactions().unwrap(), transactions[..2].to_vec());
+			assert_eq!(store.external_transactions().unwrap(), transactions[2..].to_vec());
+
+			// snapshot replaces the journal.
+			store.update().unwrap();
+			assert_eq!(db.iter_from_prefix(None, super::JOURNAL_PREFIX).count(), 0);
+			assert_eq!(store.pending_transactions().unwrap(), vec![]);
+		}
+	}
+
+	#[test]
+	fn keeps_entries_journaled_during_update() {
+		let keypair = Brain::new("abcd".into()).generate().unwrap();
+		let transactions: Vec<_> = (0..2u64).map(|nonce| {
+			let mut tx = Transaction::default();
+			tx.nonce = nonce.into();
+
+			PendingTransaction::new(tx.sign(keypair.secret(), None), None)
+		}).collect();
+
+		let db = Arc::new(::kvdb_memorydb::create(0));
+		{
+			let store = super::create(db.clone(), None, Dummy(vec![]));
+			store.journal(vec![transactions[0].clone()], vec![]).unwrap();
+			// the second entry is journaled after the snapshot was read from the node.
+			store.journal(vec![transactions[1].clone()], vec![]).unwrap();
+			store.write_txs(&[transactions[0].clone().into()], 1).unwrap();
+
+			assert_eq!(db.iter_from_prefix(None, super::JOURNAL_PREFIX).count(), 1);
+			assert_eq!(store.pending_transactions().unwrap(), transactions);
+			::std::mem::forget(store);
+		}
+		{
+			// journal indices are not reused after a restart.
+			let store = super::create(db.clone(), None, Dummy(vec![]));
+			store.journal(vec![transactions[0].clone()], vec![]).unwrap();
+			assert_eq!(db.iter_from_prefix(None, super::JOURNAL_PREFIX).count(), 2);
+			assert_eq!(store.pending_transactions().unwrap(), transactions);
+		}
+	}
 }
@@ -24,7 +24,6 @@ extern crate ethcore_transaction as transaction;
 extern crate ethereum_types;
 extern crate futures;
 extern crate futures_cpupool;
-extern crate heapsize;
 extern crate keccak_hash as hash;
 extern crate linked_hash_map;
 extern crate parking_lot;
@@ -40,7 +39,7 @@ extern crate log;
 #[macro_use]
 extern crate trace_time;
 
-#[cfg(test)]
+#[cfg(any(test, feature = "tx-relay"))]
 extern crate rustc_hex;
 #[cfg(test)]
 extern crate ethkey;
@@ -52,5 +51,7 @@ pub mod external;
 pub mod gas_price_calibrator;
 pub mod gas_pricer;
 pub mod pool;
+#[cfg(feature = "tx-relay")]
+pub mod tx_relay;
 #[cfg(feature = "work-notify")]
 pub mod work_notify;
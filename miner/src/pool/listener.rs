@@ -17,27 +17,38 @@
 //! Notifier for new transaction hashes.
 
 use std::fmt;
+use std::collections::HashSet;
 use std::sync::Arc;
 
-use ethereum_types::H256;
+use ethereum_types::{H256, U256};
 use txpool::{self, VerifiedTransaction};
 
-use pool::VerifiedTransaction as Transaction;
+use pool::{VerifiedTransaction as Transaction, ScoredTransaction};
 
 type Listener = Box<Fn(&[H256]) + Send + Sync>;
+type TransactionsListener = Box<Fn(&[Arc<Transaction>]) + Send + Sync>;
+type LifecycleListener = Box<Fn(&H256, TransactionEvent) + Send + Sync>;
 
 /// Manages notifications to pending transaction listeners.
 #[derive(Default)]
 pub struct Notifier {
 	listeners: Vec<Listener>,
+	local_listeners: Vec<TransactionsListener>,
+	full_listeners: Vec<TransactionsListener>,
 	pending: Vec<H256>,
+	pending_local: Vec<Arc<Transaction>>,
+	pending_full: Vec<Arc<Transaction>>,
 }
 
 impl fmt::Debug for Notifier {
 	fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
 		fmt.debug_struct("Notifier")
 			.field("listeners", &self.listeners.len())
+			.field("local_listeners", &self.local_listeners.len())
+			.field("full_listeners", &self.full_listeners.len())
 			.field("pending", &self.pending)
+			.field("pending_local", &self.pending_local.len())
+			.field("pending_full", &self.pending_full.len())
 			.finish()
 	}
 }
@@ -48,6 +59,16 @@ impl Notifier {
 		self.listeners.push(f)
 	}
 
+	/// Add new listener to receive newly accepted local transactions.
+	pub fn add_local(&mut self, f: TransactionsListener) {
+		self.local_listeners.push(f)
+	}
+
+	/// Add new listener to receive all newly accepted transactions.
+	pub fn add_full(&mut self, f: TransactionsListener) {
+		self.full_listeners.push(f)
+	}
+
 	/// Notify listeners about all currently pending transactions.
 	pub fn notify(&mut self) {
 		for l in &self.listeners {
@@ -55,12 +76,32 @@ impl Notifier {
 		}
 
 		self.pending.clear();
+
+		if !self.pending_local.is_empty() {
+			for l in &self.local_listeners {
+				(l)(&self.pending_local);
+			}
+			self.pending_local.clear();
+		}
+
+		if !self.pending_full.is_empty() {
+			for l in &self.full_listeners {
+				(l)(&self.pending_full);
+			}
+			self.pending_full.clear();
+		}
 	}
 }
 
 impl txpool::Listener<Transaction> for Notifier {
 	fn added(&mut self, tx: &Arc<Transaction>, _old: Option<&Arc<Transaction>>) {
 		self.pending.push(*tx.hash());
+		if tx.priority().is_local() && !self.local_listeners.is_empty() {
+			self.pending_local.push(tx.clone());
+		}
+		if !self.full_listeners.is_empty() {
+			self.pending_full.push(tx.clone());
+		}
 	}
 }
 
@@ -112,6 +153,120 @@ impl txpool::Listener<Transaction> for Logger {
 	}
 }
 
+/// Change of transaction status within the pool.
+#[derive(Debug, Clone, PartialEq)]
+pub enum TransactionEvent {
+	/// Transaction was added to the pool.
+	Added,
+	/// Transaction was never accepted to the pool.
+	Rejected(String),
+	/// Transaction was replaced by another one with the same sender and nonce.
+	Replaced(H256),
+	/// Transaction was dropped because of limit, optionally pushed out by another transaction.
+	Dropped(Option<H256>),
+	/// Transaction was marked as invalid by executor.
+	Invalid,
+	/// Transaction was canceled by the user.
+	Canceled,
+	/// Transaction was removed because its nonce became stale.
+	Culled,
+	/// Transaction was removed because it got included in an imported block.
+	Mined,
+	/// Transaction was removed because it pays less than the raised minimal gas price.
+	Demoted(U256),
+}
+
+/// Reports every status change of transactions in the pool to registered listeners.
+#[derive(Default)]
+pub struct Lifecycle {
+	listeners: Vec<LifecycleListener>,
+	demoting: Option<U256>,
+	mined: HashSet<H256>,
+}
+
+impl fmt::Debug for Lifecycle {
+	fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+		fmt.debug_struct("Lifecycle")
+			.field("listeners", &self.listeners.len())
+			.field("demoting", &self.demoting)
+			.field("mined", &self.mined.len())
+			.finish()
+	}
+}
+
+impl Lifecycle {
+	/// Add new listener to receive transaction status changes.
+	pub fn add(&mut self, f: LifecycleListener) {
+		self.listeners.push(f)
+	}
+
+	/// Report transactions removed from now on as demoted below given minimal gas price
+	/// instead of canceled, until called with `None`.
+	pub fn set_demoting(&mut self, minimal_gas_price: Option<U256>) {
+		self.demoting = minimal_gas_price;
+	}
+
+	/// Whether there is anyone listening to status changes.
+	pub fn is_empty(&self) -> bool {
+		self.listeners.is_empty()
+	}
+
+	/// Report given transactions as mined instead of culled when they are removed.
+	pub fn set_mined<I: IntoIterator<Item = H256>>(&mut self, hashes: I) {
+		if !self.listeners.is_empty() {
+			self.mined.extend(hashes);
+		}
+	}
+
+	/// Forget transactions marked as mined, once they were culled.
+	pub fn clear_mined(&mut self) {
+		self.mined.clear();
+	}
+
+	fn notify(&self, hash: &H256, event: TransactionEvent) {
+		for l in &self.listeners {
+			(l)(hash, event.clone());
+		}
+	}
+}
+
+impl txpool::Listener<Transaction> for Lifecycle {
+	fn added(&mut self, tx: &Arc<Transaction>, old: Option<&Arc<Transaction>>) {
+		self.notify(tx.hash(), TransactionEvent::Added);
+		if let Some(old) = old {
+			self.notify(old.hash(), TransactionEvent::Replaced(*tx.hash()));
+		}
+	}
+
+	fn rejected(&mut self, tx: &Arc<Transaction>, reason: &txpool::ErrorKind) {
+		self.notify(tx.hash(), TransactionEvent::Rejected(format!("{}", reason)));
+	}
+
+	fn dropped(&mut self, tx: &Arc<Transaction>, new: Option<&Transaction>) {
+		self.notify(tx.hash(), TransactionEvent::Dropped(new.map(|new| *new.hash())));
+	}
+
+	fn invalid(&mut self, tx: &Arc<Transaction>) {
+		self.notify(tx.hash(), TransactionEvent::Invalid);
+	}
+
+	fn canceled(&mut self, tx: &Arc<Transaction>) {
+		let event = match self.demoting {
+			Some(minimal_gas_price) => TransactionEvent::Demoted(minimal_gas_price),
+			None => TransactionEvent::Canceled,
+		};
+		self.notify(tx.hash(), event);
+	}
+
+	fn culled(&mut self, tx: &Arc<Transaction>) {
+		let event = match self.mined.remove(tx.hash()) {
+			true => TransactionEvent::Mined,
+			false => TransactionEvent::Culled,
+		};
+		self.notify(tx.hash(), event);
+	}
+}
+
 #[cfg(test)]
 mod tests {
 	use super::*;
@@ -144,6 +299,111 @@ mod tests {
 		);
 	}
 
+	#[test]
+	fn should_notify_local_listeners_only_about_local_transactions() {
+		// given
+		let received = Arc::new(Mutex::new(vec![]));
+		let r = received.clone();
+		let listener = Box::new(move |txs: &[Arc<Transaction>]| {
+			r.lock().extend(txs.iter().map(|tx| *tx.hash()));
+		});
+
+		let mut tx_listener = Notifier::default();
+		tx_listener.add_local(listener);
+
+		// when
+		let remote = new_tx();
+		let mut local = Transaction::from_pending_block_transaction(remote.signed().clone());
+		local.priority = ::pool::Priority::Local;
+		let local = Arc::new(local);
+		tx_listener.added(&remote, None);
+		tx_listener.added(&local, None);
+		tx_listener.notify();
+
+		// then
+		assert_eq!(*received.lock(), vec![*local.hash()]);
+	}
+
+	#[test]
+	fn should_notify_full_listeners_about_all_transactions() {
+		// given
+		let received = Arc::new(Mutex::new(vec![]));
+		let r = received.clone();
+		let listener = Box::new(move |txs: &[Arc<Transaction>]| {
+			r.lock().extend(txs.iter().map(|tx| *tx.hash()));
+		});
+
+		let mut tx_listener = Notifier::default();
+		tx_listener.add_full(listener);
+
+		// when
+		let tx = new_tx();
+		tx_listener.added(&tx, None);
+		tx_listener.notify();
+		tx_listener.notify();
+
+		// then
+		assert_eq!(*received.lock(), vec![*tx.hash()]);
+	}
+
+	#[test]
+	fn should_report_replaced_transactions() {
+		// given
+		let received = Arc::new(Mutex::new(vec![]));
+		let r = received.clone();
+		let listener = Box::new(move |hash: &H256, event: TransactionEvent| {
+			r.lock().push((*hash, event));
+		});
+
+		let mut lifecycle = Lifecycle::default();
+		lifecycle.add(listener);
+
+		// when
+		let old = new_tx();
+		let new = Arc::new(Transaction::from_pending_block_transaction(transaction::Transaction {
+			action: transaction::Action::Create,
+			data: vec![1, 2, 3],
+			nonce: 5.into(),
+			gas: 21_000.into(),
+			gas_price: 10.into(),
+			value: 0.into(),
+		}.fake_sign(5.into())));
+		lifecycle.added(&old, None);
+		lifecycle.added(&new, Some(&old));
+
+		// then
+		assert_eq!(*received.lock(), vec![
+			(*old.hash(), TransactionEvent::Added),
+			(*new.hash(), TransactionEvent::Added),
+			(*old.hash(), TransactionEvent::Replaced(*new.hash())),
+		]);
+	}
+
+	#[test]
+	fn should_report_mined_transactions() {
+		// given
+		let received = Arc::new(Mutex::new(vec![]));
+		let r = received.clone();
+		let listener = Box::new(move |hash: &H256, event: TransactionEvent| {
+			r.lock().push((*hash, event));
+		});
+
+		let mut lifecycle = Lifecycle::default();
+		lifecycle.add(listener);
+
+		// when
+		let tx = new_tx();
+		lifecycle.set_mined(vec![*tx.hash()]);
+		lifecycle.culled(&tx);
+		lifecycle.culled(&tx);
+
+		// then
+		assert_eq!(*received.lock(), vec![
+			(*tx.hash(), TransactionEvent::Mined),
+			(*tx.hash(), TransactionEvent::Culled),
+		]);
+	}
+
 	fn new_tx() -> Arc<Transaction> {
 		let signed = transaction::Transaction {
 			action: transaction::Action::Create,
@@ -53,7 +53,8 @@ pub enum Status {
 }
 
 impl Status {
-	fn is_pending(&self) -> bool {
+	/// Returns true if the transaction is still in the queue.
+	pub fn is_pending(&self) -> bool {
 		match *self {
 			Status::Pending(_) => true,
 			_ => false,
@@ -16,13 +16,16 @@
 
 //! Transaction Pool
 
+use std::mem;
+
 use ethereum_types::{U256, H256, Address};
-use heapsize::HeapSizeOf;
+use rlp::Encodable;
 use transaction;
 use txpool;
 
 mod listener;
 mod queue;
+mod quotas;
 mod ready;
 
 pub mod client;
@@ -33,7 +36,9 @@ pub mod verifier;
 #[cfg(test)]
 mod tests;
 
+pub use self::listener::TransactionEvent;
 pub use self::queue::{TransactionQueue, Status as QueueStatus};
+pub use self::quotas::Quotas;
 pub use self::txpool::{VerifiedTransaction as PoolVerifiedTransaction, Options};
 
 /// How to prioritize transactions in the pool
@@ -52,6 +57,11 @@ pub enum PendingOrdering {
 	Priority,
 	/// Get pending transactions without any care of particular ordering (cheaper).
 	Unordered,
+	/// Get pending transactions ordered by their priority with ties broken by transaction hash.
+	///
+	/// The resulting order depends only on the pool contents (not on the insertion order),
+	/// so it's reproducible across nodes and restarts. Never served from cache (expensive).
+	Deterministic,
 }
 
 /// Pending set query settings
@@ -100,7 +110,8 @@ pub enum Priority {
 }
 
 impl Priority {
-	fn is_local(&self) -> bool {
+	/// Whether the transaction was submitted locally.
+	pub fn is_local(&self) -> bool {
 		match *self {
 			Priority::Local => true,
 			_ => false,
@@ -108,6 +119,30 @@ impl Priority {
 	}
 }
 
+/// Estimated memory used by the pool to index a single transaction:
+/// entries in the hash and sender maps and in the best/worst sets, plus `Arc` counters.
+const POOL_INDEX_OVERHEAD: usize = 256;
+
+/// Memory accounted for a pooled transaction with RLP encoding of given size.
+///
+/// The encoding approximates heap allocations of the transaction (mostly its data),
+/// the rest is the fixed size of the verified transaction and its pool indexes.
+pub(crate) fn mem_usage(rlp_size: usize) -> usize {
+	rlp_size + mem::size_of::<VerifiedTransaction>() + POOL_INDEX_OVERHEAD
+}
+
+/// Lower bound of the RLP size of a signed transaction: empty fields, 21000 gas and
+/// full-length signature components.
+const MIN_TRANSACTION_RLP_SIZE: usize = 72;
+
+/// Maximal number of transactions fitting in the pool limited to `max_mem_usage` bytes.
+///
+/// Used as the count limit of pools limited by memory only, so that the caches
+/// sized after the count limit stay bounded.
+pub fn max_count_for_memory(max_mem_usage: usize) -> usize {
+	::std::cmp::max(1, max_mem_usage / mem_usage(MIN_TRANSACTION_RLP_SIZE))
+}
+
 /// Scoring properties for verified transaction.
 pub trait ScoredTransaction {
 	/// Gets transaction priority.
@@ -129,6 +164,7 @@ pub struct VerifiedTransaction {
 	sender: Address,
 	priority: Priority,
 	insertion_id: usize,
+	mem_usage: usize,
 }
 
 impl VerifiedTransaction {
@@ -140,12 +176,14 @@ impl VerifiedTransaction {
 	pub fn from_pending_block_transaction(tx: transaction::SignedTransaction) -> Self {
 		let hash = tx.hash();
 		let sender = tx.sender();
+		let mem_usage = mem_usage(tx.rlp_bytes().len());
 		VerifiedTransaction {
 			transaction: tx.into(),
 			hash,
 			sender,
 			priority: Priority::Retracted,
 			insertion_id: 0,
+			mem_usage,
 		}
 	}
 
@@ -175,7 +213,7 @@ impl txpool::VerifiedTransaction for VerifiedTransaction {
 	}
 
 	fn mem_usage(&self) -> usize {
-		self.transaction.heap_size_of_children()
+		self.mem_usage
 	}
 
 	fn sender(&self) -> &Address {
@@ -17,9 +17,11 @@
 //! Ethereum Transaction Queue
 
 use std::{cmp, fmt};
+use std::net::IpAddr;
 use std::sync::Arc;
 use std::sync::atomic::{self, AtomicUsize};
-use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::time::{Duration, Instant};
+use std::collections::{BTreeMap, BTreeSet, BinaryHeap, HashMap, HashSet, VecDeque};
 
 use ethereum_types::{H256, U256, Address};
 use parking_lot::RwLock;
@@ -27,12 +29,12 @@ use transaction;
 use txpool::{self, Verifier};
 
 use pool::{
-	self, scoring, verifier, client, ready, listener,
-	PrioritizationStrategy, PendingOrdering, PendingSettings,
+	self, scoring, verifier, client, ready, listener, quotas,
+	PrioritizationStrategy, PendingOrdering, PendingSettings, Quotas,
 };
 use pool::local_transactions::LocalTransactionsList;
 
-type Listener = (LocalTransactionsList, (listener::Notifier, listener::Logger));
+type Listener = (LocalTransactionsList, (listener::Notifier, (listener::Logger, listener::Lifecycle)));
 type Pool = txpool::Pool<pool::VerifiedTransaction, scoring::NonceAndGasPrice, Listener>;
 
 /// Max cache time in milliseconds for pending transactions.
@@ -190,6 +192,15 @@ impl RecentlyRejected {
 /// Minimal size of rejection cache, by default it's equal to queue size.
 const MIN_REJECTED_CACHE_SIZE: usize = 2048;
 
+/// Schedule of demoting transactions which became underpriced after minimal gas price was raised.
+#[derive(Debug, Default)]
+struct Demotion {
+	/// How long underpriced transactions are kept after minimal gas price is raised.
+	grace: Duration,
+	/// When currently underpriced transactions should be demoted.
+	deadline: Option<Instant>,
+}
+
 /// Ethereum Transaction Queue
 ///
 /// Responsible for:
@@ -199,10 +210,15 @@ const MIN_REJECTED_CACHE_SIZE: usize = 2048;
 #[derive(Debug)]
 pub struct TransactionQueue {
 	insertion_id: Arc<AtomicUsize>,
+	replacement_bump: Arc<AtomicUsize>,
 	pool: RwLock<Pool>,
 	options: RwLock<verifier::Options>,
 	cached_pending: RwLock<CachedPending>,
 	recently_rejected: RecentlyRejected,
+	demotion: RwLock<Demotion>,
+	quotas: RwLock<Quotas>,
+	by_sender: RwLock<quotas::Tracker<Address>>,
+	by_origin: RwLock<quotas::Tracker<IpAddr>>,
 }
 
 impl TransactionQueue {
@@ -213,20 +229,93 @@ impl TransactionQueue {
 		strategy: PrioritizationStrategy,
 	) -> Self {
 		let max_count = limits.max_count;
+		let replacement_bump = Arc::new(AtomicUsize::new(scoring::DEFAULT_REPLACEMENT_BUMP));
+		let scoring = scoring::NonceAndGasPrice::with_replacement_bump(strategy, replacement_bump.clone());
 		TransactionQueue {
 			insertion_id: Default::default(),
-			pool: RwLock::new(txpool::Pool::new(Default::default(), scoring::NonceAndGasPrice(strategy), limits)),
+			replacement_bump,
+			pool: RwLock::new(txpool::Pool::new(Default::default(), scoring, limits)),
 			options: RwLock::new(verification_options),
 			cached_pending: RwLock::new(CachedPending::none()),
 			recently_rejected: RecentlyRejected::new(cmp::max(MIN_REJECTED_CACHE_SIZE, max_count / 4)),
+			demotion: Default::default(),
+			quotas: Default::default(),
+			by_sender: Default::default(),
+			by_origin: Default::default(),
 		}
 	}
 
 	/// Update verification options
 	///
 	/// Some parameters of verification may vary in time (like block gas limit or minimal gas price).
+	/// Raising minimal gas price schedules demotion of transactions below the new price
+	/// (see `demote_underpriced`).
 	pub fn set_verifier_options(&self, options: verifier::Options) {
-		*self.options.write() = options;
+		let mut current = self.options.write();
+		if options.minimal_gas_price > current.minimal_gas_price {
+			let mut demotion = self.demotion.write();
+			if demotion.deadline.is_none() {
+				demotion.deadline = Some(Instant::now() + demotion.grace);
+			}
+		}
+		*current = options;
+	}
+
+	/// Sets the gas price bump (in basis points) required to replace a transaction
+	/// with the same sender and nonce. Applies to both local and external transactions.
+	pub fn set_replacement_bump(&self, bump: usize) {
+		self.replacement_bump.store(bump, atomic::Ordering::SeqCst);
+	}
+
+	/// Returns the gas price bump (in basis points) required to replace a transaction.
+	pub fn replacement_bump(&self) -> usize {
+		self.replacement_bump.load(atomic::Ordering::SeqCst)
+	}
+
+	/// Sets for how long transactions which became underpriced are kept in the pool.
+	pub fn set_demotion_grace(&self, grace: Duration) {
+		self.demotion.write().grace = grace;
+	}
+
+	/// Removes transactions paying less than current minimal gas price,
+	/// once the grace period after raising the price has passed.
+	///
+	/// Local and zero gas price (service) transactions are never demoted.
+	/// Returns number of removed transactions.
+	pub fn demote_underpriced(&self) -> usize {
+		{
+			let mut demotion = self.demotion.write();
+			match demotion.deadline {
+				Some(deadline) if deadline <= Instant::now() => demotion.deadline = None,
+				_ => return 0,
+			}
+		}
+
+		let minimal_gas_price = self.options.read().minimal_gas_price;
+		let underpriced: Vec<_> = self.all_transactions()
+			.into_iter()
+			.filter(|tx| {
+				let gas_price = tx.signed().gas_price;
+				!tx.priority.is_local() && !gas_price.is_zero() && gas_price < minimal_gas_price
+			})
+			.map(|tx| tx.hash)
+			.collect();
+
+		if underpriced.is_empty() {
+			return 0;
+		}
+
+		let removed = {
+			let mut pool = self.pool.write();
+			((pool.listener_mut().1).1).1.set_demoting(Some(minimal_gas_price));
+			let removed = underpriced.iter().filter_map(|hash| pool.remove(hash, false)).count();
+			((pool.listener_mut().1).1).1.set_demoting(None);
+			removed
+		};
+
+		self.cached_pending.write().clear();
+		info!(target: "txqueue", "Demoted {} transactions below minimal gas price of {} wei.", removed, minimal_gas_price);
+		removed
 	}
 
 	/// Sets the in-chain transaction checker for pool listener.
@@ -244,6 +333,18 @@ impl TransactionQueue {
 		&self,
 		client: C,
 		transactions: Vec<verifier::Transaction>,
+	) -> Vec<Result<(), transaction::Error>> {
+		self.import_from(client, transactions, None)
+	}
+
+	/// Import a set of transactions received from given IP address to the pool.
+	///
+	/// The origin is used to enforce the per-origin quota.
+	pub fn import_from<C: client::Client>(
+		&self,
+		client: C,
+		transactions: Vec<verifier::Transaction>,
+		origin: Option<IpAddr>,
 	) -> Vec<Result<(), transaction::Error>> {
 		// Run verification
 		trace_time!("pool::verify_and_import");
@@ -285,12 +386,21 @@ impl TransactionQueue {
 
 				let imported = verifier
 					.verify_transaction(transaction)
+					.and_then(|verified| self.check_quotas(&verified, origin.as_ref()).map(|_| verified))
 					.and_then(|verified| {
 						self.pool.write().import(verified).map_err(convert_error)
 					});
 
 				match imported {
-					Ok(_) => Ok(()),
+					Ok(imported) => {
+						if !imported.priority.is_local() {
+							self.by_sender.write().insert(imported.sender, imported.hash);
+							if let Some(origin) = origin {
+								self.by_origin.write().insert(origin, imported.hash);
+							}
+						}
+						Ok(())
+					},
 					Err(err) => {
 						self.recently_rejected.insert(hash, &err);
 						Err(err)
@@ -309,6 +419,87 @@ impl TransactionQueue {
 		results
 	}
 
+	/// Rejects external transactions exceeding runtime quotas.
+	///
+	/// Replacements of transactions already in the pool are not limited per sender or origin.
+	fn check_quotas(&self, tx: &pool::VerifiedTransaction, origin: Option<&IpAddr>) -> Result<(), transaction::Error> {
+		if tx.priority.is_local() {
+			return Ok(());
+		}
+
+		let quotas = *self.quotas.read();
+		let pool = self.pool.read();
+		if let Some(max_mem_usage) = quotas.max_mem_usage {
+			if pool.light_status().mem_usage + tx.mem_usage > max_mem_usage {
+				trace!(target: "txqueue", "[{:?}] Rejected, memory quota of {} bytes reached.", tx.hash, max_mem_usage);
+				return Err(transaction::Error::LimitReached);
+			}
+		}
+
+		let in_pool = |hash: &H256| pool.find(hash).is_some();
+		let replaces = |hashes: &[H256]| hashes.iter()
+			.filter_map(|hash| pool.find(hash))
+			.any(|old| old.sender == tx.sender && old.signed().nonce == tx.signed().nonce);
+
+		if let Some(max_per_sender) = quotas.max_per_sender {
+			let txs = self.by_sender.write().get(&tx.sender, &in_pool);
+			if txs.len() >= max_per_sender && !replaces(&txs) {
+				trace!(target: "txqueue", "[{:?}] Rejected, sender {:?} reached quota of {}.", tx.hash, tx.sender, max_per_sender);
+				return Err(transaction::Error::LimitReached);
+			}
+		}
+
+		if let (Some(max_per_origin), Some(origin)) = (quotas.max_per_origin, origin) {
+			let txs = self.by_origin.write().get(origin, &in_pool);
+			if txs.len() >= max_per_origin && !replaces(&txs) {
+				trace!(target: "txqueue", "[{:?}] Rejected, origin {} reached quota of {}.", tx.hash, origin, max_per_origin);
+				return Err(transaction::Error::LimitReached);
+			}
+		}
+
+		Ok(())
+	}
+
+	/// Returns current runtime quotas.
+	pub fn quotas(&self) -> Quotas {
+		*self.quotas.read()
+	}
+
+	/// Sets runtime quotas, which tighten the limits set at startup for external transactions.
+	///
+	/// Lowering the memory quota immediately removes the worst external transactions until
+	/// the pool fits. Returns number of removed transactions.
+	pub fn set_quotas(&self, quotas: Quotas) -> usize {
+		*self.quotas.write() = quotas;
+		self.recently_rejected.clear();
+
+		let max_mem_usage = match quotas.max_mem_usage {
+			Some(max_mem_usage) => max_mem_usage,
+			None => return 0,
+		};
+
+		let mut removed = 0;
+		{
+			let mut pool = self.pool.write();
+			while pool.light_status().mem_usage > max_mem_usage {
+				let worst = match pool.worst_transaction() {
+					Some(ref worst) if !worst.priority.is_local() => worst.hash,
+					_ => break,
+				};
+				if pool.remove(&worst, false).is_none() {
+					break;
+				}
+				removed += 1;
+			}
+		}
+
+		if removed > 0 {
+			self.cached_pending.write().clear();
+			info!(target: "txqueue", "Removed {} transactions to fit memory quota of {} bytes.", removed, max_mem_usage);
+		}
+		removed
+	}
+
 	/// Returns all transactions in the queue without explicit ordering.
 	pub fn all_transactions(&self) -> Vec<Arc<pool::VerifiedTransaction>> {
 		let ready = |_tx: &pool::VerifiedTransaction| txpool::Readiness::Ready;
@@ -341,6 +532,14 @@ impl TransactionQueue {
 		C: client::NonceClient,
 	{
 		let PendingSettings { block_number, current_timestamp, nonce_cap, max_len, ordering } = settings;
+
+		// Deterministic ordering is never cached, since the cached set
+		// depends on the insertion order of transactions with equal scores.
+		if let PendingOrdering::Deterministic = ordering {
+			let pending = self.collect_pending(client, block_number, current_timestamp, nonce_cap, |i| i.collect());
+			return deterministic_order(pending).into_iter().take(max_len).collect();
+		}
+
 		if let Some(pending) = self.cached_pending.read().pending(block_number, current_timestamp, nonce_cap.as_ref(), max_len) {
 			return pending;
 		}
@@ -374,6 +573,29 @@ impl TransactionQueue {
 		pending
 	}
 
+	/// Returns current pending transactions of given senders ordered by priority.
+	///
+	/// Unlike `#pending` the `max_len` cut only applies to transactions of these senders,
+	/// so they are never crowded out by others. Never served from cache.
+	pub fn pending_from_senders<C>(
+		&self,
+		client: C,
+		settings: PendingSettings,
+		senders: &HashSet<Address>,
+	) -> Vec<Arc<pool::VerifiedTransaction>> where
+		C: client::NonceClient,
+	{
+		let PendingSettings { block_number, current_timestamp, nonce_cap, max_len, ordering } = settings;
+		let pending = self.collect_pending(client, block_number, current_timestamp, nonce_cap, |i| {
+			i.filter(|tx| senders.contains(&tx.sender)).collect::<Vec<_>>()
+		});
+
+		match ordering {
+			PendingOrdering::Deterministic => deterministic_order(pending).into_iter().take(max_len).collect(),
+			_ => pending.into_iter().take(max_len).collect(),
+		}
+	}
+
 	/// Collect pending transactions.
 	///
 	/// NOTE This is re-computing the pending set and it might be expensive to do so.
@@ -449,6 +671,13 @@ impl TransactionQueue {
 			let state_readiness = ready::State::new(client.clone(), stale_id, nonce_cap);
 			removed += self.pool.write().cull(Some(chunk), state_readiness);
 		}
+		((self.pool.write().listener_mut().1).1).1.clear_mined();
+		{
+			let pool = self.pool.read();
+			let in_pool = |hash: &H256| pool.find(hash).is_some();
+			self.by_sender.write().prune(&in_pool);
+			self.by_origin.write().prune(&in_pool);
+		}
 		debug!(target: "txqueue", "Removed {} stalled transactions. {}", removed, self.status());
 	}
 
@@ -566,6 +795,36 @@ impl TransactionQueue {
 		(pool.listener_mut().1).0.add(f);
 	}
 
+	/// Add a callback to be notified about local transactions entering the pool.
+	pub fn add_local_listener(&self, f: Box<Fn(&[Arc<pool::VerifiedTransaction>]) + Send + Sync>) {
+		let mut pool = self.pool.write();
+		(pool.listener_mut().1).0.add_local(f);
+	}
+
+	/// Add a callback to be notified about all transactions entering the pool, including their details.
+	pub fn add_full_listener(&self, f: Box<Fn(&[Arc<pool::VerifiedTransaction>]) + Send + Sync>) {
+		let mut pool = self.pool.write();
+		(pool.listener_mut().1).0.add_full(f);
+	}
+
+	/// Add a callback to be notified about every status change of transactions in the pool.
+	pub fn add_lifecycle_listener(&self, f: Box<Fn(&H256, listener::TransactionEvent) + Send + Sync>) {
+		let mut pool = self.pool.write();
+		((pool.listener_mut().1).1).1.add(f);
+	}
+
+	/// Whether any callback is notified about status changes of transactions in the pool.
+	pub fn has_lifecycle_listeners(&self) -> bool {
+		!((self.pool.read().listener().1).1).1.is_empty()
+	}
+
+	/// Mark transactions included in newly imported blocks, so that lifecycle listeners
+	/// are told they were mined rather than culled on the next `cull`.
+	pub fn mined<I: IntoIterator<Item = H256>>(&self, hashes: I) {
+		let mut pool = self.pool.write();
+		((pool.listener_mut().1).1).1.set_mined(hashes);
+	}
+
 	/// Check if pending set is cached.
 	#[cfg(test)]
 	pub fn is_pending_cached(&self) -> bool {
@@ -573,6 +832,36 @@ impl TransactionQueue {
 	}
 }
 
+/// Re-orders pending transactions so that the result depends only on the set of transactions.
+///
+/// Transactions from the same sender keep their relative (nonce) order. Between senders
+/// the transaction with the highest score is picked first and ties are broken by
+/// choosing the lowest transaction hash.
+/// NOTE Sender penalization is not taken into account.
+fn deterministic_order(pending: Vec<Arc<pool::VerifiedTransaction>>) -> Vec<Arc<pool::VerifiedTransaction>> {
+	let len = pending.len();
+	let mut by_sender: HashMap<Address, VecDeque<Arc<pool::VerifiedTransaction>>> = HashMap::new();
+	for tx in pending {
+		by_sender.entry(tx.sender).or_insert_with(VecDeque::new).push_back(tx);
+	}
+
+	let key = |tx: &pool::VerifiedTransaction| (scoring::base_score(tx), cmp::Reverse(tx.hash), tx.sender);
+	let mut heads = by_sender.values()
+		.filter_map(|txs| txs.front().map(|tx| key(tx)))
+		.collect::<BinaryHeap<_>>();
+
+	let mut ordered = Vec::with_capacity(len);
+	while let Some((_, _, sender)) = heads.pop() {
+		let txs = by_sender.get_mut(&sender).expect("heads contain only senders present in the map; qed");
+		ordered.extend(txs.pop_front());
+		if let Some(next) = txs.front() {
+			heads.push(key(next));
+		}
+	}
+
+	ordered
+}
+
 fn convert_error(err: txpool::Error) -> transaction::Error {
 	use self::txpool::ErrorKind;
 
@@ -0,0 +1,107 @@
+// Copyright 2015-2018 Parity Technologies (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Runtime quotas of the transaction pool.
+//!
+//! Pool limits are fixed when the pool is created. Quotas can be changed at any time
+//! to tighten them, e.g. during a spam wave, and additionally limit the number of
+//! transactions received from a single IP address. They never apply to local transactions.
+
+use std::collections::{HashMap, HashSet};
+use std::hash::Hash;
+
+use ethereum_types::H256;
+
+/// Runtime limits of the pool, `None` means the limit set at startup applies.
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub struct Quotas {
+	/// Maximal number of transactions from a single sender.
+	pub max_per_sender: Option<usize>,
+	/// Maximal number of transactions received from a single IP address.
+	pub max_per_origin: Option<usize>,
+	/// Maximal memory used by the pooled transactions, in bytes.
+	pub max_mem_usage: Option<usize>,
+}
+
+/// Hashes of pooled transactions grouped by sender or origin.
+///
+/// Removals from the pool are not reported here, so groups are pruned of transactions
+/// no longer in the pool whenever they are counted.
+#[derive(Debug)]
+pub struct Tracker<K: Hash + Eq> {
+	groups: HashMap<K, HashSet<H256>>,
+}
+
+impl<K: Hash + Eq> Default for Tracker<K> {
+	fn default() -> Self {
+		Tracker {
+			groups: HashMap::new(),
+		}
+	}
+}
+
+impl<K: Hash + Eq> Tracker<K> {
+	/// Record a transaction imported to the pool.
+	pub fn insert(&mut self, key: K, hash: H256) {
+		self.groups.entry(key).or_insert_with(HashSet::new).insert(hash);
+	}
+
+	/// Returns transactions of given group which are still in the pool.
+	pub fn get<F: Fn(&H256) -> bool>(&mut self, key: &K, in_pool: F) -> Vec<H256> {
+		let remove = match self.groups.get_mut(key) {
+			Some(group) => {
+				group.retain(|hash| in_pool(hash));
+				group.is_empty()
+			},
+			None => return Vec::new(),
+		};
+
+		if remove {
+			self.groups.remove(key);
+			Vec::new()
+		} else {
+			self.groups[key].iter().cloned().collect()
+		}
+	}
+
+	/// Forget all transactions which are no longer in the pool.
+	pub fn prune<F: Fn(&H256) -> bool>(&mut self, in_pool: F) {
+		for group in self.groups.values_mut() {
+			group.retain(|hash| in_pool(hash));
+		}
+		self.groups.retain(|_, group| !group.is_empty());
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn should_prune_removed_transactions() {
+		let mut tracker = Tracker::default();
+		tracker.insert(1, 1.into());
+		tracker.insert(1, 2.into());
+		tracker.insert(2, 3.into());
+
+		let in_pool = |hash: &H256| *hash != 2.into();
+		assert_eq!(tracker.get(&1, &in_pool), vec![1.into()]);
+		assert_eq!(tracker.get(&3, &in_pool), vec![]);
+
+		tracker.prune(|hash| *hash == 1.into());
+		assert_eq!(tracker.groups.len(), 1);
+	}
+}
@@ -28,19 +28,49 @@
 //! from our local node (own transactions).
 
 use std::cmp;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicUsize, Ordering};
 
 use ethereum_types::U256;
 use txpool::{self, scoring};
 use super::{verifier, PrioritizationStrategy, VerifiedTransaction, ScoredTransaction};
 
+/// Default gas price bump required to replace a transaction, in basis points (12.5%).
+///
 /// Transaction with the same (sender, nonce) can be replaced only if
-/// `new_gas_price > old_gas_price + old_gas_price >> SHIFT`
-const GAS_PRICE_BUMP_SHIFT: usize = 3; // 2 = 25%, 3 = 12.5%, 4 = 6.25%
+/// `new_gas_price >= old_gas_price + old_gas_price * bump / 10_000`
+pub const DEFAULT_REPLACEMENT_BUMP: usize = 1_250;
+
+/// Maximal replacement bump that can be configured, in percent.
+pub const MAX_REPLACEMENT_BUMP_PERCENT: f64 = 1_000.0;
+
+/// Convert replacement bump given in percent to basis points.
+///
+/// Returns `None` if the value is negative or above `MAX_REPLACEMENT_BUMP_PERCENT`.
+pub fn replacement_bump_from_percent(percent: f64) -> Option<usize> {
+	if percent >= 0.0 && percent <= MAX_REPLACEMENT_BUMP_PERCENT {
+		Some((percent * 100.0).round() as usize)
+	} else {
+		None
+	}
+}
 
 /// Calculate minimal gas price requirement.
 #[inline]
-fn bump_gas_price(old_gp: U256) -> U256 {
-	old_gp.saturating_add(old_gp >> GAS_PRICE_BUMP_SHIFT)
+fn bump_gas_price(old_gp: U256, bump: usize) -> U256 {
+	old_gp.saturating_add(old_gp.saturating_mul(bump.into()) / U256::from(10_000))
+}
+
+/// Calculate the initial (not penalized) score of given transaction.
+///
+/// The score is the gas price boosted according to transaction priority.
+pub(crate) fn base_score<P: ScoredTransaction>(tx: &P) -> U256 {
+	let boost = match tx.priority() {
+		super::Priority::Local => 15,
+		super::Priority::Retracted => 10,
+		super::Priority::Regular => 0,
+	};
+	*tx.gas_price() << boost
 }
 
 /// Simple, gas-price based scoring for transactions.
@@ -48,9 +78,32 @@ fn bump_gas_price(old_gp: U256) -> U256 {
 /// NOTE: Currently penalization does not apply to new transactions that enter the pool.
 /// We might want to store penalization status in some persistent state.
 #[derive(Debug, Clone)]
-pub struct NonceAndGasPrice(pub PrioritizationStrategy);
+pub struct NonceAndGasPrice {
+	strategy: PrioritizationStrategy,
+	/// Gas price bump required to replace a transaction, in basis points.
+	/// Shared with the queue, so it can be changed while the pool is running.
+	replacement_bump: Arc<AtomicUsize>,
+}
 
 impl NonceAndGasPrice {
+	/// Create new scoring with the default replacement bump.
+	pub fn new(strategy: PrioritizationStrategy) -> Self {
+		Self::with_replacement_bump(strategy, Arc::new(AtomicUsize::new(DEFAULT_REPLACEMENT_BUMP)))
+	}
+
+	/// Create new scoring reading the replacement bump (in basis points) from given shared value.
+	pub fn with_replacement_bump(strategy: PrioritizationStrategy, replacement_bump: Arc<AtomicUsize>) -> Self {
+		NonceAndGasPrice {
+			strategy,
+			replacement_bump,
+		}
+	}
+
+	/// Prioritization strategy used by this scoring.
+	pub fn strategy(&self) -> PrioritizationStrategy {
+		self.strategy
+	}
+
 	/// Decide if the transaction should even be considered into the pool (if the pool is full).
 	///
 	/// Used by Verifier to quickly reject transactions that don't have any chance to get into the pool later on,
@@ -83,7 +136,7 @@ impl<P> txpool::Scoring<P> for NonceAndGasPrice where P: ScoredTransaction + txp
 		let old_gp = old.gas_price();
 		let new_gp = new.gas_price();
 
-		let min_required_gp = bump_gas_price(*old_gp);
+		let min_required_gp = bump_gas_price(*old_gp, self.replacement_bump.load(Ordering::Relaxed));
 
 		match min_required_gp.cmp(&new_gp) {
 			cmp::Ordering::Greater => scoring::Choice::RejectNew,
@@ -101,13 +154,7 @@ impl<P> txpool::Scoring<P> for NonceAndGasPrice where P: ScoredTransaction + txp
 				assert!(i < txs.len());
 				assert!(i < scores.len());
 
-				scores[i] = *txs[i].transaction.gas_price();
-				let boost = match txs[i].priority() {
-					super::Priority::Local => 15,
-					super::Priority::Retracted => 10,
-					super::Priority::Regular => 0,
-				};
-				scores[i] = scores[i] << boost;
+				scores[i] = base_score(&*txs[i].transaction);
 			},
 			// We are only sending an event in case of penalization.
 			// So just lower the priority of all non-local transactions.
@@ -161,7 +208,7 @@ mod tests {
 
 	#[test]
 	fn should_replace_same_sender_by_nonce() {
-		let scoring = NonceAndGasPrice(PrioritizationStrategy::GasPriceOnly);
+		let scoring = NonceAndGasPrice::new(PrioritizationStrategy::GasPriceOnly);
 
 		let tx1 = Tx {
 			nonce: 1,
@@ -199,10 +246,35 @@ mod tests {
 		assert_eq!(scoring.should_replace(&txs[3], &txs[1]), RejectNew);
 	}
 
+	#[test]
+	fn should_require_configured_replacement_bump() {
+		let bump = Arc::new(AtomicUsize::new(DEFAULT_REPLACEMENT_BUMP));
+		let scoring = NonceAndGasPrice::with_replacement_bump(PrioritizationStrategy::GasPriceOnly, bump.clone());
+
+		let keypair = Random.generate().unwrap();
+		let txs = vec![100, 111, 112, 125].into_iter().map(|gas_price| {
+			let tx = Tx { nonce: 1, gas_price, ..Default::default() };
+			tx.unsigned().sign(keypair.secret(), None).verified()
+		}).collect::<Vec<_>>();
+
+		// default bump of 12.5%
+		assert_eq!(scoring.choose(&txs[0], &txs[1]), RejectNew);
+		assert_eq!(scoring.choose(&txs[0], &txs[2]), ReplaceOld);
+
+		// raised to 25%
+		bump.store(2_500, Ordering::SeqCst);
+		assert_eq!(scoring.choose(&txs[0], &txs[2]), RejectNew);
+		assert_eq!(scoring.choose(&txs[0], &txs[3]), ReplaceOld);
+
+		// lowered to 10%
+		bump.store(1_000, Ordering::SeqCst);
+		assert_eq!(scoring.choose(&txs[0], &txs[1]), ReplaceOld);
+	}
+
 	#[test]
 	fn should_replace_different_sender_by_priority_and_gas_price() {
 		// given
-		let scoring = NonceAndGasPrice(PrioritizationStrategy::GasPriceOnly);
+		let scoring = NonceAndGasPrice::new(PrioritizationStrategy::GasPriceOnly);
 		let tx_regular_low_gas = {
 			let tx = Tx {
 				nonce: 1,
@@ -253,7 +325,7 @@ mod tests {
 	#[test]
 	fn should_calculate_score_correctly() {
 		// given
-		let scoring = NonceAndGasPrice(PrioritizationStrategy::GasPriceOnly);
+		let scoring = NonceAndGasPrice::new(PrioritizationStrategy::GasPriceOnly);
 		let (tx1, tx2, tx3) = Tx::default().signed_triple();
 		let transactions = vec![tx1, tx2, tx3].into_iter().enumerate().map(|(i, tx)| {
 			let mut verified = tx.verified();
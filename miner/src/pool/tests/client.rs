@@ -17,7 +17,7 @@
 use std::sync::{atomic, Arc};
 
 use ethereum_types::{U256, H256, Address};
-use rlp::Rlp;
+use rlp::{Encodable, Rlp};
 use transaction::{self, Transaction, SignedTransaction, UnverifiedTransaction};
 
 use pool;
@@ -88,6 +88,7 @@ impl TestClient {
 			hash: tx.hash(),
 			sender: tx.sender(),
 			priority: pool::Priority::Regular,
+			mem_usage: pool::mem_usage(tx.rlp_bytes().len()),
 			transaction: tx,
 			insertion_id: 1,
 		}
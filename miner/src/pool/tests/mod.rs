@@ -15,10 +15,11 @@
 // along with Parity.  If not, see <http://www.gnu.org/licenses/>.
 
 use ethereum_types::U256;
+use rlp::Encodable;
 use transaction::{self, PendingTransaction};
 use txpool;
 
-use pool::{verifier, TransactionQueue, PrioritizationStrategy, PendingSettings, PendingOrdering};
+use pool::{verifier, mem_usage, TransactionQueue, PrioritizationStrategy, PendingSettings, PendingOrdering, Quotas};
 
 pub mod tx;
 pub mod client;
@@ -31,7 +32,7 @@ fn new_queue() -> TransactionQueue {
 		txpool::Options {
 			max_count: 3,
 			max_per_sender: 3,
-			max_mem_usage: 50
+			max_mem_usage: 50 * 1024
 		},
 		verifier::Options {
 			minimal_gas_price: 1.into(),
@@ -49,7 +50,7 @@ fn should_return_correct_nonces_when_dropped_because_of_limit() {
 		txpool::Options {
 			max_count: 3,
 			max_per_sender: 1,
-			max_mem_usage: 50
+			max_mem_usage: 50 * 1024
 		},
 		verifier::Options {
 			minimal_gas_price: 1.into(),
@@ -103,7 +104,7 @@ fn should_never_drop_local_transactions_from_different_senders() {
 		txpool::Options {
 			max_count: 3,
 			max_per_sender: 1,
-			max_mem_usage: 50
+			max_mem_usage: 50 * 1024
 		},
 		verifier::Options {
 			minimal_gas_price: 1.into(),
@@ -249,6 +250,50 @@ fn should_import_transaction_below_min_gas_price_threshold_if_local() {
 	assert_eq!(txq.status().status.transaction_count, 1);
 }
 
+#[test]
+fn should_demote_transactions_below_raised_min_gas_price() {
+	// given
+	let txq = new_queue();
+	let cheap = Tx::gas_price(2).signed();
+	let expensive = Tx::gas_price(4).signed();
+	let local = Tx::gas_price(2).signed();
+	let res = txq.import(TestClient::new(), vec![cheap.unverified(), expensive.unverified(), local.local()]);
+	assert_eq!(res, vec![Ok(()), Ok(()), Ok(())]);
+
+	// when
+	txq.set_verifier_options(verifier::Options {
+		minimal_gas_price: 3.into(),
+		..Default::default()
+	});
+	let demoted = txq.demote_underpriced();
+
+	// then
+	assert_eq!(demoted, 1);
+	assert_eq!(txq.status().status.transaction_count, 2);
+	assert_eq!(txq.demote_underpriced(), 0);
+}
+
+#[test]
+fn should_keep_underpriced_transactions_during_grace_period() {
+	// given
+	let txq = new_queue();
+	txq.set_demotion_grace(::std::time::Duration::from_secs(3600));
+	let tx = Tx::gas_price(2).signed();
+	let res = txq.import(TestClient::new(), vec![tx.unverified()]);
+	assert_eq!(res, vec![Ok(())]);
+
+	// when
+	txq.set_verifier_options(verifier::Options {
+		minimal_gas_price: 3.into(),
+		..Default::default()
+	});
+	let demoted = txq.demote_underpriced();
+
+	// then
+	assert_eq!(demoted, 0);
+	assert_eq!(txq.status().status.transaction_count, 1);
+}
+
 #[test]
 fn should_import_txs_from_same_sender() {
 	// given
@@ -267,6 +312,25 @@ fn should_import_txs_from_same_sender() {
 	assert_eq!(top.len(), 2);
 }
 
+#[test]
+fn should_enforce_per_sender_quota_for_external_transactions() {
+	// given
+	let txq = new_queue();
+	txq.set_quotas(Quotas { max_per_sender: Some(2), ..Default::default() });
+	let (tx1, tx2, tx3) = Tx::default().signed_triple();
+	let (tx4, tx5, tx6) = Tx::default().signed_triple();
+
+	// when
+	let res = txq.import(TestClient::new(), vec![tx1, tx2, tx3].unverified());
+	txq.clear();
+	let res2 = txq.import(TestClient::new(), vec![tx4, tx5, tx6].local());
+
+	// then
+	assert_eq!(res, vec![Ok(()), Ok(()), Err(transaction::Error::LimitReached)]);
+	assert_eq!(res2, vec![Ok(()), Ok(()), Ok(())]);
+	assert_eq!(txq.status().status.transaction_count, 3);
+}
+
 #[test]
 fn should_prioritize_local_transactions_within_same_nonce_height() {
 	// given
@@ -311,6 +375,68 @@ fn should_prioritize_reimported_transactions_within_same_nonce_height() {
 	assert_eq!(top.len(), 2);
 }
 
+#[test]
+fn should_order_transactions_with_equal_score_deterministically() {
+	// given
+	let txq1 = new_queue();
+	let txq2 = new_queue();
+	let tx1 = Tx::default().signed();
+	let tx2 = Tx::default().signed();
+	let tx3 = Tx::default().signed();
+	let settings = PendingSettings {
+		block_number: 0,
+		current_timestamp: 0,
+		nonce_cap: None,
+		max_len: usize::max_value(),
+		ordering: PendingOrdering::Deterministic,
+	};
+
+	// when
+	let res = txq1.import(TestClient::new(), vec![tx1.clone(), tx2.clone(), tx3.clone()].unverified());
+	assert_eq!(res, vec![Ok(()), Ok(()), Ok(())]);
+	let res = txq2.import(TestClient::new(), vec![tx3, tx2, tx1].unverified());
+	assert_eq!(res, vec![Ok(()), Ok(()), Ok(())]);
+
+	// then
+	let top1 = txq1.pending(TestClient::new(), settings.clone());
+	let top2 = txq2.pending(TestClient::new(), settings);
+	let hashes1 = top1.iter().map(|tx| tx.hash).collect::<Vec<_>>();
+	let hashes2 = top2.iter().map(|tx| tx.hash).collect::<Vec<_>>();
+	let mut sorted = hashes1.clone();
+	sorted.sort();
+	assert_eq!(hashes1, hashes2);
+	assert_eq!(hashes1, sorted);
+	assert!(!txq1.is_pending_cached());
+}
+
+#[test]
+fn should_return_pending_transactions_of_senders_beyond_the_limit() {
+	// given
+	let txq = new_queue();
+	let tx1 = Tx::gas_price(5).signed();
+	let tx2 = Tx::gas_price(1).signed();
+	let senders = vec![tx2.sender()].into_iter().collect();
+	let settings = PendingSettings {
+		block_number: 0,
+		current_timestamp: 0,
+		nonce_cap: None,
+		max_len: 1,
+		ordering: PendingOrdering::Priority,
+	};
+
+	// when
+	let res = txq.import(TestClient::new(), vec![tx1.clone(), tx2.clone()].unverified());
+	assert_eq!(res, vec![Ok(()), Ok(())]);
+
+	// then
+	let top = txq.pending(TestClient::new(), settings.clone());
+	assert_eq!(top.len(), 1);
+	assert_eq!(top[0].hash, tx1.hash());
+	let priority = txq.pending_from_senders(TestClient::new(), settings, &senders);
+	assert_eq!(priority.len(), 1);
+	assert_eq!(priority[0].hash, tx2.hash());
+}
+
 #[test]
 fn should_not_prioritize_local_transactions_with_different_nonce_height() {
 	// given
@@ -477,7 +603,7 @@ fn should_prefer_current_transactions_when_hitting_the_limit() {
 		txpool::Options {
 			max_count: 1,
 			max_per_sender: 2,
-			max_mem_usage: 50
+			max_mem_usage: 50 * 1024
 		},
 		verifier::Options {
 			minimal_gas_price: 1.into(),
@@ -707,7 +833,7 @@ fn should_accept_local_transactions_below_min_gas_price() {
 		txpool::Options {
 			max_count: 3,
 			max_per_sender: 3,
-			max_mem_usage: 50
+			max_mem_usage: 50 * 1024
 		},
 		verifier::Options {
 			minimal_gas_price: 10.into(),
@@ -890,7 +1016,7 @@ fn should_include_local_transaction_to_a_full_pool() {
 		txpool::Options {
 			max_count: 1,
 			max_per_sender: 2,
-			max_mem_usage: 50
+			max_mem_usage: 50 * 1024
 		},
 		verifier::Options {
 			minimal_gas_price: 1.into(),
@@ -922,7 +1048,7 @@ fn should_avoid_verifying_transaction_already_in_pool() {
 		txpool::Options {
 			max_count: 1,
 			max_per_sender: 2,
-			max_mem_usage: 50
+			max_mem_usage: 50 * 1024
 		},
 		verifier::Options {
 			minimal_gas_price: 1.into(),
@@ -957,7 +1083,7 @@ fn should_avoid_reverifying_recently_rejected_transactions() {
 		txpool::Options {
 			max_count: 1,
 			max_per_sender: 2,
-			max_mem_usage: 50
+			max_mem_usage: 50 * 1024
 		},
 		verifier::Options {
 			minimal_gas_price: 1.into(),
@@ -999,7 +1125,7 @@ fn should_reject_early_in_case_gas_price_is_less_than_min_effective() {
 		txpool::Options {
 			max_count: 1,
 			max_per_sender: 2,
-			max_mem_usage: 50
+			max_mem_usage: 50 * 1024
 		},
 		verifier::Options {
 			minimal_gas_price: 1.into(),
@@ -1039,7 +1165,7 @@ fn should_not_reject_early_in_case_gas_price_is_less_than_min_effective() {
 		txpool::Options {
 			max_count: 1,
 			max_per_sender: 2,
-			max_mem_usage: 50
+			max_mem_usage: 50 * 1024
 		},
 		verifier::Options {
 			minimal_gas_price: 1.into(),
@@ -1069,3 +1195,46 @@ fn should_not_reject_early_in_case_gas_price_is_less_than_min_effective() {
 	assert_eq!(txq.status().status.transaction_count, 2);
 	assert!(client.was_verification_triggered());
 }
+
+#[test]
+fn should_limit_pool_by_memory_usage() {
+	// given
+	let tx_mem_usage = mem_usage(Tx::default().signed().rlp_bytes().len());
+	let txq = TransactionQueue::new(
+		txpool::Options {
+			max_count: 1_000,
+			max_per_sender: 10,
+			max_mem_usage: 2 * tx_mem_usage + tx_mem_usage / 2,
+		},
+		verifier::Options {
+			minimal_gas_price: 1.into(),
+			block_gas_limit: 1_000_000.into(),
+			tx_gas_limit: 1_000_000.into(),
+			no_early_reject: false,
+		},
+		PrioritizationStrategy::GasPriceOnly,
+	);
+	let tx1 = Tx::gas_price(2).signed();
+	let tx2 = Tx::gas_price(3).signed();
+	let tx3 = Tx::gas_price(1).signed();
+	let tx4 = Tx::gas_price(5).signed();
+	let (hash1, hash4) = (tx1.hash(), tx4.hash());
+
+	// when
+	let res = txq.import(TestClient::new(), vec![tx1, tx2, tx3].unverified());
+
+	// then
+	assert_eq!(&res[..2], &[Ok(()), Ok(())][..]);
+	assert!(res[2].is_err());
+	assert_eq!(txq.status().status.transaction_count, 2);
+	assert!(txq.status().status.mem_usage <= txq.status().limits.max_mem_usage);
+
+	// when
+	let res = txq.import(TestClient::new(), vec![tx4].unverified());
+
+	// then
+	assert_eq!(res, vec![Ok(())]);
+	assert_eq!(txq.status().status.transaction_count, 2);
+	assert!(txq.find(&hash1).is_none());
+	assert!(txq.find(&hash4).is_some());
+}
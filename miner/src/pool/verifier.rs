@@ -254,7 +254,8 @@ impl<C: Client> txpool::Verifier<Transaction> for Verifier<C, ::pool::scoring::N
 		};
 
 		// Verify RLP payload
-		if let Err(err) = self.client.decode_transaction(&transaction.rlp_bytes()) {
+		let rlp = transaction.rlp_bytes();
+		if let Err(err) = self.client.decode_transaction(&rlp) {
 			debug!(target: "txqueue", "[{:?}] Rejected transaction's rlp payload", err);
 			bail!(err)
 		}
@@ -320,6 +321,7 @@ impl<C: Client> txpool::Verifier<Transaction> for Verifier<C, ::pool::scoring::N
 			hash,
 			sender,
 			insertion_id: self.id.fetch_add(1, atomic::Ordering::AcqRel),
+			mem_usage: super::mem_usage(rlp.len()),
 		})
 	}
 }
@@ -0,0 +1,213 @@
+// Copyright 2015-2018 Parity Technologies (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Relays local transactions to external JSON-RPC endpoints.
+//!
+//! Every transaction is POSTed as `eth_sendRawTransaction` to all configured URLs.
+//! Failed requests are retried with exponential backoff.
+
+extern crate fetch;
+extern crate parity_reactor;
+extern crate url;
+extern crate hyper;
+
+use std::sync::Arc;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::Duration;
+
+use self::fetch::{Fetch, Request, Client as FetchClient, Method};
+use self::parity_reactor::{Remote, Handle, Timeout};
+use self::url::Url;
+use self::hyper::header::ContentType;
+
+use futures::{future, Future};
+use rustc_hex::ToHex;
+
+/// Transaction relay options.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RelayOptions {
+	/// URLs of JSON-RPC endpoints transactions are sent to.
+	pub urls: Vec<String>,
+	/// Number of retries after a failed request.
+	pub retries: usize,
+	/// Delay before the first retry, doubled with every next one.
+	pub retry_delay: Duration,
+}
+
+impl Default for RelayOptions {
+	fn default() -> Self {
+		RelayOptions {
+			urls: Vec::new(),
+			retries: 3,
+			retry_delay: Duration::from_secs(1),
+		}
+	}
+}
+
+/// Transaction relay statistics.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct RelayStats {
+	/// Number of successful requests.
+	pub relayed: usize,
+	/// Number of retried requests.
+	pub retried: usize,
+	/// Number of requests which failed after all retries.
+	pub failed: usize,
+}
+
+#[derive(Debug, Default)]
+struct RelayMetrics {
+	relayed: AtomicUsize,
+	retried: AtomicUsize,
+	failed: AtomicUsize,
+}
+
+/// POSTs local transactions to given urls.
+pub struct TransactionRelay {
+	urls: Vec<Url>,
+	client: FetchClient,
+	remote: Remote,
+	retries: usize,
+	retry_delay: Duration,
+	metrics: Arc<RelayMetrics>,
+}
+
+impl TransactionRelay {
+	/// Create new `TransactionRelay`.
+	pub fn new(options: RelayOptions, fetch: FetchClient, remote: Remote) -> Self {
+		let urls = options.urls.iter().filter_map(|u| {
+			match Url::parse(u) {
+				Ok(url) => Some(url),
+				Err(e) => {
+					warn!("Error parsing URL {} : {}", u, e);
+					None
+				}
+			}
+		}).collect();
+		TransactionRelay {
+			urls: urls,
+			client: fetch,
+			remote: remote,
+			retries: options.retries,
+			retry_delay: options.retry_delay,
+			metrics: Default::default(),
+		}
+	}
+
+	/// Send the raw signed transaction, as accepted by `eth_sendRawTransaction`, to all urls.
+	pub fn relay(&self, transaction: &[u8]) {
+		let body = request_body(transaction);
+		for url in &self.urls {
+			let post = Post {
+				url: url.clone(),
+				body: body.clone(),
+				client: self.client.clone(),
+				retries: self.retries,
+				retry_delay: self.retry_delay,
+				metrics: self.metrics.clone(),
+			};
+			self.remote.spawn_fn(move |handle| post.send(handle.clone(), 0));
+		}
+	}
+
+	/// Current statistics of the relay.
+	pub fn stats(&self) -> RelayStats {
+		RelayStats {
+			relayed: self.metrics.relayed.load(Ordering::Relaxed),
+			retried: self.metrics.retried.load(Ordering::Relaxed),
+			failed: self.metrics.failed.load(Ordering::Relaxed),
+		}
+	}
+}
+
+/// A single relayed request.
+#[derive(Clone)]
+struct Post {
+	url: Url,
+	body: String,
+	client: FetchClient,
+	retries: usize,
+	retry_delay: Duration,
+	metrics: Arc<RelayMetrics>,
+}
+
+impl Post {
+	fn send(self, handle: Handle, attempt: usize) -> Box<Future<Item=(), Error=()>> {
+		let request = Request::new(self.url.clone(), Method::Post)
+			.with_header(ContentType::json())
+			.with_body(self.body.clone());
+
+		Box::new(self.client.fetch(request, Default::default())
+			.then(move |result| -> Box<Future<Item=(), Error=()>> {
+				let error = match result {
+					Ok(ref response) if response.is_success() => {
+						trace!(target: "tx_relay", "Transaction relayed to {}", self.url);
+						self.metrics.relayed.fetch_add(1, Ordering::Relaxed);
+						return Box::new(future::ok(()));
+					},
+					Ok(response) => format!("HTTP status {}", response.status()),
+					Err(e) => format!("{}", e),
+				};
+
+				if attempt >= self.retries {
+					warn!(target: "tx_relay", "Error relaying transaction to {} : {}, giving up", self.url, error);
+					self.metrics.failed.fetch_add(1, Ordering::Relaxed);
+					return Box::new(future::ok(()));
+				}
+
+				let delay = retry_delay(self.retry_delay, attempt);
+				debug!(target: "tx_relay", "Error relaying transaction to {} : {}, retrying in {:?}", self.url, error, delay);
+				self.metrics.retried.fetch_add(1, Ordering::Relaxed);
+				match Timeout::new(delay, &handle) {
+					Ok(timeout) => Box::new(timeout.then(move |_| self.send(handle, attempt + 1))),
+					Err(_) => Box::new(future::ok(())),
+				}
+			}))
+	}
+}
+
+fn request_body(transaction: &[u8]) -> String {
+	format!(
+		r#"{{"jsonrpc":"2.0","id":1,"method":"eth_sendRawTransaction","params":["0x{}"]}}"#,
+		transaction.to_hex()
+	)
+}
+
+fn retry_delay(base: Duration, attempt: usize) -> Duration {
+	base * (1u32 << ::std::cmp::min(attempt, 16) as u32)
+}
+
+#[cfg(test)]
+mod tests {
+	use std::time::Duration;
+	use super::{request_body, retry_delay};
+
+	#[test]
+	fn should_encode_raw_transaction_request() {
+		assert_eq!(
+			request_body(&[0xf8, 0x01]),
+			r#"{"jsonrpc":"2.0","id":1,"method":"eth_sendRawTransaction","params":["0xf801"]}"#
+		);
+	}
+
+	#[test]
+	fn should_back_off_exponentially() {
+		let base = Duration::from_millis(500);
+		assert_eq!(retry_delay(base, 0), Duration::from_millis(500));
+		assert_eq!(retry_delay(base, 1), Duration::from_secs(1));
+		assert_eq!(retry_delay(base, 3), Duration::from_secs(4));
+	}
+}
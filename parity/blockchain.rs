@@ -21,14 +21,15 @@ use std::time::{Instant, Duration};
 use std::thread::sleep;
 use std::sync::Arc;
 use rustc_hex::FromHex;
-use hash::{keccak, KECCAK_NULL_RLP};
-use ethereum_types::{U256, H256, Address};
+use hash::{KECCAK_EMPTY, KECCAK_NULL_RLP};
+use ethereum_types::U256;
 use bytes::ToPretty;
 use rlp::PayloadInfo;
 use ethcore::account_provider::AccountProvider;
-use ethcore::client::{Mode, DatabaseCompactionProfile, VMType, Nonce, Balance, BlockChainClient, BlockId, BlockInfo, ImportBlock};
+use ethcore::client::{Mode, DatabaseCompactionProfile, VMType, BlockChainClient, BlockId, BlockInfo, ImportBlock};
 use ethcore::error::{ImportErrorKind, ErrorKind as EthcoreErrorKind, Error as EthcoreError};
 use ethcore::miner::Miner;
+use ethcore::state::ExportedAccount;
 use ethcore::verification::queue::VerifierSettings;
 use ethcore::verification::queue::kind::blocks::Unverified;
 use ethcore_service::ClientService;
@@ -212,6 +213,8 @@ fn execute_import_light(cmd: ImportBlockchain) -> Result<(), String> {
 	let service = LightClientService::start(config, &spec, fetch, db, cache)
 		.map_err(|e| format!("Failed to start client: {}", e))?;
 
+	let eip1559_transition = spec.engine.params().eip1559_transition;
+
 	// free up the spec in memory.
 	drop(spec);
 
@@ -241,7 +244,8 @@ fn execute_import_light(cmd: ImportBlockchain) -> Result<(), String> {
 	let do_import = |bytes: Vec<u8>| {
 		while client.queue_info().is_full() { sleep(Duration::from_secs(1)); }
 
-		let header: ::ethcore::header::Header = ::rlp::Rlp::new(&bytes).val_at(0)
+		let header = ::rlp::Rlp::new(&bytes).at(0)
+			.and_then(|r| ::ethcore::header::Header::decode_rlp(&r, eip1559_transition))
 			.map_err(|e| format!("Bad block: {}", e))?;
 
 		if client.best_block_header().number() >= header.number() { return Ok(()) }
@@ -358,7 +362,7 @@ fn execute_import(cmd: ImportBlockchain) -> Result<(), String> {
 
 	client_config.queue.verifier_settings = cmd.verifier_settings;
 
-	let restoration_db_handler = db::restoration_db_handler(&client_path, &client_config);
+	let restoration_db_handler = db::restoration_db_handler(&client_path, &client_config, None);
 	let client_db = restoration_db_handler.open(&client_path)
 		.map_err(|e| format!("Failed to open database {:?}", e))?;
 
@@ -378,6 +382,8 @@ fn execute_import(cmd: ImportBlockchain) -> Result<(), String> {
 		Default::default(),
 	).map_err(|e| format!("Client service error: {:?}", e))?;
 
+	let eip1559_transition = spec.engine.params().eip1559_transition;
+
 	// free up the spec in memory.
 	drop(spec);
 
@@ -418,7 +424,7 @@ fn execute_import(cmd: ImportBlockchain) -> Result<(), String> {
 	service.register_io_handler(informant).map_err(|_| "Unable to register informant handler".to_owned())?;
 
 	let do_import = |bytes| {
-		let block = Unverified::from_rlp(bytes).map_err(|_| "Invalid block rlp")?;
+		let block = Unverified::from_rlp(bytes, eip1559_transition).map_err(|_| "Invalid block rlp")?;
 		while client.queue_info().is_full() { sleep(Duration::from_secs(1)); }
 		match client.import_block(block) {
 			Err(EthcoreError(EthcoreErrorKind::Import(ImportErrorKind::AlreadyInChain), _)) => {
@@ -482,7 +488,7 @@ fn execute_import(cmd: ImportBlockchain) -> Result<(), String> {
 	Ok(())
 }
 
-fn start_client(
+pub fn start_client(
 	dirs: Directories,
 	spec: SpecType,
 	pruning: Pruning,
@@ -548,7 +554,7 @@ fn start_client(
 		true,
 	);
 
-	let restoration_db_handler = db::restoration_db_handler(&client_path, &client_config);
+	let restoration_db_handler = db::restoration_db_handler(&client_path, &client_config, None);
 	let client_db = restoration_db_handler.open(&client_path)
 		.map_err(|e| format!("Failed to open database {:?}", e))?;
 
@@ -636,71 +642,71 @@ fn execute_export_state(cmd: ExportState) -> Result<(), String> {
 		None => Box::new(io::stdout()),
 	};
 
-	let mut last: Option<Address> = None;
-	let at = cmd.at;
+	let accounts = client.state_producer(cmd.at, cmd.storage)
+		.ok_or("Specified block not found or fat DB is not enabled")?;
 	let mut i = 0usize;
 
-	out.write_fmt(format_args!("{{ \"state\": {{", )).expect("Couldn't write to stream.");
-	loop {
-		let accounts = client.list_accounts(at, last.as_ref(), 1000).ok_or("Specified block not found")?;
-		if accounts.is_empty() {
-			break;
+	if cmd.format.is_none() {
+		out.write_fmt(format_args!("{{ \"state\": {{", )).expect("Couldn't write to stream.");
+	}
+	for account in accounts {
+		let mut account = account.map_err(|e| format!("Error reading state: {}", e))?;
+		if cmd.min_balance.map_or(false, |m| account.balance < m) || cmd.max_balance.map_or(false, |m| account.balance > m) {
+			continue; //filtered out
+		}
+		if !cmd.code {
+			account.code = None;
 		}
 
-		for account in accounts.into_iter() {
-			let balance = client.balance(&account, at.into()).unwrap_or_else(U256::zero);
-			if cmd.min_balance.map_or(false, |m| balance < m) || cmd.max_balance.map_or(false, |m| balance > m) {
-				last = Some(account);
-				continue; //filtered out
-			}
+		match cmd.format {
+			Some(DataFormat::Binary) => {
+				out.write(&::rlp::encode(&account)).expect("Couldn't write to stream.");
+			},
+			Some(DataFormat::Hex) => {
+				out.write_fmt(format_args!("{}\n", ::rlp::encode(&account).pretty())).expect("Couldn't write to stream.");
+			},
+			None => write_json_account(&mut out, &account, i == 0),
+		}
 
-			if i != 0 {
-				out.write(b",").expect("Write error");
-			}
-			out.write_fmt(format_args!("\n\"0x{:x}\": {{\"balance\": \"{:x}\", \"nonce\": \"{:x}\"", account, balance, client.nonce(&account, at).unwrap_or_else(U256::zero))).expect("Write error");
-			let code = client.code(&account, at.into()).unwrap_or(None).unwrap_or_else(Vec::new);
-			if !code.is_empty() {
-				out.write_fmt(format_args!(", \"code_hash\": \"0x{:x}\"", keccak(&code))).expect("Write error");
-				if cmd.code {
-					out.write_fmt(format_args!(", \"code\": \"{}\"", code.to_hex())).expect("Write error");
-				}
-			}
-			let storage_root = client.storage_root(&account, at).unwrap_or(KECCAK_NULL_RLP);
-			if storage_root != KECCAK_NULL_RLP {
-				out.write_fmt(format_args!(", \"storage_root\": \"0x{:x}\"", storage_root)).expect("Write error");
-				if cmd.storage {
-					out.write_fmt(format_args!(", \"storage\": {{")).expect("Write error");
-					let mut last_storage: Option<H256> = None;
-					loop {
-						let keys = client.list_storage(at, &account, last_storage.as_ref(), 1000).ok_or("Specified block not found")?;
-						if keys.is_empty() {
-							break;
-						}
-
-						for key in keys.into_iter() {
-							if last_storage.is_some() {
-								out.write(b",").expect("Write error");
-							}
-							out.write_fmt(format_args!("\n\t\"0x{:x}\": \"0x{:x}\"", key, client.storage_at(&account, &key, at.into()).unwrap_or_else(Default::default))).expect("Write error");
-							last_storage = Some(key);
-						}
-					}
-					out.write(b"\n}").expect("Write error");
-				}
-			}
-			out.write(b"}").expect("Write error");
-			i += 1;
-			if i % 10000 == 0 {
-				info!("Account #{}", i);
-			}
-			last = Some(account);
+		i += 1;
+		if i % 10000 == 0 {
+			info!("Account #{}", i);
 		}
 	}
-	out.write_fmt(format_args!("\n}}}}")).expect("Write error");
+	if cmd.format.is_none() {
+		out.write_fmt(format_args!("\n}}}}")).expect("Write error");
+	}
 	info!("Export completed.");
 	Ok(())
 }
 
+fn write_json_account(out: &mut Box<io::Write>, account: &ExportedAccount, first: bool) {
+	if !first {
+		out.write(b",").expect("Write error");
+	}
+	out.write_fmt(format_args!("\n\"0x{:x}\": {{\"balance\": \"{:x}\", \"nonce\": \"{:x}\"", account.address, account.balance, account.nonce)).expect("Write error");
+	if account.code_hash != KECCAK_EMPTY {
+		out.write_fmt(format_args!(", \"code_hash\": \"0x{:x}\"", account.code_hash)).expect("Write error");
+		if let Some(ref code) = account.code {
+			out.write_fmt(format_args!(", \"code\": \"{}\"", code.to_hex())).expect("Write error");
+		}
+	}
+	if account.storage_root != KECCAK_NULL_RLP {
+		out.write_fmt(format_args!(", \"storage_root\": \"0x{:x}\"", account.storage_root)).expect("Write error");
+		if !account.storage.is_empty() {
+			out.write_fmt(format_args!(", \"storage\": {{")).expect("Write error");
+			for (n, (key, value)) in account.storage.iter().enumerate() {
+				if n != 0 {
+					out.write(b",").expect("Write error");
+				}
+				out.write_fmt(format_args!("\n\t\"0x{:x}\": \"0x{:x}\"", key, value)).expect("Write error");
+			}
+			out.write(b"\n}").expect("Write error");
+		}
+	}
+	out.write(b"}").expect("Write error");
+}
+
 pub fn kill_db(cmd: KillBlockchain) -> Result<(), String> {
 	let spec = cmd.spec.spec(&cmd.dirs.cache)?;
 	let genesis_hash = spec.genesis_header().hash();
@@ -0,0 +1,232 @@
+// Copyright 2015-2018 Parity Technologies (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Loading of layered TOML config files.
+//!
+//! A config file may pull in other files with a top-level `include` key holding a path or
+//! a list of paths, resolved relative to the including file. Included files are applied in
+//! order and the including file overrides all of them, tables being merged key by key.
+//!
+//! Before parsing, every `${NAME}` in the file is replaced with the value of the environment
+//! variable `NAME`, except on comment lines. Use `$${` to write a literal `${`.
+
+use std::{env, fmt, fs, io};
+use std::path::{Path, PathBuf};
+
+use serde::de::DeserializeOwned;
+use toml::{self, Value};
+use toml::value::Table;
+
+const INCLUDE_KEY: &str = "include";
+
+/// Error loading a config file.
+#[derive(Debug)]
+pub enum Error {
+	/// File could not be read.
+	Io(PathBuf, io::Error),
+	/// File is not valid TOML.
+	Syntax(PathBuf, toml::de::Error),
+	/// File contains unknown keys or values of the wrong type.
+	Invalid(PathBuf, toml::de::Error),
+	/// Referenced environment variable is not set.
+	UnsetVariable(PathBuf, usize, String),
+	/// Malformed `${...}` reference.
+	InvalidSubstitution(PathBuf, usize),
+	/// `include` is neither a path nor a list of paths.
+	InvalidInclude(PathBuf),
+	/// File includes itself, directly or through other files.
+	IncludeCycle(PathBuf),
+}
+
+impl fmt::Display for Error {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		match *self {
+			Error::Io(ref path, ref e) => write!(f, "{}: {}", path.display(), e),
+			Error::Syntax(ref path, ref e) | Error::Invalid(ref path, ref e) => write!(f, "{}: {}", path.display(), e),
+			Error::UnsetVariable(ref path, line, ref name) =>
+				write!(f, "{}:{}: environment variable `{}` is not set", path.display(), line, name),
+			Error::InvalidSubstitution(ref path, line) =>
+				write!(f, "{}:{}: expected `${{NAME}}` with a name made of letters, digits and `_`", path.display(), line),
+			Error::InvalidInclude(ref path) =>
+				write!(f, "{}: `{}` must be a path or a list of paths", path.display(), INCLUDE_KEY),
+			Error::IncludeCycle(ref path) =>
+				write!(f, "{}: file is included recursively", path.display()),
+		}
+	}
+}
+
+/// Loads the config file at `path` together with everything it includes.
+///
+/// Every file is checked against `T` on its own, so errors point at the file that caused them.
+pub fn load<T>(path: &Path) -> Result<T, Error> where
+	T: DeserializeOwned,
+{
+	let value = load_file::<T>(path, &mut Vec::new())?;
+	value.try_into().map_err(|e| Error::Invalid(path.to_owned(), e))
+}
+
+fn load_file<T>(path: &Path, stack: &mut Vec<PathBuf>) -> Result<Value, Error> where
+	T: DeserializeOwned,
+{
+	let canonical = path.canonicalize().map_err(|e| Error::Io(path.to_owned(), e))?;
+	if stack.contains(&canonical) {
+		return Err(Error::IncludeCycle(path.to_owned()));
+	}
+
+	let raw = fs::read_to_string(path).map_err(|e| Error::Io(path.to_owned(), e))?;
+	let text = substitute(path, &raw, |name| env::var(name).ok())?;
+	let mut value: Value = toml::from_str(&text).map_err(|e| Error::Syntax(path.to_owned(), e))?;
+
+	let includes = match value {
+		Value::Table(ref mut table) => match table.remove(INCLUDE_KEY) {
+			None => Vec::new(),
+			Some(Value::String(include)) => vec![include],
+			Some(Value::Array(includes)) => includes.into_iter()
+				.map(|include| match include {
+					Value::String(include) => Ok(include),
+					_ => Err(Error::InvalidInclude(path.to_owned())),
+				})
+				.collect::<Result<_, _>>()?,
+			Some(_) => return Err(Error::InvalidInclude(path.to_owned())),
+		},
+		_ => Vec::new(),
+	};
+	value.clone().try_into::<T>().map_err(|e| Error::Invalid(path.to_owned(), e))?;
+
+	let base = path.parent().unwrap_or_else(|| Path::new(""));
+	stack.push(canonical);
+	let mut merged = Value::Table(Table::new());
+	for include in includes {
+		let included = load_file::<T>(&base.join(include), stack)?;
+		merge(&mut merged, included);
+	}
+	stack.pop();
+
+	merge(&mut merged, value);
+	Ok(merged)
+}
+
+/// Replaces `${NAME}` references using `lookup`. Comment lines are left untouched.
+fn substitute<F>(path: &Path, text: &str, lookup: F) -> Result<String, Error> where
+	F: Fn(&str) -> Option<String>,
+{
+	let mut result = String::with_capacity(text.len());
+	for (index, line) in text.split('\n').enumerate() {
+		if index > 0 {
+			result.push('\n');
+		}
+
+		if line.trim_left().starts_with('#') {
+			result.push_str(line);
+			continue;
+		}
+
+		let line_number = index + 1;
+		let mut rest = line;
+		while let Some(start) = rest.find('$') {
+			result.push_str(&rest[..start]);
+			rest = &rest[start..];
+
+			if rest.starts_with("$${") {
+				result.push_str("${");
+				rest = &rest[3..];
+			} else if rest.starts_with("${") {
+				let end = rest.find('}').ok_or_else(|| Error::InvalidSubstitution(path.to_owned(), line_number))?;
+				let name = &rest[2..end];
+				if name.is_empty() || !name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_') {
+					return Err(Error::InvalidSubstitution(path.to_owned(), line_number));
+				}
+				let value = lookup(name).ok_or_else(|| Error::UnsetVariable(path.to_owned(), line_number, name.into()))?;
+				result.push_str(&value);
+				rest = &rest[end + 1..];
+			} else {
+				result.push('$');
+				rest = &rest[1..];
+			}
+		}
+		result.push_str(rest);
+	}
+	Ok(result)
+}
+
+/// Merges `overlay` into `base`. Tables are merged recursively, any other value is replaced.
+fn merge(base: &mut Value, overlay: Value) {
+	match (base, overlay) {
+		(&mut Value::Table(ref mut base), Value::Table(overlay)) => {
+			for (key, value) in overlay {
+				let value = match base.remove(&key) {
+					Some(mut existing) => {
+						merge(&mut existing, value);
+						existing
+					},
+					None => value,
+				};
+				base.insert(key, value);
+			}
+		},
+		(base, overlay) => *base = overlay,
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use std::fs;
+	use std::path::Path;
+	use tempdir::TempDir;
+	use toml::Value;
+	use super::{load, substitute, Error};
+
+	#[test]
+	fn substitutes_environment_variables() {
+		let path = Path::new("config.toml");
+		let lookup = |name: &str| if name == "PORT" { Some("30305".into()) } else { None };
+
+		assert_eq!(substitute(path, "port = ${PORT}\nx = \"$${PORT} $5\"\n# ${UNSET}", &lookup).unwrap(), "port = 30305\nx = \"${PORT} $5\"\n# ${UNSET}");
+		match substitute(path, "a = 1\nb = \"${MISSING}\"", &lookup) {
+			Err(Error::UnsetVariable(_, 2, ref name)) if name == "MISSING" => {},
+			other => panic!("Unexpected result: {:?}", other),
+		}
+		match substitute(path, "a = \"${PORT\"", &lookup) {
+			Err(Error::InvalidSubstitution(_, 1)) => {},
+			other => panic!("Unexpected result: {:?}", other),
+		}
+	}
+
+	#[test]
+	fn merges_included_files() {
+		let dir = TempDir::new("config_file").unwrap();
+		fs::create_dir(dir.path().join("base")).unwrap();
+		fs::write(dir.path().join("base/network.toml"), "[network]\nport = 30303\nmax_peers = 50\n").unwrap();
+		fs::write(dir.path().join("base/rpc.toml"), "[rpc]\nport = 8545\n").unwrap();
+		fs::write(dir.path().join("node.toml"), "include = [\"base/network.toml\", \"base/rpc.toml\"]\n[network]\nport = 30304\n").unwrap();
+
+		let config: Value = load(&dir.path().join("node.toml")).unwrap();
+		let expected: Value = "[network]\nport = 30304\nmax_peers = 50\n[rpc]\nport = 8545\n".parse().unwrap();
+		assert_eq!(config, expected);
+	}
+
+	#[test]
+	fn rejects_include_cycles() {
+		let dir = TempDir::new("config_file").unwrap();
+		fs::write(dir.path().join("a.toml"), "include = \"b.toml\"\n").unwrap();
+		fs::write(dir.path().join("b.toml"), "include = \"a.toml\"\n").unwrap();
+
+		match load::<Value>(&dir.path().join("a.toml")) {
+			Err(Error::IncludeCycle(_)) => {},
+			other => panic!("Unexpected result: {:?}", other),
+		}
+	}
+}
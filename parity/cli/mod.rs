@@ -17,6 +17,7 @@
 #[macro_use]
 mod usage;
 mod presets;
+mod config_file;
 
 usage! {
 	{
@@ -135,7 +136,7 @@ usage! {
 
 				ARG arg_export_state_format: (Option<String>) = None,
 				"--format=[FORMAT]",
-				"Export in a given format. FORMAT must be either 'hex' or 'binary'. (default: binary)",
+				"Export accounts as an RLP stream in a given format. FORMAT must be either 'hex' or 'binary'. Accounts are exported as JSON if no format is given.",
 
 				ARG arg_export_state_file: (Option<String>) = None,
 				"[FILE]",
@@ -219,6 +220,71 @@ usage! {
 			}
 		}
 
+		CMD cmd_test
+		{
+			"Run tests",
+
+			CMD cmd_test_run
+			{
+				"Run state tests in the ethereum/tests format",
+
+				ARG arg_test_run_fork: (Option<String>) = None,
+				"--fork=[FORK]",
+				"Run only cases of the given fork, e.g. Byzantium or Constantinople.",
+
+				ARG arg_test_run_path: (Option<String>) = None,
+				"<PATH>",
+				"Path to a test file or a directory of test files",
+			}
+		}
+
+		CMD cmd_test_gen
+		{
+			"Generate test fixtures",
+
+			CMD cmd_test_gen_state
+			{
+				"Generate a state test in the ethereum/tests format from a transaction of the given --chain (default: mainnet). The sender is replaced with the standard test account. Generating tests of non-recent transactions will only work with --pruning archive",
+
+				ARG arg_test_gen_state_fork: (String) = "Byzantium",
+				"--fork=[FORK]",
+				"Rules the test is generated for, as named in the state tests, e.g. Byzantium or Constantinople.",
+
+				ARG arg_test_gen_state_name: (Option<String>) = None,
+				"--name=[NAME]",
+				"Name of the test. (default: the transaction hash)",
+
+				ARG arg_test_gen_state_transaction: (Option<String>) = None,
+				"<TRANSACTION>",
+				"Hash of the transaction",
+
+				ARG arg_test_gen_state_file: (Option<String>) = None,
+				"[FILE]",
+				"Path to the file to write the test to",
+			}
+		}
+
+		CMD cmd_dev_network
+		{
+			"Generate keys, a chain specification and node configurations for a local Aura network. Every node uses its own ports and data directory, so the whole network can run on a single machine.",
+
+			ARG arg_dev_network_validators: (usize) = 4usize,
+			"--validators=[NUM]",
+			"Number of validator nodes in the network.",
+
+			ARG arg_dev_network_out: (String) = "./dev-network",
+			"--out=[DIR]",
+			"Directory to write the network to.",
+
+			ARG arg_dev_network_step_duration: (u64) = 5u64,
+			"--step-duration=[SEC]",
+			"Duration of an Aura step, i.e. the block time of the network.",
+
+			FLAG flag_dev_network_run: (bool) = false,
+			"--run",
+			"Start all nodes of the generated network in this process.",
+		}
+
 		CMD cmd_export_hardcoded_sync
 		{
 			"Print the hashed light clients headers of the given --chain (default: mainnet) in a JSON format. To be used as hardcoded headers in a genesis file.",
@@ -387,6 +453,10 @@ usage! {
 			"--no-discovery",
 			"Disable new peer discovery.",
 
+			FLAG flag_discovery_v5: (bool) = false, or |c: &Config| c.network.as_ref()?.discovery_v5.clone(),
+			"--discovery-v5",
+			"Enable topic discovery of discovery v5 alongside discovery v4. Nodes serving light clients advertise themselves and light clients look for them.",
+
 			FLAG flag_reserved_only: (bool) = false, or |c: &Config| c.network.as_ref()?.reserved_only.clone(),
 			"--reserved-only",
 			"Connect only to reserved nodes.",
@@ -395,6 +465,10 @@ usage! {
 			"--no-ancient-blocks",
 			"Disable downloading old blocks after snapshot restoration or warp sync. Not recommended.",
 
+			FLAG flag_no_ancient_receipts: (bool) = false, or |_| None,
+			"--no-ancient-receipts",
+			"Disable downloading receipts of old blocks after snapshot restoration or warp sync. Receipts of transactions in these blocks will not be available.",
+
 			FLAG flag_no_serve_light: (bool) = false, or |c: &Config| c.network.as_ref()?.no_serve_light.clone(),
 			"--no-serve-light",
 			"Disable serving of light peers.",
@@ -423,13 +497,21 @@ usage! {
 			"--snapshot-peers=[NUM]",
 			"Allow additional NUM peers for a snapshot sync.",
 
+			ARG arg_peer_slots: (Option<String>) = None, or |c: &Config| c.network.as_ref()?.peer_slots.clone(),
+			"--peer-slots=[SLOTS]",
+			"Reserve peer slots for subprotocols, as a comma separated list of PROTOCOL:MIN:MAX in order of priority, e.g. pip:10:50 for light clients. Up to MIN peers of a protocol don't take the general peer slots, at most MAX peers of the protocol are allowed.",
+
 			ARG arg_nat: (String) = "any", or |c: &Config| c.network.as_ref()?.nat.clone(),
 			"--nat=[METHOD]",
-			"Specify method to use for determining public address. Must be one of: any, none, upnp, extip:<IP>.",
+			"Specify method to use for determining public address. Must be one of: any, none, upnp, natpmp, extip:<IP>. natpmp covers both NAT-PMP and PCP routers; any tries UPnP first and falls back to natpmp.",
 
 			ARG arg_allow_ips: (String) = "all", or |c: &Config| c.network.as_ref()?.allow_ips.clone(),
 			"--allow-ips=[FILTER]",
-			"Filter outbound connections. Must be one of: private - connect to private network IP addresses only; public - connect to public network IP addresses only; all - connect to any IP address.",
+			"Filter inbound and outbound connections. Must be one of: private - connect to private network IP addresses only; public - connect to public network IP addresses only; all - connect to any IP address; none - connect to no IP address. May be followed by a space separated list of CIDR ranges to allow, or to deny when prefixed with -, e.g. \"none 10.0.0.0/8 -10.1.0.0/16\".",
+
+			ARG arg_deny_ips_file: (Option<String>) = None, or |c: &Config| c.network.as_ref()?.deny_ips_file.clone(),
+			"--deny-ips-file=[FILE]",
+			"Refuse connections with the IP addresses and CIDR ranges listed in FILE, one per line. The file is reloaded when it changes.",
 
 			ARG arg_max_pending_peers: (u16) = 64u16, or |c: &Config| c.network.as_ref()?.max_pending_peers.clone(),
 			"--max-pending-peers=[NUM]",
@@ -449,7 +531,51 @@ usage! {
 
 			ARG arg_reserved_peers: (Option<String>) = None, or |c: &Config| c.network.as_ref()?.reserved_peers.clone(),
 			"--reserved-peers=[FILE]",
-			"Provide a file containing enodes, one per line. These nodes will always have a reserved slot on top of the normal maximum peers.",
+			"Provide a file containing enodes, one per line. These nodes will always have a reserved slot on top of the normal maximum peers. The file is reloaded on SIGHUP.",
+
+			FLAG flag_unsafe_expose_rlpx_keys: (bool) = false, or |_| None,
+			"--unsafe-expose-rlpx-keys",
+			"Allow writing secrets of RLPx sessions to the file given with --rlpx-key-log. Anyone able to read the file can decrypt the p2p traffic of this node. Use for debugging only.",
+
+			ARG arg_rlpx_key_log: (Option<String>) = None, or |c: &Config| c.network.as_ref()?.rlpx_key_log.clone(),
+			"--rlpx-key-log=[FILE]",
+			"Append secrets of every established RLPx session to FILE, so that captured p2p traffic can be decrypted, e.g. in Wireshark. Requires --unsafe-expose-rlpx-keys.",
+
+			ARG arg_block_propagation: (String) = "sqrt", or |c: &Config| c.network.as_ref()?.block_propagation.clone(),
+			"--block-propagation=[STRATEGY]",
+			"Strategy used to propagate new blocks to peers. sqrt - send full blocks to the square root of peers and announce hashes to the rest; push - send full blocks to all peers; announce - only announce hashes.",
+
+			ARG arg_block_announce_window: (usize) = 256usize, or |c: &Config| c.network.as_ref()?.block_announce_window.clone(),
+			"--block-announce-window=[BLOCKS]",
+			"Number of recent blocks for which announcements to peers already known to have the block are suppressed. 0 disables deduplication.",
+
+			ARG arg_sync_headers_batch: (usize) = 128usize, or |c: &Config| c.network.as_ref()?.sync_headers_batch.clone(),
+			"--sync-headers-batch=[HEADERS]",
+			"Number of block headers requested at once during sync. Scaled up for peers with low latency and down for slow peers.",
+
+			ARG arg_sync_bodies_batch: (usize) = 32usize, or |c: &Config| c.network.as_ref()?.sync_bodies_batch.clone(),
+			"--sync-bodies-batch=[BODIES]",
+			"Number of block bodies requested at once during sync. Scaled up for peers with low latency and down for slow peers.",
+
+			ARG arg_snapshot_serve_peer_rate: (Option<u64>) = None, or |c: &Config| c.network.as_ref()?.snapshot_serve_peer_rate.clone(),
+			"--snapshot-serve-peer-rate=[KB/S]",
+			"Limit bandwidth used for sending snapshot chunks to a single peer, in kilobytes per second.",
+
+			ARG arg_snapshot_serve_rate: (Option<u64>) = None, or |c: &Config| c.network.as_ref()?.snapshot_serve_rate.clone(),
+			"--snapshot-serve-rate=[KB/S]",
+			"Limit bandwidth used for sending snapshot chunks to all peers, in kilobytes per second. Peers are served in turn.",
+
+			ARG arg_fleet_secret: (Option<String>) = None, or |c: &Config| c.network.as_ref()?.fleet_secret.clone(),
+			"--fleet-secret=[FILE]",
+			"Share peers and bans with other nodes of the same operator. FILE contains a hex-encoded 32 bytes secret shared by all fleet members.",
+
+			ARG arg_fleet_port: (u16) = 30305u16, or |c: &Config| c.network.as_ref()?.fleet_port.clone(),
+			"--fleet-port=[PORT]",
+			"UDP port to receive messages of other fleet members at.",
+
+			ARG arg_fleet_members: (Option<String>) = None, or |c: &Config| c.network.as_ref()?.fleet_members.as_ref().map(|vec| vec.join(",")),
+			"--fleet-members=[ADDRS]",
+			"Comma-separated UDP addresses (IP:PORT) of other fleet members.",
 
 			CHECK |args: &Args| {
 				if let (Some(max_peers), Some(min_peers)) = (args.arg_max_peers, args.arg_min_peers) {
@@ -475,9 +601,9 @@ usage! {
 			"--jsonrpc-interface=[IP]",
 			"Specify the hostname portion of the HTTP JSON-RPC API server, IP should be an interface's IP address, or all (all interfaces) or local.",
 
-			ARG arg_jsonrpc_apis: (String) = "web3,eth,pubsub,net,parity,private,parity_pubsub,traces,rpc,shh,shh_pubsub", or |c: &Config| c.rpc.as_ref()?.apis.as_ref().map(|vec| vec.join(",")),
+			ARG arg_jsonrpc_apis: (String) = "web3,eth,pubsub,net,parity,private,parity_pubsub,traces,rpc,shh,shh_pubsub,txpool", or |c: &Config| c.rpc.as_ref()?.apis.as_ref().map(|vec| vec.join(",")),
 			"--jsonrpc-apis=[APIS]",
-			"Specify the APIs available through the HTTP JSON-RPC interface using a comma-delimited list of API names. Possible names are: all, safe, debug, web3, net, eth, pubsub, personal, signer, parity, parity_pubsub, parity_accounts, parity_set, traces, rpc, secretstore, shh, shh_pubsub. You can also disable a specific API by putting '-' in the front, example: all,-personal. 'safe' enables the following APIs: web3, net, eth, pubsub, parity, parity_pubsub, traces, rpc, shh, shh_pubsub",
+			"Specify the APIs available through the HTTP JSON-RPC interface using a comma-delimited list of API names. Possible names are: all, safe, debug, web3, net, eth, pubsub, personal, signer, parity, parity_pubsub, parity_accounts, parity_set, admin, traces, txpool, rpc, secretstore, shh, shh_pubsub. You can also disable a specific API by putting '-' in the front, example: all,-personal. 'safe' enables the following APIs: web3, net, eth, pubsub, parity, parity_pubsub, traces, txpool, rpc, shh, shh_pubsub. Individual methods of the enabled APIs can be allowed or denied with METHOD:allow and METHOD:deny rules, the last matching rule wins and a trailing '*' matches any suffix, example: eth:*,parity:*,parity_set*:deny",
 
 			ARG arg_jsonrpc_hosts: (String) = "none", or |c: &Config| c.rpc.as_ref()?.hosts.as_ref().map(|vec| vec.join(",")),
 			"--jsonrpc-hosts=[HOSTS]",
@@ -499,6 +625,42 @@ usage! {
 			"--jsonrpc-max-payload=[MB]",
 			"Specify maximum size for HTTP JSON-RPC requests in megabytes.",
 
+			ARG arg_jsonrpc_max_response_size: (Option<usize>) = None, or |c: &Config| c.rpc.as_ref()?.max_response_size,
+			"--jsonrpc-max-response-size=[KB]",
+			"Specify maximum size of a single HTTP JSON-RPC result in kilobytes. Larger results are returned in chunks, the next chunk is fetched with parity_responseChunk(cursor).",
+
+			ARG arg_jsonrpc_batch_parallelism: (usize) = 4usize, or |c: &Config| c.rpc.as_ref()?.batch_parallelism,
+			"--jsonrpc-batch-parallelism=[NUM]",
+			"Maximum number of read-only calls of a single HTTP JSON-RPC batch processed at the same time. Other calls are processed alone and in order. Use 1 to process batches sequentially.",
+
+			FLAG flag_jsonrpc_graphql: (bool) = false, or |c: &Config| c.rpc.as_ref()?.graphql.clone(),
+			"--jsonrpc-graphql",
+			"Serve GraphQL queries (EIP-1767) at the /graphql path of the HTTP JSON-RPC server. Not available in light client mode.",
+
+			ARG arg_jsonrpc_max_requests_per_second: (Option<u32>) = None, or |c: &Config| c.rpc.as_ref()?.max_requests_per_second,
+			"--jsonrpc-max-requests-per-second=[NUM]",
+			"Maximum number of HTTP JSON-RPC requests per second accepted from a single IP address. Requests above the limit are rejected with a \"Too many requests\" error.",
+
+			ARG arg_jsonrpc_max_concurrent_requests: (Option<usize>) = None, or |c: &Config| c.rpc.as_ref()?.max_concurrent_requests,
+			"--jsonrpc-max-concurrent-requests=[NUM]",
+			"Maximum number of HTTP JSON-RPC requests from a single IP address processed at the same time.",
+
+			ARG arg_rpc_jwt_secret: (Option<String>) = None, or |c: &Config| c.rpc.as_ref()?.jwt_secret.clone(),
+			"--rpc-jwt-secret=[FILE]",
+			"Require HTTP and WebSockets JSON-RPC requests to carry an 'Authorization: Bearer <token>' header with a JWT signed (HS256) using the hex-encoded 32 bytes secret read from FILE.",
+
+			ARG arg_rpc_max_logs_range: (Option<u64>) = None, or |c: &Config| c.rpc.as_ref()?.max_logs_range,
+			"--rpc-max-logs-range=[BLOCKS]",
+			"Maximum number of blocks a single eth_getLogs or eth_getFilterLogs query can span. Queries spanning more blocks are rejected.",
+
+			ARG arg_jsonrpc_health_max_block_age: (u64) = 300u64, or |c: &Config| c.rpc.as_ref()?.health_max_block_age,
+			"--jsonrpc-health-max-block-age=[SECS]",
+			"Maximum age of the best block in seconds for the /health and /ready endpoints of the HTTP JSON-RPC server to report the node as healthy.",
+
+			ARG arg_jsonrpc_health_min_peers: (usize) = 1usize, or |c: &Config| c.rpc.as_ref()?.health_min_peers,
+			"--jsonrpc-health-min-peers=[NUM]",
+			"Minimum number of connected peers for the /health and /ready endpoints of the HTTP JSON-RPC server to report the node as healthy. /ready also fails while the node is syncing.",
+
 		["API and Console Options – WebSockets"]
 			FLAG flag_no_ws: (bool) = false, or |c: &Config| c.websockets.as_ref()?.disable.clone(),
 			"--no-ws",
@@ -512,9 +674,9 @@ usage! {
 			"--ws-interface=[IP]",
 			"Specify the hostname portion of the WebSockets JSON-RPC server, IP should be an interface's IP address, or all (all interfaces) or local.",
 
-			ARG arg_ws_apis: (String) = "web3,eth,pubsub,net,parity,parity_pubsub,private,traces,rpc,shh,shh_pubsub", or |c: &Config| c.websockets.as_ref()?.apis.as_ref().map(|vec| vec.join(",")),
+			ARG arg_ws_apis: (String) = "web3,eth,pubsub,net,parity,parity_pubsub,private,traces,rpc,shh,shh_pubsub,txpool", or |c: &Config| c.websockets.as_ref()?.apis.as_ref().map(|vec| vec.join(",")),
 			"--ws-apis=[APIS]",
-			"Specify the JSON-RPC APIs available through the WebSockets interface using a comma-delimited list of API names. Possible names are: all, safe, web3, net, eth, pubsub, personal, signer, parity, parity_pubsub, parity_accounts, parity_set, traces, rpc, secretstore, shh, shh_pubsub. You can also disable a specific API by putting '-' in the front, example: all,-personal. 'safe' enables the following APIs: web3, net, eth, pubsub, parity, parity_pubsub, traces, rpc, shh, shh_pubsub",
+			"Specify the JSON-RPC APIs available through the WebSockets interface using a comma-delimited list of API names. Possible names are: all, safe, web3, net, eth, pubsub, personal, signer, parity, parity_pubsub, parity_accounts, parity_set, admin, traces, txpool, rpc, secretstore, shh, shh_pubsub. You can also disable a specific API by putting '-' in the front, example: all,-personal. 'safe' enables the following APIs: web3, net, eth, pubsub, parity, parity_pubsub, traces, txpool, rpc, shh, shh_pubsub. Individual methods of the enabled APIs can be allowed or denied with METHOD:allow and METHOD:deny rules, the last matching rule wins and a trailing '*' matches any suffix, example: eth:*,parity:*,parity_set*:deny",
 
 			ARG arg_ws_origins: (String) = "parity://*,chrome-extension://*,moz-extension://*", or |c: &Config| c.websockets.as_ref()?.origins.as_ref().map(|vec| vec.join(",")),
 			"--ws-origins=[URL]",
@@ -528,6 +690,34 @@ usage! {
 			"--ws-max-connections=[CONN]",
 			"Maximum number of allowed concurrent WebSockets JSON-RPC connections.",
 
+			ARG arg_ws_max_requests_per_second: (Option<u32>) = None, or |c: &Config| c.websockets.as_ref()?.max_requests_per_second,
+			"--ws-max-requests-per-second=[NUM]",
+			"Maximum number of WebSockets JSON-RPC requests per second accepted from a single connection (or client address, see --ws-trusted-proxy). Requests above the limit are rejected with a \"Too many requests\" error.",
+
+			ARG arg_ws_max_concurrent_requests: (Option<usize>) = None, or |c: &Config| c.websockets.as_ref()?.max_concurrent_requests,
+			"--ws-max-concurrent-requests=[NUM]",
+			"Maximum number of WebSockets JSON-RPC requests from a single connection (or client address, see --ws-trusted-proxy) processed at the same time.",
+
+			FLAG flag_ws_trusted_proxy: (bool) = false, or |c: &Config| c.websockets.as_ref()?.trusted_proxy.clone(),
+			"--ws-trusted-proxy",
+			"Apply WebSockets JSON-RPC request limits per client address reported by the X-Forwarded-For or Forwarded header instead of per connection. Enable only if the server is reachable exclusively through a proxy which sets these headers.",
+
+			ARG arg_ws_max_response_size: (Option<usize>) = None, or |c: &Config| c.websockets.as_ref()?.max_response_size,
+			"--ws-max-response-size=[KB]",
+			"Specify maximum size of a single WebSockets JSON-RPC result in kilobytes. Larger results are returned in chunks, the next chunk is fetched with parity_responseChunk(cursor).",
+
+			ARG arg_ws_subscription_queue: (usize) = 1024usize, or |c: &Config| c.websockets.as_ref()?.subscription_queue,
+			"--ws-subscription-queue=[NUM]",
+			"Maximum number of pub-sub notifications waiting to be sent to a single WebSockets subscription.",
+
+			ARG arg_ws_subscription_overflow: (String) = "drop-oldest", or |c: &Config| c.websockets.as_ref()?.subscription_overflow.clone(),
+			"--ws-subscription-overflow=[POLICY]",
+			"What to do when the notification queue of a subscription is full. drop-oldest - drop the oldest queued notification, close - close the subscription, coalesce - merge the notification into the newest queued one (logs are sent as a batch).",
+
+			ARG arg_ws_logs_batch: (usize) = 1usize, or |c: &Config| c.websockets.as_ref()?.logs_batch,
+			"--ws-logs-batch=[NUM]",
+			"Maximum number of logs sent in a single eth_subscribe(\"logs\") notification. Logs are sent as an array when greater than 1.",
+
 		["API and Console Options – IPC"]
 			FLAG flag_no_ipc: (bool) = false, or |c: &Config| c.ipc.as_ref()?.disable.clone(),
 			"--no-ipc",
@@ -537,9 +727,9 @@ usage! {
 			"--ipc-path=[PATH]",
 			"Specify custom path for JSON-RPC over IPC service.",
 
-			ARG arg_ipc_apis: (String) = "web3,eth,pubsub,net,parity,parity_pubsub,parity_accounts,private,traces,rpc,shh,shh_pubsub", or |c: &Config| c.ipc.as_ref()?.apis.as_ref().map(|vec| vec.join(",")),
+			ARG arg_ipc_apis: (String) = "web3,eth,pubsub,net,parity,parity_pubsub,parity_accounts,private,traces,rpc,shh,shh_pubsub,txpool", or |c: &Config| c.ipc.as_ref()?.apis.as_ref().map(|vec| vec.join(",")),
 			"--ipc-apis=[APIS]",
-			"Specify custom API set available via JSON-RPC over IPC using a comma-delimited list of API names. Possible names are: all, safe, web3, net, eth, pubsub, personal, signer, parity, parity_pubsub, parity_accounts, parity_set, traces, rpc, secretstore, shh, shh_pubsub. You can also disable a specific API by putting '-' in the front, example: all,-personal. 'safe' enables the following APIs: web3, net, eth, pubsub, parity, parity_pubsub, traces, rpc, shh, shh_pubsub",
+			"Specify custom API set available via JSON-RPC over IPC using a comma-delimited list of API names. Possible names are: all, safe, web3, net, eth, pubsub, personal, signer, parity, parity_pubsub, parity_accounts, parity_set, admin, traces, txpool, rpc, secretstore, shh, shh_pubsub. You can also disable a specific API by putting '-' in the front, example: all,-personal. 'safe' enables the following APIs: web3, net, eth, pubsub, parity, parity_pubsub, traces, txpool, rpc, shh, shh_pubsub. Individual methods of the enabled APIs can be allowed or denied with METHOD:allow and METHOD:deny rules, the last matching rule wins and a trailing '*' matches any suffix, example: eth:*,parity:*,parity_set*:deny",
 
 		["API and Console Options – IPFS"]
 			FLAG flag_ipfs_api: (bool) = false, or |c: &Config| c.ipfs.as_ref()?.enable.clone(),
@@ -562,6 +752,23 @@ usage! {
 			"--ipfs-api-cors=[URL]",
 			"Specify CORS header for IPFS API responses. Special options: \"all\", \"none\".",
 
+		["Metrics Options"]
+			FLAG flag_metrics: (bool) = false, or |c: &Config| c.metrics.as_ref()?.enable.clone(),
+			"--metrics",
+			"Enable the HTTP server exposing metrics in the Prometheus format on /metrics.",
+
+			ARG arg_metrics_port: (u16) = 3000u16, or |c: &Config| c.metrics.as_ref()?.port.clone(),
+			"--metrics-port=[PORT]",
+			"Specify the port portion of the metrics server.",
+
+			ARG arg_metrics_interface: (String) = "local", or |c: &Config| c.metrics.as_ref()?.interface.clone(),
+			"--metrics-interface=[IP]",
+			"Specify the hostname portion of the metrics server, IP should be an interface's IP address or local.",
+
+			ARG arg_metrics_prefix: (String) = "", or |c: &Config| c.metrics.as_ref()?.prefix.clone(),
+			"--metrics-prefix=[PREFIX]",
+			"Prefix of the names of all exported metrics.",
+
 		["Light Client Options"]
 			ARG arg_on_demand_retry_count: (Option<usize>) = None, or |c: &Config| c.light.as_ref()?.on_demand_retry_count,
 			"--on-demand-retry-count=[RETRIES]",
@@ -636,6 +843,26 @@ usage! {
 			"--secretstore-path=[PATH]",
 			"Specify directory where Secret Store should save its data.",
 
+			ARG arg_secretstore_storage: (String) = "db", or |c: &Config| c.secretstore.as_ref()?.storage.clone(),
+			"--secretstore-storage=[STORAGE]",
+			"Secret Store key shares storage: db (stored in Secret Store database), files (stored as encrypted files in --secretstore-storage-path directory) or etcd (stored in etcd cluster, accessed through --secretstore-etcd-endpoints).",
+
+			ARG arg_secretstore_storage_path: (String) = "$BASE/secretstore/keys", or |c: &Config| c.secretstore.as_ref()?.storage_path.clone(),
+			"--secretstore-storage-path=[PATH]",
+			"Specify directory where Secret Store should save encrypted key shares when files storage is used.",
+
+			ARG arg_secretstore_storage_secret: (Option<String>) = None, or |c: &Config| c.secretstore.as_ref()?.storage_secret.clone(),
+			"--secretstore-storage-secret=[SECRET]",
+			"Hex-encoded secret used to encrypt key shares in files and etcd storages. Defaults to the node secret key if it is given in plain text.",
+
+			ARG arg_secretstore_etcd_endpoints: (String) = "http://127.0.0.1:2379", or |c: &Config| c.secretstore.as_ref()?.etcd_endpoints.as_ref().map(|vec| vec.join(",")),
+			"--secretstore-etcd-endpoints=[URLS]",
+			"Comma-separated list of etcd v3 JSON gateway endpoints used by etcd storage.",
+
+			ARG arg_secretstore_etcd_prefix: (String) = "/parity/secretstore/", or |c: &Config| c.secretstore.as_ref()?.etcd_prefix.clone(),
+			"--secretstore-etcd-prefix=[PREFIX]",
+			"Prefix of all etcd keys written by this node. Should be unique for every node in the Secret Store cluster.",
+
 			ARG arg_secretstore_secret: (Option<String>) = None, or |c: &Config| c.secretstore.as_ref()?.self_secret.clone(),
 			"--secretstore-secret=[SECRET]",
 			"Hex-encoded secret key of this node.",
@@ -673,9 +900,21 @@ usage! {
 			"--infinite-pending-block",
 			"Pending block will be created with maximal possible gas limit and will execute all transactions in the queue. Note that such block is invalid and should never be attempted to be mined.",
 
+			FLAG flag_deterministic_block_template: (bool) = false, or |c: &Config| c.mining.as_ref()?.deterministic_block_template.clone(),
+			"--deterministic-block-template",
+			"Order transactions for new blocks deterministically, breaking ties between equally priced transactions by hash instead of arrival order. The same pool contents will always produce the same block template. Use parity_pendingBlockCandidates to retrieve the ordered candidate list.",
+
+			FLAG flag_pending_block_cache: (bool) = false, or |c: &Config| c.mining.as_ref()?.pending_block_cache.clone(),
+			"--pending-block-cache",
+			"Keep the pending block up to date as transactions arrive, even when not mining. Pending state queries and work requests are then served without preparing a new block.",
+
+			FLAG flag_external_consensus: (bool) = false, or |c: &Config| c.mining.as_ref()?.external_consensus.clone(),
+			"--external-consensus",
+			"Run as an execution engine driven by an external consensus client. Blocks are submitted with engine_newBlock over the local IPC socket and the node never seals blocks itself.",
+
 			FLAG flag_no_persistent_txqueue: (bool) = false, or |c: &Config| c.parity.as_ref()?.no_persistent_txqueue,
 			"--no-persistent-txqueue",
-			"Don't save pending local transactions to disk to be restored whenever the node restarts.",
+			"Don't journal pending local and network transactions to disk to be restored whenever the node restarts.",
 
 			FLAG flag_stratum: (bool) = false, or |c: &Config| Some(c.stratum.is_some()),
 			"--stratum",
@@ -725,6 +964,10 @@ usage! {
 			"--tx-queue-mem-limit=[MB]",
 			"Maximum amount of memory that can be used by the transaction queue. Setting this parameter to 0 disables limiting.",
 
+			ARG arg_tx_pool_memory: (Option<u32>) = None, or |c: &Config| c.mining.as_ref()?.tx_pool_memory.clone(),
+			"--tx-pool-memory=[MB]",
+			"Limit the transaction queue only by the memory used by its transactions (encoded size plus bookkeeping overhead). When the limit is reached the lowest scored transactions are evicted, or the incoming one is rejected. Overrides --tx-queue-size and --tx-queue-mem-limit.",
+
 			ARG arg_tx_queue_size: (usize) = 8_192usize, or |c: &Config| c.mining.as_ref()?.tx_queue_size.clone(),
 			"--tx-queue-size=[LIMIT]",
 			"Maximum amount of transactions in the queue (waiting to be included in next block).",
@@ -737,6 +980,22 @@ usage! {
 			"--tx-queue-strategy=[S]",
 			"Prioritization strategy used to order transactions in the queue. S may be: gas_price - Prioritize txs with high gas price",
 
+			ARG arg_tx_queue_demotion_grace: (u64) = 60u64, or |c: &Config| c.mining.as_ref()?.tx_queue_demotion_grace.clone(),
+			"--tx-queue-demotion-grace=[SEC]",
+			"Time in seconds external transactions which became underpriced after the minimal gas price was raised are kept in the queue before being removed.",
+
+			ARG arg_tx_queue_replacement_bump: (f64) = 12.5f64, or |c: &Config| c.mining.as_ref()?.tx_queue_replacement_bump.clone(),
+			"--tx-queue-replacement-bump=[PERCENT]",
+			"Minimal gas price increase, in percent, a transaction needs to replace a queued transaction with the same sender and nonce. Applies to both local and network transactions.",
+
+			ARG arg_tx_queue_rebroadcast: (u64) = 10u64, or |c: &Config| c.mining.as_ref()?.tx_queue_rebroadcast.clone(),
+			"--tx-queue-rebroadcast=[MINUTES]",
+			"Time in minutes after which local transactions still pending are sent to peers again. The delay doubles with every attempt, up to two hours. Use 0 to disable.",
+
+			ARG arg_tx_priority_senders: (Option<String>) = None, or |c: &Config| c.mining.as_ref()?.tx_priority_senders.as_ref().map(|vec| vec.join(",")),
+			"--tx-priority-senders=[ADDRESSES]",
+			"Comma-separated list of senders whose valid pending transactions are always included first when assembling a block, e.g. system or oracle accounts on PoA networks.",
+
 			ARG arg_stratum_interface: (String) = "local", or |c: &Config| c.stratum.as_ref()?.interface.clone(),
 			"--stratum-interface=[IP]",
 			"Interface address for Stratum server.",
@@ -753,6 +1012,18 @@ usage! {
 			"--gas-price-percentile=[PCT]",
 			"Set PCT percentile gas price value from last 100 blocks as default gas price when sending transactions.",
 
+			ARG arg_gas_price_oracle_blocks: (usize) = 100usize, or |c: &Config| c.mining.as_ref()?.gas_price_oracle_blocks,
+			"--gas-price-oracle-blocks=[N]",
+			"Number of recent blocks analysed to suggest the default gas price (eth_gasPrice).",
+
+			ARG arg_gas_price_oracle_min: (String) = "0", or |c: &Config| c.mining.as_ref()?.gas_price_oracle_min.clone(),
+			"--gas-price-oracle-min=[WEI]",
+			"Minimal default gas price suggested, in Wei.",
+
+			ARG arg_gas_price_oracle_smoothing: (usize) = 100usize, or |c: &Config| c.mining.as_ref()?.gas_price_oracle_smoothing,
+			"--gas-price-oracle-smoothing=[PCT]",
+			"Weight in percent of the latest estimate when smoothing default gas price suggestions with an exponential moving average. 100 disables smoothing.",
+
 			ARG arg_poll_lifetime: (u32) = 60u32, or |c: &Config| c.mining.as_ref()?.poll_lifetime.clone(),
 			"--poll-lifetime=[S]",
 			"Set the lifetime of the internal index filter to S seconds.",
@@ -773,6 +1044,10 @@ usage! {
 			"--tx-time-limit=[MS]",
 			"Maximal time for processing single transaction. If enabled senders of transactions offending the limit will get other transactions penalized.",
 
+			ARG arg_tx_lifecycle_log: (Option<usize>) = None, or |c: &Config| c.mining.as_ref()?.tx_lifecycle_log.clone(),
+			"--tx-lifecycle-log=[NUM]",
+			"Record what happens to transactions seen by this node (acceptance, propagation, replacement, drop reason, inclusion) and keep the log for the last NUM transactions in the database. Query it with parity_transactionLifecycle.",
+
 			ARG arg_extra_data: (Option<String>) = None, or |c: &Config| c.mining.as_ref()?.extra_data.clone(),
 			"--extra-data=[STRING]",
 			"Specify a custom extra-data for authored blocks, no more than 32 characters.",
@@ -781,6 +1056,18 @@ usage! {
 			"--notify-work=[URLS]",
 			"URLs to which work package notifications are pushed. URLS should be a comma-delimited list of HTTP URLs.",
 
+			ARG arg_tx_relay: (Option<String>) = None, or |c: &Config| c.mining.as_ref()?.tx_relay.as_ref().map(|vec| vec.join(",")),
+			"--tx-relay=[URLS]",
+			"JSON-RPC endpoints to which newly accepted local transactions are also sent via eth_sendRawTransaction. URLS should be a comma-delimited list of HTTP URLs.",
+
+			ARG arg_tx_relay_retries: (usize) = 3usize, or |c: &Config| c.mining.as_ref()?.tx_relay_retries.clone(),
+			"--tx-relay-retries=[NUM]",
+			"Number of times a failed transaction relay request is retried.",
+
+			ARG arg_tx_relay_retry_delay: (u64) = 1000u64, or |c: &Config| c.mining.as_ref()?.tx_relay_retry_delay.clone(),
+			"--tx-relay-retry-delay=[MS]",
+			"Delay before the first retry of a failed transaction relay request in milliseconds, doubled with every next retry.",
+
 			ARG arg_stratum_secret: (Option<String>) = None, or |c: &Config| c.stratum.as_ref()?.secret.clone(),
 			"--stratum-secret=[STRING]",
 			"Secret for authorizing Stratum server for peers.",
@@ -848,10 +1135,26 @@ usage! {
 			"--cache-size-state=[MB]",
 			"Specify the maximum size of memory to use for the state cache.",
 
+			ARG arg_cache_size_calls: (u32) = 4u32, or |c: &Config| c.footprint.as_ref()?.cache_size_calls.clone(),
+			"--cache-size-calls=[MB]",
+			"Specify the maximum size of memory to use for caching eth_call results. The cache is cleared on every new best block. 0 disables the cache.",
+
 			ARG arg_db_compaction: (String) = "auto", or |c: &Config| c.footprint.as_ref()?.db_compaction.clone(),
 			"--db-compaction=[TYPE]",
 			"Database compaction type. TYPE may be one of: ssd - suitable for SSDs and fast HDDs; hdd - suitable for slow HDDs; auto - determine automatically.",
 
+			ARG arg_db_hot_path: (Option<String>) = None, or |c: &Config| c.footprint.as_ref()?.db_hot_path.clone(),
+			"--db-hot-path=[PATH]",
+			"Directory on fast storage used as a hot database tier. Entries read repeatedly from the main database are copied there and the least read ones are evicted when the tier is full. The tier is kept in its hot_tier subdirectory, which is wiped on startup.",
+
+			ARG arg_db_hot_entries: (usize) = 1_000_000usize, or |c: &Config| c.footprint.as_ref()?.db_hot_entries.clone(),
+			"--db-hot-entries=[NUM]",
+			"Maximal number of entries kept in the hot database tier.",
+
+			ARG arg_db_hot_promote_after: (u32) = 3u32, or |c: &Config| c.footprint.as_ref()?.db_hot_promote_after.clone(),
+			"--db-hot-promote-after=[NUM]",
+			"Number of reads from the main database after which an entry is promoted to the hot tier.",
+
 			ARG arg_fat_db: (String) = "auto", or |c: &Config| c.footprint.as_ref()?.fat_db.clone(),
 			"--fat-db=[BOOL]",
 			"Build appropriate information to allow enumeration of all accounts and storage keys. Doubles the size of the state database. BOOL may be one of on, off or auto.",
@@ -864,6 +1167,26 @@ usage! {
 			"--num-verifiers=[INT]",
 			"Amount of verifier threads to use or to begin with, if verifier auto-scaling is enabled.",
 
+			ARG arg_sync_threads: (usize) = 4usize, or |c: &Config| c.footprint.as_ref()?.sync_threads.clone(),
+			"--sync-threads=[INT]",
+			"Amount of threads processing network IO and sync messages.",
+
+			ARG arg_evm_threads: (Option<usize>) = None, or |c: &Config| c.footprint.as_ref()?.evm_threads.clone(),
+			"--evm-threads=[INT]",
+			"Amount of threads in the pool used for parallel block processing. Defaults to the number of CPU cores.",
+
+			ARG arg_thread_affinity: (Option<String>) = None, or |c: &Config| c.footprint.as_ref()?.thread_affinity.clone(),
+			"--thread-affinity=[CPUS]",
+			"Pin verifier, sync and block processing threads to given CPU cores, e.g. 0-3,6. Threads of each pool are assigned to the cores in turn. Supported on Linux only.",
+
+			FLAG flag_parallel_transactions: (bool) = false, or |c: &Config| c.footprint.as_ref()?.parallel_transactions.clone(),
+			"--parallel-transactions",
+			"Execute transactions of imported blocks optimistically on the --evm-threads pool. Transactions touching accounts modified earlier in the block are replayed serially.",
+
+			ARG arg_prewarm_contracts: (Option<String>) = None, or |c: &Config| c.footprint.as_ref()?.prewarm_contracts.as_ref().map(|vec| vec.join(",")),
+			"--prewarm-contracts=[CONTRACTS]",
+			"Analyze the code of given contracts at startup and never evict it from the EVM cache. CONTRACTS is a comma-delimited list of contract addresses or code hashes; code hashes not found in the genesis state are kept once first executed.",
+
 		["Import/export Options"]
 			FLAG flag_no_seal_check: (bool) = false, or |_| None,
 			"--no-seal-check",
@@ -878,6 +1201,10 @@ usage! {
 			"--snapshot-threads=[NUM]",
 			"Enables multiple threads for snapshots creation.",
 
+			FLAG flag_cold_calls: (bool) = false, or |c: &Config| c.snapshots.as_ref()?.cold_calls.clone(),
+			"--cold-calls",
+			"Answer calls at blocks with pruned state by replaying blocks on top of the state of the latest local snapshot. Only blocks within one snapshot period after the snapshot are supported.",
+
 		["Whisper Options"]
 			FLAG flag_whisper: (bool) = false, or |c: &Config| c.whisper.as_ref()?.enabled,
 			"--whisper",
@@ -887,6 +1214,10 @@ usage! {
 			"--whisper-pool-size=[MB]",
 			"Target size of the whisper message pool in megabytes.",
 
+			FLAG flag_whisper_topic_bloom: (bool) = false, or |c: &Config| c.whisper.as_ref()?.topic_bloom,
+			"--whisper-topic-bloom",
+			"Advertise the bloom of topics of installed filters to peers, so that only matching messages are relayed to this node. The node will not relay other messages.",
+
 		["Legacy Options"]
 			// Options that are hidden from config, but are still unique for its functionality.
 
@@ -1107,6 +1438,7 @@ struct Config {
 	secretstore: Option<SecretStore>,
 	private_tx: Option<PrivateTransactions>,
 	ipfs: Option<Ipfs>,
+	metrics: Option<Metrics>,
 	mining: Option<Mining>,
 	footprint: Option<Footprint>,
 	snapshots: Option<Snapshots>,
@@ -1191,16 +1523,29 @@ struct Network {
 	min_peers: Option<u16>,
 	max_peers: Option<u16>,
 	snapshot_peers: Option<u16>,
+	peer_slots: Option<String>,
 	max_pending_peers: Option<u16>,
 	nat: Option<String>,
 	allow_ips: Option<String>,
+	deny_ips_file: Option<String>,
 	id: Option<u64>,
 	bootnodes: Option<Vec<String>>,
 	discovery: Option<bool>,
+	discovery_v5: Option<bool>,
 	node_key: Option<String>,
 	reserved_peers: Option<String>,
 	reserved_only: Option<bool>,
 	no_serve_light: Option<bool>,
+	rlpx_key_log: Option<String>,
+	block_propagation: Option<String>,
+	block_announce_window: Option<usize>,
+	sync_headers_batch: Option<usize>,
+	sync_bodies_batch: Option<usize>,
+	snapshot_serve_peer_rate: Option<u64>,
+	snapshot_serve_rate: Option<u64>,
+	fleet_secret: Option<String>,
+	fleet_port: Option<u16>,
+	fleet_members: Option<Vec<String>>,
 }
 
 #[derive(Default, Debug, PartialEq, Deserialize)]
@@ -1215,6 +1560,15 @@ struct Rpc {
 	server_threads: Option<usize>,
 	processing_threads: Option<usize>,
 	max_payload: Option<usize>,
+	max_response_size: Option<usize>,
+	batch_parallelism: Option<usize>,
+	graphql: Option<bool>,
+	max_requests_per_second: Option<u32>,
+	max_concurrent_requests: Option<usize>,
+	jwt_secret: Option<String>,
+	max_logs_range: Option<u64>,
+	health_max_block_age: Option<u64>,
+	health_min_peers: Option<usize>,
 }
 
 #[derive(Default, Debug, PartialEq, Deserialize)]
@@ -1227,6 +1581,13 @@ struct Ws {
 	origins: Option<Vec<String>>,
 	hosts: Option<Vec<String>>,
 	max_connections: Option<usize>,
+	max_requests_per_second: Option<u32>,
+	max_concurrent_requests: Option<usize>,
+	trusted_proxy: Option<bool>,
+	max_response_size: Option<usize>,
+	subscription_queue: Option<usize>,
+	subscription_overflow: Option<String>,
+	logs_batch: Option<usize>,
 }
 
 #[derive(Default, Debug, PartialEq, Deserialize)]
@@ -1279,6 +1640,11 @@ struct SecretStore {
 	http_interface: Option<String>,
 	http_port: Option<u16>,
 	path: Option<String>,
+	storage: Option<String>,
+	storage_path: Option<String>,
+	storage_secret: Option<String>,
+	etcd_endpoints: Option<Vec<String>>,
+	etcd_prefix: Option<String>,
 }
 
 #[derive(Default, Debug, PartialEq, Deserialize)]
@@ -1291,6 +1657,15 @@ struct Ipfs {
 	hosts: Option<Vec<String>>,
 }
 
+#[derive(Default, Debug, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct Metrics {
+	enable: Option<bool>,
+	port: Option<u16>,
+	interface: Option<String>,
+	prefix: Option<String>,
+}
+
 #[derive(Default, Debug, PartialEq, Deserialize)]
 #[serde(deny_unknown_fields)]
 struct Mining {
@@ -1304,9 +1679,13 @@ struct Mining {
 	work_queue_size: Option<usize>,
 	tx_gas_limit: Option<String>,
 	tx_time_limit: Option<u64>,
+	tx_lifecycle_log: Option<usize>,
 	relay_set: Option<String>,
 	min_gas_price: Option<u64>,
 	gas_price_percentile: Option<usize>,
+	gas_price_oracle_blocks: Option<usize>,
+	gas_price_oracle_min: Option<String>,
+	gas_price_oracle_smoothing: Option<usize>,
 	poll_lifetime: Option<u32>,
 	usd_per_tx: Option<String>,
 	usd_per_eth: Option<String>,
@@ -1317,15 +1696,26 @@ struct Mining {
 	tx_queue_size: Option<usize>,
 	tx_queue_per_sender: Option<usize>,
 	tx_queue_mem_limit: Option<u32>,
+	tx_pool_memory: Option<u32>,
 	tx_queue_strategy: Option<String>,
+	tx_queue_demotion_grace: Option<u64>,
+	tx_queue_replacement_bump: Option<f64>,
+	tx_queue_rebroadcast: Option<u64>,
+	tx_priority_senders: Option<Vec<String>>,
 	tx_queue_ban_count: Option<u16>,
 	tx_queue_ban_time: Option<u16>,
 	tx_queue_no_unfamiliar_locals: Option<bool>,
 	tx_queue_no_early_reject: Option<bool>,
 	remove_solved: Option<bool>,
 	notify_work: Option<Vec<String>>,
+	tx_relay: Option<Vec<String>>,
+	tx_relay_retries: Option<usize>,
+	tx_relay_retry_delay: Option<u64>,
 	refuse_service_transactions: Option<bool>,
 	infinite_pending_block: Option<bool>,
+	deterministic_block_template: Option<bool>,
+	pending_block_cache: Option<bool>,
+	external_consensus: Option<bool>,
 }
 
 #[derive(Default, Debug, PartialEq, Deserialize)]
@@ -1349,10 +1739,19 @@ struct Footprint {
 	cache_size_blocks: Option<u32>,
 	cache_size_queue: Option<u32>,
 	cache_size_state: Option<u32>,
+	cache_size_calls: Option<u32>,
 	db_compaction: Option<String>,
+	db_hot_path: Option<String>,
+	db_hot_entries: Option<usize>,
+	db_hot_promote_after: Option<u32>,
 	fat_db: Option<String>,
 	scale_verifiers: Option<bool>,
 	num_verifiers: Option<usize>,
+	sync_threads: Option<usize>,
+	evm_threads: Option<usize>,
+	thread_affinity: Option<String>,
+	parallel_transactions: Option<bool>,
+	prewarm_contracts: Option<Vec<String>>,
 }
 
 #[derive(Default, Debug, PartialEq, Deserialize)]
@@ -1360,6 +1759,7 @@ struct Footprint {
 struct Snapshots {
 	disable_periodic: Option<bool>,
 	processing_threads: Option<usize>,
+	cold_calls: Option<bool>,
 }
 
 #[derive(Default, Debug, PartialEq, Deserialize)]
@@ -1377,6 +1777,7 @@ struct Misc {
 struct Whisper {
 	enabled: Option<bool>,
 	pool_size: Option<usize>,
+	topic_bloom: Option<bool>,
 }
 
 #[derive(Default, Debug, PartialEq, Deserialize)]
@@ -1579,6 +1980,11 @@ mod tests {
 			cmd_db: false,
 			cmd_db_kill: false,
 			cmd_export_hardcoded_sync: false,
+			cmd_dev_network: false,
+			cmd_test: false,
+			cmd_test_run: false,
+			cmd_test_gen: false,
+			cmd_test_gen_state: false,
 
 			// Arguments
 			arg_daemon_pid_file: None,
@@ -1588,6 +1994,9 @@ mod tests {
 			arg_export_blocks_format: None,
 			arg_export_state_file: None,
 			arg_export_state_format: None,
+			arg_test_run_path: None,
+			arg_test_gen_state_transaction: None,
+			arg_test_gen_state_file: None,
 			arg_snapshot_file: None,
 			arg_restore_file: None,
 			arg_tools_hash_file: None,
@@ -1657,16 +2066,31 @@ mod tests {
 			arg_max_peers: Some(50u16),
 			arg_max_pending_peers: 64u16,
 			arg_snapshot_peers: 0u16,
+			arg_peer_slots: None,
 			arg_allow_ips: "all".into(),
+			arg_deny_ips_file: None,
 			arg_nat: "any".into(),
 			arg_network_id: Some(1),
 			arg_bootnodes: Some("".into()),
 			flag_no_discovery: false,
+			flag_discovery_v5: false,
 			arg_node_key: None,
 			arg_reserved_peers: Some("./path_to_file".into()),
 			flag_reserved_only: false,
 			flag_no_ancient_blocks: false,
+			flag_no_ancient_receipts: false,
 			flag_no_serve_light: false,
+			flag_unsafe_expose_rlpx_keys: false,
+			arg_rlpx_key_log: None,
+			arg_block_propagation: "sqrt".into(),
+			arg_block_announce_window: 256usize,
+			arg_sync_headers_batch: 128usize,
+			arg_sync_bodies_batch: 32usize,
+			arg_snapshot_serve_peer_rate: None,
+			arg_snapshot_serve_rate: None,
+			arg_fleet_secret: None,
+			arg_fleet_port: 30305u16,
+			arg_fleet_members: None,
 
 			// -- API and Console Options
 			// RPC
@@ -1679,6 +2103,15 @@ mod tests {
 			arg_jsonrpc_server_threads: None,
 			arg_jsonrpc_threads: 4,
 			arg_jsonrpc_max_payload: None,
+			arg_jsonrpc_max_response_size: None,
+			arg_jsonrpc_batch_parallelism: 4usize,
+			flag_jsonrpc_graphql: false,
+			arg_jsonrpc_max_requests_per_second: None,
+			arg_jsonrpc_max_concurrent_requests: None,
+			arg_rpc_jwt_secret: None,
+			arg_rpc_max_logs_range: None,
+			arg_jsonrpc_health_max_block_age: 300u64,
+			arg_jsonrpc_health_min_peers: 1usize,
 
 			// WS
 			flag_no_ws: false,
@@ -1688,6 +2121,13 @@ mod tests {
 			arg_ws_origins: "none".into(),
 			arg_ws_hosts: "none".into(),
 			arg_ws_max_connections: 100,
+			arg_ws_max_requests_per_second: None,
+			arg_ws_max_concurrent_requests: None,
+			flag_ws_trusted_proxy: false,
+			arg_ws_max_response_size: None,
+			arg_ws_subscription_queue: 1024usize,
+			arg_ws_subscription_overflow: "drop-oldest".into(),
+			arg_ws_logs_batch: 1usize,
 
 			// IPC
 			flag_no_ipc: false,
@@ -1717,6 +2157,11 @@ mod tests {
 			arg_secretstore_http_interface: "local".into(),
 			arg_secretstore_http_port: 8082u16,
 			arg_secretstore_path: "$HOME/.parity/secretstore".into(),
+			arg_secretstore_storage: "db".into(),
+			arg_secretstore_storage_path: "$BASE/secretstore/keys".into(),
+			arg_secretstore_storage_secret: None,
+			arg_secretstore_etcd_endpoints: "http://127.0.0.1:2379".into(),
+			arg_secretstore_etcd_prefix: "/parity/secretstore/".into(),
 
 			// IPFS
 			flag_ipfs_api: false,
@@ -1725,6 +2170,12 @@ mod tests {
 			arg_ipfs_api_cors: "null".into(),
 			arg_ipfs_api_hosts: "none".into(),
 
+			// Metrics
+			flag_metrics: false,
+			arg_metrics_port: 3000u16,
+			arg_metrics_interface: "local".into(),
+			arg_metrics_prefix: "".into(),
+
 			// -- Sealing/Mining Options
 			arg_author: Some("0xdeadbeefcafe0000000000000000000000000001".into()),
 			arg_engine_signer: Some("0xdeadbeefcafe0000000000000000000000000001".into()),
@@ -1736,10 +2187,14 @@ mod tests {
 			arg_work_queue_size: 20usize,
 			arg_tx_gas_limit: Some("10000000".into()),
 			arg_tx_time_limit: Some(100u64),
+			arg_tx_lifecycle_log: None,
 			arg_relay_set: "cheap".into(),
 			arg_min_gas_price: Some(0u64),
 			arg_usd_per_tx: "0.0001".into(),
 			arg_gas_price_percentile: 50usize,
+			arg_gas_price_oracle_blocks: 100usize,
+			arg_gas_price_oracle_min: "0".into(),
+			arg_gas_price_oracle_smoothing: 100usize,
 			arg_poll_lifetime: 60u32,
 			arg_usd_per_eth: "auto".into(),
 			arg_price_update_period: "hourly".into(),
@@ -1751,13 +2206,24 @@ mod tests {
 			arg_tx_queue_size: 8192usize,
 			arg_tx_queue_per_sender: None,
 			arg_tx_queue_mem_limit: 4u32,
+			arg_tx_pool_memory: None,
 			arg_tx_queue_strategy: "gas_factor".into(),
+			arg_tx_queue_demotion_grace: 60u64,
+			arg_tx_queue_replacement_bump: 12.5f64,
+			arg_tx_queue_rebroadcast: 10u64,
+			arg_tx_priority_senders: None,
 			arg_tx_queue_ban_count: Some(1u16),
 			arg_tx_queue_ban_time: Some(180u16),
 			flag_remove_solved: false,
 			arg_notify_work: Some("http://localhost:3001".into()),
+			arg_tx_relay: None,
+			arg_tx_relay_retries: 3usize,
+			arg_tx_relay_retry_delay: 1000u64,
 			flag_refuse_service_transactions: false,
 			flag_infinite_pending_block: false,
+			flag_deterministic_block_template: false,
+			flag_pending_block_cache: false,
+			flag_external_consensus: false,
 
 			flag_stratum: false,
 			arg_stratum_interface: "local".to_owned(),
@@ -1773,12 +2239,21 @@ mod tests {
 			arg_cache_size_blocks: 8u32,
 			arg_cache_size_queue: 50u32,
 			arg_cache_size_state: 25u32,
+			arg_cache_size_calls: 4u32,
 			arg_cache_size: Some(128),
 			flag_fast_and_loose: false,
 			arg_db_compaction: "ssd".into(),
+			arg_db_hot_path: None,
+			arg_db_hot_entries: 1_000_000usize,
+			arg_db_hot_promote_after: 3u32,
 			arg_fat_db: "auto".into(),
 			flag_scale_verifiers: true,
 			arg_num_verifiers: Some(6),
+			arg_sync_threads: 4,
+			arg_evm_threads: None,
+			arg_thread_affinity: None,
+			flag_parallel_transactions: false,
+			arg_prewarm_contracts: None,
 
 			// -- Import/Export Options
 			arg_export_blocks_from: "1".into(),
@@ -1788,12 +2263,20 @@ mod tests {
 			flag_export_state_no_storage: false,
 			arg_export_state_min_balance: None,
 			arg_export_state_max_balance: None,
+			arg_test_run_fork: None,
+			arg_test_gen_state_fork: "Byzantium".into(),
+			arg_test_gen_state_name: None,
+			arg_dev_network_validators: 4,
+			arg_dev_network_out: "./dev-network".into(),
+			arg_dev_network_step_duration: 5,
+			flag_dev_network_run: false,
 
 			// -- Snapshot Optons
 			arg_export_state_at: "latest".into(),
 			arg_snapshot_at: "latest".into(),
 			flag_no_periodic_snapshot: false,
 			arg_snapshot_threads: None,
+			flag_cold_calls: false,
 
 			// -- Light options.
 			arg_on_demand_retry_count: Some(15),
@@ -1802,6 +2285,7 @@ mod tests {
 			// -- Whisper options.
 			flag_whisper: false,
 			arg_whisper_pool_size: 20,
+			flag_whisper_topic_bloom: false,
 
 			// -- Legacy Options
 			flag_warp: false,
@@ -1853,6 +2337,28 @@ mod tests {
 		});
 	}
 
+	#[test]
+	fn should_load_included_config_files() {
+		use std::{env, fs};
+		use tempdir::TempDir;
+
+		let dir = TempDir::new("parity_config").unwrap();
+		fs::write(dir.path().join("base.toml"), "[network]\nport = 30305\n[rpc]\nport = 8555\n").unwrap();
+		fs::write(dir.path().join("node.toml"), "include = \"base.toml\"\n[rpc]\nport = ${PARITY_TEST_RPC_PORT}\n").unwrap();
+		env::set_var("PARITY_TEST_RPC_PORT", "8565");
+
+		let config = dir.path().join("node.toml");
+		let args = Args::parse(&["parity", "--config", config.to_str().unwrap()]).unwrap();
+		assert_eq!(args.arg_port, 30305);
+		assert_eq!(args.arg_jsonrpc_port, 8565);
+
+		fs::write(dir.path().join("node.toml"), "include = \"base.toml\"\n[rpc]\nport = ${PARITY_TEST_UNSET_PORT}\n").unwrap();
+		match Args::parse(&["parity", "--config", config.to_str().unwrap()]) {
+			Err(ArgsError::ConfigFile(e)) => assert!(e.to_string().ends_with("node.toml:3: environment variable `PARITY_TEST_UNSET_PORT` is not set")),
+			other => panic!("Unexpected result: {:?}", other),
+		}
+	}
+
 	#[test]
 	fn should_parse_config_and_return_errors() {
 		let config1 = Args::parse_config(include_str!("./tests/config.invalid1.toml"));
@@ -1923,15 +2429,28 @@ mod tests {
 				max_peers: Some(20),
 				max_pending_peers: Some(30),
 				snapshot_peers: Some(40),
+				peer_slots: None,
 				allow_ips: Some("public".into()),
+				deny_ips_file: None,
 				nat: Some("any".into()),
 				id: None,
 				bootnodes: None,
 				discovery: Some(true),
+				discovery_v5: None,
 				node_key: None,
 				reserved_peers: Some("./path/to/reserved_peers".into()),
 				reserved_only: Some(true),
 				no_serve_light: None,
+				rlpx_key_log: None,
+				block_propagation: None,
+				block_announce_window: None,
+				sync_headers_batch: None,
+				sync_bodies_batch: None,
+				snapshot_serve_peer_rate: None,
+				snapshot_serve_rate: None,
+				fleet_secret: None,
+				fleet_port: None,
+				fleet_members: None,
 			}),
 			websockets: Some(Ws {
 				disable: Some(true),
@@ -1941,6 +2460,13 @@ mod tests {
 				origins: Some(vec!["none".into()]),
 				hosts: None,
 				max_connections: None,
+				max_requests_per_second: None,
+				max_concurrent_requests: None,
+				trusted_proxy: None,
+				max_response_size: None,
+				subscription_queue: None,
+				subscription_overflow: None,
+				logs_batch: None,
 			}),
 			rpc: Some(Rpc {
 				disable: Some(true),
@@ -1952,6 +2478,15 @@ mod tests {
 				server_threads: None,
 				processing_threads: None,
 				max_payload: None,
+				max_response_size: None,
+				batch_parallelism: None,
+				graphql: None,
+				max_requests_per_second: None,
+				max_concurrent_requests: None,
+				jwt_secret: None,
+				max_logs_range: None,
+				health_max_block_age: None,
+				health_min_peers: None,
 			}),
 			ipc: Some(Ipc {
 				disable: None,
@@ -1987,6 +2522,11 @@ mod tests {
 				http_interface: None,
 				http_port: Some(8082),
 				path: None,
+				storage: None,
+				storage_path: None,
+				storage_secret: None,
+				etcd_endpoints: None,
+				etcd_prefix: None,
 			}),
 			private_tx: None,
 			ipfs: Some(Ipfs {
@@ -1996,6 +2536,7 @@ mod tests {
 				cors: None,
 				hosts: None,
 			}),
+			metrics: None,
 			mining: Some(Mining {
 				author: Some("0xdeadbeefcafe0000000000000000000000000001".into()),
 				engine_signer: Some("0xdeadbeefcafe0000000000000000000000000001".into()),
@@ -2008,6 +2549,9 @@ mod tests {
 				relay_set: None,
 				min_gas_price: None,
 				gas_price_percentile: None,
+				gas_price_oracle_blocks: None,
+				gas_price_oracle_min: None,
+				gas_price_oracle_smoothing: None,
 				poll_lifetime: None,
 				usd_per_tx: None,
 				usd_per_eth: None,
@@ -2017,18 +2561,30 @@ mod tests {
 				tx_queue_size: Some(8192),
 				tx_queue_per_sender: None,
 				tx_queue_mem_limit: None,
+				tx_pool_memory: None,
 				tx_queue_strategy: None,
+				tx_queue_demotion_grace: None,
+				tx_queue_replacement_bump: None,
+				tx_queue_rebroadcast: None,
+				tx_priority_senders: None,
 				tx_queue_ban_count: None,
 				tx_queue_ban_time: None,
 				tx_queue_no_unfamiliar_locals: None,
 				tx_queue_no_early_reject: None,
 				tx_gas_limit: None,
 				tx_time_limit: None,
+				tx_lifecycle_log: None,
 				extra_data: None,
 				remove_solved: None,
 				notify_work: None,
+				tx_relay: None,
+				tx_relay_retries: None,
+				tx_relay_retry_delay: None,
 				refuse_service_transactions: None,
 				infinite_pending_block: None,
+				deterministic_block_template: None,
+				pending_block_cache: None,
+				external_consensus: None,
 			}),
 			footprint: Some(Footprint {
 				tracing: Some("on".into()),
@@ -2041,10 +2597,19 @@ mod tests {
 				cache_size_blocks: Some(16),
 				cache_size_queue: Some(100),
 				cache_size_state: Some(25),
+				cache_size_calls: None,
 				db_compaction: Some("ssd".into()),
+				db_hot_path: None,
+				db_hot_entries: None,
+				db_hot_promote_after: None,
 				fat_db: Some("off".into()),
 				scale_verifiers: Some(false),
 				num_verifiers: None,
+				sync_threads: None,
+				evm_threads: None,
+				thread_affinity: None,
+				parallel_transactions: None,
+				prewarm_contracts: None,
 			}),
 			light: Some(Light {
 				on_demand_retry_count: Some(12),
@@ -2053,6 +2618,7 @@ mod tests {
 			snapshots: Some(Snapshots {
 				disable_periodic: Some(true),
 				processing_threads: None,
+				cold_calls: None,
 			}),
 			misc: Some(Misc {
 				logging: Some("own_tx=trace".into()),
@@ -2064,6 +2630,7 @@ mod tests {
 			whisper: Some(Whisper {
 				enabled: Some(true),
 				pool_size: Some(50),
+				topic_bloom: None,
 			}),
 			stratum: None,
 		});
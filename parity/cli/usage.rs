@@ -143,7 +143,8 @@ macro_rules! usage {
 	) => {
 		use toml;
 		use std::{fs, io, process, cmp};
-		use std::io::{Read, Write};
+		use std::io::Write;
+		use std::path::Path;
 		use parity_version::version;
 		use clap::{Arg, App, SubCommand, AppSettings, ArgSettings, Error as ClapError, ErrorKind as ClapErrorKind};
 		use dir::helpers::replace_home;
@@ -164,6 +165,7 @@ macro_rules! usage {
 			Clap(ClapError),
 			Decode(toml::de::Error),
 			Config(String, io::Error),
+			ConfigFile(config_file::Error),
 			PeerConfiguration,
 		}
 
@@ -176,6 +178,11 @@ macro_rules! usage {
 						println_stderr!("{}", e);
 						process::exit(2)
 					},
+					ArgsError::ConfigFile(e) => {
+						println_stderr!("You might have supplied invalid parameters in config file.");
+						println_stderr!("{}", e);
+						process::exit(2)
+					},
 					ArgsError::Config(path, e) => {
 						println_stderr!("There was an error reading your config file at: {}", path);
 						println_stderr!("{}", e);
@@ -201,6 +208,12 @@ macro_rules! usage {
 			}
 		}
 
+		impl From<config_file::Error> for ArgsError {
+			fn from(e: config_file::Error) -> Self {
+				ArgsError::ConfigFile(e)
+			}
+		}
+
 		/// Parsed command line arguments.
 		#[derive(Debug, PartialEq)]
 		pub struct Args {
@@ -330,12 +343,10 @@ macro_rules! usage {
 				let config_file = replace_home(&::dir::default_data_path(), &config_file);
 
 				let args = match (fs::File::open(&config_file), raw_args.arg_config.clone()) {
-					// Load config file
-					(Ok(mut file), _) => {
+					// Load config file together with the files it includes
+					(Ok(_), _) => {
 						println_stderr!("Loading config file from {}", &config_file);
-						let mut config = String::new();
-						file.read_to_string(&mut config).map_err(|e| ArgsError::Config(config_file, e))?;
-						Ok(raw_args.into_args(Self::parse_config(&config)?))
+						Ok(raw_args.into_args(config_file::load(Path::new(&config_file))?))
 					},
 					// Don't display error in case default config cannot be loaded.
 					(Err(_), None) => Ok(raw_args.into_args(Config::default())),
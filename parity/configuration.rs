@@ -22,34 +22,41 @@ use std::collections::BTreeMap;
 use std::cmp;
 use cli::{Args, ArgsError};
 use hash::keccak;
-use ethereum_types::{U256, H256, Address};
+use ethereum_types::{U256, H256, Address, clean_0x};
 use parity_version::{version_data, version};
 use bytes::Bytes;
 use ansi_term::Colour;
-use sync::{NetworkConfiguration, validate_node_url, self};
+use sync::{NetworkConfiguration, self};
 use ethcore::ethstore::ethkey::{Secret, Public};
-use ethcore::client::{VMType};
+use ethcore::client::{VMType, PrewarmContract};
 use ethcore::miner::{stratum, MinerOptions};
 use ethcore::snapshot::SnapshotConfiguration;
 use ethcore::verification::queue::VerifierSettings;
 use miner::pool;
+use miner::tx_relay::RelayOptions;
 use num_cpus;
 
 use rpc::{IpcConfiguration, HttpConfiguration, WsConfiguration};
-use parity_rpc::NetworkSettings;
+use parity_rpc::{NetworkSettings, HealthThresholds, RateLimits, SubscriptionQueueConfig, GasPriceOracleOptions};
 use cache::CacheConfig;
-use helpers::{to_duration, to_mode, to_block_id, to_u256, to_pending_set, to_price, geth_ipc_path, parity_ipc_path, to_bootnodes, to_addresses, to_address, to_queue_strategy, to_queue_penalization, passwords_from_files};
+use db::TierConfig;
+use helpers::{to_duration, to_mode, to_block_id, to_u256, to_pending_set, to_price, geth_ipc_path, parity_ipc_path, to_bootnodes, to_addresses, to_address, to_queue_strategy, to_queue_penalization, passwords_from_files, to_cpu_list, to_peer_slots, reserved_nodes_from_file};
 use dir::helpers::{replace_home, replace_home_and_local};
 use params::{ResealPolicy, AccountsConfig, GasPricerConfig, MinerExtras, SpecType};
 use ethcore_logger::Config as LogConfig;
 use dir::{self, Directories, default_hypervisor_path, default_local_path, default_data_path};
 use ipfs::Configuration as IpfsConfiguration;
+use metrics::MetricsConfiguration;
 use ethcore_private_tx::{ProviderConfig, EncryptorConfig};
-use secretstore::{NodeSecretKey, Configuration as SecretStoreConfiguration, ContractAddress as SecretStoreContractAddress};
+use secretstore::{NodeSecretKey, Configuration as SecretStoreConfiguration, ContractAddress as SecretStoreContractAddress,
+	KeyStorage as SecretStoreKeyStorage};
 use updater::{UpdatePolicy, UpdateFilter, ReleaseTrack};
 use run::RunCmd;
 use blockchain::{BlockchainCmd, ImportBlockchain, ExportBlockchain, KillBlockchain, ExportState, DataFormat};
 use export_hardcoded_sync::ExportHsyncCmd;
+use test_gen::TestGenCmd;
+use dev_network::DevNetworkCmd;
+use test_run::TestRunCmd;
 use presale::ImportWallet;
 use account::{AccountCmd, NewAccount, ListAccounts, ImportAccounts, ImportFromGethAccounts};
 use snapshot::{self, SnapshotCommand};
@@ -84,6 +91,9 @@ pub enum Cmd {
 	Snapshot(SnapshotCommand),
 	Hash(Option<String>),
 	ExportHardcodedSync(ExportHsyncCmd),
+	TestGen(TestGenCmd),
+	TestRun(TestRunCmd),
+	DevNetwork(DevNetworkCmd),
 }
 
 pub struct Execute {
@@ -139,6 +149,7 @@ impl Configuration {
 		let warp_sync = !self.args.flag_no_warp;
 		let geth_compatibility = self.args.flag_geth;
 		let ipfs_conf = self.ipfs_config();
+		let metrics_conf = self.metrics_config();
 		let secretstore_conf = self.secretstore_config()?;
 		let format = self.format()?;
 
@@ -330,6 +341,37 @@ impl Configuration {
 				compaction: compaction,
 			};
 			Cmd::ExportHardcodedSync(export_hs_cmd)
+		} else if self.args.cmd_test {
+			Cmd::TestRun(TestRunCmd {
+				path: self.args.arg_test_run_path.clone().expect("CLI argument is required; qed"),
+				fork: self.args.arg_test_run_fork.clone(),
+			})
+		} else if self.args.cmd_test_gen {
+			let transaction = self.args.arg_test_gen_state_transaction.clone().expect("CLI argument is required; qed");
+			let test_gen_cmd = TestGenCmd {
+				spec: spec,
+				cache_config: cache_config,
+				dirs: dirs,
+				pruning: pruning,
+				pruning_history: pruning_history,
+				pruning_memory: self.args.arg_pruning_memory,
+				compaction: compaction,
+				tracing: tracing,
+				fat_db: fat_db,
+				transaction: clean_0x(&transaction).parse().map_err(|_| format!("Invalid transaction hash: {}", transaction))?,
+				fork: self.args.arg_test_gen_state_fork.clone(),
+				name: self.args.arg_test_gen_state_name.clone(),
+				file_path: self.args.arg_test_gen_state_file.clone(),
+			};
+			Cmd::TestGen(test_gen_cmd)
+		} else if self.args.cmd_dev_network {
+			Cmd::DevNetwork(DevNetworkCmd {
+				validators: self.args.arg_dev_network_validators,
+				out: self.args.arg_dev_network_out.clone(),
+				step_duration: self.args.arg_dev_network_step_duration,
+				key_iterations: self.args.arg_keys_iterations,
+				run: self.args.flag_dev_network_run,
+			})
 		} else {
 			let daemon = if self.args.cmd_daemon {
 				Some(self.args.arg_daemon_pid_file.clone().expect("CLI argument is required; qed"))
@@ -352,7 +394,10 @@ impl Configuration {
 				logger_config: logger_config.clone(),
 				miner_options: self.miner_options()?,
 				gas_price_percentile: self.args.arg_gas_price_percentile,
+				gas_price_oracle: self.gas_price_oracle()?,
 				poll_lifetime: self.args.arg_poll_lifetime,
+				call_cache_size: self.args.arg_cache_size_calls as usize * 1024 * 1024,
+				max_logs_range: self.args.arg_rpc_max_logs_range,
 				ws_conf: ws_conf,
 				snapshot_conf: snapshot_conf,
 				http_conf: http_conf,
@@ -374,6 +419,7 @@ impl Configuration {
 				geth_compatibility: geth_compatibility,
 				net_settings: self.network_settings()?,
 				ipfs_conf: ipfs_conf,
+				metrics_conf: metrics_conf,
 				secretstore_conf: secretstore_conf,
 				private_provider_conf: private_provider_conf,
 				private_encryptor_conf: private_enc_conf,
@@ -382,6 +428,7 @@ impl Configuration {
 				custom_bootnodes: self.args.arg_bootnodes.is_some(),
 				check_seal: !self.args.flag_no_seal_check,
 				download_old_blocks: !self.args.flag_no_ancient_blocks,
+				download_old_receipts: !self.args.flag_no_ancient_receipts,
 				verifier_settings: verifier_settings,
 				serve_light: !self.args.flag_no_serve_light,
 				light: self.args.flag_light,
@@ -390,6 +437,20 @@ impl Configuration {
 				no_hardcoded_sync: self.args.flag_no_hardcoded_sync,
 				on_demand_retry_count: self.args.arg_on_demand_retry_count,
 				on_demand_inactive_time_limit: self.args.arg_on_demand_inactive_time_limit,
+				evm_threads: self.args.arg_evm_threads,
+				thread_affinity: self.thread_affinity()?,
+				db_hot_tier: self.db_hot_tier(),
+				tx_lifecycle_log: self.args.arg_tx_lifecycle_log,
+				parallel_transactions: self.args.flag_parallel_transactions,
+				prewarm_contracts: self.prewarm_contracts()?,
+				cold_calls: self.args.flag_cold_calls,
+				block_propagation: self.args.arg_block_propagation.parse()?,
+				announcement_window: self.args.arg_block_announce_window,
+				sync_headers_batch: self.args.arg_sync_headers_batch,
+				sync_bodies_batch: self.args.arg_sync_bodies_batch,
+				snapshot_serving_peer_rate: self.args.arg_snapshot_serve_peer_rate.map(|rate| rate * 1024),
+				snapshot_serving_rate: self.args.arg_snapshot_serve_rate.map(|rate| rate * 1024),
+				reserved_peers_file: self.reserved_peers_file(),
 			};
 			Cmd::Run(run_cmd)
 		};
@@ -404,6 +465,17 @@ impl Configuration {
 		Ok(VMType::Interpreter)
 	}
 
+	fn prewarm_contracts(&self) -> Result<Vec<PrewarmContract>, String> {
+		match self.args.arg_prewarm_contracts {
+			Some(ref contracts) => contracts.split(',')
+				.map(str::trim)
+				.filter(|s| !s.is_empty())
+				.map(str::parse)
+				.collect(),
+			None => Ok(Vec::new()),
+		}
+	}
+
 	fn miner_extras(&self) -> Result<MinerExtras, String> {
 		let floor = to_u256(&self.args.arg_gas_floor_target)?;
 		let ceil = to_u256(&self.args.arg_gas_cap)?;
@@ -413,6 +485,7 @@ impl Configuration {
 			gas_range_target: (floor, ceil),
 			engine_signer: self.engine_signer()?,
 			work_notify: self.work_notify(),
+			tx_relay: self.tx_relay(),
 		};
 
 		Ok(extras)
@@ -496,6 +569,14 @@ impl Configuration {
 		self.args.arg_notify_work.as_ref().map_or_else(Vec::new, |s| s.split(',').map(|s| s.to_owned()).collect())
 	}
 
+	fn tx_relay(&self) -> RelayOptions {
+		RelayOptions {
+			urls: self.args.arg_tx_relay.as_ref().map_or_else(Vec::new, |s| s.split(',').map(|s| s.to_owned()).collect()),
+			retries: self.args.arg_tx_relay_retries,
+			retry_delay: Duration::from_millis(self.args.arg_tx_relay_retry_delay),
+		}
+	}
+
 	fn accounts_config(&self) -> Result<AccountsConfig, String> {
 		let cfg = AccountsConfig {
 			iterations: self.args.arg_keys_iterations,
@@ -526,6 +607,9 @@ impl Configuration {
 		if is_dev_chain && self.args.flag_force_sealing && self.args.arg_reseal_min_period == 0 {
 			return Err("Force sealing can't be used with reseal_min_period = 0".into());
 		}
+		if self.args.flag_external_consensus && self.args.flag_force_sealing {
+			return Err("Force sealing can't be used with external consensus".into());
+		}
 
 		let reseal = self.args.arg_reseal_on_txs.parse::<ResealPolicy>()?;
 
@@ -541,10 +625,20 @@ impl Configuration {
 			work_queue_size: self.args.arg_work_queue_size,
 			enable_resubmission: !self.args.flag_remove_solved,
 			infinite_pending_block: self.args.flag_infinite_pending_block,
+			deterministic_block_template: self.args.flag_deterministic_block_template,
+			pending_block_cache: self.args.flag_pending_block_cache,
 
 			tx_queue_penalization: to_queue_penalization(self.args.arg_tx_time_limit)?,
 			tx_queue_strategy: to_queue_strategy(&self.args.arg_tx_queue_strategy)?,
 			tx_queue_no_unfamiliar_locals: self.args.flag_tx_queue_no_unfamiliar_locals,
+			tx_queue_demotion_grace: Duration::from_secs(self.args.arg_tx_queue_demotion_grace),
+			tx_queue_replacement_bump: pool::scoring::replacement_bump_from_percent(self.args.arg_tx_queue_replacement_bump)
+				.ok_or_else(|| "--tx-queue-replacement-bump has to be between 0 and 1000 percent.".to_owned())?,
+			tx_rebroadcast_after: match self.args.arg_tx_queue_rebroadcast {
+				0 => None,
+				minutes => Some(Duration::from_secs(minutes * 60)),
+			},
+			tx_priority_senders: to_addresses(&self.args.arg_tx_priority_senders)?.into_iter().collect(),
 			refuse_service_transactions: self.args.flag_refuse_service_transactions,
 
 			pool_limits: self.pool_limits()?,
@@ -554,12 +648,41 @@ impl Configuration {
 		Ok(options)
 	}
 
+	fn gas_price_oracle(&self) -> Result<GasPriceOracleOptions, String> {
+		if self.args.arg_gas_price_oracle_blocks == 0 {
+			return Err("--gas-price-oracle-blocks has to be greater than 0.".into());
+		}
+		if self.args.arg_gas_price_oracle_smoothing == 0 || self.args.arg_gas_price_oracle_smoothing > 100 {
+			return Err("--gas-price-oracle-smoothing has to be between 1 and 100.".into());
+		}
+
+		Ok(GasPriceOracleOptions {
+			blocks: self.args.arg_gas_price_oracle_blocks,
+			percentile: self.args.arg_gas_price_percentile,
+			min_gas_price: to_u256(&self.args.arg_gas_price_oracle_min)?,
+			smoothing: self.args.arg_gas_price_oracle_smoothing,
+		})
+	}
+
 	fn pool_limits(&self) -> Result<pool::Options, String> {
 		let max_count = self.args.arg_tx_queue_size;
+		let max_per_sender = self.args.arg_tx_queue_per_sender.unwrap_or_else(|| cmp::max(16, max_count / 100));
+
+		if let Some(memory) = self.args.arg_tx_pool_memory {
+			if memory == 0 {
+				return Err("--tx-pool-memory has to be greater than 0.".into());
+			}
+			let max_mem_usage = memory as usize * 1024 * 1024;
+			return Ok(pool::Options {
+				max_count: pool::max_count_for_memory(max_mem_usage),
+				max_per_sender,
+				max_mem_usage,
+			});
+		}
 
 		Ok(pool::Options {
 			max_count,
-			max_per_sender: self.args.arg_tx_queue_per_sender.unwrap_or_else(|| cmp::max(16, max_count / 100)),
+			max_per_sender,
 			max_mem_usage: if self.args.arg_tx_queue_mem_limit > 0 {
 				self.args.arg_tx_queue_mem_limit as usize * 1024 * 1024
 			} else {
@@ -600,6 +723,7 @@ impl Configuration {
 			http_interface: self.secretstore_http_interface(),
 			http_port: self.args.arg_ports_shift + self.args.arg_secretstore_http_port,
 			data_path: self.directories().secretstore,
+			key_storage: self.secretstore_key_storage()?,
 			admin_public: self.secretstore_admin_public()?,
 		})
 	}
@@ -614,6 +738,15 @@ impl Configuration {
 		}
 	}
 
+	fn metrics_config(&self) -> MetricsConfiguration {
+		MetricsConfiguration {
+			enabled: self.args.flag_metrics,
+			port: self.args.arg_ports_shift + self.args.arg_metrics_port,
+			interface: self.metrics_interface(),
+			prefix: self.args.arg_metrics_prefix.clone(),
+		}
+	}
+
 	fn gas_pricer_config(&self) -> Result<GasPricerConfig, String> {
 		fn wei_per_gas(usd_per_tx: f32, usd_per_eth: f32) -> U256 {
 			let wei_per_usd: f32 = 1.0e18 / usd_per_eth;
@@ -658,32 +791,63 @@ impl Configuration {
 		}
 	}
 
-	fn init_reserved_nodes(&self) -> Result<Vec<String>, String> {
+	fn reserved_peers_file(&self) -> Option<String> {
+		self.args.arg_reserved_peers.as_ref().map(|path| replace_home(&self.directories().base, path))
+	}
+
+	fn denied_ips_path(&self) -> Result<Option<String>, String> {
 		use std::fs::File;
+		use std::io::Read;
 
-		match self.args.arg_reserved_peers {
-			Some(ref path) => {
-				let path = replace_home(&self.directories().base, path);
-
-				let mut buffer = String::new();
-				let mut node_file = File::open(&path).map_err(|e| format!("Error opening reserved nodes file: {}", e))?;
-				node_file.read_to_string(&mut buffer).map_err(|_| "Error reading reserved node file")?;
-				let lines = buffer.lines().map(|s| s.trim().to_owned()).filter(|s| !s.is_empty() && !s.starts_with("#")).collect::<Vec<_>>();
-
-				for line in &lines {
-					match validate_node_url(line).map(Into::into) {
-						None => continue,
-						Some(sync::ErrorKind::AddressResolve(_)) => return Err(format!("Failed to resolve hostname of a boot node: {}", line)),
-						Some(_) => return Err(format!("Invalid node address format given for a boot node: {}", line)),
-					}
-				}
+		let path = match self.args.arg_deny_ips_file {
+			Some(ref path) => replace_home(&self.directories().base, path),
+			None => return Ok(None),
+		};
+		// The network layer reloads the file when it changes, validate it once upfront.
+		let mut buffer = String::new();
+		File::open(&path).and_then(|mut f| f.read_to_string(&mut buffer))
+			.map_err(|e| format!("Error reading denied IPs file: {}", e))?;
+		IpFilter::parse_list(&buffer).map_err(|e| format!("Invalid entry in denied IPs file: {:?}", e))?;
+		Ok(Some(path))
+	}
 
-				Ok(lines)
-			},
+	fn init_reserved_nodes(&self) -> Result<Vec<String>, String> {
+		match self.reserved_peers_file() {
+			Some(path) => reserved_nodes_from_file(&path),
 			None => Ok(Vec::new())
 		}
 	}
 
+	fn fleet_config(&self, listen_address: SocketAddr) -> Result<Option<sync::FleetConfiguration>, String> {
+		use std::fs::File;
+
+		let path = match self.args.arg_fleet_secret {
+			Some(ref path) => replace_home(&self.directories().base, path),
+			None => return Ok(None),
+		};
+
+		let mut buffer = String::new();
+		File::open(&path).and_then(|mut file| file.read_to_string(&mut buffer))
+			.map_err(|e| format!("Error reading fleet secret file: {}", e))?;
+		let secret: H256 = clean_0x(buffer.trim()).parse()
+			.map_err(|_| format!("Invalid fleet secret in {}, expected 32 hex-encoded bytes.", path))?;
+
+		let members = match self.args.arg_fleet_members {
+			Some(ref members) => members.split(',')
+				.map(str::trim)
+				.filter(|s| !s.is_empty())
+				.map(|s| s.parse().map_err(|_| format!("Invalid fleet member address: {}", s)))
+				.collect::<Result<_, _>>()?,
+			None => Vec::new(),
+		};
+
+		Ok(Some(sync::FleetConfiguration {
+			secret,
+			listen_address: SocketAddr::new(listen_address.ip(), self.args.arg_ports_shift + self.args.arg_fleet_port),
+			members,
+		}))
+	}
+
 	fn net_addresses(&self) -> Result<(SocketAddr, Option<SocketAddr>), String> {
 		let port = self.args.arg_ports_shift + self.args.arg_port;
 		let listen_address = SocketAddr::new(self.interface(&self.args.arg_interface).parse().unwrap(), port);
@@ -700,6 +864,7 @@ impl Configuration {
 	fn net_config(&self) -> Result<NetworkConfiguration, String> {
 		let mut ret = NetworkConfiguration::new();
 		ret.nat_enabled = self.args.arg_nat == "any" || self.args.arg_nat == "upnp";
+		ret.nat_pmp_enabled = self.args.arg_nat == "any" || self.args.arg_nat == "natpmp";
 		ret.boot_nodes = to_bootnodes(&self.args.arg_bootnodes)?;
 		let (listen, public) = self.net_addresses()?;
 		ret.listen_address = Some(format!("{}", listen));
@@ -712,16 +877,32 @@ impl Configuration {
 			Some(Err(err)) => return Err(err),
 		};
 		ret.discovery_enabled = !self.args.flag_no_discovery && !self.args.flag_nodiscover;
+		ret.discovery_v5 = self.args.flag_discovery_v5;
 		ret.max_peers = self.max_peers();
 		ret.min_peers = self.min_peers();
 		ret.snapshot_peers = self.snapshot_peers();
+		ret.peer_slots = match self.args.arg_peer_slots {
+			Some(ref slots) => to_peer_slots(slots)?,
+			None => Vec::new(),
+		};
 		ret.ip_filter = self.ip_filter()?;
+		ret.denied_ips_path = self.denied_ips_path()?;
 		ret.max_pending_peers = self.max_pending_peers();
+		ret.io_threads = match self.args.arg_sync_threads {
+			0 => return Err("Invalid --sync-threads: must be greater than 0.".into()),
+			threads => threads,
+		};
+		ret.key_log_path = match (self.args.arg_rlpx_key_log.clone(), self.args.flag_unsafe_expose_rlpx_keys) {
+			(Some(path), true) => Some(path),
+			(Some(_), false) => return Err("--rlpx-key-log requires --unsafe-expose-rlpx-keys.".into()),
+			(None, _) => None,
+		};
 		let mut net_path = PathBuf::from(self.directories().base);
 		net_path.push("network");
 		ret.config_path = Some(net_path.to_str().unwrap().to_owned());
 		ret.reserved_nodes = self.init_reserved_nodes()?;
 		ret.allow_non_reserved = !self.args.flag_reserved_only;
+		ret.fleet = self.fleet_config(listen)?;
 		ret.client_version = {
 			let mut client_version = version();
 			if !self.args.arg_identity.is_empty() {
@@ -734,6 +915,21 @@ impl Configuration {
 		Ok(ret)
 	}
 
+	fn db_hot_tier(&self) -> Option<TierConfig> {
+		self.args.arg_db_hot_path.as_ref().map(|path| TierConfig {
+			path: replace_home(&self.directories().base, path).into(),
+			capacity: self.args.arg_db_hot_entries,
+			promote_after: self.args.arg_db_hot_promote_after,
+		})
+	}
+
+	fn thread_affinity(&self) -> Result<Vec<usize>, String> {
+		match self.args.arg_thread_affinity {
+			Some(ref cpus) => to_cpu_list(cpus),
+			None => Ok(Vec::new()),
+		}
+	}
+
 	fn network_id(&self) -> Option<u64> {
 		self.args.arg_network_id.or(self.args.arg_networkid)
 	}
@@ -814,21 +1010,33 @@ impl Configuration {
 	}
 
 	fn ipc_config(&self) -> Result<IpcConfiguration, String> {
+		let apis = {
+			let mut apis = self.args.arg_ipcapi.clone().unwrap_or(self.args.arg_ipc_apis.clone());
+			if self.args.flag_geth {
+				if !apis.is_empty() {
+					apis.push_str(",");
+				}
+				apis.push_str("personal");
+			}
+			if self.args.flag_external_consensus {
+				if !apis.is_empty() {
+					apis.push_str(",");
+				}
+				apis.push_str("engine");
+			}
+			apis
+		};
 		let conf = IpcConfiguration {
 			enabled: !(self.args.flag_ipcdisable || self.args.flag_ipc_off || self.args.flag_no_ipc),
 			socket_addr: self.ipc_path(),
-			apis: {
-				let mut apis = self.args.arg_ipcapi.clone().unwrap_or(self.args.arg_ipc_apis.clone());
-				if self.args.flag_geth {
-					if !apis.is_empty() {
- 						apis.push_str(",");
- 					}
-					apis.push_str("personal");
-				}
-				apis.parse()?
-			},
+			apis: apis.parse()?,
+			method_acl: apis.parse()?,
 		};
 
+		if self.args.flag_external_consensus && !conf.enabled {
+			return Err("External consensus requires the IPC interface to be enabled.".into());
+		}
+
 		Ok(conf)
 	}
 
@@ -838,6 +1046,7 @@ impl Configuration {
 			interface: self.rpc_interface(),
 			port: self.args.arg_ports_shift + self.args.arg_rpcport.unwrap_or(self.args.arg_jsonrpc_port),
 			apis: self.rpc_apis().parse()?,
+			method_acl: self.rpc_apis().parse()?,
 			hosts: self.rpc_hosts(),
 			cors: self.rpc_cors(),
 			server_threads: match self.args.arg_jsonrpc_server_threads {
@@ -849,11 +1058,30 @@ impl Configuration {
 				Some(max) if max > 0 => max as usize,
 				_ => 5usize,
 			},
+			max_response_size: max_response_size("--jsonrpc-max-response-size", self.args.arg_jsonrpc_max_response_size)?,
+			batch_parallelism: match self.args.arg_jsonrpc_batch_parallelism {
+				0 => return Err("Invalid --jsonrpc-batch-parallelism: must be greater than 0.".into()),
+				parallelism => parallelism,
+			},
+			graphql: self.args.flag_jsonrpc_graphql,
+			rate_limits: RateLimits {
+				requests_per_second: self.args.arg_jsonrpc_max_requests_per_second,
+				concurrent_requests: self.args.arg_jsonrpc_max_concurrent_requests,
+			},
+			jwt_secret: self.rpc_jwt_secret(),
+			health: HealthThresholds {
+				max_block_age: self.args.arg_jsonrpc_health_max_block_age,
+				min_peers: self.args.arg_jsonrpc_health_min_peers,
+			},
 		};
 
 		Ok(conf)
 	}
 
+	fn rpc_jwt_secret(&self) -> Option<PathBuf> {
+		self.args.arg_rpc_jwt_secret.as_ref().map(|path| replace_home(&self.directories().base, path).into())
+	}
+
 	fn ws_config(&self) -> Result<WsConfiguration, String> {
 		let support_token_api =
 			// enabled when not unlocking
@@ -864,16 +1092,40 @@ impl Configuration {
 			interface: self.ws_interface(),
 			port: self.args.arg_ports_shift + self.args.arg_ws_port,
 			apis: self.args.arg_ws_apis.parse()?,
+			method_acl: self.args.arg_ws_apis.parse()?,
 			hosts: self.ws_hosts(),
 			origins: self.ws_origins(),
 			signer_path: self.directories().signer.into(),
 			support_token_api,
 			max_connections: self.args.arg_ws_max_connections,
+			rate_limits: RateLimits {
+				requests_per_second: self.args.arg_ws_max_requests_per_second,
+				concurrent_requests: self.args.arg_ws_max_concurrent_requests,
+			},
+			trusted_proxy: self.args.flag_ws_trusted_proxy,
+			jwt_secret: self.rpc_jwt_secret(),
+			max_response_size: max_response_size("--ws-max-response-size", self.args.arg_ws_max_response_size)?,
+			subscription_queue: self.ws_subscription_queue()?,
 		};
 
 		Ok(conf)
 	}
 
+	fn ws_subscription_queue(&self) -> Result<SubscriptionQueueConfig, String> {
+		if self.args.arg_ws_subscription_queue == 0 {
+			return Err("Invalid --ws-subscription-queue: must be greater than 0.".into());
+		}
+		if self.args.arg_ws_logs_batch == 0 {
+			return Err("Invalid --ws-logs-batch: must be greater than 0.".into());
+		}
+
+		Ok(SubscriptionQueueConfig {
+			capacity: self.args.arg_ws_subscription_queue,
+			overflow: self.args.arg_ws_subscription_overflow.parse()?,
+			logs_batch_size: self.args.arg_ws_logs_batch,
+		})
+	}
+
 	fn private_provider_config(&self) -> Result<(ProviderConfig, EncryptorConfig, bool), String> {
 		let provider_conf = ProviderConfig {
 			validator_accounts: to_addresses(&self.args.arg_private_validators)?,
@@ -1019,6 +1271,10 @@ impl Configuration {
 		self.interface(&self.args.arg_ipfs_api_interface)
 	}
 
+	fn metrics_interface(&self) -> String {
+		self.interface(&self.args.arg_metrics_interface)
+	}
+
 	fn secretstore_interface(&self) -> String {
 		self.interface(&self.args.arg_secretstore_interface)
 	}
@@ -1038,6 +1294,27 @@ impl Configuration {
 		}
 	}
 
+	fn secretstore_key_storage(&self) -> Result<SecretStoreKeyStorage, String> {
+		let secret = match self.args.arg_secretstore_storage_secret {
+			Some(ref s) => Some(s.parse().map_err(|e| format!("Invalid secret store storage secret: {}. Error: {:?}", s, e))?),
+			None => None,
+		};
+
+		match self.args.arg_secretstore_storage.as_str() {
+			"db" => Ok(SecretStoreKeyStorage::Database),
+			"files" => Ok(SecretStoreKeyStorage::EncryptedFiles {
+				path: replace_home(&self.directories().base, &self.args.arg_secretstore_storage_path),
+				secret: secret,
+			}),
+			"etcd" => Ok(SecretStoreKeyStorage::Etcd {
+				endpoints: self.args.arg_secretstore_etcd_endpoints.split(',').filter(|e| !e.is_empty()).map(Into::into).collect(),
+				prefix: self.args.arg_secretstore_etcd_prefix.clone(),
+				secret: secret,
+			}),
+			storage => Err(format!("Invalid secret store storage: {}. Must be one of: db, files, etcd", storage)),
+		}
+	}
+
 	fn secretstore_admin_public(&self) -> Result<Option<Public>, String> {
 		match self.args.arg_secretstore_admin_public.as_ref() {
 			Some(admin_public) => Ok(Some(admin_public.parse().map_err(|e| format!("Invalid secret store admin public: {}", e))?)),
@@ -1135,10 +1412,18 @@ impl Configuration {
 		::whisper::Config {
 			enabled: self.args.flag_whisper,
 			target_message_pool_size: self.args.arg_whisper_pool_size * 1024 * 1024,
+			topic_bloom_exchange: self.args.flag_whisper_topic_bloom,
 		}
 	}
 }
 
+fn max_response_size(flag: &str, kilobytes: Option<usize>) -> Result<Option<usize>, String> {
+	match kilobytes {
+		Some(0) => Err(format!("Invalid {}: must be greater than 0.", flag)),
+		kilobytes => Ok(kilobytes.map(|kilobytes| kilobytes * 1024)),
+	}
+}
+
 fn into_secretstore_service_contract_address(s: Option<&String>) -> Result<Option<SecretStoreContractAddress>, String> {
 	match s.map(String::as_str) {
 		None | Some("none") => Ok(None),
@@ -1168,7 +1453,7 @@ mod tests {
 	use params::SpecType;
 	use presale::ImportWallet;
 	use rpc::WsConfiguration;
-	use rpc_apis::ApiSet;
+	use rpc_apis::{Api, ApiSet};
 	use run::RunCmd;
 
 	use network::{AllowIP, IpFilter};
@@ -1312,6 +1597,50 @@ mod tests {
 		})));
 	}
 
+	#[test]
+	fn test_command_test_run() {
+		let args = vec!["parity", "test", "run", "--fork", "Byzantium", "tests/"];
+		let conf = parse(&args);
+		assert_eq!(conf.into_command().unwrap().cmd, Cmd::TestRun(TestRunCmd {
+			path: "tests/".into(),
+			fork: Some("Byzantium".into()),
+		}));
+	}
+
+	#[test]
+	fn test_command_test_gen_state() {
+		let args = vec!["parity", "test-gen", "state", "--fork", "Constantinople", "0x0000000000000000000000000000000000000000000000000000000000000001", "test.json"];
+		let conf = parse(&args);
+		assert_eq!(conf.into_command().unwrap().cmd, Cmd::TestGen(TestGenCmd {
+			spec: Default::default(),
+			cache_config: Default::default(),
+			dirs: Default::default(),
+			pruning: Default::default(),
+			pruning_history: 64,
+			pruning_memory: 32,
+			compaction: Default::default(),
+			tracing: Default::default(),
+			fat_db: Default::default(),
+			transaction: 1.into(),
+			fork: "Constantinople".into(),
+			name: None,
+			file_path: Some("test.json".into()),
+		}));
+	}
+
+	#[test]
+	fn test_command_dev_network() {
+		let args = vec!["parity", "dev-network", "--validators", "2", "--out", "net", "--run"];
+		let conf = parse(&args);
+		assert_eq!(conf.into_command().unwrap().cmd, Cmd::DevNetwork(DevNetworkCmd {
+			validators: 2,
+			out: "net".into(),
+			step_duration: 5,
+			key_iterations: 10240,
+			run: true,
+		}));
+	}
+
 	#[test]
 	fn test_command_blockchain_export_with_custom_format() {
 		let args = vec!["parity", "export", "blocks", "--format", "hex", "blockchain.json"];
@@ -1344,11 +1673,17 @@ mod tests {
 			interface: "127.0.0.1".into(),
 			port: 8546,
 			apis: ApiSet::UnsafeContext,
+			method_acl: Default::default(),
 			origins: Some(vec!["parity://*".into(),"chrome-extension://*".into(), "moz-extension://*".into()]),
 			hosts: Some(vec![]),
 			signer_path: expected.into(),
 			support_token_api: true,
 			max_connections: 100,
+			rate_limits: Default::default(),
+			trusted_proxy: false,
+			jwt_secret: None,
+			max_response_size: None,
+			subscription_queue: Default::default(),
 		}, LogConfig {
 			color: true,
 			mode: None,
@@ -1367,6 +1702,109 @@ mod tests {
 		});
 	}
 
+	#[test]
+	fn test_rpc_rate_limits() {
+		let args = vec!["parity", "--jsonrpc-max-requests-per-second", "10", "--ws-max-concurrent-requests", "2"];
+		let conf = parse(&args);
+
+		assert_eq!(conf.http_config().unwrap().rate_limits, RateLimits {
+			requests_per_second: Some(10),
+			concurrent_requests: None,
+		});
+		assert_eq!(conf.ws_config().unwrap().rate_limits, RateLimits {
+			requests_per_second: None,
+			concurrent_requests: Some(2),
+		});
+		assert!(!conf.ws_config().unwrap().trusted_proxy);
+		assert!(parse(&["parity", "--ws-trusted-proxy"]).ws_config().unwrap().trusted_proxy);
+	}
+
+	#[test]
+	fn test_rpc_health_thresholds() {
+		let args = vec!["parity", "--jsonrpc-health-max-block-age", "60", "--jsonrpc-health-min-peers", "3"];
+		let conf = parse(&args);
+
+		assert_eq!(conf.http_config().unwrap().health, HealthThresholds {
+			max_block_age: 60,
+			min_peers: 3,
+		});
+		assert_eq!(parse(&["parity"]).http_config().unwrap().health, HealthThresholds::default());
+	}
+
+	#[test]
+	fn test_rpc_method_acl() {
+		let args = vec!["parity", "--jsonrpc-apis", "eth:*,parity:*,parity_setChain:deny"];
+		let conf = parse(&args);
+		let http_conf = conf.http_config().unwrap();
+
+		assert_eq!(http_conf.apis, ApiSet::List(vec![Api::Eth, Api::Parity].into_iter().collect()));
+		assert!(http_conf.method_acl.is_allowed("parity_chain"));
+		assert!(!http_conf.method_acl.is_allowed("parity_setChain"));
+		assert!(parse(&["parity"]).http_config().unwrap().method_acl.is_empty());
+		assert!(parse(&["parity", "--jsonrpc-apis", "eth,parity_setChain:maybe"]).http_config().is_err());
+
+		let ws_conf = parse(&["parity", "--ws-apis", "eth:*,eth_sign:deny"]).ws_config().unwrap();
+		assert!(ws_conf.method_acl.is_allowed("eth_call"));
+		assert!(!ws_conf.method_acl.is_allowed("eth_sign"));
+		let ipc_conf = parse(&["parity", "--ipc-apis", "parity:*,parity_set*:deny"]).ipc_config().unwrap();
+		assert!(ipc_conf.method_acl.is_allowed("parity_chain"));
+		assert!(!ipc_conf.method_acl.is_allowed("parity_setChain"));
+	}
+
+	#[test]
+	fn test_max_response_size() {
+		let conf = parse(&["parity", "--jsonrpc-max-response-size", "512", "--ws-max-response-size", "64"]);
+
+		assert_eq!(conf.http_config().unwrap().max_response_size, Some(512 * 1024));
+		assert_eq!(conf.ws_config().unwrap().max_response_size, Some(64 * 1024));
+		assert_eq!(parse(&["parity"]).ws_config().unwrap().max_response_size, None);
+		assert!(parse(&["parity", "--ws-max-response-size", "0"]).ws_config().is_err());
+	}
+
+	#[test]
+	fn test_batch_parallelism() {
+		assert_eq!(parse(&["parity"]).http_config().unwrap().batch_parallelism, 4);
+		assert_eq!(parse(&["parity", "--jsonrpc-batch-parallelism", "16"]).http_config().unwrap().batch_parallelism, 16);
+		assert!(parse(&["parity", "--jsonrpc-batch-parallelism", "0"]).http_config().is_err());
+	}
+
+	#[test]
+	fn test_prewarm_contracts() {
+		let args = vec!["parity", "--prewarm-contracts", "0x0000000000000000000000000000000000000005, 0x0000000000000000000000000000000000000000000000000000000000000006"];
+		let conf = parse(&args);
+
+		assert_eq!(conf.prewarm_contracts().unwrap(), vec![
+			PrewarmContract::Address(5.into()),
+			PrewarmContract::CodeHash(6.into()),
+		]);
+		assert!(parse(&["parity", "--prewarm-contracts", "0x05"]).prewarm_contracts().is_err());
+	}
+
+	#[test]
+	fn test_rpc_jwt_secret() {
+		let args = vec!["parity", "--rpc-jwt-secret", "/tmp/jwt.hex"];
+		let conf = parse(&args);
+
+		assert_eq!(conf.http_config().unwrap().jwt_secret, Some(PathBuf::from("/tmp/jwt.hex")));
+		assert_eq!(conf.ws_config().unwrap().jwt_secret, Some(PathBuf::from("/tmp/jwt.hex")));
+	}
+
+	#[test]
+	fn test_ws_subscription_queue() {
+		use parity_rpc::OverflowPolicy;
+
+		let args = vec!["parity", "--ws-subscription-queue", "16", "--ws-subscription-overflow", "coalesce", "--ws-logs-batch", "100"];
+		let conf = parse(&args);
+
+		assert_eq!(conf.ws_config().unwrap().subscription_queue, SubscriptionQueueConfig {
+			capacity: 16,
+			overflow: OverflowPolicy::Coalesce,
+			logs_batch_size: 100,
+		});
+		assert!(parse(&["parity", "--ws-subscription-overflow", "block"]).ws_config().is_err());
+		assert!(parse(&["parity", "--ws-subscription-queue", "0"]).ws_config().is_err());
+	}
+
 	#[test]
 	fn test_run_cmd() {
 		let args = vec!["parity"];
@@ -1382,7 +1820,10 @@ mod tests {
 			logger_config: Default::default(),
 			miner_options: Default::default(),
 			gas_price_percentile: 50,
+			gas_price_oracle: Default::default(),
 			poll_lifetime: 60,
+			call_cache_size: 4 * 1024 * 1024,
+			max_logs_range: None,
 			ws_conf: Default::default(),
 			http_conf: Default::default(),
 			ipc_conf: Default::default(),
@@ -1410,6 +1851,7 @@ mod tests {
 			geth_compatibility: false,
 			net_settings: Default::default(),
 			ipfs_conf: Default::default(),
+			metrics_conf: Default::default(),
 			secretstore_conf: Default::default(),
 			private_provider_conf: Default::default(),
 			private_encryptor_conf: Default::default(),
@@ -1421,6 +1863,7 @@ mod tests {
 			stratum: None,
 			check_seal: true,
 			download_old_blocks: true,
+			download_old_receipts: true,
 			verifier_settings: Default::default(),
 			serve_light: true,
 			light: false,
@@ -1429,6 +1872,20 @@ mod tests {
 			whisper: Default::default(),
 			on_demand_retry_count: None,
 			on_demand_inactive_time_limit: None,
+			evm_threads: None,
+			thread_affinity: Vec::new(),
+			db_hot_tier: None,
+			tx_lifecycle_log: None,
+			parallel_transactions: false,
+			prewarm_contracts: Vec::new(),
+			cold_calls: false,
+			block_propagation: sync::BlockPropagation::Sqrt,
+			announcement_window: 256,
+			sync_headers_batch: 128,
+			sync_bodies_batch: 32,
+			snapshot_serving_peer_rate: None,
+			snapshot_serving_rate: None,
+			reserved_peers_file: None,
 		};
 		expected.secretstore_conf.enabled = cfg!(feature = "secretstore");
 		expected.secretstore_conf.http_enabled = cfg!(feature = "secretstore");
@@ -1450,6 +1907,47 @@ mod tests {
 		assert_eq!(conf2.miner_options().unwrap(), mining_options);
 	}
 
+	#[test]
+	fn should_parse_replacement_bump() {
+		let conf = parse(&["parity", "--tx-queue-replacement-bump", "10"]);
+		assert_eq!(conf.miner_options().unwrap().tx_queue_replacement_bump, 1_000);
+
+		assert!(parse(&["parity", "--tx-queue-replacement-bump", "1500"]).miner_options().is_err());
+	}
+
+	#[test]
+	fn should_parse_rebroadcast_delay() {
+		let conf = parse(&["parity", "--tx-queue-rebroadcast", "5"]);
+		assert_eq!(conf.miner_options().unwrap().tx_rebroadcast_after, Some(Duration::from_secs(300)));
+
+		let conf = parse(&["parity", "--tx-queue-rebroadcast", "0"]);
+		assert_eq!(conf.miner_options().unwrap().tx_rebroadcast_after, None);
+	}
+
+	#[test]
+	fn should_parse_gas_price_oracle_options() {
+		let conf = parse(&["parity", "--gas-price-percentile", "60", "--gas-price-oracle-blocks", "20", "--gas-price-oracle-min", "1000000000", "--gas-price-oracle-smoothing", "25"]);
+		assert_eq!(conf.gas_price_oracle().unwrap(), GasPriceOracleOptions {
+			blocks: 20,
+			percentile: 60,
+			min_gas_price: 1_000_000_000.into(),
+			smoothing: 25,
+		});
+
+		assert!(parse(&["parity", "--gas-price-oracle-smoothing", "0"]).gas_price_oracle().is_err());
+	}
+
+	#[test]
+	fn should_parse_priority_senders() {
+		let conf = parse(&["parity", "--tx-priority-senders", "0x0000000000000000000000000000000000000005,0000000000000000000000000000000000000006"]);
+		let senders = conf.miner_options().unwrap().tx_priority_senders;
+		assert_eq!(senders.len(), 2);
+		assert!(senders.contains(&Address::from(5)));
+		assert!(senders.contains(&Address::from(6)));
+
+		assert!(parse(&["parity", "--tx-priority-senders", "0xnope"]).miner_options().is_err());
+	}
+
 	#[test]
 	fn should_fail_on_force_reseal_and_reseal_min_period() {
 		let conf = parse(&["parity", "--chain", "dev", "--force-sealing", "--reseal-min-period", "0"]);
@@ -1548,6 +2046,19 @@ mod tests {
 		assert(conf2);
 	}
 
+	#[test]
+	fn should_expose_engine_api_over_ipc_with_external_consensus() {
+		// given
+		let default = parse(&["parity"]);
+		let external = parse(&["parity", "--external-consensus"]);
+
+		// then
+		assert!(!default.ipc_config().unwrap().apis.list_apis().contains(&Api::Engine));
+		assert!(external.ipc_config().unwrap().apis.list_apis().contains(&Api::Engine));
+		assert!(parse(&["parity", "--external-consensus", "--no-ipc"]).ipc_config().is_err());
+		assert!(parse(&["parity", "--external-consensus", "--force-sealing"]).miner_options().is_err());
+	}
+
 	#[test]
 	fn should_parse_rpc_hosts() {
 		// given
@@ -1565,6 +2076,17 @@ mod tests {
 		assert_eq!(conf3.rpc_hosts(), Some(vec!["parity.io".into(), "something.io".into()]));
 	}
 
+	#[test]
+	fn should_parse_rpc_graphql() {
+		// when
+		let conf0 = parse(&["parity"]);
+		let conf1 = parse(&["parity", "--jsonrpc-graphql"]);
+
+		// then
+		assert!(!conf0.http_config().unwrap().graphql);
+		assert!(conf1.http_config().unwrap().graphql);
+	}
+
 	#[test]
 	fn should_parse_ipfs_hosts() {
 		// given
@@ -1647,6 +2169,29 @@ mod tests {
 		assert_eq!(reserved_nodes.unwrap().len(), 1);
 	}
 
+	#[test]
+	fn should_validate_denied_ips_file() {
+		let tempdir = TempDir::new("").unwrap();
+		let filename = tempdir.path().join("denied");
+		File::create(&filename).unwrap().write_all(b"# Sample comment\n10.0.0.0/8\n192.168.1.1\n").unwrap();
+		let args = vec!["parity", "--deny-ips-file", filename.to_str().unwrap()];
+		let conf = Configuration::parse_cli(&args).unwrap();
+		assert_eq!(conf.denied_ips_path(), Ok(Some(filename.to_str().unwrap().to_owned())));
+
+		File::create(&filename).unwrap().write_all(b"10.0.0.0/33\n").unwrap();
+		assert!(conf.denied_ips_path().is_err());
+	}
+
+	#[test]
+	fn should_parse_rpc_max_logs_range() {
+		let args = vec!["parity", "--rpc-max-logs-range", "5000"];
+		let conf = Configuration::parse_cli(&args).unwrap();
+		match conf.into_command().unwrap().cmd {
+			Cmd::Run(c) => assert_eq!(c.max_logs_range, Some(5000)),
+			_ => panic!("Should be Cmd::Run"),
+		}
+	}
+
 	#[test]
 	fn test_dev_preset() {
 		let args = vec!["parity", "--config", "dev"];
@@ -1792,6 +2337,24 @@ mod tests {
 		assert_eq!(conf1.ipfs_config().port, 5002);
 	}
 
+	#[test]
+	fn should_parse_metrics_config() {
+		// given
+
+		// when
+		let conf0 = parse(&["parity"]);
+		let conf1 = parse(&["parity", "--metrics", "--metrics-port", "9100", "--metrics-interface", "all", "--metrics-prefix", "parity_"]);
+
+		// then
+		assert_eq!(conf0.metrics_config(), Default::default());
+		assert_eq!(conf1.metrics_config(), MetricsConfiguration {
+			enabled: true,
+			port: 9100,
+			interface: "0.0.0.0".into(),
+			prefix: "parity_".into(),
+		});
+	}
+
 	#[test]
 	fn should_expose_all_servers() {
 		// given
@@ -1920,4 +2483,107 @@ mod tests {
 			_ => panic!("Should be Cmd::Run"),
 		}
 	}
+
+	#[test]
+	fn should_parse_secretstore_key_storage() {
+		let conf0 = parse(&["parity"]);
+		let conf1 = parse(&["parity", "--secretstore-storage", "etcd", "--secretstore-etcd-endpoints",
+			"http://10.0.0.1:2379,http://10.0.0.2:2379", "--secretstore-etcd-prefix", "/node1/"]);
+		let conf2 = parse(&["parity", "--secretstore-storage", "unknown"]);
+
+		assert_eq!(conf0.secretstore_config().unwrap().key_storage, SecretStoreKeyStorage::Database);
+		assert_eq!(conf1.secretstore_config().unwrap().key_storage, SecretStoreKeyStorage::Etcd {
+			endpoints: vec!["http://10.0.0.1:2379".into(), "http://10.0.0.2:2379".into()],
+			prefix: "/node1/".into(),
+			secret: None,
+		});
+		assert!(conf2.secretstore_config().is_err());
+	}
+
+	#[test]
+	fn should_parse_thread_settings() {
+		let conf0 = parse(&["parity"]);
+		let conf1 = parse(&["parity", "--sync-threads", "2", "--thread-affinity", "0-2,5"]);
+		let conf2 = parse(&["parity", "--thread-affinity", "2-1"]);
+		let conf3 = parse(&["parity", "--sync-threads", "0"]);
+
+		assert_eq!(conf0.net_config().unwrap().io_threads, 4);
+		assert_eq!(conf0.thread_affinity(), Ok(vec![]));
+		assert_eq!(conf1.net_config().unwrap().io_threads, 2);
+		assert_eq!(conf1.thread_affinity(), Ok(vec![0, 1, 2, 5]));
+		assert!(conf2.thread_affinity().is_err());
+		assert!(conf3.net_config().is_err());
+	}
+
+	#[test]
+	fn should_parse_db_hot_tier() {
+		let conf0 = parse(&["parity"]);
+		let conf1 = parse(&["parity", "--db-hot-path", "/nvme/parity", "--db-hot-entries", "500", "--db-hot-promote-after", "2"]);
+
+		assert_eq!(conf0.db_hot_tier(), None);
+		assert_eq!(conf1.db_hot_tier(), Some(TierConfig {
+			path: "/nvme/parity".into(),
+			capacity: 500,
+			promote_after: 2,
+		}));
+	}
+
+	#[test]
+	fn should_limit_pool_by_memory() {
+		let conf0 = parse(&["parity", "--tx-queue-size", "100"]);
+		let conf1 = parse(&["parity", "--tx-queue-size", "100", "--tx-pool-memory", "64"]);
+		let conf2 = parse(&["parity", "--tx-pool-memory", "0"]);
+
+		assert_eq!(conf0.pool_limits().unwrap().max_count, 100);
+		assert_eq!(conf1.pool_limits().unwrap(), pool::Options {
+			max_count: pool::max_count_for_memory(64 * 1024 * 1024),
+			max_per_sender: 16,
+			max_mem_usage: 64 * 1024 * 1024,
+		});
+		assert!(conf2.pool_limits().is_err());
+	}
+
+	#[test]
+	fn should_parse_fleet_config() {
+		let tempdir = TempDir::new("").unwrap();
+		let filename = tempdir.path().join("fleet");
+		File::create(&filename).unwrap().write_all(b"0x0000000000000000000000000000000000000000000000000000000000000001\n").unwrap();
+		let conf0 = parse(&["parity"]);
+		let conf1 = parse(&["parity", "--fleet-secret", filename.to_str().unwrap(), "--fleet-members", "10.0.0.2:30305, 10.0.0.3:30305"]);
+		let conf2 = parse(&["parity", "--fleet-secret", filename.to_str().unwrap(), "--fleet-members", "10.0.0.2"]);
+
+		assert_eq!(conf0.net_config().unwrap().fleet, None);
+		assert_eq!(conf1.net_config().unwrap().fleet, Some(::sync::FleetConfiguration {
+			secret: 1.into(),
+			listen_address: "0.0.0.0:30305".parse().unwrap(),
+			members: vec!["10.0.0.2:30305".parse().unwrap(), "10.0.0.3:30305".parse().unwrap()],
+		}));
+		assert!(conf2.net_config().is_err());
+	}
+
+	#[test]
+	fn should_parse_peer_slots() {
+		let conf0 = parse(&["parity"]);
+		let conf1 = parse(&["parity", "--peer-slots", "pip:10:50"]);
+		let conf2 = parse(&["parity", "--peer-slots", "pip:50:10"]);
+
+		assert_eq!(conf0.net_config().unwrap().peer_slots, vec![]);
+		assert_eq!(conf1.net_config().unwrap().peer_slots, vec![
+			::sync::ProtocolSlots { protocol: *b"pip", min_peers: 10, max_peers: 50 },
+		]);
+		assert!(conf2.net_config().is_err());
+	}
+
+	#[test]
+	fn should_parse_tx_relay() {
+		let conf0 = parse(&["parity"]);
+		let conf1 = parse(&["parity", "--tx-relay", "http://a:8545,http://b:8545", "--tx-relay-retries", "5", "--tx-relay-retry-delay", "200"]);
+
+		assert_eq!(conf0.tx_relay(), RelayOptions::default());
+		assert_eq!(conf1.tx_relay(), RelayOptions {
+			urls: vec!["http://a:8545".into(), "http://b:8545".into()],
+			retries: 5,
+			retry_delay: Duration::from_millis(200),
+		});
+	}
 }
@@ -19,7 +19,7 @@ extern crate migration_rocksdb;
 
 use std::{io, fs};
 use std::sync::Arc;
-use std::path::Path;
+use std::path::{Path, PathBuf};
 use blooms_db;
 use ethcore::{BlockChainDBHandler, BlockChainDB};
 use ethcore::db::NUM_COLUMNS;
@@ -32,8 +32,10 @@ use cache::CacheConfig;
 mod blooms;
 mod migration;
 mod helpers;
+mod tiered;
 
 pub use self::migration::migrate;
+pub use self::tiered::TierConfig;
 
 struct AppDB {
 	key_value: Arc<KeyValueDB>,
@@ -67,21 +69,31 @@ pub fn open_secretstore_db(data_path: &str) -> Result<Arc<KeyValueDB>, String> {
 }
 
 /// Create a restoration db handler using the config generated by `client_path` and `client_config`.
-pub fn restoration_db_handler(client_path: &Path, client_config: &ClientConfig) -> Box<BlockChainDBHandler> {
+///
+/// If `hot_tier` is given, the database at `client_path` is backed by a hot tier.
+pub fn restoration_db_handler(client_path: &Path, client_config: &ClientConfig, hot_tier: Option<TierConfig>) -> Box<BlockChainDBHandler> {
 	let client_db_config = helpers::client_db_config(client_path, client_config);
 
 	struct RestorationDBHandler {
 		config: DatabaseConfig,
+		client_path: PathBuf,
+		hot_tier: Option<TierConfig>,
 	}
 
 	impl BlockChainDBHandler for RestorationDBHandler {
 		fn open(&self, db_path: &Path) -> io::Result<Arc<BlockChainDB>> {
-			open_database(&db_path.to_string_lossy(), &self.config)
+			match self.hot_tier {
+				// restoration databases are temporary, only the main one is tiered.
+				Some(ref hot_tier) if db_path == self.client_path.as_path() => open_tiered_database(&db_path.to_string_lossy(), &self.config, hot_tier),
+				_ => open_database(&db_path.to_string_lossy(), &self.config),
+			}
 		}
 	}
 
 	Box::new(RestorationDBHandler {
 		config: client_db_config,
+		client_path: client_path.to_path_buf(),
+		hot_tier,
 	})
 }
 
@@ -114,3 +126,39 @@ pub fn open_database(client_path: &str, config: &DatabaseConfig) -> io::Result<A
 
 	Ok(Arc::new(db))
 }
+
+/// Open a main DB whose frequently read entries are cached in a hot tier.
+///
+/// The hot tier is wiped on open, as read statistics are not persisted. Refuses to open
+/// if the hot tier directory overlaps the main DB.
+pub fn open_tiered_database(client_path: &str, config: &DatabaseConfig, hot_tier: &TierConfig) -> io::Result<Arc<BlockChainDB>> {
+	let path = Path::new(client_path);
+	if hot_tier.overlaps(path) {
+		return Err(io::Error::new(io::ErrorKind::InvalidInput, format!(
+			"Hot tier directory {} overlaps the database directory {}", hot_tier.path.display(), path.display()
+		)));
+	}
+
+	let blooms_path = path.join("blooms");
+	let trace_blooms_path = path.join("trace_blooms");
+	fs::create_dir_all(&blooms_path)?;
+	fs::create_dir_all(&trace_blooms_path)?;
+
+	if hot_tier.path.exists() {
+		fs::remove_dir_all(&hot_tier.path)?;
+	}
+	let hot_config = DatabaseConfig {
+		compaction: helpers::compaction_profile(&DatabaseCompactionProfile::SSD, &hot_tier.path),
+		.. config.clone()
+	};
+
+	let cold = Arc::new(Database::open(&config, client_path)?);
+	let hot = Arc::new(Database::open(&hot_config, &hot_tier.path.to_string_lossy())?);
+	let db = AppDB {
+		key_value: Arc::new(tiered::TieredDatabase::new(cold, hot, hot_tier.capacity, hot_tier.promote_after)),
+		blooms: blooms_db::Database::open(blooms_path)?,
+		trace_blooms: blooms_db::Database::open(trace_blooms_path)?,
+	};
+
+	Ok(Arc::new(db))
+}
@@ -0,0 +1,376 @@
+// Copyright 2015-2018 Parity Technologies (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Two-tier key-value database.
+//!
+//! The cold tier is the main database and always holds the complete data set. The hot tier
+//! lives on faster storage and keeps copies of entries which are read repeatedly. Entries are
+//! promoted after `promote_after` reads from the cold tier and the least read ones are demoted
+//! once the hot tier grows beyond its capacity.
+//!
+//! Read statistics are kept in memory only, so the hot tier starts empty on every run.
+
+use std::collections::HashMap;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use kvdb::{DBOp, DBTransaction, DBValue, KeyValueDB};
+use parking_lot::Mutex;
+
+/// Fraction of the hot tier demoted at once when it overflows.
+const DEMOTION_BATCH_DIVISOR: usize = 10;
+/// Read counters of cold entries are aged once there are this many times more of them than hot tier capacity.
+const COLD_TRACKING_FACTOR: usize = 4;
+
+/// Hot tier configuration.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TierConfig {
+	/// Directory of the hot tier database.
+	pub path: PathBuf,
+	/// Maximal number of entries kept in the hot tier.
+	pub capacity: usize,
+	/// Number of cold tier reads after which an entry is promoted.
+	pub promote_after: u32,
+}
+
+impl TierConfig {
+	/// Hot tier of the chain with given genesis hash, kept in a dedicated directory
+	/// under the configured path. Only this directory is ever wiped.
+	pub fn for_chain(self, genesis_hash: &H256) -> Self {
+		TierConfig {
+			path: self.path.join("hot_tier").join(format!("{:x}", genesis_hash)),
+			..self
+		}
+	}
+
+	/// Whether the hot tier directory contains `path` or is contained in it.
+	pub fn overlaps(&self, path: &Path) -> bool {
+		let (hot, path) = (absolute(&self.path), absolute(path));
+		hot.starts_with(&path) || path.starts_with(&hot)
+	}
+}
+
+/// Resolves the existing part of `path`, which doesn't have to exist.
+fn absolute(path: &Path) -> PathBuf {
+	match path.canonicalize() {
+		Ok(path) => path,
+		Err(_) => match (path.parent(), path.file_name()) {
+			(Some(parent), Some(name)) => absolute(parent).join(name),
+			_ => path.to_path_buf(),
+		},
+	}
+}
+
+/// Hit statistics of a tiered database.
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub struct TierStats {
+	/// Reads served by the hot tier.
+	pub hot_hits: u64,
+	/// Reads served by the cold tier.
+	pub cold_hits: u64,
+	/// Entries copied to the hot tier.
+	pub promotions: u64,
+	/// Entries removed from the hot tier to make room.
+	pub demotions: u64,
+}
+
+type Key = (Option<u32>, Vec<u8>);
+
+#[derive(Default)]
+struct TierState {
+	/// Read counts of entries currently held by the hot tier.
+	hot: HashMap<Key, u64>,
+	/// Read counts of recently read cold entries.
+	cold: HashMap<Key, u32>,
+	/// Bumped on every write, so that values read before a write are never promoted.
+	generation: u64,
+	stats: TierStats,
+}
+
+/// Key-value database composed of a cold and a hot tier.
+pub struct TieredDatabase {
+	cold: Arc<KeyValueDB>,
+	hot: Arc<KeyValueDB>,
+	capacity: usize,
+	promote_after: u32,
+	state: Mutex<TierState>,
+}
+
+impl TieredDatabase {
+	/// Creates a new tiered database. The hot tier is expected to be empty.
+	pub fn new(cold: Arc<KeyValueDB>, hot: Arc<KeyValueDB>, capacity: usize, promote_after: u32) -> Self {
+		TieredDatabase {
+			cold,
+			hot,
+			capacity: ::std::cmp::max(capacity, 1),
+			promote_after: ::std::cmp::max(promote_after, 1),
+			state: Mutex::new(TierState::default()),
+		}
+	}
+
+	/// Returns hit statistics collected so far.
+	pub fn stats(&self) -> TierStats {
+		self.state.lock().stats
+	}
+
+	fn promote(&self, state: &mut TierState, key: Key, value: &DBValue) {
+		let mut transaction = DBTransaction::new();
+		transaction.put(key.0, &key.1, value);
+		self.hot.write_buffered(transaction);
+		state.hot.insert(key, 0);
+		state.stats.promotions += 1;
+
+		if state.hot.len() > self.capacity {
+			self.demote(state);
+		}
+	}
+
+	fn demote(&self, state: &mut TierState) {
+		let count = ::std::cmp::max(self.capacity / DEMOTION_BATCH_DIVISOR, 1);
+		let mut entries: Vec<_> = state.hot.iter().map(|(key, reads)| (*reads, key.clone())).collect();
+		entries.sort_by_key(|&(reads, _)| reads);
+
+		let mut transaction = DBTransaction::new();
+		for (_, key) in entries.into_iter().take(count) {
+			transaction.delete(key.0, &key.1);
+			state.hot.remove(&key);
+			state.stats.demotions += 1;
+		}
+		self.hot.write_buffered(transaction);
+
+		// halve the counters so that entries which were popular long ago can be replaced
+		for reads in state.hot.values_mut() {
+			*reads /= 2;
+		}
+		trace!(target: "db", "Demoted {} entries from the hot tier", count);
+	}
+
+	fn record_cold_read(&self, state: &mut TierState, key: Key) -> bool {
+		let reads = {
+			let reads = state.cold.entry(key.clone()).or_insert(0);
+			*reads += 1;
+			*reads
+		};
+
+		if reads >= self.promote_after {
+			state.cold.remove(&key);
+			return true;
+		}
+
+		if state.cold.len() > self.capacity.saturating_mul(COLD_TRACKING_FACTOR) {
+			state.cold.retain(|_, reads| { *reads /= 2; *reads > 0 });
+		}
+		false
+	}
+}
+
+impl KeyValueDB for TieredDatabase {
+	fn get(&self, col: Option<u32>, key: &[u8]) -> io::Result<Option<DBValue>> {
+		let tier_key = (col, key.to_vec());
+		let (is_hot, generation) = {
+			let mut state = self.state.lock();
+			let is_hot = match state.hot.get_mut(&tier_key) {
+				Some(reads) => {
+					*reads += 1;
+					true
+				},
+				None => false,
+			};
+			(is_hot, state.generation)
+		};
+
+		if is_hot {
+			if let Some(value) = self.hot.get(col, key)? {
+				self.state.lock().stats.hot_hits += 1;
+				return Ok(Some(value));
+			}
+		}
+
+		let value = self.cold.get(col, key)?;
+		if let Some(ref value) = value {
+			let mut state = self.state.lock();
+			state.stats.cold_hits += 1;
+			if !is_hot && self.record_cold_read(&mut state, tier_key.clone()) && state.generation == generation {
+				self.promote(&mut state, tier_key, value);
+			}
+		}
+		Ok(value)
+	}
+
+	fn get_by_prefix(&self, col: Option<u32>, prefix: &[u8]) -> Option<Box<[u8]>> {
+		self.cold.get_by_prefix(col, prefix)
+	}
+
+	fn write_buffered(&self, transaction: DBTransaction) {
+		{
+			let mut state = self.state.lock();
+			state.generation += 1;
+
+			let mut hot_transaction = DBTransaction::new();
+			for op in &transaction.ops {
+				match *op {
+					DBOp::Insert { col, ref key, ref value } => {
+						let key = (col, key.to_vec());
+						if state.hot.contains_key(&key) {
+							hot_transaction.put(col, &key.1, value);
+						}
+					},
+					DBOp::Delete { col, ref key } => {
+						let key = (col, key.to_vec());
+						state.cold.remove(&key);
+						if state.hot.remove(&key).is_some() {
+							hot_transaction.delete(col, &key.1);
+						}
+					},
+				}
+			}
+
+			if !hot_transaction.ops.is_empty() {
+				self.hot.write_buffered(hot_transaction);
+			}
+		}
+
+		self.cold.write_buffered(transaction);
+	}
+
+	fn flush(&self) -> io::Result<()> {
+		self.cold.flush()?;
+		self.hot.flush()
+	}
+
+	fn iter<'a>(&'a self, col: Option<u32>) -> Box<Iterator<Item=(Box<[u8]>, Box<[u8]>)> + 'a> {
+		self.cold.iter(col)
+	}
+
+	fn iter_from_prefix<'a>(&'a self, col: Option<u32>, prefix: &'a [u8]) -> Box<Iterator<Item=(Box<[u8]>, Box<[u8]>)> + 'a> {
+		self.cold.iter_from_prefix(col, prefix)
+	}
+
+	fn restore(&self, new_db: &str) -> io::Result<()> {
+		let mut state = self.state.lock();
+		state.generation += 1;
+
+		let mut transaction = DBTransaction::new();
+		for (key, _) in state.hot.drain() {
+			transaction.delete(key.0, &key.1);
+		}
+		state.cold.clear();
+		self.hot.write(transaction)?;
+
+		self.cold.restore(new_db)
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use std::sync::Arc;
+	use kvdb::{DBTransaction, KeyValueDB};
+	use tempdir::TempDir;
+	use super::super::kvdb_rocksdb::{Database, DatabaseConfig};
+	use super::{TieredDatabase, TierConfig, TierStats};
+
+	fn open(dir: &TempDir, name: &str) -> Arc<Database> {
+		let path = dir.path().join(name);
+		Arc::new(Database::open(&DatabaseConfig::with_columns(Some(1)), &path.to_string_lossy()).unwrap())
+	}
+
+	fn put(db: &KeyValueDB, key: &[u8], value: &[u8]) {
+		let mut transaction = DBTransaction::new();
+		transaction.put(Some(0), key, value);
+		db.write(transaction).unwrap();
+	}
+
+	#[test]
+	fn should_promote_after_repeated_reads() {
+		let dir = TempDir::new("tiered").unwrap();
+		let hot = open(&dir, "hot");
+		let db = TieredDatabase::new(open(&dir, "cold"), hot.clone(), 10, 2);
+		put(&db, b"key", b"value");
+
+		assert_eq!(&*db.get(Some(0), b"key").unwrap().unwrap(), b"value");
+		assert!(hot.get(Some(0), b"key").unwrap().is_none());
+		assert_eq!(&*db.get(Some(0), b"key").unwrap().unwrap(), b"value");
+		assert_eq!(&*hot.get(Some(0), b"key").unwrap().unwrap(), b"value");
+		assert_eq!(&*db.get(Some(0), b"key").unwrap().unwrap(), b"value");
+
+		assert_eq!(db.stats(), TierStats {
+			hot_hits: 1,
+			cold_hits: 2,
+			promotions: 1,
+			demotions: 0,
+		});
+	}
+
+	#[test]
+	fn should_keep_hot_tier_in_sync_with_writes() {
+		let dir = TempDir::new("tiered").unwrap();
+		let hot = open(&dir, "hot");
+		let db = TieredDatabase::new(open(&dir, "cold"), hot.clone(), 10, 1);
+		put(&db, b"key", b"value");
+		db.get(Some(0), b"key").unwrap();
+
+		put(&db, b"key", b"other");
+		assert_eq!(&*hot.get(Some(0), b"key").unwrap().unwrap(), b"other");
+		assert_eq!(&*db.get(Some(0), b"key").unwrap().unwrap(), b"other");
+
+		let mut transaction = DBTransaction::new();
+		transaction.delete(Some(0), b"key");
+		db.write(transaction).unwrap();
+		assert!(hot.get(Some(0), b"key").unwrap().is_none());
+		assert!(db.get(Some(0), b"key").unwrap().is_none());
+	}
+
+	#[test]
+	fn should_demote_least_read_entries() {
+		let dir = TempDir::new("tiered").unwrap();
+		let hot = open(&dir, "hot");
+		let db = TieredDatabase::new(open(&dir, "cold"), hot.clone(), 2, 1);
+		put(&db, b"a", b"1");
+		put(&db, b"b", b"2");
+		put(&db, b"c", b"3");
+
+		// promote `a` and read it once more from the hot tier
+		db.get(Some(0), b"a").unwrap();
+		db.get(Some(0), b"a").unwrap();
+		db.get(Some(0), b"b").unwrap();
+		db.get(Some(0), b"c").unwrap();
+
+		assert_eq!(db.stats().demotions, 1);
+		assert!(hot.get(Some(0), b"a").unwrap().is_some());
+		assert!(hot.get(Some(0), b"b").unwrap().is_none() || hot.get(Some(0), b"c").unwrap().is_none());
+	}
+
+	#[test]
+	fn should_keep_hot_tier_in_dedicated_directory() {
+		let dir = TempDir::new("tiered").unwrap();
+		let client_path = dir.path().join("chains").join("db");
+		let config = TierConfig {
+			path: dir.path().to_path_buf(),
+			capacity: 10,
+			promote_after: 1,
+		};
+
+		// the configured path contains the client database, the dedicated directory doesn't.
+		assert!(config.overlaps(&client_path));
+		let config = config.for_chain(&1.into());
+		assert!(config.path.starts_with(dir.path().join("hot_tier")));
+		assert!(!config.overlaps(&client_path));
+
+		let nested = TierConfig { path: client_path.join("hot"), ..config };
+		assert!(nested.overlaps(&client_path));
+	}
+}
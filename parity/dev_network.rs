@@ -0,0 +1,332 @@
+// Copyright 2015-2018 Parity Technologies (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Generation of local Aura development networks.
+//!
+//! The output directory contains a chain specification listing all validators and a file with the
+//! enodes of all nodes. Every node gets its own directory with a config file, a keystore holding
+//! its signing account and the password of that account. Nodes only connect to each other and
+//! use distinct ports, so the whole network can run on a single machine.
+
+use std::fs;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use ethcore::ethstore::{EthStore, SimpleSecretStore, SecretVaultRef, random_string};
+use ethcore::ethstore::accounts_dir::RootDiskDirectory;
+use ethcore_logger::RotatingLogger;
+use ethkey::{Generator, KeyPair, Random};
+use path::restrict_permissions_owner;
+
+use configuration::{Cmd, Configuration};
+use run::{self, RunningClient};
+use ExecutionAction;
+
+/// Name of the generated chain, also used as the keys directory.
+const CHAIN_NAME: &'static str = "dev-network";
+/// Network id of the generated chain.
+const NETWORK_ID: u64 = 0x2323;
+/// Highest number of validators whose ports don't overlap.
+const MAX_VALIDATORS: usize = 64;
+const BASE_P2P_PORT: u16 = 30300;
+const BASE_RPC_PORT: u16 = 8540;
+const BASE_WS_PORT: u16 = 8450;
+/// Balance of every validator account, 1M ether.
+const VALIDATOR_BALANCE: &'static str = "1000000000000000000000000";
+
+#[derive(Debug, PartialEq)]
+pub struct DevNetworkCmd {
+	pub validators: usize,
+	pub out: String,
+	pub step_duration: u64,
+	pub key_iterations: u32,
+	pub run: bool,
+}
+
+struct Node {
+	account: KeyPair,
+	node_key: KeyPair,
+	p2p_port: u16,
+	rpc_port: u16,
+	ws_port: u16,
+}
+
+impl Node {
+	fn new(index: usize) -> Self {
+		let index = index as u16;
+		Node {
+			account: Random.generate().expect("Random key generation never fails; qed"),
+			node_key: Random.generate().expect("Random key generation never fails; qed"),
+			p2p_port: BASE_P2P_PORT + index,
+			rpc_port: BASE_RPC_PORT + index,
+			ws_port: BASE_WS_PORT + index,
+		}
+	}
+
+	fn enode(&self) -> String {
+		format!("enode://{:x}@127.0.0.1:{}", self.node_key.public(), self.p2p_port)
+	}
+}
+
+pub fn execute(cmd: DevNetworkCmd, logger: Arc<RotatingLogger>) -> Result<ExecutionAction, String> {
+	let configs = generate(&cmd)?;
+	if !cmd.run {
+		return Ok(ExecutionAction::Instant(Some(format!(
+			"Generated a network of {} validators in {}. Start a node with `parity --config <FILE>` using one of:\n{}",
+			cmd.validators,
+			cmd.out,
+			configs.iter().map(|c| c.display().to_string()).collect::<Vec<_>>().join("\n"),
+		))));
+	}
+
+	let mut clients = Vec::with_capacity(configs.len());
+	for config in configs {
+		match launch(&config, logger.clone()) {
+			Ok(client) => clients.push(client),
+			Err(e) => {
+				for client in clients {
+					client.shutdown();
+				}
+				return Err(format!("Failed to start node {}: {}", config.display(), e));
+			},
+		}
+	}
+	Ok(ExecutionAction::Running(RunningClient::group(clients)))
+}
+
+/// Writes the network to the output directory and returns paths of the node config files.
+fn generate(cmd: &DevNetworkCmd) -> Result<Vec<PathBuf>, String> {
+	if cmd.validators == 0 || cmd.validators > MAX_VALIDATORS {
+		return Err(format!("Number of validators must be between 1 and {}", MAX_VALIDATORS));
+	}
+
+	fs::create_dir_all(&cmd.out).map_err(|e| format!("Could not create {}: {}", cmd.out, e))?;
+	// config files are read relative to the working directory of the node
+	let out = fs::canonicalize(&cmd.out).map_err(|e| format!("Could not resolve {}: {}", cmd.out, e))?;
+
+	let nodes = (0..cmd.validators).map(Node::new).collect::<Vec<_>>();
+
+	let spec_path = out.join("spec.json");
+	write_file(&spec_path, &chain_spec(&nodes, cmd.step_duration))?;
+
+	let reserved_peers_path = out.join("reserved-peers");
+	let enodes = nodes.iter().map(Node::enode).collect::<Vec<_>>();
+	write_file(&reserved_peers_path, &(enodes.join("\n") + "\n"))?;
+
+	nodes.iter().enumerate().map(|(index, node)| {
+		let base_path = out.join(format!("node{}", index));
+
+		let password = random_string(16);
+		let password_path = base_path.join("password");
+		fs::create_dir_all(&base_path).map_err(|e| format!("Could not create {}: {}", base_path.display(), e))?;
+		write_file(&password_path, &password)?;
+		restrict_permissions_owner(&password_path, true, false)
+			.map_err(|e| format!("Could not restrict permissions of {}: {}", password_path.display(), e))?;
+
+		let keys_dir = RootDiskDirectory::create(base_path.join("keys").join(CHAIN_NAME))
+			.map_err(|e| format!("Could not open keys directory: {}", e))?;
+		let store = EthStore::open_with_iterations(Box::new(keys_dir), cmd.key_iterations)
+			.map_err(|e| format!("Could not open keys store: {}", e))?;
+		store.insert_account(SecretVaultRef::Root, node.account.secret().clone(), &password.into())
+			.map_err(|e| format!("Could not store validator key: {}", e))?;
+
+		let config_path = base_path.join("config.toml");
+		write_file(&config_path, &node_config(node, &base_path, &spec_path, &reserved_peers_path, &password_path))?;
+		Ok(config_path)
+	}).collect()
+}
+
+fn launch(config: &Path, logger: Arc<RotatingLogger>) -> Result<RunningClient, String> {
+	let config = config.to_str().ok_or_else(|| "Path is not valid UTF-8".to_owned())?;
+	let conf = Configuration::parse_cli(&["parity", "--config", config]).map_err(|e| format!("{:?}", e))?;
+	match conf.into_command()?.cmd {
+		Cmd::Run(run_cmd) => run::execute(run_cmd, logger, |_| {}, || {}),
+		_ => Err("Config does not describe a node".into()),
+	}
+}
+
+fn write_file(path: &Path, content: &str) -> Result<(), String> {
+	fs::File::create(path)
+		.and_then(|mut file| file.write_all(content.as_bytes()))
+		.map_err(|e| format!("Could not write {}: {}", path.display(), e))
+}
+
+fn chain_spec(nodes: &[Node], step_duration: u64) -> String {
+	let validators = nodes.iter()
+		.map(|node| format!("\"0x{:x}\"", node.account.address()))
+		.collect::<Vec<_>>()
+		.join(", ");
+	let balances = nodes.iter()
+		.map(|node| format!(",\n\t\t\"0x{:x}\": {{ \"balance\": \"{}\" }}", node.account.address(), VALIDATOR_BALANCE))
+		.collect::<String>();
+
+	format!(r#"{{
+	"name": "DevNetwork",
+	"dataDir": "{data_dir}",
+	"engine": {{
+		"authorityRound": {{
+			"params": {{
+				"stepDuration": {step_duration},
+				"validators": {{ "list": [{validators}] }},
+				"maximumUncleCountTransition": 0,
+				"maximumUncleCount": 0
+			}}
+		}}
+	}},
+	"params": {{
+		"gasLimitBoundDivisor": "0x400",
+		"maximumExtraDataSize": "0x20",
+		"minGasLimit": "0x1388",
+		"networkID": "0x{network_id:x}",
+		"eip155Transition": "0x0",
+		"validateChainIdTransition": "0x0",
+		"eip140Transition": "0x0",
+		"eip211Transition": "0x0",
+		"eip214Transition": "0x0",
+		"eip658Transition": "0x0",
+		"eip145Transition": "0x0",
+		"eip1014Transition": "0x0",
+		"eip1052Transition": "0x0"
+	}},
+	"genesis": {{
+		"seal": {{
+			"authorityRound": {{
+				"step": "0x0",
+				"signature": "0x{signature}"
+			}}
+		}},
+		"difficulty": "0x20000",
+		"gasLimit": "0x7a1200"
+	}},
+	"accounts": {{
+		"0x0000000000000000000000000000000000000001": {{ "balance": "1", "builtin": {{ "name": "ecrecover", "pricing": {{ "linear": {{ "base": 3000, "word": 0 }} }} }} }},
+		"0x0000000000000000000000000000000000000002": {{ "balance": "1", "builtin": {{ "name": "sha256", "pricing": {{ "linear": {{ "base": 60, "word": 12 }} }} }} }},
+		"0x0000000000000000000000000000000000000003": {{ "balance": "1", "builtin": {{ "name": "ripemd160", "pricing": {{ "linear": {{ "base": 600, "word": 120 }} }} }} }},
+		"0x0000000000000000000000000000000000000004": {{ "balance": "1", "builtin": {{ "name": "identity", "pricing": {{ "linear": {{ "base": 15, "word": 3 }} }} }} }},
+		"0x0000000000000000000000000000000000000005": {{ "balance": "1", "builtin": {{ "name": "modexp", "activate_at": 0, "pricing": {{ "modexp": {{ "divisor": 20 }} }} }} }},
+		"0x0000000000000000000000000000000000000006": {{ "balance": "1", "builtin": {{ "name": "alt_bn128_add", "activate_at": 0, "pricing": {{ "linear": {{ "base": 500, "word": 0 }} }} }} }},
+		"0x0000000000000000000000000000000000000007": {{ "balance": "1", "builtin": {{ "name": "alt_bn128_mul", "activate_at": 0, "pricing": {{ "linear": {{ "base": 40000, "word": 0 }} }} }} }},
+		"0x0000000000000000000000000000000000000008": {{ "balance": "1", "builtin": {{ "name": "alt_bn128_pairing", "activate_at": 0, "pricing": {{ "alt_bn128_pairing": {{ "base": 100000, "pair": 80000 }} }} }} }}{balances}
+	}}
+}}
+"#,
+		data_dir = CHAIN_NAME,
+		step_duration = step_duration,
+		validators = validators,
+		network_id = NETWORK_ID,
+		signature = "0".repeat(130),
+		balances = balances,
+	)
+}
+
+// paths are written as literal strings, which don't process escapes, to support Windows paths
+fn node_config(node: &Node, base_path: &Path, spec: &Path, reserved_peers: &Path, password: &Path) -> String {
+	format!(r#"[parity]
+chain = '{spec}'
+base_path = '{base_path}'
+mode = "active"
+auto_update = "none"
+no_download = true
+
+[account]
+unlock = ["0x{account:x}"]
+password = ['{password}']
+
+[network]
+port = {p2p_port}
+node_key = "{node_key:x}"
+reserved_peers = '{reserved_peers}'
+discovery = false
+nat = "none"
+
+[rpc]
+port = {rpc_port}
+apis = ["web3", "eth", "net", "parity", "parity_set", "personal", "traces", "rpc"]
+
+[websockets]
+port = {ws_port}
+
+[ipc]
+disable = true
+
+[secretstore]
+disable = true
+
+[mining]
+engine_signer = "0x{account:x}"
+force_sealing = true
+reseal_on_txs = "none"
+"#,
+		spec = spec.display(),
+		base_path = base_path.display(),
+		account = node.account.address(),
+		password = password.display(),
+		p2p_port = node.p2p_port,
+		node_key = node.node_key.secret(),
+		reserved_peers = reserved_peers.display(),
+		rpc_port = node.rpc_port,
+		ws_port = node.ws_port,
+	)
+}
+
+#[cfg(test)]
+mod tests {
+	use std::fs;
+	use ethjson;
+	use tempdir::TempDir;
+	use configuration::{Cmd, Configuration};
+	use super::{generate, DevNetworkCmd};
+
+	#[test]
+	fn should_generate_network_of_connected_validators() {
+		let dir = TempDir::new("dev-network").unwrap();
+		let out = dir.path().join("net");
+		let cmd = DevNetworkCmd {
+			validators: 3,
+			out: out.to_str().unwrap().into(),
+			step_duration: 5,
+			key_iterations: 1,
+			run: false,
+		};
+
+		let configs = generate(&cmd).unwrap();
+		assert_eq!(configs.len(), 3);
+
+		let spec = ethjson::spec::Spec::load(fs::File::open(out.join("spec.json")).unwrap()).unwrap();
+		assert_eq!(spec.accounts.into_iter().count(), 8 + 3);
+
+		let reserved_peers = fs::read_to_string(out.join("reserved-peers")).unwrap();
+		assert_eq!(reserved_peers.lines().count(), 3);
+
+		let mut ports = Vec::new();
+		for config in &configs {
+			let conf = Configuration::parse_cli(&["parity", "--config", config.to_str().unwrap()]).unwrap();
+			match conf.into_command().unwrap().cmd {
+				Cmd::Run(run_cmd) => {
+					assert_eq!(run_cmd.net_conf.reserved_nodes.len(), 3);
+					assert_eq!(run_cmd.acc_conf.unlocked_accounts.len(), 1);
+					assert_eq!(run_cmd.acc_conf.unlocked_accounts[0], run_cmd.miner_extras.engine_signer);
+					ports.push(run_cmd.net_conf.listen_address.unwrap());
+				},
+				_ => panic!("Expected a run command"),
+			}
+		}
+		ports.sort();
+		ports.dedup();
+		assert_eq!(ports.len(), 3);
+	}
+}
@@ -15,7 +15,7 @@
 // along with Parity.  If not, see <http://www.gnu.org/licenses/>.
 
 use std::io;
-use std::io::{Write, BufReader, BufRead};
+use std::io::{Read, Write, BufReader, BufRead};
 use std::time::Duration;
 use std::fs::File;
 use ethereum_types::{U256, clean_0x, Address};
@@ -129,6 +129,48 @@ pub fn to_addresses(s: &Option<String>) -> Result<Vec<Address>, String> {
 	}
 }
 
+/// Parses list of CPU cores, e.g. `0-3,6`.
+pub fn to_cpu_list(s: &str) -> Result<Vec<usize>, String> {
+	let invalid = || format!("Invalid CPU list: {:?}. Expected comma separated list of cores or ranges, e.g. 0-3,6", s);
+	let mut cpus = Vec::new();
+	for part in s.split(',').map(str::trim).filter(|part| !part.is_empty()) {
+		let mut bounds = part.splitn(2, '-');
+		let from = bounds.next().and_then(|from| from.trim().parse::<usize>().ok()).ok_or_else(invalid)?;
+		let to = match bounds.next() {
+			Some(to) => to.trim().parse::<usize>().map_err(|_| invalid())?,
+			None => from,
+		};
+		if to < from {
+			return Err(invalid());
+		}
+		cpus.extend(from..to + 1);
+	}
+	Ok(cpus)
+}
+
+/// Parses peer slots of subprotocols, e.g. `pip:10:50,par:5:10`.
+pub fn to_peer_slots(s: &str) -> Result<Vec<sync::ProtocolSlots>, String> {
+	let invalid = || format!("Invalid peer slots: {:?}. Expected comma separated list of PROTOCOL:MIN:MAX, e.g. pip:10:50", s);
+	let mut slots = Vec::new();
+	for part in s.split(',').map(str::trim).filter(|part| !part.is_empty()) {
+		let fields = part.split(':').map(str::trim).collect::<Vec<_>>();
+		if fields.len() != 3 || fields[0].len() != 3 {
+			return Err(invalid());
+		}
+
+		let mut protocol = [0u8; 3];
+		protocol.copy_from_slice(fields[0].as_bytes());
+		let min_peers = fields[1].parse::<u32>().map_err(|_| invalid())?;
+		let max_peers = fields[2].parse::<u32>().map_err(|_| invalid())?;
+		if max_peers < min_peers {
+			return Err(invalid());
+		}
+
+		slots.push(sync::ProtocolSlots { protocol, min_peers, max_peers });
+	}
+	Ok(slots)
+}
+
 /// Tries to parse string as a price.
 pub fn to_price(s: &str) -> Result<f32, String> {
 	s.parse::<f32>().map_err(|_| format!("Invalid transaciton price 's' given. Must be a decimal number."))
@@ -178,6 +220,25 @@ pub fn to_bootnodes(bootnodes: &Option<String>) -> Result<Vec<String>, String> {
 	}
 }
 
+/// Reads and validates reserved node addresses from a file, one per line.
+/// Empty lines and lines starting with `#` are ignored.
+pub fn reserved_nodes_from_file(path: &str) -> Result<Vec<String>, String> {
+	let mut buffer = String::new();
+	let mut node_file = File::open(path).map_err(|e| format!("Error opening reserved nodes file: {}", e))?;
+	node_file.read_to_string(&mut buffer).map_err(|_| "Error reading reserved node file")?;
+	let lines = buffer.lines().map(|s| s.trim().to_owned()).filter(|s| !s.is_empty() && !s.starts_with("#")).collect::<Vec<_>>();
+
+	for line in &lines {
+		match validate_node_url(line).map(Into::into) {
+			None => continue,
+			Some(sync::ErrorKind::AddressResolve(_)) => return Err(format!("Failed to resolve hostname of a boot node: {}", line)),
+			Some(_) => return Err(format!("Invalid node address format given for a boot node: {}", line)),
+		}
+	}
+
+	Ok(lines)
+}
+
 #[cfg(test)]
 pub fn default_network_config() -> ::sync::NetworkConfiguration {
 	use sync::{NetworkConfiguration};
@@ -189,17 +250,24 @@ pub fn default_network_config() -> ::sync::NetworkConfiguration {
 		public_address: None,
 		udp_port: None,
 		nat_enabled: true,
+		nat_pmp_enabled: true,
 		discovery_enabled: true,
+		discovery_v5: false,
 		boot_nodes: Vec::new(),
 		use_secret: None,
 		max_peers: 50,
 		min_peers: 25,
 		snapshot_peers: 0,
+		peer_slots: Vec::new(),
 		max_pending_peers: 64,
 		ip_filter: IpFilter::default(),
+		denied_ips_path: None,
 		reserved_nodes: Vec::new(),
 		allow_non_reserved: true,
 		client_version: ::parity_version::version(),
+		io_threads: 4,
+		key_log_path: None,
+		fleet: None,
 	}
 }
 
@@ -330,7 +398,8 @@ mod tests {
 	use ethcore::client::{Mode, BlockId};
 	use ethcore::miner::PendingSet;
 	use ethkey::Password;
-	use super::{to_duration, to_mode, to_block_id, to_u256, to_pending_set, to_address, to_addresses, to_price, geth_ipc_path, to_bootnodes, password_from_file};
+	use super::{to_duration, to_mode, to_block_id, to_u256, to_pending_set, to_address, to_addresses, to_price, geth_ipc_path, to_bootnodes, password_from_file, to_cpu_list, to_peer_slots};
+	use sync::ProtocolSlots;
 
 	#[test]
 	fn test_to_duration() {
@@ -415,6 +484,29 @@ mod tests {
 		);
 	}
 
+	#[test]
+	fn test_to_cpu_list() {
+		assert_eq!(to_cpu_list("").unwrap(), Vec::<usize>::new());
+		assert_eq!(to_cpu_list("2").unwrap(), vec![2]);
+		assert_eq!(to_cpu_list("0-3,6").unwrap(), vec![0, 1, 2, 3, 6]);
+		assert_eq!(to_cpu_list(" 4 - 5 , 1 ").unwrap(), vec![4, 5, 1]);
+		assert!(to_cpu_list("3-1").is_err());
+		assert!(to_cpu_list("a").is_err());
+		assert!(to_cpu_list("1-").is_err());
+	}
+
+	#[test]
+	fn test_to_peer_slots() {
+		assert_eq!(to_peer_slots("").unwrap(), vec![]);
+		assert_eq!(to_peer_slots("pip:10:50, par:0:5").unwrap(), vec![
+			ProtocolSlots { protocol: *b"pip", min_peers: 10, max_peers: 50 },
+			ProtocolSlots { protocol: *b"par", min_peers: 0, max_peers: 5 },
+		]);
+		assert!(to_peer_slots("pip:10").is_err());
+		assert!(to_peer_slots("pip:10:5").is_err());
+		assert!(to_peer_slots("light:1:2").is_err());
+	}
+
 	#[test]
 	fn test_password() {
 		let tempdir = TempDir::new("").unwrap();
@@ -31,11 +31,13 @@ extern crate jsonrpc_core;
 extern crate num_cpus;
 extern crate number_prefix;
 extern crate parking_lot;
+extern crate rayon;
 extern crate regex;
 extern crate rlp;
 extern crate rpassword;
 extern crate rustc_hex;
 extern crate semver;
+extern crate stats;
 extern crate serde;
 extern crate serde_json;
 #[macro_use]
@@ -55,6 +57,7 @@ extern crate ethcore_service;
 extern crate ethcore_sync as sync;
 extern crate ethcore_transaction as transaction;
 extern crate ethereum_types;
+extern crate ethjson;
 extern crate ethkey;
 extern crate kvdb;
 extern crate parity_hash_fetch as hash_fetch;
@@ -93,9 +96,11 @@ mod configuration;
 mod export_hardcoded_sync;
 mod ipfs;
 mod deprecated;
+mod dev_network;
 mod helpers;
 mod informant;
 mod light_helpers;
+mod metrics;
 mod modules;
 mod params;
 mod presale;
@@ -105,6 +110,8 @@ mod run;
 mod secretstore;
 mod signer;
 mod snapshot;
+mod test_gen;
+mod test_run;
 mod upgrade;
 mod user_defaults;
 mod whisper;
@@ -121,7 +128,7 @@ use ethcore_logger::setup_log;
 use std::alloc::System;
 
 pub use self::configuration::Configuration;
-pub use self::run::RunningClient;
+pub use self::run::{RunningClient, ReservedPeersReloader};
 
 #[cfg(feature = "memory_profiling")]
 #[global_allocator]
@@ -206,6 +213,9 @@ fn execute<Cr, Rr>(command: Execute, on_client_rq: Cr, on_updater_rq: Rr) -> Res
 		Cmd::SignerReject { id, port, authfile } => rpc_cli::signer_reject(id, port, authfile).map(|s| ExecutionAction::Instant(Some(s))),
 		Cmd::Snapshot(snapshot_cmd) => snapshot::execute(snapshot_cmd).map(|s| ExecutionAction::Instant(Some(s))),
 		Cmd::ExportHardcodedSync(export_hs_cmd) => export_hardcoded_sync::execute(export_hs_cmd).map(|s| ExecutionAction::Instant(Some(s))),
+		Cmd::TestGen(test_gen_cmd) => test_gen::execute(test_gen_cmd).map(|s| ExecutionAction::Instant(Some(s))),
+		Cmd::TestRun(test_run_cmd) => test_run::execute(test_run_cmd).map(|s| ExecutionAction::Instant(Some(s))),
+		Cmd::DevNetwork(dev_network_cmd) => dev_network::execute(dev_network_cmd, logger),
 	}
 }
 
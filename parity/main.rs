@@ -28,6 +28,7 @@ extern crate parity_ethereum;
 extern crate parking_lot;
 
 #[cfg(windows)] extern crate winapi;
+#[cfg(not(windows))] extern crate signal_hook;
 
 use std::ffi::OsString;
 use std::fs::{remove_file, metadata, File, create_dir_all};
@@ -35,12 +36,12 @@ use std::io::{self as stdio, Read, Write};
 use std::path::PathBuf;
 use std::sync::Arc;
 use std::sync::atomic::{AtomicBool, Ordering};
-use std::{process, env};
+use std::{process, env, thread};
 
 use ctrlc::CtrlC;
 use dir::default_hypervisor_path;
 use fdlimit::raise_fd_limit;
-use parity_ethereum::{start, ExecutionAction};
+use parity_ethereum::{start, ExecutionAction, ReservedPeersReloader};
 use parking_lot::{Condvar, Mutex};
 
 const PLEASE_RESTART_EXIT_CODE: i32 = 69;
@@ -132,6 +133,31 @@ fn global_init() {
 #[cfg(not(windows))]
 fn global_cleanup() {}
 
+// Reloads reserved peers whenever SIGHUP is received.
+#[cfg(not(windows))]
+fn reload_reserved_peers_on_sighup(reloader: ReservedPeersReloader) {
+	if reloader.is_empty() {
+		return;
+	}
+
+	let signals = match signal_hook::iterator::Signals::new(&[signal_hook::SIGHUP]) {
+		Ok(signals) => signals,
+		Err(e) => {
+			warn!("Unable to listen for SIGHUP, reserved peers won't be reloaded: {}", e);
+			return;
+		},
+	};
+
+	let _ = thread::Builder::new().name("sighup".into()).spawn(move || {
+		for _ in signals.forever() {
+			reloader.reload();
+		}
+	});
+}
+
+#[cfg(windows)]
+fn reload_reserved_peers_on_sighup(_reloader: ReservedPeersReloader) {}
+
 // Starts parity binary installed via `parity-updater` and returns the code it exits with.
 fn run_parity() -> Result<(), Error> {
 	global_init();
@@ -283,6 +309,8 @@ fn main_direct(force_can_restart: bool) -> i32 {
 					}
 				});
 
+				reload_reserved_peers_on_sighup(client.reserved_peers_reloader());
+
 				// Wait for signal
 				let mut lock = exit.0.lock();
 				if !lock.should_exit {
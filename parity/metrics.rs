@@ -0,0 +1,79 @@
+// Copyright 2015-2018 Parity Technologies (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+use std::io;
+use std::sync::Arc;
+
+use jsonrpc_core::MetaIoHandler;
+use parity_reactor::TokioRemote;
+use parity_rpc::{self as rpc, DomainsValidation, HttpServer, Metadata};
+use stats::PrometheusMetrics;
+
+/// Source of exported metrics.
+pub type Source = Arc<PrometheusMetrics + Send + Sync>;
+
+#[derive(Debug, PartialEq, Clone)]
+pub struct MetricsConfiguration {
+	pub enabled: bool,
+	pub port: u16,
+	pub interface: String,
+	pub prefix: String,
+}
+
+impl Default for MetricsConfiguration {
+	fn default() -> Self {
+		MetricsConfiguration {
+			enabled: false,
+			port: 3000,
+			interface: "127.0.0.1".into(),
+			prefix: "".into(),
+		}
+	}
+}
+
+/// Starts the HTTP server exposing metrics of given sources.
+pub fn start_server(
+	conf: MetricsConfiguration,
+	sources: Vec<Source>,
+	remote: TokioRemote,
+) -> Result<Option<HttpServer>, String> {
+	if !conf.enabled {
+		return Ok(None);
+	}
+
+	let url = format!("{}:{}", conf.interface, conf.port);
+	let addr = url.parse().map_err(|_| format!("Invalid metrics listen host/port given: {}", url))?;
+
+	let start_result = rpc::start_http_with_middleware(
+		&addr,
+		DomainsValidation::Disabled,
+		DomainsValidation::Disabled,
+		MetaIoHandler::<Metadata>::default(),
+		remote,
+		rpc::RpcExtractor,
+		rpc::Metrics::new(sources, conf.prefix),
+		1,
+		1,
+	);
+
+	match start_result {
+		Ok(server) => Ok(Some(server)),
+		Err(ref err) if err.kind() == io::ErrorKind::AddrInUse => Err(
+			format!("Metrics address {} is already in use, make sure that another instance of an Ethereum client is not running or change the address using the --metrics-port and --metrics-interface options.", url)
+		),
+		Err(e) => Err(format!("Metrics error: {:?}", e)),
+	}
+}
@@ -26,6 +26,7 @@ use hash_fetch::fetch::Client as FetchClient;
 use journaldb::Algorithm;
 use miner::gas_pricer::GasPricer;
 use miner::gas_price_calibrator::{GasPriceCalibratorOptions, GasPriceCalibrator};
+use miner::tx_relay::RelayOptions;
 use parity_version::version_data;
 use user_defaults::UserDefaults;
 
@@ -278,6 +279,7 @@ pub struct MinerExtras {
 	pub extra_data: Vec<u8>,
 	pub gas_range_target: (U256, U256),
 	pub work_notify: Vec<String>,
+	pub tx_relay: RelayOptions,
 }
 
 impl Default for MinerExtras {
@@ -288,6 +290,7 @@ impl Default for MinerExtras {
 			extra_data: version_data(),
 			gas_range_target: (8_000_000.into(), 10_000_000.into()),
 			work_notify: Default::default(),
+			tx_relay: Default::default(),
 		}
 	}
 }
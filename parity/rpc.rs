@@ -25,7 +25,7 @@ use helpers::parity_ipc_path;
 use jsonrpc_core::MetaIoHandler;
 use parity_reactor::TokioRemote;
 use parity_rpc::informant::{RpcStats, Middleware};
-use parity_rpc::{self as rpc, Metadata, DomainsValidation};
+use parity_rpc::{self as rpc, Metadata, DomainsValidation, HealthThresholds, MethodAcl, MethodFilter, RateLimiter, RateLimits, ResponseLimit, SubscriptionQueueConfig};
 use rpc_apis::{self, ApiSet};
 
 pub use parity_rpc::{IpcServer, HttpServer, RequestMiddleware};
@@ -40,11 +40,18 @@ pub struct HttpConfiguration {
 	pub interface: String,
 	pub port: u16,
 	pub apis: ApiSet,
+	pub method_acl: MethodAcl,
 	pub cors: Option<Vec<String>>,
 	pub hosts: Option<Vec<String>>,
 	pub server_threads: usize,
 	pub processing_threads: usize,
 	pub max_payload: usize,
+	pub max_response_size: Option<usize>,
+	pub batch_parallelism: usize,
+	pub graphql: bool,
+	pub rate_limits: RateLimits,
+	pub jwt_secret: Option<PathBuf>,
+	pub health: HealthThresholds,
 }
 
 impl Default for HttpConfiguration {
@@ -54,11 +61,18 @@ impl Default for HttpConfiguration {
 			interface: "127.0.0.1".into(),
 			port: 8545,
 			apis: ApiSet::UnsafeContext,
+			method_acl: MethodAcl::default(),
 			cors: Some(vec![]),
 			hosts: Some(vec![]),
 			server_threads: 1,
 			processing_threads: 4,
 			max_payload: 5,
+			max_response_size: None,
+			batch_parallelism: 4,
+			graphql: false,
+			rate_limits: Default::default(),
+			jwt_secret: None,
+			health: Default::default(),
 		}
 	}
 }
@@ -68,6 +82,7 @@ pub struct IpcConfiguration {
 	pub enabled: bool,
 	pub socket_addr: String,
 	pub apis: ApiSet,
+	pub method_acl: MethodAcl,
 }
 
 impl Default for IpcConfiguration {
@@ -81,6 +96,7 @@ impl Default for IpcConfiguration {
 				parity_ipc_path(&data_dir, "$BASE/jsonrpc.ipc", 0)
 			},
 			apis: ApiSet::IpcContext,
+			method_acl: MethodAcl::default(),
 		}
 	}
 }
@@ -91,11 +107,17 @@ pub struct WsConfiguration {
 	pub interface: String,
 	pub port: u16,
 	pub apis: ApiSet,
+	pub method_acl: MethodAcl,
 	pub max_connections: usize,
 	pub origins: Option<Vec<String>>,
 	pub hosts: Option<Vec<String>>,
 	pub signer_path: PathBuf,
 	pub support_token_api: bool,
+	pub rate_limits: RateLimits,
+	pub trusted_proxy: bool,
+	pub jwt_secret: Option<PathBuf>,
+	pub max_response_size: Option<usize>,
+	pub subscription_queue: SubscriptionQueueConfig,
 }
 
 impl Default for WsConfiguration {
@@ -106,11 +128,17 @@ impl Default for WsConfiguration {
 			interface: "127.0.0.1".into(),
 			port: 8546,
 			apis: ApiSet::UnsafeContext,
+			method_acl: MethodAcl::default(),
 			max_connections: 100,
 			origins: Some(vec!["parity://*".into(),"chrome-extension://*".into(), "moz-extension://*".into()]),
 			hosts: Some(Vec::new()),
 			signer_path: replace_home(&data_dir, "$BASE/signer").into(),
 			support_token_api: true,
+			rate_limits: Default::default(),
+			trusted_proxy: false,
+			jwt_secret: None,
+			max_response_size: None,
+			subscription_queue: Default::default(),
 		}
 	}
 }
@@ -150,12 +178,20 @@ pub fn new_ws<D: rpc_apis::Dependencies>(
 	let domain = DAPPS_DOMAIN;
 	let url = format!("{}:{}", conf.interface, conf.port);
 	let addr = url.parse().map_err(|_| format!("Invalid WebSockets listen host/port given: {}", url))?;
+	let jwt_secret = jwt_secret(&conf.jwt_secret)?;
 
 	let full_handler = setup_apis(rpc_apis::ApiSet::SafeContext, deps);
 	let handler = {
 		let mut handler = MetaIoHandler::with_middleware((
-			rpc::WsDispatcher::new(full_handler),
-			Middleware::new(deps.stats.clone(), deps.apis.activity_notifier(), deps.pool.clone())
+			MethodFilter::new(conf.method_acl),
+			RateLimiter::new(conf.rate_limits),
+			(
+				ResponseLimit::new(conf.max_response_size),
+				(
+					rpc::WsDispatcher::new(full_handler),
+					Middleware::new(deps.stats.clone(), deps.apis.activity_notifier(), deps.pool.clone()),
+				),
+			),
 		));
 		let apis = conf.apis.list_apis();
 		deps.apis.extend_with_set(&mut handler, &apis);
@@ -182,8 +218,8 @@ pub fn new_ws<D: rpc_apis::Dependencies>(
 		allowed_origins,
 		allowed_hosts,
 		conf.max_connections,
-		rpc::WsExtractor::new(path.clone()),
-		rpc::WsExtractor::new(path.clone()),
+		rpc::WsExtractor::new(path.clone()).with_trusted_proxy(conf.trusted_proxy),
+		rpc::WsExtractor::new(path.clone()).with_jwt_secret(jwt_secret),
 		rpc::WsStats::new(deps.stats.clone()),
 	);
 
@@ -209,22 +245,57 @@ pub fn new_http<D: rpc_apis::Dependencies>(
 	let domain = DAPPS_DOMAIN;
 	let url = format!("{}:{}", conf.interface, conf.port);
 	let addr = url.parse().map_err(|_| format!("Invalid {} listen host/port given: {}", id, url))?;
-	let handler = setup_apis(conf.apis, deps);
+	let jwt_secret = jwt_secret(&conf.jwt_secret)?;
+	let handler = {
+		let mut handler = MetaIoHandler::with_middleware((
+			ResponseLimit::new(conf.max_response_size),
+			Middleware::new(deps.stats.clone(), deps.apis.activity_notifier(), deps.pool.clone()),
+		));
+		let apis = conf.apis.list_apis();
+		deps.apis.extend_with_set(&mut handler, &apis);
+
+		MetaIoHandler::with_middleware((
+			MethodFilter::new(conf.method_acl),
+			RateLimiter::new(conf.rate_limits),
+			rpc::BatchDispatcher::new(handler, deps.pool.clone(), conf.batch_parallelism),
+		))
+	};
 	let remote = deps.remote.clone();
 
 	let cors_domains = into_domains(conf.cors);
 	let allowed_hosts = into_domains(with_domain(conf.hosts, domain, &Some(url.clone().into())));
 
-	let start_result = rpc::start_http(
-		&addr,
-		cors_domains,
-		allowed_hosts,
-		handler,
-		remote,
-		rpc::RpcExtractor,
-		conf.server_threads,
-		conf.max_payload,
-	);
+	let graphql = if conf.graphql { deps.apis.graphql() } else { None };
+	if conf.graphql && graphql.is_none() {
+		warn!(target: "rpc", "GraphQL is not available in light client mode.");
+	}
+
+	// health endpoints are served without authentication, so that probes don't need a token.
+	let node_status = deps.apis.node_status();
+	let start_result = match jwt_secret {
+		Some(secret) => rpc::start_http_with_middleware(
+			&addr,
+			cors_domains,
+			allowed_hosts,
+			handler,
+			remote,
+			rpc::RpcExtractor,
+			rpc::Health::new(node_status, conf.health, Some(rpc::JwtAuth::new(secret, graphql))),
+			conf.server_threads,
+			conf.max_payload,
+		),
+		None => rpc::start_http_with_middleware(
+			&addr,
+			cors_domains,
+			allowed_hosts,
+			handler,
+			remote,
+			rpc::RpcExtractor,
+			rpc::Health::new(node_status, conf.health, graphql),
+			conf.server_threads,
+			conf.max_payload,
+		),
+	};
 
 	match start_result {
 		Ok(server) => Ok(Some(server)),
@@ -243,7 +314,16 @@ pub fn new_ipc<D: rpc_apis::Dependencies>(
 		return Ok(None);
 	}
 
-	let handler = setup_apis(conf.apis, dependencies);
+	let handler = {
+		let mut handler = MetaIoHandler::with_middleware((
+			MethodFilter::new(conf.method_acl),
+			Middleware::new(dependencies.stats.clone(), dependencies.apis.activity_notifier(), dependencies.pool.clone()),
+		));
+		let apis = conf.apis.list_apis();
+		dependencies.apis.extend_with_set(&mut handler, &apis);
+
+		handler
+	};
 	let remote = dependencies.remote.clone();
 	let path = PathBuf::from(&conf.socket_addr);
 	// Make sure socket file can be created on unix-like OS.
@@ -261,6 +341,13 @@ pub fn new_ipc<D: rpc_apis::Dependencies>(
 	}
 }
 
+fn jwt_secret(path: &Option<PathBuf>) -> Result<Option<Arc<rpc::JwtSecret>>, String> {
+	match *path {
+		Some(ref path) => rpc::JwtSecret::from_file(path).map(|secret| Some(Arc::new(secret))),
+		None => Ok(None),
+	}
+}
+
 fn into_domains<T: From<String>>(items: Option<Vec<String>>) -> DomainsValidation<T> {
 	items.map(|vals| vals.into_iter().map(T::from).collect()).into()
 }
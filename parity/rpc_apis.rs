@@ -37,7 +37,8 @@ use miner::external::ExternalMiner;
 use parity_reactor;
 use parity_rpc::dispatch::{FullDispatcher, LightDispatcher};
 use parity_rpc::informant::{ActivityNotifier, ClientNotifier};
-use parity_rpc::{Metadata, NetworkSettings, Host};
+use parity_rpc::{Metadata, NetworkSettings, Host, SubscriptionQueueConfig, GasPriceOracle, NodeStatusProvider, FullNodeStatus, LightNodeStatus};
+use parity_rpc::graphql::GraphQl;
 use parking_lot::{Mutex, RwLock};
 use ethcore_private_tx::Provider as PrivateTransactionManager;
 use updater::Updater;
@@ -81,6 +82,12 @@ pub enum Api {
 	/// Geth-compatible (best-effort) debug API (Potentially UNSAFE)
 	/// NOTE We don't aim to support all methods, only the ones that are useful.
 	Debug,
+	/// External consensus driver - block import and chain head (UNSAFE: Side Effects affecting the chain)
+	Engine,
+	/// Geth-compatible transaction pool introspection (Safe)
+	TxPool,
+	/// Geth-compatible peer management (UNSAFE: Side Effects affecting node operation)
+	Admin,
 }
 
 impl FromStr for Api {
@@ -90,7 +97,9 @@ impl FromStr for Api {
 		use self::Api::*;
 
 		match s {
+			"admin" => Ok(Admin),
 			"debug" => Ok(Debug),
+			"engine" => Ok(Engine),
 			"eth" => Ok(Eth),
 			"net" => Ok(Net),
 			"parity" => Ok(Parity),
@@ -106,6 +115,7 @@ impl FromStr for Api {
 			"shh_pubsub" => Ok(WhisperPubSub),
 			"signer" => Ok(Signer),
 			"traces" => Ok(Traces),
+			"txpool" => Ok(TxPool),
 			"web3" => Ok(Web3),
 			api => Err(format!("Unknown api: {}", api))
 		}
@@ -146,7 +156,7 @@ impl FromStr for ApiSet {
 	fn from_str(s: &str) -> Result<Self, Self::Err> {
 		let mut apis = HashSet::new();
 
-		for api in s.split(',') {
+		for api in s.split(',').map(str::trim) {
 			match api {
 				"all" => {
 					apis.extend(ApiSet::All.list_apis());
@@ -155,6 +165,15 @@ impl FromStr for ApiSet {
 					// Safe APIs are those that are safe even in UnsafeContext.
 					apis.extend(ApiSet::UnsafeContext.list_apis());
 				},
+				// Rules of the method access control list, only whole namespaces are handled here.
+				api if api.contains(':') => {
+					let pos = api.find(':').expect("contains ':'; qed");
+					match (api[..pos].parse::<Api>(), &api[pos + 1..]) {
+						(Ok(api), "*") | (Ok(api), "allow") => { apis.insert(api); },
+						(Ok(api), "deny") => { apis.remove(&api); },
+						_ => {},
+					}
+				},
 				// Remove the API
 				api if api.starts_with("-") => {
 					let api = api[1..].parse()?;
@@ -175,7 +194,9 @@ fn to_modules(apis: &HashSet<Api>) -> BTreeMap<String, String> {
 	let mut modules = BTreeMap::new();
 	for api in apis {
 		let (name, version) = match *api {
+			Api::Admin => ("admin", "1.0"),
 			Api::Debug => ("debug", "1.0"),
+			Api::Engine => ("engine", "1.0"),
 			Api::Eth => ("eth", "1.0"),
 			Api::EthPubSub => ("pubsub", "1.0"),
 			Api::Net => ("net", "1.0"),
@@ -189,6 +210,7 @@ fn to_modules(apis: &HashSet<Api>) -> BTreeMap<String, String> {
 			Api::SecretStore => ("secretstore", "1.0"),
 			Api::Signer => ("signer", "1.0"),
 			Api::Traces => ("traces", "1.0"),
+			Api::TxPool => ("txpool", "1.0"),
 			Api::Web3 => ("web3", "1.0"),
 			Api::Whisper => ("shh", "1.0"),
 			Api::WhisperPubSub => ("shh_pubsub", "1.0"),
@@ -211,6 +233,12 @@ pub trait Dependencies {
 		handler: &mut MetaIoHandler<Metadata, S>,
 		apis: &HashSet<Api>,
 	) where S: core::Middleware<Metadata>;
+
+	/// GraphQL handler sharing the client of the APIs, if the node supports it.
+	fn graphql(&self) -> Option<GraphQl<Client>>;
+
+	/// Status of the node reported by the health endpoints.
+	fn node_status(&self) -> Arc<NodeStatusProvider>;
 }
 
 /// RPC dependencies for a full node.
@@ -235,7 +263,11 @@ pub struct FullDependencies {
 	pub remote: parity_reactor::Remote,
 	pub whisper_rpc: Option<::whisper::RpcFactory>,
 	pub gas_price_percentile: usize,
+	pub gas_price_oracle: Arc<GasPriceOracle>,
 	pub poll_lifetime: u32,
+	pub call_cache_size: usize,
+	pub max_logs_range: Option<u64>,
+	pub subscription_queue: SubscriptionQueueConfig,
 }
 
 impl FullDependencies {
@@ -251,7 +283,8 @@ impl FullDependencies {
 			($namespace:ident, $handler:expr, $deps:expr, $nonces:expr) => {
 				{
 					let deps = &$deps;
-					let dispatcher = FullDispatcher::new(deps.client.clone(), deps.miner.clone(), $nonces, deps.gas_price_percentile);
+					let dispatcher = FullDispatcher::new(deps.client.clone(), deps.miner.clone(), $nonces, deps.gas_price_percentile)
+						.with_gas_price_oracle(deps.gas_price_oracle.clone());
 					if deps.signer_service.is_enabled() {
 						$handler.extend_with($namespace::to_delegate(SigningQueueClient::new(&deps.signer_service, dispatcher, deps.remote.clone(), &deps.secret_store)))
 					} else {
@@ -267,12 +300,18 @@ impl FullDependencies {
 			self.miner.clone(),
 			nonces.clone(),
 			self.gas_price_percentile,
-		);
+		).with_gas_price_oracle(self.gas_price_oracle.clone());
 		for api in apis {
 			match *api {
+				Api::Admin => {
+					handler.extend_with(AdminClient::new(self.sync.clone(), self.net_service.clone(), self.settings.clone()).to_delegate());
+				},
 				Api::Debug => {
 					handler.extend_with(DebugClient::new(self.client.clone()).to_delegate());
 				},
+				Api::Engine => {
+					handler.extend_with(EngineClient::new(self.client.clone()).to_delegate());
+				},
 				Api::Web3 => {
 					handler.extend_with(Web3Client::new().to_delegate());
 				},
@@ -292,13 +331,18 @@ impl FullDependencies {
 							allow_pending_receipt_query: !self.geth_compatibility,
 							send_block_number_in_get_work: !self.geth_compatibility,
 							gas_price_percentile: self.gas_price_percentile,
-							poll_lifetime: self.poll_lifetime
+							poll_lifetime: self.poll_lifetime,
+							call_cache_size: self.call_cache_size,
+							max_logs_range: self.max_logs_range,
 						}
-					);
-					handler.extend_with(client.to_delegate());
+					).with_gas_price_oracle(self.gas_price_oracle.clone());
+					let mut delegate = client.to_delegate();
+					EthCallOverride::extend_delegate(&mut delegate);
+					handler.extend_with(delegate);
 
 					if !for_generic_pubsub {
-						let filter_client = EthFilterClient::new(self.client.clone(), self.miner.clone(), self.poll_lifetime);
+						let filter_client = EthFilterClient::new(self.client.clone(), self.miner.clone(), self.poll_lifetime)
+							.with_max_logs_range(self.max_logs_range);
 						handler.extend_with(filter_client.to_delegate());
 
 						add_signing_methods!(EthSigning, handler, self, nonces.clone());
@@ -306,10 +350,10 @@ impl FullDependencies {
 				},
 				Api::EthPubSub => {
 					if !for_generic_pubsub {
-						let client = EthPubSubClient::new(self.client.clone(), self.remote.clone());
+						let client = EthPubSubClient::new(self.client.clone(), self.remote.clone(), self.subscription_queue);
 						let h = client.handler();
-						self.miner.add_transactions_listener(Box::new(move |hashes| if let Some(h) = h.upgrade() {
-							h.notify_new_transactions(hashes);
+						self.miner.add_full_transactions_listener(Box::new(move |transactions| if let Some(h) = h.upgrade() {
+							h.notify_pending_transactions(transactions);
 						}));
 
 						if let Some(h) = client.handler().upgrade() {
@@ -340,7 +384,8 @@ impl FullDependencies {
 						self.settings.clone(),
 						signer,
 						self.ws_address.clone(),
-					).to_delegate());
+						self.pool.clone(),
+					).with_gas_price_oracle(self.gas_price_oracle.clone()).to_delegate());
 
 					if !for_generic_pubsub {
 						add_signing_methods!(ParitySigning, handler, self, nonces.clone());
@@ -351,7 +396,17 @@ impl FullDependencies {
 						let mut rpc = MetaIoHandler::default();
 						let apis = ApiSet::List(apis.clone()).retain(ApiSet::PubSub).list_apis();
 						self.extend_api(&mut rpc, &apis, true);
-						handler.extend_with(PubSubClient::new(rpc, self.remote.clone()).to_delegate());
+						let client = PubSubClient::new(rpc, self.remote.clone());
+						if let Some(h) = client.reorg_handler().upgrade() {
+							self.client.add_notify(h);
+						}
+						let pool_handler = client.pool_handler();
+						self.miner.add_transactions_lifecycle_listener(Box::new(move |hash: &::ethereum_types::H256, event| {
+							if let Some(h) = pool_handler.upgrade() {
+								h.notify(hash, event);
+							}
+						}));
+						handler.extend_with(client.to_delegate());
 					}
 				},
 				Api::ParityAccounts => {
@@ -368,7 +423,7 @@ impl FullDependencies {
 					).to_delegate())
 				},
 				Api::Traces => {
-					handler.extend_with(TracesClient::new(&self.client).to_delegate())
+					handler.extend_with(TracesClient::new(&self.client, self.remote.clone()).to_delegate())
 				},
 				Api::Rpc => {
 					let modules = to_modules(&apis);
@@ -396,6 +451,9 @@ impl FullDependencies {
 				Api::Private => {
 					handler.extend_with(PrivateClient::new(self.private_tx_service.as_ref().map(|p| p.provider())).to_delegate());
 				},
+				Api::TxPool => {
+					handler.extend_with(TxPoolClient::new(self.client.clone(), self.miner.clone()).to_delegate());
+				},
 			}
 		}
 	}
@@ -417,6 +475,14 @@ impl Dependencies for FullDependencies {
 	) where S: core::Middleware<Metadata> {
 		self.extend_api(handler, apis, false)
 	}
+
+	fn graphql(&self) -> Option<GraphQl<Client>> {
+		Some(GraphQl::new(self.client.clone(), self.sync.clone()))
+	}
+
+	fn node_status(&self) -> Arc<NodeStatusProvider> {
+		Arc::new(FullNodeStatus::new(self.client.clone(), self.sync.clone()))
+	}
 }
 
 /// Light client notifier. Doesn't do anything yet, but might in the future.
@@ -447,6 +513,8 @@ pub struct LightDependencies<T> {
 	pub private_tx_service: Option<Arc<PrivateTransactionManager>>,
 	pub gas_price_percentile: usize,
 	pub poll_lifetime: u32,
+	pub max_logs_range: Option<u64>,
+	pub subscription_queue: SubscriptionQueueConfig,
 }
 
 impl<C: LightChainClient + 'static> LightDependencies<C> {
@@ -489,9 +557,15 @@ impl<C: LightChainClient + 'static> LightDependencies<C> {
 
 		for api in apis {
 			match *api {
+				Api::Admin => {
+					warn!(target: "rpc", "Admin API is not available in light client mode.")
+				},
 				Api::Debug => {
 					warn!(target: "rpc", "Debug API is not available in light client mode.")
 				},
+				Api::Engine => {
+					warn!(target: "rpc", "Engine API is not available in light client mode.")
+				},
 				Api::Web3 => {
 					handler.extend_with(Web3Client::new().to_delegate());
 				},
@@ -508,8 +582,10 @@ impl<C: LightChainClient + 'static> LightDependencies<C> {
 						self.cache.clone(),
 						self.gas_price_percentile,
 						self.poll_lifetime,
-					);
-					handler.extend_with(Eth::to_delegate(client.clone()));
+					).with_max_logs_range(self.max_logs_range);
+					let mut delegate = Eth::to_delegate(client.clone());
+					EthCallOverride::extend_delegate(&mut delegate);
+					handler.extend_with(delegate);
 
 					if !for_generic_pubsub {
 						handler.extend_with(EthFilter::to_delegate(client));
@@ -524,6 +600,7 @@ impl<C: LightChainClient + 'static> LightDependencies<C> {
 						self.cache.clone(),
 						self.remote.clone(),
 						self.gas_price_percentile,
+						self.subscription_queue,
 					);
 					self.client.add_listener(client.handler() as Weak<_>);
 					let h = client.handler();
@@ -554,6 +631,7 @@ impl<C: LightChainClient + 'static> LightDependencies<C> {
 						signer,
 						self.ws_address.clone(),
 						self.gas_price_percentile,
+						self.pool.clone(),
 					).to_delegate());
 
 					if !for_generic_pubsub {
@@ -605,7 +683,10 @@ impl<C: LightChainClient + 'static> LightDependencies<C> {
 						let private_tx_service = Some(tx_manager.clone());
 						handler.extend_with(PrivateClient::new(private_tx_service).to_delegate());
 					}
-				}
+				},
+				Api::TxPool => {
+					warn!(target: "rpc", "TxPool API is not available in light client mode.")
+				},
 			}
 		}
 	}
@@ -623,6 +704,13 @@ impl<T: LightChainClient + 'static> Dependencies for LightDependencies<T> {
 	) where S: core::Middleware<Metadata> {
 		self.extend_api(handler, apis, false)
 	}
+	fn graphql(&self) -> Option<GraphQl<Client>> {
+		None
+	}
+
+	fn node_status(&self) -> Arc<NodeStatusProvider> {
+		Arc::new(LightNodeStatus::new(self.client.clone(), self.sync.clone()))
+	}
 }
 
 impl ApiSet {
@@ -642,6 +730,7 @@ impl ApiSet {
 			Api::Whisper,
 			Api::WhisperPubSub,
 			Api::Private,
+			Api::TxPool,
 		].into_iter().cloned().collect();
 
 		match *self {
@@ -663,6 +752,7 @@ impl ApiSet {
 				public_list.insert(Api::ParityPubSub);
 				public_list.insert(Api::ParityAccounts);
 				public_list.insert(Api::ParitySet);
+				public_list.insert(Api::Admin);
 				public_list.insert(Api::Signer);
 				public_list.insert(Api::SecretStore);
 				public_list
@@ -673,6 +763,7 @@ impl ApiSet {
 				public_list.insert(Api::ParityPubSub);
 				public_list.insert(Api::ParityAccounts);
 				public_list.insert(Api::ParitySet);
+				public_list.insert(Api::Admin);
 				public_list.insert(Api::Signer);
 				public_list.insert(Api::Personal);
 				public_list.insert(Api::SecretStore);
@@ -695,7 +786,9 @@ mod test {
 
 	#[test]
 	fn test_api_parsing() {
+		assert_eq!(Api::Admin, "admin".parse().unwrap());
 		assert_eq!(Api::Debug, "debug".parse().unwrap());
+		assert_eq!(Api::Engine, "engine".parse().unwrap());
 		assert_eq!(Api::Web3, "web3".parse().unwrap());
 		assert_eq!(Api::Net, "net".parse().unwrap());
 		assert_eq!(Api::Eth, "eth".parse().unwrap());
@@ -709,6 +802,7 @@ mod test {
 		assert_eq!(Api::Rpc, "rpc".parse().unwrap());
 		assert_eq!(Api::SecretStore, "secretstore".parse().unwrap());
 		assert_eq!(Api::Private, "private".parse().unwrap());
+		assert_eq!(Api::TxPool, "txpool".parse().unwrap());
 		assert_eq!(Api::Whisper, "shh".parse().unwrap());
 		assert_eq!(Api::WhisperPubSub, "shh_pubsub".parse().unwrap());
 		assert!("rp".parse::<Api>().is_err());
@@ -728,7 +822,7 @@ mod test {
 	fn test_api_set_unsafe_context() {
 		let expected = vec![
 			// make sure this list contains only SAFE methods
-			Api::Web3, Api::Net, Api::Eth, Api::EthPubSub, Api::Parity, Api::ParityPubSub, Api::Traces, Api::Rpc, Api::Whisper, Api::WhisperPubSub, Api::Private,
+			Api::Web3, Api::Net, Api::Eth, Api::EthPubSub, Api::Parity, Api::ParityPubSub, Api::Traces, Api::Rpc, Api::Whisper, Api::WhisperPubSub, Api::Private, Api::TxPool,
 		].into_iter().collect();
 		assert_eq!(ApiSet::UnsafeContext.list_apis(), expected);
 	}
@@ -737,7 +831,7 @@ mod test {
 	fn test_api_set_ipc_context() {
 		let expected = vec![
 			// safe
-			Api::Web3, Api::Net, Api::Eth, Api::EthPubSub, Api::Parity, Api::ParityPubSub, Api::Traces, Api::Rpc, Api::Whisper, Api::WhisperPubSub, Api::Private,
+			Api::Web3, Api::Net, Api::Eth, Api::EthPubSub, Api::Parity, Api::ParityPubSub, Api::Traces, Api::Rpc, Api::Whisper, Api::WhisperPubSub, Api::Private, Api::TxPool,
 			// semi-safe
 			Api::ParityAccounts
 		].into_iter().collect();
@@ -748,11 +842,11 @@ mod test {
 	fn test_api_set_safe_context() {
 		let expected = vec![
 			// safe
-			Api::Web3, Api::Net, Api::Eth, Api::EthPubSub, Api::Parity, Api::ParityPubSub, Api::Traces, Api::Rpc, Api::SecretStore, Api::Whisper, Api::WhisperPubSub, Api::Private,
+			Api::Web3, Api::Net, Api::Eth, Api::EthPubSub, Api::Parity, Api::ParityPubSub, Api::Traces, Api::Rpc, Api::SecretStore, Api::Whisper, Api::WhisperPubSub, Api::Private, Api::TxPool,
 			// semi-safe
 			Api::ParityAccounts,
 			// Unsafe
-			Api::ParitySet, Api::Signer, Api::Debug
+			Api::ParitySet, Api::Admin, Api::Signer, Api::Debug
 		].into_iter().collect();
 		assert_eq!(ApiSet::SafeContext.list_apis(), expected);
 	}
@@ -762,10 +856,11 @@ mod test {
 		assert_eq!("all".parse::<ApiSet>().unwrap(), ApiSet::List(vec![
 			Api::Web3, Api::Net, Api::Eth, Api::EthPubSub, Api::Parity, Api::ParityPubSub, Api::Traces, Api::Rpc, Api::SecretStore, Api::Whisper, Api::WhisperPubSub,
 			Api::ParityAccounts,
-			Api::ParitySet, Api::Signer,
+			Api::ParitySet, Api::Admin, Api::Signer,
 			Api::Personal,
 			Api::Private,
 			Api::Debug,
+			Api::TxPool,
 		].into_iter().collect()));
 	}
 
@@ -774,15 +869,22 @@ mod test {
 		assert_eq!("personal,all,-personal".parse::<ApiSet>().unwrap(), ApiSet::List(vec![
 			Api::Web3, Api::Net, Api::Eth, Api::EthPubSub, Api::Parity, Api::ParityPubSub, Api::Traces, Api::Rpc, Api::SecretStore, Api::Whisper, Api::WhisperPubSub,
 			Api::ParityAccounts,
-			Api::ParitySet, Api::Signer,
-			Api::Private, Api::Debug,
+			Api::ParitySet, Api::Admin, Api::Signer,
+			Api::Private, Api::Debug, Api::TxPool,
 		].into_iter().collect()));
 	}
 
 	#[test]
 	fn test_safe_parsing() {
 		assert_eq!("safe".parse::<ApiSet>().unwrap(), ApiSet::List(vec![
-			Api::Web3, Api::Net, Api::Eth, Api::EthPubSub, Api::Parity, Api::ParityPubSub, Api::Traces, Api::Rpc, Api::Whisper, Api::WhisperPubSub, Api::Private,
+			Api::Web3, Api::Net, Api::Eth, Api::EthPubSub, Api::Parity, Api::ParityPubSub, Api::Traces, Api::Rpc, Api::Whisper, Api::WhisperPubSub, Api::Private, Api::TxPool,
+		].into_iter().collect()));
+	}
+
+	#[test]
+	fn test_method_rules_parsing() {
+		assert_eq!("eth:*, parity:allow, parity_setChain:deny, traces:*, traces:deny".parse::<ApiSet>().unwrap(), ApiSet::List(vec![
+			Api::Eth, Api::Parity,
 		].into_iter().collect()));
 	}
 }
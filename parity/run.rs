@@ -22,7 +22,7 @@ use std::thread;
 use ansi_term::Colour;
 use bytes::Bytes;
 use ethcore::account_provider::{AccountProvider, AccountProviderSettings};
-use ethcore::client::{BlockId, CallContract, Client, Mode, DatabaseCompactionProfile, VMType, BlockChainClient, BlockInfo};
+use ethcore::client::{BlockId, CallContract, Client, ColdStateConfig, PrewarmContract, Mode, DatabaseCompactionProfile, VMType, BlockChainClient, BlockInfo};
 use ethcore::ethstore::ethkey;
 use ethcore::miner::{stratum, Miner, MinerService, MinerOptions};
 use ethcore::snapshot::{self, SnapshotConfiguration};
@@ -31,8 +31,9 @@ use ethcore::verification::queue::VerifierSettings;
 use ethcore_logger::{Config as LogConfig, RotatingLogger};
 use ethcore_service::ClientService;
 use ethereum_types::Address;
-use sync::{self, SyncConfig};
+use sync::{self, SyncConfig, ManageNetwork};
 use miner::work_notify::WorkPoster;
+use miner::tx_relay::TransactionRelay;
 use futures::IntoFuture;
 use futures_cpupool::CpuPool;
 use hash_fetch::{self, fetch};
@@ -42,7 +43,7 @@ use light::Cache as LightDataCache;
 use miner::external::ExternalMiner;
 use node_filter::NodeFilter;
 use parity_reactor::EventLoop;
-use parity_rpc::{Origin, Metadata, NetworkSettings, informant, is_major_importing};
+use parity_rpc::{Origin, Metadata, NetworkSettings, GasPriceOracle, GasPriceOracleOptions, informant, is_major_importing};
 use updater::{UpdatePolicy, Updater};
 use parity_version::version;
 use ethcore_private_tx::{ProviderConfig, EncryptorConfig, SecretStoreEncryptor};
@@ -50,12 +51,13 @@ use params::{
 	SpecType, Pruning, AccountsConfig, GasPricerConfig, MinerExtras, Switch,
 	tracing_switch_to_bool, fatdb_switch_to_bool, mode_switch_to_bool
 };
-use helpers::{to_client_config, execute_upgrades, passwords_from_files};
+use helpers::{to_client_config, execute_upgrades, passwords_from_files, reserved_nodes_from_file};
 use upgrade::upgrade_key_location;
 use dir::{Directories, DatabaseDirectories};
 use cache::CacheConfig;
 use user_defaults::UserDefaults;
 use ipfs;
+use metrics::{self, MetricsConfiguration};
 use jsonrpc_core;
 use modules;
 use registrar::{RegistrarClient, Asynchronous};
@@ -92,7 +94,10 @@ pub struct RunCmd {
 	pub logger_config: LogConfig,
 	pub miner_options: MinerOptions,
 	pub gas_price_percentile: usize,
+	pub gas_price_oracle: GasPriceOracleOptions,
 	pub poll_lifetime: u32,
+	pub call_cache_size: usize,
+	pub max_logs_range: Option<u64>,
 	pub ws_conf: rpc::WsConfiguration,
 	pub http_conf: rpc::HttpConfiguration,
 	pub ipc_conf: rpc::IpcConfiguration,
@@ -112,6 +117,7 @@ pub struct RunCmd {
 	pub geth_compatibility: bool,
 	pub net_settings: NetworkSettings,
 	pub ipfs_conf: ipfs::Configuration,
+	pub metrics_conf: MetricsConfiguration,
 	pub secretstore_conf: secretstore::Configuration,
 	pub private_provider_conf: ProviderConfig,
 	pub private_encryptor_conf: EncryptorConfig,
@@ -122,6 +128,7 @@ pub struct RunCmd {
 	pub snapshot_conf: SnapshotConfiguration,
 	pub check_seal: bool,
 	pub download_old_blocks: bool,
+	pub download_old_receipts: bool,
 	pub verifier_settings: VerifierSettings,
 	pub serve_light: bool,
 	pub light: bool,
@@ -130,6 +137,38 @@ pub struct RunCmd {
 	pub no_hardcoded_sync: bool,
 	pub on_demand_retry_count: Option<usize>,
 	pub on_demand_inactive_time_limit: Option<u64>,
+	pub evm_threads: Option<usize>,
+	pub thread_affinity: Vec<usize>,
+	pub db_hot_tier: Option<db::TierConfig>,
+	pub tx_lifecycle_log: Option<usize>,
+	pub parallel_transactions: bool,
+	pub prewarm_contracts: Vec<PrewarmContract>,
+	pub cold_calls: bool,
+	pub block_propagation: sync::BlockPropagation,
+	pub announcement_window: usize,
+	pub sync_headers_batch: usize,
+	pub sync_bodies_batch: usize,
+	pub snapshot_serving_peer_rate: Option<u64>,
+	pub snapshot_serving_rate: Option<u64>,
+	pub reserved_peers_file: Option<String>,
+}
+
+// configures CPU pinning and starts the global pool used for parallel block processing.
+// returned handle keeps the pool listed in thread statistics.
+fn setup_thread_pools(cmd: &RunCmd) -> Arc<::io::ThreadPoolUsage> {
+	::io::threads::set_cpu_affinity(cmd.thread_affinity.clone());
+
+	let evm_threads = cmd.evm_threads.unwrap_or_else(::num_cpus::get);
+	let result = ::rayon::ThreadPoolBuilder::new()
+		.num_threads(evm_threads)
+		.thread_name(|index| format!("EVM #{}", index))
+		.start_handler(::io::threads::pin_current_thread)
+		.build_global();
+	if let Err(err) = result {
+		warn!("Failed to configure EVM thread pool: {}", err);
+	}
+
+	::io::ThreadPoolUsage::register_unmeasured("evm", ::rayon::current_num_threads())
 }
 
 // node info fetcher for the local store.
@@ -152,6 +191,21 @@ impl ::local_store::NodeInfo for FullNodeInfo {
 			})
 			.collect()
 	}
+
+	fn external_transactions(&self) -> Vec<::transaction::PendingTransaction> {
+		use miner::pool::ScoredTransaction;
+
+		let miner = match self.miner.as_ref() {
+			Some(m) => m,
+			None => return Vec::new(),
+		};
+
+		miner.queued_transactions()
+			.into_iter()
+			.filter(|tx| !tx.priority().is_local())
+			.map(|tx| tx.pending().clone())
+			.collect()
+	}
 }
 
 type LightClient = ::light::client::Client<::light_helpers::EpochFetch>;
@@ -162,6 +216,8 @@ fn execute_light_impl(cmd: RunCmd, logger: Arc<RotatingLogger>) -> Result<Runnin
 	use sync::{LightSyncParams, LightSync, ManageNetwork};
 	use parking_lot::{Mutex, RwLock};
 
+	let evm_pool = setup_thread_pools(&cmd);
+
 	// load spec
 	let spec = cmd.spec.spec(SpecParams::new(cmd.dirs.cache.as_ref(), OptimizeFor::Memory))?;
 
@@ -242,7 +298,7 @@ fn execute_light_impl(cmd: RunCmd, logger: Arc<RotatingLogger>) -> Result<Runnin
 
 	let mut attached_protos = Vec::new();
 	let whisper_factory = if cmd.whisper.enabled {
-		let whisper_factory = ::whisper::setup(cmd.whisper.target_message_pool_size, &mut attached_protos)
+		let whisper_factory = ::whisper::setup(&cmd.whisper, &mut attached_protos)
 			.map_err(|e| format!("Failed to initialize whisper: {}", e))?;
 		whisper_factory
 	} else {
@@ -313,7 +369,9 @@ fn execute_light_impl(cmd: RunCmd, logger: Arc<RotatingLogger>) -> Result<Runnin
 		whisper_rpc: whisper_factory,
 		private_tx_service: None, //TODO: add this to client.
 		gas_price_percentile: cmd.gas_price_percentile,
-		poll_lifetime: cmd.poll_lifetime
+		poll_lifetime: cmd.poll_lifetime,
+		max_logs_range: cmd.max_logs_range,
+		subscription_queue: cmd.ws_conf.subscription_queue,
 	});
 
 	let dependencies = rpc::Dependencies {
@@ -333,6 +391,9 @@ fn execute_light_impl(cmd: RunCmd, logger: Arc<RotatingLogger>) -> Result<Runnin
 	let http_server = rpc::new_http("HTTP JSON-RPC", "jsonrpc", cmd.http_conf.clone(), &dependencies)?;
 	let ipc_server = rpc::new_ipc(cmd.ipc_conf, &dependencies)?;
 
+	// the metrics server, chain metrics are only collected by full nodes.
+	let metrics_server = metrics::start_server(cmd.metrics_conf.clone(), vec![rpc_stats.clone() as metrics::Source], event_loop.raw_remote())?;
+
 	// the informant
 	let informant = Arc::new(Informant::new(
 		LightNodeInformantData {
@@ -352,7 +413,8 @@ fn execute_light_impl(cmd: RunCmd, logger: Arc<RotatingLogger>) -> Result<Runnin
 			rpc: rpc_direct,
 			informant,
 			client,
-			keep_alive: Box::new((event_loop, service, ws_server, http_server, ipc_server)),
+			reserved_peers: ReservedPeersReloader::new(cmd.reserved_peers_file.clone(), light_sync.clone()),
+			keep_alive: Box::new((event_loop, service, ws_server, http_server, ipc_server, metrics_server, evm_pool)),
 		}
 	})
 }
@@ -362,6 +424,8 @@ fn execute_impl<Cr, Rr>(cmd: RunCmd, logger: Arc<RotatingLogger>, on_client_rq:
 	where Cr: Fn(String) + 'static + Send,
 		Rr: Fn() + 'static + Send
 {
+	let evm_pool = setup_thread_pools(&cmd);
+
 	// load spec
 	let spec = cmd.spec.spec(&cmd.dirs.cache)?;
 
@@ -444,6 +508,7 @@ fn execute_impl<Cr, Rr>(cmd: RunCmd, logger: Arc<RotatingLogger>, on_client_rq:
 	}
 
 	sync_config.fork_block = spec.fork_block();
+	sync_config.eip1559_transition = spec.engine.params().eip1559_transition;
 	let mut warp_sync = spec.engine.supports_warp() && cmd.warp_sync;
 	if warp_sync {
 		// Logging is not initialized yet, so we print directly to stderr
@@ -464,7 +529,14 @@ fn execute_impl<Cr, Rr>(cmd: RunCmd, logger: Arc<RotatingLogger>, on_client_rq:
 		_ => sync::WarpSync::Disabled,
 	};
 	sync_config.download_old_blocks = cmd.download_old_blocks;
+	sync_config.download_old_receipts = cmd.download_old_receipts;
 	sync_config.serve_light = cmd.serve_light;
+	sync_config.block_propagation = cmd.block_propagation;
+	sync_config.announcement_window = cmd.announcement_window;
+	sync_config.headers_batch = cmd.sync_headers_batch;
+	sync_config.bodies_batch = cmd.sync_bodies_batch;
+	sync_config.snapshot_serving_peer_rate = cmd.snapshot_serving_peer_rate;
+	sync_config.snapshot_serving_rate = cmd.snapshot_serving_rate;
 
 	let passwords = passwords_from_files(&cmd.acc_conf.password_files)?;
 
@@ -498,6 +570,15 @@ fn execute_impl<Cr, Rr>(cmd: RunCmd, logger: Arc<RotatingLogger>, on_client_rq:
 		));
 	}
 
+	if !cmd.miner_extras.tx_relay.urls.is_empty() {
+		let relay = TransactionRelay::new(cmd.miner_extras.tx_relay.clone(), fetch.clone(), event_loop.remote());
+		miner.add_local_transactions_listener(Box::new(move |transactions| {
+			for tx in transactions {
+				relay.relay(&tx.signed().encode_raw());
+			}
+		}));
+	}
+
 	let engine_signer = cmd.miner_extras.engine_signer;
 	if engine_signer != Default::default() {
 		// Check if engine signer exists
@@ -540,6 +621,17 @@ fn execute_impl<Cr, Rr>(cmd: RunCmd, logger: Arc<RotatingLogger>, on_client_rq:
 	client_config.queue.verifier_settings = cmd.verifier_settings;
 	client_config.transaction_verification_queue_size = ::std::cmp::max(2048, txpool_size / 4);
 	client_config.snapshot = cmd.snapshot_conf.clone();
+	client_config.transaction_lifecycle_log = cmd.tx_lifecycle_log;
+	client_config.parallel_transactions = cmd.parallel_transactions;
+	client_config.prewarm_contracts = cmd.prewarm_contracts.clone();
+	client_config.cold_state = if cmd.cold_calls {
+		Some(ColdStateConfig {
+			snapshot_root: snapshot_path.clone(),
+			max_blocks: SNAPSHOT_PERIOD,
+		})
+	} else {
+		None
+	};
 
 	// set up bootnodes
 	let mut net_conf = cmd.net_conf;
@@ -550,7 +642,11 @@ fn execute_impl<Cr, Rr>(cmd: RunCmd, logger: Arc<RotatingLogger>, on_client_rq:
 	// set network path.
 	net_conf.net_config_path = Some(db_dirs.network_path().to_string_lossy().into_owned());
 
-	let restoration_db_handler = db::restoration_db_handler(&client_path, &client_config);
+	let restoration_db_handler = db::restoration_db_handler(
+		&client_path,
+		&client_config,
+		cmd.db_hot_tier.clone().map(|tier| tier.for_chain(&genesis_hash)),
+	);
 	let client_db = restoration_db_handler.open(&client_path)
 		.map_err(|e| format!("Failed to open database {:?}", e))?;
 
@@ -615,9 +711,49 @@ fn execute_impl<Cr, Rr>(cmd: RunCmd, logger: Arc<RotatingLogger>, on_client_rq:
 			Err(e) => warn!("Error loading cached pending transactions from disk: {}", e),
 		}
 
+		// re-queue transactions received from the network.
+		match store.external_transactions() {
+			Ok(external) => {
+				let external = external.into_iter().map(|tx| tx.transaction.into()).collect();
+				let failed = miner.import_external_transactions(&*client, external)
+					.into_iter()
+					.filter(Result::is_err)
+					.count();
+				if failed > 0 {
+					debug!("{} saved network transactions are no longer valid", failed);
+				}
+			}
+			Err(e) => warn!("Error loading cached network transactions from disk: {}", e),
+		}
+
+		// compact the replayed journal into a fresh snapshot.
+		if let Err(e) = store.update() {
+			warn!("Error updating persistent transaction queue: {}", e);
+		}
+
 		Arc::new(store)
 	};
 
+	// journal every transaction entering the queue, so a crash doesn't drop them.
+	if !cmd.no_persistent_txqueue {
+		let store = Arc::downgrade(&store);
+		miner.add_full_transactions_listener(Box::new(move |txs| {
+			use miner::pool::ScoredTransaction;
+
+			let store = match store.upgrade() {
+				Some(store) => store,
+				None => return,
+			};
+			let (local, external): (Vec<_>, Vec<_>) = txs.iter().partition(|tx| tx.priority().is_local());
+			let pending = |txs: Vec<&Arc<::miner::pool::VerifiedTransaction>>| -> Vec<::transaction::PendingTransaction> {
+				txs.into_iter().map(|tx| tx.pending().clone()).collect()
+			};
+			if let Err(e) = store.journal(pending(local), pending(external)) {
+				warn!("Error journaling transactions: {}", e);
+			}
+		}));
+	}
+
 	// register it as an IO service to update periodically.
 	service.register_io_handler(store).map_err(|_| "Unable to register local store handler".to_owned())?;
 
@@ -633,7 +769,7 @@ fn execute_impl<Cr, Rr>(cmd: RunCmd, logger: Arc<RotatingLogger>, on_client_rq:
 	let mut attached_protos = Vec::new();
 
 	let whisper_factory = if cmd.whisper.enabled {
-		let whisper_factory = ::whisper::setup(cmd.whisper.target_message_pool_size, &mut attached_protos)
+		let whisper_factory = ::whisper::setup(&cmd.whisper, &mut attached_protos)
 			.map_err(|e| format!("Failed to initialize whisper: {}", e))?;
 
 		whisper_factory
@@ -642,7 +778,7 @@ fn execute_impl<Cr, Rr>(cmd: RunCmd, logger: Arc<RotatingLogger>, on_client_rq:
 	};
 
 	// create sync object
-	let (sync_provider, manage_network, chain_notify) = modules::sync(
+	let (sync_provider, manage_network, chain_notify, sync_metrics) = modules::sync(
 		sync_config,
 		net_conf.clone().into(),
 		client.clone(),
@@ -722,7 +858,11 @@ fn execute_impl<Cr, Rr>(cmd: RunCmd, logger: Arc<RotatingLogger>, on_client_rq:
 		whisper_rpc: whisper_factory,
 		private_tx_service: Some(private_tx_service.clone()),
 		gas_price_percentile: cmd.gas_price_percentile,
+		gas_price_oracle: Arc::new(GasPriceOracle::new(cmd.gas_price_oracle.clone())),
 		poll_lifetime: cmd.poll_lifetime,
+		call_cache_size: cmd.call_cache_size,
+		max_logs_range: cmd.max_logs_range,
+		subscription_queue: cmd.ws_conf.subscription_queue,
 	});
 
 	let dependencies = rpc::Dependencies {
@@ -743,6 +883,10 @@ fn execute_impl<Cr, Rr>(cmd: RunCmd, logger: Arc<RotatingLogger>, on_client_rq:
 	let ipc_server = rpc::new_ipc(cmd.ipc_conf, &dependencies)?;
 	let http_server = rpc::new_http("HTTP JSON-RPC", "jsonrpc", cmd.http_conf.clone(), &dependencies)?;
 
+	// the metrics server
+	let metrics_sources: Vec<metrics::Source> = vec![client.clone(), sync_metrics, miner.clone(), rpc_stats.clone()];
+	let metrics_server = metrics::start_server(cmd.metrics_conf.clone(), metrics_sources, event_loop.raw_remote())?;
+
 	// secret store key server
 	let secretstore_deps = secretstore::Dependencies {
 		client: client.clone(),
@@ -814,11 +958,45 @@ fn execute_impl<Cr, Rr>(cmd: RunCmd, logger: Arc<RotatingLogger>, on_client_rq:
 			informant,
 			client,
 			client_service: Arc::new(service),
-			keep_alive: Box::new((watcher, updater, ws_server, http_server, ipc_server, secretstore_key_server, ipfs_server, event_loop)),
+			reserved_peers: ReservedPeersReloader::new(cmd.reserved_peers_file.clone(), manage_network.clone()),
+			keep_alive: Box::new((watcher, updater, ws_server, http_server, ipc_server, secretstore_key_server, ipfs_server, metrics_server, event_loop, evm_pool)),
 		}
 	})
 }
 
+/// Reloads reserved peers of running clients from the file given by `--reserved-peers`.
+#[derive(Clone, Default)]
+pub struct ReservedPeersReloader {
+	networks: Vec<(String, Arc<ManageNetwork>)>,
+}
+
+impl ReservedPeersReloader {
+	fn new(path: Option<String>, network: Arc<ManageNetwork>) -> Self {
+		ReservedPeersReloader {
+			networks: path.into_iter().map(|path| (path, network.clone())).collect(),
+		}
+	}
+
+	/// Returns `true` if no client was started with a reserved peers file.
+	pub fn is_empty(&self) -> bool {
+		self.networks.is_empty()
+	}
+
+	/// Re-reads the reserved peers files and applies them, keeping connections to peers which stay reserved.
+	pub fn reload(&self) {
+		for &(ref path, ref network) in &self.networks {
+			let result = reserved_nodes_from_file(path).and_then(|nodes| {
+				let count = nodes.len();
+				network.set_reserved_peers(nodes).map(|_| count)
+			});
+			match result {
+				Ok(count) => info!("Reloaded {} reserved peers from {}", count, path),
+				Err(e) => warn!("Unable to reload reserved peers from {}: {}", path, e),
+			}
+		}
+	}
+}
+
 /// Parity client currently executing in background threads.
 ///
 /// Should be destroyed by calling `shutdown()`, otherwise execution will continue in the
@@ -832,6 +1010,7 @@ enum RunningClientInner {
 		rpc: jsonrpc_core::MetaIoHandler<Metadata, informant::Middleware<rpc_apis::LightClientNotifier>>,
 		informant: Arc<Informant<LightNodeInformantData>>,
 		client: Arc<LightClient>,
+		reserved_peers: ReservedPeersReloader,
 		keep_alive: Box<Any>,
 	},
 	Full {
@@ -839,17 +1018,28 @@ enum RunningClientInner {
 		informant: Arc<Informant<FullNodeInformantData>>,
 		client: Arc<Client>,
 		client_service: Arc<ClientService>,
+		reserved_peers: ReservedPeersReloader,
 		keep_alive: Box<Any>,
 	},
+	Group(Vec<RunningClient>),
 }
 
 impl RunningClient {
+	/// Wraps several clients running in the same process, e.g. nodes of a local development network.
+	/// RPC queries are handled by the first client.
+	pub fn group(clients: Vec<RunningClient>) -> RunningClient {
+		RunningClient {
+			inner: RunningClientInner::Group(clients),
+		}
+	}
+
 	/// Performs a synchronous RPC query.
 	/// Blocks execution until the result is ready.
 	pub fn rpc_query_sync(&self, request: &str) -> Option<String> {
 		let metadata = Metadata {
 			origin: Origin::CApi,
 			session: None,
+			client: None,
 		};
 
 		match self.inner {
@@ -859,13 +1049,27 @@ impl RunningClient {
 			RunningClientInner::Full { ref rpc, .. } => {
 				rpc.handle_request_sync(request, metadata)
 			},
+			RunningClientInner::Group(ref clients) => {
+				clients.first().and_then(|client| client.rpc_query_sync(request))
+			},
+		}
+	}
+
+	/// Returns a handle reloading reserved peers of the client from the file given by `--reserved-peers`.
+	pub fn reserved_peers_reloader(&self) -> ReservedPeersReloader {
+		match self.inner {
+			RunningClientInner::Light { ref reserved_peers, .. } => reserved_peers.clone(),
+			RunningClientInner::Full { ref reserved_peers, .. } => reserved_peers.clone(),
+			RunningClientInner::Group(ref clients) => ReservedPeersReloader {
+				networks: clients.iter().flat_map(|client| client.reserved_peers_reloader().networks).collect(),
+			},
 		}
 	}
 
 	/// Shuts down the client.
 	pub fn shutdown(self) {
 		match self.inner {
-			RunningClientInner::Light { rpc, informant, client, keep_alive } => {
+			RunningClientInner::Light { rpc, informant, client, keep_alive, .. } => {
 				// Create a weak reference to the client so that we can wait on shutdown
 				// until it is dropped
 				let weak_client = Arc::downgrade(&client);
@@ -876,7 +1080,7 @@ impl RunningClient {
 				drop(client);
 				wait_for_drop(weak_client);
 			},
-			RunningClientInner::Full { rpc, informant, client, client_service, keep_alive } => {
+			RunningClientInner::Full { rpc, informant, client, client_service, keep_alive, .. } => {
 				info!("Finishing work, please wait...");
 				// Create a weak reference to the client so that we can wait on shutdown
 				// until it is dropped
@@ -893,7 +1097,12 @@ impl RunningClient {
 				drop(informant);
 				drop(client);
 				wait_for_drop(weak_client);
-			}
+			},
+			RunningClientInner::Group(clients) => {
+				for client in clients {
+					client.shutdown();
+				}
+			},
 		}
 	}
 }
@@ -43,6 +43,29 @@ pub enum ContractAddress {
 	Address(Address),
 }
 
+/// Secret store key shares storage.
+#[derive(Debug, PartialEq, Clone)]
+pub enum KeyStorage {
+	/// Key shares are stored in the secret store database.
+	Database,
+	/// Key shares are stored as encrypted files in given directory.
+	EncryptedFiles {
+		/// Directory path.
+		path: String,
+		/// Encryption secret. If None, plain node secret is used.
+		secret: Option<Secret>,
+	},
+	/// Key shares are stored in etcd cluster.
+	Etcd {
+		/// Gateway endpoints.
+		endpoints: Vec<String>,
+		/// Prefix of all keys.
+		prefix: String,
+		/// Encryption secret. If None, plain node secret is used.
+		secret: Option<Secret>,
+	},
+}
+
 #[derive(Debug, PartialEq, Clone)]
 /// Secret store configuration
 pub struct Configuration {
@@ -80,6 +103,8 @@ pub struct Configuration {
 	pub http_port: u16,
 	/// Data directory path for secret store
 	pub data_path: String,
+	/// Key shares storage.
+	pub key_storage: KeyStorage,
 	/// Administrator public key.
 	pub admin_public: Option<Public>,
 }
@@ -117,10 +142,10 @@ mod server {
 mod server {
 	use std::sync::Arc;
 	use ethcore_secretstore;
-	use ethkey::KeyPair;
+	use ethkey::{KeyPair, Secret};
 	use ansi_term::Colour::{Red, White};
 	use db;
-	use super::{Configuration, Dependencies, NodeSecretKey, ContractAddress};
+	use super::{Configuration, Dependencies, NodeSecretKey, ContractAddress, KeyStorage};
 
 	fn into_service_contract_address(address: ContractAddress) -> ethcore_secretstore::ContractAddress {
 		match address {
@@ -137,6 +162,25 @@ mod server {
 	impl KeyServer {
 		/// Create new key server
 		pub fn new(mut conf: Configuration, deps: Dependencies) -> Result<Self, String> {
+			let plain_secret = match conf.self_secret {
+				Some(NodeSecretKey::Plain(ref secret)) => Some(secret.clone()),
+				_ => None,
+			};
+			let storage_secret = |secret: Option<Secret>| secret.or_else(|| plain_secret.clone())
+				.ok_or_else(|| "--secretstore-storage-secret is required when key shares are not stored in database and node secret is not given in plain text".to_owned());
+			let key_storage = match conf.key_storage.clone() {
+				KeyStorage::Database => ethcore_secretstore::KeyStorageConfiguration::Database,
+				KeyStorage::EncryptedFiles { path, secret } => ethcore_secretstore::KeyStorageConfiguration::EncryptedFiles {
+					path: path,
+					secret: storage_secret(secret)?,
+				},
+				KeyStorage::Etcd { endpoints, prefix, secret } => ethcore_secretstore::KeyStorageConfiguration::Etcd {
+					endpoints: endpoints,
+					prefix: prefix,
+					secret: storage_secret(secret)?,
+				},
+			};
+
 			let self_secret: Arc<ethcore_secretstore::NodeKeyPair> = match conf.self_secret.take() {
 				Some(NodeSecretKey::Plain(secret)) => Arc::new(ethcore_secretstore::PlainNodeKeyPair::new(
 					KeyPair::from_secret(secret).map_err(|e| format!("invalid secret: {}", e))?)),
@@ -193,6 +237,7 @@ mod server {
 					admin_public: conf.admin_public,
 					auto_migrate_enabled: conf.auto_migrate_enabled,
 				},
+				key_storage: key_storage,
 			};
 
 			cconf.cluster_config.nodes.insert(self_secret.public().clone(), cconf.cluster_config.listener_address.clone());
@@ -233,6 +278,7 @@ impl Default for Configuration {
 			http_interface: "127.0.0.1".to_owned(),
 			http_port: 8082,
 			data_path: replace_home(&data_dir, "$BASE/secretstore"),
+			key_storage: KeyStorage::Database,
 		}
 	}
 }
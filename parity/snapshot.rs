@@ -183,7 +183,7 @@ impl SnapshotCommand {
 
 		client_config.snapshot = self.snapshot_conf;
 
-		let restoration_db_handler = db::restoration_db_handler(&client_path, &client_config);
+		let restoration_db_handler = db::restoration_db_handler(&client_path, &client_config, None);
 		let client_db = restoration_db_handler.open(&client_path)
 			.map_err(|e| format!("Failed to open database {:?}", e))?;
 
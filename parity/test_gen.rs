@@ -0,0 +1,333 @@
+// Copyright 2015-2018 Parity Technologies (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Generation of state test fixtures from transactions of the chain.
+//!
+//! A fixture contains the environment of the block, the transaction and the accounts and storage
+//! items touched by it, in the format of the ethereum/tests state tests. The format requires
+//! transactions to be signed with a known key, so the sender account is moved to the address of
+//! the standard test key and the transaction is signed again. The post state is computed by
+//! executing the fixture itself, so the fixture stays consistent even if the relocation affects
+//! the execution.
+
+use std::collections::BTreeMap;
+use std::fs;
+use std::io::Write;
+
+use ethcore::client::{EvmTestClient, TransactResult, TransactionId, DatabaseCompactionProfile, EnvInfo};
+use ethcore::log_entry::LogEntry;
+use ethcore::pod_state::PodState;
+use ethcore::trace::{NoopTracer, NoopVMTracer};
+use ethereum_types::{H256, U256, Address};
+use ethjson;
+use ethkey::{KeyPair, Secret};
+use hash::keccak;
+use rlp;
+use rustc_hex::ToHex;
+use serde_json;
+use transaction::{Action, SignedTransaction};
+
+use blockchain::start_client;
+use cache::CacheConfig;
+use dir::Directories;
+use params::{SpecType, Pruning, Switch};
+
+/// Secret key of the sender used across the ethereum/tests state tests.
+const TEST_SECRET: &'static str = "45a915e4d060149eb4365960e6a7a45f334393093061116b197e3240065ff2d8";
+
+#[derive(Debug, PartialEq)]
+pub struct TestGenCmd {
+	pub spec: SpecType,
+	pub cache_config: CacheConfig,
+	pub dirs: Directories,
+	pub pruning: Pruning,
+	pub pruning_history: u64,
+	pub pruning_memory: usize,
+	pub compaction: DatabaseCompactionProfile,
+	pub tracing: Switch,
+	pub fat_db: Switch,
+	pub transaction: H256,
+	pub fork: String,
+	pub name: Option<String>,
+	pub file_path: Option<String>,
+}
+
+pub fn execute(cmd: TestGenCmd) -> Result<String, String> {
+	// fail early on unknown forks
+	to_fork_spec(&cmd.fork)?;
+
+	let service = start_client(
+		cmd.dirs,
+		cmd.spec,
+		cmd.pruning,
+		cmd.pruning_history,
+		cmd.pruning_memory,
+		cmd.tracing,
+		cmd.fat_db,
+		cmd.compaction,
+		cmd.cache_config,
+		false
+	)?;
+
+	let client = service.client();
+	let (env_info, transaction, pre) = client.transaction_pre_state(TransactionId::Hash(cmd.transaction))
+		.map_err(|e| format!("Cannot replay transaction {:?}: {}", cmd.transaction, e))?;
+
+	let name = cmd.name.unwrap_or_else(|| format!("{:x}", cmd.transaction));
+	let fixture = state_test(&name, &cmd.fork, &env_info, &transaction, pre)?;
+
+	match cmd.file_path {
+		Some(path) => {
+			fs::File::create(&path)
+				.and_then(|mut file| file.write_all(fixture.as_bytes()))
+				.map_err(|e| format!("Cannot write to file {}: {}", path, e))?;
+			Ok(format!("State test {} written to {}", name, path))
+		},
+		None => Ok(fixture),
+	}
+}
+
+#[derive(Serialize)]
+struct StateTest {
+	env: Env,
+	pre: BTreeMap<String, Account>,
+	transaction: MultiTransaction,
+	post: BTreeMap<String, Vec<PostState>>,
+}
+
+#[derive(Serialize)]
+struct Env {
+	#[serde(rename="currentCoinbase")]
+	author: String,
+	#[serde(rename="currentDifficulty")]
+	difficulty: String,
+	#[serde(rename="currentGasLimit")]
+	gas_limit: String,
+	#[serde(rename="currentNumber")]
+	number: String,
+	#[serde(rename="currentTimestamp")]
+	timestamp: String,
+	#[serde(rename="previousHash")]
+	previous_hash: String,
+}
+
+#[derive(Serialize)]
+struct Account {
+	balance: String,
+	code: String,
+	nonce: String,
+	storage: BTreeMap<String, String>,
+}
+
+#[derive(Serialize)]
+struct MultiTransaction {
+	data: Vec<String>,
+	#[serde(rename="gasLimit")]
+	gas_limit: Vec<String>,
+	#[serde(rename="gasPrice")]
+	gas_price: String,
+	nonce: String,
+	#[serde(rename="secretKey")]
+	secret: String,
+	to: String,
+	value: Vec<String>,
+}
+
+#[derive(Serialize)]
+struct PostState {
+	hash: String,
+	logs: String,
+	indexes: Indexes,
+}
+
+#[derive(Serialize)]
+struct Indexes {
+	data: u64,
+	gas: u64,
+	value: u64,
+}
+
+fn to_fork_spec(fork: &str) -> Result<ethjson::spec::ForkSpec, String> {
+	serde_json::from_str(&format!("\"{}\"", fork)).map_err(|_| format!("Invalid fork: {}", fork))
+}
+
+fn uint(value: &U256) -> String {
+	format!("0x{:x}", value)
+}
+
+fn bytes(value: &[u8]) -> String {
+	format!("0x{}", value.to_hex())
+}
+
+/// Move the account of the original sender to the address of the test key.
+fn relocate_sender(pre: PodState, sender: &Address, test_sender: &Address) -> Result<PodState, String> {
+	if sender == test_sender {
+		return Ok(pre);
+	}
+
+	let mut accounts = pre.drain();
+	if accounts.contains_key(test_sender) {
+		return Err(format!("Transaction touches the account of the test sender {:x}", test_sender));
+	}
+	if let Some(account) = accounts.remove(sender) {
+		accounts.insert(*test_sender, account);
+	}
+	Ok(PodState::from(accounts))
+}
+
+/// Generate a state test named `name` executing `transaction` on top of `pre` with rules of `fork`.
+pub fn state_test(
+	name: &str,
+	fork: &str,
+	env_info: &EnvInfo,
+	transaction: &SignedTransaction,
+	pre: PodState,
+) -> Result<String, String> {
+	let spec = EvmTestClient::spec_from_json(&to_fork_spec(fork)?)
+		.ok_or_else(|| format!("Fork {} is not supported by state tests", fork))?;
+	let secret: Secret = TEST_SECRET.parse().expect("TEST_SECRET is a valid secret; qed");
+	let key_pair = KeyPair::from_secret(secret).expect("TEST_SECRET is a valid secret; qed");
+	let pre = relocate_sender(pre, &transaction.sender(), &key_pair.address())?;
+
+	let mut test = StateTest {
+		env: Env {
+			author: format!("{:x}", env_info.author),
+			difficulty: uint(&env_info.difficulty),
+			gas_limit: uint(&env_info.gas_limit),
+			number: uint(&env_info.number.into()),
+			timestamp: uint(&env_info.timestamp.into()),
+			previous_hash: format!("{:x}", env_info.last_hashes.first().cloned().unwrap_or_default()),
+		},
+		pre: pre.get().iter().map(|(address, account)| {
+			let account = Account {
+				balance: uint(&account.balance),
+				code: bytes(account.code.as_ref().map_or(&[][..], |code| &code[..])),
+				nonce: uint(&account.nonce),
+				storage: account.storage.iter()
+					.map(|(key, value)| (uint(&(*key).into()), uint(&(*value).into())))
+					.collect(),
+			};
+			(format!("{:x}", address), account)
+		}).collect(),
+		transaction: MultiTransaction {
+			data: vec![bytes(&transaction.data)],
+			gas_limit: vec![uint(&transaction.gas)],
+			gas_price: uint(&transaction.gas_price),
+			nonce: uint(&transaction.nonce),
+			secret: TEST_SECRET.into(),
+			to: match transaction.action {
+				Action::Create => String::new(),
+				Action::Call(ref address) => format!("{:x}", address),
+			},
+			value: vec![uint(&transaction.value)],
+		},
+		post: BTreeMap::new(),
+	};
+
+	// Execute the fixture the same way the state tests runner does.
+	let (state_root, logs) = {
+		let json = serde_json::to_string(&named(name, &test)).map_err(|e| e.to_string())?;
+		let (_, loaded) = ethjson::state::Test::load(json.as_bytes())
+			.map_err(|e| format!("Invalid state test generated: {}", e))?
+			.into_iter()
+			.next()
+			.expect("a single test is serialized; qed");
+
+		let env: EnvInfo = loaded.env.into();
+		let pre: PodState = loaded.pre_state.into();
+		let indexes = ethjson::state::test::PostStateIndexes { data: 0, gas: 0, value: 0 };
+		let transaction: SignedTransaction = loaded.transaction.select(&indexes).into();
+
+		let mut client = EvmTestClient::from_pod_state(&spec, pre).map_err(|e| e.to_string())?;
+		match client.transact(&env, transaction, NoopTracer, NoopVMTracer) {
+			TransactResult::Ok { state_root, logs, .. } => (state_root, logs),
+			TransactResult::Err { state_root, .. } => (state_root, Vec::new()),
+		}
+	};
+
+	test.post.insert(fork.into(), vec![PostState {
+		hash: format!("{:x}", state_root),
+		logs: format!("{:x}", keccak(&*rlp::encode_list::<LogEntry, _>(&logs))),
+		indexes: Indexes { data: 0, gas: 0, value: 0 },
+	}]);
+
+	serde_json::to_string_pretty(&named(name, &test)).map_err(|e| e.to_string())
+}
+
+fn named<'a>(name: &str, test: &'a StateTest) -> BTreeMap<String, &'a StateTest> {
+	let mut map = BTreeMap::new();
+	map.insert(name.to_owned(), test);
+	map
+}
+
+#[cfg(test)]
+mod tests {
+	use std::sync::Arc;
+	use ethcore::client::EnvInfo;
+	use ethcore::pod_state::PodState;
+	use ethereum_types::{H256, U256, Address};
+	use ethjson;
+	use ethkey::{Generator, Random};
+	use serde_json;
+	use transaction::{Action, Transaction};
+	use super::{state_test, TEST_SECRET};
+
+	#[test]
+	fn should_generate_state_test_with_relocated_sender() {
+		let key_pair = Random.generate().unwrap();
+		let contract: Address = 0x1000.into();
+		let author: Address = 0xc0.into();
+
+		// the contract stores the caller at slot 0
+		let pre: PodState = serde_json::from_str::<ethjson::blockchain::State>(&format!(r#"{{
+			"{:x}": {{ "balance": "0x0de0b6b3a7640000", "code": "0x", "nonce": "0x01", "storage": {{}} }},
+			"{:x}": {{ "balance": "0x00", "code": "0x33600055", "nonce": "0x00", "storage": {{ "0x01": "0x02" }} }}
+		}}"#, key_pair.address(), contract)).unwrap().into();
+
+		let transaction = Transaction {
+			nonce: 1.into(),
+			gas_price: 1.into(),
+			gas: 100_000.into(),
+			action: Action::Call(contract),
+			value: 0.into(),
+			data: vec![],
+		}.sign(key_pair.secret(), Some(1));
+
+		let env_info = EnvInfo {
+			number: 5,
+			author,
+			timestamp: 1000,
+			difficulty: 0x20000.into(),
+			last_hashes: Arc::new(vec![H256::from(1)]),
+			gas_used: U256::zero(),
+			base_fee: None,
+			gas_limit: 1_000_000.into(),
+		};
+
+		let json = state_test("test", "Byzantium", &env_info, &transaction, pre).unwrap();
+		let fixture: serde_json::Value = serde_json::from_str(&json).unwrap();
+		let test = &fixture["test"];
+		assert_eq!(test["transaction"]["secretKey"], TEST_SECRET);
+		assert_eq!(test["transaction"]["to"], format!("{:x}", contract));
+		assert_eq!(test["env"]["currentNumber"], "0x5");
+		assert_eq!(test["pre"].as_object().unwrap().len(), 2);
+		assert!(test["pre"].get(format!("{:x}", key_pair.address())).is_none());
+		assert_eq!(test["pre"]["a94f5374fce5edbc8e2a8697c15331677e6ebf0b"]["nonce"], "0x1");
+		assert_eq!(test["pre"][format!("{:x}", contract)]["storage"]["0x1"], "0x2");
+		assert!(test["post"]["Byzantium"][0]["hash"].as_str().unwrap() != format!("{:x}", H256::zero()));
+		assert!(ethjson::state::Test::load(json.as_bytes()).is_ok());
+	}
+}
@@ -0,0 +1,96 @@
+// Copyright 2015-2018 Parity Technologies (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Running state tests in the ethereum/tests format.
+
+use std::fs;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+
+use ethcore::client::{run_state_tests, StateTestResult, StateTestOutcome};
+use ethjson::spec::ForkSpec;
+use serde_json;
+
+#[derive(Debug, PartialEq)]
+pub struct TestRunCmd {
+	pub path: String,
+	pub fork: Option<String>,
+}
+
+pub fn execute(cmd: TestRunCmd) -> Result<String, String> {
+	let fork = match cmd.fork {
+		Some(ref fork) => Some(serde_json::from_str::<ForkSpec>(&format!("\"{}\"", fork)).map_err(|_| format!("Invalid fork: {}", fork))?),
+		None => None,
+	};
+
+	let mut files = Vec::new();
+	collect_files(Path::new(&cmd.path), &mut files)?;
+
+	let mut results = Vec::new();
+	for file in files {
+		let mut data = Vec::new();
+		fs::File::open(&file)
+			.and_then(|mut f| f.read_to_end(&mut data))
+			.map_err(|e| format!("Cannot read {}: {}", file.display(), e))?;
+		let file_results = run_state_tests(&data, |_, test_fork| fork.map_or(true, |fork| fork == *test_fork))
+			.map_err(|e| format!("{}: {}", file.display(), e))?;
+		for result in &file_results {
+			println!("{}", describe(result));
+		}
+		results.extend(file_results);
+	}
+
+	let failed = results.iter().filter(|r| r.is_failure()).count();
+	let skipped = results.iter().filter(|r| r.outcome == StateTestOutcome::Skipped).count();
+	let summary = format!("{} passed, {} failed, {} skipped", results.len() - failed - skipped, failed, skipped);
+	if failed == 0 {
+		Ok(summary)
+	} else {
+		Err(summary)
+	}
+}
+
+/// Find all JSON files at the path.
+fn collect_files(path: &Path, files: &mut Vec<PathBuf>) -> Result<(), String> {
+	if path.is_dir() {
+		let mut entries = fs::read_dir(path)
+			.map_err(|e| format!("Cannot read {}: {}", path.display(), e))?
+			.filter_map(|entry| entry.ok().map(|entry| entry.path()))
+			.collect::<Vec<_>>();
+		entries.sort();
+		for entry in entries {
+			if entry.is_dir() || entry.extension().map_or(false, |ext| ext == "json") {
+				collect_files(&entry, files)?;
+			}
+		}
+	} else {
+		files.push(path.to_path_buf());
+	}
+	Ok(())
+}
+
+fn describe(result: &StateTestResult) -> String {
+	let outcome = match result.outcome {
+		StateTestOutcome::Passed => "ok".to_owned(),
+		StateTestOutcome::Skipped => "skipped (unsupported fork)".to_owned(),
+		StateTestOutcome::Error(ref e) => format!("error: {}", e),
+		StateTestOutcome::Failed { ref expected, ref actual, ref error } => match *error {
+			Some(ref error) => format!("FAILED: state root {:?}, expected {:?} (execution error: {})", actual, expected, error),
+			None => format!("FAILED: state root {:?}, expected {:?}", actual, expected),
+		},
+	};
+	format!("{} | {:?} ({}) ... {}", result.name, result.fork, result.index + 1, outcome)
+}
@@ -28,6 +28,7 @@ use parity_whisper::rpc::{WhisperClient, PoolHandle, FilterManager};
 pub struct Config {
 	pub enabled: bool,
 	pub target_message_pool_size: usize,
+	pub topic_bloom_exchange: bool,
 }
 
 impl Default for Config {
@@ -35,6 +36,7 @@ impl Default for Config {
 		Config {
 			enabled: false,
 			target_message_pool_size: 10 * 1024 * 1024,
+			topic_bloom_exchange: false,
 		}
 	}
 }
@@ -79,13 +81,14 @@ impl RpcFactory {
 
 /// Sets up whisper protocol and RPC handler.
 ///
-/// Will target the given pool size.
+/// Will target the configured pool size.
 #[cfg(not(feature = "ipc"))]
-pub fn setup(target_pool_size: usize, protos: &mut Vec<AttachedProtocol>)
+pub fn setup(config: &Config, protos: &mut Vec<AttachedProtocol>)
 	-> io::Result<Option<RpcFactory>>
 {
 	let manager = Arc::new(FilterManager::new()?);
-	let net = Arc::new(WhisperNetwork::new(target_pool_size, manager.clone()));
+	let net = Arc::new(WhisperNetwork::new(config.target_message_pool_size, manager.clone())
+		.with_topic_bloom_exchange(config.topic_bloom_exchange));
 
 	protos.push(AttachedProtocol {
 		handler: net.clone() as Arc<_>,
@@ -107,7 +110,7 @@ pub fn setup(target_pool_size: usize, protos: &mut Vec<AttachedProtocol>)
 
 // TODO: make it possible to attach generic protocols in IPC.
 #[cfg(feature = "ipc")]
-pub fn setup(_target_pool_size: usize, _protos: &mut Vec<AttachedProtocol>)
+pub fn setup(_config: &Config, _protos: &mut Vec<AttachedProtocol>)
 	-> io::Result<Option<RpcFactory>>
 {
 	Ok(None)
@@ -0,0 +1,239 @@
+// Copyright 2015-2018 Parity Technologies (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+//! GraphQL endpoint (EIP-1767) served by the HTTP JSON-RPC server.
+//!
+//! Queries are accepted as `POST` requests to `/graphql` with a JSON body holding the `query`,
+//! and optionally `variables` and `operationName`. Only queries are supported.
+
+mod parser;
+mod schema;
+
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use ethcore::client::BlockChainClient;
+use http::{hyper, RequestMiddleware, RequestMiddlewareAction};
+use http::hyper::{header, Method, StatusCode};
+use http::hyper::server::{Request as HttpRequest, Response as HttpResponse};
+use jsonrpc_core::futures::{future, Future, Stream};
+use serde_json::{self, Map, Value as Json};
+use sync::SyncProvider;
+
+use self::parser::OperationKind;
+use self::schema::Schema;
+
+/// Path of the GraphQL endpoint.
+pub const GRAPHQL_PATH: &str = "/graphql";
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Request {
+	query: String,
+	#[serde(default)]
+	variables: Option<HashMap<String, Json>>,
+	#[serde(default)]
+	operation_name: Option<String>,
+}
+
+/// GraphQL request handler.
+pub struct GraphQl<C> {
+	schema: Arc<Schema<C>>,
+}
+
+impl<C> Clone for GraphQl<C> {
+	fn clone(&self) -> Self {
+		GraphQl {
+			schema: self.schema.clone(),
+		}
+	}
+}
+
+impl<C: BlockChainClient> GraphQl<C> {
+	/// Creates new handler sharing the given client and sync provider.
+	pub fn new(client: Arc<C>, sync: Arc<SyncProvider>) -> Self {
+		GraphQl {
+			schema: Arc::new(Schema::new(client, sync)),
+		}
+	}
+
+	/// Executes a JSON encoded request and returns the JSON encoded response.
+	pub fn handle_request(&self, request: &[u8]) -> String {
+		let response = match serde_json::from_slice(request) {
+			Ok(request) => self.execute(request),
+			Err(e) => errors(None, format!("Invalid request: {}", e)),
+		};
+		response.to_string()
+	}
+
+	fn execute(&self, request: Request) -> Json {
+		let operations = match parser::parse(&request.query) {
+			Ok(operations) => operations,
+			Err(e) => return errors(None, e),
+		};
+
+		let operation = match request.operation_name {
+			Some(ref name) => operations.iter().find(|operation| operation.name.as_ref() == Some(name)),
+			None if operations.len() == 1 => operations.first(),
+			None => return errors(None, "`operationName` is required for documents with several operations".into()),
+		};
+		let operation = match operation {
+			Some(operation) => operation,
+			None => return errors(None, "Unknown operation".into()),
+		};
+		if operation.kind != OperationKind::Query {
+			return errors(None, "Only queries are supported".into());
+		}
+
+		let mut variables = request.variables.unwrap_or_default();
+		for &(ref name, ref default) in &operation.variables {
+			if let Some(ref default) = *default {
+				if !variables.contains_key(name) {
+					let value = default.to_json(&HashMap::new());
+					variables.insert(name.clone(), value);
+				}
+			}
+		}
+
+		match self.schema.execute(&operation.selection, &variables) {
+			Ok(data) => {
+				let mut response = Map::new();
+				response.insert("data".into(), data);
+				Json::Object(response)
+			},
+			Err(e) => errors(Some(Json::Null), e),
+		}
+	}
+}
+
+/// Response reporting a single error, `data` is only present if execution has started.
+fn errors(data: Option<Json>, message: String) -> Json {
+	let mut error = Map::new();
+	error.insert("message".into(), Json::String(message));
+	let mut response = Map::new();
+	response.insert("errors".into(), Json::Array(vec![Json::Object(error)]));
+	if let Some(data) = data {
+		response.insert("data".into(), data);
+	}
+	Json::Object(response)
+}
+
+impl<C: BlockChainClient + 'static> RequestMiddleware for GraphQl<C> {
+	fn on_request(&self, request: HttpRequest) -> RequestMiddlewareAction {
+		if request.uri().path() != GRAPHQL_PATH {
+			return RequestMiddlewareAction::Proceed {
+				should_continue_on_invalid_cors: false,
+				request,
+			};
+		}
+
+		if *request.method() != Method::Post {
+			return RequestMiddlewareAction::Respond {
+				should_validate_hosts: true,
+				response: Box::new(future::ok(HttpResponse::new().with_status(StatusCode::MethodNotAllowed))),
+			};
+		}
+
+		let handler = self.clone();
+		RequestMiddlewareAction::Respond {
+			should_validate_hosts: true,
+			response: Box::new(request.body().concat2().map(move |body| {
+				HttpResponse::new()
+					.with_header(header::ContentType::json())
+					.with_body(handler.handle_request(&body))
+			}) as Box<Future<Item=HttpResponse, Error=hyper::Error> + Send>,
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use std::sync::Arc;
+	use ethcore::client::{EachBlockWith, TestBlockChainClient};
+	use serde_json::{self, Value as Json};
+	use v1::tests::helpers::{TestSyncProvider, Config};
+	use super::GraphQl;
+
+	fn graphql() -> GraphQl<TestBlockChainClient> {
+		graphql_with_blocks(3)
+	}
+
+	fn graphql_with_blocks(count: usize) -> GraphQl<TestBlockChainClient> {
+		let client = Arc::new(TestBlockChainClient::new());
+		client.add_blocks(count, EachBlockWith::Transaction);
+		let sync = Arc::new(TestSyncProvider::new(Config {
+			network_id: 3,
+			num_peers: 120,
+		}));
+		GraphQl::new(client, sync)
+	}
+
+	fn query(graphql: &GraphQl<TestBlockChainClient>, request: &str) -> Json {
+		serde_json::from_str(&graphql.handle_request(request.as_bytes())).unwrap()
+	}
+
+	#[test]
+	fn should_resolve_nested_fields() {
+		let graphql = graphql();
+		let response = query(&graphql, r#"{"query": "{ block(number: 2) { number parent { number } transactionCount transactions { index from { address } } } }"}"#);
+
+		let block = &response["data"]["block"];
+		assert_eq!(block["number"], 2);
+		assert_eq!(block["parent"]["number"], 1);
+		assert_eq!(block["transactionCount"], 1);
+		assert_eq!(block["transactions"][0]["index"], 0);
+		assert!(block["transactions"][0]["from"]["address"].is_string());
+	}
+
+	#[test]
+	fn should_use_variables_and_aliases() {
+		let graphql = graphql();
+		let response = query(&graphql, r#"{
+			"query": "query Range($from: Long!, $to: Long = 2) { range: blocks(from: $from, to: $to) { number } }",
+			"variables": { "from": 1 }
+		}"#);
+
+		let expected: Json = serde_json::from_str(r#"[{ "number": 1 }, { "number": 2 }]"#).unwrap();
+		assert_eq!(response["data"]["range"], expected);
+	}
+
+	#[test]
+	fn should_report_errors() {
+		let graphql = graphql();
+
+		let response = query(&graphql, r#"{"query": "{ block { unknown } }"}"#);
+		assert_eq!(response["data"], Json::Null);
+		assert_eq!(response["errors"][0]["message"], "Unknown field `unknown` on type `Block`");
+
+		let response = query(&graphql, r#"{"query": "mutation { sendRawTransaction(data: \"0x\") }"}"#);
+		assert_eq!(response["errors"][0]["message"], "Only queries are supported");
+	}
+
+	#[test]
+	fn should_limit_query_complexity() {
+		let graphql = graphql_with_blocks(1100);
+
+		let nested = format!("{{ block {{ {}number{} }} }}", "parent { ".repeat(100), " }".repeat(100));
+		let response = query(&graphql, &format!(r#"{{"query": "{}"}}"#, nested));
+		assert_eq!(response["errors"][0]["message"], "Document is nested deeper than 32 levels");
+
+		// each alias resolves a range of 1024 blocks
+		let ranges: String = (0..100).map(|i| format!("r{}: blocks(from: 0, to: 1023) {{ number }} ", i)).collect();
+		let response = query(&graphql, &format!(r#"{{"query": "{{ {}}}"}}"#, ranges));
+		assert_eq!(response["data"], Json::Null);
+		assert_eq!(response["errors"][0]["message"], "Query resolves more than 100000 fields");
+	}
+}
@@ -0,0 +1,642 @@
+// Copyright 2015-2018 Parity Technologies (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Parser of GraphQL query documents.
+//!
+//! Fragments are expanded while parsing: the schema has no interfaces or unions, so a spread
+//! is equivalent to writing the fields of the fragment in its place.
+
+use std::collections::{BTreeMap, HashMap};
+use std::iter::Peekable;
+use std::str::Chars;
+
+use serde_json::Value as Json;
+
+/// Maximal nesting of selection sets, fragment spreads and input values.
+pub const MAX_DEPTH: usize = 32;
+/// Maximal number of selections of a query, counting each expansion of a fragment.
+pub const MAX_SELECTIONS: usize = 10_000;
+
+/// Input value of an argument.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Value {
+	Null,
+	Int(i64),
+	Float(f64),
+	String(String),
+	Boolean(bool),
+	Enum(String),
+	List(Vec<Value>),
+	Object(BTreeMap<String, Value>),
+	Variable(String),
+}
+
+impl Value {
+	/// Converts the value to JSON, replacing variables with their values.
+	pub fn to_json(&self, variables: &HashMap<String, Json>) -> Json {
+		match *self {
+			Value::Null => Json::Null,
+			Value::Int(i) => Json::from(i),
+			Value::Float(f) => Json::from(f),
+			Value::String(ref s) | Value::Enum(ref s) => Json::String(s.clone()),
+			Value::Boolean(b) => Json::Bool(b),
+			Value::List(ref list) => Json::Array(list.iter().map(|v| v.to_json(variables)).collect()),
+			Value::Object(ref object) => Json::Object(object.iter().map(|(k, v)| (k.clone(), v.to_json(variables))).collect()),
+			Value::Variable(ref name) => variables.get(name).cloned().unwrap_or(Json::Null),
+		}
+	}
+}
+
+/// Field selected from an object.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Field {
+	/// Name of the field in the response.
+	pub alias: Option<String>,
+	pub name: String,
+	pub arguments: Vec<(String, Value)>,
+	pub directives: Vec<Directive>,
+	pub selection: Vec<Field>,
+}
+
+impl Field {
+	/// Key of the field in the response object.
+	pub fn response_key(&self) -> &str {
+		self.alias.as_ref().unwrap_or(&self.name)
+	}
+}
+
+/// Directive attached to a field, like `@include(if: $x)`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Directive {
+	pub name: String,
+	pub arguments: Vec<(String, Value)>,
+}
+
+/// Kind of the operation.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum OperationKind {
+	Query,
+	Mutation,
+	Subscription,
+}
+
+/// Single operation of a document.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Operation<S = Field> {
+	pub kind: OperationKind,
+	pub name: Option<String>,
+	/// Declared variables with their default values.
+	pub variables: Vec<(String, Option<Value>)>,
+	pub selection: Vec<S>,
+}
+
+/// Parses a document and returns its operations.
+pub fn parse(source: &str) -> Result<Vec<Operation>, String> {
+	let tokens = tokenize(source)?;
+	let mut parser = Parser { tokens, position: 0, depth: 0 };
+
+	let mut operations = Vec::new();
+	let mut fragments = HashMap::new();
+	while parser.peek().is_some() {
+		if parser.peek_name("fragment") {
+			let (name, selection) = parser.fragment()?;
+			fragments.insert(name, selection);
+		} else {
+			operations.push(parser.operation()?);
+		}
+	}
+
+	if operations.is_empty() {
+		return Err("Document does not contain any operation".into());
+	}
+
+	operations.into_iter()
+		.map(|operation| {
+			let selection = Expansion { fragments: &fragments, stack: Vec::new(), depth: 0, selections: 0 }
+				.expand(&operation.selection)?;
+			Ok(Operation {
+				kind: operation.kind,
+				name: operation.name,
+				variables: operation.variables,
+				selection,
+			})
+		})
+		.collect()
+}
+
+/// Selection as parsed, before fragments are expanded.
+#[derive(Debug, Clone)]
+enum Selection {
+	Field(Field, Vec<Selection>),
+	Spread(String, Vec<Directive>),
+	Inline(Vec<Directive>, Vec<Selection>),
+}
+
+/// Expands fragments of a single operation.
+///
+/// Spreads may be nested deeply or repeated, so the depth of the expanded selection and
+/// the number of expanded selections are limited.
+struct Expansion<'a> {
+	fragments: &'a HashMap<String, Vec<Selection>>,
+	/// Fragments being expanded.
+	stack: Vec<String>,
+	depth: usize,
+	selections: usize,
+}
+
+impl<'a> Expansion<'a> {
+	fn expand(&mut self, selection: &'a [Selection]) -> Result<Vec<Field>, String> {
+		if selection.is_empty() {
+			return Ok(Vec::new());
+		}
+
+		self.depth += 1;
+		if self.depth > MAX_DEPTH {
+			return Err(format!("Query is nested deeper than {} levels", MAX_DEPTH));
+		}
+
+		let mut fields = Vec::new();
+		for item in selection {
+			self.selections += 1;
+			if self.selections > MAX_SELECTIONS {
+				return Err(format!("Query has more than {} selections", MAX_SELECTIONS));
+			}
+
+			match *item {
+				Selection::Field(ref field, ref selection) => {
+					let mut field = field.clone();
+					field.selection = self.expand(selection)?;
+					fields.push(field);
+				},
+				Selection::Spread(ref name, ref directives) => {
+					if self.stack.contains(name) {
+						return Err(format!("Fragment `{}` spreads itself", name));
+					}
+					let fragments = self.fragments;
+					let fragment = fragments.get(name).ok_or_else(|| format!("Unknown fragment `{}`", name))?;
+					self.stack.push(name.clone());
+					let expanded = self.expand(fragment)?;
+					self.stack.pop();
+					fields.extend(with_directives(expanded, directives));
+				},
+				Selection::Inline(ref directives, ref selection) => {
+					let expanded = self.expand(selection)?;
+					fields.extend(with_directives(expanded, directives));
+				},
+			}
+		}
+
+		self.depth -= 1;
+		Ok(fields)
+	}
+}
+
+/// Directives of a fragment apply to every field it contributes.
+fn with_directives(fields: Vec<Field>, directives: &[Directive]) -> Vec<Field> {
+	fields.into_iter()
+		.map(|mut field| {
+			field.directives.extend(directives.iter().cloned());
+			field
+		})
+		.collect()
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+	Punctuator(char),
+	Spread,
+	Name(String),
+	Int(i64),
+	Float(f64),
+	String(String),
+}
+
+fn tokenize(source: &str) -> Result<Vec<Token>, String> {
+	let mut tokens = Vec::new();
+	let mut chars = source.chars().peekable();
+	while let Some(c) = chars.next() {
+		match c {
+			' ' | '\t' | '\n' | '\r' | ',' | '\u{feff}' => {},
+			'#' => {
+				while chars.peek().map_or(false, |c| *c != '\n' && *c != '\r') {
+					chars.next();
+				}
+			},
+			'!' | '$' | '(' | ')' | ':' | '=' | '@' | '[' | ']' | '{' | '}' | '|' => tokens.push(Token::Punctuator(c)),
+			'.' => {
+				if chars.next() != Some('.') || chars.next() != Some('.') {
+					return Err("Unexpected `.`".into());
+				}
+				tokens.push(Token::Spread);
+			},
+			'"' => tokens.push(Token::String(string(&mut chars)?)),
+			'-' | '0'...'9' => tokens.push(number(c, &mut chars)?),
+			'_' | 'a'...'z' | 'A'...'Z' => {
+				let mut name = c.to_string();
+				while let Some(&c) = chars.peek() {
+					if c == '_' || c.is_ascii_alphanumeric() {
+						name.push(c);
+						chars.next();
+					} else {
+						break;
+					}
+				}
+				tokens.push(Token::Name(name));
+			},
+			c => return Err(format!("Unexpected character `{}`", c)),
+		}
+	}
+	Ok(tokens)
+}
+
+fn string(chars: &mut Peekable<Chars>) -> Result<String, String> {
+	let mut value = String::new();
+	if chars.peek() == Some(&'"') {
+		chars.next();
+		if chars.peek() != Some(&'"') {
+			// empty string
+			return Ok(value);
+		}
+		chars.next();
+		// block string
+		loop {
+			match chars.next() {
+				Some('"') if chars.peek() == Some(&'"') => {
+					chars.next();
+					if chars.peek() == Some(&'"') {
+						chars.next();
+						return Ok(value);
+					}
+					value.push_str("\"\"");
+				},
+				Some(c) => value.push(c),
+				None => return Err("Unterminated block string".into()),
+			}
+		}
+	}
+
+	loop {
+		match chars.next() {
+			Some('"') => return Ok(value),
+			Some('\\') => match chars.next() {
+				Some('"') => value.push('"'),
+				Some('\\') => value.push('\\'),
+				Some('/') => value.push('/'),
+				Some('b') => value.push('\u{8}'),
+				Some('f') => value.push('\u{c}'),
+				Some('n') => value.push('\n'),
+				Some('r') => value.push('\r'),
+				Some('t') => value.push('\t'),
+				Some('u') => {
+					let code: String = chars.by_ref().take(4).collect();
+					let c = u32::from_str_radix(&code, 16).ok()
+						.and_then(::std::char::from_u32)
+						.ok_or_else(|| format!("Invalid unicode escape `\\u{}`", code))?;
+					value.push(c);
+				},
+				_ => return Err("Invalid escape sequence".into()),
+			},
+			Some('\n') | Some('\r') | None => return Err("Unterminated string".into()),
+			Some(c) => value.push(c),
+		}
+	}
+}
+
+fn number(first: char, chars: &mut Peekable<Chars>) -> Result<Token, String> {
+	let mut literal = first.to_string();
+	let mut float = false;
+	while let Some(&c) = chars.peek() {
+		match c {
+			'0'...'9' => {},
+			'.' | 'e' | 'E' | '+' => float = true,
+			'-' if literal.ends_with('e') || literal.ends_with('E') => {},
+			_ => break,
+		}
+		literal.push(c);
+		chars.next();
+	}
+
+	if float {
+		literal.parse().map(Token::Float).map_err(|_| format!("Invalid number `{}`", literal))
+	} else {
+		literal.parse().map(Token::Int).map_err(|_| format!("Invalid number `{}`", literal))
+	}
+}
+
+struct Parser {
+	tokens: Vec<Token>,
+	position: usize,
+	/// Nesting of the selection set, value or type being parsed.
+	depth: usize,
+}
+
+impl Parser {
+	fn peek(&self) -> Option<&Token> {
+		self.tokens.get(self.position)
+	}
+
+	fn peek_punctuator(&self, c: char) -> bool {
+		self.peek() == Some(&Token::Punctuator(c))
+	}
+
+	fn peek_name(&self, name: &str) -> bool {
+		match self.peek() {
+			Some(&Token::Name(ref n)) => n == name,
+			_ => false,
+		}
+	}
+
+	fn next(&mut self) -> Result<Token, String> {
+		let token = self.tokens.get(self.position).cloned().ok_or_else(|| "Unexpected end of document".to_owned())?;
+		self.position += 1;
+		Ok(token)
+	}
+
+	fn expect(&mut self, c: char) -> Result<(), String> {
+		match self.next()? {
+			Token::Punctuator(p) if p == c => Ok(()),
+			token => Err(format!("Expected `{}`, found {:?}", c, token)),
+		}
+	}
+
+	fn skip(&mut self, c: char) -> bool {
+		if self.peek_punctuator(c) {
+			self.position += 1;
+			true
+		} else {
+			false
+		}
+	}
+
+	fn enter(&mut self) -> Result<(), String> {
+		if self.depth == MAX_DEPTH {
+			return Err(format!("Document is nested deeper than {} levels", MAX_DEPTH));
+		}
+		self.depth += 1;
+		Ok(())
+	}
+
+	fn leave(&mut self) {
+		self.depth -= 1;
+	}
+
+	fn name(&mut self) -> Result<String, String> {
+		match self.next()? {
+			Token::Name(name) => Ok(name),
+			token => Err(format!("Expected a name, found {:?}", token)),
+		}
+	}
+
+	fn operation(&mut self) -> Result<Operation<Selection>, String> {
+		if self.peek_punctuator('{') {
+			return Ok(Operation {
+				kind: OperationKind::Query,
+				name: None,
+				variables: Vec::new(),
+				selection: self.selection_set()?,
+			});
+		}
+
+		let kind = match self.name()?.as_ref() {
+			"query" => OperationKind::Query,
+			"mutation" => OperationKind::Mutation,
+			"subscription" => OperationKind::Subscription,
+			other => return Err(format!("Unknown operation type `{}`", other)),
+		};
+		let name = match self.peek() {
+			Some(&Token::Name(_)) => Some(self.name()?),
+			_ => None,
+		};
+
+		let mut variables = Vec::new();
+		if self.skip('(') {
+			while !self.skip(')') {
+				self.expect('$')?;
+				let variable = self.name()?;
+				self.expect(':')?;
+				self.variable_type()?;
+				let default = if self.skip('=') { Some(self.value(true)?) } else { None };
+				variables.push((variable, default));
+			}
+		}
+		self.directives()?;
+
+		Ok(Operation {
+			kind,
+			name,
+			variables,
+			selection: self.selection_set()?,
+		})
+	}
+
+	/// Skips a variable type, arguments are checked when the query is executed.
+	fn variable_type(&mut self) -> Result<(), String> {
+		self.enter()?;
+		if self.skip('[') {
+			self.variable_type()?;
+			self.expect(']')?;
+		} else {
+			self.name()?;
+		}
+		self.skip('!');
+		self.leave();
+		Ok(())
+	}
+
+	fn fragment(&mut self) -> Result<(String, Vec<Selection>), String> {
+		self.name()?;
+		let name = self.name()?;
+		if self.name()? != "on" {
+			return Err(format!("Expected type condition of fragment `{}`", name));
+		}
+		self.name()?;
+		self.directives()?;
+		Ok((name, self.selection_set()?))
+	}
+
+	fn selection_set(&mut self) -> Result<Vec<Selection>, String> {
+		self.enter()?;
+		self.expect('{')?;
+		let mut selection = Vec::new();
+		while !self.skip('}') {
+			if self.peek() == Some(&Token::Spread) {
+				self.position += 1;
+				if self.peek_name("on") {
+					self.position += 1;
+					self.name()?;
+				}
+				if self.peek_punctuator('{') || self.peek_punctuator('@') {
+					let directives = self.directives()?;
+					selection.push(Selection::Inline(directives, self.selection_set()?));
+				} else {
+					let name = self.name()?;
+					selection.push(Selection::Spread(name, self.directives()?));
+				}
+				continue;
+			}
+
+			let mut name = self.name()?;
+			let mut alias = None;
+			if self.skip(':') {
+				alias = Some(name);
+				name = self.name()?;
+			}
+			let arguments = self.arguments()?;
+			let directives = self.directives()?;
+			let nested = if self.peek_punctuator('{') { self.selection_set()? } else { Vec::new() };
+			selection.push(Selection::Field(Field { alias, name, arguments, directives, selection: Vec::new() }, nested));
+		}
+		self.leave();
+		Ok(selection)
+	}
+
+	fn arguments(&mut self) -> Result<Vec<(String, Value)>, String> {
+		let mut arguments = Vec::new();
+		if self.skip('(') {
+			while !self.skip(')') {
+				let name = self.name()?;
+				self.expect(':')?;
+				arguments.push((name, self.value(false)?));
+			}
+		}
+		Ok(arguments)
+	}
+
+	fn directives(&mut self) -> Result<Vec<Directive>, String> {
+		let mut directives = Vec::new();
+		while self.skip('@') {
+			let name = self.name()?;
+			directives.push(Directive { name, arguments: self.arguments()? });
+		}
+		Ok(directives)
+	}
+
+	fn value(&mut self, constant: bool) -> Result<Value, String> {
+		self.enter()?;
+		let value = match self.next()? {
+			Token::Punctuator('$') if !constant => Value::Variable(self.name()?),
+			Token::Punctuator('[') => {
+				let mut list = Vec::new();
+				while !self.skip(']') {
+					list.push(self.value(constant)?);
+				}
+				Value::List(list)
+			},
+			Token::Punctuator('{') => {
+				let mut object = BTreeMap::new();
+				while !self.skip('}') {
+					let name = self.name()?;
+					self.expect(':')?;
+					object.insert(name, self.value(constant)?);
+				}
+				Value::Object(object)
+			},
+			Token::Int(i) => Value::Int(i),
+			Token::Float(f) => Value::Float(f),
+			Token::String(s) => Value::String(s),
+			Token::Name(ref name) if name == "true" => Value::Boolean(true),
+			Token::Name(ref name) if name == "false" => Value::Boolean(false),
+			Token::Name(ref name) if name == "null" => Value::Null,
+			Token::Name(name) => Value::Enum(name),
+			token => return Err(format!("Expected a value, found {:?}", token)),
+		};
+		self.leave();
+		Ok(value)
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::{parse, Field, OperationKind, Value, MAX_DEPTH};
+
+	fn field(name: &str, selection: Vec<Field>) -> Field {
+		Field { alias: None, name: name.into(), arguments: vec![], directives: vec![], selection }
+	}
+
+	#[test]
+	fn should_parse_shorthand_query() {
+		let operations = parse(r#"{ block(number: 5) { hash b: number } }"#).unwrap();
+		assert_eq!(operations.len(), 1);
+		assert_eq!(operations[0].kind, OperationKind::Query);
+
+		let block = &operations[0].selection[0];
+		assert_eq!(block.arguments, vec![("number".into(), Value::Int(5))]);
+		assert_eq!(block.selection[0], field("hash", vec![]));
+		assert_eq!(block.selection[1].alias, Some("b".into()));
+		assert_eq!(block.selection[1].response_key(), "b");
+	}
+
+	#[test]
+	fn should_parse_variables_and_fragments() {
+		let operations = parse(r#"
+			query Blocks($from: Long!, $to: Long = 10) {
+				blocks(from: $from, to: $to) { ...BlockFields }
+			}
+
+			fragment BlockFields on Block {
+				number
+				transactions { hash }
+			}
+		"#).unwrap();
+
+		let operation = &operations[0];
+		assert_eq!(operation.name, Some("Blocks".into()));
+		assert_eq!(operation.variables, vec![("from".into(), None), ("to".into(), Some(Value::Int(10)))]);
+
+		let blocks = &operation.selection[0];
+		assert_eq!(blocks.arguments[0], ("from".into(), Value::Variable("from".into())));
+		assert_eq!(blocks.selection, vec![
+			field("number", vec![]),
+			field("transactions", vec![field("hash", vec![])]),
+		]);
+	}
+
+	#[test]
+	fn should_reject_invalid_documents() {
+		assert!(parse("{ block(number: ) { hash } }").is_err());
+		assert!(parse("{ block { ...Missing } }").is_err());
+		assert!(parse("query { block { ...A } } fragment A on Block { ...A }").is_err());
+		assert!(parse("fragment A on Block { hash }").is_err());
+	}
+
+	#[test]
+	fn should_reject_deeply_nested_documents() {
+		let nested = |depth: usize| format!("{{ {}hash{} }}", "block { ".repeat(depth), " }".repeat(depth));
+		assert!(parse(&nested(MAX_DEPTH - 1)).is_ok());
+		assert_eq!(parse(&nested(100_000)), Err(format!("Document is nested deeper than {} levels", MAX_DEPTH)));
+
+		let list = format!("{{ block(number: {}1{}) {{ hash }} }}", "[".repeat(100_000), "]".repeat(100_000));
+		assert!(parse(&list).is_err());
+
+		// fragments spreading each other don't nest in the document itself
+		let mut chain = "{ block { ...F0 } }".to_owned();
+		for i in 0..100 {
+			chain.push_str(&format!(" fragment F{} on Block {{ ...F{} }}", i, i + 1));
+		}
+		chain.push_str(" fragment F100 on Block { hash }");
+		assert_eq!(parse(&chain), Err(format!("Query is nested deeper than {} levels", MAX_DEPTH)));
+	}
+
+	#[test]
+	fn should_reject_fragment_bombs() {
+		let mut bomb = "{ block { ...F0 } }".to_owned();
+		for i in 0..20 {
+			bomb.push_str(&format!(" fragment F{} on Block {{ ...F{} ...F{} }}", i, i + 1, i + 1));
+		}
+		bomb.push_str(" fragment F20 on Block { hash }");
+		assert_eq!(parse(&bomb), Err("Query has more than 10000 selections".into()));
+	}
+}
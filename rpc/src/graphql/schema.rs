@@ -0,0 +1,403 @@
+// Copyright 2015-2018 Parity Technologies (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Resolvers of the EIP-1767 schema.
+//!
+//! Supported root fields are `block`, `blocks`, `transaction`, `syncing` and `protocolVersion`.
+//! `Long` and `Int` scalars are returned as JSON numbers, `BigInt` and byte scalars as
+//! `0x`-prefixed hex strings.
+
+use std::cell::Cell;
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use ethcore::client::{BlockChainClient, BlockId, TransactionId};
+use ethcore::encoded;
+use ethcore::log_entry::LocalizedLogEntry;
+use ethcore::receipt::TransactionOutcome;
+use ethereum_types::{Address, H256};
+use serde::Serialize;
+use serde_json::{self, Map, Value as Json};
+use sync::{SyncProvider, SyncStatus};
+use transaction::{Action, LocalizedTransaction};
+
+use v1::helpers::block_import::is_major_importing;
+use v1::types::{self, Bytes};
+use super::parser::Field;
+
+/// Maximal number of blocks returned by a single `blocks` field.
+const MAX_BLOCKS_RANGE: u64 = 1024;
+/// Maximal number of fields resolved by a single query, lists resolve their selection once per item.
+const MAX_RESOLVED_FIELDS: usize = 100_000;
+
+/// Object of the schema, resolved to JSON through a selection set.
+enum Node {
+	Query,
+	Block(encoded::Block),
+	Transaction(LocalizedTransaction),
+	Log(LocalizedLogEntry),
+	Account(Address, BlockId),
+	SyncState(SyncStatus),
+}
+
+impl Node {
+	fn type_name(&self) -> &'static str {
+		match *self {
+			Node::Query => "Query",
+			Node::Block(_) => "Block",
+			Node::Transaction(_) => "Transaction",
+			Node::Log(_) => "Log",
+			Node::Account(..) => "Account",
+			Node::SyncState(_) => "SyncState",
+		}
+	}
+}
+
+/// Result of a single field.
+enum Resolved {
+	Scalar(Json),
+	Object(Option<Node>),
+	List(Vec<Node>),
+}
+
+fn scalar<T: Serialize>(value: T) -> Resolved {
+	Resolved::Scalar(serde_json::to_value(value).expect("scalars are always serializable; qed"))
+}
+
+/// Executes queries against the chain.
+pub struct Schema<C> {
+	client: Arc<C>,
+	sync: Arc<SyncProvider>,
+}
+
+impl<C: BlockChainClient> Schema<C> {
+	/// Creates new schema backed by the given client.
+	pub fn new(client: Arc<C>, sync: Arc<SyncProvider>) -> Self {
+		Schema {
+			client,
+			sync,
+		}
+	}
+
+	/// Resolves the root selection set of a query.
+	pub fn execute(&self, selection: &[Field], variables: &HashMap<String, Json>) -> Result<Json, String> {
+		self.select(&Node::Query, selection, variables, &Cell::new(0)).map(Json::Object)
+	}
+
+	fn select(&self, node: &Node, selection: &[Field], variables: &HashMap<String, Json>, resolved: &Cell<usize>) -> Result<Map<String, Json>, String> {
+		let mut result = Map::new();
+		for field in selection {
+			if !included(field, variables)? {
+				continue;
+			}
+
+			resolved.set(resolved.get() + 1);
+			if resolved.get() > MAX_RESOLVED_FIELDS {
+				return Err(format!("Query resolves more than {} fields", MAX_RESOLVED_FIELDS));
+			}
+
+			let value = if field.name == "__typename" {
+				Json::String(node.type_name().into())
+			} else {
+				let args = Arguments { field, variables };
+				match self.resolve(node, &args)? {
+					Resolved::Scalar(value) => {
+						if !field.selection.is_empty() {
+							return Err(format!("Field `{}` of a scalar type can't have a selection", field.name));
+						}
+						value
+					},
+					Resolved::Object(None) => Json::Null,
+					Resolved::Object(Some(object)) => Json::Object(self.select_object(&object, field, variables, resolved)?),
+					Resolved::List(objects) => Json::Array(objects.iter()
+						.map(|object| self.select_object(object, field, variables, resolved).map(Json::Object))
+						.collect::<Result<_, _>>()?),
+				}
+			};
+
+			merge(&mut result, field.response_key(), value);
+		}
+		Ok(result)
+	}
+
+	fn select_object(&self, node: &Node, field: &Field, variables: &HashMap<String, Json>, resolved: &Cell<usize>) -> Result<Map<String, Json>, String> {
+		if field.selection.is_empty() {
+			return Err(format!("Field `{}` of type `{}` must have a selection", field.name, node.type_name()));
+		}
+		self.select(node, &field.selection, variables, resolved)
+	}
+
+	fn resolve(&self, node: &Node, args: &Arguments) -> Result<Resolved, String> {
+		match *node {
+			Node::Query => self.query(args),
+			Node::Block(ref block) => self.block(block, args),
+			Node::Transaction(ref transaction) => self.transaction(transaction, args),
+			Node::Log(ref log) => self.log(log, args),
+			Node::Account(ref address, block) => self.account(address, block, args),
+			Node::SyncState(ref status) => self.sync_state(status, args),
+		}
+	}
+
+	fn query(&self, args: &Arguments) -> Result<Resolved, String> {
+		Ok(match args.field.name.as_ref() {
+			"block" => {
+				let id = match (args.long("number")?, args.bytes32("hash")?) {
+					(Some(_), Some(_)) => return Err("Only one of `number` and `hash` can be given".into()),
+					(Some(number), None) => BlockId::Number(number),
+					(None, Some(hash)) => BlockId::Hash(hash),
+					(None, None) => BlockId::Latest,
+				};
+				Resolved::Object(self.client.block(id).map(Node::Block))
+			},
+			"blocks" => {
+				let best = self.client.chain_info().best_block_number;
+				let from = args.long("from")?.ok_or("Argument `from` is required")?;
+				let to = args.long("to")?.unwrap_or(best).min(best);
+				if to >= from && to - from >= MAX_BLOCKS_RANGE {
+					return Err(format!("At most {} blocks can be requested at once", MAX_BLOCKS_RANGE));
+				}
+				Resolved::List((from..to.saturating_add(1))
+					.filter_map(|number| self.client.block(BlockId::Number(number)))
+					.map(Node::Block)
+					.collect())
+			},
+			"transaction" => {
+				let hash = args.bytes32("hash")?.ok_or("Argument `hash` is required")?;
+				Resolved::Object(self.client.transaction(TransactionId::Hash(hash)).map(Node::Transaction))
+			},
+			"syncing" => {
+				let status = self.sync.status();
+				if is_major_importing(Some(status.state), self.client.queue_info()) {
+					Resolved::Object(Some(Node::SyncState(status)))
+				} else {
+					Resolved::Object(None)
+				}
+			},
+			"protocolVersion" => scalar(self.sync.status().protocol_version),
+			other => return Err(unknown_field("Query", other)),
+		})
+	}
+
+	fn block(&self, block: &encoded::Block, args: &Arguments) -> Result<Resolved, String> {
+		let header = block.header_view();
+		// Ethash seals blocks with the mix hash followed by the nonce.
+		let seal_field = |index: usize| -> Result<Option<Bytes>, String> {
+			let seal = header.seal();
+			if seal.len() != 2 {
+				return Ok(None);
+			}
+			let field = ::rlp::Rlp::new(&seal[index]).data().map_err(|e| e.to_string())?.to_vec();
+			Ok(Some(Bytes::new(field)))
+		};
+		Ok(match args.field.name.as_ref() {
+			"number" => scalar(header.number()),
+			"hash" => scalar(types::H256::from(header.hash())),
+			"parent" => Resolved::Object(match header.number() {
+				0 => None,
+				_ => self.client.block(BlockId::Hash(header.parent_hash())).map(Node::Block),
+			}),
+			"mixHash" => scalar(seal_field(0)?),
+			"nonce" => scalar(seal_field(1)?),
+			"transactionsRoot" => scalar(types::H256::from(header.transactions_root())),
+			"transactionCount" => scalar(block.transactions_count()),
+			"stateRoot" => scalar(types::H256::from(header.state_root())),
+			"receiptsRoot" => scalar(types::H256::from(header.receipts_root())),
+			"miner" => Resolved::Object(Some(Node::Account(header.author(), args.block_or(BlockId::Hash(header.hash()))?))),
+			"extraData" => scalar(Bytes::new(header.extra_data())),
+			"gasLimit" => scalar(header.gas_limit().low_u64()),
+			"gasUsed" => scalar(header.gas_used().low_u64()),
+			"timestamp" => scalar(types::U256::from(header.timestamp())),
+			"logsBloom" => scalar(types::H2048::from(header.log_bloom())),
+			"difficulty" => scalar(types::U256::from(header.difficulty())),
+			"totalDifficulty" => scalar(self.client.block_total_difficulty(BlockId::Hash(header.hash())).map(types::U256::from)),
+			"ommerCount" => scalar(block.uncles_count()),
+			"ommerHash" => scalar(types::H256::from(header.uncles_hash())),
+			"transactions" => Resolved::List(block.view().localized_transactions().into_iter().map(Node::Transaction).collect()),
+			"transactionAt" => {
+				let index = args.long("index")?.ok_or("Argument `index` is required")?;
+				Resolved::Object(block.view().localized_transaction_at(index as usize).map(Node::Transaction))
+			},
+			"account" => {
+				let address = args.address("address")?.ok_or("Argument `address` is required")?;
+				Resolved::Object(Some(Node::Account(address, BlockId::Hash(header.hash()))))
+			},
+			other => return Err(unknown_field("Block", other)),
+		})
+	}
+
+	fn transaction(&self, transaction: &LocalizedTransaction, args: &Arguments) -> Result<Resolved, String> {
+		let at_block = BlockId::Hash(transaction.block_hash);
+		let receipt = || self.client.transaction_receipt(TransactionId::Hash(transaction.signed.hash()));
+		Ok(match args.field.name.as_ref() {
+			"hash" => scalar(types::H256::from(transaction.signed.hash())),
+			"nonce" => scalar(transaction.signed.nonce.low_u64()),
+			"index" => scalar(transaction.transaction_index),
+			"from" => Resolved::Object(Some(Node::Account(transaction.clone().sender(), args.block_or(at_block)?))),
+			"to" => Resolved::Object(match transaction.signed.action {
+				Action::Call(to) => Some(Node::Account(to, args.block_or(at_block)?)),
+				Action::Create => None,
+			}),
+			"value" => scalar(types::U256::from(transaction.signed.value)),
+			"gasPrice" => scalar(types::U256::from(transaction.signed.gas_price)),
+			"gas" => scalar(transaction.signed.gas.low_u64()),
+			"inputData" => scalar(Bytes::new(transaction.signed.data.clone())),
+			"block" => Resolved::Object(self.client.block(at_block).map(Node::Block)),
+			"status" => scalar(receipt().and_then(|receipt| match receipt.outcome {
+				TransactionOutcome::StatusCode(code) => Some(code),
+				_ => None,
+			})),
+			"gasUsed" => scalar(receipt().map(|receipt| receipt.gas_used.low_u64())),
+			"cumulativeGasUsed" => scalar(receipt().map(|receipt| receipt.cumulative_gas_used.low_u64())),
+			"createdContract" => {
+				let block = args.block_or(at_block)?;
+				Resolved::Object(receipt()
+					.and_then(|receipt| receipt.contract_address)
+					.map(|address| Node::Account(address, block)))
+			},
+			"logs" => match receipt() {
+				Some(receipt) => Resolved::List(receipt.logs.into_iter().map(Node::Log).collect()),
+				None => Resolved::Scalar(Json::Null),
+			},
+			"r" => scalar(types::U256::from(transaction.signature().r())),
+			"s" => scalar(types::U256::from(transaction.signature().s())),
+			"v" => scalar(types::U256::from(transaction.original_v())),
+			other => return Err(unknown_field("Transaction", other)),
+		})
+	}
+
+	fn log(&self, log: &LocalizedLogEntry, args: &Arguments) -> Result<Resolved, String> {
+		Ok(match args.field.name.as_ref() {
+			"index" => scalar(log.log_index),
+			"account" => Resolved::Object(Some(Node::Account(log.entry.address, args.block_or(BlockId::Hash(log.block_hash))?))),
+			"topics" => scalar(log.entry.topics.iter().cloned().map(types::H256::from).collect::<Vec<_>>()),
+			"data" => scalar(Bytes::new(log.entry.data.clone())),
+			"transaction" => Resolved::Object(self.client.transaction(TransactionId::Hash(log.transaction_hash)).map(Node::Transaction)),
+			other => return Err(unknown_field("Log", other)),
+		})
+	}
+
+	fn account(&self, address: &Address, block: BlockId, args: &Arguments) -> Result<Resolved, String> {
+		Ok(match args.field.name.as_ref() {
+			"address" => scalar(types::H160::from(*address)),
+			"balance" => scalar(self.client.balance(address, block.into()).map(types::U256::from)),
+			"transactionCount" => scalar(self.client.nonce(address, block).map(|nonce| nonce.low_u64())),
+			"code" => scalar(self.client.code(address, block.into()).map(|code| Bytes::new(code.unwrap_or_default()))),
+			"storage" => {
+				let slot = args.bytes32("slot")?.ok_or("Argument `slot` is required")?;
+				scalar(self.client.storage_at(address, &slot, block.into()).map(types::H256::from))
+			},
+			other => return Err(unknown_field("Account", other)),
+		})
+	}
+
+	fn sync_state(&self, status: &SyncStatus, args: &Arguments) -> Result<Resolved, String> {
+		Ok(match args.field.name.as_ref() {
+			"startingBlock" => scalar(status.start_block_number),
+			"currentBlock" => scalar(self.client.chain_info().best_block_number),
+			"highestBlock" => scalar(status.highest_block_number.unwrap_or(status.start_block_number)),
+			"pulledStates" | "knownStates" => Resolved::Scalar(Json::Null),
+			other => return Err(unknown_field("SyncState", other)),
+		})
+	}
+}
+
+fn unknown_field(type_name: &str, field: &str) -> String {
+	format!("Unknown field `{}` on type `{}`", field, type_name)
+}
+
+/// Inserts `value` under `key`, merging objects selected more than once.
+fn merge(result: &mut Map<String, Json>, key: &str, value: Json) {
+	let value = match (result.get_mut(key), value) {
+		(Some(&mut Json::Object(ref mut existing)), Json::Object(value)) => {
+			for (key, value) in value {
+				merge(existing, &key, value);
+			}
+			return;
+		},
+		(_, value) => value,
+	};
+	result.insert(key.into(), value);
+}
+
+/// Evaluates `@include` and `@skip` directives of a field.
+fn included(field: &Field, variables: &HashMap<String, Json>) -> Result<bool, String> {
+	for directive in &field.directives {
+		let condition = directive.arguments.iter()
+			.find(|&&(ref name, _)| name == "if")
+			.map(|&(_, ref value)| value.to_json(variables))
+			.ok_or_else(|| format!("Directive `@{}` requires an `if` argument", directive.name))?;
+		let condition = condition.as_bool().ok_or_else(|| format!("Argument `if` of `@{}` must be a boolean", directive.name))?;
+		match directive.name.as_ref() {
+			"include" if !condition => return Ok(false),
+			"skip" if condition => return Ok(false),
+			"include" | "skip" => {},
+			other => return Err(format!("Unknown directive `@{}`", other)),
+		}
+	}
+	Ok(true)
+}
+
+/// Typed access to the arguments of a field.
+struct Arguments<'a> {
+	field: &'a Field,
+	variables: &'a HashMap<String, Json>,
+}
+
+impl<'a> Arguments<'a> {
+	fn get(&self, name: &str) -> Option<Json> {
+		self.field.arguments.iter()
+			.find(|&&(ref n, _)| n == name)
+			.map(|&(_, ref value)| value.to_json(self.variables))
+			.and_then(|value| if value.is_null() { None } else { Some(value) })
+	}
+
+	/// `Long` scalar, given as a number, a decimal string or a hex string.
+	fn long(&self, name: &str) -> Result<Option<u64>, String> {
+		let invalid = || format!("Argument `{}` must be a Long", name);
+		match self.get(name) {
+			None => Ok(None),
+			Some(Json::Number(n)) => n.as_u64().map(Some).ok_or_else(invalid),
+			Some(Json::String(s)) => if s.starts_with("0x") {
+				u64::from_str_radix(&s[2..], 16).map(Some).map_err(|_| invalid())
+			} else {
+				s.parse().map(Some).map_err(|_| invalid())
+			},
+			Some(_) => Err(invalid()),
+		}
+	}
+
+	fn bytes32(&self, name: &str) -> Result<Option<H256>, String> {
+		self.hex(name, "Bytes32")
+	}
+
+	fn address(&self, name: &str) -> Result<Option<Address>, String> {
+		self.hex(name, "Address")
+	}
+
+	fn hex<T: ::std::str::FromStr>(&self, name: &str, type_name: &str) -> Result<Option<T>, String> {
+		let invalid = || format!("Argument `{}` must be {}", name, type_name);
+		match self.get(name) {
+			None => Ok(None),
+			Some(Json::String(ref s)) if s.starts_with("0x") => s[2..].parse().map(Some).map_err(|_| invalid()),
+			Some(_) => Err(invalid()),
+		}
+	}
+
+	/// State of accounts is read at the block given in the `block` argument, or at `default`.
+	fn block_or(&self, default: BlockId) -> Result<BlockId, String> {
+		Ok(self.long("block")?.map(BlockId::Number).unwrap_or(default))
+	}
+}
@@ -0,0 +1,256 @@
+// Copyright 2015-2018 Parity Technologies (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Health and readiness endpoints of the HTTP JSON-RPC server.
+//!
+//! `GET /health` responds with `200 OK` if the node has enough peers and its best block is recent,
+//! `GET /ready` additionally requires the node not to be syncing. Failing checks are answered
+//! with `503 Service Unavailable`. Both respond with a JSON report of the node status.
+
+use std::sync::Arc;
+
+use ethcore::client::BlockChainClient;
+use http::{RequestMiddleware, RequestMiddlewareAction};
+use http::hyper::{header, Method, StatusCode};
+use http::hyper::server::{Request as HttpRequest, Response as HttpResponse};
+use jsonrpc_core::futures::future;
+use light::client::LightChainClient;
+use serde_json;
+use sync::{SyncProvider, LightSync, LightSyncProvider};
+
+use authcodes::{DefaultTimeProvider, TimeProvider};
+use v1::block_import::is_major_importing;
+
+/// Path of the health endpoint.
+pub const HEALTH_PATH: &str = "/health";
+/// Path of the readiness endpoint.
+pub const READY_PATH: &str = "/ready";
+
+/// Thresholds a healthy node has to satisfy.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct HealthThresholds {
+	/// Maximal age of the best block, in seconds.
+	pub max_block_age: u64,
+	/// Minimal number of connected peers.
+	pub min_peers: usize,
+}
+
+impl Default for HealthThresholds {
+	fn default() -> Self {
+		HealthThresholds {
+			max_block_age: 300,
+			min_peers: 1,
+		}
+	}
+}
+
+/// State of the node checked by the health endpoints.
+#[derive(Debug, Clone, PartialEq)]
+pub struct NodeStatus {
+	/// Whether the node is doing a major sync.
+	pub syncing: bool,
+	/// Number of connected peers.
+	pub peers: usize,
+	/// Number of the best block.
+	pub best_block: u64,
+	/// Timestamp of the best block.
+	pub best_block_timestamp: u64,
+}
+
+/// Provides the current state of the node.
+pub trait NodeStatusProvider: Send + Sync {
+	/// Returns the current state of the node.
+	fn node_status(&self) -> NodeStatus;
+}
+
+/// Status of a full node.
+pub struct FullNodeStatus<C> {
+	client: Arc<C>,
+	sync: Arc<SyncProvider>,
+}
+
+impl<C> FullNodeStatus<C> {
+	/// Creates new status provider.
+	pub fn new(client: Arc<C>, sync: Arc<SyncProvider>) -> Self {
+		FullNodeStatus { client, sync }
+	}
+}
+
+impl<C: BlockChainClient> NodeStatusProvider for FullNodeStatus<C> {
+	fn node_status(&self) -> NodeStatus {
+		let status = self.sync.status();
+		let chain_info = self.client.chain_info();
+		NodeStatus {
+			syncing: is_major_importing(Some(status.state), self.client.queue_info()),
+			peers: status.num_peers,
+			best_block: chain_info.best_block_number,
+			best_block_timestamp: chain_info.best_block_timestamp,
+		}
+	}
+}
+
+/// Status of a light node.
+pub struct LightNodeStatus<C> {
+	client: Arc<C>,
+	sync: Arc<LightSync>,
+}
+
+impl<C> LightNodeStatus<C> {
+	/// Creates new status provider.
+	pub fn new(client: Arc<C>, sync: Arc<LightSync>) -> Self {
+		LightNodeStatus { client, sync }
+	}
+}
+
+impl<C: LightChainClient> NodeStatusProvider for LightNodeStatus<C> {
+	fn node_status(&self) -> NodeStatus {
+		let chain_info = self.client.chain_info();
+		NodeStatus {
+			syncing: self.sync.is_major_importing(),
+			peers: self.sync.peer_numbers().connected,
+			best_block: chain_info.best_block_number,
+			best_block_timestamp: chain_info.best_block_timestamp,
+		}
+	}
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct Report {
+	syncing: bool,
+	peers: usize,
+	best_block: u64,
+	best_block_age: u64,
+	errors: Vec<String>,
+}
+
+fn report(status: NodeStatus, thresholds: &HealthThresholds, now: u64, ready: bool) -> Report {
+	let best_block_age = now.saturating_sub(status.best_block_timestamp);
+	let mut errors = Vec::new();
+	if status.peers < thresholds.min_peers {
+		errors.push(format!("Connected to {} peers, expected at least {}.", status.peers, thresholds.min_peers));
+	}
+	if best_block_age > thresholds.max_block_age {
+		errors.push(format!("Best block is {} seconds old, expected at most {}.", best_block_age, thresholds.max_block_age));
+	}
+	if ready && status.syncing {
+		errors.push("Node is syncing.".into());
+	}
+
+	Report {
+		syncing: status.syncing,
+		peers: status.peers,
+		best_block: status.best_block,
+		best_block_age,
+		errors,
+	}
+}
+
+/// HTTP request middleware serving the health and readiness endpoints.
+///
+/// The endpoints don't require authentication, other requests are passed to the inner middleware, if any.
+pub struct Health<R> {
+	status: Arc<NodeStatusProvider>,
+	thresholds: HealthThresholds,
+	time: Box<TimeProvider + Send + Sync>,
+	inner: Option<R>,
+}
+
+impl<R> Health<R> {
+	/// Creates new middleware checking the status of the node against given thresholds.
+	pub fn new(status: Arc<NodeStatusProvider>, thresholds: HealthThresholds, inner: Option<R>) -> Self {
+		Health {
+			status,
+			thresholds,
+			time: Box::new(DefaultTimeProvider::default()),
+			inner,
+		}
+	}
+}
+
+impl<R: RequestMiddleware> RequestMiddleware for Health<R> {
+	fn on_request(&self, request: HttpRequest) -> RequestMiddlewareAction {
+		let ready = match request.uri().path() {
+			HEALTH_PATH => false,
+			READY_PATH => true,
+			_ => return match self.inner {
+				Some(ref inner) => inner.on_request(request),
+				None => RequestMiddlewareAction::Proceed {
+					should_continue_on_invalid_cors: false,
+					request,
+				},
+			},
+		};
+
+		if *request.method() != Method::Get && *request.method() != Method::Head {
+			return RequestMiddlewareAction::Respond {
+				should_validate_hosts: true,
+				response: Box::new(future::ok(HttpResponse::new().with_status(StatusCode::MethodNotAllowed))),
+			};
+		}
+
+		let report = report(self.status.node_status(), &self.thresholds, self.time.now(), ready);
+		let status = if report.errors.is_empty() { StatusCode::Ok } else { StatusCode::ServiceUnavailable };
+		let body = serde_json::to_string(&report).expect("Report serialization is infallible; qed");
+		RequestMiddlewareAction::Respond {
+			should_validate_hosts: true,
+			response: Box::new(future::ok(HttpResponse::new()
+				.with_status(status)
+				.with_header(header::ContentType::json())
+				.with_body(body))),
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::{report, HealthThresholds, NodeStatus};
+
+	const NOW: u64 = 1_500_000_000;
+
+	fn status(syncing: bool, peers: usize, best_block_timestamp: u64) -> NodeStatus {
+		NodeStatus {
+			syncing,
+			peers,
+			best_block: 10,
+			best_block_timestamp,
+		}
+	}
+
+	#[test]
+	fn should_report_healthy_node() {
+		let report = report(status(false, 5, NOW - 10), &HealthThresholds::default(), NOW, true);
+
+		assert_eq!(report.best_block_age, 10);
+		assert!(report.errors.is_empty());
+	}
+
+	#[test]
+	fn should_report_lagging_node() {
+		let thresholds = HealthThresholds { max_block_age: 60, min_peers: 3 };
+
+		assert_eq!(report(status(false, 2, NOW), &thresholds, NOW, false).errors.len(), 1);
+		assert_eq!(report(status(false, 3, NOW - 61), &thresholds, NOW, false).errors.len(), 1);
+	}
+
+	#[test]
+	fn should_not_be_ready_while_syncing() {
+		let thresholds = HealthThresholds::default();
+
+		assert!(report(status(true, 5, NOW), &thresholds, NOW, false).errors.is_empty());
+		assert_eq!(report(status(true, 5, NOW), &thresholds, NOW, true).errors, vec!["Node is syncing.".to_owned()]);
+	}
+}
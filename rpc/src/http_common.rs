@@ -16,6 +16,7 @@
 
 //! Transport-specific metadata extractors.
 
+use std::net::SocketAddr;
 use jsonrpc_core;
 use http;
 use hyper;
@@ -25,7 +26,7 @@ pub trait HttpMetaExtractor: Send + Sync + 'static {
 	/// Type of Metadata
 	type Metadata: jsonrpc_core::Metadata;
 	/// Extracts metadata from given params.
-	fn read_metadata(&self, origin: Option<String>, user_agent: Option<String>) -> Self::Metadata;
+	fn read_metadata(&self, origin: Option<String>, user_agent: Option<String>, remote: Option<SocketAddr>) -> Self::Metadata;
 }
 
 pub struct MetaExtractor<T> {
@@ -49,6 +50,6 @@ impl<M, T> http::MetaExtractor<M> for MetaExtractor<T> where
 
 		let origin = as_string(req.headers().get_raw("origin"));
 		let user_agent = as_string(req.headers().get_raw("user-agent"));
-		self.extractor.read_metadata(origin, user_agent)
+		self.extractor.read_metadata(origin, user_agent, req.remote_addr())
 	}
 }
@@ -0,0 +1,241 @@
+// Copyright 2015-2018 Parity Technologies (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+//! JWT authentication of HTTP and WebSockets requests.
+//!
+//! Requests need to carry an `Authorization: Bearer <token>` header with a JWT signed
+//! with HS256 using a shared secret. The token has to be issued (`iat` claim) at most
+//! `MAX_CLOCK_DRIFT` seconds from now and must not be expired (`exp` claim, if present).
+
+use std::fs;
+use std::path::Path;
+use std::sync::Arc;
+
+use base64;
+use crypto::digest::Sha256;
+use crypto::hmac;
+use http::{RequestMiddleware, RequestMiddlewareAction};
+use http::hyper::{Method, StatusCode};
+use http::hyper::server::{Request as HttpRequest, Response as HttpResponse};
+use jsonrpc_core::futures::future;
+use rustc_hex::FromHex;
+use serde_json;
+
+use authcodes::{DefaultTimeProvider, TimeProvider};
+
+/// Length of the secret in bytes.
+const SECRET_LENGTH: usize = 32;
+/// Maximal difference between the time a token was issued and the current time, in seconds.
+const MAX_CLOCK_DRIFT: u64 = 60;
+
+#[derive(Deserialize)]
+struct Header {
+	alg: String,
+}
+
+#[derive(Deserialize)]
+struct Claims {
+	iat: u64,
+	exp: Option<u64>,
+}
+
+/// Shared secret used to verify JWTs.
+pub struct JwtSecret {
+	key: hmac::VerifyKey<Sha256>,
+	time: Box<TimeProvider + Send + Sync>,
+}
+
+impl JwtSecret {
+	/// Creates new secret from raw bytes.
+	pub fn new(secret: &[u8]) -> Self {
+		JwtSecret {
+			key: hmac::VerifyKey::sha256(secret),
+			time: Box::new(DefaultTimeProvider::default()),
+		}
+	}
+
+	/// Reads hex-encoded 32 bytes secret from given file.
+	pub fn from_file(path: &Path) -> Result<Self, String> {
+		let contents = fs::read_to_string(path)
+			.map_err(|e| format!("Unable to read JWT secret from {}: {}", path.display(), e))?;
+		let hex = contents.trim();
+		let hex = if hex.starts_with("0x") { &hex[2..] } else { hex };
+		let secret: Vec<u8> = hex.from_hex()
+			.map_err(|e| format!("Invalid JWT secret in {}: {}", path.display(), e))?;
+		if secret.len() != SECRET_LENGTH {
+			return Err(format!("Invalid JWT secret in {}: expected {} bytes, got {}.", path.display(), SECRET_LENGTH, secret.len()));
+		}
+
+		Ok(Self::new(&secret))
+	}
+
+	#[cfg(test)]
+	fn with_time<T: TimeProvider + Send + Sync + 'static>(secret: &[u8], time: T) -> Self {
+		JwtSecret {
+			key: hmac::VerifyKey::sha256(secret),
+			time: Box::new(time),
+		}
+	}
+
+	/// Verifies value of the `Authorization` header.
+	pub fn verify_authorization(&self, authorization: Option<&str>) -> Result<(), String> {
+		let authorization = authorization.ok_or_else(|| "Missing Authorization header.".to_owned())?;
+		let mut parts = authorization.splitn(2, ' ');
+		match (parts.next(), parts.next()) {
+			(Some(scheme), Some(token)) if scheme.eq_ignore_ascii_case("bearer") => self.verify(token.trim()),
+			_ => Err("Expected bearer token.".into()),
+		}
+	}
+
+	/// Verifies signature and claims of given token.
+	pub fn verify(&self, token: &str) -> Result<(), String> {
+		let decode = |part: &str| base64::decode_config(part, base64::URL_SAFE_NO_PAD)
+			.map_err(|_| "Invalid token encoding.".to_owned());
+
+		let parts: Vec<_> = token.split('.').collect();
+		if parts.len() != 3 {
+			return Err("Invalid token format.".into());
+		}
+
+		let header: Header = serde_json::from_slice(&decode(parts[0])?)
+			.map_err(|_| "Invalid token header.".to_owned())?;
+		if header.alg != "HS256" {
+			return Err(format!("Unsupported algorithm {}, expected HS256.", header.alg));
+		}
+
+		let signed_len = parts[0].len() + 1 + parts[1].len();
+		if !hmac::verify(&self.key, token[..signed_len].as_bytes(), &decode(parts[2])?) {
+			return Err("Invalid token signature.".into());
+		}
+
+		let claims: Claims = serde_json::from_slice(&decode(parts[1])?)
+			.map_err(|_| "Invalid token claims, iat is required.".to_owned())?;
+		let now = self.time.now();
+		let drift = if claims.iat > now { claims.iat - now } else { now - claims.iat };
+		if drift > MAX_CLOCK_DRIFT {
+			return Err("Token issued too far from current time.".into());
+		}
+		if claims.exp.map_or(false, |exp| exp <= now) {
+			return Err("Token expired.".into());
+		}
+
+		Ok(())
+	}
+}
+
+/// HTTP request middleware rejecting requests without a valid JWT.
+///
+/// Authenticated requests are passed to the inner middleware, if any.
+pub struct JwtAuth<R> {
+	secret: Arc<JwtSecret>,
+	inner: Option<R>,
+}
+
+impl<R> JwtAuth<R> {
+	/// Creates new middleware verifying tokens with given secret.
+	pub fn new(secret: Arc<JwtSecret>, inner: Option<R>) -> Self {
+		JwtAuth { secret, inner }
+	}
+}
+
+impl<R: RequestMiddleware> RequestMiddleware for JwtAuth<R> {
+	fn on_request(&self, request: HttpRequest) -> RequestMiddlewareAction {
+		// CORS preflight requests don't carry credentials.
+		if *request.method() != Method::Options {
+			let authorization = request.headers().get_raw("authorization")
+				.and_then(|raw| raw.one())
+				.map(|raw| String::from_utf8_lossy(raw).into_owned());
+
+			if let Err(e) = self.secret.verify_authorization(authorization.as_ref().map(|a| a.as_str())) {
+				debug!(target: "rpc", "Rejecting unauthenticated request: {}", e);
+				return RequestMiddlewareAction::Respond {
+					should_validate_hosts: true,
+					response: Box::new(future::ok(HttpResponse::new()
+						.with_status(StatusCode::Unauthorized)
+						.with_body(e))),
+				};
+			}
+		}
+
+		match self.inner {
+			Some(ref inner) => inner.on_request(request),
+			None => RequestMiddlewareAction::Proceed {
+				should_continue_on_invalid_cors: false,
+				request,
+			},
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use base64;
+	use crypto::hmac;
+	use super::JwtSecret;
+
+	const SECRET: &[u8] = b"0123456789abcdef0123456789abcdef";
+	const NOW: u64 = 1_500_000_000;
+
+	fn token(secret: &[u8], header: &str, claims: &str) -> String {
+		let encode = |data: &str| base64::encode_config(data.as_bytes(), base64::URL_SAFE_NO_PAD);
+		let signed = format!("{}.{}", encode(header), encode(claims));
+		let signature = hmac::sign(&hmac::SigKey::sha256(secret), signed.as_bytes());
+		format!("{}.{}", signed, base64::encode_config(&*signature, base64::URL_SAFE_NO_PAD))
+	}
+
+	fn secret() -> JwtSecret {
+		JwtSecret::with_time(SECRET, || NOW)
+	}
+
+	#[test]
+	fn should_accept_valid_token() {
+		let token = token(SECRET, r#"{"alg":"HS256","typ":"JWT"}"#, &format!(r#"{{"iat":{}}}"#, NOW - 10));
+
+		assert_eq!(secret().verify(&token), Ok(()));
+		assert_eq!(secret().verify_authorization(Some(&format!("Bearer {}", token))), Ok(()));
+	}
+
+	#[test]
+	fn should_reject_invalid_signature() {
+		let token = token(b"another secret", r#"{"alg":"HS256"}"#, &format!(r#"{{"iat":{}}}"#, NOW));
+
+		assert_eq!(secret().verify(&token), Err("Invalid token signature.".into()));
+	}
+
+	#[test]
+	fn should_reject_other_algorithms() {
+		let token = token(SECRET, r#"{"alg":"none"}"#, &format!(r#"{{"iat":{}}}"#, NOW));
+
+		assert!(secret().verify(&token).is_err());
+	}
+
+	#[test]
+	fn should_reject_stale_or_expired_token() {
+		let stale = token(SECRET, r#"{"alg":"HS256"}"#, &format!(r#"{{"iat":{}}}"#, NOW - 61));
+		let expired = token(SECRET, r#"{"alg":"HS256"}"#, &format!(r#"{{"iat":{},"exp":{}}}"#, NOW - 5, NOW));
+		let missing_iat = token(SECRET, r#"{"alg":"HS256"}"#, "{}");
+
+		assert!(secret().verify(&stale).is_err());
+		assert!(secret().verify(&expired).is_err());
+		assert!(secret().verify(&missing_iat).is_err());
+	}
+
+	#[test]
+	fn should_require_bearer_authorization() {
+		assert!(secret().verify_authorization(None).is_err());
+		assert!(secret().verify_authorization(Some("Basic dXNlcjpwYXNz")).is_err());
+	}
+}
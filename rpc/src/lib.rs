@@ -22,6 +22,7 @@
 extern crate futures;
 
 extern crate ansi_term;
+extern crate base64;
 extern crate cid;
 extern crate futures_cpupool;
 extern crate itertools;
@@ -60,6 +61,7 @@ extern crate ethkey;
 extern crate ethstore;
 extern crate fetch;
 extern crate keccak_hash as hash;
+extern crate memory_cache;
 extern crate parity_reactor;
 extern crate parity_updater as updater;
 extern crate parity_version as version;
@@ -104,7 +106,11 @@ extern crate tempdir;
 pub extern crate jsonrpc_ws_server as ws;
 
 mod authcodes;
+mod health;
 mod http_common;
+mod jwt;
+mod metrics;
+pub mod graphql;
 pub mod v1;
 
 pub mod tests;
@@ -117,11 +123,18 @@ pub use http::{
 	AccessControlAllowOrigin, Host, DomainsValidation
 };
 
-pub use v1::{NetworkSettings, Metadata, Origin, informant, dispatch, signer};
+pub use v1::{NetworkSettings, SubscriptionQueueConfig, OverflowPolicy, GasPriceOracle, GasPriceOracleOptions, Metadata, Origin, informant, dispatch, signer};
 pub use v1::block_import::{is_major_importing, is_major_importing_or_waiting};
+pub use v1::batch::BatchDispatcher;
 pub use v1::extractors::{RpcExtractor, WsExtractor, WsStats, WsDispatcher};
+pub use v1::method_acl::{MethodAcl, MethodFilter};
+pub use v1::rate_limit::{RateLimiter, RateLimits};
+pub use v1::response_limit::ResponseLimit;
 pub use authcodes::{AuthCodes, TimeProvider};
 pub use http_common::HttpMetaExtractor;
+pub use jwt::{JwtAuth, JwtSecret};
+pub use metrics::Metrics;
+pub use health::{Health, HealthThresholds, NodeStatus, NodeStatusProvider, FullNodeStatus, LightNodeStatus};
 
 use std::net::SocketAddr;
 use http::tokio_core;
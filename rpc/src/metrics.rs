@@ -0,0 +1,96 @@
+// Copyright 2015-2018 Parity Technologies (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Metrics endpoint in the Prometheus text exposition format.
+
+use std::sync::Arc;
+
+use http::{RequestMiddleware, RequestMiddlewareAction};
+use http::hyper::{header, Method, StatusCode};
+use http::hyper::server::{Request as HttpRequest, Response as HttpResponse};
+use jsonrpc_core::futures::future;
+use stats::{PrometheusMetrics, PrometheusRegistry};
+
+/// Path of the metrics endpoint.
+pub const METRICS_PATH: &str = "/metrics";
+
+/// HTTP request middleware serving metrics collected from all sources on `GET /metrics`.
+///
+/// Any other request is answered with `404 Not Found`.
+pub struct Metrics {
+	sources: Vec<Arc<PrometheusMetrics + Send + Sync>>,
+	prefix: String,
+}
+
+impl Metrics {
+	/// Creates new middleware, names of all metrics start with `prefix`.
+	pub fn new(sources: Vec<Arc<PrometheusMetrics + Send + Sync>>, prefix: String) -> Self {
+		Metrics { sources, prefix }
+	}
+
+	/// Returns current values of all metrics.
+	pub fn collect(&self) -> String {
+		let mut registry = PrometheusRegistry::new(self.prefix.clone());
+		for source in &self.sources {
+			source.prometheus_metrics(&mut registry);
+		}
+		registry.output()
+	}
+}
+
+impl RequestMiddleware for Metrics {
+	fn on_request(&self, request: HttpRequest) -> RequestMiddlewareAction {
+		let response = if request.uri().path() != METRICS_PATH {
+			HttpResponse::new().with_status(StatusCode::NotFound)
+		} else if *request.method() != Method::Get {
+			HttpResponse::new().with_status(StatusCode::MethodNotAllowed)
+		} else {
+			HttpResponse::new()
+				.with_header(header::ContentType("text/plain; version=0.0.4".parse().expect("valid mime type; qed")))
+				.with_body(self.collect())
+		};
+
+		RequestMiddlewareAction::Respond {
+			should_validate_hosts: true,
+			response: Box::new(future::ok(response)),
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use std::sync::Arc;
+	use stats::{PrometheusMetrics, PrometheusRegistry};
+	use super::Metrics;
+
+	struct Peers(i64);
+
+	impl PrometheusMetrics for Peers {
+		fn prometheus_metrics(&self, registry: &mut PrometheusRegistry) {
+			registry.register_gauge("peers", "Number of peers", self.0);
+		}
+	}
+
+	#[test]
+	fn should_collect_metrics_from_all_sources() {
+		let metrics = Metrics::new(vec![Arc::new(Peers(1)), Arc::new(Peers(2))], "parity_".into());
+
+		let output = metrics.collect();
+
+		assert!(output.contains("parity_peers 1\n"));
+		assert!(output.contains("parity_peers 2\n"));
+	}
+}
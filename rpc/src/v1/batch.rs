@@ -0,0 +1,184 @@
+// Copyright 2015-2018 Parity Technologies (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Parallel processing of batch requests.
+
+use std::cmp;
+use std::sync::Arc;
+
+use futures_cpupool::CpuPool;
+use jsonrpc_core as rpc;
+use jsonrpc_core::futures::{stream, Future, Stream};
+
+use v1::Metadata;
+
+/// Methods which don't modify the state of the node, a trailing `*` matches any suffix.
+const READ_ONLY_METHODS: &[&str] = &[
+	"eth_accounts", "eth_blockNumber", "eth_call", "eth_chainId", "eth_coinbase", "eth_estimateGas",
+	"eth_gasPrice", "eth_getBalance", "eth_getBlock*", "eth_getCode", "eth_getLogs", "eth_getProof",
+	"eth_getStorageAt", "eth_getTransaction*", "eth_getUncle*", "eth_protocolVersion", "eth_syncing",
+	"net_*", "web3_*", "trace_*",
+];
+
+fn is_read_only(call: &rpc::Call) -> bool {
+	let method = match *call {
+		rpc::Call::MethodCall(ref call) => &call.method,
+		_ => return false,
+	};
+
+	READ_ONLY_METHODS.iter().any(|pattern| match pattern.ends_with('*') {
+		true => method.starts_with(&pattern[..pattern.len() - 1]),
+		false => method == pattern,
+	})
+}
+
+/// Splits calls of a batch into groups processed one after another.
+/// Consecutive read-only calls form a single group, any other call is a group on its own.
+fn groups(calls: Vec<rpc::Call>) -> Vec<Vec<rpc::Call>> {
+	let mut groups: Vec<Vec<rpc::Call>> = Vec::new();
+	let mut last_read_only = false;
+	for call in calls {
+		let read_only = is_read_only(&call);
+		if read_only && last_read_only {
+			groups.last_mut().expect("last_read_only is set after a group is pushed; qed").push(call);
+		} else {
+			groups.push(vec![call]);
+		}
+		last_read_only = read_only;
+	}
+	groups
+}
+
+/// RPC middleware processing read-only calls of a batch concurrently.
+///
+/// All requests are handled by the inner handler. Calls of a batch are dispatched to the pool one by one,
+/// up to `parallelism` read-only calls at the same time. Other calls wait for all previous calls
+/// to finish and are processed alone, so their effects are visible to the following calls.
+/// Responses are returned in the order of the calls.
+pub struct BatchDispatcher<S: rpc::Middleware<Metadata>> {
+	handler: Arc<rpc::MetaIoHandler<Metadata, S>>,
+	pool: Option<CpuPool>,
+	parallelism: usize,
+}
+
+impl<S: rpc::Middleware<Metadata>> BatchDispatcher<S> {
+	/// Creates new dispatcher for given handler. Calls are processed on the current thread if there is no pool.
+	pub fn new(handler: rpc::MetaIoHandler<Metadata, S>, pool: Option<CpuPool>, parallelism: usize) -> Self {
+		BatchDispatcher {
+			handler: Arc::new(handler),
+			pool,
+			parallelism: cmp::max(parallelism, 1),
+		}
+	}
+}
+
+fn dispatch<S: rpc::Middleware<Metadata>>(
+	handler: &Arc<rpc::MetaIoHandler<Metadata, S>>,
+	pool: &Option<CpuPool>,
+	call: rpc::Call,
+	meta: Metadata,
+) -> Box<Future<Item=Option<rpc::Output>, Error=()> + Send> {
+	let handler = handler.clone();
+	let process = move || handler.handle_rpc_request(rpc::Request::Single(call), meta).map(|response| match response {
+		Some(rpc::Response::Single(output)) => Some(output),
+		_ => None,
+	});
+
+	match *pool {
+		Some(ref pool) => Box::new(pool.spawn_fn(process)),
+		None => Box::new(process()),
+	}
+}
+
+impl<S: rpc::Middleware<Metadata>> rpc::Middleware<Metadata> for BatchDispatcher<S> {
+	type Future = rpc::FutureResponse;
+
+	fn on_request<F, X>(&self, request: rpc::Request, meta: Metadata, _process: F) -> Self::Future where
+		F: FnOnce(rpc::Request, Metadata) -> X,
+		X: rpc::futures::Future<Item=Option<rpc::Response>, Error=()> + Send + 'static,
+	{
+		let calls = match request {
+			rpc::Request::Batch(calls) if calls.len() > 1 && self.parallelism > 1 => calls,
+			request => return Box::new(self.handler.handle_rpc_request(request, meta)),
+		};
+
+		let handler = self.handler.clone();
+		let pool = self.pool.clone();
+		let parallelism = self.parallelism;
+		Box::new(stream::iter_ok::<_, ()>(groups(calls))
+			.and_then(move |group| {
+				let handler = handler.clone();
+				let pool = pool.clone();
+				let meta = meta.clone();
+				stream::iter_ok::<_, ()>(group)
+					.map(move |call| dispatch(&handler, &pool, call, meta.clone()))
+					.buffered(parallelism)
+					.collect()
+			})
+			.fold(Vec::new(), |mut outputs, group| {
+				outputs.extend(group.into_iter().filter_map(|output| output));
+				Ok::<_, ()>(outputs)
+			})
+			.map(|outputs| if outputs.is_empty() { None } else { Some(rpc::Response::Batch(outputs)) })
+		)
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use std::sync::Arc;
+	use std::sync::atomic::{AtomicUsize, Ordering};
+	use futures_cpupool::CpuPool;
+	use jsonrpc_core::{MetaIoHandler, Value};
+	use v1::Metadata;
+	use super::{groups, BatchDispatcher};
+
+	#[test]
+	fn should_group_consecutive_read_only_calls() {
+		let batch: Vec<_> = ["eth_getBalance", "eth_call", "eth_sendRawTransaction", "net_version", "eth_sendRawTransaction"]
+			.iter()
+			.enumerate()
+			.map(|(id, method)| ::serde_json::from_str(&format!(r#"{{"jsonrpc": "2.0", "method": "{}", "params": [], "id": {}}}"#, method, id)).unwrap())
+			.collect();
+
+		let sizes: Vec<_> = groups(batch).iter().map(Vec::len).collect();
+
+		assert_eq!(sizes, vec![2, 1, 1, 1]);
+	}
+
+	#[test]
+	fn should_process_writes_in_order() {
+		// given
+		let counter = Arc::new(AtomicUsize::new(0));
+		let mut handler = MetaIoHandler::default();
+		let c = counter.clone();
+		handler.add_method_with_meta("eth_blockNumber", move |_, _: Metadata| Ok(Value::from(c.load(Ordering::SeqCst))));
+		let c = counter.clone();
+		handler.add_method_with_meta("eth_sendRawTransaction", move |_, _: Metadata| Ok(Value::from(c.fetch_add(1, Ordering::SeqCst) + 1)));
+		let io = MetaIoHandler::with_middleware(BatchDispatcher::new(handler, Some(CpuPool::new(2)), 2));
+
+		// when
+		let response = io.handle_request_sync(r#"[
+			{"jsonrpc": "2.0", "method": "eth_blockNumber", "params": [], "id": 1},
+			{"jsonrpc": "2.0", "method": "eth_blockNumber", "params": [], "id": 2},
+			{"jsonrpc": "2.0", "method": "eth_sendRawTransaction", "params": [], "id": 3},
+			{"jsonrpc": "2.0", "method": "eth_blockNumber", "params": [], "id": 4}
+		]"#, Metadata::default());
+
+		// then
+		assert_eq!(response, Some(r#"[{"jsonrpc":"2.0","result":0,"id":1},{"jsonrpc":"2.0","result":0,"id":2},{"jsonrpc":"2.0","result":1,"id":3},{"jsonrpc":"2.0","result":1,"id":4}]"#.into()));
+	}
+}
@@ -16,12 +16,15 @@
 
 //! Parity-specific metadata extractors.
 
+use std::cell::RefCell;
+use std::net::{IpAddr, SocketAddr};
 use std::path::{Path, PathBuf};
 use std::sync::Arc;
 
 use authcodes;
 use http_common::HttpMetaExtractor;
 use ipc;
+use jwt::JwtSecret;
 use jsonrpc_core as core;
 use jsonrpc_pubsub::Session;
 use ws;
@@ -36,7 +39,7 @@ pub struct RpcExtractor;
 impl HttpMetaExtractor for RpcExtractor {
 	type Metadata = Metadata;
 
-	fn read_metadata(&self, origin: Option<String>, user_agent: Option<String>) -> Metadata {
+	fn read_metadata(&self, origin: Option<String>, user_agent: Option<String>, remote: Option<SocketAddr>) -> Metadata {
 		Metadata {
 			origin: Origin::Rpc(
 				format!("{} / {}",
@@ -44,6 +47,7 @@ impl HttpMetaExtractor for RpcExtractor {
 						user_agent.unwrap_or("unknown agent".to_string()))
 			),
 			session: None,
+			client: remote.map(|addr| addr.ip().to_string()),
 		}
 	}
 }
@@ -53,13 +57,41 @@ impl ipc::MetaExtractor<Metadata> for RpcExtractor {
 		Metadata {
 			origin: Origin::Ipc(req.session_id.into()),
 			session: Some(Arc::new(Session::new(req.sender.clone()))),
+			client: None,
 		}
 	}
 }
 
+thread_local! {
+	/// Forwarded client address of the connection being upgraded. The handshake request is passed
+	/// to the middleware and right after to the metadata extractor on the same event loop thread.
+	static HANDSHAKE_REMOTE: RefCell<Option<IpAddr>> = RefCell::new(None);
+}
+
+/// Client address of a WS connection, as reported by the forwarding headers of the handshake request.
+fn handshake_remote(req: &ws::ws::Request) -> Option<IpAddr> {
+	req.client_addr().ok()
+		.and_then(|addr| addr)
+		.and_then(|addr| addr.trim().parse::<IpAddr>().ok()
+			.or_else(|| addr.trim().parse::<SocketAddr>().ok().map(|addr| addr.ip())))
+}
+
+/// Identifier of a WS client used for per-client limits.
+///
+/// The WS server doesn't expose the peer address of a connection, so clients are limited
+/// per connection, unless a trusted proxy reports the client address.
+fn ws_client(forwarded: Option<IpAddr>, session_id: u64) -> String {
+	match forwarded {
+		Some(ip) => ip.to_string(),
+		None => format!("ws:{}", session_id),
+	}
+}
+
 /// WebSockets server metadata extractor and request middleware.
 pub struct WsExtractor {
 	authcodes_path: Option<PathBuf>,
+	jwt_secret: Option<Arc<JwtSecret>>,
+	trusted_proxy: bool,
 }
 
 impl WsExtractor {
@@ -67,8 +99,23 @@ impl WsExtractor {
 	pub fn new(path: Option<&Path>) -> Self {
 		WsExtractor {
 			authcodes_path: path.map(|p| p.to_owned()),
+			jwt_secret: None,
+			trusted_proxy: false,
 		}
 	}
+
+	/// Identifies clients by the `X-Forwarded-For` or `Forwarded` header of the handshake request.
+	/// Only safe when all connections come through a proxy setting these headers.
+	pub fn with_trusted_proxy(mut self, trusted_proxy: bool) -> Self {
+		self.trusted_proxy = trusted_proxy;
+		self
+	}
+
+	/// Requires connections to be authenticated with a JWT signed with given secret.
+	pub fn with_jwt_secret(mut self, secret: Option<Arc<JwtSecret>>) -> Self {
+		self.jwt_secret = secret;
+		self
+	}
 }
 
 impl ws::MetaExtractor<Metadata> for WsExtractor {
@@ -86,9 +133,13 @@ impl ws::MetaExtractor<Metadata> for WsExtractor {
 			None => Origin::Ws { session: id.into() },
 		};
 		let session = Some(Arc::new(Session::new(req.sender())));
+		let forwarded = HANDSHAKE_REMOTE.with(|remote| remote.borrow_mut().take())
+			.filter(|_| self.trusted_proxy);
+		let client = ws_client(forwarded, id);
 		Metadata {
 			origin,
 			session,
+			client: Some(client),
 		}
 	}
 }
@@ -97,6 +148,20 @@ impl ws::RequestMiddleware for WsExtractor {
 	fn process(&self, req: &ws::ws::Request) -> ws::MiddlewareAction {
 		use self::ws::ws::Response;
 
+		let remote = handshake_remote(req);
+		HANDSHAKE_REMOTE.with(|r| *r.borrow_mut() = remote);
+
+		if let Some(ref secret) = self.jwt_secret {
+			let authorization = req.header("authorization").and_then(|a| ::std::str::from_utf8(a).ok());
+			if let Err(e) = secret.verify_authorization(authorization) {
+				debug!(target: "rpc", "Rejecting unauthenticated connection: {}", e);
+				let mut response = Response::new(401, "Unauthorized");
+				response.set_body(&e);
+				add_security_headers(&mut response);
+				return Some(response).into();
+			}
+		}
+
 		// Reply with 200 Ok to HEAD requests.
 		if req.method() == "HEAD" {
 			let mut response = Response::new(200, "Ok");
@@ -242,7 +307,12 @@ impl<M: core::Middleware<Metadata>> core::Middleware<Metadata> for WsDispatcher<
 
 #[cfg(test)]
 mod tests {
-	use super::RpcExtractor;
+	use std::net::IpAddr;
+	use jsonrpc_core::{MetaIoHandler, Value};
+	use ws::{self, RequestMiddleware};
+	use v1::Metadata;
+	use v1::rate_limit::{RateLimiter, RateLimits};
+	use super::{RpcExtractor, WsExtractor, HANDSHAKE_REMOTE, ws_client};
 	use {HttpMetaExtractor, Origin};
 
 	#[test]
@@ -251,13 +321,71 @@ mod tests {
 		let extractor = RpcExtractor;
 
 		// when
-		let meta1 = extractor.read_metadata(None, None);
-		let meta2 = extractor.read_metadata(None, Some("http://parity.io".to_owned()));
-		let meta3 = extractor.read_metadata(None, Some("http://parity.io".to_owned()));
+		let meta1 = extractor.read_metadata(None, None, None);
+		let meta2 = extractor.read_metadata(None, Some("http://parity.io".to_owned()), None);
+		let meta3 = extractor.read_metadata(None, Some("http://parity.io".to_owned()), Some("10.0.0.1:4321".parse().unwrap()));
 
 		// then
 		assert_eq!(meta1.origin, Origin::Rpc("unknown origin / unknown agent".into()));
 		assert_eq!(meta2.origin, Origin::Rpc("unknown origin / http://parity.io".into()));
 		assert_eq!(meta3.origin, Origin::Rpc("unknown origin / http://parity.io".into()));
+		assert_eq!(meta1.client, None);
+		assert_eq!(meta3.client, Some("10.0.0.1".into()));
+	}
+
+	fn handshake(forwarded_for: Option<&str>) -> Option<IpAddr> {
+		let mut request = String::from("GET / HTTP/1.1\r\nHost: 127.0.0.1\r\nConnection: Upgrade\r\nSec-WebSocket-Key: x3JJHMbDL1EzLkh9GBhXDw==\r\n");
+		if let Some(address) = forwarded_for {
+			request.push_str(&format!("X-Forwarded-For: {}\r\n", address));
+		}
+		request.push_str("\r\n");
+		let request = ws::ws::Request::parse(request.as_bytes()).unwrap().unwrap();
+
+		WsExtractor::new(None).process(&request);
+		HANDSHAKE_REMOTE.with(|remote| remote.borrow_mut().take())
+	}
+
+	#[test]
+	fn should_pass_ws_remote_address_to_extractor() {
+		assert_eq!(handshake(Some("10.0.0.1, 10.0.0.2")), Some("10.0.0.1".parse().unwrap()));
+		assert_eq!(handshake(Some("10.0.0.1:4321")), Some("10.0.0.1".parse().unwrap()));
+		assert_eq!(handshake(None), None);
+	}
+
+	#[test]
+	fn should_limit_ws_clients_by_connection_or_forwarded_address() {
+		// given
+		let mut io = MetaIoHandler::with_middleware(RateLimiter::new(RateLimits {
+			requests_per_second: Some(1),
+			concurrent_requests: None,
+		}));
+		io.add_method_with_meta("hello", |_, _: Metadata| Ok(Value::String("world".into())));
+		let request = r#"{"jsonrpc": "2.0", "method": "hello", "params": [], "id": 1}"#;
+		let meta = |forwarded: Option<IpAddr>, session: u64| {
+			let mut meta = Metadata::default();
+			meta.origin = Origin::Ws { session: session.into() };
+			meta.client = Some(ws_client(forwarded, session));
+			meta
+		};
+		let first: IpAddr = "10.0.0.1".parse().unwrap();
+		let second: IpAddr = "10.0.0.2".parse().unwrap();
+
+		// when
+		let first_session = io.handle_request_sync(request, meta(Some(first), 1));
+		// the same forwarded address reconnecting is still limited
+		let reconnected = io.handle_request_sync(request, meta(Some(first), 2));
+		let other_address = io.handle_request_sync(request, meta(Some(second), 3));
+		let direct = io.handle_request_sync(request, meta(None, 4));
+		let same_connection = io.handle_request_sync(request, meta(None, 4));
+		let other_connection = io.handle_request_sync(request, meta(None, 5));
+
+		// then
+		let ok = Some(r#"{"jsonrpc":"2.0","result":"world","id":1}"#.to_owned());
+		assert_eq!(first_session, ok);
+		assert!(reconnected.unwrap().contains("Too many requests."));
+		assert_eq!(other_address, ok);
+		assert_eq!(direct, ok);
+		assert!(same_connection.unwrap().contains("Too many requests."));
+		assert_eq!(other_connection, ok);
 	}
 }
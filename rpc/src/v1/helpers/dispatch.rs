@@ -43,7 +43,7 @@ use transaction::{Action, SignedTransaction, PendingTransaction, Transaction, Er
 use jsonrpc_core::{BoxFuture, Result, Error};
 use jsonrpc_core::futures::{future, Future, Poll, Async};
 use jsonrpc_core::futures::future::Either;
-use v1::helpers::{errors, nonce, TransactionRequest, FilledTransactionRequest, ConfirmationPayload};
+use v1::helpers::{errors, nonce, GasPriceOracle, GasPriceOracleOptions, TransactionRequest, FilledTransactionRequest, ConfirmationPayload};
 use v1::types::{
 	H256 as RpcH256, H520 as RpcH520, Bytes as RpcBytes,
 	RichRawTransaction as RpcRichRawTransaction,
@@ -87,7 +87,7 @@ pub struct FullDispatcher<C, M> {
 	client: Arc<C>,
 	miner: Arc<M>,
 	nonces: Arc<Mutex<nonce::Reservations>>,
-	gas_price_percentile: usize,
+	gas_price_oracle: Arc<GasPriceOracle>,
 }
 
 impl<C, M> FullDispatcher<C, M> {
@@ -102,9 +102,18 @@ impl<C, M> FullDispatcher<C, M> {
 			client,
 			miner,
 			nonces,
-			gas_price_percentile,
+			gas_price_oracle: Arc::new(GasPriceOracle::new(GasPriceOracleOptions {
+				percentile: gas_price_percentile,
+				..Default::default()
+			})),
 		}
 	}
+
+	/// Use given oracle to fill in default gas prices.
+	pub fn with_gas_price_oracle(mut self, gas_price_oracle: Arc<GasPriceOracle>) -> Self {
+		self.gas_price_oracle = gas_price_oracle;
+		self
+	}
 }
 
 impl<C, M> Clone for FullDispatcher<C, M> {
@@ -113,7 +122,7 @@ impl<C, M> Clone for FullDispatcher<C, M> {
 			client: self.client.clone(),
 			miner: self.miner.clone(),
 			nonces: self.nonces.clone(),
-			gas_price_percentile: self.gas_price_percentile,
+			gas_price_oracle: self.gas_price_oracle.clone(),
 		}
 	}
 }
@@ -154,7 +163,7 @@ impl<C: miner::BlockChainClient + BlockChainClient, M: MinerService> Dispatcher
 			to: request.to,
 			nonce,
 			gas_price: request.gas_price.unwrap_or_else(|| {
-				default_gas_price(&*self.client, &*self.miner, self.gas_price_percentile)
+				self.gas_price_oracle.gas_price(&*self.client, &*self.miner)
 			}),
 			gas: request.gas.unwrap_or_else(|| self.miner.sensible_gas_limit()),
 			value: request.value.unwrap_or_else(|| 0.into()),
@@ -750,14 +759,6 @@ fn decrypt(accounts: &AccountProvider, address: Address, msg: Bytes, password: S
 	})
 }
 
-/// Extract the default gas price from a client and miner.
-pub fn default_gas_price<C, M>(client: &C, miner: &M, percentile: usize) -> U256 where
-	C: BlockChainClient,
-	M: MinerService,
-{
-	client.gas_price_corpus(100).percentile(percentile).cloned().unwrap_or_else(|| miner.sensible_gas_price())
-}
-
 /// Convert RPC confirmation payload to signer confirmation payload.
 /// May need to resolve in the future to fetch things like gas price.
 pub fn from_rpc<D>(payload: RpcConfirmationPayload, default_account: Address, dispatcher: &D) -> BoxFuture<ConfirmationPayload>
@@ -20,13 +20,16 @@ use std::fmt;
 
 use ethcore::account_provider::{SignError as AccountError};
 use ethcore::error::{Error as EthcoreError, ErrorKind, CallError};
+use ethcore::executed::revert_reason;
 use ethcore::client::BlockId;
+use ethcore::state::StateOverride as EthStateOverride;
 use jsonrpc_core::{futures, Error, ErrorCode, Value};
 use rlp::DecoderError;
 use transaction::Error as TransactionError;
 use ethcore_private_tx::Error as PrivateTransactionError;
 use vm::Error as VMError;
 use light::on_demand::error::{Error as OnDemandError, ErrorKind as OnDemandErrorKind};
+use v1::types::{StateOverride, into_state_override};
 
 mod codes {
 	// NOTE [ToDr] Codes from [-32099, -32000]
@@ -35,6 +38,7 @@ mod codes {
 	pub const NO_AUTHOR: i64 = -32002;
 	pub const NO_NEW_WORK: i64 = -32003;
 	pub const NO_WORK_REQUIRED: i64 = -32004;
+	pub const BLOCK_REJECTED: i64 = -32005;
 	pub const UNKNOWN_ERROR: i64 = -32009;
 	pub const TRANSACTION_ERROR: i64 = -32010;
 	pub const EXECUTION_ERROR: i64 = -32015;
@@ -47,6 +51,8 @@ mod codes {
 	pub const REQUEST_REJECTED: i64 = -32040;
 	pub const REQUEST_REJECTED_LIMIT: i64 = -32041;
 	pub const REQUEST_NOT_FOUND: i64 = -32042;
+	pub const RATE_LIMITED: i64 = -32043;
+	pub const METHOD_NOT_ALLOWED: i64 = -32044;
 	pub const ENCRYPTION_ERROR: i64 = -32055;
 	pub const ENCODING_ERROR: i64 = -32058;
 	pub const FETCH_ERROR: i64 = -32060;
@@ -86,6 +92,14 @@ pub fn request_not_found() -> Error {
 	}
 }
 
+pub fn response_cursor_not_found(cursor: &str) -> Error {
+	Error {
+		code: ErrorCode::ServerError(codes::REQUEST_NOT_FOUND),
+		message: "Response chunk not found.".into(),
+		data: Some(Value::String(format!("Cursor {} is unknown, was already used or has expired.", cursor))),
+	}
+}
+
 pub fn request_rejected() -> Error {
 	Error {
 		code: ErrorCode::ServerError(codes::REQUEST_REJECTED),
@@ -102,6 +116,22 @@ pub fn request_rejected_limit() -> Error {
 	}
 }
 
+pub fn rate_limited(details: String) -> Error {
+	Error {
+		code: ErrorCode::ServerError(codes::RATE_LIMITED),
+		message: "Too many requests.".into(),
+		data: Some(Value::String(details)),
+	}
+}
+
+pub fn method_not_allowed(method: &str) -> Error {
+	Error {
+		code: ErrorCode::ServerError(codes::METHOD_NOT_ALLOWED),
+		message: "Method not allowed.".into(),
+		data: Some(Value::String(format!("Calls to {} are denied by the node's access control list.", method))),
+	}
+}
+
 pub fn request_rejected_param_limit(limit: u64, items_desc: &str) -> Error {
 	Error {
 		code: ErrorCode::ServerError(codes::REQUEST_REJECTED_LIMIT),
@@ -110,6 +140,30 @@ pub fn request_rejected_param_limit(limit: u64, items_desc: &str) -> Error {
 	}
 }
 
+pub fn logs_range_too_large(range: u64, max: u64) -> Error {
+	Error {
+		code: ErrorCode::ServerError(codes::REQUEST_REJECTED_LIMIT),
+		message: format!("Filter spans {} blocks, which exceeds the limit of {} blocks. Split the query into smaller block ranges.", range, max),
+		data: None,
+	}
+}
+
+pub fn debug_session_not_found() -> Error {
+	Error {
+		code: ErrorCode::ServerError(codes::REQUEST_NOT_FOUND),
+		message: "Debug session not found or already finished.".into(),
+		data: None,
+	}
+}
+
+pub fn debug_session_limit(limit: usize) -> Error {
+	Error {
+		code: ErrorCode::ServerError(codes::REQUEST_REJECTED_LIMIT),
+		message: format!("At most {} debug sessions may be active at once.", limit),
+		data: None,
+	}
+}
+
 pub fn account<T: fmt::Debug>(error: &str, details: T) -> Error {
 	Error {
 		code: ErrorCode::ServerError(codes::ACCOUNT_ERROR),
@@ -137,6 +191,10 @@ pub fn invalid_params<T: fmt::Debug>(param: &str, details: T) -> Error {
 	}
 }
 
+pub fn state_override(overrides: StateOverride) -> Result<EthStateOverride, Error> {
+	into_state_override(overrides).map_err(|address| invalid_params("stateOverride", format!("Both state and stateDiff given for {:?}", address)))
+}
+
 pub fn execution<T: fmt::Debug>(data: T) -> Error {
 	Error {
 		code: ErrorCode::ServerError(codes::EXECUTION_ERROR),
@@ -153,6 +211,17 @@ pub fn state_pruned() -> Error {
 	}
 }
 
+pub fn state_not_reconstructible(range: Option<(u64, u64)>) -> Error {
+	Error {
+		code: ErrorCode::ServerError(codes::UNSUPPORTED_REQUEST),
+		message: "This request is not supported because the state of the block is pruned and can't be reconstructed from the local snapshot.".into(),
+		data: Some(Value::String(match range {
+			Some((from, to)) => format!("State can only be reconstructed for blocks #{} to #{}.", from, to),
+			None => "The state of the local snapshot is not restored (yet), try again later.".into(),
+		})),
+	}
+}
+
 pub fn state_corrupt() -> Error {
 	internal("State corrupt", "")
 }
@@ -197,6 +266,14 @@ pub fn no_work_required() -> Error {
 	}
 }
 
+pub fn block_rejected<T: fmt::Debug>(error: T) -> Error {
+	Error {
+		code: ErrorCode::ServerError(codes::BLOCK_REJECTED),
+		message: "Submitted block was rejected.".into(),
+		data: Some(Value::String(format!("{:?}", error))),
+	}
+}
+
 pub fn not_enough_data() -> Error {
 	Error {
 		code: ErrorCode::ServerError(codes::UNSUPPORTED_REQUEST),
@@ -327,6 +404,8 @@ pub fn transaction_message(error: &TransactionError) -> String {
 		},
 		InvalidSignature(ref sig) => format!("Invalid signature: {}", sig),
 		InvalidChainId => "Invalid chain id.".into(),
+		TransactionTypeNotEnabled => "Transaction type is not enabled yet.".into(),
+		PriorityFeeTooHigh => "Max priority fee per gas is higher than max fee per gas.".into(),
 		InvalidGasLimit(_) => "Supplied gas is beyond limit.".into(),
 		SenderBanned => "Sender is banned in local queue.".into(),
 		RecipientBanned => "Recipient is banned in local queue.".into(),
@@ -378,9 +457,11 @@ pub fn rlp(error: DecoderError) -> Error {
 pub fn call(error: CallError) -> Error {
 	match error {
 		CallError::StatePruned => state_pruned(),
+		CallError::StateNotReconstructible(range) => state_not_reconstructible(range),
 		CallError::StateCorrupt => state_corrupt(),
 		CallError::Exceptional => exceptional(),
 		CallError::Execution(e) => execution(e),
+		CallError::Reverted(output) => reverted(&output),
 		CallError::TransactionNotFound => internal("{}, this should not be the case with eth_call, most likely a bug.", CallError::TransactionNotFound),
 	}
 }
@@ -400,6 +481,29 @@ pub fn vm(error: &VMError, output: &[u8]) -> Error {
 	}
 }
 
+pub fn reverted(output: &[u8]) -> Error {
+	use rustc_hex::ToHex;
+
+	let message = match revert_reason(output) {
+		Some(reason) => format!("Execution reverted: {}", reason),
+		None => "Execution reverted.".into(),
+	};
+
+	Error {
+		code: ErrorCode::ServerError(codes::EXECUTION_ERROR),
+		message,
+		data: Some(Value::String(format!("0x{}", output.to_hex()))),
+	}
+}
+
+pub fn tracer<T: fmt::Display>(error: T) -> Error {
+	Error {
+		code: ErrorCode::ServerError(codes::EXECUTION_ERROR),
+		message: "Tracer execution error.".into(),
+		data: Some(Value::String(format!("{}", error))),
+	}
+}
+
 pub fn unknown_block() -> Error {
 	Error {
 		code: ErrorCode::InvalidParams,
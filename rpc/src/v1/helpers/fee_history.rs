@@ -0,0 +1,85 @@
+// Copyright 2015-2018 Parity Technologies (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Helpers for `eth_feeHistory` (EIP-1559).
+
+use ethereum_types::U256;
+use jsonrpc_core::Error;
+use v1::helpers::errors;
+
+/// Maximum number of blocks returned by a single fee history request.
+pub const MAX_FEE_HISTORY_BLOCKS: u64 = 1024;
+
+/// Check that reward percentiles are monotonically increasing values between 0 and 100.
+pub fn validate_percentiles(percentiles: &[f64]) -> Result<(), Error> {
+	let out_of_range = percentiles.iter().any(|p| !(*p >= 0.0 && *p <= 100.0));
+	let decreasing = percentiles.windows(2).any(|w| w[0] > w[1]);
+	if out_of_range || decreasing {
+		return Err(errors::invalid_params("rewardPercentiles", "expected monotonically increasing values between 0 and 100"));
+	}
+	Ok(())
+}
+
+/// Priority fees paid at the given percentiles of the gas used in a block.
+///
+/// `fees` contains the effective priority fee and the gas used of every transaction in the block.
+pub fn percentile_rewards(mut fees: Vec<(U256, U256)>, percentiles: &[f64]) -> Vec<U256> {
+	if fees.is_empty() {
+		return vec![U256::zero(); percentiles.len()];
+	}
+
+	fees.sort_by(|a, b| a.0.cmp(&b.0));
+	let total_gas = fees.iter().fold(U256::zero(), |total, &(_, gas)| total + gas).low_u64() as f64;
+
+	let mut index = 0;
+	let mut cumulative_gas = fees[0].1.low_u64() as f64;
+	percentiles.iter().map(|percentile| {
+		let threshold = total_gas * percentile / 100.0;
+		while cumulative_gas < threshold && index + 1 < fees.len() {
+			index += 1;
+			cumulative_gas += fees[index].1.low_u64() as f64;
+		}
+		fees[index].0
+	}).collect()
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn should_validate_percentiles() {
+		assert!(validate_percentiles(&[]).is_ok());
+		assert!(validate_percentiles(&[0.0, 25.0, 25.0, 100.0]).is_ok());
+		assert!(validate_percentiles(&[50.0, 25.0]).is_err());
+		assert!(validate_percentiles(&[-1.0]).is_err());
+		assert!(validate_percentiles(&[100.5]).is_err());
+	}
+
+	#[test]
+	fn should_compute_rewards_weighted_by_gas() {
+		let fees = vec![
+			(U256::from(5), U256::from(21_000)),
+			(U256::from(1), U256::from(63_000)),
+			(U256::from(3), U256::from(21_000)),
+		];
+
+		assert_eq!(percentile_rewards(fees, &[0.0, 50.0, 70.0, 90.0, 100.0]), vec![
+			1.into(), 1.into(), 3.into(), 5.into(), 5.into(),
+		]);
+		assert_eq!(percentile_rewards(vec![], &[10.0, 90.0]), vec![U256::zero(), U256::zero()]);
+	}
+}
@@ -0,0 +1,136 @@
+// Copyright 2015-2018 Parity Technologies (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Gas profile of call traces in the folded stacks format.
+//!
+//! Every line consists of `;`-separated frames followed by the gas used by the
+//! innermost frame itself (excluding its subcalls), e.g.
+//! `0x...0a:0xa9059cbb;0x...0b 5000`. The output can be rendered with
+//! flamegraph tools directly.
+
+use std::collections::HashMap;
+use std::fmt::Write;
+
+use ethcore::trace::FlatTrace;
+use ethcore::trace::trace::{Action, Res};
+use ethereum_types::U256;
+
+fn frame(trace: &FlatTrace) -> Option<String> {
+	match (&trace.action, &trace.result) {
+		(&Action::Call(ref call), _) if call.input.len() >= 4 =>
+			Some(format!("0x{:x}:0x{:02x}{:02x}{:02x}{:02x}", call.to, call.input[0], call.input[1], call.input[2], call.input[3])),
+		(&Action::Call(ref call), _) => Some(format!("0x{:x}", call.to)),
+		(&Action::Create(_), &Res::Create(ref result)) => Some(format!("create:0x{:x}", result.address)),
+		(&Action::Create(_), _) => Some("create".into()),
+		_ => None,
+	}
+}
+
+fn gas_used(trace: &FlatTrace) -> U256 {
+	match (&trace.action, &trace.result) {
+		(_, &Res::Call(ref result)) => result.gas_used,
+		(_, &Res::Create(ref result)) => result.gas_used,
+		// failed calls don't report gas used, count all the gas provided.
+		(&Action::Call(ref call), &Res::FailedCall(_)) => call.gas,
+		(&Action::Create(ref create), &Res::FailedCreate(_)) => create.gas,
+		_ => U256::zero(),
+	}
+}
+
+/// Appends folded stacks of the traces of a single transaction to `out`.
+/// All stacks start with the `root` frame, if given.
+pub fn fold(traces: &[FlatTrace], root: Option<&str>, out: &mut String) {
+	let mut frames = HashMap::new();
+	let mut subcalls_gas = HashMap::new();
+	for trace in traces {
+		let address: Vec<usize> = trace.trace_address.iter().cloned().collect();
+		if let Some((_, parent)) = address.split_last() {
+			*subcalls_gas.entry(parent.to_vec()).or_insert_with(U256::zero) += gas_used(trace);
+		}
+		if let Some(frame) = frame(trace) {
+			frames.insert(address, frame);
+		}
+	}
+
+	for trace in traces {
+		let address: Vec<usize> = trace.trace_address.iter().cloned().collect();
+		if !frames.contains_key(&address) {
+			continue;
+		}
+
+		let own_gas = gas_used(trace).saturating_sub(subcalls_gas.get(&address).cloned().unwrap_or_default());
+		if own_gas.is_zero() {
+			continue;
+		}
+
+		let mut stack: Vec<&str> = root.into_iter().collect();
+		stack.extend((0..address.len() + 1).filter_map(|len| frames.get(&address[..len]).map(|frame| frame.as_str())));
+		let _ = writeln!(out, "{} {}", stack.join(";"), own_gas.low_u64());
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use std::collections::VecDeque;
+	use ethcore::trace::FlatTrace;
+	use ethcore::trace::trace::{Action, Res, Call, CallResult};
+	use vm::CallType;
+	use super::fold;
+
+	fn call(to: u64, input: Vec<u8>, gas_used: u64, address: Vec<usize>) -> FlatTrace {
+		FlatTrace {
+			action: Action::Call(Call {
+				from: 1.into(),
+				to: to.into(),
+				value: 0.into(),
+				gas: 100_000.into(),
+				input,
+				call_type: CallType::Call,
+			}),
+			result: Res::Call(CallResult {
+				gas_used: gas_used.into(),
+				output: vec![],
+			}),
+			subtraces: 0,
+			trace_address: address.into_iter().collect::<VecDeque<_>>(),
+		}
+	}
+
+	#[test]
+	fn should_fold_nested_calls() {
+		// given
+		let traces = vec![
+			call(10, vec![0xa9, 0x05, 0x9c, 0xbb, 0x00], 30_000, vec![]),
+			call(11, vec![], 10_000, vec![0]),
+			call(12, vec![], 4_000, vec![0, 0]),
+			call(13, vec![], 5_000, vec![1]),
+		];
+		let mut out = String::new();
+
+		// when
+		fold(&traces, Some("tx"), &mut out);
+
+		// then
+		let a = "0x000000000000000000000000000000000000000a:0xa9059cbb";
+		let b = "0x000000000000000000000000000000000000000b";
+		let c = "0x000000000000000000000000000000000000000c";
+		let d = "0x000000000000000000000000000000000000000d";
+		assert_eq!(out, format!(
+			"tx;{a} 15000\ntx;{a};{b} 6000\ntx;{a};{b};{c} 4000\ntx;{a};{d} 5000\n",
+			a = a, b = b, c = c, d = d,
+		));
+	}
+}
@@ -0,0 +1,210 @@
+// Copyright 2015-2018 Parity Technologies (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Gas price suggestions based on recent blocks.
+
+use std::cmp;
+use std::collections::VecDeque;
+
+use ethcore::client::BlockChainClient;
+use ethcore::ids::BlockId;
+use ethcore::miner::MinerService;
+use ethereum_types::{H256, U256};
+use parking_lot::Mutex;
+use stats::Corpus;
+
+/// Gas price oracle options.
+#[derive(Debug, Clone, PartialEq)]
+pub struct GasPriceOracleOptions {
+	/// Number of recent blocks analysed.
+	pub blocks: usize,
+	/// Percentile of recent gas prices suggested.
+	pub percentile: usize,
+	/// Suggestions never go below this price.
+	pub min_gas_price: U256,
+	/// Weight (in percent) of the latest estimate in the exponential moving average.
+	/// `100` disables smoothing.
+	pub smoothing: usize,
+}
+
+impl Default for GasPriceOracleOptions {
+	fn default() -> Self {
+		GasPriceOracleOptions {
+			blocks: 100,
+			percentile: 50,
+			min_gas_price: U256::zero(),
+			smoothing: 100,
+		}
+	}
+}
+
+#[derive(Debug, Default)]
+struct Window {
+	/// Gas prices of transactions in the analysed blocks, oldest first.
+	blocks: VecDeque<(H256, Vec<U256>)>,
+	/// Smoothed estimate as of the latest block.
+	estimate: Option<U256>,
+}
+
+/// Suggests gas prices from a rolling window of the latest blocks.
+///
+/// Only blocks imported since the last query are read from the client.
+#[derive(Debug)]
+pub struct GasPriceOracle {
+	options: GasPriceOracleOptions,
+	window: Mutex<Window>,
+}
+
+impl GasPriceOracle {
+	/// Creates new oracle with given options.
+	pub fn new(options: GasPriceOracleOptions) -> Self {
+		GasPriceOracle {
+			options,
+			window: Default::default(),
+		}
+	}
+
+	/// Suggested gas price, falling back to the miner's one if recent blocks had no transactions.
+	pub fn gas_price<C, M>(&self, client: &C, miner: &M) -> U256 where
+		C: BlockChainClient,
+		M: MinerService,
+	{
+		let estimate = {
+			let mut window = self.window.lock();
+			self.update(&mut window, client);
+			window.estimate
+		};
+
+		cmp::max(
+			estimate.unwrap_or_else(|| miner.sensible_gas_price()),
+			self.options.min_gas_price,
+		)
+	}
+
+	/// Sorted gas prices of transactions in the analysed blocks.
+	pub fn corpus<C: BlockChainClient>(&self, client: &C) -> Corpus<U256> {
+		let mut window = self.window.lock();
+		self.update(&mut window, client);
+		window.blocks.iter()
+			.flat_map(|&(_, ref prices)| prices.iter().cloned())
+			.collect::<Vec<_>>()
+			.into()
+	}
+
+	fn update<C: BlockChainClient>(&self, window: &mut Window, client: &C) {
+		let best = client.chain_info().best_block_hash;
+		if window.blocks.back().map(|&(ref hash, _)| *hash) == Some(best) {
+			return;
+		}
+
+		// Walk back until a block already in the window, dropping blocks retracted by a reorg.
+		let mut new_blocks = Vec::new();
+		let mut hash = best;
+		let mut connected = false;
+		while new_blocks.len() < self.options.blocks {
+			if let Some(position) = window.blocks.iter().position(|&(ref h, _)| *h == hash) {
+				window.blocks.truncate(position + 1);
+				connected = true;
+				break;
+			}
+
+			let block = match client.block(BlockId::Hash(hash)) {
+				Some(block) => block,
+				None => break,
+			};
+			if block.number() == 0 {
+				break;
+			}
+
+			let prices = block.transaction_views().iter().map(|t| t.gas_price()).collect();
+			new_blocks.push((hash, prices));
+			hash = block.parent_hash();
+		}
+
+		if !connected {
+			window.blocks.clear();
+		}
+		window.blocks.extend(new_blocks.into_iter().rev());
+		while window.blocks.len() > self.options.blocks {
+			window.blocks.pop_front();
+		}
+
+		let corpus: Corpus<U256> = window.blocks.iter()
+			.flat_map(|&(_, ref prices)| prices.iter().cloned())
+			.collect::<Vec<_>>()
+			.into();
+		if let Some(latest) = corpus.percentile(self.options.percentile).cloned() {
+			window.estimate = Some(smooth(window.estimate, latest, self.options.smoothing));
+		}
+	}
+}
+
+/// Exponential moving average giving `weight` percent to the latest value.
+fn smooth(previous: Option<U256>, latest: U256, weight: usize) -> U256 {
+	let weight = cmp::min(weight, 100);
+	match previous {
+		Some(previous) => (latest * U256::from(weight) + previous * U256::from(100 - weight)) / U256::from(100),
+		None => latest,
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use ethcore::client::{TestBlockChainClient, EachBlockWith};
+	use v1::tests::helpers::TestMinerService;
+
+	#[test]
+	fn should_smooth_estimates() {
+		assert_eq!(smooth(None, 100.into(), 20), 100.into());
+		assert_eq!(smooth(Some(100.into()), 200.into(), 20), 120.into());
+		assert_eq!(smooth(Some(100.into()), 200.into(), 100), 200.into());
+	}
+
+	#[test]
+	fn should_analyse_rolling_window_of_blocks() {
+		let client = TestBlockChainClient::default();
+		let miner = TestMinerService::default();
+		let oracle = GasPriceOracle::new(GasPriceOracleOptions {
+			blocks: 2,
+			..Default::default()
+		});
+
+		// no transactions yet, miner's suggestion is used
+		assert_eq!(oracle.gas_price(&client, &miner), miner.sensible_gas_price());
+
+		client.add_blocks(2, EachBlockWith::Transaction);
+		assert_eq!(oracle.corpus(&client).len(), 2);
+		assert_eq!(oracle.gas_price(&client, &miner), 200_000_000_000u64.into());
+
+		client.add_blocks(1, EachBlockWith::Nothing);
+		assert_eq!(oracle.corpus(&client).len(), 1);
+	}
+
+	#[test]
+	fn should_never_suggest_less_than_minimum() {
+		let client = TestBlockChainClient::default();
+		let miner = TestMinerService::default();
+		let min_gas_price = U256::from(300_000_000_000u64);
+		let oracle = GasPriceOracle::new(GasPriceOracleOptions {
+			min_gas_price,
+			..Default::default()
+		});
+
+		client.add_blocks(2, EachBlockWith::Transaction);
+		assert_eq!(oracle.gas_price(&client, &miner), min_gas_price);
+	}
+}
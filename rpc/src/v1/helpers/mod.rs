@@ -20,12 +20,17 @@ pub mod errors;
 pub mod block_import;
 pub mod dispatch;
 pub mod fake_sign;
+pub mod fee_history;
+pub mod gas_flame;
 pub mod ipfs;
 pub mod light_fetch;
 pub mod nonce;
 pub mod oneshot;
 pub mod secretstore;
+pub mod signature;
+pub mod sync_progress;
 
+mod gas_price_oracle;
 mod network_settings;
 mod poll_filter;
 mod poll_manager;
@@ -34,11 +39,13 @@ mod signer;
 mod signing_queue;
 mod subscribers;
 mod subscription_manager;
+mod subscription_queue;
 
 pub use self::dispatch::{Dispatcher, FullDispatcher};
+pub use self::gas_price_oracle::{GasPriceOracle, GasPriceOracleOptions};
 pub use self::network_settings::NetworkSettings;
 pub use self::poll_manager::PollManager;
-pub use self::poll_filter::{PollFilter, SyncPollFilter, limit_logs};
+pub use self::poll_filter::{PollFilter, SyncPollFilter, limit_logs, check_logs_range};
 pub use self::requests::{
 	TransactionRequest, FilledTransactionRequest, ConfirmationRequest, ConfirmationPayload, CallRequest,
 };
@@ -50,6 +57,7 @@ pub use self::signing_queue::{
 pub use self::signer::SignerService;
 pub use self::subscribers::Subscribers;
 pub use self::subscription_manager::GenericPollManager;
+pub use self::subscription_queue::{QueuedSink, SubscriptionQueueConfig, OverflowPolicy};
 
 pub fn to_url(address: &Option<::Host>) -> Option<String> {
 	address.as_ref().map(|host| (**host).to_owned())
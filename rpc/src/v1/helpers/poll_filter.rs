@@ -21,8 +21,11 @@ use std::{
 	sync::Arc,
 };
 use ethereum_types::H256;
+use jsonrpc_core::Result;
 use parking_lot::Mutex;
+use ethcore::client::BlockId;
 use ethcore::filter::Filter;
+use v1::helpers::errors;
 use v1::types::Log;
 
 pub type BlockNumber = u64;
@@ -70,3 +73,20 @@ pub fn limit_logs(mut logs: Vec<Log>, limit: Option<usize>) -> Vec<Log> {
 		_ => logs,
 	}
 }
+
+/// Rejects filters spanning more than `max_range` blocks.
+/// Blocks which can't be resolved are not checked here, the query itself reports them.
+pub fn check_logs_range<F>(filter: &Filter, max_range: Option<u64>, block_number: F) -> Result<()> where
+	F: Fn(BlockId) -> Option<BlockNumber>,
+{
+	let max_range = match max_range {
+		Some(max_range) => max_range,
+		None => return Ok(()),
+	};
+
+	match (block_number(filter.from_block.clone()), block_number(filter.to_block.clone())) {
+		(Some(from), Some(to)) if to >= from && to - from + 1 > max_range =>
+			Err(errors::logs_range_too_large(to - from + 1, max_range)),
+		_ => Ok(()),
+	}
+}
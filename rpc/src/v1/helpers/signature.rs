@@ -0,0 +1,115 @@
+// Copyright 2015-2018 Parity Technologies (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Batched signature verification.
+
+use ethereum_types::{H256, H520};
+use ethkey::{public_to_address, recover, Signature};
+use futures_cpupool::CpuPool;
+use jsonrpc_core::{BoxFuture, Error};
+use jsonrpc_core::futures::{future, Future};
+use transaction::signature::check_replay_protection;
+use v1::helpers::dispatch::eth_data_hash;
+use v1::types::{SignatureVerificationRequest, SignatureVerification};
+
+/// Number of signatures verified by a single pool task.
+const VERIFICATION_BATCH_SIZE: usize = 64;
+
+/// Verify signed messages, spreading the recovery over the pool.
+pub fn verify_signatures(pool: &CpuPool, requests: Vec<SignatureVerificationRequest>) -> BoxFuture<Vec<SignatureVerification>> {
+	let batches = requests.chunks(VERIFICATION_BATCH_SIZE)
+		.map(|batch| {
+			let batch = batch.to_vec();
+			pool.spawn_fn(move || Ok::<_, Error>(batch.into_iter().map(verify_signature).collect::<Vec<_>>()))
+		})
+		.collect::<Vec<_>>();
+
+	Box::new(future::join_all(batches).map(|batches| batches.into_iter().flat_map(|batch| batch).collect()))
+}
+
+/// Verify a single signed message using `eth_sign` rules.
+pub fn verify_signature(request: SignatureVerificationRequest) -> SignatureVerification {
+	let signature: H520 = request.signature.into();
+	let recovered = standard_signature(&signature)
+		.and_then(|signature| recover(&signature, &eth_data_hash(request.message.0)).ok())
+		.map(|public| public_to_address(&public));
+
+	SignatureVerification {
+		valid: recovered == Some(request.address.into()),
+		recovered: recovered.map(Into::into),
+	}
+}
+
+/// Normalize `v` given in raw (0/1), electrum (27/28) or EIP-155 notation.
+fn standard_signature(signature: &H520) -> Option<Signature> {
+	let v = match signature[64] {
+		v @ 0 | v @ 1 => v,
+		v => check_replay_protection(v as u64),
+	};
+	let signature = Signature::from_rsv(&H256::from_slice(&signature[0..32]), &H256::from_slice(&signature[32..64]), v);
+
+	if signature.is_valid() {
+		Some(signature)
+	} else {
+		None
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use ethkey::{Generator, Random, sign};
+	use v1::helpers::dispatch::eth_data_hash;
+	use v1::types::SignatureVerificationRequest;
+	use super::verify_signature;
+
+	fn request(v: u8) -> (SignatureVerificationRequest, ::ethereum_types::Address) {
+		let key = Random.generate().unwrap();
+		let message = vec![1, 2, 3];
+		let mut signature = sign(key.secret(), &eth_data_hash(message.clone())).unwrap().into_electrum();
+		signature[64] = signature[64] - 27 + v;
+		let request = SignatureVerificationRequest {
+			message: message.into(),
+			signature: signature.into(),
+			address: key.address().into(),
+		};
+		(request, key.address())
+	}
+
+	#[test]
+	fn should_accept_all_v_notations() {
+		for &v in &[0, 27, 37] {
+			let (request, address) = request(v);
+			let result = verify_signature(request);
+			assert!(result.valid);
+			assert_eq!(result.recovered, Some(address.into()));
+		}
+	}
+
+	#[test]
+	fn should_reject_other_signer() {
+		let (mut request, _) = request(27);
+		request.address = Default::default();
+		assert!(!verify_signature(request).valid);
+	}
+
+	#[test]
+	fn should_reject_invalid_v() {
+		let (request, _) = request(5);
+		let result = verify_signature(request);
+		assert!(!result.valid);
+		assert_eq!(result.recovered, None);
+	}
+}
@@ -92,6 +92,24 @@ impl<T> Subscribers<T> {
 			_ => None,
 		}
 	}
+
+	/// Assigns id and adds a subscriber to the list, converting its sink with given function.
+	pub fn push_with<S, F>(&mut self, sub: Subscriber<S>, f: F) where
+		F: FnOnce(Sink<S>) -> T,
+	{
+		let id = self.next_id();
+		if let Ok(sink) = sub.assign_id(SubscriptionId::String(id.as_string())) {
+			debug!(target: "pubsub", "Adding subscription id={:?}", id);
+			self.subscriptions.insert(id, f(sink));
+		}
+	}
+
+	/// Removes all subscriptions not matching given predicate.
+	pub fn retain<F>(&mut self, mut f: F) where
+		F: FnMut(&T) -> bool,
+	{
+		self.subscriptions.retain(|_, sub| f(sub));
+	}
 }
 
 impl<T> Subscribers<Sink<T>> {
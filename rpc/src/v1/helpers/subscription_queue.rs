@@ -0,0 +1,304 @@
+// Copyright 2015-2018 Parity Technologies (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Bounded queues of notifications waiting to be sent to subscribers.
+
+use std::collections::VecDeque;
+use std::str::FromStr;
+use std::sync::Arc;
+
+use jsonrpc_core::futures::{future, Future};
+use jsonrpc_core::futures::future::{Either, Loop};
+use jsonrpc_macros::pubsub::Sink;
+use parity_reactor::Remote;
+use parking_lot::Mutex;
+
+use v1::types::pubsub;
+
+/// What to do with a notification when the queue of a subscriber is full.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum OverflowPolicy {
+	/// Drop the oldest queued notification.
+	DropOldest,
+	/// Close the subscription.
+	Close,
+	/// Merge the notification into the newest queued one.
+	/// Logs are merged into a single batch, other notifications replace the queued one.
+	Coalesce,
+}
+
+impl FromStr for OverflowPolicy {
+	type Err = String;
+
+	fn from_str(s: &str) -> Result<Self, Self::Err> {
+		match s {
+			"drop-oldest" => Ok(OverflowPolicy::DropOldest),
+			"close" => Ok(OverflowPolicy::Close),
+			"coalesce" => Ok(OverflowPolicy::Coalesce),
+			other => Err(format!("Invalid overflow policy: {}, expected one of: drop-oldest, close, coalesce.", other)),
+		}
+	}
+}
+
+/// Configuration of subscribers' notification queues.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SubscriptionQueueConfig {
+	/// Maximal number of notifications waiting to be sent to a single subscriber.
+	pub capacity: usize,
+	/// Policy applied when the queue is full.
+	pub overflow: OverflowPolicy,
+	/// Maximal number of logs sent in a single notification, `1` disables batching.
+	pub logs_batch_size: usize,
+}
+
+impl Default for SubscriptionQueueConfig {
+	fn default() -> Self {
+		SubscriptionQueueConfig {
+			capacity: 1024,
+			overflow: OverflowPolicy::DropOldest,
+			logs_batch_size: 1,
+		}
+	}
+}
+
+#[derive(Debug, Default)]
+struct Queue {
+	notifications: VecDeque<pubsub::Result>,
+	sending: bool,
+	closed: bool,
+}
+
+impl Queue {
+	/// Queues a notification. Returns `true` if the notifications need to start being sent.
+	fn push(&mut self, notification: pubsub::Result, config: &SubscriptionQueueConfig) -> bool {
+		if self.closed {
+			return false;
+		}
+
+		if !self.notifications.is_empty() && self.notifications.len() >= config.capacity {
+			match config.overflow {
+				OverflowPolicy::DropOldest => {
+					trace!(target: "pubsub", "Subscriber queue full, dropping oldest notification.");
+					self.notifications.pop_front();
+				},
+				OverflowPolicy::Close => {
+					debug!(target: "pubsub", "Subscriber queue full, closing subscription.");
+					self.close();
+					return false;
+				},
+				OverflowPolicy::Coalesce => {
+					if let Some(last) = self.notifications.back_mut() {
+						coalesce(last, notification);
+					}
+					return false;
+				},
+			}
+		}
+
+		self.notifications.push_back(notification);
+		if self.sending {
+			false
+		} else {
+			self.sending = true;
+			true
+		}
+	}
+
+	fn pop(&mut self) -> Option<pubsub::Result> {
+		let next = self.notifications.pop_front();
+		if next.is_none() {
+			self.sending = false;
+		}
+		next
+	}
+
+	fn close(&mut self) {
+		self.closed = true;
+		self.notifications.clear();
+	}
+}
+
+fn coalesce(last: &mut pubsub::Result, next: pubsub::Result) {
+	use self::pubsub::Result::{Log, Logs};
+
+	let batch = match *last {
+		Log(ref log) => Some(vec![log.clone()]),
+		_ => None,
+	};
+	if let Some(batch) = batch {
+		*last = Logs(batch);
+	}
+
+	match (last, next) {
+		(&mut Logs(ref mut logs), Log(log)) => logs.push(log),
+		(&mut Logs(ref mut logs), Logs(more)) => logs.extend(more),
+		(last, next) => *last = next,
+	}
+}
+
+/// Sink of a subscriber with a bounded queue of pending notifications.
+///
+/// Notifications are sent one at a time, so a slow subscriber only fills up its own queue.
+#[derive(Clone)]
+pub struct QueuedSink {
+	sink: Sink<pubsub::Result>,
+	queue: Arc<Mutex<Queue>>,
+	config: SubscriptionQueueConfig,
+	remote: Remote,
+}
+
+impl QueuedSink {
+	/// Creates new queue in front of given sink.
+	pub fn new(sink: Sink<pubsub::Result>, config: SubscriptionQueueConfig, remote: Remote) -> Self {
+		QueuedSink {
+			sink,
+			queue: Default::default(),
+			config,
+			remote,
+		}
+	}
+
+	/// Returns queue configuration.
+	pub fn config(&self) -> &SubscriptionQueueConfig {
+		&self.config
+	}
+
+	/// Returns true if the subscription was closed because of a queue overflow or a closed transport.
+	pub fn is_closed(&self) -> bool {
+		self.queue.lock().closed
+	}
+
+	/// Queues a notification to be sent to the subscriber.
+	pub fn notify(&self, notification: pubsub::Result) {
+		if self.queue.lock().push(notification, &self.config) {
+			self.send_queued();
+		}
+	}
+
+	fn send_queued(&self) {
+		let sink = self.sink.clone();
+		let queue = self.queue.clone();
+		let failed = self.queue.clone();
+		self.remote.spawn(future::loop_fn((), move |_| {
+			let next = queue.lock().pop();
+			match next {
+				Some(notification) => Either::A(sink.notify(Ok(notification)).map(|_| Loop::Continue(()))),
+				None => Either::B(future::ok(Loop::Break(()))),
+			}
+		}).map_err(move |e| {
+			warn!(target: "rpc", "Unable to send notification: {}", e);
+			failed.lock().close();
+		}));
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use v1::types::{pubsub, Log};
+	use super::{Queue, OverflowPolicy, SubscriptionQueueConfig};
+
+	fn config(overflow: OverflowPolicy) -> SubscriptionQueueConfig {
+		SubscriptionQueueConfig {
+			capacity: 2,
+			overflow,
+			logs_batch_size: 1,
+		}
+	}
+
+	fn hash(n: u64) -> pubsub::Result {
+		pubsub::Result::TransactionHash(n.into())
+	}
+
+	fn log(n: u64) -> Log {
+		Log {
+			address: Default::default(),
+			topics: vec![],
+			data: Default::default(),
+			block_hash: None,
+			block_number: None,
+			transaction_hash: None,
+			transaction_index: None,
+			log_index: Some(n.into()),
+			transaction_log_index: None,
+			log_type: "mined".into(),
+			removed: false,
+		}
+	}
+
+	#[test]
+	fn should_start_sending_once() {
+		let mut queue = Queue::default();
+		let config = config(OverflowPolicy::DropOldest);
+
+		assert!(queue.push(hash(1), &config));
+		assert!(!queue.push(hash(2), &config));
+		assert_eq!(queue.pop(), Some(hash(1)));
+		assert_eq!(queue.pop(), Some(hash(2)));
+		assert_eq!(queue.pop(), None);
+		assert!(queue.push(hash(3), &config));
+	}
+
+	#[test]
+	fn should_drop_oldest_notification() {
+		let mut queue = Queue::default();
+		let config = config(OverflowPolicy::DropOldest);
+
+		queue.push(hash(1), &config);
+		queue.push(hash(2), &config);
+		queue.push(hash(3), &config);
+
+		assert_eq!(queue.pop(), Some(hash(2)));
+		assert_eq!(queue.pop(), Some(hash(3)));
+		assert_eq!(queue.pop(), None);
+	}
+
+	#[test]
+	fn should_close_on_overflow() {
+		let mut queue = Queue::default();
+		let config = config(OverflowPolicy::Close);
+
+		queue.push(hash(1), &config);
+		queue.push(hash(2), &config);
+		queue.push(hash(3), &config);
+
+		assert!(queue.closed);
+		assert_eq!(queue.pop(), None);
+		assert!(!queue.push(hash(4), &config));
+	}
+
+	#[test]
+	fn should_coalesce_logs_into_batch() {
+		let mut queue = Queue::default();
+		let config = config(OverflowPolicy::Coalesce);
+
+		queue.push(pubsub::Result::Log(log(1)), &config);
+		queue.push(pubsub::Result::Log(log(2)), &config);
+		queue.push(pubsub::Result::Log(log(3)), &config);
+		queue.push(pubsub::Result::Logs(vec![log(4), log(5)]), &config);
+
+		assert_eq!(queue.pop(), Some(pubsub::Result::Log(log(1))));
+		assert_eq!(queue.pop(), Some(pubsub::Result::Logs(vec![log(2), log(3), log(4), log(5)])));
+		assert_eq!(queue.pop(), None);
+	}
+
+	#[test]
+	fn should_parse_overflow_policy() {
+		assert_eq!("drop-oldest".parse(), Ok(OverflowPolicy::DropOldest));
+		assert_eq!("close".parse(), Ok(OverflowPolicy::Close));
+		assert_eq!("coalesce".parse(), Ok(OverflowPolicy::Coalesce));
+		assert!("block".parse::<OverflowPolicy>().is_err());
+	}
+}
@@ -0,0 +1,126 @@
+// Copyright 2015-2018 Parity Technologies (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Estimates of the time remaining until the current sync stage finishes.
+
+use std::time::{Duration, Instant};
+
+use ethcore::client::BlockQueueInfo;
+use sync::{SyncState, SyncStatus};
+
+use v1::helpers::block_import::is_major_importing;
+use v1::types::SyncStage;
+
+/// Samples taken more often (in milliseconds) are ignored, so that frequent polling doesn't skew the rate.
+const MIN_SAMPLE_INTERVAL_MS: u64 = 1000;
+/// Weight of the latest sample in the moving average of the progress rate.
+const SMOOTHING: f64 = 0.3;
+
+/// Returns the stage of the sync process.
+pub fn sync_stage(status: &SyncStatus, queue_info: BlockQueueInfo) -> SyncStage {
+	match status.state {
+		SyncState::WaitingPeers => SyncStage::WaitingPeers,
+		SyncState::SnapshotManifest => SyncStage::WarpManifest,
+		SyncState::SnapshotData => SyncStage::WarpChunks,
+		SyncState::SnapshotWaiting => SyncStage::WarpRestoring,
+		state if is_major_importing(Some(state), queue_info) => SyncStage::Blocks,
+		_ => SyncStage::Idle,
+	}
+}
+
+#[derive(Debug)]
+struct Sample {
+	stage: SyncStage,
+	time: Instant,
+	done: u64,
+}
+
+/// Moving average of the progress of a sync stage.
+#[derive(Debug, Default)]
+pub struct ProgressEstimator {
+	last: Option<Sample>,
+	rate: Option<f64>,
+}
+
+impl ProgressEstimator {
+	/// Records that `done` out of `total` units (blocks or chunks) of given stage are finished.
+	/// Returns the estimated number of seconds until the stage is finished.
+	pub fn update(&mut self, stage: SyncStage, now: Instant, done: u64, total: u64) -> Option<u64> {
+		let reset = match self.last {
+			Some(ref last) => last.stage != stage || done < last.done,
+			None => true,
+		};
+		if reset {
+			self.last = Some(Sample { stage, time: now, done });
+			self.rate = None;
+			return None;
+		}
+
+		let (last_time, last_done) = {
+			let last = self.last.as_ref().expect("reset if there is no sample; qed");
+			(last.time, last.done)
+		};
+		let elapsed = now.duration_since(last_time);
+		if elapsed >= Duration::from_millis(MIN_SAMPLE_INTERVAL_MS) {
+			let seconds = elapsed.as_secs() as f64 + elapsed.subsec_nanos() as f64 / 1_000_000_000.0;
+			let rate = (done - last_done) as f64 / seconds;
+			self.rate = Some(match self.rate {
+				Some(average) => average + SMOOTHING * (rate - average),
+				None => rate,
+			});
+			self.last = Some(Sample { stage, time: now, done });
+		}
+
+		match self.rate {
+			Some(rate) if rate > 0.0 => Some((total.saturating_sub(done) as f64 / rate).ceil() as u64),
+			_ => None,
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use std::time::{Duration, Instant};
+	use v1::types::SyncStage;
+	use super::ProgressEstimator;
+
+	#[test]
+	fn should_estimate_remaining_time() {
+		// given
+		let mut estimator = ProgressEstimator::default();
+		let start = Instant::now();
+
+		// when
+		let first = estimator.update(SyncStage::Blocks, start, 100, 1100);
+		let too_early = estimator.update(SyncStage::Blocks, start + Duration::from_millis(100), 101, 1100);
+		let second = estimator.update(SyncStage::Blocks, start + Duration::from_secs(2), 300, 1100);
+
+		// then
+		assert_eq!(first, None);
+		assert_eq!(too_early, None);
+		assert_eq!(second, Some(8));
+	}
+
+	#[test]
+	fn should_reset_on_stage_change() {
+		let mut estimator = ProgressEstimator::default();
+		let start = Instant::now();
+
+		estimator.update(SyncStage::WarpChunks, start, 0, 10);
+		assert_eq!(estimator.update(SyncStage::WarpChunks, start + Duration::from_secs(1), 5, 10), Some(1));
+		assert_eq!(estimator.update(SyncStage::Blocks, start + Duration::from_secs(2), 5, 10), None);
+	}
+}
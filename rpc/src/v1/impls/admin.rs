@@ -0,0 +1,89 @@
+// Copyright 2015-2018 Parity Technologies (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Geth-compatible admin RPC implementation.
+
+use std::net::SocketAddr;
+use std::sync::Arc;
+
+use sync::{SyncProvider, ManageNetwork};
+use version::version;
+
+use jsonrpc_core::Result;
+use v1::helpers::{errors, NetworkSettings};
+use v1::traits::Admin;
+use v1::types::{NodeInfo, NodePorts, PeerInfo};
+
+/// Admin rpc implementation.
+pub struct AdminClient {
+	sync: Arc<SyncProvider>,
+	net: Arc<ManageNetwork>,
+	settings: Arc<NetworkSettings>,
+}
+
+impl AdminClient {
+	/// Creates new admin client.
+	pub fn new(sync: Arc<SyncProvider>, net: Arc<ManageNetwork>, settings: Arc<NetworkSettings>) -> Self {
+		AdminClient {
+			sync,
+			net,
+			settings,
+		}
+	}
+}
+
+impl Admin for AdminClient {
+	fn add_peer(&self, enode: String) -> Result<bool> {
+		self.net.add_reserved_peer(enode)
+			.map(|_| true)
+			.map_err(|e| errors::invalid_params("Peer address", e))
+	}
+
+	fn remove_peer(&self, enode: String) -> Result<bool> {
+		self.net.remove_reserved_peer(enode)
+			.map(|_| true)
+			.map_err(|e| errors::invalid_params("Peer address", e))
+	}
+
+	fn peers(&self) -> Result<Vec<PeerInfo>> {
+		Ok(self.sync.peers().into_iter().map(Into::into).collect())
+	}
+
+	fn node_info(&self) -> Result<NodeInfo> {
+		let enode = self.sync.enode().ok_or_else(errors::network_disabled)?;
+		let (id, address) = {
+			let mut parts = enode.trim_left_matches("enode://").splitn(2, '@');
+			let id = parts.next().unwrap_or_default().to_owned();
+			let address = parts.next()
+				.and_then(|address| address.split('?').next())
+				.and_then(|address| address.parse::<SocketAddr>().ok());
+			(id, address)
+		};
+		let port = address.map_or(self.settings.network_port, |address| address.port());
+
+		Ok(NodeInfo {
+			id,
+			name: version(),
+			ip: address.map_or_else(String::new, |address| address.ip().to_string()),
+			ports: NodePorts {
+				discovery: port,
+				listener: port,
+			},
+			listen_addr: address.map_or_else(String::new, |address| address.to_string()),
+			enode,
+		})
+	}
+}
@@ -16,18 +16,30 @@
 
 //! Debug APIs RPC implementation
 
+use std::collections::HashMap;
 use std::sync::Arc;
+use std::sync::atomic::{AtomicUsize, Ordering};
 
-use ethcore::client::BlockChainClient;
+use ethcore::client::{BlockChainClient, BlockId, Call, DebugSession, DebugState, EngineInfo, StateClient, StateInfo, TransactionId};
+use ethcore::error::CallError;
+use ethereum_types::U256 as EthU256;
+use parking_lot::Mutex;
 use transaction::LocalizedTransaction;
 
 use jsonrpc_core::Result;
+use jsonrpc_macros::Trailing;
+use v1::helpers::{errors, fake_sign};
 use v1::traits::Debug;
-use v1::types::{Block, Bytes, RichBlock, BlockTransactions, Transaction};
+use v1::types::{Block, BlockNumber, Bytes, CallRequest, DebugStep, DebugTrace, H256, RichBlock, BlockTransactions, StructLog, StructLogOptions, Transaction, U256};
+
+/// Maximal number of debug sessions active at once, each of them occupies a thread.
+const MAX_DEBUG_SESSIONS: usize = 16;
 
 /// Debug rpc implementation.
 pub struct DebugClient<C> {
 	client: Arc<C>,
+	sessions: Mutex<HashMap<u64, DebugSession>>,
+	next_session_id: AtomicUsize,
 }
 
 impl<C> DebugClient<C> {
@@ -35,11 +47,31 @@ impl<C> DebugClient<C> {
 	pub fn new(client: Arc<C>) -> Self {
 		Self {
 			client,
+			sessions: Mutex::new(HashMap::new()),
+			next_session_id: AtomicUsize::new(0),
 		}
 	}
 }
 
-impl<C: BlockChainClient + 'static> Debug for DebugClient<C> {
+fn block_id(block: BlockNumber) -> Result<BlockId> {
+	match block {
+		BlockNumber::Num(num) => Ok(BlockId::Number(num)),
+		BlockNumber::Earliest => Ok(BlockId::Earliest),
+		BlockNumber::Latest => Ok(BlockId::Latest),
+
+		BlockNumber::Pending => Err(errors::invalid_params("`BlockNumber::Pending` is not supported", ())),
+	}
+}
+
+fn session_key(session_id: U256) -> u64 {
+	let session_id: EthU256 = session_id.into();
+	session_id.low_u64()
+}
+
+impl<C, S> Debug for DebugClient<C> where
+	S: StateInfo + 'static,
+	C: BlockChainClient + StateClient<State=S> + Call<State=S> + EngineInfo + 'static
+{
 	fn bad_blocks(&self) -> Result<Vec<RichBlock>> {
 		fn cast<O, T: Copy + Into<O>>(t: &T) -> O {
 			(*t).into()
@@ -66,6 +98,8 @@ impl<C: BlockChainClient + 'static> Debug for DebugClient<C> {
 					difficulty: cast(block.header.difficulty()),
 					total_difficulty: None,
 					seal_fields: block.header.seal().into_iter().cloned().map(Into::into).collect(),
+					base_fee_per_gas: block.header.base_fee().map(Into::into),
+					canonical: None,
 					uncles: block.uncles.into_iter().map(|u| u.hash().into()).collect(),
 					transactions: BlockTransactions::Full(block.transactions
 						.into_iter()
@@ -89,6 +123,77 @@ impl<C: BlockChainClient + 'static> Debug for DebugClient<C> {
 			}
 		}).collect())
 	}
+
+	fn trace_transaction(&self, transaction_hash: H256, options: Trailing<StructLogOptions>) -> Result<DebugTrace> {
+		let options = options.unwrap_or_default();
+		let executed = self.client.replay_struct_logs(TransactionId::Hash(transaction_hash.into()), (&options).into())
+			.map_err(errors::call)?;
+
+		DebugTrace::new(executed, &options).map_err(errors::tracer)
+	}
+
+	fn trace_call(&self, request: CallRequest, block: BlockNumber, options: Trailing<StructLogOptions>) -> Result<DebugTrace> {
+		let options = options.unwrap_or_default();
+		let request = CallRequest::into(request);
+		let signed = fake_sign::sign_call(request)?;
+
+		let id = block_id(block)?;
+		let mut state = self.client.state_at(id).ok_or(errors::state_pruned())?;
+		let header = self.client.block_header(id).ok_or(errors::state_pruned())?;
+
+		let executed = self.client.call_struct_logs(&signed, (&options).into(), &mut state, &header.decode(self.client.engine().params().eip1559_transition).map_err(errors::decode)?)
+			.map_err(errors::call)?;
+
+		DebugTrace::new(executed, &options).map_err(errors::tracer)
+	}
+
+	fn debug_start_transaction(&self, request: CallRequest, block: Trailing<BlockNumber>) -> Result<U256> {
+		let request = CallRequest::into(request);
+		let signed = fake_sign::sign_call(request)?;
+		let id = block_id(block.unwrap_or_default())?;
+		let header = self.client.block_header(id).ok_or(errors::state_pruned())?.decode(self.client.engine().params().eip1559_transition).map_err(errors::decode)?;
+
+		if self.sessions.lock().len() >= MAX_DEBUG_SESSIONS {
+			return Err(errors::debug_session_limit(MAX_DEBUG_SESSIONS));
+		}
+
+		let client = self.client.clone();
+		let session = DebugSession::start(move |tracer| {
+			let mut state = client.state_at(id).ok_or(CallError::StatePruned)?;
+			client.call_stepped(&signed, tracer, &mut state, &header)
+		}).map_err(|e| errors::internal("Unable to start debug session", e))?;
+
+		let session_id = self.next_session_id.fetch_add(1, Ordering::SeqCst) as u64;
+		self.sessions.lock().insert(session_id, session);
+		Ok(session_id.into())
+	}
+
+	fn debug_step(&self, session_id: U256) -> Result<DebugStep> {
+		let session_id = session_key(session_id);
+		let mut sessions = self.sessions.lock();
+		let state = sessions.get_mut(&session_id).ok_or_else(errors::debug_session_not_found)?.step().clone();
+
+		match state {
+			DebugState::Paused(mut log) => {
+				// stack, memory and storage are available through `parity_debugInspect`
+				log.stack = None;
+				log.memory = None;
+				log.storage = None;
+				Ok(DebugStep::Paused(log.into()))
+			},
+			DebugState::Finished(result) => {
+				sessions.remove(&session_id);
+				result.map(|executed| DebugStep::Finished(executed.into())).map_err(errors::call)
+			},
+		}
+	}
+
+	fn debug_inspect(&self, session_id: U256) -> Result<StructLog> {
+		match self.sessions.lock().get(&session_key(session_id)).map(DebugSession::state) {
+			Some(&DebugState::Paused(ref log)) => Ok(log.clone().into()),
+			_ => Err(errors::debug_session_not_found()),
+		}
+	}
 }
 
 fn serialize<T: ::serde::Serialize>(t: &T) -> String {
@@ -0,0 +1,89 @@
+// Copyright 2015-2018 Parity Technologies (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+//! External consensus engine RPC implementation.
+
+use std::sync::Arc;
+
+use ethcore::block_status::BlockStatus;
+use ethcore::client::{BlockChainClient, BlockId, EngineInfo};
+use ethcore::error::{Error as EthcoreError, ErrorKind as EthcoreErrorKind, ImportErrorKind};
+use ethcore::verification::queue::kind::blocks::Unverified;
+
+use jsonrpc_core::Result;
+use v1::helpers::errors;
+use v1::traits::Engine;
+use v1::types::{Bytes, EngineHead, PayloadState, PayloadStatus};
+
+/// Engine rpc implementation.
+pub struct EngineClient<C> {
+	client: Arc<C>,
+}
+
+impl<C> EngineClient<C> {
+	/// Creates new engine client.
+	pub fn new(client: Arc<C>) -> Self {
+		EngineClient {
+			client,
+		}
+	}
+}
+
+impl<C: BlockChainClient + 'static> EngineClient<C> {
+	fn current_head(&self) -> EngineHead {
+		let info = self.client.chain_info();
+		let state_root = self.client.block_header(BlockId::Hash(info.best_block_hash))
+			.map(|header| header.state_root())
+			.unwrap_or_default();
+
+		EngineHead {
+			number: info.best_block_number.into(),
+			hash: info.best_block_hash.into(),
+			state_root: state_root.into(),
+		}
+	}
+}
+
+impl<C: BlockChainClient + EngineInfo + 'static> Engine for EngineClient<C> {
+	fn new_block(&self, block: Bytes) -> Result<PayloadStatus> {
+		let block = Unverified::from_rlp(block.into_vec(), self.client.engine().params().eip1559_transition).map_err(errors::rlp)?;
+		let hash = block.header.hash();
+
+		let (status, error) = match self.client.import_block_sync(block) {
+			Ok(_) => match self.client.block_status(BlockId::Hash(hash)) {
+				BlockStatus::InChain => (PayloadState::Valid, None),
+				BlockStatus::Queued => (PayloadState::Accepted, None),
+				BlockStatus::Bad | BlockStatus::Unknown => (PayloadState::Invalid, self.client.bad_blocks()
+					.into_iter()
+					.find(|&(ref block, _)| block.header.hash() == hash)
+					.map(|(_, reason)| reason)),
+			},
+			Err(EthcoreError(EthcoreErrorKind::Import(ImportErrorKind::AlreadyInChain), _)) => (PayloadState::Known, None),
+			Err(err) => (PayloadState::Invalid, Some(format!("{:?}", err))),
+		};
+
+		Ok(PayloadStatus {
+			status,
+			block_hash: hash.into(),
+			head: self.current_head(),
+			error,
+		})
+	}
+
+	fn head(&self) -> Result<EngineHead> {
+		Ok(self.current_head())
+	}
+}
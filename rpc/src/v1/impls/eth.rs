@@ -16,41 +16,51 @@
 
 //! Eth rpc implementation.
 
+use std::cmp;
 use std::thread;
 use std::time::{Instant, Duration, SystemTime, UNIX_EPOCH};
 use std::sync::Arc;
 
-use rlp::{self, Rlp};
+use hash::keccak;
+use rlp;
 use ethereum_types::{U256, H64, H256, Address};
+use memory_cache::MemoryLruCache;
 use parking_lot::Mutex;
 
 use ethash::{self, SeedHashCompute};
 use ethcore::account_provider::AccountProvider;
-use ethcore::client::{BlockChainClient, BlockId, TransactionId, UncleId, StateOrBlock, StateClient, StateInfo, Call, EngineInfo};
+use ethcore::client::{BlockChainClient, BlockId, TransactionId, UncleId, StateOrBlock, StateClient, StateInfo, Call, EngineInfo, ProvingBlockChainClient};
 use ethcore::filter::Filter as EthcoreFilter;
-use ethcore::header::{BlockNumber as EthBlockNumber};
+use ethcore::header::{BlockNumber as EthBlockNumber, Header};
 use ethcore::miner::{self, MinerService};
 use ethcore::snapshot::SnapshotService;
 use ethcore::encoded;
 use sync::SyncProvider;
 use miner::external::ExternalMinerService;
-use transaction::{SignedTransaction, LocalizedTransaction};
+use transaction::{SignedTransaction, LocalizedTransaction, UnverifiedTransaction};
 
 use jsonrpc_core::{BoxFuture, Result};
 use jsonrpc_core::futures::future;
 use jsonrpc_macros::Trailing;
 
-use v1::helpers::{errors, limit_logs, fake_sign};
-use v1::helpers::dispatch::{FullDispatcher, default_gas_price};
+use v1::helpers::{errors, limit_logs, check_logs_range, fake_sign, GasPriceOracle, GasPriceOracleOptions};
+use v1::helpers::fee_history::{self, MAX_FEE_HISTORY_BLOCKS};
+use v1::helpers::dispatch::FullDispatcher;
 use v1::helpers::block_import::is_major_importing;
-use v1::traits::Eth;
+use v1::traits::{Eth, EthCallOverride};
 use v1::types::{
-	RichBlock, Block, BlockTransactions, BlockNumber, Bytes, SyncStatus, SyncInfo,
-	Transaction, CallRequest, Index, Filter, Log, Receipt, Work,
+	RichBlock, Block, BlockTransactions, BlockNumber, BundleCall, BundleCallResult, Bytes, SyncStatus, SyncInfo,
+	Transaction, CallRequest, Index, Filter, Log, Receipt, Work, EthAccount, StorageProof, FeeHistory, StateOverride,
 	H64 as RpcH64, H256 as RpcH256, H160 as RpcH160, U256 as RpcU256, block_number_to_id,
 };
 use v1::metadata::Metadata;
 
+/// Maximal number of calls executed by a single `eth_callMany` request.
+const MAX_BUNDLE_CALLS: usize = 256;
+
+/// Number of recent blocks sampled to suggest a max priority fee per gas.
+const PRIORITY_FEE_SAMPLE_BLOCKS: usize = 20;
+
 const EXTRA_INFO_PROOF: &str = "Object exists in blockchain (fetched earlier), extra_info is always available if object exists; qed";
 
 /// Eth RPC options
@@ -64,7 +74,11 @@ pub struct EthClientOptions {
 	/// Gas Price Percentile used as default gas price.
 	pub gas_price_percentile: usize,
 	/// Set the timeout for the internal poll manager
-	pub poll_lifetime: u32
+	pub poll_lifetime: u32,
+	/// Maximal size in bytes of cached `eth_call` results, `0` disables the cache.
+	pub call_cache_size: usize,
+	/// Maximal number of blocks a single logs query can span.
+	pub max_logs_range: Option<u64>,
 }
 
 impl EthClientOptions {
@@ -85,6 +99,8 @@ impl Default for EthClientOptions {
 			send_block_number_in_get_work: true,
 			poll_lifetime: 60u32,
 			gas_price_percentile: 50,
+			call_cache_size: 0,
+			max_logs_range: None,
 		}
 	}
 }
@@ -104,9 +120,29 @@ pub struct EthClient<C, SN: ?Sized, S: ?Sized, M, EM> where
 	miner: Arc<M>,
 	external_miner: Arc<EM>,
 	seed_compute: Mutex<SeedHashCompute>,
+	call_cache: Mutex<CallCache>,
+	gas_price_oracle: Arc<GasPriceOracle>,
 	options: EthClientOptions,
 }
 
+/// Identifies `eth_call` executed against given block.
+///
+/// The block hash is a part of the key as well as the state root,
+/// since calls can observe block number and timestamp.
+#[derive(Debug, PartialEq, Eq, Hash)]
+struct CallKey {
+	state_root: H256,
+	block_hash: H256,
+	sender: Address,
+	transaction: H256,
+}
+
+/// Outputs of recent `eth_call`s, dropped whenever the best block changes.
+struct CallCache {
+	best_block: H256,
+	outputs: MemoryLruCache<CallKey, Vec<u8>>,
+}
+
 #[derive(Debug)]
 enum BlockNumberOrId {
 	Number(BlockNumber),
@@ -165,10 +201,35 @@ impl<C, SN: ?Sized, S: ?Sized, M, EM, T: StateInfo + 'static> EthClient<C, SN, S
 			accounts: accounts.clone(),
 			external_miner: em.clone(),
 			seed_compute: Mutex::new(SeedHashCompute::default()),
+			call_cache: Mutex::new(CallCache {
+				best_block: H256::default(),
+				outputs: MemoryLruCache::new(options.call_cache_size),
+			}),
+			gas_price_oracle: Arc::new(GasPriceOracle::new(GasPriceOracleOptions {
+				percentile: options.gas_price_percentile,
+				..Default::default()
+			})),
 			options: options,
 		}
 	}
 
+	/// Use given oracle for `eth_gasPrice`, e.g. to share it with other APIs.
+	pub fn with_gas_price_oracle(mut self, gas_price_oracle: Arc<GasPriceOracle>) -> Self {
+		self.gas_price_oracle = gas_price_oracle;
+		self
+	}
+
+	/// Returns cached output of a call, clearing the cache first if there is a new best block.
+	fn cached_call(&self, key: &CallKey) -> Option<Vec<u8>> {
+		let best_block = self.client.chain_info().best_block_hash;
+		let mut cache = self.call_cache.lock();
+		if cache.best_block != best_block {
+			cache.best_block = best_block;
+			cache.outputs = MemoryLruCache::new(self.options.call_cache_size);
+		}
+		cache.outputs.get_mut(key).cloned()
+	}
+
 	fn rich_block(&self, id: BlockNumberOrId, include_txs: bool) -> Result<Option<RichBlock>> {
 		let client = &self.client;
 
@@ -220,6 +281,7 @@ impl<C, SN: ?Sized, S: ?Sized, M, EM, T: StateInfo + 'static> EthClient<C, SN, S
 		match (block, difficulty) {
 			(Some(block), Some(total_difficulty)) => {
 				let view = block.header_view();
+				let header = block.decode_header(self.client.engine().params().eip1559_transition);
 				Ok(Some(RichBlock {
 					inner: Block {
 						hash: match is_pending {
@@ -247,7 +309,9 @@ impl<C, SN: ?Sized, S: ?Sized, M, EM, T: StateInfo + 'static> EthClient<C, SN, S
 						timestamp: view.timestamp().into(),
 						difficulty: view.difficulty().into(),
 						total_difficulty: Some(total_difficulty.into()),
-						seal_fields: view.seal().into_iter().map(Into::into).collect(),
+						seal_fields: header.seal().iter().cloned().map(Into::into).collect(),
+						base_fee_per_gas: header.base_fee().map(Into::into),
+						canonical: None,
 						uncles: block.uncle_hashes().into_iter().map(Into::into).collect(),
 						transactions: match include_txs {
 							true => BlockTransactions::Full(block.view().localized_transactions().into_iter().map(|t| Transaction::from_localized(t)).collect()),
@@ -345,7 +409,7 @@ impl<C, SN: ?Sized, S: ?Sized, M, EM, T: StateInfo + 'static> EthClient<C, SN, S
 				let uncle_id = UncleId { block: block_id, position };
 
 				let uncle = match client.uncle(uncle_id) {
-					Some(hdr) => match hdr.decode() {
+					Some(hdr) => match hdr.decode(self.client.engine().params().eip1559_transition) {
 						Ok(h) => h,
 						Err(e) => return Err(errors::decode(e))
 					},
@@ -388,6 +452,8 @@ impl<C, SN: ?Sized, S: ?Sized, M, EM, T: StateInfo + 'static> EthClient<C, SN, S
 				receipts_root: uncle.receipts_root().clone().into(),
 				extra_data: uncle.extra_data().clone().into(),
 				seal_fields: uncle.seal().into_iter().cloned().map(Into::into).collect(),
+				base_fee_per_gas: uncle.base_fee().map(Into::into),
+				canonical: None,
 				uncles: vec![],
 				transactions: BlockTransactions::Hashes(vec![]),
 			},
@@ -396,6 +462,23 @@ impl<C, SN: ?Sized, S: ?Sized, M, EM, T: StateInfo + 'static> EthClient<C, SN, S
 		Ok(Some(block))
 	}
 
+	/// Decode the header of the given block.
+	fn decoded_header(&self, id: BlockId) -> Option<Header> {
+		self.client.block_header(id)?.decode(self.client.engine().params().eip1559_transition).ok()
+	}
+
+	/// Effective priority fee and gas used of every transaction in the given block.
+	fn block_priority_fees(&self, header: &Header) -> Vec<(U256, U256)> {
+		let id = BlockId::Hash(header.hash());
+		match (self.client.block(id), self.client.block_receipts(id)) {
+			(Some(block), Some(receipts)) => block.transactions().iter()
+				.zip(receipts.iter())
+				.map(|(tx, receipt)| (tx.effective_priority_fee(header.base_fee()), receipt.gas_used))
+				.collect(),
+			_ => Vec::new(),
+		}
+	}
+
 	fn get_state(&self, number: BlockNumber) -> StateOrBlock {
 		match number {
 			BlockNumber::Num(num) => BlockId::Number(num).into(),
@@ -432,6 +515,13 @@ pub fn pending_logs<M>(miner: &M, best_block: EthBlockNumber, filter: &EthcoreFi
 		.collect()
 }
 
+/// Returns whether a known block with given hash is part of the canonical chain.
+pub fn is_canonical<C>(client: &C, hash: &H256) -> bool where C: BlockChainClient {
+	client.block_number(BlockId::Hash(*hash))
+		.and_then(|number| client.block_hash(BlockId::Number(number)))
+		.map_or(false, |canonical| canonical == *hash)
+}
+
 fn check_known<C>(client: &C, number: BlockNumber) -> Result<()> where C: BlockChainClient {
 	use ethcore::block_status::BlockStatus;
 
@@ -452,7 +542,7 @@ fn check_known<C>(client: &C, number: BlockNumber) -> Result<()> where C: BlockC
 const MAX_QUEUE_SIZE_TO_MINE_ON: usize = 4;	// because uncles go back 6.
 
 impl<C, SN: ?Sized, S: ?Sized, M, EM, T: StateInfo + 'static> Eth for EthClient<C, SN, S, M, EM> where
-	C: miner::BlockChainClient + BlockChainClient + StateClient<State=T> + Call<State=T> + EngineInfo + 'static,
+	C: miner::BlockChainClient + BlockChainClient + ProvingBlockChainClient + StateClient<State=T> + Call<State=T> + EngineInfo + 'static,
 	SN: SnapshotService + 'static,
 	S: SyncProvider + 'static,
 	M: MinerService<State=T> + 'static,
@@ -518,7 +608,74 @@ impl<C, SN: ?Sized, S: ?Sized, M, EM, T: StateInfo + 'static> Eth for EthClient<
 	}
 
 	fn gas_price(&self) -> Result<RpcU256> {
-		Ok(RpcU256::from(default_gas_price(&*self.client, &*self.miner, self.options.gas_price_percentile)))
+		Ok(RpcU256::from(self.gas_price_oracle.gas_price(&*self.client, &*self.miner)))
+	}
+
+	fn max_priority_fee_per_gas(&self) -> Result<RpcU256> {
+		let mut corpus = Vec::new();
+		let mut id = BlockId::Latest;
+		for _ in 0..PRIORITY_FEE_SAMPLE_BLOCKS {
+			let header = match self.decoded_header(id) {
+				Some(ref header) if header.number() == 0 => break,
+				Some(header) => header,
+				None => break,
+			};
+			if let Some(block) = self.client.block(BlockId::Hash(header.hash())) {
+				corpus.extend(block.transactions().iter().map(|tx| tx.effective_priority_fee(header.base_fee())));
+			}
+			id = BlockId::Hash(*header.parent_hash());
+		}
+
+		let corpus: ::stats::Corpus<U256> = corpus.into();
+		Ok(corpus.percentile(self.options.gas_price_percentile).cloned()
+			.unwrap_or_else(|| self.miner.sensible_gas_price())
+			.into())
+	}
+
+	fn fee_history(&self, block_count: RpcU256, newest_block: BlockNumber, reward_percentiles: Trailing<Vec<f64>>) -> BoxFuture<FeeHistory> {
+		let reward_percentiles = reward_percentiles.unwrap_or_default();
+		try_bf!(fee_history::validate_percentiles(&reward_percentiles));
+
+		let block_count = cmp::min(U256::from(block_count), MAX_FEE_HISTORY_BLOCKS.into()).low_u64();
+		let newest_id = match newest_block {
+			BlockNumber::Pending => BlockId::Latest,
+			number => block_number_to_id(number),
+		};
+		let newest = match self.decoded_header(newest_id) {
+			Some(header) => header,
+			None => return Box::new(future::err(errors::unknown_block())),
+		};
+
+		let oldest = (newest.number() + 1).saturating_sub(block_count);
+		let mut history = FeeHistory {
+			oldest_block: oldest.into(),
+			reward: if reward_percentiles.is_empty() { None } else { Some(Vec::new()) },
+			..Default::default()
+		};
+		if block_count == 0 {
+			return Box::new(future::ok(history));
+		}
+
+		for number in oldest..newest.number() + 1 {
+			let header = match self.decoded_header(BlockId::Number(number)) {
+				Some(header) => header,
+				None => return Box::new(future::err(errors::unknown_block())),
+			};
+			let gas_limit = header.gas_limit().low_u64();
+			history.base_fee_per_gas.push(header.base_fee().unwrap_or_default().into());
+			history.gas_used_ratio.push(match gas_limit {
+				0 => 0.0,
+				gas_limit => header.gas_used().low_u64() as f64 / gas_limit as f64,
+			});
+			if let Some(ref mut reward) = history.reward {
+				let fees = self.block_priority_fees(&header);
+				reward.push(fee_history::percentile_rewards(fees, &reward_percentiles).into_iter().map(Into::into).collect());
+			}
+		}
+		let next_base_fee = self.client.engine().machine().calc_base_fee(&newest);
+		history.base_fee_per_gas.push(next_base_fee.unwrap_or_default().into());
+
+		Box::new(future::ok(history))
 	}
 
 	fn accounts(&self) -> Result<Vec<RpcH160>> {
@@ -560,6 +717,50 @@ impl<C, SN: ?Sized, S: ?Sized, M, EM, T: StateInfo + 'static> Eth for EthClient<
 		Box::new(future::done(res))
 	}
 
+	fn proof(&self, address: RpcH160, values: Vec<RpcH256>, num: Trailing<BlockNumber>) -> BoxFuture<EthAccount> {
+		let address: Address = RpcH160::into(address);
+
+		let id = match num.unwrap_or_default() {
+			BlockNumber::Pending => {
+				return Box::new(future::err(errors::unsupported("Proofs are not available for the pending block.", None)));
+			},
+			number => {
+				try_bf!(check_known(&*self.client, number.clone()));
+				block_number_to_id(number)
+			},
+		};
+
+		let account_key = keccak(&address);
+		let (account_proof, account) = match self.client.prove_account(account_key, id) {
+			Some(proof) => proof,
+			None => return Box::new(future::err(errors::state_pruned())),
+		};
+
+		let storage_proof = values.into_iter().map(|key| {
+			let key: H256 = key.into();
+			self.client.prove_storage(account_key, keccak(&key), id).map(|(proof, value)| StorageProof {
+				key: U256::from(&*key).into(),
+				value: U256::from(&*value).into(),
+				proof: proof.into_iter().map(Bytes::new).collect(),
+			})
+		}).collect::<Option<Vec<_>>>();
+
+		let res = match storage_proof {
+			Some(storage_proof) => Ok(EthAccount {
+				address: address.into(),
+				balance: account.balance.into(),
+				nonce: account.nonce.into(),
+				code_hash: account.code_hash.into(),
+				storage_hash: account.storage_root.into(),
+				account_proof: account_proof.into_iter().map(Bytes::new).collect(),
+				storage_proof,
+			}),
+			None => Err(errors::state_pruned()),
+		};
+
+		Box::new(future::done(res))
+	}
+
 	fn transaction_count(&self, address: RpcH160, num: Trailing<BlockNumber>) -> BoxFuture<RpcU256> {
 		let address: Address = RpcH160::into(address);
 
@@ -636,8 +837,20 @@ impl<C, SN: ?Sized, S: ?Sized, M, EM, T: StateInfo + 'static> Eth for EthClient<
 		Box::new(future::done(res))
 	}
 
-	fn block_by_hash(&self, hash: RpcH256, include_txs: bool) -> BoxFuture<Option<RichBlock>> {
-		Box::new(future::done(self.rich_block(BlockId::Hash(hash.into()).into(), include_txs)))
+	fn block_by_hash(&self, hash: RpcH256, include_txs: bool, allow_non_canonical: Trailing<bool>) -> BoxFuture<Option<RichBlock>> {
+		let hash: H256 = hash.into();
+		let canonical = is_canonical(&*self.client, &hash);
+		if !canonical && !allow_non_canonical.unwrap_or(false) {
+			return Box::new(future::ok(None));
+		}
+
+		let block = try_bf!(self.rich_block(BlockId::Hash(hash).into(), include_txs));
+		Box::new(future::ok(block.map(|mut block| {
+			if allow_non_canonical.unwrap_or(false) {
+				block.inner.canonical = Some(canonical);
+			}
+			block
+		})))
 	}
 
 	fn block_by_number(&self, num: BlockNumber, include_txs: bool) -> BoxFuture<Option<RichBlock>> {
@@ -714,6 +927,9 @@ impl<C, SN: ?Sized, S: ?Sized, M, EM, T: StateInfo + 'static> Eth for EthClient<
 			Ok(value) => value,
 			Err(err) => return Box::new(future::err(err)),
 		};
+		if let Err(err) = check_logs_range(&filter, self.options.max_logs_range, |id| BlockChainClient::block_number(&*self.client, id)) {
+			return Box::new(future::err(err));
+		}
 		let mut logs = match self.client.logs(filter.clone()) {
 			Ok(logs) => logs
 				.into_iter()
@@ -814,7 +1030,7 @@ impl<C, SN: ?Sized, S: ?Sized, M, EM, T: StateInfo + 'static> Eth for EthClient<
 	}
 
 	fn send_raw_transaction(&self, raw: Bytes) -> Result<RpcH256> {
-		Rlp::new(&raw.into_vec()).as_val()
+		UnverifiedTransaction::decode_raw(&raw.into_vec())
 			.map_err(errors::rlp)
 			.and_then(|tx| SignedTransaction::new(tx).map_err(errors::transaction))
 			.and_then(|signed_transaction| {
@@ -833,10 +1049,95 @@ impl<C, SN: ?Sized, S: ?Sized, M, EM, T: StateInfo + 'static> Eth for EthClient<
 	}
 
 	fn call(&self, request: CallRequest, num: Trailing<BlockNumber>) -> BoxFuture<Bytes> {
-		let request = CallRequest::into(request);
-		let signed = try_bf!(fake_sign::sign_call(request));
+		self.call_with_override(request, num.unwrap_or_default(), Default::default())
+	}
+
+	fn estimate_gas(&self, request: CallRequest, num: Trailing<BlockNumber>) -> BoxFuture<RpcU256> {
+		self.estimate_gas_with_override(request, num.unwrap_or_default(), Default::default())
+	}
+
+	fn call_many(&self, calls: Vec<BundleCall>, num: Trailing<BlockNumber>) -> BoxFuture<Vec<BundleCallResult>> {
+		if calls.len() > MAX_BUNDLE_CALLS {
+			return Box::new(future::err(errors::request_rejected_param_limit(MAX_BUNDLE_CALLS as u64, "calls")));
+		}
+
+		let transactions = try_bf!(calls.into_iter().map(|call| match call {
+			BundleCall::Call(request) => fake_sign::sign_call(request.into()),
+			BundleCall::Raw(raw) => UnverifiedTransaction::decode_raw(&raw.into_vec())
+				.map_err(|e| errors::invalid_params("Transaction is not valid RLP", e))
+				.and_then(|tx| SignedTransaction::new(tx).map_err(errors::transaction)),
+		}).collect::<Result<Vec<_>>>());
 
 		let num = num.unwrap_or_default();
+		let (mut state, header) = if num == BlockNumber::Pending {
+			let info = self.client.chain_info();
+			let state = try_bf!(self.miner.pending_state(info.best_block_number).ok_or(errors::state_pruned()));
+			let header = try_bf!(self.miner.pending_block_header(info.best_block_number).ok_or(errors::state_pruned()));
+
+			(state, header)
+		} else {
+			let id = match num {
+				BlockNumber::Num(num) => BlockId::Number(num),
+				BlockNumber::Earliest => BlockId::Earliest,
+				BlockNumber::Latest => BlockId::Latest,
+				BlockNumber::Pending => unreachable!(), // Already covered
+			};
+
+			let state = try_bf!(self.client.reconstructed_state_at(id).map_err(errors::call));
+			let header = try_bf!(self.client.block_header(id).ok_or(errors::state_pruned()).and_then(|h| h.decode(self.client.engine().params().eip1559_transition).map_err(errors::decode)));
+
+			(state, header)
+		};
+
+		// calls failing before the execution don't change the state, so the rest of the bundle is still executed.
+		let mut cumulative_gas_used = U256::zero();
+		let results = transactions.iter().map(|transaction| {
+			match self.client.call(transaction, Default::default(), &mut state, &header) {
+				Ok(executed) => {
+					cumulative_gas_used += executed.gas_used;
+					BundleCallResult {
+						output: executed.output.into(),
+						error: executed.exception.map(|exception| format!("{}", exception)),
+						gas_used: executed.gas_used.into(),
+						cumulative_gas_used: cumulative_gas_used.into(),
+					}
+				},
+				Err(err) => BundleCallResult {
+					output: Bytes::default(),
+					error: Some(format!("{}", err)),
+					gas_used: U256::zero().into(),
+					cumulative_gas_used: cumulative_gas_used.into(),
+				},
+			}
+		}).collect();
+
+		Box::new(future::ok(results))
+	}
+
+	fn compile_lll(&self, _: String) -> Result<Bytes> {
+		Err(errors::deprecated("Compilation of LLL via RPC is deprecated".to_string()))
+	}
+
+	fn compile_serpent(&self, _: String) -> Result<Bytes> {
+		Err(errors::deprecated("Compilation of Serpent via RPC is deprecated".to_string()))
+	}
+
+	fn compile_solidity(&self, _: String) -> Result<Bytes> {
+		Err(errors::deprecated("Compilation of Solidity via RPC is deprecated".to_string()))
+	}
+}
+
+impl<C, SN: ?Sized, S: ?Sized, M, EM, T: StateInfo + 'static> EthCallOverride for EthClient<C, SN, S, M, EM> where
+	C: miner::BlockChainClient + BlockChainClient + ProvingBlockChainClient + StateClient<State=T> + Call<State=T> + EngineInfo + 'static,
+	SN: SnapshotService + 'static,
+	S: SyncProvider + 'static,
+	M: MinerService<State=T> + 'static,
+	EM: ExternalMinerService + 'static,
+{
+	fn call_with_override(&self, request: CallRequest, num: BlockNumber, overrides: StateOverride) -> BoxFuture<Bytes> {
+		let request = CallRequest::into(request);
+		let signed = try_bf!(fake_sign::sign_call(request));
+		let overrides = try_bf!(errors::state_override(overrides));
 
 		let (mut state, header) = if num == BlockNumber::Pending {
 			let info = self.client.chain_info();
@@ -852,12 +1153,27 @@ impl<C, SN: ?Sized, S: ?Sized, M, EM, T: StateInfo + 'static> Eth for EthClient<
 				BlockNumber::Pending => unreachable!(), // Already covered
 			};
 
-			let state = try_bf!(self.client.state_at(id).ok_or(errors::state_pruned()));
-			let header = try_bf!(self.client.block_header(id).ok_or(errors::state_pruned()).and_then(|h| h.decode().map_err(errors::decode)));
+			let state = try_bf!(self.client.reconstructed_state_at(id).map_err(errors::call));
+			let header = try_bf!(self.client.block_header(id).ok_or(errors::state_pruned()).and_then(|h| h.decode(self.client.engine().params().eip1559_transition).map_err(errors::decode)));
 
 			(state, header)
 		};
 
+		let cache_key = match num {
+			BlockNumber::Pending => None,
+			_ if !overrides.is_empty() || self.options.call_cache_size == 0 => None,
+			_ => Some(CallKey {
+				state_root: *header.state_root(),
+				block_hash: header.hash(),
+				sender: signed.sender(),
+				transaction: signed.hash(),
+			}),
+		};
+		if let Some(output) = cache_key.as_ref().and_then(|key| self.cached_call(key)) {
+			return Box::new(future::ok(output.into()));
+		}
+
+		try_bf!(self.client.apply_state_override(&mut state, &overrides).map_err(errors::call));
 		let result = self.client.call(&signed, Default::default(), &mut state, &header);
 
 		Box::new(future::done(result
@@ -868,16 +1184,21 @@ impl<C, SN: ?Sized, S: ?Sized, M, EM, T: StateInfo + 'static> Eth for EthClient<
 					None => Ok(executed)
 				}
 			})
-			.map(|b| b.output.into())
+			.map(|executed| {
+				if let Some(key) = cache_key {
+					self.call_cache.lock().outputs.insert(key, executed.output.clone());
+				}
+				executed.output.into()
+			})
 		))
 	}
 
-	fn estimate_gas(&self, request: CallRequest, num: Trailing<BlockNumber>) -> BoxFuture<RpcU256> {
+	fn estimate_gas_with_override(&self, request: CallRequest, num: BlockNumber, overrides: StateOverride) -> BoxFuture<RpcU256> {
 		let request = CallRequest::into(request);
 		let signed = try_bf!(fake_sign::sign_call(request));
-		let num = num.unwrap_or_default();
+		let overrides = try_bf!(errors::state_override(overrides));
 
-		let (state, header) = if num == BlockNumber::Pending {
+		let (mut state, header) = if num == BlockNumber::Pending {
 			let info = self.client.chain_info();
 			let state = try_bf!(self.miner.pending_state(info.best_block_number).ok_or(errors::state_pruned()));
 			let header = try_bf!(self.miner.pending_block_header(info.best_block_number).ok_or(errors::state_pruned()));
@@ -891,27 +1212,16 @@ impl<C, SN: ?Sized, S: ?Sized, M, EM, T: StateInfo + 'static> Eth for EthClient<
 				BlockNumber::Pending => unreachable!(), // Already covered
 			};
 
-			let state = try_bf!(self.client.state_at(id).ok_or(errors::state_pruned()));
-			let header = try_bf!(self.client.block_header(id).ok_or(errors::state_pruned()).and_then(|h| h.decode().map_err(errors::decode)));
+			let state = try_bf!(self.client.reconstructed_state_at(id).map_err(errors::call));
+			let header = try_bf!(self.client.block_header(id).ok_or(errors::state_pruned()).and_then(|h| h.decode(self.client.engine().params().eip1559_transition).map_err(errors::decode)));
 
 			(state, header)
 		};
 
+		try_bf!(self.client.apply_state_override(&mut state, &overrides).map_err(errors::call));
 		Box::new(future::done(self.client.estimate_gas(&signed, &state, &header)
 			.map(Into::into)
 			.map_err(errors::call)
 		))
 	}
-
-	fn compile_lll(&self, _: String) -> Result<Bytes> {
-		Err(errors::deprecated("Compilation of LLL via RPC is deprecated".to_string()))
-	}
-
-	fn compile_serpent(&self, _: String) -> Result<Bytes> {
-		Err(errors::deprecated("Compilation of Serpent via RPC is deprecated".to_string()))
-	}
-
-	fn compile_solidity(&self, _: String) -> Result<Bytes> {
-		Err(errors::deprecated("Compilation of Solidity via RPC is deprecated".to_string()))
-	}
 }
@@ -30,7 +30,7 @@ use jsonrpc_core::futures::{future, Future};
 use jsonrpc_core::futures::future::Either;
 use v1::traits::EthFilter;
 use v1::types::{BlockNumber, Index, Filter, FilterChanges, Log, H256 as RpcH256, U256 as RpcU256};
-use v1::helpers::{errors, SyncPollFilter, PollFilter, PollManager, limit_logs};
+use v1::helpers::{errors, SyncPollFilter, PollFilter, PollManager, limit_logs, check_logs_range};
 use v1::impls::eth::pending_logs;
 
 /// Something which provides data that can be filtered over.
@@ -41,6 +41,12 @@ pub trait Filterable {
 	/// Get a block hash by block id.
 	fn block_hash(&self, id: BlockId) -> Option<H256>;
 
+	/// Get a block number by block id.
+	fn block_number(&self, id: BlockId) -> Option<u64>;
+
+	/// Maximal number of blocks a single logs query can span.
+	fn max_logs_range(&self) -> Option<u64>;
+
 	/// pending transaction hashes at the given block (unordered).
 	fn pending_transaction_hashes(&self) -> BTreeSet<H256>;
 
@@ -62,6 +68,7 @@ pub struct EthFilterClient<C, M> {
 	client: Arc<C>,
 	miner: Arc<M>,
 	polls: Mutex<PollManager<SyncPollFilter>>,
+	max_logs_range: Option<u64>,
 }
 
 impl<C, M> EthFilterClient<C, M> {
@@ -71,8 +78,15 @@ impl<C, M> EthFilterClient<C, M> {
 			client: client,
 			miner: miner,
 			polls: Mutex::new(PollManager::new(poll_lifetime)),
+			max_logs_range: None,
 		}
 	}
+
+	/// Limits the number of blocks `eth_getFilterLogs` can scan.
+	pub fn with_max_logs_range(mut self, max_logs_range: Option<u64>) -> Self {
+		self.max_logs_range = max_logs_range;
+		self
+	}
 }
 
 impl<C, M> Filterable for EthFilterClient<C, M> where
@@ -87,6 +101,14 @@ impl<C, M> Filterable for EthFilterClient<C, M> where
 		self.client.block_hash(id)
 	}
 
+	fn block_number(&self, id: BlockId) -> Option<u64> {
+		BlockChainClient::block_number(&*self.client, id)
+	}
+
+	fn max_logs_range(&self) -> Option<u64> {
+		self.max_logs_range
+	}
+
 	fn pending_transaction_hashes(&self) -> BTreeSet<H256> {
 		self.miner.pending_transaction_hashes(&*self.client)
 	}
@@ -271,6 +293,10 @@ impl<T: Filterable + Send + Sync + 'static> EthFilter for T {
 			}
 		};
 
+		if let Err(err) = check_logs_range(&filter, self.max_logs_range(), |id| self.block_number(id)) {
+			return Box::new(future::err(err));
+		}
+
 		// fetch pending logs.
 		let pending = if include_pending {
 			let best_block = self.best_block_number();
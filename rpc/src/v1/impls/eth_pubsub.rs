@@ -26,7 +26,7 @@ use jsonrpc_macros::Trailing;
 use jsonrpc_macros::pubsub::{Sink, Subscriber};
 use jsonrpc_pubsub::SubscriptionId;
 
-use v1::helpers::{errors, limit_logs, Subscribers};
+use v1::helpers::{errors, limit_logs, Subscribers, QueuedSink, SubscriptionQueueConfig};
 use v1::helpers::light_fetch::LightFetch;
 use v1::metadata::Metadata;
 use v1::traits::EthPubSub;
@@ -35,6 +35,7 @@ use v1::types::{pubsub, RichHeader, Log};
 use ethcore::encoded;
 use ethcore::filter::Filter as EthFilter;
 use ethcore::client::{BlockChainClient, ChainNotify, ChainRoute, ChainRouteType, BlockId};
+use miner::pool::VerifiedTransaction;
 use sync::LightSync;
 use light::cache::Cache;
 use light::on_demand::OnDemand;
@@ -44,19 +45,20 @@ use ethereum_types::H256;
 use bytes::Bytes;
 use parking_lot::{RwLock, Mutex};
 
-type Client = Sink<pubsub::Result>;
+type Client = QueuedSink;
 
 /// Eth PubSub implementation.
 pub struct EthPubSubClient<C> {
 	handler: Arc<ChainNotificationHandler<C>>,
+	transaction_filters: bool,
 	heads_subscribers: Arc<RwLock<Subscribers<Client>>>,
 	logs_subscribers: Arc<RwLock<Subscribers<(Client, EthFilter)>>>,
-	transactions_subscribers: Arc<RwLock<Subscribers<Client>>>,
+	transactions_subscribers: Arc<RwLock<Subscribers<(Client, Option<pubsub::TransactionFilter>)>>>,
 }
 
 impl<C> EthPubSubClient<C> {
 	/// Creates new `EthPubSubClient`.
-	pub fn new(client: Arc<C>, remote: Remote) -> Self {
+	pub fn new(client: Arc<C>, remote: Remote, queue: SubscriptionQueueConfig) -> Self {
 		let heads_subscribers = Arc::new(RwLock::new(Subscribers::default()));
 		let logs_subscribers = Arc::new(RwLock::new(Subscribers::default()));
 		let transactions_subscribers = Arc::new(RwLock::new(Subscribers::default()));
@@ -65,10 +67,12 @@ impl<C> EthPubSubClient<C> {
 			handler: Arc::new(ChainNotificationHandler {
 				client,
 				remote,
+				queue,
 				heads_subscribers: heads_subscribers.clone(),
 				logs_subscribers: logs_subscribers.clone(),
 				transactions_subscribers: transactions_subscribers.clone(),
 			}),
+			transaction_filters: true,
 			heads_subscribers,
 			logs_subscribers,
 			transactions_subscribers,
@@ -78,7 +82,13 @@ impl<C> EthPubSubClient<C> {
 	/// Creates new `EthPubSubCient` with deterministic subscription ids.
 	#[cfg(test)]
 	pub fn new_test(client: Arc<C>, remote: Remote) -> Self {
-		let client = Self::new(client, remote);
+		Self::new_test_with_queue(client, remote, Default::default())
+	}
+
+	/// Creates new `EthPubSubCient` with deterministic subscription ids and given queue configuration.
+	#[cfg(test)]
+	pub fn new_test_with_queue(client: Arc<C>, remote: Remote, queue: SubscriptionQueueConfig) -> Self {
+		let client = Self::new(client, remote, queue);
 		*client.heads_subscribers.write() = Subscribers::new_test();
 		*client.logs_subscribers.write() = Subscribers::new_test();
 		*client.transactions_subscribers.write() = Subscribers::new_test();
@@ -100,6 +110,7 @@ impl EthPubSubClient<LightFetch> {
 		cache: Arc<Mutex<Cache>>,
 		remote: Remote,
 		gas_price_percentile: usize,
+		queue: SubscriptionQueueConfig,
 	) -> Self {
 		let fetch = LightFetch {
 			client,
@@ -108,7 +119,10 @@ impl EthPubSubClient<LightFetch> {
 			cache,
 			gas_price_percentile,
 		};
-		EthPubSubClient::new(Arc::new(fetch), remote)
+		let mut client = EthPubSubClient::new(Arc::new(fetch), remote, queue);
+		// light client is notified only about hashes of pending transactions.
+		client.transaction_filters = false;
+		client
 	}
 }
 
@@ -116,24 +130,22 @@ impl EthPubSubClient<LightFetch> {
 pub struct ChainNotificationHandler<C> {
 	client: Arc<C>,
 	remote: Remote,
+	queue: SubscriptionQueueConfig,
 	heads_subscribers: Arc<RwLock<Subscribers<Client>>>,
 	logs_subscribers: Arc<RwLock<Subscribers<(Client, EthFilter)>>>,
-	transactions_subscribers: Arc<RwLock<Subscribers<Client>>>,
+	transactions_subscribers: Arc<RwLock<Subscribers<(Client, Option<pubsub::TransactionFilter>)>>>,
 }
 
 impl<C> ChainNotificationHandler<C> {
-	fn notify(remote: &Remote, subscriber: &Client, result: pubsub::Result) {
-		remote.spawn(subscriber
-			.notify(Ok(result))
-			.map(|_| ())
-			.map_err(|e| warn!(target: "rpc", "Unable to send notification: {}", e))
-		);
+	fn queued(&self, sink: Sink<pubsub::Result>) -> Client {
+		QueuedSink::new(sink, self.queue, self.remote.clone())
 	}
 
 	fn notify_heads(&self, headers: &[(encoded::Header, BTreeMap<String, String>)]) {
+		self.heads_subscribers.write().retain(|subscriber| !subscriber.is_closed());
 		for subscriber in self.heads_subscribers.read().values() {
 			for &(ref header, ref extra_info) in headers {
-				Self::notify(&self.remote, subscriber, pubsub::Result::Header(RichHeader {
+				subscriber.notify(pubsub::Result::Header(RichHeader {
 					inner: header.into(),
 					extra_info: extra_info.clone(),
 				}));
@@ -147,6 +159,7 @@ impl<C> ChainNotificationHandler<C> {
 		T: IntoFuture<Item = Vec<Log>, Error = Error>,
 		T::Future: Send + 'static,
 	{
+		self.logs_subscribers.write().retain(|&(ref subscriber, _)| !subscriber.is_closed());
 		for &(ref subscriber, ref filter) in self.logs_subscribers.read().values() {
 			let logs = futures::future::join_all(enacted
 				.iter()
@@ -159,14 +172,20 @@ impl<C> ChainNotificationHandler<C> {
 				.collect::<Vec<_>>()
 			);
 			let limit = filter.limit;
-			let remote = self.remote.clone();
 			let subscriber = subscriber.clone();
 			self.remote.spawn(logs
 				.map(move |logs| {
-					let logs = logs.into_iter().flat_map(|log| log).collect();
-
-					for log in limit_logs(logs, limit) {
-						Self::notify(&remote, &subscriber, pubsub::Result::Log(log))
+					let logs = limit_logs(logs.into_iter().flat_map(|log| log).collect(), limit);
+					let batch_size = subscriber.config().logs_batch_size;
+
+					if batch_size > 1 {
+						for batch in logs.chunks(batch_size) {
+							subscriber.notify(pubsub::Result::Logs(batch.to_vec()));
+						}
+					} else {
+						for log in logs {
+							subscriber.notify(pubsub::Result::Log(log));
+						}
 					}
 				})
 				.map_err(|e| warn!("Unable to fetch latest logs: {:?}", e))
@@ -174,11 +193,28 @@ impl<C> ChainNotificationHandler<C> {
 		}
 	}
 
-	/// Notify all subscribers about new transaction hashes.
+	/// Notify subscribers without a filter about new transaction hashes.
 	pub fn notify_new_transactions(&self, hashes: &[H256]) {
-		for subscriber in self.transactions_subscribers.read().values() {
+		self.transactions_subscribers.write().retain(|&(ref subscriber, _)| !subscriber.is_closed());
+		for &(ref subscriber, ref filter) in self.transactions_subscribers.read().values() {
+			if filter.is_some() {
+				continue;
+			}
 			for hash in hashes {
-				Self::notify(&self.remote, subscriber, pubsub::Result::TransactionHash((*hash).into()));
+				subscriber.notify(pubsub::Result::TransactionHash((*hash).into()));
+			}
+		}
+	}
+
+	/// Notify all subscribers about hashes of new transactions matching their filters.
+	pub fn notify_pending_transactions(&self, transactions: &[Arc<VerifiedTransaction>]) {
+		self.transactions_subscribers.write().retain(|&(ref subscriber, _)| !subscriber.is_closed());
+		for &(ref subscriber, ref filter) in self.transactions_subscribers.read().values() {
+			let matching = transactions
+				.iter()
+				.filter(|tx| filter.as_ref().map_or(true, |filter| filter.matches(tx.signed())));
+			for tx in matching {
+				subscriber.notify(pubsub::Result::TransactionHash(tx.signed().hash().into()));
 			}
 		}
 	}
@@ -276,7 +312,7 @@ impl<C: Send + Sync + 'static> EthPubSub for EthPubSubClient<C> {
 	) {
 		let error = match (kind, params.into()) {
 			(pubsub::Kind::NewHeads, None) => {
-				self.heads_subscribers.write().push(subscriber);
+				self.heads_subscribers.write().push_with(subscriber, |sink| self.handler.queued(sink));
 				return;
 			},
 			(pubsub::Kind::NewHeads, _) => {
@@ -285,7 +321,7 @@ impl<C: Send + Sync + 'static> EthPubSub for EthPubSubClient<C> {
 			(pubsub::Kind::Logs, Some(pubsub::Params::Logs(filter))) => {
 				match filter.try_into() {
 					Ok(filter) => {
-						self.logs_subscribers.write().push(subscriber, filter);
+						self.logs_subscribers.write().push_with(subscriber, |sink| (self.handler.queued(sink), filter));
 						return;
 					},
 					Err(err) => err,
@@ -295,11 +331,21 @@ impl<C: Send + Sync + 'static> EthPubSub for EthPubSubClient<C> {
 				errors::invalid_params("logs", "Expected a filter object.")
 			},
 			(pubsub::Kind::NewPendingTransactions, None) => {
-				self.transactions_subscribers.write().push(subscriber);
+				self.transactions_subscribers.write().push_with(subscriber, |sink| (self.handler.queued(sink), None));
 				return;
 			},
+			(pubsub::Kind::NewPendingTransactions, Some(pubsub::Params::Transactions(filter))) => {
+				if !self.transaction_filters {
+					errors::light_unimplemented(None)
+				} else if filter.selector.as_ref().map_or(false, |selector| selector.0.len() != 4) {
+					errors::invalid_params("newPendingTransactions", "Expected a four bytes long selector.")
+				} else {
+					self.transactions_subscribers.write().push_with(subscriber, |sink| (self.handler.queued(sink), Some(filter)));
+					return;
+				}
+			},
 			(pubsub::Kind::NewPendingTransactions, _) => {
-				errors::invalid_params("newPendingTransactions", "Expected no parameters.")
+				errors::invalid_params("newPendingTransactions", "Expected no parameters or a transaction filter.")
 			},
 			_ => {
 				errors::unimplemented(None)
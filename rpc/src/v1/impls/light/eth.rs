@@ -37,17 +37,16 @@ use sync::LightSync;
 use hash::{KECCAK_NULL_RLP, KECCAK_EMPTY_LIST_RLP};
 use ethereum_types::U256;
 use parking_lot::{RwLock, Mutex};
-use rlp::Rlp;
-use transaction::SignedTransaction;
+use transaction::{SignedTransaction, UnverifiedTransaction};
 
 use v1::impls::eth_filter::Filterable;
-use v1::helpers::{errors, limit_logs};
+use v1::helpers::{errors, limit_logs, check_logs_range};
 use v1::helpers::{SyncPollFilter, PollManager};
 use v1::helpers::light_fetch::{self, LightFetch};
-use v1::traits::Eth;
+use v1::traits::{Eth, EthCallOverride};
 use v1::types::{
-	RichBlock, Block, BlockTransactions, BlockNumber, LightBlockNumber, Bytes, SyncStatus, SyncInfo,
-	Transaction, CallRequest, Index, Filter, Log, Receipt, Work,
+	RichBlock, Block, BlockTransactions, BlockNumber, LightBlockNumber, BundleCall, BundleCallResult, Bytes, SyncStatus, SyncInfo,
+	Transaction, CallRequest, Index, Filter, Log, Receipt, Work, EthAccount, FeeHistory, StateOverride,
 	H64 as RpcH64, H256 as RpcH256, H160 as RpcH160, U256 as RpcU256,
 };
 use v1::metadata::Metadata;
@@ -65,6 +64,7 @@ pub struct EthClient<T> {
 	polls: Mutex<PollManager<SyncPollFilter>>,
 	poll_lifetime: u32,
 	gas_price_percentile: usize,
+	max_logs_range: Option<u64>,
 }
 
 impl<T> Clone for EthClient<T> {
@@ -80,6 +80,7 @@ impl<T> Clone for EthClient<T> {
 			polls: Mutex::new(PollManager::new(self.poll_lifetime)),
 			poll_lifetime: self.poll_lifetime,
 			gas_price_percentile: self.gas_price_percentile,
+			max_logs_range: self.max_logs_range,
 		}
 	}
 }
@@ -107,9 +108,16 @@ impl<T: LightChainClient + 'static> EthClient<T> {
 			polls: Mutex::new(PollManager::new(poll_lifetime)),
 			poll_lifetime,
 			gas_price_percentile,
+			max_logs_range: None,
 		}
 	}
 
+	/// Limits the number of blocks a single logs query can span.
+	pub fn with_max_logs_range(mut self, max_logs_range: Option<u64>) -> Self {
+		self.max_logs_range = max_logs_range;
+		self
+	}
+
 	/// Create a light data fetcher instance.
 	fn fetcher(&self) -> LightFetch {
 		LightFetch {
@@ -128,7 +136,7 @@ impl<T: LightChainClient + 'static> EthClient<T> {
 
 		// helper for filling out a rich block once we've got a block and a score.
 		let fill_rich = move |block: encoded::Block, score: Option<U256>| {
-			let header = block.decode_header();
+			let header = block.decode_header(engine.params().eip1559_transition);
 			let extra_info = engine.extra_info(&header);
 			RichBlock {
 				inner: Block {
@@ -149,6 +157,8 @@ impl<T: LightChainClient + 'static> EthClient<T> {
 					difficulty: header.difficulty().clone().into(),
 					total_difficulty: score.map(Into::into),
 					seal_fields: header.seal().into_iter().cloned().map(Into::into).collect(),
+					base_fee_per_gas: header.base_fee().map(Into::into),
+					canonical: None,
 					uncles: block.uncle_hashes().into_iter().map(Into::into).collect(),
 					transactions: match include_txs {
 						true => BlockTransactions::Full(block.view().localized_transactions().into_iter().map(Transaction::from_localized).collect()),
@@ -257,6 +267,14 @@ impl<T: LightChainClient + 'static> Eth for EthClient<T> {
 			.unwrap_or_else(Default::default))
 	}
 
+	fn max_priority_fee_per_gas(&self) -> Result<RpcU256> {
+		Err(errors::light_unimplemented(None))
+	}
+
+	fn fee_history(&self, _block_count: RpcU256, _newest_block: BlockNumber, _reward_percentiles: Trailing<Vec<f64>>) -> BoxFuture<FeeHistory> {
+		Box::new(future::err(errors::light_unimplemented(None)))
+	}
+
 	fn accounts(&self) -> Result<Vec<RpcH160>> {
 		self.accounts.accounts()
 			.map_err(|e| errors::account("Could not fetch accounts.", e))
@@ -276,7 +294,11 @@ impl<T: LightChainClient + 'static> Eth for EthClient<T> {
 		Box::new(future::err(errors::unimplemented(None)))
 	}
 
-	fn block_by_hash(&self, hash: RpcH256, include_txs: bool) -> BoxFuture<Option<RichBlock>> {
+	fn proof(&self, _address: RpcH160, _values: Vec<RpcH256>, _num: Trailing<BlockNumber>) -> BoxFuture<EthAccount> {
+		Box::new(future::err(errors::light_unimplemented(None)))
+	}
+
+	fn block_by_hash(&self, hash: RpcH256, include_txs: bool, _allow_non_canonical: Trailing<bool>) -> BoxFuture<Option<RichBlock>> {
 		Box::new(self.rich_block(BlockId::Hash(hash.into()), include_txs).map(Some))
 	}
 
@@ -358,9 +380,9 @@ impl<T: LightChainClient + 'static> Eth for EthClient<T> {
 	}
 
 	fn send_raw_transaction(&self, raw: Bytes) -> Result<RpcH256> {
-		let best_header = self.client.best_block_header().decode().map_err(errors::decode)?;
+		let best_header = self.client.best_block_header().decode(self.client.engine().params().eip1559_transition).map_err(errors::decode)?;
 
-		Rlp::new(&raw.into_vec()).as_val()
+		UnverifiedTransaction::decode_raw(&raw.into_vec())
 			.map_err(errors::rlp)
 			.and_then(|tx| {
 				self.client.engine().verify_transaction_basic(&tx, &best_header)
@@ -381,22 +403,15 @@ impl<T: LightChainClient + 'static> Eth for EthClient<T> {
 	}
 
 	fn call(&self, req: CallRequest, num: Trailing<BlockNumber>) -> BoxFuture<Bytes> {
-		Box::new(self.fetcher().proved_execution(req, num).and_then(|res| {
-			match res {
-				Ok(exec) => Ok(exec.output.into()),
-				Err(e) => Err(errors::execution(e)),
-			}
-		}))
+		self.call_with_override(req, num.unwrap_or_default(), Default::default())
 	}
 
 	fn estimate_gas(&self, req: CallRequest, num: Trailing<BlockNumber>) -> BoxFuture<RpcU256> {
-		// TODO: binary chop for more accurate estimates.
-		Box::new(self.fetcher().proved_execution(req, num).and_then(|res| {
-			match res {
-				Ok(exec) => Ok((exec.refunded + exec.gas_used).into()),
-				Err(e) => Err(errors::execution(e)),
-			}
-		}))
+		self.estimate_gas_with_override(req, num.unwrap_or_default(), Default::default())
+	}
+
+	fn call_many(&self, _calls: Vec<BundleCall>, _num: Trailing<BlockNumber>) -> BoxFuture<Vec<BundleCallResult>> {
+		Box::new(future::err(errors::light_unimplemented(None)))
 	}
 
 	fn transaction_by_hash(&self, hash: RpcH256) -> BoxFuture<Option<Transaction>> {
@@ -488,12 +503,15 @@ impl<T: LightChainClient + 'static> Eth for EthClient<T> {
 
 	fn logs(&self, filter: Filter) -> BoxFuture<Vec<Log>> {
 		let limit = filter.limit;
+		let filter: EthcoreFilter = match filter.try_into() {
+			Ok(value) => value,
+			Err(err) => return Box::new(future::err(err)),
+		};
+		if let Err(err) = check_logs_range(&filter, self.max_logs_range, |id| Filterable::block_number(self, id)) {
+			return Box::new(future::err(err));
+		}
 
-		Box::new(
-			Filterable::logs(self, match filter.try_into() {
-				Ok(value) => value,
-				Err(err) => return Box::new(future::err(err)),
-			}).map(move |logs| limit_logs(logs, limit)))
+		Box::new(Filterable::logs(self, filter).map(move |logs| limit_logs(logs, limit)))
 	}
 
 	fn work(&self, _timeout: Trailing<u64>) -> Result<Work> {
@@ -509,6 +527,35 @@ impl<T: LightChainClient + 'static> Eth for EthClient<T> {
 	}
 }
 
+impl<T: LightChainClient + 'static> EthCallOverride for EthClient<T> {
+	fn call_with_override(&self, req: CallRequest, num: BlockNumber, overrides: StateOverride) -> BoxFuture<Bytes> {
+		if !overrides.is_empty() {
+			return Box::new(future::err(errors::light_unimplemented(Some("state override".into()))));
+		}
+
+		Box::new(self.fetcher().proved_execution(req, num).and_then(|res| {
+			match res {
+				Ok(exec) => Ok(exec.output.into()),
+				Err(e) => Err(errors::execution(e)),
+			}
+		}))
+	}
+
+	fn estimate_gas_with_override(&self, req: CallRequest, num: BlockNumber, overrides: StateOverride) -> BoxFuture<RpcU256> {
+		if !overrides.is_empty() {
+			return Box::new(future::err(errors::light_unimplemented(Some("state override".into()))));
+		}
+
+		// TODO: binary chop for more accurate estimates.
+		Box::new(self.fetcher().proved_execution(req, num).and_then(|res| {
+			match res {
+				Ok(exec) => Ok((exec.refunded + exec.gas_used).into()),
+				Err(e) => Err(errors::execution(e)),
+			}
+		}))
+	}
+}
+
 // This trait implementation triggers a blanked impl of `EthFilter`.
 impl<T: LightChainClient + 'static> Filterable for EthClient<T> {
 	fn best_block_number(&self) -> u64 { self.client.chain_info().best_block_number }
@@ -517,6 +564,14 @@ impl<T: LightChainClient + 'static> Filterable for EthClient<T> {
 		self.client.block_hash(id)
 	}
 
+	fn block_number(&self, id: BlockId) -> Option<u64> {
+		self.client.block_header(id).map(|header| header.number())
+	}
+
+	fn max_logs_range(&self) -> Option<u64> {
+		self.max_logs_range
+	}
+
 	fn pending_transaction_hashes(&self) -> BTreeSet<::ethereum_types::H256> {
 		BTreeSet::new()
 	}
@@ -539,7 +594,7 @@ impl<T: LightChainClient + 'static> Filterable for EthClient<T> {
 }
 
 fn extract_uncle_at_index<T: LightChainClient>(block: encoded::Block, index: Index, client: Arc<T>) -> Option<RichBlock> {
-		let uncle = match block.uncles().into_iter().nth(index.value()) {
+		let uncle = match block.uncles(client.engine().params().eip1559_transition).into_iter().nth(index.value()) {
 			Some(u) => u,
 			None => return None,
 		};
@@ -565,6 +620,8 @@ fn extract_uncle_at_index<T: LightChainClient>(block: encoded::Block, index: Ind
 				receipts_root: uncle.receipts_root().clone().into(),
 				extra_data: uncle.extra_data().clone().into(),
 				seal_fields: uncle.seal().into_iter().cloned().map(Into::into).collect(),
+				base_fee_per_gas: uncle.base_fee().map(Into::into),
+				canonical: None,
 				uncles: vec![],
 				transactions: BlockTransactions::Hashes(vec![]),
 			},
@@ -23,27 +23,29 @@ use version::version_data;
 use crypto::DEFAULT_MAC;
 use ethkey::{crypto::ecies, Brain, Generator};
 use ethstore::random_phrase;
-use sync::LightSyncProvider;
+use sync::{LightSyncProvider, ManageNetwork};
 use ethcore::account_provider::AccountProvider;
 use ethcore_logger::RotatingLogger;
+use futures_cpupool::CpuPool;
 
 use light::client::LightChainClient;
 
 use jsonrpc_core::{Result, BoxFuture};
 use jsonrpc_core::futures::Future;
 use jsonrpc_macros::Trailing;
-use v1::helpers::{self, errors, ipfs, SigningQueue, SignerService, NetworkSettings};
+use v1::helpers::{self, errors, ipfs, signature, SigningQueue, SignerService, NetworkSettings};
 use v1::helpers::dispatch::LightDispatcher;
 use v1::helpers::light_fetch::LightFetch;
 use v1::metadata::Metadata;
 use v1::traits::Parity;
 use v1::types::{
 	Bytes, U256, U64, H160, H256, H512, CallRequest,
-	Peers, Transaction, RpcSettings, Histogram,
-	TransactionStats, LocalTransactionStatus,
-	BlockNumber, LightBlockNumber, ConsensusCapability, VersionInfo,
-	OperationsInfo, ChainStatus,
-	AccountInfo, HwAccountInfo, Header, RichHeader, Receipt,
+	Peers, HandshakeFailure, Transaction, RpcSettings, Histogram,
+	TransactionStats, BlockPropagationStats, BlockAnnouncementStats, LocalTransactionStatus,
+	BlockNumber, BlockNumberOrHash, LightBlockNumber, ConsensusCapability, VersionInfo,
+	OperationsInfo, ChainStatus, ImportQueueStatus, TransactionLifecycle,
+	AccountInfo, HwAccountInfo, Header, RichHeader, Receipt, ThreadPoolInfo, StateDiff,
+	SignatureVerificationRequest, SignatureVerification, SyncProgress, traffic_by_protocol,
 };
 use Host;
 
@@ -57,6 +59,7 @@ pub struct ParityClient {
 	signer: Option<Arc<SignerService>>,
 	ws_address: Option<Host>,
 	gas_price_percentile: usize,
+	pool: CpuPool,
 }
 
 impl ParityClient {
@@ -70,6 +73,7 @@ impl ParityClient {
 		signer: Option<Arc<SignerService>>,
 		ws_address: Option<Host>,
 		gas_price_percentile: usize,
+		pool: CpuPool,
 	) -> Self {
 		ParityClient {
 			light_dispatch,
@@ -80,6 +84,7 @@ impl ParityClient {
 			ws_address,
 			client,
 			gas_price_percentile,
+			pool,
 		}
 	}
 
@@ -183,9 +188,14 @@ impl Parity for ParityClient {
 			connected: peer_numbers.connected,
 			max: peer_numbers.max as u32,
 			peers: peers,
+			traffic: traffic_by_protocol(self.light_dispatch.sync.traffic()),
 		})
 	}
 
+	fn net_failures(&self) -> Result<Vec<HandshakeFailure>> {
+		Ok(self.light_dispatch.sync.handshake_failures().into_iter().map(Into::into).collect())
+	}
+
 	fn net_port(&self) -> Result<u16> {
 		Ok(self.settings.network_port)
 	}
@@ -244,6 +254,14 @@ impl Parity for ParityClient {
 		Err(errors::light_unimplemented(None))
 	}
 
+	fn state_diff_between(&self, _: BlockNumber, _: BlockNumber) -> Result<Option<StateDiff>> {
+		Err(errors::light_unimplemented(None))
+	}
+
+	fn block_witness(&self, _: BlockNumber) -> Result<Option<Bytes>> {
+		Err(errors::light_unimplemented(None))
+	}
+
 	fn encrypt_message(&self, key: H512, phrase: Bytes) -> Result<Bytes> {
 		ecies::encrypt(&key.into(), &DEFAULT_MAC, &phrase.0)
 			.map_err(errors::encryption)
@@ -262,6 +280,10 @@ impl Parity for ParityClient {
 		)
 	}
 
+	fn pending_block_candidates(&self) -> Result<Option<Vec<Transaction>>> {
+		Err(errors::light_unimplemented(None))
+	}
+
 	fn all_transactions(&self) -> Result<Vec<Transaction>> {
 		let txq = self.light_dispatch.transaction_queue.read();
 		let chain_info = self.light_dispatch.client.chain_info();
@@ -296,6 +318,14 @@ impl Parity for ParityClient {
 		)
 	}
 
+	fn block_propagation_stats(&self) -> Result<BTreeMap<H256, BlockPropagationStats>> {
+		Err(errors::light_unimplemented(None))
+	}
+
+	fn block_announcement_stats(&self) -> Result<BlockAnnouncementStats> {
+		Err(errors::light_unimplemented(None))
+	}
+
 	fn local_transactions(&self) -> Result<BTreeMap<H256, LocalTransactionStatus>> {
 		let mut map = BTreeMap::new();
 		let chain_info = self.light_dispatch.client.chain_info();
@@ -315,6 +345,10 @@ impl Parity for ParityClient {
 		Ok(map)
 	}
 
+	fn transaction_lifecycle(&self, _: H256) -> Result<Option<TransactionLifecycle>> {
+		Err(errors::light_unimplemented(None))
+	}
+
 	fn ws_url(&self) -> Result<String> {
 		helpers::to_url(&self.ws_address)
 			.ok_or_else(|| errors::ws_disabled())
@@ -360,9 +394,19 @@ impl Parity for ParityClient {
 
 		Ok(ChainStatus {
 			block_gap: gap.map(|(x, y)| (x.into(), y.into())),
+			timestamp_drift: self.light_dispatch.client.engine().params().into(),
+			import_paused: false,
 		})
 	}
 
+	fn sync_progress(&self) -> Result<SyncProgress> {
+		Err(errors::light_unimplemented(None))
+	}
+
+	fn import_queue_status(&self) -> Result<ImportQueueStatus> {
+		Err(errors::light_unimplemented(None))
+	}
+
 	fn node_kind(&self) -> Result<::v1::types::NodeKind> {
 		use ::v1::types::{NodeKind, Availability, Capability};
 
@@ -372,12 +416,16 @@ impl Parity for ParityClient {
 		})
 	}
 
+	fn thread_stats(&self) -> Result<Vec<ThreadPoolInfo>> {
+		Ok(::io::threads::thread_pools().into_iter().map(Into::into).collect())
+	}
+
 	fn block_header(&self, number: Trailing<BlockNumber>) -> BoxFuture<RichHeader> {
 		use ethcore::encoded;
 
 		let engine = self.light_dispatch.client.engine().clone();
 		let from_encoded = move |encoded: encoded::Header| {
-			let header = encoded.decode().map_err(errors::decode)?;
+			let header = encoded.decode(engine.params().eip1559_transition).map_err(errors::decode)?;
 			let extra_info = engine.extra_info(&header);
 			Ok(RichHeader {
 				inner: Header {
@@ -397,6 +445,7 @@ impl Parity for ParityClient {
 					timestamp: header.timestamp().into(),
 					difficulty: header.difficulty().clone().into(),
 					seal_fields: header.seal().iter().cloned().map(Into::into).collect(),
+					base_fee_per_gas: header.base_fee().map(Into::into),
 					extra_data: Bytes::new(header.extra_data().clone()),
 				},
 				extra_info: extra_info,
@@ -406,8 +455,8 @@ impl Parity for ParityClient {
 		Box::new(self.fetcher().header(id).and_then(from_encoded))
 	}
 
-	fn block_receipts(&self, number: Trailing<BlockNumber>) -> BoxFuture<Vec<Receipt>> {
-		let id = number.unwrap_or_default().to_block_id();
+	fn block_receipts(&self, block: Trailing<BlockNumberOrHash>) -> BoxFuture<Vec<Receipt>> {
+		let id = block.unwrap_or_default().to_block_id();
 		Box::new(self.fetcher().receipts(id).and_then(|receipts| Ok(receipts.into_iter().map(Into::into).collect())))
 	}
 
@@ -415,7 +464,19 @@ impl Parity for ParityClient {
 		ipfs::cid(content)
 	}
 
+	fn verify_signatures(&self, requests: Vec<SignatureVerificationRequest>) -> BoxFuture<Vec<SignatureVerification>> {
+		signature::verify_signatures(&self.pool, requests)
+	}
+
 	fn call(&self, _requests: Vec<CallRequest>, _block: Trailing<BlockNumber>) -> Result<Vec<Bytes>> {
 		Err(errors::light_unimplemented(None))
 	}
+
+	fn gas_flame(&self, _hash: H256) -> Result<String> {
+		Err(errors::light_unimplemented(None))
+	}
+
+	fn block_gas_flame(&self, _num: BlockNumber) -> Result<String> {
+		Err(errors::light_unimplemented(None))
+	}
 }
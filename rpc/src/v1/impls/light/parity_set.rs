@@ -29,7 +29,7 @@ use jsonrpc_core::{Result, BoxFuture};
 use jsonrpc_core::futures::Future;
 use v1::helpers::errors;
 use v1::traits::ParitySet;
-use v1::types::{Bytes, H160, H256, U256, ReleaseInfo, Transaction};
+use v1::types::{BlockTemplate, Bytes, H160, H256, U256, ReleaseInfo, Transaction};
 
 /// Parity-specific rpc interface for operations altering the settings.
 pub struct ParitySetClient<F> {
@@ -78,6 +78,22 @@ impl<F: Fetch> ParitySet for ParitySetClient<F> {
 		Err(errors::light_unimplemented(None))
 	}
 
+	fn set_transactions_replacement_bump(&self, _percent: f64) -> Result<bool> {
+		Err(errors::light_unimplemented(None))
+	}
+
+	fn set_transactions_per_sender_limit(&self, _limit: usize) -> Result<bool> {
+		Err(errors::light_unimplemented(None))
+	}
+
+	fn set_transactions_per_origin_limit(&self, _limit: usize) -> Result<bool> {
+		Err(errors::light_unimplemented(None))
+	}
+
+	fn set_transactions_memory_limit(&self, _limit: usize) -> Result<bool> {
+		Err(errors::light_unimplemented(None))
+	}
+
 	fn set_tx_gas_limit(&self, _limit: U256) -> Result<bool> {
 		Err(errors::light_unimplemented(None))
 	}
@@ -96,6 +112,13 @@ impl<F: Fetch> ParitySet for ParitySetClient<F> {
 		}
 	}
 
+	fn set_reserved_peers(&self, peers: Vec<String>) -> Result<bool> {
+		match self.net.set_reserved_peers(peers) {
+			Ok(()) => Ok(true),
+			Err(e) => Err(errors::invalid_params("Peer address", e)),
+		}
+	}
+
 	fn drop_non_reserved_peers(&self) -> Result<bool> {
 		self.net.deny_unreserved_peers();
 		Ok(true)
@@ -124,6 +147,14 @@ impl<F: Fetch> ParitySet for ParitySetClient<F> {
 		Err(errors::light_unimplemented(None))
 	}
 
+	fn pause_import(&self) -> Result<bool> {
+		Err(errors::light_unimplemented(None))
+	}
+
+	fn resume_import(&self) -> Result<bool> {
+		Err(errors::light_unimplemented(None))
+	}
+
 	fn hash_content(&self, url: String) -> BoxFuture<H256> {
 		let future = self.fetch.get(&url, Default::default()).then(move |result| {
 			result
@@ -148,4 +179,16 @@ impl<F: Fetch> ParitySet for ParitySetClient<F> {
 	fn remove_transaction(&self, _hash: H256) -> Result<Option<Transaction>> {
 		Err(errors::light_unimplemented(None))
 	}
+
+	fn block_template(&self) -> Result<BlockTemplate> {
+		Err(errors::light_unimplemented(None))
+	}
+
+	fn submit_block_seal(&self, _hash: H256, _seal: Vec<Bytes>) -> Result<H256> {
+		Err(errors::light_unimplemented(None))
+	}
+
+	fn submit_block(&self, _block: Bytes) -> Result<H256> {
+		Err(errors::light_unimplemented(None))
+	}
 }
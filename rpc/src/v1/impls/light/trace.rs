@@ -18,10 +18,12 @@
 
 use jsonrpc_core::Result;
 use jsonrpc_macros::Trailing;
+use jsonrpc_macros::pubsub::Subscriber;
+use jsonrpc_pubsub::SubscriptionId;
 use v1::Metadata;
 use v1::traits::Traces;
 use v1::helpers::errors;
-use v1::types::{TraceFilter, LocalizedTrace, BlockNumber, Index, CallRequest, Bytes, TraceResults, TraceResultsWithTransactionHash, TraceOptions, H256};
+use v1::types::{TraceFilter, LocalizedTrace, BlockNumber, BlockNumberOrHash, Index, CallRequest, Bytes, TraceResults, TraceResultsWithTransactionHash, TraceOptions, H256};
 
 /// Traces api implementation.
 // TODO: all calling APIs should be possible w. proved remote TX execution.
@@ -34,7 +36,7 @@ impl Traces for TracesClient {
 		Err(errors::light_unimplemented(None))
 	}
 
-	fn block_traces(&self, _block_number: BlockNumber) -> Result<Option<Vec<LocalizedTrace>>> {
+	fn block_traces(&self, _block: BlockNumberOrHash, _allow_non_canonical: Trailing<bool>) -> Result<Option<Vec<LocalizedTrace>>> {
 		Err(errors::light_unimplemented(None))
 	}
 
@@ -65,4 +67,12 @@ impl Traces for TracesClient {
 	fn replay_block_transactions(&self,  _block_number: BlockNumber, _flags: TraceOptions) ->  Result<Vec<TraceResultsWithTransactionHash>> {
 		Err(errors::light_unimplemented(None))
 	}
+
+	fn subscribe_filter(&self, _meta: Self::Metadata, subscriber: Subscriber<Vec<LocalizedTrace>>, _filter: TraceFilter) {
+		let _ = subscriber.reject(errors::light_unimplemented(None));
+	}
+
+	fn unsubscribe_filter(&self, _id: SubscriptionId) -> Result<bool> {
+		Ok(false)
+	}
 }
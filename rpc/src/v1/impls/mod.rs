@@ -16,7 +16,9 @@
 
 //! Ethereum rpc interface implementation.
 
+mod admin;
 mod debug;
+mod engine;
 mod eth;
 mod eth_filter;
 mod eth_pubsub;
@@ -33,11 +35,14 @@ mod signer;
 mod signing;
 mod signing_unsafe;
 mod traces;
+mod txpool;
 mod web3;
 
 pub mod light;
 
+pub use self::admin::AdminClient;
 pub use self::debug::DebugClient;
+pub use self::engine::EngineClient;
 pub use self::eth::{EthClient, EthClientOptions};
 pub use self::eth_filter::EthFilterClient;
 pub use self::eth_pubsub::EthPubSubClient;
@@ -54,4 +59,5 @@ pub use self::signer::SignerClient;
 pub use self::signing::SigningQueueClient;
 pub use self::signing_unsafe::SigningUnsafeClient;
 pub use self::traces::TracesClient;
+pub use self::txpool::TxPoolClient;
 pub use self::web3::Web3Client;
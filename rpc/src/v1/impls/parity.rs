@@ -15,8 +15,10 @@
 // along with Parity.  If not, see <http://www.gnu.org/licenses/>.
 
 //! Parity-specific rpc implementation.
+use std::cmp;
 use std::sync::Arc;
 use std::str::FromStr;
+use std::time::Instant;
 use std::collections::{BTreeMap, HashSet};
 
 use ethereum_types::Address;
@@ -27,29 +29,40 @@ use ethkey::{crypto::ecies, Brain, Generator};
 use ethstore::random_phrase;
 use sync::{SyncProvider, ManageNetwork};
 use ethcore::account_provider::AccountProvider;
-use ethcore::client::{BlockChainClient, StateClient, Call};
-use ethcore::ids::BlockId;
+use ethcore::client::{BlockChainClient, EngineInfo, StateClient, Call, CallAnalytics};
+use ethcore::ids::{BlockId, TransactionId};
 use ethcore::miner::{self, MinerService};
 use ethcore::state::StateInfo;
 use ethcore_logger::RotatingLogger;
+use futures_cpupool::CpuPool;
+use parking_lot::Mutex;
 use updater::{Service as UpdateService};
 use jsonrpc_core::{BoxFuture, Result};
 use jsonrpc_core::futures::future;
 use jsonrpc_macros::Trailing;
-use v1::helpers::{self, errors, fake_sign, ipfs, SigningQueue, SignerService, NetworkSettings};
+use v1::helpers::{self, errors, fake_sign, gas_flame, ipfs, signature, GasPriceOracle, SigningQueue, SignerService, NetworkSettings};
+use v1::helpers::sync_progress::{sync_stage, ProgressEstimator};
 use v1::metadata::Metadata;
 use v1::traits::Parity;
 use v1::types::{
 	Bytes, U256, U64, H160, H256, H512, CallRequest,
-	Peers, Transaction, RpcSettings, Histogram,
-	TransactionStats, LocalTransactionStatus,
-	BlockNumber, ConsensusCapability, VersionInfo,
-	OperationsInfo, ChainStatus,
-	AccountInfo, HwAccountInfo, RichHeader, Receipt,
-	block_number_to_id
+	Peers, HandshakeFailure, Transaction, RpcSettings, Histogram,
+	TransactionStats, BlockPropagationStats, BlockAnnouncementStats, LocalTransactionStatus,
+	BlockNumber, BlockNumberOrHash, ConsensusCapability, VersionInfo,
+	OperationsInfo, ChainStatus, ImportQueueStatus, TransactionLifecycle,
+	AccountInfo, HwAccountInfo, RichHeader, Receipt, ThreadPoolInfo, StateDiff,
+	SignatureVerificationRequest, SignatureVerification, SyncStage, SyncProgress,
+	block_number_to_id, traffic_by_protocol,
 };
 use Host;
 
+/// Replay analytics needed to profile gas of nested calls.
+const TRACE_ANALYTICS: CallAnalytics = CallAnalytics {
+	transaction_tracing: true,
+	vm_tracing: false,
+	state_diffing: false,
+};
+
 /// Parity implementation.
 pub struct ParityClient<C, M, U> {
 	client: Arc<C>,
@@ -62,6 +75,9 @@ pub struct ParityClient<C, M, U> {
 	settings: Arc<NetworkSettings>,
 	signer: Option<Arc<SignerService>>,
 	ws_address: Option<Host>,
+	pool: CpuPool,
+	sync_progress: Mutex<ProgressEstimator>,
+	gas_price_oracle: Arc<GasPriceOracle>,
 }
 
 impl<C, M, U> ParityClient<C, M, U> where
@@ -79,6 +95,7 @@ impl<C, M, U> ParityClient<C, M, U> where
 		settings: Arc<NetworkSettings>,
 		signer: Option<Arc<SignerService>>,
 		ws_address: Option<Host>,
+		pool: CpuPool,
 	) -> Self {
 		ParityClient {
 			client,
@@ -91,13 +108,22 @@ impl<C, M, U> ParityClient<C, M, U> where
 			settings,
 			signer,
 			ws_address,
+			pool,
+			sync_progress: Default::default(),
+			gas_price_oracle: Arc::new(GasPriceOracle::new(Default::default())),
 		}
 	}
+
+	/// Use given oracle for `parity_gasPriceHistogram`, e.g. to share it with other APIs.
+	pub fn with_gas_price_oracle(mut self, gas_price_oracle: Arc<GasPriceOracle>) -> Self {
+		self.gas_price_oracle = gas_price_oracle;
+		self
+	}
 }
 
 impl<C, M, U, S> Parity for ParityClient<C, M, U> where
 	S: StateInfo + 'static,
-	C: miner::BlockChainClient + BlockChainClient + StateClient<State=S> + Call<State=S> + 'static,
+	C: miner::BlockChainClient + BlockChainClient + EngineInfo + StateClient<State=S> + Call<State=S> + 'static,
 	M: MinerService<State=S> + 'static,
 	U: UpdateService + 'static,
 {
@@ -191,10 +217,15 @@ impl<C, M, U, S> Parity for ParityClient<C, M, U> where
 			active: sync_status.num_active_peers,
 			connected: sync_status.num_peers,
 			max: sync_status.current_max_peers(num_peers_range.start, num_peers_range.end - 1),
-			peers: peers
+			peers: peers,
+			traffic: traffic_by_protocol(self.net.traffic()),
 		})
 	}
 
+	fn net_failures(&self) -> Result<Vec<HandshakeFailure>> {
+		Ok(self.net.handshake_failures().into_iter().map(Into::into).collect())
+	}
+
 	fn net_port(&self) -> Result<u16> {
 		Ok(self.settings.network_port)
 	}
@@ -226,8 +257,8 @@ impl<C, M, U, S> Parity for ParityClient<C, M, U> where
 	}
 
 	fn gas_price_histogram(&self) -> BoxFuture<Histogram> {
-		Box::new(future::done(self.client
-			.gas_price_corpus(100)
+		Box::new(future::done(self.gas_price_oracle
+			.corpus(&*self.client)
 			.histogram(10)
 			.ok_or_else(errors::not_enough_data)
 			.map(Into::into)
@@ -279,6 +310,28 @@ impl<C, M, U, S> Parity for ParityClient<C, M, U> where
 			.map(|a| a.into_iter().map(Into::into).collect()))
 	}
 
+	fn state_diff_between(&self, from: BlockNumber, to: BlockNumber) -> Result<Option<StateDiff>> {
+		if from == BlockNumber::Pending || to == BlockNumber::Pending {
+			warn!("BlockNumber::Pending is unsupported");
+			return Ok(None);
+		}
+
+		Ok(self.client
+			.state_diff_between(block_number_to_id(from), block_number_to_id(to))
+			.map(Into::into))
+	}
+
+	fn block_witness(&self, number: BlockNumber) -> Result<Option<Bytes>> {
+		if number == BlockNumber::Pending {
+			warn!("BlockNumber::Pending is unsupported");
+			return Ok(None);
+		}
+
+		Ok(self.client
+			.block_witness(block_number_to_id(number))
+			.map(|witness| ::rlp::encode(&witness).into_vec().into()))
+	}
+
 	fn encrypt_message(&self, key: H512, phrase: Bytes) -> Result<Bytes> {
 		ecies::encrypt(&key.into(), &DEFAULT_MAC, &phrase.0)
 			.map_err(errors::encryption)
@@ -299,6 +352,18 @@ impl<C, M, U, S> Parity for ParityClient<C, M, U> where
 		)
 	}
 
+	fn pending_block_candidates(&self) -> Result<Option<Vec<Transaction>>> {
+		let best_block_number = self.client.chain_info().best_block_number;
+
+		Ok(self.miner.pending_block_candidates(best_block_number)
+			.map(|candidates| candidates
+				.into_iter()
+				.map(|t| Transaction::from_pending(t.pending().clone()))
+				.collect()
+			)
+		)
+	}
+
 	fn all_transactions(&self) -> Result<Vec<Transaction>> {
 		let all_transactions = self.miner.queued_transactions();
 
@@ -321,6 +386,18 @@ impl<C, M, U, S> Parity for ParityClient<C, M, U> where
 		)
 	}
 
+	fn block_propagation_stats(&self) -> Result<BTreeMap<H256, BlockPropagationStats>> {
+		let stats = self.sync.blocks_stats();
+		Ok(stats.into_iter()
+			.map(|(hash, stats)| (hash.into(), stats.into()))
+			.collect()
+		)
+	}
+
+	fn block_announcement_stats(&self) -> Result<BlockAnnouncementStats> {
+		Ok(self.sync.announcement_stats().into())
+	}
+
 	fn local_transactions(&self) -> Result<BTreeMap<H256, LocalTransactionStatus>> {
 		let transactions = self.miner.local_transactions();
 		Ok(transactions
@@ -330,6 +407,10 @@ impl<C, M, U, S> Parity for ParityClient<C, M, U> where
 		)
 	}
 
+	fn transaction_lifecycle(&self, hash: H256) -> Result<Option<TransactionLifecycle>> {
+		Ok(self.client.transaction_lifecycle(&hash.into()).map(Into::into))
+	}
+
 	fn ws_url(&self) -> Result<String> {
 		helpers::to_url(&self.ws_address)
 			.ok_or_else(errors::ws_disabled)
@@ -369,9 +450,43 @@ impl<C, M, U, S> Parity for ParityClient<C, M, U> where
 
 		Ok(ChainStatus {
 			block_gap: gap.map(|(x, y)| (x.into(), y.into())),
+			timestamp_drift: self.client.engine().params().into(),
+			import_paused: self.client.is_import_paused(),
 		})
 	}
 
+	fn sync_progress(&self) -> Result<SyncProgress> {
+		let status = self.sync.status();
+		let stage = sync_stage(&status, self.client.queue_info());
+		let current_block = self.client.chain_info().best_block_number;
+		let highest_block = cmp::max(status.highest_block_number.unwrap_or(current_block), current_block);
+		let warp_chunks = match stage {
+			SyncStage::WarpChunks | SyncStage::WarpRestoring => Some((status.snapshot_chunks_done as u64, status.num_snapshot_chunks as u64)),
+			_ => None,
+		};
+
+		let eta = match (stage, warp_chunks) {
+			(SyncStage::Blocks, _) => self.sync_progress.lock().update(stage, Instant::now(), current_block, highest_block),
+			(_, Some((done, total))) => self.sync_progress.lock().update(stage, Instant::now(), done, total),
+			_ => None,
+		};
+
+		Ok(SyncProgress {
+			stage,
+			current_block: current_block.into(),
+			highest_block: highest_block.into(),
+			warp_chunks_amount: warp_chunks.map(|(_, total)| total.into()),
+			warp_chunks_processed: warp_chunks.map(|(done, _)| done.into()),
+			peers: status.num_peers,
+			active_peers: status.num_active_peers,
+			eta,
+		})
+	}
+
+	fn import_queue_status(&self) -> Result<ImportQueueStatus> {
+		Ok(self.client.import_queue_status().into())
+	}
+
 	fn node_kind(&self) -> Result<::v1::types::NodeKind> {
 		use ::v1::types::{NodeKind, Availability, Capability};
 
@@ -381,6 +496,10 @@ impl<C, M, U, S> Parity for ParityClient<C, M, U> where
 		})
 	}
 
+	fn thread_stats(&self) -> Result<Vec<ThreadPoolInfo>> {
+		Ok(::io::threads::thread_pools().into_iter().map(Into::into).collect())
+	}
+
 	fn block_header(&self, number: Trailing<BlockNumber>) -> BoxFuture<RichHeader> {
 		const EXTRA_INFO_PROOF: &str = "Object exists in blockchain (fetched earlier), extra_info is always available if object exists; qed";
 		let number = number.unwrap_or_default();
@@ -411,11 +530,9 @@ impl<C, M, U, S> Parity for ParityClient<C, M, U> where
 		}))
 	}
 
-	fn block_receipts(&self, number: Trailing<BlockNumber>) -> BoxFuture<Vec<Receipt>> {
-		let number = number.unwrap_or_default();
-
-		let id = match number {
-			BlockNumber::Pending => {
+	fn block_receipts(&self, block: Trailing<BlockNumberOrHash>) -> BoxFuture<Vec<Receipt>> {
+		let id = match block.unwrap_or_default() {
+			BlockNumberOrHash::Number(BlockNumber::Pending) => {
 				let info = self.client.chain_info();
 				let receipts = try_bf!(self.miner.pending_receipts(info.best_block_number).ok_or_else(errors::unknown_block));
 				return Box::new(future::ok(receipts
@@ -424,9 +541,10 @@ impl<C, M, U, S> Parity for ParityClient<C, M, U> where
 					.collect()
 				))
 			},
-			BlockNumber::Num(num) => BlockId::Number(num),
-			BlockNumber::Earliest => BlockId::Earliest,
-			BlockNumber::Latest => BlockId::Latest,
+			BlockNumberOrHash::Number(BlockNumber::Num(num)) => BlockId::Number(num),
+			BlockNumberOrHash::Number(BlockNumber::Earliest) => BlockId::Earliest,
+			BlockNumberOrHash::Number(BlockNumber::Latest) => BlockId::Latest,
+			BlockNumberOrHash::Hash(hash) => BlockId::Hash(hash.into()),
 		};
 		let receipts = try_bf!(self.client.block_receipts(id).ok_or_else(errors::unknown_block));
 		Box::new(future::ok(receipts.into_iter().map(Into::into).collect()))
@@ -436,6 +554,10 @@ impl<C, M, U, S> Parity for ParityClient<C, M, U> where
 		ipfs::cid(content)
 	}
 
+	fn verify_signatures(&self, requests: Vec<SignatureVerificationRequest>) -> BoxFuture<Vec<SignatureVerification>> {
+		signature::verify_signatures(&self.pool, requests)
+	}
+
 	fn call(&self, requests: Vec<CallRequest>, num: Trailing<BlockNumber>) -> Result<Vec<Bytes>> {
 		let requests = requests
 			.into_iter()
@@ -462,7 +584,7 @@ impl<C, M, U, S> Parity for ParityClient<C, M, U> where
 			};
 
 			let state = self.client.state_at(id).ok_or_else(errors::state_pruned)?;
-			let header = self.client.block_header(id).ok_or_else(errors::state_pruned)?.decode().map_err(errors::decode)?;
+			let header = self.client.block_header(id).ok_or_else(errors::state_pruned)?.decode(self.client.engine().params().eip1559_transition).map_err(errors::decode)?;
 
 			(state, header)
 		};
@@ -471,4 +593,27 @@ impl<C, M, U, S> Parity for ParityClient<C, M, U> where
 				.map(|res| res.into_iter().map(|res| res.output.into()).collect())
 				.map_err(errors::call)
 	}
+
+	fn gas_flame(&self, hash: H256) -> Result<String> {
+		let executed = self.client.replay(TransactionId::Hash(hash.into()), TRACE_ANALYTICS).map_err(errors::call)?;
+
+		let mut folded = String::new();
+		gas_flame::fold(&executed.trace, None, &mut folded);
+		Ok(folded)
+	}
+
+	fn block_gas_flame(&self, num: BlockNumber) -> Result<String> {
+		let id = match num {
+			BlockNumber::Num(num) => BlockId::Number(num),
+			BlockNumber::Earliest => BlockId::Earliest,
+			BlockNumber::Latest => BlockId::Latest,
+			BlockNumber::Pending => return Err(errors::invalid_params("`BlockNumber::Pending` is not supported", ())),
+		};
+
+		let mut folded = String::new();
+		for (hash, executed) in self.client.replay_block_transactions(id, TRACE_ANALYTICS).map_err(errors::call)? {
+			gas_flame::fold(&executed.trace, Some(&format!("0x{:x}", hash)), &mut folded);
+		}
+		Ok(folded)
+	}
 }
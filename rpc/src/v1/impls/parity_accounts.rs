@@ -20,7 +20,7 @@ use std::collections::btree_map::{BTreeMap, Entry};
 use ethereum_types::Address;
 
 use ethkey::{Brain, Generator, Secret};
-use ethstore::KeyFile;
+use ethstore::{AccountLabels, KeyFile};
 use ethcore::account_provider::AccountProvider;
 use jsonrpc_core::Result;
 use v1::helpers::errors;
@@ -42,8 +42,8 @@ impl ParityAccountsClient {
 	}
 }
 
-impl ParityAccounts for ParityAccountsClient {
-	fn all_accounts_info(&self) -> Result<BTreeMap<RpcH160, ExtAccountInfo>> {
+impl ParityAccountsClient {
+	fn accounts_info(&self) -> Result<BTreeMap<RpcH160, ExtAccountInfo>> {
 		let info = self.accounts.accounts_info().map_err(|e| errors::account("Could not fetch account info.", e))?;
 		let other = self.accounts.addresses_info();
 
@@ -73,6 +73,12 @@ impl ParityAccounts for ParityAccountsClient {
 
 		Ok(accounts)
 	}
+}
+
+impl ParityAccounts for ParityAccountsClient {
+	fn all_accounts_info(&self) -> Result<BTreeMap<RpcH160, ExtAccountInfo>> {
+		self.accounts_info()
+	}
 
 	fn new_account_from_phrase(&self, phrase: String, pass: Password) -> Result<RpcH160> {
 		let brain = Brain::new(phrase).generate().unwrap();
@@ -143,6 +149,28 @@ impl ParityAccounts for ParityAccountsClient {
 		Ok(true)
 	}
 
+	fn set_account_tags(&self, addr: RpcH160, tags: Vec<String>) -> Result<bool> {
+		self.accounts
+			.set_account_tags(addr.into(), tags)
+			.map_err(|e| errors::account("Could not set account tags.", e))
+			.map(|_| true)
+	}
+
+	fn set_account_fields(&self, addr: RpcH160, fields: BTreeMap<String, String>) -> Result<bool> {
+		self.accounts
+			.set_account_fields(addr.into(), fields)
+			.map_err(|e| errors::account("Could not set account fields.", e))
+			.map(|_| true)
+	}
+
+	fn filter_accounts_info(&self, tags: Vec<String>, fields: BTreeMap<String, String>) -> Result<BTreeMap<RpcH160, ExtAccountInfo>> {
+		let accounts = self.accounts_info()?;
+		Ok(accounts
+			.into_iter()
+			.filter(|&(_, ref info)| AccountLabels::from_json_meta(&info.meta).matches(&tags, &fields))
+			.collect())
+	}
+
 	fn import_geth_accounts(&self, addresses: Vec<RpcH160>) -> Result<Vec<RpcH160>> {
 		self.accounts
 			.import_geth_accounts(into_vec(addresses), false)
@@ -19,8 +19,10 @@ use std::io;
 use std::sync::Arc;
 use std::time::Duration;
 
-use ethcore::client::{BlockChainClient, Mode};
-use ethcore::miner::MinerService;
+use ethcore::block::IsBlock;
+use ethcore::client::{BlockChainClient, EngineInfo, Mode};
+use ethcore::miner::{self, MinerService};
+use ethcore::verification::queue::kind::blocks::Unverified;
 use sync::ManageNetwork;
 use fetch::{self, Fetch};
 use futures_cpupool::CpuPool;
@@ -31,7 +33,7 @@ use jsonrpc_core::{BoxFuture, Result};
 use jsonrpc_core::futures::Future;
 use v1::helpers::errors;
 use v1::traits::ParitySet;
-use v1::types::{Bytes, H160, H256, U256, ReleaseInfo, Transaction};
+use v1::types::{BlockTemplate, Bytes, H160, H256, U256, ReleaseInfo, Transaction};
 
 /// Parity-specific rpc interface for operations altering the settings.
 pub struct ParitySetClient<C, M, U, F = fetch::Client> {
@@ -67,7 +69,7 @@ impl<C, M, U, F> ParitySetClient<C, M, U, F>
 }
 
 impl<C, M, U, F> ParitySet for ParitySetClient<C, M, U, F> where
-	C: BlockChainClient + 'static,
+	C: miner::BlockChainClient + BlockChainClient + EngineInfo + 'static,
 	M: MinerService + 'static,
 	U: UpdateService + 'static,
 	F: Fetch + 'static,
@@ -83,6 +85,34 @@ impl<C, M, U, F> ParitySet for ParitySetClient<C, M, U, F> where
 		Ok(false)
 	}
 
+	fn set_transactions_replacement_bump(&self, percent: f64) -> Result<bool> {
+		let bump = ::miner::pool::scoring::replacement_bump_from_percent(percent)
+			.ok_or_else(|| errors::invalid_params("percent", "Replacement bump has to be between 0 and 1000 percent."))?;
+		self.miner.set_replacement_bump(bump);
+		Ok(true)
+	}
+
+	fn set_transactions_per_sender_limit(&self, limit: usize) -> Result<bool> {
+		let mut quotas = self.miner.queue_quotas();
+		quotas.max_per_sender = non_zero(limit);
+		self.miner.set_queue_quotas(quotas);
+		Ok(true)
+	}
+
+	fn set_transactions_per_origin_limit(&self, limit: usize) -> Result<bool> {
+		let mut quotas = self.miner.queue_quotas();
+		quotas.max_per_origin = non_zero(limit);
+		self.miner.set_queue_quotas(quotas);
+		Ok(true)
+	}
+
+	fn set_transactions_memory_limit(&self, limit: usize) -> Result<bool> {
+		let mut quotas = self.miner.queue_quotas();
+		quotas.max_mem_usage = non_zero(limit);
+		self.miner.set_queue_quotas(quotas);
+		Ok(true)
+	}
+
 	fn set_tx_gas_limit(&self, _limit: U256) -> Result<bool> {
 		warn!("setTxGasLimit is deprecated. Ignoring request.");
 		Ok(false)
@@ -131,6 +161,13 @@ impl<C, M, U, F> ParitySet for ParitySetClient<C, M, U, F> where
 		}
 	}
 
+	fn set_reserved_peers(&self, peers: Vec<String>) -> Result<bool> {
+		match self.net.set_reserved_peers(peers) {
+			Ok(()) => Ok(true),
+			Err(e) => Err(errors::invalid_params("Peer address", e)),
+		}
+	}
+
 	fn drop_non_reserved_peers(&self) -> Result<bool> {
 		self.net.deny_unreserved_peers();
 		Ok(true)
@@ -167,6 +204,16 @@ impl<C, M, U, F> ParitySet for ParitySetClient<C, M, U, F> where
 		Ok(true)
 	}
 
+	fn pause_import(&self) -> Result<bool> {
+		self.client.set_import_paused(true);
+		Ok(true)
+	}
+
+	fn resume_import(&self) -> Result<bool> {
+		self.client.set_import_paused(false);
+		Ok(true)
+	}
+
 	fn hash_content(&self, url: String) -> BoxFuture<H256> {
 		let future = self.fetch.get(&url, Default::default()).then(move |result| {
 			result
@@ -195,4 +242,40 @@ impl<C, M, U, F> ParitySet for ParitySetClient<C, M, U, F> where
 		   .map(|t| Transaction::from_pending(t.pending().clone()))
 		)
 	}
+
+	fn block_template(&self) -> Result<BlockTemplate> {
+		if self.miner.authoring_params().author.is_zero() {
+			warn!(target: "miner", "Cannot give block template - no author is configured. Use --author to configure!");
+			return Err(errors::no_author())
+		}
+
+		let block = self.miner.block_template(&*self.client).ok_or_else(|| {
+			warn!(target: "miner", "Cannot give block template - engine seals internally.");
+			errors::no_work_required()
+		})?;
+
+		Ok((&block).into())
+	}
+
+	fn submit_block_seal(&self, hash: H256, seal: Vec<Bytes>) -> Result<H256> {
+		let seal = seal.into_iter().map(Bytes::into_vec).collect();
+		let block = self.miner.submit_seal(hash.into(), seal).map_err(errors::block_rejected)?;
+		let hash = block.header().hash();
+		self.client.import_sealed_block(block).map_err(errors::block_rejected)?;
+		Ok(hash.into())
+	}
+
+	fn submit_block(&self, block: Bytes) -> Result<H256> {
+		let block = Unverified::from_rlp(block.into_vec(), self.client.engine().params().eip1559_transition).map_err(errors::rlp)?;
+		let hash = self.client.import_block(block).map_err(errors::block_rejected)?;
+		Ok(hash.into())
+	}
+}
+
+/// Queue quotas are removed by setting them to 0.
+fn non_zero(limit: usize) -> Option<usize> {
+	match limit {
+		0 => None,
+		limit => Some(limit),
+	}
 }
@@ -16,25 +16,43 @@
 
 //! Parity-specific PUB-SUB rpc implementation.
 
-use std::sync::Arc;
+use std::sync::{Arc, Weak};
 use std::time::Duration;
 use parking_lot::RwLock;
 
+use ethcore::client::{ChainNotify, ChainReorg as EthChainReorg};
+use ethereum_types::H256;
 use jsonrpc_core::{self as core, Result, MetaIoHandler};
 use jsonrpc_core::futures::{Future, Stream, Sink};
 use jsonrpc_macros::Trailing;
-use jsonrpc_macros::pubsub::Subscriber;
+use jsonrpc_macros::pubsub::{self, Subscriber};
 use jsonrpc_pubsub::SubscriptionId;
+use serde_json;
 use tokio_timer;
 
+use miner::pool::TransactionEvent;
 use parity_reactor::Remote;
-use v1::helpers::GenericPollManager;
+use v1::helpers::{GenericPollManager, Subscribers};
 use v1::metadata::Metadata;
 use v1::traits::PubSub;
+use v1::types::{ChainReorg, TransactionPoolEvent};
+
+/// Subscription name which is an alias for polling `parity_syncProgress`.
+const SYNCING_SUBSCRIPTION: &str = "syncing";
+/// Method polled for `SYNCING_SUBSCRIPTION`.
+const SYNC_PROGRESS_METHOD: &str = "parity_syncProgress";
+/// Subscription name for canonical chain reorganizations.
+const REORGS_SUBSCRIPTION: &str = "reorgs";
+/// Subscription name for status changes of transactions in the pool.
+const TRANSACTIONS_POOL_SUBSCRIPTION: &str = "transactionsPool";
 
 /// Parity PubSub implementation.
 pub struct PubSubClient<S: core::Middleware<Metadata>> {
 	poll_manager: Arc<RwLock<GenericPollManager<S>>>,
+	reorg_handler: Arc<ReorgNotificationHandler>,
+	reorgs_subscribers: Arc<RwLock<Subscribers<pubsub::Sink<core::Value>>>>,
+	pool_handler: Arc<PoolNotificationHandler>,
+	pool_subscribers: Arc<RwLock<Subscribers<pubsub::Sink<core::Value>>>>,
 	remote: Remote,
 }
 
@@ -55,11 +73,34 @@ impl<S: core::Middleware<Metadata>> PubSubClient<S> {
 			.for_each(move |_| pm2.read().tick())
 		);
 
+		let reorgs_subscribers = Arc::new(RwLock::new(Subscribers::default()));
+		let pool_subscribers = Arc::new(RwLock::new(Subscribers::default()));
+
 		PubSubClient {
 			poll_manager,
+			reorg_handler: Arc::new(ReorgNotificationHandler {
+				remote: remote.clone(),
+				reorgs_subscribers: reorgs_subscribers.clone(),
+			}),
+			reorgs_subscribers,
+			pool_handler: Arc::new(PoolNotificationHandler {
+				remote: remote.clone(),
+				pool_subscribers: pool_subscribers.clone(),
+			}),
+			pool_subscribers,
 			remote,
 		}
 	}
+
+	/// Returns a chain notification handler delivering `reorgs` subscriptions.
+	pub fn reorg_handler(&self) -> Weak<ReorgNotificationHandler> {
+		Arc::downgrade(&self.reorg_handler)
+	}
+
+	/// Returns a transaction pool listener delivering `transactionsPool` subscriptions.
+	pub fn pool_handler(&self) -> Weak<PoolNotificationHandler> {
+		Arc::downgrade(&self.pool_handler)
+	}
 }
 
 impl PubSubClient<core::NoopMiddleware> {
@@ -68,15 +109,88 @@ impl PubSubClient<core::NoopMiddleware> {
 	pub fn new_test(rpc: MetaIoHandler<Metadata, core::NoopMiddleware>, remote: Remote) -> Self {
 		let client = Self::new(MetaIoHandler::with_middleware(Default::default()), remote);
 		*client.poll_manager.write() = GenericPollManager::new_test(rpc);
+		*client.reorgs_subscribers.write() = Subscribers::new_test();
+		*client.pool_subscribers.write() = Subscribers::new_test();
 		client
 	}
 }
 
+/// Notifies `reorgs` subscribers about changes of the canonical chain.
+pub struct ReorgNotificationHandler {
+	remote: Remote,
+	reorgs_subscribers: Arc<RwLock<Subscribers<pubsub::Sink<core::Value>>>>,
+}
+
+impl ChainNotify for ReorgNotificationHandler {
+	fn reorg(&self, reorg: EthChainReorg) {
+		let value = match serde_json::to_value(ChainReorg::from(reorg)) {
+			Ok(value) => value,
+			Err(e) => {
+				warn!(target: "rpc", "Unable to serialize chain reorganization: {}", e);
+				return;
+			},
+		};
+
+		for subscriber in self.reorgs_subscribers.read().values() {
+			self.remote.spawn(subscriber
+				.notify(Ok(value.clone()))
+				.map(|_| ())
+				.map_err(|e| warn!(target: "rpc", "Unable to send notification: {}", e))
+			);
+		}
+	}
+}
+
+/// Notifies `transactionsPool` subscribers about status changes of transactions in the pool.
+pub struct PoolNotificationHandler {
+	remote: Remote,
+	pool_subscribers: Arc<RwLock<Subscribers<pubsub::Sink<core::Value>>>>,
+}
+
+impl PoolNotificationHandler {
+	/// Deliver a transaction pool event to all subscribers.
+	pub fn notify(&self, hash: &H256, event: TransactionEvent) {
+		let subscribers = self.pool_subscribers.read();
+		if subscribers.is_empty() {
+			return;
+		}
+
+		let value = match serde_json::to_value(TransactionPoolEvent::from((hash, event))) {
+			Ok(value) => value,
+			Err(e) => {
+				warn!(target: "rpc", "Unable to serialize transaction pool event: {}", e);
+				return;
+			},
+		};
+
+		for subscriber in subscribers.values() {
+			self.remote.spawn(subscriber
+				.notify(Ok(value.clone()))
+				.map(|_| ())
+				.map_err(|e| warn!(target: "rpc", "Unable to send notification: {}", e))
+			);
+		}
+	}
+}
+
 impl<S: core::Middleware<Metadata>> PubSub for PubSubClient<S> {
 	type Metadata = Metadata;
 
 	fn parity_subscribe(&self, mut meta: Metadata, subscriber: Subscriber<core::Value>, method: String, params: Trailing<core::Params>) {
+		if method == REORGS_SUBSCRIPTION {
+			self.reorgs_subscribers.write().push(subscriber);
+			return;
+		}
+		if method == TRANSACTIONS_POOL_SUBSCRIPTION {
+			self.pool_subscribers.write().push(subscriber);
+			return;
+		}
+
 		let params = params.unwrap_or(core::Params::Array(vec![]));
+		let method = match method.as_str() {
+			SYNCING_SUBSCRIPTION => SYNC_PROGRESS_METHOD.into(),
+			_ => method,
+		};
 		// Make sure to get rid of PubSub session otherwise it will never be dropped.
 		meta.session = None;
 
@@ -95,6 +209,13 @@ impl<S: core::Middleware<Metadata>> PubSub for PubSubClient<S> {
 	}
 
 	fn parity_unsubscribe(&self, id: SubscriptionId) -> Result<bool> {
+		if self.reorgs_subscribers.write().remove(&id).is_some() {
+			return Ok(true);
+		}
+		if self.pool_subscribers.write().remove(&id).is_some() {
+			return Ok(true);
+		}
+
 		let res = self.poll_manager.write().unsubscribe(&id);
 		Ok(res)
 	}
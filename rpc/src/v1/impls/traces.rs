@@ -16,18 +16,27 @@
 
 //! Traces api implementation.
 
+use std::cmp;
 use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
 
-use ethcore::client::{BlockChainClient, CallAnalytics, TransactionId, TraceId, StateClient, StateInfo, Call, BlockId};
-use rlp::Rlp;
-use transaction::SignedTransaction;
+use ethcore::client::{self, BlockChainClient, CallAnalytics, TransactionId, TraceId, StateClient, StateInfo, Call, BlockId, EngineInfo};
+use ethereum_types::Address;
+use parking_lot::Mutex;
+use parity_reactor::Remote;
+use transaction::{SignedTransaction, UnverifiedTransaction};
 
 use jsonrpc_core::Result;
+use jsonrpc_core::futures::{future, stream, Future, Stream};
+use jsonrpc_core::futures::future::{Either, Loop};
 use jsonrpc_macros::Trailing;
+use jsonrpc_macros::pubsub::{Sink, Subscriber};
+use jsonrpc_pubsub::SubscriptionId;
 use v1::Metadata;
 use v1::traits::Traces;
-use v1::helpers::{errors, fake_sign};
-use v1::types::{TraceFilter, LocalizedTrace, BlockNumber, Index, CallRequest, Bytes, TraceResults, TraceResultsWithTransactionHash, TraceOptions, H256, block_number_to_id};
+use v1::helpers::{errors, fake_sign, Subscribers};
+use v1::impls::eth::is_canonical;
+use v1::types::{TraceFilter, LocalizedTrace, BlockNumber, BlockNumberOrHash, Index, CallRequest, Bytes, TraceResults, TraceResultsWithTransactionHash, TraceOptions, H256, block_number_to_id};
 
 fn to_call_analytics(flags: TraceOptions) -> CallAnalytics {
 	CallAnalytics {
@@ -37,23 +46,99 @@ fn to_call_analytics(flags: TraceOptions) -> CallAnalytics {
 	}
 }
 
+/// Number of blocks scanned by the trace database at once when streaming traces.
+const STREAM_CHUNK_BLOCKS: u64 = 128;
+
+/// Scan of a range of blocks for traces matching a filter.
+struct TracesScan<C> {
+	client: Arc<C>,
+	from_address: Vec<Address>,
+	to_address: Vec<Address>,
+	next_block: u64,
+	last_block: u64,
+	skip: usize,
+	remaining: usize,
+	done: bool,
+}
+
+impl<C: BlockChainClient> TracesScan<C> {
+	/// Scans next chunk of the range and returns matching traces grouped by block.
+	/// Returns a single empty group once the whole range was scanned and `None` afterwards.
+	fn next_chunk(&mut self) -> Option<Vec<Vec<LocalizedTrace>>> {
+		if self.done {
+			return None;
+		}
+		if self.next_block > self.last_block || self.remaining == 0 {
+			self.done = true;
+			return Some(vec![Vec::new()]);
+		}
+
+		let end = cmp::min(self.next_block + STREAM_CHUNK_BLOCKS - 1, self.last_block);
+		let mut traces = self.client.filter_traces(client::TraceFilter {
+			range: BlockId::Number(self.next_block)..BlockId::Number(end),
+			from_address: self.from_address.clone(),
+			to_address: self.to_address.clone(),
+			after: None,
+			count: None,
+		}).unwrap_or_default();
+		self.next_block = end + 1;
+
+		let skipped = cmp::min(self.skip, traces.len());
+		traces.drain(..skipped);
+		self.skip -= skipped;
+		traces.truncate(self.remaining);
+		self.remaining -= traces.len();
+
+		let mut blocks: Vec<(u64, Vec<LocalizedTrace>)> = Vec::new();
+		for trace in traces {
+			let number = trace.block_number;
+			if blocks.last().map_or(true, |&(last, _)| last != number) {
+				blocks.push((number, Vec::new()));
+			}
+			blocks.last_mut().expect("pushed above if empty; qed").1.push(LocalizedTrace::from(trace));
+		}
+		Some(blocks.into_iter().map(|(_, traces)| traces).collect())
+	}
+}
+
 /// Traces api implementation.
 pub struct TracesClient<C> {
 	client: Arc<C>,
+	remote: Remote,
+	streams: Mutex<Subscribers<Arc<AtomicBool>>>,
 }
 
 impl<C> TracesClient<C> {
 	/// Creates new Traces client.
-	pub fn new(client: &Arc<C>) -> Self {
+	pub fn new(client: &Arc<C>, remote: Remote) -> Self {
 		TracesClient {
 			client: client.clone(),
+			remote,
+			streams: Default::default(),
 		}
 	}
 }
 
+impl<C: BlockChainClient + 'static> TracesClient<C> {
+	fn stream(&self, sink: Sink<Vec<LocalizedTrace>>, scan: TracesScan<C>, active: Arc<AtomicBool>) {
+		self.remote.spawn(future::loop_fn((scan, sink), move |(mut scan, sink)| {
+			let chunk = if active.load(Ordering::SeqCst) { scan.next_chunk() } else { None };
+			match chunk {
+				Some(blocks) => {
+					let notify = sink.clone();
+					Either::A(stream::iter_ok(blocks)
+						.for_each(move |traces| notify.notify(Ok(traces)).map(|_| ()))
+						.map(move |_| Loop::Continue((scan, sink))))
+				},
+				None => Either::B(future::ok(Loop::Break(()))),
+			}
+		}).map_err(|e| warn!(target: "rpc", "Unable to send notification: {}", e)));
+	}
+}
+
 impl<C, S> Traces for TracesClient<C> where
 	S: StateInfo + 'static,
-	C: BlockChainClient + StateClient<State=S> + Call<State=S> + 'static
+	C: BlockChainClient + StateClient<State=S> + Call<State=S> + EngineInfo + 'static
 {
 	type Metadata = Metadata;
 
@@ -62,14 +147,25 @@ impl<C, S> Traces for TracesClient<C> where
 			.map(|traces| traces.into_iter().map(LocalizedTrace::from).collect()))
 	}
 
-	fn block_traces(&self, block_number: BlockNumber) -> Result<Option<Vec<LocalizedTrace>>> {
-		let id = match block_number {
-			BlockNumber::Pending => return Ok(None),
-			num => block_number_to_id(num)
+	fn block_traces(&self, block: BlockNumberOrHash, allow_non_canonical: Trailing<bool>) -> Result<Option<Vec<LocalizedTrace>>> {
+		let allow_non_canonical = allow_non_canonical.unwrap_or(false);
+		let (id, canonical) = match block {
+			BlockNumberOrHash::Number(BlockNumber::Pending) => return Ok(None),
+			BlockNumberOrHash::Number(num) => (block_number_to_id(num), true),
+			BlockNumberOrHash::Hash(hash) => {
+				let hash = hash.into();
+				(BlockId::Hash(hash), is_canonical(&*self.client, &hash))
+			},
 		};
+		if !canonical && !allow_non_canonical {
+			return Ok(None);
+		}
 
 		Ok(self.client.block_traces(id)
-			.map(|traces| traces.into_iter().map(LocalizedTrace::from).collect()))
+			.map(|traces| traces.into_iter()
+				.map(LocalizedTrace::from)
+				.map(|trace| if allow_non_canonical { trace.with_canonical(canonical) } else { trace })
+				.collect()))
 	}
 
 	fn transaction_traces(&self, transaction_hash: H256) -> Result<Option<Vec<LocalizedTrace>>> {
@@ -104,7 +200,7 @@ impl<C, S> Traces for TracesClient<C> where
 		let mut state = self.client.state_at(id).ok_or(errors::state_pruned())?;
 		let header = self.client.block_header(id).ok_or(errors::state_pruned())?;
 
-		self.client.call(&signed, to_call_analytics(flags), &mut state, &header.decode().map_err(errors::decode)?)
+		self.client.call(&signed, to_call_analytics(flags), &mut state, &header.decode(self.client.engine().params().eip1559_transition).map_err(errors::decode)?)
 			.map(TraceResults::from)
 			.map_err(errors::call)
 	}
@@ -131,7 +227,7 @@ impl<C, S> Traces for TracesClient<C> where
 		let mut state = self.client.state_at(id).ok_or(errors::state_pruned())?;
 		let header = self.client.block_header(id).ok_or(errors::state_pruned())?;
 
-		self.client.call_many(&requests, &mut state, &header.decode().map_err(errors::decode)?)
+		self.client.call_many(&requests, &mut state, &header.decode(self.client.engine().params().eip1559_transition).map_err(errors::decode)?)
 			.map(|results| results.into_iter().map(TraceResults::from).collect())
 			.map_err(errors::call)
 	}
@@ -139,7 +235,7 @@ impl<C, S> Traces for TracesClient<C> where
 	fn raw_transaction(&self, raw_transaction: Bytes, flags: TraceOptions, block: Trailing<BlockNumber>) -> Result<TraceResults> {
 		let block = block.unwrap_or_default();
 
-		let tx = Rlp::new(&raw_transaction.into_vec()).as_val().map_err(|e| errors::invalid_params("Transaction is not valid RLP", e))?;
+		let tx = UnverifiedTransaction::decode_raw(&raw_transaction.into_vec()).map_err(|e| errors::invalid_params("Transaction is not valid RLP", e))?;
 		let signed = SignedTransaction::new(tx).map_err(errors::transaction)?;
 
 		let id = match block {
@@ -153,7 +249,7 @@ impl<C, S> Traces for TracesClient<C> where
 		let mut state = self.client.state_at(id).ok_or(errors::state_pruned())?;
 		let header = self.client.block_header(id).ok_or(errors::state_pruned())?;
 
-		self.client.call(&signed, to_call_analytics(flags), &mut state, &header.decode().map_err(errors::decode)?)
+		self.client.call(&signed, to_call_analytics(flags), &mut state, &header.decode(self.client.engine().params().eip1559_transition).map_err(errors::decode)?)
 			.map(TraceResults::from)
 			.map_err(errors::call)
 	}
@@ -177,4 +273,55 @@ impl<C, S> Traces for TracesClient<C> where
 			.map(|results| results.into_iter().map(TraceResultsWithTransactionHash::from).collect())
 			.map_err(errors::call)
 	}
+
+	fn subscribe_filter(&self, _meta: Metadata, subscriber: Subscriber<Vec<LocalizedTrace>>, filter: TraceFilter) {
+		let filter: client::TraceFilter = filter.into();
+		let range = self.client.block_number(filter.range.start)
+			.and_then(|first| self.client.block_number(filter.range.end).map(|last| (first, last)));
+		let (first_block, last_block) = match range {
+			Some(range) => range,
+			None => {
+				let _ = subscriber.reject(errors::unknown_block());
+				return;
+			},
+		};
+
+		let tracing_enabled = self.client.filter_traces(client::TraceFilter {
+			range: BlockId::Number(first_block)..BlockId::Number(first_block),
+			from_address: Vec::new(),
+			to_address: Vec::new(),
+			after: None,
+			count: Some(0),
+		}).is_some();
+		if !tracing_enabled {
+			let _ = subscriber.reject(errors::unsupported("Tracing is disabled.", Some("Restart with --tracing on.")));
+			return;
+		}
+
+		let scan = TracesScan {
+			client: self.client.clone(),
+			from_address: filter.from_address,
+			to_address: filter.to_address,
+			next_block: first_block,
+			last_block,
+			skip: filter.after.unwrap_or(0),
+			remaining: filter.count.unwrap_or(usize::max_value()),
+			done: false,
+		};
+		let active = Arc::new(AtomicBool::new(true));
+		self.streams.lock().push_with(subscriber, |sink| {
+			self.stream(sink, scan, active.clone());
+			active
+		});
+	}
+
+	fn unsubscribe_filter(&self, id: SubscriptionId) -> Result<bool> {
+		match self.streams.lock().remove(&id) {
+			Some(active) => {
+				active.store(false, Ordering::SeqCst);
+				Ok(true)
+			},
+			None => Ok(false),
+		}
+	}
 }
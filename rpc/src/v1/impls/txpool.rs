@@ -0,0 +1,118 @@
+// Copyright 2015-2018 Parity Technologies (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Geth-compatible transaction pool RPC implementation.
+
+use std::collections::{BTreeMap, HashSet};
+use std::sync::Arc;
+
+use ethcore::miner::{self, MinerService};
+use miner::pool::VerifiedTransaction;
+use transaction::Action;
+
+use jsonrpc_core::Result;
+use v1::traits::TxPool;
+use v1::types::{Transaction, TxPoolContent, TxPoolStatus};
+
+/// TxPool rpc implementation.
+pub struct TxPoolClient<C, M> {
+	client: Arc<C>,
+	miner: Arc<M>,
+}
+
+impl<C, M> TxPoolClient<C, M> {
+	/// Creates new txpool client.
+	pub fn new(client: Arc<C>, miner: Arc<M>) -> Self {
+		TxPoolClient {
+			client,
+			miner,
+		}
+	}
+}
+
+impl<C, M> TxPoolClient<C, M> where
+	C: miner::BlockChainClient,
+	M: MinerService,
+{
+	/// Returns transactions ready for inclusion and the remaining ones still waiting in the queue.
+	fn pool(&self) -> (Vec<Arc<VerifiedTransaction>>, Vec<Arc<VerifiedTransaction>>) {
+		let pending = self.miner.ready_transactions(
+			&*self.client,
+			usize::max_value(),
+			miner::PendingOrdering::Priority,
+		);
+		let pending_hashes = pending.iter().map(|t| t.signed().hash()).collect::<HashSet<_>>();
+		let queued = self.miner.queued_transactions()
+			.into_iter()
+			.filter(|t| !pending_hashes.contains(&t.signed().hash()))
+			.collect();
+
+		(pending, queued)
+	}
+
+	/// Groups pool transactions by sender and nonce, mapping each with `f`.
+	fn grouped<T, F>(&self, f: F) -> TxPoolContent<T> where
+		F: Fn(&VerifiedTransaction) -> T,
+	{
+		let (pending, queued) = self.pool();
+		let mut content = TxPoolContent::default();
+		for t in pending {
+			content.pending.entry(t.signed().sender().into())
+				.or_insert_with(BTreeMap::new)
+				.insert(t.signed().nonce.low_u64(), f(&t));
+		}
+		for t in queued {
+			content.queued.entry(t.signed().sender().into())
+				.or_insert_with(BTreeMap::new)
+				.insert(t.signed().nonce.low_u64(), f(&t));
+		}
+		content
+	}
+}
+
+/// Summarizes a transaction the way geth's `txpool_inspect` does.
+fn inspect(t: &VerifiedTransaction) -> String {
+	let tx = t.signed();
+	let to = match tx.action {
+		Action::Call(ref to) => format!("0x{:x}", to),
+		Action::Create => "contract creation".into(),
+	};
+	format!("{}: {} wei + {} gas × {} wei", to, tx.value, tx.gas, tx.gas_price)
+}
+
+impl<C, M> TxPool for TxPoolClient<C, M> where
+	C: miner::BlockChainClient + 'static,
+	M: MinerService + 'static,
+{
+	fn content(&self) -> Result<TxPoolContent<Transaction>> {
+		Ok(self.grouped(|t| Transaction::from_pending(t.pending().clone())))
+	}
+
+	fn inspect(&self) -> Result<TxPoolContent<String>> {
+		Ok(self.grouped(inspect))
+	}
+
+	fn status(&self) -> Result<TxPoolStatus> {
+		let (pending, queued) = self.pool();
+		let queue_status = self.miner.queue_status();
+		Ok(TxPoolStatus {
+			pending: (pending.len() as u64).into(),
+			queued: (queued.len() as u64).into(),
+			memory: (queue_status.status.mem_usage as u64).into(),
+			memory_limit: (queue_status.limits.max_mem_usage as u64).into(),
+		})
+	}
+}
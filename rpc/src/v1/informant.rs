@@ -16,6 +16,7 @@
 
 //! RPC Requests Statistics
 
+use std::collections::HashMap;
 use std::fmt;
 use std::sync::Arc;
 use std::sync::atomic::{self, AtomicUsize};
@@ -24,6 +25,7 @@ use futures_cpupool as pool;
 use jsonrpc_core as rpc;
 use order_stat;
 use parking_lot::RwLock;
+use stats::{LatencyHistogram, PrometheusMetrics, PrometheusRegistry};
 
 pub use self::pool::CpuPool;
 
@@ -135,6 +137,7 @@ impl<T: Default + Copy + Ord> StatsCalculator<T> {
 pub struct RpcStats {
 	requests: RwLock<RateCalculator>,
 	roundtrips: RwLock<StatsCalculator<u32>>,
+	method_latencies: RwLock<HashMap<String, LatencyHistogram>>,
 	active_sessions: AtomicUsize,
 }
 
@@ -160,6 +163,21 @@ impl RpcStats {
 		self.roundtrips.write().add(microseconds)
 	}
 
+	/// Add time taken to process a call of given method.
+	pub fn add_method_latency(&self, method: &str, duration: time::Duration) {
+		let mut latencies = self.method_latencies.write();
+		if let Some(histogram) = latencies.get_mut(method) {
+			histogram.observe(duration);
+			return;
+		}
+		latencies.entry(method.to_owned()).or_insert_with(LatencyHistogram::default).observe(duration);
+	}
+
+	/// Returns number of calls of given method.
+	pub fn method_calls(&self, method: &str) -> u64 {
+		self.method_latencies.read().get(method).map_or(0, LatencyHistogram::count)
+	}
+
 	/// Returns number of open sessions
 	pub fn sessions(&self) -> usize {
 		self.active_sessions.load(atomic::Ordering::Relaxed)
@@ -176,6 +194,17 @@ impl RpcStats {
 	}
 }
 
+impl PrometheusMetrics for RpcStats {
+	fn prometheus_metrics(&self, registry: &mut PrometheusRegistry) {
+		registry.register_gauge("rpc_sessions", "Number of open RPC sessions", self.sessions() as i64);
+		registry.register_gauge("rpc_requests_rate", "Number of RPC requests per second", self.requests_rate() as i64);
+		let latencies = self.method_latencies.read();
+		let mut methods: Vec<_> = latencies.iter().map(|(method, histogram)| (method.as_str(), histogram)).collect();
+		methods.sort_by(|a, b| a.0.cmp(b.0));
+		registry.register_histograms("rpc_request_duration_seconds", "Time taken to process RPC calls", "method", methods);
+	}
+}
+
 /// Notifies about RPC activity.
 pub trait ActivityNotifier: Send + Sync + 'static {
 	/// Activity on RPC interface
@@ -221,17 +250,21 @@ impl<M: rpc::Metadata, T: ActivityNotifier> rpc::Middleware<M> for Middleware<T>
 		self.notifier.active();
 		self.stats.count_request();
 
-		let id = match request {
-			rpc::Request::Single(rpc::Call::MethodCall(ref call)) => Some(call.id.clone()),
-			_ => None,
+		let (id, method) = match request {
+			rpc::Request::Single(rpc::Call::MethodCall(ref call)) => (Some(call.id.clone()), Some(call.method.clone())),
+			_ => (None, None),
 		};
 		let stats = self.stats.clone();
 		let future = process(request, meta).map(move |res| {
-			let time = Self::as_micro(start.elapsed());
+			let elapsed = start.elapsed();
+			let time = Self::as_micro(elapsed);
 			if time > 10_000 {
 				debug!(target: "rpc", "[{:?}] Took {}ms", id, time / 1_000);
 			}
 			stats.add_roundtrip(time);
+			if let Some(method) = method {
+				stats.add_method_latency(&method, elapsed);
+			}
 			res
 		});
 
@@ -257,6 +290,8 @@ impl ActivityNotifier for ClientNotifier {
 #[cfg(test)]
 mod tests {
 
+	use std::time::Duration;
+	use stats::{PrometheusMetrics, PrometheusRegistry};
 	use super::{RateCalculator, StatsCalculator, RpcStats};
 
 	#[test]
@@ -314,6 +349,26 @@ mod tests {
 		assert_eq!(stats.approximated_roundtrip(), 125);
 	}
 
+	#[test]
+	fn should_export_method_latencies() {
+		// given
+		let stats = RpcStats::default();
+		stats.add_method_latency("eth_call", Duration::from_millis(3));
+		stats.add_method_latency("eth_call", Duration::from_millis(30));
+		stats.add_method_latency("eth_blockNumber", Duration::from_millis(1));
+
+		// when
+		let mut registry = PrometheusRegistry::new(String::new());
+		stats.prometheus_metrics(&mut registry);
+		let output = registry.output();
+
+		// then
+		assert_eq!(stats.method_calls("eth_call"), 2);
+		assert_eq!(stats.method_calls("eth_getBalance"), 0);
+		assert!(output.contains("rpc_request_duration_seconds_count{method=\"eth_call\"} 2\n"));
+		assert!(output.contains("rpc_request_duration_seconds_count{method=\"eth_blockNumber\"} 1\n"));
+	}
+
 	#[test]
 	fn should_be_sync_and_send() {
 		let stats = RpcStats::default();
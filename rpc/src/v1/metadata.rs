@@ -29,6 +29,8 @@ pub struct Metadata {
 	pub origin: Origin,
 	/// Request PubSub Session
 	pub session: Option<Arc<Session>>,
+	/// Client identifier (remote IP) used for per-client limits, if known.
+	pub client: Option<String>,
 }
 
 impl jsonrpc_core::Metadata for Metadata {}
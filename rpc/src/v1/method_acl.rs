@@ -0,0 +1,219 @@
+// Copyright 2015-2018 Parity Technologies (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Per-method RPC access control lists.
+
+use std::str::FromStr;
+use std::sync::Arc;
+
+use jsonrpc_core as rpc;
+
+use v1::Metadata;
+use v1::helpers::errors;
+
+/// Whether a rule allows or denies matching methods.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Access {
+	/// Calls are processed.
+	Allow,
+	/// Calls are rejected.
+	Deny,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct Rule {
+	/// Method name, a trailing `*` matches any suffix.
+	pattern: String,
+	access: Access,
+}
+
+impl Rule {
+	fn matches(&self, method: &str) -> bool {
+		if self.pattern.ends_with('*') {
+			method.starts_with(&self.pattern[..self.pattern.len() - 1])
+		} else {
+			method == self.pattern
+		}
+	}
+}
+
+/// Ordered list of `method:allow` and `method:deny` rules, the last matching rule wins.
+///
+/// Methods not matched by any rule are allowed, entries without an access suffix are ignored
+/// so the list can be parsed from the same string as the enabled APIs.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct MethodAcl {
+	rules: Vec<Rule>,
+}
+
+impl MethodAcl {
+	/// Returns true if there are no rules.
+	pub fn is_empty(&self) -> bool {
+		self.rules.is_empty()
+	}
+
+	/// Returns true if calls to given method are allowed.
+	pub fn is_allowed(&self, method: &str) -> bool {
+		self.rules.iter().rev()
+			.find(|rule| rule.matches(method))
+			.map_or(true, |rule| rule.access == Access::Allow)
+	}
+}
+
+impl FromStr for MethodAcl {
+	type Err = String;
+
+	fn from_str(s: &str) -> Result<Self, Self::Err> {
+		let mut rules = Vec::new();
+		for entry in s.split(',').map(str::trim) {
+			let (pattern, access) = match entry.find(':') {
+				Some(pos) => (&entry[..pos], &entry[pos + 1..]),
+				None => continue,
+			};
+			let access = match access {
+				"*" | "allow" => Access::Allow,
+				"deny" => Access::Deny,
+				other => return Err(format!("Invalid access {} of {}, expected one of: allow, deny, *.", other, pattern)),
+			};
+			if pattern.is_empty() {
+				return Err(format!("Missing method name in rule: {}", entry));
+			}
+			rules.push(Rule {
+				pattern: pattern.into(),
+				access,
+			});
+		}
+
+		Ok(MethodAcl { rules })
+	}
+}
+
+/// RPC middleware rejecting calls to methods denied by the access control list.
+///
+/// Denied calls of a batch are rejected individually, the rest of the batch is processed.
+#[derive(Clone, Default)]
+pub struct MethodFilter {
+	acl: Arc<MethodAcl>,
+}
+
+impl MethodFilter {
+	/// Creates new middleware enforcing given access control list.
+	pub fn new(acl: MethodAcl) -> Self {
+		MethodFilter {
+			acl: Arc::new(acl),
+		}
+	}
+
+	/// Returns the rejection of the call if it is denied.
+	fn check(&self, call: &rpc::Call) -> Option<Option<rpc::Output>> {
+		match *call {
+			rpc::Call::MethodCall(ref call) if !self.acl.is_allowed(&call.method) => Some(Some(rpc::Output::from(
+				Err(errors::method_not_allowed(&call.method)), call.id.clone(), call.jsonrpc,
+			))),
+			rpc::Call::Notification(ref notification) if !self.acl.is_allowed(&notification.method) => Some(None),
+			_ => None,
+		}
+	}
+}
+
+impl rpc::Middleware<Metadata> for MethodFilter {
+	type Future = rpc::FutureResponse;
+
+	fn on_request<F, X>(&self, request: rpc::Request, meta: Metadata, process: F) -> Self::Future where
+		F: FnOnce(rpc::Request, Metadata) -> X,
+		X: rpc::futures::Future<Item=Option<rpc::Response>, Error=()> + Send + 'static,
+	{
+		use self::rpc::futures::{future, Future};
+
+		if self.acl.is_empty() {
+			return Box::new(process(request, meta));
+		}
+
+		match request {
+			rpc::Request::Single(call) => match self.check(&call) {
+				Some(rejection) => {
+					debug!(target: "rpc", "Rejecting call denied by the access control list.");
+					Box::new(future::ok(rejection.map(rpc::Response::Single)))
+				},
+				None => Box::new(process(rpc::Request::Single(call), meta)),
+			},
+			rpc::Request::Batch(calls) => {
+				let mut rejected = Vec::new();
+				let mut allowed = Vec::with_capacity(calls.len());
+				for call in calls {
+					match self.check(&call) {
+						Some(rejection) => rejected.extend(rejection),
+						None => allowed.push(call),
+					}
+				}
+
+				if allowed.is_empty() {
+					let response = if rejected.is_empty() { None } else { Some(rpc::Response::Batch(rejected)) };
+					return Box::new(future::ok(response));
+				}
+
+				Box::new(process(rpc::Request::Batch(allowed), meta).map(move |response| {
+					let mut outputs = match response {
+						Some(rpc::Response::Batch(outputs)) => outputs,
+						Some(rpc::Response::Single(output)) => vec![output],
+						None => Vec::new(),
+					};
+					outputs.extend(rejected);
+					if outputs.is_empty() { None } else { Some(rpc::Response::Batch(outputs)) }
+				}))
+			},
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use jsonrpc_core::{MetaIoHandler, Value};
+	use v1::Metadata;
+	use super::{MethodAcl, MethodFilter};
+
+	#[test]
+	fn should_apply_last_matching_rule() {
+		let acl: MethodAcl = "eth:*, parity_set*:deny, parity_setChain:allow, -personal".parse().unwrap();
+
+		assert!(acl.is_allowed("eth_call"));
+		assert!(!acl.is_allowed("parity_setMode"));
+		assert!(acl.is_allowed("parity_setChain"));
+		assert!(acl.is_allowed("parity_chain"));
+		assert!("eth_call:maybe".parse::<MethodAcl>().is_err());
+		assert!("web3,eth".parse::<MethodAcl>().unwrap().is_empty());
+	}
+
+	#[test]
+	fn should_reject_denied_calls() {
+		// given
+		let mut io = MetaIoHandler::with_middleware(MethodFilter::new("secret:deny".parse().unwrap()));
+		io.add_method_with_meta("hello", |_, _: Metadata| Ok(Value::String("world".into())));
+		io.add_method_with_meta("secret", |_, _: Metadata| Ok(Value::String("42".into())));
+
+		// when
+		let single = io.handle_request_sync(r#"{"jsonrpc": "2.0", "method": "secret", "params": [], "id": 1}"#, Metadata::default());
+		let batch = io.handle_request_sync(r#"[
+			{"jsonrpc": "2.0", "method": "secret", "params": [], "id": 1},
+			{"jsonrpc": "2.0", "method": "hello", "params": [], "id": 2}
+		]"#, Metadata::default());
+
+		// then
+		let denied = r#"{"jsonrpc":"2.0","error":{"code":-32044,"message":"Method not allowed.","data":"Calls to secret are denied by the node's access control list."},"id":1}"#;
+		assert_eq!(single, Some(denied.into()));
+		assert_eq!(batch, Some(format!(r#"[{{"jsonrpc":"2.0","result":"world","id":2}},{}]"#, denied)));
+	}
+}
@@ -36,14 +36,18 @@ mod types;
 #[cfg(test)]
 mod tests;
 
+pub mod batch;
 pub mod extractors;
 pub mod informant;
 pub mod metadata;
+pub mod method_acl;
+pub mod rate_limit;
+pub mod response_limit;
 pub mod traits;
 
-pub use self::traits::{Debug, Eth, EthFilter, EthPubSub, EthSigning, Net, Parity, ParityAccounts, ParitySet, ParitySigning, Personal, PubSub, Private, Rpc, SecretStore, Signer, Traces, Web3};
+pub use self::traits::{Admin, Debug, Engine, Eth, EthCallOverride, EthFilter, EthPubSub, EthSigning, Net, Parity, ParityAccounts, ParitySet, ParitySigning, Personal, PubSub, Private, Rpc, SecretStore, Signer, Traces, TxPool, Web3};
 pub use self::impls::*;
-pub use self::helpers::{NetworkSettings, block_import, dispatch};
+pub use self::helpers::{NetworkSettings, SubscriptionQueueConfig, OverflowPolicy, GasPriceOracle, GasPriceOracleOptions, block_import, dispatch};
 pub use self::metadata::Metadata;
 pub use self::types::Origin;
 pub use self::extractors::{RpcExtractor, WsExtractor, WsStats, WsDispatcher};
@@ -0,0 +1,280 @@
+// Copyright 2015-2018 Parity Technologies (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Per-client RPC request limits.
+
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use jsonrpc_core as rpc;
+use parking_lot::Mutex;
+
+use v1::Metadata;
+use v1::helpers::errors;
+
+/// Number of tracked clients above which idle ones are forgotten.
+const MAX_IDLE_CLIENTS: usize = 1024;
+
+/// Limits applied to every client (remote IP) of an RPC server.
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub struct RateLimits {
+	/// Maximal number of requests per second.
+	pub requests_per_second: Option<u32>,
+	/// Maximal number of requests processed at the same time.
+	pub concurrent_requests: Option<usize>,
+}
+
+impl RateLimits {
+	/// Returns true if no limits are set.
+	pub fn is_unlimited(&self) -> bool {
+		self.requests_per_second.is_none() && self.concurrent_requests.is_none()
+	}
+}
+
+#[derive(Debug)]
+struct Client {
+	window_start: Instant,
+	requests: u32,
+	active: usize,
+}
+
+impl Client {
+	fn new(now: Instant) -> Self {
+		Client {
+			window_start: now,
+			requests: 0,
+			active: 0,
+		}
+	}
+
+	fn is_idle(&self, now: Instant) -> bool {
+		self.active == 0 && now.duration_since(self.window_start) >= Duration::from_secs(1)
+	}
+}
+
+#[derive(Debug, Default)]
+struct Clients {
+	limits: RateLimits,
+	clients: Mutex<HashMap<String, Client>>,
+}
+
+impl Clients {
+	fn acquire(&self, key: &str, now: Instant) -> Result<(), String> {
+		let mut clients = self.clients.lock();
+		if clients.len() > MAX_IDLE_CLIENTS {
+			clients.retain(|_, client| !client.is_idle(now));
+		}
+
+		let client = clients.entry(key.to_owned()).or_insert_with(|| Client::new(now));
+		if now.duration_since(client.window_start) >= Duration::from_secs(1) {
+			client.window_start = now;
+			client.requests = 0;
+		}
+
+		if let Some(limit) = self.limits.requests_per_second {
+			if client.requests >= limit {
+				return Err(format!("Limit of {} requests per second exceeded.", limit));
+			}
+		}
+		if let Some(limit) = self.limits.concurrent_requests {
+			if client.active >= limit {
+				return Err(format!("Limit of {} concurrent requests exceeded.", limit));
+			}
+		}
+
+		client.requests += 1;
+		client.active += 1;
+		Ok(())
+	}
+
+	fn release(&self, key: &str) {
+		if let Some(client) = self.clients.lock().get_mut(key) {
+			client.active = client.active.saturating_sub(1);
+		}
+	}
+}
+
+/// Releases the concurrency slot of a client once the request is done.
+struct Slot {
+	clients: Arc<Clients>,
+	key: String,
+}
+
+impl Drop for Slot {
+	fn drop(&mut self) {
+		self.clients.release(&self.key);
+	}
+}
+
+/// RPC middleware rejecting requests of clients exceeding configured limits.
+///
+/// Clients are identified by `Metadata::client`, requests without a known client are not limited.
+/// A batch counts as a single request.
+#[derive(Clone, Default)]
+pub struct RateLimiter {
+	clients: Arc<Clients>,
+}
+
+impl RateLimiter {
+	/// Creates new rate limiter with given limits.
+	pub fn new(limits: RateLimits) -> Self {
+		RateLimiter {
+			clients: Arc::new(Clients {
+				limits,
+				clients: Default::default(),
+			}),
+		}
+	}
+
+	fn rejection(request: &rpc::Request, details: String) -> Option<rpc::Response> {
+		let failure = |call: &rpc::Call| match *call {
+			rpc::Call::MethodCall(ref call) => Some(rpc::Output::from(
+				Err(errors::rate_limited(details.clone())), call.id.clone(), call.jsonrpc,
+			)),
+			rpc::Call::Invalid(ref id) => Some(rpc::Output::from(
+				Err(errors::rate_limited(details.clone())), id.clone(), Some(rpc::Version::V2),
+			)),
+			rpc::Call::Notification(_) => None,
+		};
+
+		match *request {
+			rpc::Request::Single(ref call) => failure(call).map(rpc::Response::Single),
+			rpc::Request::Batch(ref calls) => {
+				let outputs: Vec<_> = calls.iter().filter_map(failure).collect();
+				if outputs.is_empty() {
+					None
+				} else {
+					Some(rpc::Response::Batch(outputs))
+				}
+			},
+		}
+	}
+}
+
+impl rpc::Middleware<Metadata> for RateLimiter {
+	type Future = rpc::FutureResponse;
+
+	fn on_request<F, X>(&self, request: rpc::Request, meta: Metadata, process: F) -> Self::Future where
+		F: FnOnce(rpc::Request, Metadata) -> X,
+		X: rpc::futures::Future<Item=Option<rpc::Response>, Error=()> + Send + 'static,
+	{
+		use self::rpc::futures::{future, Future};
+
+		if self.clients.limits.is_unlimited() {
+			return Box::new(process(request, meta));
+		}
+
+		let key = match meta.client.clone() {
+			Some(client) => client,
+			None => return Box::new(process(request, meta)),
+		};
+
+		if let Err(details) = self.clients.acquire(&key, Instant::now()) {
+			debug!(target: "rpc", "Rejecting request from {}: {}", key, details);
+			return Box::new(future::ok(Self::rejection(&request, details)));
+		}
+
+		let slot = Slot {
+			clients: self.clients.clone(),
+			key,
+		};
+		Box::new(process(request, meta).then(move |res| {
+			drop(slot);
+			res
+		}))
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use std::time::{Duration, Instant};
+	use jsonrpc_core::{MetaIoHandler, Value};
+	use v1::Metadata;
+	use super::{Clients, RateLimiter, RateLimits};
+
+	fn clients(limits: RateLimits) -> Clients {
+		Clients {
+			limits,
+			clients: Default::default(),
+		}
+	}
+
+	#[test]
+	fn should_limit_requests_per_second() {
+		// given
+		let clients = clients(RateLimits { requests_per_second: Some(2), concurrent_requests: None });
+		let now = Instant::now();
+
+		// when
+		let first = clients.acquire("a", now);
+		clients.release("a");
+		let second = clients.acquire("a", now);
+		clients.release("a");
+		let third = clients.acquire("a", now);
+		let other = clients.acquire("b", now);
+		let next_second = clients.acquire("a", now + Duration::from_secs(1));
+
+		// then
+		assert!(first.is_ok());
+		assert!(second.is_ok());
+		assert!(third.is_err());
+		assert!(other.is_ok());
+		assert!(next_second.is_ok());
+	}
+
+	#[test]
+	fn should_limit_concurrent_requests() {
+		// given
+		let clients = clients(RateLimits { requests_per_second: None, concurrent_requests: Some(1) });
+		let now = Instant::now();
+
+		// when
+		let first = clients.acquire("a", now);
+		let second = clients.acquire("a", now);
+		clients.release("a");
+		let third = clients.acquire("a", now);
+
+		// then
+		assert!(first.is_ok());
+		assert!(second.is_err());
+		assert!(third.is_ok());
+	}
+
+	#[test]
+	fn should_reject_with_rate_limited_error() {
+		// given
+		let mut io = MetaIoHandler::with_middleware(RateLimiter::new(RateLimits {
+			requests_per_second: Some(1),
+			concurrent_requests: None,
+		}));
+		io.add_method_with_meta("hello", |_, _: Metadata| Ok(Value::String("world".into())));
+		let mut meta = Metadata::default();
+		meta.client = Some("127.0.0.1".into());
+		let request = r#"{"jsonrpc": "2.0", "method": "hello", "params": [], "id": 1}"#;
+
+		// when
+		let first = io.handle_request_sync(request, meta.clone());
+		let second = io.handle_request_sync(request, meta.clone());
+		let unknown_client = io.handle_request_sync(request, Metadata::default());
+
+		// then
+		let ok = r#"{"jsonrpc":"2.0","result":"world","id":1}"#;
+		assert_eq!(first, Some(ok.into()));
+		assert_eq!(second, Some(r#"{"jsonrpc":"2.0","error":{"code":-32043,"message":"Too many requests.","data":"Limit of 1 requests per second exceeded."},"id":1}"#.into()));
+		assert_eq!(unknown_client, Some(ok.into()));
+	}
+}
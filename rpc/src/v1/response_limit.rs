@@ -0,0 +1,246 @@
+// Copyright 2015-2018 Parity Technologies (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Limit of the size of RPC results.
+//!
+//! A result which is encoded to more bytes than the limit is replaced with the first chunk of its
+//! JSON encoding: `{"data": "...", "cursor": "0x..."}`. Following chunks are returned by
+//! `parity_responseChunk(cursor)` until the cursor is `null`. Concatenated `data` of all chunks
+//! is the JSON encoding of the original result.
+
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use jsonrpc_core as rpc;
+use parking_lot::Mutex;
+use rand::{self, Rng};
+use serde_json;
+
+use v1::Metadata;
+use v1::helpers::errors;
+
+/// Method returning the next chunk of a partial result.
+pub const RESPONSE_CHUNK_METHOD: &str = "parity_responseChunk";
+/// Maximal number of partial results waiting to be fetched, the oldest are dropped first.
+const MAX_PENDING_RESULTS: usize = 64;
+/// Number of seconds after which chunks which were not fetched are dropped.
+const PENDING_RESULT_TIMEOUT_SECS: u64 = 60;
+
+#[derive(Debug, Serialize)]
+struct Chunk {
+	data: String,
+	cursor: Option<String>,
+}
+
+struct Pending {
+	remaining: String,
+	created: Instant,
+}
+
+/// Returns the length of the longest prefix of `data` which fits into `max_size` bytes
+/// once encoded as a JSON string. The prefix is never empty unless `data` is.
+fn chunk_len(data: &str, max_size: usize) -> usize {
+	// surrounding quotes
+	let mut size = 2;
+	for (index, c) in data.char_indices() {
+		size += match c {
+			'"' | '\\' => 2,
+			c if (c as u32) < 0x20 => 6,
+			c => c.len_utf8(),
+		};
+		if size > max_size && index > 0 {
+			return index;
+		}
+	}
+	data.len()
+}
+
+fn is_chunk_call(call: &rpc::Call) -> bool {
+	match *call {
+		rpc::Call::MethodCall(ref call) => call.method == RESPONSE_CHUNK_METHOD,
+		_ => false,
+	}
+}
+
+/// RPC middleware splitting results larger than the limit into chunks.
+///
+/// The limit applies to every result of a batch separately and doesn't include the JSON-RPC envelope.
+#[derive(Clone, Default)]
+pub struct ResponseLimit {
+	max_size: Option<usize>,
+	pending: Arc<Mutex<HashMap<String, Pending>>>,
+}
+
+impl ResponseLimit {
+	/// Creates new middleware limiting results to `max_size` bytes, `None` disables the limit.
+	pub fn new(max_size: Option<usize>) -> Self {
+		ResponseLimit {
+			max_size,
+			pending: Default::default(),
+		}
+	}
+
+	/// Splits off the first chunk of `data` and stores the rest under a new cursor.
+	fn chunk(&self, max_size: usize, mut data: String, now: Instant) -> Chunk {
+		let len = chunk_len(&data, max_size);
+		let remaining = data.split_off(len);
+		if remaining.is_empty() {
+			return Chunk { data, cursor: None };
+		}
+
+		let mut rng = rand::thread_rng();
+		let cursor = format!("0x{:016x}{:016x}", rng.gen::<u64>(), rng.gen::<u64>());
+		let timeout = Duration::from_secs(PENDING_RESULT_TIMEOUT_SECS);
+		let mut pending = self.pending.lock();
+		pending.retain(|_, pending| now.duration_since(pending.created) < timeout);
+		if pending.len() >= MAX_PENDING_RESULTS {
+			let oldest = pending.iter().min_by_key(|&(_, pending)| pending.created).map(|(cursor, _)| cursor.clone());
+			if let Some(oldest) = oldest {
+				debug!(target: "rpc", "Too many partial results, dropping the oldest.");
+				pending.remove(&oldest);
+			}
+		}
+		pending.insert(cursor.clone(), Pending { remaining, created: now });
+
+		Chunk { data, cursor: Some(cursor) }
+	}
+
+	fn next_chunk(&self, max_size: usize, call: &rpc::MethodCall) -> rpc::Output {
+		let now = Instant::now();
+		let timeout = Duration::from_secs(PENDING_RESULT_TIMEOUT_SECS);
+		let result = call.params.clone().unwrap_or(rpc::Params::None).parse::<(String,)>()
+			.and_then(|(cursor,)| self.pending.lock().remove(&cursor)
+				.and_then(|pending| if now.duration_since(pending.created) < timeout { Some(pending) } else { None })
+				.ok_or_else(|| errors::response_cursor_not_found(&cursor))
+			)
+			.map(|pending| {
+				let chunk = self.chunk(max_size, pending.remaining, now);
+				serde_json::to_value(chunk).expect("Chunk serialization is infallible; qed")
+			});
+		rpc::Output::from(result, call.id.clone(), call.jsonrpc)
+	}
+
+	fn limit(&self, max_size: usize, output: rpc::Output) -> rpc::Output {
+		match output {
+			rpc::Output::Success(mut success) => {
+				let data = serde_json::to_string(&success.result).expect("Value serialization is infallible; qed");
+				if data.len() > max_size {
+					trace!(target: "rpc", "Splitting result of {} bytes into chunks.", data.len());
+					let chunk = self.chunk(max_size, data, Instant::now());
+					success.result = serde_json::to_value(chunk).expect("Chunk serialization is infallible; qed");
+				}
+				rpc::Output::Success(success)
+			},
+			failure => failure,
+		}
+	}
+}
+
+impl rpc::Middleware<Metadata> for ResponseLimit {
+	type Future = rpc::FutureResponse;
+
+	fn on_request<F, X>(&self, request: rpc::Request, meta: Metadata, process: F) -> Self::Future where
+		F: FnOnce(rpc::Request, Metadata) -> X,
+		X: rpc::futures::Future<Item=Option<rpc::Response>, Error=()> + Send + 'static,
+	{
+		use self::rpc::futures::{future, Future};
+
+		let max_size = match self.max_size {
+			Some(max_size) => max_size,
+			None => return Box::new(process(request, meta)),
+		};
+		let limit = self.clone();
+
+		match request {
+			rpc::Request::Single(rpc::Call::MethodCall(ref call)) if call.method == RESPONSE_CHUNK_METHOD => {
+				Box::new(future::ok(Some(rpc::Response::Single(self.next_chunk(max_size, call)))))
+			},
+			rpc::Request::Batch(calls) => {
+				let (chunk_calls, calls): (Vec<_>, Vec<_>) = calls.into_iter().partition(is_chunk_call);
+				let chunks: Vec<_> = chunk_calls.iter().filter_map(|call| match *call {
+					rpc::Call::MethodCall(ref call) => Some(self.next_chunk(max_size, call)),
+					_ => None,
+				}).collect();
+
+				if calls.is_empty() {
+					return Box::new(future::ok(Some(rpc::Response::Batch(chunks))));
+				}
+
+				Box::new(process(rpc::Request::Batch(calls), meta).map(move |response| {
+					let outputs = match response {
+						Some(rpc::Response::Batch(outputs)) => outputs,
+						Some(rpc::Response::Single(output)) => vec![output],
+						None => Vec::new(),
+					};
+					let mut outputs: Vec<_> = outputs.into_iter().map(|output| limit.limit(max_size, output)).collect();
+					outputs.extend(chunks);
+					if outputs.is_empty() { None } else { Some(rpc::Response::Batch(outputs)) }
+				}))
+			},
+			request => Box::new(process(request, meta).map(move |response| response.map(|response| match response {
+				rpc::Response::Single(output) => rpc::Response::Single(limit.limit(max_size, output)),
+				rpc::Response::Batch(outputs) => rpc::Response::Batch(
+					outputs.into_iter().map(|output| limit.limit(max_size, output)).collect()
+				),
+			}))),
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use jsonrpc_core::{MetaIoHandler, Value};
+	use serde_json;
+	use v1::Metadata;
+	use super::{chunk_len, ResponseLimit};
+
+	#[test]
+	fn should_count_escaped_characters() {
+		assert_eq!(chunk_len("abcdef", 6), 4);
+		assert_eq!(chunk_len(r#""a"b"#, 6), 2);
+		assert_eq!(chunk_len("abc", 1), 1);
+		assert_eq!(chunk_len("", 1), 0);
+	}
+
+	#[test]
+	fn should_return_large_results_in_chunks() {
+		// given
+		let mut io = MetaIoHandler::with_middleware(ResponseLimit::new(Some(32)));
+		io.add_method_with_meta("small", |_, _: Metadata| Ok(Value::String("world".into())));
+		io.add_method_with_meta("large", |_, _: Metadata| Ok(Value::Array((0..20).map(Value::from).collect())));
+
+		// when
+		let small = io.handle_request_sync(r#"{"jsonrpc": "2.0", "method": "small", "params": [], "id": 1}"#, Metadata::default());
+		let mut request = r#"{"jsonrpc": "2.0", "method": "large", "params": [], "id": 1}"#.to_owned();
+		let mut data = String::new();
+		loop {
+			let response: Value = serde_json::from_str(&io.handle_request_sync(&request, Metadata::default()).unwrap()).unwrap();
+			let chunk = &response["result"];
+			data.push_str(chunk["data"].as_str().unwrap());
+			match chunk["cursor"].as_str() {
+				Some(cursor) => request = format!(r#"{{"jsonrpc": "2.0", "method": "parity_responseChunk", "params": ["{}"], "id": 1}}"#, cursor),
+				None => break,
+			}
+		}
+		let reused = io.handle_request_sync(&request, Metadata::default()).unwrap();
+
+		// then
+		assert_eq!(small, Some(r#"{"jsonrpc":"2.0","result":"world","id":1}"#.into()));
+		assert_eq!(data, "[0,1,2,3,4,5,6,7,8,9,10,11,12,13,14,15,16,17,18,19]");
+		assert!(reused.contains(r#""code":-32042"#));
+	}
+}
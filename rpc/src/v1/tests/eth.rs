@@ -22,6 +22,7 @@ use ethereum_types::{H256, Address};
 use ethcore::account_provider::AccountProvider;
 use ethcore::client::{BlockChainClient, Client, ClientConfig, ChainInfo, ImportBlock};
 use ethcore::ethereum;
+use ethcore::header::BlockNumber;
 use ethcore::ids::BlockId;
 use ethcore::miner::Miner;
 use ethcore::spec::{Genesis, Spec};
@@ -85,7 +86,7 @@ impl EthTester {
 		let tester = Self::from_spec(make_spec(chain));
 
 		for b in chain.blocks_rlp() {
-			if let Ok(block) = Unverified::from_rlp(b) {
+			if let Ok(block) = Unverified::from_rlp(b, BlockNumber::max_value()) {
 				let _ = tester.client.import_block(block);
 				tester.client.flush_queue();
 				tester.client.import_verified_blocks();
@@ -178,6 +179,30 @@ fn eth_get_balance() {
 	assert_eq!(tester.handler.handle_request_sync(req_new_acc).unwrap(), res_new_acc);
 }
 
+#[test]
+fn eth_get_proof() {
+	let chain = extract_chain!("BlockchainTests/bcWalletTest/wallet2outOf3txs");
+	let tester = EthTester::from_chain(&chain);
+
+	let req_latest = r#"{
+		"jsonrpc": "2.0",
+		"method": "eth_getProof",
+		"params": ["0xaaaf5374fce5edbc8e2a8697c15331677e6ebaaa", ["0x0000000000000000000000000000000000000000000000000000000000000000"], "latest"],
+		"id": 1
+	}"#;
+	let res_latest = tester.handler.handle_request_sync(req_latest).unwrap();
+	assert!(res_latest.contains(r#""address":"0xaaaf5374fce5edbc8e2a8697c15331677e6ebaaa","balance":"0x9""#));
+	assert!(res_latest.contains(r#""storageProof":[{"key":"0x0","value":"0x0","proof":["#));
+
+	let req_pending = r#"{
+		"jsonrpc": "2.0",
+		"method": "eth_getProof",
+		"params": ["0xaaaf5374fce5edbc8e2a8697c15331677e6ebaaa", [], "pending"],
+		"id": 2
+	}"#;
+	assert!(tester.handler.handle_request_sync(req_pending).unwrap().contains("error"));
+}
+
 #[test]
 fn eth_block_number() {
 	let chain = extract_chain!("BlockchainTests/bcGasPricerTest/RPC_API_Test");
@@ -422,7 +447,7 @@ fn verify_transaction_counts(name: String, chain: BlockChain) {
 	let tester = EthTester::from_chain(&chain);
 
 	let mut id = 1;
-	for b in chain.blocks_rlp().into_iter().filter_map(|b| Unverified::from_rlp(b).ok()) {
+	for b in chain.blocks_rlp().into_iter().filter_map(|b| Unverified::from_rlp(b, BlockNumber::max_value()).ok()) {
 		let count = b.transactions.len();
 
 		let hash = b.header.hash();
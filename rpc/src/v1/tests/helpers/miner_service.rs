@@ -21,7 +21,7 @@ use std::collections::{BTreeMap, BTreeSet, HashMap};
 
 use bytes::Bytes;
 use ethcore::account_provider::SignError as AccountError;
-use ethcore::block::{Block, SealedBlock, IsBlock};
+use ethcore::block::{Block, ClosedBlock, SealedBlock, IsBlock};
 use ethcore::client::{Nonce, PrepareOpenBlock, StateClient, EngineInfo};
 use ethcore::engines::EthEngine;
 use ethcore::error::Error;
@@ -31,7 +31,7 @@ use ethcore::miner::{self, MinerService, AuthoringParams};
 use ethcore::receipt::RichReceipt;
 use ethereum_types::{H256, U256, Address};
 use miner::pool::local_transactions::Status as LocalTransactionStatus;
-use miner::pool::{verifier, VerifiedTransaction, QueueStatus};
+use miner::pool::{verifier, VerifiedTransaction, QueueStatus, Quotas};
 use parking_lot::{RwLock, Mutex};
 use transaction::{self, UnverifiedTransaction, SignedTransaction, PendingTransaction};
 use txpool;
@@ -51,6 +51,10 @@ pub struct TestMinerService {
 	pub next_nonces: RwLock<HashMap<Address, U256>>,
 	/// Password held by Engine.
 	pub password: RwLock<Password>,
+	/// Gas price bump required to replace a transaction, in basis points.
+	pub replacement_bump: RwLock<usize>,
+	/// Runtime quotas of the transaction queue.
+	pub queue_quotas: RwLock<Quotas>,
 
 	authoring_params: RwLock<AuthoringParams>,
 }
@@ -64,6 +68,8 @@ impl Default for TestMinerService {
 			pending_receipts: Default::default(),
 			next_nonces: Default::default(),
 			password: RwLock::new("".into()),
+			replacement_bump: RwLock::new(0),
+			queue_quotas: Default::default(),
 			authoring_params: RwLock::new(AuthoringParams {
 				author: Address::zero(),
 				gas_range_target: (12345.into(), 54321.into()),
@@ -196,6 +202,12 @@ impl MinerService for TestMinerService {
 		Some((header.hash(), header.number(), header.timestamp(), *header.difficulty()))
 	}
 
+	fn block_template<C: PrepareOpenBlock>(&self, chain: &C) -> Option<ClosedBlock> {
+		let params = self.authoring_params();
+		let open_block = chain.prepare_open_block(params.author, params.gas_range_target, params.extra_data).unwrap();
+		Some(open_block.close().unwrap())
+	}
+
 	fn transaction(&self, hash: &H256) -> Option<Arc<VerifiedTransaction>> {
 		self.pending_transactions.lock().get(hash).cloned().map(|tx| {
 			Arc::new(VerifiedTransaction::from_pending_block_transaction(tx))
@@ -212,10 +224,18 @@ impl MinerService for TestMinerService {
 		Some(self.pending_transactions.lock().values().cloned().collect())
 	}
 
+	fn pending_block_candidates(&self, _best_block: BlockNumber) -> Option<Vec<Arc<VerifiedTransaction>>> {
+		Some(self.queued_transactions())
+	}
+
 	fn local_transactions(&self) -> BTreeMap<H256, LocalTransactionStatus> {
 		self.local_transactions.lock().iter().map(|(hash, stats)| (*hash, stats.clone())).collect()
 	}
 
+	fn transactions_to_rebroadcast(&self) -> Vec<H256> {
+		Vec::new()
+	}
+
 	fn ready_transactions<C>(&self, _chain: &C, _max_len: usize, _ordering: miner::PendingOrdering) -> Vec<Arc<VerifiedTransaction>> {
 		self.queued_transactions()
 	}
@@ -263,6 +283,18 @@ impl MinerService for TestMinerService {
 		}
 	}
 
+	fn set_replacement_bump(&self, bump: usize) {
+		*self.replacement_bump.write() = bump;
+	}
+
+	fn queue_quotas(&self) -> Quotas {
+		*self.queue_quotas.read()
+	}
+
+	fn set_queue_quotas(&self, quotas: Quotas) {
+		*self.queue_quotas.write() = quotas;
+	}
+
 	/// Submit `seal` as a valid solution for the header of `pow_hash`.
 	/// Will check the seal, but not actually insert the block into the chain.
 	fn submit_seal(&self, _pow_hash: H256, _seal: Vec<Bytes>) -> Result<SealedBlock, Error> {
@@ -16,10 +16,10 @@
 
 //! Test implementation of SyncProvider.
 
-use std::collections::BTreeMap;
+use std::collections::{BTreeMap, HashMap};
 use ethereum_types::H256;
 use parking_lot::RwLock;
-use sync::{SyncProvider, EthProtocolInfo, SyncStatus, SyncState, PeerInfo, TransactionStats};
+use sync::{SyncProvider, EthProtocolInfo, SyncStatus, SyncState, PeerInfo, TransactionStats, BlockPropagationStats, BlockAnnouncementStats, Traffic};
 
 /// TestSyncProvider config.
 pub struct Config {
@@ -85,6 +85,9 @@ impl SyncProvider for TestSyncProvider {
 					head: 50.into(),
 				}),
 				pip_info: None,
+				traffic: hash_map![
+					*b"eth" => Traffic { bytes_in: 512, bytes_out: 256 }
+				],
 			},
 			PeerInfo {
 				id: None,
@@ -98,6 +101,7 @@ impl SyncProvider for TestSyncProvider {
 					head: 60.into()
 				}),
 				pip_info: None,
+				traffic: HashMap::new(),
 			}
 		]
 	}
@@ -122,4 +126,27 @@ impl SyncProvider for TestSyncProvider {
 			}
 		]
 	}
+
+	fn blocks_stats(&self) -> BTreeMap<H256, BlockPropagationStats> {
+		map![
+			3.into() => BlockPropagationStats {
+				number: 7,
+				first_seen: 1_500_000_000_000,
+				import_delay: Some(120),
+				announced_by: map![
+					128.into() => 0,
+					16.into() => 35
+				],
+			}
+		]
+	}
+
+	fn announcement_stats(&self) -> BlockAnnouncementStats {
+		BlockAnnouncementStats {
+			received: 10,
+			duplicates_received: 6,
+			sent: 4,
+			duplicates_suppressed: 2,
+		}
+	}
 }
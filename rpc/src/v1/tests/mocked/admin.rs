@@ -0,0 +1,67 @@
+// Copyright 2015-2018 Parity Technologies (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+use std::sync::Arc;
+
+use sync::{ManageNetwork, SyncProvider};
+use jsonrpc_core::IoHandler;
+use v1::{Admin, AdminClient};
+use v1::helpers::NetworkSettings;
+use v1::tests::helpers::{Config, TestSyncProvider};
+use super::manage_network::TestManageNetwork;
+
+fn io() -> IoHandler {
+	let sync = Arc::new(TestSyncProvider::new(Config {
+		network_id: 3,
+		num_peers: 2,
+	}));
+	let net = Arc::new(TestManageNetwork);
+	let settings = Arc::new(NetworkSettings::default());
+
+	let mut io = IoHandler::new();
+	io.extend_with(AdminClient::new(sync as Arc<SyncProvider>, net as Arc<ManageNetwork>, settings).to_delegate());
+	io
+}
+
+#[test]
+fn rpc_admin_add_and_remove_peer() {
+	let io = io();
+
+	let request = r#"{"jsonrpc": "2.0", "method": "admin_addPeer", "params":["enode://a979fb575495b8d6db44f750317d0f4622bf4c2aa3365d6af7c284339968eef29b69ad0dce72a4d8db5ebb4968de0e3bec910127f134779fbcb0cb6d3331163c@22.99.55.44:7770"], "id": 1}"#;
+	let response = r#"{"jsonrpc":"2.0","result":true,"id":1}"#;
+	assert_eq!(io.handle_request_sync(request), Some(response.to_owned()));
+
+	let request = r#"{"jsonrpc": "2.0", "method": "admin_removePeer", "params":["enode://a979fb575495b8d6db44f750317d0f4622bf4c2aa3365d6af7c284339968eef29b69ad0dce72a4d8db5ebb4968de0e3bec910127f134779fbcb0cb6d3331163c@22.99.55.44:7770"], "id": 1}"#;
+	assert_eq!(io.handle_request_sync(request), Some(response.to_owned()));
+}
+
+#[test]
+fn rpc_admin_peers() {
+	let io = io();
+
+	let request = r#"{"jsonrpc": "2.0", "method": "admin_peers", "params":[], "id": 1}"#;
+	let response = r#"{"jsonrpc":"2.0","result":[{"caps":["eth/62","eth/63"],"id":"node1","name":"Parity-Ethereum/1","network":{"localAddress":"127.0.0.1:8888","remoteAddress":"127.0.0.1:7777"},"protocols":{"eth":{"difficulty":"0x28","head":"0000000000000000000000000000000000000000000000000000000000000032","version":62},"pip":null}},{"caps":["eth/63","eth/64"],"id":null,"name":"Parity-Ethereum/2","network":{"localAddress":"127.0.0.1:3333","remoteAddress":"Handshake"},"protocols":{"eth":{"difficulty":null,"head":"000000000000000000000000000000000000000000000000000000000000003c","version":64},"pip":null}}],"id":1}"#;
+	assert_eq!(io.handle_request_sync(request), Some(response.to_owned()));
+}
+
+#[test]
+fn rpc_admin_node_info_without_network() {
+	let io = io();
+
+	let request = r#"{"jsonrpc": "2.0", "method": "admin_nodeInfo", "params":[], "id": 1}"#;
+	let response = r#"{"jsonrpc":"2.0","error":{"code":-32000,"message":"Network is disabled or not yet up."},"id":1}"#;
+	assert_eq!(io.handle_request_sync(request), Some(response.to_owned()));
+}
@@ -16,13 +16,36 @@
 
 use std::sync::Arc;
 
-use ethcore::client::TestBlockChainClient;
+use ethcore::client::{Executed, TestBlockChainClient};
+use ethcore::trace::StructLog;
 
 use jsonrpc_core::IoHandler;
 use v1::{Debug, DebugClient};
 
 fn io() -> IoHandler {
 	let client = Arc::new(TestBlockChainClient::new());
+	client.set_struct_logs_result(Ok(Executed {
+		exception: None,
+		gas: 20_000.into(),
+		gas_used: 21_005.into(),
+		refunded: 0.into(),
+		cumulative_gas_used: 21_005.into(),
+		logs: vec![],
+		contracts_created: vec![],
+		output: vec![1, 2, 3],
+		trace: vec![],
+		vm_trace: Some(vec![StructLog {
+			pc: 0,
+			instruction: 0x60,
+			gas: 0x10.into(),
+			gas_cost: 3.into(),
+			depth: 1,
+			stack: Some(vec![]),
+			memory: Some(vec![]),
+			storage: Some(vec![(0.into(), 1.into())].into_iter().collect()),
+		}]),
+		state_diff: None,
+	}));
 
 	let mut io = IoHandler::new();
 	io.extend_with(DebugClient::new(client).to_delegate());
@@ -35,3 +58,56 @@ fn rpc_debug_get_bad_blocks() {
 	let response = "{\"jsonrpc\":\"2.0\",\"result\":[{\"author\":\"0x0000000000000000000000000000000000000000\",\"difficulty\":\"0x0\",\"extraData\":\"0x\",\"gasLimit\":\"0x0\",\"gasUsed\":\"0x0\",\"hash\":\"0x27bfb37e507ce90da141307204b1c6ba24194380613590ac50ca4b1d7198ff65\",\"logsBloom\":\"0x00000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000\",\"miner\":\"0x0000000000000000000000000000000000000000\",\"number\":\"0x0\",\"parentHash\":\"0x0000000000000000000000000000000000000000000000000000000000000000\",\"reason\":\"Invalid block\",\"receiptsRoot\":\"0x56e81f171bcc55a6ff8345e692c0f86e5b48e01b996cadc001622fb5e363b421\",\"rlp\":\"\\\"0x010203\\\"\",\"sealFields\":[],\"sha3Uncles\":\"0x1dcc4de8dec75d7aab85b567b6ccd41ad312451b948a7413f0a142fd40d49347\",\"size\":\"0x3\",\"stateRoot\":\"0x56e81f171bcc55a6ff8345e692c0f86e5b48e01b996cadc001622fb5e363b421\",\"timestamp\":\"0x0\",\"totalDifficulty\":null,\"transactions\":[],\"transactionsRoot\":\"0x56e81f171bcc55a6ff8345e692c0f86e5b48e01b996cadc001622fb5e363b421\",\"uncles\":[]}],\"id\":1}";
 	assert_eq!(io().handle_request_sync(request), Some(response.to_owned()));
 }
+
+#[test]
+fn rpc_debug_trace_transaction() {
+	let request = r#"{"jsonrpc": "2.0", "method": "debug_traceTransaction", "params": ["0x0000000000000000000000000000000000000000000000000000000000000005"], "id": 1}"#;
+	let response = r#"{"jsonrpc":"2.0","result":{"failed":false,"gas":21005,"returnValue":"010203","structLogs":[{"depth":1,"gas":16,"gasCost":3,"memory":[],"op":"PUSH1","pc":0,"stack":[],"storage":{"0000000000000000000000000000000000000000000000000000000000000000":"0000000000000000000000000000000000000000000000000000000000000001"}}]},"id":1}"#;
+	assert_eq!(io().handle_request_sync(request), Some(response.to_owned()));
+}
+
+#[test]
+fn rpc_debug_trace_call() {
+	let request = r#"{"jsonrpc": "2.0", "method": "debug_traceCall", "params": [{}, "latest", {"disableStack": true, "disableMemory": true, "disableStorage": true}], "id": 1}"#;
+	let response = r#"{"jsonrpc":"2.0","result":{"failed":false,"gas":21005,"returnValue":"010203","structLogs":[{"depth":1,"gas":16,"gasCost":3,"memory":[],"op":"PUSH1","pc":0,"stack":[],"storage":{"0000000000000000000000000000000000000000000000000000000000000000":"0000000000000000000000000000000000000000000000000000000000000001"}}]},"id":1}"#;
+	assert_eq!(io().handle_request_sync(request), Some(response.to_owned()));
+}
+
+#[test]
+fn rpc_debug_trace_call_pending() {
+	let request = r#"{"jsonrpc": "2.0", "method": "debug_traceCall", "params": [{}, "pending"], "id": 1}"#;
+	let response = r#"{"jsonrpc":"2.0","error":{"code":-32602,"message":"Couldn't parse parameters: `BlockNumber::Pending` is not supported","data":"()"},"id":1}"#;
+	assert_eq!(io().handle_request_sync(request), Some(response.to_owned()));
+}
+
+#[test]
+fn rpc_debug_trace_call_invalid_tracer() {
+	let request = r#"{"jsonrpc": "2.0", "method": "debug_traceCall", "params": [{}, "latest", {"tracer": "0x00"}], "id": 1}"#;
+	let response = io().handle_request_sync(request).unwrap();
+	assert!(response.contains(r#""code":-32015,"message":"Tracer execution error.","data":"Invalid tracer module: "#));
+}
+
+#[test]
+fn rpc_debug_step_through_transaction() {
+	let io = io();
+
+	let request = r#"{"jsonrpc": "2.0", "method": "parity_debugStartTransaction", "params": [{}], "id": 1}"#;
+	let response = r#"{"jsonrpc":"2.0","result":"0x0","id":1}"#;
+	assert_eq!(io.handle_request_sync(request), Some(response.to_owned()));
+
+	let request = r#"{"jsonrpc": "2.0", "method": "parity_debugStep", "params": ["0x0"], "id": 1}"#;
+	let response = r#"{"jsonrpc":"2.0","result":{"depth":1,"gas":97,"gasCost":0,"op":"STOP","pc":2},"id":1}"#;
+	assert_eq!(io.handle_request_sync(request), Some(response.to_owned()));
+
+	let request = r#"{"jsonrpc": "2.0", "method": "parity_debugInspect", "params": ["0x0"], "id": 1}"#;
+	let response = r#"{"jsonrpc":"2.0","result":{"depth":1,"gas":97,"gasCost":0,"memory":[],"op":"STOP","pc":2,"stack":["0x2a"],"storage":{}},"id":1}"#;
+	assert_eq!(io.handle_request_sync(request), Some(response.to_owned()));
+
+	let request = r#"{"jsonrpc": "2.0", "method": "parity_debugStep", "params": ["0x0"], "id": 1}"#;
+	let response = r#"{"jsonrpc":"2.0","result":{"failed":false,"gas":21005,"returnValue":"010203","structLogs":[{"depth":1,"gas":16,"gasCost":3,"memory":[],"op":"PUSH1","pc":0,"stack":[],"storage":{"0000000000000000000000000000000000000000000000000000000000000000":"0000000000000000000000000000000000000000000000000000000000000001"}}]},"id":1}"#;
+	assert_eq!(io.handle_request_sync(request), Some(response.to_owned()));
+
+	let request = r#"{"jsonrpc": "2.0", "method": "parity_debugStep", "params": ["0x0"], "id": 1}"#;
+	let response = r#"{"jsonrpc":"2.0","error":{"code":-32042,"message":"Debug session not found or already finished."},"id":1}"#;
+	assert_eq!(io.handle_request_sync(request), Some(response.to_owned()));
+}
@@ -0,0 +1,73 @@
+// Copyright 2015-2018 Parity Technologies (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+use std::sync::Arc;
+
+use ethcore::client::{BlockChainClient, TestBlockChainClient};
+use ethcore::header::Header;
+use rlp::RlpStream;
+use rustc_hex::ToHex;
+
+use jsonrpc_core::IoHandler;
+use v1::{Engine, EngineClient};
+
+fn io(client: Arc<TestBlockChainClient>) -> IoHandler {
+	let mut io = IoHandler::new();
+	io.extend_with(EngineClient::new(client).to_delegate());
+	io
+}
+
+#[test]
+fn rpc_engine_get_head() {
+	let client = Arc::new(TestBlockChainClient::new());
+	let io = io(client.clone());
+
+	let request = r#"{"jsonrpc": "2.0", "method": "engine_getHead", "params": [], "id": 1}"#;
+	let response = io.handle_request_sync(request).unwrap();
+	let expected_hash = format!(r#""hash":"0x{:x}""#, client.chain_info().best_block_hash);
+	assert!(response.contains(r#""number":"0x0""#), "Unexpected response: {}", response);
+	assert!(response.contains(&expected_hash), "Unexpected response: {}", response);
+}
+
+#[test]
+fn rpc_engine_new_block() {
+	let client = Arc::new(TestBlockChainClient::new());
+	let io = io(client.clone());
+
+	let mut header = Header::new();
+	header.set_number(1);
+	header.set_parent_hash(client.chain_info().best_block_hash);
+	let mut block = RlpStream::new_list(3);
+	block.append(&header);
+	block.begin_list(0);
+	block.begin_list(0);
+
+	let request = format!(r#"{{"jsonrpc": "2.0", "method": "engine_newBlock", "params": ["0x{}"], "id": 1}}"#, block.out().to_hex());
+	let response = io.handle_request_sync(&request).unwrap();
+	assert!(response.contains(r#""status":"valid""#), "Unexpected response: {}", response);
+	assert!(response.contains(&format!(r#""blockHash":"0x{:x}""#, header.hash())), "Unexpected response: {}", response);
+	assert!(response.contains(r#""number":"0x1""#), "Unexpected response: {}", response);
+	assert_eq!(client.chain_info().best_block_hash, header.hash());
+}
+
+#[test]
+fn rpc_engine_new_block_rejects_invalid_rlp() {
+	let io = io(Arc::new(TestBlockChainClient::new()));
+
+	let request = r#"{"jsonrpc": "2.0", "method": "engine_newBlock", "params": ["0x01"], "id": 1}"#;
+	let response = io.handle_request_sync(request).unwrap();
+	assert!(response.contains(r#""code":-32602"#), "Unexpected response: {}", response);
+}
@@ -23,6 +23,7 @@ use ethereum_types::{H160, H256, U256, Address};
 use parking_lot::Mutex;
 use ethcore::account_provider::AccountProvider;
 use ethcore::client::{BlockChainClient, BlockId, EachBlockWith, Executed, TestBlockChainClient, TransactionId};
+use ethcore::header::Header;
 use ethcore::log_entry::{LocalizedLogEntry, LogEntry};
 use ethcore::miner::MinerService;
 use ethcore::receipt::{LocalizedReceipt, TransactionOutcome};
@@ -34,7 +35,7 @@ use rustc_hex::{FromHex, ToHex};
 use transaction::{Transaction, Action};
 
 use jsonrpc_core::IoHandler;
-use v1::{Eth, EthClient, EthClientOptions, EthFilter, EthFilterClient, EthSigning, SigningUnsafeClient};
+use v1::{Eth, EthCallOverride, EthClient, EthClientOptions, EthFilter, EthFilterClient, EthSigning, SigningUnsafeClient};
 use v1::helpers::nonce;
 use v1::helpers::dispatch::FullDispatcher;
 use v1::tests::helpers::{TestSyncProvider, Config, TestMinerService, TestSnapshotService};
@@ -92,8 +93,12 @@ impl EthTester {
 		let external_miner = Arc::new(ExternalMiner::new(hashrates.clone()));
 		let gas_price_percentile = options.gas_price_percentile;
 		let poll_lifetime = options.poll_lifetime;
-		let eth = EthClient::new(&client, &snapshot, &sync, &opt_ap, &miner, &external_miner, options).to_delegate();
-		let filter = EthFilterClient::new(client.clone(), miner.clone(), poll_lifetime).to_delegate();
+		let max_logs_range = options.max_logs_range;
+		let mut eth = EthClient::new(&client, &snapshot, &sync, &opt_ap, &miner, &external_miner, options).to_delegate();
+		EthCallOverride::extend_delegate(&mut eth);
+		let filter = EthFilterClient::new(client.clone(), miner.clone(), poll_lifetime)
+			.with_max_logs_range(max_logs_range)
+			.to_delegate();
 		let reservations = Arc::new(Mutex::new(nonce::Reservations::new()));
 
 		let dispatcher = FullDispatcher::new(client.clone(), miner.clone(), reservations, gas_price_percentile);
@@ -242,6 +247,24 @@ fn rpc_eth_logs_error() {
 	assert_eq!(tester.io.handle_request_sync(request), Some(response.to_owned()));
 }
 
+#[test]
+fn rpc_eth_logs_range_limit() {
+	// given
+	let tester = EthTester::new_with_options(EthClientOptions::with(|options| {
+		options.max_logs_range = Some(5);
+	}));
+	tester.add_blocks(10, EachBlockWith::Nothing);
+
+	// when
+	let too_large = r#"{"jsonrpc": "2.0", "method": "eth_getLogs", "params": [{"fromBlock":"0x1","toBlock":"latest"}], "id": 1}"#;
+	let allowed = r#"{"jsonrpc": "2.0", "method": "eth_getLogs", "params": [{"fromBlock":"0x6","toBlock":"0xa"}], "id": 1}"#;
+
+	// then
+	let response = r#"{"jsonrpc":"2.0","error":{"code":-32041,"message":"Filter spans 10 blocks, which exceeds the limit of 5 blocks. Split the query into smaller block ranges."},"id":1}"#;
+	assert_eq!(tester.io.handle_request_sync(too_large), Some(response.to_owned()));
+	assert_eq!(tester.io.handle_request_sync(allowed), Some(r#"{"jsonrpc":"2.0","result":[],"id":1}"#.to_owned()));
+}
+
 #[test]
 fn rpc_logs_filter() {
 	let tester = EthTester::default();
@@ -401,6 +424,27 @@ fn rpc_eth_gas_price() {
 	assert_eq!(EthTester::default().io.handle_request_sync(request), Some(response.to_owned()));
 }
 
+#[test]
+fn rpc_eth_max_priority_fee_per_gas() {
+	let request = r#"{"jsonrpc": "2.0", "method": "eth_maxPriorityFeePerGas", "params": [], "id": 1}"#;
+	let response = r#"{"jsonrpc":"2.0","result":"0x4a817c800","id":1}"#;
+
+	assert_eq!(EthTester::default().io.handle_request_sync(request), Some(response.to_owned()));
+}
+
+#[test]
+fn rpc_eth_fee_history() {
+	let tester = EthTester::default();
+
+	let request = r#"{"jsonrpc": "2.0", "method": "eth_feeHistory", "params": ["0x1", "latest"], "id": 1}"#;
+	let response = r#"{"jsonrpc":"2.0","result":{"baseFeePerGas":["0x0","0x0"],"gasUsedRatio":[0.0],"oldestBlock":"0x0"},"id":1}"#;
+	assert_eq!(tester.io.handle_request_sync(request), Some(response.to_owned()));
+
+	let request = r#"{"jsonrpc": "2.0", "method": "eth_feeHistory", "params": ["0x1", "latest", [50, 10]], "id": 1}"#;
+	let response = r#"{"jsonrpc":"2.0","error":{"code":-32602,"message":"Couldn't parse parameters: rewardPercentiles","data":"\"expected monotonically increasing values between 0 and 100\""},"id":1}"#;
+	assert_eq!(tester.io.handle_request_sync(request), Some(response.to_owned()));
+}
+
 #[test]
 fn rpc_eth_accounts() {
 	let tester = EthTester::default();
@@ -414,6 +458,39 @@ fn rpc_eth_accounts() {
 	assert_eq!(tester.io.handle_request_sync(request), Some(response.to_owned()));
 }
 
+#[test]
+fn rpc_eth_block_by_hash_non_canonical() {
+	// given
+	let tester = EthTester::default();
+	tester.add_blocks(2, EachBlockWith::Nothing);
+	let mut header = Header::new();
+	header.set_number(1);
+	header.set_parent_hash(tester.client.genesis_hash);
+	header.set_extra_data(b"fork".to_vec());
+	let mut block = rlp::RlpStream::new_list(3);
+	block.append(&header);
+	block.append_raw(&rlp::EMPTY_LIST_RLP, 1);
+	block.append_raw(&rlp::EMPTY_LIST_RLP, 1);
+	tester.client.blocks.write().insert(header.hash(), block.out());
+	let canonical = tester.client.numbers.read()[&1];
+
+	// when
+	let request = |hash: &H256, flag: &str| format!(
+		r#"{{"jsonrpc": "2.0", "method": "eth_getBlockByHash", "params": ["0x{:x}", false{}], "id": 1}}"#,
+		hash, flag,
+	);
+	let fork_default = tester.io.handle_request_sync(&request(&header.hash(), "")).unwrap();
+	let fork_allowed = tester.io.handle_request_sync(&request(&header.hash(), ", true")).unwrap();
+	let canonical_default = tester.io.handle_request_sync(&request(&canonical, "")).unwrap();
+	let canonical_allowed = tester.io.handle_request_sync(&request(&canonical, ", true")).unwrap();
+
+	// then
+	assert_eq!(fork_default, r#"{"jsonrpc":"2.0","result":null,"id":1}"#);
+	assert!(fork_allowed.contains(r#""canonical":false"#), "unexpected response: {}", fork_allowed);
+	assert!(!canonical_default.contains("canonical"), "unexpected response: {}", canonical_default);
+	assert!(canonical_allowed.contains(r#""canonical":true"#), "unexpected response: {}", canonical_allowed);
+}
+
 #[test]
 fn rpc_eth_block_number() {
 	let tester = EthTester::default();
@@ -690,6 +767,162 @@ fn rpc_eth_call() {
 	assert_eq!(tester.io.handle_request_sync(request), Some(response.to_owned()));
 }
 
+#[test]
+fn rpc_eth_call_many() {
+	let tester = EthTester::default();
+	tester.client.set_execution_result(Ok(Executed {
+		exception: None,
+		gas: U256::zero(),
+		gas_used: U256::from(0xff30),
+		refunded: U256::from(0x5),
+		cumulative_gas_used: U256::zero(),
+		logs: vec![],
+		contracts_created: vec![],
+		output: vec![0x12, 0x34, 0xff],
+		trace: vec![],
+		vm_trace: None,
+		state_diff: None,
+	}));
+
+	let request = r#"{
+		"jsonrpc": "2.0",
+		"method": "eth_callMany",
+		"params": [[{
+			"from": "0xb60e8dd61c5d32be8058bb8eb970870f07233155",
+			"to": "0xd46e8dd67c5d32be8058bb8eb970870f07244567",
+			"data": "0xd46e8dd6"
+		}, {
+			"to": "0xd46e8dd67c5d32be8058bb8eb970870f07244567"
+		}],
+		"latest"],
+		"id": 1
+	}"#;
+	let response = r#"{"jsonrpc":"2.0","result":[{"cumulativeGasUsed":"0xff30","gasUsed":"0xff30","output":"0x1234ff"},{"cumulativeGasUsed":"0x1fe60","gasUsed":"0xff30","output":"0x1234ff"}],"id":1}"#;
+
+	assert_eq!(tester.io.handle_request_sync(request), Some(response.to_owned()));
+}
+
+#[test]
+fn rpc_eth_call_many_rejects_invalid_raw_transaction() {
+	let tester = EthTester::default();
+
+	let request = r#"{
+		"jsonrpc": "2.0",
+		"method": "eth_callMany",
+		"params": [["0x1234"]],
+		"id": 1
+	}"#;
+	let response = r#"{"jsonrpc":"2.0","error":{"code":-32602,"message":"Couldn't parse parameters: Transaction is not valid RLP","data":"Custom(\"Unknown transaction type.\")"},"id":1}"#;
+
+	assert_eq!(tester.io.handle_request_sync(request), Some(response.to_owned()));
+}
+
+#[test]
+fn rpc_eth_call_cached_until_new_block() {
+	let tester = EthTester::new_with_options(EthClientOptions::with(|options| {
+		options.call_cache_size = 1024;
+	}));
+	let executed = |output: Vec<u8>| Ok(Executed {
+		exception: None,
+		gas: U256::zero(),
+		gas_used: U256::from(0xff30),
+		refunded: U256::from(0x5),
+		cumulative_gas_used: U256::zero(),
+		logs: vec![],
+		contracts_created: vec![],
+		output: output,
+		trace: vec![],
+		vm_trace: None,
+		state_diff: None,
+	});
+
+	let request = r#"{
+		"jsonrpc": "2.0",
+		"method": "eth_call",
+		"params": [{
+			"from": "0xb60e8dd61c5d32be8058bb8eb970870f07233155",
+			"to": "0xd46e8dd67c5d32be8058bb8eb970870f07244567",
+			"data": "0xd46e8dd6"
+		},
+		"latest"],
+		"id": 1
+	}"#;
+
+	tester.client.set_execution_result(executed(vec![0x12, 0x34]));
+	let response = r#"{"jsonrpc":"2.0","result":"0x1234","id":1}"#;
+	assert_eq!(tester.io.handle_request_sync(request), Some(response.to_owned()));
+
+	tester.client.set_execution_result(executed(vec![0x56]));
+	assert_eq!(tester.io.handle_request_sync(request), Some(response.to_owned()));
+
+	tester.client.add_blocks(1, EachBlockWith::Nothing);
+	let response = r#"{"jsonrpc":"2.0","result":"0x56","id":1}"#;
+	assert_eq!(tester.io.handle_request_sync(request), Some(response.to_owned()));
+}
+
+#[test]
+fn rpc_eth_call_with_state_override() {
+	let tester = EthTester::default();
+	tester.client.set_execution_result(Ok(Executed {
+		exception: None,
+		gas: U256::zero(),
+		gas_used: U256::from(0xff30),
+		refunded: U256::from(0x5),
+		cumulative_gas_used: U256::zero(),
+		logs: vec![],
+		contracts_created: vec![],
+		output: vec![0x12, 0x34, 0xff],
+		trace: vec![],
+		vm_trace: None,
+		state_diff: None,
+	}));
+
+	let request = r#"{
+		"jsonrpc": "2.0",
+		"method": "eth_call",
+		"params": [{
+			"from": "0xb60e8dd61c5d32be8058bb8eb970870f07233155",
+			"to": "0xd46e8dd67c5d32be8058bb8eb970870f07244567",
+			"data": "0xd46e8dd67c5d32be"
+		},
+		"latest",
+		{
+			"0xd46e8dd67c5d32be8058bb8eb970870f07244567": {
+				"balance": "0x1",
+				"code": "0x6000"
+			}
+		}],
+		"id": 1
+	}"#;
+	let response = r#"{"jsonrpc":"2.0","result":"0x1234ff","id":1}"#;
+
+	assert_eq!(tester.io.handle_request_sync(request), Some(response.to_owned()));
+}
+
+#[test]
+fn rpc_eth_call_rejects_state_and_state_diff() {
+	let tester = EthTester::default();
+
+	let request = r#"{
+		"jsonrpc": "2.0",
+		"method": "eth_call",
+		"params": [{
+			"to": "0xd46e8dd67c5d32be8058bb8eb970870f07244567"
+		},
+		"latest",
+		{
+			"0xd46e8dd67c5d32be8058bb8eb970870f07244567": {
+				"state": {},
+				"stateDiff": {}
+			}
+		}],
+		"id": 1
+	}"#;
+	let response = tester.io.handle_request_sync(request).unwrap();
+
+	assert!(response.contains(r#""code":-32602"#), "unexpected response: {}", response);
+}
+
 #[test]
 fn rpc_eth_call_default_block() {
 	let tester = EthTester::default();
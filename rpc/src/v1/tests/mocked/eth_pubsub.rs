@@ -153,6 +153,67 @@ fn should_subscribe_to_logs() {
 	assert_eq!(res, None);
 }
 
+#[test]
+fn should_send_logs_in_batches() {
+	use ethcore::log_entry::{LocalizedLogEntry, LogEntry};
+	use ethcore::ids::BlockId;
+	use ethcore::client::BlockInfo;
+	use v1::SubscriptionQueueConfig;
+
+	// given
+	let el = EventLoop::spawn();
+	let mut client = TestBlockChainClient::new();
+	client.add_blocks(1, EachBlockWith::Transaction);
+	let h1 = client.block_hash_delta_minus(1);
+	let block = client.block(BlockId::Hash(h1)).unwrap();
+	let tx_hash = block.transactions()[0].hash();
+	client.set_logs((0..3).map(|i| LocalizedLogEntry {
+		entry: LogEntry {
+			address: 5.into(),
+			topics: vec![],
+			data: vec![],
+		},
+		block_hash: h1,
+		block_number: block.header().number(),
+		transaction_hash: tx_hash,
+		transaction_index: 0,
+		log_index: i,
+		transaction_log_index: i,
+	}).collect());
+
+	let pubsub = EthPubSubClient::new_test_with_queue(Arc::new(client), el.remote(), SubscriptionQueueConfig {
+		logs_batch_size: 2,
+		..Default::default()
+	});
+	let handler = pubsub.handler().upgrade().unwrap();
+	let pubsub = pubsub.to_delegate();
+
+	let mut io = MetaIoHandler::default();
+	io.extend_with(pubsub);
+
+	let mut metadata = Metadata::default();
+	let (sender, receiver) = futures::sync::mpsc::channel(8);
+	metadata.session = Some(Arc::new(Session::new(sender)));
+
+	// Subscribe
+	let request = r#"{"jsonrpc": "2.0", "method": "eth_subscribe", "params": ["logs", {}], "id": 1}"#;
+	let response = r#"{"jsonrpc":"2.0","result":"0x416d77337e24399d","id":1}"#;
+	assert_eq!(io.handle_request_sync(request, metadata.clone()), Some(response.to_owned()));
+
+	// Check notifications
+	let log = |i: usize| format!(
+		r#"{{"address":"0x0000000000000000000000000000000000000005","blockHash":"0x3457d2fa2e3dd33c78ac681cf542e429becf718859053448748383af67e23218","blockNumber":"0x1","data":"0x","logIndex":"0x{0:x}","removed":false,"topics":[],"transactionHash":"0x{1:x}","transactionIndex":"0x0","transactionLogIndex":"0x{0:x}","type":"mined"}}"#,
+		i, tx_hash,
+	);
+	handler.new_blocks(vec![], vec![], ChainRoute::new(vec![(h1, ChainRouteType::Enacted)]), vec![], vec![], DURATION_ZERO);
+	let (res, receiver) = receiver.into_future().wait().unwrap();
+	let response = format!(r#"{{"jsonrpc":"2.0","method":"eth_subscription","params":{{"result":[{},{}],"subscription":"0x416d77337e24399d"}}}}"#, log(0), log(1));
+	assert_eq!(res, Some(response));
+	let (res, _receiver) = receiver.into_future().wait().unwrap();
+	let response = format!(r#"{{"jsonrpc":"2.0","method":"eth_subscription","params":{{"result":[{}],"subscription":"0x416d77337e24399d"}}}}"#, log(2));
+	assert_eq!(res, Some(response));
+}
+
 #[test]
 fn should_subscribe_to_pending_transactions() {
 	// given
@@ -172,7 +233,7 @@ fn should_subscribe_to_pending_transactions() {
 
 	// Fail if params are provided
 	let request = r#"{"jsonrpc": "2.0", "method": "eth_subscribe", "params": ["newPendingTransactions", {}], "id": 1}"#;
-	let response = r#"{"jsonrpc":"2.0","error":{"code":-32602,"message":"Couldn't parse parameters: newPendingTransactions","data":"\"Expected no parameters.\""},"id":1}"#;
+	let response = r#"{"jsonrpc":"2.0","error":{"code":-32602,"message":"Couldn't parse parameters: newPendingTransactions","data":"\"Expected no parameters or a transaction filter.\""},"id":1}"#;
 	assert_eq!(io.handle_request_sync(request, metadata.clone()), Some(response.to_owned()));
 
 	// Subscribe
@@ -200,6 +261,58 @@ fn should_subscribe_to_pending_transactions() {
 	assert_eq!(res, None);
 }
 
+#[test]
+fn should_subscribe_to_filtered_pending_transactions() {
+	use miner::pool::VerifiedTransaction;
+	use transaction::{Action, Transaction};
+
+	// given
+	let el = EventLoop::spawn();
+	let client = TestBlockChainClient::new();
+
+	let pubsub = EthPubSubClient::new_test(Arc::new(client), el.remote());
+	let handler = pubsub.handler().upgrade().unwrap();
+	let pubsub = pubsub.to_delegate();
+
+	let mut io = MetaIoHandler::default();
+	io.extend_with(pubsub);
+
+	let mut metadata = Metadata::default();
+	let (sender, receiver) = futures::sync::mpsc::channel(8);
+	metadata.session = Some(Arc::new(Session::new(sender)));
+
+	// Fail if selector is invalid
+	let request = r#"{"jsonrpc": "2.0", "method": "eth_subscribe", "params": ["newPendingTransactions", {"selector": "0xa9"}], "id": 1}"#;
+	let response = r#"{"jsonrpc":"2.0","error":{"code":-32602,"message":"Couldn't parse parameters: newPendingTransactions","data":"\"Expected a four bytes long selector.\""},"id":1}"#;
+	assert_eq!(io.handle_request_sync(request, metadata.clone()), Some(response.to_owned()));
+
+	// Subscribe
+	let request = r#"{"jsonrpc": "2.0", "method": "eth_subscribe", "params": ["newPendingTransactions", {"to": "0x000000000000000000000000000000000000000a", "minGasPrice": "0x10", "selector": "0xa9059cbb"}], "id": 1}"#;
+	let response = r#"{"jsonrpc":"2.0","result":"0x416d77337e24399d","id":1}"#;
+	assert_eq!(io.handle_request_sync(request, metadata.clone()), Some(response.to_owned()));
+
+	// Send new transactions
+	let tx = |to: u64, gas_price: u64, data: Vec<u8>| Arc::new(VerifiedTransaction::from_pending_block_transaction(Transaction {
+		action: Action::Call(to.into()),
+		gas_price: gas_price.into(),
+		data,
+		..Default::default()
+	}.fake_sign(5.into())));
+	let matching = tx(10, 16, vec![0xa9, 0x05, 0x9c, 0xbb, 0x01]);
+	handler.notify_pending_transactions(&[
+		tx(11, 16, vec![0xa9, 0x05, 0x9c, 0xbb]),
+		tx(10, 15, vec![0xa9, 0x05, 0x9c, 0xbb]),
+		tx(10, 16, vec![0x09, 0x5e, 0xa7, 0xb3]),
+		matching.clone(),
+	]);
+	// Filtered subscribers are not notified about bare hashes
+	handler.notify_new_transactions(&[5.into()]);
+
+	let (res, _receiver) = receiver.into_future().wait().unwrap();
+	let response = format!(r#"{{"jsonrpc":"2.0","method":"eth_subscription","params":{{"result":"0x{:x}","subscription":"0x416d77337e24399d"}}}}"#, matching.signed().hash());
+	assert_eq!(res, Some(response));
+}
+
 #[test]
 fn should_return_unimplemented() {
 	// given
@@ -14,8 +14,10 @@
 // You should have received a copy of the GNU General Public License
 // along with Parity.  If not, see <http://www.gnu.org/licenses/>.
 
+use std::collections::HashMap;
 use std::ops::Range;
-use sync::ManageNetwork;
+use std::time::{Duration, UNIX_EPOCH};
+use sync::{ManageNetwork, HandshakeFailure, HandshakeFailureReason, Traffic};
 use self::ethcore_network::{ProtocolId, NetworkContext};
 
 extern crate ethcore_network;
@@ -28,8 +30,30 @@ impl ManageNetwork for TestManageNetwork {
 	fn deny_unreserved_peers(&self) { }
 	fn remove_reserved_peer(&self, _peer: String) -> Result<(), String> { Ok(()) }
 	fn add_reserved_peer(&self, _peer: String) -> Result<(), String> { Ok(()) }
+	fn set_reserved_peers(&self, _peers: Vec<String>) -> Result<(), String> { Ok(()) }
 	fn start_network(&self) {}
 	fn stop_network(&self) {}
 	fn num_peers_range(&self) -> Range<u32> { 25 .. 51 }
+	fn handshake_failures(&self) -> Vec<HandshakeFailure> {
+		vec![
+			HandshakeFailure {
+				remote_id: None,
+				remote_address: "127.0.0.1:7777".into(),
+				reason: HandshakeFailureReason::AckTimeout,
+				time: UNIX_EPOCH + Duration::from_millis(1000),
+			},
+			HandshakeFailure {
+				remote_id: Some(1.into()),
+				remote_address: "127.0.0.1:8888".into(),
+				reason: HandshakeFailureReason::GenesisMismatch,
+				time: UNIX_EPOCH + Duration::from_millis(1500),
+			},
+		]
+	}
+	fn traffic(&self) -> HashMap<ProtocolId, Traffic> {
+		let mut traffic = HashMap::new();
+		traffic.insert(*b"eth", Traffic { bytes_in: 2048, bytes_out: 1024 });
+		traffic
+	}
 	fn with_proto_context(&self, _: ProtocolId, _: &mut FnMut(&NetworkContext)) { }
 }
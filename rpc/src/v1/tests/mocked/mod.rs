@@ -17,7 +17,9 @@
 //! RPC mocked tests. Most of these test that the RPC server is serializing and forwarding
 //! method calls properly.
 
+mod admin;
 mod debug;
+mod engine;
 mod eth;
 mod eth_pubsub;
 mod manage_network;
@@ -32,4 +34,5 @@ mod secretstore;
 mod signer;
 mod signing;
 mod traces;
+mod txpool;
 mod web3;
@@ -16,18 +16,21 @@
 
 use std::sync::Arc;
 use ethcore::account_provider::AccountProvider;
-use ethcore::client::{TestBlockChainClient, Executed, TransactionId};
+use ethcore::client::{TestBlockChainClient, Executed, TransactionId, TransactionLifecycle, LifecycleEntry, LifecycleEvent};
 use ethcore::receipt::{LocalizedReceipt, TransactionOutcome};
 use ethcore_logger::RotatingLogger;
 use ethereum_types::{Address, U256, H256};
 use ethstore::ethkey::{Generator, Random};
+use futures_cpupool::CpuPool;
 use miner::pool::local_transactions::Status as LocalTransactionStatus;
-use sync::ManageNetwork;
+use sync::{ManageNetwork, SyncState};
 
 use jsonrpc_core::IoHandler;
 use v1::{Parity, ParityClient};
 use v1::metadata::Metadata;
 use v1::helpers::{SignerService, NetworkSettings};
+use v1::helpers::dispatch::eth_data_hash;
+use v1::types::H520;
 use v1::tests::helpers::{TestSyncProvider, Config, TestMinerService, TestUpdater};
 use super::manage_network::TestManageNetwork;
 use Host;
@@ -83,6 +86,7 @@ impl Dependencies {
 			self.settings.clone(),
 			signer,
 			self.ws_address.clone(),
+			CpuPool::new(1),
 		)
 	}
 
@@ -304,11 +308,24 @@ fn rpc_parity_net_peers() {
 	let io = deps.default_client();
 
 	let request = r#"{"jsonrpc": "2.0", "method": "parity_netPeers", "params":[], "id": 1}"#;
-	let response = r#"{"jsonrpc":"2.0","result":{"active":0,"connected":120,"max":50,"peers":[{"caps":["eth/62","eth/63"],"id":"node1","name":"Parity-Ethereum/1","network":{"localAddress":"127.0.0.1:8888","remoteAddress":"127.0.0.1:7777"},"protocols":{"eth":{"difficulty":"0x28","head":"0000000000000000000000000000000000000000000000000000000000000032","version":62},"pip":null}},{"caps":["eth/63","eth/64"],"id":null,"name":"Parity-Ethereum/2","network":{"localAddress":"127.0.0.1:3333","remoteAddress":"Handshake"},"protocols":{"eth":{"difficulty":null,"head":"000000000000000000000000000000000000000000000000000000000000003c","version":64},"pip":null}}]},"id":1}"#;
+	let response = r#"{"jsonrpc":"2.0","result":{"active":0,"connected":120,"max":50,"peers":[{"caps":["eth/62","eth/63"],"id":"node1","name":"Parity-Ethereum/1","network":{"localAddress":"127.0.0.1:8888","remoteAddress":"127.0.0.1:7777","traffic":{"eth":{"bytesIn":512,"bytesOut":256}}},"protocols":{"eth":{"difficulty":"0x28","head":"0000000000000000000000000000000000000000000000000000000000000032","version":62},"pip":null}},{"caps":["eth/63","eth/64"],"id":null,"name":"Parity-Ethereum/2","network":{"localAddress":"127.0.0.1:3333","remoteAddress":"Handshake","traffic":{}},"protocols":{"eth":{"difficulty":null,"head":"000000000000000000000000000000000000000000000000000000000000003c","version":64},"pip":null}}],"traffic":{"eth":{"bytesIn":2048,"bytesOut":1024}}},"id":1}"#;
 
 	assert_eq!(io.handle_request_sync(request), Some(response.to_owned()));
 }
 
+#[test]
+fn rpc_parity_net_failures() {
+	let deps = Dependencies::new();
+	let io = deps.default_client();
+
+	let request = r#"{"jsonrpc": "2.0", "method": "parity_netFailures", "params":[], "id": 1}"#;
+	let response = r#"{"jsonrpc":"2.0","result":[{"id":null,"reason":"ack timeout","remoteAddress":"127.0.0.1:7777","time":1000},{"id":"0x"#.to_owned()
+		+ &format!("{:0>128}", 1)
+		+ r#"","reason":"genesis mismatch","remoteAddress":"127.0.0.1:8888","time":1500}],"id":1}"#;
+
+	assert_eq!(io.handle_request_sync(request), Some(response));
+}
+
 #[test]
 fn rpc_parity_net_port() {
 	let deps = Dependencies::new();
@@ -375,6 +392,17 @@ fn rpc_parity_pending_transactions() {
 	assert_eq!(io.handle_request_sync(request), Some(response.to_owned()));
 }
 
+#[test]
+fn rpc_parity_pending_block_candidates() {
+	let deps = Dependencies::new();
+	let io = deps.default_client();
+
+	let request = r#"{"jsonrpc": "2.0", "method": "parity_pendingBlockCandidates", "params":[], "id": 1}"#;
+	let response = r#"{"jsonrpc":"2.0","result":[],"id":1}"#;
+
+	assert_eq!(io.handle_request_sync(request), Some(response.to_owned()));
+}
+
 #[test]
 fn rpc_parity_encrypt() {
 	let deps = Dependencies::new();
@@ -438,6 +466,28 @@ fn rpc_parity_transactions_stats() {
 	assert_eq!(io.handle_request_sync(request), Some(response.to_owned()));
 }
 
+#[test]
+fn rpc_parity_block_propagation_stats() {
+	let deps = Dependencies::new();
+	let io = deps.default_client();
+
+	let request = r#"{"jsonrpc": "2.0", "method": "parity_blockPropagationStats", "params":[], "id": 1}"#;
+	let response = r#"{"jsonrpc":"2.0","result":{"0x0000000000000000000000000000000000000000000000000000000000000003":{"announcedBy":{"0x00000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000010":35,"0x00000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000080":0},"firstSeen":1500000000000,"importDelay":120,"number":7},"id":1}"#;
+
+	assert_eq!(io.handle_request_sync(request), Some(response.to_owned()));
+}
+
+#[test]
+fn rpc_parity_block_announcement_stats() {
+	let deps = Dependencies::new();
+	let io = deps.default_client();
+
+	let request = r#"{"jsonrpc": "2.0", "method": "parity_blockAnnouncementStats", "params":[], "id": 1}"#;
+	let response = r#"{"jsonrpc":"2.0","result":{"duplicatesReceived":6,"duplicatesSuppressed":2,"received":10,"sent":4},"id":1}"#;
+
+	assert_eq!(io.handle_request_sync(request), Some(response.to_owned()));
+}
+
 #[test]
 fn rpc_parity_local_transactions() {
 	let deps = Dependencies::new();
@@ -460,6 +510,27 @@ fn rpc_parity_local_transactions() {
 	assert_eq!(io.handle_request_sync(request), Some(response.to_owned()));
 }
 
+#[test]
+fn rpc_parity_transaction_lifecycle() {
+	let deps = Dependencies::new();
+	let io = deps.default_client();
+	deps.client.lifecycles.write().insert(10.into(), TransactionLifecycle {
+		hash: 10.into(),
+		events: vec![
+			LifecycleEntry { timestamp: 1, event: LifecycleEvent::Accepted },
+			LifecycleEntry { timestamp: 2, event: LifecycleEvent::Replaced(15.into()) },
+		],
+	});
+
+	let request = r#"{"jsonrpc": "2.0", "method": "parity_transactionLifecycle", "params":["0x000000000000000000000000000000000000000000000000000000000000000a"], "id": 1}"#;
+	let response = r#"{"jsonrpc":"2.0","result":{"events":[{"event":"accepted","timestamp":"0x1"},{"event":"replaced","hash":"0x000000000000000000000000000000000000000000000000000000000000000f","timestamp":"0x2"}],"hash":"0x000000000000000000000000000000000000000000000000000000000000000a"},"id":1}"#;
+	assert_eq!(io.handle_request_sync(request), Some(response.to_owned()));
+
+	let request = r#"{"jsonrpc": "2.0", "method": "parity_transactionLifecycle", "params":["0x000000000000000000000000000000000000000000000000000000000000000f"], "id": 1}"#;
+	let response = r#"{"jsonrpc":"2.0","result":null,"id":1}"#;
+	assert_eq!(io.handle_request_sync(request), Some(response.to_owned()));
+}
+
 #[test]
 fn rpc_parity_chain_status() {
 	let deps = Dependencies::new();
@@ -469,7 +540,38 @@ fn rpc_parity_chain_status() {
 	*deps.client.first_block.write() = Some((H256::from(U256::from(1234)), 3333));
 
 	let request = r#"{"jsonrpc": "2.0", "method": "parity_chainStatus", "params":[], "id": 1}"#;
-	let response = r#"{"jsonrpc":"2.0","result":{"blockGap":["0x6","0xd05"]},"id":1}"#;
+	let response = r#"{"jsonrpc":"2.0","result":{"blockGap":["0x6","0xd05"],"importPaused":false,"timestampDrift":{"acceptable":15000,"invalid":150000}},"id":1}"#;
+
+	assert_eq!(io.handle_request_sync(request), Some(response.to_owned()));
+}
+
+#[test]
+fn rpc_parity_sync_progress() {
+	let deps = Dependencies::new();
+	let io = deps.default_client();
+
+	{
+		let mut status = deps.sync.status.write();
+		status.state = SyncState::SnapshotData;
+		status.highest_block_number = Some(100);
+		status.num_active_peers = 2;
+		status.num_snapshot_chunks = 40;
+		status.snapshot_chunks_done = 10;
+	}
+
+	let request = r#"{"jsonrpc": "2.0", "method": "parity_syncProgress", "params":[], "id": 1}"#;
+	let response = r#"{"jsonrpc":"2.0","result":{"activePeers":2,"currentBlock":"0x0","eta":null,"highestBlock":"0x64","peers":120,"stage":"warpChunks","warpChunksAmount":"0x28","warpChunksProcessed":"0xa"},"id":1}"#;
+
+	assert_eq!(io.handle_request_sync(request), Some(response.to_owned()));
+}
+
+#[test]
+fn rpc_parity_import_queue_status() {
+	let deps = Dependencies::new();
+	let io = deps.default_client();
+
+	let request = r#"{"jsonrpc": "2.0", "method": "parity_importQueueStatus", "params":[], "id": 1}"#;
+	let response = r#"{"jsonrpc":"2.0","result":{"blocks":[],"executing":null,"unverified":0,"verified":0,"verifying":0},"id":1}"#;
 
 	assert_eq!(io.handle_request_sync(request), Some(response.to_owned()));
 }
@@ -485,6 +587,52 @@ fn rpc_parity_node_kind() {
 	assert_eq!(io.handle_request_sync(request), Some(response.to_owned()));
 }
 
+#[test]
+fn rpc_parity_thread_stats() {
+	use std::time::Duration;
+	use io::ThreadPoolUsage;
+	use serde_json::{self, Value};
+
+	let deps = Dependencies::new();
+	let io = deps.default_client();
+	let usage = ThreadPoolUsage::register("rpc-test-pool", 3);
+	usage.note_busy(Duration::from_millis(500));
+
+	let request = r#"{"jsonrpc": "2.0", "method": "parity_threadStats", "params":[], "id": 1}"#;
+	let response: Value = serde_json::from_str(&io.handle_request_sync(request).unwrap()).unwrap();
+	let pool = response["result"].as_array().unwrap().iter()
+		.find(|pool| pool["name"] == "rpc-test-pool")
+		.cloned()
+		.unwrap();
+
+	assert_eq!(pool["threads"], 3);
+	assert_eq!(pool["busySeconds"], 0.5);
+	assert!(pool["utilization"].is_number());
+}
+
+#[test]
+fn rpc_parity_state_diff_between() {
+	let deps = Dependencies::new();
+	let io = deps.default_client();
+
+	// test client doesn't support fat db
+	let request = r#"{"jsonrpc": "2.0", "method": "parity_stateDiffBetween", "params":["0x1", "latest"], "id": 1}"#;
+	let response = r#"{"jsonrpc":"2.0","result":null,"id":1}"#;
+
+	assert_eq!(io.handle_request_sync(request), Some(response.to_owned()));
+}
+
+#[test]
+fn rpc_parity_block_witness() {
+	let deps = Dependencies::new();
+	let io = deps.default_client();
+
+	let request = r#"{"jsonrpc": "2.0", "method": "parity_blockWitness", "params":["latest"], "id": 1}"#;
+	let response = r#"{"jsonrpc":"2.0","result":null,"id":1}"#;
+
+	assert_eq!(io.handle_request_sync(request), Some(response.to_owned()));
+}
+
 #[test]
 fn rpc_parity_cid() {
 	let deps = Dependencies::new();
@@ -533,6 +681,49 @@ fn rpc_parity_call() {
 	assert_eq!(io.handle_request_sync(request), Some(response.to_owned()));
 }
 
+#[test]
+fn rpc_parity_gas_flame() {
+	use ethcore::trace::FlatTrace;
+	use ethcore::trace::trace::{Action, Res, Call, CallResult};
+	use vm::CallType;
+
+	let deps = Dependencies::new();
+	deps.client.set_execution_result(Ok(Executed {
+		exception: None,
+		gas: U256::zero(),
+		gas_used: U256::from(0xff30),
+		refunded: U256::zero(),
+		cumulative_gas_used: U256::zero(),
+		logs: vec![],
+		contracts_created: vec![],
+		output: vec![],
+		trace: vec![FlatTrace {
+			action: Action::Call(Call {
+				from: 1.into(),
+				to: 10.into(),
+				value: 0.into(),
+				gas: 0x76c0.into(),
+				input: vec![0xa9, 0x05, 0x9c, 0xbb],
+				call_type: CallType::Call,
+			}),
+			result: Res::Call(CallResult {
+				gas_used: 0x5208.into(),
+				output: vec![],
+			}),
+			subtraces: 0,
+			trace_address: Default::default(),
+		}],
+		vm_trace: None,
+		state_diff: None,
+	}));
+	let io = deps.default_client();
+
+	let request = r#"{"jsonrpc": "2.0", "method": "parity_gasFlame", "params": ["0x0000000000000000000000000000000000000000000000000000000000000005"], "id": 1}"#;
+	let response = r#"{"jsonrpc":"2.0","result":"0x000000000000000000000000000000000000000a:0xa9059cbb 21000\n","id":1}"#;
+
+	assert_eq!(io.handle_request_sync(request), Some(response.to_owned()));
+}
+
 #[test]
 fn rpc_parity_block_receipts() {
 	let deps = Dependencies::new();
@@ -563,3 +754,52 @@ fn rpc_parity_block_receipts() {
 
 	assert_eq!(io.handle_request_sync(request), Some(response.to_owned()));
 }
+
+#[test]
+fn rpc_parity_block_receipts_by_hash() {
+	let deps = Dependencies::new();
+	deps.client.receipts.write()
+		.insert(TransactionId::Hash(1.into()), LocalizedReceipt {
+			transaction_hash: 1.into(),
+			transaction_index: 0,
+			block_hash: 3.into(),
+			block_number: 0,
+			cumulative_gas_used: 21_000.into(),
+			gas_used: 21_000.into(),
+			contract_address: None,
+			logs: vec![],
+			log_bloom: 1.into(),
+			outcome: TransactionOutcome::Unknown,
+			to: None,
+			from: 9.into(),
+		});
+	let io = deps.default_client();
+
+	let request = r#"{
+		"jsonrpc": "2.0",
+		"method": "parity_getBlockReceipts",
+		"params": ["0x0000000000000000000000000000000000000000000000000000000000000003"],
+		"id": 1
+	}"#;
+	let result = io.handle_request_sync(request).unwrap();
+	assert!(result.contains(r#""transactionHash":"0x0000000000000000000000000000000000000000000000000000000000000001""#), "{}", result);
+}
+
+#[test]
+fn rpc_parity_verify_signatures() {
+	let deps = Dependencies::new();
+	let io = deps.default_client();
+	let address = deps.accounts.new_account(&"password123".into()).unwrap();
+	let hash = eth_data_hash(vec![5u8]);
+	let signature = H520(deps.accounts.sign(address, Some("password123".into()), hash).unwrap().into_electrum());
+
+	let request = r#"{"jsonrpc": "2.0", "method": "parity_verifySignatures", "params":[[
+		{"message":"0x05","signature":"0x"#.to_owned() + &format!("{:?}", signature) + r#"","address":"0x"# + &format!("{:x}", address) + r#""},
+		{"message":"0x06","signature":"0x"# + &format!("{:?}", signature) + r#"","address":"0x"# + &format!("{:x}", address) + r#""}
+	]], "id": 1}"#;
+	let response = r#"{"jsonrpc":"2.0","result":[{"recovered":"0x"#.to_owned() + &format!("{:x}", address) + r#"","valid":true},{"recovered":""#;
+
+	let result = io.handle_request_sync(&request).unwrap();
+	assert!(result.starts_with(&response), "{}", result);
+	assert!(result.ends_with(r#","valid":false}],"id":1}"#), "{}", result);
+}
@@ -121,6 +121,62 @@ fn should_be_able_to_set_meta() {
 	assert_eq!(res, Some(response));
 }
 
+#[test]
+fn should_be_able_to_set_tags_and_fields() {
+	let tester = setup();
+	tester.accounts.new_account(&"".into()).unwrap();
+	let accounts = tester.accounts.accounts().unwrap();
+	assert_eq!(accounts.len(), 1);
+	let address = accounts[0];
+	tester.accounts.set_account_meta(address.clone(), r#"{"description":"signer"}"#.to_owned()).unwrap();
+
+	let request = format!(r#"{{"jsonrpc": "2.0", "method": "parity_setAccountTags", "params": ["0x{:x}", ["ops", "hot"]], "id": 1}}"#, address);
+	let response = r#"{"jsonrpc":"2.0","result":true,"id":1}"#;
+	let res = tester.io.handle_request_sync(&request);
+	assert_eq!(res, Some(response.into()));
+
+	let request = format!(r#"{{"jsonrpc": "2.0", "method": "parity_setAccountFields", "params": ["0x{:x}", {{"role": "signer", "owner": "ops"}}], "id": 1}}"#, address);
+	let res = tester.io.handle_request_sync(&request);
+	assert_eq!(res, Some(response.into()));
+
+	let meta = tester.accounts.accounts_info().unwrap().get(&address).unwrap().meta.clone();
+	assert_eq!(meta, r#"{"description":"signer","fields":{"owner":"ops","role":"signer"},"tags":["ops","hot"]}"#);
+}
+
+#[test]
+fn should_not_set_tags_of_unknown_account() {
+	let tester = setup();
+
+	let request = r#"{"jsonrpc": "2.0", "method": "parity_setAccountTags", "params": ["0x0000000000000000000000000000000000000001", ["ops"]], "id": 1}"#;
+	let res = tester.io.handle_request_sync(&request).unwrap();
+	assert!(res.contains("Could not set account tags."));
+}
+
+#[test]
+fn should_filter_accounts_info_by_labels() {
+	let tester = setup();
+	let hot = tester.accounts.new_account(&"".into()).unwrap();
+	let cold = tester.accounts.new_account(&"".into()).unwrap();
+	tester.accounts.set_account_tags(hot, vec!["ops".into(), "hot".into()]).unwrap();
+	tester.accounts.set_account_tags(cold, vec!["ops".into()]).unwrap();
+	tester.accounts.set_account_fields(cold, vec![("role".to_owned(), "treasury".to_owned())].into_iter().collect()).unwrap();
+
+	let request = r#"{"jsonrpc": "2.0", "method": "parity_filterAccountsInfo", "params": [["ops"], {}], "id": 1}"#;
+	let res = tester.io.handle_request_sync(&request).unwrap();
+	assert!(res.contains(&format!("0x{:x}", hot)));
+	assert!(res.contains(&format!("0x{:x}", cold)));
+
+	let request = r#"{"jsonrpc": "2.0", "method": "parity_filterAccountsInfo", "params": [["hot"], {}], "id": 1}"#;
+	let res = tester.io.handle_request_sync(&request).unwrap();
+	assert!(res.contains(&format!("0x{:x}", hot)));
+	assert!(!res.contains(&format!("0x{:x}", cold)));
+
+	let request = r#"{"jsonrpc": "2.0", "method": "parity_filterAccountsInfo", "params": [[], {"role": "treasury"}], "id": 1}"#;
+	let res = tester.io.handle_request_sync(&request).unwrap();
+	assert!(!res.contains(&format!("0x{:x}", hot)));
+	assert!(res.contains(&format!("0x{:x}", cold)));
+}
+
 #[test]
 fn should_be_able_to_kill_account() {
 	let tester = setup();
@@ -20,7 +20,8 @@ use rustc_hex::FromHex;
 use ethereum_types::{U256, Address};
 
 use ethcore::miner::MinerService;
-use ethcore::client::TestBlockChainClient;
+use ethcore::client::{ChainInfo, TestBlockChainClient};
+use miner::pool::Quotas;
 use sync::ManageNetwork;
 use futures_cpupool::CpuPool;
 
@@ -195,6 +196,56 @@ fn rpc_parity_set_transactions_limit() {
 	assert_eq!(io.handle_request_sync(request), Some(response.to_owned()));
 }
 
+#[test]
+fn rpc_parity_set_transactions_replacement_bump() {
+	let miner = miner_service();
+	let client = client_service();
+	let network = network_service();
+	let updater = updater_service();
+	let mut io = IoHandler::new();
+	io.extend_with(parity_set_client(&client, &miner, &updater, &network).to_delegate());
+
+	let request = r#"{"jsonrpc": "2.0", "method": "parity_setTransactionsReplacementBump", "params":[12.5], "id": 1}"#;
+	let response = r#"{"jsonrpc":"2.0","result":true,"id":1}"#;
+
+	assert_eq!(io.handle_request_sync(request), Some(response.to_owned()));
+	assert_eq!(*miner.replacement_bump.read(), 1_250);
+
+	let request = r#"{"jsonrpc": "2.0", "method": "parity_setTransactionsReplacementBump", "params":[-1], "id": 2}"#;
+	let response = io.handle_request_sync(request).unwrap();
+
+	assert!(response.contains("\"code\":-32602"));
+	assert_eq!(*miner.replacement_bump.read(), 1_250);
+}
+
+#[test]
+fn rpc_parity_set_transactions_quotas() {
+	let miner = miner_service();
+	let client = client_service();
+	let network = network_service();
+	let updater = updater_service();
+	let mut io = IoHandler::new();
+	io.extend_with(parity_set_client(&client, &miner, &updater, &network).to_delegate());
+
+	let response = r#"{"jsonrpc":"2.0","result":true,"id":1}"#;
+	let request = r#"{"jsonrpc": "2.0", "method": "parity_setTransactionsPerSenderLimit", "params":[16], "id": 1}"#;
+	assert_eq!(io.handle_request_sync(request), Some(response.to_owned()));
+	let request = r#"{"jsonrpc": "2.0", "method": "parity_setTransactionsPerOriginLimit", "params":[64], "id": 1}"#;
+	assert_eq!(io.handle_request_sync(request), Some(response.to_owned()));
+	let request = r#"{"jsonrpc": "2.0", "method": "parity_setTransactionsMemoryLimit", "params":[1048576], "id": 1}"#;
+	assert_eq!(io.handle_request_sync(request), Some(response.to_owned()));
+
+	assert_eq!(*miner.queue_quotas.read(), Quotas {
+		max_per_sender: Some(16),
+		max_per_origin: Some(64),
+		max_mem_usage: Some(1_048_576),
+	});
+
+	let request = r#"{"jsonrpc": "2.0", "method": "parity_setTransactionsPerOriginLimit", "params":[0], "id": 1}"#;
+	assert_eq!(io.handle_request_sync(request), Some(response.to_owned()));
+	assert_eq!(miner.queue_quotas.read().max_per_origin, None);
+}
+
 #[test]
 fn rpc_parity_set_hash_content() {
 	let miner = miner_service();
@@ -210,6 +261,29 @@ fn rpc_parity_set_hash_content() {
 	assert_eq!(io.handle_request_sync(request), Some(response.to_owned()));
 }
 
+#[test]
+fn rpc_parity_pause_and_resume_import() {
+	use ethcore::client::BlockChainClient;
+
+	let miner = miner_service();
+	let client = client_service();
+	let network = network_service();
+	let updater = updater_service();
+
+	let mut io = IoHandler::new();
+	io.extend_with(parity_set_client(&client, &miner, &updater, &network).to_delegate());
+
+	let request = r#"{"jsonrpc": "2.0", "method": "parity_pauseImport", "params":[], "id": 1}"#;
+	let response = r#"{"jsonrpc":"2.0","result":true,"id":1}"#;
+	assert_eq!(io.handle_request_sync(request), Some(response.to_owned()));
+	assert!(client.is_import_paused());
+
+	let request = r#"{"jsonrpc": "2.0", "method": "parity_resumeImport", "params":[], "id": 1}"#;
+	let response = r#"{"jsonrpc":"2.0","result":true,"id":1}"#;
+	assert_eq!(io.handle_request_sync(request), Some(response.to_owned()));
+	assert!(!client.is_import_paused());
+}
+
 #[test]
 fn rpc_parity_remove_transaction() {
 	use transaction::{Transaction, Action};
@@ -238,3 +312,59 @@ fn rpc_parity_remove_transaction() {
 	miner.pending_transactions.lock().insert(hash, signed);
 	assert_eq!(io.handle_request_sync(&request), Some(response.to_owned()));
 }
+
+#[test]
+fn rpc_parity_block_template() {
+	use serde_json;
+
+	let miner = miner_service();
+	let client = client_service();
+	let network = network_service();
+	let updater = updater_service();
+	let mut io = IoHandler::new();
+	io.extend_with(parity_set_client(&client, &miner, &updater, &network).to_delegate());
+
+	let request = r#"{"jsonrpc": "2.0", "method": "parity_getBlockTemplate", "params": [], "id": 1}"#;
+	let response = r#"{"jsonrpc":"2.0","error":{"code":-32002,"message":"Author not configured. Run Parity with --author to configure."},"id":1}"#;
+	assert_eq!(io.handle_request_sync(request), Some(response.to_owned()));
+
+	let author = Address::from_str("cd1722f3947def4cf144679da39c4c32bdc35681").unwrap();
+	miner.set_author(author, None).unwrap();
+
+	let response: serde_json::Value = serde_json::from_str(&io.handle_request_sync(request).unwrap()).unwrap();
+	let template = &response["result"];
+	assert_eq!(template["number"], "0x1");
+	assert_eq!(template["author"], "0xcd1722f3947def4cf144679da39c4c32bdc35681");
+	assert_eq!(template["parentHash"], format!("0x{:x}", client.chain_info().best_block_hash));
+	assert_eq!(template["transactions"], serde_json::Value::Array(vec![]));
+}
+
+#[test]
+fn rpc_parity_submit_block_rejects_invalid_rlp() {
+	let miner = miner_service();
+	let client = client_service();
+	let network = network_service();
+	let updater = updater_service();
+	let mut io = IoHandler::new();
+	io.extend_with(parity_set_client(&client, &miner, &updater, &network).to_delegate());
+
+	let request = r#"{"jsonrpc": "2.0", "method": "parity_submitBlock", "params": ["0x01"], "id": 1}"#;
+	let response = io.handle_request_sync(request).unwrap();
+	assert!(response.contains(r#""code":-32602"#), "Unexpected response: {}", response);
+}
+
+#[test]
+fn rpc_parity_set_reserved_peers() {
+	let miner = miner_service();
+	let client = client_service();
+	let network = network_service();
+	let updater = updater_service();
+
+	let mut io = IoHandler::new();
+	io.extend_with(parity_set_client(&client, &miner, &updater, &network).to_delegate());
+
+	let request = r#"{"jsonrpc": "2.0", "method": "parity_setReservedPeers", "params":[["enode://a979fb575495b8d6db44f750317d0f4622bf4c2aa3365d6af7c284339968eef29b69ad0dce72a4d8db5ebb4968de0e3bec910127f134779fbcb0cb6d3331163c@22.99.55.44:7770"]], "id": 1}"#;
+	let response = r#"{"jsonrpc":"2.0","result":true,"id":1}"#;
+
+	assert_eq!(io.handle_request_sync(request), Some(response.to_owned()));
+}
@@ -20,6 +20,8 @@ use jsonrpc_core::{self as core, MetaIoHandler};
 use jsonrpc_core::futures::{self, Stream, Future};
 use jsonrpc_pubsub::Session;
 
+use ethcore::client::{ChainNotify, ChainReorg};
+use miner::pool::TransactionEvent;
 use parity_reactor::EventLoop;
 use v1::{PubSub, PubSubClient, Metadata};
 
@@ -75,3 +77,78 @@ fn should_subscribe_to_a_method() {
 	let (res, _receiver) = receiver.into_future().wait().unwrap();
 	assert_eq!(res, None);
 }
+
+#[test]
+fn should_subscribe_to_reorgs() {
+	// given
+	let el = EventLoop::spawn();
+	let pubsub = PubSubClient::new_test(rpc(), el.remote());
+	let handler = pubsub.reorg_handler().upgrade().unwrap();
+	let pubsub = pubsub.to_delegate();
+
+	let mut io = MetaIoHandler::default();
+	io.extend_with(pubsub);
+
+	let mut metadata = Metadata::default();
+	let (sender, receiver) = futures::sync::mpsc::channel(8);
+	metadata.session = Some(Arc::new(Session::new(sender)));
+
+	// Subscribe
+	let request = r#"{"jsonrpc": "2.0", "method": "parity_subscribe", "params": ["reorgs"], "id": 1}"#;
+	let response = r#"{"jsonrpc":"2.0","result":"0x416d77337e24399d","id":1}"#;
+	assert_eq!(io.handle_request_sync(request, metadata.clone()), Some(response.to_owned()));
+
+	// Check notifications
+	handler.reorg(ChainReorg {
+		common_ancestor: 1.into(),
+		common_ancestor_number: 1,
+		retracted: vec![2.into()],
+		enacted: vec![3.into()],
+	});
+	let (res, receiver) = receiver.into_future().wait().unwrap();
+	let response = r#"{"jsonrpc":"2.0","method":"parity_subscription","params":{"result":{"commonAncestor":"0x0000000000000000000000000000000000000000000000000000000000000001","commonAncestorNumber":"0x1","enacted":["0x0000000000000000000000000000000000000000000000000000000000000003"],"retracted":["0x0000000000000000000000000000000000000000000000000000000000000002"]},"subscription":"0x416d77337e24399d"}}"#;
+	assert_eq!(res, Some(response.into()));
+
+	// And unsubscribe
+	let request = r#"{"jsonrpc": "2.0", "method": "parity_unsubscribe", "params": ["0x416d77337e24399d"], "id": 1}"#;
+	let response = r#"{"jsonrpc":"2.0","result":true,"id":1}"#;
+	assert_eq!(io.handle_request_sync(request, metadata), Some(response.to_owned()));
+
+	let (res, _receiver) = receiver.into_future().wait().unwrap();
+	assert_eq!(res, None);
+}
+
+#[test]
+fn should_subscribe_to_transactions_pool() {
+	// given
+	let el = EventLoop::spawn();
+	let pubsub = PubSubClient::new_test(rpc(), el.remote());
+	let handler = pubsub.pool_handler().upgrade().unwrap();
+	let pubsub = pubsub.to_delegate();
+
+	let mut io = MetaIoHandler::default();
+	io.extend_with(pubsub);
+
+	let mut metadata = Metadata::default();
+	let (sender, receiver) = futures::sync::mpsc::channel(8);
+	metadata.session = Some(Arc::new(Session::new(sender)));
+
+	// Subscribe
+	let request = r#"{"jsonrpc": "2.0", "method": "parity_subscribe", "params": ["transactionsPool"], "id": 1}"#;
+	let response = r#"{"jsonrpc":"2.0","result":"0x416d77337e24399d","id":1}"#;
+	assert_eq!(io.handle_request_sync(request, metadata.clone()), Some(response.to_owned()));
+
+	// Check notifications
+	handler.notify(&1.into(), TransactionEvent::Dropped(Some(2.into())));
+	let (res, receiver) = receiver.into_future().wait().unwrap();
+	let response = r#"{"jsonrpc":"2.0","method":"parity_subscription","params":{"result":{"event":"dropped","hash":"0x0000000000000000000000000000000000000000000000000000000000000001","pushedOutBy":"0x0000000000000000000000000000000000000000000000000000000000000002","reason":"pool limit reached"},"subscription":"0x416d77337e24399d"}}"#;
+	assert_eq!(res, Some(response.into()));
+
+	// And unsubscribe
+	let request = r#"{"jsonrpc": "2.0", "method": "parity_unsubscribe", "params": ["0x416d77337e24399d"], "id": 1}"#;
+	let response = r#"{"jsonrpc":"2.0","result":true,"id":1}"#;
+	assert_eq!(io.handle_request_sync(request, metadata), Some(response.to_owned()));
+
+	let (res, _receiver) = receiver.into_future().wait().unwrap();
+	assert_eq!(res, None);
+}
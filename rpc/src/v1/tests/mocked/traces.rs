@@ -24,12 +24,17 @@ use ethcore::client::TestBlockChainClient;
 use vm::CallType;
 
 use jsonrpc_core::IoHandler;
+use jsonrpc_core::futures::{self, Future, Stream};
+use jsonrpc_pubsub::Session;
+use parity_reactor::EventLoop;
+use serde_json;
 use v1::tests::helpers::{TestMinerService};
 use v1::{Metadata, Traces, TracesClient};
 
 struct Tester {
 	client: Arc<TestBlockChainClient>,
 	_miner: Arc<TestMinerService>,
+	_el: EventLoop,
 	io: IoHandler<Metadata>,
 }
 
@@ -66,13 +71,15 @@ fn io() -> Tester {
 		state_diff: None,
 	}));
 	let miner = Arc::new(TestMinerService::default());
-	let traces = TracesClient::new(&client);
+	let el = EventLoop::spawn();
+	let traces = TracesClient::new(&client, el.remote());
 	let mut io = IoHandler::default();
 	io.extend_with(traces.to_delegate());
 
 	Tester {
 		client: client,
 		_miner: miner,
+		_el: el,
 		io: io,
 	}
 }
@@ -98,6 +105,30 @@ fn rpc_trace_filter_missing_trace() {
 	assert_eq!(tester.io.handle_request_sync(request), Some(response.to_owned()));
 }
 
+#[test]
+fn rpc_trace_subscribe_filter() {
+	// given
+	let tester = io();
+	let mut metadata = Metadata::default();
+	let (sender, receiver) = futures::sync::mpsc::channel(8);
+	metadata.session = Some(Arc::new(Session::new(sender)));
+
+	// when
+	let request = r#"{"jsonrpc":"2.0","method":"trace_subscribeFilter","params": [{"fromBlock":"0xa","toBlock":"0xa"}],"id":1}"#;
+	let response: serde_json::Value = serde_json::from_str(&(*tester.io).handle_request_sync(request, metadata.clone()).unwrap()).unwrap();
+	let id = response["result"].as_str().unwrap().to_owned();
+
+	// then
+	let trace = r#"{"action":{"callType":"call","from":"0x000000000000000000000000000000000000000f","gas":"0x100","input":"0x010203","to":"0x0000000000000000000000000000000000000010","value":"0x1"},"blockHash":"0x000000000000000000000000000000000000000000000000000000000000000a","blockNumber":10,"result":null,"subtraces":0,"traceAddress":[0],"transactionHash":"0x0000000000000000000000000000000000000000000000000000000000000005","transactionPosition":0,"type":"call"}"#;
+	let (res, receiver) = receiver.into_future().wait().unwrap();
+	assert_eq!(res, Some(format!(r#"{{"jsonrpc":"2.0","method":"trace_filterSubscription","params":{{"result":[{}],"subscription":"{}"}}}}"#, trace, id)));
+	let (res, _receiver) = receiver.into_future().wait().unwrap();
+	assert_eq!(res, Some(format!(r#"{{"jsonrpc":"2.0","method":"trace_filterSubscription","params":{{"result":[],"subscription":"{}"}}}}"#, id)));
+
+	let request = format!(r#"{{"jsonrpc":"2.0","method":"trace_unsubscribeFilter","params": ["{}"],"id":1}}"#, id);
+	assert_eq!((*tester.io).handle_request_sync(&request, metadata), Some(r#"{"jsonrpc":"2.0","result":true,"id":1}"#.to_owned()));
+}
+
 #[test]
 fn rpc_trace_block() {
 	let tester = io();
@@ -108,6 +139,20 @@ fn rpc_trace_block() {
 	assert_eq!(tester.io.handle_request_sync(request), Some(response.to_owned()));
 }
 
+#[test]
+fn rpc_trace_block_non_canonical() {
+	let tester = io();
+	let hash = "0x000000000000000000000000000000000000000000000000000000000000000a";
+
+	let request = format!(r#"{{"jsonrpc":"2.0","method":"trace_block","params": ["{}"],"id":1}}"#, hash);
+	let response = r#"{"jsonrpc":"2.0","result":null,"id":1}"#;
+	assert_eq!(tester.io.handle_request_sync(&request), Some(response.to_owned()));
+
+	let request = format!(r#"{{"jsonrpc":"2.0","method":"trace_block","params": ["{}", true],"id":1}}"#, hash);
+	let response = r#"{"jsonrpc":"2.0","result":[{"action":{"callType":"call","from":"0x000000000000000000000000000000000000000f","gas":"0x100","input":"0x010203","to":"0x0000000000000000000000000000000000000010","value":"0x1"},"blockHash":"0x000000000000000000000000000000000000000000000000000000000000000a","blockNumber":10,"canonical":false,"result":null,"subtraces":0,"traceAddress":[0],"transactionHash":"0x0000000000000000000000000000000000000000000000000000000000000005","transactionPosition":0,"type":"call"}],"id":1}"#;
+	assert_eq!(tester.io.handle_request_sync(&request), Some(response.to_owned()));
+}
+
 #[test]
 fn rpc_trace_block_missing_traces() {
 	let tester = io();
@@ -0,0 +1,78 @@
+// Copyright 2015-2018 Parity Technologies (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+use std::sync::Arc;
+
+use ethcore::client::TestBlockChainClient;
+use transaction::{Action, Transaction};
+
+use jsonrpc_core::IoHandler;
+use v1::{TxPool, TxPoolClient};
+use v1::tests::helpers::TestMinerService;
+
+fn io() -> (Arc<TestMinerService>, IoHandler) {
+	let client = Arc::new(TestBlockChainClient::new());
+	let miner = Arc::new(TestMinerService::default());
+	let mut io = IoHandler::new();
+	io.extend_with(TxPoolClient::new(client, miner.clone()).to_delegate());
+	(miner, io)
+}
+
+fn add_transaction(miner: &TestMinerService) {
+	let tx = Transaction {
+		nonce: 1.into(),
+		gas_price: 0x9184e72a000u64.into(),
+		gas: 0x76c0.into(),
+		action: Action::Call(5.into()),
+		value: 0x9184e72au64.into(),
+		data: vec![]
+	};
+	let signed = tx.fake_sign(2.into());
+	miner.pending_transactions.lock().insert(signed.hash(), signed);
+}
+
+#[test]
+fn rpc_txpool_content() {
+	let (miner, io) = io();
+	add_transaction(&miner);
+
+	let request = r#"{"jsonrpc": "2.0", "method": "txpool_content", "params": [], "id": 1}"#;
+	let response = r#"{"jsonrpc":"2.0","result":{"pending":{"0x0000000000000000000000000000000000000002":{"1":{"blockHash":null,"blockNumber":null,"chainId":null,"condition":null,"creates":null,"from":"0x0000000000000000000000000000000000000002","gas":"0x76c0","gasPrice":"0x9184e72a000","hash":"0xa2e0da8a8064e0b9f93e95a53c2db6d01280efb8ac72a708d25487e67dd0f8fc","input":"0x","nonce":"0x1","publicKey":null,"r":"0x1","raw":"0xe9018609184e72a0008276c0940000000000000000000000000000000000000005849184e72a80800101","s":"0x1","standardV":"0x4","to":"0x0000000000000000000000000000000000000005","transactionIndex":null,"v":"0x0","value":"0x9184e72a"}}},"queued":{}},"id":1}"#;
+
+	assert_eq!(io.handle_request_sync(request), Some(response.to_owned()));
+}
+
+#[test]
+fn rpc_txpool_inspect() {
+	let (miner, io) = io();
+	add_transaction(&miner);
+
+	let request = r#"{"jsonrpc": "2.0", "method": "txpool_inspect", "params": [], "id": 1}"#;
+	let response = r#"{"jsonrpc":"2.0","result":{"pending":{"0x0000000000000000000000000000000000000002":{"1":"0x0000000000000000000000000000000000000005: 2439964202 wei + 30400 gas × 10000000000000 wei"}},"queued":{}},"id":1}"#;
+
+	assert_eq!(io.handle_request_sync(request), Some(response.to_owned()));
+}
+
+#[test]
+fn rpc_txpool_status() {
+	let (miner, io) = io();
+	add_transaction(&miner);
+
+	let request = r#"{"jsonrpc": "2.0", "method": "txpool_status", "params": [], "id": 1}"#;
+	let response = r#"{"jsonrpc":"2.0","result":{"memory":"0x3e8","memoryLimit":"0x1388","pending":"0x1","queued":"0x0"},"id":1}"#;
+
+	assert_eq!(io.handle_request_sync(request), Some(response.to_owned()));
+}
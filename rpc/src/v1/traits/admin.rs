@@ -0,0 +1,42 @@
+// Copyright 2015-2018 Parity Technologies (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Geth-compatible admin RPC interface.
+
+use jsonrpc_core::Result;
+
+use v1::types::{NodeInfo, PeerInfo};
+
+build_rpc_trait! {
+	/// Peer management, following the semantics of geth's `admin` namespace.
+	pub trait Admin {
+		/// Adds given enode as a reserved peer the node keeps connected to.
+		#[rpc(name = "admin_addPeer")]
+		fn add_peer(&self, String) -> Result<bool>;
+
+		/// Removes given enode from the reserved peers.
+		#[rpc(name = "admin_removePeer")]
+		fn remove_peer(&self, String) -> Result<bool>;
+
+		/// Returns connected peers.
+		#[rpc(name = "admin_peers")]
+		fn peers(&self) -> Result<Vec<PeerInfo>>;
+
+		/// Returns information about the local node.
+		#[rpc(name = "admin_nodeInfo")]
+		fn node_info(&self) -> Result<NodeInfo>;
+	}
+}
@@ -17,8 +17,9 @@
 //! Debug RPC interface.
 
 use jsonrpc_core::Result;
+use jsonrpc_macros::Trailing;
 
-use v1::types::RichBlock;
+use v1::types::{BlockNumber, CallRequest, DebugStep, DebugTrace, H256, RichBlock, StructLog, StructLogOptions, U256};
 
 build_rpc_trait! {
 	/// Debug RPC interface.
@@ -26,5 +27,29 @@ build_rpc_trait! {
 		/// Returns recently seen bad blocks.
 		#[rpc(name = "debug_getBadBlocks")]
 		fn bad_blocks(&self) -> Result<Vec<RichBlock>>;
+
+		/// Executes the transaction with the given hash and returns its opcode-level struct logs,
+		/// or the output of the given tracer.
+		#[rpc(name = "debug_traceTransaction")]
+		fn trace_transaction(&self, H256, Trailing<StructLogOptions>) -> Result<DebugTrace>;
+
+		/// Executes the given call at the given block and returns its opcode-level struct logs,
+		/// or the output of the given tracer.
+		#[rpc(name = "debug_traceCall")]
+		fn trace_call(&self, CallRequest, BlockNumber, Trailing<StructLogOptions>) -> Result<DebugTrace>;
+
+		/// Starts executing the given call at the given block, pausing before the first instruction.
+		/// Returns the id of the debug session.
+		#[rpc(name = "parity_debugStartTransaction")]
+		fn debug_start_transaction(&self, CallRequest, Trailing<BlockNumber>) -> Result<U256>;
+
+		/// Executes the current instruction of a debug session and pauses before the next one.
+		/// Once the execution finishes its result is returned and the session is closed.
+		#[rpc(name = "parity_debugStep")]
+		fn debug_step(&self, U256) -> Result<DebugStep>;
+
+		/// Returns the stack, memory and storage of a debug session before its current instruction.
+		#[rpc(name = "parity_debugInspect")]
+		fn debug_inspect(&self, U256) -> Result<StructLog>;
 	}
 }
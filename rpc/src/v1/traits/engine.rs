@@ -0,0 +1,34 @@
+// Copyright 2015-2018 Parity Technologies (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+//! External consensus engine RPC interface.
+
+use jsonrpc_core::Result;
+
+use v1::types::{Bytes, EngineHead, PayloadStatus};
+
+build_rpc_trait! {
+	/// Interface used by an external consensus client to drive block import.
+	pub trait Engine {
+		/// Imports and executes an RLP-encoded block and returns the resulting chain head.
+		#[rpc(name = "engine_newBlock")]
+		fn new_block(&self, Bytes) -> Result<PayloadStatus>;
+
+		/// Returns the current head of the chain.
+		#[rpc(name = "engine_getHead")]
+		fn head(&self) -> Result<EngineHead>;
+	}
+}
@@ -15,12 +15,14 @@
 // along with Parity.  If not, see <http://www.gnu.org/licenses/>.
 
 //! Eth rpc interface.
-use jsonrpc_core::{Result, BoxFuture};
-use jsonrpc_macros::Trailing;
+use jsonrpc_core::{Result, BoxFuture, Error, Metadata, Params, Value};
+use jsonrpc_core::futures::Future;
+use jsonrpc_macros::{IoDelegate, Trailing};
+use serde_json;
 
-use v1::types::{RichBlock, BlockNumber, Bytes, CallRequest, Filter, FilterChanges, Index};
-use v1::types::{Log, Receipt, SyncStatus, Transaction, Work};
-use v1::types::{H64, H160, H256, U256};
+use v1::types::{RichBlock, BlockNumber, Bytes, BundleCall, BundleCallResult, CallRequest, Filter, FilterChanges, Index};
+use v1::types::{Log, Receipt, SyncStatus, Transaction, Work, EthAccount, FeeHistory};
+use v1::types::{H64, H160, H256, U256, StateOverride};
 
 build_rpc_trait! {
 	/// Eth rpc interface.
@@ -51,6 +53,14 @@ build_rpc_trait! {
 		#[rpc(name = "eth_gasPrice")]
 		fn gas_price(&self) -> Result<U256>;
 
+		/// Returns a suggested max priority fee per gas for EIP-1559 transactions.
+		#[rpc(name = "eth_maxPriorityFeePerGas")]
+		fn max_priority_fee_per_gas(&self) -> Result<U256>;
+
+		/// Returns base fees, gas used ratios and priority fee percentiles of a range of blocks.
+		#[rpc(name = "eth_feeHistory")]
+		fn fee_history(&self, U256, BlockNumber, Trailing<Vec<f64>>) -> BoxFuture<FeeHistory>;
+
 		/// Returns accounts list.
 		#[rpc(name = "eth_accounts")]
 		fn accounts(&self) -> Result<Vec<H160>>;
@@ -67,9 +77,15 @@ build_rpc_trait! {
 		#[rpc(name = "eth_getStorageAt")]
 		fn storage_at(&self, H160, U256, Trailing<BlockNumber>) -> BoxFuture<H256>;
 
+		/// Returns the account and storage values of the specified account including the Merkle proofs (EIP-1186).
+		#[rpc(name = "eth_getProof")]
+		fn proof(&self, H160, Vec<H256>, Trailing<BlockNumber>) -> BoxFuture<EthAccount>;
+
 		/// Returns block with given hash.
+		/// Blocks outside of the canonical chain are only returned if the optional flag is set,
+		/// in which case the block is marked with `canonical`.
 		#[rpc(name = "eth_getBlockByHash")]
-		fn block_by_hash(&self, H256, bool) -> BoxFuture<Option<RichBlock>>;
+		fn block_by_hash(&self, H256, bool, Trailing<bool>) -> BoxFuture<Option<RichBlock>>;
 
 		/// Returns block with given number.
 		#[rpc(name = "eth_getBlockByNumber")]
@@ -115,6 +131,11 @@ build_rpc_trait! {
 		#[rpc(name = "eth_estimateGas")]
 		fn estimate_gas(&self, CallRequest, Trailing<BlockNumber>) -> BoxFuture<U256>;
 
+		/// Executes calls and signed transactions one after another on top of the state of given block,
+		/// every call sees the changes made by the previous ones.
+		#[rpc(name = "eth_callMany")]
+		fn call_many(&self, Vec<BundleCall>, Trailing<BlockNumber>) -> BoxFuture<Vec<BundleCallResult>>;
+
 		/// Get transaction by its hash.
 		#[rpc(name = "eth_getTransactionByHash")]
 		fn transaction_by_hash(&self, H256) -> BoxFuture<Option<Transaction>>;
@@ -206,3 +227,45 @@ build_rpc_trait! {
 		fn uninstall_filter(&self, Index) -> Result<bool>;
 	}
 }
+
+/// `eth_call` and `eth_estimateGas` accepting a state override set as an optional third parameter.
+///
+/// `build_rpc_trait!` only supports a single trailing parameter, so these methods are registered
+/// by `extend_delegate`, replacing the two-parameter versions of `Eth`.
+pub trait EthCallOverride: Sized + Send + Sync + 'static {
+	/// Call contract with the given accounts overridden, returning the output data.
+	fn call_with_override(&self, CallRequest, BlockNumber, StateOverride) -> BoxFuture<Bytes>;
+
+	/// Estimate gas needed for execution of given contract with the given accounts overridden.
+	fn estimate_gas_with_override(&self, CallRequest, BlockNumber, StateOverride) -> BoxFuture<U256>;
+
+	/// Registers `eth_call` and `eth_estimateGas` in the given delegate.
+	fn extend_delegate<M: Metadata>(delegate: &mut IoDelegate<Self, M>) {
+		delegate.add_async_method("eth_call", |client: &Self, params: Params| {
+			let (request, num, overrides) = try_bf!(parse_call_params(params));
+			Box::new(client.call_with_override(request, num, overrides).map(to_value)) as BoxFuture<Value>
+		});
+		delegate.add_async_method("eth_estimateGas", |client: &Self, params: Params| {
+			let (request, num, overrides) = try_bf!(parse_call_params(params));
+			Box::new(client.estimate_gas_with_override(request, num, overrides).map(to_value)) as BoxFuture<Value>
+		});
+	}
+}
+
+fn to_value<T: ::serde::Serialize>(value: T) -> Value {
+	serde_json::to_value(value).expect("RPC types serialization is infallible; qed")
+}
+
+fn parse_call_params(params: Params) -> Result<(CallRequest, BlockNumber, StateOverride)> {
+	let len = match params {
+		Params::Array(ref values) => values.len(),
+		_ => 0,
+	};
+
+	match len {
+		1 => params.parse::<(CallRequest,)>().map(|(request,)| (request, BlockNumber::default(), StateOverride::default())),
+		2 => params.parse::<(CallRequest, BlockNumber)>().map(|(request, num)| (request, num, StateOverride::default())),
+		3 => params.parse::<(CallRequest, BlockNumber, StateOverride)>(),
+		_ => Err(Error::invalid_params("Expected 1, 2 or 3 parameters.")),
+	}
+}
@@ -16,7 +16,9 @@
 
 //! Ethereum rpc interfaces.
 
+pub mod admin;
 pub mod debug;
+pub mod engine;
 pub mod eth;
 pub mod eth_pubsub;
 pub mod eth_signing;
@@ -32,10 +34,13 @@ pub mod rpc;
 pub mod secretstore;
 pub mod signer;
 pub mod traces;
+pub mod txpool;
 pub mod web3;
 
+pub use self::admin::Admin;
 pub use self::debug::Debug;
-pub use self::eth::{Eth, EthFilter};
+pub use self::engine::Engine;
+pub use self::eth::{Eth, EthCallOverride, EthFilter};
 pub use self::eth_pubsub::EthPubSub;
 pub use self::eth_signing::EthSigning;
 pub use self::net::Net;
@@ -50,4 +55,5 @@ pub use self::rpc::Rpc;
 pub use self::secretstore::SecretStore;
 pub use self::signer::Signer;
 pub use self::traces::Traces;
+pub use self::txpool::TxPool;
 pub use self::web3::Web3;
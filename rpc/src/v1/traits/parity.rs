@@ -23,11 +23,12 @@ use jsonrpc_macros::Trailing;
 
 use v1::types::{
 	H160, H256, H512, U256, U64, Bytes, CallRequest,
-	Peers, Transaction, RpcSettings, Histogram,
-	TransactionStats, LocalTransactionStatus,
-	BlockNumber, ConsensusCapability, VersionInfo,
-	OperationsInfo, ChainStatus,
-	AccountInfo, HwAccountInfo, RichHeader, Receipt,
+	Peers, HandshakeFailure, Transaction, RpcSettings, Histogram,
+	TransactionStats, BlockPropagationStats, BlockAnnouncementStats, LocalTransactionStatus,
+	BlockNumber, BlockNumberOrHash, ConsensusCapability, VersionInfo,
+	OperationsInfo, ChainStatus, ImportQueueStatus, TransactionLifecycle,
+	AccountInfo, HwAccountInfo, RichHeader, Receipt, ThreadPoolInfo, StateDiff,
+	SignatureVerificationRequest, SignatureVerification, SyncProgress,
 };
 
 build_rpc_trait! {
@@ -87,6 +88,10 @@ build_rpc_trait! {
 		#[rpc(name = "parity_netPeers")]
 		fn net_peers(&self) -> Result<Peers>;
 
+		/// Returns recently failed handshakes with remote nodes, oldest first
+		#[rpc(name = "parity_netFailures")]
+		fn net_failures(&self) -> Result<Vec<HandshakeFailure>>;
+
 		/// Returns network port
 		#[rpc(name = "parity_netPort")]
 		fn net_port(&self) -> Result<u16>;
@@ -133,6 +138,17 @@ build_rpc_trait! {
 		#[rpc(name = "parity_listStorageKeys")]
 		fn list_storage_keys(&self, H160, u64, Option<H256>, Trailing<BlockNumber>) -> Result<Option<Vec<H256>>>;
 
+		/// Returns changes of accounts and storage between states after the first and the second block
+		/// if Fat DB is enabled (`--fat-db`), or null if not.
+		#[rpc(name = "parity_stateDiffBetween")]
+		fn state_diff_between(&self, BlockNumber, BlockNumber) -> Result<Option<StateDiff>>;
+
+		/// Re-executes the block and returns RLP-encoded witness: the parent header, recent block hashes
+		/// and all trie nodes and code the execution needs, so the block can be executed without the state database.
+		/// Returns null if the block or its parent state is not available.
+		#[rpc(name = "parity_blockWitness")]
+		fn block_witness(&self, BlockNumber) -> Result<Option<Bytes>>;
+
 		/// Encrypt some data with a public key under ECIES.
 		/// First parameter is the 512-byte destination public key, second is the message.
 		#[rpc(name = "parity_encryptMessage")]
@@ -142,6 +158,11 @@ build_rpc_trait! {
 		#[rpc(name = "parity_pendingTransactions")]
 		fn pending_transactions(&self, Trailing<usize>) -> Result<Vec<Transaction>>;
 
+		/// Returns the ordered list of candidate transactions used to build the current pending block
+		/// or null if the node is not sealing.
+		#[rpc(name = "parity_pendingBlockCandidates")]
+		fn pending_block_candidates(&self) -> Result<Option<Vec<Transaction>>>;
+
 		/// Returns all transactions from transaction queue.
 		///
 		/// Some of them might not be ready to be included in a block yet.
@@ -156,10 +177,23 @@ build_rpc_trait! {
 		#[rpc(name = "parity_pendingTransactionsStats")]
 		fn pending_transactions_stats(&self) -> Result<BTreeMap<H256, TransactionStats>>;
 
+		/// Returns when recently announced blocks were first seen, announced by each peer and imported.
+		#[rpc(name = "parity_blockPropagationStats")]
+		fn block_propagation_stats(&self) -> Result<BTreeMap<H256, BlockPropagationStats>>;
+
+		/// Returns counters of block announcements received from and sent to peers.
+		#[rpc(name = "parity_blockAnnouncementStats")]
+		fn block_announcement_stats(&self) -> Result<BlockAnnouncementStats>;
+
 		/// Returns a list of current and past local transactions with status details.
 		#[rpc(name = "parity_localTransactions")]
 		fn local_transactions(&self) -> Result<BTreeMap<H256, LocalTransactionStatus>>;
 
+		/// Returns what happened to a transaction since this node first saw it, if the
+		/// transaction lifecycle log is enabled and still holds the transaction.
+		#[rpc(name = "parity_transactionLifecycle")]
+		fn transaction_lifecycle(&self, H256) -> Result<Option<TransactionLifecycle>>;
+
 		/// Returns current WS Server interface and port or an error if ws server is disabled.
 		#[rpc(name = "parity_wsUrl")]
 		fn ws_url(&self) -> Result<String>;
@@ -202,10 +236,24 @@ build_rpc_trait! {
 		#[rpc(name = "parity_chainStatus")]
 		fn chain_status(&self) -> Result<ChainStatus>;
 
+		/// Get the progress of the sync: current stage, blocks or snapshot chunks, peers and estimated time
+		/// until the stage finishes. Subscribe with `parity_subscribe("syncing")` to be notified of changes.
+		#[rpc(name = "parity_syncProgress")]
+		fn sync_progress(&self) -> Result<SyncProgress>;
+
+		/// Get the status of the block import queue: number of blocks in each stage,
+		/// wait times of queued blocks and the block currently being executed.
+		#[rpc(name = "parity_importQueueStatus")]
+		fn import_queue_status(&self) -> Result<ImportQueueStatus>;
+
 		/// Get node kind info.
 		#[rpc(name = "parity_nodeKind")]
 		fn node_kind(&self) -> Result<::v1::types::NodeKind>;
 
+		/// Get sizes and utilization of the node thread pools.
+		#[rpc(name = "parity_threadStats")]
+		fn thread_stats(&self) -> Result<Vec<ThreadPoolInfo>>;
+
 		/// Get block header.
 		/// Same as `eth_getBlockByNumber` but without uncles and transactions.
 		#[rpc(name = "parity_getBlockHeaderByNumber")]
@@ -213,9 +261,10 @@ build_rpc_trait! {
 
 		/// Get block receipts.
 		/// Allows you to fetch receipts from the entire block at once.
+		/// The block is selected by number or by hash.
 		/// If no parameter is provided defaults to `latest`.
 		#[rpc(name = "parity_getBlockReceipts")]
-		fn block_receipts(&self, Trailing<BlockNumber>) -> BoxFuture<Vec<Receipt>>;
+		fn block_receipts(&self, Trailing<BlockNumberOrHash>) -> BoxFuture<Vec<Receipt>>;
 
 		/// Get IPFS CIDv0 given protobuf encoded bytes.
 		#[rpc(name = "parity_cidV0")]
@@ -224,5 +273,19 @@ build_rpc_trait! {
 		/// Call contract, returning the output data.
 		#[rpc(name = "parity_call")]
 		fn call(&self, Vec<CallRequest>, Trailing<BlockNumber>) -> Result<Vec<Bytes>>;
+
+		/// Verify `eth_sign` signatures of messages against their expected signers.
+		#[rpc(name = "parity_verifySignatures")]
+		fn verify_signatures(&self, Vec<SignatureVerificationRequest>) -> BoxFuture<Vec<SignatureVerification>>;
+
+		/// Replays the transaction and returns gas used by its nested calls
+		/// as folded stacks, ready to be rendered as a flamegraph.
+		#[rpc(name = "parity_gasFlame")]
+		fn gas_flame(&self, H256) -> Result<String>;
+
+		/// Replays all transactions of the block and returns gas used by their nested calls
+		/// as folded stacks, each rooted at the transaction hash.
+		#[rpc(name = "parity_blockGasFlame")]
+		fn block_gas_flame(&self, BlockNumber) -> Result<String>;
 	}
 }
@@ -72,6 +72,20 @@ build_rpc_trait! {
 		#[rpc(name = "parity_setAccountMeta")]
 		fn set_account_meta(&self, H160, String) -> Result<bool>;
 
+		/// Set an account's tags. Other metadata of the account is preserved.
+		#[rpc(name = "parity_setAccountTags")]
+		fn set_account_tags(&self, H160, Vec<String>) -> Result<bool>;
+
+		/// Set an account's structured fields (e.g. role, owner or rotation date).
+		/// Other metadata of the account is preserved.
+		#[rpc(name = "parity_setAccountFields")]
+		fn set_account_fields(&self, H160, BTreeMap<String, String>) -> Result<bool>;
+
+		/// Returns information of accounts having all the given tags and field values.
+		/// Arguments: `tags`, `fields`.
+		#[rpc(name = "parity_filterAccountsInfo")]
+		fn filter_accounts_info(&self, Vec<String>, BTreeMap<String, String>) -> Result<BTreeMap<H160, ExtAccountInfo>>;
+
 		/// Imports a number of Geth accounts, with the list provided as the argument.
 		#[rpc(name = "parity_importGethAccounts")]
 		fn import_geth_accounts(&self, Vec<H160>) -> Result<Vec<H160>>;
@@ -18,7 +18,7 @@
 
 use jsonrpc_core::{BoxFuture, Result};
 
-use v1::types::{Bytes, H160, H256, U256, ReleaseInfo, Transaction};
+use v1::types::{BlockTemplate, Bytes, H160, H256, U256, ReleaseInfo, Transaction};
 
 build_rpc_trait! {
 	/// Parity-specific rpc interface for operations altering the settings.
@@ -51,6 +51,23 @@ build_rpc_trait! {
 		#[rpc(name = "parity_setTransactionsLimit")]
 		fn set_transactions_limit(&self, usize) -> Result<bool>;
 
+		/// Sets the gas price increase (in percent) a transaction needs to replace a queued
+		/// transaction with the same sender and nonce.
+		#[rpc(name = "parity_setTransactionsReplacementBump")]
+		fn set_transactions_replacement_bump(&self, f64) -> Result<bool>;
+
+		/// Limits the number of queued transactions from a single sender. 0 restores the startup limit.
+		#[rpc(name = "parity_setTransactionsPerSenderLimit")]
+		fn set_transactions_per_sender_limit(&self, usize) -> Result<bool>;
+
+		/// Limits the number of queued transactions received from a single IP address. 0 removes the limit.
+		#[rpc(name = "parity_setTransactionsPerOriginLimit")]
+		fn set_transactions_per_origin_limit(&self, usize) -> Result<bool>;
+
+		/// Limits the memory (in bytes) used by queued transactions. 0 restores the startup limit.
+		#[rpc(name = "parity_setTransactionsMemoryLimit")]
+		fn set_transactions_memory_limit(&self, usize) -> Result<bool>;
+
 		/// Sets the maximum amount of gas a single transaction may consume.
 		#[rpc(name = "parity_setMaxTransactionGas")]
 		fn set_tx_gas_limit(&self, U256) -> Result<bool>;
@@ -63,6 +80,10 @@ build_rpc_trait! {
 		#[rpc(name = "parity_removeReservedPeer")]
 		fn remove_reserved_peer(&self, String) -> Result<bool>;
 
+		/// Replace all reserved peers with the given list. Connections to peers which stay reserved are kept.
+		#[rpc(name = "parity_setReservedPeers")]
+		fn set_reserved_peers(&self, Vec<String>) -> Result<bool>;
+
 		/// Drop all non-reserved peers.
 		#[rpc(name = "parity_dropNonReservedPeers")]
 		fn drop_non_reserved_peers(&self) -> Result<bool>;
@@ -91,6 +112,15 @@ build_rpc_trait! {
 		#[rpc(name = "parity_setChain")]
 		fn set_spec_name(&self, String) -> Result<bool>;
 
+		/// Pause block import and sealing for maintenance, keeping the network and RPC up.
+		/// Incoming blocks are queued and imported once `parity_resumeImport` is called.
+		#[rpc(name = "parity_pauseImport")]
+		fn pause_import(&self) -> Result<bool>;
+
+		/// Resume block import and sealing paused with `parity_pauseImport`.
+		#[rpc(name = "parity_resumeImport")]
+		fn resume_import(&self) -> Result<bool>;
+
 		/// Hash a file content under given URL.
 		#[rpc(name = "parity_hashContent")]
 		fn hash_content(&self, String) -> BoxFuture<H256>;
@@ -111,5 +141,21 @@ build_rpc_trait! {
 		/// Returns `true` when transaction was removed, `false` if it was not found.
 		#[rpc(name = "parity_removeTransaction")]
 		fn remove_transaction(&self, H256) -> Result<Option<Transaction>>;
+
+		/// Returns the block being prepared for sealing: its header fields, the selected transactions
+		/// and the state root after executing them.
+		/// The block can be sealed by an external block producer and submitted with `parity_submitBlockSeal`.
+		#[rpc(name = "parity_getBlockTemplate")]
+		fn block_template(&self) -> Result<BlockTemplate>;
+
+		/// Seals the block template of given hash with given RLP-encoded seal fields and imports it.
+		/// Returns the hash of the sealed block.
+		#[rpc(name = "parity_submitBlockSeal")]
+		fn submit_block_seal(&self, H256, Vec<Bytes>) -> Result<H256>;
+
+		/// Imports a RLP-encoded block assembled and sealed by an external block producer.
+		/// Returns the hash of the block.
+		#[rpc(name = "parity_submitBlock")]
+		fn submit_block(&self, Bytes) -> Result<H256>;
 	}
 }
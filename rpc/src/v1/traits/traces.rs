@@ -18,7 +18,9 @@
 
 use jsonrpc_core::Result;
 use jsonrpc_macros::Trailing;
-use v1::types::{TraceFilter, LocalizedTrace, BlockNumber, Index, CallRequest, Bytes, TraceResults, TraceResultsWithTransactionHash, H256, TraceOptions};
+use jsonrpc_macros::pubsub::Subscriber;
+use jsonrpc_pubsub::SubscriptionId;
+use v1::types::{TraceFilter, LocalizedTrace, BlockNumber, BlockNumberOrHash, Index, CallRequest, Bytes, TraceResults, TraceResultsWithTransactionHash, H256, TraceOptions};
 
 build_rpc_trait! {
 	/// Traces specific rpc interface.
@@ -38,8 +40,10 @@ build_rpc_trait! {
 		fn transaction_traces(&self, H256) -> Result<Option<Vec<LocalizedTrace>>>;
 
 		/// Returns all traces produced at given block.
+		/// Traces of blocks outside of the canonical chain are only returned if the optional flag is set,
+		/// in which case the traces are marked with `canonical`.
 		#[rpc(name = "trace_block")]
-		fn block_traces(&self, BlockNumber) -> Result<Option<Vec<LocalizedTrace>>>;
+		fn block_traces(&self, BlockNumberOrHash, Trailing<bool>) -> Result<Option<Vec<LocalizedTrace>>>;
 
 		/// Executes the given call and returns a number of possible traces for it.
 		#[rpc(name = "trace_call")]
@@ -60,5 +64,16 @@ build_rpc_trait! {
 		/// Executes all the transactions at the given block and returns a number of possible traces for each transaction.
 		#[rpc(name = "trace_replayBlockTransactions")]
 		fn replay_block_transactions(&self, BlockNumber, TraceOptions) ->  Result<Vec<TraceResultsWithTransactionHash>>;
+
+		#[pubsub(name = "trace_filterSubscription")] {
+			/// Streams traces matching given filter as the range is scanned, one notification per block.
+			/// An empty notification is sent once the whole range was scanned.
+			#[rpc(name = "trace_subscribeFilter")]
+			fn subscribe_filter(&self, Self::Metadata, Subscriber<Vec<LocalizedTrace>>, TraceFilter);
+
+			/// Stops streaming of traces.
+			#[rpc(name = "trace_unsubscribeFilter")]
+			fn unsubscribe_filter(&self, SubscriptionId) -> Result<bool>;
+		}
 	}
 }
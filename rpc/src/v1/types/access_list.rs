@@ -0,0 +1,68 @@
+// Copyright 2015-2018 Parity Technologies (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Access list of a typed transaction.
+
+use transaction;
+use v1::types::{H160, H256};
+
+/// Addresses and storage keys a transaction plans to access (EIP-2930).
+#[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct AccessListItem {
+	/// Accessed address
+	pub address: H160,
+	/// Accessed storage keys of the address
+	#[serde(rename="storageKeys")]
+	pub storage_keys: Vec<H256>,
+}
+
+impl From<transaction::AccessListItem> for AccessListItem {
+	fn from(item: transaction::AccessListItem) -> Self {
+		AccessListItem {
+			address: item.address.into(),
+			storage_keys: item.storage_keys.into_iter().map(Into::into).collect(),
+		}
+	}
+}
+
+impl Into<transaction::AccessListItem> for AccessListItem {
+	fn into(self) -> transaction::AccessListItem {
+		transaction::AccessListItem {
+			address: self.address.into(),
+			storage_keys: self.storage_keys.into_iter().map(Into::into).collect(),
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use serde_json;
+	use ethereum_types::{H160, H256};
+	use super::AccessListItem;
+
+	#[test]
+	fn access_list_item_deserialization() {
+		let s = r#"{"address":"0x0000000000000000000000000000000000000001","storageKeys":["0x0000000000000000000000000000000000000000000000000000000000000002"]}"#;
+		let deserialized: AccessListItem = serde_json::from_str(s).unwrap();
+
+		assert_eq!(deserialized, AccessListItem {
+			address: H160::from(1).into(),
+			storage_keys: vec![H256::from(2).into()],
+		});
+		assert_eq!(serde_json::to_string(&deserialized).unwrap(), s);
+	}
+}
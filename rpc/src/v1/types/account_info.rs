@@ -14,6 +14,8 @@
 // You should have received a copy of the GNU General Public License
 // along with Parity.  If not, see <http://www.gnu.org/licenses/>.
 
+use v1::types::{Bytes, H160, H256, U256};
+
 /// Account information.
 #[derive(Debug, Default, Clone, PartialEq, Serialize)]
 pub struct AccountInfo {
@@ -41,3 +43,34 @@ pub struct HwAccountInfo {
 	/// Device manufacturer.
 	pub manufacturer: String,
 }
+
+/// Account state with Merkle proofs (used by `eth_getProof`, EIP-1186).
+#[derive(Debug, Default, Clone, PartialEq, Serialize)]
+#[serde(rename_all="camelCase")]
+pub struct EthAccount {
+	/// Account address
+	pub address: H160,
+	/// Account balance
+	pub balance: U256,
+	/// Account nonce
+	pub nonce: U256,
+	/// Account code hash
+	pub code_hash: H256,
+	/// Account storage root
+	pub storage_hash: H256,
+	/// Trie nodes on the path from the state root to the account
+	pub account_proof: Vec<Bytes>,
+	/// Proofs of requested storage entries
+	pub storage_proof: Vec<StorageProof>,
+}
+
+/// Storage entry with a Merkle proof.
+#[derive(Debug, Default, Clone, PartialEq, Serialize)]
+pub struct StorageProof {
+	/// Storage key
+	pub key: U256,
+	/// Storage value
+	pub value: U256,
+	/// Trie nodes on the path from the storage root to the entry
+	pub proof: Vec<Bytes>,
+}
@@ -0,0 +1,44 @@
+// Copyright 2015-2018 Parity Technologies (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Types of the admin API.
+
+/// Information about the local node.
+#[derive(Debug, Serialize)]
+pub struct NodeInfo {
+	/// Public node id.
+	pub id: String,
+	/// Client version.
+	pub name: String,
+	/// Enode URL of the node.
+	pub enode: String,
+	/// Public IP address.
+	pub ip: String,
+	/// Network ports.
+	pub ports: NodePorts,
+	/// Address the node listens on for connections.
+	#[serde(rename = "listenAddr")]
+	pub listen_addr: String,
+}
+
+/// Network ports of the local node.
+#[derive(Debug, Serialize)]
+pub struct NodePorts {
+	/// UDP discovery port.
+	pub discovery: u16,
+	/// TCP listening port.
+	pub listener: u16,
+}
@@ -81,6 +81,12 @@ pub struct Block {
 	pub total_difficulty: Option<U256>,
 	/// Seal fields
 	pub seal_fields: Vec<Bytes>,
+	/// Base fee per gas (EIP-1559), omitted before the transition.
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub base_fee_per_gas: Option<U256>,
+	/// Whether the block is canonical, only present for queries allowing non-canonical blocks.
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub canonical: Option<bool>,
 	/// Uncles' hashes
 	pub uncles: Vec<H256>,
 	/// Transactions
@@ -126,6 +132,9 @@ pub struct Header {
 	pub difficulty: U256,
 	/// Seal fields
 	pub seal_fields: Vec<Bytes>,
+	/// Base fee per gas (EIP-1559), omitted before the transition.
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub base_fee_per_gas: Option<U256>,
 	/// Size in bytes
 	pub size: Option<U256>,
 }
@@ -158,6 +167,7 @@ impl<'a> From<&'a EthHeader> for Header {
 			seal_fields: h.view().decode_seal()
 				.expect("Client/Miner returns only valid headers. We only serialize headers from Client/Miner; qed")
 				.into_iter().map(Into::into).collect(),
+			base_fee_per_gas: None,
 		}
 	}
 }
@@ -240,6 +250,8 @@ mod tests {
 			difficulty: U256::default(),
 			total_difficulty: Some(U256::default()),
 			seal_fields: vec![Bytes::default(), Bytes::default()],
+			base_fee_per_gas: None,
+			canonical: None,
 			uncles: vec![],
 			transactions: BlockTransactions::Hashes(vec![].into()),
 			size: Some(69.into()),
@@ -278,6 +290,8 @@ mod tests {
 			difficulty: U256::default(),
 			total_difficulty: Some(U256::default()),
 			seal_fields: vec![Bytes::default(), Bytes::default()],
+			base_fee_per_gas: None,
+			canonical: None,
 			uncles: vec![],
 			transactions: BlockTransactions::Hashes(vec![].into()),
 			size: None,
@@ -315,6 +329,7 @@ mod tests {
 			timestamp: U256::default(),
 			difficulty: U256::default(),
 			seal_fields: vec![Bytes::default(), Bytes::default()],
+			base_fee_per_gas: None,
 			size: Some(69.into()),
 		};
 		let serialized_header = serde_json::to_string(&header).unwrap();
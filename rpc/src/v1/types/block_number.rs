@@ -18,6 +18,7 @@ use std::fmt;
 use serde::{Deserialize, Deserializer, Serialize, Serializer};
 use serde::de::{Error, Visitor};
 use ethcore::client::BlockId;
+use v1::types::H256;
 
 /// Represents rpc api block number param.
 #[derive(Debug, PartialEq, Clone, Hash, Eq)]
@@ -116,6 +117,61 @@ impl<'a> Visitor<'a> for BlockNumberVisitor {
 	}
 }
 
+/// Represents rpc api param selecting a block either by number or by hash.
+#[derive(Debug, PartialEq, Clone, Hash, Eq)]
+pub enum BlockNumberOrHash {
+	/// Block number or tag
+	Number(BlockNumber),
+	/// Block hash
+	Hash(H256),
+}
+
+impl Default for BlockNumberOrHash {
+	fn default() -> Self {
+		BlockNumberOrHash::Number(BlockNumber::default())
+	}
+}
+
+impl<'a> Deserialize<'a> for BlockNumberOrHash {
+	fn deserialize<D>(deserializer: D) -> Result<BlockNumberOrHash, D::Error> where D: Deserializer<'a> {
+		deserializer.deserialize_any(BlockNumberOrHashVisitor)
+	}
+}
+
+impl LightBlockNumber for BlockNumberOrHash {
+	fn to_block_id(self) -> BlockId {
+		match self {
+			BlockNumberOrHash::Number(number) => number.to_block_id(),
+			BlockNumberOrHash::Hash(hash) => BlockId::Hash(hash.into()),
+		}
+	}
+}
+
+struct BlockNumberOrHashVisitor;
+
+impl<'a> Visitor<'a> for BlockNumberOrHashVisitor {
+	type Value = BlockNumberOrHash;
+
+	fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+		write!(formatter, "a block number, a block hash or 'latest', 'earliest' or 'pending'")
+	}
+
+	fn visit_str<E>(self, value: &str) -> Result<Self::Value, E> where E: Error {
+		// A hash is the only 32-byte hex value, block numbers are never that long.
+		if value.len() == 66 && value.starts_with("0x") {
+			value[2..].parse().map(BlockNumberOrHash::Hash).map_err(|e| {
+				Error::custom(format!("Invalid block hash: {:?}", e))
+			})
+		} else {
+			BlockNumberVisitor.visit_str(value).map(BlockNumberOrHash::Number)
+		}
+	}
+
+	fn visit_string<E>(self, value: String) -> Result<Self::Value, E> where E: Error {
+		self.visit_str(value.as_ref())
+	}
+}
+
 /// Converts `BlockNumber` to `BlockId`, panics on `BlockNumber::Pending`
 pub fn block_number_to_id(number: BlockNumber) -> BlockId {
 	match number {
@@ -140,6 +196,17 @@ mod tests {
 		assert_eq!(deserialized, vec![BlockNumber::Num(10), BlockNumber::Latest, BlockNumber::Earliest, BlockNumber::Pending])
 	}
 
+	#[test]
+	fn block_number_or_hash_deserialization() {
+		let s = r#"["0xa", "latest", "0x0000000000000000000000000000000000000000000000000000000000000003"]"#;
+		let deserialized: Vec<BlockNumberOrHash> = serde_json::from_str(s).unwrap();
+		assert_eq!(deserialized, vec![
+			BlockNumberOrHash::Number(BlockNumber::Num(10)),
+			BlockNumberOrHash::Number(BlockNumber::Latest),
+			BlockNumberOrHash::Hash(3.into()),
+		])
+	}
+
 	#[test]
 	fn should_not_deserialize_decimal() {
 		let s = r#""10""#;
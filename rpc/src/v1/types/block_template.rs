@@ -0,0 +1,88 @@
+// Copyright 2015-2018 Parity Technologies (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Block template for external block producers.
+
+use ethcore::block::{ClosedBlock, IsBlock};
+use rlp;
+use v1::types::{Bytes, H160, H256, H2048, U256};
+
+/// Block assembled by the node and ready to be sealed by an external block producer.
+#[derive(Debug, Serialize)]
+#[serde(rename_all="camelCase")]
+pub struct BlockTemplate {
+	/// Hash of the block without the seal, used to submit the seal.
+	pub hash: H256,
+	/// Hash of the parent
+	pub parent_hash: H256,
+	/// Hash of the uncles
+	#[serde(rename="sha3Uncles")]
+	pub uncles_hash: H256,
+	/// Authors address
+	pub author: H160,
+	/// State root hash after executing the transactions
+	pub state_root: H256,
+	/// Transactions root hash
+	pub transactions_root: H256,
+	/// Transactions receipts root hash
+	pub receipts_root: H256,
+	/// Block number
+	pub number: U256,
+	/// Gas Used
+	pub gas_used: U256,
+	/// Gas Limit
+	pub gas_limit: U256,
+	/// Extra data
+	pub extra_data: Bytes,
+	/// Logs bloom
+	pub logs_bloom: H2048,
+	/// Timestamp
+	pub timestamp: U256,
+	/// Difficulty
+	pub difficulty: U256,
+	/// Base fee per gas (EIP-1559), omitted before the transition.
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub base_fee_per_gas: Option<U256>,
+	/// Raw selected transactions, in block order.
+	pub transactions: Vec<Bytes>,
+	/// RLP of the uncle headers.
+	pub uncles: Vec<Bytes>,
+}
+
+impl<'a> From<&'a ClosedBlock> for BlockTemplate {
+	fn from(block: &'a ClosedBlock) -> Self {
+		let header = block.header();
+		BlockTemplate {
+			hash: block.hash().into(),
+			parent_hash: (*header.parent_hash()).into(),
+			uncles_hash: (*header.uncles_hash()).into(),
+			author: (*header.author()).into(),
+			state_root: (*header.state_root()).into(),
+			transactions_root: (*header.transactions_root()).into(),
+			receipts_root: (*header.receipts_root()).into(),
+			number: header.number().into(),
+			gas_used: (*header.gas_used()).into(),
+			gas_limit: (*header.gas_limit()).into(),
+			extra_data: header.extra_data().clone().into(),
+			logs_bloom: (*header.log_bloom()).into(),
+			timestamp: header.timestamp().into(),
+			difficulty: (*header.difficulty()).into(),
+			base_fee_per_gas: header.base_fee().map(Into::into),
+			transactions: block.transactions().iter().map(|tx| tx.encode_raw().into()).collect(),
+			uncles: block.uncles().iter().map(|uncle| rlp::encode(uncle).into_vec().into()).collect(),
+		}
+	}
+}
@@ -0,0 +1,76 @@
+// Copyright 2015-2018 Parity Technologies (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Bundles of calls executed on top of each other.
+
+use v1::types::{Bytes, CallRequest, U256};
+
+/// Single call of a bundle.
+#[derive(Debug, PartialEq, Deserialize)]
+#[serde(untagged)]
+pub enum BundleCall {
+	/// Signed RLP-encoded transaction.
+	Raw(Bytes),
+	/// Unsigned call, executed like `eth_call`.
+	Call(CallRequest),
+}
+
+/// Result of a single call of a bundle.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BundleCallResult {
+	/// Output data, revert data if the call reverted.
+	pub output: Bytes,
+	/// Reason of the failure, if the call failed.
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub error: Option<String>,
+	/// Gas used by this call.
+	pub gas_used: U256,
+	/// Gas used by this call and all calls before it.
+	pub cumulative_gas_used: U256,
+}
+
+#[cfg(test)]
+mod tests {
+	use serde_json;
+	use v1::types::Bytes;
+	use super::{BundleCall, BundleCallResult};
+
+	#[test]
+	fn bundle_call_deserialization() {
+		let s = r#"["0xf86b", {"to": "0x0000000000000000000000000000000000000001", "data": "0x12"}]"#;
+		let deserialized: Vec<BundleCall> = serde_json::from_str(s).unwrap();
+
+		assert_eq!(deserialized[0], BundleCall::Raw(Bytes::new(vec![0xf8, 0x6b])));
+		match deserialized[1] {
+			BundleCall::Call(ref request) => assert_eq!(request.data, Some(Bytes::new(vec![0x12]))),
+			_ => panic!("expected call request"),
+		}
+	}
+
+	#[test]
+	fn bundle_call_result_serialization() {
+		let result = BundleCallResult {
+			output: Bytes::new(vec![]),
+			error: Some("Reverted".into()),
+			gas_used: 21_000.into(),
+			cumulative_gas_used: 42_000.into(),
+		};
+
+		let serialized = serde_json::to_string(&result).unwrap();
+		assert_eq!(serialized, r#"{"output":"0x","error":"Reverted","gasUsed":"0x5208","cumulativeGasUsed":"0xa410"}"#);
+	}
+}
@@ -0,0 +1,65 @@
+// Copyright 2015-2018 Parity Technologies (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Canonical chain reorganization.
+
+use ethcore::client::ChainReorg as EthChainReorg;
+use v1::types::{H256, U256};
+
+/// Change of the canonical chain.
+#[derive(Debug, Serialize, PartialEq)]
+#[serde(rename_all="camelCase")]
+pub struct ChainReorg {
+	/// Hash of the last block shared by the old and the new canonical chain.
+	pub common_ancestor: H256,
+	/// Number of the common ancestor.
+	pub common_ancestor_number: U256,
+	/// Hashes of blocks removed from the canonical chain, starting right after the common ancestor.
+	pub retracted: Vec<H256>,
+	/// Hashes of blocks added to the canonical chain, starting right after the common ancestor.
+	pub enacted: Vec<H256>,
+}
+
+impl From<EthChainReorg> for ChainReorg {
+	fn from(reorg: EthChainReorg) -> Self {
+		ChainReorg {
+			common_ancestor: reorg.common_ancestor.into(),
+			common_ancestor_number: reorg.common_ancestor_number.into(),
+			retracted: reorg.retracted.into_iter().map(Into::into).collect(),
+			enacted: reorg.enacted.into_iter().map(Into::into).collect(),
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use serde_json;
+	use ethcore::client::ChainReorg as EthChainReorg;
+	use super::ChainReorg;
+
+	#[test]
+	fn chain_reorg_serialization() {
+		let reorg: ChainReorg = EthChainReorg {
+			common_ancestor: 1.into(),
+			common_ancestor_number: 16,
+			retracted: vec![2.into()],
+			enacted: vec![3.into(), 4.into()],
+		}.into();
+
+		let serialized = serde_json::to_string(&reorg).unwrap();
+		assert_eq!(serialized, r#"{"commonAncestor":"0x0000000000000000000000000000000000000000000000000000000000000001","commonAncestorNumber":"0x10","retracted":["0x0000000000000000000000000000000000000000000000000000000000000002"],"enacted":["0x0000000000000000000000000000000000000000000000000000000000000003","0x0000000000000000000000000000000000000000000000000000000000000004"]}"#);
+	}
+}
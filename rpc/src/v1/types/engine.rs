@@ -0,0 +1,87 @@
+// Copyright 2015-2018 Parity Technologies (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Types of the external consensus engine API.
+
+use v1::types::{H256, U256};
+
+/// Head of the chain as seen by the execution engine.
+#[derive(Debug, Default, Serialize)]
+#[serde(rename_all="camelCase")]
+pub struct EngineHead {
+	/// Number of the best block.
+	pub number: U256,
+	/// Hash of the best block.
+	pub hash: H256,
+	/// State root of the best block.
+	pub state_root: H256,
+}
+
+/// Outcome of importing a block submitted by the consensus driver.
+#[derive(Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all="lowercase")]
+pub enum PayloadState {
+	/// Block was executed and is now part of the chain.
+	Valid,
+	/// Block failed verification or execution.
+	Invalid,
+	/// Block was already imported before.
+	Known,
+	/// Block was accepted, but is still waiting in the import queue.
+	Accepted,
+}
+
+/// Result of `engine_newBlock`.
+#[derive(Debug, Serialize)]
+#[serde(rename_all="camelCase")]
+pub struct PayloadStatus {
+	/// Import outcome.
+	pub status: PayloadState,
+	/// Hash of the submitted block.
+	pub block_hash: H256,
+	/// Chain head after the import.
+	pub head: EngineHead,
+	/// Reason the block was rejected.
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub error: Option<String>,
+}
+
+#[cfg(test)]
+mod tests {
+	use serde_json;
+	use super::{EngineHead, PayloadState, PayloadStatus};
+
+	#[test]
+	fn payload_status_serialization() {
+		let status = PayloadStatus {
+			status: PayloadState::Valid,
+			block_hash: 5.into(),
+			head: EngineHead {
+				number: 1.into(),
+				hash: 5.into(),
+				state_root: 6.into(),
+			},
+			error: None,
+		};
+		let serialized = serde_json::to_string(&status).unwrap();
+		assert_eq!(serialized, r#"{"status":"valid","blockHash":"0x0000000000000000000000000000000000000000000000000000000000000005","head":{"number":"0x1","hash":"0x0000000000000000000000000000000000000000000000000000000000000005","stateRoot":"0x0000000000000000000000000000000000000000000000000000000000000006"}}"#);
+
+		let status = PayloadStatus { status: PayloadState::Invalid, error: Some("bad".into()), ..status };
+		let serialized = serde_json::to_string(&status).unwrap();
+		assert!(serialized.starts_with(r#"{"status":"invalid","#));
+		assert!(serialized.ends_with(r#","error":"bad"}"#));
+	}
+}
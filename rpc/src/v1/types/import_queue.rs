@@ -0,0 +1,132 @@
+// Copyright 2015-2018 Parity Technologies (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Block import queue status.
+
+use std::time::Duration;
+use ethcore::client::ImportQueueStatus as EthImportQueueStatus;
+use ethcore::verification::queue::{QueueStage as EthQueueStage, QueuedItem as EthQueuedItem};
+use v1::types::H256;
+
+/// Stage of a queued block.
+#[derive(Debug, Serialize, PartialEq)]
+#[serde(rename_all="camelCase")]
+pub enum QueueStage {
+	/// Pending verification.
+	Unverified,
+	/// Being verified.
+	Verifying,
+	/// Verified, pending import.
+	Verified,
+}
+
+impl From<EthQueueStage> for QueueStage {
+	fn from(stage: EthQueueStage) -> Self {
+		match stage {
+			EthQueueStage::Unverified => QueueStage::Unverified,
+			EthQueueStage::Verifying => QueueStage::Verifying,
+			EthQueueStage::Verified => QueueStage::Verified,
+		}
+	}
+}
+
+/// A block waiting in the import queue.
+#[derive(Debug, Serialize)]
+#[serde(rename_all="camelCase")]
+pub struct QueuedBlock {
+	/// Block hash.
+	pub hash: H256,
+	/// Stage the block is currently in.
+	pub stage: QueueStage,
+	/// Milliseconds since the block was queued.
+	pub wait_time: u64,
+}
+
+fn as_millis(duration: Duration) -> u64 {
+	duration.as_secs() * 1000 + (duration.subsec_nanos() / 1_000_000) as u64
+}
+
+impl From<EthQueuedItem> for QueuedBlock {
+	fn from(item: EthQueuedItem) -> Self {
+		QueuedBlock {
+			hash: item.hash.into(),
+			stage: item.stage.into(),
+			wait_time: as_millis(item.wait_time),
+		}
+	}
+}
+
+/// Block import queue status.
+#[derive(Debug, Serialize)]
+#[serde(rename_all="camelCase")]
+pub struct ImportQueueStatus {
+	/// Number of blocks pending verification.
+	pub unverified: usize,
+	/// Number of blocks being verified.
+	pub verifying: usize,
+	/// Number of verified blocks pending import.
+	pub verified: usize,
+	/// Hash of the block currently being executed.
+	pub executing: Option<H256>,
+	/// Queued blocks, longest waiting first.
+	pub blocks: Vec<QueuedBlock>,
+}
+
+impl From<EthImportQueueStatus> for ImportQueueStatus {
+	fn from(status: EthImportQueueStatus) -> Self {
+		ImportQueueStatus {
+			unverified: status.info.unverified_queue_size,
+			verifying: status.info.verifying_queue_size,
+			verified: status.info.verified_queue_size,
+			executing: status.executing.map(Into::into),
+			blocks: status.items.into_iter().map(Into::into).collect(),
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use std::time::Duration;
+	use serde_json;
+	use ethcore::client::ImportQueueStatus as EthImportQueueStatus;
+	use ethcore::verification::queue::{QueueInfo, QueueStage, QueuedItem};
+	use super::ImportQueueStatus;
+
+	#[test]
+	fn import_queue_status_serialization() {
+		let status: ImportQueueStatus = EthImportQueueStatus {
+			info: QueueInfo {
+				unverified_queue_size: 1,
+				verifying_queue_size: 0,
+				verified_queue_size: 2,
+				max_queue_size: 0,
+				max_mem_use: 0,
+				mem_used: 0,
+			},
+			items: vec![QueuedItem {
+				hash: 5.into(),
+				stage: QueueStage::Verifying,
+				wait_time: Duration::from_millis(1500),
+			}],
+			executing: Some(1.into()),
+		}.into();
+
+		assert_eq!(
+			serde_json::to_string(&status).unwrap(),
+			r#"{"unverified":1,"verifying":0,"verified":2,"executing":"0x0000000000000000000000000000000000000000000000000000000000000001","blocks":[{"hash":"0x0000000000000000000000000000000000000000000000000000000000000005","stage":"verifying","waitTime":1500}]}"#
+		);
+	}
+}
@@ -16,66 +16,97 @@
 
 //! RPC types
 
+mod access_list;
+mod admin;
 mod account_info;
 mod block;
 mod block_number;
+mod block_template;
 mod bytes;
+mod call_bundle;
 mod call_request;
+mod chain_reorg;
 mod confirmations;
 mod consensus_status;
 mod derivation;
+mod engine;
+mod fee_history;
 mod filter;
 mod hash;
 mod histogram;
+mod import_queue;
 mod index;
 mod log;
 mod node_kind;
+mod pool_event;
 mod provenance;
 mod receipt;
 mod rpc_settings;
 mod secretstore;
+mod signature_verification;
+mod state_override;
+mod struct_log;
 mod sync;
+mod thread_pool;
 mod trace;
 mod trace_filter;
 mod transaction;
+mod transaction_lifecycle;
 mod transaction_request;
 mod transaction_condition;
+mod txpool;
 mod uint;
 mod work;
 mod private_receipt;
 
 pub mod pubsub;
 
-pub use self::account_info::{AccountInfo, ExtAccountInfo, HwAccountInfo};
+pub use self::access_list::AccessListItem;
+pub use self::admin::{NodeInfo, NodePorts};
+pub use self::account_info::{AccountInfo, ExtAccountInfo, HwAccountInfo, EthAccount, StorageProof};
 pub use self::bytes::Bytes;
 pub use self::block::{RichBlock, Block, BlockTransactions, Header, RichHeader, Rich};
-pub use self::block_number::{BlockNumber, LightBlockNumber, block_number_to_id};
+pub use self::block_number::{BlockNumber, BlockNumberOrHash, LightBlockNumber, block_number_to_id};
+pub use self::block_template::BlockTemplate;
+pub use self::call_bundle::{BundleCall, BundleCallResult};
 pub use self::call_request::CallRequest;
+pub use self::chain_reorg::ChainReorg;
 pub use self::confirmations::{
 	ConfirmationPayload, ConfirmationRequest, ConfirmationResponse, ConfirmationResponseWithToken,
 	TransactionModification, SignRequest, DecryptRequest, Either
 };
 pub use self::consensus_status::*;
 pub use self::derivation::{DeriveHash, DeriveHierarchical, Derive};
+pub use self::engine::{EngineHead, PayloadState, PayloadStatus};
+pub use self::fee_history::FeeHistory;
 pub use self::filter::{Filter, FilterChanges};
 pub use self::hash::{H64, H160, H256, H512, H520, H2048};
 pub use self::histogram::Histogram;
 pub use self::index::Index;
 pub use self::log::Log;
 pub use self::node_kind::{NodeKind, Availability, Capability};
+pub use self::pool_event::TransactionPoolEvent;
 pub use self::provenance::Origin;
 pub use self::receipt::Receipt;
 pub use self::rpc_settings::RpcSettings;
 pub use self::secretstore::EncryptedDocumentKey;
+pub use self::signature_verification::{SignatureVerificationRequest, SignatureVerification};
+pub use self::state_override::{AccountOverride, StateOverride, into_state_override};
+pub use self::struct_log::{DebugStep, DebugTrace, StructLog, StructLogOptions, StructLogTrace};
+pub use self::sync::traffic_by_protocol;
 pub use self::sync::{
-	SyncStatus, SyncInfo, Peers, PeerInfo, PeerNetworkInfo, PeerProtocolsInfo,
-	TransactionStats, ChainStatus, EthProtocolInfo, PipProtocolInfo,
+	SyncStatus, SyncInfo, SyncStage, SyncProgress, Peers, PeerInfo, PeerNetworkInfo, PeerProtocolsInfo, HandshakeFailure, Traffic,
+	TransactionStats, BlockPropagationStats, BlockAnnouncementStats, ChainStatus, TimestampDrift, EthProtocolInfo, PipProtocolInfo,
 };
-pub use self::trace::{LocalizedTrace, TraceResults, TraceResultsWithTransactionHash};
+pub use self::import_queue::{ImportQueueStatus, QueuedBlock, QueueStage};
+pub use self::transaction_lifecycle::{TransactionLifecycle, LifecycleEntry};
+pub use self::thread_pool::ThreadPoolInfo;
+pub use self::trace::{LocalizedTrace, TraceResults, TraceResultsWithTransactionHash, StateDiff};
 pub use self::trace_filter::TraceFilter;
 pub use self::transaction::{Transaction, RichRawTransaction, LocalTransactionStatus};
 pub use self::transaction_request::TransactionRequest;
 pub use self::transaction_condition::TransactionCondition;
+pub use self::txpool::{TxPoolContent, TxPoolStatus};
 pub use self::uint::{U128, U256, U64};
 pub use self::work::Work;
 pub use self::private_receipt::{PrivateTransactionReceipt, PrivateTransactionReceiptAndTransaction};
@@ -0,0 +1,157 @@
+// Copyright 2015-2018 Parity Technologies (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Transaction pool events.
+
+use serde::{Serialize, Serializer};
+use serde::ser::SerializeStruct;
+use ethereum_types::H256 as EthH256;
+use miner::pool::TransactionEvent;
+use v1::types::{H256, U256};
+
+/// Change of transaction status within the pool, as delivered to `transactionsPool` subscribers.
+#[derive(Debug, PartialEq)]
+pub enum TransactionPoolEvent {
+	/// Added to the pool.
+	Added(H256),
+	/// Never accepted to the pool.
+	Rejected(H256, String),
+	/// Replaced by another transaction with the same sender and nonce.
+	Replaced(H256, H256),
+	/// Dropped because of pool limits, optionally pushed out by another transaction.
+	Dropped(H256, Option<H256>),
+	/// Marked as invalid when building a block.
+	Invalid(H256),
+	/// Canceled by the user.
+	Canceled(H256),
+	/// Removed because its nonce became stale.
+	Culled(H256),
+	/// Removed because it got included in an imported block.
+	Mined(H256),
+	/// Removed after falling below a raised minimal gas price.
+	Demoted(H256, U256),
+}
+
+impl Serialize for TransactionPoolEvent {
+	fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+		where S: Serializer
+	{
+		use self::TransactionPoolEvent::*;
+
+		let elems = match *self {
+			Added(..) | Rejected(..) | Invalid(..) | Canceled(..) | Culled(..) | Mined(..) => 3,
+			Replaced(..) | Dropped(..) | Demoted(..) => 4,
+		};
+
+		let event = "event";
+		let hash = "hash";
+		let reason = "reason";
+
+		let mut struc = serializer.serialize_struct("TransactionPoolEvent", elems)?;
+		match *self {
+			Added(ref tx) => {
+				struc.serialize_field(event, "added")?;
+				struc.serialize_field(hash, tx)?;
+				struc.serialize_field(reason, "accepted to the pool")?;
+			},
+			Rejected(ref tx, ref error) => {
+				struc.serialize_field(event, "rejected")?;
+				struc.serialize_field(hash, tx)?;
+				struc.serialize_field(reason, error)?;
+			},
+			Replaced(ref tx, ref by) => {
+				struc.serialize_field(event, "replaced")?;
+				struc.serialize_field(hash, tx)?;
+				struc.serialize_field(reason, "replaced by a transaction with the same nonce and higher gas price")?;
+				struc.serialize_field("replacedBy", by)?;
+			},
+			Dropped(ref tx, ref by) => {
+				struc.serialize_field(event, "dropped")?;
+				struc.serialize_field(hash, tx)?;
+				struc.serialize_field(reason, "pool limit reached")?;
+				struc.serialize_field("pushedOutBy", by)?;
+			},
+			Invalid(ref tx) => {
+				struc.serialize_field(event, "dropped")?;
+				struc.serialize_field(hash, tx)?;
+				struc.serialize_field(reason, "invalid when building a block")?;
+			},
+			Canceled(ref tx) => {
+				struc.serialize_field(event, "dropped")?;
+				struc.serialize_field(hash, tx)?;
+				struc.serialize_field(reason, "canceled by the user")?;
+			},
+			Culled(ref tx) => {
+				struc.serialize_field(event, "culled")?;
+				struc.serialize_field(hash, tx)?;
+				struc.serialize_field(reason, "nonce already used")?;
+			},
+			Mined(ref tx) => {
+				struc.serialize_field(event, "mined")?;
+				struc.serialize_field(hash, tx)?;
+				struc.serialize_field(reason, "included in a block")?;
+			},
+			Demoted(ref tx, ref gas_price) => {
+				struc.serialize_field(event, "dropped")?;
+				struc.serialize_field(hash, tx)?;
+				struc.serialize_field(reason, "gas price below the raised minimal gas price")?;
+				struc.serialize_field("minimalGasPrice", gas_price)?;
+			},
+		}
+
+		struc.end()
+	}
+}
+
+impl<'a> From<(&'a EthH256, TransactionEvent)> for TransactionPoolEvent {
+	fn from((hash, event): (&'a EthH256, TransactionEvent)) -> Self {
+		let tx = (*hash).into();
+		match event {
+			TransactionEvent::Added => TransactionPoolEvent::Added(tx),
+			TransactionEvent::Rejected(reason) => TransactionPoolEvent::Rejected(tx, reason),
+			TransactionEvent::Replaced(by) => TransactionPoolEvent::Replaced(tx, by.into()),
+			TransactionEvent::Dropped(by) => TransactionPoolEvent::Dropped(tx, by.map(Into::into)),
+			TransactionEvent::Invalid => TransactionPoolEvent::Invalid(tx),
+			TransactionEvent::Canceled => TransactionPoolEvent::Canceled(tx),
+			TransactionEvent::Culled => TransactionPoolEvent::Culled(tx),
+			TransactionEvent::Mined => TransactionPoolEvent::Mined(tx),
+			TransactionEvent::Demoted(gas_price) => TransactionPoolEvent::Demoted(tx, gas_price.into()),
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use serde_json;
+	use ethereum_types::H256;
+	use miner::pool::TransactionEvent;
+	use super::TransactionPoolEvent;
+
+	#[test]
+	fn pool_event_serialization() {
+		let replaced = TransactionPoolEvent::from((&H256::from(1), TransactionEvent::Replaced(2.into())));
+		let mined = TransactionPoolEvent::from((&H256::from(3), TransactionEvent::Mined));
+
+		assert_eq!(
+			serde_json::to_string(&replaced).unwrap(),
+			r#"{"event":"replaced","hash":"0x0000000000000000000000000000000000000000000000000000000000000001","reason":"replaced by a transaction with the same nonce and higher gas price","replacedBy":"0x0000000000000000000000000000000000000000000000000000000000000002"}"#
+		);
+		assert_eq!(
+			serde_json::to_string(&mined).unwrap(),
+			r#"{"event":"mined","hash":"0x0000000000000000000000000000000000000000000000000000000000000003","reason":"included in a block"}"#
+		);
+	}
+}
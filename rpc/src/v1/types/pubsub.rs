@@ -19,7 +19,9 @@
 use serde::{Deserialize, Deserializer, Serialize, Serializer};
 use serde::de::Error;
 use serde_json::{Value, from_value};
-use v1::types::{RichHeader, Filter, Log, H256};
+use ethereum_types::{Address, U256 as EthU256};
+use transaction::{Action, SignedTransaction};
+use v1::types::{RichHeader, Filter, Log, Bytes, H160, H256, U256};
 
 /// Subscription result.
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -28,6 +30,8 @@ pub enum Result {
 	Header(RichHeader),
 	/// Log
 	Log(Log),
+	/// Batch of logs
+	Logs(Vec<Log>),
 	/// Transaction hash
 	TransactionHash(H256),
 }
@@ -39,6 +43,7 @@ impl Serialize for Result {
 		match *self {
 			Result::Header(ref header) => header.serialize(serializer),
 			Result::Log(ref log) => log.serialize(serializer),
+			Result::Logs(ref logs) => logs.serialize(serializer),
 			Result::TransactionHash(ref hash) => hash.serialize(serializer),
 		}
 	}
@@ -62,6 +67,53 @@ pub enum Kind {
 	Syncing,
 }
 
+/// Pending transactions subscription filter.
+#[derive(Debug, Default, Deserialize, PartialEq, Eq, Hash, Clone)]
+#[serde(deny_unknown_fields)]
+pub struct TransactionFilter {
+	/// Sender of the transaction.
+	pub from: Option<H160>,
+	/// Recipient of the transaction.
+	pub to: Option<H160>,
+	/// Minimal gas price.
+	#[serde(rename="minGasPrice")]
+	pub min_gas_price: Option<U256>,
+	/// Method selector (first four bytes of the transaction data).
+	pub selector: Option<Bytes>,
+}
+
+impl TransactionFilter {
+	/// Returns true if given transaction matches the filter.
+	pub fn matches(&self, tx: &SignedTransaction) -> bool {
+		if let Some(ref from) = self.from {
+			let from: Address = from.clone().into();
+			if from != tx.sender() {
+				return false;
+			}
+		}
+
+		if let Some(ref to) = self.to {
+			let to: Address = to.clone().into();
+			match tx.action {
+				Action::Call(ref address) if *address == to => {},
+				_ => return false,
+			}
+		}
+
+		if let Some(min_gas_price) = self.min_gas_price {
+			let min_gas_price: EthU256 = min_gas_price.into();
+			if tx.gas_price < min_gas_price {
+				return false;
+			}
+		}
+
+		match self.selector {
+			Some(ref selector) => tx.data.starts_with(&selector.0),
+			None => true,
+		}
+	}
+}
+
 /// Subscription kind.
 #[derive(Debug, PartialEq, Eq, Hash, Clone)]
 pub enum Params {
@@ -69,6 +121,8 @@ pub enum Params {
 	None,
 	/// Log parameters.
 	Logs(Filter),
+	/// Pending transactions parameters.
+	Transactions(TransactionFilter),
 }
 
 impl Default for Params {
@@ -87,6 +141,7 @@ impl<'a> Deserialize<'a> for Params {
 		}
 
 		from_value(v.clone()).map(Params::Logs)
+			.or_else(|_| from_value(v).map(Params::Transactions))
 			.map_err(|e| D::Error::custom(format!("Invalid Pub-Sub parameters: {}", e)))
 	}
 }
@@ -94,7 +149,7 @@ impl<'a> Deserialize<'a> for Params {
 #[cfg(test)]
 mod tests {
 	use serde_json;
-	use super::{Result, Kind, Params};
+	use super::{Result, Kind, Params, TransactionFilter};
 	use v1::types::{RichHeader, Header, Filter};
 	use v1::types::filter::VariadicValue;
 
@@ -144,6 +199,19 @@ mod tests {
 		}));
 	}
 
+	#[test]
+	fn should_deserialize_transaction_filter() {
+		let params = serde_json::from_str::<Params>(
+			r#"{"to":"0x000000000000000000000000000000000000000a","minGasPrice":"0x3b9aca00","selector":"0xa9059cbb"}"#
+		).unwrap();
+		assert_eq!(params, Params::Transactions(TransactionFilter {
+			from: None,
+			to: Some(10.into()),
+			min_gas_price: Some(1_000_000_000.into()),
+			selector: Some(vec![0xa9, 0x05, 0x9c, 0xbb].into()),
+		}));
+	}
+
 	#[test]
 	fn should_serialize_header() {
 		let header = Result::Header(RichHeader {
@@ -165,6 +233,7 @@ mod tests {
 				timestamp: Default::default(),
 				difficulty: Default::default(),
 				seal_fields: vec![Default::default(), Default::default()],
+				base_fee_per_gas: None,
 				size: Some(69.into()),
 			},
 		});
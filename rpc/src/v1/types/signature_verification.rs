@@ -0,0 +1,60 @@
+// Copyright 2015-2018 Parity Technologies (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Batched signature verification.
+
+use v1::types::{Bytes, H160, H520};
+
+/// Signed message to verify.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct SignatureVerificationRequest {
+	/// Message, hashed with the `eth_sign` prefix before recovery.
+	pub message: Bytes,
+	/// Signature, `v` may be in electrum or EIP-155 notation.
+	pub signature: H520,
+	/// Expected signer.
+	pub address: H160,
+}
+
+/// Outcome of a signature verification.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct SignatureVerification {
+	/// Whether the signature was made by the expected signer.
+	pub valid: bool,
+	/// Address recovered from the signature, if recovery succeeded.
+	pub recovered: Option<H160>,
+}
+
+#[cfg(test)]
+mod tests {
+	use serde_json;
+	use super::{SignatureVerificationRequest, SignatureVerification};
+
+	#[test]
+	fn signature_verification_request_deserialization() {
+		let s = r#"{"message":"0x01","signature":"0x0000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000","address":"0x0000000000000000000000000000000000000000"}"#;
+		let deserialized: SignatureVerificationRequest = serde_json::from_str(s).unwrap();
+
+		assert_eq!(deserialized.message.0, vec![1]);
+	}
+
+	#[test]
+	fn signature_verification_serialization() {
+		let s = SignatureVerification { valid: false, recovered: None };
+		assert_eq!(serde_json::to_string(&s).unwrap(), r#"{"valid":false,"recovered":null}"#);
+	}
+}
@@ -0,0 +1,104 @@
+// Copyright 2015-2018 Parity Technologies (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+//! State override set for calls.
+
+use std::collections::{BTreeMap, HashMap};
+
+use ethereum_types;
+
+use ethcore::state::{AccountOverride as EthAccountOverride, StateOverride as EthStateOverride};
+use v1::types::{Bytes, H160, H256, U256};
+
+/// Temporary modifications of a single account applied for the duration of a call.
+#[derive(Debug, Default, Clone, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+#[serde(rename_all="camelCase")]
+pub struct AccountOverride {
+	/// Fake balance to set for the account.
+	pub balance: Option<U256>,
+	/// Fake nonce to set for the account.
+	pub nonce: Option<U256>,
+	/// Fake code to set for the account.
+	pub code: Option<Bytes>,
+	/// Fake storage replacing the whole account storage.
+	pub state: Option<BTreeMap<H256, H256>>,
+	/// Fake storage slots, leaving the remaining storage intact.
+	pub state_diff: Option<BTreeMap<H256, H256>>,
+}
+
+/// Account overrides keyed by address.
+pub type StateOverride = BTreeMap<H160, AccountOverride>;
+
+/// Converts the state override set into its `ethcore` counterpart.
+/// Fails if both `state` and `stateDiff` are given for the same account.
+pub fn into_state_override(overrides: StateOverride) -> Result<EthStateOverride, H160> {
+	overrides.into_iter().map(|(address, account)| {
+		if account.state.is_some() && account.state_diff.is_some() {
+			return Err(address);
+		}
+
+		Ok((address.into(), EthAccountOverride {
+			balance: account.balance.map(Into::into),
+			nonce: account.nonce.map(Into::into),
+			code: account.code.map(Bytes::into_vec),
+			state: account.state.map(into_storage),
+			state_diff: account.state_diff.map(into_storage),
+		}))
+	}).collect()
+}
+
+fn into_storage(storage: BTreeMap<H256, H256>) -> HashMap<ethereum_types::H256, ethereum_types::H256> {
+	storage.into_iter().map(|(k, v)| (k.into(), v.into())).collect()
+}
+
+#[cfg(test)]
+mod tests {
+	use serde_json;
+	use ethereum_types::Address;
+	use super::{StateOverride, into_state_override};
+
+	#[test]
+	fn state_override_deserialization() {
+		let s = r#"{
+			"0x0000000000000000000000000000000000000001": {
+				"balance": "0x10",
+				"code": "0x6000",
+				"stateDiff": {
+					"0x0000000000000000000000000000000000000000000000000000000000000001": "0x0000000000000000000000000000000000000000000000000000000000000002"
+				}
+			}
+		}"#;
+		let overrides: StateOverride = serde_json::from_str(s).unwrap();
+		let overrides = into_state_override(overrides).unwrap();
+		let account = &overrides[&Address::from(1)];
+
+		assert_eq!(account.balance, Some(16.into()));
+		assert_eq!(account.nonce, None);
+		assert_eq!(account.code, Some(vec![0x60, 0x00]));
+		assert_eq!(account.state, None);
+		assert_eq!(account.state_diff, Some(vec![(1.into(), 2.into())].into_iter().collect()));
+	}
+
+	#[test]
+	fn should_reject_state_and_state_diff() {
+		let s = r#"{
+			"0x0000000000000000000000000000000000000001": { "state": {}, "stateDiff": {} }
+		}"#;
+		let overrides: StateOverride = serde_json::from_str(s).unwrap();
+		assert_eq!(into_state_override(overrides), Err(1.into()));
+	}
+}
@@ -0,0 +1,201 @@
+// Copyright 2015-2018 Parity Technologies (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Opcode-level struct logs returned by `debug` tracing.
+
+use std::collections::BTreeMap;
+
+use ethcore::client::Executed;
+use ethcore::trace::{self, FlatTrace, StructLog as EthStructLog, StructLogOptions as EthStructLogOptions};
+use rustc_hex::ToHex;
+use serde_json::{self, Value};
+use v1::types::{Bytes, U256};
+
+/// Struct logging options.
+#[derive(Debug, Default, Clone, PartialEq, Deserialize)]
+#[serde(default)]
+#[serde(rename_all="camelCase")]
+pub struct StructLogOptions {
+	/// Don't capture the stack.
+	pub disable_stack: bool,
+	/// Don't capture the memory.
+	pub disable_memory: bool,
+	/// Don't capture the storage.
+	pub disable_storage: bool,
+	/// Wasm tracer module to run over the struct logs instead of returning them.
+	pub tracer: Option<Bytes>,
+}
+
+impl<'a> Into<EthStructLogOptions> for &'a StructLogOptions {
+	fn into(self) -> EthStructLogOptions {
+		// a tracer may inspect the stack and memory of every step
+		let traced = self.tracer.is_some();
+		EthStructLogOptions {
+			disable_stack: self.disable_stack && !traced,
+			disable_memory: self.disable_memory && !traced,
+			disable_storage: self.disable_storage,
+		}
+	}
+}
+
+/// Machine state right before executing a single instruction.
+#[derive(Debug, Serialize)]
+#[serde(rename_all="camelCase")]
+pub struct StructLog {
+	/// Program counter.
+	pub pc: usize,
+	/// Instruction name.
+	pub op: &'static str,
+	/// Gas available before executing the instruction.
+	pub gas: u64,
+	/// Gas cost of the instruction.
+	pub gas_cost: u64,
+	/// Call depth.
+	pub depth: usize,
+	/// Stack, bottom item first.
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub stack: Option<Vec<U256>>,
+	/// Memory as 32-byte hex words.
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub memory: Option<Vec<String>>,
+	/// Storage slots of the current frame accessed so far, as hex words.
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub storage: Option<BTreeMap<String, String>>,
+}
+
+impl From<EthStructLog> for StructLog {
+	fn from(l: EthStructLog) -> Self {
+		StructLog {
+			pc: l.pc,
+			op: l.op_name(),
+			gas: l.gas.low_u64(),
+			gas_cost: l.gas_cost.low_u64(),
+			depth: l.depth,
+			stack: l.stack.map(|stack| stack.into_iter().map(Into::into).collect()),
+			memory: l.memory.map(|memory| memory.chunks(32).map(|word| word.to_hex()).collect()),
+			storage: l.storage.map(|storage| storage.into_iter().map(|(k, v)| (k.to_hex(), v.to_hex())).collect()),
+		}
+	}
+}
+
+/// Result of an execution traced with struct logging.
+#[derive(Debug, Serialize)]
+#[serde(rename_all="camelCase")]
+pub struct StructLogTrace {
+	/// Gas used by the execution.
+	pub gas: u64,
+	/// Whether the execution failed.
+	pub failed: bool,
+	/// Output of the execution, as hex.
+	pub return_value: String,
+	/// Logs of all executed instructions.
+	pub struct_logs: Vec<StructLog>,
+}
+
+impl From<Executed<FlatTrace, Vec<EthStructLog>>> for StructLogTrace {
+	fn from(t: Executed<FlatTrace, Vec<EthStructLog>>) -> Self {
+		StructLogTrace {
+			gas: t.gas_used.low_u64(),
+			failed: t.exception.is_some(),
+			return_value: t.output.to_hex(),
+			struct_logs: t.vm_trace.unwrap_or_default().into_iter().map(Into::into).collect(),
+		}
+	}
+}
+
+/// Result of a single step of an interactive debug session.
+#[derive(Debug, Serialize)]
+#[serde(untagged)]
+pub enum DebugStep {
+	/// Execution is paused before the given instruction.
+	Paused(StructLog),
+	/// Execution has finished.
+	Finished(StructLogTrace),
+}
+
+/// Result of `debug` tracing.
+#[derive(Debug, Serialize)]
+#[serde(untagged)]
+pub enum DebugTrace {
+	/// Struct logs of the execution.
+	StructLogs(StructLogTrace),
+	/// JSON output of a custom tracer.
+	Tracer(Value),
+}
+
+impl DebugTrace {
+	/// Builds the result of tracing an execution with given options.
+	///
+	/// Runs the tracer if one was supplied; its output has to be valid JSON.
+	pub fn new(executed: Executed<FlatTrace, Vec<EthStructLog>>, options: &StructLogOptions) -> Result<Self, String> {
+		match options.tracer {
+			Some(ref code) => {
+				let logs = executed.vm_trace.unwrap_or_default();
+				let output = trace::run_wasm_tracer(&code.0, &logs)?;
+				serde_json::from_slice(&output)
+					.map(DebugTrace::Tracer)
+					.map_err(|e| format!("Tracer output is not valid JSON: {}", e))
+			},
+			None => Ok(DebugTrace::StructLogs(executed.into())),
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use serde_json;
+	use ethcore::trace::StructLog as EthStructLog;
+	use super::{StructLog, StructLogOptions};
+
+	#[test]
+	fn struct_log_options_deserialization() {
+		let s = r#"{"disableStorage":true}"#;
+		let options: StructLogOptions = serde_json::from_str(s).unwrap();
+		assert_eq!(options, StructLogOptions {
+			disable_stack: false,
+			disable_memory: false,
+			disable_storage: true,
+			tracer: None,
+		});
+	}
+
+	#[test]
+	fn tracer_should_force_stack_and_memory_capture() {
+		let s = r#"{"disableStack":true,"disableMemory":true,"disableStorage":true,"tracer":"0x0061736d"}"#;
+		let options: StructLogOptions = serde_json::from_str(s).unwrap();
+		let options: ::ethcore::trace::StructLogOptions = (&options).into();
+		assert!(!options.disable_stack);
+		assert!(!options.disable_memory);
+		assert!(options.disable_storage);
+	}
+
+	#[test]
+	fn struct_log_serialization() {
+		let log: StructLog = EthStructLog {
+			pc: 2,
+			instruction: 0x52,
+			gas: 0x10.into(),
+			gas_cost: 0x6.into(),
+			depth: 1,
+			stack: Some(vec![0x2a.into(), 0.into()]),
+			memory: Some(vec![0u8; 32]),
+			storage: None,
+		}.into();
+
+		let serialized = serde_json::to_string(&log).unwrap();
+		assert_eq!(serialized, r#"{"pc":2,"op":"MSTORE","gas":16,"gasCost":6,"depth":1,"stack":["0x2a","0x0"],"memory":["0000000000000000000000000000000000000000000000000000000000000000"]}"#);
+	}
+}
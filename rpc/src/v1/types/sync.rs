@@ -14,8 +14,15 @@
 // You should have received a copy of the GNU General Public License
 // along with Parity.  If not, see <http://www.gnu.org/licenses/>.
 
-use std::collections::BTreeMap;
-use sync::{self, PeerInfo as SyncPeerInfo, TransactionStats as SyncTransactionStats};
+use std::collections::{BTreeMap, HashMap};
+use std::time::UNIX_EPOCH;
+use ethcore::spec::CommonParams;
+use sync::{
+	self, PeerInfo as SyncPeerInfo, TransactionStats as SyncTransactionStats,
+	BlockPropagationStats as SyncBlockPropagationStats, BlockAnnouncementStats as SyncBlockAnnouncementStats,
+};
+use sync::HandshakeFailure as SyncHandshakeFailure;
+use sync::Traffic as SyncTraffic;
 use serde::{Serialize, Serializer};
 use v1::types::{U256, H512};
 
@@ -50,6 +57,8 @@ pub struct Peers {
 	pub max: u32,
 	/// Detailed information on peers
 	pub peers: Vec<PeerInfo>,
+	/// Bytes exchanged with all peers since the start, per protocol
+	pub traffic: BTreeMap<String, Traffic>,
 }
 
 /// Peer connection information
@@ -76,6 +85,35 @@ pub struct PeerNetworkInfo {
 	/// Local endpoint address
 	#[serde(rename="localAddress")]
 	pub local_address: String,
+	/// Bytes exchanged with the peer, per protocol
+	pub traffic: BTreeMap<String, Traffic>,
+}
+
+/// Number of bytes received and sent
+#[derive(Default, Debug, Serialize, PartialEq)]
+pub struct Traffic {
+	/// Bytes received
+	#[serde(rename="bytesIn")]
+	pub bytes_in: u64,
+	/// Bytes sent
+	#[serde(rename="bytesOut")]
+	pub bytes_out: u64,
+}
+
+impl From<SyncTraffic> for Traffic {
+	fn from(t: SyncTraffic) -> Self {
+		Traffic {
+			bytes_in: t.bytes_in,
+			bytes_out: t.bytes_out,
+		}
+	}
+}
+
+/// Converts traffic keyed by protocol id into traffic keyed by protocol name.
+pub fn traffic_by_protocol(traffic: HashMap<[u8; 3], SyncTraffic>) -> BTreeMap<String, Traffic> {
+	traffic.into_iter()
+		.map(|(protocol, t)| (String::from_utf8_lossy(&protocol).into_owned(), t.into()))
+		.collect()
 }
 
 /// Peer protocols information
@@ -129,6 +167,32 @@ impl From<sync::PipProtocolInfo> for PipProtocolInfo {
 	}
 }
 
+/// Recently failed handshake with a remote node
+#[derive(Debug, Serialize)]
+pub struct HandshakeFailure {
+	/// Public node id, if it was already known
+	pub id: Option<H512>,
+	/// Remote endpoint address
+	#[serde(rename="remoteAddress")]
+	pub remote_address: String,
+	/// Why the handshake has failed
+	pub reason: String,
+	/// Time of the failure, in milliseconds since the unix epoch
+	pub time: u64,
+}
+
+impl From<SyncHandshakeFailure> for HandshakeFailure {
+	fn from(f: SyncHandshakeFailure) -> Self {
+		let since_epoch = f.time.duration_since(UNIX_EPOCH).unwrap_or_default();
+		HandshakeFailure {
+			id: f.remote_id.map(Into::into),
+			remote_address: f.remote_address,
+			reason: f.reason.to_string(),
+			time: since_epoch.as_secs() * 1000 + (since_epoch.subsec_nanos() / 1_000_000) as u64,
+		}
+	}
+}
+
 /// Sync status
 #[derive(Debug, PartialEq)]
 pub enum SyncStatus {
@@ -148,6 +212,46 @@ impl Serialize for SyncStatus {
 	}
 }
 
+/// Stage of the sync process.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum SyncStage {
+	/// Synced, following the chain head.
+	Idle,
+	/// Collecting enough peers to start syncing.
+	WaitingPeers,
+	/// Waiting for the snapshot manifest.
+	WarpManifest,
+	/// Downloading snapshot chunks.
+	WarpChunks,
+	/// Waiting for the snapshot restoration to finish.
+	WarpRestoring,
+	/// Downloading and importing blocks.
+	Blocks,
+}
+
+/// Progress of the sync process.
+#[derive(Debug, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SyncProgress {
+	/// Current stage.
+	pub stage: SyncStage,
+	/// Best imported block.
+	pub current_block: U256,
+	/// Highest block seen so far.
+	pub highest_block: U256,
+	/// Warp sync snapshot chunks total.
+	pub warp_chunks_amount: Option<U256>,
+	/// Warp sync snapshot chunks processed.
+	pub warp_chunks_processed: Option<U256>,
+	/// Number of connected peers.
+	pub peers: usize,
+	/// Number of peers we sync from.
+	pub active_peers: usize,
+	/// Estimated number of seconds until the current stage is finished.
+	pub eta: Option<u64>,
+}
+
 /// Propagation statistics for pending transaction.
 #[derive(Default, Debug, Serialize)]
 pub struct TransactionStats {
@@ -168,6 +272,7 @@ impl From<SyncPeerInfo> for PeerInfo {
 			network: PeerNetworkInfo {
 				remote_address: p.remote_address,
 				local_address: p.local_address,
+				traffic: traffic_by_protocol(p.traffic),
 			},
 			protocols: PeerProtocolsInfo {
 				eth: p.eth_info.map(Into::into),
@@ -189,19 +294,99 @@ impl From<SyncTransactionStats> for TransactionStats {
 	}
 }
 
+/// Block propagation stats
+#[derive(Default, Debug, Serialize)]
+pub struct BlockPropagationStats {
+	/// Block number.
+	pub number: u64,
+	/// Time of the first announcement, in milliseconds since the unix epoch.
+	#[serde(rename="firstSeen")]
+	pub first_seen: u64,
+	/// Milliseconds between the first announcement and import, if imported.
+	#[serde(rename="importDelay")]
+	pub import_delay: Option<u64>,
+	/// Milliseconds between the first announcement and the announcement by each peer.
+	#[serde(rename="announcedBy")]
+	pub announced_by: BTreeMap<H512, u64>,
+}
+
+impl From<SyncBlockPropagationStats> for BlockPropagationStats {
+	fn from(s: SyncBlockPropagationStats) -> Self {
+		BlockPropagationStats {
+			number: s.number,
+			first_seen: s.first_seen,
+			import_delay: s.import_delay,
+			announced_by: s.announced_by
+				.into_iter()
+				.map(|(id, delay)| (id.into(), delay))
+				.collect(),
+		}
+	}
+}
+
+/// Block announcement counters
+#[derive(Default, Debug, Serialize)]
+pub struct BlockAnnouncementStats {
+	/// Block announcements received from peers.
+	pub received: u64,
+	/// Received announcements of blocks already announced by another peer.
+	#[serde(rename="duplicatesReceived")]
+	pub duplicates_received: u64,
+	/// Block announcements sent to peers.
+	pub sent: u64,
+	/// Announcements not sent because the peer was already known to have the block.
+	#[serde(rename="duplicatesSuppressed")]
+	pub duplicates_suppressed: u64,
+}
+
+impl From<SyncBlockAnnouncementStats> for BlockAnnouncementStats {
+	fn from(s: SyncBlockAnnouncementStats) -> Self {
+		BlockAnnouncementStats {
+			received: s.received,
+			duplicates_received: s.duplicates_received,
+			sent: s.sent,
+			duplicates_suppressed: s.duplicates_suppressed,
+		}
+	}
+}
+
 /// Chain status.
 #[derive(Default, Debug, Serialize)]
 pub struct ChainStatus {
 	/// Describes the gap in the blockchain, if there is one: (first, last)
 	#[serde(rename="blockGap")]
 	pub block_gap: Option<(U256, U256)>,
+	/// Future timestamp drift policy applied to imported blocks.
+	#[serde(rename="timestampDrift")]
+	pub timestamp_drift: TimestampDrift,
+	/// Whether block import and sealing are paused for maintenance.
+	#[serde(rename="importPaused")]
+	pub import_paused: bool,
+}
+
+/// Future timestamp drift policy, in milliseconds.
+#[derive(Default, Debug, Serialize)]
+pub struct TimestampDrift {
+	/// Drift after which blocks are held back until their time comes.
+	pub acceptable: u64,
+	/// Drift after which blocks are rejected as invalid.
+	pub invalid: u64,
+}
+
+impl<'a> From<&'a CommonParams> for TimestampDrift {
+	fn from(params: &'a CommonParams) -> Self {
+		TimestampDrift {
+			acceptable: params.acceptable_timestamp_drift,
+			invalid: params.invalid_timestamp_drift,
+		}
+	}
 }
 
 #[cfg(test)]
 mod tests {
 	use serde_json;
 	use std::collections::BTreeMap;
-	use super::{SyncInfo, SyncStatus, Peers, TransactionStats, ChainStatus};
+	use super::{SyncInfo, SyncStatus, Peers, TransactionStats, BlockPropagationStats, ChainStatus, TimestampDrift};
 
 	#[test]
 	fn test_serialize_sync_info() {
@@ -232,12 +417,14 @@ mod tests {
 	fn test_serialize_block_gap() {
 		let mut t = ChainStatus::default();
 		let serialized = serde_json::to_string(&t).unwrap();
-		assert_eq!(serialized, r#"{"blockGap":null}"#);
+		assert_eq!(serialized, r#"{"blockGap":null,"timestampDrift":{"acceptable":0,"invalid":0},"importPaused":false}"#);
 
 		t.block_gap = Some((1.into(), 5.into()));
+		t.timestamp_drift = TimestampDrift { acceptable: 500, invalid: 2000 };
+		t.import_paused = true;
 
 		let serialized = serde_json::to_string(&t).unwrap();
-		assert_eq!(serialized, r#"{"blockGap":["0x1","0x5"]}"#);
+		assert_eq!(serialized, r#"{"blockGap":["0x1","0x5"],"timestampDrift":{"acceptable":500,"invalid":2000},"importPaused":true}"#);
 	}
 
 	#[test]
@@ -252,4 +439,19 @@ mod tests {
 		let serialized = serde_json::to_string(&stats).unwrap();
 		assert_eq!(serialized, r#"{"firstSeen":100,"propagatedTo":{"0x0000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000a":50}}"#)
 	}
+
+	#[test]
+	fn test_serialize_block_propagation_stats() {
+		let stats = BlockPropagationStats {
+			number: 7,
+			first_seen: 1_500_000_000_000,
+			import_delay: Some(120),
+			announced_by: map![
+				10.into() => 35
+			],
+		};
+
+		let serialized = serde_json::to_string(&stats).unwrap();
+		assert_eq!(serialized, r#"{"number":7,"firstSeen":1500000000000,"importDelay":120,"announcedBy":{"0x0000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000a":35}}"#)
+	}
 }
@@ -0,0 +1,106 @@
+// Copyright 2015-2018 Parity Technologies (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Thread pools statistics.
+
+use std::time::Duration;
+use io::ThreadPoolStats;
+
+/// Thread pool statistics.
+#[derive(Debug, Serialize)]
+pub struct ThreadPoolInfo {
+	/// Name of the pool.
+	pub name: String,
+	/// Number of threads.
+	pub threads: usize,
+	/// Seconds threads of the pool spent on work, if measured.
+	#[serde(rename="busySeconds")]
+	pub busy_seconds: Option<f64>,
+	/// Seconds since the pool has been started.
+	#[serde(rename="uptimeSeconds")]
+	pub uptime_seconds: f64,
+	/// Fraction of available thread time spent on work, if measured.
+	pub utilization: Option<f64>,
+	/// Number of work items waiting for the pool, if it's fed by a queue.
+	#[serde(rename="queueDepth")]
+	pub queue_depth: Option<usize>,
+	/// Highest observed number of work items waiting for the pool.
+	#[serde(rename="queueMaxDepth")]
+	pub queue_max_depth: Option<usize>,
+	/// Capacity of the queue feeding the pool.
+	#[serde(rename="queueCapacity")]
+	pub queue_capacity: Option<usize>,
+	/// Number of messages dropped because the queue was full.
+	#[serde(rename="queueDropped")]
+	pub queue_dropped: Option<usize>,
+}
+
+fn as_secs(duration: Duration) -> f64 {
+	duration.as_secs() as f64 + duration.subsec_nanos() as f64 / 1_000_000_000.0
+}
+
+impl From<ThreadPoolStats> for ThreadPoolInfo {
+	fn from(stats: ThreadPoolStats) -> Self {
+		ThreadPoolInfo {
+			utilization: stats.utilization(),
+			queue_depth: stats.queue.map(|queue| queue.depth),
+			queue_max_depth: stats.queue.map(|queue| queue.max_depth),
+			queue_capacity: stats.queue.map(|queue| queue.capacity),
+			queue_dropped: stats.queue.map(|queue| queue.dropped),
+			busy_seconds: stats.busy.map(as_secs),
+			uptime_seconds: as_secs(stats.uptime),
+			threads: stats.threads,
+			name: stats.name,
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use std::time::Duration;
+	use serde_json;
+	use io::{ThreadPoolStats, QueueStats};
+	use super::ThreadPoolInfo;
+
+	#[test]
+	fn thread_pool_info_serialization() {
+		let info: ThreadPoolInfo = ThreadPoolStats {
+			name: "verification".into(),
+			threads: 2,
+			busy: Some(Duration::from_secs(1)),
+			uptime: Duration::from_secs(2),
+			queue: None,
+		}.into();
+
+		assert_eq!(
+			serde_json::to_string(&info).unwrap(),
+			r#"{"name":"verification","threads":2,"busySeconds":1.0,"uptimeSeconds":2.0,"utilization":0.25,"queueDepth":null,"queueMaxDepth":null,"queueCapacity":null,"queueDropped":null}"#
+		);
+
+		let info: ThreadPoolInfo = ThreadPoolStats {
+			name: "network".into(),
+			threads: 4,
+			busy: None,
+			uptime: Duration::from_secs(2),
+			queue: Some(QueueStats { depth: 3, max_depth: 10, capacity: 16, overflowed: 2, dropped: 1 }),
+		}.into();
+
+		assert_eq!(
+			serde_json::to_string(&info).unwrap(),
+			r#"{"name":"network","threads":4,"busySeconds":null,"uptimeSeconds":2.0,"utilization":null,"queueDepth":3,"queueMaxDepth":10,"queueCapacity":16,"queueDropped":1}"#
+		);
+	}
+}
@@ -483,6 +483,16 @@ pub struct LocalizedTrace {
 	block_number: u64,
 	/// Block Hash
 	block_hash: H256,
+	/// Whether the block is canonical, only present for queries allowing non-canonical blocks
+	canonical: Option<bool>,
+}
+
+impl LocalizedTrace {
+	/// Marks whether the block of the trace is canonical.
+	pub fn with_canonical(mut self, canonical: bool) -> Self {
+		self.canonical = Some(canonical);
+		self
+	}
 }
 
 impl Serialize for LocalizedTrace {
@@ -523,6 +533,9 @@ impl Serialize for LocalizedTrace {
 		struc.serialize_field("transactionHash", &self.transaction_hash)?;
 		struc.serialize_field("blockNumber", &self.block_number)?;
 		struc.serialize_field("blockHash", &self.block_hash)?;
+		if let Some(ref canonical) = self.canonical {
+			struc.serialize_field("canonical", canonical)?;
+		}
 
 		struc.end()
 	}
@@ -539,6 +552,7 @@ impl From<EthLocalizedTrace> for LocalizedTrace {
 			transaction_hash: t.transaction_hash.map(Into::into),
 			block_number: t.block_number.into(),
 			block_hash: t.block_hash.into(),
+			canonical: None,
 		}
 	}
 }
@@ -703,6 +717,7 @@ mod tests {
 			transaction_hash: Some(12.into()),
 			block_number: 13,
 			block_hash: 14.into(),
+			canonical: None,
 		};
 		let serialized = serde_json::to_string(&t).unwrap();
 		assert_eq!(serialized, r#"{"type":"call","action":{"from":"0x0000000000000000000000000000000000000004","to":"0x0000000000000000000000000000000000000005","value":"0x6","gas":"0x7","input":"0x1234","callType":"call"},"result":{"gasUsed":"0x8","output":"0x5678"},"traceAddress":[10],"subtraces":1,"transactionPosition":11,"transactionHash":"0x000000000000000000000000000000000000000000000000000000000000000c","blockNumber":13,"blockHash":"0x000000000000000000000000000000000000000000000000000000000000000e"}"#);
@@ -726,6 +741,7 @@ mod tests {
 			transaction_hash: Some(12.into()),
 			block_number: 13,
 			block_hash: 14.into(),
+			canonical: None,
 		};
 		let serialized = serde_json::to_string(&t).unwrap();
 		assert_eq!(serialized, r#"{"type":"call","action":{"from":"0x0000000000000000000000000000000000000004","to":"0x0000000000000000000000000000000000000005","value":"0x6","gas":"0x7","input":"0x1234","callType":"call"},"error":"Out of gas","traceAddress":[10],"subtraces":1,"transactionPosition":11,"transactionHash":"0x000000000000000000000000000000000000000000000000000000000000000c","blockNumber":13,"blockHash":"0x000000000000000000000000000000000000000000000000000000000000000e"}"#);
@@ -751,6 +767,7 @@ mod tests {
 			transaction_hash: Some(12.into()),
 			block_number: 13,
 			block_hash: 14.into(),
+			canonical: None,
 		};
 		let serialized = serde_json::to_string(&t).unwrap();
 		assert_eq!(serialized, r#"{"type":"create","action":{"from":"0x0000000000000000000000000000000000000004","value":"0x6","gas":"0x7","init":"0x1234"},"result":{"gasUsed":"0x8","code":"0x5678","address":"0x00000000000000000000000000000000000000ff"},"traceAddress":[10],"subtraces":1,"transactionPosition":11,"transactionHash":"0x000000000000000000000000000000000000000000000000000000000000000c","blockNumber":13,"blockHash":"0x000000000000000000000000000000000000000000000000000000000000000e"}"#);
@@ -772,6 +789,7 @@ mod tests {
 			transaction_hash: Some(12.into()),
 			block_number: 13,
 			block_hash: 14.into(),
+			canonical: None,
 		};
 		let serialized = serde_json::to_string(&t).unwrap();
 		assert_eq!(serialized, r#"{"type":"create","action":{"from":"0x0000000000000000000000000000000000000004","value":"0x6","gas":"0x7","init":"0x1234"},"error":"Out of gas","traceAddress":[10],"subtraces":1,"transactionPosition":11,"transactionHash":"0x000000000000000000000000000000000000000000000000000000000000000c","blockNumber":13,"blockHash":"0x000000000000000000000000000000000000000000000000000000000000000e"}"#);
@@ -792,6 +810,7 @@ mod tests {
 			transaction_hash: Some(12.into()),
 			block_number: 13,
 			block_hash: 14.into(),
+			canonical: None,
 		};
 		let serialized = serde_json::to_string(&t).unwrap();
 		assert_eq!(serialized, r#"{"type":"suicide","action":{"address":"0x0000000000000000000000000000000000000004","refundAddress":"0x0000000000000000000000000000000000000006","balance":"0x7"},"result":null,"traceAddress":[10],"subtraces":1,"transactionPosition":11,"transactionHash":"0x000000000000000000000000000000000000000000000000000000000000000c","blockNumber":13,"blockHash":"0x000000000000000000000000000000000000000000000000000000000000000e"}"#);
@@ -812,6 +831,7 @@ mod tests {
 			transaction_hash: None,
 			block_number: 13,
 			block_hash: 14.into(),
+			canonical: None,
 		};
 		let serialized = serde_json::to_string(&t).unwrap();
 		assert_eq!(serialized, r#"{"type":"reward","action":{"author":"0x0000000000000000000000000000000000000004","value":"0x6","rewardType":"block"},"result":null,"traceAddress":[10],"subtraces":1,"transactionPosition":null,"transactionHash":null,"blockNumber":13,"blockHash":"0x000000000000000000000000000000000000000000000000000000000000000e"}"#);
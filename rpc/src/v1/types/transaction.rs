@@ -21,7 +21,7 @@ use serde::ser::SerializeStruct;
 use ethcore::{contract_address, CreateContractAddress};
 use miner;
 use transaction::{LocalizedTransaction, Action, PendingTransaction, SignedTransaction};
-use v1::types::{Bytes, H160, H256, U256, H512, U64, TransactionCondition};
+use v1::types::{AccessListItem, Bytes, H160, H256, U256, H512, U64, TransactionCondition};
 
 /// Transaction
 #[derive(Debug, Default, Clone, PartialEq, Serialize)]
@@ -73,6 +73,18 @@ pub struct Transaction {
 	pub s: U256,
 	/// Transaction activates at specified block.
 	pub condition: Option<TransactionCondition>,
+	/// EIP-2718 type of the transaction, omitted for legacy transactions.
+	#[serde(rename="type", skip_serializing_if = "Option::is_none")]
+	pub transaction_type: Option<U64>,
+	/// Access list of the transaction, omitted for legacy transactions.
+	#[serde(rename="accessList", skip_serializing_if = "Option::is_none")]
+	pub access_list: Option<Vec<AccessListItem>>,
+	/// Max priority fee per gas (EIP-1559), omitted for other transactions.
+	#[serde(rename="maxPriorityFeePerGas", skip_serializing_if = "Option::is_none")]
+	pub max_priority_fee_per_gas: Option<U256>,
+	/// Max fee per gas (EIP-1559), omitted for other transactions.
+	#[serde(rename="maxFeePerGas", skip_serializing_if = "Option::is_none")]
+	pub max_fee_per_gas: Option<U256>,
 }
 
 /// Local Transaction Status
@@ -200,14 +212,21 @@ impl Transaction {
 				Action::Create => Some(contract_address(scheme, &t.sender(), &t.nonce, &t.data).0.into()),
 				Action::Call(_) => None,
 			},
-			raw: ::rlp::encode(&t.signed).into_vec().into(),
+			raw: t.signed.encode_raw().into(),
 			public_key: t.recover_public().ok().map(Into::into),
 			chain_id: t.chain_id().map(U64::from),
 			standard_v: t.standard_v().into(),
-			v: t.original_v().into(),
+			v: match t.access_list() {
+				Some(_) => t.standard_v().into(),
+				None => t.original_v().into(),
+			},
 			r: signature.r().into(),
 			s: signature.s().into(),
 			condition: None,
+			transaction_type: t.access_list().map(|_| U64::from(t.tx_type() as u64)),
+			access_list: t.access_list().map(|items| items.iter().cloned().map(Into::into).collect()),
+			max_priority_fee_per_gas: t.max_priority_fee_per_gas().map(Into::into),
+			max_fee_per_gas: t.max_priority_fee_per_gas().map(|_| t.gas_price.into()),
 		}
 	}
 
@@ -234,14 +253,21 @@ impl Transaction {
 				Action::Create => Some(contract_address(scheme, &t.sender(), &t.nonce, &t.data).0.into()),
 				Action::Call(_) => None,
 			},
-			raw: ::rlp::encode(&t).into_vec().into(),
+			raw: t.encode_raw().into(),
 			public_key: t.public_key().map(Into::into),
 			chain_id: t.chain_id().map(U64::from),
 			standard_v: t.standard_v().into(),
-			v: t.original_v().into(),
+			v: match t.access_list() {
+				Some(_) => t.standard_v().into(),
+				None => t.original_v().into(),
+			},
 			r: signature.r().into(),
 			s: signature.s().into(),
 			condition: None,
+			transaction_type: t.access_list().map(|_| U64::from(t.tx_type() as u64)),
+			access_list: t.access_list().map(|items| items.iter().cloned().map(Into::into).collect()),
+			max_priority_fee_per_gas: t.max_priority_fee_per_gas().map(Into::into),
+			max_fee_per_gas: t.max_priority_fee_per_gas().map(|_| t.gas_price.into()),
 		}
 	}
 
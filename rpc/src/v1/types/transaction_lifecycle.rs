@@ -0,0 +1,182 @@
+// Copyright 2015-2018 Parity Technologies (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Transaction lifecycle log.
+
+use serde::{Serialize, Serializer};
+use serde::ser::SerializeStruct;
+use ethcore::client::{
+	TransactionLifecycle as EthTransactionLifecycle,
+	LifecycleEntry as EthLifecycleEntry,
+	LifecycleEvent,
+};
+use v1::types::{H256, U256};
+
+/// Something that happened to a transaction.
+#[derive(Debug, PartialEq)]
+pub enum LifecycleEntry {
+	/// Accepted to the transaction queue.
+	Accepted(U256),
+	/// Never accepted to the transaction queue.
+	Rejected(U256, String),
+	/// Sent to given number of peers.
+	Propagated(U256, u64),
+	/// Replaced by another transaction.
+	Replaced(U256, H256),
+	/// Dropped because of queue limits, optionally pushed out by another transaction.
+	Dropped(U256, Option<H256>),
+	/// Marked as invalid when building a block.
+	Invalid(U256),
+	/// Canceled by the user.
+	Canceled(U256),
+	/// Removed from the queue because it got mined or its nonce became stale.
+	Culled(U256),
+	/// Included in an imported block.
+	Included(U256, U256, H256),
+	/// Removed from the queue after falling below a raised minimal gas price.
+	Demoted(U256, U256),
+}
+
+impl Serialize for LifecycleEntry {
+	fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+		where S: Serializer
+	{
+		use self::LifecycleEntry::*;
+
+		let elems = match *self {
+			Accepted(..) | Invalid(..) | Canceled(..) | Culled(..) => 2,
+			Rejected(..) | Propagated(..) | Replaced(..) | Dropped(..) | Demoted(..) => 3,
+			Included(..) => 4,
+		};
+
+		let event = "event";
+		let timestamp = "timestamp";
+
+		let mut struc = serializer.serialize_struct("LifecycleEntry", elems)?;
+		match *self {
+			Accepted(ref time) => {
+				struc.serialize_field(event, "accepted")?;
+				struc.serialize_field(timestamp, time)?;
+			},
+			Rejected(ref time, ref reason) => {
+				struc.serialize_field(event, "rejected")?;
+				struc.serialize_field(timestamp, time)?;
+				struc.serialize_field("error", reason)?;
+			},
+			Propagated(ref time, ref peers) => {
+				struc.serialize_field(event, "propagated")?;
+				struc.serialize_field(timestamp, time)?;
+				struc.serialize_field("peers", peers)?;
+			},
+			Replaced(ref time, ref hash) => {
+				struc.serialize_field(event, "replaced")?;
+				struc.serialize_field(timestamp, time)?;
+				struc.serialize_field("hash", hash)?;
+			},
+			Dropped(ref time, ref hash) => {
+				struc.serialize_field(event, "dropped")?;
+				struc.serialize_field(timestamp, time)?;
+				struc.serialize_field("hash", hash)?;
+			},
+			Invalid(ref time) => {
+				struc.serialize_field(event, "invalid")?;
+				struc.serialize_field(timestamp, time)?;
+			},
+			Canceled(ref time) => {
+				struc.serialize_field(event, "canceled")?;
+				struc.serialize_field(timestamp, time)?;
+			},
+			Culled(ref time) => {
+				struc.serialize_field(event, "culled")?;
+				struc.serialize_field(timestamp, time)?;
+			},
+			Included(ref time, ref number, ref hash) => {
+				struc.serialize_field(event, "included")?;
+				struc.serialize_field(timestamp, time)?;
+				struc.serialize_field("blockNumber", number)?;
+				struc.serialize_field("blockHash", hash)?;
+			},
+			Demoted(ref time, ref gas_price) => {
+				struc.serialize_field(event, "demoted")?;
+				struc.serialize_field(timestamp, time)?;
+				struc.serialize_field("minimalGasPrice", gas_price)?;
+			},
+		}
+
+		struc.end()
+	}
+}
+
+impl From<EthLifecycleEntry> for LifecycleEntry {
+	fn from(entry: EthLifecycleEntry) -> Self {
+		let time = entry.timestamp.into();
+		match entry.event {
+			LifecycleEvent::Accepted => LifecycleEntry::Accepted(time),
+			LifecycleEvent::Rejected(reason) => LifecycleEntry::Rejected(time, reason),
+			LifecycleEvent::Propagated(peers) => LifecycleEntry::Propagated(time, peers),
+			LifecycleEvent::Replaced(hash) => LifecycleEntry::Replaced(time, hash.into()),
+			LifecycleEvent::Dropped(hash) => LifecycleEntry::Dropped(time, hash.map(Into::into)),
+			LifecycleEvent::Invalid => LifecycleEntry::Invalid(time),
+			LifecycleEvent::Canceled => LifecycleEntry::Canceled(time),
+			LifecycleEvent::Culled => LifecycleEntry::Culled(time),
+			LifecycleEvent::Included(number, hash) => LifecycleEntry::Included(time, number.into(), hash.into()),
+			LifecycleEvent::Demoted(gas_price) => LifecycleEntry::Demoted(time, gas_price.into()),
+		}
+	}
+}
+
+/// Everything the node recorded about a transaction, oldest event first.
+#[derive(Debug, Serialize)]
+pub struct TransactionLifecycle {
+	/// Transaction hash.
+	pub hash: H256,
+	/// Recorded events.
+	pub events: Vec<LifecycleEntry>,
+}
+
+impl From<EthTransactionLifecycle> for TransactionLifecycle {
+	fn from(lifecycle: EthTransactionLifecycle) -> Self {
+		TransactionLifecycle {
+			hash: lifecycle.hash.into(),
+			events: lifecycle.events.into_iter().map(Into::into).collect(),
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use serde_json;
+	use ethcore::client::{TransactionLifecycle as EthTransactionLifecycle, LifecycleEntry, LifecycleEvent};
+	use super::TransactionLifecycle;
+
+	#[test]
+	fn transaction_lifecycle_serialization() {
+		let lifecycle: TransactionLifecycle = EthTransactionLifecycle {
+			hash: 1.into(),
+			events: vec![
+				LifecycleEntry { timestamp: 16, event: LifecycleEvent::Accepted },
+				LifecycleEntry { timestamp: 17, event: LifecycleEvent::Propagated(3) },
+				LifecycleEntry { timestamp: 18, event: LifecycleEvent::Dropped(None) },
+				LifecycleEntry { timestamp: 19, event: LifecycleEvent::Included(5, 2.into()) },
+			],
+		}.into();
+
+		assert_eq!(
+			serde_json::to_string(&lifecycle).unwrap(),
+			r#"{"hash":"0x0000000000000000000000000000000000000000000000000000000000000001","events":[{"event":"accepted","timestamp":"0x10"},{"event":"propagated","timestamp":"0x11","peers":3},{"event":"dropped","timestamp":"0x12","hash":null},{"event":"included","timestamp":"0x13","blockNumber":"0x5","blockHash":"0x0000000000000000000000000000000000000000000000000000000000000002"}]}"#
+		);
+	}
+}
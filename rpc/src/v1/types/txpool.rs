@@ -0,0 +1,53 @@
+// Copyright 2015-2018 Parity Technologies (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Types of the transaction pool API.
+
+use std::collections::BTreeMap;
+
+use v1::types::{H160, U64};
+
+/// Transactions in the pool, grouped by sender and nonce.
+#[derive(Debug, Serialize)]
+pub struct TxPoolContent<T> {
+	/// Transactions ready to be included in the next block.
+	pub pending: BTreeMap<H160, BTreeMap<u64, T>>,
+	/// Transactions waiting for a nonce gap to be filled.
+	pub queued: BTreeMap<H160, BTreeMap<u64, T>>,
+}
+
+impl<T> Default for TxPoolContent<T> {
+	fn default() -> Self {
+		TxPoolContent {
+			pending: BTreeMap::new(),
+			queued: BTreeMap::new(),
+		}
+	}
+}
+
+/// Number of transactions in the pool.
+#[derive(Debug, Default, Serialize)]
+pub struct TxPoolStatus {
+	/// Number of transactions ready to be included in the next block.
+	pub pending: U64,
+	/// Number of transactions waiting for a nonce gap to be filled.
+	pub queued: U64,
+	/// Memory used by the pooled transactions, in bytes.
+	pub memory: U64,
+	/// Maximal memory the pool can use, in bytes.
+	#[serde(rename = "memoryLimit")]
+	pub memory_limit: U64,
+}
@@ -0,0 +1,37 @@
+#!/usr/bin/env sh
+# Checks the feature matrix documented in ethcore/light/README.md:
+# each embedded build must compile and must not pull in the listed crates.
+
+ERR=0
+
+# $1 manifest path, $2 cargo features (may be empty), $3.. forbidden crate names
+check() {
+    manifest="$1"
+    features="$2"
+    shift 2
+
+    echo "Checking $manifest [features: ${features:-none}]"
+    if ! out="$(cargo check -v --manifest-path "$manifest" --no-default-features --features "$features" 2>&1)"; then
+        echo "$out"
+        ERR=1
+        return
+    fi
+
+    for krate in "$@"; do
+        if echo "$out" | grep -q -E "^ *(Compiling|Fresh) $krate v"; then
+            echo "  ERROR: $manifest depends on $krate"
+            ERR=1
+        fi
+    done
+}
+
+# Minimal light client library: no RPC servers, no RocksDB, no USB, no whisper/secretstore.
+check ethcore/light/Cargo.toml "" \
+    rocksdb kvdb-rocksdb jsonrpc-core jsonrpc-http-server jsonrpc-ws-server \
+    parity-whisper ethcore-secretstore hardware-wallet hidapi libusb ethcore-stratum
+
+# Light client with on-disk storage.
+check ethcore/light/Cargo.toml "rocksdb" \
+    jsonrpc-core jsonrpc-http-server jsonrpc-ws-server parity-whisper ethcore-secretstore
+
+exit $ERR
@@ -257,10 +257,22 @@ fn upgrade_db(db: Arc<KeyValueDB>) -> Result<Arc<KeyValueDB>, Error> {
 	}
 }
 
+/// Serialize key share using current serialization format.
+pub fn serialize_key_share(key: DocumentKeyShare) -> Result<Vec<u8>, Error> {
+	let key: CurrentSerializableDocumentKeyShare = key.into();
+	serde_json::to_vec(&key).map_err(|e| Error::Database(e.to_string()))
+}
+
+/// Deserialize key share, serialized using current serialization format.
+pub fn deserialize_key_share(key: &[u8]) -> Result<DocumentKeyShare, Error> {
+	serde_json::from_slice::<CurrentSerializableDocumentKeyShare>(key)
+		.map_err(|e| Error::Database(e.to_string()))
+		.map(Into::into)
+}
+
 impl KeyStorage for PersistentKeyStorage {
 	fn insert(&self, document: ServerKeyId, key: DocumentKeyShare) -> Result<(), Error> {
-		let key: CurrentSerializableDocumentKeyShare = key.into();
-		let key = serde_json::to_vec(&key).map_err(|e| Error::Database(e.to_string()))?;
+		let key = serialize_key_share(key)?;
 		let mut batch = self.db.transaction();
 		batch.put(None, &document, &key);
 		self.db.write(batch).map_err(Into::into)
@@ -275,10 +287,7 @@ impl KeyStorage for PersistentKeyStorage {
 			.map_err(|e| Error::Database(e.to_string()))
 			.and_then(|key| match key {
 				None => Ok(None),
-				Some(key) => serde_json::from_slice::<CurrentSerializableDocumentKeyShare>(&key)
-					.map_err(|e| Error::Database(e.to_string()))
-					.map(Into::into)
-					.map(Some),
+				Some(key) => deserialize_key_share(&key).map(Some),
 			})
 	}
 
@@ -315,9 +324,9 @@ impl<'a> Iterator for PersistentKeyStorageIterator<'a> {
 
 	fn next(&mut self) -> Option<(ServerKeyId, DocumentKeyShare)> {
 		self.iter.as_mut().next()
-			.and_then(|(db_key, db_val)| serde_json::from_slice::<CurrentSerializableDocumentKeyShare>(&db_val)
+			.and_then(|(db_key, db_val)| deserialize_key_share(&db_val)
 					  .ok()
-					  .map(|key| ((*db_key).into(), key.into())))
+					  .map(|key| ((*db_key).into(), key)))
 	}
 }
 
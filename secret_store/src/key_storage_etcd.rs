@@ -0,0 +1,278 @@
+// Copyright 2015-2018 Parity Technologies (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+use std::io::Read;
+use std::time::Duration;
+use base64;
+use futures::Future;
+use serde_json::{self, Value};
+use rustc_hex::{ToHex, FromHex};
+use crypto::DEFAULT_MAC;
+use ethkey::{crypto, KeyPair, Secret};
+use fetch::{self, Fetch, Request, Abort, BodyReader, Url};
+use key_storage::{KeyStorage, DocumentKeyShare, serialize_key_share, deserialize_key_share};
+use types::{Error, ServerKeyId};
+
+/// Max duration (in seconds) of single request to etcd gateway.
+const REQUEST_TIMEOUT_SECS: u64 = 10;
+/// Max size of etcd gateway response.
+const MAX_RESPONSE_SIZE: usize = 64 * 1024 * 1024;
+
+/// Document encryption keys storage, which keeps key shares in the etcd cluster.
+/// The cluster is accessed through the etcd v3 JSON gateway. Key shares are encrypted
+/// with the key pair, derived from the configured secret, before leaving the node.
+pub struct EtcdKeyStorage {
+	/// Fetch client.
+	client: fetch::Client,
+	/// Gateway endpoints. Requests are sent to the first endpoint which responds.
+	endpoints: Vec<Url>,
+	/// Prefix of all keys, stored by this node.
+	prefix: Vec<u8>,
+	/// Key pair used to encrypt/decrypt key shares.
+	key_pair: KeyPair,
+}
+
+impl EtcdKeyStorage {
+	/// Create new etcd key storage.
+	pub fn new(endpoints: Vec<String>, prefix: String, secret: Secret) -> Result<Self, Error> {
+		let endpoints = endpoints.into_iter()
+			.map(|endpoint| Url::parse(&endpoint)
+				.map_err(|e| Error::Internal(format!("invalid etcd endpoint {}: {}", endpoint, e))))
+			.collect::<Result<Vec<_>, _>>()?;
+		if endpoints.is_empty() {
+			return Err(Error::Internal("at least one etcd endpoint is required".into()));
+		}
+
+		Ok(EtcdKeyStorage {
+			client: fetch::Client::new().map_err(|e| Error::Internal(format!("{:?}", e)))?,
+			endpoints: endpoints,
+			prefix: prefix.into_bytes(),
+			key_pair: KeyPair::from_secret(secret)?,
+		})
+	}
+
+	fn key(&self, document: &ServerKeyId) -> Vec<u8> {
+		let mut key = self.prefix.clone();
+		key.extend_from_slice(document.to_hex().as_bytes());
+		key
+	}
+
+	fn document(&self, key: &[u8]) -> Option<ServerKeyId> {
+		if !key.starts_with(&self.prefix) {
+			return None;
+		}
+
+		::std::str::from_utf8(&key[self.prefix.len()..]).ok()
+			.and_then(|id| id.from_hex().ok())
+			.and_then(|id: Vec<u8>| if id.len() == 32 { Some(ServerKeyId::from_slice(&id)) } else { None })
+	}
+
+	fn decrypt(&self, value: &Value) -> Result<DocumentKeyShare, Error> {
+		let value = decode_bytes(value)?;
+		let key = crypto::ecies::decrypt(self.key_pair.secret(), &DEFAULT_MAC, &value)?;
+		deserialize_key_share(&key)
+	}
+
+	/// Range of all keys, stored by this node.
+	fn range(&self) -> Result<Vec<Value>, Error> {
+		let response = self.call("range", json!({
+			"key": base64::encode(&self.prefix),
+			"range_end": base64::encode(&prefix_range_end(&self.prefix)),
+		}))?;
+		Ok(kvs(response))
+	}
+
+	/// Call etcd gateway method (`put`, `range`, `deleterange`).
+	fn call(&self, method: &str, body: Value) -> Result<Value, Error> {
+		let body = serde_json::to_vec(&body).map_err(|e| Error::Internal(e.to_string()))?;
+		let mut last_error = None;
+		for endpoint in &self.endpoints {
+			let url = match endpoint.join(&format!("v3beta/kv/{}", method)) {
+				Ok(url) => url,
+				Err(e) => {
+					last_error = Some(Error::Internal(e.to_string()));
+					continue;
+				},
+			};
+
+			let abort = Abort::default().with_max_duration(Duration::from_secs(REQUEST_TIMEOUT_SECS)).with_max_size(MAX_RESPONSE_SIZE);
+			let response = self.client.fetch(Request::post(url).with_body(body.clone()), abort).wait()
+				.map_err(|e| Error::Database(format!("etcd request to {} has failed: {:?}", endpoint, e)))
+				.and_then(|response| if response.is_success() {
+					Ok(response)
+				} else {
+					Err(Error::Database(format!("etcd request to {} has failed with status {}", endpoint, response.status())))
+				})
+				.and_then(|response| {
+					let mut body = Vec::new();
+					BodyReader::new(response).read_to_end(&mut body)?;
+					serde_json::from_slice(&body).map_err(|e| Error::Database(e.to_string()))
+				});
+
+			match response {
+				Ok(response) => return Ok(response),
+				Err(error) => {
+					warn!(target: "secretstore", "{}", error);
+					last_error = Some(error);
+				},
+			}
+		}
+
+		Err(last_error.expect("endpoints are checked to be non-empty in constructor; qed"))
+	}
+}
+
+impl KeyStorage for EtcdKeyStorage {
+	fn insert(&self, document: ServerKeyId, key: DocumentKeyShare) -> Result<(), Error> {
+		let key = serialize_key_share(key)?;
+		let encrypted = crypto::ecies::encrypt(self.key_pair.public(), &DEFAULT_MAC, &key)?;
+		self.call("put", json!({
+			"key": base64::encode(&self.key(&document)),
+			"value": base64::encode(&encrypted),
+		})).map(|_| ())
+	}
+
+	fn update(&self, document: ServerKeyId, key: DocumentKeyShare) -> Result<(), Error> {
+		self.insert(document, key)
+	}
+
+	fn get(&self, document: &ServerKeyId) -> Result<Option<DocumentKeyShare>, Error> {
+		let response = self.call("range", json!({
+			"key": base64::encode(&self.key(document)),
+		}))?;
+		match kvs(response).into_iter().next() {
+			Some(kv) => self.decrypt(&kv["value"]).map(Some),
+			None => Ok(None),
+		}
+	}
+
+	fn remove(&self, document: &ServerKeyId) -> Result<(), Error> {
+		self.call("deleterange", json!({
+			"key": base64::encode(&self.key(document)),
+		})).map(|_| ())
+	}
+
+	fn clear(&self) -> Result<(), Error> {
+		self.call("deleterange", json!({
+			"key": base64::encode(&self.prefix),
+			"range_end": base64::encode(&prefix_range_end(&self.prefix)),
+		})).map(|_| ())
+	}
+
+	fn contains(&self, document: &ServerKeyId) -> bool {
+		let response = self.call("range", json!({
+			"key": base64::encode(&self.key(document)),
+			"count_only": true,
+		}));
+		match response {
+			// zero count is omitted from the gateway response
+			Ok(response) => response["count"].as_str()
+				.and_then(|count| count.parse::<u64>().ok())
+				.map_or(false, |count| count != 0),
+			Err(error) => {
+				warn!(target: "secretstore", "Failed to check if key share of {} is stored in etcd: {}", document, error);
+				false
+			},
+		}
+	}
+
+	fn iter<'a>(&'a self) -> Box<Iterator<Item=(ServerKeyId, DocumentKeyShare)> + 'a> {
+		let kvs = match self.range() {
+			Ok(kvs) => kvs,
+			Err(error) => {
+				warn!(target: "secretstore", "Failed to read key shares from etcd: {}", error);
+				Vec::new()
+			},
+		};
+		Box::new(kvs.into_iter()
+			.filter_map(move |kv| {
+				let document = decode_bytes(&kv["key"]).ok().and_then(|key| self.document(&key))?;
+				match self.decrypt(&kv["value"]) {
+					Ok(key) => Some((document, key)),
+					Err(error) => {
+						warn!(target: "secretstore", "Failed to read key share of {} from etcd: {}", document, error);
+						None
+					},
+				}
+			}))
+	}
+}
+
+/// Key/value pairs from the range response.
+fn kvs(response: Value) -> Vec<Value> {
+	match response {
+		Value::Object(mut response) => match response.remove("kvs") {
+			Some(Value::Array(kvs)) => kvs,
+			_ => Vec::new(),
+		},
+		_ => Vec::new(),
+	}
+}
+
+/// Decode base64-encoded bytes from the gateway response.
+fn decode_bytes(value: &Value) -> Result<Vec<u8>, Error> {
+	value.as_str()
+		.ok_or_else(|| Error::Database("missing value in etcd response".into()))
+		.and_then(|value| base64::decode(value).map_err(|e| Error::Database(e.to_string())))
+}
+
+/// Returns the end of range, which covers all keys with given prefix.
+fn prefix_range_end(prefix: &[u8]) -> Vec<u8> {
+	let mut end = prefix.to_vec();
+	while let Some(last) = end.pop() {
+		if last < 0xff {
+			end.push(last + 1);
+			return end;
+		}
+	}
+
+	// all keys are in range
+	vec![0]
+}
+
+#[cfg(test)]
+mod tests {
+	use ethkey::{Random, Generator};
+	use types::ServerKeyId;
+	use super::{EtcdKeyStorage, prefix_range_end};
+
+	#[test]
+	fn prefix_range_end_works() {
+		assert_eq!(prefix_range_end(b"abc"), b"abd".to_vec());
+		assert_eq!(prefix_range_end(&[0x01, 0xff]), vec![0x02]);
+		assert_eq!(prefix_range_end(&[0xff, 0xff]), vec![0]);
+		assert_eq!(prefix_range_end(b""), vec![0]);
+	}
+
+	#[test]
+	fn etcd_keys_are_prefixed() {
+		let storage = EtcdKeyStorage::new(vec!["http://127.0.0.1:2379".into()], "/secretstore/node1/".into(),
+			Random.generate().unwrap().secret().clone()).unwrap();
+		let document = ServerKeyId::from(42);
+
+		let key = storage.key(&document);
+		assert!(key.starts_with(b"/secretstore/node1/"));
+		assert_eq!(storage.document(&key), Some(document));
+		assert_eq!(storage.document(b"/secretstore/node2/00"), None);
+	}
+
+	#[test]
+	fn etcd_storage_requires_endpoints() {
+		let secret = Random.generate().unwrap().secret().clone();
+		assert!(EtcdKeyStorage::new(vec![], "/".into(), secret.clone()).is_err());
+		assert!(EtcdKeyStorage::new(vec!["not an url".into()], "/".into(), secret).is_err());
+	}
+}
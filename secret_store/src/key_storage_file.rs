@@ -0,0 +1,214 @@
+// Copyright 2015-2018 Parity Technologies (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+use std::fs;
+use std::io::{ErrorKind, Write};
+use std::path::{Path, PathBuf};
+use parking_lot::Mutex;
+use rustc_hex::{ToHex, FromHex};
+use crypto::DEFAULT_MAC;
+use ethkey::{crypto, KeyPair, Secret};
+use key_storage::{KeyStorage, DocumentKeyShare, serialize_key_share, deserialize_key_share};
+use types::{Error, ServerKeyId};
+
+/// Extension of the temporary file, used to atomically replace key share file.
+const TEMP_FILE_EXTENSION: &'static str = "tmp";
+
+/// Document encryption keys storage, which keeps every key share in a separate file.
+/// Files are encrypted with the key pair, derived from the configured secret, so the
+/// directory could be safely copied (or mounted from network storage) when the node is replaced.
+pub struct EncryptedFileKeyStorage {
+	/// Directory with key shares files.
+	path: PathBuf,
+	/// Key pair used to encrypt/decrypt key shares.
+	key_pair: KeyPair,
+	/// Writes guard.
+	write_lock: Mutex<()>,
+}
+
+impl EncryptedFileKeyStorage {
+	/// Create new encrypted file key storage in given directory.
+	pub fn new<P: Into<PathBuf>>(path: P, secret: Secret) -> Result<Self, Error> {
+		let path = path.into();
+		fs::create_dir_all(&path)?;
+
+		Ok(EncryptedFileKeyStorage {
+			path: path,
+			key_pair: KeyPair::from_secret(secret)?,
+			write_lock: Mutex::new(()),
+		})
+	}
+
+	fn key_path(&self, document: &ServerKeyId) -> PathBuf {
+		self.path.join(document.to_hex())
+	}
+
+	fn read_key(&self, path: &Path) -> Result<Option<DocumentKeyShare>, Error> {
+		let encrypted = match fs::read(path) {
+			Ok(encrypted) => encrypted,
+			Err(ref e) if e.kind() == ErrorKind::NotFound => return Ok(None),
+			Err(e) => return Err(e.into()),
+		};
+
+		let key = crypto::ecies::decrypt(self.key_pair.secret(), &DEFAULT_MAC, &encrypted)?;
+		deserialize_key_share(&key).map(Some)
+	}
+
+	fn documents(&self) -> Result<Vec<ServerKeyId>, Error> {
+		let mut documents = Vec::new();
+		for entry in fs::read_dir(&self.path)? {
+			let entry = entry?;
+			let document = entry.file_name().into_string().ok()
+				.and_then(|name| name.from_hex().ok())
+				.and_then(|id: Vec<u8>| if id.len() == 32 { Some(ServerKeyId::from_slice(&id)) } else { None });
+			if let Some(document) = document {
+				documents.push(document);
+			}
+		}
+		Ok(documents)
+	}
+}
+
+impl KeyStorage for EncryptedFileKeyStorage {
+	fn insert(&self, document: ServerKeyId, key: DocumentKeyShare) -> Result<(), Error> {
+		let key = serialize_key_share(key)?;
+		let encrypted = crypto::ecies::encrypt(self.key_pair.public(), &DEFAULT_MAC, &key)?;
+
+		let _lock = self.write_lock.lock();
+		let path = self.key_path(&document);
+		let temp_path = path.with_extension(TEMP_FILE_EXTENSION);
+		{
+			let mut file = fs::File::create(&temp_path)?;
+			file.write_all(&encrypted)?;
+			file.sync_all()?;
+		}
+		fs::rename(&temp_path, &path).map_err(Into::into)
+	}
+
+	fn update(&self, document: ServerKeyId, key: DocumentKeyShare) -> Result<(), Error> {
+		self.insert(document, key)
+	}
+
+	fn get(&self, document: &ServerKeyId) -> Result<Option<DocumentKeyShare>, Error> {
+		self.read_key(&self.key_path(document))
+	}
+
+	fn remove(&self, document: &ServerKeyId) -> Result<(), Error> {
+		let _lock = self.write_lock.lock();
+		match fs::remove_file(self.key_path(document)) {
+			Err(ref e) if e.kind() == ErrorKind::NotFound => Ok(()),
+			result => result.map_err(Into::into),
+		}
+	}
+
+	fn clear(&self) -> Result<(), Error> {
+		for document in self.documents()? {
+			self.remove(&document)?;
+		}
+		Ok(())
+	}
+
+	fn contains(&self, document: &ServerKeyId) -> bool {
+		self.key_path(document).is_file()
+	}
+
+	fn iter<'a>(&'a self) -> Box<Iterator<Item=(ServerKeyId, DocumentKeyShare)> + 'a> {
+		let documents = self.documents()
+			.map_err(|e| warn!(target: "secretstore", "error listing key shares in {}: {}", self.path.display(), e))
+			.unwrap_or_default();
+		Box::new(documents.into_iter()
+			.filter_map(move |document| self.get(&document).ok()
+				.and_then(|key| key.map(|key| (document, key)))))
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	extern crate tempdir;
+
+	use std::fs;
+	use self::tempdir::TempDir;
+	use rustc_hex::ToHex;
+	use serde_json;
+	use ethkey::{Random, Generator, Public};
+	use key_storage::{KeyStorage, DocumentKeyShare, DocumentKeyShareVersion};
+	use types::ServerKeyId;
+	use super::EncryptedFileKeyStorage;
+
+	fn key_share(threshold: usize) -> DocumentKeyShare {
+		DocumentKeyShare {
+			author: Default::default(),
+			threshold: threshold,
+			public: Public::default(),
+			common_point: Some(Random.generate().unwrap().public().clone()),
+			encrypted_point: Some(Random.generate().unwrap().public().clone()),
+			versions: vec![DocumentKeyShareVersion {
+				hash: Default::default(),
+				id_numbers: vec![
+					(Random.generate().unwrap().public().clone(), Random.generate().unwrap().secret().clone())
+				].into_iter().collect(),
+				secret_share: Random.generate().unwrap().secret().clone(),
+			}],
+		}
+	}
+
+	#[test]
+	fn encrypted_file_key_storage() {
+		let tempdir = TempDir::new("").unwrap();
+		let secret = Random.generate().unwrap().secret().clone();
+		let (key1, value1) = (ServerKeyId::from(1), key_share(100));
+		let (key2, value2) = (ServerKeyId::from(2), key_share(200));
+		let key3 = ServerKeyId::from(3);
+
+		let key_storage = EncryptedFileKeyStorage::new(tempdir.path(), secret.clone()).unwrap();
+		key_storage.insert(key1.clone(), value1.clone()).unwrap();
+		key_storage.insert(key2.clone(), value2.clone()).unwrap();
+		assert_eq!(key_storage.get(&key1), Ok(Some(value1.clone())));
+		assert_eq!(key_storage.get(&key2), Ok(Some(value2.clone())));
+		assert_eq!(key_storage.get(&key3), Ok(None));
+		assert!(key_storage.contains(&key1));
+		assert!(!key_storage.contains(&key3));
+		drop(key_storage);
+
+		let key_storage = EncryptedFileKeyStorage::new(tempdir.path(), secret).unwrap();
+		assert_eq!(key_storage.get(&key1), Ok(Some(value1.clone())));
+		assert_eq!(key_storage.get(&key2), Ok(Some(value2.clone())));
+		assert_eq!(key_storage.iter().count(), 2);
+
+		key_storage.remove(&key1).unwrap();
+		key_storage.remove(&key3).unwrap();
+		assert_eq!(key_storage.get(&key1), Ok(None));
+		assert_eq!(key_storage.iter().collect::<Vec<_>>(), vec![(key2, value2)]);
+
+		key_storage.clear().unwrap();
+		assert_eq!(key_storage.iter().count(), 0);
+	}
+
+	#[test]
+	fn encrypted_file_key_storage_requires_same_secret() {
+		let tempdir = TempDir::new("").unwrap();
+		let key = ServerKeyId::from(1);
+
+		let key_storage = EncryptedFileKeyStorage::new(tempdir.path(), Random.generate().unwrap().secret().clone()).unwrap();
+		key_storage.insert(key.clone(), key_share(1)).unwrap();
+
+		let stored = fs::read(tempdir.path().join(key.to_hex())).unwrap();
+		assert!(serde_json::from_slice::<serde_json::Value>(&stored).is_err());
+
+		let key_storage = EncryptedFileKeyStorage::new(tempdir.path(), Random.generate().unwrap().secret().clone()).unwrap();
+		assert!(key_storage.get(&key).is_err());
+	}
+}
@@ -14,6 +14,7 @@
 // You should have received a copy of the GNU General Public License
 // along with Parity.  If not, see <http://www.gnu.org/licenses/>.
 
+extern crate base64;
 extern crate byteorder;
 extern crate ethabi;
 extern crate ethcore;
@@ -24,6 +25,7 @@ extern crate ethcore_sync as sync;
 extern crate ethcore_transaction as transaction;
 extern crate ethereum_types;
 extern crate ethkey;
+extern crate fetch;
 extern crate futures_cpupool;
 extern crate hyper;
 extern crate keccak_hash as hash;
@@ -31,7 +33,6 @@ extern crate kvdb;
 extern crate parking_lot;
 extern crate rustc_hex;
 extern crate serde;
-extern crate serde_json;
 extern crate tiny_keccak;
 extern crate tokio;
 extern crate tokio_core;
@@ -49,6 +50,8 @@ extern crate futures;
 #[macro_use]
 extern crate serde_derive;
 #[macro_use]
+extern crate serde_json;
+#[macro_use]
 extern crate lazy_static;
 #[macro_use]
 extern crate log;
@@ -64,6 +67,8 @@ mod traits;
 mod acl_storage;
 mod key_server;
 mod key_storage;
+mod key_storage_etcd;
+mod key_storage_file;
 mod serialization;
 mod key_server_set;
 mod node_key_pair;
@@ -77,7 +82,7 @@ use ethcore::miner::Miner;
 use sync::SyncProvider;
 
 pub use types::{ServerKeyId, EncryptedDocumentKey, RequestSignature, Public,
-	Error, NodeAddress, ContractAddress, ServiceConfiguration, ClusterConfiguration};
+	Error, NodeAddress, ContractAddress, ServiceConfiguration, ClusterConfiguration, KeyStorageConfiguration};
 pub use traits::{NodeKeyPair, KeyServer};
 pub use self::node_key_pair::{PlainNodeKeyPair, KeyStoreNodeKeyPair};
 
@@ -91,7 +96,13 @@ pub fn start(client: Arc<Client>, sync: Arc<SyncProvider>, miner: Arc<Miner>, se
 
 	let key_server_set = key_server_set::OnChainKeyServerSet::new(trusted_client.clone(), config.cluster_config.key_server_set_contract_address.take(),
 		self_key_pair.clone(), config.cluster_config.auto_migrate_enabled, config.cluster_config.nodes.clone())?;
-	let key_storage = Arc::new(key_storage::PersistentKeyStorage::new(db)?);
+	let key_storage: Arc<key_storage::KeyStorage> = match config.key_storage.clone() {
+		KeyStorageConfiguration::Database => Arc::new(key_storage::PersistentKeyStorage::new(db)?),
+		KeyStorageConfiguration::EncryptedFiles { path, secret } =>
+			Arc::new(key_storage_file::EncryptedFileKeyStorage::new(path, secret)?),
+		KeyStorageConfiguration::Etcd { endpoints, prefix, secret } =>
+			Arc::new(key_storage_etcd::EtcdKeyStorage::new(endpoints, prefix, secret)?),
+	};
 	let key_server = Arc::new(key_server::KeyServerImpl::new(&config.cluster_config, key_server_set.clone(), self_key_pair.clone(), acl_storage.clone(), key_storage.clone())?);
 	let cluster = key_server.cluster();
 	let key_server: Arc<KeyServer> = key_server;
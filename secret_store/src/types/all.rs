@@ -70,6 +70,31 @@ pub struct ServiceConfiguration {
 	pub acl_check_contract_address: Option<ContractAddress>,
 	/// Cluster configuration.
 	pub cluster_config: ClusterConfiguration,
+	/// Key shares storage configuration.
+	pub key_storage: KeyStorageConfiguration,
+}
+
+/// Key shares storage configuration.
+#[derive(Debug, Clone)]
+pub enum KeyStorageConfiguration {
+	/// Key shares are stored in the node database.
+	Database,
+	/// Key shares are stored in the given directory, one file per key, encrypted with the given secret.
+	EncryptedFiles {
+		/// Directory path.
+		path: String,
+		/// Secret used to encrypt key shares.
+		secret: ethkey::Secret,
+	},
+	/// Key shares are stored in the etcd cluster (accessed through v3 JSON gateway), encrypted with the given secret.
+	Etcd {
+		/// Gateway endpoints (e.g. http://127.0.0.1:2379).
+		endpoints: Vec<String>,
+		/// Prefix of all keys, written by this node.
+		prefix: String,
+		/// Secret used to encrypt key shares.
+		secret: ethkey::Secret,
+	},
 }
 
 /// Key server cluster configuration
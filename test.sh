@@ -36,6 +36,10 @@ time cargo check --no-default-features
 time cargo check --manifest-path util/io/Cargo.toml --no-default-features
 time cargo check --manifest-path util/io/Cargo.toml --features "mio"
 
+# Validate embedded feature matrix
+echo "________Validate feature matrix________"
+time ./scripts/validate_features.sh
+
 # Validate chainspecs
 echo "________Validate chainspecs________"
 time ./scripts/validate_chainspecs.sh
@@ -80,6 +80,9 @@ extern crate num_cpus;
 extern crate timer;
 extern crate fnv;
 extern crate time;
+extern crate libc;
+#[macro_use]
+extern crate lazy_static;
 
 #[cfg(feature = "mio")]
 mod service_mio;
@@ -87,6 +90,8 @@ mod service_mio;
 mod service_non_mio;
 #[cfg(feature = "mio")]
 mod worker;
+pub mod threads;
+pub mod queue;
 
 use std::cell::Cell;
 use std::{fmt, error};
@@ -138,8 +143,11 @@ impl From<::std::io::Error> for IoError {
 
 #[cfg(feature = "mio")]
 impl<Message> From<NotifyError<service_mio::IoMessage<Message>>> for IoError where Message: Send {
-	fn from(_err: NotifyError<service_mio::IoMessage<Message>>) -> IoError {
-		IoError::Mio(::std::io::Error::new(::std::io::ErrorKind::ConnectionAborted, "Network IO notification error"))
+	fn from(err: NotifyError<service_mio::IoMessage<Message>>) -> IoError {
+		match err {
+			NotifyError::Full(_) => IoError::Mio(::std::io::Error::new(::std::io::ErrorKind::WouldBlock, "Network IO notification queue is full")),
+			_ => IoError::Mio(::std::io::Error::new(::std::io::ErrorKind::ConnectionAborted, "Network IO notification error")),
+		}
 	}
 }
 
@@ -177,6 +185,11 @@ pub trait IoHandler<Message>: Send + Sync where Message: Send + Sync + 'static {
 pub use service_mio::{TimerToken, StreamToken, IoContext, IoService, IoChannel, IoManager, TOKENS_PER_HANDLER};
 #[cfg(not(feature = "mio"))]
 pub use service_non_mio::{TimerToken, IoContext, IoService, IoChannel, TOKENS_PER_HANDLER};
+pub use threads::{ThreadPoolUsage, ThreadPoolStats};
+pub use queue::{QueueConfig, QueueStats, OverflowPolicy};
+
+/// Default number of worker threads of the IO service.
+pub const DEFAULT_IO_WORKERS: usize = 4;
 
 #[cfg(test)]
 mod tests {
@@ -275,4 +288,17 @@ mod tests {
 		thread::sleep(Duration::from_secs(2));
 		assert!(handler.0.load(atomic::Ordering::SeqCst) >= 2);
 	}
+
+	#[test]
+	fn queue_stats_are_reported() {
+		#[derive(Clone)]
+		struct MyMessage;
+
+		let config = QueueConfig { capacity: 8, overflow: OverflowPolicy::DropMessages };
+		let service = IoService::<MyMessage>::start_with_config("test-queue-pool", 1, config).expect("Error creating network service");
+		assert_eq!(service.queue_stats().capacity, 8);
+
+		let pool = threads::thread_pools().into_iter().find(|pool| pool.name == "test-queue-pool").unwrap();
+		assert_eq!(pool.queue.map(|queue| queue.capacity), Some(8));
+	}
 }
@@ -0,0 +1,162 @@
+// Copyright 2015-2018 Parity Technologies (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Bounded work queue of the IO service.
+//!
+//! Work waiting for the worker threads is limited to `QueueConfig::capacity` items.
+//! Once the queue is full, new work is handled according to the `OverflowPolicy`.
+
+use std::cmp;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+/// Default maximal number of work items waiting for the worker threads.
+pub const DEFAULT_QUEUE_CAPACITY: usize = 16384;
+
+/// What to do with new work once the queue is full.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum OverflowPolicy {
+	/// Handle the work on the thread which produced it. The producer (e.g. the event loop)
+	/// is stalled until the work is done, which in turn blocks its own producers.
+	Backpressure,
+	/// Drop user messages. IO events and timers are still handled as with `Backpressure`,
+	/// since losing them would stall the affected handlers.
+	DropMessages,
+}
+
+/// IO service work queue configuration.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct QueueConfig {
+	/// Maximal number of work items waiting for the worker threads.
+	pub capacity: usize,
+	/// What to do with new work once the queue is full.
+	pub overflow: OverflowPolicy,
+}
+
+impl Default for QueueConfig {
+	fn default() -> Self {
+		QueueConfig {
+			capacity: DEFAULT_QUEUE_CAPACITY,
+			overflow: OverflowPolicy::Backpressure,
+		}
+	}
+}
+
+/// Work queue statistics.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct QueueStats {
+	/// Number of work items waiting for the worker threads.
+	pub depth: usize,
+	/// Highest observed depth of the queue.
+	pub max_depth: usize,
+	/// Maximal depth of the queue.
+	pub capacity: usize,
+	/// Number of work items which didn't fit in the queue.
+	pub overflowed: usize,
+	/// Number of messages dropped because of the overflow.
+	pub dropped: usize,
+}
+
+/// Depth counters of a work queue, shared between the producer and the workers.
+#[derive(Debug)]
+pub struct QueueMetrics {
+	capacity: usize,
+	depth: AtomicUsize,
+	max_depth: AtomicUsize,
+	overflowed: AtomicUsize,
+	dropped: AtomicUsize,
+}
+
+impl QueueMetrics {
+	/// Create counters for a queue with given capacity.
+	pub fn new(capacity: usize) -> Self {
+		QueueMetrics {
+			capacity: cmp::max(capacity, 1),
+			depth: AtomicUsize::new(0),
+			max_depth: AtomicUsize::new(0),
+			overflowed: AtomicUsize::new(0),
+			dropped: AtomicUsize::new(0),
+		}
+	}
+
+	/// Reserve a slot for a new work item. Returns `false` if the queue is full,
+	/// in which case the item must not be queued.
+	pub fn try_push(&self) -> bool {
+		let depth = self.depth.fetch_add(1, Ordering::AcqRel);
+		if depth >= self.capacity {
+			self.depth.fetch_sub(1, Ordering::AcqRel);
+			self.overflowed.fetch_add(1, Ordering::Relaxed);
+			return false;
+		}
+
+		let mut max_depth = self.max_depth.load(Ordering::Relaxed);
+		while depth + 1 > max_depth {
+			let previous = self.max_depth.compare_and_swap(max_depth, depth + 1, Ordering::Relaxed);
+			if previous == max_depth {
+				break;
+			}
+			max_depth = previous;
+		}
+		true
+	}
+
+	/// Release a slot of the work item taken by a worker.
+	pub fn pop(&self) {
+		self.depth.fetch_sub(1, Ordering::AcqRel);
+	}
+
+	/// Note a work item dropped because of the overflow.
+	pub fn note_dropped(&self) {
+		self.dropped.fetch_add(1, Ordering::Relaxed);
+	}
+
+	/// Current statistics of the queue.
+	pub fn stats(&self) -> QueueStats {
+		QueueStats {
+			depth: self.depth.load(Ordering::Acquire),
+			max_depth: self.max_depth.load(Ordering::Relaxed),
+			capacity: self.capacity,
+			overflowed: self.overflowed.load(Ordering::Relaxed),
+			dropped: self.dropped.load(Ordering::Relaxed),
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::{QueueMetrics, QueueStats};
+
+	#[test]
+	fn should_reject_work_over_capacity() {
+		let queue = QueueMetrics::new(2);
+		assert!(queue.try_push());
+		assert!(queue.try_push());
+		assert!(!queue.try_push());
+		queue.note_dropped();
+
+		queue.pop();
+		assert!(queue.try_push());
+		queue.pop();
+		queue.pop();
+
+		assert_eq!(queue.stats(), QueueStats {
+			depth: 0,
+			max_depth: 2,
+			capacity: 2,
+			overflowed: 1,
+			dropped: 1,
+		});
+	}
+}
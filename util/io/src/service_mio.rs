@@ -24,6 +24,9 @@ use crossbeam::sync::chase_lev;
 use slab::Slab;
 use {IoError, IoHandler};
 use worker::{Worker, Work, WorkType};
+use threads::ThreadPoolUsage;
+use queue::{QueueConfig, QueueMetrics, QueueStats, OverflowPolicy};
+use DEFAULT_IO_WORKERS;
 use parking_lot::{Condvar, RwLock, Mutex};
 use std::time::Duration;
 
@@ -186,16 +189,21 @@ pub struct IoManager<Message> where Message: Send + Sync {
 	workers: Vec<Worker>,
 	worker_channel: chase_lev::Worker<Work<Message>>,
 	work_ready: Arc<Condvar>,
+	queue: Arc<QueueMetrics>,
+	overflow: OverflowPolicy,
 }
 
 impl<Message> IoManager<Message> where Message: Send + Sync + 'static {
 	/// Creates a new instance and registers it with the event loop.
 	pub fn start(
 		event_loop: &mut EventLoop<IoManager<Message>>,
-		handlers: Arc<RwLock<Slab<Arc<IoHandler<Message>>>>>
+		handlers: Arc<RwLock<Slab<Arc<IoHandler<Message>>>>>,
+		num_workers: usize,
+		usage: Arc<ThreadPoolUsage>,
+		queue: Arc<QueueMetrics>,
+		overflow: OverflowPolicy,
 	) -> Result<(), IoError> {
 		let (worker, stealer) = chase_lev::deque();
-		let num_workers = 4;
 		let work_ready_mutex =  Arc::new(Mutex::new(()));
 		let work_ready = Arc::new(Condvar::new());
 		let workers = (0..num_workers).map(|i|
@@ -205,6 +213,8 @@ impl<Message> IoManager<Message> where Message: Send + Sync + 'static {
 				IoChannel::new(event_loop.channel(), Arc::downgrade(&handlers)),
 				work_ready.clone(),
 				work_ready_mutex.clone(),
+				usage.clone(),
+				queue.clone(),
 			)
 		).collect();
 
@@ -214,29 +224,53 @@ impl<Message> IoManager<Message> where Message: Send + Sync + 'static {
 			worker_channel: worker,
 			workers: workers,
 			work_ready: work_ready,
+			queue: queue,
+			overflow: overflow,
 		};
 		event_loop.run(&mut io)?;
 		Ok(())
 	}
+
+	/// Queue the work for the worker threads. If the queue is full the work is handled
+	/// on the event loop thread (or dropped), so no new events are processed until the workers catch up.
+	fn dispatch(&mut self, event_loop: &mut EventLoop<IoManager<Message>>, work: Work<Message>) {
+		if self.queue.try_push() {
+			self.worker_channel.push(work);
+			return;
+		}
+
+		match (self.overflow, &work.work_type) {
+			(OverflowPolicy::DropMessages, &WorkType::Message(_)) => {
+				trace!(target: "io", "Work queue is full, dropping message for handler {}", work.handler_id);
+				self.queue.note_dropped();
+			},
+			_ => {
+				trace!(target: "io", "Work queue is full, handling work on the event loop thread");
+				self.work_ready.notify_all();
+				Worker::do_work(work, IoChannel::new(event_loop.channel(), Arc::downgrade(&self.handlers)));
+			},
+		}
+	}
 }
 
 impl<Message> Handler for IoManager<Message> where Message: Send + Sync + 'static {
 	type Timeout = Token;
 	type Message = IoMessage<Message>;
 
-	fn ready(&mut self, _event_loop: &mut EventLoop<Self>, token: Token, events: Ready) {
+	fn ready(&mut self, event_loop: &mut EventLoop<Self>, token: Token, events: Ready) {
 		let handler_index  = token.0 / TOKENS_PER_HANDLER;
 		let token_id  = token.0 % TOKENS_PER_HANDLER;
-		if let Some(handler) = self.handlers.read().get(handler_index) {
+		let handler = self.handlers.read().get(handler_index).cloned();
+		if let Some(handler) = handler {
 			if events.is_hup() {
-				self.worker_channel.push(Work { work_type: WorkType::Hup, token: token_id, handler: handler.clone(), handler_id: handler_index });
+				self.dispatch(event_loop, Work { work_type: WorkType::Hup, token: token_id, handler: handler.clone(), handler_id: handler_index });
 			}
 			else {
 				if events.is_readable() {
-					self.worker_channel.push(Work { work_type: WorkType::Readable, token: token_id, handler: handler.clone(), handler_id: handler_index });
+					self.dispatch(event_loop, Work { work_type: WorkType::Readable, token: token_id, handler: handler.clone(), handler_id: handler_index });
 				}
 				if events.is_writable() {
-					self.worker_channel.push(Work { work_type: WorkType::Writable, token: token_id, handler: handler.clone(), handler_id: handler_index });
+					self.dispatch(event_loop, Work { work_type: WorkType::Writable, token: token_id, handler: handler.clone(), handler_id: handler_index });
 				}
 			}
 			self.work_ready.notify_all();
@@ -246,7 +280,8 @@ impl<Message> Handler for IoManager<Message> where Message: Send + Sync + 'stati
 	fn timeout(&mut self, event_loop: &mut EventLoop<Self>, token: Token) {
 		let handler_index  = token.0  / TOKENS_PER_HANDLER;
 		let token_id  = token.0  % TOKENS_PER_HANDLER;
-		if let Some(handler) = self.handlers.read().get(handler_index) {
+		let handler = self.handlers.read().get(handler_index).cloned();
+		if let Some(handler) = handler {
 			let maybe_timer = self.timers.read().get(&token.0).cloned();
 			if let Some(timer) = maybe_timer {
 				if timer.once {
@@ -255,7 +290,7 @@ impl<Message> Handler for IoManager<Message> where Message: Send + Sync + 'stati
 				} else {
 					event_loop.timeout(token, timer.delay).expect("Error re-registering user timer");
 				}
-				self.worker_channel.push(Work { work_type: WorkType::Timeout, token: token_id, handler: handler.clone(), handler_id: handler_index });
+				self.dispatch(event_loop, Work { work_type: WorkType::Timeout, token: token_id, handler: handler, handler_id: handler_index });
 				self.work_ready.notify_all();
 			}
 		}
@@ -317,9 +352,9 @@ impl<Message> Handler for IoManager<Message> where Message: Send + Sync + 'stati
 			IoMessage::UserMessage(data) => {
 				//TODO: better way to iterate the slab
 				for id in 0 .. MAX_HANDLERS {
-					if let Some(h) = self.handlers.read().get(id) {
-						let handler = h.clone();
-						self.worker_channel.push(Work {
+					let handler = self.handlers.read().get(id).cloned();
+					if let Some(handler) = handler {
+						self.dispatch(event_loop, Work {
 							work_type: WorkType::Message(data.clone()),
 							token: 0,
 							handler: handler,
@@ -433,27 +468,52 @@ pub struct IoService<Message> where Message: Send + Sync + 'static {
 	thread: Mutex<Option<JoinHandle<()>>>,
 	host_channel: Mutex<Sender<IoMessage<Message>>>,
 	handlers: Arc<RwLock<Slab<Arc<IoHandler<Message>>>>>,
+	queue: Arc<QueueMetrics>,
 }
 
 impl<Message> IoService<Message> where Message: Send + Sync + 'static {
 	/// Starts IO event loop
 	pub fn start() -> Result<IoService<Message>, IoError> {
+		Self::start_with_workers("io", DEFAULT_IO_WORKERS)
+	}
+
+	/// Starts IO event loop with given number of worker threads.
+	/// Utilization of the workers is reported under given name.
+	pub fn start_with_workers(name: &str, num_workers: usize) -> Result<IoService<Message>, IoError> {
+		Self::start_with_config(name, num_workers, QueueConfig::default())
+	}
+
+	/// Starts IO event loop with given number of worker threads and work queue configuration.
+	/// The same capacity limits the number of messages waiting for the event loop, `send` fails
+	/// when that one is full.
+	pub fn start_with_config(name: &str, num_workers: usize, queue_config: QueueConfig) -> Result<IoService<Message>, IoError> {
+		let usage = ThreadPoolUsage::register(name, num_workers);
+		let queue = Arc::new(QueueMetrics::new(queue_config.capacity));
+		usage.attach_queue(queue.clone());
 		let mut config = EventLoopBuilder::new();
 		config.messages_per_tick(1024);
+		config.notify_capacity(queue_config.capacity);
 		let mut event_loop = config.build().expect("Error creating event loop");
 		let channel = event_loop.channel();
 		let handlers = Arc::new(RwLock::new(Slab::with_capacity(MAX_HANDLERS)));
 		let h = handlers.clone();
+		let q = queue.clone();
 		let thread = thread::spawn(move || {
-			IoManager::<Message>::start(&mut event_loop, h).expect("Error starting IO service");
+			IoManager::<Message>::start(&mut event_loop, h, num_workers, usage, q, queue_config.overflow).expect("Error starting IO service");
 		});
 		Ok(IoService {
 			thread: Mutex::new(Some(thread)),
 			host_channel: Mutex::new(channel),
 			handlers: handlers,
+			queue: queue,
 		})
 	}
 
+	/// Statistics of the queue of work waiting for the worker threads.
+	pub fn queue_stats(&self) -> QueueStats {
+		self.queue.stats()
+	}
+
 	pub fn stop(&self) {
 		trace!(target: "shutdown", "[IoService] Closing...");
 		// Clear handlers so that shared pointers are not stuck on stack
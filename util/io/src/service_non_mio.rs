@@ -23,6 +23,8 @@ use {IoError, IoHandler};
 use parking_lot::{RwLock, Mutex};
 use num_cpus;
 use std::time::Duration;
+use threads::{self, ThreadPoolUsage};
+use queue::{QueueConfig, QueueMetrics, QueueStats, OverflowPolicy};
 use timer::{Timer, Guard as TimerGuard};
 use time::Duration as TimeDuration;
 
@@ -90,13 +92,7 @@ impl<Message> IoContext<Message> where Message: Send + Sync + 'static {
 
 	/// Broadcast a message to other IO clients
 	pub fn message(&self, message: Message) -> Result<(), IoError> {
-		if let Some(ref channel) = *self.shared.channel.lock() {
-			channel.push(WorkTask::UserMessage(Arc::new(message)));
-		}
-		for thread in self.shared.threads.read().iter() {
-			thread.unpark();
-		}
-
+		push_task(&self.shared, WorkTask::UserMessage(Arc::new(message)));
 		Ok(())
 	}
 
@@ -130,14 +126,11 @@ impl<Message> IoChannel<Message> where Message: Send + Sync + 'static {
 	/// Send a message through the channel
 	pub fn send(&self, message: Message) -> Result<(), IoError> {
 		if let Some(shared) = self.shared.upgrade() {
-			match *shared.channel.lock() {
-				Some(ref channel) => channel.push(WorkTask::UserMessage(Arc::new(message))),
-				None => self.send_sync(message)?
-			};
-
-			for thread in shared.threads.read().iter() {
-				thread.unpark();
+			if shared.channel.lock().is_none() {
+				return self.send_sync(message);
 			}
+
+			push_task(&shared, WorkTask::UserMessage(Arc::new(message)));
 		}
 
 		Ok(())
@@ -161,13 +154,7 @@ impl<Message> IoChannel<Message> where Message: Send + Sync + 'static {
 	// Send low level io message
 	fn send_raw(&self, message: WorkTask<Message>) {
 		if let Some(shared) = self.shared.upgrade() {
-			if let Some(ref channel) = *shared.channel.lock() {
-				channel.push(message);
-			}
-
-			for thread in shared.threads.read().iter() {
-				thread.unpark();
-			}
+			push_task(&shared, message);
 		}
 	}
 
@@ -199,6 +186,10 @@ struct Shared<Message> where Message: Send + Sync + 'static {
 	timers: Mutex<FnvHashMap<TimerToken, TimerGuard>>,
 	// Channel used to send work to the worker threads.
 	channel: Mutex<Option<chase_lev::Worker<WorkTask<Message>>>>,
+	// Depth of the work queue.
+	queue: Arc<QueueMetrics>,
+	// What to do with new work once the queue is full.
+	overflow: OverflowPolicy,
 }
 
 // Messages used to communicate with the event loop from other threads.
@@ -224,7 +215,21 @@ impl<Message> Clone for WorkTask<Message> where Message: Send + Sized {
 impl<Message> IoService<Message> where Message: Send + Sync + 'static {
 	/// Starts IO event loop
 	pub fn start() -> Result<IoService<Message>, IoError> {
+		Self::start_with_workers("io", num_cpus::get())
+	}
+
+	/// Starts IO event loop with given number of worker threads.
+	/// Utilization of the workers is reported under given name.
+	pub fn start_with_workers(name: &str, num_workers: usize) -> Result<IoService<Message>, IoError> {
+		Self::start_with_config(name, num_workers, QueueConfig::default())
+	}
+
+	/// Starts IO event loop with given number of worker threads and work queue configuration.
+	pub fn start_with_config(name: &str, num_workers: usize, queue_config: QueueConfig) -> Result<IoService<Message>, IoError> {
 		let (tx, rx) = chase_lev::deque();
+		let usage = ThreadPoolUsage::register(name, num_workers);
+		let queue = Arc::new(QueueMetrics::new(queue_config.capacity));
+		usage.attach_queue(queue.clone());
 
 		let shared = Arc::new(Shared {
 			handlers: RwLock::new(Slab::with_capacity(MAX_HANDLERS)),
@@ -232,13 +237,17 @@ impl<Message> IoService<Message> where Message: Send + Sync + 'static {
 			timer: Mutex::new(Timer::new()),
 			timers: Mutex::new(FnvHashMap::default()),
 			channel: Mutex::new(Some(tx)),
+			queue: queue,
+			overflow: queue_config.overflow,
 		});
 
-		let thread_joins = (0 .. num_cpus::get()).map(|_| {
+		let thread_joins = (0 .. num_workers).map(|index| {
 			let rx = rx.clone();
 			let shared = shared.clone();
+			let usage = usage.clone();
 			thread::spawn(move || {
-				do_work(&shared, rx)
+				threads::pin_current_thread(index);
+				do_work(&shared, rx, &usage)
 			})
 		}).collect::<Vec<_>>();
 
@@ -283,15 +292,15 @@ impl<Message> IoService<Message> where Message: Send + Sync + 'static {
 
 	/// Send a message over the network. Normaly `HostIo::send` should be used. This can be used from non-io threads.
 	pub fn send_message(&self, message: Message) -> Result<(), IoError> {
-		if let Some(ref channel) = *self.shared.channel.lock() {
-			channel.push(WorkTask::UserMessage(Arc::new(message)));
-		}
-		for thread in self.shared.threads.read().iter() {
-			thread.unpark();
-		}
+		push_task(&self.shared, WorkTask::UserMessage(Arc::new(message)));
 		Ok(())
 	}
 
+	/// Statistics of the queue of work waiting for the worker threads.
+	pub fn queue_stats(&self) -> QueueStats {
+		self.shared.queue.stats()
+	}
+
 	/// Create a new message channel
 	#[inline]
 	pub fn channel(&self) -> IoChannel<Message> {
@@ -307,7 +316,58 @@ impl<Message> Drop for IoService<Message> where Message: Send + Sync {
 	}
 }
 
-fn do_work<Message>(shared: &Arc<Shared<Message>>, rx: chase_lev::Stealer<WorkTask<Message>>)
+// Queue the work for the worker threads. If the queue is full the work is handled
+// on the calling thread (or dropped), which slows down the producer.
+fn push_task<Message>(shared: &Arc<Shared<Message>>, task: WorkTask<Message>)
+	where Message: Send + Sync + 'static
+{
+	if !shared.queue.try_push() {
+		match (shared.overflow, task) {
+			(OverflowPolicy::DropMessages, WorkTask::UserMessage(_)) => {
+				trace!(target: "io", "Work queue is full, dropping message");
+				shared.queue.note_dropped();
+			},
+			(_, task) => {
+				trace!(target: "io", "Work queue is full, handling work on the calling thread");
+				handle_task(shared, task);
+			},
+		}
+		return;
+	}
+
+	match *shared.channel.lock() {
+		Some(ref channel) => channel.push(task),
+		None => shared.queue.pop(),
+	}
+
+	for thread in shared.threads.read().iter() {
+		thread.unpark();
+	}
+}
+
+fn handle_task<Message>(shared: &Arc<Shared<Message>>, task: WorkTask<Message>)
+	where Message: Send + Sync + 'static
+{
+	match task {
+		WorkTask::Shutdown => {},
+		WorkTask::UserMessage(message) => {
+			for id in 0 .. MAX_HANDLERS {
+				if let Some(handler) = shared.handlers.read().get(id) {
+					let ctxt = IoContext { handler: id, shared: shared.clone() };
+					handler.message(&ctxt, &message);
+				}
+			}
+		},
+		WorkTask::TimerTrigger { handler_id, token } => {
+			if let Some(handler) = shared.handlers.read().get(handler_id) {
+				let ctxt = IoContext { handler: handler_id, shared: shared.clone() };
+				handler.timeout(&ctxt, token);
+			}
+		},
+	}
+}
+
+fn do_work<Message>(shared: &Arc<Shared<Message>>, rx: chase_lev::Stealer<WorkTask<Message>>, usage: &ThreadPoolUsage)
 	where Message: Send + Sync + 'static 
 {
 	loop {
@@ -315,19 +375,9 @@ fn do_work<Message>(shared: &Arc<Shared<Message>>, rx: chase_lev::Stealer<WorkTa
 			chase_lev::Steal::Abort => continue,
 			chase_lev::Steal::Empty => thread::park(),
 			chase_lev::Steal::Data(WorkTask::Shutdown) => break,
-			chase_lev::Steal::Data(WorkTask::UserMessage(message)) => {
-				for id in 0 .. MAX_HANDLERS {
-					if let Some(handler) = shared.handlers.read().get(id) {
-						let ctxt = IoContext { handler: id, shared: shared.clone() };
-						handler.message(&ctxt, &message);
-					}
-				}
-			},
-			chase_lev::Steal::Data(WorkTask::TimerTrigger { handler_id, token }) => {
-				if let Some(handler) = shared.handlers.read().get(handler_id) {
-					let ctxt = IoContext { handler: handler_id, shared: shared.clone() };
-					handler.timeout(&ctxt, token);
-				}
+			chase_lev::Steal::Data(task) => {
+				shared.queue.pop();
+				usage.measure(|| handle_task(shared, task))
 			},
 		}
 	}
@@ -0,0 +1,224 @@
+// Copyright 2015-2018 Parity Technologies (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Worker thread pools: CPU pinning and utilization statistics.
+//!
+//! Pools register themselves with `ThreadPoolUsage::register` and report time their
+//! threads spent doing actual work. Statistics of all live pools are available through `thread_pools`.
+
+use std::io;
+use std::sync::{Arc, Weak};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::{Duration, Instant};
+use parking_lot::{Mutex, RwLock};
+use queue::{QueueMetrics, QueueStats};
+
+lazy_static! {
+	static ref CPU_AFFINITY: RwLock<Vec<usize>> = RwLock::new(Vec::new());
+	static ref POOLS: Mutex<Vec<Weak<ThreadPoolUsage>>> = Mutex::new(Vec::new());
+}
+
+/// Set CPU cores worker threads are pinned to. Threads of each pool are assigned
+/// to the cores round-robin. Empty list disables pinning.
+///
+/// Affects only threads started after the call.
+pub fn set_cpu_affinity(cpus: Vec<usize>) {
+	*CPU_AFFINITY.write() = cpus;
+}
+
+/// Pin current thread to one of the configured cores, chosen by the thread index in its pool.
+pub fn pin_current_thread(index: usize) {
+	let cpu = {
+		let cpus = CPU_AFFINITY.read();
+		if cpus.is_empty() {
+			return;
+		}
+		cpus[index % cpus.len()]
+	};
+
+	if let Err(err) = set_current_thread_affinity(cpu) {
+		warn!(target: "io", "Failed to pin thread to CPU {}: {}", cpu, err);
+	}
+}
+
+#[cfg(target_os = "linux")]
+fn set_current_thread_affinity(cpu: usize) -> io::Result<()> {
+	use std::mem;
+	use libc;
+
+	unsafe {
+		let mut set: libc::cpu_set_t = mem::zeroed();
+		libc::CPU_SET(cpu, &mut set);
+		if libc::sched_setaffinity(0, mem::size_of::<libc::cpu_set_t>(), &set) != 0 {
+			return Err(io::Error::last_os_error());
+		}
+	}
+	Ok(())
+}
+
+#[cfg(not(target_os = "linux"))]
+fn set_current_thread_affinity(_cpu: usize) -> io::Result<()> {
+	Err(io::Error::new(io::ErrorKind::Other, "CPU pinning is supported on Linux only"))
+}
+
+/// Utilization tracker of a single thread pool.
+#[derive(Debug)]
+pub struct ThreadPoolUsage {
+	name: String,
+	threads: AtomicUsize,
+	measured: bool,
+	busy_micros: AtomicUsize,
+	started: Instant,
+	queue: RwLock<Option<Arc<QueueMetrics>>>,
+}
+
+impl ThreadPoolUsage {
+	/// Register a pool which reports time spent on work.
+	/// The pool is reported by `thread_pools` while the returned handle is alive.
+	pub fn register<S: Into<String>>(name: S, threads: usize) -> Arc<Self> {
+		Self::new(name.into(), threads, true)
+	}
+
+	/// Register a pool with unknown utilization, only the number of threads is reported.
+	pub fn register_unmeasured<S: Into<String>>(name: S, threads: usize) -> Arc<Self> {
+		Self::new(name.into(), threads, false)
+	}
+
+	fn new(name: String, threads: usize, measured: bool) -> Arc<Self> {
+		let usage = Arc::new(ThreadPoolUsage {
+			name,
+			threads: AtomicUsize::new(threads),
+			measured,
+			busy_micros: AtomicUsize::new(0),
+			started: Instant::now(),
+			queue: RwLock::new(None),
+		});
+
+		let mut pools = POOLS.lock();
+		pools.retain(|pool| pool.upgrade().is_some());
+		pools.push(Arc::downgrade(&usage));
+		usage
+	}
+
+	/// Update the number of threads in the pool.
+	pub fn set_threads(&self, threads: usize) {
+		self.threads.store(threads, Ordering::Relaxed);
+	}
+
+	/// Report depth of the queue feeding the pool together with its statistics.
+	pub fn attach_queue(&self, queue: Arc<QueueMetrics>) {
+		*self.queue.write() = Some(queue);
+	}
+
+	/// Note time spent by one of the threads on work.
+	pub fn note_busy(&self, duration: Duration) {
+		let micros = duration.as_secs() as usize * 1_000_000 + duration.subsec_micros() as usize;
+		self.busy_micros.fetch_add(micros, Ordering::Relaxed);
+	}
+
+	/// Run `f` noting the time it took as work.
+	pub fn measure<T, F: FnOnce() -> T>(&self, f: F) -> T {
+		let start = Instant::now();
+		let result = f();
+		self.note_busy(start.elapsed());
+		result
+	}
+
+	/// Current statistics of the pool.
+	pub fn stats(&self) -> ThreadPoolStats {
+		let busy = self.busy_micros.load(Ordering::Relaxed) as u64;
+		ThreadPoolStats {
+			name: self.name.clone(),
+			threads: self.threads.load(Ordering::Relaxed),
+			busy: if self.measured { Some(Duration::from_micros(busy)) } else { None },
+			uptime: self.started.elapsed(),
+			queue: self.queue.read().as_ref().map(|queue| queue.stats()),
+		}
+	}
+}
+
+/// Statistics of a thread pool.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ThreadPoolStats {
+	/// Name of the pool.
+	pub name: String,
+	/// Number of threads.
+	pub threads: usize,
+	/// Cumulative time threads of the pool spent on work, if known.
+	pub busy: Option<Duration>,
+	/// Time since the pool has been started.
+	pub uptime: Duration,
+	/// Statistics of the queue feeding the pool, if any.
+	pub queue: Option<QueueStats>,
+}
+
+impl ThreadPoolStats {
+	/// Fraction of available thread time spent on work since the pool has been started.
+	pub fn utilization(&self) -> Option<f64> {
+		let available = duration_secs(self.uptime) * self.threads as f64;
+		match self.busy {
+			Some(busy) if available > 0.0 => Some(duration_secs(busy) / available),
+			_ => None,
+		}
+	}
+}
+
+fn duration_secs(duration: Duration) -> f64 {
+	duration.as_secs() as f64 + duration.subsec_nanos() as f64 / 1_000_000_000.0
+}
+
+/// Statistics of all live thread pools.
+pub fn thread_pools() -> Vec<ThreadPoolStats> {
+	POOLS.lock().iter()
+		.filter_map(|pool| pool.upgrade())
+		.map(|pool| pool.stats())
+		.collect()
+}
+
+#[cfg(test)]
+mod tests {
+	use std::time::Duration;
+	use super::{ThreadPoolUsage, ThreadPoolStats, thread_pools};
+
+	#[test]
+	fn should_report_live_pools() {
+		let usage = ThreadPoolUsage::register("test-live-pool", 2);
+		usage.note_busy(Duration::from_millis(1500));
+		usage.set_threads(3);
+
+		let stats = thread_pools().into_iter().find(|pool| pool.name == "test-live-pool").unwrap();
+		assert_eq!(stats.threads, 3);
+		assert_eq!(stats.busy, Some(Duration::from_millis(1500)));
+
+		drop(usage);
+		assert!(thread_pools().into_iter().all(|pool| pool.name != "test-live-pool"));
+	}
+
+	#[test]
+	fn should_calculate_utilization() {
+		let stats = ThreadPoolStats {
+			name: "test".into(),
+			threads: 4,
+			busy: Some(Duration::from_secs(2)),
+			uptime: Duration::from_secs(2),
+			queue: None,
+		};
+		assert_eq!(stats.utilization(), Some(0.25));
+
+		let unmeasured = ThreadPoolStats { busy: None, ..stats };
+		assert_eq!(unmeasured.utilization(), None);
+	}
+}
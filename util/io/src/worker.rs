@@ -21,6 +21,8 @@ use crossbeam::sync::chase_lev;
 use service_mio::{HandlerId, IoChannel, IoContext};
 use IoHandler;
 use LOCAL_STACK_SIZE;
+use threads::{self, ThreadPoolUsage};
+use queue::QueueMetrics;
 
 use parking_lot::{Condvar, Mutex};
 
@@ -57,6 +59,8 @@ impl Worker {
 						channel: IoChannel<Message>,
 						wait: Arc<Condvar>,
 						wait_mutex: Arc<Mutex<()>>,
+						usage: Arc<ThreadPoolUsage>,
+						queue: Arc<QueueMetrics>,
 					   ) -> Worker
 					where Message: Send + Sync + 'static {
 		let deleting = Arc::new(AtomicBool::new(false));
@@ -69,7 +73,8 @@ impl Worker {
 		worker.thread = Some(thread::Builder::new().stack_size(STACK_SIZE).name(format!("IO Worker #{}", index)).spawn(
 			move || {
 				LOCAL_STACK_SIZE.with(|val| val.set(STACK_SIZE));
-				Worker::work_loop(stealer, channel.clone(), wait, wait_mutex.clone(), deleting)
+				threads::pin_current_thread(index);
+				Worker::work_loop(stealer, channel.clone(), wait, wait_mutex.clone(), deleting, usage, queue)
 			})
 			.expect("Error creating worker thread"));
 		worker
@@ -78,7 +83,9 @@ impl Worker {
 	fn work_loop<Message>(stealer: chase_lev::Stealer<Work<Message>>,
 						channel: IoChannel<Message>, wait: Arc<Condvar>,
 						wait_mutex: Arc<Mutex<()>>,
-						deleting: Arc<AtomicBool>)
+						deleting: Arc<AtomicBool>,
+						usage: Arc<ThreadPoolUsage>,
+						queue: Arc<QueueMetrics>)
 						where Message: Send + Sync + 'static {
 		loop {
 			{
@@ -91,14 +98,18 @@ impl Worker {
 
 			while !deleting.load(AtomicOrdering::Acquire) {
 				match stealer.steal() {
-					chase_lev::Steal::Data(work) => Worker::do_work(work, channel.clone()),
+					chase_lev::Steal::Data(work) => {
+						queue.pop();
+						usage.measure(|| Worker::do_work(work, channel.clone()))
+					},
 					_ => break,
 				}
 			}
 		}
 	}
 
-	fn do_work<Message>(work: Work<Message>, channel: IoChannel<Message>) where Message: Send + Sync + 'static {
+	/// Handle the work on the current thread.
+	pub fn do_work<Message>(work: Work<Message>, channel: IoChannel<Message>) where Message: Send + Sync + 'static {
 		match work.work_type {
 			WorkType::Readable => {
 				work.handler.stream_readable(&IoContext::new(channel, work.handler_id), work.token);
@@ -28,6 +28,7 @@ use rlp::{Rlp, RlpStream};
 use std::io::{self, Cursor, Read, Write};
 use io::{IoContext, StreamToken};
 use handshake::Handshake;
+use key_log::{KeyLog, SessionSecrets};
 use rcrypto::blockmodes::*;
 use rcrypto::aessafe::*;
 use rcrypto::symmetriccipher::*;
@@ -292,7 +293,8 @@ pub struct EncryptedConnection {
 
 impl EncryptedConnection {
 	/// Create an encrypted connection out of the handshake.
-	pub fn new(handshake: &mut Handshake) -> Result<EncryptedConnection, Error> {
+	/// Session secrets are written to `key_log` if given.
+	pub fn new(handshake: &mut Handshake, key_log: Option<&KeyLog>) -> Result<EncryptedConnection, Error> {
 		let shared = crypto::ecdh::agree(handshake.ecdhe.secret(), &handshake.remote_ephemeral)?;
 		let mut nonce_material = H512::new();
 		if handshake.originated {
@@ -314,9 +316,27 @@ impl EncryptedConnection {
 		let iv = vec![0u8; 16];
 		let decoder = CtrMode::new(AesSafe256Encryptor::new(&key_material[32..64]), iv);
 
+		let aes_secret = H256::from_slice(&key_material[32..64]);
+
 		keccak(&key_material).copy_to(&mut key_material[32..64]);
 		let mac_encoder = EcbEncryptor::new(AesSafe256Encryptor::new(&key_material[32..64]), NoPadding);
 
+		if let Some(key_log) = key_log {
+			let (initiator_nonce, recipient_nonce) = if handshake.originated {
+				(&handshake.nonce, &handshake.remote_nonce)
+			} else {
+				(&handshake.remote_nonce, &handshake.nonce)
+			};
+			key_log.log(&SessionSecrets {
+				local_address: &handshake.connection.local_addr_str(),
+				remote_address: &handshake.connection.remote_addr_str(),
+				initiator_nonce,
+				recipient_nonce,
+				aes_secret: &aes_secret,
+				mac_secret: &key_material[32..64],
+			});
+		}
+
 		let mut egress_mac = Keccak::new_keccak256();
 		let mut mac_material = H256::from_slice(&key_material[32..64]) ^ handshake.remote_nonce;
 		egress_mac.update(&mac_material);
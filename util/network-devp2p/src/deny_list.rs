@@ -0,0 +1,114 @@
+// Copyright 2015-2018 Parity Technologies (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+//! File with IP addresses and CIDR ranges to refuse connections with.
+//!
+//! The file is polled for modifications so that operators can update it without a restart.
+
+use std::fs::{self, File};
+use std::io::Read;
+use std::path::PathBuf;
+use std::time::SystemTime;
+
+use ipnetwork::IpNetwork;
+use network::IpFilter;
+
+pub struct DenyList {
+	path: PathBuf,
+	modified: Option<SystemTime>,
+	networks: Vec<IpNetwork>,
+}
+
+impl DenyList {
+	/// Create a list backed by the file at `path`. Nothing is denied until the first `reload`.
+	pub fn new(path: &str) -> DenyList {
+		DenyList {
+			path: PathBuf::from(path),
+			modified: None,
+			networks: Vec::new(),
+		}
+	}
+
+	/// Denied ranges as of the last successful reload.
+	pub fn networks(&self) -> &[IpNetwork] {
+		&self.networks
+	}
+
+	/// Reload the file if it was modified since the last reload. Returns `true` if the list changed.
+	/// The previous list is kept if the file can't be read or contains an invalid entry.
+	pub fn reload(&mut self) -> bool {
+		let modified = match fs::metadata(&self.path).and_then(|m| m.modified()) {
+			Ok(modified) => modified,
+			Err(e) => {
+				warn!(target: "network", "Error reading denied IPs file {}: {}", self.path.display(), e);
+				return false;
+			}
+		};
+		if self.modified == Some(modified) {
+			return false;
+		}
+		self.modified = Some(modified);
+
+		let mut content = String::new();
+		if let Err(e) = File::open(&self.path).and_then(|mut f| f.read_to_string(&mut content)) {
+			warn!(target: "network", "Error reading denied IPs file {}: {}", self.path.display(), e);
+			return false;
+		}
+		match IpFilter::parse_list(&content) {
+			Ok(ref networks) if *networks == self.networks => false,
+			Ok(networks) => {
+				info!(target: "network", "Loaded {} denied IP ranges from {}", networks.len(), self.path.display());
+				self.networks = networks;
+				true
+			},
+			Err(e) => {
+				warn!(target: "network", "Invalid entry in denied IPs file {}: {:?}", self.path.display(), e);
+				false
+			},
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use std::io::Write;
+	use std::str::FromStr;
+	use tempdir::TempDir;
+
+	#[test]
+	fn reloads_when_changed() {
+		let tempdir = TempDir::new("").unwrap();
+		let path = tempdir.path().join("denied");
+		File::create(&path).unwrap().write_all(b"10.0.0.0/8\n").unwrap();
+
+		let mut list = DenyList::new(path.to_str().unwrap());
+		assert!(list.reload());
+		assert_eq!(list.networks(), &[IpNetwork::from_str("10.0.0.0/8").unwrap()]);
+		assert!(!list.reload());
+
+		// An invalid file keeps the previous list.
+		File::create(&path).unwrap().write_all(b"10.0.0.0/8\nnot an ip\n").unwrap();
+		list.modified = None;
+		assert!(!list.reload());
+		assert_eq!(list.networks().len(), 1);
+
+		File::create(&path).unwrap().write_all(b"10.0.0.0/8\n192.168.0.1\n").unwrap();
+		list.modified = None;
+		assert!(list.reload());
+		assert_eq!(list.networks().len(), 2);
+	}
+}
@@ -27,6 +27,10 @@ use node_table::*;
 use network::{Error, ErrorKind};
 use ethkey::{Secret, KeyPair, sign, recover};
 use network::IpFilter;
+use discovery_v5::{
+	TopicDiscovery, topic_hash, V5_PREFIX, PACKET_TOPIC_REGISTER, PACKET_TOPIC_QUERY, PACKET_TOPIC_NODES,
+	MAX_TOPICS_PER_REGISTRATION, MAX_TOPIC_LENGTH,
+};
 
 use PROTOCOL_VERSION;
 
@@ -141,6 +145,7 @@ pub struct Discovery<'a> {
 	adding_nodes: Vec<NodeEntry>,
 	ip_filter: IpFilter,
 	request_backoff: &'a [Duration],
+	topics: Option<TopicDiscovery>,
 }
 
 pub struct TableUpdates {
@@ -167,9 +172,15 @@ impl<'a> Discovery<'a> {
 			adding_nodes: Vec::new(),
 			ip_filter,
 			request_backoff: &REQUEST_BACKOFF,
+			topics: None,
 		}
 	}
 
+	/// Enable discovery v5 topic advertisement and search.
+	pub fn enable_v5(&mut self, advertised: Vec<String>, searched: Vec<String>) {
+		self.topics = Some(TopicDiscovery::new(advertised, searched));
+	}
+
 	/// Add a new node to discovery table. Pings the node.
 	pub fn add_node(&mut self, e: NodeEntry) {
 		// If distance returns None, then we are trying to add ourself.
@@ -182,6 +193,11 @@ impl<'a> Discovery<'a> {
 		}
 	}
 
+	/// Replace the filter for addresses of discovered nodes.
+	pub fn set_ip_filter(&mut self, ip_filter: IpFilter) {
+		self.ip_filter = ip_filter;
+	}
+
 	/// Add a list of nodes. Pings a few nodes each round
 	pub fn add_node_list(&mut self, nodes: Vec<NodeEntry>) {
 		for node in nodes {
@@ -366,7 +382,11 @@ impl<'a> Discovery<'a> {
 	}
 
 	fn nearest_node_entries(&self, target: &NodeId) -> Vec<NodeEntry> {
-		let target_hash = keccak(target);
+		self.nearest_node_entries_to_hash(&keccak(target))
+	}
+
+	fn nearest_node_entries_to_hash(&self, target_hash: &H256) -> Vec<NodeEntry> {
+		let target_hash = *target_hash;
 		let target_distance = self.id_hash ^ target_hash;
 
 		let mut ret = Vec::<NodeEntry>::with_capacity(BUCKET_SIZE);
@@ -422,22 +442,12 @@ impl<'a> Discovery<'a> {
 
 
 	pub fn on_packet(&mut self, packet: &[u8], from: SocketAddr) -> Result<Option<TableUpdates>, Error> {
-		// validate packet
-		if packet.len() < 32 + 65 + 4 + 1 {
-			return Err(ErrorKind::BadProtocol.into());
+		if packet.starts_with(V5_PREFIX) {
+			return self.on_v5_packet(&packet[V5_PREFIX.len()..], from);
 		}
 
-		let hash_signed = keccak(&packet[32..]);
-		if hash_signed[..] != packet[0..32] {
-			return Err(ErrorKind::BadProtocol.into());
-		}
-
-		let signed = &packet[(32 + 65)..];
-		let signature = H520::from_slice(&packet[32..(32 + 65)]);
-		let node_id = recover(&signature.into(), &keccak(signed))?;
-
-		let packet_id = signed[0];
-		let rlp = Rlp::new(&signed[1..]);
+		let (hash_signed, node_id, packet_id, payload) = verify_packet(packet)?;
+		let rlp = Rlp::new(payload);
 		match packet_id {
 			PACKET_PING => self.on_ping(&rlp, &node_id, &from, &hash_signed),
 			PACKET_PONG => self.on_pong(&rlp, &node_id, &from),
@@ -450,6 +460,185 @@ impl<'a> Discovery<'a> {
 		}
 	}
 
+	fn on_v5_packet(&mut self, packet: &[u8], from: SocketAddr) -> Result<Option<TableUpdates>, Error> {
+		if self.topics.is_none() {
+			trace!(target: "discovery", "Ignoring discovery v5 packet from {:?}", &from);
+			return Ok(None);
+		}
+
+		let (_, node_id, packet_id, payload) = verify_packet(packet)?;
+		let rlp = Rlp::new(payload);
+		match packet_id {
+			PACKET_TOPIC_REGISTER => self.on_topic_register(&rlp, &node_id, &from),
+			PACKET_TOPIC_QUERY => self.on_topic_query(&rlp, &node_id, &from),
+			PACKET_TOPIC_NODES => self.on_topic_nodes(&rlp, &node_id, &from),
+			_ => {
+				debug!(target: "discovery", "Unknown discovery v5 packet: {}", packet_id);
+				Ok(None)
+			}
+		}
+	}
+
+	fn send_v5_packet(&mut self, packet_id: u8, address: &SocketAddr, payload: &[u8]) -> Result<(), Error> {
+		let packet = assemble_packet(packet_id, payload, &self.secret)?;
+		let mut prefixed = Bytes::with_capacity(V5_PREFIX.len() + packet.len());
+		prefixed.extend_from_slice(V5_PREFIX);
+		prefixed.extend_from_slice(&packet);
+		self.send_to(prefixed, address.clone());
+		Ok(())
+	}
+
+	/// Routing table entry of a node with a verified endpoint matching `from`.
+	fn verified_node(&self, node_id: &NodeId, from: &SocketAddr) -> Option<NodeEntry> {
+		let id_hash = keccak(node_id);
+		let dist = Discovery::distance(&self.id_hash, &id_hash)?;
+		self.node_buckets[dist].nodes.iter()
+			.find(|n| n.id_hash == id_hash && n.address.endpoint.udp_address() == *from)
+			.map(|n| n.address.clone())
+	}
+
+	fn on_topic_register(&mut self, rlp: &Rlp, node_id: &NodeId, from: &SocketAddr) -> Result<Option<TableUpdates>, Error> {
+		let topics: Vec<String> = rlp.list_at(0)?;
+		let timestamp: u64 = rlp.val_at(1)?;
+		self.check_timestamp(timestamp)?;
+		if topics.len() > MAX_TOPICS_PER_REGISTRATION || topics.iter().any(|t| t.len() > MAX_TOPIC_LENGTH) {
+			debug!(target: "discovery", "Oversized topic registration from {:?}", &from);
+			return Err(ErrorKind::BadProtocol.into());
+		}
+
+		let node = match self.verified_node(node_id, from) {
+			Some(node) => node,
+			None => {
+				trace!(target: "discovery", "Topic registration from unverified node {:?}", &from);
+				self.add_node(NodeEntry { id: *node_id, endpoint: NodeEndpoint { address: *from, udp_port: from.port() } });
+				return Ok(None);
+			}
+		};
+
+		trace!(target: "discovery", "Got TopicRegister {:?} from {:?}", topics, &from);
+		let now = Instant::now();
+		let table = &mut self.topics.as_mut().expect("v5 packets are handled only if topics are enabled; qed").table;
+		for topic in &topics {
+			if !table.register(topic, node.clone(), now) {
+				debug!(target: "discovery", "Topic table full, ignoring registration of {}", topic);
+			}
+		}
+		Ok(None)
+	}
+
+	fn on_topic_query(&mut self, rlp: &Rlp, _node_id: &NodeId, from: &SocketAddr) -> Result<Option<TableUpdates>, Error> {
+		let topic: String = rlp.val_at(0)?;
+		let timestamp: u64 = rlp.val_at(1)?;
+		self.check_timestamp(timestamp)?;
+		trace!(target: "discovery", "Got TopicQuery {} from {:?}", topic, &from);
+
+		let nodes = self.topics.as_ref().expect("v5 packets are handled only if topics are enabled; qed").table.nodes(&topic, Instant::now());
+		if nodes.is_empty() {
+			return Ok(None);
+		}
+		for p in Discovery::prepare_topic_nodes_packets(&topic, &nodes) {
+			self.send_v5_packet(PACKET_TOPIC_NODES, from, &p)?;
+		}
+		trace!(target: "discovery", "Sent {} TopicNodes to {:?}", nodes.len(), &from);
+		Ok(None)
+	}
+
+	fn prepare_topic_nodes_packets(topic: &str, nodes: &[NodeEntry]) -> Vec<Bytes> {
+		let limit = (MAX_DATAGRAM_SIZE - V5_PREFIX.len() - 109 - MAX_TOPIC_LENGTH) / 90;
+		nodes.chunks(limit).map(|c| {
+			let mut rlp = RlpStream::new_list(3);
+			rlp.append(&topic);
+			rlp.begin_list(c.len());
+			for n in c {
+				rlp.begin_list(4);
+				n.endpoint.to_rlp(&mut rlp);
+				rlp.append(&n.id);
+			}
+			append_expiration(&mut rlp);
+			rlp.out()
+		}).collect()
+	}
+
+	fn on_topic_nodes(&mut self, rlp: &Rlp, node_id: &NodeId, from: &SocketAddr) -> Result<Option<TableUpdates>, Error> {
+		let topic: String = rlp.val_at(0)?;
+		let timestamp: u64 = rlp.val_at(2)?;
+		self.check_timestamp(timestamp)?;
+		if !self.topics.as_mut().expect("v5 packets are handled only if topics are enabled; qed").query_answered(node_id, &topic) {
+			debug!(target: "discovery", "Got unexpected TopicNodes from {:?} ; node_id={:#x}", &from, node_id);
+			return Ok(None);
+		}
+
+		let mut added = HashMap::new();
+		for r in rlp.at(1)?.iter() {
+			let endpoint = NodeEndpoint::from_rlp(&r)?;
+			if !endpoint.is_valid() {
+				debug!(target: "discovery", "Bad address: {:?}", endpoint);
+				continue;
+			}
+			let entry = NodeEntry { id: r.val_at(3)?, endpoint };
+			if !self.is_allowed(&entry) {
+				debug!(target: "discovery", "Address not allowed: {:?}", entry);
+				continue;
+			}
+			self.add_node(entry.clone());
+			added.insert(entry.id, entry);
+		}
+		trace!(target: "discovery", "Got {} TopicNodes for {} from {:?}", added.len(), topic, &from);
+
+		if added.is_empty() {
+			return Ok(None);
+		}
+		Ok(Some(TableUpdates { added, removed: HashSet::new() }))
+	}
+
+	/// Registers advertised topics and searches for searched topics, if due.
+	fn topic_round(&mut self, now: Instant) {
+		let (registrations, searches) = match self.topics {
+			Some(ref mut topics) => {
+				topics.expire(now);
+				(topics.due_registrations(now), topics.due_searches(now))
+			},
+			None => return,
+		};
+
+		// register each topic with the nodes closest to it, all topics sharing a registrar in one packet.
+		let mut registrars: HashMap<NodeId, (NodeEntry, Vec<String>)> = HashMap::new();
+		for topic in registrations {
+			for node in self.nearest_node_entries_to_hash(&topic_hash(&topic)).into_iter().take(ALPHA) {
+				registrars.entry(node.id).or_insert_with(|| (node, Vec::new())).1.push(topic.clone());
+			}
+		}
+		for (_, (node, topics)) in registrars {
+			for chunk in topics.chunks(MAX_TOPICS_PER_REGISTRATION) {
+				let mut rlp = RlpStream::new_list(2);
+				rlp.append_list::<String, String>(chunk);
+				append_expiration(&mut rlp);
+				if let Err(e) = self.send_v5_packet(PACKET_TOPIC_REGISTER, &node.endpoint.udp_address(), &rlp.drain()) {
+					warn!(target: "discovery", "Error sending TopicRegister packet: {:?}", e);
+				}
+			}
+		}
+
+		for topic in searches {
+			for node in self.nearest_node_entries_to_hash(&topic_hash(&topic)).into_iter().take(ALPHA) {
+				if self.topics.as_ref().map_or(true, |t| t.is_querying(&node.id)) {
+					continue;
+				}
+				let mut rlp = RlpStream::new_list(2);
+				rlp.append(&topic);
+				append_expiration(&mut rlp);
+				match self.send_v5_packet(PACKET_TOPIC_QUERY, &node.endpoint.udp_address(), &rlp.drain()) {
+					Ok(()) => {
+						if let Some(ref mut topics) = self.topics {
+							topics.query_sent(node.id, topic.clone(), now);
+						}
+					},
+					Err(e) => warn!(target: "discovery", "Error sending TopicQuery packet: {:?}", e),
+				}
+			}
+		}
+	}
+
 	/// Validate that given timestamp is in within one second of now or in the future
 	fn check_timestamp(&self, timestamp: u64) -> Result<(), Error> {
 		let secs_since_epoch = SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default().as_secs();
@@ -687,7 +876,13 @@ impl<'a> Discovery<'a> {
 		} else if self.in_flight_pings.len() == 0 && !self.discovery_initiated {
 			self.discovery_initiated = true;
 			self.refresh();
+			// the routing table got populated by the initial pings, don't wait for the next schedule.
+			if let Some(ref mut topics) = self.topics {
+				topics.reset_schedule(Instant::now());
+			}
 		}
+
+		self.topic_round(Instant::now());
 	}
 
 	pub fn refresh(&mut self) {
@@ -725,6 +920,25 @@ fn append_expiration(rlp: &mut RlpStream) {
 	rlp.append(&timestamp);
 }
 
+/// Checks the hash and the signature of a packet.
+/// Returns the hash, the id of the sender, the packet id and the payload.
+fn verify_packet(packet: &[u8]) -> Result<(H256, NodeId, u8, &[u8]), Error> {
+	if packet.len() < 32 + 65 + 4 + 1 {
+		return Err(ErrorKind::BadProtocol.into());
+	}
+
+	let hash_signed = keccak(&packet[32..]);
+	if hash_signed[..] != packet[0..32] {
+		return Err(ErrorKind::BadProtocol.into());
+	}
+
+	let signed = &packet[(32 + 65)..];
+	let signature = H520::from_slice(&packet[32..(32 + 65)]);
+	let node_id = recover(&signature.into(), &keccak(signed))?;
+
+	Ok((hash_signed, node_id, signed[0], &signed[1..]))
+}
+
 fn assemble_packet(packet_id: u8, bytes: &[u8], secret: &Secret) -> Result<Bytes, Error> {
 	let mut packet = Bytes::with_capacity(bytes.len() + 32 + 65 + 1);
 	packet.resize(32 + 65, 0); // Filled in below
@@ -1186,4 +1400,57 @@ mod tests {
 			panic!("Expected no changes to discovery1's table for unexpected pong");
 		}
 	}
+
+	#[test]
+	fn finds_nodes_advertising_topic() {
+		let key_registrar = Random.generate().unwrap();
+		let key_advertiser = Random.generate().unwrap();
+		let key_searcher = Random.generate().unwrap();
+		let ep_registrar = NodeEndpoint { address: SocketAddr::from_str("127.0.0.1:40447").unwrap(), udp_port: 40447 };
+		let ep_advertiser = NodeEndpoint { address: SocketAddr::from_str("127.0.0.1:40448").unwrap(), udp_port: 40448 };
+		let ep_searcher = NodeEndpoint { address: SocketAddr::from_str("127.0.0.1:40449").unwrap(), udp_port: 40449 };
+		let mut registrar = Discovery::new(&key_registrar, ep_registrar.clone(), IpFilter::default());
+		let mut advertiser = Discovery::new(&key_advertiser, ep_advertiser.clone(), IpFilter::default());
+		let mut searcher = Discovery::new(&key_searcher, ep_searcher.clone(), IpFilter::default());
+		registrar.enable_v5(vec![], vec![]);
+		advertiser.enable_v5(vec!["pip@1".into()], vec![]);
+		searcher.enable_v5(vec![], vec!["pip@1".into()]);
+
+		let registrar_entry = NodeEntry { id: registrar.id, endpoint: ep_registrar.clone() };
+		registrar.init_node_list(vec![NodeEntry { id: advertiser.id, endpoint: ep_advertiser.clone() }]);
+		advertiser.init_node_list(vec![registrar_entry.clone()]);
+		searcher.init_node_list(vec![registrar_entry]);
+
+		// the advertiser registers the topic
+		advertiser.topic_round(Instant::now());
+		let register = advertiser.dequeue_send().unwrap();
+		assert!(register.payload.starts_with(V5_PREFIX));
+		assert_eq!(register.payload[V5_PREFIX.len() + 32 + 65], PACKET_TOPIC_REGISTER);
+		assert!(registrar.on_packet(&register.payload, ep_advertiser.address).unwrap().is_none());
+
+		// registrations from unknown nodes are ignored
+		let v4_node = Discovery::new(&key_searcher, ep_searcher.clone(), IpFilter::default());
+		let mut rlp = RlpStream::new_list(2);
+		rlp.append_list::<String, String>(&["pip@2".to_owned()]);
+		append_expiration(&mut rlp);
+		let mut spoofed = V5_PREFIX.to_vec();
+		spoofed.extend_from_slice(&assemble_packet(PACKET_TOPIC_REGISTER, &rlp.drain(), &v4_node.secret).unwrap());
+		registrar.on_packet(&spoofed, ep_searcher.address).unwrap();
+		assert!(registrar.topics.as_ref().unwrap().table.nodes("pip@2", Instant::now()).is_empty());
+		while registrar.dequeue_send().is_some() {}
+
+		// the searcher queries the registrar and learns about the advertiser
+		searcher.topic_round(Instant::now());
+		let query = searcher.dequeue_send().unwrap();
+		assert!(registrar.on_packet(&query.payload, ep_searcher.address).unwrap().is_none());
+		let nodes = registrar.dequeue_send().unwrap();
+		assert_eq!(nodes.address, ep_searcher.address);
+
+		let updates = searcher.on_packet(&nodes.payload, ep_registrar.address).unwrap().unwrap();
+		assert_eq!(updates.added.len(), 1);
+		assert!(updates.added.contains_key(&advertiser.id));
+
+		// answers to queries which were not sent are ignored
+		assert!(searcher.on_packet(&nodes.payload, ep_registrar.address).unwrap().is_none());
+	}
 }
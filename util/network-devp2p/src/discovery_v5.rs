@@ -0,0 +1,239 @@
+// Copyright 2015-2018 Parity Technologies (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Topic advertisement and search of discovery v5.
+//!
+//! Discovery v5 packets share the UDP socket and the routing table with discovery v4 and are
+//! told apart by `V5_PREFIX`. A node advertising a topic registers itself with the nodes closest
+//! to `keccak(topic)`, which answer topic queries with the nodes registered for the topic.
+//! Registrations are accepted only from nodes with a verified endpoint in the routing table.
+
+use std::collections::{HashMap, VecDeque};
+use std::time::{Duration, Instant};
+
+use ethereum_types::H256;
+use hash::keccak;
+
+use discovery::NodeEntry;
+use node_table::NodeId;
+
+/// Prefix of all discovery v5 packets.
+pub const V5_PREFIX: &[u8] = b"temporary discovery v5";
+
+pub const PACKET_TOPIC_REGISTER: u8 = 6;
+pub const PACKET_TOPIC_QUERY: u8 = 7;
+pub const PACKET_TOPIC_NODES: u8 = 8;
+
+/// Maximal number of topics registered with a single packet.
+pub const MAX_TOPICS_PER_REGISTRATION: usize = 8;
+/// Maximal length of a topic.
+pub const MAX_TOPIC_LENGTH: usize = 64;
+
+/// How long a registration is kept by the registrar.
+const REGISTRATION_LIFETIME: Duration = Duration::from_secs(15 * 60);
+/// How often advertised topics are registered again, well before registrations expire.
+const REGISTRATION_INTERVAL: Duration = Duration::from_secs(5 * 60);
+/// How often searched topics are queried.
+const SEARCH_INTERVAL: Duration = Duration::from_secs(30);
+/// Time to wait for the answer to a topic query.
+const QUERY_TIMEOUT: Duration = Duration::from_secs(2);
+/// Maximal number of nodes remembered for a single topic, oldest registrations are dropped first.
+const MAX_TOPIC_NODES: usize = 64;
+/// Maximal number of distinct topics remembered.
+const MAX_TOPICS: usize = 256;
+
+/// Hash the distance to a topic is measured to.
+pub fn topic_hash(topic: &str) -> H256 {
+	keccak(topic.as_bytes())
+}
+
+struct Registration {
+	node: NodeEntry,
+	expires: Instant,
+}
+
+/// Nodes which registered topics with this node.
+#[derive(Default)]
+pub struct TopicTable {
+	topics: HashMap<String, VecDeque<Registration>>,
+}
+
+impl TopicTable {
+	/// Registers `node` for `topic`, replacing its previous registration. Returns `false` if the table is full.
+	pub fn register(&mut self, topic: &str, node: NodeEntry, now: Instant) -> bool {
+		if !self.topics.contains_key(topic) && self.topics.len() >= MAX_TOPICS {
+			return false;
+		}
+
+		let registrations = self.topics.entry(topic.to_owned()).or_insert_with(VecDeque::new);
+		registrations.retain(|r| r.node.id != node.id);
+		registrations.push_back(Registration { node, expires: now + REGISTRATION_LIFETIME });
+		if registrations.len() > MAX_TOPIC_NODES {
+			registrations.pop_front();
+		}
+		true
+	}
+
+	/// Nodes registered for `topic`, most recent first.
+	pub fn nodes(&self, topic: &str, now: Instant) -> Vec<NodeEntry> {
+		self.topics.get(topic).map_or_else(Vec::new, |registrations| registrations.iter()
+			.rev()
+			.filter(|r| r.expires > now)
+			.map(|r| r.node.clone())
+			.collect())
+	}
+
+	/// Forgets expired registrations.
+	pub fn expire(&mut self, now: Instant) {
+		for registrations in self.topics.values_mut() {
+			registrations.retain(|r| r.expires > now);
+		}
+		self.topics.retain(|_, registrations| !registrations.is_empty());
+	}
+}
+
+/// Topic state of discovery v5.
+pub struct TopicDiscovery {
+	/// Topics registered by other nodes.
+	pub table: TopicTable,
+	advertised: Vec<String>,
+	searched: Vec<String>,
+	next_registration: Instant,
+	next_search: Instant,
+	in_flight_queries: HashMap<NodeId, (String, Instant)>,
+}
+
+impl TopicDiscovery {
+	pub fn new(advertised: Vec<String>, searched: Vec<String>) -> Self {
+		let now = Instant::now();
+		TopicDiscovery {
+			table: TopicTable::default(),
+			advertised,
+			searched,
+			next_registration: now,
+			next_search: now,
+			in_flight_queries: HashMap::new(),
+		}
+	}
+
+	/// Topics to register at `now`, if registration is due.
+	pub fn due_registrations(&mut self, now: Instant) -> Vec<String> {
+		if self.advertised.is_empty() || now < self.next_registration {
+			return Vec::new();
+		}
+		self.next_registration = now + REGISTRATION_INTERVAL;
+		self.advertised.clone()
+	}
+
+	/// Topics to search for at `now`, if a search is due.
+	pub fn due_searches(&mut self, now: Instant) -> Vec<String> {
+		if self.searched.is_empty() || now < self.next_search {
+			return Vec::new();
+		}
+		self.next_search = now + SEARCH_INTERVAL;
+		self.searched.clone()
+	}
+
+	/// Makes the next round register and search right away, e.g. once the routing table got populated.
+	pub fn reset_schedule(&mut self, now: Instant) {
+		self.next_registration = now;
+		self.next_search = now;
+	}
+
+	/// Notes a topic query sent to `node`.
+	pub fn query_sent(&mut self, node: NodeId, topic: String, now: Instant) {
+		self.in_flight_queries.insert(node, (topic, now));
+	}
+
+	/// Returns `true` if `node` answers a query for `topic`. The query is completed.
+	pub fn query_answered(&mut self, node: &NodeId, topic: &str) -> bool {
+		match self.in_flight_queries.get(node) {
+			Some(&(ref queried, _)) if queried == topic => {},
+			_ => return false,
+		}
+		self.in_flight_queries.remove(node);
+		true
+	}
+
+	/// Is a query to `node` in flight.
+	pub fn is_querying(&self, node: &NodeId) -> bool {
+		self.in_flight_queries.contains_key(node)
+	}
+
+	/// Forgets expired registrations and unanswered queries.
+	pub fn expire(&mut self, now: Instant) {
+		self.table.expire(now);
+		self.in_flight_queries.retain(|_, &mut (_, sent_at)| now.duration_since(sent_at) <= QUERY_TIMEOUT);
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use std::time::{Duration, Instant};
+	use std::net::SocketAddr;
+	use std::str::FromStr;
+	use discovery::NodeEntry;
+	use node_table::{NodeId, NodeEndpoint};
+	use super::{TopicTable, TopicDiscovery, MAX_TOPIC_NODES, REGISTRATION_LIFETIME};
+
+	fn node(id: u64) -> NodeEntry {
+		NodeEntry {
+			id: NodeId::from(id),
+			endpoint: NodeEndpoint {
+				address: SocketAddr::from_str("127.0.0.1:30303").unwrap(),
+				udp_port: 30303,
+			},
+		}
+	}
+
+	#[test]
+	fn topic_table_keeps_most_recent_registrations() {
+		let now = Instant::now();
+		let mut table = TopicTable::default();
+		for i in 0..(MAX_TOPIC_NODES as u64 + 1) {
+			assert!(table.register("pip@1", node(i), now));
+		}
+		// registering again moves the node to the front
+		assert!(table.register("pip@1", node(1), now));
+
+		let nodes = table.nodes("pip@1", now);
+		assert_eq!(nodes.len(), MAX_TOPIC_NODES);
+		assert_eq!(nodes[0].id, NodeId::from(1));
+		assert!(nodes.iter().all(|n| n.id != NodeId::from(0)));
+		assert!(table.nodes("pip@3", now).is_empty());
+
+		table.expire(now + REGISTRATION_LIFETIME);
+		assert!(table.nodes("pip@1", now).is_empty());
+	}
+
+	#[test]
+	fn schedules_registrations_and_queries() {
+		let now = Instant::now();
+		let mut topics = TopicDiscovery::new(vec!["pip@1".into()], vec![]);
+		assert_eq!(topics.due_registrations(now), vec!["pip@1".to_owned()]);
+		assert!(topics.due_registrations(now + Duration::from_secs(1)).is_empty());
+		assert!(topics.due_searches(now).is_empty());
+
+		topics.query_sent(NodeId::from(1), "pip@1".into(), now);
+		assert!(!topics.query_answered(&NodeId::from(1), "pip@2"));
+		assert!(topics.query_answered(&NodeId::from(1), "pip@1"));
+		assert!(!topics.query_answered(&NodeId::from(1), "pip@1"));
+
+		topics.query_sent(NodeId::from(2), "pip@1".into(), now);
+		topics.expire(now + Duration::from_secs(3));
+		assert!(!topics.is_querying(&NodeId::from(2)));
+	}
+}
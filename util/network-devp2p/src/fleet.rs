@@ -0,0 +1,229 @@
+// Copyright 2015-2018 Parity Technologies (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Sharing of peers and bans between nodes of the same operator.
+//!
+//! Fleet members periodically send each other a UDP datagram with the nodes they recently
+//! connected to and the nodes they banned. Datagram layout is `mac || rlp([timestamp, nodes, banned])`,
+//! where `mac` is HMAC-SHA256 of the rest of the datagram keyed with the secret shared by the fleet.
+
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::net::SocketAddr;
+use std::str::FromStr;
+
+use crypto::hmac;
+use ethereum_types::H256;
+use network::{Error, ErrorKind, FleetConfiguration};
+use rlp::{Rlp, RlpStream};
+
+use discovery::NodeEntry;
+use node_table::{Node, NodeId};
+
+/// Maximal size of a fleet datagram.
+pub const MAX_FLEET_DATAGRAM_SIZE: usize = 32 * 1024;
+/// Maximal number of nodes shared in a single message.
+const MAX_SHARED_NODES: usize = 64;
+/// Maximal number of remembered bans, oldest are forgotten first.
+const MAX_BANS: usize = 256;
+/// Maximal difference between the timestamp of a message and the current time, in seconds.
+const MAX_CLOCK_DRIFT: u64 = 60;
+
+const MAC_LENGTH: usize = 32;
+
+/// Peers and bans received from a fleet member.
+#[derive(Debug, Default)]
+pub struct FleetUpdate {
+	/// Nodes the member successfully connected to.
+	pub nodes: Vec<NodeEntry>,
+	/// Nodes banned by the member and not banned locally yet.
+	pub banned: Vec<NodeId>,
+}
+
+/// State of the fleet channel.
+pub struct Fleet {
+	secret: H256,
+	members: Vec<SocketAddr>,
+	banned: HashSet<NodeId>,
+	bans_order: VecDeque<NodeId>,
+	last_timestamps: HashMap<SocketAddr, u64>,
+}
+
+impl Fleet {
+	/// Creates new fleet channel state.
+	pub fn new(config: &FleetConfiguration) -> Self {
+		Fleet {
+			secret: config.secret,
+			members: config.members.clone(),
+			banned: HashSet::new(),
+			bans_order: VecDeque::new(),
+			last_timestamps: HashMap::new(),
+		}
+	}
+
+	/// Addresses of other fleet members.
+	pub fn members(&self) -> &[SocketAddr] {
+		&self.members
+	}
+
+	/// Nodes banned by this node or any other fleet member.
+	pub fn banned(&self) -> &HashSet<NodeId> {
+		&self.banned
+	}
+
+	/// Bans a node. Returns `false` if it was banned already.
+	pub fn ban(&mut self, id: NodeId) -> bool {
+		if !self.banned.insert(id) {
+			return false;
+		}
+		self.bans_order.push_back(id);
+		if self.bans_order.len() > MAX_BANS {
+			if let Some(oldest) = self.bans_order.pop_front() {
+				self.banned.remove(&oldest);
+			}
+		}
+		true
+	}
+
+	/// Creates an authenticated message sharing given nodes and all known bans.
+	pub fn message(&self, nodes: &[NodeEntry], now: u64) -> Vec<u8> {
+		let nodes: Vec<_> = nodes.iter().take(MAX_SHARED_NODES).collect();
+		let mut rlp = RlpStream::new_list(3);
+		rlp.append(&now);
+		rlp.begin_list(nodes.len());
+		for node in nodes {
+			rlp.append(&Node::new(node.id, node.endpoint.clone()).to_string());
+		}
+		rlp.begin_list(self.bans_order.len());
+		for id in &self.bans_order {
+			rlp.append(id);
+		}
+		let payload = rlp.out();
+
+		let mac = hmac::sign(&hmac::SigKey::sha256(&self.secret[..]), &payload);
+		let mut message = Vec::with_capacity(MAC_LENGTH + payload.len());
+		message.extend_from_slice(&*mac);
+		message.extend_from_slice(&payload);
+		message
+	}
+
+	/// Verifies and processes a message received from given address.
+	pub fn on_packet(&mut self, packet: &[u8], from: SocketAddr, now: u64) -> Result<FleetUpdate, Error> {
+		if packet.len() <= MAC_LENGTH {
+			return Err(ErrorKind::BadProtocol.into());
+		}
+		let (mac, payload) = packet.split_at(MAC_LENGTH);
+		if !hmac::verify(&hmac::VerifyKey::sha256(&self.secret[..]), payload, mac) {
+			debug!(target: "network", "Fleet message with invalid MAC from {}", from);
+			return Err(ErrorKind::Auth.into());
+		}
+
+		let rlp = Rlp::new(payload);
+		let timestamp: u64 = rlp.val_at(0)?;
+		let drift = if timestamp > now { timestamp - now } else { now - timestamp };
+		let last = self.last_timestamps.get(&from).cloned().unwrap_or(0);
+		if drift > MAX_CLOCK_DRIFT || timestamp <= last {
+			debug!(target: "network", "Expired fleet message from {}", from);
+			return Err(ErrorKind::Expired.into());
+		}
+		self.last_timestamps.insert(from, timestamp);
+
+		let mut update = FleetUpdate::default();
+		for node in rlp.at(1)?.iter().take(MAX_SHARED_NODES) {
+			let url: String = node.as_val()?;
+			match Node::from_str(&url) {
+				Ok(node) => update.nodes.push(NodeEntry { id: node.id, endpoint: node.endpoint }),
+				Err(e) => debug!(target: "network", "Invalid node {} shared by fleet member {}: {:?}", url, from, e),
+			}
+		}
+		for id in rlp.at(2)?.iter().take(MAX_BANS) {
+			let id: NodeId = id.as_val()?;
+			if self.ban(id) {
+				update.banned.push(id);
+			}
+		}
+
+		Ok(update)
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use std::net::SocketAddr;
+	use std::str::FromStr;
+	use network::FleetConfiguration;
+	use discovery::NodeEntry;
+	use node_table::Node;
+	use super::Fleet;
+
+	const NOW: u64 = 1_500_000_000;
+
+	fn fleet(secret: u64) -> Fleet {
+		Fleet::new(&FleetConfiguration {
+			secret: secret.into(),
+			listen_address: "127.0.0.1:30310".parse().unwrap(),
+			members: vec!["127.0.0.1:30311".parse().unwrap()],
+		})
+	}
+
+	fn node() -> NodeEntry {
+		let node = Node::from_str("enode://a979fb575495b8d6db44f750317d0f4622bf4c2aa3365d6af7c284339968eef29b69ad0dce72a4d8db5ebb4968de0e3bec910127f134779fbcb0cb6d3331163c@22.99.55.44:7770").unwrap();
+		NodeEntry { id: node.id, endpoint: node.endpoint }
+	}
+
+	#[test]
+	fn should_share_nodes_and_bans() {
+		// given
+		let mut sender = fleet(1);
+		let mut receiver = fleet(1);
+		let from: SocketAddr = "127.0.0.1:30311".parse().unwrap();
+		sender.ban(5.into());
+
+		// when
+		let update = receiver.on_packet(&sender.message(&[node()], NOW), from, NOW).unwrap();
+
+		// then
+		assert_eq!(update.nodes.len(), 1);
+		assert_eq!(update.nodes[0].id, node().id);
+		assert_eq!(update.nodes[0].endpoint, node().endpoint);
+		assert_eq!(update.banned, vec![5.into()]);
+		assert!(receiver.banned().contains(&5.into()));
+	}
+
+	#[test]
+	fn should_reject_unauthenticated_or_replayed_messages() {
+		let sender = fleet(1);
+		let mut receiver = fleet(1);
+		let from: SocketAddr = "127.0.0.1:30311".parse().unwrap();
+		let message = sender.message(&[node()], NOW);
+
+		assert!(fleet(2).on_packet(&message, from, NOW).is_err());
+		assert!(receiver.on_packet(&sender.message(&[], NOW - 61), from, NOW).is_err());
+		assert!(receiver.on_packet(&message, from, NOW).is_ok());
+		assert!(receiver.on_packet(&message, from, NOW).is_err());
+	}
+
+	#[test]
+	fn should_forget_oldest_bans() {
+		let mut fleet = fleet(1);
+		for i in 0..(super::MAX_BANS as u64 + 1) {
+			assert!(fleet.ban(i.into()));
+		}
+
+		assert!(!fleet.ban(1.into()));
+		assert!(!fleet.banned().contains(&0.into()));
+		assert_eq!(fleet.banned().len(), super::MAX_BANS);
+	}
+}
@@ -15,7 +15,7 @@
 // along with Parity.  If not, see <http://www.gnu.org/licenses/>.
 
 use std::net::{SocketAddr, SocketAddrV4, Ipv4Addr};
-use std::collections::{HashMap, HashSet};
+use std::collections::{HashMap, HashSet, VecDeque};
 use std::str::FromStr;
 use std::sync::Arc;
 use std::sync::atomic::{AtomicBool, Ordering as AtomicOrdering};
@@ -24,7 +24,7 @@ use std::cmp::{min, max};
 use std::path::{Path, PathBuf};
 use std::io::{Read, Write, self};
 use std::fs;
-use std::time::Duration;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
 use ethkey::{KeyPair, Secret, Random, Generator};
 use hash::keccak;
 use mio::*;
@@ -40,9 +40,14 @@ use PROTOCOL_VERSION;
 use node_table::*;
 use network::{NetworkConfiguration, NetworkIoMessage, ProtocolId, PeerId, PacketId};
 use network::{NonReservedPeerMode, NetworkContext as NetworkContextTrait};
-use network::{SessionInfo, Error, ErrorKind, DisconnectReason, NetworkProtocolHandler};
+use network::{SessionInfo, Error, ErrorKind, DisconnectReason, NetworkProtocolHandler, ProtocolSlots};
+use network::{HandshakeFailure, HandshakeFailureReason, ReputationChange, IpFilter, Traffic};
 use discovery::{Discovery, TableUpdates, NodeEntry, MAX_DATAGRAM_SIZE};
-use ip_utils::{map_external_address, select_public_address};
+use ip_utils::{map_external_address, select_public_address, is_ip_allowed};
+use key_log::KeyLog;
+use fleet::{Fleet, MAX_FLEET_DATAGRAM_SIZE};
+use natpmp::PortMapper;
+use deny_list::DenyList;
 use parity_path::restrict_permissions_owner;
 use parking_lot::{Mutex, RwLock};
 use network::{ConnectionFilter, ConnectionDirection};
@@ -54,6 +59,9 @@ const MAX_HANDSHAKES: usize = 1024;
 
 const DEFAULT_PORT: u16 = 30303;
 
+// Number of recent handshake failures to keep.
+const MAX_HANDSHAKE_FAILURES: usize = 128;
+
 // StreamToken/TimerToken
 const TCP_ACCEPT: StreamToken = SYS_TIMER + 1;
 const IDLE: TimerToken = SYS_TIMER + 2;
@@ -62,6 +70,10 @@ const DISCOVERY_REFRESH: TimerToken = SYS_TIMER + 4;
 const FAST_DISCOVERY_REFRESH: TimerToken = SYS_TIMER + 5;
 const DISCOVERY_ROUND: TimerToken = SYS_TIMER + 6;
 const NODE_TABLE: TimerToken = SYS_TIMER + 7;
+const FLEET: StreamToken = SYS_TIMER + 8;
+const FLEET_GOSSIP: TimerToken = SYS_TIMER + 9;
+const NAT_RENEWAL: TimerToken = SYS_TIMER + 10;
+const DENY_LIST_RELOAD: TimerToken = SYS_TIMER + 11;
 const FIRST_SESSION: StreamToken = 0;
 const LAST_SESSION: StreamToken = FIRST_SESSION + MAX_SESSIONS - 1;
 const USER_TIMER: TimerToken = LAST_SESSION + 256;
@@ -78,6 +90,12 @@ const FAST_DISCOVERY_REFRESH_TIMEOUT: Duration = Duration::from_secs(10);
 const DISCOVERY_ROUND_TIMEOUT: Duration = Duration::from_millis(300);
 // for NODE_TABLE TimerToken
 const NODE_TABLE_TIMEOUT: Duration = Duration::from_secs(300);
+// for FLEET_GOSSIP TimerToken
+const FLEET_GOSSIP_TIMEOUT: Duration = Duration::from_secs(30);
+// for NAT_RENEWAL TimerToken
+const NAT_RENEWAL_TIMEOUT: Duration = Duration::from_secs(60);
+// for DENY_LIST_RELOAD TimerToken
+const DENY_LIST_RELOAD_TIMEOUT: Duration = Duration::from_secs(10);
 
 #[derive(Debug, PartialEq, Eq)]
 /// Protocol info
@@ -200,6 +218,16 @@ impl<'s> NetworkContextTrait for NetworkContext<'s> {
 			.map(|node| self.reserved_peers.contains(&node))
 			.unwrap_or(false)
 	}
+
+	fn note_handshake_failure(&self, peer: PeerId, reason: HandshakeFailureReason) {
+		self.io.message(NetworkIoMessage::HandshakeFailed(peer, reason))
+			.unwrap_or_else(|e| warn!("Error sending network IO message: {:?}", e));
+	}
+
+	fn note_reputation(&self, peer: PeerId, change: ReputationChange) {
+		self.io.message(NetworkIoMessage::ReputationChanged(peer, change))
+			.unwrap_or_else(|e| warn!("Error sending network IO message: {:?}", e));
+	}
 }
 
 /// Shared host information
@@ -218,6 +246,8 @@ pub struct HostInfo {
 	pub local_endpoint: NodeEndpoint,
 	/// Public address + discovery port
 	pub public_endpoint: Option<NodeEndpoint>,
+	/// File to export session secrets to.
+	pub key_log: Option<KeyLog>,
 }
 
 impl HostInfo {
@@ -261,6 +291,15 @@ pub struct Host {
 	reserved_nodes: RwLock<HashSet<NodeId>>,
 	stopping: AtomicBool,
 	filter: Option<Arc<ConnectionFilter>>,
+	handshake_failures: Mutex<VecDeque<HandshakeFailure>>,
+	fleet_socket: Mutex<Option<UdpSocket>>,
+	fleet: Mutex<Option<Fleet>>,
+	port_mapper: Mutex<Option<PortMapper>>,
+	/// IP filter of the configuration with the addresses of the deny list added.
+	ip_filter: RwLock<IpFilter>,
+	deny_list: Mutex<Option<DenyList>>,
+	/// Traffic of sessions which are already closed, per protocol.
+	closed_traffic: Mutex<HashMap<ProtocolId, Traffic>>,
 }
 
 impl Host {
@@ -294,8 +333,24 @@ impl Host {
 
 		let boot_nodes = config.boot_nodes.clone();
 		let reserved_nodes = config.reserved_nodes.clone();
+		let mut deny_list = config.denied_ips_path.as_ref().map(|path| DenyList::new(path));
+		let ip_filter = match deny_list {
+			Some(ref mut list) => {
+				list.reload();
+				config.ip_filter.with_denied(list.networks())
+			},
+			None => config.ip_filter.clone(),
+		};
 		config.max_handshakes = min(config.max_handshakes, MAX_HANDSHAKES as u32);
 
+		let key_log = match config.key_log_path {
+			Some(ref path) => {
+				warn!(target: "network", "Writing RLPx session secrets to {}. Anyone able to read it can decrypt the p2p traffic of this node.", path);
+				Some(KeyLog::open(Path::new(path))?)
+			},
+			None => None,
+		};
+
 		let mut host = Host {
 			info: RwLock::new(HostInfo {
 				keys,
@@ -305,6 +360,7 @@ impl Host {
 				capabilities: Vec::new(),
 				public_endpoint: None,
 				local_endpoint,
+				key_log,
 			}),
 			discovery: Mutex::new(None),
 			udp_socket: Mutex::new(None),
@@ -317,6 +373,13 @@ impl Host {
 			reserved_nodes: RwLock::new(HashSet::new()),
 			stopping: AtomicBool::new(false),
 			filter,
+			handshake_failures: Mutex::new(VecDeque::with_capacity(MAX_HANDSHAKE_FAILURES)),
+			fleet_socket: Mutex::new(None),
+			fleet: Mutex::new(None),
+			port_mapper: Mutex::new(None),
+			ip_filter: RwLock::new(ip_filter),
+			deny_list: Mutex::new(deny_list),
+			closed_traffic: Mutex::new(HashMap::new()),
 		};
 
 		for n in boot_nodes {
@@ -396,6 +459,46 @@ impl Host {
 		Ok(())
 	}
 
+	/// Replaces all reserved nodes. Sessions with nodes which stay reserved are kept.
+	/// In reserved-only mode sessions with nodes which are no longer reserved are dropped.
+	pub fn set_reserved_nodes(&self, ids: &[String], io: &IoContext<NetworkIoMessage>) -> Result<(), Error> {
+		let nodes = ids.iter().map(|id| Node::from_str(id)).collect::<Result<Vec<_>, _>>()?;
+
+		{
+			let mut table = self.nodes.write();
+			for n in &nodes {
+				table.add_node(Node::new(n.id, n.endpoint.clone()));
+			}
+		}
+		if let Some(ref mut discovery) = *self.discovery.lock() {
+			for n in &nodes {
+				discovery.add_node(NodeEntry { endpoint: n.endpoint.clone(), id: n.id });
+			}
+		}
+
+		let reserved: HashSet<NodeId> = nodes.into_iter().map(|n| n.id).collect();
+		*self.reserved_nodes.write() = reserved.clone();
+
+		if let NonReservedPeerMode::Deny = self.info.read().config.non_reserved_mode {
+			let mut to_kill = Vec::new();
+			for e in self.sessions.read().iter() {
+				let mut s = e.lock();
+				if s.id().map_or(false, |id| reserved.contains(id)) {
+					continue;
+				}
+
+				s.disconnect(io, DisconnectReason::ClientQuit);
+				to_kill.push(s.token());
+			}
+			for p in to_kill {
+				trace!(target: "network", "Disconnecting peer which is no longer reserved: {}", p);
+				self.kill_connection(p, io, false);
+			}
+		}
+
+		Ok(())
+	}
+
 	pub fn external_url(&self) -> Option<String> {
 		let info = self.info.read();
 		info.public_endpoint.as_ref().map(|e| format!("{}", Node::new(*info.id(), e.clone())))
@@ -435,27 +538,76 @@ impl Host {
 		peers
 	}
 
+	/// Get recent handshake failures, oldest first.
+	pub fn handshake_failures(&self) -> Vec<HandshakeFailure> {
+		self.handshake_failures.lock().iter().cloned().collect()
+	}
+
+	/// Bytes exchanged with all peers since the start, per protocol.
+	pub fn traffic(&self) -> HashMap<ProtocolId, Traffic> {
+		let mut traffic = HashMap::new();
+		for session in self.sessions.read().iter() {
+			let s = session.lock();
+			if s.expired() {
+				continue;
+			}
+			for (protocol, t) in &s.info.traffic {
+				traffic.entry(*protocol).or_insert_with(Traffic::default).add(t);
+			}
+		}
+		for (protocol, t) in self.closed_traffic.lock().iter() {
+			traffic.entry(*protocol).or_insert_with(Traffic::default).add(t);
+		}
+		traffic
+	}
+
+	fn note_handshake_failure(&self, session: &Session, reason: HandshakeFailureReason) {
+		trace!(target: "network", "Handshake with {:?} failed: {}", session.id(), reason);
+		let failure = HandshakeFailure {
+			remote_id: session.id().cloned(),
+			remote_address: session.remote_addr().map(|a| a.to_string()).unwrap_or_else(|_| "unknown".into()),
+			reason,
+			time: SystemTime::now(),
+		};
+		let mut failures = self.handshake_failures.lock();
+		if failures.len() == MAX_HANDSHAKE_FAILURES {
+			failures.pop_front();
+		}
+		failures.push_back(failure);
+	}
+
 	fn init_public_interface(&self, io: &IoContext<NetworkIoMessage>) -> Result<(), Error> {
 		if self.info.read().public_endpoint.is_some() {
 			return Ok(());
 		}
 		let local_endpoint = self.info.read().local_endpoint.clone();
 		let public_address = self.info.read().config.public_address;
-		let allow_ips = self.info.read().config.ip_filter.clone();
+		let allow_ips = self.ip_filter.read().clone();
+		let (nat_enabled, nat_pmp_enabled) = {
+			let info = self.info.read();
+			(info.config.nat_enabled, info.config.nat_pmp_enabled)
+		};
 		let public_endpoint = match public_address {
 			None => {
 				let public_address = select_public_address(local_endpoint.address.port());
 				let public_endpoint = NodeEndpoint { address: public_address, udp_port: local_endpoint.udp_port };
-				if self.info.read().config.nat_enabled {
-					match map_external_address(&local_endpoint) {
-						Some(endpoint) => {
-							info!("NAT mapped to external address {}", endpoint.address);
+				let upnp_endpoint = if nat_enabled { map_external_address(&local_endpoint) } else { None };
+				match upnp_endpoint {
+					Some(endpoint) => {
+						info!("NAT mapped to external address {}", endpoint.address);
+						endpoint
+					},
+					None if nat_pmp_enabled => match PortMapper::map(&local_endpoint) {
+						Some(mapper) => {
+							let endpoint = mapper.external_endpoint();
+							info!("NAT-PMP mapped to external address {}", endpoint.address);
+							*self.port_mapper.lock() = Some(mapper);
+							io.register_timer(NAT_RENEWAL, NAT_RENEWAL_TIMEOUT)?;
 							endpoint
 						},
 						None => public_endpoint
-					}
-				} else {
-					public_endpoint
+					},
+					None => public_endpoint
 				}
 			}
 			Some(addr) => NodeEndpoint { address: addr, udp_port: local_endpoint.udp_port }
@@ -471,7 +623,11 @@ impl Host {
 		let discovery = {
 			let info = self.info.read();
 			if info.config.discovery_enabled && info.config.non_reserved_mode == NonReservedPeerMode::Accept {
-				Some(Discovery::new(&info.keys, public_endpoint, allow_ips))
+				let mut discovery = Discovery::new(&info.keys, public_endpoint, allow_ips);
+				if info.config.discovery_v5 {
+					discovery.enable_v5(info.config.advertised_topics.clone(), info.config.searched_topics.clone());
+				}
+				Some(discovery)
 			} else { None }
 		};
 
@@ -488,7 +644,21 @@ impl Host {
 			io.register_timer(DISCOVERY_REFRESH, DISCOVERY_REFRESH_TIMEOUT)?;
 			io.register_timer(DISCOVERY_ROUND, DISCOVERY_ROUND_TIMEOUT)?;
 		}
+
+		let fleet = self.info.read().config.fleet.clone();
+		if let Some(fleet) = fleet {
+			let socket = UdpSocket::bind(&fleet.listen_address)?;
+			info!(target: "network", "Sharing peers with {} fleet members at {}", fleet.members.len(), fleet.listen_address);
+			*self.fleet_socket.lock() = Some(socket);
+			*self.fleet.lock() = Some(Fleet::new(&fleet));
+			io.register_stream(FLEET)?;
+			io.register_timer(FLEET_GOSSIP, FLEET_GOSSIP_TIMEOUT)?;
+		}
+
 		io.register_timer(NODE_TABLE, NODE_TABLE_TIMEOUT)?;
+		if self.deny_list.lock().is_some() {
+			io.register_timer(DENY_LIST_RELOAD, DENY_LIST_RELOAD_TIMEOUT)?;
+		}
 		io.register_stream(TCP_ACCEPT)?;
 		Ok(())
 	}
@@ -503,18 +673,34 @@ impl Host {
 	}
 
 	// returns (handshakes, egress, ingress)
-	fn session_count(&self) -> (usize, usize, usize) {
+	/// Returns the number of pending handshakes, outgoing and incoming sessions, and the number
+	/// of sessions belonging to each of the configured peer slots. Sessions taking reserved
+	/// peer slots are not counted as outgoing or incoming.
+	fn session_count(&self) -> (usize, usize, usize, Vec<usize>) {
+		let slots = self.info.read().config.peer_slots.clone();
 		let mut handshakes = 0;
 		let mut egress = 0;
 		let mut ingress = 0;
+		let mut slot_sessions = vec![0; slots.len()];
 		for s in self.sessions.read().iter() {
 			match s.try_lock() {
-				Some(ref s) if s.is_ready() && s.info.originated => egress += 1,
-				Some(ref s) if s.is_ready() && !s.info.originated => ingress += 1,
+				Some(ref s) if s.is_ready() => {
+					if let Some(index) = ProtocolSlots::find(&slots, &s.info.capabilities) {
+						slot_sessions[index] += 1;
+						if slot_sessions[index] <= slots[index].min_peers as usize {
+							continue;
+						}
+					}
+					if s.info.originated {
+						egress += 1;
+					} else {
+						ingress += 1;
+					}
+				},
 				_ => handshakes +=1,
 			}
 		}
-		(handshakes, egress, ingress)
+		(handshakes, egress, ingress, slot_sessions)
 	}
 
 	fn connecting_to(&self, id: &NodeId) -> bool {
@@ -543,7 +729,7 @@ impl Host {
 
 			config.min_peers
 		};
-		let (_, egress_count, ingress_count) = self.session_count();
+		let (_, egress_count, ingress_count, _) = self.session_count();
 
 		return egress_count + ingress_count >= min_peers as usize;
 	}
@@ -556,10 +742,10 @@ impl Host {
 			}
 			let config = &info.config;
 
-			(config.min_peers, config.non_reserved_mode == NonReservedPeerMode::Deny, config.max_handshakes as usize, config.ip_filter.clone(), *info.id())
+			(config.min_peers, config.non_reserved_mode == NonReservedPeerMode::Deny, config.max_handshakes as usize, self.ip_filter.read().clone(), *info.id())
 		};
 
-		let (handshake_count, egress_count, ingress_count) = self.session_count();
+		let (handshake_count, egress_count, ingress_count, _) = self.session_count();
 		let reserved_nodes = self.reserved_nodes.read();
 		if egress_count + ingress_count >= min_peers as usize + reserved_nodes.len() {
 			// check if all pinned nodes are connected.
@@ -711,6 +897,14 @@ impl Host {
 						Err(e) => {
 							let s = session.lock();
 							trace!(target: "network", "Session read error: {}:{:?} ({:?}) {:?}", token, s.id(), s.remote_addr(), e);
+							if !s.is_ready() {
+								let reason = match *e.kind() {
+									ErrorKind::Disconnect(DisconnectReason::TooManyPeers) => HandshakeFailureReason::TooManyPeers,
+									ErrorKind::Disconnect(reason) => HandshakeFailureReason::Disconnected(reason),
+									_ => HandshakeFailureReason::Error(e.to_string()),
+								};
+								self.note_handshake_failure(&s, reason);
+							}
 							match *e.kind() {
 								ErrorKind::Disconnect(DisconnectReason::IncompatibleProtocol) | ErrorKind::Disconnect(DisconnectReason::UselessPeer) => {
 									if let Some(id) = s.id() {
@@ -727,9 +921,9 @@ impl Host {
 							break;
 						},
 						Ok(SessionData::Ready) => {
-							let (_, egress_count, ingress_count) = self.session_count();
+							let (_, egress_count, ingress_count, slot_sessions) = self.session_count();
 							let mut s = session.lock();
-							let (min_peers, mut max_peers, reserved_only, self_id) = {
+							let (min_peers, mut max_peers, reserved_only, self_id, peer_slots) = {
 								let info = self.info.read();
 								let mut max_peers = info.config.max_peers;
 								for cap in &s.info.capabilities {
@@ -738,7 +932,7 @@ impl Host {
 										break;
 									}
 								}
-								(info.config.min_peers as usize, max_peers as usize, info.config.non_reserved_mode == NonReservedPeerMode::Deny, *info.id())
+								(info.config.min_peers as usize, max_peers as usize, info.config.non_reserved_mode == NonReservedPeerMode::Deny, *info.id(), info.config.peer_slots.clone())
 							};
 
 							max_peers = max(max_peers, min_peers);
@@ -748,19 +942,38 @@ impl Host {
 							// Check for the session limit.
 							// Outgoing connections are allowed as long as their count is <= min_peers
 							// Incoming connections are allowed to take all of the max_peers reserve, or at most half of the slots.
+							// Sessions within the reserved slots of their subprotocol are not subject to the general limits.
 							let max_ingress = max(max_peers - min_peers, min_peers / 2);
-							if reserved_only ||
-								(s.info.originated && egress_count > min_peers) ||
-								(!s.info.originated && ingress_count > max_ingress) {
+							let (reserved_slot, slots_full) = match ProtocolSlots::find(&peer_slots, &s.info.capabilities) {
+								Some(index) => {
+									let slot = &peer_slots[index];
+									let count = slot_sessions.get(index).cloned().unwrap_or(0);
+									(count <= slot.min_peers as usize, count > max(slot.max_peers, slot.min_peers) as usize)
+								},
+								None => (false, false),
+							};
+							if reserved_only || slots_full ||
+								(!reserved_slot && s.info.originated && egress_count > min_peers) ||
+								(!reserved_slot && !s.info.originated && ingress_count > max_ingress) {
 								if !self.reserved_nodes.read().contains(&id) {
 									// only proceed if the connecting peer is reserved.
 									trace!(target: "network", "Disconnecting non-reserved peer {:?}", id);
+									self.note_handshake_failure(&s, HandshakeFailureReason::TooManyPeers);
 									s.disconnect(io, DisconnectReason::TooManyPeers);
 									kill = true;
 									break;
 								}
 							}
 
+							let address_allowed = s.remote_addr().map_or(true, |a| is_ip_allowed(&a.ip(), &self.ip_filter.read()));
+							if !address_allowed && !self.reserved_nodes.read().contains(&id) {
+								trace!(target: "network", "Disconnecting peer {:?} with filtered address", id);
+								self.note_handshake_failure(&s, HandshakeFailureReason::AddressNotAllowed);
+								s.disconnect(io, DisconnectReason::DisconnectRequested);
+								kill = true;
+								break;
+							}
+
 							if !self.filter.as_ref().map_or(true, |f| f.connection_allowed(&self_id, &id, ConnectionDirection::Inbound)) {
 								trace!(target: "network", "Inbound connection not allowed for {:?}", id);
 								s.disconnect(io, DisconnectReason::UnexpectedIdentity);
@@ -789,6 +1002,7 @@ impl Host {
 
 							// Note connection success
 							self.nodes.write().note_success(&id);
+							s.info.reputation = self.nodes.read().reputation(&id);
 
 							for (p, _) in self.handlers.read().iter() {
 								if s.have_capability(*p) {
@@ -908,6 +1122,13 @@ impl Host {
 
 	fn connection_timeout(&self, token: StreamToken, io: &IoContext<NetworkIoMessage>) {
 		trace!(target: "network", "Connection timeout: {}", token);
+		let session = { self.sessions.read().get(token).cloned() };
+		if let Some(session) = session {
+			let s = session.lock();
+			if !s.expired() && !s.is_ready() {
+				self.note_handshake_failure(&s, HandshakeFailureReason::AckTimeout);
+			}
+		}
 		self.kill_connection(token, io, true)
 	}
 
@@ -931,6 +1152,10 @@ impl Host {
 					}
 					s.set_expired();
 					failure_id = s.id().cloned();
+					let mut closed_traffic = self.closed_traffic.lock();
+					for (protocol, t) in &s.info.traffic {
+						closed_traffic.entry(*protocol).or_insert_with(Traffic::default).add(t);
+					}
 				}
 				deregister = remote || s.done();
 			}
@@ -951,6 +1176,125 @@ impl Host {
 		}
 	}
 
+	fn fleet_readable(&self, io: &IoContext<NetworkIoMessage>) {
+		let mut updates = Vec::new();
+		if let (Some(socket), Some(fleet)) = (self.fleet_socket.lock().as_ref(), self.fleet.lock().as_mut()) {
+			let mut buf = [0u8; MAX_FLEET_DATAGRAM_SIZE];
+			loop {
+				match socket.recv_from(&mut buf) {
+					Ok(Some((len, address))) => match fleet.on_packet(&buf[0..len], address, unix_time()) {
+						Ok(update) => updates.push(update),
+						Err(e) => debug!(target: "network", "Error processing fleet message from {}: {:?}", address, e),
+					},
+					Ok(None) => break,
+					Err(e) => {
+						debug!(target: "network", "Error reading fleet socket: {:?}", e);
+						break;
+					}
+				}
+			}
+		}
+
+		let self_id = *self.info.read().id();
+		for update in updates {
+			let entries: Vec<_> = update.nodes.into_iter().filter(|entry| entry.id != self_id).collect();
+			{
+				let mut nodes = self.nodes.write();
+				for entry in &entries {
+					if !nodes.contains(&entry.id) {
+						nodes.add_node(Node::new(entry.id, entry.endpoint.clone()));
+					}
+				}
+				for id in &update.banned {
+					nodes.mark_as_useless(id);
+				}
+			}
+			if let Some(ref mut discovery) = *self.discovery.lock() {
+				discovery.add_node_list(entries);
+			}
+			if !update.banned.is_empty() {
+				self.disconnect_banned(&update.banned, io);
+			}
+		}
+		if self.discovery.lock().is_some() {
+			io.update_registration(DISCOVERY).unwrap_or_else(|e| debug!("Error updating discovery registration: {:?}", e));
+		}
+	}
+
+	fn reload_deny_list(&self, io: &IoContext<NetworkIoMessage>) {
+		let ip_filter = match self.deny_list.lock().as_mut() {
+			Some(list) => {
+				if !list.reload() {
+					return;
+				}
+				self.info.read().config.ip_filter.with_denied(list.networks())
+			},
+			None => return,
+		};
+		if let Some(discovery) = self.discovery.lock().as_mut() {
+			discovery.set_ip_filter(ip_filter.clone());
+		}
+		*self.ip_filter.write() = ip_filter.clone();
+
+		let reserved = self.reserved_nodes.read().clone();
+		let mut to_kill = Vec::new();
+		for e in self.sessions.read().iter() {
+			let mut s = e.lock();
+			let is_reserved = s.id().map_or(false, |id| reserved.contains(id));
+			let is_denied = s.remote_addr().map_or(false, |a| !is_ip_allowed(&a.ip(), &ip_filter));
+			if is_denied && !is_reserved {
+				s.disconnect(io, DisconnectReason::DisconnectRequested);
+				to_kill.push(s.token());
+			}
+		}
+		for p in to_kill {
+			trace!(target: "network", "Disconnecting peer with denied address: {}", p);
+			self.kill_connection(p, io, false);
+		}
+	}
+
+	fn renew_port_mappings(&self) {
+		let changed = match self.port_mapper.lock().as_mut() {
+			Some(mapper) => mapper.renew(Instant::now()),
+			None => return,
+		};
+		if let Some(endpoint) = changed {
+			info!("NAT-PMP external address changed to {}", endpoint.address);
+			self.info.write().public_endpoint = Some(endpoint);
+		}
+	}
+
+	fn fleet_gossip(&self) {
+		let nodes = self.nodes.read().verified_entries();
+		if let (Some(socket), Some(fleet)) = (self.fleet_socket.lock().as_ref(), self.fleet.lock().as_ref()) {
+			let message = fleet.message(&nodes, unix_time());
+			for member in fleet.members() {
+				match socket.send_to(&message, member) {
+					Ok(Some(_)) => {},
+					Ok(None) => debug!(target: "network", "Fleet socket not writable, skipping {}", member),
+					Err(e) => debug!(target: "network", "Error sending fleet message to {}: {:?}", member, e),
+				}
+			}
+		}
+	}
+
+	fn disconnect_banned(&self, banned: &[NodeId], io: &IoContext<NetworkIoMessage>) {
+		let reserved = self.reserved_nodes.read().clone();
+		let mut to_kill = Vec::new();
+		for e in self.sessions.read().iter() {
+			let mut s = e.lock();
+			let is_banned = s.id().map_or(false, |id| banned.contains(id) && !reserved.contains(id));
+			if is_banned {
+				s.disconnect(io, DisconnectReason::DisconnectRequested);
+				to_kill.push(s.token());
+			}
+		}
+		for p in to_kill {
+			trace!(target: "network", "Disconnecting peer banned by fleet: {}", p);
+			self.kill_connection(p, io, false);
+		}
+	}
+
 	fn update_nodes(&self, _io: &IoContext<NetworkIoMessage>, node_changes: TableUpdates) {
 		let mut to_remove: Vec<PeerId> = Vec::new();
 		{
@@ -1008,6 +1352,7 @@ impl IoHandler<NetworkIoMessage> for Host {
 		match stream {
 			FIRST_SESSION ... LAST_SESSION => self.session_readable(stream, io),
 			DISCOVERY => self.discovery_readable(io),
+			FLEET => self.fleet_readable(io),
 			TCP_ACCEPT => self.accept(io),
 			_ => panic!("Received unknown readable token"),
 		}
@@ -1020,6 +1365,7 @@ impl IoHandler<NetworkIoMessage> for Host {
 		match stream {
 			FIRST_SESSION ... LAST_SESSION => self.session_writable(stream, io),
 			DISCOVERY => self.discovery_writable(io),
+			FLEET => (),
 			_ => panic!("Received unknown writable token"),
 		}
 	}
@@ -1053,9 +1399,18 @@ impl IoHandler<NetworkIoMessage> for Host {
 			},
 			NODE_TABLE => {
 				trace!(target: "network", "Refreshing node table");
-				self.nodes.write().clear_useless();
-				self.nodes.write().save();
+				let mut nodes = self.nodes.write();
+				nodes.clear_useless();
+				if let Some(ref fleet) = *self.fleet.lock() {
+					for id in fleet.banned() {
+						nodes.mark_as_useless(id);
+					}
+				}
+				nodes.save();
 			},
+			FLEET_GOSSIP => self.fleet_gossip(),
+			NAT_RENEWAL => self.renew_port_mappings(),
+			DENY_LIST_RELOAD => self.reload_deny_list(io),
 			_ => match self.timers.read().get(&token).cloned() {
 				Some(timer) => match self.handlers.read().get(&timer.protocol).cloned() {
 					None => { warn!(target: "network", "No handler found for protocol: {:?}", timer.protocol) },
@@ -1125,11 +1480,33 @@ impl IoHandler<NetworkIoMessage> for Host {
 						let mut nodes = self.nodes.write();
 						nodes.note_failure(&id);
 						nodes.mark_as_useless(id);
+						if let Some(ref mut fleet) = *self.fleet.lock() {
+							fleet.ban(*id);
+						}
 					}
 				}
 				trace!(target: "network", "Disabling peer {}", peer);
 				self.kill_connection(*peer, io, false);
 			},
+			NetworkIoMessage::HandshakeFailed(ref peer, ref reason) => {
+				let session = { self.sessions.read().get(*peer).cloned() };
+				if let Some(session) = session {
+					self.note_handshake_failure(&session.lock(), reason.clone());
+				}
+			},
+			NetworkIoMessage::ReputationChanged(ref peer, ref change) => {
+				let session = { self.sessions.read().get(*peer).cloned() };
+				if let Some(session) = session {
+					let mut s = session.lock();
+					let id = s.id().cloned();
+					if let Some(id) = id {
+						trace!(target: "network", "Reputation of peer {} changed: {:?}", peer, change);
+						let mut nodes = self.nodes.write();
+						nodes.note_reputation(&id, change.value());
+						s.info.reputation = nodes.reputation(&id);
+					}
+				}
+			},
 			NetworkIoMessage::InitPublicInterface =>
 				self.init_public_interface(io).unwrap_or_else(|e| warn!("Error initializing public interface: {:?}", e)),
 			_ => {}	// ignore others.
@@ -1151,6 +1528,13 @@ impl IoHandler<NetworkIoMessage> for Host {
 				},
 				_ => panic!("Error registering discovery socket"),
 			}
+			FLEET => match self.fleet_socket.lock().as_ref() {
+				Some(socket) => {
+					event_loop.register(socket, reg, Ready::readable(), PollOpt::edge())
+						.expect("Error registering fleet socket");
+				},
+				_ => panic!("Error registering fleet socket"),
+			}
 			TCP_ACCEPT => event_loop.register(&*self.tcp_listener.lock(), Token(TCP_ACCEPT), Ready::all(), PollOpt::edge()).expect("Error registering stream"),
 			_ => warn!("Unexpected stream registration")
 		}
@@ -1169,6 +1553,7 @@ impl IoHandler<NetworkIoMessage> for Host {
 				}
 			}
 			DISCOVERY => (),
+			FLEET => (),
 			_ => warn!("Unexpected stream deregistration")
 		}
 	}
@@ -1193,12 +1578,23 @@ impl IoHandler<NetworkIoMessage> for Host {
 				},
 				_ => panic!("Error reregistering discovery socket"),
 			}
+			FLEET => match self.fleet_socket.lock().as_ref() {
+				Some(socket) => {
+					event_loop.reregister(socket, reg, Ready::readable(), PollOpt::edge())
+						.expect("Error reregistering fleet socket");
+				},
+				_ => panic!("Error reregistering fleet socket"),
+			}
 			TCP_ACCEPT => event_loop.reregister(&*self.tcp_listener.lock(), Token(TCP_ACCEPT), Ready::all(), PollOpt::edge()).expect("Error reregistering stream"),
 			_ => warn!("Unexpected stream update")
 		}
 	}
 }
 
+fn unix_time() -> u64 {
+	SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default().as_secs()
+}
+
 fn save_key(path: &Path, key: &Secret) {
 	let mut path_buf = PathBuf::from(path);
 	if let Err(e) = fs::create_dir_all(path_buf.as_path()) {
@@ -22,6 +22,7 @@ use igd::{PortMappingProtocol, search_gateway_from_timeout};
 use std::time::Duration;
 use node_table::NodeEndpoint;
 use ipnetwork::IpNetwork;
+use network::{AllowIP, IpFilter};
 
 /// Socket address extension for rustc beta. To be replaces with now unstable API
 pub trait SocketAddrExt {
@@ -279,6 +280,18 @@ fn get_if_addrs() -> io::Result<Vec<IpAddr>> {
 	Ok(Vec::new())
 }
 
+/// Whether connections with `ip` are allowed by `filter`.
+pub fn is_ip_allowed(ip: &IpAddr, filter: &IpFilter) -> bool {
+	let predefined = match filter.predefined {
+		AllowIP::All => true,
+		AllowIP::Private => ip.is_usable_private(),
+		AllowIP::Public => ip.is_usable_public(),
+		AllowIP::None => false,
+	};
+	(predefined || filter.custom_allow.iter().any(|ipnet| ip.is_within(ipnet)))
+		&& !filter.custom_block.iter().any(|ipnet| ip.is_within(ipnet))
+}
+
 /// Select the best available public address
 pub fn select_public_address(port: u16) -> SocketAddr {
 	match get_if_addrs() {
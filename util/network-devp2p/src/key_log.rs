@@ -0,0 +1,117 @@
+// Copyright 2015-2018 Parity Technologies (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Export of RLPx session secrets for debugging captured traffic.
+//!
+//! Following the NSS key log convention, every established session appends a single line:
+//!
+//! `RLPX <local address> <remote address> <initiator nonce> <recipient nonce> <aes secret> <mac secret>`
+//!
+//! All values but the addresses are hex encoded. Together with the `auth` and `ack` packets
+//! seen on the wire they are enough to decrypt frames of the session and check their MACs.
+
+use std::fs::{File, OpenOptions};
+use std::io::{self, Write};
+use std::path::Path;
+
+use ethereum_types::H256;
+use parity_path::restrict_permissions_owner;
+use parking_lot::Mutex;
+use rustc_hex::ToHex;
+
+/// Secrets of a single RLPx session.
+pub struct SessionSecrets<'a> {
+	/// Local socket address.
+	pub local_address: &'a str,
+	/// Remote socket address.
+	pub remote_address: &'a str,
+	/// Nonce of the side which sent `auth`.
+	pub initiator_nonce: &'a H256,
+	/// Nonce of the side which sent `ack`.
+	pub recipient_nonce: &'a H256,
+	/// Frame encryption key.
+	pub aes_secret: &'a [u8],
+	/// Frame MAC key.
+	pub mac_secret: &'a [u8],
+}
+
+impl<'a> SessionSecrets<'a> {
+	fn to_line(&self) -> String {
+		format!("RLPX {} {} {:x} {:x} {} {}\n",
+			self.local_address,
+			self.remote_address,
+			self.initiator_nonce,
+			self.recipient_nonce,
+			self.aes_secret.to_hex(),
+			self.mac_secret.to_hex(),
+		)
+	}
+}
+
+/// Append-only file with secrets of established sessions.
+pub struct KeyLog {
+	file: Mutex<File>,
+}
+
+impl KeyLog {
+	/// Open the key log, creating the file if it does not exist.
+	pub fn open(path: &Path) -> io::Result<KeyLog> {
+		let file = OpenOptions::new().create(true).append(true).open(path)?;
+		if let Err(e) = restrict_permissions_owner(path, true, false) {
+			warn!(target: "network", "Failed to modify permissions of the key log ({})", e);
+		}
+		Ok(KeyLog {
+			file: Mutex::new(file),
+		})
+	}
+
+	/// Record secrets of an established session.
+	pub fn log(&self, secrets: &SessionSecrets) {
+		let mut file = self.file.lock();
+		if let Err(e) = file.write_all(secrets.to_line().as_bytes()).and_then(|_| file.flush()) {
+			warn!(target: "network", "Error writing RLPx key log: {}", e);
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use std::fs;
+	use tempdir::TempDir;
+	use super::*;
+
+	#[test]
+	fn appends_one_line_per_session() {
+		let dir = TempDir::new("key_log").unwrap();
+		let path = dir.path().join("rlpx.keys");
+		let log = KeyLog::open(&path).unwrap();
+		let nonce = H256::from(1);
+		let secrets = SessionSecrets {
+			local_address: "127.0.0.1:30303",
+			remote_address: "127.0.0.1:30304",
+			initiator_nonce: &nonce,
+			recipient_nonce: &nonce,
+			aes_secret: &[0xaa; 2],
+			mac_secret: &[0xbb; 2],
+		};
+		log.log(&secrets);
+		log.log(&secrets);
+
+		let nonce_hex = "0000000000000000000000000000000000000000000000000000000000000001";
+		let line = format!("RLPX 127.0.0.1:30303 127.0.0.1:30304 {} {} aaaa bbbb\n", nonce_hex, nonce_hex);
+		assert_eq!(fs::read_to_string(&path).unwrap(), format!("{}{}", line, line));
+	}
+}
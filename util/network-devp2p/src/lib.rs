@@ -106,6 +106,11 @@ mod discovery;
 mod service;
 mod node_table;
 mod ip_utils;
+mod key_log;
+mod fleet;
+mod discovery_v5;
+mod natpmp;
+mod deny_list;
 
 pub use service::NetworkService;
 pub use host::NetworkContext;
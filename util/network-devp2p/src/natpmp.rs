@@ -0,0 +1,491 @@
+// Copyright 2015-2018 Parity Technologies (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Port mapping with NAT-PMP (RFC 6886) and PCP (RFC 6887), for routers without UPnP.
+//!
+//! PCP is tried first. A router which only speaks NAT-PMP answers a PCP request with an
+//! "unsupported version" error, and is then driven with NAT-PMP. Mappings are leased by the
+//! router, `PortMapper::renew` has to be called periodically to keep them alive.
+
+use std::io;
+use std::net::{IpAddr, Ipv4Addr, SocketAddr, SocketAddrV4, UdpSocket};
+use std::time::{Duration, Instant};
+
+use rand;
+
+use ip_utils::select_public_address;
+use node_table::NodeEndpoint;
+
+/// Port the router listens on for NAT-PMP and PCP requests.
+const SERVER_PORT: u16 = 5351;
+/// Mapping lifetime requested from the router, in seconds.
+const REQUESTED_LIFETIME: u32 = 7200;
+/// Time to wait for the first response, doubled with every retransmission.
+const INITIAL_RESPONSE_TIMEOUT_MS: u64 = 250;
+/// Number of times a request is sent before the router is considered unresponsive.
+const MAX_ATTEMPTS: u32 = 4;
+/// Delay before retrying a failed lease renewal.
+const RENEWAL_RETRY: Duration = Duration::from_secs(60);
+
+const PCP_VERSION: u8 = 2;
+const NATPMP_VERSION: u8 = 0;
+const PCP_OPCODE_MAP: u8 = 1;
+const NATPMP_OPCODE_EXTERNAL_ADDRESS: u8 = 0;
+const NATPMP_OPCODE_MAP_UDP: u8 = 1;
+const NATPMP_OPCODE_MAP_TCP: u8 = 2;
+const RESPONSE_BIT: u8 = 0x80;
+const RESULT_SUCCESS: u16 = 0;
+const RESULT_UNSUPPORTED_VERSION: u16 = 1;
+const PCP_MAP_REQUEST_SIZE: usize = 60;
+const NATPMP_MAP_RESPONSE_SIZE: usize = 16;
+const NATPMP_EXTERNAL_ADDRESS_RESPONSE_SIZE: usize = 12;
+
+/// Transport protocol of a mapping.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Protocol {
+	Tcp,
+	Udp,
+}
+
+impl Protocol {
+	fn iana_number(&self) -> u8 {
+		match *self {
+			Protocol::Tcp => 6,
+			Protocol::Udp => 17,
+		}
+	}
+
+	fn natpmp_opcode(&self) -> u8 {
+		match *self {
+			Protocol::Tcp => NATPMP_OPCODE_MAP_TCP,
+			Protocol::Udp => NATPMP_OPCODE_MAP_UDP,
+		}
+	}
+}
+
+/// Port mapping protocol spoken by the router.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Version {
+	Pcp,
+	NatPmp,
+}
+
+/// Mapping granted by the router.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Mapping {
+	external_port: u16,
+	/// External address, only reported by PCP.
+	external_ip: Option<Ipv4Addr>,
+	lifetime: u32,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum ResponseError {
+	/// Sending failed or the gateway didn't answer.
+	NoResponse,
+	Malformed,
+	UnsupportedVersion,
+	Failure(u16),
+}
+
+fn put_u16(buf: &mut [u8], value: u16) {
+	buf[0] = (value >> 8) as u8;
+	buf[1] = value as u8;
+}
+
+fn put_u32(buf: &mut [u8], value: u32) {
+	put_u16(&mut buf[0..2], (value >> 16) as u16);
+	put_u16(&mut buf[2..4], value as u16);
+}
+
+fn get_u16(buf: &[u8]) -> u16 {
+	(buf[0] as u16) << 8 | buf[1] as u16
+}
+
+fn get_u32(buf: &[u8]) -> u32 {
+	(get_u16(&buf[0..2]) as u32) << 16 | get_u16(&buf[2..4]) as u32
+}
+
+/// Writes an IPv4 address in the IPv4-mapped IPv6 form used by PCP.
+fn put_mapped_ipv4(buf: &mut [u8], ip: &Ipv4Addr) {
+	for b in buf[0..10].iter_mut() {
+		*b = 0;
+	}
+	buf[10] = 0xff;
+	buf[11] = 0xff;
+	buf[12..16].copy_from_slice(&ip.octets());
+}
+
+fn get_mapped_ipv4(buf: &[u8]) -> Option<Ipv4Addr> {
+	if buf[0..10].iter().any(|b| *b != 0) || buf[10] != 0xff || buf[11] != 0xff {
+		return None;
+	}
+	Some(Ipv4Addr::new(buf[12], buf[13], buf[14], buf[15]))
+}
+
+/// Checks the common response header and turns a failed result code into an error.
+fn check_header(data: &[u8], version: u8, opcode: u8) -> Result<(), ResponseError> {
+	if data.len() < 4 {
+		return Err(ResponseError::Malformed);
+	}
+	if data[0] != version {
+		return Err(ResponseError::UnsupportedVersion);
+	}
+	if data[1] != opcode | RESPONSE_BIT {
+		return Err(ResponseError::Malformed);
+	}
+	// PCP has a reserved byte followed by an 8-bit result code, NAT-PMP a 16-bit result code.
+	let result = if version == PCP_VERSION { data[3] as u16 } else { get_u16(&data[2..4]) };
+	match result {
+		RESULT_SUCCESS => Ok(()),
+		RESULT_UNSUPPORTED_VERSION => Err(ResponseError::UnsupportedVersion),
+		code => Err(ResponseError::Failure(code)),
+	}
+}
+
+fn encode_pcp_map(nonce: &[u8; 12], protocol: Protocol, client: &Ipv4Addr, internal_port: u16, suggested_port: u16, lifetime: u32) -> [u8; PCP_MAP_REQUEST_SIZE] {
+	let mut packet = [0u8; PCP_MAP_REQUEST_SIZE];
+	packet[0] = PCP_VERSION;
+	packet[1] = PCP_OPCODE_MAP;
+	put_u32(&mut packet[4..8], lifetime);
+	put_mapped_ipv4(&mut packet[8..24], client);
+	packet[24..36].copy_from_slice(nonce);
+	packet[36] = protocol.iana_number();
+	put_u16(&mut packet[40..42], internal_port);
+	put_u16(&mut packet[42..44], suggested_port);
+	// Suggested external address is left as all zeros, letting the router pick one.
+	put_mapped_ipv4(&mut packet[44..60], &Ipv4Addr::new(0, 0, 0, 0));
+	packet
+}
+
+fn decode_pcp_map(data: &[u8], nonce: &[u8; 12], protocol: Protocol, internal_port: u16) -> Result<Mapping, ResponseError> {
+	check_header(data, PCP_VERSION, PCP_OPCODE_MAP)?;
+	if data.len() < PCP_MAP_REQUEST_SIZE {
+		return Err(ResponseError::Malformed);
+	}
+	if &data[24..36] != &nonce[..] || data[36] != protocol.iana_number() || get_u16(&data[40..42]) != internal_port {
+		return Err(ResponseError::Malformed);
+	}
+	Ok(Mapping {
+		external_port: get_u16(&data[42..44]),
+		external_ip: Some(get_mapped_ipv4(&data[44..60]).ok_or(ResponseError::Malformed)?),
+		lifetime: get_u32(&data[4..8]),
+	})
+}
+
+fn encode_natpmp_map(protocol: Protocol, internal_port: u16, suggested_port: u16, lifetime: u32) -> [u8; 12] {
+	let mut packet = [0u8; 12];
+	packet[0] = NATPMP_VERSION;
+	packet[1] = protocol.natpmp_opcode();
+	put_u16(&mut packet[4..6], internal_port);
+	put_u16(&mut packet[6..8], suggested_port);
+	put_u32(&mut packet[8..12], lifetime);
+	packet
+}
+
+fn decode_natpmp_map(data: &[u8], protocol: Protocol, internal_port: u16) -> Result<Mapping, ResponseError> {
+	check_header(data, NATPMP_VERSION, protocol.natpmp_opcode())?;
+	if data.len() < NATPMP_MAP_RESPONSE_SIZE || get_u16(&data[8..10]) != internal_port {
+		return Err(ResponseError::Malformed);
+	}
+	Ok(Mapping {
+		external_port: get_u16(&data[10..12]),
+		external_ip: None,
+		lifetime: get_u32(&data[12..16]),
+	})
+}
+
+fn encode_natpmp_external_address() -> [u8; 2] {
+	[NATPMP_VERSION, NATPMP_OPCODE_EXTERNAL_ADDRESS]
+}
+
+fn decode_natpmp_external_address(data: &[u8]) -> Result<Ipv4Addr, ResponseError> {
+	check_header(data, NATPMP_VERSION, NATPMP_OPCODE_EXTERNAL_ADDRESS)?;
+	if data.len() < NATPMP_EXTERNAL_ADDRESS_RESPONSE_SIZE {
+		return Err(ResponseError::Malformed);
+	}
+	Ok(Ipv4Addr::new(data[8], data[9], data[10], data[11]))
+}
+
+/// Finds the default gateway in the contents of `/proc/net/route`.
+fn parse_route_table(table: &str) -> Option<Ipv4Addr> {
+	table.lines().skip(1)
+		.map(|line| line.split_whitespace().collect::<Vec<_>>())
+		.filter(|fields| fields.len() > 2 && fields[1] == "00000000")
+		.filter_map(|fields| u32::from_str_radix(fields[2], 16).ok())
+		.filter(|gateway| *gateway != 0)
+		// The kernel prints addresses as native-endian integers of network-order bytes.
+		.map(|gateway| Ipv4Addr::from(u32::from_be(gateway)))
+		.next()
+}
+
+#[cfg(target_os = "linux")]
+fn default_gateway() -> Option<Ipv4Addr> {
+	use std::fs::File;
+	use std::io::Read;
+
+	let mut table = String::new();
+	File::open("/proc/net/route").and_then(|mut f| f.read_to_string(&mut table)).ok()?;
+	parse_route_table(&table)
+}
+
+#[cfg(not(target_os = "linux"))]
+fn default_gateway() -> Option<Ipv4Addr> {
+	None
+}
+
+/// Gateway to send requests to. Without a routing table, assume the router is
+/// the first address of the local /24 network, as it is for most home routers.
+fn find_gateway() -> Option<Ipv4Addr> {
+	default_gateway().or_else(|| match select_public_address(0).ip() {
+		IpAddr::V4(ip) if ip.is_private() => {
+			let o = ip.octets();
+			Some(Ipv4Addr::new(o[0], o[1], o[2], 1))
+		},
+		_ => None,
+	})
+}
+
+/// A single leased mapping.
+struct Lease {
+	protocol: Protocol,
+	internal_port: u16,
+	external_port: u16,
+	renew_at: Instant,
+}
+
+/// Port mappings of the node's TCP and UDP ports on a NAT-PMP or PCP router.
+pub struct PortMapper {
+	socket: UdpSocket,
+	client: Ipv4Addr,
+	version: Version,
+	/// PCP requires renewals to carry the nonce of the original request.
+	nonce: [u8; 12],
+	external_ip: Ipv4Addr,
+	tcp: Lease,
+	udp: Lease,
+}
+
+impl PortMapper {
+	/// Probes the gateway and maps the TCP and UDP ports of `local`.
+	/// Returns `None` if there is no NAT-PMP or PCP capable router.
+	pub fn map(local: &NodeEndpoint) -> Option<PortMapper> {
+		let tcp_port = local.address.port();
+		let gateway = find_gateway()?;
+		let socket = match Self::connect(gateway) {
+			Ok(socket) => socket,
+			Err(e) => {
+				debug!(target: "network", "NAT-PMP socket error: {:?}", e);
+				return None;
+			}
+		};
+		let client = match socket.local_addr() {
+			Ok(SocketAddr::V4(addr)) => *addr.ip(),
+			_ => return None,
+		};
+		let now = Instant::now();
+		let mut mapper = PortMapper {
+			socket,
+			client,
+			version: Version::Pcp,
+			nonce: rand::random(),
+			external_ip: Ipv4Addr::new(0, 0, 0, 0),
+			tcp: Lease { protocol: Protocol::Tcp, internal_port: tcp_port, external_port: tcp_port, renew_at: now },
+			udp: Lease { protocol: Protocol::Udp, internal_port: local.udp_port, external_port: local.udp_port, renew_at: now },
+		};
+
+		match mapper.request_mapping(Protocol::Tcp) {
+			Ok(_) => {},
+			Err(ResponseError::UnsupportedVersion) => {
+				trace!(target: "network", "Gateway {} does not support PCP, falling back to NAT-PMP", gateway);
+				mapper.version = Version::NatPmp;
+				if let Err(e) = mapper.request_mapping(Protocol::Tcp) {
+					debug!(target: "network", "NAT-PMP mapping error: {:?}", e);
+					return None;
+				}
+			},
+			Err(e) => {
+				debug!(target: "network", "PCP mapping error: {:?}", e);
+				return None;
+			},
+		}
+		if let Err(e) = mapper.request_mapping(Protocol::Udp) {
+			debug!(target: "network", "{:?} mapping error: {:?}", mapper.version, e);
+			return None;
+		}
+		Some(mapper)
+	}
+
+	fn connect(gateway: Ipv4Addr) -> io::Result<UdpSocket> {
+		let socket = UdpSocket::bind(SocketAddrV4::new(Ipv4Addr::new(0, 0, 0, 0), 0))?;
+		socket.connect(SocketAddrV4::new(gateway, SERVER_PORT))?;
+		Ok(socket)
+	}
+
+	/// Sends `packet` to the gateway, retransmitting with exponential backoff until a response arrives.
+	fn exchange(&self, packet: &[u8], response: &mut [u8]) -> Result<usize, ResponseError> {
+		let mut timeout = Duration::from_millis(INITIAL_RESPONSE_TIMEOUT_MS);
+		for _ in 0..MAX_ATTEMPTS {
+			self.socket.send(packet).map_err(|_| ResponseError::NoResponse)?;
+			self.socket.set_read_timeout(Some(timeout)).map_err(|_| ResponseError::NoResponse)?;
+			match self.socket.recv(response) {
+				Ok(len) => return Ok(len),
+				Err(ref e) if e.kind() == io::ErrorKind::WouldBlock || e.kind() == io::ErrorKind::TimedOut => {
+					timeout *= 2;
+				},
+				// ICMP port unreachable, nothing listens on the gateway.
+				Err(_) => break,
+			}
+		}
+		Err(ResponseError::NoResponse)
+	}
+
+	fn request_mapping(&mut self, protocol: Protocol) -> Result<(), ResponseError> {
+		let (internal_port, suggested_port) = {
+			let lease = self.lease(protocol);
+			(lease.internal_port, lease.external_port)
+		};
+		let mut response = [0u8; 1100];
+		let mapping = match self.version {
+			Version::Pcp => {
+				let request = encode_pcp_map(&self.nonce, protocol, &self.client, internal_port, suggested_port, REQUESTED_LIFETIME);
+				let len = self.exchange(&request, &mut response)?;
+				decode_pcp_map(&response[..len], &self.nonce, protocol, internal_port)?
+			},
+			Version::NatPmp => {
+				let len = self.exchange(&encode_natpmp_external_address(), &mut response)?;
+				self.external_ip = decode_natpmp_external_address(&response[..len])?;
+				let request = encode_natpmp_map(protocol, internal_port, suggested_port, REQUESTED_LIFETIME);
+				let len = self.exchange(&request, &mut response)?;
+				decode_natpmp_map(&response[..len], protocol, internal_port)?
+			},
+		};
+		if let Some(ip) = mapping.external_ip {
+			self.external_ip = ip;
+		}
+		let lease = self.lease_mut(protocol);
+		lease.external_port = mapping.external_port;
+		// Renew halfway through the lease, as recommended by both RFCs.
+		lease.renew_at = Instant::now() + Duration::from_secs(mapping.lifetime as u64 / 2);
+		Ok(())
+	}
+
+	fn lease(&self, protocol: Protocol) -> &Lease {
+		match protocol {
+			Protocol::Tcp => &self.tcp,
+			Protocol::Udp => &self.udp,
+		}
+	}
+
+	fn lease_mut(&mut self, protocol: Protocol) -> &mut Lease {
+		match protocol {
+			Protocol::Tcp => &mut self.tcp,
+			Protocol::Udp => &mut self.udp,
+		}
+	}
+
+	/// External endpoint the router forwards to the node.
+	pub fn external_endpoint(&self) -> NodeEndpoint {
+		NodeEndpoint {
+			address: SocketAddr::V4(SocketAddrV4::new(self.external_ip, self.tcp.external_port)),
+			udp_port: self.udp.external_port,
+		}
+	}
+
+	/// Renews leases which are due. Returns the new external endpoint if the router changed it.
+	pub fn renew(&mut self, now: Instant) -> Option<NodeEndpoint> {
+		let before = self.external_endpoint();
+		for protocol in &[Protocol::Tcp, Protocol::Udp] {
+			if self.lease(*protocol).renew_at > now {
+				continue;
+			}
+			if let Err(e) = self.request_mapping(*protocol) {
+				debug!(target: "network", "{:?} lease renewal error: {:?}", self.version, e);
+				self.lease_mut(*protocol).renew_at = now + RENEWAL_RETRY;
+			}
+		}
+		let after = self.external_endpoint();
+		if after != before { Some(after) } else { None }
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn pcp_map_roundtrip() {
+		let nonce = [7u8; 12];
+		let client = Ipv4Addr::new(192, 168, 1, 20);
+		let request = encode_pcp_map(&nonce, Protocol::Tcp, &client, 30303, 30303, REQUESTED_LIFETIME);
+		assert_eq!(request[0], PCP_VERSION);
+		assert_eq!(get_mapped_ipv4(&request[8..24]), Some(client));
+
+		// The router echoes the request, filling in the result, lifetime and assigned endpoint.
+		let mut response = request;
+		response[1] |= RESPONSE_BIT;
+		put_u32(&mut response[4..8], 3600);
+		put_u16(&mut response[42..44], 40000);
+		put_mapped_ipv4(&mut response[44..60], &Ipv4Addr::new(203, 0, 113, 5));
+		assert_eq!(decode_pcp_map(&response, &nonce, Protocol::Tcp, 30303), Ok(Mapping {
+			external_port: 40000,
+			external_ip: Some(Ipv4Addr::new(203, 0, 113, 5)),
+			lifetime: 3600,
+		}));
+		assert_eq!(decode_pcp_map(&response, &[8u8; 12], Protocol::Tcp, 30303), Err(ResponseError::Malformed));
+		assert_eq!(decode_pcp_map(&response, &nonce, Protocol::Udp, 30303), Err(ResponseError::Malformed));
+	}
+
+	#[test]
+	fn natpmp_router_rejects_pcp() {
+		// A NAT-PMP router answers requests of unknown versions with version 0 and result 1.
+		let response = [0u8, PCP_OPCODE_MAP | RESPONSE_BIT, 0, 1, 0, 0, 0, 0];
+		assert_eq!(decode_pcp_map(&response, &[0u8; 12], Protocol::Tcp, 30303), Err(ResponseError::UnsupportedVersion));
+	}
+
+	#[test]
+	fn natpmp_map_roundtrip() {
+		let request = encode_natpmp_map(Protocol::Udp, 30303, 30303, REQUESTED_LIFETIME);
+		assert_eq!(request, [0, 1, 0, 0, 0x76, 0x5f, 0x76, 0x5f, 0, 0, 0x1c, 0x20]);
+
+		let response = [0, 0x81, 0, 0, 0, 0, 0, 42, 0x76, 0x5f, 0x9c, 0x40, 0, 0, 0x0e, 0x10];
+		assert_eq!(decode_natpmp_map(&response, Protocol::Udp, 30303), Ok(Mapping {
+			external_port: 40000,
+			external_ip: None,
+			lifetime: 3600,
+		}));
+		let refused = [0, 0x81, 0, 2, 0, 0, 0, 42, 0x76, 0x5f, 0, 0, 0, 0, 0, 0];
+		assert_eq!(decode_natpmp_map(&refused, Protocol::Udp, 30303), Err(ResponseError::Failure(2)));
+	}
+
+	#[test]
+	fn natpmp_external_address() {
+		assert_eq!(encode_natpmp_external_address(), [0, 0]);
+		let response = [0, 0x80, 0, 0, 0, 0, 0, 42, 203, 0, 113, 5];
+		assert_eq!(decode_natpmp_external_address(&response), Ok(Ipv4Addr::new(203, 0, 113, 5)));
+		assert_eq!(decode_natpmp_external_address(&response[..8]), Err(ResponseError::Malformed));
+	}
+
+	#[test]
+	fn finds_default_gateway() {
+		let table = "Iface\tDestination\tGateway \tFlags\tRefCnt\tUse\tMetric\tMask\t\tMTU\tWindow\tIRTT\n\
+			eth0\t0001A8C0\t00000000\t0001\t0\t0\t0\t00FFFFFF\t0\t0\t0\n\
+			eth0\t00000000\t0101A8C0\t0003\t0\t0\t0\t00000000\t0\t0\t0\n";
+		let expected = if cfg!(target_endian = "little") { Ipv4Addr::new(192, 168, 1, 1) } else { Ipv4Addr::new(1, 1, 168, 192) };
+		assert_eq!(parse_route_table(table), Some(expected));
+		assert_eq!(parse_route_table("Iface\tDestination\tGateway\n"), None);
+	}
+}
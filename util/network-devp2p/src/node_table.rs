@@ -17,7 +17,7 @@
 use discovery::{TableUpdates, NodeEntry};
 use ethereum_types::H512;
 use ip_utils::*;
-use network::{Error, ErrorKind, AllowIP, IpFilter};
+use network::{Error, ErrorKind, IpFilter};
 use rlp::{Rlp, RlpStream, DecoderError};
 use serde_json;
 use std::collections::{HashMap, HashSet};
@@ -51,21 +51,7 @@ impl NodeEndpoint {
 	}
 
 	pub fn is_allowed(&self, filter: &IpFilter) -> bool {
-		(self.is_allowed_by_predefined(&filter.predefined) || filter.custom_allow.iter().any(|ipnet| {
-			self.address.ip().is_within(ipnet)
-		}))
-		&& !filter.custom_block.iter().any(|ipnet| {
-			self.address.ip().is_within(ipnet)
-		})
-	}
-
-	pub fn is_allowed_by_predefined(&self, filter: &AllowIP) -> bool {
-		match filter {
-			AllowIP::All => true,
-			AllowIP::Private => self.address.ip().is_usable_private(),
-			AllowIP::Public => self.address.ip().is_usable_public(),
-			AllowIP::None => false,
-		}
+		is_ip_allowed(&self.address.ip(), filter)
 	}
 
 	pub fn from_rlp(rlp: &Rlp) -> Result<Self, DecoderError> {
@@ -179,6 +165,8 @@ pub struct Node {
 	pub endpoint: NodeEndpoint,
 	pub peer_type: PeerType,
 	pub last_contact: Option<NodeContact>,
+	/// Score of the node based on its behaviour in previous sessions.
+	pub reputation: i32,
 }
 
 impl Node {
@@ -188,6 +176,7 @@ impl Node {
 			endpoint,
 			peer_type: PeerType::Optional,
 			last_contact: None,
+			reputation: 0,
 		}
 	}
 }
@@ -218,6 +207,7 @@ impl FromStr for Node {
 			endpoint,
 			peer_type: PeerType::Optional,
 			last_contact: None,
+			reputation: 0,
 		})
 	}
 }
@@ -236,6 +226,8 @@ impl Hash for Node {
 }
 
 const MAX_NODES: usize = 1024;
+/// Bounds of the reputation of a node.
+const MAX_REPUTATION: i32 = 1000;
 const NODES_FILE: &str = "nodes.json";
 
 /// Node table backed by disk file.
@@ -256,8 +248,11 @@ impl NodeTable {
 
 	/// Add a node to table
 	pub fn add_node(&mut self, mut node: Node) {
-		// preserve node last_contact
-		node.last_contact = self.nodes.get(&node.id).and_then(|n| n.last_contact);
+		// preserve node last_contact and reputation
+		if let Some(n) = self.nodes.get(&node.id) {
+			node.last_contact = n.last_contact;
+			node.reputation = n.reputation;
+		}
 		self.nodes.insert(node.id, node);
 	}
 
@@ -331,6 +326,20 @@ impl NodeTable {
 		}).collect()
 	}
 
+	/// Entries of nodes the last connection to which succeeded.
+	pub fn verified_entries(&self) -> Vec<NodeEntry> {
+		self.nodes.values()
+			.filter(|n| match n.last_contact {
+				Some(NodeContact::Success(_)) => true,
+				_ => false,
+			})
+			.map(|n| NodeEntry {
+				endpoint: n.endpoint.clone(),
+				id: n.id,
+			})
+			.collect()
+	}
+
 	/// Get particular node
 	pub fn get_mut(&mut self, id: &NodeId) -> Option<&mut Node> {
 		self.nodes.get_mut(id)
@@ -368,6 +377,18 @@ impl NodeTable {
 		}
 	}
 
+	/// Adjust the reputation of a node by `change`, keeping it within the bounds.
+	pub fn note_reputation(&mut self, id: &NodeId, change: i32) {
+		if let Some(node) = self.nodes.get_mut(id) {
+			node.reputation = node.reputation.saturating_add(change).max(-MAX_REPUTATION).min(MAX_REPUTATION);
+		}
+	}
+
+	/// Reputation of a node, `0` for unknown nodes.
+	pub fn reputation(&self, id: &NodeId) -> i32 {
+		self.nodes.get(id).map_or(0, |n| n.reputation)
+	}
+
 	/// Mark as useless, no further attempts to connect until next call to `clear_useless`.
 	pub fn mark_as_useless(&mut self, id: &NodeId) {
 		self.useless_nodes.insert(id.clone());
@@ -485,6 +506,8 @@ mod json {
 	pub struct Node {
 		pub url: String,
 		pub last_contact: Option<NodeContact>,
+		#[serde(default)]
+		pub reputation: i32,
 	}
 
 	impl Node {
@@ -492,6 +515,7 @@ mod json {
 			match super::Node::from_str(&self.url) {
 				Ok(mut node) => {
 					node.last_contact = self.last_contact.map(|c| c.into_node_contact());
+					node.reputation = self.reputation;
 					Some(node)
 				},
 				_ => None,
@@ -512,7 +536,8 @@ mod json {
 
 			Node {
 				url: format!("{}", node),
-				last_contact
+				last_contact,
+				reputation: node.reputation,
 			}
 		}
 	}
@@ -526,6 +551,7 @@ mod tests {
 	use std::str::FromStr;
 	use tempdir::TempDir;
 	use ipnetwork::IpNetwork;
+	use network::AllowIP;
 
 	#[test]
 	fn endpoint_parse() {
@@ -678,6 +704,27 @@ mod tests {
 		}
 	}
 
+	#[test]
+	fn table_save_load_reputation() {
+		let tempdir = TempDir::new("").unwrap();
+		let node = Node::from_str("enode://a979fb575495b8d6db44f750317d0f4622bf4c2aa3365d6af7c284339968eef29b69ad0dce72a4d8db5ebb4968de0e3bec910127f134779fbcb0cb6d3331163c@22.99.55.44:7770").unwrap();
+		let id = node.id;
+
+		{
+			let mut table = NodeTable::new(Some(tempdir.path().to_str().unwrap().to_owned()));
+			table.add_node(node);
+			table.note_reputation(&id, 10);
+			table.note_reputation(&id, -5000);
+			assert_eq!(table.reputation(&id), -MAX_REPUTATION);
+			table.note_reputation(&id, 1);
+		}
+
+		{
+			let table = NodeTable::new(Some(tempdir.path().to_str().unwrap().to_owned()));
+			assert_eq!(table.reputation(&id), 1 - MAX_REPUTATION);
+		}
+	}
+
 	#[test]
 	fn custom_allow() {
 		let filter = IpFilter {
@@ -723,4 +770,16 @@ mod tests {
 		assert!(!NodeEndpoint::from_str("[fc00::]:5550").unwrap().is_allowed(&filter));
 		assert!(NodeEndpoint::from_str("[fd00::]:5550").unwrap().is_allowed(&filter));
 	}
+	#[test]
+	fn deny_list() {
+		let denied = IpFilter::parse_list("# consortium exclusions\n10.1.0.0/16\n\n 1.2.3.4 \nfc00::/8\n").unwrap();
+		assert_eq!(denied.len(), 3);
+		let filter = IpFilter::parse("private").unwrap().with_denied(&denied);
+		assert!(NodeEndpoint::from_str("10.0.0.1:7770").unwrap().is_allowed(&filter));
+		assert!(!NodeEndpoint::from_str("10.1.200.1:7770").unwrap().is_allowed(&filter));
+		assert!(!NodeEndpoint::from_str("[fc00::1]:5550").unwrap().is_allowed(&filter));
+		assert!(NodeEndpoint::from_str("1.2.3.5:7770").unwrap().is_allowed(&IpFilter::default().with_denied(&denied)));
+		assert!(!NodeEndpoint::from_str("1.2.3.4:7770").unwrap().is_allowed(&IpFilter::default().with_denied(&denied)));
+		assert!(IpFilter::parse_list("10.1.0.0/33").is_err());
+	}
 }
@@ -15,10 +15,11 @@
 // along with Parity.  If not, see <http://www.gnu.org/licenses/>.
 
 use network::{Error, NetworkConfiguration, NetworkProtocolHandler, NonReservedPeerMode};
-use network::{NetworkContext, PeerId, ProtocolId, NetworkIoMessage};
+use network::{NetworkContext, PeerId, ProtocolId, NetworkIoMessage, HandshakeFailure, Traffic};
 use host::Host;
 use io::*;
 use parking_lot::RwLock;
+use std::collections::HashMap;
 use std::net::SocketAddr;
 use std::ops::Range;
 use std::sync::Arc;
@@ -56,7 +57,7 @@ impl NetworkService {
 	/// Starts IO event loop
 	pub fn new(config: NetworkConfiguration, filter: Option<Arc<ConnectionFilter>>) -> Result<NetworkService, Error> {
 		let host_handler = Arc::new(HostHandler { public_url: RwLock::new(None) });
-		let io_service = IoService::<NetworkIoMessage>::start()?;
+		let io_service = IoService::<NetworkIoMessage>::start_with_workers("network", config.io_threads)?;
 
 		Ok(NetworkService {
 			io_service,
@@ -152,6 +153,16 @@ impl NetworkService {
 		self.host.read().as_ref().map(|h| h.connected_peers()).unwrap_or_else(Vec::new)
 	}
 
+	/// Get recent handshake failures, oldest first.
+	pub fn handshake_failures(&self) -> Vec<HandshakeFailure> {
+		self.host.read().as_ref().map(|h| h.handshake_failures()).unwrap_or_else(Vec::new)
+	}
+
+	/// Returns bytes exchanged with all peers since the network was started, per protocol.
+	pub fn traffic(&self) -> HashMap<ProtocolId, Traffic> {
+		self.host.read().as_ref().map(|h| h.traffic()).unwrap_or_else(HashMap::new)
+	}
+
 	/// Try to add a reserved peer.
 	pub fn add_reserved_peer(&self, peer: &str) -> Result<(), Error> {
 		let host = self.host.read();
@@ -172,6 +183,17 @@ impl NetworkService {
 		}
 	}
 
+	/// Replace all reserved peers, keeping connections to peers which stay reserved.
+	pub fn set_reserved_peers(&self, peers: &[String]) -> Result<(), Error> {
+		let host = self.host.read();
+		if let Some(ref host) = *host {
+			let io_ctxt = IoContext::new(self.io_service.channel(), 0);
+			host.set_reserved_nodes(peers, &io_ctxt)
+		} else {
+			Ok(())
+		}
+	}
+
 	/// Set the non-reserved peer mode.
 	pub fn set_non_reserved_mode(&self, mode: NonReservedPeerMode) {
 		let host = self.host.read();
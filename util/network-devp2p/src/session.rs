@@ -28,7 +28,7 @@ use connection::{EncryptedConnection, Packet, Connection, MAX_PAYLOAD_SIZE};
 use handshake::Handshake;
 use io::{IoContext, StreamToken};
 use network::{Error, ErrorKind, DisconnectReason, SessionInfo, ProtocolId, PeerCapabilityInfo};
-use network::SessionCapabilityInfo;
+use network::{SessionCapabilityInfo, Traffic, BASE_PROTOCOL_ID};
 use host::*;
 use node_table::NodeId;
 use snappy;
@@ -120,6 +120,8 @@ impl Session {
 				originated,
 				remote_address: "Handshake".to_owned(),
 				local_address: local_addr,
+				reputation: 0,
+				traffic: HashMap::new(),
 			},
 			ping_time: Instant::now(),
 			pong_time: None,
@@ -133,7 +135,7 @@ impl Session {
 		let connection = if let State::Handshake(ref mut h) = self.state {
 			self.info.id = Some(h.id);
 			self.info.remote_address = h.connection.remote_addr_str();
-			EncryptedConnection::new(h)?
+			EncryptedConnection::new(h, host.key_log.as_ref())?
 		} else {
 			panic!("Unexpected state");
 		};
@@ -289,7 +291,13 @@ impl Session {
 			payload = &compressed[0..len];
 		}
 		rlp.append_raw(payload, 1);
-		self.send(io, &rlp.drain())
+		let packet = rlp.drain();
+		self.traffic_mut(protocol.unwrap_or(BASE_PROTOCOL_ID)).bytes_out += packet.len() as u64;
+		self.send(io, &packet)
+	}
+
+	fn traffic_mut(&mut self, protocol: ProtocolId) -> &mut Traffic {
+		self.info.traffic.entry(protocol).or_insert_with(Traffic::default)
 	}
 
 	/// Keep this session alive. Returns false if ping timeout happened
@@ -338,6 +346,9 @@ impl Session {
 		if packet_id != PACKET_HELLO && packet_id != PACKET_DISCONNECT && !self.had_hello {
 			return Err(ErrorKind::BadProtocol.into());
 		}
+		if packet_id < PACKET_USER || packet_id > PACKET_LAST {
+			self.traffic_mut(BASE_PROTOCOL_ID).bytes_in += packet.data.len() as u64;
+		}
 		let data = if self.compression {
 			let compressed = &packet.data[1..];
 			if snappy::decompressed_len(&compressed)? > MAX_PAYLOAD_SIZE {
@@ -386,6 +397,7 @@ impl Session {
 				// map to protocol
 				let protocol = self.info.capabilities[i].protocol;
 				let protocol_packet_id = packet_id - self.info.capabilities[i].id_offset;
+				self.traffic_mut(protocol).bytes_in += packet.data.len() as u64;
 
 				match *self.protocol_states.entry(protocol).or_insert_with(|| ProtocolState::Pending(Vec::new())) {
 					ProtocolState::Connected => {
@@ -146,3 +146,25 @@ fn net_timeout() {
 		thread::sleep(Duration::from_millis(50));
 	}
 }
+
+#[test]
+fn net_traffic() {
+	let key1 = Random.generate().unwrap();
+	let mut config1 = NetworkConfiguration::new_local();
+	config1.use_secret = Some(key1.secret().clone());
+	let mut service1 = NetworkService::new(config1, None).unwrap();
+	service1.start().unwrap();
+	let handler1 = TestProtocol::register(&mut service1, false);
+	let mut config2 = NetworkConfiguration::new_local();
+	config2.boot_nodes = vec![ service1.local_url().unwrap() ];
+	let mut service2 = NetworkService::new(config2, None).unwrap();
+	service2.start().unwrap();
+	let handler2 = TestProtocol::register(&mut service2, false);
+	while !(handler1.got_packet() && handler2.got_packet()) {
+		thread::sleep(Duration::from_millis(50));
+	}
+	let traffic = service1.traffic();
+	let tst = traffic[b"tst"];
+	assert!(tst.bytes_in > 0 && tst.bytes_out > 0);
+	assert!(traffic[&BASE_PROTOCOL_ID].bytes_in > 0);
+}
@@ -39,14 +39,15 @@ pub use io::TimerToken;
 pub use error::{Error, ErrorKind, DisconnectReason};
 
 use std::cmp::Ordering;
+use std::fmt;
 use std::collections::HashMap;
-use std::net::{SocketAddr, SocketAddrV4, Ipv4Addr};
+use std::net::{IpAddr, SocketAddr, SocketAddrV4, Ipv4Addr};
 use std::str::{self, FromStr};
 use std::sync::Arc;
-use std::time::Duration;
+use std::time::{Duration, SystemTime};
 use ipnetwork::{IpNetwork, IpNetworkError};
 use ethkey::Secret;
-use ethereum_types::H512;
+use ethereum_types::{H256, H512};
 use rlp::{Decodable, DecoderError, Rlp};
 
 /// Protocol handler level packet id
@@ -54,6 +55,9 @@ pub type PacketId = u8;
 /// Protocol / handler id
 pub type ProtocolId = [u8; 3];
 
+/// Protocol id under which traffic of the base devp2p protocol (hello, ping, disconnect) is accounted.
+pub const BASE_PROTOCOL_ID: ProtocolId = *b"p2p";
+
 /// Node public key
 pub type NodeId = H512;
 
@@ -89,6 +93,78 @@ pub enum NetworkIoMessage {
 	DisablePeer(PeerId),
 	/// Network has been started with the host as the given enode.
 	NetworkStarted(String),
+	/// Protocol handshake with a peer has failed.
+	HandshakeFailed(PeerId, HandshakeFailureReason),
+	/// Reputation of a peer has changed.
+	ReputationChanged(PeerId, ReputationChange),
+}
+
+/// Reason of a failed handshake with a remote node.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum HandshakeFailureReason {
+	/// Remote node did not answer our `auth` or `hello` in time.
+	AckTimeout,
+	/// Remote node is on a chain with a different genesis block.
+	GenesisMismatch,
+	/// Remote node is on a different network.
+	NetworkIdMismatch,
+	/// Either side refused the connection because of the peer limit.
+	TooManyPeers,
+	/// Remote address is not allowed by the IP filter.
+	AddressNotAllowed,
+	/// Remote node disconnected for another reason.
+	Disconnected(DisconnectReason),
+	/// Handshake could not be completed because of an error.
+	Error(String),
+}
+
+impl fmt::Display for HandshakeFailureReason {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		match *self {
+			HandshakeFailureReason::AckTimeout => f.write_str("ack timeout"),
+			HandshakeFailureReason::GenesisMismatch => f.write_str("genesis mismatch"),
+			HandshakeFailureReason::NetworkIdMismatch => f.write_str("network id mismatch"),
+			HandshakeFailureReason::TooManyPeers => f.write_str("too many peers"),
+			HandshakeFailureReason::AddressNotAllowed => f.write_str("address not allowed"),
+			HandshakeFailureReason::Disconnected(ref reason) => write!(f, "disconnected: {}", reason),
+			HandshakeFailureReason::Error(ref e) => write!(f, "error: {}", e),
+		}
+	}
+}
+
+/// Event affecting the reputation of a peer.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ReputationChange {
+	/// Peer has delivered useful data.
+	UsefulData,
+	/// Peer did not answer a request in time.
+	Timeout,
+	/// Peer has sent an invalid response.
+	InvalidResponse,
+}
+
+impl ReputationChange {
+	/// Amount by which the reputation changes.
+	pub fn value(&self) -> i32 {
+		match *self {
+			ReputationChange::UsefulData => 1,
+			ReputationChange::Timeout => -10,
+			ReputationChange::InvalidResponse => -50,
+		}
+	}
+}
+
+/// Recently failed handshake.
+#[derive(Debug, Clone)]
+pub struct HandshakeFailure {
+	/// Remote node id, if it was already known.
+	pub remote_id: Option<NodeId>,
+	/// Remote endpoint address.
+	pub remote_address: String,
+	/// Why the handshake has failed.
+	pub reason: HandshakeFailureReason,
+	/// When the handshake has failed.
+	pub time: SystemTime,
 }
 
 /// Shared session information
@@ -112,6 +188,27 @@ pub struct SessionInfo {
 	pub remote_address: String,
 	/// Local endpoint address of the session
 	pub local_address: String,
+	/// Reputation of the peer, including previous sessions.
+	pub reputation: i32,
+	/// Bytes exchanged with the peer per protocol, see `BASE_PROTOCOL_ID`.
+	pub traffic: HashMap<ProtocolId, Traffic>,
+}
+
+/// Number of bytes of packet payloads received from and sent to peers.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Traffic {
+	/// Bytes received
+	pub bytes_in: u64,
+	/// Bytes sent
+	pub bytes_out: u64,
+}
+
+impl Traffic {
+	/// Add the bytes of `other` to this one.
+	pub fn add(&mut self, other: &Traffic) {
+		self.bytes_in += other.bytes_in;
+		self.bytes_out += other.bytes_out;
+	}
 }
 
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -181,8 +278,16 @@ pub struct NetworkConfiguration {
 	pub udp_port: Option<u16>,
 	/// Enable NAT configuration
 	pub nat_enabled: bool,
+	/// Enable NAT-PMP and PCP port mapping, used when UPnP is not available
+	pub nat_pmp_enabled: bool,
 	/// Enable discovery
 	pub discovery_enabled: bool,
+	/// Enable topic discovery of discovery v5 alongside discovery v4
+	pub discovery_v5: bool,
+	/// Discovery v5 topics advertised by this node
+	pub advertised_topics: Vec<String>,
+	/// Discovery v5 topics to find peers for
+	pub searched_topics: Vec<String>,
 	/// List of initial node addresses
 	pub boot_nodes: Vec<String>,
 	/// Use provided node key instead of default
@@ -195,14 +300,24 @@ pub struct NetworkConfiguration {
 	pub max_handshakes: u32,
 	/// Reserved protocols. Peers with <key> protocol get additional <value> connection slots.
 	pub reserved_protocols: HashMap<ProtocolId, u32>,
+	/// Peer slots of specific subprotocols, in order of priority.
+	pub peer_slots: Vec<ProtocolSlots>,
 	/// List of reserved node addresses.
 	pub reserved_nodes: Vec<String>,
 	/// The non-reserved peer mode.
 	pub non_reserved_mode: NonReservedPeerMode,
 	/// IP filter
 	pub ip_filter: IpFilter,
+	/// File with IP addresses and CIDR ranges to refuse connections with, reloaded when it changes
+	pub denied_ips_path: Option<String>,
 	/// Client identifier
 	pub client_version: String,
+	/// Number of worker threads of the network IO service
+	pub io_threads: usize,
+	/// File to append secrets of established RLPx sessions to. For debugging only.
+	pub key_log_path: Option<String>,
+	/// Sharing of peers and bans with other nodes of the same operator.
+	pub fleet: Option<FleetConfiguration>,
 }
 
 impl Default for NetworkConfiguration {
@@ -221,17 +336,26 @@ impl NetworkConfiguration {
 			public_address: None,
 			udp_port: None,
 			nat_enabled: true,
+			nat_pmp_enabled: true,
 			discovery_enabled: true,
+			discovery_v5: false,
+			advertised_topics: Vec::new(),
+			searched_topics: Vec::new(),
 			boot_nodes: Vec::new(),
 			use_secret: None,
 			min_peers: 25,
 			max_peers: 50,
 			max_handshakes: 64,
 			reserved_protocols: HashMap::new(),
+			peer_slots: Vec::new(),
 			ip_filter: IpFilter::default(),
+			denied_ips_path: None,
 			reserved_nodes: Vec::new(),
 			non_reserved_mode: NonReservedPeerMode::Accept,
 			client_version: "Parity-network".into(),
+			io_threads: io::DEFAULT_IO_WORKERS,
+			key_log_path: None,
+			fleet: None,
 		}
 	}
 
@@ -247,10 +371,47 @@ impl NetworkConfiguration {
 		let mut config = NetworkConfiguration::new();
 		config.listen_address = Some(SocketAddr::V4(SocketAddrV4::new(Ipv4Addr::new(127, 0, 0, 1), 0)));
 		config.nat_enabled = false;
+		config.nat_pmp_enabled = false;
 		config
 	}
 }
 
+/// Peer slots of a subprotocol.
+///
+/// A session belongs to the slots of the first (highest priority) configured protocol it supports.
+/// Up to `min_peers` of such sessions don't count towards the general peer limits, so e.g. light
+/// clients don't take the slots of peers needed for block propagation.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ProtocolSlots {
+	/// Protocol id.
+	pub protocol: ProtocolId,
+	/// Number of slots reserved for peers with the protocol.
+	pub min_peers: u32,
+	/// Maximal number of peers with the protocol.
+	pub max_peers: u32,
+}
+
+impl ProtocolSlots {
+	/// Returns the index of the slots the session with given capabilities belongs to.
+	pub fn find(slots: &[ProtocolSlots], capabilities: &[SessionCapabilityInfo]) -> Option<usize> {
+		slots.iter().position(|slot| capabilities.iter().any(|cap| cap.protocol == slot.protocol))
+	}
+}
+
+/// Configuration of the fleet channel.
+///
+/// Nodes of the same operator periodically send each other peers they successfully connected to
+/// and nodes they banned, authenticated with a shared secret.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FleetConfiguration {
+	/// Secret shared by all fleet members.
+	pub secret: H256,
+	/// UDP address to receive messages of other members at.
+	pub listen_address: SocketAddr,
+	/// UDP addresses of other fleet members.
+	pub members: Vec<SocketAddr>,
+}
+
 /// IO access point. This is passed to all IO handlers and provides an interface to the IO subsystem.
 pub trait NetworkContext {
 	/// Send a packet over the network to another peer.
@@ -288,6 +449,12 @@ pub trait NetworkContext {
 
 	/// Returns whether the given peer ID is a reserved peer.
 	fn is_reserved_peer(&self, peer: PeerId) -> bool;
+
+	/// Record a failed protocol handshake with a peer. Should be called before the peer is disconnected.
+	fn note_handshake_failure(&self, peer: PeerId, reason: HandshakeFailureReason);
+
+	/// Adjust the reputation of a peer. The reputation is persisted in the node table.
+	fn note_reputation(&self, peer: PeerId, change: ReputationChange);
 }
 
 impl<'a, T> NetworkContext for &'a T where T: ?Sized + NetworkContext {
@@ -338,6 +505,14 @@ impl<'a, T> NetworkContext for &'a T where T: ?Sized + NetworkContext {
 	fn is_reserved_peer(&self, peer: PeerId) -> bool {
 		(**self).is_reserved_peer(peer)
 	}
+
+	fn note_handshake_failure(&self, peer: PeerId, reason: HandshakeFailureReason) {
+		(**self).note_handshake_failure(peer, reason)
+	}
+
+	fn note_reputation(&self, peer: PeerId, change: ReputationChange) {
+		(**self).note_reputation(peer, change)
+	}
 }
 
 /// Network IO protocol handler. This needs to be implemented for each new subprotocol.
@@ -414,6 +589,27 @@ impl IpFilter {
         }
         Ok(filter)
     }
+
+    /// Parse a list of IP addresses and CIDR ranges, one per line. Empty lines and
+    /// lines starting with `#` are ignored, an address without a prefix length is a single host.
+    pub fn parse_list(s: &str) -> Result<Vec<IpNetwork>, IpNetworkError> {
+        s.lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty() && !line.starts_with("#"))
+            .map(|line| match IpAddr::from_str(line) {
+                Ok(ip @ IpAddr::V4(_)) => IpNetwork::new(ip, 32),
+                Ok(ip @ IpAddr::V6(_)) => IpNetwork::new(ip, 128),
+                Err(_) => IpNetwork::from_str(line),
+            })
+            .collect()
+    }
+
+    /// Returns this filter with the given ranges added to the blocked ones.
+    pub fn with_denied(&self, denied: &[IpNetwork]) -> IpFilter {
+        let mut filter = self.clone();
+        filter.custom_block.extend_from_slice(denied);
+        filter
+    }
 }
 
 /// IP fiter
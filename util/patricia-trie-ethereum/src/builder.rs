@@ -0,0 +1,379 @@
+// Copyright 2015-2018 Parity Technologies (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Bottom-up trie construction from a stream of sorted keys.
+//!
+//! Since keys arrive in ascending order, every subtrie left of the current key is final
+//! and is encoded and written to the database right away. Only the nodes on the path
+//! of the last key are kept in memory and no node is ever written twice.
+
+use std::{fmt, mem};
+use ethereum_types::H256;
+use hashdb::HashDB;
+use keccak_hasher::KeccakHasher;
+use rlp::RlpStream;
+use trie::NodeCodec;
+
+use RlpCodec;
+
+/// Error returned when a key is less than the previous one.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UnsortedKey(pub Vec<u8>);
+
+impl fmt::Display for UnsortedKey {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		write!(f, "Trie key {:?} is less than the previous key", self.0)
+	}
+}
+
+/// Reference to a finished node, as it appears in the parent node.
+enum ChildRef {
+	/// Rlp of a node shorter than 32 bytes.
+	Inline(Vec<u8>),
+	/// Hash of a node written to the database.
+	Hash(H256),
+}
+
+enum Node {
+	Empty,
+	Leaf(Vec<u8>, Vec<u8>),
+	Extension(Vec<u8>, Box<Node>),
+	Branch(Box<[Node; 16]>, Option<Vec<u8>>),
+	Done(ChildRef),
+}
+
+fn empty_children() -> Box<[Node; 16]> {
+	use self::Node::Empty;
+	Box::new([Empty, Empty, Empty, Empty, Empty, Empty, Empty, Empty, Empty, Empty, Empty, Empty, Empty, Empty, Empty, Empty])
+}
+
+/// Builds a trie from key-value pairs supplied in ascending key order.
+///
+/// The builder doesn't borrow the database, so building can be spread over
+/// several calls writing to the same database.
+pub struct TrieBuilder {
+	root: Node,
+	last_key: Option<Vec<u8>>,
+}
+
+impl Default for TrieBuilder {
+	fn default() -> Self {
+		TrieBuilder::new()
+	}
+}
+
+impl TrieBuilder {
+	/// Create a builder of an empty trie.
+	pub fn new() -> Self {
+		TrieBuilder {
+			root: Node::Empty,
+			last_key: None,
+		}
+	}
+
+	/// Build a trie from the strictly ascending `iter`, write it to `db` and return its root.
+	pub fn from_sorted_iter<I, K, V>(db: &mut HashDB<KeccakHasher>, iter: I) -> Result<H256, UnsortedKey> where
+		I: IntoIterator<Item = (K, V)>,
+		K: AsRef<[u8]>,
+		V: AsRef<[u8]>,
+	{
+		let mut builder = TrieBuilder::new();
+		for (key, value) in iter {
+			let key = key.as_ref();
+			if builder.last_key.as_ref().map_or(false, |last| key == &last[..]) {
+				return Err(UnsortedKey(key.to_vec()));
+			}
+			builder.insert(db, key, value.as_ref())?;
+		}
+		Ok(builder.finish(db))
+	}
+
+	/// Whether `key` can be inserted, i.e. it's not less than the previously inserted key.
+	pub fn accepts(&self, key: &[u8]) -> bool {
+		self.last_key.as_ref().map_or(true, |last| key >= &last[..])
+	}
+
+	/// Add an item to the trie, writing finished nodes to `db`. The key must not be less than
+	/// the keys of all previously inserted items; inserting the last key again replaces its value.
+	pub fn insert(&mut self, db: &mut HashDB<KeccakHasher>, key: &[u8], value: &[u8]) -> Result<(), UnsortedKey> {
+		if !self.accepts(key) {
+			return Err(UnsortedKey(key.to_vec()));
+		}
+		self.last_key = Some(key.to_vec());
+
+		let nibbles = as_nibbles(key);
+		let mut root = mem::replace(&mut self.root, Node::Empty);
+		insert_at(db, &mut root, &nibbles, value.to_vec());
+		self.root = root;
+		Ok(())
+	}
+
+	/// Write the remaining nodes to `db` and return the root of the trie.
+	pub fn finish(self, db: &mut HashDB<KeccakHasher>) -> H256 {
+		if let Node::Empty = self.root {
+			return RlpCodec::hashed_null_node();
+		}
+
+		// the root node is always stored by its hash, even when it's short
+		let encoded = encode(db, self.root);
+		db.insert(&encoded)
+	}
+}
+
+fn insert_at(db: &mut HashDB<KeccakHasher>, node: &mut Node, key: &[u8], value: Vec<u8>) {
+	*node = match mem::replace(node, Node::Empty) {
+		Node::Empty => Node::Leaf(key.to_vec(), value),
+		Node::Leaf(ref partial, _) if &partial[..] == key => Node::Leaf(key.to_vec(), value),
+		Node::Leaf(partial, old) => {
+			// keys are sorted, so the new key is not a prefix of `partial`
+			let shared = shared_prefix_len(&partial, key);
+			let mut children = empty_children();
+			let branch_value = if shared == partial.len() {
+				Some(old)
+			} else {
+				let done = finalize(db, Node::Leaf(partial[shared + 1..].to_vec(), old));
+				children[partial[shared] as usize] = Node::Done(done);
+				None
+			};
+			children[key[shared] as usize] = Node::Leaf(key[shared + 1..].to_vec(), value);
+			with_extension(&partial[..shared], Node::Branch(children, branch_value))
+		},
+		Node::Extension(partial, mut child) => {
+			let shared = shared_prefix_len(&partial, key);
+			if shared == partial.len() {
+				insert_at(db, &mut child, &key[shared..], value);
+				Node::Extension(partial, child)
+			} else {
+				// the new key leaves the subtrie below the extension, which is complete now
+				let old = with_extension(&partial[shared + 1..], *child);
+				let mut children = empty_children();
+				children[partial[shared] as usize] = Node::Done(finalize(db, old));
+				children[key[shared] as usize] = Node::Leaf(key[shared + 1..].to_vec(), value);
+				with_extension(&partial[..shared], Node::Branch(children, None))
+			}
+		},
+		Node::Branch(mut children, branch_value) => {
+			let index = key[0] as usize;
+			for child in children[..index].iter_mut() {
+				match *child {
+					Node::Empty | Node::Done(_) => {},
+					_ => {
+						let done = finalize(db, mem::replace(child, Node::Empty));
+						*child = Node::Done(done);
+					},
+				}
+			}
+			insert_at(db, &mut children[index], &key[1..], value);
+			Node::Branch(children, branch_value)
+		},
+		Node::Done(_) => unreachable!("keys are sorted, finished subtries are never revisited; qed"),
+	};
+}
+
+/// Encode the node, writing it to the database unless it's inlined in the parent.
+fn finalize(db: &mut HashDB<KeccakHasher>, node: Node) -> ChildRef {
+	if let Node::Done(done) = node {
+		return done;
+	}
+
+	let encoded = encode(db, node);
+	if encoded.len() < 32 {
+		ChildRef::Inline(encoded)
+	} else {
+		ChildRef::Hash(db.insert(&encoded))
+	}
+}
+
+fn encode(db: &mut HashDB<KeccakHasher>, node: Node) -> Vec<u8> {
+	let mut stream = RlpStream::new();
+	match node {
+		Node::Empty => {
+			stream.append_empty_data();
+		},
+		Node::Leaf(partial, value) => {
+			stream.begin_list(2);
+			stream.append(&hex_prefix_encode(&partial, true));
+			stream.append(&value);
+		},
+		Node::Extension(partial, child) => {
+			stream.begin_list(2);
+			stream.append(&hex_prefix_encode(&partial, false));
+			let child = finalize(db, *child);
+			append_child(&mut stream, &child);
+		},
+		Node::Branch(mut children, value) => {
+			stream.begin_list(17);
+			for child in children.iter_mut() {
+				match mem::replace(child, Node::Empty) {
+					Node::Empty => {
+						stream.append_empty_data();
+					},
+					node => {
+						let child = finalize(db, node);
+						append_child(&mut stream, &child);
+					},
+				}
+			}
+			match value {
+				Some(value) => stream.append(&value),
+				None => stream.append_empty_data(),
+			};
+		},
+		Node::Done(ChildRef::Inline(encoded)) => return encoded,
+		Node::Done(ChildRef::Hash(_)) => unreachable!("hashed nodes are only referenced by their parents; qed"),
+	}
+	stream.out()
+}
+
+fn append_child(stream: &mut RlpStream, child: &ChildRef) {
+	match *child {
+		ChildRef::Inline(ref encoded) => stream.append_raw(encoded, 1),
+		ChildRef::Hash(ref hash) => stream.append(hash),
+	};
+}
+
+/// Prepend an extension node with `partial` key to `node`, if `partial` is not empty.
+fn with_extension(partial: &[u8], node: Node) -> Node {
+	if partial.is_empty() {
+		node
+	} else {
+		Node::Extension(partial.to_vec(), Box::new(node))
+	}
+}
+
+fn as_nibbles(bytes: &[u8]) -> Vec<u8> {
+	let mut res = Vec::with_capacity(bytes.len() * 2);
+	for byte in bytes {
+		res.push(byte >> 4);
+		res.push(byte & 0b1111);
+	}
+	res
+}
+
+fn hex_prefix_encode(nibbles: &[u8], leaf: bool) -> Vec<u8> {
+	let inlen = nibbles.len();
+	let oddness_factor = inlen % 2;
+	let mut res = Vec::with_capacity(inlen / 2 + 1);
+
+	let mut first_byte = ((inlen as u8 & 1) + (2 * leaf as u8)) << 4;
+	if oddness_factor == 1 {
+		first_byte += nibbles[0];
+	}
+	res.push(first_byte);
+
+	let mut offset = oddness_factor;
+	while offset < inlen {
+		res.push((nibbles[offset] << 4) + nibbles[offset + 1]);
+		offset += 2;
+	}
+	res
+}
+
+fn shared_prefix_len(first: &[u8], second: &[u8]) -> usize {
+	first.iter().zip(second.iter()).take_while(|&(a, b)| a == b).count()
+}
+
+#[cfg(test)]
+mod tests {
+	use ethereum_types::H256;
+	use hashdb::{DBValue, HashDB, Hasher};
+	use keccak_hasher::KeccakHasher;
+	use memorydb::MemoryDB;
+	use trie::{Trie, TrieMut};
+	use {TrieDB, TrieDBMut};
+	use super::{TrieBuilder, UnsortedKey};
+
+	fn sorted_items(n: usize) -> Vec<(Vec<u8>, Vec<u8>)> {
+		let mut items = (0..n)
+			.map(|i| {
+				let key = KeccakHasher::hash(&[(i >> 8) as u8, i as u8]).to_vec();
+				// mix short keys, keys being prefixes of other keys and values of various lengths
+				let key = match i % 4 {
+					0 => key[..1 + i % 3].to_vec(),
+					1 => key[31 - i % 3..].to_vec(),
+					_ => key,
+				};
+				(key, vec![i as u8; 1 + i % 40])
+			})
+			.collect::<Vec<_>>();
+		items.sort();
+		items.dedup_by(|a, b| a.0 == b.0);
+		items
+	}
+
+	#[test]
+	fn should_build_same_trie_as_triedbmut() {
+		for &n in &[0, 1, 2, 3, 17, 100, 1000] {
+			let items = sorted_items(n);
+
+			let mut expected_db = MemoryDB::<KeccakHasher>::new();
+			let mut expected_root = H256::new();
+			{
+				let mut t = TrieDBMut::new(&mut expected_db, &mut expected_root);
+				for &(ref key, ref value) in &items {
+					t.insert(key, value).unwrap();
+				}
+			}
+
+			let mut db = MemoryDB::<KeccakHasher>::new();
+			let root = TrieBuilder::from_sorted_iter(&mut db, items.clone()).unwrap();
+			assert_eq!(root, expected_root, "{} items", n);
+			assert_eq!(db.keys(), expected_db.keys(), "{} items", n);
+
+			let t = TrieDB::new(&db, &root).unwrap();
+			for (key, value) in items {
+				assert_eq!(t.get(&key).unwrap(), Some(DBValue::from_vec(value)));
+			}
+		}
+	}
+
+	#[test]
+	fn should_reject_unsorted_keys() {
+		let mut db = MemoryDB::<KeccakHasher>::new();
+		let items = vec![(b"b".to_vec(), b"1".to_vec()), (b"a".to_vec(), b"2".to_vec())];
+		assert_eq!(TrieBuilder::from_sorted_iter(&mut db, items), Err(UnsortedKey(b"a".to_vec())));
+
+		let items = vec![(b"a".to_vec(), b"1".to_vec()), (b"a".to_vec(), b"2".to_vec())];
+		assert_eq!(TrieBuilder::from_sorted_iter(&mut db, items), Err(UnsortedKey(b"a".to_vec())));
+
+		let mut builder = TrieBuilder::new();
+		builder.insert(&mut db, b"b", b"1").unwrap();
+		assert!(!builder.accepts(b"a"));
+		assert_eq!(builder.insert(&mut db, b"a", b"2"), Err(UnsortedKey(b"a".to_vec())));
+	}
+
+	#[test]
+	fn should_build_incrementally_and_replace_last_value() {
+		let items = sorted_items(100);
+
+		let mut expected_db = MemoryDB::<KeccakHasher>::new();
+		let expected_root = TrieBuilder::from_sorted_iter(&mut expected_db, items.clone()).unwrap();
+
+		// insert every item with a placeholder value first, as an account split across chunks would be.
+		let mut db = MemoryDB::<KeccakHasher>::new();
+		let mut builder = TrieBuilder::new();
+		for chunk in items.chunks(7) {
+			for &(ref key, ref value) in chunk {
+				builder.insert(&mut db, key, b"placeholder").unwrap();
+				assert!(builder.accepts(key));
+				builder.insert(&mut db, key, value).unwrap();
+			}
+		}
+		let root = builder.finish(&mut db);
+		assert_eq!(root, expected_root);
+		assert_eq!(db.keys(), expected_db.keys());
+	}
+}
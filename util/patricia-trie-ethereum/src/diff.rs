@@ -0,0 +1,337 @@
+// Copyright 2015-2018 Parity Technologies (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Difference between two tries stored in the same database.
+//!
+//! Both tries are walked at once and only the subtries which differ are visited.
+//! Subtries referenced by the same hash from both tries are skipped without being loaded,
+//! so the cost is proportional to the number of changes, not to the size of the tries.
+
+use ethereum_types::H256;
+use hashdb::{DBValue, HashDB, Hasher};
+use keccak_hasher::KeccakHasher;
+use parity_bytes::Bytes;
+use rlp::DecoderError;
+use trie::{NibbleSlice, NodeCodec, TrieError, node::Node};
+
+use {RlpCodec, Result};
+
+/// Change of a single trie item.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TrieChange {
+	/// Item exists only in the second trie.
+	Created {
+		/// Key of the item.
+		key: Bytes,
+		/// Value in the second trie.
+		value: DBValue,
+	},
+	/// Item exists only in the first trie.
+	Deleted {
+		/// Key of the item.
+		key: Bytes,
+		/// Value in the first trie.
+		value: DBValue,
+	},
+	/// Item exists in both tries, but with different values.
+	Modified {
+		/// Key of the item.
+		key: Bytes,
+		/// Value in the first trie.
+		old: DBValue,
+		/// Value in the second trie.
+		new: DBValue,
+	},
+}
+
+impl TrieChange {
+	/// Key of the changed item.
+	pub fn key(&self) -> &[u8] {
+		match *self {
+			TrieChange::Created { ref key, .. } |
+			TrieChange::Deleted { ref key, .. } |
+			TrieChange::Modified { ref key, .. } => key,
+		}
+	}
+}
+
+/// Reference to a node, as stored in its parent.
+#[derive(PartialEq)]
+enum NodeRef {
+	Hash(H256),
+	Inline(Bytes),
+}
+
+/// Decoded node. Partial keys are stored as nibbles.
+enum OwnedNode {
+	Empty,
+	Leaf(Vec<u8>, DBValue),
+	Extension(Vec<u8>, NodeRef),
+	Branch(Vec<Option<NodeRef>>, Option<DBValue>),
+}
+
+/// Node visited by the walk: either a stored one or a part of a leaf or an extension.
+enum Child {
+	Ref(NodeRef),
+	Node(OwnedNode),
+}
+
+/// Compute the items which differ between the tries with roots `root_a` and `root_b`.
+///
+/// Changes are ordered by key and describe how to turn the first trie into the second one.
+pub fn trie_diff(db: &HashDB<KeccakHasher>, root_a: &H256, root_b: &H256) -> Result<Vec<TrieChange>> {
+	let a = root_child(db, root_a)?;
+	let b = root_child(db, root_b)?;
+
+	let mut changes = Vec::new();
+	diff_children(db, a, b, &mut Vec::new(), &mut changes)?;
+	Ok(changes)
+}
+
+fn root_child(db: &HashDB<KeccakHasher>, root: &H256) -> Result<Option<Child>> {
+	if *root == RlpCodec::HASHED_NULL_NODE {
+		return Ok(None);
+	}
+
+	if !db.contains(root) {
+		return Err(Box::new(TrieError::InvalidStateRoot(*root)));
+	}
+
+	Ok(Some(Child::Ref(NodeRef::Hash(*root))))
+}
+
+fn diff_children(
+	db: &HashDB<KeccakHasher>,
+	a: Option<Child>,
+	b: Option<Child>,
+	path: &mut Vec<u8>,
+	changes: &mut Vec<TrieChange>,
+) -> Result<()> {
+	match (&a, &b) {
+		(&None, &None) => return Ok(()),
+		(&Some(Child::Ref(ref a)), &Some(Child::Ref(ref b))) if a == b => return Ok(()),
+		_ => {},
+	}
+
+	let (children_a, value_a) = branch_view(resolve(db, a)?);
+	let (children_b, value_b) = branch_view(resolve(db, b)?);
+
+	match (value_a, value_b) {
+		(None, None) => {},
+		(Some(value), None) => changes.push(TrieChange::Deleted { key: nibbles_to_key(path), value }),
+		(None, Some(value)) => changes.push(TrieChange::Created { key: nibbles_to_key(path), value }),
+		(Some(old), Some(new)) => if old != new {
+			changes.push(TrieChange::Modified { key: nibbles_to_key(path), old, new });
+		},
+	}
+
+	for (index, (a, b)) in children_a.into_iter().zip(children_b).enumerate() {
+		path.push(index as u8);
+		diff_children(db, a, b, path, changes)?;
+		path.pop();
+	}
+
+	Ok(())
+}
+
+fn resolve(db: &HashDB<KeccakHasher>, child: Option<Child>) -> Result<OwnedNode> {
+	match child {
+		None => Ok(OwnedNode::Empty),
+		Some(Child::Node(node)) => Ok(node),
+		Some(Child::Ref(NodeRef::Hash(hash))) => {
+			let data = db.get(&hash).ok_or_else(|| Box::new(TrieError::IncompleteDatabase(hash)))?;
+			decode(&data).map_err(|e| Box::new(TrieError::DecoderError(hash, e)))
+		},
+		Some(Child::Ref(NodeRef::Inline(data))) => {
+			decode(&data).map_err(|e| Box::new(TrieError::DecoderError(KeccakHasher::hash(&data), e)))
+		},
+	}
+}
+
+/// Present the node as a branch: children indexed by the next nibble of the key
+/// and the value stored exactly at the current path.
+fn branch_view(node: OwnedNode) -> (Vec<Option<Child>>, Option<DBValue>) {
+	let mut children = (0..16).map(|_| None).collect::<Vec<_>>();
+	let value = match node {
+		OwnedNode::Empty => None,
+		OwnedNode::Leaf(partial, value) => match partial.split_first() {
+			None => Some(value),
+			Some((&first, rest)) => {
+				children[first as usize] = Some(Child::Node(OwnedNode::Leaf(rest.to_vec(), value)));
+				None
+			},
+		},
+		OwnedNode::Extension(partial, child) => {
+			let (&first, rest) = partial.split_first().expect("extensions with empty key are rejected by decode; qed");
+			children[first as usize] = Some(match rest.is_empty() {
+				true => Child::Ref(child),
+				false => Child::Node(OwnedNode::Extension(rest.to_vec(), child)),
+			});
+			None
+		},
+		OwnedNode::Branch(refs, value) => {
+			for (child, node_ref) in children.iter_mut().zip(refs) {
+				*child = node_ref.map(Child::Ref);
+			}
+			value
+		},
+	};
+	(children, value)
+}
+
+fn decode(data: &[u8]) -> ::std::result::Result<OwnedNode, DecoderError> {
+	let node = match RlpCodec::decode(data)? {
+		Node::Empty => OwnedNode::Empty,
+		Node::Leaf(partial, value) => OwnedNode::Leaf(nibbles(&partial), DBValue::from_slice(value)),
+		Node::Extension(partial, child) => {
+			if partial.is_empty() {
+				return Err(DecoderError::Custom("Extension node with empty key."));
+			}
+			let child = node_ref(child).ok_or(DecoderError::Custom("Extension node without child."))?;
+			OwnedNode::Extension(nibbles(&partial), child)
+		},
+		Node::Branch(children, value) => OwnedNode::Branch(
+			children.iter().map(|child| node_ref(child)).collect(),
+			value.map(DBValue::from_slice),
+		),
+	};
+	Ok(node)
+}
+
+fn node_ref(data: &[u8]) -> Option<NodeRef> {
+	if RlpCodec::is_empty_node(data) {
+		return None;
+	}
+
+	Some(match RlpCodec::try_decode_hash(data) {
+		Some(hash) => NodeRef::Hash(hash),
+		None => NodeRef::Inline(data.to_vec()),
+	})
+}
+
+fn nibbles(slice: &NibbleSlice) -> Vec<u8> {
+	(0..slice.len()).map(|i| slice.at(i)).collect()
+}
+
+fn nibbles_to_key(nibbles: &[u8]) -> Bytes {
+	nibbles.chunks(2)
+		.map(|pair| (pair[0] << 4) | pair.get(1).cloned().unwrap_or(0))
+		.collect()
+}
+
+#[cfg(test)]
+mod tests {
+	use ethereum_types::H256;
+	use hashdb::{DBValue, HashDB};
+	use keccak_hasher::KeccakHasher;
+	use memorydb::MemoryDB;
+	use trie::{NodeCodec, TrieMut};
+	use {RlpCodec, TrieDB, TrieDBMut, TrieProof};
+	use super::{trie_diff, TrieChange};
+
+	const LONG_VALUE: &str = "value which is too long to be inlined in the parent node";
+
+	fn build<I, K, V>(memdb: &mut MemoryDB<KeccakHasher>, items: I) -> H256 where
+		I: IntoIterator<Item = (K, V)>,
+		K: AsRef<[u8]>,
+		V: AsRef<[u8]>,
+	{
+		let mut root = H256::new();
+		{
+			let mut t = TrieDBMut::new(memdb, &mut root);
+			for (key, value) in items {
+				t.insert(key.as_ref(), value.as_ref()).unwrap();
+			}
+		}
+		root
+	}
+
+	fn created(key: &[u8], value: &[u8]) -> TrieChange {
+		TrieChange::Created { key: key.to_vec(), value: DBValue::from_slice(value) }
+	}
+
+	fn deleted(key: &[u8], value: &[u8]) -> TrieChange {
+		TrieChange::Deleted { key: key.to_vec(), value: DBValue::from_slice(value) }
+	}
+
+	fn modified(key: &[u8], old: &[u8], new: &[u8]) -> TrieChange {
+		TrieChange::Modified { key: key.to_vec(), old: DBValue::from_slice(old), new: DBValue::from_slice(new) }
+	}
+
+	#[test]
+	fn should_diff_tries() {
+		let mut memdb = MemoryDB::new();
+		let root_a = build(&mut memdb, vec![
+			("do", "verb"), ("dog", "puppy"), ("doge", "coin"), ("horse", "stallion"),
+		]);
+		let root_b = build(&mut memdb, vec![
+			("dog", "puppy"), ("doge", "wow"), ("horse", "stallion"), ("house", "home"), ("a", LONG_VALUE),
+		]);
+
+		assert_eq!(trie_diff(&memdb, &root_a, &root_b).unwrap(), vec![
+			created(b"a", LONG_VALUE.as_bytes()),
+			deleted(b"do", b"verb"),
+			modified(b"doge", b"coin", b"wow"),
+			created(b"house", b"home"),
+		]);
+		assert_eq!(trie_diff(&memdb, &root_b, &root_a).unwrap(), vec![
+			deleted(b"a", LONG_VALUE.as_bytes()),
+			created(b"do", b"verb"),
+			modified(b"doge", b"wow", b"coin"),
+			deleted(b"house", b"home"),
+		]);
+	}
+
+	#[test]
+	fn should_diff_with_empty_trie() {
+		let mut memdb = MemoryDB::new();
+		let root = build(&mut memdb, vec![("dog", "puppy"), ("doge", "coin")]);
+		let empty = RlpCodec::HASHED_NULL_NODE;
+
+		assert_eq!(trie_diff(&memdb, &empty, &root).unwrap(), vec![
+			created(b"dog", b"puppy"), created(b"doge", b"coin"),
+		]);
+		assert_eq!(trie_diff(&memdb, &root, &empty).unwrap(), vec![
+			deleted(b"dog", b"puppy"), deleted(b"doge", b"coin"),
+		]);
+		assert!(trie_diff(&memdb, &root, &H256::from(1)).is_err());
+	}
+
+	#[test]
+	fn should_not_visit_shared_subtries() {
+		let mut items = (0u8..200)
+			.map(|i| (vec![i, i.wrapping_mul(7)], LONG_VALUE.as_bytes().to_vec()))
+			.collect::<Vec<_>>();
+		let mut memdb = MemoryDB::new();
+		let root_a = build(&mut memdb, items.clone());
+		items[100].1 = b"new value".to_vec();
+		let root_b = build(&mut memdb, items.clone());
+		let changed_key = items[100].0.clone();
+
+		// keep only the nodes on the path to the changed item
+		let mut pruned = MemoryDB::<KeccakHasher>::new();
+		for root in &[root_a, root_b] {
+			for node in TrieDB::new(&memdb, root).unwrap().prove(&changed_key).unwrap() {
+				pruned.insert(&node);
+			}
+		}
+
+		assert_eq!(trie_diff(&pruned, &root_a, &root_b).unwrap(), vec![
+			modified(&changed_key, LONG_VALUE.as_bytes(), b"new value"),
+		]);
+		assert!(trie_diff(&pruned, &root_a, &root_a).unwrap().is_empty());
+	}
+}
@@ -0,0 +1,163 @@
+// Copyright 2015-2018 Parity Technologies (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Iteration over a bounded range of trie keys.
+//!
+//! The iterator seeks directly to the first key of the range and stops as soon as
+//! a key falls outside of it, so only the nodes covering the requested range are visited.
+//!
+//! NOTE Bounds are compared against the keys yielded by the underlying iterator.
+//! For secure tries (`SecTrieDB`, `FatDB`) the trie is ordered by hashed keys,
+//! so bounded iteration should be performed on a plain `TrieDB` over hashed keys.
+
+use ethereum_types::H256;
+use keccak_hasher::KeccakHasher;
+use rlp::DecoderError;
+use trie::{Trie, TrieItem, TrieIterator};
+
+use {RlpCodec, Result};
+
+/// Iterator over trie items with keys in a bounded range.
+pub struct BoundedIterator<'a> {
+	inner: Box<TrieIterator<KeccakHasher, RlpCodec, Item = TrieItem<'a, H256, DecoderError>> + 'a>,
+	prefix: Option<Vec<u8>>,
+	end: Option<Vec<u8>>,
+	done: bool,
+}
+
+impl<'a> BoundedIterator<'a> {
+	/// Iterate over all items which keys start with given `prefix`.
+	pub fn prefix<T>(trie: &'a T, prefix: &[u8]) -> Result<Self> where
+		T: Trie<KeccakHasher, RlpCodec> + ?Sized,
+	{
+		let mut inner = trie.iter()?;
+		inner.seek(prefix)?;
+
+		Ok(BoundedIterator {
+			inner,
+			prefix: Some(prefix.to_vec()),
+			end: None,
+			done: false,
+		})
+	}
+
+	/// Iterate over items with keys in range `[start, end)`.
+	///
+	/// If `end` is `None` the iteration continues until the last key of the trie.
+	pub fn range<T>(trie: &'a T, start: &[u8], end: Option<&[u8]>) -> Result<Self> where
+		T: Trie<KeccakHasher, RlpCodec> + ?Sized,
+	{
+		let mut inner = trie.iter()?;
+		inner.seek(start)?;
+
+		Ok(BoundedIterator {
+			inner,
+			prefix: None,
+			end: end.map(|end| end.to_vec()),
+			done: false,
+		})
+	}
+
+	fn is_in_bounds(&self, key: &[u8]) -> bool {
+		let matches_prefix = self.prefix.as_ref().map_or(true, |prefix| key.starts_with(prefix));
+		let below_end = self.end.as_ref().map_or(true, |end| key < &**end);
+		matches_prefix && below_end
+	}
+}
+
+impl<'a> Iterator for BoundedIterator<'a> {
+	type Item = TrieItem<'a, H256, DecoderError>;
+
+	fn next(&mut self) -> Option<Self::Item> {
+		if self.done {
+			return None;
+		}
+
+		match self.inner.next() {
+			Some(Ok((key, value))) => {
+				if self.is_in_bounds(&key) {
+					Some(Ok((key, value)))
+				} else {
+					// keys are yielded in order, nothing more to return.
+					self.done = true;
+					None
+				}
+			},
+			Some(Err(err)) => Some(Err(err)),
+			None => {
+				self.done = true;
+				None
+			},
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use ethereum_types::H256;
+	use memorydb::MemoryDB;
+	use keccak_hasher::KeccakHasher;
+	use trie::TrieMut;
+	use {TrieDB, TrieDBMut};
+	use super::BoundedIterator;
+
+	fn populate(memdb: &mut MemoryDB<KeccakHasher>, keys: &[&[u8]]) -> H256 {
+		let mut root = H256::new();
+		{
+			let mut t = TrieDBMut::new(memdb, &mut root);
+			for key in keys {
+				t.insert(key, key).unwrap();
+			}
+		}
+		root
+	}
+
+	fn keys(iter: BoundedIterator) -> Vec<Vec<u8>> {
+		iter.map(|item| item.unwrap().0).collect()
+	}
+
+	#[test]
+	fn should_iterate_over_prefix() {
+		let mut memdb = MemoryDB::new();
+		let root = populate(&mut memdb, &[b"a", b"ab", b"abc", b"abd", b"b", b"bcd"]);
+		let t = TrieDB::new(&memdb, &root).unwrap();
+
+		let iter = BoundedIterator::prefix(&t, b"ab").unwrap();
+		assert_eq!(keys(iter), vec![b"ab".to_vec(), b"abc".to_vec(), b"abd".to_vec()]);
+
+		let iter = BoundedIterator::prefix(&t, b"c").unwrap();
+		assert!(keys(iter).is_empty());
+
+		let iter = BoundedIterator::prefix(&t, b"").unwrap();
+		assert_eq!(keys(iter).len(), 6);
+	}
+
+	#[test]
+	fn should_iterate_over_range() {
+		let mut memdb = MemoryDB::new();
+		let root = populate(&mut memdb, &[b"a", b"ab", b"abc", b"abd", b"b", b"bcd"]);
+		let t = TrieDB::new(&memdb, &root).unwrap();
+
+		let iter = BoundedIterator::range(&t, b"aa", Some(b"abd")).unwrap();
+		assert_eq!(keys(iter), vec![b"ab".to_vec(), b"abc".to_vec()]);
+
+		let iter = BoundedIterator::range(&t, b"abd", None).unwrap();
+		assert_eq!(keys(iter), vec![b"abd".to_vec(), b"b".to_vec(), b"bcd".to_vec()]);
+
+		let iter = BoundedIterator::range(&t, b"b", Some(b"b")).unwrap();
+		assert!(keys(iter).is_empty());
+	}
+}
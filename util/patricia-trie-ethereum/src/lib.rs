@@ -24,8 +24,18 @@ extern crate hashdb;
 extern crate keccak_hasher;
 extern crate rlp;
 
+extern crate memorydb;
+
+mod builder;
+mod diff;
+mod iterator;
+mod proof;
 mod rlp_node_codec;
 
+pub use builder::{TrieBuilder, UnsortedKey};
+pub use diff::{TrieChange, trie_diff};
+pub use iterator::BoundedIterator;
+pub use proof::{TrieProof, proof_db, verify_proof};
 pub use rlp_node_codec::RlpNodeCodec;
 
 use ethereum_types::H256;
@@ -0,0 +1,132 @@
+// Copyright 2015-2018 Parity Technologies (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Merkle proofs of (non-)inclusion of keys in a trie.
+//!
+//! A proof is the list of trie nodes visited while looking up a key, starting from the root.
+//! It proves either the value stored under the key or the absence of the key.
+
+use ethereum_types::H256;
+use hashdb::{DBValue, HashDB};
+use keccak_hasher::KeccakHasher;
+use memorydb::MemoryDB;
+use parity_bytes::Bytes;
+use trie::{Trie, Recorder};
+
+use {RlpCodec, TrieDB, Result};
+
+/// Merkle proof generation for tries.
+pub trait TrieProof {
+	/// Generate a proof for given `key`.
+	///
+	/// Returns all the nodes on the path to the key (or to the place where the key would be)
+	/// ordered from the root.
+	/// NOTE: For secure tries the key is hashed before the lookup, so the proof
+	/// has to be verified against the hashed key.
+	fn prove(&self, key: &[u8]) -> Result<Vec<Bytes>>;
+
+	/// Generate a proof for given `key` omitting the nodes above `from_level`.
+	fn prove_from_level(&self, key: &[u8], from_level: u32) -> Result<Vec<Bytes>>;
+}
+
+impl<T> TrieProof for T where T: Trie<KeccakHasher, RlpCodec> + ?Sized {
+	fn prove(&self, key: &[u8]) -> Result<Vec<Bytes>> {
+		self.prove_from_level(key, 0)
+	}
+
+	fn prove_from_level(&self, key: &[u8], from_level: u32) -> Result<Vec<Bytes>> {
+		let mut recorder = Recorder::with_depth(from_level);
+		self.get_with(key, &mut recorder)?;
+
+		Ok(recorder.drain().into_iter().map(|record| record.data).collect())
+	}
+}
+
+/// Load all proof nodes into an in-memory database.
+pub fn proof_db(proof: &[Bytes]) -> MemoryDB<KeccakHasher> {
+	let mut db = MemoryDB::new();
+	for node in proof {
+		db.insert(&node[..]);
+	}
+	db
+}
+
+/// Verify a proof for given `key` against the trie `root`.
+///
+/// Returns the proven value, `None` if the proof shows that the key is absent
+/// or an error if the proof is incomplete or invalid.
+pub fn verify_proof(root: &H256, key: &[u8], proof: &[Bytes]) -> Result<Option<DBValue>> {
+	let db = proof_db(proof);
+	TrieDB::new(&db, root)?.get(key)
+}
+
+#[cfg(test)]
+mod tests {
+	use ethereum_types::H256;
+	use hashdb::DBValue;
+	use memorydb::MemoryDB;
+	use trie::TrieMut;
+	use {TrieDB, TrieDBMut};
+	use super::{TrieProof, verify_proof};
+
+	fn test_trie(memdb: &mut MemoryDB<::keccak_hasher::KeccakHasher>) -> H256 {
+		let mut root = H256::new();
+		{
+			let mut t = TrieDBMut::new(memdb, &mut root);
+			t.insert(b"dog", b"puppy").unwrap();
+			t.insert(b"doge", b"coin").unwrap();
+			t.insert(b"horse", b"stallion").unwrap();
+			t.insert(b"do", b"verb").unwrap();
+		}
+		root
+	}
+
+	#[test]
+	fn should_prove_existing_key() {
+		let mut memdb = MemoryDB::new();
+		let root = test_trie(&mut memdb);
+		let t = TrieDB::new(&memdb, &root).unwrap();
+
+		let proof = t.prove(b"doge").unwrap();
+
+		assert!(!proof.is_empty());
+		assert_eq!(verify_proof(&root, b"doge", &proof).unwrap(), Some(DBValue::from_slice(b"coin")));
+	}
+
+	#[test]
+	fn should_prove_absent_key() {
+		let mut memdb = MemoryDB::new();
+		let root = test_trie(&mut memdb);
+		let t = TrieDB::new(&memdb, &root).unwrap();
+
+		let proof = t.prove(b"cat").unwrap();
+
+		assert_eq!(verify_proof(&root, b"cat", &proof).unwrap(), None);
+	}
+
+	#[test]
+	fn should_reject_incomplete_proof() {
+		let mut memdb = MemoryDB::new();
+		let root = test_trie(&mut memdb);
+		let t = TrieDB::new(&memdb, &root).unwrap();
+
+		let mut proof = t.prove(b"horse").unwrap();
+		proof.pop();
+
+		assert!(verify_proof(&root, b"horse", &proof).is_err());
+		assert!(verify_proof(&H256::new(), b"horse", &t.prove(b"horse").unwrap()).is_err());
+	}
+}
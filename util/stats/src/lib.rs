@@ -22,6 +22,10 @@ use std::ops::{Add, Sub, Deref, Div};
 #[macro_use]
 extern crate log;
 
+pub mod prometheus;
+
+pub use prometheus::{LatencyHistogram, PrometheusMetrics, PrometheusRegistry};
+
 /// Sorted corpus of data.
 #[derive(Debug, Clone, PartialEq)]
 pub struct Corpus<T>(Vec<T>);
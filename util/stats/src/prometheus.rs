@@ -0,0 +1,200 @@
+// Copyright 2015-2018 Parity Technologies (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Metrics in the Prometheus text exposition format.
+
+use std::fmt::Write;
+use std::time::Duration;
+
+/// Upper bounds of latency histogram buckets, in seconds.
+pub const LATENCY_BUCKETS: &[f64] = &[0.001, 0.005, 0.01, 0.025, 0.05, 0.1, 0.25, 0.5, 1.0, 2.5, 5.0, 10.0];
+
+/// Histogram of durations with fixed `LATENCY_BUCKETS`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct LatencyHistogram {
+	/// Cumulative count of observations within each bucket.
+	buckets: Vec<u64>,
+	count: u64,
+	sum: f64,
+}
+
+impl Default for LatencyHistogram {
+	fn default() -> Self {
+		LatencyHistogram {
+			buckets: vec![0; LATENCY_BUCKETS.len()],
+			count: 0,
+			sum: 0.0,
+		}
+	}
+}
+
+impl LatencyHistogram {
+	/// Records a single observation.
+	pub fn observe(&mut self, duration: Duration) {
+		let seconds = duration.as_secs() as f64 + duration.subsec_nanos() as f64 / 1_000_000_000.0;
+		for (bucket, bound) in self.buckets.iter_mut().zip(LATENCY_BUCKETS) {
+			if seconds <= *bound {
+				*bucket += 1;
+			}
+		}
+		self.count += 1;
+		self.sum += seconds;
+	}
+
+	/// Number of observations.
+	pub fn count(&self) -> u64 {
+		self.count
+	}
+}
+
+/// Source of metrics.
+pub trait PrometheusMetrics {
+	/// Appends current values of all metrics to the registry.
+	fn prometheus_metrics(&self, registry: &mut PrometheusRegistry);
+}
+
+/// Collects metrics and encodes them in the text exposition format.
+pub struct PrometheusRegistry {
+	prefix: String,
+	output: String,
+}
+
+impl PrometheusRegistry {
+	/// Creates new registry, names of all metrics start with `prefix`.
+	pub fn new(prefix: String) -> Self {
+		PrometheusRegistry {
+			prefix,
+			output: String::new(),
+		}
+	}
+
+	fn header(&mut self, name: &str, help: &str, kind: &str) {
+		let _ = writeln!(self.output, "# HELP {}{} {}", self.prefix, name, help);
+		let _ = writeln!(self.output, "# TYPE {}{} {}", self.prefix, name, kind);
+	}
+
+	/// Adds a value that can go up and down.
+	pub fn register_gauge(&mut self, name: &str, help: &str, value: i64) {
+		self.header(name, help, "gauge");
+		let _ = writeln!(self.output, "{}{} {}", self.prefix, name, value);
+	}
+
+	/// Adds a value that only goes up.
+	pub fn register_counter(&mut self, name: &str, help: &str, value: u64) {
+		self.header(name, help, "counter");
+		let _ = writeln!(self.output, "{}{} {}", self.prefix, name, value);
+	}
+
+	/// Adds a family of counters distinguished by the value of the `label`.
+	pub fn register_counters<'a, I>(&mut self, name: &str, help: &str, label: &str, counters: I) where
+		I: IntoIterator<Item = (&'a str, u64)>,
+	{
+		self.header(name, help, "counter");
+		for (value, counter) in counters {
+			let _ = writeln!(self.output, "{}{}{{{}=\"{}\"}} {}", self.prefix, name, label, escape(value), counter);
+		}
+	}
+
+	/// Adds a single histogram without labels.
+	pub fn register_histogram(&mut self, name: &str, help: &str, histogram: &LatencyHistogram) {
+		self.register_histograms(name, help, "", ::std::iter::once(("", histogram)));
+	}
+
+	/// Adds a family of histograms distinguished by the value of the `label`.
+	pub fn register_histograms<'a, I>(&mut self, name: &str, help: &str, label: &str, histograms: I) where
+		I: IntoIterator<Item = (&'a str, &'a LatencyHistogram)>,
+	{
+		self.header(name, help, "histogram");
+		for (value, histogram) in histograms {
+			let labels = match label.is_empty() {
+				true => String::new(),
+				false => format!("{}=\"{}\",", label, escape(value)),
+			};
+			for (count, bound) in histogram.buckets.iter().zip(LATENCY_BUCKETS) {
+				let _ = writeln!(self.output, "{}{}_bucket{{{}le=\"{}\"}} {}", self.prefix, name, labels, bound, count);
+			}
+			let _ = writeln!(self.output, "{}{}_bucket{{{}le=\"+Inf\"}} {}", self.prefix, name, labels, histogram.count);
+			let labels = labels.trim_right_matches(',');
+			let labels = match labels.is_empty() {
+				true => String::new(),
+				false => format!("{{{}}}", labels),
+			};
+			let _ = writeln!(self.output, "{}{}_sum{} {}", self.prefix, name, labels, histogram.sum);
+			let _ = writeln!(self.output, "{}{}_count{} {}", self.prefix, name, labels, histogram.count);
+		}
+	}
+
+	/// Returns all registered metrics.
+	pub fn output(self) -> String {
+		self.output
+	}
+}
+
+fn escape(value: &str) -> String {
+	value.replace('\\', "\\\\").replace('"', "\\\"").replace('\n', "\\n")
+}
+
+#[cfg(test)]
+mod tests {
+	use std::time::Duration;
+	use super::{LatencyHistogram, PrometheusRegistry};
+
+	#[test]
+	fn should_encode_gauges_and_counters() {
+		let mut registry = PrometheusRegistry::new("parity_".into());
+		registry.register_gauge("chain_block", "Best block number", 10);
+		registry.register_counter("chain_blocks_imported", "Number of imported blocks", 5);
+
+		assert_eq!(registry.output(), "\
+			# HELP parity_chain_block Best block number\n\
+			# TYPE parity_chain_block gauge\n\
+			parity_chain_block 10\n\
+			# HELP parity_chain_blocks_imported Number of imported blocks\n\
+			# TYPE parity_chain_blocks_imported counter\n\
+			parity_chain_blocks_imported 5\n\
+		");
+	}
+
+	#[test]
+	fn should_encode_labeled_counters() {
+		let mut registry = PrometheusRegistry::new("parity_".into());
+		registry.register_counters("network_bytes_in", "Bytes received", "protocol", vec![("eth", 10), ("par", 5)]);
+
+		assert_eq!(registry.output(), "\
+			# HELP parity_network_bytes_in Bytes received\n\
+			# TYPE parity_network_bytes_in counter\n\
+			parity_network_bytes_in{protocol=\"eth\"} 10\n\
+			parity_network_bytes_in{protocol=\"par\"} 5\n\
+		");
+	}
+
+	#[test]
+	fn should_encode_labeled_histograms() {
+		let mut histogram = LatencyHistogram::default();
+		histogram.observe(Duration::from_millis(3));
+		histogram.observe(Duration::from_millis(20));
+		let mut registry = PrometheusRegistry::new(String::new());
+		registry.register_histograms("rpc_duration_seconds", "RPC latency", "method", vec![("eth_call", &histogram)]);
+
+		let output = registry.output();
+		assert!(output.contains("# TYPE rpc_duration_seconds histogram\n"));
+		assert!(output.contains("rpc_duration_seconds_bucket{method=\"eth_call\",le=\"0.001\"} 0\n"));
+		assert!(output.contains("rpc_duration_seconds_bucket{method=\"eth_call\",le=\"0.005\"} 1\n"));
+		assert!(output.contains("rpc_duration_seconds_bucket{method=\"eth_call\",le=\"0.025\"} 2\n"));
+		assert!(output.contains("rpc_duration_seconds_bucket{method=\"eth_call\",le=\"+Inf\"} 2\n"));
+		assert!(output.contains("rpc_duration_seconds_count{method=\"eth_call\"} 2\n"));
+	}
+}
@@ -17,9 +17,16 @@
 //! Generates Keccak-flavoured trie roots.
 
 extern crate ethereum_types;
+extern crate keccak_hash;
 extern crate keccak_hasher;
+extern crate rayon;
+extern crate rlp;
 extern crate triehash;
 
+mod parallel;
+
+pub use parallel::{par_trie_root, par_sec_trie_root, par_ordered_trie_root};
+
 use ethereum_types::H256;
 use keccak_hasher::KeccakHasher;
 
@@ -0,0 +1,224 @@
+// Copyright 2015-2018 Parity Technologies (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Multi-threaded trie root calculation.
+//!
+//! Produces exactly the same roots as the sequential functions, but the subtries
+//! below the topmost branch nodes are encoded and hashed independently on the rayon pool.
+
+use std::cmp;
+use std::collections::BTreeMap;
+use ethereum_types::H256;
+use keccak_hash::keccak;
+use rayon::prelude::*;
+use rlp::{self, RlpStream};
+
+/// Branches with fewer items than this are always processed on the calling thread.
+const PARALLEL_THRESHOLD: usize = 64;
+/// Branches deeper than this (in nibbles) are always processed on the calling thread.
+/// Two levels give up to 256 independent subtries, enough to keep all cores busy.
+const PARALLEL_DEPTH: usize = 2;
+
+/// Generates a trie root hash for a vector of key-value tuples, hashing independent subtries in parallel.
+pub fn par_trie_root<I, K, V>(input: I) -> H256
+where
+	I: IntoIterator<Item = (K, V)>,
+	K: AsRef<[u8]>,
+	V: AsRef<[u8]> + Sync,
+{
+	let input = input.into_iter()
+		.map(|(k, v)| (k.as_ref().to_vec(), v))
+		.collect::<BTreeMap<_, _>>()
+		.into_iter()
+		.map(|(k, v)| (as_nibbles(&k), v))
+		.collect::<Vec<_>>();
+
+	let mut stream = RlpStream::new();
+	hash256rlp(&input, 0, &mut stream);
+	keccak(stream.out())
+}
+
+/// Generates a key-hashed (secure) trie root hash for a vector of key-value tuples,
+/// hashing independent subtries in parallel.
+pub fn par_sec_trie_root<I, K, V>(input: I) -> H256
+where
+	I: IntoIterator<Item = (K, V)>,
+	K: AsRef<[u8]>,
+	V: AsRef<[u8]> + Sync,
+{
+	par_trie_root(input.into_iter().map(|(k, v)| (keccak(k), v)))
+}
+
+/// Generates a trie root hash for a vector of values, hashing independent subtries in parallel.
+pub fn par_ordered_trie_root<I, V>(input: I) -> H256
+where
+	I: IntoIterator<Item = V>,
+	V: AsRef<[u8]> + Sync,
+{
+	par_trie_root(input.into_iter().enumerate().map(|(i, v)| (rlp::encode(&i), v)))
+}
+
+fn as_nibbles(bytes: &[u8]) -> Vec<u8> {
+	let mut res = Vec::with_capacity(bytes.len() * 2);
+	for byte in bytes {
+		res.push(byte >> 4);
+		res.push(byte & 0b1111);
+	}
+	res
+}
+
+fn hex_prefix_encode(nibbles: &[u8], leaf: bool) -> Vec<u8> {
+	let inlen = nibbles.len();
+	let oddness_factor = inlen % 2;
+	let mut res = Vec::with_capacity(inlen / 2 + 1);
+
+	let mut first_byte = ((inlen as u8 & 1) + (2 * leaf as u8)) << 4;
+	if oddness_factor == 1 {
+		first_byte += nibbles[0];
+	}
+	res.push(first_byte);
+
+	let mut offset = oddness_factor;
+	while offset < inlen {
+		res.push((nibbles[offset] << 4) + nibbles[offset + 1]);
+		offset += 2;
+	}
+	res
+}
+
+fn shared_prefix_len(first: &[u8], second: &[u8]) -> usize {
+	first.iter().zip(second.iter()).take_while(|&(a, b)| a == b).count()
+}
+
+/// Appends rlp of the node built from the sorted `input`, ignoring first `pre_len` nibbles of every key.
+fn hash256rlp<V: AsRef<[u8]> + Sync>(input: &[(Vec<u8>, V)], pre_len: usize, stream: &mut RlpStream) {
+	if input.is_empty() {
+		stream.append_empty_data();
+		return;
+	}
+
+	let key: &[u8] = &input[0].0;
+	let value: &[u8] = input[0].1.as_ref();
+
+	// leaf
+	if input.len() == 1 {
+		stream.begin_list(2);
+		stream.append(&hex_prefix_encode(&key[pre_len..], true));
+		stream.append(&value);
+		return;
+	}
+
+	// extension
+	let shared_prefix = input.iter().skip(1)
+		.fold(key.len(), |acc, &(ref k, _)| cmp::min(shared_prefix_len(key, k), acc));
+	if shared_prefix > pre_len {
+		stream.begin_list(2);
+		stream.append(&hex_prefix_encode(&key[pre_len..shared_prefix], false));
+		hash256aux(input, shared_prefix, stream);
+		return;
+	}
+
+	// branch
+	stream.begin_list(17);
+	let (value, mut begin) = if pre_len == key.len() { (Some(value), 1) } else { (None, 0) };
+
+	let mut children = Vec::with_capacity(16);
+	for i in 0..16 {
+		let len = input[begin..].iter().take_while(|pair| pair.0[pre_len] == i).count();
+		children.push(&input[begin..begin + len]);
+		begin += len;
+	}
+
+	if input.len() >= PARALLEL_THRESHOLD && pre_len < PARALLEL_DEPTH {
+		let encoded = children.par_iter()
+			.map(|child| {
+				let mut stream = RlpStream::new();
+				hash256child(child, pre_len + 1, &mut stream);
+				stream.out()
+			})
+			.collect::<Vec<_>>();
+		for child in encoded {
+			stream.append_raw(&child, 1);
+		}
+	} else {
+		for child in children {
+			hash256child(child, pre_len + 1, stream);
+		}
+	}
+
+	match value {
+		Some(value) => stream.append(&value),
+		None => stream.append_empty_data(),
+	};
+}
+
+fn hash256child<V: AsRef<[u8]> + Sync>(input: &[(Vec<u8>, V)], pre_len: usize, stream: &mut RlpStream) {
+	if input.is_empty() {
+		stream.append_empty_data();
+	} else {
+		hash256aux(input, pre_len, stream);
+	}
+}
+
+/// Appends the node inline if its rlp is shorter than 32 bytes, otherwise appends its hash.
+fn hash256aux<V: AsRef<[u8]> + Sync>(input: &[(Vec<u8>, V)], pre_len: usize, stream: &mut RlpStream) {
+	let mut s = RlpStream::new();
+	hash256rlp(input, pre_len, &mut s);
+	let out = s.out();
+	match out.len() {
+		0...31 => stream.append_raw(&out, 1),
+		_ => stream.append(&keccak(out)),
+	};
+}
+
+#[cfg(test)]
+mod tests {
+	use keccak_hash::keccak;
+	use {trie_root, sec_trie_root, ordered_trie_root};
+	use super::{par_trie_root, par_sec_trie_root, par_ordered_trie_root};
+
+	fn test_input(n: usize) -> Vec<(Vec<u8>, Vec<u8>)> {
+		(0..n)
+			.map(|i| {
+				let key = keccak(&[(i >> 8) as u8, i as u8]);
+				// mix short keys and keys being prefixes of other keys
+				let key = match i % 3 {
+					0 => key[..1 + i % 5].to_vec(),
+					_ => key.to_vec(),
+				};
+				(key, vec![i as u8; 1 + i % 40])
+			})
+			.collect()
+	}
+
+	#[test]
+	fn should_match_sequential_roots() {
+		for &n in &[0, 1, 2, 17, 63, 64, 65, 300, 5000] {
+			let input = test_input(n);
+			assert_eq!(par_trie_root(input.clone()), trie_root(input.clone()), "trie_root, {} items", n);
+			assert_eq!(par_sec_trie_root(input.clone()), sec_trie_root(input.clone()), "sec_trie_root, {} items", n);
+
+			let values = input.into_iter().map(|(_, v)| v).collect::<Vec<_>>();
+			assert_eq!(par_ordered_trie_root(values.clone()), ordered_trie_root(values), "ordered_trie_root, {} items", n);
+		}
+	}
+
+	#[test]
+	fn should_use_last_value_for_duplicated_keys() {
+		let input = vec![(b"a", b"1"), (b"b", b"2"), (b"a", b"3")];
+		assert_eq!(par_trie_root(input.clone()), trie_root(input));
+	}
+}
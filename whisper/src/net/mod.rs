@@ -52,6 +52,13 @@ pub const SUPPORTED_VERSIONS: &'static [(u8, u8)] = &[
 // maximum tolerated delay between messages packets.
 const MAX_TOLERATED_DELAY: Duration = Duration::from_millis(5000);
 
+// number of junk messages tolerated from a peer before the ratio
+// of junk to useful messages is taken into account.
+const JUNK_TOLERANCE: u64 = 64;
+
+// maximum tolerated number of junk messages per useful message.
+const MAX_JUNK_RATIO: u64 = 4;
+
 /// Whisper protocol ID
 pub const PROTOCOL_ID: ::network::ProtocolId = *b"shh";
 
@@ -78,6 +85,13 @@ pub trait MessageHandler: Send + Sync {
 	/// If there is a significant overhead in this thread, then an attacker
 	/// can determine which kinds of messages we are listening for.
 	fn handle_messages(&self, message: &[Message]);
+
+	/// Bloom of all topics this node is interested in.
+	///
+	/// Advertised to peers when topic bloom exchange is enabled, so that
+	/// only matching messages are relayed to this node.
+	/// `None` means that all messages are of interest.
+	fn topic_bloom(&self) -> Option<H512> { None }
 }
 
 // errors in importing a whisper message.
@@ -89,6 +103,7 @@ enum Error {
 	UnknownPeer(PeerId),
 	UnexpectedMessage,
 	InvalidPowReq,
+	JunkRelayer,
 }
 
 impl From<DecoderError> for Error {
@@ -118,6 +133,7 @@ impl fmt::Display for Error {
 			Error::UnknownPeer(ref id) => write!(f, "Message received from unknown peer: {}", id),
 			Error::UnexpectedMessage => write!(f, "Unexpected message."),
 			Error::InvalidPowReq => write!(f, "Peer sent invalid PoW requirement."),
+			Error::JunkRelayer => write!(f, "Peer relays too many unwanted messages."),
 		}
 	}
 }
@@ -324,6 +340,12 @@ struct Peer {
 	pow_requirement: f64,
 	is_parity: bool,
 	_protocol_version: usize,
+	// topic bloom sent to the peer and the time it was sent at.
+	sent_bloom: Option<(H512, SystemTime)>,
+	// number of new messages relayed by the peer.
+	useful_messages: u64,
+	// number of duplicate or unwanted messages relayed by the peer.
+	junk_messages: u64,
 }
 
 impl Peer {
@@ -366,6 +388,29 @@ impl Peer {
 			State::Confirmed => true,
 		}
 	}
+
+	// the topic bloom the peer is expected to respect at given time.
+	// peers are given some time to process our topic filter packet.
+	fn expected_bloom(&self, now: SystemTime) -> Option<&H512> {
+		match self.sent_bloom {
+			Some((ref bloom, sent_at)) if sent_at + MAX_TOLERATED_DELAY <= now => Some(bloom),
+			_ => None,
+		}
+	}
+
+	fn note_useful(&mut self, count: usize) {
+		self.useful_messages += count as u64;
+	}
+
+	fn note_junk(&mut self, count: usize) {
+		self.junk_messages += count as u64;
+	}
+
+	// whether the peer relays mostly messages we did not ask for.
+	fn is_junk_relayer(&self) -> bool {
+		self.junk_messages > JUNK_TOLERANCE
+			&& self.junk_messages > self.useful_messages.saturating_mul(MAX_JUNK_RATIO)
+	}
 }
 
 /// Pool status.
@@ -423,6 +468,7 @@ pub struct Network<T> {
 	messages: Arc<RwLock<Messages>>,
 	handler: T,
 	peers: RwLock<HashMap<PeerId, Mutex<Peer>>>,
+	topic_bloom_exchange: bool,
 }
 
 // public API.
@@ -433,9 +479,19 @@ impl<T> Network<T> {
 			messages: Arc::new(RwLock::new(Messages::new(messages_size_bytes))),
 			handler: handler,
 			peers: RwLock::new(HashMap::new()),
+			topic_bloom_exchange: false,
 		}
 	}
 
+	/// Advertise the bloom of topics the message handler is interested in to peers.
+	///
+	/// Peers will relay only matching messages to this node, so it will not
+	/// relay any other messages either. Should not be enabled on relaying nodes.
+	pub fn with_topic_bloom_exchange(mut self, enabled: bool) -> Self {
+		self.topic_bloom_exchange = enabled;
+		self
+	}
+
 	/// Post a message to the whisper network to be relayed.
 	pub fn post_message<C: ?Sized + Context>(&self, message: Message, context: &C) -> bool
 		where T: MessageHandler
@@ -459,6 +515,10 @@ impl<T: MessageHandler> Network<T> {
 		// prune messages.
 		let now = SystemTime::now();
 		let pruned_hashes = self.messages.write().prune(now);
+		let topic_bloom = match self.topic_bloom_exchange {
+			true => self.handler.topic_bloom(),
+			false => None,
+		};
 
 		let messages = self.messages.read();
 		let peers = self.peers.read();
@@ -484,6 +544,14 @@ impl<T: MessageHandler> Network<T> {
 				State::Confirmed => {}
 			}
 
+			// let the peer know about changes in topics we're interested in.
+			if let Some(ref bloom) = topic_bloom {
+				if peer_data.sent_bloom.as_ref().map_or(true, |&(ref sent, _)| sent != bloom) {
+					peer_data.sent_bloom = Some((bloom.clone(), now));
+					io.send(*peer_id, packet::TOPIC_FILTER, ::rlp::encode(bloom).into_vec());
+				}
+			}
+
 			// construct packet, skipping messages the peer won't accept.
 			let mut stream = RlpStream::new();
 			stream.begin_unbounded_list();
@@ -523,16 +591,16 @@ impl<T: MessageHandler> Network<T> {
 		}
 	}
 
-	fn on_messages(&self, peer: &PeerId, message_packet: Rlp)
+	fn on_messages(&self, peer_id: &PeerId, message_packet: Rlp)
 		-> Result<(), Error>
 	{
 		let mut messages_vec = {
 			let peers = self.peers.read();
-			let peer = match peers.get(peer) {
+			let peer = match peers.get(peer_id) {
 				Some(peer) => peer,
 				None => {
 					debug!(target: "whisper", "Received message from unknown peer.");
-					return Err(Error::UnknownPeer(*peer));
+					return Err(Error::UnknownPeer(*peer_id));
 				}
 			};
 
@@ -549,20 +617,42 @@ impl<T: MessageHandler> Network<T> {
 			if messages_vec.is_empty() { return Ok(()) }
 
 			// disallow duplicates in packet.
+			let received = messages_vec.len();
 			messages_vec.retain(|message| peer.note_known(&message));
+
+			// drop messages not matching the topic bloom we've sent.
+			if let Some(bloom) = peer.expected_bloom(now).cloned() {
+				messages_vec.retain(|message| &(&bloom & message.bloom()) == message.bloom());
+			}
+
+			peer.note_junk(received - messages_vec.len());
+			if peer.is_junk_relayer() {
+				debug!(target: "whisper", "Peer {} relayed {} junk messages and {} useful ones",
+					peer_id, peer.junk_messages, peer.useful_messages);
+				return Err(Error::JunkRelayer);
+			}
+
 			messages_vec
 		};
 
 		// import for relaying.
-		let mut messages = self.messages.write();
+		let useful = {
+			let mut messages = self.messages.write();
 
-		messages_vec.retain(|message| messages.may_accept(&message));
-		messages.reserve(messages_vec.len());
+			messages_vec.retain(|message| messages.may_accept(&message));
+			messages.reserve(messages_vec.len());
 
-		self.handler.handle_messages(&messages_vec);
+			self.handler.handle_messages(&messages_vec);
+
+			let mut useful = 0;
+			for message in messages_vec {
+				if messages.insert(message) { useful += 1 }
+			}
+			useful
+		};
 
-		for message in messages_vec {
-			messages.insert(message);
+		if let Some(peer) = self.peers.read().get(peer_id) {
+			peer.lock().note_useful(useful);
 		}
 
 		Ok(())
@@ -655,6 +745,9 @@ impl<T: MessageHandler> Network<T> {
 			pow_requirement: 0f64,
 			is_parity: io.protocol_version(PARITY_PROTOCOL_ID, *peer).is_some(),
 			_protocol_version: version,
+			sent_bloom: None,
+			useful_messages: 0,
+			junk_messages: 0,
 		}));
 
 		io.send(*peer, packet::STATUS, ::rlp::EMPTY_LIST_RLP.to_vec());
@@ -18,14 +18,15 @@
 
 use std::collections::HashSet;
 use std::sync::mpsc;
+use std::time::UNIX_EPOCH;
 
 use parking_lot::Mutex;
 use network::{NodeId, PeerId};
 
-use message::{CreateParams, Message};
+use message::{CreateParams, Message, Topic};
 use super::*;
 
-struct TestHandler(Mutex<mpsc::Sender<Message>>);
+struct TestHandler(Mutex<mpsc::Sender<Message>>, Option<H512>);
 
 impl MessageHandler for TestHandler {
 	fn handle_messages(&self, messages: &[Message]) {
@@ -34,6 +35,10 @@ impl MessageHandler for TestHandler {
 			let _ = tx.send(message.clone());
 		}
 	}
+
+	fn topic_bloom(&self) -> Option<H512> {
+		self.1.clone()
+	}
 }
 
 struct TestPeer {
@@ -43,11 +48,13 @@ struct TestPeer {
 }
 
 impl TestPeer {
-	fn create() -> Self {
+	fn create(topic_bloom: Option<H512>) -> Self {
 		let (tx, rx) = mpsc::channel();
+		let exchange_bloom = topic_bloom.is_some();
 
 		TestPeer {
-			network: Network::new(10 * 1024 * 1024, TestHandler(Mutex::new(tx))),
+			network: Network::new(10 * 1024 * 1024, TestHandler(Mutex::new(tx), topic_bloom))
+				.with_topic_bloom_exchange(exchange_bloom),
 			recv: rx,
 			disconnected: Mutex::new(HashSet::new()),
 		}
@@ -60,7 +67,12 @@ struct TestNetwork {
 
 impl TestNetwork {
 	fn new(n_peers: usize) -> Self {
-		let unconnected_peers: Vec<_> = (0..n_peers).map(|_| TestPeer::create()).collect();
+		Self::with_blooms(vec![None; n_peers])
+	}
+
+	fn with_blooms(blooms: Vec<Option<H512>>) -> Self {
+		let n_peers = blooms.len();
+		let unconnected_peers: Vec<_> = blooms.into_iter().map(TestPeer::create).collect();
 		for i in 0..n_peers {
 			for j in (i + 1)..n_peers {
 				let (peer1, peer2) = (&unconnected_peers[i], &unconnected_peers[j]);
@@ -80,6 +92,10 @@ impl TestNetwork {
 	fn post_message_from(&self, id: PeerId, msg: Message) {
 		self.peers[id].network.post_message(msg, &TestContext::new(&self.peers, id));
 	}
+
+	fn rally(&self, id: PeerId) {
+		self.peers[id].network.rally(&TestContext::new(&self.peers, id));
+	}
 }
 
 enum Event {
@@ -190,3 +206,49 @@ fn message_gets_relayed() {
 		assert_eq!(network.peers[i].recv.try_recv().unwrap(), message);
 	}
 }
+
+fn message_with_topic(topic: [u8; 4], payload: &[u8]) -> Message {
+	Message::create(CreateParams {
+		ttl: 500,
+		payload: payload.to_vec(),
+		topics: vec![topic.into()],
+		work: 1,
+	}).unwrap()
+}
+
+#[test]
+fn message_relayed_only_if_matches_topic_bloom() {
+	let network = TestNetwork::with_blooms(vec![None, Some(Topic([0, 1, 2, 3]).bloom())]);
+
+	// send topic bloom to the peer.
+	network.rally(1);
+
+	network.post_message_from(0, message_with_topic([9, 9, 9, 9], b"unwanted"));
+	assert!(network.peers[1].recv.try_recv().is_err());
+
+	let message = message_with_topic([0, 1, 2, 3], b"wanted");
+	network.post_message_from(0, message.clone());
+	assert_eq!(network.peers[1].recv.try_recv().unwrap(), message);
+}
+
+#[test]
+fn junk_relayer_gets_disabled() {
+	let bloom = Topic([0, 1, 2, 3]).bloom();
+	let network = TestNetwork::with_blooms(vec![None, Some(bloom.clone())]);
+
+	// pretend the topic bloom was sent to the peer long ago.
+	network.peers[1].network.peers.read()[&0].lock().sent_bloom = Some((bloom, UNIX_EPOCH));
+
+	let junk = (0..JUNK_TOLERANCE + 1)
+		.map(|i| message_with_topic([9, 9, 9, 9], format!("junk {}", i).as_bytes()))
+		.collect::<Vec<_>>();
+	let mut stream = RlpStream::new_list(junk.len());
+	for message in &junk {
+		stream.append(message.envelope());
+	}
+
+	network.peers[1].network.on_packet(&TestContext::new(&network.peers, 1), &0, packet::MESSAGES, &stream.out());
+
+	assert!(network.peers[1].recv.try_recv().is_err());
+	assert!(network.peers[1].disconnected.lock().contains(&0));
+}
@@ -189,6 +189,17 @@ impl ::net::MessageHandler for Arc<Manager> {
 			}
 		}
 	}
+
+	fn topic_bloom(&self) -> Option<H512> {
+		let filters = self.filters.read();
+		let bloom = filters.values()
+			.flat_map(|filter| match *filter {
+				FilterEntry::Poll(ref filter, _) | FilterEntry::Subscription(ref filter, _) => filter.topics.iter(),
+			})
+			.fold(H512::zero(), |acc, &(_, ref bloom, _)| &acc | bloom);
+
+		Some(bloom)
+	}
 }
 
 impl Drop for Manager {